@@ -25,7 +25,7 @@ use async_trait::async_trait;
 use thiserror::Error;
 use tracing::error;
 
-use crate::{ActorContext, QueueCapacity, SendError};
+use crate::{ActorContext, PanicReport, QueueCapacity, SendError};
 
 /// The actor exit status represents the outcome of the execution of an actor,
 /// after the end of the execution.
@@ -76,8 +76,8 @@ pub enum ActorExitStatus {
     Failure(Arc<anyhow::Error>),
 
     /// The thread or the task executing the actor loop panicked.
-    #[error("Panicked")]
-    Panicked,
+    #[error("Panicked({0})")]
+    Panicked(Arc<PanicReport>),
 }
 
 impl From<anyhow::Error> for ActorExitStatus {
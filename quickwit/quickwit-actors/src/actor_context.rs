@@ -21,8 +21,9 @@ use std::convert::Infallible;
 use std::fmt;
 use std::future::Future;
 use std::ops::Deref;
-use std::sync::Arc;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use quickwit_common::metrics::IntCounter;
 use quickwit_common::{KillSwitch, Progress, ProtectedZoneGuard};
@@ -67,6 +68,8 @@ pub struct ActorContextInner<A: Actor> {
     actor_state: AtomicState,
     backpressure_micros_counter_opt: Option<IntCounter>,
     observable_state_tx: watch::Sender<A::ObservableState>,
+    /// Deadline of the message being processed, if it was sent with one.
+    deadline_opt: Mutex<Option<Instant>>,
 }
 
 impl<A: Actor> ActorContext<A> {
@@ -84,6 +87,7 @@ impl<A: Actor> ActorContext<A> {
                 actor_state: AtomicState::default(),
                 observable_state_tx,
                 backpressure_micros_counter_opt,
+                deadline_opt: Mutex::new(None),
             }
             .into(),
         }
@@ -183,6 +187,36 @@ impl<A: Actor> ActorContext<A> {
         self.progress.record_progress();
     }
 
+    /// Returns the deadline of the message being processed, if the sender set one with
+    /// `Mailbox::ask_with_timeout`, or if it was propagated from an upstream request.
+    pub fn deadline(&self) -> Option<Instant> {
+        *self
+            .deadline_opt
+            .lock()
+            .expect("Deadline lock should not be poisoned.")
+    }
+
+    /// Returns the time left before the deadline of the message being processed, if any. Handlers
+    /// can use it to cut their work short when the sender is about to give up on the reply.
+    pub fn remaining_budget(&self) -> Option<Duration> {
+        self.deadline()
+            .map(|deadline| deadline.saturating_duration_since(Instant::now()))
+    }
+
+    /// Returns whether the deadline of the message being processed has passed.
+    pub fn is_deadline_exceeded(&self) -> bool {
+        self.remaining_budget()
+            .map(|remaining_budget| remaining_budget.is_zero())
+            .unwrap_or(false)
+    }
+
+    pub(crate) fn set_deadline(&self, deadline_opt: Option<Instant>) {
+        *self
+            .deadline_opt
+            .lock()
+            .expect("Deadline lock should not be poisoned.") = deadline_opt;
+    }
+
     pub(crate) fn state(&self) -> ActorState {
         self.actor_state.get_state()
     }
@@ -266,9 +300,22 @@ impl<A: Actor> ActorContext<A> {
     {
         let _guard = self.protect_zone();
         debug!(from=%self.self_mailbox.actor_instance_id(), send=%mailbox.actor_instance_id(), msg=?msg, "ask");
-        mailbox
-            .ask_with_backpressure_counter(msg, self.backpressure_micros_counter_opt.as_ref())
-            .await
+        // The deadline of the message being processed, if any, is propagated downstream.
+        match self.deadline() {
+            Some(deadline) => {
+                mailbox
+                    .ask_with_deadline(msg, deadline, self.backpressure_micros_counter_opt.as_ref())
+                    .await
+            }
+            None => {
+                mailbox
+                    .ask_with_backpressure_counter(
+                        msg,
+                        self.backpressure_micros_counter_opt.as_ref(),
+                    )
+                    .await
+            }
+        }
     }
 
     /// Similar to `send_message`, except this method
@@ -285,7 +332,13 @@ impl<A: Actor> ActorContext<A> {
     {
         let _guard = self.protect_zone();
         debug!(from=%self.self_mailbox.actor_instance_id(), send=%mailbox.actor_instance_id(), msg=?msg, "ask");
-        mailbox.ask_for_res(msg).await
+        match self.deadline() {
+            Some(deadline) => mailbox
+                .ask_with_deadline::<M, E>(msg, deadline, None)
+                .await?
+                .map_err(AskError::from),
+            None => mailbox.ask_for_res(msg).await,
+        }
     }
 
     /// Send the Success message to terminate the destination actor with the Success exit status.
@@ -336,19 +389,48 @@ impl<A: Actor> ActorContext<A> {
 
     /// Schedules a message that will be sent to the high-priority
     /// queue of the actor Mailbox once `after_duration` has elapsed.
-    pub async fn schedule_self_msg<M>(&self, after_duration: Duration, message: M)
+    ///
+    /// The returned handle can be used to cancel the message before it is sent. Dropping the
+    /// handle does not cancel the message.
+    pub async fn schedule_self_msg<M>(
+        &self,
+        after_duration: Duration,
+        message: M,
+    ) -> ScheduledMessageHandle
     where
         A: DeferableReplyHandler<M>,
         M: Sync + Send + std::fmt::Debug + 'static,
     {
+        let scheduled_message_handle = ScheduledMessageHandle::default();
+        let cancelled = scheduled_message_handle.cancelled.clone();
         let self_mailbox = self.inner.self_mailbox.clone();
         let callback = move || {
-            let _ = self_mailbox.send_message_with_high_priority(message);
+            if !cancelled.load(Ordering::Relaxed) {
+                let _ = self_mailbox.send_message_with_high_priority(message);
+            }
         };
         self.inner
             .spawn_ctx
             .scheduler_client
             .schedule_event(callback, after_duration);
+        scheduled_message_handle
+    }
+}
+
+/// Handle over a message scheduled with [`ActorContext::schedule_self_msg`].
+#[derive(Clone, Debug, Default)]
+pub struct ScheduledMessageHandle {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl ScheduledMessageHandle {
+    /// Cancels the scheduled message. This has no effect if the message was already sent.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
     }
 }
 
@@ -359,7 +441,7 @@ fn should_activate_kill_switch(exit_status: &ActorExitStatus) -> bool {
     match exit_status {
         ActorExitStatus::DownstreamClosed => true,
         ActorExitStatus::Failure(_) => true,
-        ActorExitStatus::Panicked => true,
+        ActorExitStatus::Panicked(_) => true,
         ActorExitStatus::Success => false,
         ActorExitStatus::Quit => false,
         ActorExitStatus::Killed => false,
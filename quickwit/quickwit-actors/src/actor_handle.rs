@@ -127,10 +127,11 @@ impl<A: Actor> ActorHandle<A> {
 
     /// Observe the current state.
     ///
-    /// The observation will be scheduled as a high priority message, therefore it will be executed
-    /// after the current active message and the current command queue have been processed.
+    /// The observation will be scheduled as a control message, therefore it will be executed
+    /// after the current active message and the pending control messages have been processed, but
+    /// before the pending high and low priority messages.
     pub async fn observe(&self) -> Observation<A::ObservableState> {
-        self.observe_with_priority(Priority::High).await
+        self.observe_with_priority(Priority::Control).await
     }
 
     async fn observe_with_priority(&self, priority: Priority) -> Observation<A::ObservableState> {
@@ -165,7 +166,7 @@ impl<A: Actor> ActorHandle<A> {
         let _ = self
             .actor_context
             .mailbox()
-            .send_message_with_high_priority(Command::Pause);
+            .send_message_with_control_priority(Command::Pause);
     }
 
     /// Resumes a paused actor.
@@ -173,7 +174,7 @@ impl<A: Actor> ActorHandle<A> {
         let _ = self
             .actor_context
             .mailbox()
-            .send_message_with_high_priority(Command::Resume);
+            .send_message_with_control_priority(Command::Resume);
     }
 
     /// Kills the actor. Its finalize function will still be called.
@@ -187,7 +188,7 @@ impl<A: Actor> ActorHandle<A> {
         let _ = self
             .actor_context
             .mailbox()
-            .send_message_with_high_priority(Command::Nudge);
+            .send_message_with_control_priority(Command::Nudge);
         self.join().await
     }
 
@@ -202,10 +203,15 @@ impl<A: Actor> ActorHandle<A> {
         let _ = self
             .actor_context
             .mailbox()
-            .send_message_with_high_priority(Command::Quit);
+            .send_message_with_control_priority(Command::Quit);
         self.join().await
     }
 
+    /// Returns the exit status of the actor if it has exited, without waiting.
+    pub fn try_status(&self) -> Option<ActorExitStatus> {
+        self.join_handle.try_status()
+    }
+
     /// Waits until the actor exits by itself. This is the equivalent of `Thread::join`.
     pub async fn join(self) -> (ActorExitStatus, A::ObservableState) {
         let exit_status = self.join_handle.join().await;
@@ -321,7 +327,12 @@ mod tests {
         let (mailbox, handle) = universe.spawn_builder().spawn(PanickingActor::default());
         mailbox.send_message(Panic).await?;
         let (exit_status, count) = handle.join().await;
-        assert!(matches!(exit_status, ActorExitStatus::Panicked));
+        let panic_report = match exit_status {
+            ActorExitStatus::Panicked(panic_report) => panic_report,
+            other_exit_status => panic!("Expected a panic, got `{other_exit_status:?}`."),
+        };
+        assert_eq!(panic_report.message, "Oops");
+        assert!(panic_report.location_opt.is_some());
         assert!(matches!(count, 1)); //< Upon panick we cannot get a post mortem state.
         Ok(())
     }
@@ -336,4 +347,33 @@ mod tests {
         assert!(matches!(count, 1)); //< Upon panick we cannot get a post mortem state.
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_exit_status_can_be_observed_repeatedly() -> anyhow::Result<()> {
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, handle) = universe.spawn_builder().spawn(ExitActor::default());
+        assert!(handle.try_status().is_none());
+
+        mailbox.send_message(Exit).await?;
+        let exit_status = loop {
+            if let Some(exit_status) = handle.try_status() {
+                break exit_status;
+            }
+            tokio::task::yield_now().await;
+        };
+        assert!(matches!(exit_status, ActorExitStatus::DownstreamClosed));
+        assert!(matches!(
+            handle.try_status(),
+            Some(ActorExitStatus::DownstreamClosed)
+        ));
+        let exit_statuses = universe.quit().await;
+        assert_eq!(exit_statuses.len(), 1);
+        assert!(exit_statuses
+            .values()
+            .all(|exit_status| matches!(exit_status, ActorExitStatus::DownstreamClosed)));
+
+        let (exit_status, _count) = handle.join().await;
+        assert!(matches!(exit_status, ActorExitStatus::DownstreamClosed));
+        Ok(())
+    }
 }
@@ -125,11 +125,13 @@ pub enum QueueCapacity {
     Unbounded,
 }
 
-/// Creates a channel with the ability to send high priority messages.
+/// Creates a channel with the ability to send control and high priority messages.
 ///
 /// A high priority message is guaranteed to be consumed before any
-/// low priority message sent after it.
+/// low priority message sent after it, and a control message before any
+/// high or low priority message sent after it.
 pub fn channel<T>(queue_capacity: QueueCapacity) -> (Sender<T>, Receiver<T>) {
+    let (control_tx, control_rx) = flume::unbounded();
     let (high_priority_tx, high_priority_rx) = flume::unbounded();
     let (low_priority_tx, low_priority_rx) = match queue_capacity {
         QueueCapacity::Bounded(cap) => flume::bounded(cap),
@@ -138,13 +140,16 @@ pub fn channel<T>(queue_capacity: QueueCapacity) -> (Sender<T>, Receiver<T>) {
     let receiver = Receiver {
         low_priority_rx,
         high_priority_rx,
+        control_rx,
         _high_priority_tx: high_priority_tx.clone(),
+        _control_tx: control_tx.clone(),
         pending_low_priority_message: LockedOption::none(),
         _clone_is_forbidden: CloneIsForbidden,
     };
     let sender = Sender {
         low_priority_tx,
         high_priority_tx,
+        control_tx,
     };
     (sender, receiver)
 }
@@ -152,6 +157,7 @@ pub fn channel<T>(queue_capacity: QueueCapacity) -> (Sender<T>, Receiver<T>) {
 pub struct Sender<T> {
     low_priority_tx: flume::Sender<T>,
     high_priority_tx: flume::Sender<T>,
+    control_tx: flume::Sender<T>,
 }
 
 impl<T> Sender<T> {
@@ -173,6 +179,13 @@ impl<T> Sender<T> {
         self.high_priority_tx.send(msg)?;
         Ok(())
     }
+
+    /// Sends a message in the control lane, which is consumed before the high and low priority
+    /// lanes, so that control messages are never stuck behind a backlog of regular messages.
+    pub fn send_control_priority(&self, msg: T) -> Result<(), SendError> {
+        self.control_tx.send(msg)?;
+        Ok(())
+    }
 }
 
 // Message to future generations. I created this flag to prevent you
@@ -183,7 +196,9 @@ struct CloneIsForbidden;
 pub struct Receiver<T> {
     low_priority_rx: flume::Receiver<T>,
     high_priority_rx: flume::Receiver<T>,
+    control_rx: flume::Receiver<T>,
     _high_priority_tx: flume::Sender<T>,
+    _control_tx: flume::Sender<T>,
     pending_low_priority_message: LockedOption<T>,
     _clone_is_forbidden: CloneIsForbidden,
 }
@@ -195,6 +210,7 @@ impl<T> Drop for Receiver<T> {
         //
         // They are only dropped when both the receivers AND the sender are dropped.
         // We fix this behavior by drainng the channel upon drop.
+        self.control_rx.drain();
         self.high_priority_rx.drain();
         self.low_priority_rx.drain();
     }
@@ -205,34 +221,46 @@ impl<T> Receiver<T> {
         self.low_priority_rx.is_empty()
             && self.pending_low_priority_message.is_none()
             && self.high_priority_rx.is_empty()
+            && self.control_rx.is_empty()
     }
 
-    pub fn try_recv_high_priority_message(&self) -> Result<T, RecvError> {
-        match self.high_priority_rx.try_recv() {
-            Ok(msg) => Ok(msg),
+    /// Returns the number of messages waiting in the low priority lane.
+    pub fn num_low_priority_messages(&self) -> usize {
+        self.low_priority_rx.len() + usize::from(self.pending_low_priority_message.is_some())
+    }
+
+    /// Returns the next control message or, if there is none, the next high priority message.
+    fn try_recv_control_or_high_priority(&self) -> Option<T> {
+        match self.control_rx.try_recv() {
+            Ok(msg) => Some(msg),
+            Err(TryRecvError::Empty) => self.high_priority_rx.try_recv().ok(),
             Err(TryRecvError::Disconnected) => {
                 unreachable!(
-                    "This can never happen, as the high priority Sender is owned by the Receiver."
+                    "This can never happen, as the control Sender is owned by the Receiver."
                 );
             }
-            Err(TryRecvError::Empty) => {
-                if self.low_priority_rx.is_disconnected() {
-                    // We check that no new high priority message were sent
-                    // in between.
-                    if let Ok(msg) = self.high_priority_rx.try_recv() {
-                        Ok(msg)
-                    } else {
-                        Err(RecvError::Disconnected)
-                    }
-                } else {
-                    Err(RecvError::NoMessageAvailable)
-                }
+        }
+    }
+
+    pub fn try_recv_high_priority_message(&self) -> Result<T, RecvError> {
+        if let Some(msg) = self.try_recv_control_or_high_priority() {
+            return Ok(msg);
+        }
+        if self.low_priority_rx.is_disconnected() {
+            // We check that no new control or high priority message were sent
+            // in between.
+            if let Some(msg) = self.try_recv_control_or_high_priority() {
+                Ok(msg)
+            } else {
+                Err(RecvError::Disconnected)
             }
+        } else {
+            Err(RecvError::NoMessageAvailable)
         }
     }
 
     pub fn try_recv(&self) -> Result<T, RecvError> {
-        if let Ok(msg) = self.high_priority_rx.try_recv() {
+        if let Some(msg) = self.try_recv_control_or_high_priority() {
             return Ok(msg);
         }
         if let Some(pending_msg) = self.pending_low_priority_message.take() {
@@ -240,7 +268,7 @@ impl<T> Receiver<T> {
         }
         match self.low_priority_rx.try_recv() {
             Ok(low_msg) => {
-                if let Ok(high_msg) = self.high_priority_rx.try_recv() {
+                if let Some(high_msg) = self.try_recv_control_or_high_priority() {
                     self.pending_low_priority_message.place(low_msg);
                     Ok(high_msg)
                 } else {
@@ -248,7 +276,7 @@ impl<T> Receiver<T> {
                 }
             }
             Err(TryRecvError::Disconnected) => {
-                if let Ok(high_msg) = self.high_priority_rx.try_recv() {
+                if let Some(high_msg) = self.try_recv_control_or_high_priority() {
                     Ok(high_msg)
                 } else {
                     Err(RecvError::Disconnected)
@@ -258,11 +286,22 @@ impl<T> Receiver<T> {
         }
     }
 
+    /// Waits for the next control or high priority message.
     pub async fn recv_high_priority(&self) -> T {
-        self.high_priority_rx
-            .recv_async()
-            .await
-            .expect("The Receiver owns the high priority Sender to avoid any disconnection.")
+        if let Some(msg) = self.try_recv_control_or_high_priority() {
+            return msg;
+        }
+        tokio::select! {
+            biased;
+            control_msg_res = self.control_rx.recv_async() => {
+                control_msg_res
+                    .expect("The Receiver owns the control Sender to avoid any disconnection.")
+            }
+            high_priority_msg_res = self.high_priority_rx.recv_async() => {
+                high_priority_msg_res
+                    .expect("The Receiver owns the high priority Sender to avoid any disconnection.")
+            }
+        }
     }
 
     pub async fn recv(&self) -> Result<T, RecvError> {
@@ -276,6 +315,16 @@ impl<T> Receiver<T> {
             // We don't really care about fairness here.
             // We will double check if there is a command or not anyway.
             biased;
+            control_msg_res = self.control_rx.recv_async() => {
+                match control_msg_res {
+                    Ok(control_msg) => {
+                        Ok(control_msg)
+                    },
+                    Err(_) => {
+                        unreachable!("The Receiver owns the control Sender to avoid any disconnection.")
+                    },
+                }
+            }
             high_priority_msg_res = self.high_priority_rx.recv_async() => {
                 match high_priority_msg_res {
                     Ok(high_priority_msg) => {
@@ -383,6 +432,29 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_recv_control_priority() -> anyhow::Result<()> {
+        let (sender, receiver) = super::channel::<usize>(QueueCapacity::Unbounded);
+        sender.send_low_priority(1).await?;
+        sender.send_high_priority(2)?;
+        sender.send_high_priority(3)?;
+        sender.send_control_priority(4)?;
+        assert_eq!(receiver.recv().await, Ok(4));
+        assert_eq!(receiver.recv().await, Ok(2));
+        sender.send_control_priority(5)?;
+        assert_eq!(receiver.try_recv(), Ok(5));
+        assert_eq!(receiver.try_recv(), Ok(3));
+        assert_eq!(receiver.try_recv(), Ok(1));
+        assert_eq!(receiver.try_recv(), Err(RecvError::NoMessageAvailable));
+
+        sender.send_high_priority(6)?;
+        sender.send_control_priority(7)?;
+        assert_eq!(receiver.recv_high_priority().await, 7);
+        assert_eq!(receiver.try_recv_high_priority_message(), Ok(6));
+        assert!(receiver.is_empty());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_try_recv() -> anyhow::Result<()> {
         let (sender, receiver) = super::channel::<usize>(QueueCapacity::Unbounded);
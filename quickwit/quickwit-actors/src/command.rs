@@ -26,10 +26,10 @@ use crate::{Actor, ActorContext, ActorExitStatus, Handler};
 /// They are similar to UNIX signals.
 ///
 /// They are treated with a higher priority than regular actor messages.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum Command {
     /// Temporarily pauses the actor. A paused actor only checks
-    /// on its high priority channel and still shows "progress". It appears as
+    /// on its control and high priority channels and still shows "progress". It appears as
     /// healthy to the supervisor.
     ///
     /// Scheduled message are still processed.
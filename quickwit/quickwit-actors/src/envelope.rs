@@ -19,11 +19,15 @@
 
 use std::any::Any;
 use std::fmt;
+use std::time::Instant;
 
 use async_trait::async_trait;
 use tokio::sync::oneshot;
+use tracing::Instrument;
 
 use crate::actor::DeferableReplyHandler;
+use crate::message_tracing::{record_handling_time, MessageTrace};
+use crate::metrics::QueuedMessageGuard;
 use crate::scheduler::NoAdvanceTimeGuard;
 use crate::{Actor, ActorContext, ActorExitStatus};
 
@@ -37,6 +41,9 @@ use crate::{Actor, ActorContext, ActorExitStatus};
 
 pub struct Envelope<A> {
     handler_envelope: Box<dyn EnvelopeT<A>>,
+    deadline_opt: Option<Instant>,
+    queued_message_guard_opt: Option<QueuedMessageGuard>,
+    message_trace_opt: Option<MessageTrace>,
     _no_advance_time_guard: Option<NoAdvanceTimeGuard>,
 }
 
@@ -56,13 +63,47 @@ impl<A: Actor> Envelope<A> {
         }
     }
 
+    /// Records that the message was received from the mailbox.
+    pub(crate) fn mark_dequeued(&mut self) {
+        if let Some(queued_message_guard) = self.queued_message_guard_opt.take() {
+            queued_message_guard.dequeue();
+        }
+    }
+
+    /// Returns the instant after which the sender no longer waits for the reply, if any.
+    pub fn deadline_opt(&self) -> Option<Instant> {
+        self.deadline_opt
+    }
+
     /// Execute the captured handle function.
+    ///
+    /// The deadline of the message is exposed to the handler through the actor context for the
+    /// time of the call. If the message was sent with message tracing enabled, the call is
+    /// instrumented with the span of the message.
     pub async fn handle_message(
         &mut self,
         actor: &mut A,
         ctx: &ActorContext<A>,
     ) -> Result<(), ActorExitStatus> {
-        self.handler_envelope.handle_message(actor, ctx).await?;
+        ctx.set_deadline(self.deadline_opt);
+        let handle_result = if let Some(message_trace) = self.message_trace_opt.take() {
+            let span = message_trace.handling_span(
+                ctx.actor_instance_id(),
+                self.handler_envelope.message_type_name(),
+            );
+            let start = Instant::now();
+            let handle_result = self
+                .handler_envelope
+                .handle_message(actor, ctx)
+                .instrument(span.clone())
+                .await;
+            record_handling_time(&span, start.elapsed());
+            handle_result
+        } else {
+            self.handler_envelope.handle_message(actor, ctx).await
+        };
+        ctx.set_deadline(None);
+        handle_result?;
         Ok(())
     }
 }
@@ -78,6 +119,8 @@ impl<A: Actor> fmt::Debug for Envelope<A> {
 trait EnvelopeT<A: Actor>: Send + Sync {
     fn debug_msg(&self) -> String;
 
+    fn message_type_name(&self) -> &'static str;
+
     /// Returns the message as a boxed any.
     ///
     /// This method is only useful in unit tests.
@@ -106,6 +149,10 @@ where
         }
     }
 
+    fn message_type_name(&self) -> &'static str {
+        std::any::type_name::<M>()
+    }
+
     fn message(&mut self) -> Box<dyn Any> {
         if let Some((_, message)) = self.take() {
             Box::new(message)
@@ -139,6 +186,9 @@ where
 
 pub(crate) fn wrap_in_envelope<A, M>(
     msg: M,
+    deadline_opt: Option<Instant>,
+    queued_message_guard_opt: Option<QueuedMessageGuard>,
+    message_trace_opt: Option<MessageTrace>,
     no_advance_time_guard: Option<NoAdvanceTimeGuard>,
 ) -> (Envelope<A>, oneshot::Receiver<A::Reply>)
 where
@@ -149,6 +199,9 @@ where
     let handler_envelope = Some((response_tx, msg));
     let envelope = Envelope {
         handler_envelope: Box::new(handler_envelope),
+        deadline_opt,
+        queued_message_guard_opt,
+        message_trace_opt,
         _no_advance_time_guard: no_advance_time_guard,
     };
     (envelope, response_rx)
@@ -40,13 +40,18 @@ pub mod channel_with_priority;
 mod command;
 mod envelope;
 mod mailbox;
+mod message_tracing;
+mod metrics;
 mod observation;
+mod panic_report;
+mod pool;
 mod registry;
+mod resource_budget;
 pub(crate) mod scheduler;
 mod spawn_builder;
 mod supervisor;
 
-pub use scheduler::{start_scheduler, SchedulerClient};
+pub use scheduler::{start_deterministic_scheduler, start_scheduler, SchedulerClient};
 
 #[cfg(test)]
 pub(crate) mod tests;
@@ -61,12 +66,16 @@ pub use spawn_builder::SpawnContext;
 use thiserror::Error;
 pub use universe::Universe;
 
-pub use self::actor_context::ActorContext;
+pub use self::actor_context::{ActorContext, ScheduledMessageHandle};
 pub use self::actor_state::ActorState;
 pub use self::channel_with_priority::{QueueCapacity, RecvError, SendError, TrySendError};
-pub use self::mailbox::{Inbox, Mailbox};
+pub use self::mailbox::{Inbox, Mailbox, WeakMailbox};
+pub use self::message_tracing::{is_message_tracing_enabled, set_message_tracing_enabled};
+pub use self::panic_report::PanicReport;
+pub use self::pool::{ActorPool, ActorPoolConfig, ActorPoolState};
 pub use self::registry::ActorObservation;
-pub use self::supervisor::{Supervisor, SupervisorState};
+pub use self::resource_budget::{ResourceBudget, ResourceGuard};
+pub use self::supervisor::{RestartPolicy, Supervisor, SupervisorState};
 
 /// Heartbeat used to verify that actors are progressing.
 ///
@@ -96,6 +105,8 @@ pub enum AskError<E: fmt::Debug> {
     MessageNotDelivered,
     #[error("Error while the message was being processed.")]
     ProcessMessageError,
+    #[error("The reply was not received before the deadline.")]
+    Timeout,
     #[error("The handler returned an error: `{0:?}`.")]
     ErrorReply(#[from] E),
 }
@@ -105,6 +116,7 @@ impl<E: fmt::Debug + ServiceError> ServiceError for AskError<E> {
         match self {
             AskError::MessageNotDelivered => ServiceErrorCode::Internal,
             AskError::ProcessMessageError => ServiceErrorCode::Internal,
+            AskError::Timeout => ServiceErrorCode::Unavailable,
             AskError::ErrorReply(err) => err.status_code(),
         }
     }
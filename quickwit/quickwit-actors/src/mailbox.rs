@@ -22,7 +22,7 @@ use std::convert::Infallible;
 use std::fmt;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::{Arc, Weak};
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use quickwit_common::metrics::IntCounter;
@@ -30,6 +30,8 @@ use tokio::sync::oneshot;
 
 use crate::channel_with_priority::{Receiver, Sender, TrySendError};
 use crate::envelope::{wrap_in_envelope, Envelope};
+use crate::message_tracing::MessageTrace;
+use crate::metrics::{MailboxMetrics, QueuedMessageGuard};
 use crate::scheduler::SchedulerClient;
 use crate::{
     Actor, ActorContext, ActorExitStatus, AskError, DeferableReplyHandler, Handler, QueueCapacity,
@@ -43,12 +45,13 @@ use crate::{
 ///
 /// The actor holds its `Inbox` counterpart.
 ///
-/// The mailbox can receive high priority and low priority messages.
-/// Commands are typically sent as high priority messages, whereas regular
-/// actor messages are sent to the low priority channel.
+/// The mailbox can receive control, high priority, and low priority messages.
+/// Commands and observations are sent as control messages, scheduled messages
+/// are sent as high priority messages, whereas regular actor messages are sent
+/// to the low priority channel.
 ///
-/// Whenever a high priority message is available, it is processed
-/// before low priority messages.
+/// Whenever a control message is available, it is processed before high priority
+/// messages, which are themselves processed before low priority messages.
 ///
 /// If all mailboxes are dropped, the actor will process all of the pending messages
 /// and gracefully exit with [`crate::actor::ActorExitStatus::Success`].
@@ -72,11 +75,16 @@ impl<A: Actor> Mailbox<A> {
 impl<A: Actor> Drop for Mailbox<A> {
     fn drop(&mut self) {
         let old_val = self.ref_count.fetch_sub(1, Ordering::SeqCst);
-        if old_val == 2 {
+        let num_actors = self.num_actors();
+        if old_val == num_actors + 1 {
             // This was the last mailbox.
-            // `ref_count == 1` means that only the mailbox in the ActorContext
-            // is remaining.
-            let _ = self.send_message_with_high_priority(LastMailbox);
+            // `ref_count == num_actors` means that only the mailboxes in the
+            // ActorContexts are remaining.
+            //
+            // Every actor consuming the inbox needs to be woken up.
+            for _ in 0..num_actors {
+                let _ = self.send_message_with_high_priority(LastMailbox);
+            }
         }
     }
 }
@@ -112,6 +120,7 @@ impl<A: Actor> Handler<LastMailbox> for A {
 
 #[derive(Copy, Clone)]
 pub(crate) enum Priority {
+    Control,
     High,
     Low,
 }
@@ -128,7 +137,24 @@ impl<A: Actor> Clone for Mailbox<A> {
 
 impl<A: Actor> Mailbox<A> {
     pub(crate) fn is_last_mailbox(&self) -> bool {
-        self.ref_count.load(Ordering::SeqCst) == 1
+        self.ref_count.load(Ordering::SeqCst) <= self.num_actors()
+    }
+
+    /// Returns the number of running actors consuming the messages of this mailbox.
+    ///
+    /// It is greater than 1 for the workers of an [`crate::ActorPool`]. A mailbox whose actor is
+    /// not spawned yet (or already exited) counts as having one actor, since it is expected to
+    /// be handed over to an actor context.
+    fn num_actors(&self) -> usize {
+        self.inner.num_actors.load(Ordering::SeqCst).max(1)
+    }
+
+    pub(crate) fn register_actor(&self) {
+        self.inner.num_actors.fetch_add(1, Ordering::SeqCst);
+    }
+
+    pub(crate) fn unregister_actor(&self) {
+        self.inner.num_actors.fetch_sub(1, Ordering::SeqCst);
     }
 
     pub fn id(&self) -> &str {
@@ -144,6 +170,9 @@ struct Inner<A: Actor> {
     pub(crate) tx: Sender<Envelope<A>>,
     scheduler_client_opt: Option<SchedulerClient>,
     instance_id: String,
+    metrics: Arc<MailboxMetrics>,
+    /// Number of running actors consuming the messages of the mailbox.
+    num_actors: AtomicUsize,
 }
 
 impl<A: Actor> fmt::Debug for Mailbox<A> {
@@ -191,7 +220,7 @@ impl<A: Actor> Mailbox<A> {
         A: DeferableReplyHandler<M>,
         M: 'static + Send + Sync + fmt::Debug,
     {
-        let (envelope, response_rx) = self.wrap_in_envelope(message);
+        let (envelope, response_rx) = self.wrap_in_envelope(message, None);
         self.inner
             .tx
             .try_send_low_priority(envelope)
@@ -208,7 +237,11 @@ impl<A: Actor> Mailbox<A> {
         Ok(response_rx)
     }
 
-    fn wrap_in_envelope<M>(&self, message: M) -> (Envelope<A>, oneshot::Receiver<A::Reply>)
+    fn wrap_in_envelope<M>(
+        &self,
+        message: M,
+        deadline_opt: Option<Instant>,
+    ) -> (Envelope<A>, oneshot::Receiver<A::Reply>)
     where
         A: DeferableReplyHandler<M>,
         M: 'static + Send + Sync + fmt::Debug,
@@ -218,7 +251,14 @@ impl<A: Actor> Mailbox<A> {
             .scheduler_client_opt
             .as_ref()
             .map(|scheduler_client| scheduler_client.no_advance_time_guard());
-        wrap_in_envelope(message, guard)
+        let queued_message_guard = QueuedMessageGuard::new(self.inner.metrics.clone());
+        wrap_in_envelope(
+            message,
+            deadline_opt,
+            Some(queued_message_guard),
+            MessageTrace::capture_if_enabled(),
+            guard,
+        )
     }
 
     /// Sends a message to the actor owning the associated inbox.
@@ -235,9 +275,19 @@ impl<A: Actor> Mailbox<A> {
         A: DeferableReplyHandler<M>,
         M: 'static + Send + Sync + fmt::Debug,
     {
-        let (envelope, response_rx) = self.wrap_in_envelope(message);
+        let (envelope, response_rx) = self.wrap_in_envelope(message, None);
+        self.send_envelope_with_backpressure_counter(envelope, backpressure_micros_counter_opt)
+            .await?;
+        Ok(response_rx)
+    }
+
+    async fn send_envelope_with_backpressure_counter(
+        &self,
+        envelope: Envelope<A>,
+        backpressure_micros_counter_opt: Option<&IntCounter>,
+    ) -> Result<(), SendError> {
         match self.inner.tx.try_send_low_priority(envelope) {
-            Ok(()) => Ok(response_rx),
+            Ok(()) => Ok(()),
             Err(TrySendError::Full(envelope)) => {
                 if let Some(backpressure_micros_counter) = backpressure_micros_counter_opt {
                     let now = Instant::now();
@@ -247,7 +297,7 @@ impl<A: Actor> Mailbox<A> {
                 } else {
                     self.inner.tx.send_low_priority(envelope).await?;
                 }
-                Ok(response_rx)
+                Ok(())
             }
             Err(TrySendError::Disconnected) => Err(SendError::Disconnected),
         }
@@ -261,11 +311,27 @@ impl<A: Actor> Mailbox<A> {
         A: DeferableReplyHandler<M>,
         M: 'static + Send + Sync + fmt::Debug,
     {
-        let (envelope, response_rx) = self.wrap_in_envelope(message);
+        let (envelope, response_rx) = self.wrap_in_envelope(message, None);
         self.inner.tx.send_high_priority(envelope)?;
         Ok(response_rx)
     }
 
+    /// Sends a message in the control lane of the mailbox, which is processed before the high and
+    /// low priority messages. It is meant for the commands and observations that must not wait
+    /// behind a backlog of regular or scheduled messages.
+    pub(crate) fn send_message_with_control_priority<M>(
+        &self,
+        message: M,
+    ) -> Result<oneshot::Receiver<A::Reply>, SendError>
+    where
+        A: DeferableReplyHandler<M>,
+        M: 'static + Send + Sync + fmt::Debug,
+    {
+        let (envelope, response_rx) = self.wrap_in_envelope(message, None);
+        self.inner.tx.send_control_priority(envelope)?;
+        Ok(response_rx)
+    }
+
     pub(crate) async fn send_message_with_priority<M>(
         &self,
         message: M,
@@ -275,8 +341,9 @@ impl<A: Actor> Mailbox<A> {
         A: DeferableReplyHandler<M>,
         M: 'static + Send + Sync + fmt::Debug,
     {
-        let (envelope, response_rx) = self.wrap_in_envelope(message);
+        let (envelope, response_rx) = self.wrap_in_envelope(message, None);
         match priority {
+            Priority::Control => self.inner.tx.send_control_priority(envelope)?,
             Priority::High => self.inner.tx.send_high_priority(envelope)?,
             Priority::Low => {
                 self.inner.tx.send_low_priority(envelope).await?;
@@ -325,6 +392,50 @@ impl<A: Actor> Mailbox<A> {
             .map_err(|_| AskError::ProcessMessageError)
     }
 
+    /// Similar to `ask`, except the actor reply is only awaited for `timeout`. Past this delay,
+    /// `AskError::Timeout` is returned.
+    ///
+    /// The deadline is propagated to the handler context: the handler can check its remaining
+    /// budget with `ActorContext::remaining_budget` and cut its work short. The requests it sends
+    /// with `ActorContext::ask` inherit the same deadline.
+    pub async fn ask_with_timeout<M, T>(
+        &self,
+        message: M,
+        timeout: Duration,
+    ) -> Result<T, AskError<Infallible>>
+    where
+        A: DeferableReplyHandler<M, Reply = T>,
+        M: 'static + Send + Sync + fmt::Debug,
+    {
+        self.ask_with_deadline(message, Instant::now() + timeout, None)
+            .await
+    }
+
+    /// Similar to `ask_with_backpressure_counter`, except the actor reply is only awaited until
+    /// `deadline`, which is propagated to the handler context.
+    pub(crate) async fn ask_with_deadline<M, E>(
+        &self,
+        message: M,
+        deadline: Instant,
+        backpressure_micros_counter_opt: Option<&IntCounter>,
+    ) -> Result<A::Reply, AskError<E>>
+    where
+        A: DeferableReplyHandler<M>,
+        M: 'static + Send + Sync + fmt::Debug,
+        E: fmt::Debug,
+    {
+        let (envelope, response_rx) = self.wrap_in_envelope(message, Some(deadline));
+        let send_and_wait = async {
+            self.send_envelope_with_backpressure_counter(envelope, backpressure_micros_counter_opt)
+                .await
+                .map_err(|_send_error| AskError::MessageNotDelivered)?;
+            response_rx.await.map_err(|_| AskError::ProcessMessageError)
+        };
+        tokio::time::timeout_at(deadline.into(), send_and_wait)
+            .await
+            .unwrap_or_else(|_elapsed| Err(AskError::Timeout))
+    }
+
     /// Similar to `send_message`, except this method
     /// waits asynchronously for the actor reply.
     ///
@@ -346,12 +457,14 @@ impl<A: Actor> Mailbox<A> {
 
 pub struct Inbox<A: Actor> {
     rx: Arc<Receiver<Envelope<A>>>,
+    metrics: Arc<MailboxMetrics>,
 }
 
 impl<A: Actor> Clone for Inbox<A> {
     fn clone(&self) -> Self {
         Inbox {
             rx: self.rx.clone(),
+            metrics: self.metrics.clone(),
         }
     }
 }
@@ -361,20 +474,29 @@ impl<A: Actor> Inbox<A> {
         self.rx.is_empty()
     }
 
+    /// Returns the number of regular messages waiting to be processed.
+    pub(crate) fn queue_depth(&self) -> usize {
+        self.rx.num_low_priority_messages()
+    }
+
+    pub(crate) fn metrics(&self) -> &MailboxMetrics {
+        &self.metrics
+    }
+
     pub(crate) async fn recv(&self) -> Result<Envelope<A>, RecvError> {
-        self.rx.recv().await
+        self.rx.recv().await.map(dequeued)
     }
 
     pub(crate) async fn recv_cmd_and_scheduled_msg_only(&self) -> Envelope<A> {
-        self.rx.recv_high_priority().await
+        dequeued(self.rx.recv_high_priority().await)
     }
 
     pub(crate) fn try_recv(&self) -> Result<Envelope<A>, RecvError> {
-        self.rx.try_recv()
+        self.rx.try_recv().map(dequeued)
     }
 
     pub async fn recv_typed_message<M: 'static>(&self) -> Option<M> {
-        while let Ok(mut envelope) = self.rx.recv().await {
+        while let Ok(mut envelope) = self.recv().await {
             if let Some(msg) = envelope.message_typed() {
                 return Some(msg);
             }
@@ -384,7 +506,7 @@ impl<A: Actor> Inbox<A> {
 
     #[allow(dead_code)] // temporary
     pub(crate) fn try_recv_cmd_and_scheduled_msg_only(&self) -> Result<Envelope<A>, RecvError> {
-        self.rx.try_recv_high_priority_message()
+        self.rx.try_recv_high_priority_message().map(dequeued)
     }
 
     /// Destroys the inbox and returns the list of pending messages or commands
@@ -421,18 +543,29 @@ pub(crate) fn create_mailbox<A: Actor>(
 ) -> (Mailbox<A>, Inbox<A>) {
     let (tx, rx) = crate::channel_with_priority::channel(queue_capacity);
     let ref_count = Arc::new(AtomicUsize::new(1));
+    let metrics = MailboxMetrics::for_actor_type::<A>();
     let mailbox = Mailbox {
         inner: Arc::new(Inner {
             tx,
             instance_id: quickwit_common::new_coolid(&actor_name),
             scheduler_client_opt,
+            metrics: metrics.clone(),
+            num_actors: AtomicUsize::new(0),
         }),
         ref_count,
     };
-    let inbox = Inbox { rx: Arc::new(rx) };
+    let inbox = Inbox {
+        rx: Arc::new(rx),
+        metrics,
+    };
     (mailbox, inbox)
 }
 
+fn dequeued<A: Actor>(mut envelope: Envelope<A>) -> Envelope<A> {
+    envelope.mark_dequeued();
+    envelope
+}
+
 pub struct WeakMailbox<A: Actor> {
     inner: Weak<Inner<A>>,
     ref_count: Weak<AtomicUsize>,
@@ -442,6 +575,7 @@ impl<A: Actor> WeakMailbox<A> {
     pub fn upgrade(&self) -> Option<Mailbox<A>> {
         let inner = self.inner.upgrade()?;
         let ref_count = self.ref_count.upgrade()?;
+        ref_count.fetch_add(1, Ordering::SeqCst);
         Some(Mailbox { inner, ref_count })
     }
 }
@@ -590,6 +724,59 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[derive(Default)]
+    struct DeadlineActor;
+
+    impl Actor for DeadlineActor {
+        type ObservableState = ();
+
+        fn observable_state(&self) -> Self::ObservableState {}
+    }
+
+    #[async_trait]
+    impl Handler<Duration> for DeadlineActor {
+        type Reply = Option<Duration>;
+
+        async fn handle(
+            &mut self,
+            sleep_duration: Duration,
+            ctx: &ActorContext<Self>,
+        ) -> Result<Option<Duration>, ActorExitStatus> {
+            if !sleep_duration.is_zero() {
+                tokio::time::sleep(sleep_duration).await;
+            }
+            Ok(ctx.remaining_budget())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mailbox_ask_with_timeout() {
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, _handle) = universe.spawn_builder().spawn(DeadlineActor);
+
+        let remaining_budget_opt = mailbox.ask(Duration::ZERO).await.unwrap();
+        assert!(remaining_budget_opt.is_none());
+
+        let remaining_budget_opt = mailbox
+            .ask_with_timeout(Duration::ZERO, Duration::from_secs(60))
+            .await
+            .unwrap();
+        let remaining_budget = remaining_budget_opt.unwrap();
+        assert!(remaining_budget > Duration::ZERO);
+        assert!(remaining_budget <= Duration::from_secs(60));
+
+        let ask_error = mailbox
+            .ask_with_timeout(Duration::from_millis(500), Duration::from_millis(10))
+            .await
+            .unwrap_err();
+        assert!(matches!(ask_error, AskError::Timeout));
+
+        // The deadline of a request does not leak into the following ones.
+        let remaining_budget_opt = mailbox.ask(Duration::ZERO).await.unwrap();
+        assert!(remaining_budget_opt.is_none());
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_try_send() {
         let universe = Universe::with_accelerated_time();
@@ -0,0 +1,116 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+use tracing::{info_span, Span};
+
+/// Enables message tracing when set to `true`. It can also be toggled at runtime with
+/// [`set_message_tracing_enabled`].
+const MESSAGE_TRACING_ENV_KEY: &str = "QW_ACTORS_MESSAGE_TRACING";
+
+static MESSAGE_TRACING_ENABLED: Lazy<AtomicBool> = Lazy::new(|| {
+    AtomicBool::new(quickwit_common::get_from_env(
+        MESSAGE_TRACING_ENV_KEY,
+        false,
+    ))
+});
+
+/// Enables or disables the emission of a tracing span for every message handled by an actor.
+///
+/// The spans are named `actor_message` and carry the actor instance ID, the type of the message,
+/// the time the message spent in the mailbox, and the time spent handling it. They are children
+/// of the span that was current when the message was sent, so the latency of a message crossing
+/// several actors can be followed in a tracing backend such as Jaeger.
+///
+/// Only the messages sent after this call are affected.
+pub fn set_message_tracing_enabled(enabled: bool) {
+    MESSAGE_TRACING_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_message_tracing_enabled() -> bool {
+    MESSAGE_TRACING_ENABLED.load(Ordering::Relaxed)
+}
+
+/// Context captured when a message is sent, used to emit its span when it is handled.
+pub(crate) struct MessageTrace {
+    sent_at: Instant,
+    sender_span: Span,
+}
+
+impl MessageTrace {
+    /// Captures the trace context of a message about to be sent, if message tracing is enabled.
+    pub fn capture_if_enabled() -> Option<MessageTrace> {
+        if !is_message_tracing_enabled() {
+            return None;
+        }
+        Some(MessageTrace {
+            sent_at: Instant::now(),
+            sender_span: Span::current(),
+        })
+    }
+
+    /// Creates the span of the handling of the message. The handling time must be recorded with
+    /// [`record_handling_time`] once the message is handled.
+    pub fn handling_span(&self, actor_instance_id: &str, message_type: &'static str) -> Span {
+        let queue_wait = self.sent_at.elapsed();
+        info_span!(
+            parent: &self.sender_span,
+            "actor_message",
+            actor = actor_instance_id,
+            message = message_type,
+            queue_wait_micros = queue_wait.as_micros() as u64,
+            handling_micros = tracing::field::Empty,
+        )
+    }
+}
+
+pub(crate) fn record_handling_time(span: &Span, handling_time: Duration) {
+    span.record("handling_micros", handling_time.as_micros() as u64);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::{Ping, PingReceiverActor};
+    use crate::Universe;
+
+    #[tokio::test]
+    async fn test_message_tracing_toggle() {
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, handle) = universe.spawn_builder().spawn(PingReceiverActor::default());
+
+        set_message_tracing_enabled(false);
+        assert!(!is_message_tracing_enabled());
+        assert!(MessageTrace::capture_if_enabled().is_none());
+
+        set_message_tracing_enabled(true);
+        assert!(is_message_tracing_enabled());
+        assert!(MessageTrace::capture_if_enabled().is_some());
+
+        mailbox.send_message(Ping).await.unwrap();
+        mailbox.send_message(Ping).await.unwrap();
+        assert_eq!(handle.process_pending_and_observe().await.state, 2);
+
+        set_message_tracing_enabled(false);
+        universe.assert_quit().await;
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use once_cell::sync::Lazy;
+use quickwit_common::metrics::{
+    new_counter_vec, new_gauge_vec, new_histogram_vec, Histogram, HistogramVec, IntCounter,
+    IntCounterVec, IntGauge, IntGaugeVec,
+};
+
+pub struct ActorMetrics {
+    pub mailbox_queue_depth: IntGaugeVec<1>,
+    pub mailbox_enqueued_messages_total: IntCounterVec<1>,
+    pub mailbox_dequeued_messages_total: IntCounterVec<1>,
+    pub message_handling_duration_seconds: HistogramVec<1>,
+}
+
+impl Default for ActorMetrics {
+    fn default() -> Self {
+        ActorMetrics {
+            mailbox_queue_depth: new_gauge_vec(
+                "mailbox_queue_depth",
+                "Number of messages waiting in the mailboxes (per actor type).",
+                "quickwit_actors",
+                ["actor"],
+            ),
+            mailbox_enqueued_messages_total: new_counter_vec(
+                "mailbox_enqueued_messages_total",
+                "Number of messages sent to the mailboxes (per actor type).",
+                "quickwit_actors",
+                ["actor"],
+            ),
+            mailbox_dequeued_messages_total: new_counter_vec(
+                "mailbox_dequeued_messages_total",
+                "Number of messages received from the mailboxes (per actor type).",
+                "quickwit_actors",
+                ["actor"],
+            ),
+            message_handling_duration_seconds: new_histogram_vec(
+                "message_handling_duration_seconds",
+                "Time spent handling a message (per actor type).",
+                "quickwit_actors",
+                ["actor"],
+            ),
+        }
+    }
+}
+
+pub static ACTOR_METRICS: Lazy<ActorMetrics> = Lazy::new(ActorMetrics::default);
+
+/// Metrics of the mailbox of an actor, shared by its mailboxes, its inbox, and the messages in
+/// flight.
+pub(crate) struct MailboxMetrics {
+    queue_depth: IntGauge,
+    enqueued_messages: IntCounter,
+    dequeued_messages: IntCounter,
+    pub handling_duration: Histogram,
+}
+
+impl MailboxMetrics {
+    pub fn for_actor_type<A>() -> Arc<MailboxMetrics> {
+        let actor_type = actor_type_label::<A>();
+        let label_values = [actor_type.as_str()];
+        let mailbox_metrics = MailboxMetrics {
+            queue_depth: ACTOR_METRICS
+                .mailbox_queue_depth
+                .with_label_values(label_values),
+            enqueued_messages: ACTOR_METRICS
+                .mailbox_enqueued_messages_total
+                .with_label_values(label_values),
+            dequeued_messages: ACTOR_METRICS
+                .mailbox_dequeued_messages_total
+                .with_label_values(label_values),
+            handling_duration: ACTOR_METRICS
+                .message_handling_duration_seconds
+                .with_label_values(label_values),
+        };
+        Arc::new(mailbox_metrics)
+    }
+}
+
+/// Accounts for a message in the queue depth of a mailbox until the message is received or
+/// dropped.
+pub(crate) struct QueuedMessageGuard {
+    mailbox_metrics: Arc<MailboxMetrics>,
+}
+
+impl QueuedMessageGuard {
+    pub fn new(mailbox_metrics: Arc<MailboxMetrics>) -> Self {
+        mailbox_metrics.enqueued_messages.inc();
+        mailbox_metrics.queue_depth.inc();
+        QueuedMessageGuard { mailbox_metrics }
+    }
+
+    pub fn dequeue(self) {
+        self.mailbox_metrics.dequeued_messages.inc();
+    }
+}
+
+impl Drop for QueuedMessageGuard {
+    fn drop(&mut self) {
+        self.mailbox_metrics.queue_depth.dec();
+    }
+}
+
+/// Returns the type name of the actor stripped of its module paths, for instance
+/// `Supervisor<Indexer>`, which keeps the cardinality of the metrics bounded and readable.
+fn actor_type_label<A>() -> String {
+    let type_name = std::any::type_name::<A>();
+    let mut label = String::with_capacity(type_name.len());
+    let mut path = String::new();
+
+    for c in type_name.chars() {
+        if c.is_alphanumeric() || c == '_' || c == ':' {
+            path.push(c);
+            continue;
+        }
+        label.push_str(path.rsplit("::").next().unwrap_or_default());
+        path.clear();
+        label.push(c);
+    }
+    label.push_str(path.rsplit("::").next().unwrap_or_default());
+    label
+}
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+
+    use super::*;
+    use crate::tests::{Ping, PingReceiverActor};
+    use crate::{
+        Actor, ActorContext, ActorExitStatus, Handler, QueueCapacity, Supervisor, Universe,
+    };
+
+    // The metrics are global: this actor type is only used by this test to avoid interferences.
+    #[derive(Default)]
+    struct MailboxMetricsTestActor;
+
+    impl Actor for MailboxMetricsTestActor {
+        type ObservableState = ();
+
+        fn observable_state(&self) -> Self::ObservableState {}
+    }
+
+    #[async_trait]
+    impl Handler<Ping> for MailboxMetricsTestActor {
+        type Reply = ();
+
+        async fn handle(
+            &mut self,
+            _msg: Ping,
+            _ctx: &ActorContext<Self>,
+        ) -> Result<(), ActorExitStatus> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_mailbox_metrics() {
+        let label_values = ["MailboxMetricsTestActor"];
+        let queue_depth = ACTOR_METRICS
+            .mailbox_queue_depth
+            .with_label_values(label_values);
+        let enqueued_messages = ACTOR_METRICS
+            .mailbox_enqueued_messages_total
+            .with_label_values(label_values);
+        let dequeued_messages = ACTOR_METRICS
+            .mailbox_dequeued_messages_total
+            .with_label_values(label_values);
+        let handling_duration = ACTOR_METRICS
+            .message_handling_duration_seconds
+            .with_label_values(label_values);
+
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, inbox) = universe.create_mailbox::<MailboxMetricsTestActor>(
+            "MailboxMetricsTestActor",
+            QueueCapacity::Unbounded,
+        );
+        mailbox.send_message(Ping).await.unwrap();
+        mailbox.send_message(Ping).await.unwrap();
+        assert_eq!(queue_depth.get(), 2);
+        assert_eq!(enqueued_messages.get(), 2);
+
+        inbox.recv().await.unwrap();
+        assert_eq!(queue_depth.get(), 1);
+        assert_eq!(dequeued_messages.get(), 1);
+
+        // The pending messages dropped with the mailbox no longer count in the queue depth.
+        drop(mailbox);
+        drop(inbox);
+        assert_eq!(queue_depth.get(), 0);
+
+        let (mailbox, handle) = universe.spawn_builder().spawn(MailboxMetricsTestActor);
+        mailbox.ask(Ping).await.unwrap();
+        drop(mailbox);
+        handle.join().await;
+        assert!(handling_duration.get_sample_count() >= 1);
+        assert_eq!(queue_depth.get(), 0);
+    }
+
+    #[test]
+    fn test_actor_type_label() {
+        assert_eq!(actor_type_label::<PingReceiverActor>(), "PingReceiverActor");
+        assert_eq!(
+            actor_type_label::<Supervisor<PingReceiverActor>>(),
+            "Supervisor<PingReceiverActor>"
+        );
+        assert_eq!(
+            actor_type_label::<(usize, Option<String>)>(),
+            "(usize, Option<String>)"
+        );
+    }
+}
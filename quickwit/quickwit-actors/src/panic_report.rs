@@ -0,0 +1,112 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::any::Any;
+use std::backtrace::Backtrace;
+use std::cell::RefCell;
+use std::fmt;
+use std::sync::Once;
+
+use serde::Serialize;
+
+/// Describes why an actor panicked.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct PanicReport {
+    /// Message of the panic, extracted from its payload.
+    pub message: String,
+    /// Location in the source code where the panic occurred, if known.
+    #[serde(rename = "location", skip_serializing_if = "Option::is_none")]
+    pub location_opt: Option<String>,
+    /// Backtrace captured when the panic occurred, if known.
+    #[serde(rename = "backtrace", skip_serializing_if = "Option::is_none")]
+    pub backtrace_opt: Option<String>,
+}
+
+impl fmt::Display for PanicReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        if let Some(location) = &self.location_opt {
+            write!(f, " at {location}")?;
+        }
+        Ok(())
+    }
+}
+
+impl PanicReport {
+    pub(crate) fn new(message: String) -> Self {
+        PanicReport {
+            message,
+            location_opt: None,
+            backtrace_opt: None,
+        }
+    }
+
+    /// Builds a report from a panic payload only.
+    pub(crate) fn from_payload(payload: &(dyn Any + Send)) -> Self {
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            message.to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "Box<dyn Any>".to_string()
+        };
+        PanicReport::new(message)
+    }
+
+    /// Builds a report from the payload of a panic that was just caught on the current thread,
+    /// along with the location and backtrace captured by the panic hook.
+    pub(crate) fn from_caught_panic(payload: &(dyn Any + Send)) -> Self {
+        let mut panic_report = PanicReport::from_payload(payload);
+
+        if let Some(panic_context) = LAST_PANIC_CONTEXT.with(|cell| cell.borrow_mut().take()) {
+            panic_report.location_opt = panic_context.location_opt;
+            panic_report.backtrace_opt = Some(panic_context.backtrace);
+        }
+        panic_report
+    }
+}
+
+struct PanicContext {
+    location_opt: Option<String>,
+    backtrace: String,
+}
+
+thread_local! {
+    // Context of the last panic that occurred on this thread. The panic hook runs on the
+    // panicking thread before unwinding, which is also where the actor loop catches the panic.
+    static LAST_PANIC_CONTEXT: RefCell<Option<PanicContext>> = RefCell::new(None);
+}
+
+/// Installs a panic hook capturing the location and the backtrace of the panics, so that they
+/// can be attached to the exit status of the actors. The previous hook is still called.
+pub(crate) fn install_panic_hook() {
+    static INSTALL_PANIC_HOOK: Once = Once::new();
+
+    INSTALL_PANIC_HOOK.call_once(|| {
+        let previous_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |panic_info| {
+            let panic_context = PanicContext {
+                location_opt: panic_info.location().map(ToString::to_string),
+                backtrace: Backtrace::force_capture().to_string(),
+            };
+            LAST_PANIC_CONTEXT.with(|cell| *cell.borrow_mut() = Some(panic_context));
+            previous_hook(panic_info);
+        }));
+    });
+}
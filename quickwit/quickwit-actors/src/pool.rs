@@ -0,0 +1,469 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use quickwit_common::metrics::IntCounter;
+use quickwit_common::KillSwitch;
+use serde::Serialize;
+use tracing::{error, info};
+
+use crate::mailbox::{Inbox, WeakMailbox};
+use crate::spawn_builder::SpawnBuilder;
+use crate::{
+    Actor, ActorContext, ActorExitStatus, ActorHandle, ActorState, Command, Handler, Health,
+    Mailbox, SpawnContext, Supervisable,
+};
+
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize)]
+pub struct ActorPoolState {
+    pub num_workers: usize,
+    pub num_scale_ups: usize,
+    pub num_scale_downs: usize,
+}
+
+/// Defines how an [`ActorPool`] scales its number of workers.
+///
+/// The pool adds a worker whenever messages are piling up in its mailbox, and removes one once
+/// its mailbox has been empty with some idle workers for `scale_down_idle_period`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct ActorPoolConfig {
+    /// Number of workers spawned with the pool, below which the pool never scales down.
+    pub min_num_workers: usize,
+    /// Number of workers above which the pool never scales up.
+    pub max_num_workers: usize,
+    /// Number of messages waiting in the mailbox from which a worker is added.
+    pub scale_up_queue_depth: usize,
+    /// Duration the mailbox must remain empty with idle workers before a worker is removed.
+    pub scale_down_idle_period: Duration,
+}
+
+impl Default for ActorPoolConfig {
+    fn default() -> Self {
+        ActorPoolConfig {
+            min_num_workers: 1,
+            max_num_workers: 1,
+            scale_up_queue_depth: 1,
+            scale_down_idle_period: Duration::from_secs(60),
+        }
+    }
+}
+
+impl ActorPoolConfig {
+    /// Scales the pool between `min_num_workers` and `max_num_workers`. A pool always has at
+    /// least one worker.
+    pub fn new(min_num_workers: usize, max_num_workers: usize) -> Self {
+        let min_num_workers = min_num_workers.max(1);
+        ActorPoolConfig {
+            min_num_workers,
+            max_num_workers: max_num_workers.max(min_num_workers),
+            ..Default::default()
+        }
+    }
+}
+
+/// An actor pool runs several instances of an actor, called workers, behind a single mailbox.
+///
+/// Each message sent to the mailbox is processed by one of the workers. The number of workers is
+/// adjusted at runtime depending on the number of messages waiting in the mailbox (see
+/// [`ActorPoolConfig`]), which makes it possible to use all the cores on CPU-bound stages, such as
+/// merges, when they are lagging behind, and to release them when they are idle.
+///
+/// The workers should therefore be stateless, or at least not rely on receiving all the messages.
+/// The workers share the kill switch of the pool: if a worker fails, the whole pool is killed.
+pub struct ActorPool<A: Actor> {
+    actor_name: String,
+    actor_factory: Box<dyn Fn() -> A + Sync + Send>,
+    pool_config: ActorPoolConfig,
+    // The pool must not hold a mailbox of its workers, otherwise they would never detect that
+    // they are not reachable anymore and exit.
+    weak_mailbox: WeakMailbox<A>,
+    inbox: Inbox<A>,
+    kill_switch: KillSwitch,
+    backpressure_micros_counter_opt: Option<IntCounter>,
+    worker_handles: Vec<ActorHandle<A>>,
+    /// Set once the workers started exiting successfully, which means that no one can send
+    /// messages to the pool anymore.
+    is_mailbox_closed: bool,
+    /// Number of workers that were asked to quit and have not exited yet.
+    num_pending_quits: usize,
+    idle_since_opt: Option<Instant>,
+    state: ActorPoolState,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct ScaleLoop;
+
+#[async_trait]
+impl<A: Actor> Actor for ActorPool<A> {
+    type ObservableState = ActorPoolState;
+
+    fn observable_state(&self) -> Self::ObservableState {
+        self.state
+    }
+
+    fn name(&self) -> String {
+        format!("ActorPool({})", self.actor_name)
+    }
+
+    fn queue_capacity(&self) -> crate::QueueCapacity {
+        crate::QueueCapacity::Unbounded
+    }
+
+    async fn initialize(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+        ctx.schedule_self_msg(crate::HEARTBEAT, ScaleLoop).await;
+        Ok(())
+    }
+
+    async fn finalize(
+        &mut self,
+        exit_status: &ActorExitStatus,
+        _ctx: &ActorContext<Self>,
+    ) -> anyhow::Result<()> {
+        match exit_status {
+            ActorExitStatus::Quit | ActorExitStatus::DownstreamClosed => {
+                self.stop_workers(Command::Quit).await;
+            }
+            ActorExitStatus::Killed
+            | ActorExitStatus::Failure(_)
+            | ActorExitStatus::Panicked(_) => {
+                self.kill_switch.kill();
+                self.stop_workers(Command::Nudge).await;
+            }
+            ActorExitStatus::Success => {}
+        }
+        Ok(())
+    }
+}
+
+impl<A: Actor> ActorPool<A> {
+    pub(crate) fn new(
+        actor_name: String,
+        actor_factory: Box<dyn Fn() -> A + Sync + Send>,
+        pool_config: ActorPoolConfig,
+        weak_mailbox: WeakMailbox<A>,
+        inbox: Inbox<A>,
+        kill_switch: KillSwitch,
+        backpressure_micros_counter_opt: Option<IntCounter>,
+    ) -> Self {
+        ActorPool {
+            actor_name,
+            actor_factory,
+            pool_config,
+            weak_mailbox,
+            inbox,
+            kill_switch,
+            backpressure_micros_counter_opt,
+            worker_handles: Vec::new(),
+            is_mailbox_closed: false,
+            num_pending_quits: 0,
+            idle_since_opt: None,
+            state: ActorPoolState::default(),
+        }
+    }
+
+    /// Spawns a worker consuming the messages of the pool mailbox.
+    ///
+    /// `spawn_ctx` is expected to be the spawn context of the pool so that the workers are
+    /// registered as its children.
+    pub(crate) fn spawn_worker(&mut self, spawn_ctx: &SpawnContext, mailbox: Mailbox<A>) {
+        let mut spawn_builder = SpawnBuilder::new(spawn_ctx.clone())
+            .set_mailboxes(mailbox, self.inbox.clone())
+            .set_kill_switch(self.kill_switch.clone());
+        if let Some(backpressure_micros_counter) = &self.backpressure_micros_counter_opt {
+            spawn_builder =
+                spawn_builder.set_backpressure_micros_counter(backpressure_micros_counter.clone());
+        }
+        let (_, worker_handle) = spawn_builder.spawn((*self.actor_factory)());
+        self.worker_handles.push(worker_handle);
+        self.state.num_workers = self.num_workers();
+    }
+
+    /// Returns the number of workers, excluding the ones that are about to quit.
+    fn num_workers(&self) -> usize {
+        self.worker_handles.len() - self.num_pending_quits
+    }
+
+    async fn supervise_workers(&mut self) -> Result<(), ActorExitStatus> {
+        let mut exit_status_opt: Option<ActorExitStatus> = None;
+        let mut running_worker_handles = Vec::with_capacity(self.worker_handles.len());
+
+        for worker_handle in std::mem::take(&mut self.worker_handles) {
+            if !worker_handle.state().is_exit() {
+                if worker_handle.harvest_health() == Health::FailureOrUnhealthy
+                    && !worker_handle.state().is_exit()
+                {
+                    error!(actor_name = %self.actor_name, "actor-pool-worker-frozen");
+                    exit_status_opt = Some(ActorExitStatus::from(anyhow::anyhow!(
+                        "A worker of the pool `{}` is frozen.",
+                        self.actor_name
+                    )));
+                }
+                running_worker_handles.push(worker_handle);
+                continue;
+            }
+            let (worker_exit_status, _last_state) = worker_handle.join().await;
+            match worker_exit_status {
+                ActorExitStatus::Success => {
+                    // The workers only exit successfully once the pool mailbox cannot receive any
+                    // more messages.
+                    self.is_mailbox_closed = true;
+                }
+                ActorExitStatus::Quit => {
+                    self.num_pending_quits = self.num_pending_quits.saturating_sub(1);
+                }
+                ActorExitStatus::DownstreamClosed
+                | ActorExitStatus::Killed
+                | ActorExitStatus::Failure(_)
+                | ActorExitStatus::Panicked(_) => {
+                    exit_status_opt = Some(worker_exit_status);
+                }
+            }
+        }
+        self.worker_handles = running_worker_handles;
+        self.num_pending_quits = self.num_pending_quits.min(self.worker_handles.len());
+        self.state.num_workers = self.num_workers();
+
+        if let Some(exit_status) = exit_status_opt {
+            return Err(exit_status);
+        }
+        if self.is_mailbox_closed && self.worker_handles.is_empty() {
+            return Err(ActorExitStatus::Success);
+        }
+        Ok(())
+    }
+
+    fn scale(&mut self, ctx: &ActorContext<ActorPool<A>>) {
+        if self.is_mailbox_closed {
+            // The remaining workers are winding down.
+            return;
+        }
+        let mailbox = match self.weak_mailbox.upgrade() {
+            Some(mailbox) => mailbox,
+            None => {
+                // No one can send messages to the pool anymore.
+                return;
+            }
+        };
+        let num_workers = self.num_workers();
+        let queue_depth = self.inbox.queue_depth();
+
+        if num_workers < self.pool_config.min_num_workers {
+            for _ in num_workers..self.pool_config.min_num_workers {
+                self.spawn_worker(ctx.spawn_ctx(), mailbox.clone());
+            }
+            self.idle_since_opt = None;
+            return;
+        }
+        if queue_depth >= self.pool_config.scale_up_queue_depth
+            && num_workers < self.pool_config.max_num_workers
+        {
+            info!(
+                actor_name = %self.actor_name,
+                queue_depth = queue_depth,
+                num_workers = num_workers + 1,
+                "actor-pool-scale-up"
+            );
+            self.spawn_worker(ctx.spawn_ctx(), mailbox);
+            self.state.num_scale_ups += 1;
+            self.idle_since_opt = None;
+            return;
+        }
+        let has_idle_worker = self
+            .worker_handles
+            .iter()
+            .any(|worker_handle| worker_handle.state() == ActorState::Idle);
+
+        if queue_depth > 0 || !has_idle_worker || num_workers <= self.pool_config.min_num_workers {
+            self.idle_since_opt = None;
+            return;
+        }
+        let now = Instant::now();
+        let idle_since = *self.idle_since_opt.get_or_insert(now);
+
+        if now.duration_since(idle_since) < self.pool_config.scale_down_idle_period {
+            return;
+        }
+        info!(
+            actor_name = %self.actor_name,
+            num_workers = num_workers - 1,
+            "actor-pool-scale-down"
+        );
+        // Any worker may receive the command. It does not matter since they are interchangeable.
+        if mailbox
+            .send_message_with_control_priority(Command::Quit)
+            .is_ok()
+        {
+            self.num_pending_quits += 1;
+            self.state.num_workers = self.num_workers();
+            self.state.num_scale_downs += 1;
+        }
+        self.idle_since_opt = None;
+    }
+
+    /// Sends one command per worker, and waits for all of them to exit.
+    ///
+    /// Commands sent to the pool mailbox are received by any of the workers, so they cannot be
+    /// addressed to one specific worker the way `ActorHandle::quit` does.
+    async fn stop_workers(&mut self, command: Command) {
+        if let Some(mailbox) = self.weak_mailbox.upgrade() {
+            for _ in 0..self.worker_handles.len() {
+                let _ = mailbox.send_message_with_control_priority(command);
+            }
+        }
+        for worker_handle in self.worker_handles.drain(..) {
+            worker_handle.join().await;
+        }
+        self.num_pending_quits = 0;
+        self.state.num_workers = 0;
+    }
+}
+
+#[async_trait]
+impl<A: Actor> Handler<ScaleLoop> for ActorPool<A> {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _msg: ScaleLoop,
+        ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        self.supervise_workers().await?;
+        self.scale(ctx);
+        ctx.schedule_self_msg(crate::HEARTBEAT, ScaleLoop).await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+
+    use super::{ActorPoolConfig, ActorPoolState};
+    use crate::{Actor, ActorContext, ActorExitStatus, Handler, QueueCapacity, Universe};
+
+    #[derive(Debug)]
+    struct Work(Duration);
+
+    #[derive(Default, Clone)]
+    struct Worker;
+
+    impl Actor for Worker {
+        type ObservableState = ();
+
+        fn observable_state(&self) -> Self::ObservableState {}
+
+        fn queue_capacity(&self) -> QueueCapacity {
+            QueueCapacity::Bounded(10)
+        }
+    }
+
+    #[async_trait]
+    impl Handler<Work> for Worker {
+        type Reply = ();
+
+        async fn handle(
+            &mut self,
+            Work(duration): Work,
+            ctx: &ActorContext<Self>,
+        ) -> Result<(), ActorExitStatus> {
+            ctx.protect_future(ctx.sleep(duration)).await;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_actor_pool_config() {
+        let pool_config = ActorPoolConfig::new(0, 0);
+        assert_eq!(pool_config.min_num_workers, 1);
+        assert_eq!(pool_config.max_num_workers, 1);
+
+        let pool_config = ActorPoolConfig::new(2, 8);
+        assert_eq!(pool_config.min_num_workers, 2);
+        assert_eq!(pool_config.max_num_workers, 8);
+    }
+
+    #[tokio::test]
+    async fn test_actor_pool_scales_up_and_down() {
+        let universe = Universe::with_accelerated_time();
+        let pool_config = ActorPoolConfig {
+            scale_down_idle_period: Duration::from_secs(5),
+            ..ActorPoolConfig::new(1, 3)
+        };
+        let (mailbox, pool_handle) = universe.spawn_builder().spawn_pool(Worker, pool_config);
+        assert_eq!(pool_handle.observe().await.num_workers, 1);
+
+        for _ in 0..10 {
+            mailbox
+                .send_message(Work(Duration::from_secs(2)))
+                .await
+                .unwrap();
+        }
+        universe.sleep(crate::HEARTBEAT * 4).await;
+        assert_eq!(
+            *pool_handle.observe().await,
+            ActorPoolState {
+                num_workers: 3,
+                num_scale_ups: 2,
+                num_scale_downs: 0,
+            }
+        );
+        universe.sleep(Duration::from_secs(60)).await;
+        assert_eq!(
+            *pool_handle.observe().await,
+            ActorPoolState {
+                num_workers: 1,
+                num_scale_ups: 2,
+                num_scale_downs: 2,
+            }
+        );
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_actor_pool_exits_successfully_once_mailbox_dropped() {
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, pool_handle) = universe
+            .spawn_builder()
+            .spawn_pool(Worker, ActorPoolConfig::new(3, 3));
+        mailbox.send_message(Work(Duration::ZERO)).await.unwrap();
+        drop(mailbox);
+        let (exit_status, pool_state) = pool_handle.join().await;
+        assert!(exit_status.is_success());
+        assert_eq!(pool_state.num_workers, 0);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_actor_pool_quits_its_workers() {
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, pool_handle) = universe
+            .spawn_builder()
+            .spawn_pool(Worker, ActorPoolConfig::new(2, 2));
+        mailbox.ask(Work(Duration::from_secs(1))).await.unwrap();
+        let (exit_status, pool_state) = pool_handle.quit().await;
+        assert!(matches!(exit_status, ActorExitStatus::Quit));
+        assert_eq!(pool_state.num_workers, 0);
+        assert!(mailbox.send_message(Work(Duration::ZERO)).await.is_err());
+        universe.assert_quit().await;
+    }
+}
@@ -18,7 +18,7 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::any::{Any, TypeId};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::pin::Pin;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
@@ -32,10 +32,11 @@ use tokio::task::JoinHandle;
 
 use crate::command::Observe;
 use crate::mailbox::WeakMailbox;
-use crate::{Actor, ActorExitStatus, Command, Mailbox};
+use crate::{Actor, ActorExitStatus, Command, Mailbox, PanicReport};
 
 struct TypedJsonObservable<A: Actor> {
     actor_instance_id: String,
+    parent_actor_instance_id_opt: Option<String>,
     weak_mailbox: WeakMailbox<A>,
     join_handle: ActorJoinHandle,
 }
@@ -45,6 +46,8 @@ trait JsonObservable: Sync + Send {
     fn is_disconnected(&self) -> bool;
     fn any(&self) -> &dyn Any;
     fn actor_instance_id(&self) -> &str;
+    fn parent_actor_instance_id_opt(&self) -> Option<&str>;
+    fn panic_report_opt(&self) -> Option<PanicReport>;
     async fn observe(&self) -> Option<JsonValue>;
     async fn quit(&self) -> ActorExitStatus;
     async fn join(&self) -> ActorExitStatus;
@@ -64,16 +67,28 @@ impl<A: Actor> JsonObservable for TypedJsonObservable<A> {
     fn actor_instance_id(&self) -> &str {
         self.actor_instance_id.as_str()
     }
+    fn parent_actor_instance_id_opt(&self) -> Option<&str> {
+        self.parent_actor_instance_id_opt.as_deref()
+    }
+    fn panic_report_opt(&self) -> Option<PanicReport> {
+        match self.join_handle.try_status()? {
+            ActorExitStatus::Panicked(panic_report) => Some(PanicReport::clone(&panic_report)),
+            _ => None,
+        }
+    }
     async fn observe(&self) -> Option<JsonValue> {
         let mailbox = self.weak_mailbox.upgrade()?;
-        let oneshot_rx = mailbox.send_message_with_high_priority(Observe).ok()?;
+        let oneshot_rx = mailbox.send_message_with_control_priority(Observe).ok()?;
         let state: <A as Actor>::ObservableState = oneshot_rx.await.ok()?;
         serde_json::to_value(&state).ok()
     }
 
     async fn quit(&self) -> ActorExitStatus {
+        if let Some(exit_status) = self.join_handle.try_status() {
+            return exit_status;
+        }
         if let Some(mailbox) = self.weak_mailbox.upgrade() {
-            let _ = mailbox.send_message_with_high_priority(Command::Quit);
+            let _ = mailbox.send_message_with_control_priority(Command::Quit);
         }
         self.join().await
     }
@@ -118,10 +133,22 @@ pub struct ActorObservation {
     pub type_name: &'static str,
     pub instance_id: String,
     pub obs: Option<JsonValue>,
+    /// Observations of the actors spawned by this actor, such as the actors of a pipeline, or the
+    /// actor watched by a supervisor.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<ActorObservation>,
+    /// Report of the panic of the actor, if it panicked.
+    #[serde(rename = "panic", skip_serializing_if = "Option::is_none")]
+    pub panic_opt: Option<PanicReport>,
 }
 
 impl ActorRegistry {
-    pub fn register<A: Actor>(&self, mailbox: &Mailbox<A>, join_handle: ActorJoinHandle) {
+    pub fn register<A: Actor>(
+        &self,
+        mailbox: &Mailbox<A>,
+        join_handle: ActorJoinHandle,
+        parent_actor_instance_id_opt: Option<String>,
+    ) {
         let typed_id = TypeId::of::<A>();
         let actor_instance_id = mailbox.actor_instance_id().to_string();
         let weak_mailbox = mailbox.downgrade();
@@ -134,10 +161,13 @@ impl ActorRegistry {
             .push(Arc::new(TypedJsonObservable {
                 weak_mailbox,
                 actor_instance_id,
+                parent_actor_instance_id_opt,
                 join_handle,
             }));
     }
 
+    /// Observes the live actors and returns their observations arranged as a forest. Actors
+    /// whose parent is no longer alive are listed at the root.
     pub async fn observe(&self, timeout: Duration) -> Vec<ActorObservation> {
         self.gc();
         let mut obs_futures = Vec::new();
@@ -149,19 +179,25 @@ impl ActorRegistry {
                 let obs_clone = obs.clone();
                 let type_name = registry_for_type.type_name;
                 let instance_id = obs.actor_instance_id().to_string();
+                let parent_instance_id_opt = obs.parent_actor_instance_id_opt().map(String::from);
+                let panic_opt = obs.panic_report_opt();
                 obs_futures.push(async move {
                     let obs = tokio::time::timeout(timeout, obs_clone.observe())
                         .await
                         .unwrap_or(None);
-                    ActorObservation {
+                    let observation = ActorObservation {
                         type_name,
                         instance_id,
                         obs,
-                    }
+                        children: Vec::new(),
+                        panic_opt,
+                    };
+                    (parent_instance_id_opt, observation)
                 });
             }
         }
-        future::join_all(obs_futures.into_iter()).await
+        let observations = future::join_all(obs_futures.into_iter()).await;
+        build_observation_forest(observations)
     }
 
     pub fn get<A: Actor>(&self) -> Vec<Mailbox<A>> {
@@ -209,6 +245,45 @@ impl ActorRegistry {
     }
 }
 
+fn build_observation_forest(
+    observations: Vec<(Option<String>, ActorObservation)>,
+) -> Vec<ActorObservation> {
+    let instance_ids: HashSet<String> = observations
+        .iter()
+        .map(|(_, observation)| observation.instance_id.clone())
+        .collect();
+    let mut roots = Vec::new();
+    let mut children_per_parent: HashMap<String, Vec<ActorObservation>> = HashMap::new();
+
+    for (parent_instance_id_opt, observation) in observations {
+        match parent_instance_id_opt {
+            Some(parent_instance_id) if instance_ids.contains(&parent_instance_id) => {
+                children_per_parent
+                    .entry(parent_instance_id)
+                    .or_default()
+                    .push(observation);
+            }
+            _ => roots.push(observation),
+        }
+    }
+    attach_children(&mut roots, &mut children_per_parent);
+    roots
+}
+
+fn attach_children(
+    observations: &mut Vec<ActorObservation>,
+    children_per_parent: &mut HashMap<String, Vec<ActorObservation>>,
+) {
+    observations.sort_by(|left, right| left.instance_id.cmp(&right.instance_id));
+
+    for observation in observations.iter_mut() {
+        if let Some(mut children) = children_per_parent.remove(&observation.instance_id) {
+            attach_children(&mut children, children_per_parent);
+            observation.children = children;
+        }
+    }
+}
+
 fn get_iter<A: Actor>(
     actors: &mut HashMap<TypeId, ActorRegistryForSpecificType>,
 ) -> impl Iterator<Item = Mailbox<A>> + '_ {
@@ -226,8 +301,8 @@ fn get_iter<A: Actor>(
         .filter(|mailbox| !mailbox.is_disconnected())
 }
 
-/// This structure contains an optional exit handle. The handle is present
-/// until the join() method is called.
+/// Shareable handle over the task running an actor loop. The exit status of the actor is cached,
+/// so the handle can be joined several times, concurrently or after the actor has exited.
 #[derive(Clone)]
 pub(crate) struct ActorJoinHandle {
     holder: Shared<Pin<Box<dyn Future<Output = ActorExitStatus> + Send>>>,
@@ -243,15 +318,27 @@ impl ActorJoinHandle {
     async fn inner_join(join_handle: JoinHandle<ActorExitStatus>) -> ActorExitStatus {
         join_handle.await.unwrap_or_else(|join_err| {
             if join_err.is_panic() {
-                ActorExitStatus::Panicked
+                let panic_report = PanicReport::from_payload(&*join_err.into_panic());
+                ActorExitStatus::Panicked(Arc::new(panic_report))
             } else {
                 ActorExitStatus::Killed
             }
         })
     }
 
-    /// Joins the actor and returns its exit status on the first invocation.
-    /// Returns None afterwards.
+    /// Returns the exit status of the actor if it has exited, without waiting. The actor does not
+    /// need to have been joined beforehand.
+    pub(crate) fn try_status(&self) -> Option<ActorExitStatus> {
+        if let Some(exit_status) = self.holder.peek() {
+            return Some(exit_status.clone());
+        }
+        // Polls the actor task once, so that an exit status that nobody has awaited yet gets
+        // cached.
+        self.holder.clone().now_or_never()
+    }
+
+    /// Waits for the actor to exit and returns its exit status. Subsequent calls return the
+    /// cached exit status.
     pub(crate) async fn join(&self) -> ActorExitStatus {
         self.holder.clone().await
     }
@@ -259,8 +346,7 @@ impl ActorJoinHandle {
 
 #[cfg(test)]
 mod tests {
-    use std::time::Duration;
-
+    use super::*;
     use crate::tests::PingReceiverActor;
     use crate::Universe;
 
@@ -301,4 +387,50 @@ mod tests {
         assert_eq!(obs.len(), 1);
         universe.assert_quit().await;
     }
+
+    #[tokio::test]
+    async fn test_observe_supervised_actor_as_child_of_supervisor() {
+        let universe = Universe::with_accelerated_time();
+        let (_mailbox, _handle) = universe
+            .spawn_builder::<PingReceiverActor>()
+            .supervise_default();
+        let obs = universe.observe(Duration::from_millis(1000)).await;
+        assert_eq!(obs.len(), 1);
+        assert!(obs[0].type_name.contains("Supervisor"));
+        assert_eq!(obs[0].children.len(), 1);
+        assert!(obs[0].children[0].type_name.ends_with("PingReceiverActor"));
+        assert!(obs[0].children[0].children.is_empty());
+        universe.assert_quit().await;
+    }
+
+    #[test]
+    fn test_build_observation_forest() {
+        let observation = |instance_id: &str| ActorObservation {
+            type_name: "TestActor",
+            instance_id: instance_id.to_string(),
+            obs: None,
+            children: Vec::new(),
+            panic_opt: None,
+        };
+        let observations = vec![
+            (Some("pipeline".to_string()), observation("uploader")),
+            (None, observation("pipeline")),
+            (Some("pipeline".to_string()), observation("indexer")),
+            (Some("indexer".to_string()), observation("doc-processor")),
+            (Some("dead-pipeline".to_string()), observation("orphan")),
+        ];
+        let forest = build_observation_forest(observations);
+        assert_eq!(forest.len(), 2);
+        assert_eq!(forest[0].instance_id, "orphan");
+        assert_eq!(forest[1].instance_id, "pipeline");
+
+        let pipeline_children = &forest[1].children;
+        assert_eq!(pipeline_children.len(), 2);
+        assert_eq!(pipeline_children[0].instance_id, "indexer");
+        assert_eq!(pipeline_children[1].instance_id, "uploader");
+        assert_eq!(
+            pipeline_children[0].children[0].instance_id,
+            "doc-processor"
+        );
+    }
 }
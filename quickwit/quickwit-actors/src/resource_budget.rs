@@ -0,0 +1,239 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
+
+use tokio::sync::Notify;
+
+/// A budget of a shared resource (memory bytes, concurrent uploads, ...) registered in a
+/// [`Universe`](crate::Universe).
+///
+/// Actors acquire a share of the budget in the form of a [`ResourceGuard`]. The share is given
+/// back to the budget when the guard is dropped. Since all the actors of a universe see the same
+/// budgets, this makes it possible to cap the resource usage of several pipelines running on the
+/// same node.
+#[derive(Clone)]
+pub struct ResourceBudget {
+    inner: Arc<InnerResourceBudget>,
+}
+
+struct InnerResourceBudget {
+    name: String,
+    capacity: u64,
+    in_use: Mutex<u64>,
+    released: Notify,
+}
+
+impl fmt::Debug for ResourceBudget {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("ResourceBudget")
+            .field("name", &self.inner.name)
+            .field("capacity", &self.inner.capacity)
+            .field("available", &self.available())
+            .finish()
+    }
+}
+
+impl ResourceBudget {
+    pub fn new(name: impl ToString, capacity: u64) -> Self {
+        let inner = InnerResourceBudget {
+            name: name.to_string(),
+            capacity,
+            in_use: Mutex::new(0),
+            released: Notify::new(),
+        };
+        ResourceBudget {
+            inner: Arc::new(inner),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.inner.capacity
+    }
+
+    /// Returns the amount of the resource that has not been acquired yet.
+    pub fn available(&self) -> u64 {
+        let in_use = *self.inner.in_use.lock().unwrap();
+        self.inner.capacity - in_use
+    }
+
+    /// Acquires `amount` units of the resource, waiting for other guards to be released if the
+    /// budget is exhausted.
+    ///
+    /// Requests exceeding the capacity of the budget are clamped to the capacity, so they wait
+    /// for the whole budget instead of waiting forever.
+    pub async fn acquire(&self, amount: u64) -> ResourceGuard {
+        loop {
+            // The notification future must be created before checking the budget, otherwise a
+            // release happening in between would be missed.
+            let released = self.inner.released.notified();
+            if let Some(resource_guard) = self.try_acquire(amount) {
+                return resource_guard;
+            }
+            released.await;
+        }
+    }
+
+    /// Acquires `amount` units of the resource if they are available right away.
+    pub fn try_acquire(&self, amount: u64) -> Option<ResourceGuard> {
+        let amount = amount.min(self.inner.capacity);
+        let mut in_use = self.inner.in_use.lock().unwrap();
+
+        if *in_use + amount > self.inner.capacity {
+            return None;
+        }
+        *in_use += amount;
+        Some(ResourceGuard {
+            budget: self.clone(),
+            amount,
+        })
+    }
+
+    fn release(&self, amount: u64) {
+        *self.inner.in_use.lock().unwrap() -= amount;
+        self.inner.released.notify_waiters();
+    }
+}
+
+/// A share of a [`ResourceBudget`]. The share is released when the guard is dropped.
+#[must_use]
+pub struct ResourceGuard {
+    budget: ResourceBudget,
+    amount: u64,
+}
+
+impl fmt::Debug for ResourceGuard {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("ResourceGuard")
+            .field("budget", &self.budget.name())
+            .field("amount", &self.amount)
+            .finish()
+    }
+}
+
+impl ResourceGuard {
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+}
+
+impl Drop for ResourceGuard {
+    fn drop(&mut self) {
+        self.budget.release(self.amount);
+    }
+}
+
+/// The resource budgets of a universe, indexed by name.
+#[derive(Clone, Default)]
+pub(crate) struct ResourceBudgets {
+    budgets: Arc<RwLock<HashMap<String, ResourceBudget>>>,
+}
+
+impl ResourceBudgets {
+    /// Returns the budget registered under `name`, registering it with the given capacity if it
+    /// does not exist yet. The capacity of an existing budget is left untouched.
+    pub fn register(&self, name: &str, capacity: u64) -> ResourceBudget {
+        if let Some(budget) = self.get(name) {
+            return budget;
+        }
+        self.budgets
+            .write()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert_with(|| ResourceBudget::new(name, capacity))
+            .clone()
+    }
+
+    pub fn get(&self, name: &str) -> Option<ResourceBudget> {
+        self.budgets.read().unwrap().get(name).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::Universe;
+
+    #[test]
+    fn test_resource_budget_try_acquire() {
+        let budget = ResourceBudget::new("test-budget", 10);
+        assert_eq!(budget.available(), 10);
+
+        let guard_6 = budget.try_acquire(6).unwrap();
+        assert_eq!(guard_6.amount(), 6);
+        assert_eq!(budget.available(), 4);
+
+        assert!(budget.try_acquire(5).is_none());
+
+        let guard_4 = budget.try_acquire(4).unwrap();
+        assert_eq!(budget.available(), 0);
+
+        drop(guard_6);
+        assert_eq!(budget.available(), 6);
+
+        drop(guard_4);
+        assert_eq!(budget.available(), 10);
+
+        let guard_capacity = budget.try_acquire(100).unwrap();
+        assert_eq!(guard_capacity.amount(), 10);
+    }
+
+    #[tokio::test]
+    async fn test_resource_budget_acquire_waits_for_release() {
+        let budget = ResourceBudget::new("test-budget", 10);
+        let guard = budget.acquire(8).await;
+
+        let budget_clone = budget.clone();
+        let acquire_join_handle = tokio::spawn(async move { budget_clone.acquire(5).await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!acquire_join_handle.is_finished());
+
+        drop(guard);
+        let guard = acquire_join_handle.await.unwrap();
+        assert_eq!(guard.amount(), 5);
+        assert_eq!(budget.available(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_universe_resource_budgets_are_shared() {
+        let universe = Universe::with_accelerated_time();
+        assert!(universe.resource_budget("memory").is_none());
+
+        let budget = universe.register_resource_budget("memory", 100);
+        assert_eq!(budget.capacity(), 100);
+
+        let child_ctx = universe.spawn_ctx().child_context();
+        let same_budget = child_ctx.register_resource_budget("memory", 1_000);
+        assert_eq!(same_budget.capacity(), 100);
+
+        let _guard = same_budget.try_acquire(30).unwrap();
+        assert_eq!(universe.resource_budget("memory").unwrap().available(), 70);
+        universe.assert_quit().await;
+    }
+}
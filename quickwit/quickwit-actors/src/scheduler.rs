@@ -22,7 +22,7 @@ use std::collections::binary_heap::PeekMut;
 use std::collections::BinaryHeap;
 use std::future::Future;
 use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
-use std::sync::{Arc, Weak};
+use std::sync::{Arc, Mutex, Weak};
 use std::time::{Duration, Instant};
 
 use tokio::sync::oneshot;
@@ -32,6 +32,9 @@ type Callback = Box<dyn FnOnce() + Sync + Send + 'static>;
 
 struct TimeoutEvent {
     deadline: Instant,
+    // Breaks ties between events with the same deadline. It is drawn from the seeded random
+    // generator in deterministic mode, and is equal to `event_id` otherwise.
+    tie_breaker: u64,
     event_id: u64, //< only useful to break ties in a deterministic way.
     callback: Callback,
 }
@@ -54,6 +57,7 @@ impl Ord for TimeoutEvent {
     fn cmp(&self, other: &Self) -> std::cmp::Ordering {
         self.deadline
             .cmp(&other.deadline)
+            .then_with(|| self.tie_breaker.cmp(&other.tie_breaker))
             .then_with(|| self.event_id.cmp(&other.event_id))
     }
 }
@@ -75,14 +79,61 @@ struct SchedulerClientInner {
     no_advance_time_guard_count: AtomicUsize,
     accelerate_time: AtomicBool,
     tx: flume::Sender<SchedulerMessage>,
+    /// Seed of the scheduler, if it runs in deterministic mode.
+    seed_opt: Option<u64>,
+    /// Random generator used to perturb the interleaving of the actors in deterministic mode.
+    rng_opt: Option<Mutex<DeterministicRng>>,
 }
 
+/// Small and fast pseudo-random generator (SplitMix64), whose sequence is entirely defined by its
+/// seed.
+struct DeterministicRng {
+    state: u64,
+}
+
+impl DeterministicRng {
+    fn new(seed: u64) -> Self {
+        DeterministicRng { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Maximum number of times an actor yields before processing a message in deterministic mode.
+const MAX_NUM_YIELDS_BEFORE_MESSAGE: u64 = 3;
+
 impl SchedulerClient {
     /// Returns true if someone asked for the time to be accelerated.
     fn time_is_accelerated(&self) -> bool {
         self.inner.accelerate_time.load(Ordering::Relaxed)
     }
 
+    /// Returns the seed of the scheduler if it runs in deterministic mode.
+    pub fn seed(&self) -> Option<u64> {
+        self.inner.seed_opt
+    }
+
+    /// Returns the number of times an actor should yield before processing its next message.
+    ///
+    /// In deterministic mode, it is drawn from the seeded random generator in order to explore
+    /// different interleavings of the actors, which can then be replayed with the same seed.
+    /// Otherwise, it is always 0.
+    pub(crate) fn num_yields_before_next_message(&self) -> usize {
+        match &self.inner.rng_opt {
+            Some(rng) => {
+                let random = rng.lock().unwrap().next_u64();
+                (random % (MAX_NUM_YIELDS_BEFORE_MESSAGE + 1)) as usize
+            }
+            None => 0,
+        }
+    }
+
     /// Returns true if something is preventing for accelerating the time.
     fn is_advance_time_forbidden(&self) -> bool {
         self.inner
@@ -194,15 +245,39 @@ impl Drop for NoAdvanceTimeGuard {
 }
 
 pub fn start_scheduler() -> SchedulerClient {
+    start_scheduler_with_seed_opt(None)
+}
+
+/// Starts a scheduler in deterministic mode, for tests.
+///
+/// In deterministic mode:
+/// - time is accelerated and only advances by jumping from one scheduled event to the next one,
+///   regardless of the wall clock, so that the deadlines of the events do not depend on how fast
+///   the actors run;
+/// - the events with the same deadline are triggered in an order drawn from `seed`;
+/// - actors yield a number of times drawn from `seed` before processing each message.
+///
+/// As a result, the interleavings of the actors running on a single-threaded runtime only depend
+/// on the seed: a failure observed with a given seed can be replayed by running the test again
+/// with the same seed.
+pub fn start_deterministic_scheduler(seed: u64) -> SchedulerClient {
+    let scheduler_client = start_scheduler_with_seed_opt(Some(seed));
+    scheduler_client.accelerate_time();
+    scheduler_client
+}
+
+fn start_scheduler_with_seed_opt(seed_opt: Option<u64>) -> SchedulerClient {
     let (tx, rx) = flume::unbounded::<SchedulerMessage>();
     let scheduler_client = SchedulerClient {
         inner: Arc::new(SchedulerClientInner {
             no_advance_time_guard_count: AtomicUsize::default(),
             accelerate_time: Default::default(),
             tx,
+            seed_opt,
+            rng_opt: seed_opt.map(|seed| Mutex::new(DeterministicRng::new(seed))),
         }),
     };
-    let mut scheduler = Scheduler::new(&scheduler_client);
+    let mut scheduler = Scheduler::new(&scheduler_client, seed_opt);
     tokio::spawn(async move {
         while let Ok(scheduler_message) = rx.recv_async().await {
             match scheduler_message {
@@ -224,6 +299,10 @@ struct Scheduler {
     // Instant::now() + simulated_time_shift`. By default `simulated_time_shift` is set to 0
     // but it can be shifted when the scheduler has to process a simulate sleep event`.
     simulated_time_shift: Duration,
+    // In deterministic mode, the simulated time does not follow the wall clock: it is defined as
+    // `simulated_time = virtual_clock_start + simulated_time_shift`.
+    virtual_clock_start_opt: Option<Instant>,
+    tie_breaker_rng_opt: Option<DeterministicRng>,
     future_events: BinaryHeap<Reverse<TimeoutEvent>>,
     next_timeout: Option<JoinHandle<()>>,
     weak_scheduler_client: Weak<SchedulerClientInner>,
@@ -271,9 +350,18 @@ impl Scheduler {
 
     /// Schedules a Timeout event callback if necessary.
     fn schedule_next_timeout(&mut self) {
-        let Some(scheduler_client) = self.scheduler_client() else { return };
+        let Some(scheduler_client) = self.scheduler_client() else {
+            return;
+        };
         let simulated_now = self.simulated_now();
-        let Some(next_deadline) = self.next_event_deadline() else { return; };
+        let Some(next_deadline) = self.next_event_deadline() else {
+            return;
+        };
+        if self.virtual_clock_start_opt.is_some() && next_deadline > simulated_now {
+            // The virtual clock does not move by itself: time will jump to the next deadline
+            // once nothing prevents it from advancing.
+            return;
+        }
         let timeout: Duration = if next_deadline <= simulated_now {
             // This should almost never happen, because we supposedly triggered
             // all pending events.
@@ -302,10 +390,13 @@ impl Scheduler {
 }
 
 impl Scheduler {
-    pub fn new(scheduler_client: &SchedulerClient) -> Self {
+    pub fn new(scheduler_client: &SchedulerClient, seed_opt: Option<u64>) -> Self {
         Scheduler {
             event_id_generator: 0u64,
             simulated_time_shift: Duration::default(),
+            virtual_clock_start_opt: seed_opt.map(|_| Instant::now()),
+            // We use a different stream than the one of the scheduler client.
+            tie_breaker_rng_opt: seed_opt.map(|seed| DeterministicRng::new(!seed)),
             future_events: Default::default(),
             next_timeout: None,
             weak_scheduler_client: Arc::downgrade(&scheduler_client.inner),
@@ -320,14 +411,18 @@ impl Scheduler {
     /// - no message is queued for processing, no initialize or no finalize
     /// is being processed.
     fn advance_time_if_necessary(&mut self) {
-        let Some(scheduler_client) = self.scheduler_client() else { return; };
+        let Some(scheduler_client) = self.scheduler_client() else {
+            return;
+        };
         if !scheduler_client.time_is_accelerated() {
             return;
         }
         if scheduler_client.is_advance_time_forbidden() {
             return;
         }
-        let Some(advance_to_instant) = self.next_event_deadline() else { return; };
+        let Some(advance_to_instant) = self.next_event_deadline() else {
+            return;
+        };
         let now = self.simulated_now();
         if let Some(time_shift) = advance_to_instant.checked_duration_since(now) {
             self.simulated_time_shift += time_shift;
@@ -339,14 +434,20 @@ impl Scheduler {
     }
 
     fn simulated_now(&self) -> Instant {
-        Instant::now() + self.simulated_time_shift
+        let clock = self.virtual_clock_start_opt.unwrap_or_else(Instant::now);
+        clock + self.simulated_time_shift
     }
 
     fn timeout_event(&mut self, deadline: Instant, callback: Callback) -> TimeoutEvent {
         let event_id = self.event_id_generator;
         self.event_id_generator += 1;
+        let tie_breaker = match &mut self.tie_breaker_rng_opt {
+            Some(tie_breaker_rng) => tie_breaker_rng.next_u64(),
+            None => event_id,
+        };
         TimeoutEvent {
             deadline,
+            tie_breaker,
             event_id,
             callback,
         }
@@ -356,12 +457,12 @@ impl Scheduler {
 #[cfg(test)]
 mod tests {
     use std::sync::atomic::{AtomicUsize, Ordering};
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
     use std::time::{Duration, Instant};
 
     use async_trait::async_trait;
 
-    use crate::{Actor, ActorContext, ActorExitStatus, Handler, Universe};
+    use crate::{Actor, ActorContext, ActorExitStatus, Handler, ScheduledMessageHandle, Universe};
 
     struct ClockActor {
         count: Arc<AtomicUsize>,
@@ -428,4 +529,135 @@ mod tests {
         assert!(elapsed.as_millis() < 50);
         universe.assert_quit().await;
     }
+
+    struct StoppableClockActor {
+        count: Arc<AtomicUsize>,
+        next_tick_opt: Option<ScheduledMessageHandle>,
+    }
+
+    #[derive(Debug)]
+    struct StopTicking;
+
+    #[async_trait]
+    impl Actor for StoppableClockActor {
+        type ObservableState = ();
+        fn observable_state(&self) -> Self::ObservableState {}
+
+        async fn initialize(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+            self.handle(Tick, ctx).await
+        }
+    }
+
+    #[async_trait]
+    impl Handler<Tick> for StoppableClockActor {
+        type Reply = ();
+
+        async fn handle(
+            &mut self,
+            _tick: Tick,
+            ctx: &ActorContext<Self>,
+        ) -> Result<(), ActorExitStatus> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            let next_tick = ctx.schedule_self_msg(Duration::from_secs(1), Tick).await;
+            self.next_tick_opt = Some(next_tick);
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl Handler<StopTicking> for StoppableClockActor {
+        type Reply = ();
+
+        async fn handle(
+            &mut self,
+            _stop_ticking: StopTicking,
+            _ctx: &ActorContext<Self>,
+        ) -> Result<(), ActorExitStatus> {
+            if let Some(next_tick) = self.next_tick_opt.take() {
+                next_tick.cancel();
+                assert!(next_tick.is_cancelled());
+            }
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_cancel_scheduled_message() {
+        let count: Arc<AtomicUsize> = Default::default();
+        let clock_actor = StoppableClockActor {
+            count: count.clone(),
+            next_tick_opt: None,
+        };
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, _handle) = universe.spawn_builder().spawn(clock_actor);
+        universe.sleep(Duration::from_millis(2_500)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+
+        mailbox.ask(StopTicking).await.unwrap();
+        universe.sleep(Duration::from_secs(10)).await;
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+        universe.assert_quit().await;
+    }
+
+    struct RecordingActor {
+        id: usize,
+        records: Arc<Mutex<Vec<usize>>>,
+    }
+
+    #[derive(Debug)]
+    struct Record;
+
+    #[async_trait]
+    impl Actor for RecordingActor {
+        type ObservableState = ();
+
+        fn observable_state(&self) -> Self::ObservableState {}
+
+        async fn initialize(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+            ctx.schedule_self_msg(Duration::from_secs(1), Record).await;
+            Ok(())
+        }
+    }
+
+    #[async_trait]
+    impl Handler<Record> for RecordingActor {
+        type Reply = ();
+
+        async fn handle(
+            &mut self,
+            _record: Record,
+            ctx: &ActorContext<Self>,
+        ) -> Result<(), ActorExitStatus> {
+            self.records.lock().unwrap().push(self.id);
+            ctx.schedule_self_msg(Duration::from_secs(1), Record).await;
+            Ok(())
+        }
+    }
+
+    /// Returns the order in which the actors handled their messages.
+    async fn run_recording_actors(seed: u64) -> Vec<usize> {
+        let universe = Universe::with_seed(seed);
+        assert_eq!(universe.seed(), Some(seed));
+        let records: Arc<Mutex<Vec<usize>>> = Default::default();
+
+        for id in 0..4 {
+            let recording_actor = RecordingActor {
+                id,
+                records: records.clone(),
+            };
+            universe.spawn_builder().spawn(recording_actor);
+        }
+        universe.sleep(Duration::from_millis(10_500)).await;
+        universe.assert_quit().await;
+        let records = records.lock().unwrap();
+        records.clone()
+    }
+
+    #[tokio::test]
+    async fn test_deterministic_scheduler_replays_interleavings() {
+        let records = run_recording_actors(42).await;
+        assert_eq!(records.len(), 40);
+        assert_eq!(run_recording_actors(42).await, records);
+        assert_ne!(run_recording_actors(43).await, records);
+    }
 }
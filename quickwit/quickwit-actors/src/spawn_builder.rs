@@ -17,18 +17,27 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+
 use anyhow::Context;
+use futures::FutureExt;
 use quickwit_common::metrics::IntCounter;
 use tokio::sync::watch;
 use tracing::{debug, error, info};
 
 use crate::envelope::Envelope;
 use crate::mailbox::{create_mailbox, Inbox};
-use crate::registry::{ActorJoinHandle, ActorRegistry};
+use crate::panic_report::install_panic_hook;
+use crate::pool::{ActorPool, ActorPoolConfig};
+use crate::registry::{ActorJoinHandle, ActorObservation, ActorRegistry};
+use crate::resource_budget::ResourceBudgets;
 use crate::scheduler::{NoAdvanceTimeGuard, SchedulerClient};
-use crate::supervisor::Supervisor;
+use crate::supervisor::{RestartPolicy, Supervisor};
 use crate::{
-    Actor, ActorContext, ActorExitStatus, ActorHandle, KillSwitch, Mailbox, QueueCapacity,
+    Actor, ActorContext, ActorExitStatus, ActorHandle, KillSwitch, Mailbox, PanicReport,
+    QueueCapacity, ResourceBudget,
 };
 
 #[derive(Clone)]
@@ -36,6 +45,10 @@ pub struct SpawnContext {
     pub(crate) scheduler_client: SchedulerClient,
     pub(crate) kill_switch: KillSwitch,
     pub(crate) registry: ActorRegistry,
+    /// Instance ID of the actor spawning the actors, if any. It is used to arrange the actor
+    /// observations in a hierarchy.
+    pub(crate) parent_actor_instance_id_opt: Option<String>,
+    pub(crate) resource_budgets: ResourceBudgets,
 }
 
 impl SpawnContext {
@@ -44,6 +57,8 @@ impl SpawnContext {
             scheduler_client,
             kill_switch: Default::default(),
             registry: ActorRegistry::default(),
+            parent_actor_instance_id_opt: None,
+            resource_budgets: ResourceBudgets::default(),
         }
     }
 
@@ -68,8 +83,28 @@ impl SpawnContext {
             scheduler_client: self.scheduler_client.clone(),
             kill_switch: self.kill_switch.child(),
             registry: self.registry.clone(),
+            parent_actor_instance_id_opt: self.parent_actor_instance_id_opt.clone(),
+            resource_budgets: self.resource_budgets.clone(),
         }
     }
+
+    /// Registers a resource budget shared by all the actors of the universe and returns it. If a
+    /// budget with the same name is already registered, it is returned as is and `capacity` is
+    /// ignored.
+    pub fn register_resource_budget(&self, name: &str, capacity: u64) -> ResourceBudget {
+        self.resource_budgets.register(name, capacity)
+    }
+
+    /// Returns the resource budget registered under `name`, if any.
+    pub fn resource_budget(&self, name: &str) -> Option<ResourceBudget> {
+        self.resource_budgets.get(name)
+    }
+
+    /// Observes all the actors of the spawn context's universe and returns their observations
+    /// arranged as a forest: the actors spawned by another actor are listed as its children.
+    pub async fn observe(&self, timeout: Duration) -> Vec<ActorObservation> {
+        self.registry.observe(timeout).await
+    }
 }
 
 /// `SpawnBuilder` makes it possible to configure misc parameters before spawning an actor.
@@ -79,6 +114,7 @@ pub struct SpawnBuilder<A: Actor> {
     #[allow(clippy::type_complexity)]
     mailboxes: Option<(Mailbox<A>, Inbox<A>)>,
     backpressure_micros_counter_opt: Option<IntCounter>,
+    restart_policy: RestartPolicy,
 }
 
 impl<A: Actor> SpawnBuilder<A> {
@@ -87,6 +123,7 @@ impl<A: Actor> SpawnBuilder<A> {
             spawn_ctx,
             mailboxes: None,
             backpressure_micros_counter_opt: None,
+            restart_policy: RestartPolicy::default(),
         }
     }
 
@@ -124,6 +161,15 @@ impl<A: Actor> SpawnBuilder<A> {
         self
     }
 
+    /// Sets the policy applied by the supervisor when the actor fails.
+    ///
+    /// Only relevant for supervised actors. By default, the actor is restarted immediately and
+    /// indefinitely.
+    pub fn set_restart_policy(mut self, restart_policy: RestartPolicy) -> Self {
+        self.restart_policy = restart_policy;
+        self
+    }
+
     fn take_or_create_mailboxes(&mut self, actor: &A) -> (Mailbox<A>, Inbox<A>) {
         if let Some((mailbox, inbox)) = self.mailboxes.take() {
             return (mailbox, inbox);
@@ -144,9 +190,13 @@ impl<A: Actor> SpawnBuilder<A> {
         let (mailbox, inbox) = self.take_or_create_mailboxes(actor);
         let obs_state = actor.observable_state();
         let (state_tx, state_rx) = watch::channel(obs_state);
+        // The actors spawned by this actor are its children.
+        let mut actor_spawn_ctx = self.spawn_ctx.clone();
+        actor_spawn_ctx.parent_actor_instance_id_opt =
+            Some(mailbox.actor_instance_id().to_string());
         let ctx = ActorContext::new(
             mailbox,
-            self.spawn_ctx.clone(),
+            actor_spawn_ctx,
             state_tx,
             self.backpressure_micros_counter_opt,
         );
@@ -158,14 +208,20 @@ impl<A: Actor> SpawnBuilder<A> {
         // We prevent fast forward of the scheduler during  initialization.
         let no_advance_time_guard = self.spawn_ctx.scheduler_client.no_advance_time_guard();
         let runtime_handle = actor.runtime_handle();
+        let parent_actor_instance_id_opt = self.spawn_ctx.parent_actor_instance_id_opt.clone();
         let (ctx, inbox, state_rx) = self.create_actor_context_and_inbox(&actor);
         debug!(actor_id = %ctx.actor_instance_id(), "spawn-actor");
         let mailbox = ctx.mailbox().clone();
+        // Unregistered when the `ActorExecutionEnv` is dropped.
+        mailbox.register_actor();
+        install_panic_hook();
         let ctx_clone = ctx.clone();
         let loop_async_actor_future =
             async move { actor_loop(actor, inbox, no_advance_time_guard, ctx).await };
         let join_handle = ActorJoinHandle::new(runtime_handle.spawn(loop_async_actor_future));
-        ctx_clone.registry().register(&mailbox, join_handle.clone());
+        ctx_clone
+            .registry()
+            .register(&mailbox, join_handle.clone(), parent_actor_instance_id_opt);
         let actor_handle = ActorHandle::new(state_rx, join_handle, ctx_clone);
         (mailbox, actor_handle)
     }
@@ -178,20 +234,84 @@ impl<A: Actor> SpawnBuilder<A> {
         let actor_name = actor.name();
         let (mailbox, inbox) = self.take_or_create_mailboxes(&actor);
         self.mailboxes = Some((mailbox, inbox.clone()));
-        let child_ctx = self.spawn_ctx.child_context();
+        // The supervisor mailboxes are created upfront so that the supervised actor is registered
+        // as a child of its supervisor.
+        let (supervisor_mailbox, supervisor_inbox) =
+            self.spawn_ctx.create_mailbox::<Supervisor<A>>(
+                format!("Supervisor({actor_name})"),
+                QueueCapacity::Unbounded,
+            );
+        let mut child_ctx = self.spawn_ctx.child_context();
+        child_ctx.parent_actor_instance_id_opt =
+            Some(supervisor_mailbox.actor_instance_id().to_string());
         let parent_spawn_ctx = std::mem::replace(&mut self.spawn_ctx, child_ctx);
+        let restart_policy = self.restart_policy;
         let (mailbox, actor_handle) = self.spawn(actor);
-        let supervisor = Supervisor::new(actor_name, Box::new(actor_factory), inbox, actor_handle);
-        let (_supervisor_mailbox, supervisor_handle) =
-            parent_spawn_ctx.spawn_builder().spawn(supervisor);
+        let supervisor = Supervisor::new(
+            actor_name,
+            Box::new(actor_factory),
+            inbox,
+            actor_handle,
+            restart_policy,
+        );
+        let (_supervisor_mailbox, supervisor_handle) = parent_spawn_ctx
+            .spawn_builder()
+            .set_mailboxes(supervisor_mailbox, supervisor_inbox)
+            .spawn(supervisor);
         (mailbox, supervisor_handle)
     }
+
+    /// Spawns a pool of actors created by `actor_factory` behind a single mailbox. The number of
+    /// workers is scaled according to `pool_config`.
+    pub fn spawn_pool_fn<F: Fn() -> A + Send + Sync + 'static>(
+        mut self,
+        actor_factory: F,
+        pool_config: ActorPoolConfig,
+    ) -> (Mailbox<A>, ActorHandle<ActorPool<A>>) {
+        let actor = actor_factory();
+        let actor_name = actor.name();
+        let (mailbox, inbox) = self.take_or_create_mailboxes(&actor);
+        drop(actor);
+        // Like for supervisors, the pool mailboxes are created upfront so that the workers are
+        // registered as children of their pool.
+        let (pool_mailbox, pool_inbox) = self.spawn_ctx.create_mailbox::<ActorPool<A>>(
+            format!("ActorPool({actor_name})"),
+            QueueCapacity::Unbounded,
+        );
+        let mut workers_spawn_ctx = self.spawn_ctx.child_context();
+        workers_spawn_ctx.parent_actor_instance_id_opt =
+            Some(pool_mailbox.actor_instance_id().to_string());
+        let mut pool = ActorPool::new(
+            actor_name,
+            Box::new(actor_factory),
+            pool_config,
+            mailbox.downgrade(),
+            inbox,
+            self.spawn_ctx.kill_switch.clone(),
+            self.backpressure_micros_counter_opt.take(),
+        );
+        for _ in 0..pool_config.min_num_workers.max(1) {
+            pool.spawn_worker(&workers_spawn_ctx, mailbox.clone());
+        }
+        let (_pool_mailbox, pool_handle) = SpawnBuilder::new(self.spawn_ctx)
+            .set_mailboxes(pool_mailbox, pool_inbox)
+            .spawn(pool);
+        (mailbox, pool_handle)
+    }
 }
 
 impl<A: Actor + Clone> SpawnBuilder<A> {
     pub fn supervise(self, actor: A) -> (Mailbox<A>, ActorHandle<Supervisor<A>>) {
         self.supervise_fn(move || actor.clone())
     }
+
+    pub fn spawn_pool(
+        self,
+        actor: A,
+        pool_config: ActorPoolConfig,
+    ) -> (Mailbox<A>, ActorHandle<ActorPool<A>>) {
+        self.spawn_pool_fn(move || actor.clone(), pool_config)
+    }
 }
 
 impl<A: Actor + Default> SpawnBuilder<A> {
@@ -246,7 +366,18 @@ impl<A: Actor> ActorExecutionEnv<A> {
         &mut self,
         mut envelope: Envelope<A>,
     ) -> Result<(), ActorExitStatus> {
+        // In deterministic mode, this perturbs the interleaving of the actors in a reproducible
+        // way.
+        let num_yields = self
+            .ctx
+            .spawn_ctx()
+            .scheduler_client
+            .num_yields_before_next_message();
+        for _ in 0..num_yields {
+            self.ctx.yield_now().await;
+        }
         self.yield_and_check_if_killed().await?;
+        let _handling_duration_timer = self.inbox.metrics().handling_duration.start_timer();
         envelope.handle_message(&mut self.actor, &self.ctx).await?;
         Ok(())
     }
@@ -305,7 +436,8 @@ impl<A: Actor> ActorExecutionEnv<A> {
             .with_context(|| format!("Finalization of actor {}", self.actor.name()))
         {
             error!(error=?finalize_error, "Finalizing failed, set exit status to panicked.");
-            return ActorExitStatus::Panicked;
+            let panic_report = PanicReport::new(format!("{finalize_error:#}"));
+            return ActorExitStatus::Panicked(Arc::new(panic_report));
         }
         exit_status
     }
@@ -319,8 +451,8 @@ impl<A: Actor> ActorExecutionEnv<A> {
             ActorExitStatus::Failure(err) => {
                 error!(cause=?err, exit_status=?exit_status, "actor-failure");
             }
-            ActorExitStatus::Panicked => {
-                error!(exit_status=?exit_status, "actor-failure");
+            ActorExitStatus::Panicked(panic_report) => {
+                error!(panic=%panic_report, exit_status=?exit_status, "actor-failure");
             }
         }
         info!(actor_id = %self.ctx.actor_instance_id(), exit_status = %exit_status, "actor-exit");
@@ -333,6 +465,7 @@ impl<A: Actor> Drop for ActorExecutionEnv<A> {
     // even in case of a panic.
     fn drop(&mut self) {
         self.ctx.observe(&mut self.actor);
+        self.ctx.mailbox().unregister_actor();
     }
 }
 
@@ -341,6 +474,36 @@ async fn actor_loop<A: Actor>(
     inbox: Inbox<A>,
     no_advance_time_guard: NoAdvanceTimeGuard,
     ctx: ActorContext<A>,
+) -> ActorExitStatus {
+    let ctx_clone = ctx.clone();
+    // The execution environment is dropped while unwinding, which collects the last observation
+    // of the actor.
+    let actor_loop_res =
+        AssertUnwindSafe(actor_loop_inner(actor, inbox, no_advance_time_guard, ctx))
+            .catch_unwind()
+            .await;
+    match actor_loop_res {
+        Ok(exit_status) => exit_status,
+        Err(panic_payload) => {
+            let panic_report = PanicReport::from_caught_panic(&*panic_payload);
+            error!(
+                actor_id = %ctx_clone.actor_instance_id(),
+                panic = %panic_report,
+                backtrace = panic_report.backtrace_opt.as_deref().unwrap_or_default(),
+                "actor-panic"
+            );
+            let exit_status = ActorExitStatus::Panicked(Arc::new(panic_report));
+            ctx_clone.exit(&exit_status);
+            exit_status
+        }
+    }
+}
+
+async fn actor_loop_inner<A: Actor>(
+    actor: A,
+    inbox: Inbox<A>,
+    no_advance_time_guard: NoAdvanceTimeGuard,
+    ctx: ActorContext<A>,
 ) -> ActorExitStatus {
     let mut actor_env = ActorExecutionEnv { actor, inbox, ctx };
 
@@ -17,20 +17,82 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
 use async_trait::async_trait;
 use serde::Serialize;
-use tracing::{info, warn};
+use tracing::{error, info, warn};
 
 use crate::mailbox::Inbox;
 use crate::{
-    Actor, ActorContext, ActorExitStatus, ActorHandle, ActorState, Handler, Health, Supervisable,
+    Actor, ActorContext, ActorExitStatus, ActorHandle, ActorState, Handler, Health, Mailbox,
+    PanicReport, Supervisable,
 };
 
-#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize)]
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize)]
 pub struct SupervisorState {
     pub num_panics: usize,
     pub num_errors: usize,
     pub num_kills: usize,
+    /// Report of the last panic of the supervised actor.
+    #[serde(rename = "last_panic", skip_serializing_if = "Option::is_none")]
+    pub last_panic_opt: Option<PanicReport>,
+}
+
+/// Defines how a [`Supervisor`] restarts its actor after a failure.
+///
+/// The default policy restarts the actor immediately and indefinitely.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct RestartPolicy {
+    /// Delay before restarting the actor after its first failure in the restart window.
+    pub initial_backoff: Duration,
+    /// Upper bound of the delay before restarting the actor. The delay doubles with each restart
+    /// in the restart window.
+    pub max_backoff: Duration,
+    /// Maximum number of restarts in the restart window. Once exceeded, the supervisor stops
+    /// restarting the actor and exits with a failure, escalating it to its parent.
+    pub max_restarts_opt: Option<usize>,
+    /// Sliding window over which restarts are counted.
+    pub restart_window: Duration,
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy {
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+            max_restarts_opt: None,
+            restart_window: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+impl RestartPolicy {
+    /// Restarts the actor after a delay starting at `initial_backoff` and doubling with each
+    /// restart in the restart window, up to `max_backoff`.
+    pub fn exponential_backoff(initial_backoff: Duration, max_backoff: Duration) -> Self {
+        RestartPolicy {
+            initial_backoff,
+            max_backoff: max_backoff.max(initial_backoff),
+            ..Default::default()
+        }
+    }
+
+    /// Gives up after `max_restarts` restarts within `restart_window`.
+    pub fn with_max_restarts(mut self, max_restarts: usize, restart_window: Duration) -> Self {
+        self.max_restarts_opt = Some(max_restarts);
+        self.restart_window = restart_window;
+        self
+    }
+
+    /// Returns the delay before the `restart_ord`-th restart of the window, starting at 0.
+    fn backoff(&self, restart_ord: usize) -> Duration {
+        let multiplier = 1u32.checked_shl(restart_ord as u32).unwrap_or(u32::MAX);
+        self.initial_backoff
+            .saturating_mul(multiplier)
+            .min(self.max_backoff)
+    }
 }
 
 pub struct Supervisor<A: Actor> {
@@ -39,17 +101,24 @@ pub struct Supervisor<A: Actor> {
     inbox: Inbox<A>,
     handle_opt: Option<ActorHandle<A>>,
     state: SupervisorState,
+    restart_policy: RestartPolicy,
+    restart_instants: VecDeque<Instant>,
+    /// Mailbox of the actor while its restart is delayed.
+    pending_restart_mailbox_opt: Option<Mailbox<A>>,
 }
 
 #[derive(Debug, Copy, Clone)]
 struct SuperviseLoop;
 
+#[derive(Debug, Copy, Clone)]
+struct RestartActor;
+
 #[async_trait]
 impl<A: Actor> Actor for Supervisor<A> {
     type ObservableState = SupervisorState;
 
     fn observable_state(&self) -> Self::ObservableState {
-        self.state
+        self.state.clone()
     }
 
     fn name(&self) -> String {
@@ -84,7 +153,7 @@ impl<A: Actor> Actor for Supervisor<A> {
             ActorExitStatus::Failure(_)
             | ActorExitStatus::Success
             | ActorExitStatus::DownstreamClosed => {}
-            ActorExitStatus::Panicked => {}
+            ActorExitStatus::Panicked(_) => {}
         }
 
         Ok(())
@@ -97,6 +166,7 @@ impl<A: Actor> Supervisor<A> {
         actor_factory: Box<dyn Fn() -> A + Sync + Send>,
         inbox: Inbox<A>,
         handle: ActorHandle<A>,
+        restart_policy: RestartPolicy,
     ) -> Self {
         let state = Default::default();
         Supervisor {
@@ -105,6 +175,9 @@ impl<A: Actor> Supervisor<A> {
             inbox,
             handle_opt: Some(handle),
             state,
+            restart_policy,
+            restart_instants: VecDeque::new(),
+            pending_restart_mailbox_opt: None,
         }
     }
 
@@ -112,12 +185,14 @@ impl<A: Actor> Supervisor<A> {
         &mut self,
         ctx: &ActorContext<Supervisor<A>>,
     ) -> Result<(), ActorExitStatus> {
-        match self
-            .handle_opt
-            .as_ref()
-            .expect("The actor handle should always be set.")
-            .harvest_health()
-        {
+        let actor_handle = match self.handle_opt.as_ref() {
+            Some(actor_handle) => actor_handle,
+            None => {
+                // The actor is waiting to be restarted.
+                return Ok(());
+            }
+        };
+        match actor_handle.harvest_health() {
             Health::Healthy => {
                 return Ok(());
             }
@@ -154,10 +229,53 @@ impl<A: Actor> Supervisor<A> {
             ActorExitStatus::Failure(_err) => {
                 self.state.num_errors += 1;
             }
-            ActorExitStatus::Panicked => {
+            ActorExitStatus::Panicked(panic_report) => {
                 self.state.num_panics += 1;
+                self.state.last_panic_opt = Some(PanicReport::clone(&panic_report));
             }
         }
+        let now = Instant::now();
+
+        while let Some(restart_instant) = self.restart_instants.front() {
+            if now.duration_since(*restart_instant) < self.restart_policy.restart_window {
+                break;
+            }
+            self.restart_instants.pop_front();
+        }
+        let num_recent_restarts = self.restart_instants.len();
+
+        if let Some(max_restarts) = self.restart_policy.max_restarts_opt {
+            if num_recent_restarts >= max_restarts {
+                error!(
+                    actor_name = %self.actor_name,
+                    max_restarts = max_restarts,
+                    "Actor exceeded its maximum number of restarts. Giving up."
+                );
+                return Err(ActorExitStatus::from(anyhow::anyhow!(
+                    "Actor `{}` failed {} times within {:?}.",
+                    self.actor_name,
+                    num_recent_restarts + 1,
+                    self.restart_policy.restart_window
+                )));
+            }
+        }
+        self.restart_instants.push_back(now);
+        let backoff = self.restart_policy.backoff(num_recent_restarts);
+
+        if backoff.is_zero() {
+            self.restart_actor(actor_mailbox, ctx);
+        } else {
+            info!(
+                backoff_secs = backoff.as_secs_f32(),
+                "delaying-actor-restart"
+            );
+            self.pending_restart_mailbox_opt = Some(actor_mailbox);
+            ctx.schedule_self_msg(backoff, RestartActor).await;
+        }
+        Ok(())
+    }
+
+    fn restart_actor(&mut self, actor_mailbox: Mailbox<A>, ctx: &ActorContext<Supervisor<A>>) {
         info!("respawning-actor");
         let (_, actor_handle) = ctx
             .spawn_actor()
@@ -165,6 +283,21 @@ impl<A: Actor> Supervisor<A> {
             .set_kill_switch(ctx.kill_switch().child())
             .spawn((*self.actor_factory)());
         self.handle_opt = Some(actor_handle);
+    }
+}
+
+#[async_trait]
+impl<A: Actor> Handler<RestartActor> for Supervisor<A> {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _msg: RestartActor,
+        ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        if let Some(actor_mailbox) = self.pending_restart_mailbox_opt.take() {
+            self.restart_actor(actor_mailbox, ctx);
+        }
         Ok(())
     }
 }
@@ -191,7 +324,7 @@ mod tests {
     use async_trait::async_trait;
     use tracing::info;
 
-    use crate::supervisor::SupervisorState;
+    use crate::supervisor::{RestartPolicy, SupervisorState};
     use crate::{Actor, ActorContext, ActorExitStatus, AskError, Handler, Universe};
 
     #[derive(Copy, Clone, Debug)]
@@ -277,17 +410,17 @@ mod tests {
             mailbox.ask(FailingActorMessage::Increment).await.unwrap(),
             1
         );
-        assert_eq!(
-            *supervisor_handle.observe().await,
-            SupervisorState {
-                num_panics: 1,
-                num_errors: 0,
-                num_kills: 0
-            }
-        );
+        let supervisor_state = supervisor_handle.observe().await.state;
+        assert_eq!(supervisor_state.num_panics, 1);
+        assert_eq!(supervisor_state.num_errors, 0);
+        assert_eq!(supervisor_state.num_kills, 0);
+        let last_panic = supervisor_state.last_panic_opt.unwrap();
+        assert_eq!(last_panic.message, "Failing actor panicked");
+        assert!(last_panic.location_opt.unwrap().contains("supervisor.rs"));
+        assert!(last_panic.backtrace_opt.is_some());
         assert!(!matches!(
             supervisor_handle.quit().await.0,
-            ActorExitStatus::Panicked
+            ActorExitStatus::Panicked(_)
         ));
     }
 
@@ -314,12 +447,13 @@ mod tests {
             SupervisorState {
                 num_panics: 0,
                 num_errors: 1,
-                num_kills: 0
+                num_kills: 0,
+                ..Default::default()
             }
         );
         assert!(!matches!(
             supervisor_handle.quit().await.0,
-            ActorExitStatus::Panicked
+            ActorExitStatus::Panicked(_)
         ));
     }
 
@@ -341,7 +475,8 @@ mod tests {
             SupervisorState {
                 num_panics: 0,
                 num_errors: 0,
-                num_kills: 0
+                num_kills: 0,
+                ..Default::default()
             }
         );
         mailbox
@@ -359,12 +494,13 @@ mod tests {
             SupervisorState {
                 num_panics: 0,
                 num_errors: 0,
-                num_kills: 1
+                num_kills: 1,
+                ..Default::default()
             }
         );
         assert!(!matches!(
             supervisor_handle.quit().await.0,
-            ActorExitStatus::Panicked
+            ActorExitStatus::Panicked(_)
         ));
     }
 
@@ -419,4 +555,73 @@ mod tests {
         let (exit_status, _state) = supervisor_handle.join().await;
         assert!(matches!(exit_status, ActorExitStatus::Success));
     }
+
+    #[test]
+    fn test_restart_policy_backoff() {
+        let restart_policy = RestartPolicy::default();
+        assert_eq!(restart_policy.backoff(0), Duration::ZERO);
+        assert_eq!(restart_policy.backoff(10), Duration::ZERO);
+
+        let restart_policy =
+            RestartPolicy::exponential_backoff(Duration::from_secs(1), Duration::from_secs(10));
+        assert_eq!(restart_policy.backoff(0), Duration::from_secs(1));
+        assert_eq!(restart_policy.backoff(1), Duration::from_secs(2));
+        assert_eq!(restart_policy.backoff(3), Duration::from_secs(8));
+        assert_eq!(restart_policy.backoff(4), Duration::from_secs(10));
+        assert_eq!(restart_policy.backoff(100), Duration::from_secs(10));
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_restart_with_backoff() {
+        let universe = Universe::with_accelerated_time();
+        let actor = FailingActor::default();
+        let restart_policy =
+            RestartPolicy::exponential_backoff(Duration::from_millis(100), Duration::from_secs(1));
+        let (mailbox, supervisor_handle) = universe
+            .spawn_builder()
+            .set_restart_policy(restart_policy)
+            .supervise(actor);
+        assert_eq!(
+            mailbox.ask(FailingActorMessage::Increment).await.unwrap(),
+            1
+        );
+        assert!(mailbox.ask(FailingActorMessage::ReturnError).await.is_err());
+        assert_eq!(
+            mailbox.ask(FailingActorMessage::Increment).await.unwrap(),
+            1
+        );
+        assert_eq!(
+            *supervisor_handle.observe().await,
+            SupervisorState {
+                num_panics: 0,
+                num_errors: 1,
+                num_kills: 0,
+                ..Default::default()
+            }
+        );
+        assert!(!matches!(
+            supervisor_handle.quit().await.0,
+            ActorExitStatus::Panicked(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_supervisor_escalates_failure_after_max_restarts() {
+        let universe = Universe::with_accelerated_time();
+        let actor = FailingActor::default();
+        let restart_policy = RestartPolicy::default().with_max_restarts(1, Duration::from_secs(60));
+        let (mailbox, supervisor_handle) = universe
+            .spawn_builder()
+            .set_restart_policy(restart_policy)
+            .supervise(actor);
+        assert!(mailbox.ask(FailingActorMessage::ReturnError).await.is_err());
+        assert_eq!(
+            mailbox.ask(FailingActorMessage::Increment).await.unwrap(),
+            1
+        );
+        assert!(mailbox.ask(FailingActorMessage::ReturnError).await.is_err());
+        let (exit_status, supervisor_state) = supervisor_handle.join().await;
+        assert!(matches!(exit_status, ActorExitStatus::Failure(_)));
+        assert_eq!(supervisor_state.num_errors, 2);
+    }
 }
@@ -295,14 +295,14 @@ async fn test_pause_actor() {
         assert!(ping_mailbox.send_message(Ping).await.is_ok());
     }
     assert!(ping_mailbox
-        .send_message_with_high_priority(Command::Pause)
+        .send_message_with_control_priority(Command::Pause)
         .is_ok());
     let first_state = ping_handle.observe().await.state;
     assert!(first_state < 1000);
     let second_state = ping_handle.observe().await.state;
     assert_eq!(first_state, second_state);
     assert!(ping_mailbox
-        .send_message_with_high_priority(Command::Resume)
+        .send_message_with_control_priority(Command::Resume)
         .is_ok());
     let end_state = ping_handle.process_pending_and_observe().await.state;
     assert_eq!(end_state, 1000);
@@ -506,7 +506,11 @@ async fn test_actor_finalize_error_set_exit_status_to_panicked() -> anyhow::Resu
     assert!(matches!(handle.state(), ActorState::Processing));
     drop(mailbox);
     let (exit, _) = handle.join().await;
-    assert!(matches!(exit, ActorExitStatus::Panicked));
+    let panic_report = match exit {
+        ActorExitStatus::Panicked(panic_report) => panic_report,
+        other_exit_status => panic!("Expected a panic, got `{other_exit_status:?}`."),
+    };
+    assert!(panic_report.message.contains("Finalize error"));
     Ok(())
 }
 
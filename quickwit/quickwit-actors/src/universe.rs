@@ -23,9 +23,9 @@ use std::time::Duration;
 
 use crate::mailbox::create_mailbox;
 use crate::registry::ActorObservation;
-use crate::scheduler::start_scheduler;
+use crate::scheduler::{start_deterministic_scheduler, start_scheduler};
 use crate::spawn_builder::{SpawnBuilder, SpawnContext};
-use crate::{Actor, ActorExitStatus, Command, Inbox, Mailbox, QueueCapacity};
+use crate::{Actor, ActorExitStatus, Command, Inbox, Mailbox, QueueCapacity, ResourceBudget};
 
 /// Universe serves as the top-level context in which Actor can be spawned.
 /// It is *not* a singleton. A typical application will usually have only one universe hosting all
@@ -58,13 +58,40 @@ impl Universe {
     ///
     /// The time "jumps" only happen when no actor is processing any message,
     /// running initialization or finalize.
+    ///
+    /// If the `QW_ACTORS_SEED` environment variable is set, the universe runs in deterministic
+    /// mode instead (see [`Universe::with_seed`]). It accepts either a seed, to replay a run, or
+    /// `random`.
     #[cfg(any(test, feature = "testsuite"))]
     pub fn with_accelerated_time() -> Universe {
+        if let Some(seed) = seed_from_env() {
+            return Universe::with_seed(seed);
+        }
         let universe = Universe::new();
         universe.spawn_ctx().scheduler_client.accelerate_time();
         universe
     }
 
+    /// Creates a universe with accelerated time, in which the interleavings of the actors are
+    /// derived from `seed` (see [`crate::start_deterministic_scheduler`]).
+    ///
+    /// Running a test on a single-threaded runtime with the same seed replays the same
+    /// interleavings, which makes it possible to reproduce flaky failures. The seed is logged when
+    /// the universe is created, and printed if the test panics.
+    #[cfg(any(test, feature = "testsuite"))]
+    pub fn with_seed(seed: u64) -> Universe {
+        tracing::info!(seed = seed, "deterministic-universe");
+        let scheduler_client = start_deterministic_scheduler(seed);
+        Universe {
+            spawn_ctx: SpawnContext::new(scheduler_client),
+        }
+    }
+
+    /// Returns the seed of the universe if it runs in deterministic mode.
+    pub fn seed(&self) -> Option<u64> {
+        self.spawn_ctx.scheduler_client.seed()
+    }
+
     pub fn spawn_ctx(&self) -> &SpawnContext {
         &self.spawn_ctx
     }
@@ -93,6 +120,16 @@ impl Universe {
         self.spawn_ctx.registry.observe(timeout).await
     }
 
+    /// Registers a resource budget shared by all the actors of the universe. See
+    /// [`SpawnContext::register_resource_budget`].
+    pub fn register_resource_budget(&self, name: &str, capacity: u64) -> ResourceBudget {
+        self.spawn_ctx.register_resource_budget(name, capacity)
+    }
+
+    pub fn resource_budget(&self, name: &str) -> Option<ResourceBudget> {
+        self.spawn_ctx.resource_budget(name)
+    }
+
     pub fn kill(&self) {
         self.spawn_ctx.kill_switch.kill();
     }
@@ -134,12 +171,44 @@ impl Universe {
             .quit()
             .await
             .values()
-            .any(|status| matches!(status, ActorExitStatus::Panicked)));
+            .any(|status| matches!(status, ActorExitStatus::Panicked(_))));
+    }
+}
+
+/// Environment variable used to run the time-accelerated universes in deterministic mode.
+const ACTORS_SEED_ENV_KEY: &str = "QW_ACTORS_SEED";
+
+#[cfg(any(test, feature = "testsuite"))]
+fn seed_from_env() -> Option<u64> {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    let seed_str = std::env::var(ACTORS_SEED_ENV_KEY).ok()?;
+    if seed_str.eq_ignore_ascii_case("random") {
+        return Some(RandomState::new().build_hasher().finish());
+    }
+    match seed_str.parse::<u64>() {
+        Ok(seed) => Some(seed),
+        Err(_) => {
+            tracing::warn!(
+                "Ignoring invalid value `{seed_str}` of `{ACTORS_SEED_ENV_KEY}`: expected a \
+                 number or `random`."
+            );
+            None
+        }
     }
 }
 
 impl Drop for Universe {
     fn drop(&mut self) {
+        if let Some(seed) = self.seed() {
+            if thread::panicking() {
+                eprintln!(
+                    "The test panicked in a deterministic universe with seed {seed}. Replay it \
+                     with `{ACTORS_SEED_ENV_KEY}={seed}`."
+                );
+            }
+        }
         if cfg!(any(test, feature = "testsuite"))
             && !self.spawn_ctx.registry.is_empty()
             && !thread::panicking()
@@ -251,7 +320,7 @@ mod tests {
             .quit()
             .await
             .values()
-            .any(|status| matches!(status, ActorExitStatus::Panicked)));
+            .any(|status| matches!(status, ActorExitStatus::Panicked(_))));
     }
 
     #[tokio::test]
@@ -265,7 +334,7 @@ mod tests {
             .quit()
             .await
             .values()
-            .any(|status| matches!(status, ActorExitStatus::Panicked)));
+            .any(|status| matches!(status, ActorExitStatus::Panicked(_))));
     }
 
     #[tokio::test]
@@ -21,8 +21,14 @@ use anyhow::bail;
 use clap::{arg, Arg, ArgMatches, Command};
 use tracing::Level;
 
+use crate::cluster::{build_cluster_command, ClusterCliCommand};
+use crate::config::{build_config_command, ConfigCliCommand};
+use crate::delete_task::{build_delete_task_command, DeleteTaskCliCommand};
+use crate::exit_code::EXIT_CODES_HELP;
+use crate::generate::{build_generate_command, GenerateCliCommand};
 use crate::index::{build_index_command, IndexCliCommand};
-use crate::service::{build_run_command, RunCliCommand};
+use crate::repl::{build_repl_command, ReplCliCommand};
+use crate::service::{build_drain_command, build_run_command, DrainCliCommand, RunCliCommand};
 use crate::source::{build_source_command, SourceCliCommand};
 use crate::split::{build_split_command, SplitCliCommand};
 use crate::tool::{build_tool_command, ToolCliCommand};
@@ -44,11 +50,23 @@ pub fn build_cli<'a>() -> Command<'a> {
             .global(true)
             .required(false)
         )
+        .arg(arg!(--output <OUTPUT_FORMAT> "Output format of the commands displaying data. Possible values are `table`, `json`, `pretty_json`, and `yaml`.")
+            .aliases(&["output-format", "format"])
+            .global(true)
+            .required(false)
+        )
         .subcommand(build_run_command().display_order(1))
         .subcommand(build_index_command().display_order(2))
         .subcommand(build_source_command().display_order(3))
         .subcommand(build_split_command().display_order(4))
-        .subcommand(build_tool_command().display_order(5))
+        .subcommand(build_delete_task_command().display_order(5))
+        .subcommand(build_tool_command().display_order(6))
+        .subcommand(build_drain_command().display_order(7))
+        .subcommand(build_cluster_command().display_order(8))
+        .subcommand(build_repl_command().display_order(9))
+        .subcommand(build_config_command().display_order(10))
+        .subcommand(build_generate_command().display_order(11))
+        .after_help(EXIT_CODES_HELP)
         .arg_required_else_help(true)
         .disable_help_subcommand(true)
         .subcommand_required(true)
@@ -60,7 +78,13 @@ pub enum CliCommand {
     Index(IndexCliCommand),
     Split(SplitCliCommand),
     Source(SourceCliCommand),
+    DeleteTask(DeleteTaskCliCommand),
     Tool(ToolCliCommand),
+    Drain(DrainCliCommand),
+    Cluster(ClusterCliCommand),
+    Repl(ReplCliCommand),
+    Config(ConfigCliCommand),
+    Generate(GenerateCliCommand),
 }
 
 impl CliCommand {
@@ -70,7 +94,13 @@ impl CliCommand {
             CliCommand::Index(subcommand) => subcommand.default_log_level(),
             CliCommand::Source(_) => Level::ERROR,
             CliCommand::Split(_) => Level::ERROR,
+            CliCommand::DeleteTask(_) => Level::ERROR,
             CliCommand::Tool(_) => Level::ERROR,
+            CliCommand::Drain(_) => Level::ERROR,
+            CliCommand::Cluster(_) => Level::ERROR,
+            CliCommand::Repl(_) => Level::ERROR,
+            CliCommand::Config(_) => Level::ERROR,
+            CliCommand::Generate(_) => Level::ERROR,
         }
     }
 
@@ -83,7 +113,15 @@ impl CliCommand {
             "run" => RunCliCommand::parse_cli_args(submatches).map(CliCommand::Run),
             "source" => SourceCliCommand::parse_cli_args(submatches).map(CliCommand::Source),
             "split" => SplitCliCommand::parse_cli_args(submatches).map(CliCommand::Split),
+            "delete-task" => {
+                DeleteTaskCliCommand::parse_cli_args(submatches).map(CliCommand::DeleteTask)
+            }
             "tool" => ToolCliCommand::parse_cli_args(submatches).map(CliCommand::Tool),
+            "drain" => DrainCliCommand::parse_cli_args(submatches).map(CliCommand::Drain),
+            "cluster" => ClusterCliCommand::parse_cli_args(submatches).map(CliCommand::Cluster),
+            "repl" => ReplCliCommand::parse_cli_args(submatches).map(CliCommand::Repl),
+            "config" => ConfigCliCommand::parse_cli_args(submatches).map(CliCommand::Config),
+            "generate" => GenerateCliCommand::parse_cli_args(submatches).map(CliCommand::Generate),
             _ => bail!("Subcommand `{}` is not implemented.", subcommand),
         }
     }
@@ -94,7 +132,13 @@ impl CliCommand {
             CliCommand::Run(subcommand) => subcommand.execute().await,
             CliCommand::Source(subcommand) => subcommand.execute().await,
             CliCommand::Split(subcommand) => subcommand.execute().await,
+            CliCommand::DeleteTask(subcommand) => subcommand.execute().await,
             CliCommand::Tool(subcommand) => subcommand.execute().await,
+            CliCommand::Drain(subcommand) => subcommand.execute().await,
+            CliCommand::Cluster(subcommand) => subcommand.execute().await,
+            CliCommand::Repl(subcommand) => subcommand.execute().await,
+            CliCommand::Config(subcommand) => subcommand.execute().await,
+            CliCommand::Generate(subcommand) => subcommand.execute().await,
         }
     }
 }
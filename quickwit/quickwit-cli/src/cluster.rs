@@ -0,0 +1,541 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::time::Duration;
+
+use anyhow::bail;
+use chitchat::ChitchatId;
+use clap::{arg, ArgMatches, Command};
+use colored::Colorize;
+use itertools::Itertools;
+use quickwit_cluster::{ClusterMember, ClusterSnapshot, DecommissionStatus};
+use quickwit_common::GREEN_COLOR;
+use quickwit_rest_client::rest_client::{QuickwitClient, Transport};
+use reqwest::Url;
+use serde::Serialize;
+use tabled::Tabled;
+use tracing::debug;
+
+use crate::{
+    cluster_endpoint_arg, make_table, parse_output_format, parse_watch_interval, print_output,
+    watch, watch_arg, OutputFormat,
+};
+
+/// Interval at which the decommission command checks whether the node is decommissioned.
+const DECOMMISSION_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+pub fn build_cluster_command<'a>() -> Command<'a> {
+    Command::new("cluster")
+        .about("Manages the nodes of a cluster.")
+        .arg(cluster_endpoint_arg())
+        .subcommand(
+            Command::new("status")
+                .about("Displays the nodes of the cluster and their status.")
+                .long_about(
+                    "Displays a health summary of the cluster, i.e. the number of ready, not \
+                     ready, and dead nodes and the number of ready nodes running each service, \
+                     followed by the status, services, and number of indexing tasks of each node. \
+                     With `--watch`, the status is refreshed periodically.",
+                )
+                .alias("st")
+                .args(&[watch_arg()]),
+        )
+        .subcommand(
+            Command::new("decommission")
+                .about("Decommissions a node and waits until it can be terminated safely.")
+                .long_about(
+                    "Decommissions a node: the control plane moves its indexing pipelines to the \
+                     other indexers, the searchers stop sending it leaf search jobs, and the node \
+                     drains its indexing pipelines and completes its ongoing searches. The \
+                     command returns once the node can be terminated safely.",
+                )
+                .args(&[arg!(<NODE> "ID of the node to decommission").display_order(1)]),
+        )
+        .subcommand(Command::new("tenants").about("Lists the tenants owning at least one index."))
+        .arg_required_else_help(true)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ClusterStatusArgs {
+    pub cluster_endpoint: Url,
+    pub output_format: OutputFormat,
+    pub watch_interval_opt: Option<Duration>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct DecommissionNodeArgs {
+    pub cluster_endpoint: Url,
+    pub node_id: String,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ListTenantsArgs {
+    pub cluster_endpoint: Url,
+    pub output_format: OutputFormat,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ClusterCliCommand {
+    Status(ClusterStatusArgs),
+    Decommission(DecommissionNodeArgs),
+    Tenants(ListTenantsArgs),
+}
+
+impl ClusterCliCommand {
+    pub fn parse_cli_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let (subcommand, submatches) = matches
+            .subcommand()
+            .ok_or_else(|| anyhow::anyhow!("Failed to parse sub-matches."))?;
+        match subcommand {
+            "decommission" => Self::parse_decommission_args(submatches),
+            "status" => Self::parse_status_args(submatches),
+            "tenants" => Self::parse_tenants_args(submatches),
+            _ => bail!("Subcommand `{}` is not implemented.", subcommand),
+        }
+    }
+
+    fn parse_status_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let output_format = parse_output_format(matches)?;
+        let watch_interval_opt = parse_watch_interval(matches)?;
+        Ok(Self::Status(ClusterStatusArgs {
+            cluster_endpoint,
+            output_format,
+            watch_interval_opt,
+        }))
+    }
+
+    fn parse_decommission_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let node_id = matches
+            .value_of("NODE")
+            .map(String::from)
+            .expect("`NODE` is a required arg.");
+        Ok(Self::Decommission(DecommissionNodeArgs {
+            cluster_endpoint,
+            node_id,
+        }))
+    }
+
+    fn parse_tenants_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let output_format = parse_output_format(matches)?;
+        Ok(Self::Tenants(ListTenantsArgs {
+            cluster_endpoint,
+            output_format,
+        }))
+    }
+
+    pub async fn execute(self) -> anyhow::Result<()> {
+        match self {
+            Self::Status(args) => cluster_status_cli(args).await,
+            Self::Decommission(args) => decommission_node_cli(args).await,
+            Self::Tenants(args) => list_tenants_cli(args).await,
+        }
+    }
+}
+
+async fn cluster_status_cli(args: ClusterStatusArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "cluster-status");
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = &QuickwitClient::new(transport);
+    watch(
+        args.watch_interval_opt,
+        args.output_format,
+        move || async move {
+            let cluster_snapshot = qw_client.cluster().snapshot().await?;
+            let cluster_status = ClusterStatus::from(cluster_snapshot);
+            print_output(args.output_format, &cluster_status, |cluster_status| {
+                let title = format!(
+                    "Cluster `{}`: {}",
+                    cluster_status.cluster_id,
+                    cluster_status.health_summary()
+                );
+                make_table(&title, cluster_status.nodes.iter().cloned(), false)
+            })
+        },
+    )
+    .await
+}
+
+/// Cluster members and their status, as displayed by the `cluster status` command.
+#[derive(Serialize)]
+struct ClusterStatus {
+    cluster_id: String,
+    num_ready_nodes: usize,
+    num_not_ready_nodes: usize,
+    num_dead_nodes: usize,
+    /// Number of ready nodes running each service.
+    num_ready_nodes_per_service: BTreeMap<&'static str, usize>,
+    nodes: Vec<NodeStatusRow>,
+}
+
+impl ClusterStatus {
+    fn new(
+        cluster_id: String,
+        ready_nodes: impl IntoIterator<Item = ChitchatId>,
+        live_nodes: impl IntoIterator<Item = ChitchatId>,
+        dead_nodes: impl IntoIterator<Item = ChitchatId>,
+        members: Vec<ClusterMember>,
+    ) -> Self {
+        let mut members: HashMap<ChitchatId, ClusterMember> = members
+            .into_iter()
+            .map(|member| (member.chitchat_id(), member))
+            .collect();
+        let ready_nodes = make_node_rows(ready_nodes, "ready", &mut members);
+        let live_nodes = make_node_rows(live_nodes, "not ready", &mut members);
+        let dead_nodes = make_node_rows(dead_nodes, "dead", &mut members);
+
+        let mut num_ready_nodes_per_service = BTreeMap::new();
+        for node in &ready_nodes {
+            for service in &node.services {
+                *num_ready_nodes_per_service.entry(*service).or_default() += 1;
+            }
+        }
+        let num_ready_nodes = ready_nodes.len();
+        let num_not_ready_nodes = live_nodes.len();
+        let num_dead_nodes = dead_nodes.len();
+        let nodes = ready_nodes
+            .into_iter()
+            .chain(live_nodes)
+            .chain(dead_nodes)
+            .sorted_by(|left, right| left.node_id.cmp(&right.node_id))
+            .collect();
+        Self {
+            cluster_id,
+            num_ready_nodes,
+            num_not_ready_nodes,
+            num_dead_nodes,
+            num_ready_nodes_per_service,
+            nodes,
+        }
+    }
+
+    /// Returns a one-line health summary of the cluster, for instance `2 ready, 1 not ready, 0
+    /// dead node(s) | indexer: 1, searcher: 2`.
+    fn health_summary(&self) -> String {
+        let mut health_summary = format!(
+            "{} ready, {} not ready, {} dead node(s)",
+            self.num_ready_nodes, self.num_not_ready_nodes, self.num_dead_nodes
+        );
+        if !self.num_ready_nodes_per_service.is_empty() {
+            let service_mix = self
+                .num_ready_nodes_per_service
+                .iter()
+                .map(|(service, num_nodes)| format!("{service}: {num_nodes}"))
+                .join(", ");
+            health_summary.push_str(" | ");
+            health_summary.push_str(&service_mix);
+        }
+        health_summary
+    }
+}
+
+fn make_node_rows(
+    chitchat_ids: impl IntoIterator<Item = ChitchatId>,
+    status: &'static str,
+    members: &mut HashMap<ChitchatId, ClusterMember>,
+) -> Vec<NodeStatusRow> {
+    chitchat_ids
+        .into_iter()
+        .map(|chitchat_id| {
+            let member_opt = members.remove(&chitchat_id);
+            NodeStatusRow::new(chitchat_id, status, member_opt)
+        })
+        .collect()
+}
+
+impl From<ClusterSnapshot> for ClusterStatus {
+    fn from(cluster_snapshot: ClusterSnapshot) -> Self {
+        let members = cluster_snapshot.members();
+        Self::new(
+            cluster_snapshot.cluster_id,
+            cluster_snapshot.ready_nodes,
+            cluster_snapshot.live_nodes,
+            cluster_snapshot.dead_nodes,
+            members,
+        )
+    }
+}
+
+#[derive(Clone, Serialize, Tabled)]
+struct NodeStatusRow {
+    #[tabled(rename = "Node ID")]
+    node_id: String,
+    #[tabled(rename = "Status")]
+    status: &'static str,
+    #[tabled(rename = "Services")]
+    #[serde(skip)]
+    services_str: String,
+    #[tabled(skip)]
+    services: Vec<&'static str>,
+    #[tabled(rename = "Indexing tasks")]
+    num_indexing_tasks: usize,
+    #[tabled(skip)]
+    indexing_tasks: Vec<String>,
+    #[tabled(rename = "Generation ID")]
+    generation_id: u64,
+    #[tabled(rename = "Gossip address")]
+    gossip_address: SocketAddr,
+}
+
+impl NodeStatusRow {
+    /// The member is missing when the node state is not available, which is usually the case of
+    /// dead nodes.
+    fn new(
+        chitchat_id: ChitchatId,
+        status: &'static str,
+        member_opt: Option<ClusterMember>,
+    ) -> Self {
+        let (services, indexing_tasks) = match member_opt {
+            Some(member) => {
+                let services: Vec<&'static str> = member
+                    .enabled_services
+                    .iter()
+                    .map(|service| service.as_str())
+                    .sorted()
+                    .collect();
+                let indexing_tasks: Vec<String> = member
+                    .indexing_tasks
+                    .iter()
+                    .map(|indexing_task| {
+                        format!("{}:{}", indexing_task.index_uid, indexing_task.source_id)
+                    })
+                    .sorted()
+                    .collect();
+                (services, indexing_tasks)
+            }
+            None => (Vec::new(), Vec::new()),
+        };
+        Self {
+            node_id: chitchat_id.node_id,
+            status,
+            services_str: services.join(", "),
+            services,
+            num_indexing_tasks: indexing_tasks.len(),
+            indexing_tasks,
+            generation_id: chitchat_id.generation_id,
+            gossip_address: chitchat_id.gossip_advertise_addr,
+        }
+    }
+}
+
+async fn decommission_node_cli(args: DecommissionNodeArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "decommission-node");
+    println!("❯ Decommissioning node `{}`...", args.node_id);
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    let mut node_decommission_status = qw_client.cluster().decommission_node(&args.node_id).await?;
+
+    while node_decommission_status.decommission_status != DecommissionStatus::Decommissioned {
+        tokio::time::sleep(DECOMMISSION_POLL_INTERVAL).await;
+        node_decommission_status = qw_client
+            .cluster()
+            .node_decommission_status(&args.node_id)
+            .await?;
+    }
+    println!(
+        "{} Node `{}` decommissioned, it can be terminated safely.",
+        "✔".color(GREEN_COLOR),
+        args.node_id
+    );
+    Ok(())
+}
+
+#[derive(Tabled)]
+struct TenantRow {
+    #[tabled(rename = "Tenant ID")]
+    tenant_id: String,
+}
+
+async fn list_tenants_cli(args: ListTenantsArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "list-tenants");
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    let tenant_ids = qw_client.cluster().tenants().await?;
+    print_output(args.output_format, &tenant_ids, |tenant_ids| {
+        let rows = tenant_ids.iter().map(|tenant_id| TenantRow {
+            tenant_id: tenant_id.clone(),
+        });
+        format!("\n{}\n", make_table("Tenants", rows, false))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_config::service::QuickwitService;
+    use quickwit_proto::indexing_api::IndexingTask;
+
+    use super::*;
+    use crate::cli::{build_cli, CliCommand};
+
+    #[test]
+    fn test_parse_cluster_status_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "cluster",
+            "status",
+            "--endpoint",
+            "http://127.0.0.1:8000",
+            "--output",
+            "json",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_command = CliCommand::Cluster(ClusterCliCommand::Status(ClusterStatusArgs {
+            cluster_endpoint: Url::from_str("http://127.0.0.1:8000").unwrap(),
+            output_format: OutputFormat::Json,
+            watch_interval_opt: None,
+        }));
+        assert_eq!(command, expected_command);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec!["cluster", "status", "--watch", "2s"])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_command = CliCommand::Cluster(ClusterCliCommand::Status(ClusterStatusArgs {
+            cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+            output_format: OutputFormat::Table,
+            watch_interval_opt: Some(Duration::from_secs(2)),
+        }));
+        assert_eq!(command, expected_command);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_decommission_node_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "cluster",
+            "decommission",
+            "node-1",
+            "--endpoint",
+            "http://127.0.0.1:8000",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_command =
+            CliCommand::Cluster(ClusterCliCommand::Decommission(DecommissionNodeArgs {
+                cluster_endpoint: Url::from_str("http://127.0.0.1:8000").unwrap(),
+                node_id: "node-1".to_string(),
+            }));
+        assert_eq!(command, expected_command);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_tenants_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "cluster",
+            "tenants",
+            "--endpoint",
+            "http://127.0.0.1:8000",
+            "--output",
+            "yaml",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_command = CliCommand::Cluster(ClusterCliCommand::Tenants(ListTenantsArgs {
+            cluster_endpoint: Url::from_str("http://127.0.0.1:8000").unwrap(),
+            output_format: OutputFormat::Yaml,
+        }));
+        assert_eq!(command, expected_command);
+        Ok(())
+    }
+
+    #[test]
+    fn test_cluster_status() {
+        let gossip_advertise_addr: SocketAddr = ([127, 0, 0, 1], 7280).into();
+        let grpc_advertise_addr: SocketAddr = ([127, 0, 0, 1], 7281).into();
+        let make_member = |node_id: &str, enabled_services: &[QuickwitService]| {
+            ClusterMember::new(
+                node_id.to_string(),
+                1.into(),
+                true,
+                enabled_services.iter().copied().collect(),
+                gossip_advertise_addr,
+                grpc_advertise_addr,
+                Vec::new(),
+            )
+        };
+        let mut indexer = make_member("node-1", &[QuickwitService::Indexer]);
+        indexer.indexing_tasks = vec![
+            IndexingTask {
+                index_uid: "wikipedia:01GX".to_string(),
+                source_id: "kafka".to_string(),
+            },
+            IndexingTask {
+                index_uid: "hdfs-logs:01GY".to_string(),
+                source_id: "kafka".to_string(),
+            },
+        ];
+        let searcher = make_member(
+            "node-2",
+            &[QuickwitService::Searcher, QuickwitService::Metastore],
+        );
+        let not_ready_searcher = make_member("node-3", &[QuickwitService::Searcher]);
+        let dead_node = ChitchatId::new("node-4".to_string(), 1, gossip_advertise_addr);
+
+        let cluster_status = ClusterStatus::new(
+            "qw-cluster".to_string(),
+            [indexer.chitchat_id(), searcher.chitchat_id()],
+            [not_ready_searcher.chitchat_id()],
+            [dead_node],
+            vec![indexer, searcher, not_ready_searcher],
+        );
+        assert_eq!(cluster_status.num_ready_nodes, 2);
+        assert_eq!(cluster_status.num_not_ready_nodes, 1);
+        assert_eq!(cluster_status.num_dead_nodes, 1);
+        assert_eq!(
+            cluster_status.health_summary(),
+            "2 ready, 1 not ready, 1 dead node(s) | indexer: 1, metastore: 1, searcher: 1"
+        );
+        let node_ids: Vec<&str> = cluster_status
+            .nodes
+            .iter()
+            .map(|node| node.node_id.as_str())
+            .collect();
+        assert_eq!(node_ids, ["node-1", "node-2", "node-3", "node-4"]);
+
+        let indexer_row = &cluster_status.nodes[0];
+        assert_eq!(indexer_row.status, "ready");
+        assert_eq!(indexer_row.services_str, "indexer");
+        assert_eq!(indexer_row.num_indexing_tasks, 2);
+        assert_eq!(
+            indexer_row.indexing_tasks,
+            ["hdfs-logs:01GY:kafka", "wikipedia:01GX:kafka"]
+        );
+        assert_eq!(cluster_status.nodes[1].services_str, "metastore, searcher");
+        assert_eq!(cluster_status.nodes[2].status, "not ready");
+
+        let dead_node_row = &cluster_status.nodes[3];
+        assert_eq!(dead_node_row.status, "dead");
+        assert!(dead_node_row.services.is_empty());
+        assert_eq!(dead_node_row.num_indexing_tasks, 0);
+    }
+}
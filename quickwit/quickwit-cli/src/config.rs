@@ -0,0 +1,423 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::str::FromStr;
+
+use anyhow::bail;
+use clap::{arg, ArgGroup, ArgMatches, Command};
+use colored::Colorize;
+use quickwit_common::uri::Uri;
+use quickwit_common::{run_checklist, GREEN_COLOR, RED_COLOR};
+use quickwit_config::{
+    load_index_config_from_user_config, load_source_config_from_user_config, ConfigFormat,
+    IndexConfig, QuickwitConfig, SourceConfig,
+};
+use quickwit_indexing::check_source_connectivity;
+use quickwit_metastore::quickwit_metastore_uri_resolver;
+use quickwit_storage::{load_file, quickwit_storage_uri_resolver};
+use tracing::debug;
+
+use crate::exit_code::{ExitCode, WithExitCode};
+use crate::load_quickwit_config;
+
+/// Index root URI used to resolve the URI of index configs that do not set `index_uri` when no
+/// node config is provided. It matches the default index root URI of a node.
+const DEFAULT_INDEX_ROOT_PATH: &str = "qwdata/indexes";
+
+pub fn build_config_command<'a>() -> Command<'a> {
+    Command::new("config")
+        .about("Validates config files.")
+        .subcommand(
+            Command::new("validate")
+                .about("Validates node, index, and source config files.")
+                .long_about(
+                    "Loads node, index, and source config files and runs the same validation as \
+                     the node does on startup or on index and source creation. Then, checks \
+                     connectivity to the metastore, storage, and sources they reference without \
+                     creating anything.",
+                )
+                .args(&[
+                    arg!(--"node-config" <NODE_CONFIG> "Location of the node config file.")
+                        .required(false)
+                        .display_order(1),
+                    arg!(--"index-config" <INDEX_CONFIG> "Location of an index config file. Can be specified multiple times.")
+                        .required(false)
+                        .multiple_occurrences(true)
+                        .display_order(2),
+                    arg!(--"source-config" <SOURCE_CONFIG> "Location of a source config file. Can be specified multiple times.")
+                        .required(false)
+                        .multiple_occurrences(true)
+                        .display_order(3),
+                    arg!(--"skip-connectivity-checks" "Only validates the config files, without checking connectivity.")
+                        .required(false)
+                        .display_order(4),
+                ])
+                .group(
+                    ArgGroup::new("configs")
+                        .args(&["node-config", "index-config", "source-config"])
+                        .multiple(true)
+                        .required(true),
+                ),
+        )
+        .arg_required_else_help(true)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ValidateConfigArgs {
+    pub node_config_uri_opt: Option<Uri>,
+    pub index_config_uris: Vec<Uri>,
+    pub source_config_uris: Vec<Uri>,
+    pub skip_connectivity_checks: bool,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum ConfigCliCommand {
+    Validate(ValidateConfigArgs),
+}
+
+impl ConfigCliCommand {
+    pub fn parse_cli_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let (subcommand, submatches) = matches
+            .subcommand()
+            .ok_or_else(|| anyhow::anyhow!("Failed to parse sub-matches."))?;
+        match subcommand {
+            "validate" => Self::parse_validate_args(submatches),
+            _ => bail!("Subcommand `{}` is not implemented.", subcommand),
+        }
+    }
+
+    fn parse_validate_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let node_config_uri_opt = matches
+            .value_of("node-config")
+            .map(Uri::from_str)
+            .transpose()?;
+        let index_config_uris = matches
+            .values_of("index-config")
+            .map(|values| values.map(Uri::from_str).collect::<Result<Vec<_>, _>>())
+            .transpose()?
+            .unwrap_or_default();
+        let source_config_uris = matches
+            .values_of("source-config")
+            .map(|values| values.map(Uri::from_str).collect::<Result<Vec<_>, _>>())
+            .transpose()?
+            .unwrap_or_default();
+        let skip_connectivity_checks = matches.is_present("skip-connectivity-checks");
+        Ok(Self::Validate(ValidateConfigArgs {
+            node_config_uri_opt,
+            index_config_uris,
+            source_config_uris,
+            skip_connectivity_checks,
+        }))
+    }
+
+    pub async fn execute(self) -> anyhow::Result<()> {
+        match self {
+            Self::Validate(args) => validate_config_cli(args).await,
+        }
+    }
+}
+
+/// Configs that passed validation.
+#[derive(Default)]
+struct ValidConfigs {
+    node_config_opt: Option<QuickwitConfig>,
+    index_configs: Vec<IndexConfig>,
+    source_configs: Vec<SourceConfig>,
+}
+
+async fn validate_config_cli(args: ValidateConfigArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "validate-config");
+    let (valid_configs, num_invalid_configs) = validate_configs(&args).await;
+
+    if !args.skip_connectivity_checks {
+        run_connectivity_checks(&valid_configs).await?;
+    }
+    if num_invalid_configs > 0 {
+        return Err(anyhow::anyhow!(
+            "{num_invalid_configs} config file(s) are invalid."
+        ))
+        .with_exit_code(ExitCode::ConfigError);
+    }
+    Ok(())
+}
+
+/// Loads and validates the config files, printing the outcome for each one of them. Returns the
+/// valid configs and the number of invalid ones.
+async fn validate_configs(args: &ValidateConfigArgs) -> (ValidConfigs, usize) {
+    let mut valid_configs = ValidConfigs::default();
+    let mut num_invalid_configs = 0;
+
+    if let Some(node_config_uri) = &args.node_config_uri_opt {
+        let node_config_res = load_quickwit_config(node_config_uri).await;
+        valid_configs.node_config_opt = print_outcome("Node", node_config_uri, node_config_res);
+        if valid_configs.node_config_opt.is_none() {
+            num_invalid_configs += 1;
+        }
+    }
+    let default_index_root_uri = match &valid_configs.node_config_opt {
+        Some(node_config) => node_config.default_index_root_uri.clone(),
+        None => Uri::from_str(DEFAULT_INDEX_ROOT_PATH).expect("The URI should be valid."),
+    };
+    for index_config_uri in &args.index_config_uris {
+        let index_config_res = load_index_config(index_config_uri, &default_index_root_uri).await;
+        if let Some(index_config) = print_outcome("Index", index_config_uri, index_config_res) {
+            valid_configs.index_configs.push(index_config);
+        } else {
+            num_invalid_configs += 1;
+        }
+    }
+    for source_config_uri in &args.source_config_uris {
+        let source_config_res = load_source_config(source_config_uri).await;
+        if let Some(source_config) = print_outcome("Source", source_config_uri, source_config_res) {
+            valid_configs.source_configs.push(source_config);
+        } else {
+            num_invalid_configs += 1;
+        }
+    }
+    (valid_configs, num_invalid_configs)
+}
+
+async fn load_index_config(
+    index_config_uri: &Uri,
+    default_index_root_uri: &Uri,
+) -> anyhow::Result<IndexConfig> {
+    let config_content = load_file(index_config_uri).await?;
+    let config_format = ConfigFormat::sniff_from_uri(index_config_uri)?;
+    load_index_config_from_user_config(
+        config_format,
+        config_content.as_slice(),
+        default_index_root_uri,
+    )
+}
+
+async fn load_source_config(source_config_uri: &Uri) -> anyhow::Result<SourceConfig> {
+    let config_content = load_file(source_config_uri).await?;
+    let config_format = ConfigFormat::sniff_from_uri(source_config_uri)?;
+    load_source_config_from_user_config(config_format, config_content.as_slice())
+}
+
+fn print_outcome<T>(
+    config_kind: &str,
+    config_uri: &Uri,
+    config_res: anyhow::Result<T>,
+) -> Option<T> {
+    match config_res {
+        Ok(config) => {
+            println!(
+                "{} {config_kind} config `{config_uri}` is valid.",
+                "✔".color(GREEN_COLOR)
+            );
+            Some(config)
+        }
+        Err(error) => {
+            println!(
+                "{} {config_kind} config `{config_uri}` is invalid: {error:#}",
+                "✖".color(RED_COLOR)
+            );
+            None
+        }
+    }
+}
+
+/// Checks connectivity to the metastore, storage, and sources referenced by the valid configs.
+async fn run_connectivity_checks(valid_configs: &ValidConfigs) -> anyhow::Result<()> {
+    let mut checks: Vec<(String, anyhow::Result<()>)> = Vec::new();
+
+    if let Some(node_config) = &valid_configs.node_config_opt {
+        let metastore_check_res = quickwit_metastore_uri_resolver()
+            .check_connectivity(&node_config.metastore_config, &node_config.metastore_uri)
+            .await;
+        checks.push(("metastore".to_string(), metastore_check_res));
+        checks.push((
+            "default index storage".to_string(),
+            check_storage_connectivity(&node_config.default_index_root_uri).await,
+        ));
+    }
+    for index_config in &valid_configs.index_configs {
+        checks.push((
+            format!("index `{}` storage", index_config.index_id),
+            check_storage_connectivity(&index_config.index_uri).await,
+        ));
+    }
+    for source_config in &valid_configs.source_configs {
+        checks.push((
+            format!("source `{}`", source_config.source_id),
+            check_source_connectivity(source_config).await,
+        ));
+    }
+    if checks.is_empty() {
+        return Ok(());
+    }
+    let (check_item_names, check_item_results): (Vec<String>, Vec<anyhow::Result<()>>) =
+        checks.into_iter().unzip();
+    run_checklist(
+        check_item_names
+            .iter()
+            .map(String::as_str)
+            .zip(check_item_results)
+            .collect(),
+    )?;
+    Ok(())
+}
+
+/// Checks that the storage is reachable. Local file storage is only resolved because its
+/// connectivity check creates the root directory.
+async fn check_storage_connectivity(storage_uri: &Uri) -> anyhow::Result<()> {
+    let storage = quickwit_storage_uri_resolver().resolve(storage_uri)?;
+    if storage_uri.protocol().is_file() {
+        return Ok(());
+    }
+    storage.check_connectivity().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{build_cli, CliCommand};
+
+    const NODE_CONFIG: &str = r#"
+        version: 0.6
+        data_dir: #data_dir
+    "#;
+
+    const INDEX_CONFIG: &str = r#"
+        version: 0.6
+        index_id: hdfs-logs
+        doc_mapping:
+          field_mappings:
+            - name: timestamp
+              type: datetime
+              fast: true
+            - name: body
+              type: text
+          timestamp_field: timestamp
+    "#;
+
+    const SOURCE_CONFIG: &str = r#"
+        version: 0.6
+        source_id: hdfs-logs-file-source
+        source_type: file
+        params:
+          filepath: #filepath
+    "#;
+
+    #[test]
+    fn test_parse_validate_config_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "config",
+            "validate",
+            "--index-config",
+            "/indexes/hdfs-logs.yaml",
+            "--index-config",
+            "/indexes/wikipedia.yaml",
+            "--source-config",
+            "/sources/kafka.yaml",
+            "--skip-connectivity-checks",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_command = CliCommand::Config(ConfigCliCommand::Validate(ValidateConfigArgs {
+            node_config_uri_opt: None,
+            index_config_uris: vec![
+                Uri::from_str("file:///indexes/hdfs-logs.yaml").unwrap(),
+                Uri::from_str("file:///indexes/wikipedia.yaml").unwrap(),
+            ],
+            source_config_uris: vec![Uri::from_str("file:///sources/kafka.yaml").unwrap()],
+            skip_connectivity_checks: true,
+        }));
+        assert_eq!(command, expected_command);
+
+        let app = build_cli().no_binary_name(true);
+        let matches =
+            app.try_get_matches_from(vec!["config", "validate", "--node-config", "node.yaml"])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Config(ConfigCliCommand::Validate(ValidateConfigArgs {
+                node_config_uri_opt: Some(_),
+                skip_connectivity_checks: false,
+                ..
+            }))
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let error = app
+            .try_get_matches_from(vec!["config", "validate", "--skip-connectivity-checks"])
+            .unwrap_err();
+        assert_eq!(error.kind(), clap::ErrorKind::MissingRequiredArgument);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_validate_config_cli() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data_dir_path = temp_dir.path().join("qwdata");
+        std::fs::create_dir(&data_dir_path).unwrap();
+        let log_file_path = temp_dir.path().join("hdfs-logs.json");
+        std::fs::write(&log_file_path, "").unwrap();
+
+        let node_config_path = temp_dir.path().join("node.yaml");
+        let node_config =
+            NODE_CONFIG.replace("#data_dir", data_dir_path.to_string_lossy().as_ref());
+        std::fs::write(&node_config_path, node_config).unwrap();
+        let index_config_path = temp_dir.path().join("index.yaml");
+        std::fs::write(&index_config_path, INDEX_CONFIG).unwrap();
+        let source_config_path = temp_dir.path().join("source.yaml");
+        let source_config =
+            SOURCE_CONFIG.replace("#filepath", log_file_path.to_string_lossy().as_ref());
+        std::fs::write(&source_config_path, source_config).unwrap();
+
+        let args = ValidateConfigArgs {
+            node_config_uri_opt: Some(Uri::from_str(node_config_path.to_str().unwrap()).unwrap()),
+            index_config_uris: vec![Uri::from_str(index_config_path.to_str().unwrap()).unwrap()],
+            source_config_uris: vec![Uri::from_str(source_config_path.to_str().unwrap()).unwrap()],
+            skip_connectivity_checks: false,
+        };
+        validate_config_cli(args).await.unwrap();
+
+        // The checks must not create the metastore nor the index directories.
+        assert!(!data_dir_path.join("indexes").exists());
+    }
+
+    #[tokio::test]
+    async fn test_validate_config_cli_invalid_configs() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let index_config_path = temp_dir.path().join("index.yaml");
+        // The timestamp field must be a datetime field.
+        let index_config = INDEX_CONFIG.replace("type: datetime", "type: u64");
+        std::fs::write(&index_config_path, index_config).unwrap();
+        let source_config_path = temp_dir.path().join("source.yaml");
+        let source_config = SOURCE_CONFIG.replace("source_type: file", "source_type: foo");
+        std::fs::write(&source_config_path, source_config).unwrap();
+
+        let args = ValidateConfigArgs {
+            node_config_uri_opt: None,
+            index_config_uris: vec![Uri::from_str(index_config_path.to_str().unwrap()).unwrap()],
+            source_config_uris: vec![Uri::from_str(source_config_path.to_str().unwrap()).unwrap()],
+            skip_connectivity_checks: false,
+        };
+        let (valid_configs, num_invalid_configs) = validate_configs(&args).await;
+        assert!(valid_configs.index_configs.is_empty());
+        assert!(valid_configs.source_configs.is_empty());
+        assert_eq!(num_invalid_configs, 2);
+
+        let error = validate_config_cli(args).await.unwrap_err();
+        assert_eq!(error.to_string(), "2 config file(s) are invalid.");
+    }
+}
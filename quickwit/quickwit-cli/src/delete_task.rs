@@ -0,0 +1,413 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+use clap::{arg, ArgMatches, Command};
+use colored::Colorize;
+use itertools::Itertools;
+use quickwit_common::GREEN_COLOR;
+use quickwit_rest_client::rest_client::{QuickwitClient, Transport};
+use quickwit_serve::{DeleteTaskState, DeleteTaskStatus};
+use reqwest::Url;
+use tabled::{Table, Tabled};
+use time::OffsetDateTime;
+use tracing::debug;
+
+use crate::{
+    cluster_endpoint_arg, make_table, parse_output_format, print_output, prompt_confirmation,
+    OutputFormat,
+};
+
+pub fn build_delete_task_command<'a>() -> Command<'a> {
+    Command::new("delete-task")
+        .about("Manages delete tasks: lists, describes, cancels...")
+        .arg(cluster_endpoint_arg())
+        .subcommand(
+            Command::new("list")
+                .about("Lists the delete tasks of an index along with their progress.")
+                .alias("ls")
+                .args(&[arg!(--index <INDEX> "ID of the target index").display_order(1)]),
+        )
+        .subcommand(
+            Command::new("describe")
+                .about("Displays the state and progress of a delete task.")
+                .alias("desc")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index").display_order(1),
+                    arg!(--opstamp <OPSTAMP> "Opstamp of the target delete task").display_order(2),
+                ]),
+        )
+        .subcommand(
+            Command::new("cancel")
+                .about("Cancels a delete task that has not been applied to any split yet.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index").display_order(1),
+                    arg!(--opstamp <OPSTAMP> "Opstamp of the delete task to cancel")
+                        .display_order(2),
+                ]),
+        )
+        .arg_required_else_help(true)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ListDeleteTasksArgs {
+    pub cluster_endpoint: Url,
+    pub index_id: String,
+    pub output_format: OutputFormat,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct DescribeDeleteTaskArgs {
+    pub cluster_endpoint: Url,
+    pub index_id: String,
+    pub opstamp: u64,
+    pub output_format: OutputFormat,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct CancelDeleteTaskArgs {
+    pub cluster_endpoint: Url,
+    pub index_id: String,
+    pub opstamp: u64,
+    pub assume_yes: bool,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum DeleteTaskCliCommand {
+    List(ListDeleteTasksArgs),
+    Describe(DescribeDeleteTaskArgs),
+    Cancel(CancelDeleteTaskArgs),
+}
+
+impl DeleteTaskCliCommand {
+    pub fn parse_cli_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let (subcommand, submatches) = matches
+            .subcommand()
+            .ok_or_else(|| anyhow::anyhow!("Failed to parse sub-matches."))?;
+        match subcommand {
+            "cancel" => Self::parse_cancel_args(submatches),
+            "describe" => Self::parse_describe_args(submatches),
+            "list" => Self::parse_list_args(submatches),
+            _ => bail!("Subcommand `{}` is not implemented.", subcommand),
+        }
+    }
+
+    fn parse_list_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .map(String::from)
+            .expect("`index` is a required arg.");
+        let output_format = parse_output_format(matches)?;
+        Ok(Self::List(ListDeleteTasksArgs {
+            cluster_endpoint,
+            index_id,
+            output_format,
+        }))
+    }
+
+    fn parse_describe_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .map(String::from)
+            .expect("`index` is a required arg.");
+        let opstamp = parse_opstamp(
+            matches
+                .value_of("opstamp")
+                .expect("`opstamp` is a required arg."),
+        )?;
+        let output_format = parse_output_format(matches)?;
+        Ok(Self::Describe(DescribeDeleteTaskArgs {
+            cluster_endpoint,
+            index_id,
+            opstamp,
+            output_format,
+        }))
+    }
+
+    fn parse_cancel_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .map(String::from)
+            .expect("`index` is a required arg.");
+        let opstamp = parse_opstamp(
+            matches
+                .value_of("opstamp")
+                .expect("`opstamp` is a required arg."),
+        )?;
+        let assume_yes = matches.is_present("yes");
+        Ok(Self::Cancel(CancelDeleteTaskArgs {
+            cluster_endpoint,
+            index_id,
+            opstamp,
+            assume_yes,
+        }))
+    }
+
+    pub async fn execute(self) -> anyhow::Result<()> {
+        match self {
+            Self::List(args) => list_delete_tasks_cli(args).await,
+            Self::Describe(args) => describe_delete_task_cli(args).await,
+            Self::Cancel(args) => cancel_delete_task_cli(args).await,
+        }
+    }
+}
+
+async fn list_delete_tasks_cli(args: ListDeleteTasksArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "list-delete-tasks");
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    let delete_task_statuses = qw_client.delete_tasks(&args.index_id).list().await?;
+    print_output(
+        args.output_format,
+        &delete_task_statuses,
+        |delete_task_statuses| make_delete_task_table(delete_task_statuses, "Delete tasks", false),
+    )
+}
+
+async fn describe_delete_task_cli(args: DescribeDeleteTaskArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "describe-delete-task");
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    let delete_task_status = qw_client
+        .delete_tasks(&args.index_id)
+        .get(args.opstamp)
+        .await?;
+    print_output(
+        args.output_format,
+        &delete_task_status,
+        |delete_task_status| {
+            make_delete_task_table(
+                std::slice::from_ref(delete_task_status),
+                "Delete task",
+                true,
+            )
+        },
+    )
+}
+
+async fn cancel_delete_task_cli(args: CancelDeleteTaskArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "cancel-delete-task");
+    println!("❯ Cancelling delete task...");
+    if !args.assume_yes {
+        let prompt = format!(
+            "This operation will cancel the delete task `{}` of index `{}`. Do you want to \
+             proceed?",
+            args.opstamp, args.index_id
+        );
+        if !prompt_confirmation(&prompt, false) {
+            return Ok(());
+        }
+    }
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    qw_client
+        .delete_tasks(&args.index_id)
+        .cancel(args.opstamp)
+        .await
+        .context("Failed to cancel delete task.")?;
+    println!(
+        "{} Delete task successfully cancelled.",
+        "✔".color(GREEN_COLOR)
+    );
+    Ok(())
+}
+
+fn make_delete_task_table(
+    delete_task_statuses: &[DeleteTaskStatus],
+    title: &str,
+    transpose: bool,
+) -> Table {
+    let rows = delete_task_statuses
+        .iter()
+        .map(|delete_task_status| {
+            let delete_task = &delete_task_status.delete_task;
+            let created_at = OffsetDateTime::from_unix_timestamp(delete_task.create_timestamp)
+                .expect("Failed to create `OffsetDateTime` from delete task create timestamp.");
+            let (query, time_range) = if let Some(delete_query) = &delete_task.delete_query {
+                let time_range = format!(
+                    "[{}..{}]",
+                    display_timestamp_opt(delete_query.start_timestamp),
+                    display_timestamp_opt(delete_query.end_timestamp)
+                );
+                (delete_query.query_ast.clone(), time_range)
+            } else {
+                (String::new(), "[*]".to_string())
+            };
+            DeleteTaskRow {
+                opstamp: delete_task.opstamp,
+                state: display_delete_task_state(delete_task_status.state),
+                created_at,
+                query,
+                time_range,
+                num_matched_splits: delete_task_status.num_matched_splits,
+                num_processed_splits: delete_task_status.num_processed_splits,
+                num_remaining_docs: delete_task_status.num_remaining_docs,
+                remaining_size_mega_bytes: delete_task_status.remaining_size_in_bytes / 1_000_000,
+            }
+        })
+        .sorted_by_key(|row| row.opstamp);
+    make_table(title, rows, transpose)
+}
+
+fn display_timestamp_opt(timestamp_opt: Option<i64>) -> String {
+    timestamp_opt
+        .map(|timestamp| timestamp.to_string())
+        .unwrap_or_else(|| "*".to_string())
+}
+
+fn display_delete_task_state(state: DeleteTaskState) -> &'static str {
+    match state {
+        DeleteTaskState::Pending => "pending",
+        DeleteTaskState::InProgress => "in progress",
+        DeleteTaskState::Completed => "completed",
+    }
+}
+
+fn parse_opstamp(opstamp_arg: &str) -> anyhow::Result<u64> {
+    opstamp_arg.parse::<u64>().with_context(|| {
+        format!("Failed to parse opstamp `{opstamp_arg}`. Expected a positive integer.")
+    })
+}
+
+#[derive(Tabled)]
+struct DeleteTaskRow {
+    #[tabled(rename = "Opstamp")]
+    opstamp: u64,
+    #[tabled(rename = "State")]
+    state: &'static str,
+    #[tabled(rename = "Created at")]
+    created_at: OffsetDateTime,
+    #[tabled(rename = "Query")]
+    query: String,
+    #[tabled(rename = "Time range")]
+    time_range: String,
+    #[tabled(rename = "Matched splits")]
+    num_matched_splits: usize,
+    #[tabled(rename = "Processed splits")]
+    num_processed_splits: usize,
+    #[tabled(rename = "Remaining docs")]
+    num_remaining_docs: usize,
+    #[tabled(rename = "Remaining size (MB)")]
+    remaining_size_mega_bytes: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::{build_cli, CliCommand};
+
+    #[test]
+    fn test_parse_list_delete_tasks_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "delete-task",
+            "list",
+            "--index",
+            "hdfs-logs",
+            "--output",
+            "pretty_json",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::DeleteTask(DeleteTaskCliCommand::List(ListDeleteTasksArgs {
+                index_id,
+                output_format: OutputFormat::PrettyJson,
+                ..
+            })) if index_id == "hdfs-logs"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_describe_delete_task_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "delete-task",
+            "describe",
+            "--index",
+            "hdfs-logs",
+            "--opstamp",
+            "42",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::DeleteTask(DeleteTaskCliCommand::Describe(DescribeDeleteTaskArgs {
+                index_id,
+                opstamp: 42,
+                ..
+            })) if index_id == "hdfs-logs"
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "delete-task",
+            "describe",
+            "--index",
+            "hdfs-logs",
+            "--opstamp",
+            "forty-two",
+        ])?;
+        CliCommand::parse_cli_args(&matches).unwrap_err();
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_cancel_delete_task_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "delete-task",
+            "cancel",
+            "--endpoint",
+            "https://quickwit-cluster.io",
+            "--index",
+            "hdfs-logs",
+            "--opstamp",
+            "42",
+            "--yes",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::DeleteTask(DeleteTaskCliCommand::Cancel(CancelDeleteTaskArgs {
+                cluster_endpoint,
+                index_id,
+                opstamp: 42,
+                assume_yes: true,
+            })) if cluster_endpoint == Url::from_str("https://quickwit-cluster.io").unwrap()
+                && index_id == "hdfs-logs"
+        ));
+        Ok(())
+    }
+}
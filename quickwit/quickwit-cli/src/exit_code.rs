@@ -0,0 +1,258 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::error::Error;
+use std::fmt;
+
+use quickwit_common::ChecklistError;
+use quickwit_metastore::{MetastoreError, MetastoreResolverError};
+use quickwit_rest_client::error::Error as RestClientError;
+use quickwit_storage::{StorageError, StorageErrorKind, StorageResolverError};
+use reqwest::StatusCode;
+
+/// Help text listing the exit codes of the CLI, displayed at the bottom of `quickwit --help`.
+pub const EXIT_CODES_HELP: &str = "EXIT CODES:
+    0    Success
+    1    Failure (unclassified error)
+    2    Invalid command arguments
+    3    Config error (invalid or missing config, invalid URI)
+    4    Connectivity error (cluster, metastore, storage or source unreachable)
+    5    Not found (index, source, split or delete task does not exist)
+    6    Conflict (resource already exists or was concurrently modified)
+    7    Partial failure (the command completed but some operations failed)";
+
+/// Exit codes returned by the CLI so that scripts can branch on the type of failure.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExitCode {
+    Success = 0,
+    Failure = 1,
+    InvalidArgs = 2,
+    ConfigError = 3,
+    ConnectivityError = 4,
+    NotFound = 5,
+    Conflict = 6,
+    PartialFailure = 7,
+}
+
+impl ExitCode {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+}
+
+/// Error tagged with the exit code the CLI should return. It displays exactly like the error it
+/// wraps.
+#[derive(Debug)]
+pub struct ExitCodeError {
+    exit_code: ExitCode,
+    error: anyhow::Error,
+}
+
+impl fmt::Display for ExitCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl Error for ExitCodeError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.error.source()
+    }
+}
+
+pub trait WithExitCode<T> {
+    /// Tags the error with the exit code the CLI should return.
+    fn with_exit_code(self, exit_code: ExitCode) -> anyhow::Result<T>;
+}
+
+impl<T, E> WithExitCode<T> for Result<T, E>
+where E: Into<anyhow::Error>
+{
+    fn with_exit_code(self, exit_code: ExitCode) -> anyhow::Result<T> {
+        self.map_err(|error| {
+            ExitCodeError {
+                exit_code,
+                error: error.into(),
+            }
+            .into()
+        })
+    }
+}
+
+/// Returns the exit code matching `error`. Errors explicitly tagged with
+/// [`WithExitCode::with_exit_code`] take precedence, otherwise the exit code is inferred from
+/// the first error of the chain whose type is known.
+pub fn exit_code_for_error(error: &anyhow::Error) -> ExitCode {
+    for cause in error.chain() {
+        if let Some(exit_code_error) = cause.downcast_ref::<ExitCodeError>() {
+            return exit_code_error.exit_code;
+        }
+    }
+    error
+        .chain()
+        .find_map(classify_error)
+        .unwrap_or(ExitCode::Failure)
+}
+
+fn classify_error(error: &(dyn Error + 'static)) -> Option<ExitCode> {
+    if let Some(rest_client_error) = error.downcast_ref::<RestClientError>() {
+        return classify_rest_client_error(rest_client_error);
+    }
+    if let Some(reqwest_error) = error.downcast_ref::<reqwest::Error>() {
+        return classify_reqwest_error(reqwest_error);
+    }
+    if let Some(metastore_error) = error.downcast_ref::<MetastoreError>() {
+        return classify_metastore_error(metastore_error);
+    }
+    if let Some(metastore_resolver_error) = error.downcast_ref::<MetastoreResolverError>() {
+        return match metastore_resolver_error {
+            MetastoreResolverError::InvalidUri(_)
+            | MetastoreResolverError::ProtocolUnsupported(_) => Some(ExitCode::ConfigError),
+            MetastoreResolverError::FailedToOpenMetastore(metastore_error) => {
+                classify_metastore_error(metastore_error).or(Some(ExitCode::ConnectivityError))
+            }
+        };
+    }
+    if let Some(storage_error) = error.downcast_ref::<StorageError>() {
+        return match storage_error.kind() {
+            StorageErrorKind::DoesNotExist => Some(ExitCode::NotFound),
+            _ => None,
+        };
+    }
+    if let Some(storage_resolver_error) = error.downcast_ref::<StorageResolverError>() {
+        return match storage_resolver_error {
+            StorageResolverError::InvalidUri { .. }
+            | StorageResolverError::ProtocolUnsupported { .. } => Some(ExitCode::ConfigError),
+            StorageResolverError::FailedToOpenStorage { .. } => Some(ExitCode::ConnectivityError),
+        };
+    }
+    if error.downcast_ref::<ChecklistError>().is_some() {
+        return Some(ExitCode::ConnectivityError);
+    }
+    None
+}
+
+fn classify_rest_client_error(error: &RestClientError) -> Option<ExitCode> {
+    match error {
+        RestClientError::Client(reqwest_error) => classify_reqwest_error(reqwest_error),
+        RestClientError::UrlParse(_) => Some(ExitCode::ConfigError),
+        _ => error.status_code().and_then(classify_status_code),
+    }
+}
+
+fn classify_reqwest_error(error: &reqwest::Error) -> Option<ExitCode> {
+    if error.is_connect() || error.is_timeout() {
+        return Some(ExitCode::ConnectivityError);
+    }
+    error.status().and_then(classify_status_code)
+}
+
+fn classify_status_code(status_code: StatusCode) -> Option<ExitCode> {
+    match status_code {
+        StatusCode::NOT_FOUND => Some(ExitCode::NotFound),
+        StatusCode::CONFLICT => Some(ExitCode::Conflict),
+        StatusCode::BAD_GATEWAY | StatusCode::SERVICE_UNAVAILABLE | StatusCode::GATEWAY_TIMEOUT => {
+            Some(ExitCode::ConnectivityError)
+        }
+        _ => None,
+    }
+}
+
+fn classify_metastore_error(error: &MetastoreError) -> Option<ExitCode> {
+    match error {
+        MetastoreError::ConnectionError { .. } => Some(ExitCode::ConnectivityError),
+        MetastoreError::IndexDoesNotExist { .. }
+        | MetastoreError::SourceDoesNotExist { .. }
+        | MetastoreError::SplitsDoNotExist { .. }
+        | MetastoreError::DeleteTaskDoesNotExist { .. } => Some(ExitCode::NotFound),
+        MetastoreError::IndexAlreadyExists { .. }
+        | MetastoreError::SourceAlreadyExists { .. }
+        | MetastoreError::IndexConfigVersionConflict { .. } => Some(ExitCode::Conflict),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+    use quickwit_rest_client::error::ApiError;
+
+    use super::*;
+
+    #[test]
+    fn test_exit_code_for_tagged_error() {
+        let result: anyhow::Result<()> = Err::<(), _>(anyhow::anyhow!("Failed to parse config."))
+            .with_exit_code(ExitCode::ConfigError)
+            .context("Failed to load quickwit config.");
+        let error = result.unwrap_err();
+        assert_eq!(exit_code_for_error(&error), ExitCode::ConfigError);
+        assert_eq!(exit_code_for_error(&error).code(), 3);
+        assert_eq!(
+            format!("{error:#}"),
+            "Failed to load quickwit config.: Failed to parse config."
+        );
+    }
+
+    #[test]
+    fn test_exit_code_for_typed_errors() {
+        let error = anyhow::anyhow!("Something went wrong.");
+        assert_eq!(exit_code_for_error(&error), ExitCode::Failure);
+
+        let error = anyhow::Error::new(MetastoreError::IndexDoesNotExist {
+            index_id: "test-index".to_string(),
+        })
+        .context("Failed to fetch index metadata.");
+        assert_eq!(exit_code_for_error(&error), ExitCode::NotFound);
+
+        let error = anyhow::Error::new(MetastoreError::IndexAlreadyExists {
+            index_id: "test-index".to_string(),
+        });
+        assert_eq!(exit_code_for_error(&error), ExitCode::Conflict);
+
+        let error = anyhow::Error::new(MetastoreResolverError::ProtocolUnsupported(
+            "ftp".to_string(),
+        ));
+        assert_eq!(exit_code_for_error(&error), ExitCode::ConfigError);
+
+        let error = anyhow::Error::new(MetastoreResolverError::FailedToOpenMetastore(
+            MetastoreError::ConnectionError {
+                message: "Connection refused.".to_string(),
+            },
+        ));
+        assert_eq!(exit_code_for_error(&error), ExitCode::ConnectivityError);
+
+        let error = anyhow::Error::new(
+            StorageErrorKind::DoesNotExist
+                .with_error(anyhow::anyhow!("File `split.split` does not exist.")),
+        );
+        assert_eq!(exit_code_for_error(&error), ExitCode::NotFound);
+
+        let error = anyhow::Error::new(RestClientError::Api(ApiError {
+            message: None,
+            code: StatusCode::NOT_FOUND,
+        }));
+        assert_eq!(exit_code_for_error(&error), ExitCode::NotFound);
+
+        let error = anyhow::Error::new(RestClientError::Api(ApiError {
+            message: None,
+            code: StatusCode::INTERNAL_SERVER_ERROR,
+        }));
+        assert_eq!(exit_code_for_error(&error), ExitCode::Failure);
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+use clap::{arg, ArgMatches, Command};
+use clap_complete::Shell;
+use clap_mangen::Man;
+use colored::Colorize;
+use quickwit_common::GREEN_COLOR;
+use tracing::debug;
+
+use crate::cli::build_cli;
+
+/// Name of the binary used in the completion scripts and the man pages.
+const BINARY_NAME: &str = "quickwit";
+
+pub fn build_generate_command<'a>() -> Command<'a> {
+    Command::new("generate")
+        .about("Generates shell completion scripts and man pages.")
+        .subcommand(
+            Command::new("completions")
+                .about("Generates the completion script of a shell and writes it to stdout.")
+                .long_about(
+                    "Generates the completion script of a shell and writes it to stdout. For \
+                     instance, `quickwit generate completions bash > \
+                     /usr/share/bash-completion/completions/quickwit` installs the completions \
+                     for bash.",
+                )
+                .args(
+                    &[arg!(<SHELL> "Shell to generate the completion script for.")
+                        .possible_values(&["bash", "elvish", "fish", "powershell", "zsh"])],
+                ),
+        )
+        .subcommand(
+            Command::new("man")
+                .about("Generates the man pages of the CLI and writes them to a directory.")
+                .long_about(
+                    "Generates the man page of the `quickwit` command and one man page per \
+                     subcommand, for instance `quickwit-index-create.1`, and writes them to a \
+                     directory.",
+                )
+                .args(&[
+                    arg!(--"output-dir" <OUTPUT_DIR> "Directory where the man pages are written.")
+                        .default_value(".")
+                        .required(false),
+                ]),
+        )
+        .arg_required_else_help(true)
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct GenerateCompletionsArgs {
+    pub shell: Shell,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct GenerateManPagesArgs {
+    pub output_dir: PathBuf,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum GenerateCliCommand {
+    Completions(GenerateCompletionsArgs),
+    ManPages(GenerateManPagesArgs),
+}
+
+impl GenerateCliCommand {
+    pub fn parse_cli_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let (subcommand, submatches) = matches
+            .subcommand()
+            .ok_or_else(|| anyhow::anyhow!("Failed to parse sub-matches."))?;
+        match subcommand {
+            "completions" => Self::parse_completions_args(submatches),
+            "man" => Self::parse_man_pages_args(submatches),
+            _ => bail!("Subcommand `{}` is not implemented.", subcommand),
+        }
+    }
+
+    fn parse_completions_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let shell = matches
+            .value_of("SHELL")
+            .map(Shell::from_str)
+            .expect("`SHELL` is a required arg.")
+            .map_err(|error| anyhow::anyhow!(error))?;
+        Ok(Self::Completions(GenerateCompletionsArgs { shell }))
+    }
+
+    fn parse_man_pages_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let output_dir = matches
+            .value_of("output-dir")
+            .map(PathBuf::from)
+            .expect("`output-dir` has a default value.");
+        Ok(Self::ManPages(GenerateManPagesArgs { output_dir }))
+    }
+
+    pub async fn execute(self) -> anyhow::Result<()> {
+        match self {
+            Self::Completions(args) => generate_completions_cli(args),
+            Self::ManPages(args) => generate_man_pages_cli(args),
+        }
+    }
+}
+
+fn generate_completions_cli(args: GenerateCompletionsArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "generate-completions");
+    generate_completions(args.shell, &mut io::stdout());
+    Ok(())
+}
+
+fn generate_completions(shell: Shell, writer: &mut dyn Write) {
+    let mut command = build_cli();
+    clap_complete::generate(shell, &mut command, BINARY_NAME, writer);
+}
+
+fn generate_man_pages_cli(args: GenerateManPagesArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "generate-man-pages");
+    fs::create_dir_all(&args.output_dir).with_context(|| {
+        format!(
+            "Failed to create output directory `{}`.",
+            args.output_dir.display()
+        )
+    })?;
+    let man_page_paths = generate_man_pages(&args.output_dir)?;
+    println!(
+        "{} Wrote {} man pages to `{}`.",
+        "✔".color(GREEN_COLOR),
+        man_page_paths.len(),
+        args.output_dir.display()
+    );
+    Ok(())
+}
+
+/// Writes the man page of the `quickwit` command and of each of its subcommands to
+/// `output_dir`. Returns the paths of the man pages.
+fn generate_man_pages(output_dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let command = build_cli().about("Sub-second search & analytics engine on cloud storage.");
+    let mut man_page_paths = Vec::new();
+    write_man_pages(
+        command,
+        BINARY_NAME.to_string(),
+        output_dir,
+        &mut man_page_paths,
+    )?;
+    Ok(man_page_paths)
+}
+
+/// Writes the man page of `command`, named after the full command path, for instance
+/// `quickwit-index-create.1`, then recurses into its subcommands.
+fn write_man_pages(
+    command: Command,
+    command_path: String,
+    output_dir: &Path,
+    man_page_paths: &mut Vec<PathBuf>,
+) -> anyhow::Result<()> {
+    let subcommands: Vec<Command> = command.get_subcommands().cloned().collect();
+
+    let man_page_path = output_dir.join(format!("{command_path}.1"));
+    let mut man_page_file = File::create(&man_page_path)
+        .with_context(|| format!("Failed to create man page `{}`.", man_page_path.display()))?;
+    Man::new(command.name(command_path.clone())).render(&mut man_page_file)?;
+    man_page_paths.push(man_page_path);
+
+    for subcommand in subcommands {
+        let subcommand_path = format!("{command_path}-{}", subcommand.get_name());
+        write_man_pages(subcommand, subcommand_path, output_dir, man_page_paths)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::CliCommand;
+
+    #[test]
+    fn test_parse_generate_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec!["generate", "completions", "zsh"])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_command =
+            CliCommand::Generate(GenerateCliCommand::Completions(GenerateCompletionsArgs {
+                shell: Shell::Zsh,
+            }));
+        assert_eq!(command, expected_command);
+
+        let app = build_cli().no_binary_name(true);
+        let matches =
+            app.try_get_matches_from(vec!["generate", "man", "--output-dir", "/tmp/man"])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_command =
+            CliCommand::Generate(GenerateCliCommand::ManPages(GenerateManPagesArgs {
+                output_dir: PathBuf::from("/tmp/man"),
+            }));
+        assert_eq!(command, expected_command);
+
+        let app = build_cli().no_binary_name(true);
+        assert!(app
+            .try_get_matches_from(vec!["generate", "completions", "cmd"])
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_completions() {
+        let mut completion_script = Vec::new();
+        generate_completions(Shell::Bash, &mut completion_script);
+        let completion_script = String::from_utf8(completion_script).unwrap();
+        assert!(completion_script.contains("complete -F _quickwit"));
+        assert!(completion_script.contains("local-ingest"));
+    }
+
+    #[test]
+    fn test_generate_man_pages() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let man_page_paths = generate_man_pages(temp_dir.path()).unwrap();
+        assert!(man_page_paths.len() > 1);
+
+        for man_page_name in ["quickwit.1", "quickwit-index.1", "quickwit-index-create.1"] {
+            let man_page_path = temp_dir.path().join(man_page_name);
+            assert!(man_page_paths.contains(&man_page_path));
+        }
+        let man_page = fs::read_to_string(temp_dir.path().join("quickwit-index-create.1")).unwrap();
+        assert!(man_page.contains("Creates an index from an index config file."));
+    }
+}
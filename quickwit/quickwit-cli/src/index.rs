@@ -17,44 +17,56 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fmt::Display;
-use std::io::{stdout, Stdout, Write};
-use std::path::PathBuf;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::time::{Duration, Instant};
-use std::{fmt, io};
+use std::time::Duration;
 
 use anyhow::{bail, Context};
 use byte_unit::Byte;
 use bytes::Bytes;
 use clap::{arg, Arg, ArgMatches, Command};
-use colored::{ColoredString, Colorize};
-use humantime::format_duration;
-use indicatif::{ProgressBar, ProgressStyle};
+use colored::Colorize;
 use itertools::Itertools;
-use quickwit_actors::{ActorHandle, ObservationType};
 use quickwit_common::uri::Uri;
-use quickwit_common::GREEN_COLOR;
-use quickwit_config::{ConfigFormat, IndexConfig};
-use quickwit_indexing::models::IndexingStatistics;
-use quickwit_indexing::IndexingPipeline;
-use quickwit_metastore::{IndexMetadata, Split, SplitState};
+use quickwit_common::{split_file, GREEN_COLOR};
+use quickwit_config::{build_doc_mapper, ConfigFormat, IndexConfig, MetastoreConfig};
+use quickwit_core::IndexService;
+use quickwit_doc_mapper::{DocMapper, SOURCE_FIELD_NAME};
+use quickwit_indexing::get_tantivy_directory_from_split_bundle;
+use quickwit_metastore::{
+    aggregate_field_stats, quickwit_metastore_uri_resolver, IndexFieldStats, IndexMetadata,
+    ListSplitsQuery, Split, SplitMetadata, SplitState,
+};
 use quickwit_proto::SortOrder;
 use quickwit_rest_client::models::IngestSource;
 use quickwit_rest_client::rest_client::{CommitType, IngestEvent, QuickwitClient, Transport};
-use quickwit_search::SearchResponseRest;
-use quickwit_serve::{ListSplitsQueryParams, SearchRequestQueryString, SortByField};
-use quickwit_storage::load_file;
+use quickwit_search::{SearchResponseRest, SqlResponse};
+use quickwit_serve::{
+    DeleteQueryRequest, ListSplitsQueryParams, SearchRequestQueryString, SortByField,
+    SqlRequestQueryString,
+};
+use quickwit_storage::{load_file, quickwit_storage_uri_resolver, Storage};
 use quickwit_telemetry::payload::TelemetryEvent;
 use reqwest::Url;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value as JsonValue};
 use tabled::object::{Columns, Segment};
 use tabled::{Alignment, Concat, Format, Modify, Panel, Rotate, Style, Table, Tabled};
+use tantivy::schema::NamedFieldDocument;
+use tantivy::{DocAddress, Index, ReloadPolicy, SegmentOrdinal};
 use thousands::Separable;
-use tracing::{debug, Level};
+use tracing::{debug, warn, Level};
 
 use crate::stats::{mean, percentile, std_deviation};
-use crate::{cluster_endpoint_arg, make_table, prompt_confirmation, THROUGHPUT_WINDOW_SIZE};
+use crate::tool::display_split_removal_info;
+use crate::{
+    bytes_progress_bar, cluster_endpoint_arg, docs_progress_bar, make_table, parse_output_format,
+    parse_watch_interval, print_output, prompt_confirmation, spinner, watch, watch_arg,
+    OutputFormat,
+};
 
 pub fn build_index_command<'a>() -> Command<'a> {
     Command::new("index")
@@ -70,6 +82,67 @@ pub fn build_index_command<'a>() -> Command<'a> {
                         .required(false),
                 ])
             )
+        .subcommand(
+            Command::new("update")
+                .display_order(2)
+                .about("Updates an index from an index config file.")
+                .long_about("Updates the config of an index from an index config file. The index ID, index URI, and doc mapping cannot be updated.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1),
+                    arg!(--"index-config" <INDEX_CONFIG> "Location of the index config file."),
+                    arg!(--"expected-config-version" <VERSION> "Config version the index is expected to be at. The update fails if the index config was updated in the meantime.")
+                        .required(false),
+                ])
+                .subcommand(
+                    Command::new("search-settings")
+                        .about("Updates the search settings of an index.")
+                        .args(&[
+                            arg!(--index <INDEX> "ID of the target index")
+                                .display_order(1),
+                            arg!(--"default-search-fields" <FIELD_NAME> "List of fields searched when the query does not target a field explicitly. Space-separated list, e.g. \"field1 field2\". Pass no value to clear the list.")
+                                .multiple_values(true)
+                                .min_values(0),
+                        ])
+                )
+                .subcommand(
+                    Command::new("retention-policy")
+                        .about("Updates the retention policy of an index.")
+                        .long_about("Updates the retention policy of an index. The settings that are not passed keep their current value.")
+                        .args(&[
+                            arg!(--index <INDEX> "ID of the target index")
+                                .display_order(1),
+                            arg!(--period <PERIOD> "Duration for which the splits are retained, e.g. `30 days`.")
+                                .required(false),
+                            arg!(--"max-size" <MAX_SIZE> "Maximum total size of the published splits of the index, e.g. `500GB`.")
+                                .required(false),
+                            arg!(--schedule <SCHEDULE> "Frequency at which the retention policy is evaluated, e.g. `hourly`, `daily`, or a cron expression.")
+                                .required(false),
+                            arg!(--disable "Removes the retention policy of the index.")
+                                .required(false)
+                                .conflicts_with_all(&["period", "max-size", "schedule"]),
+                        ])
+                )
+                .subcommand(
+                    Command::new("indexing-settings")
+                        .about("Updates the indexing settings of an index.")
+                        .long_about("Updates the indexing settings of an index. The settings that are not passed keep their current value.")
+                        .args(&[
+                            arg!(--index <INDEX> "ID of the target index")
+                                .display_order(1),
+                            arg!(--"commit-timeout-secs" <SECS> "Maximum time in seconds before the documents of a split are committed.")
+                                .required(false),
+                            arg!(--"split-num-docs-target" <NUM_DOCS> "Number of documents of the splits the merge policy aims at.")
+                                .required(false),
+                            arg!(--"heap-size" <HEAP_SIZE> "Heap size of the indexer of each indexing pipeline, e.g. `2GB`.")
+                                .required(false),
+                            arg!(--"max-merge-write-throughput" <THROUGHPUT> "Maximum write throughput of the merge pipelines, e.g. `20MB`.")
+                                .required(false),
+                        ])
+                )
+                .args_conflicts_with_subcommands(true)
+                .subcommand_negates_reqs(true)
+            )
         .subcommand(
             Command::new("clear")
                 .display_order(2)
@@ -94,14 +167,104 @@ pub fn build_index_command<'a>() -> Command<'a> {
                         .required(false),
                 ])
             )
+        .subcommand(
+            Command::new("delete-by-query")
+                .display_order(3)
+                .about("Deletes the documents of an index matching a query.")
+                .long_about("Displays the estimated number of documents matching the query and the published splits whose time range overlaps the query, then submits a delete task after confirmation. The documents are deleted asynchronously by the janitor: use `quickwit delete-task describe` to follow the progress of the delete task. The query must target fields explicitly, for instance `level:debug`.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1),
+                    arg!(--query <QUERY> "Query matching the documents to delete, expressed in natural query language, for instance `level:debug AND service:payments`.")
+                        .display_order(2),
+                    arg!(--"start-timestamp" <TIMESTAMP> "Restricts the deletion to the documents whose timestamp is greater than or equal to that timestamp in seconds (time-series indexes only).")
+                        .required(false),
+                    arg!(--"end-timestamp" <TIMESTAMP> "Restricts the deletion to the documents whose timestamp is less than that timestamp in seconds (time-series indexes only).")
+                        .required(false),
+                ])
+            )
+        .subcommand(
+            Command::new("restore")
+                .display_order(3)
+                .about("Restores an index from the trash.")
+                .long_about("Restores an index that was deleted while the index trash is enabled and whose trash retention period has not expired yet.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1),
+                ])
+            )
+        .subcommand(
+            Command::new("pause-merges")
+                .display_order(3)
+                .about("Pauses the merges of an index.")
+                .long_about("Stops planning new merges for an index until they are resumed, for instance during a massive backfill or an incident. Ongoing merges run to completion. The setting is persisted in the metastore.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1),
+                ])
+            )
+        .subcommand(
+            Command::new("resume-merges")
+                .display_order(3)
+                .about("Resumes the merges of an index.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1),
+                ])
+            )
+        .subcommand(
+            Command::new("gc")
+                .display_order(3)
+                .about("Garbage collects the stale staged splits and the splits marked for deletion of an index.")
+                .long_about("Triggers an immediate garbage collection run on an index, without waiting for the janitor. Stale staged splits are selected according to the staged split grace period of the index.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1),
+                    arg!(--"dry-run" "Executes the command in dry run mode and only displays the list of splits candidates for garbage collection.")
+                        .required(false),
+                ])
+            )
+        .subcommand(
+            Command::new("clone")
+                .display_order(3)
+                .about("Clones an index from one metastore to another.")
+                .long_about("Copies the published splits of an index and registers them into a target metastore, for instance to promote an index from staging to production or to migrate it to another bucket. The split files are copied only if the target index URI differs from the source index URI. Sources and checkpoints are not cloned. This command does not require a running Quickwit service, but no indexing pipeline should be publishing splits to the source index in the meantime.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the source index")
+                        .display_order(1),
+                    arg!(--from <METASTORE_URI> "URI of the source metastore, for instance `s3://staging-bucket/indexes`.")
+                        .display_order(2),
+                    arg!(--to <METASTORE_URI> "URI of the target metastore, for instance `s3://prod-bucket/indexes`.")
+                        .display_order(3),
+                    arg!(--"target-index" <TARGET_INDEX> "ID of the target index. Defaults to the ID of the source index.")
+                        .required(false),
+                    arg!(--"target-index-uri" <TARGET_INDEX_URI> "URI of the target index. Defaults to the URI of the source index.")
+                        .required(false),
+                ])
+            )
+        .subcommand(
+            Command::new("reindex")
+                .display_order(3)
+                .about("Reindexes the documents of an index into another index.")
+                .long_about("Reads the documents from the doc store of the published splits of an index and ingests them into a target index, for instance to apply a new doc mapping without access to the original data sources. Only the stored fields are reindexed, or the original documents if the source index stores them (`store_source: true`). The target index must be created beforehand. The reindexed splits are recorded in a checkpoint file, so an interrupted reindex resumes where it stopped when it is run again with the same checkpoint file. The documents of the split being reindexed when the reindex was interrupted are ingested again. The reindex does not resume if some of the splits left to reindex were merged and garbage collected in the meantime. The splits are downloaded one at a time to a temporary directory.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the source index")
+                        .display_order(1),
+                    arg!(--"target-index" <TARGET_INDEX> "ID of the target index")
+                        .display_order(2),
+                    arg!(--checkpoint <CHECKPOINT_FILE> "Location of the checkpoint file recording the progress of the reindex. Defaults to `reindex-<index>-<target-index>.json` in the current directory.")
+                        .required(false),
+                ])
+            )
         .subcommand(
             Command::new("describe")
                 .display_order(4)
                 .about("Displays descriptive statistics of an index.")
-                .long_about("Displays descriptive statistics of an index. Displayed statistics are: number of published splits, number of documents, splits min/max timestamps, size of splits.")
+                .long_about("Displays descriptive statistics of an index. Displayed statistics are: number of published splits, number of documents, splits min/max timestamps, size of splits, and per-field statistics (documents with and without a value, number of values and tokens, average value length, fast field size). With `--watch`, the statistics are refreshed periodically to monitor the ingestion progress.")
                 .args(&[
                     arg!(--index <INDEX> "ID of the target index")
                         .display_order(1),
+                    watch_arg(),
                 ])
             )
         .subcommand(
@@ -164,6 +327,14 @@ pub fn build_index_command<'a>() -> Command<'a> {
                         .required(false),
                 ])
             )
+        .subcommand(
+            Command::new("sql")
+                .display_order(8)
+                .about("Runs a SQL query over an index.")
+                .args(&[
+                    arg!(--query <QUERY> "SQL SELECT statement naming the target index in its FROM clause (SELECT service, count(*) FROM logs GROUP BY service)."),
+                ])
+            )
         .arg_required_else_help(true)
 }
 
@@ -182,10 +353,48 @@ pub struct CreateIndexArgs {
     pub assume_yes: bool,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct UpdateIndexArgs {
+    pub cluster_endpoint: Url,
+    pub index_id: String,
+    pub index_config_uri: Uri,
+    pub expected_config_version_opt: Option<u64>,
+}
+
+/// A partial update of the config of an index.
+#[derive(Debug, Eq, PartialEq)]
+pub enum IndexConfigUpdate {
+    SearchSettings {
+        default_search_fields: Vec<String>,
+    },
+    RetentionPolicy {
+        period_opt: Option<String>,
+        max_size_opt: Option<Byte>,
+        schedule_opt: Option<String>,
+    },
+    DisableRetentionPolicy,
+    IndexingSettings {
+        commit_timeout_secs_opt: Option<usize>,
+        split_num_docs_target_opt: Option<usize>,
+        heap_size_opt: Option<Byte>,
+        max_merge_write_throughput_opt: Option<Byte>,
+    },
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct UpdateIndexSettingsArgs {
+    pub cluster_endpoint: Url,
+    pub index_id: String,
+    pub index_config_update: IndexConfigUpdate,
+    pub assume_yes: bool,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct DescribeIndexArgs {
     pub cluster_endpoint: Url,
     pub index_id: String,
+    pub output_format: OutputFormat,
+    pub watch_interval_opt: Option<Duration>,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -211,6 +420,12 @@ pub struct SearchIndexArgs {
     pub sort_by_score: bool,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct SqlIndexArgs {
+    pub cluster_endpoint: Url,
+    pub query: String,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct DeleteIndexArgs {
     pub cluster_endpoint: Url,
@@ -219,26 +434,83 @@ pub struct DeleteIndexArgs {
     pub assume_yes: bool,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct DeleteByQueryArgs {
+    pub cluster_endpoint: Url,
+    pub index_id: String,
+    pub query: String,
+    pub start_timestamp: Option<i64>,
+    pub end_timestamp: Option<i64>,
+    pub assume_yes: bool,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct RestoreIndexArgs {
+    pub cluster_endpoint: Url,
+    pub index_id: String,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ToggleMergesArgs {
+    pub cluster_endpoint: Url,
+    pub index_id: String,
+    pub pause: bool,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct GarbageCollectIndexArgs {
+    pub cluster_endpoint: Url,
+    pub index_id: String,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct CloneIndexArgs {
+    pub source_metastore_uri: Uri,
+    pub target_metastore_uri: Uri,
+    pub index_id: String,
+    pub target_index_id: String,
+    pub target_index_uri_opt: Option<Uri>,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ReindexArgs {
+    pub cluster_endpoint: Url,
+    pub index_id: String,
+    pub target_index_id: String,
+    pub checkpoint_path: PathBuf,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct ListIndexesArgs {
     pub cluster_endpoint: Url,
+    pub output_format: OutputFormat,
 }
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum IndexCliCommand {
     Clear(ClearIndexArgs),
+    Clone(CloneIndexArgs),
     Create(CreateIndexArgs),
     Delete(DeleteIndexArgs),
+    DeleteByQuery(DeleteByQueryArgs),
     Describe(DescribeIndexArgs),
+    GarbageCollect(GarbageCollectIndexArgs),
     Ingest(IngestDocsArgs),
     List(ListIndexesArgs),
+    Reindex(ReindexArgs),
+    Restore(RestoreIndexArgs),
     Search(SearchIndexArgs),
+    Sql(SqlIndexArgs),
+    ToggleMerges(ToggleMergesArgs),
+    Update(UpdateIndexArgs),
+    UpdateSettings(UpdateIndexSettingsArgs),
 }
 
 impl IndexCliCommand {
     pub fn default_log_level(&self) -> Level {
         match self {
-            Self::Search(_) => Level::ERROR,
+            Self::Search(_) | Self::Sql(_) => Level::ERROR,
             _ => Level::INFO,
         }
     }
@@ -249,12 +521,21 @@ impl IndexCliCommand {
             .ok_or_else(|| anyhow::anyhow!("Failed to parse sub-matches."))?;
         match subcommand {
             "clear" => Self::parse_clear_args(submatches),
+            "clone" => Self::parse_clone_args(submatches),
             "create" => Self::parse_create_args(submatches),
             "delete" => Self::parse_delete_args(submatches),
+            "delete-by-query" => Self::parse_delete_by_query_args(submatches),
             "describe" => Self::parse_describe_args(submatches),
+            "gc" => Self::parse_garbage_collect_args(submatches),
             "ingest" => Self::parse_ingest_args(submatches),
             "list" => Self::parse_list_args(submatches),
+            "pause-merges" => Self::parse_toggle_merges_args(submatches, true),
+            "reindex" => Self::parse_reindex_args(submatches),
+            "restore" => Self::parse_restore_args(submatches),
+            "resume-merges" => Self::parse_toggle_merges_args(submatches, false),
             "search" => Self::parse_search_args(submatches),
+            "sql" => Self::parse_sql_args(submatches),
+            "update" => Self::parse_update_args(submatches),
             _ => bail!("Index subcommand `{}` is not implemented.", subcommand),
         }
     }
@@ -296,6 +577,103 @@ impl IndexCliCommand {
         }))
     }
 
+    fn parse_update_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        if let Some((subcommand, submatches)) = matches.subcommand() {
+            return Self::parse_update_settings_args(subcommand, submatches);
+        }
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .expect("`index` is a required arg.")
+            .to_string();
+        let index_config_uri = matches
+            .value_of("index-config")
+            .map(Uri::from_str)
+            .expect("`index-config` is a required arg.")?;
+        let expected_config_version_opt = if matches.is_present("expected-config-version") {
+            Some(matches.value_of_t::<u64>("expected-config-version")?)
+        } else {
+            None
+        };
+        Ok(Self::Update(UpdateIndexArgs {
+            cluster_endpoint,
+            index_id,
+            index_config_uri,
+            expected_config_version_opt,
+        }))
+    }
+
+    fn parse_update_settings_args(subcommand: &str, matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .expect("`index` is a required arg.")
+            .to_string();
+        let parse_byte = |arg_name: &str| -> anyhow::Result<Option<Byte>> {
+            matches
+                .value_of(arg_name)
+                .map(|value| {
+                    Byte::from_str(value)
+                        .map_err(|error| anyhow::anyhow!("Invalid `{arg_name}` value: {error}."))
+                })
+                .transpose()
+        };
+        let index_config_update = match subcommand {
+            "search-settings" => {
+                let default_search_fields = matches
+                    .values_of("default-search-fields")
+                    .map(|values| values.map(|value| value.to_string()).collect())
+                    .unwrap_or_default();
+                IndexConfigUpdate::SearchSettings {
+                    default_search_fields,
+                }
+            }
+            "retention-policy" if matches.is_present("disable") => {
+                IndexConfigUpdate::DisableRetentionPolicy
+            }
+            "retention-policy" => IndexConfigUpdate::RetentionPolicy {
+                period_opt: matches.value_of("period").map(|value| value.to_string()),
+                max_size_opt: parse_byte("max-size")?,
+                schedule_opt: matches.value_of("schedule").map(|value| value.to_string()),
+            },
+            "indexing-settings" => {
+                let commit_timeout_secs_opt = if matches.is_present("commit-timeout-secs") {
+                    Some(matches.value_of_t::<usize>("commit-timeout-secs")?)
+                } else {
+                    None
+                };
+                let split_num_docs_target_opt = if matches.is_present("split-num-docs-target") {
+                    Some(matches.value_of_t::<usize>("split-num-docs-target")?)
+                } else {
+                    None
+                };
+                IndexConfigUpdate::IndexingSettings {
+                    commit_timeout_secs_opt,
+                    split_num_docs_target_opt,
+                    heap_size_opt: parse_byte("heap-size")?,
+                    max_merge_write_throughput_opt: parse_byte("max-merge-write-throughput")?,
+                }
+            }
+            _ => bail!(
+                "Index update subcommand `{}` is not implemented.",
+                subcommand
+            ),
+        };
+        let assume_yes = matches.is_present("yes");
+        Ok(Self::UpdateSettings(UpdateIndexSettingsArgs {
+            cluster_endpoint,
+            index_id,
+            index_config_update,
+            assume_yes,
+        }))
+    }
+
     fn parse_describe_args(matches: &ArgMatches) -> anyhow::Result<Self> {
         let cluster_endpoint = matches
             .value_of("endpoint")
@@ -305,9 +683,13 @@ impl IndexCliCommand {
             .value_of("index")
             .expect("`index` is a required arg.")
             .to_string();
+        let output_format = parse_output_format(matches)?;
+        let watch_interval_opt = parse_watch_interval(matches)?;
         Ok(Self::Describe(DescribeIndexArgs {
             cluster_endpoint,
             index_id,
+            output_format,
+            watch_interval_opt,
         }))
     }
 
@@ -316,7 +698,11 @@ impl IndexCliCommand {
             .value_of("endpoint")
             .map(Url::from_str)
             .expect("`endpoint` is a required arg.")?;
-        Ok(Self::List(ListIndexesArgs { cluster_endpoint }))
+        let output_format = parse_output_format(matches)?;
+        Ok(Self::List(ListIndexesArgs {
+            cluster_endpoint,
+            output_format,
+        }))
     }
 
     fn parse_ingest_args(matches: &ArgMatches) -> anyhow::Result<Self> {
@@ -399,84 +785,818 @@ impl IndexCliCommand {
         }))
     }
 
-    fn parse_delete_args(matches: &ArgMatches) -> anyhow::Result<Self> {
-        let cluster_endpoint = matches
-            .value_of("endpoint")
-            .map(Url::from_str)
-            .expect("`endpoint` is a required arg.")?;
-        let index_id = matches
-            .value_of("index")
-            .expect("`index` is a required arg.")
-            .to_string();
-        let dry_run = matches.is_present("dry-run");
-        let assume_yes = matches.is_present("yes");
-        Ok(Self::Delete(DeleteIndexArgs {
-            index_id,
-            dry_run,
-            cluster_endpoint,
-            assume_yes,
-        }))
+    fn parse_sql_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let query = matches
+            .value_of("query")
+            .context("`query` is a required arg.")?
+            .to_string();
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        Ok(Self::Sql(SqlIndexArgs {
+            cluster_endpoint,
+            query,
+        }))
+    }
+
+    fn parse_delete_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .expect("`index` is a required arg.")
+            .to_string();
+        let dry_run = matches.is_present("dry-run");
+        let assume_yes = matches.is_present("yes");
+        Ok(Self::Delete(DeleteIndexArgs {
+            index_id,
+            dry_run,
+            cluster_endpoint,
+            assume_yes,
+        }))
+    }
+
+    fn parse_delete_by_query_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .expect("`index` is a required arg.")
+            .to_string();
+        let query = matches
+            .value_of("query")
+            .expect("`query` is a required arg.")
+            .to_string();
+        let start_timestamp = if matches.is_present("start-timestamp") {
+            Some(matches.value_of_t::<i64>("start-timestamp")?)
+        } else {
+            None
+        };
+        let end_timestamp = if matches.is_present("end-timestamp") {
+            Some(matches.value_of_t::<i64>("end-timestamp")?)
+        } else {
+            None
+        };
+        let assume_yes = matches.is_present("yes");
+        Ok(Self::DeleteByQuery(DeleteByQueryArgs {
+            cluster_endpoint,
+            index_id,
+            query,
+            start_timestamp,
+            end_timestamp,
+            assume_yes,
+        }))
+    }
+
+    fn parse_restore_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .expect("`index` is a required arg.")
+            .to_string();
+        Ok(Self::Restore(RestoreIndexArgs {
+            cluster_endpoint,
+            index_id,
+        }))
+    }
+
+    fn parse_toggle_merges_args(matches: &ArgMatches, pause: bool) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .expect("`index` is a required arg.")
+            .to_string();
+        Ok(Self::ToggleMerges(ToggleMergesArgs {
+            cluster_endpoint,
+            index_id,
+            pause,
+        }))
+    }
+
+    fn parse_garbage_collect_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .expect("`index` is a required arg.")
+            .to_string();
+        let dry_run = matches.is_present("dry-run");
+        Ok(Self::GarbageCollect(GarbageCollectIndexArgs {
+            cluster_endpoint,
+            index_id,
+            dry_run,
+        }))
+    }
+
+    fn parse_reindex_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .expect("`index` is a required arg.")
+            .to_string();
+        let target_index_id = matches
+            .value_of("target-index")
+            .expect("`target-index` is a required arg.")
+            .to_string();
+        let checkpoint_path = matches
+            .value_of("checkpoint")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(format!("reindex-{index_id}-{target_index_id}.json")));
+        Ok(Self::Reindex(ReindexArgs {
+            cluster_endpoint,
+            index_id,
+            target_index_id,
+            checkpoint_path,
+        }))
+    }
+
+    fn parse_clone_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let source_metastore_uri = matches
+            .value_of("from")
+            .map(Uri::from_str)
+            .expect("`from` is a required arg.")?;
+        let target_metastore_uri = matches
+            .value_of("to")
+            .map(Uri::from_str)
+            .expect("`to` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .expect("`index` is a required arg.")
+            .to_string();
+        let target_index_id = matches
+            .value_of("target-index")
+            .unwrap_or(&index_id)
+            .to_string();
+        let target_index_uri_opt = matches
+            .value_of("target-index-uri")
+            .map(Uri::from_str)
+            .transpose()?;
+        Ok(Self::Clone(CloneIndexArgs {
+            source_metastore_uri,
+            target_metastore_uri,
+            index_id,
+            target_index_id,
+            target_index_uri_opt,
+        }))
+    }
+
+    pub async fn execute(self) -> anyhow::Result<()> {
+        match self {
+            Self::Clear(args) => clear_index_cli(args).await,
+            Self::Clone(args) => clone_index_cli(args).await,
+            Self::Create(args) => create_index_cli(args).await,
+            Self::Delete(args) => delete_index_cli(args).await,
+            Self::DeleteByQuery(args) => delete_by_query_cli(args).await,
+            Self::Describe(args) => describe_index_cli(args).await,
+            Self::GarbageCollect(args) => garbage_collect_index_cli(args).await,
+            Self::Ingest(args) => ingest_docs_cli(args).await,
+            Self::List(args) => list_index_cli(args).await,
+            Self::Reindex(args) => reindex_cli(args).await,
+            Self::Restore(args) => restore_index_cli(args).await,
+            Self::Search(args) => search_index_cli(args).await,
+            Self::Sql(args) => sql_index_cli(args).await,
+            Self::ToggleMerges(args) => toggle_merges_cli(args).await,
+            Self::Update(args) => update_index_cli(args).await,
+            Self::UpdateSettings(args) => update_index_settings_cli(args).await,
+        }
+    }
+}
+
+pub async fn clear_index_cli(args: ClearIndexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "clear-index");
+    if !args.assume_yes {
+        let prompt = format!(
+            "This operation will delete all the splits of the index `{}` and reset its \
+             checkpoint. Do you want to proceed?",
+            args.index_id
+        );
+        if !prompt_confirmation(&prompt, false) {
+            return Ok(());
+        }
+    }
+    let endpoint = args.cluster_endpoint;
+    let transport = Transport::new(endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    qw_client.indexes().clear(&args.index_id).await?;
+    println!("{} Index successfully cleared.", "✔".color(GREEN_COLOR),);
+    Ok(())
+}
+
+pub async fn create_index_cli(args: CreateIndexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "create-index");
+    println!("❯ Creating index...");
+    quickwit_telemetry::send_telemetry_event(TelemetryEvent::Create).await;
+    let file_content = load_file(&args.index_config_uri).await?;
+    let config_format = ConfigFormat::sniff_from_uri(&args.index_config_uri)?;
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    // TODO: nice to have: check first if the index exists by send a GET request, if we get a 404,
+    // the index does not exist. If it exists, we can display the prompt.
+    if args.overwrite && !args.assume_yes {
+        // Stop if user answers no.
+        let prompt = "This operation will overwrite the index and delete all its data. Do you \
+                      want to proceed?"
+            .to_string();
+        if !prompt_confirmation(&prompt, false) {
+            return Ok(());
+        }
+    }
+    let bytes = Bytes::from(file_content.to_vec());
+    qw_client
+        .indexes()
+        .create(bytes, config_format, args.overwrite)
+        .await?;
+    println!("{} Index successfully created.", "✔".color(GREEN_COLOR));
+    Ok(())
+}
+
+pub async fn update_index_cli(args: UpdateIndexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "update-index");
+    println!("❯ Updating index...");
+    let file_content = load_file(&args.index_config_uri).await?;
+    let config_format = ConfigFormat::sniff_from_uri(&args.index_config_uri)?;
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    let bytes = Bytes::from(file_content.to_vec());
+    let index_metadata = qw_client
+        .indexes()
+        .update(
+            &args.index_id,
+            bytes,
+            config_format,
+            args.expected_config_version_opt,
+        )
+        .await?;
+    println!(
+        "{} Index successfully updated (config version: {}).",
+        "✔".color(GREEN_COLOR),
+        index_metadata.config_version
+    );
+    Ok(())
+}
+
+pub async fn update_index_settings_cli(args: UpdateIndexSettingsArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "update-index-settings");
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    let index_metadata = qw_client.indexes().get(&args.index_id).await?;
+    let config_version = index_metadata.config_version;
+
+    let current_index_config_json = serde_json::to_value(index_metadata.into_index_config())?;
+    let mut new_index_config_json = current_index_config_json.clone();
+    apply_index_config_update(&args.index_config_update, &mut new_index_config_json);
+    let new_index_config: IndexConfig = serde_json::from_value(new_index_config_json)
+        .context("Failed to validate the updated index config.")?;
+
+    let new_index_config_json = serde_json::to_value(&new_index_config)?;
+    let changes = diff_index_configs(&current_index_config_json, &new_index_config_json);
+    if changes.is_empty() {
+        println!("Index config is already up to date.");
+        return Ok(());
+    }
+    println!("❯ Index config changes:");
+    for change in &changes {
+        if let Some(current_value) = change.current_value_opt {
+            println!("{}", format!("- {}: {current_value}", change.path).red());
+        }
+        if let Some(new_value) = change.new_value_opt {
+            println!("{}", format!("+ {}: {new_value}", change.path).green());
+        }
+    }
+    if !args.assume_yes && !prompt_confirmation("Do you want to apply these changes?", false) {
+        return Ok(());
+    }
+    let bytes = Bytes::from(serde_json::to_vec(&new_index_config)?);
+    let index_metadata = qw_client
+        .indexes()
+        .update(
+            &args.index_id,
+            bytes,
+            ConfigFormat::Json,
+            Some(config_version),
+        )
+        .await?;
+    println!(
+        "{} Index successfully updated (config version: {}).",
+        "✔".color(GREEN_COLOR),
+        index_metadata.config_version
+    );
+    Ok(())
+}
+
+/// Applies a partial update to the JSON representation of an index config.
+fn apply_index_config_update(
+    index_config_update: &IndexConfigUpdate,
+    index_config_json: &mut JsonValue,
+) {
+    match index_config_update {
+        IndexConfigUpdate::SearchSettings {
+            default_search_fields,
+        } => {
+            index_config_json["search_settings"]["default_search_fields"] =
+                json!(default_search_fields);
+        }
+        IndexConfigUpdate::RetentionPolicy {
+            period_opt,
+            max_size_opt,
+            schedule_opt,
+        } => {
+            let retention_policy_json = &mut index_config_json["retention"];
+            if let Some(period) = period_opt {
+                retention_policy_json["period"] = json!(period);
+            }
+            if let Some(max_size) = max_size_opt {
+                retention_policy_json["max_size"] = json!(max_size);
+            }
+            if let Some(schedule) = schedule_opt {
+                retention_policy_json["schedule"] = json!(schedule);
+            }
+        }
+        IndexConfigUpdate::DisableRetentionPolicy => {
+            index_config_json["retention"] = JsonValue::Null;
+        }
+        IndexConfigUpdate::IndexingSettings {
+            commit_timeout_secs_opt,
+            split_num_docs_target_opt,
+            heap_size_opt,
+            max_merge_write_throughput_opt,
+        } => {
+            let indexing_settings_json = &mut index_config_json["indexing_settings"];
+            if let Some(commit_timeout_secs) = commit_timeout_secs_opt {
+                indexing_settings_json["commit_timeout_secs"] = json!(commit_timeout_secs);
+            }
+            if let Some(split_num_docs_target) = split_num_docs_target_opt {
+                indexing_settings_json["split_num_docs_target"] = json!(split_num_docs_target);
+            }
+            if let Some(heap_size) = heap_size_opt {
+                indexing_settings_json["resources"]["heap_size"] = json!(heap_size);
+            }
+            if let Some(max_merge_write_throughput) = max_merge_write_throughput_opt {
+                indexing_settings_json["resources"]["max_merge_write_throughput"] =
+                    json!(max_merge_write_throughput);
+            }
+        }
+    }
+}
+
+/// A setting that differs between two versions of an index config.
+#[derive(Debug, PartialEq)]
+struct IndexConfigChange<'a> {
+    /// Dotted path of the setting, e.g. `retention.period`.
+    path: String,
+    current_value_opt: Option<&'a JsonValue>,
+    new_value_opt: Option<&'a JsonValue>,
+}
+
+/// Lists the settings that differ between two JSON representations of an index config.
+fn diff_index_configs<'a>(
+    current: &'a JsonValue,
+    new: &'a JsonValue,
+) -> Vec<IndexConfigChange<'a>> {
+    let current_settings = flatten_json(current);
+    let new_settings = flatten_json(new);
+
+    current_settings
+        .keys()
+        .chain(new_settings.keys())
+        .sorted()
+        .dedup()
+        .filter_map(|path| {
+            let current_value_opt = current_settings.get(path).copied();
+            let new_value_opt = new_settings.get(path).copied();
+
+            if current_value_opt == new_value_opt {
+                return None;
+            }
+            Some(IndexConfigChange {
+                path: path.clone(),
+                current_value_opt,
+                new_value_opt,
+            })
+        })
+        .collect()
+}
+
+/// Flattens a JSON value into a map of dotted paths to leaf values. Arrays are leaves.
+fn flatten_json(json_value: &JsonValue) -> BTreeMap<String, &JsonValue> {
+    fn flatten_into<'a>(
+        path: String,
+        json_value: &'a JsonValue,
+        settings: &mut BTreeMap<String, &'a JsonValue>,
+    ) {
+        match json_value {
+            JsonValue::Object(json_object) => {
+                for (key, value) in json_object {
+                    let child_path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{path}.{key}")
+                    };
+                    flatten_into(child_path, value, settings);
+                }
+            }
+            JsonValue::Null => {}
+            _ => {
+                settings.insert(path, json_value);
+            }
+        }
+    }
+    let mut settings = BTreeMap::new();
+    flatten_into(String::new(), json_value, &mut settings);
+    settings
+}
+
+pub async fn restore_index_cli(args: RestoreIndexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "restore-index");
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    qw_client.indexes().restore(&args.index_id).await?;
+    println!("{} Index successfully restored.", "✔".color(GREEN_COLOR));
+    Ok(())
+}
+
+pub async fn toggle_merges_cli(args: ToggleMergesArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "toggle-merges");
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    if args.pause {
+        qw_client.indexes().pause_merges(&args.index_id).await?;
+        println!("{} Merges successfully paused.", "✔".color(GREEN_COLOR));
+    } else {
+        qw_client.indexes().resume_merges(&args.index_id).await?;
+        println!("{} Merges successfully resumed.", "✔".color(GREEN_COLOR));
+    }
+    Ok(())
+}
+
+pub async fn garbage_collect_index_cli(args: GarbageCollectIndexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "garbage-collect-index");
+    println!("❯ Garbage collecting index...");
+    quickwit_telemetry::send_telemetry_event(TelemetryEvent::GarbageCollect).await;
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    let spinner = spinner("Collecting garbage...");
+    let removal_info_res = qw_client
+        .indexes()
+        .garbage_collect(&args.index_id, args.dry_run)
+        .await;
+    spinner.finish_and_clear();
+    let removal_info = removal_info_res?;
+    display_split_removal_info(removal_info, args.dry_run)
+}
+
+pub async fn clone_index_cli(args: CloneIndexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "clone-index");
+    println!("❯ Cloning index...");
+    let num_splits = clone_index(&args).await?;
+    println!(
+        "{} Index successfully cloned ({} splits).",
+        "✔".color(GREEN_COLOR),
+        num_splits
+    );
+    Ok(())
+}
+
+/// Clones the published splits of an index into a target metastore and returns the number of
+/// cloned splits.
+async fn clone_index(args: &CloneIndexArgs) -> anyhow::Result<usize> {
+    let metastore_uri_resolver = quickwit_metastore_uri_resolver();
+    let source_metastore = metastore_uri_resolver
+        .resolve(&args.source_metastore_uri)
+        .await?;
+    // The target metastore records the cloned index and splits in its audit log.
+    let target_metastore = metastore_uri_resolver
+        .resolve_with_config(&MetastoreConfig::default(), &args.target_metastore_uri)
+        .await?;
+    let source_index_metadata = source_metastore.index_metadata(&args.index_id).await?;
+    let source_index_uri = source_index_metadata.index_uri().clone();
+    let list_splits_query = ListSplitsQuery::for_index(source_index_metadata.index_uid.clone())
+        .with_split_state(SplitState::Published);
+    let splits = source_metastore.list_splits(list_splits_query).await?;
+
+    let mut target_index_config = source_index_metadata.into_index_config();
+    target_index_config.index_id = args.target_index_id.clone();
+    if let Some(target_index_uri) = &args.target_index_uri_opt {
+        target_index_config.index_uri = target_index_uri.clone();
+    }
+    let target_index_uri = target_index_config.index_uri.clone();
+    let storage_uri_resolver = quickwit_storage_uri_resolver();
+    let index_service = IndexService::new(target_metastore.clone(), storage_uri_resolver.clone());
+    let target_index_uid = index_service
+        .create_index(target_index_config, false)
+        .await?
+        .index_uid;
+
+    if target_index_uri != source_index_uri {
+        let source_storage = storage_uri_resolver.resolve(&source_index_uri)?;
+        let target_storage = storage_uri_resolver.resolve(&target_index_uri)?;
+        let total_num_bytes = splits
+            .iter()
+            .map(|split| split.split_metadata.footer_offsets.end)
+            .sum();
+        let progress_bar = bytes_progress_bar(Some(total_num_bytes));
+        progress_bar.set_message("Copying split files...");
+
+        for split in &splits {
+            let split_path = PathBuf::from(split_file(split.split_id()));
+            let split_bytes = source_storage.get_all(&split_path).await?;
+            target_storage
+                .put(&split_path, Box::new(split_bytes.as_slice().to_vec()))
+                .await?;
+            progress_bar.inc(split_bytes.len() as u64);
+        }
+        progress_bar.finish_and_clear();
+    } else {
+        warn!(
+            index_uri=%target_index_uri,
+            "The source and target indexes share the same index URI: deleting the splits of one \
+             index will also delete the splits of the other."
+        );
+    }
+    let num_splits = splits.len();
+    if num_splits == 0 {
+        return Ok(0);
+    }
+    let split_ids: Vec<String> = splits
+        .iter()
+        .map(|split| split.split_id().to_string())
+        .collect();
+    let split_metadatas: Vec<SplitMetadata> = splits
+        .into_iter()
+        .map(|split| {
+            let mut split_metadata = split.split_metadata;
+            split_metadata.index_uid = target_index_uid.clone();
+            split_metadata
+        })
+        .collect();
+    target_metastore
+        .stage_splits(target_index_uid.clone(), split_metadatas)
+        .await?;
+    let split_ids_ref: Vec<&str> = split_ids.iter().map(String::as_str).collect();
+    target_metastore
+        .publish_splits(target_index_uid, &split_ids_ref, &[], None)
+        .await?;
+    Ok(num_splits)
+}
+
+pub async fn reindex_cli(args: ReindexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "reindex");
+    println!("❯ Reindexing documents...");
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    let index_metadata = qw_client.indexes().get(&args.index_id).await?;
+    // Fails early if the target index does not exist.
+    qw_client.indexes().get(&args.target_index_id).await?;
+    let index_storage = quickwit_storage_uri_resolver().resolve(index_metadata.index_uri())?;
+
+    let mut checkpoint = match ReindexCheckpoint::load(&args.checkpoint_path)? {
+        Some(checkpoint) => {
+            if checkpoint.index_id != args.index_id
+                || checkpoint.target_index_id != args.target_index_id
+            {
+                bail!(
+                    "Checkpoint file `{}` records the reindex of index `{}` into index `{}`.",
+                    args.checkpoint_path.display(),
+                    checkpoint.index_id,
+                    checkpoint.target_index_id
+                );
+            }
+            // The splits recorded in the checkpoint may have been merged since the reindex started,
+            // their files are then garbage collected.
+            let list_splits_query_params = ListSplitsQueryParams {
+                split_states: Some(vec![SplitState::Published, SplitState::MarkedForDeletion]),
+                ..Default::default()
+            };
+            let split_states: HashMap<String, SplitState> = qw_client
+                .splits(&args.index_id)
+                .list(list_splits_query_params)
+                .await?
+                .into_iter()
+                .map(|split| (split.split_id().to_string(), split.split_state))
+                .collect();
+            let splits_left = checkpoint
+                .splits
+                .iter()
+                .filter(|split| !checkpoint.reindexed_split_ids.contains(&split.split_id));
+            let unreadable_split_ids =
+                find_unreadable_splits(splits_left, &split_states, &*index_storage).await?;
+            if !unreadable_split_ids.is_empty() {
+                bail!(
+                    "Cannot resume the reindex from checkpoint file `{}`: splits {} of index `{}` \
+                     no longer exist, they were probably merged and garbage collected since the \
+                     reindex started. Delete the documents already reindexed into index `{}` and \
+                     the checkpoint file to restart the reindex from scratch.",
+                    args.checkpoint_path.display(),
+                    unreadable_split_ids
+                        .iter()
+                        .map(|split_id| format!("`{split_id}`"))
+                        .join(", "),
+                    args.index_id,
+                    args.target_index_id
+                );
+            }
+            println!(
+                "Resuming reindex from checkpoint file `{}` ({}/{} splits reindexed).",
+                args.checkpoint_path.display(),
+                checkpoint.reindexed_split_ids.len(),
+                checkpoint.splits.len()
+            );
+            checkpoint
+        }
+        None => {
+            // The list of splits is recorded once for all, so that the splits published in the
+            // meantime, for instance by merges, do not cause documents to be reindexed twice.
+            let list_splits_query_params = ListSplitsQueryParams {
+                split_states: Some(vec![SplitState::Published]),
+                ..Default::default()
+            };
+            let splits = qw_client
+                .splits(&args.index_id)
+                .list(list_splits_query_params)
+                .await?
+                .into_iter()
+                .map(|split| SplitToReindex {
+                    split_id: split.split_id().to_string(),
+                    num_docs: split.split_metadata.num_docs as u64,
+                })
+                .sorted_by(|left, right| left.split_id.cmp(&right.split_id))
+                .collect();
+            let checkpoint = ReindexCheckpoint {
+                index_id: args.index_id.clone(),
+                target_index_id: args.target_index_id.clone(),
+                splits,
+                reindexed_split_ids: BTreeSet::new(),
+            };
+            checkpoint.save(&args.checkpoint_path)?;
+            checkpoint
+        }
+    };
+    let index_config = index_metadata.index_config();
+    let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)?;
+    // The splits are downloaded one at a time to a scratch directory rather than loaded in memory.
+    let scratch_dir = tempfile::tempdir().context("Failed to create the scratch directory.")?;
+    let splits_to_reindex: Vec<SplitToReindex> = checkpoint
+        .splits
+        .iter()
+        .filter(|split| !checkpoint.reindexed_split_ids.contains(&split.split_id))
+        .cloned()
+        .collect();
+    let progress_bar =
+        docs_progress_bar(splits_to_reindex.iter().map(|split| split.num_docs).sum());
+
+    for split in splits_to_reindex {
+        progress_bar.set_message(format!("Reindexing split `{}`...", split.split_id));
+        let split_path = PathBuf::from(split_file(&split.split_id));
+        let scratch_split_path = scratch_dir.path().join(&split_path);
+        index_storage
+            .copy_to_file(&split_path, &scratch_split_path)
+            .await
+            .with_context(|| format!("Failed to fetch split `{}`.", split.split_id))?;
+        let doc_mapper_clone = doc_mapper.clone();
+        let scratch_split_path_clone = scratch_split_path.clone();
+        let ndjson_docs = tokio::task::spawn_blocking(move || {
+            read_split_docs_as_ndjson(&scratch_split_path_clone, &*doc_mapper_clone)
+        })
+        .await??;
+        tokio::fs::remove_file(&scratch_split_path).await?;
+        qw_client
+            .ingest(
+                &args.target_index_id,
+                IngestSource::Bytes(ndjson_docs.into()),
+                None,
+                CommitType::Auto,
+            )
+            .await?;
+        checkpoint.reindexed_split_ids.insert(split.split_id);
+        checkpoint.save(&args.checkpoint_path)?;
+        progress_bar.inc(split.num_docs);
+    }
+    progress_bar.finish_and_clear();
+    println!(
+        "{} Index successfully reindexed ({} splits).",
+        "✔".color(GREEN_COLOR),
+        checkpoint.splits.len()
+    );
+    Ok(())
+}
+
+/// Progress of a reindex, saved after each reindexed split.
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+struct ReindexCheckpoint {
+    index_id: String,
+    target_index_id: String,
+    /// Splits of the source index published when the reindex started.
+    splits: Vec<SplitToReindex>,
+    reindexed_split_ids: BTreeSet<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+struct SplitToReindex {
+    split_id: String,
+    num_docs: u64,
+}
+
+impl ReindexCheckpoint {
+    /// Loads the checkpoint from `path`. Returns `None` if the file does not exist.
+    fn load(path: &Path) -> anyhow::Result<Option<Self>> {
+        let checkpoint_json = match std::fs::read(path) {
+            Ok(checkpoint_json) => checkpoint_json,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => {
+                return Err(error).with_context(|| {
+                    format!("Failed to read checkpoint file `{}`.", path.display())
+                })
+            }
+        };
+        let checkpoint = serde_json::from_slice(&checkpoint_json)
+            .with_context(|| format!("Failed to parse checkpoint file `{}`.", path.display()))?;
+        Ok(Some(checkpoint))
     }
 
-    pub async fn execute(self) -> anyhow::Result<()> {
-        match self {
-            Self::Clear(args) => clear_index_cli(args).await,
-            Self::Create(args) => create_index_cli(args).await,
-            Self::Delete(args) => delete_index_cli(args).await,
-            Self::Describe(args) => describe_index_cli(args).await,
-            Self::Ingest(args) => ingest_docs_cli(args).await,
-            Self::List(args) => list_index_cli(args).await,
-            Self::Search(args) => search_index_cli(args).await,
-        }
+    /// Saves the checkpoint to `path`. The file is replaced atomically so that an interrupted
+    /// reindex never leaves a truncated checkpoint behind.
+    fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let checkpoint_json = serde_json::to_vec_pretty(self)?;
+        let temp_path = path.with_extension("tmp");
+        std::fs::write(&temp_path, checkpoint_json)
+            .and_then(|_| std::fs::rename(&temp_path, path))
+            .with_context(|| format!("Failed to write checkpoint file `{}`.", path.display()))
     }
 }
 
-pub async fn clear_index_cli(args: ClearIndexArgs) -> anyhow::Result<()> {
-    debug!(args=?args, "clear-index");
-    if !args.assume_yes {
-        let prompt = format!(
-            "This operation will delete all the splits of the index `{}` and reset its \
-             checkpoint. Do you want to proceed?",
-            args.index_id
-        );
-        if !prompt_confirmation(&prompt, false) {
-            return Ok(());
+/// Returns the IDs of the splits that can no longer be read, because they are neither published nor
+/// marked for deletion, or because their file was already deleted by the garbage collector.
+async fn find_unreadable_splits<'a>(
+    splits: impl Iterator<Item = &'a SplitToReindex>,
+    split_states: &HashMap<String, SplitState>,
+    index_storage: &dyn Storage,
+) -> anyhow::Result<Vec<String>> {
+    let mut unreadable_split_ids = Vec::new();
+
+    for split in splits {
+        let is_readable = match split_states.get(&split.split_id) {
+            Some(SplitState::Published) => true,
+            Some(SplitState::MarkedForDeletion) => {
+                let split_path = PathBuf::from(split_file(&split.split_id));
+                index_storage.exists(&split_path).await?
+            }
+            _ => false,
+        };
+        if !is_readable {
+            unreadable_split_ids.push(split.split_id.clone());
         }
     }
-    let endpoint = args.cluster_endpoint;
-    let transport = Transport::new(endpoint);
-    let qw_client = QuickwitClient::new(transport);
-    qw_client.indexes().clear(&args.index_id).await?;
-    println!("{} Index successfully cleared.", "✔".color(GREEN_COLOR),);
-    Ok(())
+    Ok(unreadable_split_ids)
 }
 
-pub async fn create_index_cli(args: CreateIndexArgs) -> anyhow::Result<()> {
-    debug!(args=?args, "create-index");
-    println!("❯ Creating index...");
-    quickwit_telemetry::send_telemetry_event(TelemetryEvent::Create).await;
-    let file_content = load_file(&args.index_config_uri).await?;
-    let config_format = ConfigFormat::sniff_from_uri(&args.index_config_uri)?;
-    let transport = Transport::new(args.cluster_endpoint);
-    let qw_client = QuickwitClient::new(transport);
-    // TODO: nice to have: check first if the index exists by send a GET request, if we get a 404,
-    // the index does not exist. If it exists, we can display the prompt.
-    if args.overwrite && !args.assume_yes {
-        // Stop if user answers no.
-        let prompt = "This operation will overwrite the index and delete all its data. Do you \
-                      want to proceed?"
-            .to_string();
-        if !prompt_confirmation(&prompt, false) {
-            return Ok(());
+/// Reads the documents stored in the split file at `split_path` and returns them as NDJSON. The
+/// original documents are returned if the doc mapping stores them, otherwise the documents are
+/// rebuilt from the stored fields.
+fn read_split_docs_as_ndjson(
+    split_path: &Path,
+    doc_mapper: &dyn DocMapper,
+) -> anyhow::Result<Vec<u8>> {
+    let split_directory = get_tantivy_directory_from_split_bundle(split_path)?;
+    let index = Index::open(split_directory)?;
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()?;
+    let searcher = reader.searcher();
+    let schema = searcher.schema();
+    let mut ndjson_docs = Vec::new();
+
+    for (segment_ord, segment_reader) in searcher.segment_readers().iter().enumerate() {
+        for doc_id in segment_reader.doc_ids_alive() {
+            let doc_address = DocAddress::new(segment_ord as SegmentOrdinal, doc_id);
+            let doc = searcher.doc(doc_address)?;
+            let NamedFieldDocument(named_doc) = schema.to_named_doc(&doc);
+            let mut doc_json = doc_mapper.doc_to_json(named_doc)?;
+            let doc_json = match doc_json.remove(SOURCE_FIELD_NAME) {
+                Some(source_json) => source_json,
+                None => JsonValue::Object(doc_json),
+            };
+            serde_json::to_writer(&mut ndjson_docs, &doc_json)?;
+            ndjson_docs.push(b'\n');
         }
     }
-    let bytes = Bytes::from(file_content.to_vec());
-    qw_client
-        .indexes()
-        .create(bytes, config_format, args.overwrite)
-        .await?;
-    println!("{} Index successfully created.", "✔".color(GREEN_COLOR));
-    Ok(())
+    Ok(ndjson_docs)
 }
 
 pub async fn list_index_cli(args: ListIndexesArgs) -> anyhow::Result<()> {
@@ -484,16 +1604,21 @@ pub async fn list_index_cli(args: ListIndexesArgs) -> anyhow::Result<()> {
     let transport = Transport::new(args.cluster_endpoint);
     let qw_client = QuickwitClient::new(transport);
     let indexes_metadatas = qw_client.indexes().list().await?;
-    let index_table = make_list_indexes_table(
-        indexes_metadatas
-            .into_iter()
-            .map(IndexMetadata::into_index_config),
-    );
-    println!("\n{index_table}\n");
-    Ok(())
+    print_output(
+        args.output_format,
+        &indexes_metadatas,
+        |indexes_metadatas| {
+            let index_table = make_list_indexes_table(
+                indexes_metadatas
+                    .iter()
+                    .map(|index_metadata| index_metadata.index_config().clone()),
+            );
+            format!("\n{index_table}\n")
+        },
+    )
 }
 
-fn make_list_indexes_table<I>(indexes: I) -> Table
+pub(crate) fn make_list_indexes_table<I>(indexes: I) -> Table
 where I: IntoIterator<Item = IndexConfig> {
     let rows = indexes
         .into_iter()
@@ -518,18 +1643,30 @@ pub async fn describe_index_cli(args: DescribeIndexArgs) -> anyhow::Result<()> {
     let endpoint =
         Url::parse(args.cluster_endpoint.as_str()).context("Failed to parse cluster endpoint.")?;
     let transport = Transport::new(endpoint);
-    let qw_client = QuickwitClient::new(transport);
-    let index_metadata = qw_client.indexes().get(&args.index_id).await?;
-    let list_splits_query_params = ListSplitsQueryParams::default();
-    let splits = qw_client
-        .splits(&args.index_id)
-        .list(list_splits_query_params)
-        .await?;
-    let index_stats = IndexStats::from_metadata(index_metadata, splits)?;
-    println!("{}", index_stats.display_as_table());
-    Ok(())
+    let qw_client = &QuickwitClient::new(transport);
+    let index_id = &args.index_id;
+    watch(
+        args.watch_interval_opt,
+        args.output_format,
+        move || async move {
+            let index_metadata = qw_client.indexes().get(index_id).await?;
+            let list_splits_query_params = ListSplitsQueryParams::default();
+            let splits = qw_client
+                .splits(index_id)
+                .list(list_splits_query_params)
+                .await?;
+            let index_stats = IndexStats::from_metadata(index_metadata, splits)?;
+            print_output(
+                args.output_format,
+                &index_stats,
+                IndexStats::display_as_table,
+            )
+        },
+    )
+    .await
 }
 
+#[derive(Serialize)]
 pub struct IndexStats {
     pub index_id: String,
     pub index_uri: Uri,
@@ -540,10 +1677,30 @@ pub struct IndexStats {
     pub timestamp_range: Option<(i64, i64)>,
     pub num_docs_descriptive: Option<DescriptiveStats>,
     pub num_bytes_descriptive: Option<DescriptiveStats>,
+    pub merges_paused: bool,
+    pub field_stats: Vec<IndexFieldStats>,
+}
+
+#[derive(Tabled)]
+struct IndexFieldStatsRow {
+    #[tabled(rename = "Field")]
+    field_name: String,
+    #[tabled(rename = "Docs with value")]
+    num_docs_with_value: u64,
+    #[tabled(rename = "Null docs")]
+    num_null_docs: u64,
+    #[tabled(rename = "Values")]
+    num_values: u64,
+    #[tabled(rename = "Tokens")]
+    num_tokens: u64,
+    #[tabled(rename = "Avg length (bytes)")]
+    avg_value_num_bytes: String,
+    #[tabled(rename = "Fast field size")]
+    fast_field_size: String,
 }
 
 impl Tabled for IndexStats {
-    const LENGTH: usize = 7;
+    const LENGTH: usize = 8;
 
     fn fields(&self) -> Vec<String> {
         vec![
@@ -556,6 +1713,11 @@ impl Tabled for IndexStats {
                 .to_string(),
             display_option_in_table(&self.timestamp_field_name),
             display_timestamp_range(&self.timestamp_range),
+            if self.merges_paused {
+                "paused".to_string()
+            } else {
+                "active".to_string()
+            },
         ]
     }
 
@@ -568,6 +1730,7 @@ impl Tabled for IndexStats {
             "Size of published splits: ".to_string(),
             "Timestamp field: ".to_string(),
             "Timestamp range: ".to_string(),
+            "Merges: ".to_string(),
         ]
     }
 }
@@ -646,6 +1809,9 @@ impl IndexStats {
         } else {
             (None, None)
         };
+        let field_stats =
+            aggregate_field_stats(published_splits.iter().map(|split| &split.split_metadata));
+        let merges_paused = index_metadata.merges_paused;
         let index_config = index_metadata.into_index_config();
 
         Ok(Self {
@@ -658,6 +1824,8 @@ impl IndexStats {
             timestamp_range,
             num_docs_descriptive,
             num_bytes_descriptive,
+            merges_paused,
+            field_stats,
         })
     }
 
@@ -690,7 +1858,25 @@ impl IndexStats {
             index_stats_table
         };
 
-        index_stats_table.to_string()
+        if self.field_stats.is_empty() {
+            return index_stats_table.to_string();
+        }
+        let field_stats_rows = self
+            .field_stats
+            .iter()
+            .map(|field_stats| IndexFieldStatsRow {
+                field_name: field_stats.field_name.clone(),
+                num_docs_with_value: field_stats.num_docs_with_value,
+                num_null_docs: field_stats.num_null_docs,
+                num_values: field_stats.num_values,
+                num_tokens: field_stats.num_tokens,
+                avg_value_num_bytes: format!("{:.1}", field_stats.avg_value_num_bytes),
+                fast_field_size: Byte::from(field_stats.fast_field_num_bytes)
+                    .get_appropriate_unit(false)
+                    .to_string(),
+            });
+        let field_stats_table = make_table("Field statistics (published)", field_stats_rows, false);
+        format!("{index_stats_table}\n\n{field_stats_table}")
     }
 }
 
@@ -712,7 +1898,7 @@ fn create_table(table: impl Tabled, header: &str) -> Table {
         .with(Panel("\n", 0))
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct DescriptiveStats {
     mean_val: f32,
     std_val: f32,
@@ -776,24 +1962,16 @@ pub async fn ingest_docs_cli(args: IngestDocsArgs) -> anyhow::Result<()> {
         println!("❯ Ingesting documents from stdin.");
     }
     quickwit_telemetry::send_telemetry_event(TelemetryEvent::Ingest).await;
-    let progress_bar = match &args.input_path_opt {
-        Some(filepath) => {
-            let file_len = std::fs::metadata(filepath).context("File not found")?.len();
-            ProgressBar::new(file_len)
-        }
-        None => ProgressBar::new_spinner(),
+    let file_len_opt = match &args.input_path_opt {
+        Some(filepath) => Some(std::fs::metadata(filepath).context("File not found")?.len()),
+        None => None,
     };
-    progress_bar.enable_steady_tick(Duration::from_millis(100));
-    progress_bar.set_style(progress_bar_style());
-    progress_bar.set_message("0MiB/s");
+    let progress_bar = bytes_progress_bar(file_len_opt);
     let update_progress_bar = |ingest_event: IngestEvent| {
         match ingest_event {
             IngestEvent::IngestedDocBatch(num_bytes) => progress_bar.inc(num_bytes as u64),
             IngestEvent::Sleep => {} // To
         };
-        let throughput =
-            progress_bar.position() as f64 / progress_bar.elapsed().as_secs_f64() / 1024.0 / 1024.0;
-        progress_bar.set_message(format!("{throughput:.1} MiB/s"));
     };
 
     let transport = Transport::new(args.cluster_endpoint);
@@ -818,14 +1996,6 @@ pub async fn ingest_docs_cli(args: IngestDocsArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
-fn progress_bar_style() -> ProgressStyle {
-    ProgressStyle::with_template(
-        "{spinner:.blue} [{elapsed_precise}] {bytes}/{total_bytes} ({msg})",
-    )
-    .expect("Progress style should always be valid.")
-    .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"])
-}
-
 pub async fn search_index(args: SearchIndexArgs) -> anyhow::Result<SearchResponseRest> {
     let aggs: Option<serde_json::Value> = args
         .aggregation
@@ -863,6 +2033,25 @@ pub async fn search_index_cli(args: SearchIndexArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+pub async fn sql_index(args: SqlIndexArgs) -> anyhow::Result<SqlResponse> {
+    let sql_query = SqlRequestQueryString {
+        query: args.query,
+        ..Default::default()
+    };
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    let sql_response = qw_client.sql(sql_query).await?;
+    Ok(sql_response)
+}
+
+pub async fn sql_index_cli(args: SqlIndexArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "sql-index");
+    let sql_response = sql_index(args).await?;
+    let sql_response_json = serde_json::to_string_pretty(&sql_response)?;
+    println!("{sql_response_json}");
+    Ok(())
+}
+
 pub async fn delete_index_cli(args: DeleteIndexArgs) -> anyhow::Result<()> {
     debug!(args=?args, "delete-index");
     if !args.dry_run && !args.assume_yes {
@@ -901,195 +2090,116 @@ pub async fn delete_index_cli(args: DeleteIndexArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Starts a tokio task that displays the indexing statistics
-/// every once in awhile.
-pub async fn start_statistics_reporting_loop(
-    pipeline_handle: ActorHandle<IndexingPipeline>,
-    is_stdin: bool,
-) -> anyhow::Result<IndexingStatistics> {
-    let mut stdout_handle = stdout();
-    let start_time = Instant::now();
-    let mut throughput_calculator = ThroughputCalculator::new(start_time);
-    let mut report_interval = tokio::time::interval(Duration::from_secs(1));
-
-    loop {
-        // TODO fixme. The way we wait today is a bit lame: if the indexing pipeline exits, we will
-        // still wait up to an entire heartbeat...  Ideally we should  select between two
-        // futures.
-        report_interval.tick().await;
-        // Try to receive with a timeout of 1 second.
-        // 1 second is also the frequency at which we update statistic in the console
-        let observation = pipeline_handle.observe().await;
-
-        // Let's not display live statistics to allow screen to scroll.
-        if observation.state.num_docs > 0 {
-            display_statistics(
-                &mut stdout_handle,
-                &mut throughput_calculator,
-                &observation.state,
-            )?;
-        }
-
-        if observation.obs_type == ObservationType::PostMortem {
-            break;
-        }
-    }
-    let (pipeline_exit_status, pipeline_statistics) = pipeline_handle.join().await;
-    if !pipeline_exit_status.is_success() {
-        bail!(pipeline_exit_status);
-    }
-    // If we have received zero docs at this point,
-    // there is no point in displaying report.
-    if pipeline_statistics.num_docs == 0 {
-        return Ok(pipeline_statistics);
-    }
-
-    if is_stdin {
-        display_statistics(
-            &mut stdout_handle,
-            &mut throughput_calculator,
-            &pipeline_statistics,
-        )?;
-    }
-    // display end of task report
-    println!();
-    let secs = Duration::from_secs(start_time.elapsed().as_secs());
-    if pipeline_statistics.num_invalid_docs == 0 {
-        println!(
-            "Indexed {} documents in {}.",
-            pipeline_statistics.num_docs.separate_with_commas(),
-            format_duration(secs)
-        );
-    } else {
-        let num_indexed_docs = (pipeline_statistics.num_docs
-            - pipeline_statistics.num_invalid_docs)
-            .separate_with_commas();
-
-        let error_rate = (pipeline_statistics.num_invalid_docs as f64
-            / pipeline_statistics.num_docs as f64)
-            * 100.0;
-
-        println!(
-            "Indexed {} out of {} documents in {}. Failed to index {} document(s). {}\n",
-            num_indexed_docs,
-            pipeline_statistics.num_docs.separate_with_commas(),
-            format_duration(secs),
-            pipeline_statistics.num_invalid_docs.separate_with_commas(),
-            colorize_error_rate(error_rate),
-        );
-    }
-
-    Ok(pipeline_statistics)
-}
-
-fn colorize_error_rate(error_rate: f64) -> ColoredString {
-    let error_rate_message = format!("({error_rate:.1}% error rate)");
-    if error_rate < 1.0 {
-        error_rate_message.yellow()
-    } else if error_rate < 5.0 {
-        error_rate_message.truecolor(255, 181, 46) //< Orange
-    } else {
-        error_rate_message.red()
-    }
+/// Estimated impact of a delete query, displayed before submitting the delete task.
+#[derive(Debug, Eq, PartialEq)]
+pub struct DeleteByQueryPreview {
+    /// Number of documents matching the query, as returned by a count search. Documents ingested
+    /// after the preview and before the delete task is applied may be deleted as well.
+    pub num_matching_docs: u64,
+    /// Number of published splits whose time range overlaps the query. The delete task is
+    /// applied to these splits, which are rewritten if they contain matching documents.
+    pub num_candidate_splits: usize,
+    pub num_candidate_split_docs: u64,
+    pub candidate_splits_size_in_bytes: u64,
 }
 
-/// A struct to print data on the standard output.
-struct Printer<'a> {
-    pub stdout: &'a mut Stdout,
+pub async fn preview_delete_by_query(
+    args: &DeleteByQueryArgs,
+) -> anyhow::Result<DeleteByQueryPreview> {
+    let transport = Transport::new(args.cluster_endpoint.clone());
+    let qw_client = QuickwitClient::new(transport);
+    let count_request = SearchRequestQueryString {
+        query: args.query.clone(),
+        start_timestamp: args.start_timestamp,
+        end_timestamp: args.end_timestamp,
+        max_hits: 0,
+        ..Default::default()
+    };
+    let count_response = qw_client.search(&args.index_id, count_request).await?;
+    let list_splits_query_params = ListSplitsQueryParams {
+        split_states: Some(vec![SplitState::Published]),
+        start_timestamp: args.start_timestamp,
+        end_timestamp: args.end_timestamp,
+        ..Default::default()
+    };
+    let candidate_splits = qw_client
+        .splits(&args.index_id)
+        .list(list_splits_query_params)
+        .await?;
+    Ok(DeleteByQueryPreview {
+        num_matching_docs: count_response.num_hits,
+        num_candidate_splits: candidate_splits.len(),
+        num_candidate_split_docs: candidate_splits
+            .iter()
+            .map(|split| split.split_metadata.num_docs as u64)
+            .sum(),
+        candidate_splits_size_in_bytes: candidate_splits
+            .iter()
+            .map(|split| split.split_metadata.uncompressed_docs_size_in_bytes)
+            .sum(),
+    })
 }
 
-impl<'a> Printer<'a> {
-    pub fn print_header(&mut self, header: &str) -> io::Result<()> {
-        write!(&mut self.stdout, " {}", header.bright_blue())?;
-        Ok(())
-    }
-
-    pub fn print_value(&mut self, fmt_args: fmt::Arguments) -> io::Result<()> {
-        write!(&mut self.stdout, " {fmt_args}")
-    }
-
-    pub fn flush(&mut self) -> io::Result<()> {
-        self.stdout.flush()
+pub async fn delete_by_query_cli(args: DeleteByQueryArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "delete-by-query");
+    let preview = preview_delete_by_query(&args).await?;
+    if preview.num_matching_docs == 0 {
+        println!("No documents match the query, no delete task was submitted.");
+        return Ok(());
     }
-}
-
-fn display_statistics(
-    stdout: &mut Stdout,
-    throughput_calculator: &mut ThroughputCalculator,
-    statistics: &IndexingStatistics,
-) -> anyhow::Result<()> {
-    let elapsed_duration = time::Duration::try_from(throughput_calculator.elapsed_time())?;
-    let elapsed_time = format!(
-        "{:02}:{:02}:{:02}",
-        elapsed_duration.whole_hours(),
-        elapsed_duration.whole_minutes() % 60,
-        elapsed_duration.whole_seconds() % 60
+    println!(
+        "Documents matching the query: {}",
+        preview.num_matching_docs.separate_with_commas()
     );
-    let throughput_mb_s = throughput_calculator.calculate(statistics.total_bytes_processed);
-    let mut printer = Printer { stdout };
-    printer.print_header("Num docs")?;
-    printer.print_value(format_args!("{:>7}", statistics.num_docs))?;
-    printer.print_header("Parse errs")?;
-    printer.print_value(format_args!("{:>5}", statistics.num_invalid_docs))?;
-    printer.print_header("PublSplits")?;
-    printer.print_value(format_args!("{:>3}", statistics.num_published_splits))?;
-    printer.print_header("Input size")?;
-    printer.print_value(format_args!(
-        "{:>5}MB",
-        statistics.total_bytes_processed / 1_000_000
-    ))?;
-    printer.print_header("Thrghput")?;
-    printer.print_value(format_args!("{throughput_mb_s:>5.2}MB/s"))?;
-    printer.print_header("Time")?;
-    printer.print_value(format_args!("{elapsed_time}\n"))?;
-    printer.flush()?;
-    Ok(())
-}
-
-/// ThroughputCalculator is used to calculate throughput.
-struct ThroughputCalculator {
-    /// Stores the time series of processed bytes value.
-    processed_bytes_values: VecDeque<(Instant, u64)>,
-    /// Store the time this calculator started
-    start_time: Instant,
-}
-
-impl ThroughputCalculator {
-    /// Creates new instance.
-    pub fn new(start_time: Instant) -> Self {
-        let processed_bytes_values: VecDeque<(Instant, u64)> = (0..THROUGHPUT_WINDOW_SIZE)
-            .map(|_| (start_time, 0u64))
-            .collect();
-        Self {
-            processed_bytes_values,
-            start_time,
+    println!(
+        "Candidate splits: {} ({} documents, {})",
+        preview.num_candidate_splits,
+        preview.num_candidate_split_docs.separate_with_commas(),
+        Byte::from_bytes(preview.candidate_splits_size_in_bytes as u128)
+            .get_appropriate_unit(false)
+    );
+    if !args.assume_yes {
+        let prompt = format!(
+            "This operation will delete the documents matching the query from the index `{}`. Do \
+             you want to proceed?",
+            args.index_id
+        );
+        if !prompt_confirmation(&prompt, false) {
+            return Ok(());
         }
     }
-
-    /// Calculates the throughput.
-    pub fn calculate(&mut self, current_processed_bytes: u64) -> f64 {
-        self.processed_bytes_values.pop_front();
-        let current_instant = Instant::now();
-        let (first_instant, first_processed_bytes) = *self.processed_bytes_values.front().unwrap();
-        let elapsed_time = (current_instant - first_instant).as_millis() as f64 / 1_000f64;
-        self.processed_bytes_values
-            .push_back((current_instant, current_processed_bytes));
-        (current_processed_bytes - first_processed_bytes) as f64
-            / 1_000_000f64
-            / elapsed_time.max(1f64)
-    }
-
-    pub fn elapsed_time(&self) -> Duration {
-        self.start_time.elapsed()
-    }
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    let delete_query_request = DeleteQueryRequest {
+        query: args.query,
+        search_fields: Vec::new(),
+        start_timestamp: args.start_timestamp,
+        end_timestamp: args.end_timestamp,
+    };
+    let delete_task = qw_client
+        .delete_tasks(&args.index_id)
+        .create(delete_query_request)
+        .await?;
+    println!(
+        "{} Delete task successfully submitted (opstamp {}). Run `quickwit delete-task describe \
+         --index {} --opstamp {}` to follow its progress.",
+        "✔".color(GREEN_COLOR),
+        delete_task.opstamp,
+        args.index_id,
+        delete_task.opstamp
+    );
+    Ok(())
 }
 
 #[cfg(test)]
 mod test {
 
     use std::ops::RangeInclusive;
+    use std::time::Duration;
 
-    use quickwit_metastore::SplitMetadata;
+    use quickwit_doc_mapper::DefaultDocMapper;
+    use quickwit_metastore::{SplitFieldStats, SplitMetadata};
+    use quickwit_storage::{OwnedBytes, PutPayload, RamStorage, SplitPayloadBuilder};
 
     use super::*;
 
@@ -1114,8 +2224,18 @@ mod test {
         let index_uri = "s3://some-test-bucket";
 
         let index_metadata = IndexMetadata::for_test(&index_id, index_uri);
-        let split_metadata_1 =
+        let mut split_metadata_1 =
             split_metadata_for_test(&split_id_1, 100_000, 1111..=2222, 15_000_000);
+        split_metadata_1.field_stats.insert(
+            "body".to_string(),
+            SplitFieldStats {
+                num_docs_with_value: 90_000,
+                num_values: 90_000,
+                value_num_bytes: 900_000,
+                num_tokens: 180_000,
+                fast_field_num_bytes: 0,
+            },
+        );
         let split_metadata_2 =
             split_metadata_for_test(&split_id_2, 100_000, 1000..=3000, 30_000_000);
 
@@ -1145,6 +2265,11 @@ mod test {
             Some("timestamp".to_string())
         );
         assert_eq!(index_stats.timestamp_range, Some((1111, 2222)));
+        assert!(!index_stats.merges_paused);
+        assert_eq!(index_stats.field_stats.len(), 1);
+        assert_eq!(index_stats.field_stats[0].field_name, "body");
+        assert_eq!(index_stats.field_stats[0].num_null_docs, 10_000);
+        assert_eq!(index_stats.field_stats[0].avg_value_num_bytes, 10.0);
 
         Ok(())
     }
@@ -1198,4 +2323,239 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_apply_index_config_update() {
+        let index_config = IndexConfig::for_test("test-index", "ram:///indexes/test-index");
+        let current_index_config_json = serde_json::to_value(index_config).unwrap();
+
+        let mut new_index_config_json = current_index_config_json.clone();
+        apply_index_config_update(
+            &IndexConfigUpdate::SearchSettings {
+                default_search_fields: vec!["body".to_string()],
+            },
+            &mut new_index_config_json,
+        );
+        let changes = diff_index_configs(&current_index_config_json, &new_index_config_json);
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].path, "search_settings.default_search_fields");
+        assert_eq!(changes[0].new_value_opt, Some(&json!(["body"])));
+
+        let mut new_index_config_json = current_index_config_json.clone();
+        apply_index_config_update(
+            &IndexConfigUpdate::RetentionPolicy {
+                period_opt: Some("30 days".to_string()),
+                max_size_opt: None,
+                schedule_opt: Some("daily".to_string()),
+            },
+            &mut new_index_config_json,
+        );
+        let new_index_config: IndexConfig =
+            serde_json::from_value(new_index_config_json.clone()).unwrap();
+        let retention_policy = new_index_config.retention_policy.unwrap();
+        assert_eq!(
+            retention_policy.retention_period().unwrap(),
+            Some(Duration::from_secs(30 * 24 * 3600))
+        );
+        let changes = diff_index_configs(&current_index_config_json, &new_index_config_json);
+        assert_eq!(
+            changes
+                .iter()
+                .map(|change| change.path.as_str())
+                .collect::<Vec<_>>(),
+            ["retention.period", "retention.schedule"]
+        );
+        assert!(changes
+            .iter()
+            .all(|change| change.current_value_opt.is_none()));
+
+        let current_index_config_json = new_index_config_json;
+        let mut new_index_config_json = current_index_config_json.clone();
+        apply_index_config_update(
+            &IndexConfigUpdate::DisableRetentionPolicy,
+            &mut new_index_config_json,
+        );
+        let changes = diff_index_configs(&current_index_config_json, &new_index_config_json);
+        assert_eq!(changes.len(), 2);
+        assert!(changes.iter().all(|change| change.new_value_opt.is_none()));
+
+        let mut new_index_config_json = current_index_config_json.clone();
+        apply_index_config_update(
+            &IndexConfigUpdate::IndexingSettings {
+                commit_timeout_secs_opt: Some(30),
+                split_num_docs_target_opt: None,
+                heap_size_opt: Some(Byte::from_bytes(3_000_000_000)),
+                max_merge_write_throughput_opt: None,
+            },
+            &mut new_index_config_json,
+        );
+        let new_index_config: IndexConfig = serde_json::from_value(new_index_config_json).unwrap();
+        assert_eq!(new_index_config.indexing_settings.commit_timeout_secs, 30);
+        assert_eq!(
+            new_index_config.indexing_settings.resources.heap_size,
+            Byte::from_bytes(3_000_000_000)
+        );
+    }
+
+    #[test]
+    fn test_apply_index_config_update_invalid() {
+        let index_config = IndexConfig::for_test("test-index", "ram:///indexes/test-index");
+        let mut index_config_json = serde_json::to_value(index_config).unwrap();
+        apply_index_config_update(
+            &IndexConfigUpdate::SearchSettings {
+                default_search_fields: vec!["unknown-field".to_string()],
+            },
+            &mut index_config_json,
+        );
+        serde_json::from_value::<IndexConfig>(index_config_json).unwrap_err();
+    }
+
+    async fn build_split_data(
+        doc_mapper: &dyn DocMapper,
+        json_docs: &[&str],
+    ) -> anyhow::Result<OwnedBytes> {
+        let temp_dir = tempfile::tempdir()?;
+        let index = Index::create_in_dir(temp_dir.path(), doc_mapper.schema())?;
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+        for json_doc in json_docs {
+            let (_partition, doc) = doc_mapper.doc_from_json_str(json_doc)?;
+            index_writer.add_document(doc)?;
+        }
+        index_writer.commit()?;
+        index_writer.wait_merging_threads()?;
+
+        let split_files = std::fs::read_dir(temp_dir.path())?
+            .map(|dir_entry| dir_entry.map(|dir_entry| dir_entry.path()))
+            .collect::<std::io::Result<Vec<PathBuf>>>()?;
+        let split_data = SplitPayloadBuilder::get_split_payload(&split_files, &[])?
+            .read_all()
+            .await?;
+        Ok(split_data)
+    }
+
+    fn parse_ndjson(ndjson_docs: &[u8]) -> Vec<JsonValue> {
+        serde_json::Deserializer::from_slice(ndjson_docs)
+            .into_iter::<JsonValue>()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_read_split_docs_as_ndjson() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let split_path = temp_dir.path().join("split-1.split");
+        let doc_mapper = serde_json::from_str::<DefaultDocMapper>(
+            r#"{
+                "field_mappings": [
+                    {"name": "body", "type": "text", "stored": true},
+                    {"name": "count", "type": "u64", "stored": true}
+                ]
+            }"#,
+        )?;
+        let split_data = build_split_data(
+            &doc_mapper,
+            &[
+                r#"{"body": "hello world", "count": 1}"#,
+                r#"{"body": "hello quickwit", "count": 2}"#,
+            ],
+        )
+        .await?;
+        std::fs::write(&split_path, split_data.as_slice())?;
+        let ndjson_docs = read_split_docs_as_ndjson(&split_path, &doc_mapper)?;
+        assert_eq!(
+            parse_ndjson(&ndjson_docs),
+            [
+                json!({"body": "hello world", "count": 1}),
+                json!({"body": "hello quickwit", "count": 2}),
+            ]
+        );
+
+        // The original documents are returned when the doc mapping stores them.
+        let doc_mapper = serde_json::from_str::<DefaultDocMapper>(
+            r#"{
+                "field_mappings": [
+                    {"name": "body", "type": "text", "stored": false}
+                ],
+                "store_source": true
+            }"#,
+        )?;
+        let split_data = build_split_data(
+            &doc_mapper,
+            &[r#"{"body": "hello world", "severity": "INFO"}"#],
+        )
+        .await?;
+        std::fs::write(&split_path, split_data.as_slice())?;
+        let ndjson_docs = read_split_docs_as_ndjson(&split_path, &doc_mapper)?;
+        assert_eq!(
+            parse_ndjson(&ndjson_docs),
+            [json!({"body": "hello world", "severity": "INFO"})]
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_find_unreadable_splits() -> anyhow::Result<()> {
+        let index_storage = RamStorage::default();
+        index_storage
+            .put(Path::new("split-2.split"), Box::new(b"split-2".to_vec()))
+            .await?;
+        let split_states = HashMap::from_iter([
+            ("split-1".to_string(), SplitState::Published),
+            ("split-2".to_string(), SplitState::MarkedForDeletion),
+            ("split-3".to_string(), SplitState::MarkedForDeletion),
+        ]);
+        let splits: Vec<SplitToReindex> = ["split-1", "split-2", "split-3", "split-4"]
+            .into_iter()
+            .map(|split_id| SplitToReindex {
+                split_id: split_id.to_string(),
+                num_docs: 10,
+            })
+            .collect();
+        let unreadable_split_ids =
+            find_unreadable_splits(splits.iter(), &split_states, &index_storage).await?;
+        assert_eq!(unreadable_split_ids, ["split-3", "split-4"]);
+
+        let unreadable_split_ids =
+            find_unreadable_splits(splits[..2].iter(), &split_states, &index_storage).await?;
+        assert!(unreadable_split_ids.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_reindex_checkpoint_load_save() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let checkpoint_path = temp_dir.path().join("checkpoint.json");
+        assert!(ReindexCheckpoint::load(&checkpoint_path)?.is_none());
+
+        let mut checkpoint = ReindexCheckpoint {
+            index_id: "hdfs-logs".to_string(),
+            target_index_id: "hdfs-logs-v2".to_string(),
+            splits: vec![
+                SplitToReindex {
+                    split_id: "split-1".to_string(),
+                    num_docs: 10,
+                },
+                SplitToReindex {
+                    split_id: "split-2".to_string(),
+                    num_docs: 20,
+                },
+            ],
+            reindexed_split_ids: BTreeSet::new(),
+        };
+        checkpoint.save(&checkpoint_path)?;
+        assert_eq!(
+            ReindexCheckpoint::load(&checkpoint_path)?.unwrap(),
+            checkpoint
+        );
+        checkpoint.reindexed_split_ids.insert("split-1".to_string());
+        checkpoint.save(&checkpoint_path)?;
+        assert_eq!(
+            ReindexCheckpoint::load(&checkpoint_path)?.unwrap(),
+            checkpoint
+        );
+
+        std::fs::write(&checkpoint_path, "not a checkpoint")?;
+        ReindexCheckpoint::load(&checkpoint_path).unwrap_err();
+        Ok(())
+    }
 }
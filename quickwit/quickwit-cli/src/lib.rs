@@ -20,38 +20,56 @@
 #![deny(clippy::disallowed_methods)]
 
 use std::collections::HashSet;
-use std::time::Duration;
+use std::fmt::Display;
+use std::future::Future;
+use std::io;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
 
 use anyhow::{bail, Context};
-use clap::{arg, Arg};
+use clap::{arg, Arg, ArgMatches};
+use colored::Colorize;
 use dialoguer::theme::ColorfulTheme;
 use dialoguer::Confirm;
+use futures::{Stream, TryStreamExt};
+use indicatif::{ProgressBar, ProgressStyle};
 use once_cell::sync::Lazy;
-use quickwit_common::run_checklist;
 use quickwit_common::runtimes::RuntimesConfiguration;
 use quickwit_common::uri::Uri;
+use quickwit_common::{run_checklist, RED_COLOR};
 use quickwit_config::service::QuickwitService;
 use quickwit_config::{ConfigFormat, QuickwitConfig, SourceConfig, DEFAULT_QW_CONFIG_PATH};
 use quickwit_indexing::check_source_connectivity;
 use quickwit_metastore::quickwit_metastore_uri_resolver;
 use quickwit_storage::{load_file, quickwit_storage_uri_resolver};
 use regex::Regex;
+use serde::ser::SerializeSeq;
+use serde::{Serialize, Serializer};
 use tabled::object::Rows;
 use tabled::{Alignment, Header, Modify, Style, Table, Tabled};
+use tokio::time::MissedTickBehavior;
 use tracing::info;
 
+use crate::exit_code::{ExitCode, WithExitCode};
+
 pub mod cli;
+pub mod cluster;
+pub mod config;
+pub mod delete_task;
+pub mod exit_code;
+pub mod generate;
 pub mod index;
 #[cfg(feature = "jemalloc")]
 pub mod jemalloc;
+pub mod repl;
 pub mod service;
 pub mod source;
 pub mod split;
 pub mod stats;
 pub mod tool;
 
-/// Throughput calculation window size.
-const THROUGHPUT_WINDOW_SIZE: usize = 5;
+/// Interval at which the progress bars and spinners are redrawn.
+const PROGRESS_BAR_TICK_INTERVAL: Duration = Duration::from_millis(100);
 
 pub const QW_ENABLE_JAEGER_EXPORTER_ENV_KEY: &str = "QW_ENABLE_JAEGER_EXPORTER";
 
@@ -82,6 +100,184 @@ fn cluster_endpoint_arg<'a>() -> Arg<'a> {
         .global(true)
 }
 
+/// Format in which the commands displaying data print their output.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    Json,
+    PrettyJson,
+    Yaml,
+}
+
+impl FromStr for OutputFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(output_format_str: &str) -> anyhow::Result<Self> {
+        match output_format_str {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "pretty_json" => Ok(OutputFormat::PrettyJson),
+            "yaml" => Ok(OutputFormat::Yaml),
+            _ => bail!(
+                "Failed to parse output format `{output_format_str}`. Supported formats are: \
+                 `table`, `json`, `pretty_json`, and `yaml`."
+            ),
+        }
+    }
+}
+
+/// Parses the value of the global `--output` arg.
+fn parse_output_format(matches: &ArgMatches) -> anyhow::Result<OutputFormat> {
+    let output_format = matches
+        .value_of("output")
+        .map(OutputFormat::from_str)
+        .transpose()?
+        .unwrap_or_default();
+    Ok(output_format)
+}
+
+/// Prints `value` to stdout in the requested output format. `make_table` is only called when
+/// the output format is [`OutputFormat::Table`].
+fn print_output<T, D>(
+    output_format: OutputFormat,
+    value: &T,
+    make_table: impl FnOnce(&T) -> D,
+) -> anyhow::Result<()>
+where
+    T: Serialize,
+    D: Display,
+{
+    match output_format {
+        OutputFormat::Table => println!("{}", make_table(value)),
+        OutputFormat::Json => println!("{}", serde_json::to_string(value)?),
+        OutputFormat::PrettyJson => println!("{}", serde_json::to_string_pretty(value)?),
+        // The YAML serializer already terminates the document with a newline.
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(value)?),
+    }
+    Ok(())
+}
+
+/// Prints the pages of items yielded by `pages` to stdout as they are received, so that long
+/// lists are never held in memory at once. The JSON and YAML outputs are a single list, as printed
+/// by [`print_output`], whereas tables are printed page by page. `make_table` is only called when
+/// the output format is [`OutputFormat::Table`].
+async fn print_output_stream<T, D>(
+    output_format: OutputFormat,
+    mut pages: impl Stream<Item = anyhow::Result<Vec<T>>> + Unpin,
+    make_table: impl Fn(&[T]) -> D,
+) -> anyhow::Result<()>
+where
+    T: Serialize,
+    D: Display,
+{
+    match output_format {
+        OutputFormat::Table => {
+            while let Some(page) = pages.try_next().await? {
+                println!("{}", make_table(&page));
+            }
+        }
+        OutputFormat::Json => {
+            serialize_pages(&mut serde_json::Serializer::new(io::stdout()), pages).await?;
+            println!();
+        }
+        OutputFormat::PrettyJson => {
+            serialize_pages(&mut serde_json::Serializer::pretty(io::stdout()), pages).await?;
+            println!();
+        }
+        // The YAML serializer already terminates the document with a newline.
+        OutputFormat::Yaml => {
+            serialize_pages(&mut serde_yaml::Serializer::new(io::stdout()), pages).await?
+        }
+    }
+    Ok(())
+}
+
+/// Serializes the items of `pages` as a single sequence.
+async fn serialize_pages<S, T>(
+    serializer: S,
+    mut pages: impl Stream<Item = anyhow::Result<Vec<T>>> + Unpin,
+) -> anyhow::Result<()>
+where
+    S: Serializer,
+    S::Error: Send + Sync + 'static,
+    T: Serialize,
+{
+    let mut seq_serializer = serializer.serialize_seq(None)?;
+
+    while let Some(page) = pages.try_next().await? {
+        for item in &page {
+            seq_serializer.serialize_element(item)?;
+        }
+    }
+    seq_serializer.end()?;
+    Ok(())
+}
+
+fn watch_arg<'a>() -> Arg<'a> {
+    arg!(--watch <INTERVAL> "Refreshes the output at the given interval, for instance `5s`, until interrupted with Ctrl+C.")
+        .required(false)
+}
+
+/// Parses the value of the `--watch` arg.
+fn parse_watch_interval(matches: &ArgMatches) -> anyhow::Result<Option<Duration>> {
+    let watch_interval_opt = matches
+        .value_of("watch")
+        .map(parse_duration_with_unit)
+        .transpose()
+        .context("Failed to parse watch interval.")?;
+    if watch_interval_opt == Some(Duration::ZERO) {
+        bail!("Watch interval must be greater than zero.");
+    }
+    Ok(watch_interval_opt)
+}
+
+/// Calls `display` once or, in watch mode, every `watch_interval` until the command is
+/// interrupted. In table format, the terminal is cleared before each refresh; in the other
+/// formats, each refresh is printed as a new document so the output can be piped. Errors are
+/// printed without ending the watch, so the command survives a node restarting.
+async fn watch<F, Fut>(
+    watch_interval_opt: Option<Duration>,
+    output_format: OutputFormat,
+    mut display: F,
+) -> anyhow::Result<()>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = anyhow::Result<()>>,
+{
+    let watch_interval = match watch_interval_opt {
+        Some(watch_interval) => watch_interval,
+        None => return display().await,
+    };
+    let clear_screen = output_format == OutputFormat::Table && atty::is(atty::Stream::Stdout);
+    let mut interval = tokio::time::interval(watch_interval);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+
+    let watch_loop = async {
+        loop {
+            interval.tick().await;
+            if clear_screen {
+                print!("\x1B[2J\x1B[1;1H");
+                println!(
+                    "Every {}: {}\n",
+                    humantime::format_duration(watch_interval),
+                    humantime::format_rfc3339_seconds(SystemTime::now())
+                );
+            }
+            if let Err(error) = display().await {
+                eprintln!("{} {error:#}", "✖".color(RED_COLOR));
+            }
+        }
+    };
+    tokio::select! {
+        _ = watch_loop => {}
+        ctrl_c_res = tokio::signal::ctrl_c() => {
+            ctrl_c_res.context("Failed to listen for Ctrl+C signal.")?;
+        }
+    }
+    Ok(())
+}
+
 /// Parse duration with unit like `1s`, `2m`, `3h`, `5d`.
 pub fn parse_duration_with_unit(duration_with_unit_str: &str) -> anyhow::Result<Duration> {
     static DURATION_WITH_UNIT_RE: Lazy<Regex> =
@@ -116,26 +312,31 @@ pub fn start_actor_runtimes(services: &HashSet<QuickwitService>) -> anyhow::Resu
 async fn load_quickwit_config(config_uri: &Uri) -> anyhow::Result<QuickwitConfig> {
     let config_content = load_file(config_uri)
         .await
-        .context("Failed to load quickwit config.")?;
-    let config_format = ConfigFormat::sniff_from_uri(config_uri)?;
+        .context("Failed to load quickwit config.")
+        .with_exit_code(ExitCode::ConfigError)?;
+    let config_format =
+        ConfigFormat::sniff_from_uri(config_uri).with_exit_code(ExitCode::ConfigError)?;
     let config = QuickwitConfig::load(config_format, config_content.as_slice())
         .await
-        .with_context(|| format!("Failed to deserialize quickwit config `{config_uri}`."))?;
+        .with_context(|| format!("Failed to deserialize quickwit config `{config_uri}`."))
+        .with_exit_code(ExitCode::ConfigError)?;
     info!(config_uri=%config_uri, config=?config, "Loaded Quickwit config.");
     Ok(config)
 }
 
-/// Runs connectivity checks for a given `metastore_uri` and `index_id`.
+/// Runs connectivity checks for the metastore of a given `config` and `index_id`.
 /// Optionally, it takes a `SourceConfig` that will be checked instead
 /// of the index's sources.
 pub async fn run_index_checklist(
-    metastore_uri: &Uri,
+    config: &QuickwitConfig,
     index_id: &str,
     source_to_check: Option<&SourceConfig>,
 ) -> anyhow::Result<()> {
     let mut checks: Vec<(&str, anyhow::Result<()>)> = Vec::new();
     let metastore_uri_resolver = quickwit_metastore_uri_resolver();
-    let metastore = metastore_uri_resolver.resolve(metastore_uri).await?;
+    let metastore = metastore_uri_resolver
+        .resolve_with_config(&config.metastore_config, &config.metastore_uri)
+        .await?;
     checks.push(("metastore", metastore.check_connectivity().await));
 
     let index_metadata = metastore.index_metadata(index_id).await?;
@@ -182,6 +383,54 @@ pub fn make_table<T: Tabled>(
         .with(Modify::new(Rows::single(0)).with(Alignment::center()))
 }
 
+/// Creates a progress bar tracking a number of bytes processed, displaying the throughput and,
+/// when the total number of bytes is known, the ETA. The progress bar is drawn on stderr and is
+/// hidden when stderr is not a terminal.
+fn bytes_progress_bar(total_num_bytes_opt: Option<u64>) -> ProgressBar {
+    let (progress_bar, template) = match total_num_bytes_opt {
+        Some(total_num_bytes) => (
+            ProgressBar::new(total_num_bytes),
+            "{spinner:.blue} [{elapsed_precise}] [{bar:30}] {bytes}/{total_bytes} \
+             ({bytes_per_sec}, ETA {eta}) {msg}",
+        ),
+        None => (
+            ProgressBar::new_spinner(),
+            "{spinner:.blue} [{elapsed_precise}] {bytes} ({bytes_per_sec}) {msg}",
+        ),
+    };
+    progress_bar.set_style(progress_style(template));
+    progress_bar.enable_steady_tick(PROGRESS_BAR_TICK_INTERVAL);
+    progress_bar
+}
+
+/// Creates a progress bar tracking a number of documents processed, displaying the throughput
+/// and the ETA.
+fn docs_progress_bar(total_num_docs: u64) -> ProgressBar {
+    let progress_bar = ProgressBar::new(total_num_docs);
+    progress_bar.set_style(progress_style(
+        "{spinner:.blue} [{elapsed_precise}] [{bar:30}] {human_pos}/{human_len} docs ({per_sec}, \
+         ETA {eta}) {msg}",
+    ));
+    progress_bar.enable_steady_tick(PROGRESS_BAR_TICK_INTERVAL);
+    progress_bar
+}
+
+/// Creates a spinner for the operations whose progress cannot be measured.
+fn spinner(message: &'static str) -> ProgressBar {
+    let spinner = ProgressBar::new_spinner();
+    spinner.set_style(progress_style("{spinner:.blue} [{elapsed_precise}] {msg}"));
+    spinner.set_message(message);
+    spinner.enable_steady_tick(PROGRESS_BAR_TICK_INTERVAL);
+    spinner
+}
+
+fn progress_style(template: &str) -> ProgressStyle {
+    ProgressStyle::with_template(template)
+        .expect("Progress style should always be valid.")
+        .progress_chars("=> ")
+        .tick_strings(&["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏", "✔"])
+}
+
 /// Prompts user for confirmation.
 fn prompt_confirmation(prompt: &str, default: bool) -> bool {
     if Confirm::with_theme(&ColorfulTheme::default())
@@ -197,6 +446,173 @@ fn prompt_confirmation(prompt: &str, default: bool) -> bool {
     }
 }
 
+pub mod log_filter {
+    use std::sync::Mutex;
+
+    use anyhow::Context;
+    use once_cell::sync::OnceCell;
+    use tracing_subscriber::{reload, EnvFilter, Registry};
+
+    struct LogFilterHandle {
+        reload_handle: reload::Handle<EnvFilter, Registry>,
+        startup_filter: String,
+        current_filter: Mutex<String>,
+    }
+
+    static LOG_FILTER_HANDLE: OnceCell<LogFilterHandle> = OnceCell::new();
+
+    /// Registers the handle used to replace the log filter set up on startup.
+    pub fn init(reload_handle: reload::Handle<EnvFilter, Registry>, startup_filter: String) {
+        let log_filter_handle = LogFilterHandle {
+            reload_handle,
+            current_filter: Mutex::new(startup_filter.clone()),
+            startup_filter,
+        };
+        let _ = LOG_FILTER_HANDLE.set(log_filter_handle);
+    }
+
+    /// Returns whether the log filter can be changed at runtime.
+    pub fn is_reloadable() -> bool {
+        LOG_FILTER_HANDLE.get().is_some()
+    }
+
+    /// Returns the log filter currently applied, if it can be changed at runtime.
+    pub fn current() -> Option<String> {
+        let log_filter_handle = LOG_FILTER_HANDLE.get()?;
+        let current_filter = log_filter_handle
+            .current_filter
+            .lock()
+            .expect("The lock should not be poisoned.")
+            .clone();
+        Some(current_filter)
+    }
+
+    /// Replaces the log filter. `None` restores the filter set up on startup.
+    pub fn set(filter_opt: Option<&str>) -> anyhow::Result<()> {
+        let log_filter_handle = LOG_FILTER_HANDLE
+            .get()
+            .context("The log filter cannot be changed at runtime.")?;
+        let filter = filter_opt.unwrap_or(&log_filter_handle.startup_filter);
+        let env_filter = EnvFilter::try_new(filter)
+            .with_context(|| format!("Failed to parse log filter `{filter}`."))?;
+        log_filter_handle
+            .reload_handle
+            .reload(env_filter)
+            .context("Failed to reload log filter.")?;
+        *log_filter_handle
+            .current_filter
+            .lock()
+            .expect("The lock should not be poisoned.") = filter.to_string();
+        Ok(())
+    }
+}
+
+pub mod log_format {
+    use std::fmt;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    use quickwit_config::LogFormat;
+    use tracing::{span, Event, Subscriber};
+    use tracing_subscriber::field::RecordFields;
+    use tracing_subscriber::fmt::format::{DefaultFields, Format, Full, Json, JsonFields, Writer};
+    use tracing_subscriber::fmt::time::FormatTime;
+    use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+    use tracing_subscriber::registry::LookupSpan;
+
+    static IS_JSON_FORMAT: AtomicBool = AtomicBool::new(false);
+
+    /// Sets the format of the log events written from now on. The log format is only known once
+    /// the node config is loaded, so the events logged beforehand are written as text.
+    pub fn set(log_format: LogFormat) {
+        IS_JSON_FORMAT.store(log_format == LogFormat::Json, Ordering::Relaxed);
+    }
+
+    fn is_json_format() -> bool {
+        IS_JSON_FORMAT.load(Ordering::Relaxed)
+    }
+
+    /// Writes the log events as text lines or as JSON objects, depending on the log format.
+    pub struct EventFormat<T> {
+        text_format: Format<Full, T>,
+        json_format: Format<Json, T>,
+    }
+
+    impl<T: Clone> EventFormat<T> {
+        pub fn new(text_format: Format<Full, T>) -> Self {
+            let json_format = text_format
+                .clone()
+                .with_ansi(false)
+                .json()
+                .flatten_event(true);
+            Self {
+                text_format,
+                json_format,
+            }
+        }
+    }
+
+    impl<S, N, T> FormatEvent<S, N> for EventFormat<T>
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+        N: for<'a> FormatFields<'a> + 'static,
+        T: FormatTime,
+    {
+        fn format_event(
+            &self,
+            ctx: &FmtContext<'_, S, N>,
+            writer: Writer<'_>,
+            event: &Event<'_>,
+        ) -> fmt::Result {
+            if is_json_format() {
+                self.json_format.format_event(ctx, writer, event)
+            } else {
+                self.text_format.format_event(ctx, writer, event)
+            }
+        }
+    }
+
+    /// Formats the fields of the spans as text or as JSON, depending on the log format, so that
+    /// the JSON log events can embed them.
+    #[derive(Default)]
+    pub struct FieldsFormat {
+        text_fields: DefaultFields,
+        json_fields: JsonFields,
+    }
+
+    impl<'writer> FormatFields<'writer> for FieldsFormat {
+        fn format_fields<R: RecordFields>(
+            &self,
+            writer: Writer<'writer>,
+            fields: R,
+        ) -> fmt::Result {
+            if is_json_format() {
+                self.json_fields.format_fields(writer, fields)
+            } else {
+                self.text_fields.format_fields(writer, fields)
+            }
+        }
+
+        fn add_fields(
+            &self,
+            current: &'writer mut FormattedFields<Self>,
+            fields: &span::Record<'_>,
+        ) -> fmt::Result {
+            let current_fields = std::mem::take(&mut current.fields);
+            let (new_fields, result) = if is_json_format() {
+                let mut json_fields = FormattedFields::<JsonFields>::new(current_fields);
+                let result = self.json_fields.add_fields(&mut json_fields, fields);
+                (json_fields.fields, result)
+            } else {
+                let mut text_fields = FormattedFields::<DefaultFields>::new(current_fields);
+                let result = self.text_fields.add_fields(&mut text_fields, fields);
+                (text_fields.fields, result)
+            };
+            current.fields = new_fields;
+            result
+        }
+    }
+}
+
 pub mod busy_detector {
     use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
     use std::time::Instant;
@@ -277,9 +693,71 @@ pub mod busy_detector {
 
 #[cfg(test)]
 mod tests {
+    use std::str::FromStr;
+    use std::sync::atomic::{AtomicUsize, Ordering};
     use std::time::Duration;
 
-    use super::parse_duration_with_unit;
+    use clap::Command;
+
+    use super::{
+        parse_duration_with_unit, parse_watch_interval, serialize_pages, watch, watch_arg,
+        OutputFormat,
+    };
+
+    #[test]
+    fn test_parse_output_format() {
+        assert_eq!(
+            OutputFormat::from_str("table").unwrap(),
+            OutputFormat::Table
+        );
+        assert_eq!(OutputFormat::from_str("json").unwrap(), OutputFormat::Json);
+        assert_eq!(
+            OutputFormat::from_str("pretty_json").unwrap(),
+            OutputFormat::PrettyJson
+        );
+        assert_eq!(OutputFormat::from_str("yaml").unwrap(), OutputFormat::Yaml);
+        assert!(OutputFormat::from_str("xml").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_serialize_pages() {
+        let pages = || {
+            let pages: [anyhow::Result<Vec<u64>>; 3] =
+                [Ok(vec![1, 2]), Ok(Vec::new()), Ok(vec![3])];
+            futures::stream::iter(pages)
+        };
+        let items: Vec<u64> = vec![1, 2, 3];
+
+        let mut json = Vec::new();
+        serialize_pages(&mut serde_json::Serializer::new(&mut json), pages())
+            .await
+            .unwrap();
+        assert_eq!(json, serde_json::to_vec(&items).unwrap());
+
+        let mut pretty_json = Vec::new();
+        serialize_pages(
+            &mut serde_json::Serializer::pretty(&mut pretty_json),
+            pages(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(pretty_json, serde_json::to_vec_pretty(&items).unwrap());
+
+        let mut yaml = Vec::new();
+        serialize_pages(&mut serde_yaml::Serializer::new(&mut yaml), pages())
+            .await
+            .unwrap();
+        assert_eq!(yaml, serde_yaml::to_string(&items).unwrap().into_bytes());
+
+        let mut empty_json = Vec::new();
+        serialize_pages(
+            &mut serde_json::Serializer::new(&mut empty_json),
+            futures::stream::iter([anyhow::Ok(Vec::<u64>::new())]),
+        )
+        .await
+        .unwrap();
+        assert_eq!(empty_json, b"[]");
+    }
 
     #[test]
     fn test_parse_duration_with_unit() -> anyhow::Result<()> {
@@ -301,4 +779,50 @@ mod tests {
         assert!(parse_duration_with_unit("1h30").is_err());
         Ok(())
     }
+
+    #[test]
+    fn test_parse_watch_interval() -> anyhow::Result<()> {
+        let parse_watch_args = |args: &[&str]| {
+            let matches = Command::new("describe")
+                .arg(watch_arg())
+                .try_get_matches_from(args)
+                .unwrap();
+            parse_watch_interval(&matches)
+        };
+        assert_eq!(parse_watch_args(&["describe"])?, None);
+        assert_eq!(
+            parse_watch_args(&["describe", "--watch", "5s"])?,
+            Some(Duration::from_secs(5))
+        );
+        assert!(parse_watch_args(&["describe", "--watch", "0s"]).is_err());
+        assert!(parse_watch_args(&["describe", "--watch", "5"]).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_watch() {
+        let num_calls = &AtomicUsize::new(0);
+        watch(None, OutputFormat::Json, move || async move {
+            num_calls.fetch_add(1, Ordering::Relaxed);
+            Ok(())
+        })
+        .await
+        .unwrap();
+        assert_eq!(num_calls.load(Ordering::Relaxed), 1);
+
+        // The watch goes on when the display fails and only ends when interrupted.
+        let num_calls = &AtomicUsize::new(0);
+        let watch_future = watch(
+            Some(Duration::from_secs(1)),
+            OutputFormat::Json,
+            move || async move {
+                num_calls.fetch_add(1, Ordering::Relaxed);
+                anyhow::bail!("Failed to connect to the cluster.")
+            },
+        );
+        tokio::time::timeout(Duration::from_millis(1_500), watch_future)
+            .await
+            .unwrap_err();
+        assert_eq!(num_calls.load(Ordering::Relaxed), 2);
+    }
 }
@@ -26,18 +26,21 @@ use opentelemetry::sdk::{trace, Resource};
 use opentelemetry::{global, KeyValue};
 use opentelemetry_otlp::WithExportConfig;
 use quickwit_cli::cli::{build_cli, CliCommand};
+use quickwit_cli::exit_code::{exit_code_for_error, ExitCode};
 #[cfg(feature = "jemalloc")]
 use quickwit_cli::jemalloc::start_jemalloc_metrics_loop;
 use quickwit_cli::{
-    busy_detector, QW_ENABLE_JAEGER_EXPORTER_ENV_KEY, QW_ENABLE_OPENTELEMETRY_OTLP_EXPORTER_ENV_KEY,
+    busy_detector, log_filter, log_format, QW_ENABLE_JAEGER_EXPORTER_ENV_KEY,
+    QW_ENABLE_OPENTELEMETRY_OTLP_EXPORTER_ENV_KEY,
 };
 use quickwit_common::RED_COLOR;
+use quickwit_config::LogFormat;
 use quickwit_serve::BuildInfo;
 use quickwit_telemetry::payload::TelemetryEvent;
 use tracing::Level;
 use tracing_subscriber::fmt::time::UtcTime;
 use tracing_subscriber::prelude::*;
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::{reload, EnvFilter};
 
 fn setup_logging_and_tracing(
     level: Level,
@@ -56,20 +59,33 @@ fn setup_logging_and_tracing(
         .or_else(|_| EnvFilter::try_new(format!("quickwit={level}")))
         .context("Failed to set up tracing env filter.")?;
     global::set_text_map_propagator(TraceContextPropagator::new());
+    let startup_filter = env_filter.to_string();
+    let (env_filter, reload_handle) = reload::Layer::new(env_filter);
+    log_filter::init(reload_handle, startup_filter);
     let registry = tracing_subscriber::registry().with(env_filter);
-    let event_format = tracing_subscriber::fmt::format()
-        .with_target(true)
-        .with_ansi(ansi)
-        .with_timer(
-            // We do not rely on the Rfc3339 implementation, because it has a nanosecond precision.
-            // See discussion here: https://github.com/time-rs/time/discussions/418
-            UtcTime::new(
-                time::format_description::parse(
-                    "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z",
-                )
-                .expect("Time format invalid."),
+    // The node config sets the log format once it is loaded. The environment variable is read
+    // beforehand so that the startup logs are written in the same format.
+    if let Some(env_log_format) = env::var("QW_LOG_FORMAT")
+        .ok()
+        .and_then(|log_format_str| log_format_str.parse::<LogFormat>().ok())
+    {
+        log_format::set(env_log_format);
+    }
+    let event_format = log_format::EventFormat::new(
+        tracing_subscriber::fmt::format()
+            .with_target(true)
+            .with_ansi(ansi)
+            .with_timer(
+                // We do not rely on the Rfc3339 implementation, because it has a nanosecond
+                // precision. See discussion here: https://github.com/time-rs/time/discussions/418
+                UtcTime::new(
+                    time::format_description::parse(
+                        "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z",
+                    )
+                    .expect("Time format invalid."),
+                ),
             ),
-        );
+    );
     if std::env::var_os(QW_ENABLE_JAEGER_EXPORTER_ENV_KEY).is_some() {
         let tracer = opentelemetry_jaeger::new_agent_pipeline()
             .with_service_name("quickwit")
@@ -78,7 +94,11 @@ fn setup_logging_and_tracing(
             .context("Failed to initialize Jaeger exporter.")?;
         registry
             .with(tracing_opentelemetry::layer().with_tracer(tracer))
-            .with(tracing_subscriber::fmt::layer().event_format(event_format))
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .event_format(event_format)
+                    .fmt_fields(log_format::FieldsFormat::default()),
+            )
             .try_init()
             .context("Failed to set up tracing.")?;
     } else if std::env::var_os(QW_ENABLE_OPENTELEMETRY_OTLP_EXPORTER_ENV_KEY).is_some() {
@@ -95,12 +115,20 @@ fn setup_logging_and_tracing(
             .context("Failed to initialize OpenTelemetry OTLP exporter.")?;
         registry
             .with(tracing_opentelemetry::layer().with_tracer(tracer))
-            .with(tracing_subscriber::fmt::layer().event_format(event_format))
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .event_format(event_format)
+                    .fmt_fields(log_format::FieldsFormat::default()),
+            )
             .try_init()
             .context("Failed to set up tracing.")?;
     } else {
         registry
-            .with(tracing_subscriber::fmt::layer().event_format(event_format))
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .event_format(event_format)
+                    .fmt_fields(log_format::FieldsFormat::default()),
+            )
             .try_init()
             .context("Failed to set up tracing.")?;
     }
@@ -138,7 +166,7 @@ async fn main_impl() -> anyhow::Result<()> {
         Ok(command) => command,
         Err(err) => {
             eprintln!("Failed to parse command arguments: {err:?}");
-            std::process::exit(1);
+            std::process::exit(ExitCode::InvalidArgs.code());
         }
     };
 
@@ -152,9 +180,9 @@ async fn main_impl() -> anyhow::Result<()> {
     )?;
     let return_code: i32 = if let Err(err) = command.execute().await {
         eprintln!("{} Command failed: {:?}\n", "✘".color(RED_COLOR), err);
-        1
+        exit_code_for_error(&err).code()
     } else {
-        0
+        ExitCode::Success.code()
     };
     quickwit_telemetry::send_telemetry_event(TelemetryEvent::EndCommand { return_code }).await;
     telemetry_handle.terminate_telemetry().await;
@@ -179,15 +207,20 @@ mod tests {
     use std::str::FromStr;
     use std::time::Duration;
 
+    use byte_unit::Byte;
     use quickwit_cli::cli::{build_cli, CliCommand};
     use quickwit_cli::index::{
-        ClearIndexArgs, CreateIndexArgs, DeleteIndexArgs, DescribeIndexArgs, IndexCliCommand,
-        IngestDocsArgs, SearchIndexArgs,
+        ClearIndexArgs, CloneIndexArgs, CreateIndexArgs, DeleteByQueryArgs, DeleteIndexArgs,
+        DescribeIndexArgs, IndexCliCommand, IndexConfigUpdate, IngestDocsArgs, ReindexArgs,
+        RestoreIndexArgs, SearchIndexArgs, SqlIndexArgs, ToggleMergesArgs, UpdateIndexArgs,
+        UpdateIndexSettingsArgs,
     };
     use quickwit_cli::split::{DescribeSplitArgs, SplitCliCommand};
     use quickwit_cli::tool::{
-        ExtractSplitArgs, GarbageCollectIndexArgs, LocalIngestDocsArgs, MergeArgs, ToolCliCommand,
+        ExtractSplitArgs, GarbageCollectIndexArgs, LocalIngestDocsArgs, LocalSearchArgs, MergeArgs,
+        MergeSplitsArgs, ReconcileOrphanFilesArgs, ToolCliCommand,
     };
+    use quickwit_cli::OutputFormat;
     use quickwit_common::uri::Uri;
     use quickwit_rest_client::rest_client::CommitType;
     use reqwest::Url;
@@ -263,6 +296,321 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_update_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "update",
+            "--index",
+            "wikipedia",
+            "--index-config",
+            "index-conf.yaml",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_index_config_uri = Uri::from_str(&format!(
+            "file://{}/index-conf.yaml",
+            std::env::current_dir().unwrap().display()
+        ))
+        .unwrap();
+        let expected_cmd = CliCommand::Index(IndexCliCommand::Update(UpdateIndexArgs {
+            cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+            index_id: "wikipedia".to_string(),
+            index_config_uri: expected_index_config_uri.clone(),
+            expected_config_version_opt: None,
+        }));
+        assert_eq!(command, expected_cmd);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "update",
+            "--index",
+            "wikipedia",
+            "--index-config",
+            "index-conf.yaml",
+            "--expected-config-version",
+            "3",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_cmd = CliCommand::Index(IndexCliCommand::Update(UpdateIndexArgs {
+            cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+            index_id: "wikipedia".to_string(),
+            index_config_uri: expected_index_config_uri,
+            expected_config_version_opt: Some(3),
+        }));
+        assert_eq!(command, expected_cmd);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_update_settings_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "update",
+            "search-settings",
+            "--index",
+            "wikipedia",
+            "--default-search-fields",
+            "title",
+            "body",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_cmd =
+            CliCommand::Index(IndexCliCommand::UpdateSettings(UpdateIndexSettingsArgs {
+                cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+                index_id: "wikipedia".to_string(),
+                index_config_update: IndexConfigUpdate::SearchSettings {
+                    default_search_fields: vec!["title".to_string(), "body".to_string()],
+                },
+                assume_yes: false,
+            }));
+        assert_eq!(command, expected_cmd);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "update",
+            "retention-policy",
+            "--index",
+            "wikipedia",
+            "--period",
+            "30 days",
+            "--max-size",
+            "10GB",
+            "--yes",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_cmd =
+            CliCommand::Index(IndexCliCommand::UpdateSettings(UpdateIndexSettingsArgs {
+                cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+                index_id: "wikipedia".to_string(),
+                index_config_update: IndexConfigUpdate::RetentionPolicy {
+                    period_opt: Some("30 days".to_string()),
+                    max_size_opt: Some(Byte::from_bytes(10_000_000_000)),
+                    schedule_opt: None,
+                },
+                assume_yes: true,
+            }));
+        assert_eq!(command, expected_cmd);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "update",
+            "retention-policy",
+            "--index",
+            "wikipedia",
+            "--disable",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::UpdateSettings(UpdateIndexSettingsArgs {
+                index_config_update: IndexConfigUpdate::DisableRetentionPolicy,
+                ..
+            }))
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "update",
+            "indexing-settings",
+            "--index",
+            "wikipedia",
+            "--commit-timeout-secs",
+            "30",
+            "--heap-size",
+            "3GB",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_cmd =
+            CliCommand::Index(IndexCliCommand::UpdateSettings(UpdateIndexSettingsArgs {
+                cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+                index_id: "wikipedia".to_string(),
+                index_config_update: IndexConfigUpdate::IndexingSettings {
+                    commit_timeout_secs_opt: Some(30),
+                    split_num_docs_target_opt: None,
+                    heap_size_opt: Some(Byte::from_bytes(3_000_000_000)),
+                    max_merge_write_throughput_opt: None,
+                },
+                assume_yes: false,
+            }));
+        assert_eq!(command, expected_cmd);
+
+        let app = build_cli().no_binary_name(true);
+        let matches_res = app.try_get_matches_from([
+            "index",
+            "update",
+            "retention-policy",
+            "--index",
+            "wikipedia",
+            "--disable",
+            "--period",
+            "30 days",
+        ]);
+        assert!(matches_res.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_restore_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(["index", "restore", "--index", "wikipedia"])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_cmd = CliCommand::Index(IndexCliCommand::Restore(RestoreIndexArgs {
+            cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+            index_id: "wikipedia".to_string(),
+        }));
+        assert_eq!(command, expected_cmd);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_toggle_merges_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches =
+            app.try_get_matches_from(["index", "pause-merges", "--index", "wikipedia"])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_cmd = CliCommand::Index(IndexCliCommand::ToggleMerges(ToggleMergesArgs {
+            cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+            index_id: "wikipedia".to_string(),
+            pause: true,
+        }));
+        assert_eq!(command, expected_cmd);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "resume-merges",
+            "--index",
+            "wikipedia",
+            "--endpoint",
+            "http://localhost:7280",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_cmd = CliCommand::Index(IndexCliCommand::ToggleMerges(ToggleMergesArgs {
+            cluster_endpoint: Url::from_str("http://localhost:7280").unwrap(),
+            index_id: "wikipedia".to_string(),
+            pause: false,
+        }));
+        assert_eq!(command, expected_cmd);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_clone_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "clone",
+            "--index",
+            "wikipedia",
+            "--from",
+            "s3://staging-bucket/indexes",
+            "--to",
+            "s3://prod-bucket/indexes",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_cmd = CliCommand::Index(IndexCliCommand::Clone(CloneIndexArgs {
+            source_metastore_uri: Uri::from_well_formed("s3://staging-bucket/indexes"),
+            target_metastore_uri: Uri::from_well_formed("s3://prod-bucket/indexes"),
+            index_id: "wikipedia".to_string(),
+            target_index_id: "wikipedia".to_string(),
+            target_index_uri_opt: None,
+        }));
+        assert_eq!(command, expected_cmd);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "clone",
+            "--index",
+            "wikipedia",
+            "--from",
+            "s3://staging-bucket/indexes",
+            "--to",
+            "s3://prod-bucket/indexes",
+            "--target-index",
+            "wikipedia-prod",
+            "--target-index-uri",
+            "s3://prod-bucket/indexes/wikipedia-prod",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_cmd = CliCommand::Index(IndexCliCommand::Clone(CloneIndexArgs {
+            source_metastore_uri: Uri::from_well_formed("s3://staging-bucket/indexes"),
+            target_metastore_uri: Uri::from_well_formed("s3://prod-bucket/indexes"),
+            index_id: "wikipedia".to_string(),
+            target_index_id: "wikipedia-prod".to_string(),
+            target_index_uri_opt: Some(Uri::from_well_formed(
+                "s3://prod-bucket/indexes/wikipedia-prod",
+            )),
+        }));
+        assert_eq!(command, expected_cmd);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_reindex_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "reindex",
+            "--index",
+            "hdfs-logs",
+            "--target-index",
+            "hdfs-logs-v2",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_cmd = CliCommand::Index(IndexCliCommand::Reindex(ReindexArgs {
+            cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+            index_id: "hdfs-logs".to_string(),
+            target_index_id: "hdfs-logs-v2".to_string(),
+            checkpoint_path: PathBuf::from("reindex-hdfs-logs-hdfs-logs-v2.json"),
+        }));
+        assert_eq!(command, expected_cmd);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "reindex",
+            "--index",
+            "hdfs-logs",
+            "--target-index",
+            "hdfs-logs-v2",
+            "--checkpoint",
+            "/tmp/reindex.json",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_cmd = CliCommand::Index(IndexCliCommand::Reindex(ReindexArgs {
+            cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+            index_id: "hdfs-logs".to_string(),
+            target_index_id: "hdfs-logs-v2".to_string(),
+            checkpoint_path: PathBuf::from("/tmp/reindex.json"),
+        }));
+        assert_eq!(command, expected_cmd);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_index_gc_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches =
+            app.try_get_matches_from(["index", "gc", "--index", "wikipedia", "--dry-run"])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_cmd = CliCommand::Index(IndexCliCommand::GarbageCollect(
+            quickwit_cli::index::GarbageCollectIndexArgs {
+                cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+                index_id: "wikipedia".to_string(),
+                dry_run: true,
+            },
+        ));
+        assert_eq!(command, expected_cmd);
+        Ok(())
+    }
+
     #[test]
     fn test_parse_ingest_args() -> anyhow::Result<()> {
         let app = build_cli().no_binary_name(true);
@@ -447,6 +795,26 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_sql_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "index",
+            "sql",
+            "--query",
+            "SELECT count(*) FROM wikipedia",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Sql(SqlIndexArgs {
+                query,
+                ..
+            })) if &query == "SELECT count(*) FROM wikipedia"
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_parse_delete_args() {
         let app = build_cli().no_binary_name(true);
@@ -478,6 +846,60 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_parse_delete_by_query_args() {
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from([
+                "index",
+                "delete-by-query",
+                "--index",
+                "wikipedia",
+                "--query",
+                "title:spam",
+            ])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(&matches).unwrap();
+        let expected_command =
+            CliCommand::Index(IndexCliCommand::DeleteByQuery(DeleteByQueryArgs {
+                cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+                index_id: "wikipedia".to_string(),
+                query: "title:spam".to_string(),
+                start_timestamp: None,
+                end_timestamp: None,
+                assume_yes: false,
+            }));
+        assert_eq!(command, expected_command);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from([
+                "index",
+                "delete-by-query",
+                "--index",
+                "wikipedia",
+                "--query",
+                "title:spam",
+                "--start-timestamp",
+                "1650000000",
+                "--end-timestamp",
+                "1660000000",
+                "--yes",
+            ])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(&matches).unwrap();
+        let expected_command =
+            CliCommand::Index(IndexCliCommand::DeleteByQuery(DeleteByQueryArgs {
+                cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+                index_id: "wikipedia".to_string(),
+                query: "title:spam".to_string(),
+                start_timestamp: Some(1650000000),
+                end_timestamp: Some(1660000000),
+                assume_yes: true,
+            }));
+        assert_eq!(command, expected_command);
+    }
+
     #[test]
     fn test_parse_describe_index_args() {
         let app = build_cli().no_binary_name(true);
@@ -489,9 +911,50 @@ mod tests {
             command,
             CliCommand::Index(IndexCliCommand::Describe(DescribeIndexArgs {
                 index_id,
+                output_format: OutputFormat::Table,
+                watch_interval_opt: None,
                 ..
             })) if &index_id == "wikipedia"
         ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from([
+                "index",
+                "describe",
+                "--index",
+                "wikipedia",
+                "--output",
+                "json",
+            ])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(&matches).unwrap();
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Describe(DescribeIndexArgs {
+                output_format: OutputFormat::Json,
+                ..
+            }))
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from(["index", "describe", "--index", "wikipedia", "--watch", "5s"])
+            .unwrap();
+        let command = CliCommand::parse_cli_args(&matches).unwrap();
+        assert!(matches!(
+            command,
+            CliCommand::Index(IndexCliCommand::Describe(DescribeIndexArgs {
+                watch_interval_opt: Some(watch_interval),
+                ..
+            })) if watch_interval == Duration::from_secs(5)
+        ));
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app
+            .try_get_matches_from(["index", "describe", "--index", "wikipedia", "--watch", "5"])
+            .unwrap();
+        assert!(CliCommand::parse_cli_args(&matches).is_err());
     }
 
     #[test]
@@ -546,6 +1009,63 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_local_search_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "tool",
+            "local-search",
+            "--index",
+            "wikipedia",
+            "--query",
+            "Barack Obama",
+            "--config",
+            "/config.yaml",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_command = CliCommand::Tool(ToolCliCommand::LocalSearch(LocalSearchArgs {
+            config_uri: Uri::from_str("file:///config.yaml").unwrap(),
+            index_id: "wikipedia".to_string(),
+            index_uri_opt: None,
+            query: "Barack Obama".to_string(),
+            aggregation: None,
+            max_hits: 20,
+            start_offset: 0,
+            search_fields: None,
+            snippet_fields: None,
+            start_timestamp: None,
+            end_timestamp: None,
+            sort_by_score: false,
+        }));
+        assert_eq!(command, expected_command);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "tool",
+            "local-search",
+            "--index",
+            "wikipedia",
+            "--index-uri",
+            "s3://my-bucket/indexes/wikipedia",
+            "--query",
+            "Barack Obama",
+            "--max-hits",
+            "5",
+            "--sort-by-score",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Tool(ToolCliCommand::LocalSearch(LocalSearchArgs {
+                index_uri_opt: Some(index_uri),
+                max_hits: 5,
+                sort_by_score: true,
+                ..
+            })) if index_uri == Uri::from_well_formed("s3://my-bucket/indexes/wikipedia")
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_parse_garbage_collect_args() -> anyhow::Result<()> {
         let app = build_cli().no_binary_name(true);
@@ -594,6 +1114,33 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_reconcile_orphans_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "tool",
+            "reconcile-orphans",
+            "--index",
+            "wikipedia",
+            "--grace-period",
+            "2h",
+            "--config",
+            "/config.yaml",
+            "--dry-run",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        assert!(matches!(
+            command,
+            CliCommand::Tool(ToolCliCommand::ReconcileOrphanFiles(ReconcileOrphanFilesArgs {
+                index_id,
+                grace_period,
+                dry_run: true,
+                ..
+            })) if &index_id == "wikipedia" && grace_period == Duration::from_secs(2 * 60 * 60)
+        ));
+        Ok(())
+    }
+
     #[test]
     fn test_parse_merge_args() -> anyhow::Result<()> {
         let app = build_cli().no_binary_name(true);
@@ -618,4 +1165,47 @@ mod tests {
         ));
         Ok(())
     }
+
+    #[test]
+    fn test_parse_merge_splits_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "tool",
+            "merge-splits",
+            "--index",
+            "wikipedia",
+            "--config",
+            "/config.yaml",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_command = CliCommand::Tool(ToolCliCommand::MergeSplits(MergeSplitsArgs {
+            config_uri: Uri::from_str("file:///config.yaml").unwrap(),
+            index_id: "wikipedia".to_string(),
+            source_id_opt: None,
+            dry_run: false,
+        }));
+        assert_eq!(command, expected_command);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from([
+            "tool",
+            "merge-splits",
+            "--index",
+            "wikipedia",
+            "--source",
+            "kafka-source",
+            "--dry-run",
+            "--config",
+            "/config.yaml",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_command = CliCommand::Tool(ToolCliCommand::MergeSplits(MergeSplitsArgs {
+            config_uri: Uri::from_str("file:///config.yaml").unwrap(),
+            index_id: "wikipedia".to_string(),
+            source_id_opt: Some("kafka-source".to_string()),
+            dry_run: true,
+        }));
+        assert_eq!(command, expected_command);
+        Ok(())
+    }
 }
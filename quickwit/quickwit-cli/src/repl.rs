@@ -0,0 +1,577 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::VecDeque;
+use std::str::FromStr;
+
+use anyhow::{bail, Context};
+use clap::{arg, ArgMatches, Command};
+use colored::Colorize;
+use dialoguer::theme::ColorfulTheme;
+use dialoguer::{Completion, History, Input};
+use itertools::Itertools;
+use quickwit_common::RED_COLOR;
+use quickwit_config::build_doc_mapper;
+use quickwit_metastore::IndexMetadata;
+use quickwit_rest_client::rest_client::{QuickwitClient, Transport};
+use quickwit_search::SearchResponseRest;
+use quickwit_serve::SearchRequestQueryString;
+use reqwest::Url;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
+use tabled::builder::Builder;
+use tabled::object::Rows;
+use tabled::{Alignment, Modify, Style, Table, Tabled};
+use tracing::debug;
+
+use crate::index::make_list_indexes_table;
+use crate::{cluster_endpoint_arg, make_table, parse_output_format, print_output, OutputFormat};
+
+/// Default number of hits displayed for each query.
+const DEFAULT_MAX_HITS: usize = 20;
+
+/// Maximum number of entries kept in the query history.
+const MAX_HISTORY_LEN: usize = 1_000;
+
+const REPL_COMMANDS: [&str; 9] = [
+    ".exit", ".fields", ".format", ".help", ".history", ".indexes", ".limit", ".quit", ".use",
+];
+
+const REPL_HELP: &str = "\
+Enter a query to search the selected index, or one of the following commands:
+  .indexes                List the indexes of the cluster.
+  .use <index>            Select the index to query.
+  .fields                 List the fields of the selected index.
+  .format <format>        Set the output format: `table`, `json`, `pretty_json`, or `yaml`.
+  .limit <max hits>       Set the maximum number of hits displayed for each query.
+  .history                Display the query history.
+  .help                   Display this help message.
+  .quit                   Exit the REPL.
+Use the Up/Down arrows to browse the history and Tab or the Right arrow to complete field names.";
+
+pub fn build_repl_command<'a>() -> Command<'a> {
+    Command::new("repl")
+        .about("Starts an interactive prompt to query the indexes of a cluster.")
+        .long_about(
+            "Starts an interactive prompt to query the indexes of a cluster. The prompt keeps a \
+             history of the queries and auto-completes the field names of the selected index. \
+             Type `.help` in the prompt to display the available commands.",
+        )
+        .arg(cluster_endpoint_arg())
+        .args(
+            &[arg!(--index <INDEX> "ID of the index selected at startup.")
+                .display_order(2)
+                .required(false)],
+        )
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct ReplCliCommand {
+    pub cluster_endpoint: Url,
+    pub index_id_opt: Option<String>,
+    pub output_format: OutputFormat,
+}
+
+impl ReplCliCommand {
+    pub fn parse_cli_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let index_id_opt = matches.value_of("index").map(String::from);
+        let output_format = parse_output_format(matches)?;
+        Ok(ReplCliCommand {
+            cluster_endpoint,
+            index_id_opt,
+            output_format,
+        })
+    }
+
+    pub async fn execute(self) -> anyhow::Result<()> {
+        debug!(args=?self, "repl");
+        let transport = Transport::new(self.cluster_endpoint);
+        let qw_client = QuickwitClient::new(transport);
+        let mut session = ReplSession::new(qw_client, self.output_format);
+        session.refresh_index_ids().await?;
+
+        if let Some(index_id) = self.index_id_opt {
+            session.use_index(index_id).await?;
+        }
+        println!("Type `.help` to display the available commands.");
+
+        loop {
+            let prompt = match &session.index_id_opt {
+                Some(index_id) => format!("quickwit ({index_id})"),
+                None => "quickwit".to_string(),
+            };
+            let completion = &session.completion;
+            let history = &mut session.history;
+            // Reading from the terminal blocks the current thread.
+            let line_res = tokio::task::block_in_place(|| {
+                Input::<String>::with_theme(&ColorfulTheme::default())
+                    .with_prompt(prompt)
+                    .allow_empty(true)
+                    .completion_with(completion)
+                    .history_with(history)
+                    .interact_text()
+            });
+            // The prompt fails when the input is closed or is not a terminal.
+            let Ok(line) = line_res else {
+                return Ok(());
+            };
+            let repl_command = match parse_repl_command(&line) {
+                Ok(Some(ReplCommand::Quit)) => return Ok(()),
+                Ok(Some(repl_command)) => repl_command,
+                Ok(None) => continue,
+                Err(error) => {
+                    println!("{} {error:#}", "✘".color(RED_COLOR));
+                    continue;
+                }
+            };
+            if let Err(error) = session.execute(repl_command).await {
+                println!("{} {error:#}", "✘".color(RED_COLOR));
+            }
+        }
+    }
+}
+
+/// A command entered in the REPL prompt.
+#[derive(Debug, Eq, PartialEq)]
+enum ReplCommand {
+    Help,
+    ListIndexes,
+    UseIndex(String),
+    ListFields,
+    SetOutputFormat(OutputFormat),
+    SetMaxHits(usize),
+    ShowHistory,
+    Quit,
+    Search(String),
+}
+
+/// Parses a line entered in the REPL prompt. Returns `None` if the line is blank.
+fn parse_repl_command(line: &str) -> anyhow::Result<Option<ReplCommand>> {
+    let line = line.trim();
+
+    if line.is_empty() {
+        return Ok(None);
+    }
+    if !line.starts_with('.') {
+        return Ok(Some(ReplCommand::Search(line.to_string())));
+    }
+    let (command, argument) = line
+        .split_once(char::is_whitespace)
+        .map(|(command, argument)| (command, argument.trim()))
+        .unwrap_or((line, ""));
+    let repl_command = match (command, argument) {
+        (".help", "") => ReplCommand::Help,
+        (".indexes", "") => ReplCommand::ListIndexes,
+        (".use", index_id) if !index_id.is_empty() => ReplCommand::UseIndex(index_id.to_string()),
+        (".fields", "") => ReplCommand::ListFields,
+        (".format", output_format) if !output_format.is_empty() => {
+            ReplCommand::SetOutputFormat(OutputFormat::from_str(output_format)?)
+        }
+        (".limit", max_hits) if !max_hits.is_empty() => {
+            let max_hits = max_hits
+                .parse()
+                .with_context(|| format!("Invalid max hits `{max_hits}`."))?;
+            ReplCommand::SetMaxHits(max_hits)
+        }
+        (".history", "") => ReplCommand::ShowHistory,
+        (".quit" | ".exit", "") => ReplCommand::Quit,
+        (".use" | ".format" | ".limit", _) => {
+            bail!("Command `{command}` expects an argument. Type `.help` for usage.")
+        }
+        _ => bail!("Invalid command `{line}`. Type `.help` for usage."),
+    };
+    Ok(Some(repl_command))
+}
+
+struct ReplSession {
+    qw_client: QuickwitClient,
+    index_id_opt: Option<String>,
+    output_format: OutputFormat,
+    max_hits: usize,
+    completion: ReplCompletion,
+    history: ReplHistory,
+}
+
+impl ReplSession {
+    fn new(qw_client: QuickwitClient, output_format: OutputFormat) -> Self {
+        Self {
+            qw_client,
+            index_id_opt: None,
+            output_format,
+            max_hits: DEFAULT_MAX_HITS,
+            completion: ReplCompletion::default(),
+            history: ReplHistory::default(),
+        }
+    }
+
+    async fn execute(&mut self, repl_command: ReplCommand) -> anyhow::Result<()> {
+        match repl_command {
+            ReplCommand::Help => println!("{REPL_HELP}"),
+            ReplCommand::ListIndexes => {
+                let indexes_metadatas = self.refresh_index_ids().await?;
+                print_output(
+                    self.output_format,
+                    &indexes_metadatas,
+                    |indexes_metadatas| {
+                        make_list_indexes_table(
+                            indexes_metadatas
+                                .iter()
+                                .map(|index_metadata| index_metadata.index_config().clone()),
+                        )
+                    },
+                )?;
+            }
+            ReplCommand::UseIndex(index_id) => self.use_index(index_id).await?,
+            ReplCommand::ListFields => {
+                let index_id = self.selected_index_id()?;
+                let index_metadata = self.qw_client.indexes().get(index_id).await?;
+                let fields = list_fields(&index_metadata)?;
+                print_output(self.output_format, &fields, |fields| {
+                    make_table("Fields", fields.iter().cloned(), false)
+                })?;
+            }
+            ReplCommand::SetOutputFormat(output_format) => self.output_format = output_format,
+            ReplCommand::SetMaxHits(max_hits) => self.max_hits = max_hits,
+            ReplCommand::ShowHistory => {
+                for (position, query) in self.history.entries.iter().rev().enumerate() {
+                    println!("{:>5}  {query}", position + 1);
+                }
+            }
+            ReplCommand::Quit => {}
+            ReplCommand::Search(query) => {
+                let index_id = self.selected_index_id()?;
+                let search_request = SearchRequestQueryString {
+                    query,
+                    max_hits: self.max_hits as u64,
+                    ..Default::default()
+                };
+                let search_response = self.qw_client.search(index_id, search_request).await?;
+                print_output(self.output_format, &search_response, |search_response| {
+                    format!(
+                        "{}\n{} hits ({} ms)",
+                        make_hits_table(search_response),
+                        search_response.num_hits,
+                        search_response.elapsed_time_micros / 1_000
+                    )
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn selected_index_id(&self) -> anyhow::Result<&str> {
+        self.index_id_opt
+            .as_deref()
+            .context("No index selected. Select an index with `.use <index>`.")
+    }
+
+    /// Fetches the list of indexes and updates the index IDs used for completion.
+    async fn refresh_index_ids(&mut self) -> anyhow::Result<Vec<IndexMetadata>> {
+        let indexes_metadatas = self.qw_client.indexes().list().await?;
+        self.completion.index_ids = indexes_metadatas
+            .iter()
+            .map(|index_metadata| index_metadata.index_id().to_string())
+            .sorted()
+            .collect();
+        Ok(indexes_metadatas)
+    }
+
+    /// Selects the index to query and fetches its field names for completion.
+    async fn use_index(&mut self, index_id: String) -> anyhow::Result<()> {
+        let index_metadata = self.qw_client.indexes().get(&index_id).await?;
+        self.completion.field_names = list_fields(&index_metadata)?
+            .into_iter()
+            .map(|field| field.field_name)
+            .collect();
+        self.index_id_opt = Some(index_id);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Tabled)]
+struct FieldRow {
+    #[tabled(rename = "Field")]
+    field_name: String,
+    #[tabled(rename = "Type")]
+    field_type: String,
+}
+
+/// Lists the fields of the doc mapping of an index, excluding the internal fields.
+fn list_fields(index_metadata: &IndexMetadata) -> anyhow::Result<Vec<FieldRow>> {
+    let index_config = index_metadata.index_config();
+    let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)?;
+    let fields = doc_mapper
+        .schema()
+        .fields()
+        .filter(|(_, field_entry)| !field_entry.name().starts_with('_'))
+        .map(|(_, field_entry)| FieldRow {
+            field_name: field_entry.name().to_string(),
+            field_type: format!("{:?}", field_entry.field_type().value_type()).to_lowercase(),
+        })
+        .sorted_by(|left, right| left.field_name.cmp(&right.field_name))
+        .collect();
+    Ok(fields)
+}
+
+/// Renders the hits of a search response as a table with one column per top-level field.
+fn make_hits_table(search_response: &SearchResponseRest) -> Table {
+    let mut columns: Vec<&str> = Vec::new();
+
+    for hit in &search_response.hits {
+        if let Some(hit_obj) = hit.as_object() {
+            for key in hit_obj.keys() {
+                if !columns.contains(&key.as_str()) {
+                    columns.push(key);
+                }
+            }
+        }
+    }
+    let mut builder = Builder::default();
+    builder.set_columns(columns.iter().copied());
+
+    for hit in &search_response.hits {
+        let record = columns.iter().map(|column| match hit.get(column) {
+            Some(JsonValue::String(value)) => value.clone(),
+            Some(JsonValue::Null) | None => String::new(),
+            Some(value) => value.to_string(),
+        });
+        builder.add_record(record);
+    }
+    builder
+        .build()
+        .with(Modify::new(Rows::new(1..)).with(Alignment::left()))
+        .with(Style::ascii())
+}
+
+/// Auto-completes the commands, the index IDs, and the field names of the selected index.
+#[derive(Default)]
+struct ReplCompletion {
+    index_ids: Vec<String>,
+    field_names: Vec<String>,
+}
+
+impl Completion for ReplCompletion {
+    fn get(&self, input: &str) -> Option<String> {
+        if let Some(index_id_prefix) = input.strip_prefix(".use ") {
+            let index_id =
+                complete_word(index_id_prefix, self.index_ids.iter().map(String::as_str))?;
+            return Some(format!(".use {index_id}"));
+        }
+        if input.starts_with('.') && !input.contains(char::is_whitespace) {
+            return complete_word(input, REPL_COMMANDS);
+        }
+        // Completes the last word of the query, ignoring the leading occurrence operators and
+        // parentheses.
+        let word_start = input
+            .rfind(|ch: char| ch.is_whitespace() || matches!(ch, '(' | '+' | '-'))
+            .map(|position| position + 1)
+            .unwrap_or(0);
+        let (head, word) = input.split_at(word_start);
+
+        if word.is_empty() || word.contains(':') {
+            return None;
+        }
+        let field_name = complete_word(word, self.field_names.iter().map(String::as_str))?;
+        let suffix = if self.field_names.contains(&field_name) {
+            ":"
+        } else {
+            ""
+        };
+        Some(format!("{head}{field_name}{suffix}"))
+    }
+}
+
+/// Returns the candidate starting with `prefix` if it is unique, otherwise the longest common
+/// prefix of the candidates if it is longer than `prefix`.
+fn complete_word<'a>(
+    prefix: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<String> {
+    let matching_candidates: Vec<&str> = candidates
+        .into_iter()
+        .filter(|candidate| candidate.starts_with(prefix))
+        .collect();
+    let (first_candidate, other_candidates) = matching_candidates.split_first()?;
+    let common_prefix_len =
+        other_candidates
+            .iter()
+            .fold(first_candidate.len(), |common_prefix_len, candidate| {
+                first_candidate
+                    .bytes()
+                    .zip(candidate.bytes())
+                    .take(common_prefix_len)
+                    .take_while(|(left, right)| left == right)
+                    .count()
+            });
+    let common_prefix = &first_candidate[..common_prefix_len];
+
+    if common_prefix.len() > prefix.len() {
+        Some(common_prefix.to_string())
+    } else {
+        None
+    }
+}
+
+/// In-memory history of the queries and commands entered in the REPL, most recent first.
+#[derive(Default)]
+struct ReplHistory {
+    entries: VecDeque<String>,
+}
+
+impl History<String> for ReplHistory {
+    fn read(&self, position: usize) -> Option<String> {
+        self.entries.get(position).cloned()
+    }
+
+    fn write(&mut self, line: &String) {
+        let line = line.trim();
+
+        if line.is_empty() || self.entries.front().map(String::as_str) == Some(line) {
+            return;
+        }
+        if self.entries.len() == MAX_HISTORY_LEN {
+            self.entries.pop_back();
+        }
+        self.entries.push_front(line.to_string());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::cli::{build_cli, CliCommand};
+
+    #[test]
+    fn test_parse_repl_args() -> anyhow::Result<()> {
+        let command = build_cli().no_binary_name(true);
+        let matches = command.try_get_matches_from(vec![
+            "repl",
+            "--index",
+            "hdfs-logs",
+            "--endpoint",
+            "http://127.0.0.1:8000",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_command = CliCommand::Repl(ReplCliCommand {
+            cluster_endpoint: Url::from_str("http://127.0.0.1:8000").unwrap(),
+            index_id_opt: Some("hdfs-logs".to_string()),
+            output_format: OutputFormat::Table,
+        });
+        assert_eq!(command, expected_command);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_repl_command() {
+        assert_eq!(parse_repl_command("  ").unwrap(), None);
+        assert_eq!(
+            parse_repl_command(" severity_text:ERROR ").unwrap(),
+            Some(ReplCommand::Search("severity_text:ERROR".to_string()))
+        );
+        assert_eq!(
+            parse_repl_command(".use  hdfs-logs").unwrap(),
+            Some(ReplCommand::UseIndex("hdfs-logs".to_string()))
+        );
+        assert_eq!(
+            parse_repl_command(".format json").unwrap(),
+            Some(ReplCommand::SetOutputFormat(OutputFormat::Json))
+        );
+        assert_eq!(
+            parse_repl_command(".limit 5").unwrap(),
+            Some(ReplCommand::SetMaxHits(5))
+        );
+        assert_eq!(
+            parse_repl_command(".exit").unwrap(),
+            Some(ReplCommand::Quit)
+        );
+        parse_repl_command(".use").unwrap_err();
+        parse_repl_command(".limit foo").unwrap_err();
+        parse_repl_command(".format csv").unwrap_err();
+        parse_repl_command(".indexes foo").unwrap_err();
+        parse_repl_command(".foo").unwrap_err();
+    }
+
+    #[test]
+    fn test_repl_completion() {
+        let completion = ReplCompletion {
+            index_ids: vec!["hdfs-logs".to_string(), "wikipedia".to_string()],
+            field_names: vec![
+                "attributes.city".to_string(),
+                "attributes.class".to_string(),
+                "body".to_string(),
+                "severity_text".to_string(),
+            ],
+        };
+        assert_eq!(completion.get(".in").unwrap(), ".indexes");
+        assert_eq!(completion.get(".h"), None);
+        assert_eq!(completion.get(".use w").unwrap(), ".use wikipedia");
+        assert_eq!(completion.get("sev").unwrap(), "severity_text:");
+        assert_eq!(
+            completion.get("body:foo AND (+attr").unwrap(),
+            "body:foo AND (+attributes.c"
+        );
+        assert_eq!(completion.get("attributes.ci").unwrap(), "attributes.city:");
+        assert_eq!(completion.get("body:fo"), None);
+        assert_eq!(completion.get("foo"), None);
+        assert_eq!(completion.get(""), None);
+    }
+
+    #[test]
+    fn test_repl_history() {
+        let mut history = ReplHistory::default();
+        history.write(&"body:foo".to_string());
+        history.write(&"body:foo".to_string());
+        history.write(&"  ".to_string());
+        history.write(&".fields".to_string());
+        assert_eq!(history.read(0).unwrap(), ".fields");
+        assert_eq!(history.read(1).unwrap(), "body:foo");
+        assert_eq!(history.read(2), None);
+    }
+
+    #[test]
+    fn test_make_hits_table() {
+        let search_response = SearchResponseRest {
+            num_hits: 2,
+            hits: vec![
+                json!({"body": "foo", "severity_text": "INFO"}),
+                json!({"body": "bar", "attributes": {"city": "paris"}}),
+            ],
+            snippets: None,
+            elapsed_time_micros: 1_000,
+            errors: Vec::new(),
+            aggregations: None,
+        };
+        let table = make_hits_table(&search_response).to_string();
+        let rows: Vec<&str> = table.lines().filter(|line| line.starts_with('|')).collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[0].contains("body"));
+        assert!(rows[0].contains("severity_text"));
+        assert!(rows[0].contains("attributes"));
+        assert!(rows[1].contains("foo"));
+        assert!(rows[1].contains("INFO"));
+        assert!(rows[2].contains("bar"));
+        assert!(rows[2].contains(r#"{"city":"paris"}"#));
+    }
+}
@@ -19,17 +19,27 @@
 
 use std::collections::HashSet;
 use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
 
+use anyhow::bail;
 use clap::{arg, ArgMatches, Command};
+use colored::Colorize;
 use itertools::Itertools;
 use quickwit_common::uri::Uri;
+use quickwit_common::GREEN_COLOR;
 use quickwit_config::service::QuickwitService;
-use quickwit_serve::serve_quickwit;
+use quickwit_rest_client::rest_client::{QuickwitClient, Transport};
+use quickwit_serve::{serve_quickwit, RuntimeHooks};
 use quickwit_telemetry::payload::TelemetryEvent;
+use reqwest::Url;
 use tokio::signal;
 use tracing::debug;
 
-use crate::{config_cli_arg, load_quickwit_config, start_actor_runtimes};
+use crate::{cluster_endpoint_arg, config_cli_arg, load_quickwit_config, start_actor_runtimes};
+
+/// Interval at which the drain command checks whether the indexing pipelines are drained.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 pub fn build_run_command<'a>() -> Command<'a> {
     Command::new("run")
@@ -43,6 +53,18 @@ pub fn build_run_command<'a>() -> Command<'a> {
         ])
 }
 
+pub fn build_drain_command<'a>() -> Command<'a> {
+    Command::new("drain")
+        .about("Drains the indexing pipelines of a running node.")
+        .long_about(
+            "Stops the indexing pipelines of a running node from pulling from their sources and \
+             waits until they have published their in-flight splits. Running it before shutting \
+             down an indexer, for instance in a Kubernetes preStop hook, ensures that no indexed \
+             document is lost or indexed twice.",
+        )
+        .arg(cluster_endpoint_arg())
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct RunCliCommand {
     pub config_uri: Uri,
@@ -72,6 +94,7 @@ impl RunCliCommand {
     pub async fn execute(&self) -> anyhow::Result<()> {
         debug!(args = ?self, "run-service");
         let mut config = load_quickwit_config(&self.config_uri).await?;
+        crate::log_format::set(config.log_format);
         crate::busy_detector::set_enabled(true);
 
         if let Some(services) = &self.services {
@@ -87,7 +110,58 @@ impl RunCliCommand {
                 .await
                 .expect("Registering a signal handler for SIGINT should not fail.");
         });
-        let _ = serve_quickwit(config, shutdown_signal).await?;
+        let mut runtime_hooks = RuntimeHooks {
+            set_log_filter_opt: None,
+            current_log_filter_opt: None,
+            set_busy_detector_enabled_opt: Some(Arc::new(crate::busy_detector::set_enabled)),
+        };
+        if crate::log_filter::is_reloadable() {
+            runtime_hooks.set_log_filter_opt = Some(Arc::new(crate::log_filter::set));
+            runtime_hooks.current_log_filter_opt = Some(Arc::new(crate::log_filter::current));
+        }
+        let _ = serve_quickwit(config, runtime_hooks, shutdown_signal).await?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct DrainCliCommand {
+    pub cluster_endpoint: Url,
+}
+
+impl DrainCliCommand {
+    pub fn parse_cli_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        Ok(DrainCliCommand { cluster_endpoint })
+    }
+
+    pub async fn execute(&self) -> anyhow::Result<()> {
+        debug!(args = ?self, "drain-service");
+        println!("❯ Draining indexing pipelines...");
+        let transport = Transport::new(self.cluster_endpoint.clone());
+        let qw_client = QuickwitClient::new(transport);
+        let initial_counters = qw_client.indexing().drain().await?;
+        let mut counters = initial_counters.clone();
+
+        while counters.num_running_pipelines > 0 {
+            tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+            counters = qw_client.node_stats().indexing().await?;
+        }
+        let num_failed_pipelines =
+            counters.num_failed_pipelines - initial_counters.num_failed_pipelines;
+        if num_failed_pipelines > 0 {
+            bail!(
+                "{num_failed_pipelines} indexing pipeline(s) failed while draining. Their \
+                 unpublished documents will be indexed again."
+            );
+        }
+        println!(
+            "{} Indexing pipelines successfully drained.",
+            "✔".color(GREEN_COLOR)
+        );
         Ok(())
     }
 }
@@ -168,6 +242,19 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_parse_drain_args() -> anyhow::Result<()> {
+        let command = build_cli().no_binary_name(true);
+        let matches =
+            command.try_get_matches_from(vec!["drain", "--endpoint", "http://127.0.0.1:8000"])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_command = CliCommand::Drain(DrainCliCommand {
+            cluster_endpoint: Url::from_str("http://127.0.0.1:8000").unwrap(),
+        });
+        assert_eq!(command, expected_command);
+        Ok(())
+    }
+
     #[test]
     fn test_parse_service_run_indexer_only_args() -> anyhow::Result<()> {
         let command = build_cli().no_binary_name(true);
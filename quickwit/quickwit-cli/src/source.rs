@@ -18,6 +18,7 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::str::FromStr;
+use std::time::Duration;
 
 use anyhow::{bail, Context};
 use bytes::Bytes;
@@ -27,15 +28,20 @@ use itertools::Itertools;
 use quickwit_common::uri::Uri;
 use quickwit_common::GREEN_COLOR;
 use quickwit_config::{validate_identifier, ConfigFormat, SourceConfig};
-use quickwit_metastore::checkpoint::SourceCheckpoint;
+use quickwit_metastore::checkpoint::{PartitionId, Position, SourceCheckpoint};
 use quickwit_rest_client::rest_client::{QuickwitClient, Transport};
+use quickwit_serve::CheckpointRewindTarget;
 use quickwit_storage::load_file;
 use reqwest::Url;
+use serde::Serialize;
 use serde_json::Value as JsonValue;
 use tabled::{Table, Tabled};
 use tracing::debug;
 
-use crate::{cluster_endpoint_arg, make_table, prompt_confirmation};
+use crate::{
+    cluster_endpoint_arg, make_table, parse_output_format, parse_watch_interval, print_output,
+    prompt_confirmation, watch, watch_arg, OutputFormat,
+};
 
 pub fn build_source_command<'a>() -> Command<'a> {
     Command::new("source")
@@ -95,12 +101,14 @@ pub fn build_source_command<'a>() -> Command<'a> {
         .subcommand(
             Command::new("describe")
                 .about("Describes a source.")
+                .long_about("Describes a source: displays its config and its checkpoint. With `--watch`, the checkpoint is refreshed periodically to monitor the ingestion progress.")
                 .alias("desc")
                 .args(&[
                     arg!(--index <INDEX_ID> "ID of the target index")
                         .display_order(1),
                     arg!(--source <SOURCE_ID> "ID of the source.")
                         .display_order(2),
+                    watch_arg(),
                 ])
             )
         .subcommand(
@@ -123,6 +131,25 @@ pub fn build_source_command<'a>() -> Command<'a> {
                         .display_order(2),
                 ])
             )
+        .subcommand(
+            Command::new("rewind-checkpoint")
+                .about("Rewinds a source checkpoint to replay the documents located after the given positions or timestamp.")
+                .args(&[
+                    arg!(--index <INDEX_ID> "Index ID")
+                        .display_order(1),
+                    arg!(--source <SOURCE_ID> "Source ID")
+                        .display_order(2),
+                    arg!(--positions <POSITIONS> "Comma-separated list of `partition_id=position` positions to rewind the partitions to. An empty position rewinds the partition to its beginning.")
+                        .display_order(3)
+                        .required(false)
+                        .required_unless_present("timestamp")
+                        .conflicts_with("timestamp")
+                        .use_value_delimiter(true),
+                    arg!(--timestamp <TIMESTAMP> "Rewinds the checkpoint to the positions it was at, at the given UTC Unix timestamp (in seconds).")
+                        .display_order(4)
+                        .required(false),
+                ])
+            )
         .arg_required_else_help(true)
 }
 
@@ -154,12 +181,15 @@ pub struct DescribeSourceArgs {
     pub cluster_endpoint: Url,
     pub index_id: String,
     pub source_id: String,
+    pub output_format: OutputFormat,
+    pub watch_interval_opt: Option<Duration>,
 }
 
 #[derive(Debug, Eq, PartialEq)]
 pub struct ListSourcesArgs {
     pub cluster_endpoint: Url,
     pub index_id: String,
+    pub output_format: OutputFormat,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -170,6 +200,15 @@ pub struct ResetCheckpointArgs {
     pub assume_yes: bool,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct RewindCheckpointArgs {
+    pub cluster_endpoint: Url,
+    pub index_id: String,
+    pub source_id: String,
+    pub rewind_target: CheckpointRewindTarget,
+    pub assume_yes: bool,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub enum SourceCliCommand {
     CreateSource(CreateSourceArgs),
@@ -178,6 +217,7 @@ pub enum SourceCliCommand {
     DescribeSource(DescribeSourceArgs),
     ListSources(ListSourcesArgs),
     ResetCheckpoint(ResetCheckpointArgs),
+    RewindCheckpoint(RewindCheckpointArgs),
 }
 
 impl SourceCliCommand {
@@ -189,6 +229,7 @@ impl SourceCliCommand {
             Self::DescribeSource(args) => describe_source_cli(args).await,
             Self::ListSources(args) => list_sources_cli(args).await,
             Self::ResetCheckpoint(args) => reset_checkpoint_cli(args).await,
+            Self::RewindCheckpoint(args) => rewind_checkpoint_cli(args).await,
         }
     }
 
@@ -210,6 +251,9 @@ impl SourceCliCommand {
             "reset-checkpoint" => {
                 Self::parse_reset_checkpoint_args(submatches).map(Self::ResetCheckpoint)
             }
+            "rewind-checkpoint" => {
+                Self::parse_rewind_checkpoint_args(submatches).map(Self::RewindCheckpoint)
+            }
             _ => bail!("Source subcommand `{}` is not implemented.", subcommand),
         }
     }
@@ -294,10 +338,14 @@ impl SourceCliCommand {
             .value_of("source")
             .map(String::from)
             .expect("`source` is a required arg.");
+        let output_format = parse_output_format(matches)?;
+        let watch_interval_opt = parse_watch_interval(matches)?;
         Ok(DescribeSourceArgs {
             cluster_endpoint,
             index_id,
             source_id,
+            output_format,
+            watch_interval_opt,
         })
     }
 
@@ -310,9 +358,11 @@ impl SourceCliCommand {
             .value_of("index")
             .map(String::from)
             .expect("`index` is a required arg.");
+        let output_format = parse_output_format(matches)?;
         Ok(ListSourcesArgs {
             cluster_endpoint,
             index_id,
+            output_format,
         })
     }
 
@@ -337,6 +387,54 @@ impl SourceCliCommand {
             assume_yes,
         })
     }
+
+    fn parse_rewind_checkpoint_args(matches: &ArgMatches) -> anyhow::Result<RewindCheckpointArgs> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .map(String::from)
+            .expect("`index` is a required arg.");
+        let source_id = matches
+            .value_of("source")
+            .map(String::from)
+            .expect("`source` is a required arg.");
+        let rewind_target = if let Some(positions) = matches.values_of("positions") {
+            let target_checkpoint = positions
+                .map(parse_partition_position)
+                .collect::<anyhow::Result<SourceCheckpoint>>()?;
+            CheckpointRewindTarget::Positions(target_checkpoint)
+        } else {
+            let timestamp = matches
+                .value_of("timestamp")
+                .map(i64::from_str)
+                .expect("`timestamp` is required when `positions` is missing.")
+                .context("Failed to parse timestamp.")?;
+            CheckpointRewindTarget::Timestamp(timestamp)
+        };
+        let assume_yes = matches.is_present("yes");
+        Ok(RewindCheckpointArgs {
+            cluster_endpoint,
+            index_id,
+            source_id,
+            rewind_target,
+            assume_yes,
+        })
+    }
+}
+
+fn parse_partition_position(
+    partition_position_arg: &str,
+) -> anyhow::Result<(PartitionId, Position)> {
+    let Some((partition_id, position)) = partition_position_arg.split_once('=') else {
+        bail!(
+            "Failed to parse partition position `{partition_position_arg}`. Expected format is \
+             `partition_id=position`."
+        );
+    };
+    Ok((PartitionId::from(partition_id), Position::from(position)))
 }
 
 async fn create_source_cli(args: CreateSourceArgs) -> anyhow::Result<()> {
@@ -400,24 +498,56 @@ async fn delete_source_cli(args: DeleteSourceArgs) -> anyhow::Result<()> {
 async fn describe_source_cli(args: DescribeSourceArgs) -> anyhow::Result<()> {
     debug!(args=?args, "describe-source");
     let transport = Transport::new(args.cluster_endpoint);
-    let qw_client = QuickwitClient::new(transport);
-    let index_metadata = qw_client
-        .indexes()
-        .get(&args.index_id)
-        .await
-        .context("Failed to fetch index metadata.")?;
-    let source_checkpoint = index_metadata
-        .checkpoint
-        .source_checkpoint(&args.source_id)
-        .cloned()
-        .unwrap_or_default();
-    let (source_table, params_table, checkpoint_table) = make_describe_source_tables(
-        source_checkpoint,
-        index_metadata.sources.into_values(),
-        &args.source_id,
-    )?;
-    display_tables(&[source_table, params_table, checkpoint_table]);
-    Ok(())
+    let qw_client = &QuickwitClient::new(transport);
+    let index_id = &args.index_id;
+    let source_id = &args.source_id;
+    watch(
+        args.watch_interval_opt,
+        args.output_format,
+        move || async move {
+            let index_metadata = qw_client
+                .indexes()
+                .get(index_id)
+                .await
+                .context("Failed to fetch index metadata.")?;
+            let source_checkpoint = index_metadata
+                .checkpoint
+                .source_checkpoint(source_id)
+                .cloned()
+                .unwrap_or_default();
+            let source_config = index_metadata
+                .sources
+                .get(source_id)
+                .cloned()
+                .with_context(|| format!("Source `{source_id}` does not exist."))?;
+            let source_description = SourceDescription {
+                source: source_config,
+                checkpoint: source_checkpoint,
+            };
+            print_output(
+                args.output_format,
+                &source_description,
+                |source_description| {
+                    let (source_table, params_table, checkpoint_table) =
+                        make_describe_source_tables(
+                            source_description.checkpoint.clone(),
+                            [source_description.source.clone()],
+                            &source_description.source.source_id,
+                        )
+                        .expect("The source config should have the described source ID.");
+                    join_tables(&[source_table, params_table, checkpoint_table])
+                },
+            )
+        },
+    )
+    .await
+}
+
+/// Source config and checkpoint displayed by the `source describe` command.
+#[derive(Serialize)]
+struct SourceDescription {
+    source: SourceConfig,
+    checkpoint: SourceCheckpoint,
 }
 
 fn make_describe_source_tables<I>(
@@ -467,9 +597,14 @@ async fn list_sources_cli(args: ListSourcesArgs) -> anyhow::Result<()> {
         .get(&args.index_id)
         .await
         .context("Failed to fetch indexes metadatas.")?;
-    let table = make_list_sources_table(index_metadata.sources.into_values());
-    display_tables(&[table]);
-    Ok(())
+    let sources = index_metadata
+        .sources
+        .into_values()
+        .sorted_by(|left, right| left.source_id.cmp(&right.source_id))
+        .collect_vec();
+    print_output(args.output_format, &sources, |sources| {
+        make_list_sources_table(sources.iter().cloned())
+    })
 }
 
 fn make_list_sources_table<I>(sources: I) -> Table
@@ -512,10 +647,11 @@ struct CheckpointRow {
 }
 
 fn display_tables(tables: &[Table]) {
-    println!(
-        "{}",
-        tables.iter().map(|table| table.to_string()).join("\n\n")
-    );
+    println!("{}", join_tables(tables));
+}
+
+fn join_tables(tables: &[Table]) -> String {
+    tables.iter().map(|table| table.to_string()).join("\n\n")
 }
 
 async fn reset_checkpoint_cli(args: ResetCheckpointArgs) -> anyhow::Result<()> {
@@ -541,6 +677,46 @@ async fn reset_checkpoint_cli(args: ResetCheckpointArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn rewind_checkpoint_cli(args: RewindCheckpointArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "rewind-checkpoint-source");
+    println!("❯ Rewinding source checkpoint...");
+    if !args.assume_yes {
+        let target_display = match &args.rewind_target {
+            CheckpointRewindTarget::Positions(target_checkpoint) => {
+                format!("the positions `{target_checkpoint:?}`")
+            }
+            CheckpointRewindTarget::Timestamp(timestamp) => {
+                format!("its positions at timestamp `{timestamp}`")
+            }
+        };
+        let prompt = format!(
+            "This operation will rewind the source checkpoint to {target_display} and the \
+             documents located after them will be indexed again. Do you want to proceed?"
+        );
+        if !prompt_confirmation(&prompt, false) {
+            return Ok(());
+        }
+    }
+    let transport = Transport::new(args.cluster_endpoint);
+    let qw_client = QuickwitClient::new(transport);
+    let source_checkpoint = qw_client
+        .sources(&args.index_id)
+        .rewind_checkpoint(&args.source_id, &args.rewind_target)
+        .await?;
+    println!(
+        "{} Checkpoint successfully rewound.",
+        "✔".color(GREEN_COLOR)
+    );
+    let checkpoint_rows = source_checkpoint
+        .iter()
+        .map(|(partition_id, position)| CheckpointRow {
+            partition_id: partition_id.0.to_string(),
+            offset: position.as_str().to_string(),
+        });
+    display_tables(&[make_table("Checkpoint", checkpoint_rows, false)]);
+    Ok(())
+}
+
 /// Recursively flattens a JSON object into a vector of `(path, value)` tuples where `path`
 /// represents the full path of each property in the original object. For instance, `{"root": true,
 /// "parent": {"child": 0}}` yields `[("root", true), ("parent.child", 0)]`. Arrays are not
@@ -570,11 +746,11 @@ fn flatten_json(value: JsonValue) -> Vec<(String, JsonValue)> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::num::NonZeroUsize;
     use std::str::FromStr;
 
     use quickwit_config::{SourceInputFormat, SourceParams};
-    use quickwit_metastore::checkpoint::{PartitionId, Position};
     use serde_json::json;
 
     use super::*;
@@ -714,6 +890,8 @@ mod tests {
                 cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
                 index_id: "hdfs-logs".to_string(),
                 source_id: "hdfs-logs-source".to_string(),
+                output_format: OutputFormat::Table,
+                watch_interval_opt: None,
             }));
         assert_eq!(command, expected_command);
     }
@@ -743,6 +921,95 @@ mod tests {
         assert_eq!(command, expected_command);
     }
 
+    #[test]
+    fn test_parse_rewind_checkpoint_args() {
+        {
+            let app = build_cli().no_binary_name(true);
+            let matches = app
+                .try_get_matches_from(vec![
+                    "source",
+                    "rewind-checkpoint",
+                    "--index",
+                    "hdfs-logs",
+                    "--source",
+                    "hdfs-logs-source",
+                    "--positions",
+                    "partition-0=00000000000000000042,partition-1=",
+                ])
+                .unwrap();
+            let command = CliCommand::parse_cli_args(&matches).unwrap();
+            let target_checkpoint: SourceCheckpoint = vec![
+                (PartitionId::from("partition-0"), Position::from(42u64)),
+                (PartitionId::from("partition-1"), Position::Beginning),
+            ]
+            .into_iter()
+            .collect();
+            let expected_command =
+                CliCommand::Source(SourceCliCommand::RewindCheckpoint(RewindCheckpointArgs {
+                    cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+                    index_id: "hdfs-logs".to_string(),
+                    source_id: "hdfs-logs-source".to_string(),
+                    rewind_target: CheckpointRewindTarget::Positions(target_checkpoint),
+                    assume_yes: false,
+                }));
+            assert_eq!(command, expected_command);
+        }
+        {
+            let app = build_cli().no_binary_name(true);
+            let matches = app
+                .try_get_matches_from(vec![
+                    "source",
+                    "rewind-checkpoint",
+                    "--index",
+                    "hdfs-logs",
+                    "--source",
+                    "hdfs-logs-source",
+                    "--timestamp",
+                    "1640577000",
+                    "--yes",
+                ])
+                .unwrap();
+            let command = CliCommand::parse_cli_args(&matches).unwrap();
+            let expected_command =
+                CliCommand::Source(SourceCliCommand::RewindCheckpoint(RewindCheckpointArgs {
+                    cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+                    index_id: "hdfs-logs".to_string(),
+                    source_id: "hdfs-logs-source".to_string(),
+                    rewind_target: CheckpointRewindTarget::Timestamp(1640577000),
+                    assume_yes: true,
+                }));
+            assert_eq!(command, expected_command);
+        }
+        {
+            let app = build_cli().no_binary_name(true);
+            let matches = app
+                .try_get_matches_from(vec![
+                    "source",
+                    "rewind-checkpoint",
+                    "--index",
+                    "hdfs-logs",
+                    "--source",
+                    "hdfs-logs-source",
+                    "--positions",
+                    "partition-0",
+                ])
+                .unwrap();
+            CliCommand::parse_cli_args(&matches).unwrap_err();
+        }
+        {
+            let app = build_cli().no_binary_name(true);
+            app.try_get_matches_from(vec![
+                "source",
+                "rewind-checkpoint",
+                "--index",
+                "hdfs-logs",
+                "--source",
+                "hdfs-logs-source",
+            ])
+            .unwrap_err();
+        }
+    }
+
     #[test]
     fn test_make_describe_source_tables() {
         assert!(make_describe_source_tables(
@@ -764,6 +1031,7 @@ mod tests {
             source_params: SourceParams::file("path/to/file"),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         }];
         let expected_source = vec![SourceRow {
             source_id: "foo-source".to_string(),
@@ -804,12 +1072,20 @@ mod tests {
     fn test_parse_list_sources_args() {
         let app = build_cli().no_binary_name(true);
         let matches = app
-            .try_get_matches_from(vec!["source", "list", "--index", "hdfs-logs"])
+            .try_get_matches_from(vec![
+                "source",
+                "list",
+                "--index",
+                "hdfs-logs",
+                "--output",
+                "yaml",
+            ])
             .unwrap();
         let command = CliCommand::parse_cli_args(&matches).unwrap();
         let expected_command = CliCommand::Source(SourceCliCommand::ListSources(ListSourcesArgs {
             cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
             index_id: "hdfs-logs".to_string(),
+            output_format: OutputFormat::Yaml,
         }));
         assert_eq!(command, expected_command);
     }
@@ -825,6 +1101,7 @@ mod tests {
                 source_params: SourceParams::stdin(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             },
             SourceConfig {
                 source_id: "bar-source".to_string(),
@@ -834,6 +1111,7 @@ mod tests {
                 source_params: SourceParams::stdin(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             },
         ];
         let expected_sources = [
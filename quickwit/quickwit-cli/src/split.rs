@@ -17,22 +17,40 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 
 use anyhow::{bail, Context};
+use byte_unit::Byte;
 use clap::{arg, ArgMatches, Command};
 use colored::Colorize;
+use futures::stream::{self, LocalBoxStream, StreamExt};
 use itertools::Itertools;
+use quickwit_common::uri::Uri;
 use quickwit_common::GREEN_COLOR;
+use quickwit_directories::BundleDirectory;
 use quickwit_metastore::{Split, SplitState};
 use quickwit_rest_client::rest_client::{QuickwitClient, Transport};
 use quickwit_serve::ListSplitsQueryParams;
+use quickwit_storage::{load_file, quickwit_storage_uri_resolver, OwnedBytes};
 use reqwest::Url;
+use serde::Serialize;
+use serde_json::Value as JsonValue;
 use tabled::{Table, Tabled};
+use tantivy::directory::FileSlice;
+use tantivy::{DocAddress, Index, ReloadPolicy, SegmentOrdinal};
 use time::{format_description, Date, OffsetDateTime, PrimitiveDateTime};
 use tracing::debug;
 
-use crate::{cluster_endpoint_arg, make_table, prompt_confirmation};
+use crate::{
+    cluster_endpoint_arg, make_table, parse_output_format, print_output, print_output_stream,
+    prompt_confirmation, OutputFormat,
+};
+
+/// Maximum number of splits fetched per request when listing splits.
+const LIST_SPLITS_PAGE_SIZE: usize = 1_000;
 
 pub fn build_split_command<'a>() -> Command<'a> {
     Command::new("split")
@@ -59,14 +77,14 @@ pub fn build_split_command<'a>() -> Command<'a> {
                     arg!(--"end-date" <END_DATE> "Selects the splits that contain documents before this date (time-series indexes only).")
                         .display_order(5)
                         .required(false),
+                    arg!(--labels <LABELS> "Selects the splits carrying all the labels of this comma-separated list of `key:value` labels.")
+                        .display_order(6)
+                        .required(false)
+                        .use_value_delimiter(true),
                     // arg!(--tags <TAGS> "Selects the splits whose tags are all included in this comma-separated list of tags.")
                     //     .display_order(6)
                     //     .required(false)
                     //     .use_value_delimiter(true),
-                    arg!(--"output-format" <OUTPUT_FORMAT> "Output format. Possible values are `table`, `json`, and `pretty_json`.")
-                        .alias("format")
-                        .display_order(7)
-                        .required(false)
                 ])
             )
         .subcommand(
@@ -81,6 +99,29 @@ pub fn build_split_command<'a>() -> Command<'a> {
                     arg!(--verbose "Displays additional metadata about the hotcache."),
                 ])
             )
+        .subcommand(
+            Command::new("inspect")
+                .about("Downloads a split and displays its size breakdown, field statistics, and documents.")
+                .long_about("Downloads a split, or reads a split file, and displays its size breakdown by file, the term statistics of its fields, its time range, and optionally its first documents. Useful to debug doc mapping and split size anomalies.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1)
+                        .required(false)
+                        .required_unless_present("split-uri"),
+                    arg!(--split <SPLIT> "ID of the target split")
+                        .display_order(2)
+                        .required(false)
+                        .required_unless_present("split-uri"),
+                    arg!(--"split-uri" <SPLIT_URI> "URI of the split file to inspect, for instance a local `.split` file, instead of a split of an index.")
+                        .display_order(3)
+                        .required(false)
+                        .conflicts_with_all(&["index", "split"]),
+                    arg!(--"num-docs" <NUM_DOCS> "Number of documents to display.")
+                        .display_order(4)
+                        .default_value("0")
+                        .required(false),
+                ])
+            )
         .subcommand(
             Command::new("mark-for-deletion")
                 .about("Marks one or multiple splits of an index for deletion.")
@@ -100,29 +141,6 @@ pub fn build_split_command<'a>() -> Command<'a> {
         .arg_required_else_help(true)
 }
 
-#[derive(Debug, Eq, PartialEq)]
-enum OutputFormat {
-    Table, // Default
-    Json,
-    PrettyJson,
-}
-
-impl FromStr for OutputFormat {
-    type Err = anyhow::Error;
-
-    fn from_str(output_format_str: &str) -> anyhow::Result<Self> {
-        match output_format_str {
-            "table" => Ok(OutputFormat::Table),
-            "json" => Ok(OutputFormat::Json),
-            "pretty_json" => Ok(OutputFormat::PrettyJson),
-            _ => bail!(
-                "Failed to parse output format `{output_format_str}`. Supported formats are: \
-                 `table`, `json`, and `pretty_json`."
-            ),
-        }
-    }
-}
-
 #[derive(Debug, PartialEq)]
 pub struct ListSplitArgs {
     pub cluster_endpoint: Url,
@@ -131,8 +149,9 @@ pub struct ListSplitArgs {
     pub create_date: Option<OffsetDateTime>,
     pub start_date: Option<OffsetDateTime>,
     pub end_date: Option<OffsetDateTime>,
+    pub labels: Option<Vec<String>>,
     // pub tags: Option<TagFilterAst>,
-    output_format: OutputFormat,
+    pub output_format: OutputFormat,
 }
 
 #[derive(Debug, Eq, PartialEq)]
@@ -149,6 +168,23 @@ pub struct DescribeSplitArgs {
     pub index_id: String,
     pub split_id: String,
     pub verbose: bool,
+    pub output_format: OutputFormat,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub enum SplitLocation {
+    /// Split of an index, downloaded from the index storage.
+    Index { index_id: String, split_id: String },
+    /// Split file located at a URI.
+    Uri(Uri),
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct InspectSplitArgs {
+    pub cluster_endpoint: Url,
+    pub split_location: SplitLocation,
+    pub num_docs: usize,
+    pub output_format: OutputFormat,
 }
 
 #[derive(Debug, PartialEq)]
@@ -156,6 +192,7 @@ pub enum SplitCliCommand {
     List(ListSplitArgs),
     MarkForDeletion(MarkForDeletionArgs),
     Describe(DescribeSplitArgs),
+    Inspect(InspectSplitArgs),
 }
 
 impl SplitCliCommand {
@@ -165,6 +202,7 @@ impl SplitCliCommand {
             .ok_or_else(|| anyhow::anyhow!("Failed to parse sub-matches."))?;
         match subcommand {
             "describe" => Self::parse_describe_args(submatches),
+            "inspect" => Self::parse_inspect_args(submatches),
             "list" => Self::parse_list_args(submatches),
             "mark-for-deletion" => Self::parse_mark_for_deletion_args(submatches),
             _ => bail!("Subcommand `{}` is not implemented.", subcommand),
@@ -201,6 +239,15 @@ impl SplitCliCommand {
             .value_of("end-date")
             .map(|arg| parse_date(arg, "end"))
             .transpose()?;
+        let labels = matches
+            .values_of("labels")
+            .map(|values| {
+                values
+                    .into_iter()
+                    .map(parse_split_label)
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .transpose()?;
         // let tags = matches.values_of("tags").map(|values| {
         //     TagFilterAst::And(
         //         values
@@ -212,11 +259,7 @@ impl SplitCliCommand {
         //             .collect(),
         //     )
         // });
-        let output_format = matches
-            .value_of("output-format")
-            .map(OutputFormat::from_str)
-            .transpose()?
-            .unwrap_or(OutputFormat::Table);
+        let output_format = parse_output_format(matches)?;
 
         Ok(Self::List(ListSplitArgs {
             cluster_endpoint,
@@ -225,6 +268,7 @@ impl SplitCliCommand {
             start_date,
             end_date,
             create_date,
+            labels,
             // tags,
             output_format,
         }))
@@ -267,12 +311,43 @@ impl SplitCliCommand {
             .map(Url::from_str)
             .expect("`endpoint` is a required arg.")?;
         let verbose = matches.is_present("verbose");
+        let output_format = parse_output_format(matches)?;
 
         Ok(Self::Describe(DescribeSplitArgs {
             cluster_endpoint,
             index_id,
             split_id,
             verbose,
+            output_format,
+        }))
+    }
+
+    fn parse_inspect_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let cluster_endpoint = matches
+            .value_of("endpoint")
+            .map(Url::from_str)
+            .expect("`endpoint` is a required arg.")?;
+        let split_location = if let Some(split_uri) = matches.value_of("split-uri") {
+            SplitLocation::Uri(Uri::from_str(split_uri)?)
+        } else {
+            let index_id = matches
+                .value_of("index")
+                .map(String::from)
+                .expect("`index` is a required arg.");
+            let split_id = matches
+                .value_of("split")
+                .map(String::from)
+                .expect("`split` is a required arg.");
+            SplitLocation::Index { index_id, split_id }
+        };
+        let num_docs = matches.value_of_t::<usize>("num-docs")?;
+        let output_format = parse_output_format(matches)?;
+
+        Ok(Self::Inspect(InspectSplitArgs {
+            cluster_endpoint,
+            split_location,
+            num_docs,
+            output_format,
         }))
     }
 
@@ -281,6 +356,7 @@ impl SplitCliCommand {
             Self::List(args) => list_split_cli(args).await,
             Self::MarkForDeletion(args) => mark_splits_for_deletion_cli(args).await,
             Self::Describe(args) => describe_split_cli(args).await,
+            Self::Inspect(args) => inspect_split_cli(args).await,
         }
     }
 }
@@ -294,23 +370,51 @@ async fn list_split_cli(args: ListSplitArgs) -> anyhow::Result<()> {
         start_timestamp: args.start_date.map(OffsetDateTime::unix_timestamp),
         end_timestamp: args.end_date.map(OffsetDateTime::unix_timestamp),
         end_create_timestamp: args.create_date.map(OffsetDateTime::unix_timestamp),
+        page_size: Some(LIST_SPLITS_PAGE_SIZE),
+        after_split_id: None,
+        labels: args.labels,
     };
     // TODO: plug tags.
     // if let Some(tags) = args.tags {
     //     query = query.with_tags_filter(tags);
     // }
-    let splits = qw_client
-        .splits(&args.index_id)
-        .list(list_splits_query_params)
-        .await
-        .expect("Failed to fetch splits.");
-    let output = match args.output_format {
-        OutputFormat::Json => serde_json::to_string(&splits)?,
-        OutputFormat::PrettyJson => serde_json::to_string_pretty(&splits)?,
-        OutputFormat::Table => make_split_table(&splits, "Splits").to_string(),
-    };
-    println!("{output}");
-    Ok(())
+    let splits_stream = stream_splits(&qw_client, &args.index_id, list_splits_query_params);
+    print_output_stream(args.output_format, splits_stream, |splits| {
+        make_split_table(splits, "Splits")
+    })
+    .await
+}
+
+/// Streams the splits of the index `index_id` page by page, so that listing the splits of large
+/// indexes does not require holding all of them in memory at once.
+fn stream_splits<'a>(
+    qw_client: &'a QuickwitClient,
+    index_id: &'a str,
+    list_splits_query_params: ListSplitsQueryParams,
+) -> LocalBoxStream<'a, anyhow::Result<Vec<Split>>> {
+    stream::try_unfold(
+        Some(list_splits_query_params),
+        move |query_params_opt| async move {
+            let Some(mut list_splits_query_params) = query_params_opt else {
+                return Ok(None);
+            };
+            let splits = qw_client
+                .splits(index_id)
+                .list(list_splits_query_params.clone())
+                .await
+                .context("Failed to fetch splits.")?;
+
+            let next_query_params_opt = if splits.len() < LIST_SPLITS_PAGE_SIZE {
+                None
+            } else {
+                list_splits_query_params.after_split_id =
+                    splits.last().map(|split| split.split_id().to_string());
+                Some(list_splits_query_params)
+            };
+            Ok(Some((splits, next_query_params_opt)))
+        },
+    )
+    .boxed_local()
 }
 
 async fn mark_splits_for_deletion_cli(args: MarkForDeletionArgs) -> anyhow::Result<()> {
@@ -349,22 +453,11 @@ async fn describe_split_cli(args: DescribeSplitArgs) -> anyhow::Result<()> {
     debug!(args=?args, "describe-split");
     let transport = Transport::new(args.cluster_endpoint);
     let qw_client = QuickwitClient::new(transport);
-    let list_splits_query_params = ListSplitsQueryParams::default();
-    let split = qw_client
-        .splits(&args.index_id)
-        .list(list_splits_query_params)
-        .await
-        .expect("Failed to fetch splits.")
-        .into_iter()
-        .find(|split| split.split_id() == args.split_id)
-        .with_context(|| {
-            format!(
-                "Could not find split metadata in metastore {}",
-                args.split_id
-            )
-        })?;
+    let split = fetch_split(&qw_client, &args.index_id, &args.split_id).await?;
 
-    println!("{}", make_split_table(&[split], "Split"));
+    print_output(args.output_format, &split, |split| {
+        make_split_table(std::slice::from_ref(split), "Split")
+    })?;
 
     // TODO: if we have access to the storage, we could fetch that.
     // let split_file = PathBuf::from(format!("{}.split", args.split_id));
@@ -399,6 +492,252 @@ async fn describe_split_cli(args: DescribeSplitArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn fetch_split(
+    qw_client: &QuickwitClient,
+    index_id: &str,
+    split_id: &str,
+) -> anyhow::Result<Split> {
+    let list_splits_query_params = ListSplitsQueryParams::default();
+    qw_client
+        .splits(index_id)
+        .list(list_splits_query_params)
+        .await
+        .context("Failed to fetch splits.")?
+        .into_iter()
+        .find(|split| split.split_id() == split_id)
+        .with_context(|| format!("Could not find split metadata in metastore {split_id}"))
+}
+
+async fn inspect_split_cli(args: InspectSplitArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "inspect-split");
+    let (split_id, split_data, time_range) = match args.split_location {
+        SplitLocation::Index { index_id, split_id } => {
+            let transport = Transport::new(args.cluster_endpoint);
+            let qw_client = QuickwitClient::new(transport);
+            let index_metadata = qw_client.indexes().get(&index_id).await?;
+            let split = fetch_split(&qw_client, &index_id, &split_id).await?;
+            let index_storage =
+                quickwit_storage_uri_resolver().resolve(index_metadata.index_uri())?;
+            let split_file = PathBuf::from(format!("{split_id}.split"));
+            let split_data = index_storage.get_all(&split_file).await?;
+            (split_id, split_data, split.split_metadata.time_range)
+        }
+        SplitLocation::Uri(split_uri) => {
+            let split_id = split_uri
+                .file_name()
+                .and_then(Path::file_stem)
+                .map(|file_stem| file_stem.to_string_lossy().to_string())
+                .with_context(|| format!("URI `{split_uri}` is not a valid split file URI."))?;
+            let split_data = load_file(&split_uri).await?;
+            (split_id, split_data, None)
+        }
+    };
+    let mut split_inspection = inspect_split_data(split_id, split_data, args.num_docs)?;
+    split_inspection.time_range = time_range;
+    print_output(
+        args.output_format,
+        &split_inspection,
+        display_split_inspection,
+    )
+}
+
+/// Size breakdown, field statistics, and documents of a split, as displayed by the
+/// `split inspect` command.
+#[derive(Debug, Serialize)]
+struct SplitInspection {
+    split_id: String,
+    num_docs: u64,
+    num_segments: usize,
+    num_bytes: u64,
+    time_range: Option<RangeInclusive<i64>>,
+    files: Vec<SplitFileStats>,
+    fields: Vec<FieldStats>,
+    docs: Vec<JsonValue>,
+}
+
+#[derive(Debug, Serialize)]
+struct SplitFileStats {
+    file_name: String,
+    num_bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct FieldStats {
+    field_name: String,
+    field_type: String,
+    indexed: bool,
+    stored: bool,
+    fast: bool,
+    /// Number of distinct terms of the field, summed over the segments of the split. `None` if
+    /// the field is not indexed.
+    num_terms: Option<u64>,
+    /// Number of tokens indexed for the field. `None` if the field is not indexed.
+    num_tokens: Option<u64>,
+}
+
+/// Opens the split contained in `split_data`, computes its size breakdown and the statistics of
+/// its fields, and extracts its first `num_docs` documents.
+fn inspect_split_data(
+    split_id: String,
+    split_data: OwnedBytes,
+    num_docs: usize,
+) -> anyhow::Result<SplitInspection> {
+    let files: Vec<SplitFileStats> = BundleDirectory::get_stats_split(split_data.clone())?
+        .into_iter()
+        .map(|(path, num_bytes)| SplitFileStats {
+            file_name: path.to_string_lossy().to_string(),
+            num_bytes,
+        })
+        .collect();
+    let num_bytes = split_data.len() as u64;
+    let bundle_directory = BundleDirectory::open_split(FileSlice::new(Arc::new(split_data)))?;
+    let index = Index::open(bundle_directory)?;
+    let reader = index
+        .reader_builder()
+        .reload_policy(ReloadPolicy::Manual)
+        .try_into()?;
+    let searcher = reader.searcher();
+    let schema = searcher.schema();
+
+    let mut fields = Vec::new();
+    for (field, field_entry) in schema.fields() {
+        let (num_terms, num_tokens) = if field_entry.is_indexed() {
+            let mut num_terms = 0;
+            let mut num_tokens = 0;
+            for segment_reader in searcher.segment_readers() {
+                let inverted_index = segment_reader.inverted_index(field)?;
+                num_terms += inverted_index.terms().num_terms() as u64;
+                num_tokens += inverted_index.total_num_tokens();
+            }
+            (Some(num_terms), Some(num_tokens))
+        } else {
+            (None, None)
+        };
+        fields.push(FieldStats {
+            field_name: field_entry.name().to_string(),
+            field_type: format!("{:?}", field_entry.field_type().value_type()),
+            indexed: field_entry.is_indexed(),
+            stored: field_entry.is_stored(),
+            fast: field_entry.is_fast(),
+            num_terms,
+            num_tokens,
+        });
+    }
+    let docs = searcher
+        .segment_readers()
+        .iter()
+        .enumerate()
+        .flat_map(|(segment_ord, segment_reader)| {
+            segment_reader
+                .doc_ids_alive()
+                .map(move |doc_id| DocAddress::new(segment_ord as SegmentOrdinal, doc_id))
+        })
+        .take(num_docs)
+        .map(|doc_address| {
+            let doc = searcher.doc(doc_address)?;
+            let doc_json = serde_json::from_str(&schema.to_json(&doc))?;
+            Ok(doc_json)
+        })
+        .collect::<anyhow::Result<Vec<JsonValue>>>()?;
+
+    Ok(SplitInspection {
+        split_id,
+        num_docs: searcher.num_docs(),
+        num_segments: searcher.segment_readers().len(),
+        num_bytes,
+        time_range: None,
+        files,
+        fields,
+        docs,
+    })
+}
+
+#[derive(Tabled)]
+struct SplitInspectionRow {
+    #[tabled(rename = "ID")]
+    split_id: String,
+    #[tabled(rename = "Num docs")]
+    num_docs: u64,
+    #[tabled(rename = "Num segments")]
+    num_segments: usize,
+    #[tabled(rename = "Size")]
+    size: String,
+    #[tabled(rename = "Time range")]
+    time_range: String,
+}
+
+#[derive(Tabled)]
+struct FieldStatsRow {
+    #[tabled(rename = "Field")]
+    field_name: String,
+    #[tabled(rename = "Type")]
+    field_type: String,
+    #[tabled(rename = "Indexed")]
+    indexed: bool,
+    #[tabled(rename = "Stored")]
+    stored: bool,
+    #[tabled(rename = "Fast")]
+    fast: bool,
+    #[tabled(rename = "Num terms")]
+    num_terms: String,
+    #[tabled(rename = "Num tokens")]
+    num_tokens: String,
+}
+
+fn display_split_inspection(split_inspection: &SplitInspection) -> String {
+    let time_range = if let Some(time_range) = &split_inspection.time_range {
+        format!("[{time_range:?}]")
+    } else {
+        "[*]".to_string()
+    };
+    let split_row = SplitInspectionRow {
+        split_id: split_inspection.split_id.clone(),
+        num_docs: split_inspection.num_docs,
+        num_segments: split_inspection.num_segments,
+        size: display_num_bytes(split_inspection.num_bytes),
+        time_range,
+    };
+    let file_rows = split_inspection.files.iter().map(|file| FileRow {
+        file_name: file.file_name.clone(),
+        size: display_num_bytes(file.num_bytes),
+    });
+    let field_rows = split_inspection.fields.iter().map(|field| FieldStatsRow {
+        field_name: field.field_name.clone(),
+        field_type: field.field_type.clone(),
+        indexed: field.indexed,
+        stored: field.stored,
+        fast: field.fast,
+        num_terms: display_count_opt(field.num_terms),
+        num_tokens: display_count_opt(field.num_tokens),
+    });
+    let mut sections = vec![
+        make_table("Split", [split_row], true).to_string(),
+        make_table("Files", file_rows, false).to_string(),
+        make_table("Fields", field_rows, false).to_string(),
+    ];
+    if !split_inspection.docs.is_empty() {
+        let docs = split_inspection
+            .docs
+            .iter()
+            .map(|doc| doc.to_string())
+            .join("\n");
+        sections.push(docs);
+    }
+    sections.join("\n\n")
+}
+
+fn display_num_bytes(num_bytes: u64) -> String {
+    Byte::from(num_bytes)
+        .get_appropriate_unit(false)
+        .to_string()
+}
+
+fn display_count_opt(count_opt: Option<u64>) -> String {
+    count_opt
+        .map(|count| count.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
 fn make_split_table(splits: &[Split], title: &str) -> Table {
     let rows = splits
         .iter()
@@ -413,6 +752,12 @@ fn make_split_table(splits: &[Split], title: &str) -> Table {
                     .expect("Failed to create `OffsetDateTime` from split create timestamp.");
             let updated_at = OffsetDateTime::from_unix_timestamp(split.update_timestamp)
                 .expect("Failed to create `OffsetDateTime` from split update timestamp.");
+            let labels = split
+                .split_metadata
+                .labels
+                .iter()
+                .map(|(key, value)| format!("{key}:{value}"))
+                .join(", ");
 
             SplitRow {
                 split_id: split.split_metadata.split_id.clone(),
@@ -422,6 +767,7 @@ fn make_split_table(splits: &[Split], title: &str) -> Table {
                 created_at,
                 updated_at,
                 time_range,
+                labels,
             }
         })
         .sorted_by(|left, right| left.created_at.cmp(&right.created_at));
@@ -466,6 +812,13 @@ fn parse_split_state(split_state_arg: &str) -> anyhow::Result<SplitState> {
     Ok(split_state)
 }
 
+fn parse_split_label(split_label_arg: &str) -> anyhow::Result<String> {
+    if !split_label_arg.contains(':') {
+        bail!("Failed to parse split label `{split_label_arg}`. Expected format is `key:value`.");
+    }
+    Ok(split_label_arg.to_string())
+}
+
 #[derive(Tabled)]
 struct SplitRow {
     #[tabled(rename = "ID")]
@@ -482,10 +835,16 @@ struct SplitRow {
     updated_at: OffsetDateTime,
     #[tabled(rename = "Time range")]
     time_range: String,
+    #[tabled(rename = "Labels")]
+    labels: String,
 }
 
 #[cfg(test)]
 mod tests {
+    use quickwit_storage::{PutPayload, SplitPayloadBuilder};
+    use serde_json::json;
+    use tantivy::doc;
+    use tantivy::schema::{Schema, FAST, STORED, TEXT};
     use time::macros::datetime;
 
     use super::*;
@@ -507,6 +866,8 @@ mod tests {
             "2020-12-24",
             "--end-date",
             "2020-12-25T12:42",
+            "--labels",
+            "backfill:2023-01,batch_id:42",
             // "--tags",
             // "tenant:a,service:zk",
             "--format",
@@ -518,6 +879,10 @@ mod tests {
         let expected_create_date = Some(datetime!(2020-12-24 00:00 UTC));
         let expected_start_date = Some(datetime!(2020-12-24 00:00 UTC));
         let expected_end_date = Some(datetime!(2020-12-25 12:42 UTC));
+        let expected_labels = Some(vec![
+            "backfill:2023-01".to_string(),
+            "batch_id:42".to_string(),
+        ]);
         // let expected_tags = Some(TagFilterAst::And(vec![
         //     TagFilterAst::Tag {
         //         is_present: true,
@@ -537,6 +902,7 @@ mod tests {
                 create_date,
                 start_date,
                 end_date,
+                labels,
                 // tags,
                 output_format,
                 ..
@@ -545,6 +911,7 @@ mod tests {
                    && create_date == expected_create_date
                    && start_date == expected_start_date
                    && end_date == expected_end_date
+                   && labels == expected_labels
                    // && tags == expected_tags
                    && output_format == expected_output_format
         ));
@@ -591,6 +958,8 @@ mod tests {
             "wikipedia",
             "--split",
             "ABC",
+            "--output",
+            "yaml",
         ])?;
         let command = CliCommand::parse_cli_args(&matches)?;
         assert!(matches!(
@@ -599,12 +968,126 @@ mod tests {
                 index_id,
                 split_id,
                 verbose: false,
+                output_format: OutputFormat::Yaml,
                 ..
             })) if &index_id == "wikipedia" && &split_id == "ABC"
         ));
         Ok(())
     }
 
+    #[test]
+    fn test_parse_split_inspect_args() -> anyhow::Result<()> {
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "split",
+            "inspect",
+            "--index",
+            "wikipedia",
+            "--split",
+            "ABC",
+            "--num-docs",
+            "10",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_command = CliCommand::Split(SplitCliCommand::Inspect(InspectSplitArgs {
+            cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+            split_location: SplitLocation::Index {
+                index_id: "wikipedia".to_string(),
+                split_id: "ABC".to_string(),
+            },
+            num_docs: 10,
+            output_format: OutputFormat::Table,
+        }));
+        assert_eq!(command, expected_command);
+
+        let app = build_cli().no_binary_name(true);
+        let matches = app.try_get_matches_from(vec![
+            "split",
+            "inspect",
+            "--split-uri",
+            "file:///splits/ABC.split",
+        ])?;
+        let command = CliCommand::parse_cli_args(&matches)?;
+        let expected_command = CliCommand::Split(SplitCliCommand::Inspect(InspectSplitArgs {
+            cluster_endpoint: Url::from_str("http://127.0.0.1:7280").unwrap(),
+            split_location: SplitLocation::Uri(Uri::for_test("file:///splits/ABC.split")),
+            num_docs: 0,
+            output_format: OutputFormat::Table,
+        }));
+        assert_eq!(command, expected_command);
+
+        let app = build_cli().no_binary_name(true);
+        app.try_get_matches_from(vec!["split", "inspect", "--index", "wikipedia"])
+            .unwrap_err();
+
+        let app = build_cli().no_binary_name(true);
+        app.try_get_matches_from(vec![
+            "split",
+            "inspect",
+            "--index",
+            "wikipedia",
+            "--split-uri",
+            "file:///splits/ABC.split",
+        ])
+        .unwrap_err();
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_inspect_split_data() -> anyhow::Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let mut schema_builder = Schema::builder();
+        let body_field = schema_builder.add_text_field("body", TEXT | STORED);
+        let count_field = schema_builder.add_u64_field("count", FAST);
+        let index = Index::create_in_dir(temp_dir.path(), schema_builder.build())?;
+        let mut index_writer = index.writer_with_num_threads(1, 15_000_000)?;
+        index_writer.add_document(doc!(body_field => "hello world", count_field => 1u64))?;
+        index_writer.add_document(doc!(body_field => "hello quickwit", count_field => 2u64))?;
+        index_writer.add_document(doc!(body_field => "happy tax payer", count_field => 3u64))?;
+        index_writer.commit()?;
+        index_writer.wait_merging_threads()?;
+
+        let split_files = std::fs::read_dir(temp_dir.path())?
+            .map(|dir_entry| dir_entry.map(|dir_entry| dir_entry.path()))
+            .collect::<std::io::Result<Vec<PathBuf>>>()?;
+        let split_data = SplitPayloadBuilder::get_split_payload(&split_files, &[])?
+            .read_all()
+            .await?;
+        let split_inspection = inspect_split_data("test-split".to_string(), split_data, 2)?;
+
+        assert_eq!(split_inspection.split_id, "test-split");
+        assert_eq!(split_inspection.num_docs, 3);
+        assert_eq!(split_inspection.num_segments, 1);
+        assert!(split_inspection
+            .files
+            .iter()
+            .any(|file| file.file_name == "meta.json"));
+        assert!(split_inspection
+            .files
+            .iter()
+            .any(|file| file.file_name == "hotcache"));
+
+        assert_eq!(split_inspection.fields.len(), 2);
+        let body_stats = &split_inspection.fields[0];
+        assert_eq!(body_stats.field_name, "body");
+        assert!(body_stats.indexed);
+        assert!(body_stats.stored);
+        assert!(!body_stats.fast);
+        assert_eq!(body_stats.num_terms, Some(6));
+        assert_eq!(body_stats.num_tokens, Some(7));
+
+        let count_stats = &split_inspection.fields[1];
+        assert_eq!(count_stats.field_name, "count");
+        assert!(!count_stats.indexed);
+        assert!(count_stats.fast);
+        assert_eq!(count_stats.num_terms, None);
+
+        assert_eq!(split_inspection.docs.len(), 2);
+        assert_eq!(split_inspection.docs[0]["body"], json!(["hello world"]));
+        assert_eq!(split_inspection.docs[1]["body"], json!(["hello quickwit"]));
+        Ok(())
+    }
+
     #[test]
     fn test_parse_date() {
         assert_eq!(
@@ -17,13 +17,13 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::{HashSet, VecDeque};
-use std::io::{stdout, Stdout, Write};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::env;
 use std::num::NonZeroUsize;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
-use std::{env, fmt, io};
 
 use anyhow::{bail, Context};
 use chitchat::transport::ChannelTransport;
@@ -31,32 +31,51 @@ use chitchat::FailureDetectorConfig;
 use clap::{arg, ArgMatches, Command};
 use colored::{ColoredString, Colorize};
 use humantime::format_duration;
-use quickwit_actors::{ActorExitStatus, ActorHandle, ObservationType, Universe};
+use indicatif::ProgressBar;
+use quickwit_actors::{ActorExitStatus, ActorHandle, Mailbox, ObservationType, Universe};
 use quickwit_cluster::{Cluster, ClusterMember};
+use quickwit_common::io::IoControls;
 use quickwit_common::uri::Uri;
-use quickwit_common::{GREEN_COLOR, RED_COLOR};
+use quickwit_common::GREEN_COLOR;
 use quickwit_config::service::QuickwitService;
 use quickwit_config::{
-    IndexerConfig, QuickwitConfig, SourceConfig, SourceInputFormat, SourceParams, TransformConfig,
+    build_doc_mapper, IndexConfig, IndexerConfig, IndexingSettings, MergePolicyConfig,
+    QuickwitConfig, SourceConfig, SourceInputFormat, SourceParams, TransformConfig,
     VecSourceParams, CLI_INGEST_SOURCE_ID,
 };
 use quickwit_core::{clear_cache_directory, IndexService};
-use quickwit_indexing::actors::{IndexingService, MergePipeline, MergePipelineId};
+use quickwit_indexing::actors::{
+    IndexingService, MergeExecutor, MergePipeline, MergePipelineId, MergeSplitDownloader, Packager,
+    Publisher, PublisherType, Uploader, UploaderType,
+};
+use quickwit_indexing::merge_policy::{merge_policy_from_settings, MergeOperation, MergePolicy};
 use quickwit_indexing::models::{
-    DetachIndexingPipeline, DetachMergePipeline, IndexingStatistics, SpawnPipeline,
+    DetachIndexingPipeline, DetachMergePipeline, IndexingPipelineId, IndexingStatistics,
+    ScratchDirectory, SpawnPipeline,
+};
+use quickwit_indexing::{IndexingPipeline, IndexingSplitStore};
+use quickwit_metastore::{
+    quickwit_metastore_uri_resolver, ListSplitsQuery, Metastore, SplitMetadata, SplitState,
 };
-use quickwit_indexing::IndexingPipeline;
-use quickwit_metastore::quickwit_metastore_uri_resolver;
+use quickwit_proto::{query_ast_from_user_text, IndexUid, SearchRequest, SortOrder};
+use quickwit_search::{single_node_search, SearchResponseRest};
+use quickwit_serve::SplitRemovalInfo;
 use quickwit_storage::{quickwit_storage_uri_resolver, BundleStorage, Storage};
 use quickwit_telemetry::payload::TelemetryEvent;
+use tantivy::Inventory;
 use thousands::Separable;
 use tracing::{debug, info};
 
+use crate::exit_code::{ExitCode, WithExitCode};
 use crate::{
-    config_cli_arg, load_quickwit_config, parse_duration_with_unit, run_index_checklist,
-    start_actor_runtimes, THROUGHPUT_WINDOW_SIZE,
+    bytes_progress_bar, config_cli_arg, load_quickwit_config, parse_duration_with_unit,
+    run_index_checklist, spinner, start_actor_runtimes,
 };
 
+/// Maturation period of the merge policy used by the `merge-splits` command: long enough for the
+/// splits to never be considered mature because of their age.
+const OFFLINE_MERGE_MATURATION_PERIOD: Duration = Duration::from_secs(100 * 365 * 24 * 3600);
+
 pub fn build_tool_command<'a>() -> Command<'a> {
     Command::new("tool")
         .about("Performs utility operations. Requires a node config.")
@@ -79,6 +98,41 @@ pub fn build_tool_command<'a>() -> Command<'a> {
                         .required(false),
                 ])
             )
+        .subcommand(
+            Command::new("local-search")
+                .display_order(10)
+                .about("Searches an index locally.")
+                .long_about("Local search runs a query directly against the splits of an index with an embedded searcher, without requiring any running Quickwit service. The index metadata is read from the metastore of the node config or, if `--index-uri` is set, from the file-backed metastore located at the index URI.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1),
+                    arg!(--"index-uri" <INDEX_URI> "URI of the index, for instance `s3://my-bucket/indexes/my-index`. Its parent must be the root of a file-backed metastore. The node config is not loaded when this argument is set.")
+                        .display_order(2)
+                        .required(false),
+                    arg!(--query <QUERY> "Query expressed in natural query language ((barack AND obama) OR \"president of united states\"). Learn more on https://quickwit.io/docs/reference/search-language.")
+                        .display_order(3),
+                    arg!(--aggregation <AGG> "JSON serialized aggregation request in tantivy/elasticsearch format.")
+                        .required(false),
+                    arg!(--"max-hits" <MAX_HITS> "Maximum number of hits returned.")
+                        .default_value("20")
+                        .required(false),
+                    arg!(--"start-offset" <OFFSET> "Offset in the global result set of the first hit returned.")
+                        .default_value("0")
+                        .required(false),
+                    arg!(--"search-fields" <FIELD_NAME> "List of fields that Quickwit will search into if the user query does not explicitly target a field in the query. It overrides the default search fields defined in the index config. Space-separated list, e.g. \"field1 field2\". ")
+                        .multiple_values(true)
+                        .required(false),
+                    arg!(--"snippet-fields" <FIELD_NAME> "List of fields that Quickwit will return snippet highlight on. Space-separated list, e.g. \"field1 field2\". ")
+                        .multiple_values(true)
+                        .required(false),
+                    arg!(--"start-timestamp" <TIMESTAMP> "Filters out documents before that timestamp (time-series indexes only).")
+                        .required(false),
+                    arg!(--"end-timestamp" <TIMESTAMP> "Filters out documents after that timestamp (time-series indexes only).")
+                        .required(false),
+                    arg!(--"sort-by-score" "Setting this flag calculates and sorts documents by their BM25 score.")
+                        .required(false),
+                ])
+            )
         .subcommand(
             Command::new("extract-split")
                 .about("Downloads and extracts a split to a directory.")
@@ -104,6 +158,21 @@ pub fn build_tool_command<'a>() -> Command<'a> {
                         .required(false),
                 ])
             )
+        .subcommand(
+            Command::new("reconcile-orphans")
+                .display_order(10)
+                .about("Deletes the split files stored under the index URI that are unknown to the metastore.")
+                .long_about("Lists the split files stored under the index URI and deletes the orphan ones, i.e. the files left behind by crashed uploads that do not belong to any split of the metastore.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index")
+                        .display_order(1),
+                    arg!(--"grace-period" <GRACE_PERIOD> "Threshold period after which orphan split files are deleted.")
+                        .default_value("1h")
+                        .required(false),
+                    arg!(--"dry-run" "Executes the command in dry run mode and only displays the list of orphan split files.")
+                        .required(false),
+                ])
+            )
         .subcommand(
             Command::new("merge")
                 .display_order(10)
@@ -114,6 +183,20 @@ pub fn build_tool_command<'a>() -> Command<'a> {
                     arg!(--source <SOURCE_ID> "ID of the target source."),
                 ])
             )
+        .subcommand(
+            Command::new("merge-splits")
+                .display_order(10)
+                .about("Merges the published splits of an index directly against the index storage and the metastore.")
+                .long_about("Merges the published splits of an index according to its merge policy, directly against the index storage and the metastore and without running an indexer. Unlike the merge pipelines of the indexers, the splits produced by different nodes are merged together and the maturation period of the merge policy is ignored, which makes it possible to compact an index whose indexers have been decommissioned. This command should not run while indexers are merging the splits of the index.")
+                .args(&[
+                    arg!(--index <INDEX> "ID of the target index.")
+                        .display_order(1),
+                    arg!(--source <SOURCE_ID> "Only merges the splits of this source.")
+                        .required(false),
+                    arg!(--"dry-run" "Executes the command in dry run mode and only displays the merges planned for the current splits.")
+                        .required(false),
+                ])
+            )
         .arg_required_else_help(true)
 }
 
@@ -135,6 +218,14 @@ pub struct GarbageCollectIndexArgs {
     pub dry_run: bool,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct ReconcileOrphanFilesArgs {
+    pub config_uri: Uri,
+    pub index_id: String,
+    pub grace_period: Duration,
+    pub dry_run: bool,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct MergeArgs {
     pub config_uri: Uri,
@@ -142,6 +233,30 @@ pub struct MergeArgs {
     pub source_id: String,
 }
 
+#[derive(Debug, Eq, PartialEq)]
+pub struct MergeSplitsArgs {
+    pub config_uri: Uri,
+    pub index_id: String,
+    pub source_id_opt: Option<String>,
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Eq, PartialEq)]
+pub struct LocalSearchArgs {
+    pub config_uri: Uri,
+    pub index_id: String,
+    pub index_uri_opt: Option<Uri>,
+    pub query: String,
+    pub aggregation: Option<String>,
+    pub max_hits: usize,
+    pub start_offset: usize,
+    pub search_fields: Option<Vec<String>>,
+    pub snippet_fields: Option<Vec<String>>,
+    pub start_timestamp: Option<i64>,
+    pub end_timestamp: Option<i64>,
+    pub sort_by_score: bool,
+}
+
 #[derive(Debug, Eq, PartialEq)]
 pub struct ExtractSplitArgs {
     pub config_uri: Uri,
@@ -155,7 +270,10 @@ pub enum ToolCliCommand {
     GarbageCollect(GarbageCollectIndexArgs),
     LocalIngest(LocalIngestDocsArgs),
     Merge(MergeArgs),
+    MergeSplits(MergeSplitsArgs),
+    LocalSearch(LocalSearchArgs),
     ExtractSplit(ExtractSplitArgs),
+    ReconcileOrphanFiles(ReconcileOrphanFilesArgs),
 }
 
 impl ToolCliCommand {
@@ -167,7 +285,10 @@ impl ToolCliCommand {
             "gc" => Self::parse_garbage_collect_args(submatches),
             "local-ingest" => Self::parse_local_ingest_args(submatches),
             "merge" => Self::parse_merge_args(submatches),
+            "merge-splits" => Self::parse_merge_splits_args(submatches),
+            "local-search" => Self::parse_local_search_args(submatches),
             "extract-split" => Self::parse_extract_split_args(submatches),
+            "reconcile-orphans" => Self::parse_reconcile_orphans_args(submatches),
             _ => bail!("Tool subcommand `{}` is not implemented.", subcommand),
         }
     }
@@ -224,6 +345,25 @@ impl ToolCliCommand {
         }))
     }
 
+    fn parse_merge_splits_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let config_uri = matches
+            .value_of("config")
+            .map(Uri::from_str)
+            .expect("`config` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .expect("`index` is a required arg.")
+            .to_string();
+        let source_id_opt = matches.value_of("source").map(String::from);
+        let dry_run = matches.is_present("dry-run");
+        Ok(Self::MergeSplits(MergeSplitsArgs {
+            config_uri,
+            index_id,
+            source_id_opt,
+            dry_run,
+        }))
+    }
+
     fn parse_garbage_collect_args(matches: &ArgMatches) -> anyhow::Result<Self> {
         let config_uri = matches
             .value_of("config")
@@ -246,6 +386,81 @@ impl ToolCliCommand {
         }))
     }
 
+    fn parse_reconcile_orphans_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let config_uri = matches
+            .value_of("config")
+            .map(Uri::from_str)
+            .expect("`config` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .expect("`index` is a required arg.")
+            .to_string();
+        let grace_period = matches
+            .value_of("grace-period")
+            .map(parse_duration_with_unit)
+            .expect("`grace-period` should have a default value.")?;
+        let dry_run = matches.is_present("dry-run");
+        Ok(Self::ReconcileOrphanFiles(ReconcileOrphanFilesArgs {
+            index_id,
+            grace_period,
+            dry_run,
+            config_uri,
+        }))
+    }
+
+    fn parse_local_search_args(matches: &ArgMatches) -> anyhow::Result<Self> {
+        let config_uri = matches
+            .value_of("config")
+            .map(Uri::from_str)
+            .expect("`config` is a required arg.")?;
+        let index_id = matches
+            .value_of("index")
+            .expect("`index` is a required arg.")
+            .to_string();
+        let index_uri_opt = matches
+            .value_of("index-uri")
+            .map(Uri::from_str)
+            .transpose()?;
+        let query = matches
+            .value_of("query")
+            .context("`query` is a required arg.")?
+            .to_string();
+        let aggregation = matches.value_of("aggregation").map(|el| el.to_string());
+        let max_hits = matches.value_of_t::<usize>("max-hits")?;
+        let start_offset = matches.value_of_t::<usize>("start-offset")?;
+        let search_fields = matches
+            .values_of("search-fields")
+            .map(|values| values.map(|value| value.to_string()).collect());
+        let snippet_fields = matches
+            .values_of("snippet-fields")
+            .map(|values| values.map(|value| value.to_string()).collect());
+        let sort_by_score = matches.is_present("sort-by-score");
+        let start_timestamp = if matches.is_present("start-timestamp") {
+            Some(matches.value_of_t::<i64>("start-timestamp")?)
+        } else {
+            None
+        };
+        let end_timestamp = if matches.is_present("end-timestamp") {
+            Some(matches.value_of_t::<i64>("end-timestamp")?)
+        } else {
+            None
+        };
+        Ok(Self::LocalSearch(LocalSearchArgs {
+            config_uri,
+            index_id,
+            index_uri_opt,
+            query,
+            aggregation,
+            max_hits,
+            start_offset,
+            search_fields,
+            snippet_fields,
+            start_timestamp,
+            end_timestamp,
+            sort_by_score,
+        }))
+    }
+
     fn parse_extract_split_args(matches: &ArgMatches) -> anyhow::Result<Self> {
         let index_id = matches
             .value_of("index")
@@ -276,7 +491,10 @@ impl ToolCliCommand {
             Self::GarbageCollect(args) => garbage_collect_index_cli(args).await,
             Self::LocalIngest(args) => local_ingest_docs_cli(args).await,
             Self::Merge(args) => merge_cli(args).await,
+            Self::MergeSplits(args) => merge_splits_cli(args).await,
+            Self::LocalSearch(args) => local_search_cli(args).await,
             Self::ExtractSplit(args) => extract_split_cli(args).await,
+            Self::ReconcileOrphanFiles(args) => reconcile_orphan_files_cli(args).await,
         }
     }
 }
@@ -304,11 +522,12 @@ pub async fn local_ingest_docs_cli(args: LocalIngestDocsArgs) -> anyhow::Result<
         source_params,
         transform_config,
         input_format: SourceInputFormat::Json,
+        split_labels: BTreeMap::new(),
     };
-    run_index_checklist(&config.metastore_uri, &args.index_id, Some(&source_config)).await?;
+    run_index_checklist(&config, &args.index_id, Some(&source_config)).await?;
     let metastore_uri_resolver = quickwit_metastore_uri_resolver();
     let metastore = metastore_uri_resolver
-        .resolve(&config.metastore_uri)
+        .resolve_with_config(&config.metastore_config, &config.metastore_uri)
         .await?;
 
     if args.overwrite {
@@ -353,7 +572,7 @@ pub async fn local_ingest_docs_cli(args: LocalIngestDocsArgs) -> anyhow::Result<
         .await?;
 
     let is_stdin_atty = atty::is(atty::Stream::Stdin);
-    if args.input_path_opt.is_none() && is_stdin_atty {
+    let progress_bar = if args.input_path_opt.is_none() && is_stdin_atty {
         let eof_shortcut = match env::consts::OS {
             "windows" => "CTRL+Z",
             _ => "CTRL+D",
@@ -362,10 +581,18 @@ pub async fn local_ingest_docs_cli(args: LocalIngestDocsArgs) -> anyhow::Result<
             "Please, enter JSON documents one line at a time.\nEnd your input using \
              {eof_shortcut}."
         );
-    }
+        // Redrawing a progress bar would garble the documents typed in the terminal.
+        ProgressBar::hidden()
+    } else {
+        let input_len_opt = args
+            .input_path_opt
+            .as_ref()
+            .and_then(|input_path| std::fs::metadata(input_path).ok())
+            .map(|metadata| metadata.len());
+        bytes_progress_bar(input_len_opt)
+    };
     let statistics =
-        start_statistics_reporting_loop(indexing_pipeline_handle, args.input_path_opt.is_none())
-            .await?;
+        start_statistics_reporting_loop(indexing_pipeline_handle, progress_bar).await?;
     merge_pipeline_handle.quit().await;
     // Shutdown the indexing server.
     universe
@@ -392,7 +619,8 @@ pub async fn local_ingest_docs_cli(args: LocalIngestDocsArgs) -> anyhow::Result<
             println!("{} Documents successfully indexed.", "✔".color(GREEN_COLOR));
             Ok(())
         }
-        _ => bail!("Failed to ingest all the documents."),
+        _ => Err(anyhow::anyhow!("Failed to ingest all the documents."))
+            .with_exit_code(ExitCode::PartialFailure),
     }
 }
 
@@ -400,7 +628,7 @@ pub async fn merge_cli(args: MergeArgs) -> anyhow::Result<()> {
     debug!(args=?args, "run-merge-operations");
     println!("❯ Merging splits locally...");
     let config = load_quickwit_config(&args.config_uri).await?;
-    run_index_checklist(&config.metastore_uri, &args.index_id, None).await?;
+    run_index_checklist(&config, &args.index_id, None).await?;
     let indexer_config = IndexerConfig {
         ..Default::default()
     };
@@ -410,7 +638,7 @@ pub async fn merge_cli(args: MergeArgs) -> anyhow::Result<()> {
     let cluster = create_empty_cluster(&config).await?;
     let metastore_uri_resolver = quickwit_metastore_uri_resolver();
     let metastore = metastore_uri_resolver
-        .resolve(&config.metastore_uri)
+        .resolve_with_config(&config.metastore_config, &config.metastore_uri)
         .await?;
     let storage_resolver = quickwit_storage_uri_resolver().clone();
     start_actor_runtimes(&HashSet::from_iter([QuickwitService::Indexer]))?;
@@ -438,6 +666,7 @@ pub async fn merge_cli(args: MergeArgs) -> anyhow::Result<()> {
                 source_params: SourceParams::Vec(VecSourceParams::default()),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             },
             pipeline_ord: 0,
         })
@@ -448,11 +677,16 @@ pub async fn merge_cli(args: MergeArgs) -> anyhow::Result<()> {
         })
         .await?;
 
+    let spinner = spinner("Starting merges...");
     let mut check_interval = tokio::time::interval(Duration::from_secs(1));
     loop {
         check_interval.tick().await;
 
         let observation = pipeline_handle.observe().await;
+        spinner.set_message(format!(
+            "{} ongoing merge(s), {} published split(s)",
+            observation.num_ongoing_merges, observation.num_published_splits
+        ));
 
         if observation.num_ongoing_merges == 0 {
             info!("Merge pipeline has no more ongoing merges, Exiting.");
@@ -465,6 +699,7 @@ pub async fn merge_cli(args: MergeArgs) -> anyhow::Result<()> {
         }
     }
 
+    spinner.finish_and_clear();
     let (pipeline_exit_status, _pipeline_statistics) = pipeline_handle.quit().await;
     indexing_service_handle.quit().await;
     if !matches!(
@@ -477,6 +712,254 @@ pub async fn merge_cli(args: MergeArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
+pub async fn merge_splits_cli(args: MergeSplitsArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "merge-splits");
+    println!("❯ Merging splits...");
+    let config = load_quickwit_config(&args.config_uri).await?;
+    run_index_checklist(&config, &args.index_id, None).await?;
+    let metastore = quickwit_metastore_uri_resolver()
+        .resolve_with_config(&config.metastore_config, &config.metastore_uri)
+        .await?;
+    let index_metadata = metastore.index_metadata(&args.index_id).await?;
+    let index_uid = index_metadata.index_uid.clone();
+    let index_config = index_metadata.into_index_config();
+    let merge_policy = offline_merge_policy(&index_config.indexing_settings);
+
+    let query =
+        ListSplitsQuery::for_index(index_uid.clone()).with_split_state(SplitState::Published);
+    let source_ids: BTreeSet<String> = metastore
+        .list_splits(query)
+        .await?
+        .into_iter()
+        .map(|split| split.split_metadata.source_id)
+        .filter(|source_id| {
+            args.source_id_opt
+                .as_ref()
+                .map_or(true, |target_source_id| source_id == target_source_id)
+        })
+        .collect();
+
+    if args.dry_run {
+        for source_id in source_ids {
+            let splits = list_published_splits(&*metastore, &index_uid, &source_id).await?;
+            for merge_operation in plan_merge_operations(splits, &*merge_policy) {
+                let num_docs: usize = merge_operation
+                    .splits
+                    .iter()
+                    .map(|split| split.num_docs)
+                    .sum();
+                println!(
+                    "Source `{source_id}`: merge {} splits ({} docs).",
+                    merge_operation.splits.len(),
+                    num_docs.separate_with_commas()
+                );
+            }
+        }
+        return Ok(());
+    }
+    start_actor_runtimes(&HashSet::from_iter([QuickwitService::Indexer]))?;
+    let scratch_directory =
+        ScratchDirectory::create_in_dir(config.data_dir_path.join("merge-splits")).await?;
+    let spinner = spinner("Starting merges...");
+    let mut num_merge_ops = 0;
+
+    for source_id in source_ids {
+        let pipeline_id = IndexingPipelineId {
+            index_uid: index_uid.clone(),
+            source_id,
+            node_id: config.node_id.clone(),
+            pipeline_ord: 0,
+        };
+        num_merge_ops += merge_source_splits(
+            &config,
+            &index_config,
+            metastore.clone(),
+            pipeline_id,
+            &*merge_policy,
+            scratch_directory.clone(),
+            &spinner,
+        )
+        .await?;
+    }
+    spinner.finish_and_clear();
+    println!(
+        "{} Merge successful: {num_merge_ops} merge operation(s).",
+        "✔".color(GREEN_COLOR)
+    );
+    Ok(())
+}
+
+/// Merges the published splits of a source round after round until the merge policy does not
+/// plan any merge, and returns the number of merge operations. The merges are executed by the
+/// same actors as the merge pipelines of the indexers, minus the merge planner.
+async fn merge_source_splits(
+    config: &QuickwitConfig,
+    index_config: &IndexConfig,
+    metastore: Arc<dyn Metastore>,
+    pipeline_id: IndexingPipelineId,
+    merge_policy: &dyn MergePolicy,
+    scratch_directory: ScratchDirectory,
+    spinner: &ProgressBar,
+) -> anyhow::Result<usize> {
+    let universe = Universe::new();
+    let index_storage = quickwit_storage_uri_resolver().resolve(&index_config.index_uri)?;
+    let split_store = IndexingSplitStore::create_without_local_store(index_storage);
+
+    let publisher = Publisher::new(PublisherType::MergePublisher, metastore.clone(), None, None);
+    let (publisher_mailbox, publisher_handle) = universe.spawn_builder().spawn(publisher);
+    let uploader = Uploader::new(
+        UploaderType::MergeUploader,
+        metastore.clone(),
+        split_store.clone(),
+        publisher_mailbox.into(),
+        config.indexer_config.max_concurrent_split_uploads,
+    );
+    let (uploader_mailbox, _) = universe.spawn_builder().spawn(uploader);
+    let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)?;
+    let split_pruning_fields = doc_mapper.split_pruning_fields()?;
+    let packager = Packager::new("MergePackager", split_pruning_fields, uploader_mailbox);
+    let (packager_mailbox, _) = universe.spawn_builder().spawn(packager);
+    let merge_executor = MergeExecutor::new(
+        pipeline_id.clone(),
+        metastore.clone(),
+        doc_mapper,
+        IoControls::default(),
+        packager_mailbox,
+    );
+    let (merge_executor_mailbox, _) = universe.spawn_builder().spawn(merge_executor);
+    let merge_split_downloader = MergeSplitDownloader {
+        scratch_directory,
+        split_store,
+        executor_mailbox: merge_executor_mailbox,
+        io_controls: IoControls::default(),
+    };
+    let (merge_split_downloader_mailbox, _) =
+        universe.spawn_builder().spawn(merge_split_downloader);
+
+    let merge_result = run_merge_rounds(
+        &*metastore,
+        &pipeline_id,
+        merge_policy,
+        &merge_split_downloader_mailbox,
+        &publisher_handle,
+        spinner,
+    )
+    .await;
+    universe.quit().await;
+    merge_result
+}
+
+async fn run_merge_rounds(
+    metastore: &dyn Metastore,
+    pipeline_id: &IndexingPipelineId,
+    merge_policy: &dyn MergePolicy,
+    merge_split_downloader_mailbox: &Mailbox<MergeSplitDownloader>,
+    publisher_handle: &ActorHandle<Publisher>,
+    spinner: &ProgressBar,
+) -> anyhow::Result<usize> {
+    // A merge operation is dropped once its merged split is published, or when it fails.
+    let ongoing_merge_operations_inventory = Inventory::new();
+    let mut num_merge_ops = 0;
+    loop {
+        let splits =
+            list_published_splits(metastore, &pipeline_id.index_uid, &pipeline_id.source_id)
+                .await?;
+        let merge_operations = plan_merge_operations(splits, merge_policy);
+        if merge_operations.is_empty() {
+            return Ok(num_merge_ops);
+        }
+        let num_round_merge_ops = merge_operations.len();
+        let num_replace_operations_before = publisher_handle.observe().await.num_replace_operations;
+
+        for merge_operation in merge_operations {
+            let tracked_merge_operation = ongoing_merge_operations_inventory.track(merge_operation);
+            merge_split_downloader_mailbox
+                .send_message(tracked_merge_operation)
+                .await?;
+        }
+        let mut check_interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            check_interval.tick().await;
+            let num_ongoing_merge_ops = ongoing_merge_operations_inventory.list().len();
+            spinner.set_message(format!(
+                "source `{}`: {num_ongoing_merge_ops} ongoing merge(s), {num_merge_ops} completed \
+                 merge(s)",
+                pipeline_id.source_id
+            ));
+            if num_ongoing_merge_ops == 0 {
+                break;
+            }
+        }
+        let num_replace_operations = (publisher_handle.observe().await.num_replace_operations
+            - num_replace_operations_before) as usize;
+        num_merge_ops += num_replace_operations;
+
+        if num_replace_operations < num_round_merge_ops {
+            return Err(anyhow::anyhow!(
+                "{} merge operation(s) of source `{}` failed. Check the logs for more details.",
+                num_round_merge_ops - num_replace_operations,
+                pipeline_id.source_id
+            ))
+            .with_exit_code(ExitCode::PartialFailure);
+        }
+    }
+}
+
+/// Returns the metadata of the published splits of a source.
+async fn list_published_splits(
+    metastore: &dyn Metastore,
+    index_uid: &IndexUid,
+    source_id: &str,
+) -> anyhow::Result<Vec<SplitMetadata>> {
+    let query =
+        ListSplitsQuery::for_index(index_uid.clone()).with_split_state(SplitState::Published);
+    let splits = metastore
+        .list_splits(query)
+        .await?
+        .into_iter()
+        .map(|split| split.split_metadata)
+        .filter(|split| split.source_id == source_id)
+        .collect();
+    Ok(splits)
+}
+
+/// Builds the merge policy of the index with an infinite maturation period, so that the splits
+/// are merged regardless of their age.
+fn offline_merge_policy(indexing_settings: &IndexingSettings) -> Arc<dyn MergePolicy> {
+    let mut indexing_settings = indexing_settings.clone();
+    match &mut indexing_settings.merge_policy {
+        MergePolicyConfig::Nop => {}
+        MergePolicyConfig::ConstWriteAmplification(config) => {
+            config.maturation_period = OFFLINE_MERGE_MATURATION_PERIOD;
+        }
+        MergePolicyConfig::StableLog(config) => {
+            config.maturation_period = OFFLINE_MERGE_MATURATION_PERIOD;
+        }
+    }
+    merge_policy_from_settings(&indexing_settings)
+}
+
+/// Plans the merges of the splits of a source. Unlike the merge planner of the indexing
+/// pipelines, the splits are only grouped by partition and not by node.
+fn plan_merge_operations(
+    splits: Vec<SplitMetadata>,
+    merge_policy: &dyn MergePolicy,
+) -> Vec<MergeOperation> {
+    let mut partitioned_young_splits: HashMap<u64, Vec<SplitMetadata>> = HashMap::new();
+    for split in splits {
+        if !merge_policy.is_mature(&split) {
+            partitioned_young_splits
+                .entry(split.partition_id)
+                .or_default()
+                .push(split);
+        }
+    }
+    partitioned_young_splits
+        .into_values()
+        .flat_map(|mut young_splits| merge_policy.operations(&mut young_splits))
+        .collect()
+}
+
 pub async fn garbage_collect_index_cli(args: GarbageCollectIndexArgs) -> anyhow::Result<()> {
     debug!(args=?args, "garbage-collect-index");
     println!("❯ Garbage collecting index...");
@@ -484,15 +967,27 @@ pub async fn garbage_collect_index_cli(args: GarbageCollectIndexArgs) -> anyhow:
 
     let quickwit_config = load_quickwit_config(&args.config_uri).await?;
     let index_service = IndexService::from_config(quickwit_config.clone()).await?;
-    let removal_info = index_service
-        .garbage_collect_index(&args.index_id, args.grace_period, args.dry_run)
-        .await?;
+    let spinner = spinner("Collecting garbage...");
+    let removal_info_res = index_service
+        .garbage_collect_index(&args.index_id, Some(args.grace_period), args.dry_run)
+        .await;
+    spinner.finish_and_clear();
+    let removal_info = removal_info_res?;
+    display_split_removal_info(removal_info, args.dry_run)
+}
+
+/// Displays the outcome of a garbage collection run. Returns an error if some splits could not
+/// be removed.
+pub(crate) fn display_split_removal_info(
+    removal_info: SplitRemovalInfo,
+    dry_run: bool,
+) -> anyhow::Result<()> {
     if removal_info.removed_split_entries.is_empty() && removal_info.failed_split_ids.is_empty() {
         println!("No dangling files to garbage collect.");
         return Ok(());
     }
 
-    if args.dry_run {
+    if dry_run {
         println!("The following files will be garbage collected.");
         for file_entry in removal_info.removed_split_entries {
             println!(" - {}", file_entry.file_name);
@@ -526,17 +1021,121 @@ pub async fn garbage_collect_index_cli(args: GarbageCollectIndexArgs) -> anyhow:
             "{} Index successfully garbage collected.",
             "✔".color(GREEN_COLOR)
         );
-    } else if removal_info.removed_split_entries.is_empty()
-        && !removal_info.failed_split_ids.is_empty()
-    {
-        println!("{} Failed to garbage collect index.", "✘".color(RED_COLOR));
+        Ok(())
+    } else if removal_info.removed_split_entries.is_empty() {
+        bail!("Failed to garbage collect index.")
     } else {
-        println!(
-            "{} Index partially garbage collected.",
-            "✘".color(RED_COLOR)
-        );
+        Err(anyhow::anyhow!("Index partially garbage collected."))
+            .with_exit_code(ExitCode::PartialFailure)
+    }
+}
+
+pub async fn reconcile_orphan_files_cli(args: ReconcileOrphanFilesArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "reconcile-orphan-files");
+    println!("❯ Reconciling index storage with metastore...");
+
+    let quickwit_config = load_quickwit_config(&args.config_uri).await?;
+    let index_service = IndexService::from_config(quickwit_config).await?;
+    let removal_info = index_service
+        .reconcile_orphan_files(&args.index_id, args.grace_period, args.dry_run)
+        .await?;
+    if removal_info.removed_file_entries.is_empty() && removal_info.failed_file_paths.is_empty() {
+        println!("No orphan files found.");
+        return Ok(());
+    }
+
+    if args.dry_run {
+        println!("The following orphan files will be deleted.");
+        for file_entry in removal_info.removed_file_entries {
+            println!(" - {}", file_entry.file_name);
+        }
+        return Ok(());
+    }
+
+    if !removal_info.failed_file_paths.is_empty() {
+        println!("The following orphan files were attempted to be removed, but failed.");
+        for file_path in removal_info.failed_file_paths.iter() {
+            println!(" - {}", file_path.display());
+        }
     }
 
+    let deleted_bytes: u64 = removal_info
+        .removed_file_entries
+        .iter()
+        .map(|entry| entry.file_size_in_bytes)
+        .sum();
+    println!("{}MB of orphan files deleted.", deleted_bytes / 1_000_000);
+
+    if !removal_info.failed_file_paths.is_empty() {
+        return Err(anyhow::anyhow!(
+            "Failed to delete {} orphan files.",
+            removal_info.failed_file_paths.len()
+        ))
+        .with_exit_code(ExitCode::PartialFailure);
+    }
+    println!(
+        "{} Index storage successfully reconciled.",
+        "✔".color(GREEN_COLOR)
+    );
+    Ok(())
+}
+
+pub async fn local_search(args: LocalSearchArgs) -> anyhow::Result<SearchResponseRest> {
+    let metastore = if let Some(index_uri) = &args.index_uri_opt {
+        if index_uri.file_name() != Some(Path::new(&args.index_id)) {
+            bail!(
+                "Index URI `{index_uri}` must end with the index ID `{}`.",
+                args.index_id
+            );
+        }
+        let metastore_uri = index_uri
+            .parent()
+            .with_context(|| format!("Index URI `{index_uri}` has no parent."))?;
+        quickwit_metastore_uri_resolver()
+            .resolve(&metastore_uri)
+            .await?
+    } else {
+        let quickwit_config = load_quickwit_config(&args.config_uri).await?;
+        quickwit_metastore_uri_resolver()
+            .resolve_with_config(
+                &quickwit_config.metastore_config,
+                &quickwit_config.metastore_uri,
+            )
+            .await?
+    };
+    let (sort_order, sort_by_field) = if args.sort_by_score {
+        (Some(SortOrder::Desc as i32), Some("_score".to_string()))
+    } else {
+        (None, None)
+    };
+    let query_ast = query_ast_from_user_text(&args.query, args.search_fields);
+    let search_request = SearchRequest {
+        index_id: args.index_id,
+        query_ast: serde_json::to_string(&query_ast)?,
+        snippet_fields: args.snippet_fields.unwrap_or_default(),
+        start_timestamp: args.start_timestamp,
+        end_timestamp: args.end_timestamp,
+        max_hits: args.max_hits as u64,
+        start_offset: args.start_offset as u64,
+        aggregation_request: args.aggregation,
+        sort_order,
+        sort_by_field,
+    };
+    let search_response = single_node_search(
+        search_request,
+        &*metastore,
+        quickwit_storage_uri_resolver().clone(),
+    )
+    .await?;
+    let search_response_rest = SearchResponseRest::try_from(search_response)?;
+    Ok(search_response_rest)
+}
+
+async fn local_search_cli(args: LocalSearchArgs) -> anyhow::Result<()> {
+    debug!(args=?args, "local-search");
+    let search_response_rest = local_search(args).await?;
+    let search_response_json = serde_json::to_string_pretty(&search_response_rest)?;
+    println!("{search_response_json}");
     Ok(())
 }
 
@@ -548,7 +1147,10 @@ async fn extract_split_cli(args: ExtractSplitArgs) -> anyhow::Result<()> {
     let storage_uri_resolver = quickwit_storage_uri_resolver();
     let metastore_uri_resolver = quickwit_metastore_uri_resolver();
     let metastore = metastore_uri_resolver
-        .resolve(&quickwit_config.metastore_uri)
+        .resolve_with_config(
+            &quickwit_config.metastore_config,
+            &quickwit_config.metastore_uri,
+        )
         .await?;
     let index_metadata = metastore.index_metadata(&args.index_id).await?;
     let index_storage = storage_uri_resolver.resolve(index_metadata.index_uri())?;
@@ -571,15 +1173,13 @@ async fn extract_split_cli(args: ExtractSplitArgs) -> anyhow::Result<()> {
     Ok(())
 }
 
-/// Starts a tokio task that displays the indexing statistics
-/// every once in awhile.
+/// Reports the progress of the indexing pipeline on the progress bar until the pipeline exits,
+/// then displays the indexing statistics.
 pub async fn start_statistics_reporting_loop(
     pipeline_handle: ActorHandle<IndexingPipeline>,
-    is_stdin: bool,
+    progress_bar: ProgressBar,
 ) -> anyhow::Result<IndexingStatistics> {
-    let mut stdout_handle = stdout();
     let start_time = Instant::now();
-    let mut throughput_calculator = ThroughputCalculator::new(start_time);
     let mut report_interval = tokio::time::interval(Duration::from_secs(1));
 
     loop {
@@ -590,21 +1190,16 @@ pub async fn start_statistics_reporting_loop(
         // Try to receive with a timeout of 1 second.
         // 1 second is also the frequency at which we update statistic in the console
         let observation = pipeline_handle.observe().await;
-
-        // Let's not display live statistics to allow screen to scroll.
-        if observation.state.num_docs > 0 {
-            display_statistics(
-                &mut stdout_handle,
-                &mut throughput_calculator,
-                &observation.state,
-            )?;
-        }
+        update_progress_bar(&progress_bar, &observation.state);
 
         if observation.obs_type == ObservationType::PostMortem {
             break;
         }
     }
     let (pipeline_exit_status, pipeline_statistics) = pipeline_handle.join().await;
+    update_progress_bar(&progress_bar, &pipeline_statistics);
+    progress_bar.finish();
+
     if !pipeline_exit_status.is_success() {
         bail!(pipeline_exit_status);
     }
@@ -613,14 +1208,6 @@ pub async fn start_statistics_reporting_loop(
     if pipeline_statistics.num_docs == 0 {
         return Ok(pipeline_statistics);
     }
-
-    if is_stdin {
-        display_statistics(
-            &mut stdout_handle,
-            &mut throughput_calculator,
-            &pipeline_statistics,
-        )?;
-    }
     // display end of task report
     println!();
     let secs = Duration::from_secs(start_time.elapsed().as_secs());
@@ -663,95 +1250,14 @@ fn colorize_error_rate(error_rate: f64) -> ColoredString {
     }
 }
 
-/// A struct to print data on the standard output.
-struct Printer<'a> {
-    pub stdout: &'a mut Stdout,
-}
-
-impl<'a> Printer<'a> {
-    pub fn print_header(&mut self, header: &str) -> io::Result<()> {
-        write!(&mut self.stdout, " {}", header.bright_blue())?;
-        Ok(())
-    }
-
-    pub fn print_value(&mut self, fmt_args: fmt::Arguments) -> io::Result<()> {
-        write!(&mut self.stdout, " {fmt_args}")
-    }
-
-    pub fn flush(&mut self) -> io::Result<()> {
-        self.stdout.flush()
-    }
-}
-
-fn display_statistics(
-    stdout: &mut Stdout,
-    throughput_calculator: &mut ThroughputCalculator,
-    statistics: &IndexingStatistics,
-) -> anyhow::Result<()> {
-    let elapsed_duration = time::Duration::try_from(throughput_calculator.elapsed_time())?;
-    let elapsed_time = format!(
-        "{:02}:{:02}:{:02}",
-        elapsed_duration.whole_hours(),
-        elapsed_duration.whole_minutes() % 60,
-        elapsed_duration.whole_seconds() % 60
-    );
-    let throughput_mb_s = throughput_calculator.calculate(statistics.total_bytes_processed);
-    let mut printer = Printer { stdout };
-    printer.print_header("Num docs")?;
-    printer.print_value(format_args!("{:>7}", statistics.num_docs))?;
-    printer.print_header("Parse errs")?;
-    printer.print_value(format_args!("{:>5}", statistics.num_invalid_docs))?;
-    printer.print_header("PublSplits")?;
-    printer.print_value(format_args!("{:>3}", statistics.num_published_splits))?;
-    printer.print_header("Input size")?;
-    printer.print_value(format_args!(
-        "{:>5}MB",
-        statistics.total_bytes_processed / 1_000_000
-    ))?;
-    printer.print_header("Thrghput")?;
-    printer.print_value(format_args!("{throughput_mb_s:>5.2}MB/s"))?;
-    printer.print_header("Time")?;
-    printer.print_value(format_args!("{elapsed_time}\n"))?;
-    printer.flush()?;
-    Ok(())
-}
-
-/// ThroughputCalculator is used to calculate throughput.
-struct ThroughputCalculator {
-    /// Stores the time series of processed bytes value.
-    processed_bytes_values: VecDeque<(Instant, u64)>,
-    /// Store the time this calculator started
-    start_time: Instant,
-}
-
-impl ThroughputCalculator {
-    /// Creates new instance.
-    pub fn new(start_time: Instant) -> Self {
-        let processed_bytes_values: VecDeque<(Instant, u64)> = (0..THROUGHPUT_WINDOW_SIZE)
-            .map(|_| (start_time, 0u64))
-            .collect();
-        Self {
-            processed_bytes_values,
-            start_time,
-        }
-    }
-
-    /// Calculates the throughput.
-    pub fn calculate(&mut self, current_processed_bytes: u64) -> f64 {
-        self.processed_bytes_values.pop_front();
-        let current_instant = Instant::now();
-        let (first_instant, first_processed_bytes) = *self.processed_bytes_values.front().unwrap();
-        let elapsed_time = (current_instant - first_instant).as_millis() as f64 / 1_000f64;
-        self.processed_bytes_values
-            .push_back((current_instant, current_processed_bytes));
-        (current_processed_bytes - first_processed_bytes) as f64
-            / 1_000_000f64
-            / elapsed_time.max(1f64)
-    }
-
-    pub fn elapsed_time(&self) -> Duration {
-        self.start_time.elapsed()
-    }
+fn update_progress_bar(progress_bar: &ProgressBar, statistics: &IndexingStatistics) {
+    progress_bar.set_position(statistics.total_bytes_processed);
+    progress_bar.set_message(format!(
+        "{} docs, {} parse error(s), {} published split(s)",
+        statistics.num_docs.separate_with_commas(),
+        statistics.num_invalid_docs.separate_with_commas(),
+        statistics.num_published_splits
+    ));
 }
 
 async fn create_empty_cluster(config: &QuickwitConfig) -> anyhow::Result<Cluster> {
@@ -775,3 +1281,59 @@ async fn create_empty_cluster(config: &QuickwitConfig) -> anyhow::Result<Cluster
     .await?;
     Ok(cluster)
 }
+
+#[cfg(test)]
+mod tests {
+    use quickwit_config::ConstWriteAmplificationMergePolicyConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_plan_merge_operations() {
+        let indexing_settings = IndexingSettings {
+            merge_policy: MergePolicyConfig::ConstWriteAmplification(
+                ConstWriteAmplificationMergePolicyConfig {
+                    max_merge_ops: 3,
+                    merge_factor: 3,
+                    max_merge_factor: 3,
+                    maturation_period: Duration::from_secs(3600),
+                },
+            ),
+            ..Default::default()
+        };
+        // The splits were produced a long time ago by decommissioned indexers.
+        let splits: Vec<SplitMetadata> = [
+            ("split-1", "indexer-1", 0),
+            ("split-2", "indexer-2", 0),
+            ("split-3", "indexer-3", 0),
+            ("split-4", "indexer-1", 1),
+            ("split-5", "indexer-2", 1),
+        ]
+        .into_iter()
+        .map(|(split_id, node_id, partition_id)| SplitMetadata {
+            split_id: split_id.to_string(),
+            node_id: node_id.to_string(),
+            partition_id,
+            num_docs: 1_000,
+            ..Default::default()
+        })
+        .collect();
+
+        let merge_policy = merge_policy_from_settings(&indexing_settings);
+        assert!(plan_merge_operations(splits.clone(), &*merge_policy).is_empty());
+
+        let merge_policy = offline_merge_policy(&indexing_settings);
+        let merge_operations = plan_merge_operations(splits, &*merge_policy);
+        assert_eq!(merge_operations.len(), 1);
+
+        let merged_split_ids: BTreeSet<&str> = merge_operations[0]
+            .splits
+            .iter()
+            .map(|split| split.split_id())
+            .collect();
+        assert_eq!(
+            merged_split_ids,
+            BTreeSet::from(["split-1", "split-2", "split-3"])
+        );
+    }
+}
@@ -30,12 +30,14 @@ use clap::ErrorKind;
 use helpers::{TestEnv, TestStorageType};
 use quickwit_cli::cli::build_cli;
 use quickwit_cli::index::{
-    create_index_cli, delete_index_cli, search_index, CreateIndexArgs, DeleteIndexArgs,
-    SearchIndexArgs,
+    clone_index_cli, create_index_cli, delete_by_query_cli, delete_index_cli,
+    preview_delete_by_query, search_index, CloneIndexArgs, CreateIndexArgs, DeleteByQueryArgs,
+    DeleteIndexArgs, SearchIndexArgs,
 };
 use quickwit_cli::service::RunCliCommand;
 use quickwit_cli::tool::{
-    garbage_collect_index_cli, local_ingest_docs_cli, GarbageCollectIndexArgs, LocalIngestDocsArgs,
+    garbage_collect_index_cli, local_ingest_docs_cli, local_search, GarbageCollectIndexArgs,
+    LocalIngestDocsArgs, LocalSearchArgs,
 };
 use quickwit_common::fs::get_cache_directory_path;
 use quickwit_common::rand::append_random_suffix;
@@ -44,6 +46,7 @@ use quickwit_common::ChecklistError;
 use quickwit_config::service::QuickwitService;
 use quickwit_config::CLI_INGEST_SOURCE_ID;
 use quickwit_metastore::{quickwit_metastore_uri_resolver, Metastore, MetastoreError, SplitState};
+use quickwit_rest_client::rest_client::{QuickwitClient, Transport};
 use serde_json::{json, Number, Value};
 use tokio::time::{sleep, Duration};
 
@@ -494,6 +497,113 @@ async fn test_search_index_cli() {
     assert_eq!(search_res.num_hits, 0);
 }
 
+#[tokio::test]
+async fn test_local_search_cli() {
+    quickwit_common::setup_logging_for_tests();
+    let index_id = append_random_suffix("test-local-search-cmd");
+    let test_env = create_test_env(index_id.clone(), TestStorageType::LocalFileSystem).unwrap();
+    test_env.start_server().await.unwrap();
+    create_logs_index(&test_env).await.unwrap();
+
+    local_ingest_docs(test_env.resource_files["logs"].as_path(), &test_env)
+        .await
+        .unwrap();
+
+    let create_local_search_args = |index_uri_opt: Option<Uri>, query: &str| LocalSearchArgs {
+        config_uri: test_env.config_uri.clone(),
+        index_id: index_id.clone(),
+        index_uri_opt,
+        query: query.to_string(),
+        aggregation: None,
+        max_hits: 20,
+        start_offset: 0,
+        search_fields: None,
+        snippet_fields: None,
+        start_timestamp: None,
+        end_timestamp: None,
+        sort_by_score: false,
+    };
+
+    // Reads the index metadata from the metastore of the node config.
+    let args = create_local_search_args(None, "level:info");
+    let search_res = local_search(args).await.unwrap();
+    assert_eq!(search_res.num_hits, 2);
+
+    // Reads the index metadata from the file-backed metastore located at the index URI.
+    let args =
+        create_local_search_args(Some(test_env.index_uri.clone()), "+level:info +city:paris");
+    let search_res = local_search(args).await.unwrap();
+    assert_eq!(search_res.num_hits, 1);
+
+    let args = create_local_search_args(
+        Some(test_env.metastore_uri.join("another-index").unwrap()),
+        "level:info",
+    );
+    let error = local_search(args).await.unwrap_err();
+    assert!(error.to_string().contains("must end with the index ID"));
+}
+
+#[tokio::test]
+async fn test_clone_index_cli() {
+    quickwit_common::setup_logging_for_tests();
+    let index_id = append_random_suffix("test-clone-cmd");
+    let test_env = create_test_env(index_id.clone(), TestStorageType::LocalFileSystem).unwrap();
+    test_env.start_server().await.unwrap();
+    create_logs_index(&test_env).await.unwrap();
+
+    local_ingest_docs(test_env.resource_files["logs"].as_path(), &test_env)
+        .await
+        .unwrap();
+
+    let target_metastore_uri = Uri::from_well_formed(format!(
+        "file://{}",
+        test_env.data_dir_path.join("cloned-indexes").display()
+    ));
+    let target_index_id = format!("{index_id}-clone");
+    let target_index_uri = target_metastore_uri.join(&target_index_id).unwrap();
+    let args = CloneIndexArgs {
+        source_metastore_uri: test_env.metastore_uri.clone(),
+        target_metastore_uri: target_metastore_uri.clone(),
+        index_id: index_id.clone(),
+        target_index_id: target_index_id.clone(),
+        target_index_uri_opt: Some(target_index_uri.clone()),
+    };
+    clone_index_cli(args).await.unwrap();
+
+    let target_metastore = quickwit_metastore_uri_resolver()
+        .resolve(&target_metastore_uri)
+        .await
+        .unwrap();
+    let target_index_metadata = target_metastore
+        .index_metadata(&target_index_id)
+        .await
+        .unwrap();
+    assert_eq!(target_index_metadata.index_uri(), &target_index_uri);
+    let splits = target_metastore
+        .list_all_splits(target_index_metadata.index_uid)
+        .await
+        .unwrap();
+    assert_eq!(splits.len(), 1);
+    assert_eq!(splits[0].split_state, SplitState::Published);
+
+    let args = LocalSearchArgs {
+        config_uri: test_env.config_uri.clone(),
+        index_id: target_index_id,
+        index_uri_opt: Some(target_index_uri),
+        query: "level:info".to_string(),
+        aggregation: None,
+        max_hits: 20,
+        start_offset: 0,
+        search_fields: None,
+        snippet_fields: None,
+        start_timestamp: None,
+        end_timestamp: None,
+        sort_by_score: false,
+    };
+    let search_res = local_search(args).await.unwrap();
+    assert_eq!(search_res.num_hits, 2);
+}
+
 #[tokio::test]
 async fn test_delete_index_cli_dry_run() {
     quickwit_common::setup_logging_for_tests();
@@ -575,6 +685,49 @@ async fn test_delete_index_cli() {
         .is_err());
 }
 
+#[tokio::test]
+async fn test_delete_by_query_cli() {
+    quickwit_common::setup_logging_for_tests();
+    let index_id = append_random_suffix("test-delete-by-query-cmd");
+    let test_env = create_test_env(index_id.clone(), TestStorageType::LocalFileSystem).unwrap();
+    test_env.start_server().await.unwrap();
+    create_logs_index(&test_env).await.unwrap();
+
+    local_ingest_docs(test_env.resource_files["logs"].as_path(), &test_env)
+        .await
+        .unwrap();
+
+    let create_delete_by_query_args = |query: &str| DeleteByQueryArgs {
+        cluster_endpoint: test_env.cluster_endpoint.clone(),
+        index_id: index_id.clone(),
+        query: query.to_string(),
+        start_timestamp: None,
+        end_timestamp: None,
+        assume_yes: true,
+    };
+    let preview = preview_delete_by_query(&create_delete_by_query_args("level:info"))
+        .await
+        .unwrap();
+    assert_eq!(preview.num_matching_docs, 2);
+    assert_eq!(preview.num_candidate_splits, 1);
+    assert_eq!(preview.num_candidate_split_docs, 5);
+
+    let qw_client = QuickwitClient::new(Transport::new(test_env.cluster_endpoint.clone()));
+
+    // No delete task is submitted when no documents match the query.
+    delete_by_query_cli(create_delete_by_query_args("level:fatal"))
+        .await
+        .unwrap();
+    let delete_tasks = qw_client.delete_tasks(&index_id).list().await.unwrap();
+    assert!(delete_tasks.is_empty());
+
+    delete_by_query_cli(create_delete_by_query_args("level:info"))
+        .await
+        .unwrap();
+    let delete_tasks = qw_client.delete_tasks(&index_id).list().await.unwrap();
+    assert_eq!(delete_tasks.len(), 1);
+}
+
 #[tokio::test]
 async fn test_garbage_collect_cli_no_grace() {
     quickwit_common::setup_logging_for_tests();
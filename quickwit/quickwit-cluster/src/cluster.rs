@@ -41,11 +41,12 @@ use tracing::{info, warn};
 
 use crate::change::{compute_cluster_change_events, ClusterChange};
 use crate::member::{
-    build_cluster_member, ClusterMember, NodeStateExt, ENABLED_SERVICES_KEY,
-    GRPC_ADVERTISE_ADDR_KEY, INDEXING_TASK_PREFIX, READINESS_KEY, READINESS_VALUE_NOT_READY,
-    READINESS_VALUE_READY,
+    build_cluster_member, ClusterMember, NodeStateExt, AVAILABILITY_ZONE_KEY,
+    DECOMMISSION_REQUEST_PREFIX, DECOMMISSION_STATUS_KEY, ENABLED_SERVICES_KEY,
+    GRPC_ADVERTISE_ADDR_KEY, INDEXER_LOAD_KEY, INDEXING_TASK_PREFIX, READINESS_KEY,
+    READINESS_VALUE_NOT_READY, READINESS_VALUE_READY, SEARCHER_LOAD_KEY,
 };
-use crate::ClusterNode;
+use crate::{ClusterNode, DecommissionStatus, IndexerLoad, SearcherLoad};
 
 const GOSSIP_INTERVAL: Duration = if cfg!(any(test, feature = "testsuite")) {
     Duration::from_millis(25)
@@ -119,6 +120,7 @@ impl Cluster {
             gossip_listen_addr=%gossip_listen_addr,
             gossip_advertise_addr=%self_node.gossip_advertise_addr,
             grpc_advertise_addr=%self_node.grpc_advertise_addr,
+            availability_zone=?self_node.availability_zone_opt,
             peer_seed_addrs=%peer_seed_addrs.join(", "),
             "Joining cluster."
         );
@@ -131,29 +133,29 @@ impl Cluster {
             gossip_interval: GOSSIP_INTERVAL,
             marked_for_deletion_grace_period: MARKED_FOR_DELETION_GRACE_PERIOD,
         };
-        let chitchat_handle = spawn_chitchat(
-            chitchat_config,
-            vec![
-                (
-                    ENABLED_SERVICES_KEY.to_string(),
-                    self_node
-                        .enabled_services
-                        .iter()
-                        .map(|service| service.as_str())
-                        .join(","),
-                ),
-                (
-                    GRPC_ADVERTISE_ADDR_KEY.to_string(),
-                    self_node.grpc_advertise_addr.to_string(),
-                ),
-                (
-                    READINESS_KEY.to_string(),
-                    READINESS_VALUE_NOT_READY.to_string(),
-                ),
-            ],
-            transport,
-        )
-        .await?;
+        let mut initial_key_values = vec![
+            (
+                ENABLED_SERVICES_KEY.to_string(),
+                self_node
+                    .enabled_services
+                    .iter()
+                    .map(|service| service.as_str())
+                    .join(","),
+            ),
+            (
+                GRPC_ADVERTISE_ADDR_KEY.to_string(),
+                self_node.grpc_advertise_addr.to_string(),
+            ),
+            (
+                READINESS_KEY.to_string(),
+                READINESS_VALUE_NOT_READY.to_string(),
+            ),
+        ];
+        if let Some(availability_zone) = &self_node.availability_zone_opt {
+            initial_key_values.push((AVAILABILITY_ZONE_KEY.to_string(), availability_zone.clone()));
+        }
+        let chitchat_handle =
+            spawn_chitchat(chitchat_config, initial_key_values, transport).await?;
 
         let chitchat = chitchat_handle.chitchat();
         let live_nodes_stream = chitchat.lock().await.live_nodes_watcher();
@@ -188,6 +190,17 @@ impl Cluster {
         WatchStream::new(self.inner.read().await.ready_members_rx.clone())
     }
 
+    /// Returns the live nodes of the cluster, ready or not, ordered by Chitchat ID.
+    pub async fn live_nodes(&self) -> Vec<ClusterNode> {
+        self.inner
+            .read()
+            .await
+            .live_nodes
+            .values()
+            .cloned()
+            .collect()
+    }
+
     /// Returns a stream of changes affecting the set of ready nodes in the cluster.
     pub async fn ready_nodes_change_stream(&self) -> impl Stream<Item = ClusterChange> {
         // The subscriber channel must be unbounded because we do no want to block when sending the
@@ -334,11 +347,76 @@ impl Cluster {
         Ok(())
     }
 
+    /// Advertises the live load of the self node's searcher to the other nodes of the cluster.
+    pub async fn update_self_node_searcher_load(
+        &self,
+        searcher_load: &SearcherLoad,
+    ) -> anyhow::Result<()> {
+        let searcher_load_value = serde_json::to_string(searcher_load)?;
+        self.set_self_key_value(SEARCHER_LOAD_KEY, searcher_load_value)
+            .await;
+        Ok(())
+    }
+
+    /// Advertises the load of the self node's indexing pipelines to the other nodes of the cluster.
+    pub async fn update_self_node_indexer_load(
+        &self,
+        indexer_load: &IndexerLoad,
+    ) -> anyhow::Result<()> {
+        let indexer_load_value = serde_json::to_string(indexer_load)?;
+        self.set_self_key_value(INDEXER_LOAD_KEY, indexer_load_value)
+            .await;
+        Ok(())
+    }
+
+    /// Requests the decommission of a node. The request is advertised via gossip and the target
+    /// node decommissions itself once it receives it.
+    pub async fn request_node_decommission(&self, chitchat_id: &ChitchatId) {
+        self.set_self_key_value(decommission_request_key(chitchat_id), "true")
+            .await
+    }
+
+    /// Returns whether a node of the cluster requested the decommission of the self node.
+    pub async fn is_self_node_decommission_requested(&self) -> bool {
+        let decommission_request_key = decommission_request_key(&self.self_chitchat_id);
+        let chitchat = self.chitchat().await;
+        let chitchat_guard = chitchat.lock().await;
+        let is_requested = std::iter::once(&self.self_chitchat_id)
+            .chain(chitchat_guard.live_nodes())
+            .filter_map(|chitchat_id| chitchat_guard.node_state(chitchat_id))
+            .any(|node_state| node_state.get(&decommission_request_key).is_some());
+        is_requested
+    }
+
+    /// Returns the decommission status of the self node.
+    pub async fn self_node_decommission_status(&self) -> DecommissionStatus {
+        self.chitchat()
+            .await
+            .lock()
+            .await
+            .node_state(&self.self_chitchat_id)
+            .expect("The self node should always be present in the set of live nodes.")
+            .decommission_status()
+    }
+
+    /// Sets the self node's decommission status.
+    pub async fn set_self_node_decommission_status(&self, decommission_status: DecommissionStatus) {
+        self.set_self_key_value(DECOMMISSION_STATUS_KEY, decommission_status.as_str())
+            .await
+    }
+
     async fn chitchat(&self) -> Arc<Mutex<Chitchat>> {
         self.inner.read().await.chitchat_handle.chitchat()
     }
 }
 
+fn decommission_request_key(chitchat_id: &ChitchatId) -> String {
+    format!(
+        "{DECOMMISSION_REQUEST_PREFIX}:{}:{}",
+        chitchat_id.node_id, chitchat_id.generation_id
+    )
+}
+
 /// Deprecated: this is going away soon.
 fn spawn_ready_members_task(
     cluster_id: String,
@@ -479,6 +557,24 @@ pub struct ClusterSnapshot {
     pub chitchat_state_snapshot: ClusterStateSnapshot,
 }
 
+impl ClusterSnapshot {
+    /// Returns the members of the cluster built from the node states of the Chitchat cluster
+    /// state snapshot. The nodes whose state is incomplete or malformed are skipped.
+    pub fn members(&self) -> Vec<ClusterMember> {
+        self.chitchat_state_snapshot
+            .node_state_snapshots
+            .iter()
+            .filter_map(|node_state_snapshot| {
+                build_cluster_member(
+                    node_state_snapshot.chitchat_id.clone(),
+                    &node_state_snapshot.node_state,
+                )
+                .ok()
+            })
+            .collect()
+    }
+}
+
 /// Computes the gRPC port from the listen address for tests.
 #[cfg(any(test, feature = "testsuite"))]
 pub fn grpc_addr_from_listen_addr_for_test(listen_addr: SocketAddr) -> SocketAddr {
@@ -648,6 +744,24 @@ mod tests {
         node.shutdown().await;
     }
 
+    #[tokio::test]
+    async fn test_cluster_snapshot_members() -> anyhow::Result<()> {
+        let transport = ChannelTransport::default();
+        let node =
+            create_cluster_for_test(Vec::new(), &["indexer", "searcher"], &transport, true).await?;
+        let cluster_snapshot = node.snapshot().await;
+        let members = cluster_snapshot.members();
+        assert_eq!(members.len(), 1);
+        assert_eq!(members[0].chitchat_id(), node.self_chitchat_id);
+        assert!(members[0].is_ready);
+        assert_eq!(
+            members[0].enabled_services,
+            HashSet::from_iter([QuickwitService::Indexer, QuickwitService::Searcher])
+        );
+        node.shutdown().await;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_cluster_multiple_nodes() -> anyhow::Result<()> {
         let transport = ChannelTransport::default();
@@ -805,6 +919,195 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_cluster_members_searcher_load() {
+        let transport = ChannelTransport::default();
+        let cluster1 = create_cluster_for_test(Vec::new(), &["searcher"], &transport, true)
+            .await
+            .unwrap();
+        let cluster2 = create_cluster_for_test(
+            vec![cluster1.gossip_listen_addr.to_string()],
+            &["searcher"],
+            &transport,
+            true,
+        )
+        .await
+        .unwrap();
+        let searcher_load = SearcherLoad {
+            num_inflight_leaf_searches: 3,
+            leaf_search_latency_millis: 120,
+            cached_split_ids: vec!["split-1".to_string()],
+        };
+        cluster2
+            .update_self_node_searcher_load(&searcher_load)
+            .await
+            .unwrap();
+        cluster1
+            .wait_for_ready_members(
+                |members| {
+                    members.len() == 2
+                        && members
+                            .iter()
+                            .any(|member| member.searcher_load_opt.as_ref() == Some(&searcher_load))
+                },
+                Duration::from_secs(30),
+            )
+            .await
+            .unwrap();
+        let members = cluster1.ready_members().await;
+        let member_node_1 = members
+            .iter()
+            .find(|member| member.chitchat_id() == cluster1.self_chitchat_id)
+            .unwrap();
+        assert!(member_node_1.searcher_load_opt.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cluster_members_indexer_load() {
+        let transport = ChannelTransport::default();
+        let cluster1 = create_cluster_for_test(Vec::new(), &["searcher"], &transport, true)
+            .await
+            .unwrap();
+        let cluster2 = create_cluster_for_test(
+            vec![cluster1.gossip_listen_addr.to_string()],
+            &["indexer"],
+            &transport,
+            true,
+        )
+        .await
+        .unwrap();
+        let indexer_load = IndexerLoad {
+            num_pipelines: 3,
+            num_saturated_pipelines: 1,
+            num_idle_pipelines: 1,
+            source_lag: 1_000,
+        };
+        cluster2
+            .update_self_node_indexer_load(&indexer_load)
+            .await
+            .unwrap();
+        cluster1
+            .wait_for_ready_members(
+                |members| {
+                    members
+                        .iter()
+                        .any(|member| member.indexer_load_opt.as_ref() == Some(&indexer_load))
+                },
+                Duration::from_secs(30),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cluster_members_availability_zone() {
+        let transport = ChannelTransport::default();
+        let cluster1 = create_cluster_for_test(Vec::new(), &["searcher"], &transport, true)
+            .await
+            .unwrap();
+        let gossip_advertise_addr: SocketAddr = ([127, 0, 0, 1], 9_999).into();
+        let mut self_node = ClusterMember::new(
+            "node_zone".to_string(),
+            crate::GenerationId(1),
+            true,
+            HashSet::from_iter([QuickwitService::Searcher]),
+            gossip_advertise_addr,
+            grpc_addr_from_listen_addr_for_test(gossip_advertise_addr),
+            Vec::new(),
+        );
+        self_node.availability_zone_opt = Some("us-east-1a".to_string());
+        let cluster2 = Cluster::join(
+            "test-cluster".to_string(),
+            self_node,
+            gossip_advertise_addr,
+            vec![cluster1.gossip_listen_addr.to_string()],
+            create_failure_detector_config_for_test(),
+            &transport,
+        )
+        .await
+        .unwrap();
+        cluster2.set_self_node_readiness(true).await;
+        cluster1
+            .wait_for_ready_members(
+                |members| {
+                    members.len() == 2
+                        && members.iter().any(|member| {
+                            member.availability_zone_opt.as_deref() == Some("us-east-1a")
+                        })
+                },
+                Duration::from_secs(30),
+            )
+            .await
+            .unwrap();
+        let members = cluster1.ready_members().await;
+        let member_node_1 = members
+            .iter()
+            .find(|member| member.chitchat_id() == cluster1.self_chitchat_id)
+            .unwrap();
+        assert!(member_node_1.availability_zone_opt.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cluster_node_decommission() {
+        let transport = ChannelTransport::default();
+        let cluster1 = create_cluster_for_test(Vec::new(), &["searcher"], &transport, true)
+            .await
+            .unwrap();
+        let cluster2 = create_cluster_for_test(
+            vec![cluster1.gossip_listen_addr.to_string()],
+            &["indexer"],
+            &transport,
+            true,
+        )
+        .await
+        .unwrap();
+        cluster1
+            .wait_for_ready_members(|members| members.len() == 2, Duration::from_secs(30))
+            .await
+            .unwrap();
+        assert!(!cluster1.is_self_node_decommission_requested().await);
+        assert!(!cluster2.is_self_node_decommission_requested().await);
+
+        cluster1
+            .request_node_decommission(&cluster2.self_chitchat_id)
+            .await;
+        let cluster2_clone = cluster2.clone();
+        wait_until_predicate(
+            move || {
+                let cluster2_clone = cluster2_clone.clone();
+                async move { cluster2_clone.is_self_node_decommission_requested().await }
+            },
+            Duration::from_secs(10),
+            Duration::from_millis(100),
+        )
+        .await
+        .unwrap();
+        assert!(!cluster1.is_self_node_decommission_requested().await);
+
+        assert_eq!(
+            cluster2.self_node_decommission_status().await,
+            DecommissionStatus::Active
+        );
+        cluster2
+            .set_self_node_decommission_status(DecommissionStatus::Decommissioned)
+            .await;
+        assert_eq!(
+            cluster2.self_node_decommission_status().await,
+            DecommissionStatus::Decommissioned
+        );
+        cluster1
+            .wait_for_ready_members(
+                |members| {
+                    members.iter().any(|member| {
+                        member.decommission_status == DecommissionStatus::Decommissioned
+                    })
+                },
+                Duration::from_secs(30),
+            )
+            .await
+            .unwrap();
+    }
+
     #[tokio::test]
     async fn test_chitchat_state_set_high_number_of_tasks() {
         let transport = ChannelTransport::default();
@@ -0,0 +1,302 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use async_trait::async_trait;
+use chitchat::transport::{Socket, Transport};
+use chitchat::{ChitchatMessage, Serializable};
+use quickwit_common::tower::make_endpoint;
+use quickwit_proto::cluster_api::cluster_api_service_client::ClusterApiServiceClient;
+use quickwit_proto::cluster_api::cluster_api_service_server::{
+    ClusterApiService, ClusterApiServiceServer,
+};
+use quickwit_proto::cluster_api::{GossipRequest, GossipResponse};
+use quickwit_proto::SpanContextInterceptor;
+use tokio::sync::{mpsc, Mutex};
+use tonic::codegen::InterceptedService;
+use tonic::transport::Channel;
+use tracing::debug;
+
+/// Capacity of the queue of the gossip messages received and not yet processed by the node.
+const INBOX_CAPACITY: usize = 1_000;
+
+const GOSSIP_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+type Inbox = mpsc::Receiver<(SocketAddr, ChitchatMessage)>;
+
+type GossipClient = ClusterApiServiceClient<InterceptedService<Channel, SpanContextInterceptor>>;
+
+/// Gossip transport delivering the messages of the membership protocol through the gRPC service of
+/// the nodes instead of UDP datagrams. The node must mount the service returned by
+/// [`GrpcTransport::grpc_service`] and gossip on its gRPC address. The messages are sent with TLS
+/// when the gRPC client TLS settings of the node are set (see
+/// [`quickwit_common::tower::set_client_tls_config`]).
+#[derive(Clone)]
+pub struct GrpcTransport {
+    self_addr: SocketAddr,
+    inbox_tx: mpsc::Sender<(SocketAddr, ChitchatMessage)>,
+    inbox_rx_opt: Arc<Mutex<Option<Inbox>>>,
+}
+
+impl GrpcTransport {
+    /// Creates a transport for the node advertising `self_addr` as gossip address.
+    pub fn new(self_addr: SocketAddr) -> Self {
+        let (inbox_tx, inbox_rx) = mpsc::channel(INBOX_CAPACITY);
+        Self {
+            self_addr,
+            inbox_tx,
+            inbox_rx_opt: Arc::new(Mutex::new(Some(inbox_rx))),
+        }
+    }
+
+    /// Returns the gRPC service receiving the gossip messages sent to the node.
+    pub fn grpc_service(&self) -> ClusterApiServiceServer<GrpcGossipAdapter> {
+        let adapter = GrpcGossipAdapter {
+            inbox_tx: self.inbox_tx.clone(),
+        };
+        ClusterApiServiceServer::new(adapter)
+    }
+}
+
+#[async_trait]
+impl Transport for GrpcTransport {
+    async fn open(&self, _listen_addr: SocketAddr) -> anyhow::Result<Box<dyn Socket>> {
+        let inbox_rx = self
+            .inbox_rx_opt
+            .lock()
+            .await
+            .take()
+            .context("The gRPC gossip transport can only be opened once.")?;
+        let socket = GrpcSocket {
+            self_addr: self.self_addr,
+            clients: HashMap::new(),
+            inbox_rx,
+        };
+        Ok(Box::new(socket))
+    }
+}
+
+struct GrpcSocket {
+    self_addr: SocketAddr,
+    clients: HashMap<SocketAddr, GossipClient>,
+    inbox_rx: Inbox,
+}
+
+impl GrpcSocket {
+    fn client(&mut self, addr: SocketAddr) -> anyhow::Result<GossipClient> {
+        if let Some(client) = self.clients.get(&addr) {
+            return Ok(client.clone());
+        }
+        let channel = make_endpoint(addr)
+            .connect_timeout(Duration::from_secs(5))
+            .connect_lazy();
+        let client = ClusterApiServiceClient::with_interceptor(channel, SpanContextInterceptor);
+        self.clients.insert(addr, client.clone());
+        Ok(client)
+    }
+}
+
+#[async_trait]
+impl Socket for GrpcSocket {
+    async fn send(&mut self, to: SocketAddr, message: ChitchatMessage) -> anyhow::Result<()> {
+        let mut client = self.client(to)?;
+        let mut message_bytes = Vec::new();
+        message.serialize(&mut message_bytes);
+        let request = GossipRequest {
+            from_addr: self.self_addr.to_string(),
+            message: message_bytes,
+        };
+        // Like datagrams, the messages are sent without waiting for their delivery: the protocol
+        // tolerates lost messages and a slow peer must not stall the gossip loop.
+        tokio::spawn(async move {
+            match tokio::time::timeout(GOSSIP_REQUEST_TIMEOUT, client.gossip(request)).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(status)) => {
+                    debug!(to=%to, status=?status, "Failed to send gossip message.");
+                }
+                Err(_) => {
+                    debug!(to=%to, "Gossip message request timed out.");
+                }
+            }
+        });
+        Ok(())
+    }
+
+    async fn recv(&mut self) -> anyhow::Result<(SocketAddr, ChitchatMessage)> {
+        self.inbox_rx
+            .recv()
+            .await
+            .context("The gRPC gossip service was dropped.")
+    }
+}
+
+/// gRPC service queuing the received gossip messages for the membership protocol.
+#[derive(Clone)]
+pub struct GrpcGossipAdapter {
+    inbox_tx: mpsc::Sender<(SocketAddr, ChitchatMessage)>,
+}
+
+#[async_trait]
+impl ClusterApiService for GrpcGossipAdapter {
+    async fn gossip(
+        &self,
+        request: tonic::Request<GossipRequest>,
+    ) -> Result<tonic::Response<GossipResponse>, tonic::Status> {
+        let gossip_request = request.into_inner();
+        let from_addr: SocketAddr = gossip_request.from_addr.parse().map_err(|_| {
+            tonic::Status::invalid_argument(format!(
+                "Invalid gossip address `{}`.",
+                gossip_request.from_addr
+            ))
+        })?;
+        let message = ChitchatMessage::deserialize(&mut &gossip_request.message[..])
+            .map_err(|error| tonic::Status::invalid_argument(error.to_string()))?;
+        // A full inbox drops the message, as a full socket buffer drops datagrams.
+        if self.inbox_tx.try_send((from_addr, message)).is_err() {
+            debug!(from=%from_addr, "Dropped gossip message.");
+        }
+        Ok(tonic::Response::new(GossipResponse {}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_common::net::find_available_tcp_port;
+    use quickwit_common::tower::make_endpoint_with_tls;
+    use tonic::transport::{Certificate, ClientTlsConfig, Identity, Server, ServerTlsConfig};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_grpc_transport() {
+        let addr_1: SocketAddr = ([127, 0, 0, 1], find_available_tcp_port().unwrap()).into();
+        let addr_2: SocketAddr = ([127, 0, 0, 1], find_available_tcp_port().unwrap()).into();
+
+        let transport_1 = GrpcTransport::new(addr_1);
+        let transport_2 = GrpcTransport::new(addr_2);
+        let mut socket_1 = transport_1.open(addr_1).await.unwrap();
+        let mut socket_2 = transport_2.open(addr_2).await.unwrap();
+        assert!(transport_2.open(addr_2).await.is_err());
+
+        tokio::spawn(
+            Server::builder()
+                .add_service(transport_2.grpc_service())
+                .serve(addr_2),
+        );
+        // The first messages may be sent before the server is up.
+        let (from_addr, message) = loop {
+            socket_1
+                .send(addr_2, ChitchatMessage::BadCluster)
+                .await
+                .unwrap();
+            if let Ok(received) =
+                tokio::time::timeout(Duration::from_millis(100), socket_2.recv()).await
+            {
+                break received.unwrap();
+            }
+        };
+        assert_eq!(from_addr, addr_1);
+        assert!(matches!(message, ChitchatMessage::BadCluster));
+    }
+
+    #[tokio::test]
+    async fn test_grpc_gossip_over_tls() {
+        let addr: SocketAddr = ([127, 0, 0, 1], find_available_tcp_port().unwrap()).into();
+        let transport = GrpcTransport::new(addr);
+        let mut socket = transport.open(addr).await.unwrap();
+
+        let identity = Identity::from_pem(
+            include_str!("../resources/tests/tls/node.pem"),
+            include_str!("../resources/tests/tls/node-key.pem"),
+        );
+        let server_tls_config = ServerTlsConfig::new().identity(identity);
+        tokio::spawn(
+            Server::builder()
+                .tls_config(server_tls_config)
+                .unwrap()
+                .add_service(transport.grpc_service())
+                .serve(addr),
+        );
+        let ca_certificate = Certificate::from_pem(include_str!("../resources/tests/tls/ca.pem"));
+        let client_tls_config = ClientTlsConfig::new()
+            .ca_certificate(ca_certificate)
+            .domain_name("quickwit.cluster.local");
+        let channel = make_endpoint_with_tls(addr, Some(&client_tls_config)).connect_lazy();
+        let mut client = ClusterApiServiceClient::new(channel);
+
+        let mut message = Vec::new();
+        ChitchatMessage::BadCluster.serialize(&mut message);
+        let request = GossipRequest {
+            from_addr: addr.to_string(),
+            message,
+        };
+        // The first requests may be sent before the server is up.
+        let mut num_attempts = 0;
+        while client.gossip(request.clone()).await.is_err() {
+            num_attempts += 1;
+            assert!(num_attempts < 50, "Failed to gossip over TLS.");
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+        let (from_addr, message) = socket.recv().await.unwrap();
+        assert_eq!(from_addr, addr);
+        assert!(matches!(message, ChitchatMessage::BadCluster));
+
+        // Plaintext requests are rejected by the TLS server.
+        let channel = make_endpoint_with_tls(addr, None).connect_lazy();
+        ClusterApiServiceClient::new(channel)
+            .gossip(request)
+            .await
+            .unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_grpc_gossip_adapter_rejects_invalid_requests() {
+        let addr: SocketAddr = ([127, 0, 0, 1], 7281).into();
+        let adapter = GrpcGossipAdapter {
+            inbox_tx: GrpcTransport::new(addr).inbox_tx,
+        };
+        let mut message = Vec::new();
+        ChitchatMessage::BadCluster.serialize(&mut message);
+
+        let request = GossipRequest {
+            from_addr: "not-an-address".to_string(),
+            message,
+        };
+        let status = adapter
+            .gossip(tonic::Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let request = GossipRequest {
+            from_addr: addr.to_string(),
+            message: b"garbage".to_vec(),
+        };
+        let status = adapter
+            .gossip(tonic::Request::new(request))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+}
@@ -21,12 +21,13 @@
 
 mod change;
 mod cluster;
+mod grpc_transport;
 mod member;
 mod node;
 
 use std::collections::HashSet;
 
-use chitchat::transport::UdpTransport;
+use chitchat::transport::{Transport, UdpTransport};
 use chitchat::FailureDetectorConfig;
 use quickwit_config::service::QuickwitService;
 use quickwit_config::QuickwitConfig as NodeConfig;
@@ -36,7 +37,8 @@ pub use crate::change::ClusterChange;
 #[cfg(any(test, feature = "testsuite"))]
 pub use crate::cluster::{create_cluster_for_test, grpc_addr_from_listen_addr_for_test};
 pub use crate::cluster::{Cluster, ClusterSnapshot, NodeIdSchema};
-pub use crate::member::ClusterMember;
+pub use crate::grpc_transport::{GrpcGossipAdapter, GrpcTransport};
+pub use crate::member::{ClusterMember, DecommissionStatus, IndexerLoad, SearcherLoad};
 pub use crate::node::ClusterNode;
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
@@ -58,9 +60,12 @@ impl From<u64> for GenerationId {
     }
 }
 
+/// Joins the cluster. The gossip messages are exchanged over UDP unless a gRPC transport is
+/// provided.
 pub async fn start_cluster_service(
     node_config: &NodeConfig,
     enabled_services: &HashSet<QuickwitService>,
+    grpc_transport_opt: Option<&GrpcTransport>,
 ) -> anyhow::Result<Cluster> {
     let cluster_id = node_config.cluster_id.clone();
     let gossip_listen_addr = node_config.gossip_listen_addr;
@@ -70,7 +75,7 @@ pub async fn start_cluster_service(
     let node_id = node_config.node_id.clone();
     let generation_id = GenerationId::now();
     let is_ready = false;
-    let self_node = ClusterMember::new(
+    let mut self_node = ClusterMember::new(
         node_id,
         generation_id,
         is_ready,
@@ -79,13 +84,18 @@ pub async fn start_cluster_service(
         node_config.grpc_advertise_addr,
         indexing_tasks,
     );
+    self_node.availability_zone_opt = node_config.availability_zone_opt.clone();
+    let transport: &dyn Transport = match grpc_transport_opt {
+        Some(grpc_transport) => grpc_transport,
+        None => &UdpTransport,
+    };
     let cluster = Cluster::join(
         cluster_id,
         self_node,
         gossip_listen_addr,
         peer_seed_addrs,
         FailureDetectorConfig::default(),
-        &UdpTransport,
+        transport,
     )
     .await?;
     Ok(cluster)
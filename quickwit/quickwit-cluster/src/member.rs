@@ -19,11 +19,13 @@
 
 use std::collections::HashSet;
 use std::net::SocketAddr;
+use std::str::FromStr;
 
 use anyhow::{anyhow, Context};
 use chitchat::{ChitchatId, NodeState};
 use itertools::Itertools;
 use quickwit_proto::indexing_api::IndexingTask;
+use serde::{Deserialize, Serialize};
 use tracing::warn;
 
 use crate::{GenerationId, QuickwitService};
@@ -31,10 +33,22 @@ use crate::{GenerationId, QuickwitService};
 // Keys used to store member's data in chitchat state.
 pub(crate) const GRPC_ADVERTISE_ADDR_KEY: &str = "grpc_advertise_addr";
 pub(crate) const ENABLED_SERVICES_KEY: &str = "enabled_services";
+pub(crate) const AVAILABILITY_ZONE_KEY: &str = "availability_zone";
 // An indexing task key is formatted as
 // `{INDEXING_TASK_PREFIX}{INDEXING_TASK_SEPARATOR}{index_id}{INDEXING_TASK_SEPARATOR}{source_id}`.
 pub(crate) const INDEXING_TASK_PREFIX: &str = "indexing_task";
 pub(crate) const INDEXING_TASK_SEPARATOR: char = ':';
+// Key used to store the searcher load serialized in JSON.
+pub(crate) const SEARCHER_LOAD_KEY: &str = "searcher_load";
+// Key used to store the indexer load serialized in JSON.
+pub(crate) const INDEXER_LOAD_KEY: &str = "indexer_load";
+
+// Key used to store the decommission status of the node.
+pub(crate) const DECOMMISSION_STATUS_KEY: &str = "decommission_status";
+// A decommission request key is formatted as
+// `{DECOMMISSION_REQUEST_PREFIX}:{node_id}:{generation_id}`. Any node can request the
+// decommission of another node by setting this key in its own state.
+pub(crate) const DECOMMISSION_REQUEST_PREFIX: &str = "decommission_request";
 
 // Readiness key and values used to store node's readiness in Chitchat state.
 pub(crate) const READINESS_KEY: &str = "readiness";
@@ -45,6 +59,12 @@ pub(crate) trait NodeStateExt {
     fn grpc_advertise_addr(&self) -> anyhow::Result<SocketAddr>;
 
     fn is_ready(&self) -> bool;
+
+    fn searcher_load_opt(&self) -> Option<SearcherLoad>;
+
+    fn indexer_load_opt(&self) -> Option<IndexerLoad>;
+
+    fn decommission_status(&self) -> DecommissionStatus;
 }
 
 impl NodeStateExt for NodeState {
@@ -65,6 +85,110 @@ impl NodeStateExt for NodeState {
             .map(|health_value| health_value == READINESS_VALUE_READY)
             .unwrap_or(false)
     }
+
+    fn searcher_load_opt(&self) -> Option<SearcherLoad> {
+        let searcher_load_value = self.get(SEARCHER_LOAD_KEY)?;
+        match serde_json::from_str(searcher_load_value) {
+            Ok(searcher_load) => Some(searcher_load),
+            Err(error) => {
+                warn!(error=%error, "Malformed searcher load value.");
+                None
+            }
+        }
+    }
+
+    fn indexer_load_opt(&self) -> Option<IndexerLoad> {
+        let indexer_load_value = self.get(INDEXER_LOAD_KEY)?;
+        match serde_json::from_str(indexer_load_value) {
+            Ok(indexer_load) => Some(indexer_load),
+            Err(error) => {
+                warn!(error=%error, "Malformed indexer load value.");
+                None
+            }
+        }
+    }
+
+    fn decommission_status(&self) -> DecommissionStatus {
+        match self
+            .get(DECOMMISSION_STATUS_KEY)
+            .map(|decommission_status_value| decommission_status_value.parse())
+        {
+            Some(Ok(decommission_status)) => decommission_status,
+            Some(Err(error)) => {
+                warn!(error=%error, "Malformed decommission status value.");
+                DecommissionStatus::Active
+            }
+            None => DecommissionStatus::Active,
+        }
+    }
+}
+
+/// Decommission status of a node.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DecommissionStatus {
+    /// The node runs normally.
+    #[default]
+    Active,
+    /// The node no longer accepts new work and waits for its ongoing work to complete.
+    Decommissioning,
+    /// The node has completed its ongoing work and can be terminated safely.
+    Decommissioned,
+}
+
+impl DecommissionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DecommissionStatus::Active => "active",
+            DecommissionStatus::Decommissioning => "decommissioning",
+            DecommissionStatus::Decommissioned => "decommissioned",
+        }
+    }
+}
+
+impl FromStr for DecommissionStatus {
+    type Err = anyhow::Error;
+
+    fn from_str(decommission_status_str: &str) -> Result<Self, Self::Err> {
+        match decommission_status_str {
+            "active" => Ok(DecommissionStatus::Active),
+            "decommissioning" => Ok(DecommissionStatus::Decommissioning),
+            "decommissioned" => Ok(DecommissionStatus::Decommissioned),
+            _ => Err(anyhow!(
+                "Unknown decommission status `{decommission_status_str}`."
+            )),
+        }
+    }
+}
+
+/// Live load of a searcher, advertised to the other nodes via gossip so that the root searchers
+/// can take it into account when placing leaf search jobs.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SearcherLoad {
+    /// Number of leaf search requests being executed by the searcher.
+    pub num_inflight_leaf_searches: u64,
+    /// Moving average of the leaf search latency in milliseconds.
+    pub leaf_search_latency_millis: u64,
+    /// IDs of the splits searched recently by the searcher. Their footers and fast fields are
+    /// likely to be in its caches.
+    pub cached_split_ids: Vec<String>,
+}
+
+/// Load of the indexing pipelines of an indexer, advertised to the other nodes via gossip so that
+/// the control plane can derive autoscaling signals.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct IndexerLoad {
+    /// Number of indexing pipelines running on the indexer.
+    pub num_pipelines: u64,
+    /// Number of pipelines whose source spends most of its time waiting for the downstream actors
+    /// of the pipeline.
+    pub num_saturated_pipelines: u64,
+    /// Number of pipelines whose source hardly ever waits for the downstream actors of the
+    /// pipeline.
+    pub num_idle_pipelines: u64,
+    /// Number of records available in the sources of the pipelines and not indexed yet. Only the
+    /// sources able to measure their lag contribute.
+    pub source_lag: u64,
 }
 
 /// Cluster member.
@@ -85,11 +209,20 @@ pub struct ClusterMember {
     /// gRPC advertise address, i.e. the address that other nodes should use to communicate with
     /// the node via gRPC.
     pub grpc_advertise_addr: SocketAddr,
+    /// Availability zone (or rack) the node runs in, if configured.
+    pub availability_zone_opt: Option<String>,
     /// Running indexing plan.
     /// None if the node is not an indexer or the indexer has not yet started some indexing
     /// pipelines.
     pub indexing_tasks: Vec<IndexingTask>,
     pub is_ready: bool,
+    /// Live load of the searcher. None if the node is not a searcher or has not advertised its
+    /// load yet.
+    pub searcher_load_opt: Option<SearcherLoad>,
+    /// Load of the indexing pipelines. None if the node is not an indexer or has not advertised
+    /// its load yet.
+    pub indexer_load_opt: Option<IndexerLoad>,
+    pub decommission_status: DecommissionStatus,
 }
 
 impl ClusterMember {
@@ -109,7 +242,11 @@ impl ClusterMember {
             enabled_services,
             gossip_advertise_addr,
             grpc_advertise_addr,
+            availability_zone_opt: None,
             indexing_tasks,
+            searcher_load_opt: None,
+            indexer_load_opt: None,
+            decommission_status: DecommissionStatus::Active,
         }
     }
 
@@ -148,7 +285,7 @@ pub(crate) fn build_cluster_member(
         })?;
     let grpc_advertise_addr = node_state.grpc_advertise_addr()?;
     let indexing_tasks = parse_indexing_tasks(node_state, &chitchat_id.node_id);
-    let member = ClusterMember::new(
+    let mut member = ClusterMember::new(
         chitchat_id.node_id,
         chitchat_id.generation_id.into(),
         is_ready,
@@ -157,6 +294,10 @@ pub(crate) fn build_cluster_member(
         grpc_advertise_addr,
         indexing_tasks,
     );
+    member.availability_zone_opt = node_state.get(AVAILABILITY_ZONE_KEY).map(str::to_string);
+    member.searcher_load_opt = node_state.searcher_load_opt();
+    member.indexer_load_opt = node_state.indexer_load_opt();
+    member.decommission_status = node_state.decommission_status();
     Ok(member)
 }
 
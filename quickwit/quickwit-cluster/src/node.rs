@@ -28,6 +28,7 @@ use quickwit_proto::indexing_api::IndexingTask;
 use tonic::transport::Channel;
 
 use crate::member::build_cluster_member;
+use crate::DecommissionStatus;
 
 #[derive(Clone)]
 pub struct ClusterNode {
@@ -48,8 +49,10 @@ impl ClusterNode {
             channel,
             enabled_services: member.enabled_services,
             grpc_advertise_addr: member.grpc_advertise_addr,
+            availability_zone_opt: member.availability_zone_opt,
             indexing_tasks: member.indexing_tasks,
             is_ready: member.is_ready,
+            decommission_status: member.decommission_status,
             is_self_node,
         };
         let node = ClusterNode {
@@ -78,6 +81,10 @@ impl ClusterNode {
         self.inner.grpc_advertise_addr
     }
 
+    pub fn availability_zone_opt(&self) -> Option<&str> {
+        self.inner.availability_zone_opt.as_deref()
+    }
+
     pub fn indexing_tasks(&self) -> &[IndexingTask] {
         &self.inner.indexing_tasks
     }
@@ -86,6 +93,10 @@ impl ClusterNode {
         self.inner.is_ready
     }
 
+    pub fn decommission_status(&self) -> DecommissionStatus {
+        self.inner.decommission_status
+    }
+
     pub fn is_self_node(&self) -> bool {
         self.inner.is_self_node
     }
@@ -107,8 +118,10 @@ impl PartialEq for ClusterNode {
         self.inner.chitchat_id == other.inner.chitchat_id
             && self.inner.enabled_services == other.inner.enabled_services
             && self.inner.grpc_advertise_addr == other.inner.grpc_advertise_addr
+            && self.inner.availability_zone_opt == other.inner.availability_zone_opt
             && self.inner.indexing_tasks == other.inner.indexing_tasks
             && self.inner.is_ready == other.inner.is_ready
+            && self.inner.decommission_status == other.inner.decommission_status
             && self.inner.is_self_node == other.inner.is_self_node
     }
 }
@@ -118,7 +131,9 @@ struct InnerNode {
     channel: Channel,
     enabled_services: HashSet<QuickwitService>,
     grpc_advertise_addr: SocketAddr,
+    availability_zone_opt: Option<String>,
     indexing_tasks: Vec<IndexingTask>,
     is_ready: bool,
+    decommission_status: DecommissionStatus,
     is_self_node: bool,
 }
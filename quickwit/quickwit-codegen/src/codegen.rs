@@ -17,6 +17,10 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::env;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
 use heck::ToSnakeCase;
 use proc_macro2::TokenStream;
 use prost_build::{Method, Service, ServiceGenerator};
@@ -54,8 +58,18 @@ impl Codegen {
             .service_generator(service_generator)
             .out_dir(out_dir);
 
+        // The file descriptor sets are served by the gRPC reflection service.
+        let descriptor_dir_path = PathBuf::from(env::var("OUT_DIR")?);
+
         for proto in protos {
             println!("cargo:rerun-if-changed={proto}");
+            let proto_name = Path::new(proto)
+                .file_stem()
+                .and_then(|file_stem| file_stem.to_str())
+                .context("Proto file name must be valid UTF-8.")?;
+            prost_config.file_descriptor_set_path(
+                descriptor_dir_path.join(format!("{proto_name}_descriptor.bin")),
+            );
             prost_config.compile_protos(&[proto], includes)?;
         }
         Ok(())
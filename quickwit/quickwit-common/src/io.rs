@@ -141,6 +141,12 @@ impl IoControls {
         self
     }
 
+    /// Changes the throughput limit of these controls and of all their clones, which share the
+    /// same limiter.
+    pub fn update_throughput_limit(&self, throughput: f64) {
+        self.throughput_limiter.set_speed_limit(throughput);
+    }
+
     pub fn set_bytes_counter(mut self, bytes_counter: IntCounter) -> Self {
         self.bytes_counter = bytes_counter;
         self
@@ -352,6 +358,24 @@ mod tests {
         assert_eq!(io_controls.num_bytes(), 200_000u64);
     }
 
+    #[tokio::test]
+    async fn test_controlled_writer_update_throughput_limit() {
+        let io_controls = IoControls::default().set_throughput_limit(2_000_000f64);
+        // The limit is shared by the clones of the controls.
+        io_controls.clone().update_throughput_limit(20_000_000f64);
+        let mut controlled_write = io_controls.clone().wrap_write(sink());
+        let buf = vec![44u8; 1_000];
+        let start = Instant::now();
+        // We write 1MB, which would take 500ms with the initial limit.
+        for _ in 0..1_000 {
+            controlled_write.write_all(&buf).await.unwrap();
+        }
+        controlled_write.flush().await.unwrap();
+        let elapsed = start.elapsed();
+        assert!(elapsed <= Duration::from_millis(250));
+        assert_eq!(io_controls.num_bytes(), 1_000_000u64);
+    }
+
     #[tokio::test]
     async fn test_controlled_writer_no_limit_async() {
         let io_controls = IoControls::default();
@@ -134,7 +134,25 @@ pub fn new_histogram_vec<const N: usize>(
     namespace: &str,
     label_names: [&str; N],
 ) -> HistogramVec<N> {
-    let histogram_opts = HistogramOpts::new(name, description).namespace(namespace);
+    new_histogram_vec_with_buckets(
+        name,
+        description,
+        namespace,
+        label_names,
+        prometheus::DEFAULT_BUCKETS.to_vec(),
+    )
+}
+
+pub fn new_histogram_vec_with_buckets<const N: usize>(
+    name: &str,
+    description: &str,
+    namespace: &str,
+    label_names: [&str; N],
+    buckets: Vec<f64>,
+) -> HistogramVec<N> {
+    let histogram_opts = HistogramOpts::new(name, description)
+        .namespace(namespace)
+        .buckets(buckets);
     let underlying = PrometheusHistogramVec::new(histogram_opts, &label_names)
         .expect("Failed to create histogram vec");
     prometheus::register(Box::new(underlying.clone())).expect("Failed to register histogram vec");
@@ -0,0 +1,98 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+use anyhow::{bail, Context};
+use http::header::{HeaderMap, HeaderValue, AUTHORIZATION};
+use once_cell::sync::OnceCell;
+use tonic::metadata::{AsciiMetadataValue, MetadataMap};
+
+static CLUSTER_AUTHORIZATION: OnceCell<HeaderValue> = OnceCell::new();
+
+/// Sets the cluster key the node presents as a bearer token to the gRPC services of the other
+/// nodes of the cluster. The key cannot be changed once set.
+pub fn set_cluster_key(cluster_key: &str) -> anyhow::Result<()> {
+    let mut authorization = HeaderValue::from_str(&format!("Bearer {cluster_key}"))
+        .context("Cluster key must only contain visible ASCII characters.")?;
+    authorization.set_sensitive(true);
+    let current_authorization = CLUSTER_AUTHORIZATION.get_or_init(|| authorization.clone());
+
+    if *current_authorization != authorization {
+        bail!("Cluster key is already set to a different value.");
+    }
+    Ok(())
+}
+
+/// Adds the cluster key to the headers of a request sent to another node of the cluster, unless
+/// the request already carries credentials.
+pub fn inject_cluster_key(headers: &mut HeaderMap) {
+    if let Some(authorization) = CLUSTER_AUTHORIZATION.get() {
+        headers
+            .entry(AUTHORIZATION)
+            .or_insert_with(|| authorization.clone());
+    }
+}
+
+/// Same as [`inject_cluster_key`] for the metadata of a `tonic` request.
+pub fn inject_cluster_key_metadata(metadata: &mut MetadataMap) {
+    let Some(authorization) = CLUSTER_AUTHORIZATION.get() else {
+        return;
+    };
+    if metadata.contains_key(AUTHORIZATION.as_str()) {
+        return;
+    }
+    if let Some(authorization) = authorization
+        .to_str()
+        .ok()
+        .and_then(|authorization| authorization.parse::<AsciiMetadataValue>().ok())
+    {
+        metadata.insert(AUTHORIZATION.as_str(), authorization);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_key() {
+        let mut headers = HeaderMap::new();
+        inject_cluster_key(&mut headers);
+        assert!(headers.is_empty());
+
+        set_cluster_key("cluster-key-0123456789").unwrap();
+        set_cluster_key("cluster-key-0123456789").unwrap();
+        set_cluster_key("other-cluster-key-0123456789").unwrap_err();
+
+        inject_cluster_key(&mut headers);
+        assert_eq!(
+            headers.get(AUTHORIZATION).unwrap(),
+            "Bearer cluster-key-0123456789"
+        );
+        let mut headers = HeaderMap::new();
+        headers.insert(AUTHORIZATION, HeaderValue::from_static("Bearer api-key"));
+        inject_cluster_key(&mut headers);
+        assert_eq!(headers.get(AUTHORIZATION).unwrap(), "Bearer api-key");
+
+        let mut metadata = MetadataMap::new();
+        inject_cluster_key_metadata(&mut metadata);
+        assert_eq!(
+            metadata.get("authorization").unwrap(),
+            "Bearer cluster-key-0123456789"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::net::SocketAddr;
+
+use anyhow::{anyhow, Context};
+use once_cell::sync::OnceCell;
+use tonic::transport::{ClientTlsConfig, Endpoint, Uri};
+
+static CLIENT_TLS_CONFIG: OnceCell<ClientTlsConfig> = OnceCell::new();
+
+/// Sets the TLS settings the node uses to connect to the gRPC services of the other nodes of the
+/// cluster. The settings cannot be changed once set.
+pub fn set_client_tls_config(tls_config: ClientTlsConfig) -> anyhow::Result<()> {
+    // Fails early on invalid settings rather than when the first channel is created.
+    Endpoint::from_static("https://127.0.0.1")
+        .tls_config(tls_config.clone())
+        .context("Failed to load the gRPC client TLS settings.")?;
+    CLIENT_TLS_CONFIG
+        .set(tls_config)
+        .map_err(|_| anyhow!("gRPC client TLS settings are already set."))
+}
+
+/// Creates an endpoint for the gRPC server of the node listening on `socket_addr`. The endpoint
+/// connects with TLS once the client TLS settings are set.
+pub fn make_endpoint(socket_addr: SocketAddr) -> Endpoint {
+    make_endpoint_with_tls(socket_addr, CLIENT_TLS_CONFIG.get())
+}
+
+/// Same as [`make_endpoint`] with explicit TLS settings.
+pub fn make_endpoint_with_tls(
+    socket_addr: SocketAddr,
+    tls_config_opt: Option<&ClientTlsConfig>,
+) -> Endpoint {
+    let scheme = if tls_config_opt.is_some() {
+        "https"
+    } else {
+        "http"
+    };
+    let uri = Uri::builder()
+        .scheme(scheme)
+        .authority(socket_addr.to_string())
+        .path_and_query("/")
+        .build()
+        .expect("The provided arguments should be valid.");
+    let endpoint = Endpoint::from(uri);
+
+    match tls_config_opt {
+        Some(tls_config) => endpoint
+            .tls_config(tls_config.clone())
+            .expect("The TLS settings should have been validated."),
+        None => endpoint,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_make_endpoint_with_tls() {
+        let socket_addr: SocketAddr = ([127, 0, 0, 1], 7281).into();
+
+        let endpoint = make_endpoint_with_tls(socket_addr, None);
+        assert_eq!(endpoint.uri(), "http://127.0.0.1:7281/");
+
+        let tls_config = ClientTlsConfig::new().domain_name("quickwit.cluster.local");
+        let endpoint = make_endpoint_with_tls(socket_addr, Some(&tls_config));
+        assert_eq!(endpoint.uri(), "https://127.0.0.1:7281/");
+    }
+
+    #[test]
+    fn test_set_client_tls_config_rejects_invalid_settings() {
+        let tls_config = ClientTlsConfig::new().domain_name("not a server name");
+        set_client_tls_config(tls_config).unwrap_err();
+    }
+}
@@ -21,7 +21,9 @@ mod box_layer;
 mod box_service;
 mod buffer;
 mod change;
+mod cluster_key;
 mod estimate_rate;
+mod grpc_tls;
 mod metrics;
 mod pool;
 mod rate;
@@ -36,8 +38,10 @@ pub use box_layer::BoxLayer;
 pub use box_service::BoxService;
 pub use buffer::{Buffer, BufferError, BufferLayer};
 pub use change::Change;
+pub use cluster_key::{inject_cluster_key, inject_cluster_key_metadata, set_cluster_key};
 pub use estimate_rate::{EstimateRate, EstimateRateLayer};
 use futures::{Future, Stream};
+pub use grpc_tls::{make_endpoint, make_endpoint_with_tls, set_client_tls_config};
 pub use metrics::{PrometheusMetrics, PrometheusMetricsLayer};
 pub use pool::Pool;
 pub use rate::{ConstantRate, Rate};
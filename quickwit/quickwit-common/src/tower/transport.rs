@@ -28,17 +28,16 @@ use std::time::Duration;
 
 use futures::stream::once;
 use futures::{Stream, StreamExt};
-use http::Uri;
 use tokio::sync::{mpsc, watch};
 use tokio_stream::wrappers::UnboundedReceiverStream;
-use tonic::transport::{Channel, Endpoint};
+use tonic::transport::Channel;
 use tower::balance::p2c::Balance;
 use tower::buffer::Buffer;
 use tower::discover::Change as TowerChange;
 use tower::load::{CompleteOnResponse, PendingRequestsDiscover};
 use tower::{BoxError, Service, ServiceExt};
 
-use super::{BoxFuture, BoxStream, Change};
+use super::{inject_cluster_key, make_endpoint, BoxFuture, BoxStream, Change};
 
 // Transforms a boxed stream of `Change<K, Channel>` into a stream of `Result<TowerChange<K,
 // Channel>, Infallible>>` while keeping track of the number of connections.
@@ -166,7 +165,8 @@ where K: Hash + Eq + Clone
         self.inner.poll_ready(cx)
     }
 
-    fn call(&mut self, request: HttpRequest) -> Self::Future {
+    fn call(&mut self, mut request: HttpRequest) -> Self::Future {
+        inject_cluster_key(request.headers_mut());
         Box::pin(self.inner.call(request))
     }
 }
@@ -185,13 +185,7 @@ where K: Hash + Eq + Clone + Send + 'static
 ///
 /// The function is marked as `async` because it requires an executor (`connect_lazy`).
 pub async fn make_channel(socket_addr: SocketAddr) -> Channel {
-    let uri = Uri::builder()
-        .scheme("http")
-        .authority(socket_addr.to_string())
-        .path_and_query("/")
-        .build()
-        .expect("The provided arguments should be valid.");
-    Endpoint::from(uri)
+    make_endpoint(socket_addr)
         .connect_timeout(Duration::from_secs(5))
         .timeout(Duration::from_secs(30))
         .connect_lazy()
@@ -0,0 +1,128 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use anyhow::bail;
+use byte_unit::Byte;
+use serde::{Deserialize, Serialize};
+
+use crate::{RateLimitConfig, RetentionPolicy};
+
+/// Cluster-scoped settings stored in the metastore and distributed to all the nodes of the
+/// cluster, so that they do not have to be repeated in every node config. Unset settings take
+/// their value from the node and index configs.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct ClusterSettings {
+    /// Retention policy of the indexes that do not define their own.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_retention_policy: Option<RetentionPolicy>,
+    /// Rate limits of the REST API and of the OTLP and Jaeger gRPC services, replacing the
+    /// `rate_limits` section of the node configs.
+    #[schema(value_type = Object)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limits: Option<RateLimitConfig>,
+    /// Merge write throughput limit of the merge pipelines of all the indexers, overriding the one
+    /// of the index configs.
+    #[schema(value_type = String)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_merge_write_throughput: Option<Byte>,
+}
+
+impl ClusterSettings {
+    pub fn validate(&self) -> anyhow::Result<()> {
+        if let Some(default_retention_policy) = &self.default_retention_policy {
+            if default_retention_policy.expiration_field().is_some() {
+                bail!(
+                    "The default retention policy cannot define an `expiration_field`: the field \
+                     may not exist in all the indexes."
+                );
+            }
+            default_retention_policy.validate()?;
+        }
+        if let Some(rate_limits) = &self.rate_limits {
+            rate_limits.validate()?;
+        }
+        if let Some(max_merge_write_throughput) = self.max_merge_write_throughput {
+            if max_merge_write_throughput.get_bytes() == 0 {
+                bail!("The `max_merge_write_throughput` setting must be strictly positive.");
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cluster_settings_serde() {
+        let cluster_settings: ClusterSettings = serde_json::from_str("{}").unwrap();
+        assert_eq!(cluster_settings, ClusterSettings::default());
+        assert_eq!(serde_json::to_string(&cluster_settings).unwrap(), "{}");
+
+        let cluster_settings_json = r#"{
+            "default_retention_policy": {"period": "30 days"},
+            "rate_limits": {"principals": {"*": {"requests_per_sec": 100}}},
+            "max_merge_write_throughput": "50MB"
+        }"#;
+        let cluster_settings: ClusterSettings =
+            serde_json::from_str(cluster_settings_json).unwrap();
+        cluster_settings.validate().unwrap();
+        assert_eq!(
+            cluster_settings
+                .default_retention_policy
+                .as_ref()
+                .unwrap()
+                .retention_period()
+                .unwrap(),
+            Some(std::time::Duration::from_secs(30 * 24 * 3600))
+        );
+        assert!(cluster_settings.rate_limits.as_ref().unwrap().is_enabled());
+        assert_eq!(
+            cluster_settings.max_merge_write_throughput.unwrap(),
+            Byte::from_bytes(50_000_000)
+        );
+        serde_json::from_str::<ClusterSettings>(r#"{"unknown_setting": 1}"#).unwrap_err();
+    }
+
+    #[test]
+    fn test_cluster_settings_validate() {
+        let cluster_settings: ClusterSettings = serde_json::from_str(
+            r#"{"default_retention_policy": {"period": "30 days", "expiration_field": "ts"}}"#,
+        )
+        .unwrap();
+        cluster_settings.validate().unwrap_err();
+
+        let cluster_settings: ClusterSettings =
+            serde_json::from_str(r#"{"default_retention_policy": {"period": "forever"}}"#).unwrap();
+        cluster_settings.validate().unwrap_err();
+
+        let cluster_settings: ClusterSettings =
+            serde_json::from_str(r#"{"rate_limits": {"indexes": {"logs/*": {}}}}"#).unwrap();
+        cluster_settings.validate().unwrap_err();
+
+        let cluster_settings: ClusterSettings =
+            serde_json::from_str(r#"{"max_merge_write_throughput": "0"}"#).unwrap();
+        cluster_settings.validate().unwrap_err();
+    }
+}
@@ -20,7 +20,7 @@
 pub(crate) mod serialize;
 
 use std::collections::BTreeSet;
-use std::num::NonZeroU32;
+use std::num::{NonZeroU32, NonZeroU64};
 use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
@@ -30,17 +30,18 @@ use byte_unit::Byte;
 use chrono::Utc;
 use cron::Schedule;
 use humantime::parse_duration;
+use quickwit_common::is_false;
 use quickwit_common::uri::Uri;
 use quickwit_doc_mapper::{
-    DefaultDocMapper, DefaultDocMapperBuilder, DocMapper, FieldMappingEntry, ModeType,
-    QuickwitJsonOptions,
+    CatchAllFieldOptions, DefaultDocMapper, DefaultDocMapperBuilder, DocMapper, FieldMappingEntry,
+    ModeType, QuickwitJsonOptions,
 };
 use serde::{Deserialize, Serialize};
 pub use serialize::load_index_config_from_user_config;
 
 use crate::index_config::serialize::VersionedIndexConfig;
 use crate::merge_policy_config::{MergePolicyConfig, StableLogMergePolicyConfig};
-use crate::TestableForRegression;
+use crate::{validate_identifier, TestableForRegression};
 
 // Note(fmassot): `DocMapping` is a struct only used for
 // serialization/deserialization of `DocMapper` parameters.
@@ -62,6 +63,18 @@ pub struct DocMapping {
     #[schema(value_type = Vec<String>)]
     #[serde(default)]
     pub tag_fields: BTreeSet<String>,
+    /// Keyword fields whose values are recorded in a bloom filter per split, so that searches
+    /// for a given value skip the splits that cannot contain it.
+    #[schema(value_type = Vec<String>)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub bloom_filter_fields: BTreeSet<String>,
+    /// Numeric fast fields whose minimum and maximum values are recorded per split, so that range
+    /// queries on these fields skip the splits whose values are all out of the range.
+    #[schema(value_type = Vec<String>)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeSet::is_empty")]
+    pub min_max_fields: BTreeSet<String>,
     #[serde(default)]
     pub store_source: bool,
     #[serde(default)]
@@ -76,6 +89,11 @@ pub struct DocMapping {
     #[schema(value_type = u32)]
     #[serde(default = "DefaultDocMapper::default_max_num_partitions")]
     pub max_num_partitions: NonZeroU32,
+    /// Text field concatenating the values of text fields at indexing time, searched by default
+    /// when no default search fields are set.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub catch_all_field: Option<CatchAllFieldOptions>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, utoipa::ToSchema)]
@@ -146,6 +164,12 @@ pub struct IndexingSettings {
     pub merge_policy: MergePolicyConfig,
     #[serde(default)]
     pub resources: IndexingResources,
+    /// Routing expression evaluated on the documents ingested through the ingest API. When set,
+    /// documents are spread across the indexers ingesting the index according to the hash of
+    /// the expression, so that documents sharing the same value end up in the same splits.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub partition_by: Option<String>,
 }
 
 impl IndexingSettings {
@@ -187,6 +211,7 @@ impl Default for IndexingSettings {
             split_num_docs_target: Self::default_split_num_docs_target(),
             merge_policy: MergePolicyConfig::default(),
             resources: IndexingResources::default(),
+            partition_by: None,
         }
     }
 }
@@ -198,13 +223,62 @@ pub struct SearchSettings {
     pub default_search_fields: Vec<String>,
 }
 
+/// Garbage collection settings of an index. Unset settings fall back to the defaults of the
+/// janitor node configuration.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct GarbageCollectionSettings {
+    /// Interval between two garbage collection runs on the index.
+    #[schema(value_type = Option<u64>)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interval_secs: Option<NonZeroU64>,
+    /// Period after which a split that is still staged, e.g. because of an indexing failure, is
+    /// garbage collected.
+    #[schema(value_type = Option<u64>)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub staged_split_grace_period_secs: Option<NonZeroU64>,
+}
+
+impl GarbageCollectionSettings {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn interval(&self, default_interval: Duration) -> Duration {
+        self.interval_secs
+            .map(|interval_secs| Duration::from_secs(interval_secs.get()))
+            .unwrap_or(default_interval)
+    }
+
+    pub fn staged_split_grace_period(&self, default_grace_period: Duration) -> Duration {
+        self.staged_split_grace_period_secs
+            .map(|grace_period_secs| Duration::from_secs(grace_period_secs.get()))
+            .unwrap_or(default_grace_period)
+    }
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct RetentionPolicy {
     /// Duration of time for which the splits should be retained, expressed in a human-friendly way
     /// (`1 hour`, `3 days`, `a week`, ...).
     #[serde(rename = "period")]
-    retention_period: String,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    retention_period: Option<String>,
+
+    /// Maximum total size of the published splits of the index. When the index grows larger, its
+    /// oldest splits are marked for deletion until it fits within the limit again.
+    #[schema(value_type = String)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_size: Option<Byte>,
+
+    /// Fast datetime field holding the expiration date of the documents. When set, the documents
+    /// whose expiration date is in the past are deleted with a delete task.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expiration_field: Option<String>,
 
     /// Defines the frequency at which the retention policy is evaluated and applied, expressed in
     /// a human-friendly way (`hourly`, `daily`, ...) or as a cron expression (`0 0 * * * *`,
@@ -212,35 +286,182 @@ pub struct RetentionPolicy {
     #[serde(default = "RetentionPolicy::default_schedule")]
     #[serde(rename = "schedule")]
     evaluation_schedule: String,
+
+    /// When set, the janitor only reports the splits the policy would mark for deletion, without
+    /// actually marking them.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    dry_run: bool,
 }
 
 impl RetentionPolicy {
-    pub fn new(retention_period: String, evaluation_schedule: String) -> Self {
+    pub fn new(
+        retention_period_opt: Option<String>,
+        max_size_opt: Option<Byte>,
+        evaluation_schedule: String,
+    ) -> Self {
         Self {
-            retention_period,
+            retention_period: retention_period_opt,
+            max_size: max_size_opt,
+            expiration_field: None,
             evaluation_schedule,
+            dry_run: false,
         }
     }
 
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    pub fn with_expiration_field(mut self, expiration_field: String) -> Self {
+        self.expiration_field = Some(expiration_field);
+        self
+    }
+
     fn default_schedule() -> String {
         "hourly".to_string()
     }
 
-    pub fn retention_period(&self) -> anyhow::Result<Duration> {
-        parse_duration(&self.retention_period).with_context(|| {
+    pub fn retention_period(&self) -> anyhow::Result<Option<Duration>> {
+        self.retention_period
+            .as_ref()
+            .map(|retention_period| {
+                parse_duration(retention_period).with_context(|| {
+                    format!("Failed to parse retention period `{retention_period}`.")
+                })
+            })
+            .transpose()
+    }
+
+    pub fn max_size(&self) -> Option<Byte> {
+        self.max_size
+    }
+
+    pub fn expiration_field(&self) -> Option<&str> {
+        self.expiration_field.as_deref()
+    }
+
+    pub fn dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    pub fn evaluation_schedule(&self) -> anyhow::Result<Schedule> {
+        let evaluation_schedule = prepend_at_char(&self.evaluation_schedule);
+
+        Schedule::from_str(&evaluation_schedule).with_context(|| {
             format!(
-                "Failed to parse retention period `{}`.",
-                self.retention_period
+                "Failed to parse retention evaluation schedule `{}`.",
+                self.evaluation_schedule
             )
         })
     }
 
+    pub fn duration_until_next_evaluation(&self) -> anyhow::Result<Duration> {
+        let schedule = self.evaluation_schedule()?;
+        let future_date = schedule
+            .upcoming(Utc)
+            .next()
+            .expect("Failed to obtain next evaluation date.");
+        let duration = (future_date - Utc::now())
+            .to_std()
+            .map_err(|err| anyhow::anyhow!(err.to_string()))?;
+        Ok(duration)
+    }
+
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        if self.retention_period.is_none()
+            && self.max_size.is_none()
+            && self.expiration_field.is_none()
+        {
+            anyhow::bail!(
+                "The retention policy must define at least one of `period`, `max_size`, and \
+                 `expiration_field`."
+            );
+        }
+        if let Some(expiration_field) = &self.expiration_field {
+            if expiration_field.is_empty() {
+                anyhow::bail!("The retention policy `expiration_field` must not be empty.");
+            }
+        }
+        self.retention_period()?;
+        self.evaluation_schedule()?;
+        Ok(())
+    }
+}
+
+/// Defines how the old documents of an index are aggregated into a downsampled index: the
+/// documents are counted per time bucket and per group, and the counts are ingested into the
+/// target index before the raw data ages out.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RollupPolicy {
+    /// ID of the index receiving the rolled up documents.
+    pub target_index_id: String,
+
+    /// Age after which the documents are rolled up, expressed in a human-friendly way (`1 hour`,
+    /// `3 days`, `a week`, ...).
+    min_age: String,
+
+    /// Width of the time buckets, expressed in a human-friendly way (`1 minute`, `1 hour`, ...).
+    interval: String,
+
+    /// Fields whose values split the documents of a time bucket into groups.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub group_by: Vec<String>,
+
+    /// Defines the frequency at which the rollup is evaluated, expressed in a human-friendly way
+    /// (`hourly`, `daily`, ...) or as a cron expression (`0 0 * * * *`, `0 0 0 * * *`).
+    #[serde(default = "RollupPolicy::default_schedule")]
+    #[serde(rename = "schedule")]
+    evaluation_schedule: String,
+}
+
+impl RollupPolicy {
+    pub fn new(
+        target_index_id: String,
+        min_age: String,
+        interval: String,
+        group_by: Vec<String>,
+        evaluation_schedule: String,
+    ) -> Self {
+        Self {
+            target_index_id,
+            min_age,
+            interval,
+            group_by,
+            evaluation_schedule,
+        }
+    }
+
+    fn default_schedule() -> String {
+        "hourly".to_string()
+    }
+
+    pub fn min_age(&self) -> anyhow::Result<Duration> {
+        parse_duration(&self.min_age)
+            .with_context(|| format!("Failed to parse rollup min age `{}`.", self.min_age))
+    }
+
+    pub fn interval(&self) -> anyhow::Result<Duration> {
+        let interval = parse_duration(&self.interval)
+            .with_context(|| format!("Failed to parse rollup interval `{}`.", self.interval))?;
+        if interval.as_secs() == 0 || interval.subsec_nanos() != 0 {
+            anyhow::bail!(
+                "The rollup interval must be a whole number of seconds, got `{}`.",
+                self.interval
+            );
+        }
+        Ok(interval)
+    }
+
     pub fn evaluation_schedule(&self) -> anyhow::Result<Schedule> {
         let evaluation_schedule = prepend_at_char(&self.evaluation_schedule);
 
         Schedule::from_str(&evaluation_schedule).with_context(|| {
             format!(
-                "Failed to parse retention evaluation schedule `{}`.",
+                "Failed to parse rollup evaluation schedule `{}`.",
                 self.evaluation_schedule
             )
         })
@@ -258,8 +479,14 @@ impl RetentionPolicy {
         Ok(duration)
     }
 
-    fn validate(&self) -> anyhow::Result<()> {
-        self.retention_period()?;
+    fn validate(&self, index_id: &str) -> anyhow::Result<()> {
+        validate_identifier("Rollup target index ID", &self.target_index_id)?;
+
+        if self.target_index_id == index_id {
+            anyhow::bail!("The rollup target index must differ from the rolled up index.");
+        }
+        self.min_age()?;
+        self.interval()?;
         self.evaluation_schedule()?;
         Ok(())
     }
@@ -290,6 +517,8 @@ pub struct IndexConfig {
     pub indexing_settings: IndexingSettings,
     pub search_settings: SearchSettings,
     pub retention_policy: Option<RetentionPolicy>,
+    pub rollup_policy: Option<RollupPolicy>,
+    pub garbage_collection_settings: GarbageCollectionSettings,
 }
 
 impl IndexConfig {
@@ -374,6 +603,8 @@ impl IndexConfig {
             indexing_settings,
             search_settings,
             retention_policy: Default::default(),
+            rollup_policy: Default::default(),
+            garbage_collection_settings: Default::default(),
         }
     }
 }
@@ -430,9 +661,13 @@ impl TestableForRegression for IndexConfig {
             partition_key: Some("tenant".to_string()),
             max_num_partitions: NonZeroU32::new(100).unwrap(),
             timestamp_field: Some("timestamp".to_string()),
+            catch_all_field: None,
+            bloom_filter_fields: BTreeSet::new(),
+            min_max_fields: BTreeSet::new(),
         };
         let retention_policy = Some(RetentionPolicy::new(
-            "90 days".to_string(),
+            Some("90 days".to_string()),
+            None,
             "daily".to_string(),
         ));
         let stable_log_config = StableLogMergePolicyConfig {
@@ -461,7 +696,9 @@ impl TestableForRegression for IndexConfig {
             doc_mapping,
             indexing_settings,
             retention_policy,
+            rollup_policy: None,
             search_settings,
+            garbage_collection_settings: GarbageCollectionSettings::default(),
         }
     }
 
@@ -488,6 +725,10 @@ impl TestableForRegression for IndexConfig {
         );
         assert_eq!(self.indexing_settings, other.indexing_settings);
         assert_eq!(self.search_settings, other.search_settings);
+        assert_eq!(
+            self.garbage_collection_settings,
+            other.garbage_collection_settings
+        );
     }
 }
 
@@ -502,10 +743,13 @@ pub fn build_doc_mapper(
         timestamp_field: doc_mapping.timestamp_field.clone(),
         field_mappings: doc_mapping.field_mappings.clone(),
         tag_fields: doc_mapping.tag_fields.iter().cloned().collect(),
+        bloom_filter_fields: doc_mapping.bloom_filter_fields.iter().cloned().collect(),
+        min_max_fields: doc_mapping.min_max_fields.iter().cloned().collect(),
         mode: doc_mapping.mode,
         dynamic_mapping: doc_mapping.dynamic_mapping.clone(),
         partition_key: doc_mapping.partition_key.clone(),
         max_num_partitions: doc_mapping.max_num_partitions,
+        catch_all_field: doc_mapping.catch_all_field.clone(),
     };
     Ok(Arc::new(builder.try_build()?))
 }
@@ -557,8 +801,11 @@ mod tests {
             vec!["tenant_id".to_string()]
         );
         let expected_retention_policy = RetentionPolicy {
-            retention_period: "90 days".to_string(),
+            retention_period: Some("90 days".to_string()),
+            max_size: None,
+            expiration_field: None,
             evaluation_schedule: "daily".to_string(),
+            dry_run: false,
         };
         assert_eq!(
             index_config.retention_policy.unwrap(),
@@ -726,11 +973,177 @@ mod tests {
             .contains("Failed to parse human-readable duration `x`"));
     }
 
+    #[test]
+    fn test_index_config_garbage_collection_settings() {
+        let default_index_root_uri = Uri::from_well_formed("s3://defaultbucket/");
+        {
+            let index_config_yaml = r#"
+                version: 0.6
+                index_id: hdfs-logs
+                doc_mapping: {}
+            "#;
+            let index_config = load_index_config_from_user_config(
+                ConfigFormat::Yaml,
+                index_config_yaml.as_bytes(),
+                &default_index_root_uri,
+            )
+            .unwrap();
+            let garbage_collection_settings = index_config.garbage_collection_settings;
+            assert!(garbage_collection_settings.is_default());
+            assert_eq!(
+                garbage_collection_settings.interval(Duration::from_secs(60)),
+                Duration::from_secs(60)
+            );
+            assert_eq!(
+                garbage_collection_settings.staged_split_grace_period(Duration::from_secs(3600)),
+                Duration::from_secs(3600)
+            );
+        }
+        {
+            let index_config_yaml = r#"
+                version: 0.6
+                index_id: hdfs-logs
+                doc_mapping: {}
+                garbage_collection:
+                  interval_secs: 300
+                  staged_split_grace_period_secs: 600
+            "#;
+            let index_config = load_index_config_from_user_config(
+                ConfigFormat::Yaml,
+                index_config_yaml.as_bytes(),
+                &default_index_root_uri,
+            )
+            .unwrap();
+            let garbage_collection_settings = index_config.garbage_collection_settings;
+            assert_eq!(
+                garbage_collection_settings.interval(Duration::from_secs(60)),
+                Duration::from_secs(300)
+            );
+            assert_eq!(
+                garbage_collection_settings.staged_split_grace_period(Duration::from_secs(3600)),
+                Duration::from_secs(600)
+            );
+        }
+        {
+            let index_config_yaml = r#"
+                version: 0.6
+                index_id: hdfs-logs
+                doc_mapping: {}
+                garbage_collection:
+                  interval_secs: 0
+            "#;
+            load_index_config_from_user_config(
+                ConfigFormat::Yaml,
+                index_config_yaml.as_bytes(),
+                &default_index_root_uri,
+            )
+            .unwrap_err();
+        }
+    }
+
+    #[test]
+    fn test_index_config_rollup_policy() {
+        let default_index_root_uri = Uri::from_well_formed("s3://defaultbucket/");
+        {
+            let index_config_yaml = r#"
+                version: 0.6
+                index_id: hdfs-logs
+                doc_mapping:
+                  timestamp_field: timestamp
+                  field_mappings:
+                    - name: timestamp
+                      type: datetime
+                      fast: true
+                    - name: service
+                      type: text
+                      tokenizer: raw
+                      fast: true
+                rollup:
+                  target_index_id: hdfs-logs-rollup
+                  min_age: 7 days
+                  interval: 1 minute
+                  group_by: [service]
+            "#;
+            let index_config = load_index_config_from_user_config(
+                ConfigFormat::Yaml,
+                index_config_yaml.as_bytes(),
+                &default_index_root_uri,
+            )
+            .unwrap();
+            let rollup_policy = index_config.rollup_policy.unwrap();
+            assert_eq!(rollup_policy.target_index_id, "hdfs-logs-rollup");
+            assert_eq!(
+                rollup_policy.min_age().unwrap(),
+                Duration::from_secs(7 * 24 * 3600)
+            );
+            assert_eq!(rollup_policy.interval().unwrap(), Duration::from_secs(60));
+            assert_eq!(rollup_policy.group_by, ["service"]);
+            assert_eq!(rollup_policy.evaluation_schedule, "hourly");
+        }
+        {
+            // The rolled up index must declare a timestamp field.
+            let index_config_yaml = r#"
+                version: 0.6
+                index_id: hdfs-logs
+                doc_mapping: {}
+                rollup:
+                  target_index_id: hdfs-logs-rollup
+                  min_age: 7 days
+                  interval: 1 minute
+            "#;
+            load_index_config_from_user_config(
+                ConfigFormat::Yaml,
+                index_config_yaml.as_bytes(),
+                &default_index_root_uri,
+            )
+            .unwrap_err();
+        }
+        {
+            // The documents must be rolled up before they expire.
+            let index_config_yaml = r#"
+                version: 0.6
+                index_id: hdfs-logs
+                doc_mapping:
+                  timestamp_field: timestamp
+                  field_mappings:
+                    - name: timestamp
+                      type: datetime
+                      fast: true
+                retention:
+                  period: 7 days
+                rollup:
+                  target_index_id: hdfs-logs-rollup
+                  min_age: 7 days
+                  interval: 1 minute
+            "#;
+            let error = load_index_config_from_user_config(
+                ConfigFormat::Yaml,
+                index_config_yaml.as_bytes(),
+                &default_index_root_uri,
+            )
+            .unwrap_err();
+            assert!(error.to_string().contains("min age"));
+        }
+        {
+            let rollup_policy = RollupPolicy::new(
+                "hdfs-logs-rollup".to_string(),
+                "7 days".to_string(),
+                "500ms".to_string(),
+                Vec::new(),
+                "hourly".to_string(),
+            );
+            rollup_policy.interval().unwrap_err();
+        }
+    }
+
     #[test]
     fn test_retention_policy_serialization() {
         let retention_policy = RetentionPolicy {
-            retention_period: "90 days".to_string(),
+            retention_period: Some("90 days".to_string()),
+            max_size: None,
+            expiration_field: None,
             evaluation_schedule: "hourly".to_string(),
+            dry_run: false,
         };
         let retention_policy_yaml = serde_yaml::to_string(&retention_policy).unwrap();
         assert_eq!(
@@ -749,8 +1162,11 @@ mod tests {
                 serde_yaml::from_str::<RetentionPolicy>(retention_policy_yaml).unwrap();
 
             let expected_retention_policy = RetentionPolicy {
-                retention_period: "90 days".to_string(),
+                retention_period: Some("90 days".to_string()),
+                max_size: None,
+                expiration_field: None,
                 evaluation_schedule: "hourly".to_string(),
+                dry_run: false,
             };
             assert_eq!(retention_policy, expected_retention_policy);
         }
@@ -763,28 +1179,72 @@ mod tests {
                 serde_yaml::from_str::<RetentionPolicy>(retention_policy_yaml).unwrap();
 
             let expected_retention_policy = RetentionPolicy {
-                retention_period: "90 days".to_string(),
+                retention_period: Some("90 days".to_string()),
+                max_size: None,
+                expiration_field: None,
                 evaluation_schedule: "daily".to_string(),
+                dry_run: false,
             };
             assert_eq!(retention_policy, expected_retention_policy);
         }
+        {
+            let retention_policy_yaml = r#"
+            max_size: 100 GB
+            dry_run: true
+        "#;
+            let retention_policy =
+                serde_yaml::from_str::<RetentionPolicy>(retention_policy_yaml).unwrap();
+
+            let expected_retention_policy = RetentionPolicy {
+                retention_period: None,
+                max_size: Some(Byte::from_bytes(100_000_000_000)),
+                expiration_field: None,
+                evaluation_schedule: "hourly".to_string(),
+                dry_run: true,
+            };
+            assert_eq!(retention_policy, expected_retention_policy);
+        }
+        {
+            let retention_policy_yaml = r#"
+            expiration_field: expires_at
+        "#;
+            let retention_policy =
+                serde_yaml::from_str::<RetentionPolicy>(retention_policy_yaml).unwrap();
+            retention_policy.validate().unwrap();
+            assert_eq!(retention_policy.expiration_field(), Some("expires_at"));
+            assert_eq!(retention_policy.retention_period().unwrap(), None);
+        }
+        {
+            let retention_policy_yaml = r#"
+            schedule: daily
+        "#;
+            let retention_policy =
+                serde_yaml::from_str::<RetentionPolicy>(retention_policy_yaml).unwrap();
+            retention_policy.validate().unwrap_err();
+        }
     }
 
     #[test]
     fn test_parse_retention_policy_period() {
         {
             let retention_policy = RetentionPolicy {
-                retention_period: "1 hour".to_string(),
+                retention_period: Some("1 hour".to_string()),
+                max_size: None,
+                expiration_field: None,
                 evaluation_schedule: "hourly".to_string(),
+                dry_run: false,
             };
             assert_eq!(
                 retention_policy.retention_period().unwrap(),
-                Duration::from_secs(3600)
+                Some(Duration::from_secs(3600))
             );
             {
                 let retention_policy = RetentionPolicy {
-                    retention_period: "foo".to_string(),
+                    retention_period: Some("foo".to_string()),
+                    max_size: None,
+                    expiration_field: None,
                     evaluation_schedule: "hourly".to_string(),
+                    dry_run: false,
                 };
                 assert_eq!(
                     retention_policy.retention_period().unwrap_err().to_string(),
@@ -807,8 +1267,11 @@ mod tests {
         let hourly_schedule = Schedule::from_str("@hourly").unwrap();
         {
             let retention_policy = RetentionPolicy {
-                retention_period: "1 hour".to_string(),
+                retention_period: Some("1 hour".to_string()),
+                max_size: None,
+                expiration_field: None,
                 evaluation_schedule: "@hourly".to_string(),
+                dry_run: false,
             };
             assert_eq!(
                 retention_policy.evaluation_schedule().unwrap(),
@@ -817,8 +1280,11 @@ mod tests {
         }
         {
             let retention_policy = RetentionPolicy {
-                retention_period: "1 hour".to_string(),
+                retention_period: Some("1 hour".to_string()),
+                max_size: None,
+                expiration_field: None,
                 evaluation_schedule: "hourly".to_string(),
+                dry_run: false,
             };
             assert_eq!(
                 retention_policy.evaluation_schedule().unwrap(),
@@ -827,8 +1293,11 @@ mod tests {
         }
         {
             let retention_policy = RetentionPolicy {
-                retention_period: "1 hour".to_string(),
+                retention_period: Some("1 hour".to_string()),
+                max_size: None,
+                expiration_field: None,
                 evaluation_schedule: "0 * * * * *".to_string(),
+                dry_run: false,
             };
             let evaluation_schedule = retention_policy.evaluation_schedule().unwrap();
             assert_eq!(evaluation_schedule.seconds().count(), 1);
@@ -840,22 +1309,51 @@ mod tests {
     fn test_retention_policy_validate() {
         {
             let retention_policy = RetentionPolicy {
-                retention_period: "1 hour".to_string(),
+                retention_period: Some("1 hour".to_string()),
+                max_size: None,
+                expiration_field: None,
                 evaluation_schedule: "hourly".to_string(),
+                dry_run: false,
             };
             retention_policy.validate().unwrap();
         }
         {
             let retention_policy = RetentionPolicy {
-                retention_period: "foo".to_string(),
+                retention_period: Some("foo".to_string()),
+                max_size: None,
+                expiration_field: None,
                 evaluation_schedule: "hourly".to_string(),
+                dry_run: false,
             };
             retention_policy.validate().unwrap_err();
         }
         {
             let retention_policy = RetentionPolicy {
-                retention_period: "1 hour".to_string(),
+                retention_period: Some("1 hour".to_string()),
+                max_size: None,
+                expiration_field: None,
                 evaluation_schedule: "foo".to_string(),
+                dry_run: false,
+            };
+            retention_policy.validate().unwrap_err();
+        }
+        {
+            let retention_policy = RetentionPolicy {
+                retention_period: None,
+                max_size: Some(Byte::from_bytes(1_000_000)),
+                expiration_field: None,
+                evaluation_schedule: "hourly".to_string(),
+                dry_run: false,
+            };
+            retention_policy.validate().unwrap();
+        }
+        {
+            let retention_policy = RetentionPolicy {
+                retention_period: None,
+                max_size: None,
+                expiration_field: None,
+                evaluation_schedule: "hourly".to_string(),
+                dry_run: false,
             };
             retention_policy.validate().unwrap_err();
         }
@@ -866,8 +1364,11 @@ mod tests {
         let schedule_test_helper_fn = |schedule_str: &str| {
             let hourly_schedule = Schedule::from_str(&prepend_at_char(schedule_str)).unwrap();
             let retention_policy = RetentionPolicy {
-                retention_period: "1 hour".to_string(),
+                retention_period: Some("1 hour".to_string()),
+                max_size: None,
+                expiration_field: None,
                 evaluation_schedule: schedule_str.to_string(),
+                dry_run: false,
             };
 
             let next_evaluation_duration = chrono::Duration::nanoseconds(
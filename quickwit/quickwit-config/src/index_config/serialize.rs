@@ -19,12 +19,13 @@
 
 use anyhow::Context;
 use quickwit_common::uri::Uri;
+use quickwit_doc_mapper::RoutingExpr;
 use serde::{Deserialize, Serialize};
 use tracing::info;
 
 use crate::{
-    build_doc_mapper, validate_identifier, ConfigFormat, DocMapping, IndexConfig, IndexingSettings,
-    RetentionPolicy, SearchSettings,
+    build_doc_mapper, validate_identifier, ConfigFormat, DocMapping, GarbageCollectionSettings,
+    IndexConfig, IndexingSettings, RetentionPolicy, RollupPolicy, SearchSettings,
 };
 
 /// Alias for the latest serialization format.
@@ -90,7 +91,9 @@ impl IndexConfigForSerialization {
         if let Some(retention_policy) = &self.retention_policy {
             retention_policy.validate()?;
 
-            if self.doc_mapping.timestamp_field.is_none() {
+            if retention_policy.retention_period()?.is_some()
+                && self.doc_mapping.timestamp_field.is_none()
+            {
                 anyhow::bail!(
                     "Failed to validate index config. The retention policy requires a timestamp \
                      field, but the indexing settings do not declare one."
@@ -98,6 +101,32 @@ impl IndexConfigForSerialization {
             }
         }
 
+        if let Some(rollup_policy) = &self.rollup_policy {
+            rollup_policy.validate(&self.index_id)?;
+
+            if self.doc_mapping.timestamp_field.is_none() {
+                anyhow::bail!(
+                    "Failed to validate index config. The rollup policy requires a timestamp \
+                     field, but the indexing settings do not declare one."
+                );
+            }
+            let retention_period_opt = self
+                .retention_policy
+                .as_ref()
+                .map(|retention_policy| retention_policy.retention_period())
+                .transpose()?
+                .flatten();
+            if let Some(retention_period) = retention_period_opt {
+                if rollup_policy.min_age()? >= retention_period {
+                    anyhow::bail!(
+                        "Failed to validate index config. The rollup min age must be shorter than \
+                         the retention period, otherwise the documents expire before being rolled \
+                         up."
+                    );
+                }
+            }
+        }
+
         // Note: this needs a deep refactoring to separate the doc mapping configuration,
         // and doc mapper implementations.
         // TODO see if we should store the byproducton the IndexConfig.
@@ -105,6 +134,15 @@ impl IndexConfigForSerialization {
 
         self.indexing_settings.merge_policy.validate()?;
 
+        if let Some(partition_by) = &self.indexing_settings.partition_by {
+            if partition_by.trim().is_empty() {
+                anyhow::bail!("Failed to validate index config. `partition_by` must not be empty.");
+            }
+            RoutingExpr::new(partition_by).with_context(|| {
+                format!("Failed to parse `partition_by` routing expression `{partition_by}`.")
+            })?;
+        }
+
         Ok(IndexConfig {
             index_id: self.index_id,
             index_uri,
@@ -112,6 +150,8 @@ impl IndexConfigForSerialization {
             indexing_settings: self.indexing_settings,
             search_settings: self.search_settings,
             retention_policy: self.retention_policy,
+            rollup_policy: self.rollup_policy,
+            garbage_collection_settings: self.garbage_collection_settings,
         })
     }
 }
@@ -147,6 +187,12 @@ pub struct IndexConfigV0_6 {
     #[serde(rename = "retention")]
     #[serde(default)]
     pub retention_policy: Option<RetentionPolicy>,
+    #[serde(rename = "rollup")]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollup_policy: Option<RollupPolicy>,
+    #[serde(rename = "garbage_collection")]
+    #[serde(default, skip_serializing_if = "GarbageCollectionSettings::is_default")]
+    pub garbage_collection_settings: GarbageCollectionSettings,
 }
 
 impl From<IndexConfig> for IndexConfigV0_6 {
@@ -158,12 +204,16 @@ impl From<IndexConfig> for IndexConfigV0_6 {
             indexing_settings: index_config.indexing_settings,
             search_settings: index_config.search_settings,
             retention_policy: index_config.retention_policy,
+            rollup_policy: index_config.rollup_policy,
+            garbage_collection_settings: index_config.garbage_collection_settings,
         }
     }
 }
 
 #[cfg(test)]
 mod test {
+    use byte_unit::Byte;
+
     use super::*;
     use crate::merge_policy_config::{MergePolicyConfig, StableLogMergePolicyConfig};
 
@@ -209,20 +259,55 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_validate_partition_by() {
+        let mut index_config = minimal_index_config_for_serialization();
+        index_config.indexing_settings.partition_by = Some("tenant_id".to_string());
+        index_config.clone().validate_and_build(None).unwrap();
+
+        index_config.indexing_settings.partition_by = Some(" ".to_string());
+        let validation_err = index_config
+            .clone()
+            .validate_and_build(None)
+            .unwrap_err()
+            .to_string();
+        assert!(validation_err.contains("`partition_by` must not be empty"));
+
+        index_config.indexing_settings.partition_by = Some("unknown_fn(tenant_id)".to_string());
+        let validation_err = index_config
+            .validate_and_build(None)
+            .unwrap_err()
+            .to_string();
+        assert!(validation_err.contains("Failed to parse `partition_by` routing expression"));
+    }
+
     #[test]
     fn test_validate_retention_policy() {
         // Not yet invalid, but we modify it right after this.
         let mut invalid_index_config: IndexConfigForSerialization =
             minimal_index_config_for_serialization();
         invalid_index_config.retention_policy = Some(RetentionPolicy {
-            retention_period: "90 days".to_string(),
+            retention_period: Some("90 days".to_string()),
+            max_size: None,
             evaluation_schedule: "hourly".to_string(),
+            dry_run: false,
         });
         let validation_err = invalid_index_config
             .validate_and_build(None)
             .unwrap_err()
             .to_string();
         assert!(validation_err.contains("The retention policy requires a timestamp field"));
+
+        // A size-based retention policy does not require a timestamp field.
+        let mut index_config: IndexConfigForSerialization =
+            minimal_index_config_for_serialization();
+        index_config.retention_policy = Some(RetentionPolicy {
+            retention_period: None,
+            max_size: Some(Byte::from_bytes(1_000_000)),
+            evaluation_schedule: "hourly".to_string(),
+            dry_run: false,
+        });
+        index_config.validate_and_build(None).unwrap();
     }
 
     #[test]
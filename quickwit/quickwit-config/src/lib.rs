@@ -22,12 +22,14 @@
 use std::str::FromStr;
 
 use anyhow::{bail, Context};
+pub use cluster_settings::ClusterSettings;
 use json_comments::StripComments;
 use once_cell::sync::OnceCell;
 use quickwit_common::net::is_valid_hostname;
 use quickwit_common::uri::Uri;
 use regex::Regex;
 
+mod cluster_settings;
 mod config_value;
 mod index_config;
 pub mod merge_policy_config;
@@ -41,8 +43,9 @@ mod templating;
 // See #2048
 use index_config::serialize::{IndexConfigV0_6, VersionedIndexConfig};
 pub use index_config::{
-    build_doc_mapper, load_index_config_from_user_config, DocMapping, IndexConfig,
-    IndexingResources, IndexingSettings, RetentionPolicy, SearchSettings,
+    build_doc_mapper, load_index_config_from_user_config, DocMapping, GarbageCollectionSettings,
+    IndexConfig, IndexingResources, IndexingSettings, RetentionPolicy, RollupPolicy,
+    SearchSettings,
 };
 use serde::de::DeserializeOwned;
 use serde::Serialize;
@@ -59,8 +62,12 @@ use crate::merge_policy_config::{
     ConstWriteAmplificationMergePolicyConfig, MergePolicyConfig, StableLogMergePolicyConfig,
 };
 pub use crate::quickwit_config::{
-    IndexerConfig, IngestApiConfig, JaegerConfig, QuickwitConfig, SearcherConfig,
-    DEFAULT_QW_CONFIG_PATH,
+    ApiKeyConfig, AuditLogConfig, AuthConfig, AuthGrant, AuthRole, GossipTransport,
+    GrpcCompression, GrpcTlsConfig, IndexerConfig, IngestApiConfig, JaegerConfig, JanitorConfig,
+    JwtConfig, LogFormat, MetastoreConfig, OtlpCommitMode, OtlpIdEncoding, OtlpIngestConfig,
+    OtlpLimitsConfig, OtlpPromotedAttribute, OtlpPromotedAttributeType, OtlpTailSamplingConfig,
+    OtlpTailSamplingPolicy, PostgresMetastoreConfig, QuickwitConfig, RateLimitConfig, RateLimits,
+    RestCompressionConfig, SearcherConfig, DEFAULT_QW_CONFIG_PATH,
 };
 use crate::source_config::serialize::{SourceConfigV0_6, VersionedSourceConfig};
 
@@ -70,6 +77,9 @@ use crate::source_config::serialize::{SourceConfigV0_6, VersionedSourceConfig};
     IndexingSettings,
     SearchSettings,
     RetentionPolicy,
+    RollupPolicy,
+    ClusterSettings,
+    GarbageCollectionSettings,
     MergePolicyConfig,
     DocMapping,
     VersionedSourceConfig,
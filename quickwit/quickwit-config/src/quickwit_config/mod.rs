@@ -19,12 +19,13 @@
 
 mod serialize;
 
-use std::collections::{HashMap, HashSet};
-use std::env;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::net::SocketAddr;
-use std::num::NonZeroU64;
+use std::num::{NonZeroU32, NonZeroU64, NonZeroUsize};
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::time::Duration;
+use std::{env, fmt};
 
 use anyhow::bail;
 use byte_unit::Byte;
@@ -48,10 +49,53 @@ pub struct IndexerConfig {
     pub split_store_max_num_splits: usize,
     #[serde(default = "IndexerConfig::default_max_concurrent_split_uploads")]
     pub max_concurrent_split_uploads: usize,
-    /// Enables the OpenTelemetry exporter endpoint to ingest logs and traces via the OpenTelemetry
-    /// Protocol (OTLP).
+    /// Maximum amount of memory that the indexers of all the indexing pipelines running on the
+    /// node can use collectively. If not set, each pipeline is only bounded by its own
+    /// `heap_size`.
+    #[serde(default)]
+    pub max_indexing_memory_usage: Option<Byte>,
+    /// Enables the OpenTelemetry exporter endpoint to ingest logs, traces, and metrics via the
+    /// OpenTelemetry Protocol (OTLP).
     #[serde(default = "IndexerConfig::default_enable_otlp_endpoint")]
     pub enable_otlp_endpoint: bool,
+    /// Index receiving the logs ingested via the OTLP endpoint.
+    #[serde(default = "IndexerConfig::default_otlp_logs_index_id")]
+    pub otlp_logs_index_id: String,
+    /// Index receiving the spans ingested via the OTLP endpoint.
+    #[serde(default = "IndexerConfig::default_otlp_trace_index_id")]
+    pub otlp_trace_index_id: String,
+    /// Resource attribute identifying the tenant emitting the logs and spans ingested via the
+    /// OTLP endpoint. When set, the logs and spans are routed to per-tenant indexes named
+    /// `{otlp_logs_index_id}-{tenant}` and `{otlp_trace_index_id}-{tenant}`.
+    #[serde(default)]
+    pub otlp_tenant_attribute: Option<String>,
+    /// Log and span attributes copied into dedicated fast fields of the OTLP logs and trace
+    /// indexes.
+    #[serde(default)]
+    pub otlp_promoted_attributes: Vec<OtlpPromotedAttribute>,
+    /// Buffers the spans ingested via the OTLP endpoint per trace and only indexes the traces
+    /// matching one of the sampling policies. Disabled when not set.
+    #[serde(default)]
+    pub otlp_tail_sampling: Option<OtlpTailSamplingConfig>,
+    /// Encoding of the trace and span IDs of the logs and spans ingested via the OTLP endpoint.
+    #[serde(default)]
+    pub otlp_id_encoding: OtlpIdEncoding,
+    /// Derives request, error, and duration metrics per service from the spans ingested via the
+    /// OTLP endpoint and exposes them on the `/metrics` endpoint.
+    #[serde(default)]
+    pub enable_otlp_span_metrics: bool,
+    /// Limits protecting the node from the requests received on the OTLP endpoint.
+    #[serde(default)]
+    pub otlp_limits: OtlpLimitsConfig,
+    /// Compression accepted on the OTLP gRPC endpoint.
+    #[serde(default)]
+    pub otlp_grpc_compression: GrpcCompression,
+    /// Commit mode and batching of the log records ingested via the OTLP endpoint.
+    #[serde(default)]
+    pub otlp_logs_ingest: OtlpIngestConfig,
+    /// Commit mode and batching of the spans ingested via the OTLP endpoint.
+    #[serde(default)]
+    pub otlp_trace_ingest: OtlpIngestConfig,
 }
 
 impl IndexerConfig {
@@ -59,6 +103,14 @@ impl IndexerConfig {
         !(cfg!(feature = "test") || cfg!(feature = "testsuite"))
     }
 
+    fn default_otlp_logs_index_id() -> String {
+        "otel-logs-v0".to_string()
+    }
+
+    fn default_otlp_trace_index_id() -> String {
+        "otel-trace-v0".to_string()
+    }
+
     fn default_max_concurrent_split_uploads() -> usize {
         12
     }
@@ -75,9 +127,21 @@ impl IndexerConfig {
     pub fn for_test() -> anyhow::Result<Self> {
         let indexer_config = IndexerConfig {
             enable_otlp_endpoint: true,
+            otlp_logs_index_id: Self::default_otlp_logs_index_id(),
+            otlp_trace_index_id: Self::default_otlp_trace_index_id(),
+            otlp_tenant_attribute: None,
+            otlp_promoted_attributes: Vec::new(),
+            otlp_tail_sampling: None,
+            otlp_id_encoding: OtlpIdEncoding::default(),
+            enable_otlp_span_metrics: false,
+            otlp_limits: OtlpLimitsConfig::default(),
+            otlp_grpc_compression: GrpcCompression::default(),
+            otlp_logs_ingest: OtlpIngestConfig::default(),
+            otlp_trace_ingest: OtlpIngestConfig::default(),
             split_store_max_num_bytes: Byte::from_bytes(1_000_000),
             split_store_max_num_splits: 3,
             max_concurrent_split_uploads: 4,
+            max_indexing_memory_usage: None,
         };
         Ok(indexer_config)
     }
@@ -87,13 +151,296 @@ impl Default for IndexerConfig {
     fn default() -> Self {
         Self {
             enable_otlp_endpoint: Self::default_enable_otlp_endpoint(),
+            otlp_logs_index_id: Self::default_otlp_logs_index_id(),
+            otlp_trace_index_id: Self::default_otlp_trace_index_id(),
+            otlp_tenant_attribute: None,
+            otlp_promoted_attributes: Vec::new(),
+            otlp_tail_sampling: None,
+            otlp_id_encoding: OtlpIdEncoding::default(),
+            enable_otlp_span_metrics: false,
+            otlp_limits: OtlpLimitsConfig::default(),
+            otlp_grpc_compression: GrpcCompression::default(),
+            otlp_logs_ingest: OtlpIngestConfig::default(),
+            otlp_trace_ingest: OtlpIngestConfig::default(),
             split_store_max_num_bytes: Self::default_split_store_max_num_bytes(),
             split_store_max_num_splits: Self::default_split_store_max_num_splits(),
             max_concurrent_split_uploads: Self::default_max_concurrent_split_uploads(),
+            max_indexing_memory_usage: None,
+        }
+    }
+}
+
+/// Log or span attribute copied into a dedicated fast field of the OTLP logs and trace indexes, so
+/// that filtering and aggregating on it does not go through the `attributes` JSON fields.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OtlpPromotedAttribute {
+    /// Key of the attribute, for instance `http.status_code`. The attribute is looked up in the
+    /// attributes of the log record or span first, then in the attributes of its resource.
+    pub key: String,
+    /// Name of the field receiving the attribute value. Defaults to the key of the attribute with
+    /// dots replaced by underscores.
+    #[serde(default)]
+    pub field_name: Option<String>,
+    #[serde(rename = "type")]
+    pub field_type: OtlpPromotedAttributeType,
+}
+
+impl OtlpPromotedAttribute {
+    pub fn field_name(&self) -> String {
+        match &self.field_name {
+            Some(field_name) => field_name.clone(),
+            None => self.key.replace('.', "_"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OtlpPromotedAttributeType {
+    Text,
+    I64,
+    U64,
+    F64,
+    Bool,
+}
+
+/// Compression of the messages exchanged with a gRPC service. Clients that do not compress their
+/// requests or do not advertise support for the encoding are still served uncompressed messages.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GrpcCompression {
+    None,
+    #[default]
+    Gzip,
+}
+
+/// Format of the log events written by the node on the standard output.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Human-readable lines.
+    #[default]
+    Text,
+    /// One JSON object per line, for log shippers.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(log_format_str: &str) -> anyhow::Result<Self> {
+        match log_format_str {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => bail!("Unknown log format `{log_format_str}`. Expected `text` or `json`."),
+        }
+    }
+}
+
+/// Transport used by the cluster membership protocol to exchange gossip messages between nodes.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GossipTransport {
+    /// Datagrams sent to the gossip port of the nodes.
+    #[default]
+    Udp,
+    /// Unary calls to the gRPC service of the nodes, for networks where UDP traffic between nodes
+    /// is blocked. The gossip port is then the gRPC port.
+    Grpc,
+}
+
+impl FromStr for GossipTransport {
+    type Err = anyhow::Error;
+
+    fn from_str(gossip_transport_str: &str) -> anyhow::Result<Self> {
+        match gossip_transport_str {
+            "udp" => Ok(GossipTransport::Udp),
+            "grpc" => Ok(GossipTransport::Grpc),
+            _ => bail!(
+                "Unknown gossip transport `{gossip_transport_str}`. Expected `udp` or `grpc`."
+            ),
+        }
+    }
+}
+
+/// TLS settings of the gRPC server of the node and of the connections to the gRPC servers of the
+/// other nodes, including the gossip messages when the nodes gossip over gRPC. All the nodes of
+/// the cluster must enable TLS.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GrpcTlsConfig {
+    /// PEM file holding the certificate chain presented by the gRPC server of the node.
+    pub cert_path: PathBuf,
+    /// PEM file holding the private key of the certificate.
+    pub key_path: PathBuf,
+    /// PEM file holding the certificate of the authority issuing the certificates of the nodes.
+    pub ca_cert_path: PathBuf,
+    /// Name the certificates of the nodes are issued for. The nodes are addressed by IP address,
+    /// so their certificates are verified against this name instead.
+    pub server_name: String,
+}
+
+impl GrpcTlsConfig {
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.server_name.is_empty() {
+            bail!("gRPC TLS server name must not be empty.");
+        }
+        Ok(())
+    }
+}
+
+/// Encoding of the trace and span IDs stored in the OTLP logs and trace indexes. IDs are accepted
+/// in both encodings at ingestion and query time regardless of this setting.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OtlpIdEncoding {
+    #[default]
+    Base64,
+    /// Lowercase hex, as defined by the W3C Trace Context specification.
+    Hex,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OtlpLimitsConfig {
+    /// Maximum size of the payload of a request, once decompressed. Larger requests are rejected
+    /// and must not be retried.
+    #[serde(default = "OtlpLimitsConfig::default_max_payload_size")]
+    pub max_payload_size: Byte,
+    /// Maximum number of log records in a request.
+    #[serde(default = "OtlpLimitsConfig::default_max_items_per_request")]
+    pub max_log_records_per_request: NonZeroUsize,
+    /// Maximum number of spans in a request.
+    #[serde(default = "OtlpLimitsConfig::default_max_items_per_request")]
+    pub max_spans_per_request: NonZeroUsize,
+    /// Delay after which exporters are told to retry the requests rejected because the ingest
+    /// queue is full.
+    #[serde(default = "OtlpLimitsConfig::default_retry_after_secs")]
+    pub retry_after_secs: NonZeroU64,
+}
+
+impl OtlpLimitsConfig {
+    fn default_max_payload_size() -> Byte {
+        Byte::from_bytes(100 * 1024 * 1024) // 100MiB
+    }
+
+    fn default_max_items_per_request() -> NonZeroUsize {
+        NonZeroUsize::new(100_000).unwrap()
+    }
+
+    fn default_retry_after_secs() -> NonZeroU64 {
+        NonZeroU64::new(5).unwrap()
+    }
+
+    pub fn retry_after(&self) -> Duration {
+        Duration::from_secs(self.retry_after_secs.get())
+    }
+}
+
+impl Default for OtlpLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_payload_size: Self::default_max_payload_size(),
+            max_log_records_per_request: Self::default_max_items_per_request(),
+            max_spans_per_request: Self::default_max_items_per_request(),
+            retry_after_secs: Self::default_retry_after_secs(),
+        }
+    }
+}
+
+/// When the OTLP endpoint acknowledges the requests.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OtlpCommitMode {
+    /// Acknowledges the requests once the documents are appended to the write-ahead log of the
+    /// ingest API.
+    #[default]
+    Async,
+    /// Acknowledges the requests once the documents are committed and published, i.e.
+    /// searchable.
+    Sync,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OtlpIngestConfig {
+    #[serde(default)]
+    pub commit: OtlpCommitMode,
+    /// Delay during which the documents of concurrent requests are coalesced into a single
+    /// request to the ingest API. Disabled when set to 0.
+    #[serde(default)]
+    pub batch_delay_millis: u64,
+    /// Maximum size of the documents coalesced into a single request to the ingest API.
+    #[serde(default = "OtlpIngestConfig::default_max_batch_size")]
+    pub max_batch_size: Byte,
+}
+
+impl OtlpIngestConfig {
+    fn default_max_batch_size() -> Byte {
+        Byte::from_bytes(10 * 1024 * 1024) // 10MiB
+    }
+
+    pub fn batch_delay(&self) -> Duration {
+        Duration::from_millis(self.batch_delay_millis)
+    }
+}
+
+impl Default for OtlpIngestConfig {
+    fn default() -> Self {
+        Self {
+            commit: OtlpCommitMode::default(),
+            batch_delay_millis: 0,
+            max_batch_size: Self::default_max_batch_size(),
         }
     }
 }
 
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OtlpTailSamplingConfig {
+    /// Time to wait after receiving the first span of a trace before deciding whether the trace
+    /// is sampled.
+    #[serde(default = "OtlpTailSamplingConfig::default_decision_wait_secs")]
+    pub decision_wait_secs: NonZeroU64,
+    /// Maximum number of traces buffered while waiting for a decision. Once reached, the oldest
+    /// traces are decided early.
+    #[serde(default = "OtlpTailSamplingConfig::default_max_buffered_traces")]
+    pub max_buffered_traces: NonZeroUsize,
+    /// A trace is sampled if it matches any of these policies.
+    pub policies: Vec<OtlpTailSamplingPolicy>,
+}
+
+impl OtlpTailSamplingConfig {
+    fn default_decision_wait_secs() -> NonZeroU64 {
+        NonZeroU64::new(10).unwrap()
+    }
+
+    fn default_max_buffered_traces() -> NonZeroUsize {
+        NonZeroUsize::new(50_000).unwrap()
+    }
+
+    pub fn decision_wait(&self) -> Duration {
+        Duration::from_secs(self.decision_wait_secs.get())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum OtlpTailSamplingPolicy {
+    /// Samples the traces containing at least one span with an error status.
+    Error,
+    /// Samples the traces lasting at least `threshold_millis`, from the start of their first span
+    /// to the end of their last span.
+    Latency { threshold_millis: u64 },
+    /// Samples a fraction of the traces chosen deterministically from their trace ID, so that
+    /// all the nodes make the same decision for a given trace.
+    Probabilistic { sampling_rate: f64 },
+}
+
+// The sampling rate is validated to be a finite number between 0 and 1.
+impl Eq for OtlpTailSamplingPolicy {}
+
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
 #[serde(deny_unknown_fields, default)]
 pub struct SearcherConfig {
@@ -159,6 +506,9 @@ pub struct JaegerConfig {
     /// The maximum number of spans that can be retrieved in a single request.
     #[serde(default = "JaegerConfig::default_max_fetch_spans")]
     pub max_fetch_spans: NonZeroU64,
+    /// Compression of the requests accepted and of the responses sent by the gRPC endpoint.
+    #[serde(default)]
+    pub grpc_compression: GrpcCompression,
 }
 
 impl JaegerConfig {
@@ -194,6 +544,586 @@ impl Default for JaegerConfig {
             lookback_period_hours: Self::default_lookback_period_hours(),
             max_trace_duration_secs: Self::default_max_trace_duration_secs(),
             max_fetch_spans: Self::default_max_fetch_spans(),
+            grpc_compression: GrpcCompression::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JanitorConfig {
+    /// When set, deleting an index moves it to the trash, where it is retained for this many
+    /// hours and can be restored before the janitor deletes it permanently.
+    #[serde(default)]
+    index_trash_retention_hours: Option<NonZeroU64>,
+    /// Maximum number of delete task pipelines applying delete operations at the same time. The
+    /// indexes with pending delete operations take turns in a round-robin fashion.
+    #[serde(default = "JanitorConfig::default_max_concurrent_delete_pipelines")]
+    pub max_concurrent_delete_pipelines: NonZeroUsize,
+    /// Default interval between two garbage collection runs on an index. Can be overridden per
+    /// index.
+    #[serde(default = "JanitorConfig::default_gc_interval_secs")]
+    pub gc_interval_secs: NonZeroU64,
+    /// Default period after which a staged split is garbage collected. Can be overridden per
+    /// index.
+    #[serde(default = "JanitorConfig::default_gc_staged_split_grace_period_secs")]
+    pub gc_staged_split_grace_period_secs: NonZeroU64,
+    /// When `true`, the garbage collector, the retention policies, and the delete task pipelines
+    /// only log and report what they would remove, without mutating the metastore or the
+    /// storage.
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+impl JanitorConfig {
+    pub fn index_trash_retention_period_opt(&self) -> Option<Duration> {
+        self.index_trash_retention_hours
+            .map(|index_trash_retention_hours| {
+                Duration::from_secs(index_trash_retention_hours.get() * 3600)
+            })
+    }
+
+    pub fn gc_interval(&self) -> Duration {
+        Duration::from_secs(self.gc_interval_secs.get())
+    }
+
+    pub fn gc_staged_split_grace_period(&self) -> Duration {
+        Duration::from_secs(self.gc_staged_split_grace_period_secs.get())
+    }
+
+    fn default_max_concurrent_delete_pipelines() -> NonZeroUsize {
+        NonZeroUsize::new(4).unwrap()
+    }
+
+    fn default_gc_interval_secs() -> NonZeroU64 {
+        NonZeroU64::new(60).unwrap()
+    }
+
+    fn default_gc_staged_split_grace_period_secs() -> NonZeroU64 {
+        NonZeroU64::new(24 * 3600).unwrap()
+    }
+}
+
+impl Default for JanitorConfig {
+    fn default() -> Self {
+        Self {
+            index_trash_retention_hours: None,
+            max_concurrent_delete_pipelines: Self::default_max_concurrent_delete_pipelines(),
+            gc_interval_secs: Self::default_gc_interval_secs(),
+            gc_staged_split_grace_period_secs: Self::default_gc_staged_split_grace_period_secs(),
+            dry_run: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MetastoreConfig {
+    /// Settings applied when the metastore URI points to a PostgreSQL database.
+    #[serde(rename = "postgres")]
+    #[serde(default)]
+    pub postgres_config: PostgresMetastoreConfig,
+    /// Tenant the indexes of the node belong to. When set, the node only sees the indexes of
+    /// this tenant in a metastore shared by several tenants.
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PostgresMetastoreConfig {
+    /// Maximum number of connections held by each connection pool (primary and read replica).
+    #[serde(default = "PostgresMetastoreConfig::default_max_connections")]
+    pub max_connections: NonZeroU32,
+    /// Minimum number of idle connections maintained by each connection pool.
+    #[serde(default)]
+    pub min_connections: u32,
+    /// How long to wait for a connection to become available before giving up.
+    #[serde(default = "PostgresMetastoreConfig::default_acquire_connection_timeout_secs")]
+    acquire_connection_timeout_secs: NonZeroU64,
+    /// How long a connection can stay idle in the pool before being closed.
+    #[serde(default = "PostgresMetastoreConfig::default_idle_connection_timeout_secs")]
+    idle_connection_timeout_secs: NonZeroU64,
+    /// Sets the `statement_timeout` session parameter on every connection. When unset, the
+    /// server default applies.
+    #[serde(default)]
+    statement_timeout_secs: Option<NonZeroU64>,
+    /// Optional URI of a read replica. When set, read-only calls issued on the search path
+    /// (`list_splits`) are routed to the replica instead of the primary.
+    #[serde(default)]
+    pub read_replica_uri: Option<Uri>,
+}
+
+impl PostgresMetastoreConfig {
+    pub fn acquire_connection_timeout(&self) -> Duration {
+        Duration::from_secs(self.acquire_connection_timeout_secs.get())
+    }
+
+    pub fn idle_connection_timeout(&self) -> Duration {
+        Duration::from_secs(self.idle_connection_timeout_secs.get())
+    }
+
+    pub fn statement_timeout_opt(&self) -> Option<Duration> {
+        self.statement_timeout_secs
+            .map(|statement_timeout_secs| Duration::from_secs(statement_timeout_secs.get()))
+    }
+
+    fn default_max_connections() -> NonZeroU32 {
+        NonZeroU32::new(10).unwrap()
+    }
+
+    fn default_acquire_connection_timeout_secs() -> NonZeroU64 {
+        NonZeroU64::new(2).unwrap()
+    }
+
+    fn default_idle_connection_timeout_secs() -> NonZeroU64 {
+        NonZeroU64::new(1).unwrap()
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.min_connections > self.max_connections.get() {
+            bail!(
+                "PostgreSQL metastore `min_connections` ({}) must be lower than or equal to \
+                 `max_connections` ({}).",
+                self.min_connections,
+                self.max_connections
+            );
+        }
+        if let Some(read_replica_uri) = &self.read_replica_uri {
+            if !read_replica_uri.protocol().is_postgresql() {
+                bail!(
+                    "PostgreSQL metastore read replica URI `{read_replica_uri}` must use the \
+                     `postgres` or `postgresql` protocol."
+                );
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for PostgresMetastoreConfig {
+    fn default() -> Self {
+        Self {
+            max_connections: Self::default_max_connections(),
+            min_connections: 0,
+            acquire_connection_timeout_secs: Self::default_acquire_connection_timeout_secs(),
+            idle_connection_timeout_secs: Self::default_idle_connection_timeout_secs(),
+            statement_timeout_secs: None,
+            read_replica_uri: None,
+        }
+    }
+}
+
+/// Roles granted to the principal of a request. Each route requires one of them; `admin` implies
+/// the other two.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthRole {
+    /// Search, SQL, PromQL, and trace queries.
+    Search,
+    /// Document ingestion (ingest, bulk, OTLP, and Zipkin span endpoints).
+    Ingest,
+    /// Index, source, and cluster management.
+    Admin,
+}
+
+impl AuthRole {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuthRole::Search => "search",
+            AuthRole::Ingest => "ingest",
+            AuthRole::Admin => "admin",
+        }
+    }
+}
+
+impl FromStr for AuthRole {
+    type Err = anyhow::Error;
+
+    fn from_str(role_str: &str) -> anyhow::Result<Self> {
+        match role_str {
+            "search" => Ok(AuthRole::Search),
+            "ingest" => Ok(AuthRole::Ingest),
+            "admin" => Ok(AuthRole::Admin),
+            _ => bail!(
+                "Unknown role `{role_str}`. Supported roles are `search`, `ingest`, and `admin`."
+            ),
+        }
+    }
+}
+
+/// A role granted on the indexes matching an index ID pattern, written `<role>:<pattern>`, for
+/// instance `search:team-a-*`. A bare `<role>` grants the role on all the indexes.
+#[derive(Clone, Debug, Eq, Hash, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct AuthGrant {
+    pub role: AuthRole,
+    /// Index ID pattern, where `*` matches any sequence of characters.
+    pub index_id_pattern: String,
+}
+
+impl AuthGrant {
+    /// Whether this grant implies `role`, `admin` implying the other roles.
+    pub fn implies(&self, role: AuthRole) -> bool {
+        self.role == role || self.role == AuthRole::Admin
+    }
+}
+
+impl FromStr for AuthGrant {
+    type Err = anyhow::Error;
+
+    fn from_str(grant_str: &str) -> anyhow::Result<Self> {
+        let (role_str, index_id_pattern) = grant_str.split_once(':').unwrap_or((grant_str, "*"));
+        let role = role_str.parse()?;
+
+        if index_id_pattern.is_empty()
+            || !index_id_pattern
+                .chars()
+                .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '*'))
+        {
+            bail!(
+                "Index ID pattern `{index_id_pattern}` of grant `{grant_str}` is invalid. \
+                 Patterns may only contain alphanumeric characters, `-`, `_`, and `*`."
+            );
+        }
+        Ok(Self {
+            role,
+            index_id_pattern: index_id_pattern.to_string(),
+        })
+    }
+}
+
+impl TryFrom<String> for AuthGrant {
+    type Error = anyhow::Error;
+
+    fn try_from(grant_str: String) -> anyhow::Result<Self> {
+        grant_str.parse()
+    }
+}
+
+impl From<AuthGrant> for String {
+    fn from(grant: AuthGrant) -> Self {
+        grant.to_string()
+    }
+}
+
+impl fmt::Display for AuthGrant {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.index_id_pattern == "*" {
+            write!(f, "{}", self.role.as_str())
+        } else {
+            write!(f, "{}:{}", self.role.as_str(), self.index_id_pattern)
+        }
+    }
+}
+
+/// A static API key, sent by clients as a bearer token.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiKeyConfig {
+    /// Name of the principal authenticated by this key, used in logs.
+    pub name: String,
+    pub key: String,
+    /// Roles granted to the principal, optionally restricted to an index ID pattern.
+    pub roles: Vec<AuthGrant>,
+    /// Tenant the principal is restricted to. When set, its searches and delete tasks only match
+    /// the documents whose tenant field holds this value.
+    #[serde(default)]
+    pub tenant_id: Option<String>,
+}
+
+/// Validation settings for JWT bearer tokens issued by an OIDC provider or signed with a shared
+/// secret.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JwtConfig {
+    /// Expected value of the `iss` claim. Unless `jwks_uri` or `hmac_secret` is set, the signing
+    /// keys are fetched from the JWKS advertised by the issuer's OpenID discovery document.
+    pub issuer: String,
+    /// Expected value (or one of the values) of the `aud` claim. When unset, the audience is not
+    /// checked.
+    #[serde(default)]
+    pub audience: Option<String>,
+    /// URI of the JSON Web Key Set used to verify RS256 and ES256 signatures.
+    #[serde(default)]
+    pub jwks_uri: Option<String>,
+    /// Shared secret used to verify HS256 signatures.
+    #[serde(default)]
+    pub hmac_secret: Option<String>,
+    /// Claim holding the roles of the principal, either as an array of strings or as a
+    /// space-separated string. Each role can be restricted to an index ID pattern, like the
+    /// roles of API keys.
+    #[serde(default = "JwtConfig::default_roles_claim")]
+    pub roles_claim: String,
+    /// Tolerated clock skew when checking the `exp` and `nbf` claims.
+    #[serde(default = "JwtConfig::default_leeway_secs")]
+    pub leeway_secs: u64,
+    /// Claim holding the tenant the principal is restricted to. Tokens without this claim are
+    /// not restricted to a tenant.
+    #[serde(default)]
+    pub tenant_claim: Option<String>,
+}
+
+impl JwtConfig {
+    fn default_roles_claim() -> String {
+        "roles".to_string()
+    }
+
+    fn default_leeway_secs() -> u64 {
+        60
+    }
+}
+
+/// Authentication of the REST API and of the gRPC services. Authentication is disabled when
+/// neither API keys, JWT validation, nor a cluster key are configured.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuthConfig {
+    #[serde(default)]
+    pub api_keys: Vec<ApiKeyConfig>,
+    #[serde(default)]
+    pub jwt: Option<JwtConfig>,
+    /// Key shared by the nodes of the cluster, granting the `admin` role on all indexes. The nodes
+    /// present it to each other's gRPC services.
+    #[serde(default)]
+    pub cluster_key: Option<String>,
+    /// Field filtered on the tenant of the principals restricted to a tenant.
+    #[serde(default = "AuthConfig::default_tenant_field")]
+    pub tenant_field: String,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self {
+            api_keys: Vec::new(),
+            jwt: None,
+            cluster_key: None,
+            tenant_field: Self::default_tenant_field(),
+        }
+    }
+}
+
+impl AuthConfig {
+    fn default_tenant_field() -> String {
+        "tenant_id".to_string()
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.api_keys.is_empty() || self.jwt.is_some() || self.cluster_key.is_some()
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        let mut names = HashSet::with_capacity(self.api_keys.len());
+        let mut keys = HashSet::with_capacity(self.api_keys.len());
+
+        for api_key in &self.api_keys {
+            if api_key.name.is_empty() {
+                bail!("API key name must not be empty.");
+            }
+            if !names.insert(&api_key.name) {
+                bail!("API key name `{}` is used more than once.", api_key.name);
+            }
+            if api_key.key.len() < 16 {
+                bail!(
+                    "API key `{}` must be at least 16 characters long.",
+                    api_key.name
+                );
+            }
+            if !keys.insert(&api_key.key) {
+                bail!("API key `{}` is used more than once.", api_key.name);
+            }
+            if api_key.roles.is_empty() {
+                bail!("API key `{}` must grant at least one role.", api_key.name);
+            }
+            if api_key.tenant_id.as_deref() == Some("") {
+                bail!("API key `{}` tenant ID must not be empty.", api_key.name);
+            }
+        }
+        if self.tenant_field.is_empty() {
+            bail!("Auth `tenant_field` must not be empty.");
+        }
+        if let Some(jwt_config) = &self.jwt {
+            if jwt_config.issuer.is_empty() {
+                bail!("JWT issuer must not be empty.");
+            }
+            if jwt_config.jwks_uri.is_some() && jwt_config.hmac_secret.is_some() {
+                bail!("JWT `jwks_uri` and `hmac_secret` are mutually exclusive.");
+            }
+            if let Some(jwks_uri) = &jwt_config.jwks_uri {
+                if !jwks_uri.starts_with("https://") && !jwks_uri.starts_with("http://") {
+                    bail!("JWT `jwks_uri` `{jwks_uri}` must be an HTTP(S) URL.");
+                }
+            }
+            if let Some(hmac_secret) = &jwt_config.hmac_secret {
+                if hmac_secret.len() < 32 {
+                    bail!("JWT `hmac_secret` must be at least 32 bytes long.");
+                }
+            }
+        }
+        match &self.cluster_key {
+            Some(cluster_key) => {
+                if cluster_key.len() < 16 {
+                    bail!("Auth `cluster_key` must be at least 16 characters long.");
+                }
+                if !cluster_key.bytes().all(|byte| byte.is_ascii_graphic()) {
+                    bail!("Auth `cluster_key` must only contain visible ASCII characters.");
+                }
+                if keys.contains(cluster_key) {
+                    bail!("Auth `cluster_key` must differ from the API keys.");
+                }
+            }
+            None if self.is_enabled() => {
+                bail!(
+                    "Auth `cluster_key` must be set when authentication is enabled, the nodes of \
+                     the cluster present it to each other's gRPC services."
+                );
+            }
+            None => {}
+        }
+        Ok(())
+    }
+}
+
+/// Audit log of the search, ingest, and admin requests received by the REST API and the OTLP and
+/// Jaeger gRPC services. The audit log is disabled when it has no destination.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuditLogConfig {
+    /// File to which the audit records are appended as JSON lines.
+    #[serde(default)]
+    pub file_path: Option<PathBuf>,
+    /// Index receiving the audit records via the ingest API. The index is created on startup if
+    /// it does not exist.
+    #[serde(default)]
+    pub index_id: Option<String>,
+    /// Whether the audit records of search requests contain their query.
+    #[serde(default = "AuditLogConfig::default_record_queries")]
+    pub record_queries: bool,
+}
+
+impl AuditLogConfig {
+    fn default_record_queries() -> bool {
+        true
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.file_path.is_some() || self.index_id.is_some()
+    }
+}
+
+impl Default for AuditLogConfig {
+    fn default() -> Self {
+        Self {
+            file_path: None,
+            index_id: None,
+            record_queries: Self::default_record_queries(),
+        }
+    }
+}
+
+/// Limits enforced on the requests of a principal or on the requests targeting an index.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimits {
+    /// Maximum number of search, ingest, and admin requests per second.
+    #[serde(default)]
+    pub requests_per_sec: Option<NonZeroU32>,
+    /// Maximum number of bytes per second sent to the ingest endpoints.
+    #[serde(default)]
+    pub ingest_bytes_per_sec: Option<Byte>,
+    /// Maximum number of search requests executed concurrently.
+    #[serde(default)]
+    pub max_concurrent_searches: Option<NonZeroUsize>,
+}
+
+/// Rate limits of the REST API and of the OTLP and Jaeger gRPC services, keyed by principal and by
+/// index. Each principal and each index gets its own token buckets.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RateLimitConfig {
+    /// Limits of the principals, keyed by principal name. The `*` entry applies to the principals
+    /// without a dedicated entry.
+    #[serde(default)]
+    pub principals: BTreeMap<String, RateLimits>,
+    /// Limits of the indexes, keyed by index ID pattern. An index is subject to the limits of the
+    /// longest pattern it matches.
+    #[serde(default)]
+    pub indexes: BTreeMap<String, RateLimits>,
+}
+
+impl RateLimitConfig {
+    pub fn is_enabled(&self) -> bool {
+        !self.principals.is_empty() || !self.indexes.is_empty()
+    }
+
+    pub(crate) fn validate(&self) -> anyhow::Result<()> {
+        for index_id_pattern in self.indexes.keys() {
+            if index_id_pattern.is_empty()
+                || !index_id_pattern
+                    .chars()
+                    .all(|ch| ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '*'))
+            {
+                bail!(
+                    "Rate limit index ID pattern `{index_id_pattern}` is invalid. Patterns may \
+                     only contain alphanumeric characters, `-`, `_`, and `*`."
+                );
+            }
+        }
+        let all_limits = self.principals.values().chain(self.indexes.values());
+
+        for limits in all_limits {
+            if let Some(ingest_bytes_per_sec) = limits.ingest_bytes_per_sec {
+                if ingest_bytes_per_sec.get_bytes() == 0 {
+                    bail!("Rate limit `ingest_bytes_per_sec` must be strictly positive.");
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Compression of the REST API responses, negotiated with the `Accept-Encoding` header of the
+/// requests.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RestCompressionConfig {
+    /// Whether responses may be compressed with gzip.
+    #[serde(default = "RestCompressionConfig::default_gzip")]
+    pub gzip: bool,
+    /// Whether responses may be compressed with deflate.
+    #[serde(default)]
+    pub deflate: bool,
+    /// Minimum size of the response bodies to compress. Smaller bodies are sent uncompressed.
+    #[serde(default = "RestCompressionConfig::default_min_response_size")]
+    pub min_response_size: Byte,
+}
+
+impl RestCompressionConfig {
+    fn default_gzip() -> bool {
+        true
+    }
+
+    fn default_min_response_size() -> Byte {
+        Byte::from_bytes(10 * 1024) // 10KiB
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        if self.min_response_size.get_bytes() > u16::MAX as u128 {
+            bail!(
+                "REST compression `min_response_size` must not exceed {} bytes.",
+                u16::MAX
+            );
+        }
+        Ok(())
+    }
+}
+
+impl Default for RestCompressionConfig {
+    fn default() -> Self {
+        Self {
+            gzip: Self::default_gzip(),
+            deflate: false,
+            min_response_size: Self::default_min_response_size(),
         }
     }
 }
@@ -208,15 +1138,26 @@ pub struct QuickwitConfig {
     pub grpc_listen_addr: SocketAddr,
     pub gossip_advertise_addr: SocketAddr,
     pub grpc_advertise_addr: SocketAddr,
+    pub gossip_transport: GossipTransport,
+    pub grpc_tls_config: Option<GrpcTlsConfig>,
+    pub availability_zone_opt: Option<String>,
     pub peer_seeds: Vec<String>,
     pub data_dir_path: PathBuf,
     pub metastore_uri: Uri,
+    pub metastore_config: MetastoreConfig,
     pub default_index_root_uri: Uri,
     pub rest_cors_allow_origins: Vec<String>,
+    pub rest_cors_allow_headers: Vec<String>,
+    pub rest_compression_config: RestCompressionConfig,
+    pub log_format: LogFormat,
     pub indexer_config: IndexerConfig,
     pub searcher_config: SearcherConfig,
     pub ingest_api_config: IngestApiConfig,
     pub jaeger_config: JaegerConfig,
+    pub janitor_config: JanitorConfig,
+    pub auth_config: AuthConfig,
+    pub audit_log_config: AuditLogConfig,
+    pub rate_limit_config: RateLimitConfig,
 }
 
 impl QuickwitConfig {
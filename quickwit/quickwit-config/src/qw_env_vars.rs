@@ -56,10 +56,13 @@ qw_env_vars!(
     QW_REST_LISTEN_PORT,
     QW_GOSSIP_LISTEN_PORT,
     QW_GRPC_LISTEN_PORT,
+    QW_GOSSIP_TRANSPORT,
     QW_PEER_SEEDS,
     QW_DATA_DIR,
     QW_METASTORE_URI,
-    QW_DEFAULT_INDEX_ROOT_URI
+    QW_DEFAULT_INDEX_ROOT_URI,
+    QW_LOG_FORMAT,
+    QW_AVAILABILITY_ZONE
 );
 
 #[cfg(test)]
@@ -19,6 +19,7 @@
 
 pub(crate) mod serialize;
 
+use std::collections::BTreeMap;
 use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
@@ -83,6 +84,11 @@ pub struct SourceConfig {
     // Denotes the input data format.
     #[serde(default)]
     pub input_format: SourceInputFormat,
+
+    /// Labels attached to every split produced by this source.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub split_labels: BTreeMap<String, String>,
 }
 
 impl SourceConfig {
@@ -124,6 +130,7 @@ impl SourceConfig {
             source_params: SourceParams::IngestApi,
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         }
     }
 
@@ -137,6 +144,7 @@ impl SourceConfig {
             source_params: SourceParams::IngestCli,
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         }
     }
 
@@ -150,6 +158,7 @@ impl SourceConfig {
             source_params,
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         }
     }
 }
@@ -172,6 +181,7 @@ impl TestableForRegression for SourceConfig {
                 timezone_opt: None,
             }),
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         }
     }
 
@@ -507,6 +517,7 @@ mod tests {
                 timezone_opt: Some("local".to_string()),
             }),
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         assert_eq!(source_config, expected_source_config);
         assert_eq!(source_config.desired_num_pipelines.get(), 2);
@@ -603,6 +614,7 @@ mod tests {
                 timezone_opt: Some("local".to_string()),
             }),
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         assert_eq!(source_config, expected_source_config);
         assert_eq!(source_config.desired_num_pipelines.get(), 1);
@@ -1006,6 +1018,7 @@ mod tests {
                 timezone_opt: None,
             }),
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         assert_eq!(source_config, expected_source_config);
         assert_eq!(source_config.desired_num_pipelines.get(), 1);
@@ -1124,4 +1137,41 @@ mod tests {
                 .unwrap();
         assert_eq!(source_config.input_format, SourceInputFormat::PlainText);
     }
+
+    #[tokio::test]
+    async fn test_source_config_split_labels() {
+        let file_content = r#"{
+            "version": "0.6",
+            "source_id": "backfill-source",
+            "source_type": "file",
+            "params": {"filepath": "/backfill.json"},
+            "split_labels": {"backfill": "2023-01", "batch_id": "42"}
+        }"#;
+        let source_config =
+            load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                .unwrap();
+        let expected_split_labels = BTreeMap::from_iter([
+            ("backfill".to_string(), "2023-01".to_string()),
+            ("batch_id".to_string(), "42".to_string()),
+        ]);
+        assert_eq!(source_config.split_labels, expected_split_labels);
+
+        let source_config_json = serde_json::to_value(&source_config).unwrap();
+        assert_eq!(
+            source_config_json["split_labels"],
+            serde_json::json!({"backfill": "2023-01", "batch_id": "42"})
+        );
+
+        let file_content = r#"{
+            "version": "0.6",
+            "source_id": "backfill-source",
+            "source_type": "file",
+            "params": {"filepath": "/backfill.json"},
+            "split_labels": {"backfill:tag": "2023-01"}
+        }"#;
+        let error =
+            load_source_config_from_user_config(ConfigFormat::Json, file_content.as_bytes())
+                .unwrap_err();
+        assert!(error.to_string().contains("Split label key `backfill:tag`"));
+    }
 }
@@ -17,6 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
 use std::num::NonZeroUsize;
 
 use anyhow::bail;
@@ -110,6 +111,17 @@ impl SourceConfigForSerialization {
         if let Some(transform_config) = &self.transform {
             transform_config.compile_vrl_script()?;
         }
+        for (label_key, label_value) in &self.split_labels {
+            if label_key.is_empty() || label_key.contains([':', ',']) {
+                bail!(
+                    "Split label key `{label_key}` is invalid: it must be non-empty and cannot \
+                     contain `:` or `,`."
+                );
+            }
+            if label_value.contains(',') {
+                bail!("Split label value `{label_value}` is invalid: it cannot contain `,`.");
+            }
+        }
         Ok(SourceConfig {
             source_id: self.source_id,
             max_num_pipelines_per_indexer,
@@ -118,6 +130,7 @@ impl SourceConfigForSerialization {
             source_params: self.source_params,
             transform_config: self.transform,
             input_format: self.input_format,
+            split_labels: self.split_labels,
         })
     }
 }
@@ -132,6 +145,7 @@ impl From<SourceConfig> for SourceConfigV0_6 {
             source_params: source_config.source_params,
             transform: source_config.transform_config,
             input_format: source_config.input_format,
+            split_labels: source_config.split_labels,
         }
     }
 }
@@ -189,4 +203,9 @@ pub struct SourceConfigV0_6 {
     // Denotes the input data format.
     #[serde(default)]
     pub input_format: SourceInputFormat,
+
+    /// Labels attached to every split produced by this source.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub split_labels: BTreeMap<String, String>,
 }
@@ -0,0 +1,183 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use quickwit_cluster::{ClusterMember, DecommissionStatus};
+
+/// Aggregate source lag, in number of messages, that a single indexer is expected to catch up on
+/// in a reasonable amount of time. Above this threshold, more indexers are desired.
+const MAX_SOURCE_LAG_PER_INDEXER: u64 = 1_000_000;
+
+/// Autoscaling signal computed by the control plane from the loads advertised by the indexers.
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct IndexerAutoscalingSignal {
+    /// Number of active indexers, i.e. excluding the indexers being decommissioned.
+    pub num_indexers: usize,
+    /// Number of indexers the cluster should run to keep up with its sources.
+    pub desired_num_indexers: usize,
+    pub num_pipelines: u64,
+    pub num_saturated_pipelines: u64,
+    pub num_idle_pipelines: u64,
+    /// Aggregate lag of the sources, in number of messages. Only Kafka sources report a lag.
+    pub source_lag: u64,
+}
+
+/// Computes the number of indexers desired by the cluster:
+/// - if some pipelines are saturated, adds indexers in proportion of the saturated pipelines;
+/// - if the aggregate source lag exceeds what the current indexers can absorb, adds indexers in
+///   proportion of the lag;
+/// - if all the pipelines are idle, removes one indexer.
+///
+/// The desired number of indexers is never lower than one.
+pub fn compute_indexer_autoscaling_signal(indexers: &[ClusterMember]) -> IndexerAutoscalingSignal {
+    let mut signal = IndexerAutoscalingSignal::default();
+
+    for indexer in indexers {
+        if indexer.decommission_status != DecommissionStatus::Active {
+            continue;
+        }
+        signal.num_indexers += 1;
+
+        if let Some(indexer_load) = &indexer.indexer_load_opt {
+            signal.num_pipelines += indexer_load.num_pipelines;
+            signal.num_saturated_pipelines += indexer_load.num_saturated_pipelines;
+            signal.num_idle_pipelines += indexer_load.num_idle_pipelines;
+            signal.source_lag += indexer_load.source_lag;
+        }
+    }
+    let num_indexers = signal.num_indexers;
+    let mut desired_num_indexers = num_indexers;
+
+    if signal.num_saturated_pipelines > 0 {
+        let num_additional_indexers = div_ceil(
+            num_indexers as u64 * signal.num_saturated_pipelines,
+            signal.num_pipelines.max(1),
+        )
+        .max(1);
+        desired_num_indexers = num_indexers + num_additional_indexers as usize;
+    }
+    if signal.source_lag > MAX_SOURCE_LAG_PER_INDEXER * num_indexers as u64 {
+        let num_indexers_for_lag = div_ceil(signal.source_lag, MAX_SOURCE_LAG_PER_INDEXER);
+        desired_num_indexers = desired_num_indexers.max(num_indexers_for_lag as usize);
+    }
+    if signal.num_pipelines > 0
+        && signal.num_idle_pipelines == signal.num_pipelines
+        && signal.source_lag == 0
+    {
+        desired_num_indexers = num_indexers.saturating_sub(1);
+    }
+    signal.desired_num_indexers = desired_num_indexers.max(1);
+    signal
+}
+
+fn div_ceil(numerator: u64, denominator: u64) -> u64 {
+    (numerator + denominator - 1) / denominator
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use quickwit_cluster::{GenerationId, IndexerLoad};
+    use quickwit_config::service::QuickwitService;
+
+    use super::*;
+
+    fn indexer_for_test(node_id: &str, indexer_load: IndexerLoad) -> ClusterMember {
+        let mut indexer = ClusterMember::new(
+            node_id.to_string(),
+            GenerationId::from(1),
+            true,
+            HashSet::from_iter([QuickwitService::Indexer]),
+            ([127, 0, 0, 1], 7280).into(),
+            ([127, 0, 0, 1], 7281).into(),
+            Vec::new(),
+        );
+        indexer.indexer_load_opt = Some(indexer_load);
+        indexer
+    }
+
+    #[test]
+    fn test_compute_indexer_autoscaling_signal() {
+        let signal = compute_indexer_autoscaling_signal(&[]);
+        assert_eq!(signal.num_indexers, 0);
+        assert_eq!(signal.desired_num_indexers, 1);
+
+        let busy_load = IndexerLoad {
+            num_pipelines: 2,
+            ..Default::default()
+        };
+        let indexers = vec![
+            indexer_for_test("indexer-1", busy_load.clone()),
+            indexer_for_test("indexer-2", busy_load.clone()),
+        ];
+        let signal = compute_indexer_autoscaling_signal(&indexers);
+        assert_eq!(signal.num_indexers, 2);
+        assert_eq!(signal.num_pipelines, 4);
+        assert_eq!(signal.desired_num_indexers, 2);
+
+        let saturated_load = IndexerLoad {
+            num_pipelines: 2,
+            num_saturated_pipelines: 2,
+            ..Default::default()
+        };
+        let indexers = vec![
+            indexer_for_test("indexer-1", busy_load.clone()),
+            indexer_for_test("indexer-2", saturated_load),
+        ];
+        let signal = compute_indexer_autoscaling_signal(&indexers);
+        assert_eq!(signal.num_saturated_pipelines, 2);
+        assert_eq!(signal.desired_num_indexers, 3);
+
+        let lagging_load = IndexerLoad {
+            num_pipelines: 2,
+            source_lag: 3 * MAX_SOURCE_LAG_PER_INDEXER + 1,
+            ..Default::default()
+        };
+        let indexers = vec![
+            indexer_for_test("indexer-1", busy_load.clone()),
+            indexer_for_test("indexer-2", lagging_load),
+        ];
+        let signal = compute_indexer_autoscaling_signal(&indexers);
+        assert_eq!(signal.source_lag, 3 * MAX_SOURCE_LAG_PER_INDEXER + 1);
+        assert_eq!(signal.desired_num_indexers, 4);
+
+        let idle_load = IndexerLoad {
+            num_pipelines: 2,
+            num_idle_pipelines: 2,
+            ..Default::default()
+        };
+        let indexers = vec![
+            indexer_for_test("indexer-1", idle_load.clone()),
+            indexer_for_test("indexer-2", idle_load.clone()),
+        ];
+        let signal = compute_indexer_autoscaling_signal(&indexers);
+        assert_eq!(signal.desired_num_indexers, 1);
+
+        let mut decommissioning_indexer = indexer_for_test("indexer-2", busy_load);
+        decommissioning_indexer.decommission_status = DecommissionStatus::Decommissioning;
+        let indexers = vec![
+            indexer_for_test("indexer-1", idle_load),
+            decommissioning_indexer,
+        ];
+        let signal = compute_indexer_autoscaling_signal(&indexers);
+        assert_eq!(signal.num_indexers, 1);
+        assert_eq!(signal.num_pipelines, 2);
+        assert_eq!(signal.desired_num_indexers, 1);
+    }
+}
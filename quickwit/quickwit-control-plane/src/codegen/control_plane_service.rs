@@ -6,6 +6,66 @@ pub struct NotifyIndexChangeRequest {}
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
 pub struct NotifyIndexChangeResponse {}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetIndexingPlanRequest {}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetIndexingPlanResponse {
+    /// / Indexing tasks assigned to each indexer by the last applied indexing plan.
+    #[prost(message, repeated, tag = "1")]
+    pub node_indexing_tasks: ::prost::alloc::vec::Vec<NodeIndexingTasks>,
+    /// / Number of indexing plans applied since the control plane started.
+    #[prost(uint64, tag = "2")]
+    pub num_applied_plans: u64,
+    /// / Number of seconds elapsed since the last indexing plan was applied. Absent if no plan was applied yet.
+    #[prost(uint64, optional, tag = "3")]
+    pub last_applied_plan_elapsed_secs: ::core::option::Option<u64>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct NodeIndexingTasks {
+    #[prost(string, tag = "1")]
+    pub node_id: ::prost::alloc::string::String,
+    #[prost(message, repeated, tag = "2")]
+    pub indexing_tasks: ::prost::alloc::vec::Vec<PlannedIndexingTask>,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PlannedIndexingTask {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub source_id: ::prost::alloc::string::String,
+}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetIndexerAutoscalingRequest {}
+#[derive(serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct GetIndexerAutoscalingResponse {
+    /// / Number of indexers the cluster should run to keep up with its sources.
+    #[prost(uint64, tag = "1")]
+    pub desired_num_indexers: u64,
+    /// / Number of active indexers, i.e. excluding the indexers being decommissioned.
+    #[prost(uint64, tag = "2")]
+    pub num_indexers: u64,
+    #[prost(uint64, tag = "3")]
+    pub num_pipelines: u64,
+    #[prost(uint64, tag = "4")]
+    pub num_saturated_pipelines: u64,
+    #[prost(uint64, tag = "5")]
+    pub num_idle_pipelines: u64,
+    /// / Aggregate lag of the sources, in number of messages.
+    #[prost(uint64, tag = "6")]
+    pub source_lag: u64,
+}
 /// BEGIN quickwit-codegen
 #[cfg_attr(any(test, feature = "testsuite"), mockall::automock)]
 #[async_trait::async_trait]
@@ -14,6 +74,14 @@ pub trait ControlPlaneService: std::fmt::Debug + dyn_clone::DynClone + Send + Sy
         &mut self,
         request: NotifyIndexChangeRequest,
     ) -> crate::Result<NotifyIndexChangeResponse>;
+    async fn get_indexing_plan(
+        &mut self,
+        request: GetIndexingPlanRequest,
+    ) -> crate::Result<GetIndexingPlanResponse>;
+    async fn get_indexer_autoscaling(
+        &mut self,
+        request: GetIndexerAutoscalingRequest,
+    ) -> crate::Result<GetIndexerAutoscalingResponse>;
 }
 dyn_clone::clone_trait_object!(ControlPlaneService);
 #[cfg(any(test, feature = "testsuite"))]
@@ -80,6 +148,18 @@ impl ControlPlaneService for ControlPlaneServiceClient {
     ) -> crate::Result<NotifyIndexChangeResponse> {
         self.inner.notify_index_change(request).await
     }
+    async fn get_indexing_plan(
+        &mut self,
+        request: GetIndexingPlanRequest,
+    ) -> crate::Result<GetIndexingPlanResponse> {
+        self.inner.get_indexing_plan(request).await
+    }
+    async fn get_indexer_autoscaling(
+        &mut self,
+        request: GetIndexerAutoscalingRequest,
+    ) -> crate::Result<GetIndexerAutoscalingResponse> {
+        self.inner.get_indexer_autoscaling(request).await
+    }
 }
 #[cfg(any(test, feature = "testsuite"))]
 impl From<MockControlPlaneService> for ControlPlaneServiceClient {
@@ -106,6 +186,38 @@ impl tower::Service<NotifyIndexChangeRequest> for Box<dyn ControlPlaneService> {
         Box::pin(fut)
     }
 }
+impl tower::Service<GetIndexingPlanRequest> for Box<dyn ControlPlaneService> {
+    type Response = GetIndexingPlanResponse;
+    type Error = crate::ControlPlaneError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: GetIndexingPlanRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.get_indexing_plan(request).await };
+        Box::pin(fut)
+    }
+}
+impl tower::Service<GetIndexerAutoscalingRequest> for Box<dyn ControlPlaneService> {
+    type Response = GetIndexerAutoscalingResponse;
+    type Error = crate::ControlPlaneError;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+    fn poll_ready(
+        &mut self,
+        _cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Result<(), Self::Error>> {
+        std::task::Poll::Ready(Ok(()))
+    }
+    fn call(&mut self, request: GetIndexerAutoscalingRequest) -> Self::Future {
+        let mut svc = self.clone();
+        let fut = async move { svc.get_indexer_autoscaling(request).await };
+        Box::pin(fut)
+    }
+}
 /// A tower block is a set of towers. Each tower is stack of layers (middlewares) that are applied to a service.
 #[derive(Debug)]
 struct ControlPlaneServiceTowerBlock {
@@ -114,11 +226,23 @@ struct ControlPlaneServiceTowerBlock {
         NotifyIndexChangeResponse,
         crate::ControlPlaneError,
     >,
+    get_indexing_plan_svc: quickwit_common::tower::BoxService<
+        GetIndexingPlanRequest,
+        GetIndexingPlanResponse,
+        crate::ControlPlaneError,
+    >,
+    get_indexer_autoscaling_svc: quickwit_common::tower::BoxService<
+        GetIndexerAutoscalingRequest,
+        GetIndexerAutoscalingResponse,
+        crate::ControlPlaneError,
+    >,
 }
 impl Clone for ControlPlaneServiceTowerBlock {
     fn clone(&self) -> Self {
         Self {
             notify_index_change_svc: self.notify_index_change_svc.clone(),
+            get_indexing_plan_svc: self.get_indexing_plan_svc.clone(),
+            get_indexer_autoscaling_svc: self.get_indexer_autoscaling_svc.clone(),
         }
     }
 }
@@ -130,6 +254,18 @@ impl ControlPlaneService for ControlPlaneServiceTowerBlock {
     ) -> crate::Result<NotifyIndexChangeResponse> {
         self.notify_index_change_svc.ready().await?.call(request).await
     }
+    async fn get_indexing_plan(
+        &mut self,
+        request: GetIndexingPlanRequest,
+    ) -> crate::Result<GetIndexingPlanResponse> {
+        self.get_indexing_plan_svc.ready().await?.call(request).await
+    }
+    async fn get_indexer_autoscaling(
+        &mut self,
+        request: GetIndexerAutoscalingRequest,
+    ) -> crate::Result<GetIndexerAutoscalingResponse> {
+        self.get_indexer_autoscaling_svc.ready().await?.call(request).await
+    }
 }
 #[derive(Debug, Default)]
 pub struct ControlPlaneServiceTowerBlockBuilder {
@@ -142,6 +278,24 @@ pub struct ControlPlaneServiceTowerBlockBuilder {
             crate::ControlPlaneError,
         >,
     >,
+    #[allow(clippy::type_complexity)]
+    get_indexing_plan_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn ControlPlaneService>,
+            GetIndexingPlanRequest,
+            GetIndexingPlanResponse,
+            crate::ControlPlaneError,
+        >,
+    >,
+    #[allow(clippy::type_complexity)]
+    get_indexer_autoscaling_layer: Option<
+        quickwit_common::tower::BoxLayer<
+            Box<dyn ControlPlaneService>,
+            GetIndexerAutoscalingRequest,
+            GetIndexerAutoscalingResponse,
+            crate::ControlPlaneError,
+        >,
+    >,
 }
 impl ControlPlaneServiceTowerBlockBuilder {
     pub fn shared_layer<L>(mut self, layer: L) -> Self
@@ -153,9 +307,29 @@ impl ControlPlaneServiceTowerBlockBuilder {
                 Error = crate::ControlPlaneError,
             > + Clone + Send + Sync + 'static,
         <L::Service as tower::Service<NotifyIndexChangeRequest>>::Future: Send + 'static,
+        L::Service: tower::Service<
+                GetIndexingPlanRequest,
+                Response = GetIndexingPlanResponse,
+                Error = crate::ControlPlaneError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<GetIndexingPlanRequest>>::Future: Send + 'static,
+        L::Service: tower::Service<
+                GetIndexerAutoscalingRequest,
+                Response = GetIndexerAutoscalingResponse,
+                Error = crate::ControlPlaneError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<GetIndexerAutoscalingRequest>>::Future: Send + 'static,
     {
         self
             .notify_index_change_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
+        self
+            .get_indexing_plan_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer.clone()),
+        );
+        self
+            .get_indexer_autoscaling_layer = Some(
             quickwit_common::tower::BoxLayer::new(layer),
         );
         self
@@ -176,6 +350,35 @@ impl ControlPlaneServiceTowerBlockBuilder {
         );
         self
     }
+    pub fn get_indexing_plan_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn ControlPlaneService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                GetIndexingPlanRequest,
+                Response = GetIndexingPlanResponse,
+                Error = crate::ControlPlaneError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<GetIndexingPlanRequest>>::Future: Send + 'static,
+    {
+        self.get_indexing_plan_layer = Some(quickwit_common::tower::BoxLayer::new(layer));
+        self
+    }
+    pub fn get_indexer_autoscaling_layer<L>(mut self, layer: L) -> Self
+    where
+        L: tower::Layer<Box<dyn ControlPlaneService>> + Send + Sync + 'static,
+        L::Service: tower::Service<
+                GetIndexerAutoscalingRequest,
+                Response = GetIndexerAutoscalingResponse,
+                Error = crate::ControlPlaneError,
+            > + Clone + Send + Sync + 'static,
+        <L::Service as tower::Service<GetIndexerAutoscalingRequest>>::Future: Send + 'static,
+    {
+        self
+            .get_indexer_autoscaling_layer = Some(
+            quickwit_common::tower::BoxLayer::new(layer),
+        );
+        self
+    }
     pub fn build<T>(self, instance: T) -> ControlPlaneServiceClient
     where
         T: ControlPlaneService,
@@ -220,8 +423,22 @@ impl ControlPlaneServiceTowerBlockBuilder {
         } else {
             quickwit_common::tower::BoxService::new(boxed_instance.clone())
         };
+        let get_indexing_plan_svc = if let Some(layer) = self.get_indexing_plan_layer {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
+        let get_indexer_autoscaling_svc = if let Some(layer) = self
+            .get_indexer_autoscaling_layer
+        {
+            layer.layer(boxed_instance.clone())
+        } else {
+            quickwit_common::tower::BoxService::new(boxed_instance.clone())
+        };
         let tower_block = ControlPlaneServiceTowerBlock {
             notify_index_change_svc,
+            get_indexing_plan_svc,
+            get_indexer_autoscaling_svc,
         };
         ControlPlaneServiceClient::new(tower_block)
     }
@@ -300,11 +517,23 @@ where
     ControlPlaneServiceMailbox<
         A,
     >: tower::Service<
-        NotifyIndexChangeRequest,
-        Response = NotifyIndexChangeResponse,
-        Error = crate::ControlPlaneError,
-        Future = BoxFuture<NotifyIndexChangeResponse, crate::ControlPlaneError>,
-    >,
+            NotifyIndexChangeRequest,
+            Response = NotifyIndexChangeResponse,
+            Error = crate::ControlPlaneError,
+            Future = BoxFuture<NotifyIndexChangeResponse, crate::ControlPlaneError>,
+        >
+        + tower::Service<
+            GetIndexingPlanRequest,
+            Response = GetIndexingPlanResponse,
+            Error = crate::ControlPlaneError,
+            Future = BoxFuture<GetIndexingPlanResponse, crate::ControlPlaneError>,
+        >
+        + tower::Service<
+            GetIndexerAutoscalingRequest,
+            Response = GetIndexerAutoscalingResponse,
+            Error = crate::ControlPlaneError,
+            Future = BoxFuture<GetIndexerAutoscalingResponse, crate::ControlPlaneError>,
+        >,
 {
     async fn notify_index_change(
         &mut self,
@@ -312,6 +541,18 @@ where
     ) -> crate::Result<NotifyIndexChangeResponse> {
         self.call(request).await
     }
+    async fn get_indexing_plan(
+        &mut self,
+        request: GetIndexingPlanRequest,
+    ) -> crate::Result<GetIndexingPlanResponse> {
+        self.call(request).await
+    }
+    async fn get_indexer_autoscaling(
+        &mut self,
+        request: GetIndexerAutoscalingRequest,
+    ) -> crate::Result<GetIndexerAutoscalingResponse> {
+        self.call(request).await
+    }
 }
 #[derive(Debug, Clone)]
 pub struct ControlPlaneServiceGrpcClientAdapter<T> {
@@ -345,6 +586,26 @@ where
             .map(|response| response.into_inner())
             .map_err(|error| error.into())
     }
+    async fn get_indexing_plan(
+        &mut self,
+        request: GetIndexingPlanRequest,
+    ) -> crate::Result<GetIndexingPlanResponse> {
+        self.inner
+            .get_indexing_plan(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
+    async fn get_indexer_autoscaling(
+        &mut self,
+        request: GetIndexerAutoscalingRequest,
+    ) -> crate::Result<GetIndexerAutoscalingResponse> {
+        self.inner
+            .get_indexer_autoscaling(request)
+            .await
+            .map(|response| response.into_inner())
+            .map_err(|error| error.into())
+    }
 }
 #[derive(Debug)]
 pub struct ControlPlaneServiceGrpcServerAdapter {
@@ -372,6 +633,28 @@ for ControlPlaneServiceGrpcServerAdapter {
             .map(tonic::Response::new)
             .map_err(|error| error.into())
     }
+    async fn get_indexing_plan(
+        &self,
+        request: tonic::Request<GetIndexingPlanRequest>,
+    ) -> Result<tonic::Response<GetIndexingPlanResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .get_indexing_plan(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
+    async fn get_indexer_autoscaling(
+        &self,
+        request: tonic::Request<GetIndexerAutoscalingRequest>,
+    ) -> Result<tonic::Response<GetIndexerAutoscalingResponse>, tonic::Status> {
+        self.inner
+            .clone()
+            .get_indexer_autoscaling(request.into_inner())
+            .await
+            .map(tonic::Response::new)
+            .map_err(|error| error.into())
+    }
 }
 /// Generated client implementations.
 pub mod control_plane_service_grpc_client {
@@ -469,6 +752,46 @@ pub mod control_plane_service_grpc_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// / Returns the indexing plan last applied by the Control Plane.
+        pub async fn get_indexing_plan(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetIndexingPlanRequest>,
+        ) -> Result<tonic::Response<super::GetIndexingPlanResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/control_plane_service.ControlPlaneService/getIndexingPlan",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// / Returns the autoscaling signal computed by the Control Plane for the indexers.
+        pub async fn get_indexer_autoscaling(
+            &mut self,
+            request: impl tonic::IntoRequest<super::GetIndexerAutoscalingRequest>,
+        ) -> Result<tonic::Response<super::GetIndexerAutoscalingResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/control_plane_service.ControlPlaneService/getIndexerAutoscaling",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
     }
 }
 /// Generated server implementations.
@@ -488,6 +811,16 @@ pub mod control_plane_service_grpc_server {
             &self,
             request: tonic::Request<super::NotifyIndexChangeRequest>,
         ) -> Result<tonic::Response<super::NotifyIndexChangeResponse>, tonic::Status>;
+        /// / Returns the indexing plan last applied by the Control Plane.
+        async fn get_indexing_plan(
+            &self,
+            request: tonic::Request<super::GetIndexingPlanRequest>,
+        ) -> Result<tonic::Response<super::GetIndexingPlanResponse>, tonic::Status>;
+        /// / Returns the autoscaling signal computed by the Control Plane for the indexers.
+        async fn get_indexer_autoscaling(
+            &self,
+            request: tonic::Request<super::GetIndexerAutoscalingRequest>,
+        ) -> Result<tonic::Response<super::GetIndexerAutoscalingResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct ControlPlaneServiceGrpcServer<T: ControlPlaneServiceGrpc> {
@@ -589,6 +922,86 @@ pub mod control_plane_service_grpc_server {
                     };
                     Box::pin(fut)
                 }
+                "/control_plane_service.ControlPlaneService/getIndexingPlan" => {
+                    #[allow(non_camel_case_types)]
+                    struct getIndexingPlanSvc<T: ControlPlaneServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: ControlPlaneServiceGrpc,
+                    > tonic::server::UnaryService<super::GetIndexingPlanRequest>
+                    for getIndexingPlanSvc<T> {
+                        type Response = super::GetIndexingPlanResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetIndexingPlanRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).get_indexing_plan(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = getIndexingPlanSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/control_plane_service.ControlPlaneService/getIndexerAutoscaling" => {
+                    #[allow(non_camel_case_types)]
+                    struct getIndexerAutoscalingSvc<T: ControlPlaneServiceGrpc>(pub Arc<T>);
+                    impl<
+                        T: ControlPlaneServiceGrpc,
+                    > tonic::server::UnaryService<super::GetIndexerAutoscalingRequest>
+                    for getIndexerAutoscalingSvc<T> {
+                        type Response = super::GetIndexerAutoscalingResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::GetIndexerAutoscalingRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).get_indexer_autoscaling(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = getIndexerAutoscalingSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
@@ -131,7 +131,9 @@ impl PhysicalIndexingPlan {
 ///    deterministic too). This is not bullet proof as the node score has an impact
 ///    on the assignment too.
 /// 2. Select node candidates that can run the task, see [`select_node_candidates`]
-///    function.
+///    function. Among them, keep the nodes located in the availability zones running
+///    the fewest tasks of the same (index_id, source_id), see
+///    [`select_least_loaded_zone_candidates`] function.
 /// 3. For each node, compute a score for this task, the higher, the better, see
 ///    `compute_node_score` function.
 /// 4. Select the best node (highest score) and assign the task to
@@ -152,6 +154,13 @@ pub(crate) fn build_physical_indexing_plan(
         .iter()
         .map(|indexer| indexer.node_id.to_string())
         .collect_vec();
+    let availability_zones: HashMap<&str, &str> = indexers
+        .iter()
+        .filter_map(|indexer| {
+            let availability_zone = indexer.availability_zone_opt.as_deref()?;
+            Some((indexer.node_id.as_str(), availability_zone))
+        })
+        .collect();
 
     // Build the plan.
     let mut plan = PhysicalIndexingPlan::new(node_ids.clone());
@@ -162,6 +171,12 @@ pub(crate) fn build_physical_indexing_plan(
             .get(&IndexSourceId::from(indexing_task.clone()))
             .expect("SourceConfig should always be present.");
         let candidates = select_node_candidates(&node_ids, &plan, source_config, &indexing_task);
+        let candidates = select_least_loaded_zone_candidates(
+            candidates,
+            &availability_zones,
+            &plan,
+            &indexing_task,
+        );
 
         // It's theoretically possible to have no candidate as all indexers can already
         // have more than `max_num_pipelines_per_indexer` assigned for a given source.
@@ -234,6 +249,42 @@ fn select_node_candidates<'a>(
         .collect_vec()
 }
 
+/// Returns the candidates located in the availability zones running the fewest tasks of the
+/// given (index ID, source ID), so that the pipelines of a source are spread across zones.
+/// Nodes without availability zone are considered to be in the same zone.
+fn select_least_loaded_zone_candidates<'a>(
+    candidates: Vec<&'a str>,
+    availability_zones: &HashMap<&str, &str>,
+    physical_plan: &PhysicalIndexingPlan,
+    indexing_task: &IndexingTask,
+) -> Vec<&'a str> {
+    let mut num_tasks_per_zone: HashMap<Option<&str>, usize> = HashMap::new();
+    for node_id in physical_plan.indexing_tasks_per_node().keys() {
+        let availability_zone_opt = availability_zones.get(node_id.as_str()).copied();
+        *num_tasks_per_zone.entry(availability_zone_opt).or_default() += physical_plan
+            .num_indexing_tasks_for(node_id, &indexing_task.index_uid, &indexing_task.source_id);
+    }
+    let num_tasks_in_zone = |node_id: &str| {
+        let availability_zone_opt = availability_zones.get(node_id).copied();
+        num_tasks_per_zone
+            .get(&availability_zone_opt)
+            .copied()
+            .unwrap_or(0)
+    };
+    let min_num_tasks_in_zone = match candidates
+        .iter()
+        .map(|node_id| num_tasks_in_zone(node_id))
+        .min()
+    {
+        Some(min_num_tasks_in_zone) => min_num_tasks_in_zone,
+        None => return candidates,
+    };
+    candidates
+        .into_iter()
+        .filter(|node_id| num_tasks_in_zone(node_id) == min_num_tasks_in_zone)
+        .collect_vec()
+}
+
 /// Lame scoring function for a given node ID defined by
 /// `score = indexing_tasks_per_node_mean - num_indexing_tasks_for_given_node`.
 /// The lower the number of tasks, the higher the score will be.
@@ -312,7 +363,7 @@ pub(crate) fn build_indexing_plan(
 
 #[cfg(test)]
 mod tests {
-    use std::collections::{HashMap, HashSet};
+    use std::collections::{BTreeMap, HashMap, HashSet};
     use std::net::SocketAddr;
     use std::num::NonZeroUsize;
 
@@ -397,6 +448,7 @@ mod tests {
                 source_params: kafka_source_params_for_test(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             },
         );
 
@@ -432,6 +484,7 @@ mod tests {
                 source_params: SourceParams::IngestApi,
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             },
         );
 
@@ -475,6 +528,7 @@ mod tests {
                 source_params: SourceParams::File(FileSourceParams { filepath: None }),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             },
         );
         source_configs_map.insert(
@@ -487,6 +541,7 @@ mod tests {
                 source_params: SourceParams::IngestCli,
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             },
         );
         source_configs_map.insert(
@@ -499,6 +554,7 @@ mod tests {
                 source_params: kafka_source_params_for_test(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             },
         );
         let indexing_tasks = build_indexing_plan(&indexers, &source_configs_map);
@@ -534,6 +590,7 @@ mod tests {
                 source_params: kafka_source_params_for_test(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             },
         );
         source_configs_map.insert(
@@ -546,6 +603,7 @@ mod tests {
                 source_params: kafka_source_params_for_test(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             },
         );
         let mut indexing_tasks = Vec::new();
@@ -616,6 +674,7 @@ mod tests {
                 source_params: kafka_source_params_for_test(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             },
         );
         let indexing_tasks = vec![
@@ -637,6 +696,64 @@ mod tests {
         assert_eq!(physical_plan.num_indexing_tasks(), 1);
     }
 
+    #[test]
+    fn test_build_physical_indexing_plan_spreads_sources_across_availability_zones() {
+        let mut source_configs_map = HashMap::new();
+        let mut indexing_tasks = Vec::new();
+        for index_ord in 0..10 {
+            let index_source_id = IndexSourceId {
+                index_uid: IndexUid::from_parts(format!("index-{index_ord}"), "1111111111111"),
+                source_id: "source".to_string(),
+            };
+            for _ in 0..2 {
+                indexing_tasks.push(IndexingTask {
+                    index_uid: index_source_id.index_uid.to_string(),
+                    source_id: index_source_id.source_id.clone(),
+                });
+            }
+            source_configs_map.insert(
+                index_source_id.clone(),
+                SourceConfig {
+                    source_id: index_source_id.source_id,
+                    max_num_pipelines_per_indexer: NonZeroUsize::new(2).unwrap(),
+                    desired_num_pipelines: NonZeroUsize::new(2).unwrap(),
+                    enabled: true,
+                    source_params: kafka_source_params_for_test(),
+                    transform_config: None,
+                    input_format: SourceInputFormat::Json,
+                    split_labels: BTreeMap::new(),
+                },
+            );
+        }
+        let mut indexers = cluster_members_for_test(4, QuickwitService::Indexer);
+        for (indexer_ord, indexer) in indexers.iter_mut().enumerate() {
+            let availability_zone = if indexer_ord < 3 {
+                "us-east-1a"
+            } else {
+                "us-east-1b"
+            };
+            indexer.availability_zone_opt = Some(availability_zone.to_string());
+        }
+        let physical_plan =
+            build_physical_indexing_plan(&indexers, &source_configs_map, indexing_tasks.clone());
+        assert_eq!(physical_plan.num_indexing_tasks(), indexing_tasks.len());
+
+        // The two pipelines of each index run in distinct zones.
+        let indexer_zone_b_tasks = physical_plan
+            .indexing_tasks_per_node()
+            .get(&indexers[3].node_id)
+            .unwrap();
+        for index_ord in 0..10 {
+            let index_uid =
+                IndexUid::from_parts(format!("index-{index_ord}"), "1111111111111").to_string();
+            let num_tasks_in_zone_b = indexer_zone_b_tasks
+                .iter()
+                .filter(|task| task.index_uid == index_uid)
+                .count();
+            assert_eq!(num_tasks_in_zone_b, 1);
+        }
+    }
+
     proptest! {
         #[test]
         fn test_building_indexing_tasks_and_physical_plan(num_indexers in 1usize..50usize, index_id_sources in proptest::collection::vec(gen_kafka_source(), 1..20)) {
@@ -678,6 +795,7 @@ mod tests {
               source_params: kafka_source_params_for_test(),
               transform_config: None,
               input_format: SourceInputFormat::Json,
+              split_labels: BTreeMap::new(),
           })
       }
     }
@@ -0,0 +1,167 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+
+use quickwit_cluster::{Cluster, ClusterChange, ClusterMember, ClusterNode};
+use quickwit_common::tower::{BalanceChannel, Change};
+use quickwit_config::service::QuickwitService;
+use tokio_stream::StreamExt;
+use tracing::info;
+
+/// Key ordering the control plane candidates: the candidate with the smallest key is the leader.
+/// The node that started first wins, so that a node joining the cluster does not take over the
+/// leadership. Ties are broken by node ID.
+type LeadershipKey = (u64, String);
+
+fn leadership_key(generation_id: u64, node_id: &str) -> LeadershipKey {
+    (generation_id, node_id.to_string())
+}
+
+fn member_leadership_key(member: &ClusterMember) -> LeadershipKey {
+    leadership_key(member.generation_id.as_u64(), &member.node_id)
+}
+
+fn node_leadership_key(node: &ClusterNode) -> LeadershipKey {
+    let chitchat_id = node.chitchat_id();
+    leadership_key(chitchat_id.generation_id, &chitchat_id.node_id)
+}
+
+/// Elects the control plane leader among the members of the cluster: the ready member running the
+/// control plane service that started first. Every node applies the same rule to the same
+/// Chitchat state, so the nodes agree on the leader without exchanging messages, and a new leader
+/// is elected as soon as the failure detector evicts the current one.
+pub fn elect_leader(members: &[ClusterMember]) -> Option<&ClusterMember> {
+    members
+        .iter()
+        .filter(|member| {
+            member.is_ready
+                && member
+                    .enabled_services
+                    .contains(&QuickwitService::ControlPlane)
+        })
+        .min_by_key(|member| member_leadership_key(member))
+}
+
+/// Returns a channel to the control plane leader, which follows the leader as it changes.
+pub async fn leader_channel(cluster: &Cluster) -> BalanceChannel<SocketAddr> {
+    let (channel, change_tx) = BalanceChannel::new();
+    let mut cluster_change_stream = cluster.ready_nodes_change_stream().await;
+
+    tokio::spawn(async move {
+        let mut candidates: BTreeMap<LeadershipKey, ClusterNode> = BTreeMap::new();
+        let mut leader_addr_opt: Option<SocketAddr> = None;
+
+        while let Some(cluster_change) = cluster_change_stream.next().await {
+            match cluster_change {
+                ClusterChange::Add(node)
+                    if node
+                        .enabled_services()
+                        .contains(&QuickwitService::ControlPlane) =>
+                {
+                    candidates.insert(node_leadership_key(&node), node);
+                }
+                ClusterChange::Remove(node) => {
+                    candidates.remove(&node_leadership_key(&node));
+                }
+                _ => continue,
+            }
+            let new_leader_opt = candidates.values().next();
+            let new_leader_addr_opt = new_leader_opt.map(|leader| leader.grpc_advertise_addr());
+
+            if new_leader_addr_opt == leader_addr_opt {
+                continue;
+            }
+            if let Some(leader_addr) = leader_addr_opt.take() {
+                if change_tx.send(Change::Remove(leader_addr)).is_err() {
+                    return;
+                }
+            }
+            if let Some(new_leader) = new_leader_opt {
+                info!(leader_node_id=%new_leader.node_id(), "New control plane leader elected.");
+
+                let new_leader_addr = new_leader.grpc_advertise_addr();
+                if change_tx
+                    .send(Change::Insert(new_leader_addr, new_leader.channel()))
+                    .is_err()
+                {
+                    return;
+                }
+                leader_addr_opt = Some(new_leader_addr);
+            }
+        }
+    });
+    channel
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use quickwit_cluster::GenerationId;
+
+    use super::*;
+
+    fn member_for_test(
+        node_id: &str,
+        generation_id: u64,
+        is_ready: bool,
+        enabled_services: &[QuickwitService],
+    ) -> ClusterMember {
+        ClusterMember::new(
+            node_id.to_string(),
+            GenerationId::from(generation_id),
+            is_ready,
+            HashSet::from_iter(enabled_services.iter().copied()),
+            ([127, 0, 0, 1], 7280).into(),
+            ([127, 0, 0, 1], 7281).into(),
+            Vec::new(),
+        )
+    }
+
+    #[test]
+    fn test_elect_leader() {
+        assert!(elect_leader(&[]).is_none());
+
+        let members = vec![
+            member_for_test("indexer", 1, true, &[QuickwitService::Indexer]),
+            member_for_test("node-3", 3, true, &[QuickwitService::ControlPlane]),
+            member_for_test("node-2", 2, true, &[QuickwitService::ControlPlane]),
+            member_for_test("node-1", 1, false, &[QuickwitService::ControlPlane]),
+        ];
+        // The oldest ready control plane is elected.
+        assert_eq!(elect_leader(&members).unwrap().node_id, "node-2");
+
+        let members = vec![
+            member_for_test("node-b", 1, true, &[QuickwitService::ControlPlane]),
+            member_for_test("node-a", 1, true, &[QuickwitService::ControlPlane]),
+        ];
+        // Ties are broken by node ID.
+        assert_eq!(elect_leader(&members).unwrap().node_id, "node-a");
+
+        let members = vec![member_for_test(
+            "indexer",
+            1,
+            true,
+            &[QuickwitService::Indexer],
+        )];
+        assert!(elect_leader(&members).is_none());
+    }
+}
@@ -17,9 +17,12 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+pub mod autoscaling;
 #[path = "codegen/control_plane_service.rs"]
 mod control_plane_service;
 pub mod indexing_plan;
+pub mod leader_election;
+pub mod metrics;
 pub mod scheduler;
 
 use std::sync::Arc;
@@ -32,11 +35,16 @@ use quickwit_common::pubsub::EventSubscriber;
 use quickwit_config::SourceParams;
 use quickwit_grpc_clients::service_client_pool::ServiceClientPool;
 use quickwit_metastore::{Metastore, MetastoreEvent};
+use quickwit_proto::{ServiceError, ServiceErrorCode};
 use scheduler::IndexingScheduler;
 use tracing::error;
 
 pub type Result<T> = std::result::Result<T, ControlPlaneError>;
 
+/// Encoded file descriptor set of the control plane service proto.
+pub const FILE_DESCRIPTOR_SET: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/control_plane_descriptor.bin"));
+
 #[derive(Debug, Clone, thiserror::Error)]
 pub enum ControlPlaneError {
     #[error("An internal error occurred: {0}.")]
@@ -54,6 +62,15 @@ impl From<ControlPlaneError> for tonic::Status {
     }
 }
 
+impl ServiceError for ControlPlaneError {
+    fn status_code(&self) -> ServiceErrorCode {
+        match self {
+            ControlPlaneError::Internal(_) => ServiceErrorCode::Internal,
+            ControlPlaneError::Unavailable(_) => ServiceErrorCode::Unavailable,
+        }
+    }
+}
+
 impl From<tonic::Status> for ControlPlaneError {
     fn from(status: tonic::Status) -> Self {
         match status.code() {
@@ -75,6 +92,7 @@ impl From<AskError<ControlPlaneError>> for ControlPlaneError {
             AskError::ProcessMessageError => ControlPlaneError::Internal(
                 "An error occurred while processing the request".to_string(),
             ),
+            AskError::Timeout => ControlPlaneError::Unavailable("Request timed out".to_string()),
         }
     }
 }
@@ -94,7 +112,7 @@ pub async fn start_control_plane_service(
 }
 
 /// Notify the control plane when one of the following event occurs:
-/// - an index is deleted.
+/// - an index is deleted, trashed, or restored.
 /// - a source, other than the ingest CLI source, is created.
 /// - a source is deleted.
 /// Note: we don't need to send an event to the control plane on index creation.
@@ -108,6 +126,8 @@ impl EventSubscriber<MetastoreEvent> for ControlPlaneServiceClient {
     async fn handle_event(&mut self, event: MetastoreEvent) {
         let event = match event {
             MetastoreEvent::DeleteIndex { .. } => "delete-index",
+            MetastoreEvent::TrashIndex { .. } => "trash-index",
+            MetastoreEvent::RestoreIndex { .. } => "restore-index",
             MetastoreEvent::AddSource { source_config, .. } => {
                 if matches!(
                     source_config.source_params,
@@ -0,0 +1,59 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use once_cell::sync::Lazy;
+use quickwit_common::metrics::{new_gauge, IntGauge};
+
+pub struct ControlPlaneMetrics {
+    pub indexers: IntGauge,
+    pub desired_indexers: IntGauge,
+    pub saturated_pipelines: IntGauge,
+    pub source_lag: IntGauge,
+}
+
+impl Default for ControlPlaneMetrics {
+    fn default() -> Self {
+        ControlPlaneMetrics {
+            indexers: new_gauge(
+                "indexers",
+                "Number of active indexers in the cluster",
+                "quickwit_control_plane",
+            ),
+            desired_indexers: new_gauge(
+                "desired_indexers",
+                "Number of indexers the cluster should run to keep up with its sources",
+                "quickwit_control_plane",
+            ),
+            saturated_pipelines: new_gauge(
+                "saturated_pipelines",
+                "Number of indexing pipelines that cannot keep up with their source",
+                "quickwit_control_plane",
+            ),
+            source_lag: new_gauge(
+                "source_lag",
+                "Aggregate number of messages not yet consumed by the indexing pipelines",
+                "quickwit_control_plane",
+            ),
+        }
+    }
+}
+
+/// `CONTROL_PLANE_METRICS` exposes control plane related metrics through a prometheus endpoint.
+pub static CONTROL_PLANE_METRICS: Lazy<ControlPlaneMetrics> =
+    Lazy::new(ControlPlaneMetrics::default);
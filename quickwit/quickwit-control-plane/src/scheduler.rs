@@ -37,10 +37,17 @@ use quickwit_proto::indexing_api::{ApplyIndexingPlanRequest, IndexingTask};
 use serde::Serialize;
 use tracing::{debug, error, info, warn};
 
+use crate::autoscaling::compute_indexer_autoscaling_signal;
 use crate::indexing_plan::{
     build_indexing_plan, build_physical_indexing_plan, IndexSourceId, PhysicalIndexingPlan,
 };
-use crate::{NotifyIndexChangeRequest, NotifyIndexChangeResponse};
+use crate::leader_election::elect_leader;
+use crate::metrics::CONTROL_PLANE_METRICS;
+use crate::{
+    GetIndexerAutoscalingRequest, GetIndexerAutoscalingResponse, GetIndexingPlanRequest,
+    GetIndexingPlanResponse, NodeIndexingTasks, NotifyIndexChangeRequest,
+    NotifyIndexChangeResponse, PlannedIndexingTask,
+};
 
 const REFRESH_PLAN_LOOP_INTERVAL: Duration = if cfg!(any(test, feature = "testsuite")) {
     Duration::from_secs(3)
@@ -56,6 +63,8 @@ const MIN_DURATION_BETWEEN_SCHEDULING: Duration = if cfg!(any(test, feature = "t
 
 #[derive(Debug, Clone, Default, Serialize)]
 pub struct IndexingSchedulerState {
+    /// Whether the control plane of the node is the leader of the cluster.
+    pub is_leader: bool,
     pub num_applied_physical_indexing_plan: usize,
     pub num_schedule_indexing_plan: usize,
     pub last_applied_physical_plan: Option<PhysicalIndexingPlan>,
@@ -97,6 +106,10 @@ pub struct IndexingSchedulerState {
 /// Finally, in order to give the time for each indexer to run their indexing tasks, the control
 /// phase will wait at least [`MIN_DURATION_BETWEEN_SCHEDULING`] before comparing the desired
 /// plan with the running plan.
+///
+/// Several nodes can run a control plane: only the leader, see [`elect_leader`], schedules
+/// indexing plans, the others stand by. Since the control loop runs every [`HEARTBEAT`], a standby
+/// control plane takes over shortly after the failure detector evicts the leader.
 pub struct IndexingScheduler {
     cluster: Cluster,
     metastore: Arc<dyn Metastore>,
@@ -151,7 +164,37 @@ impl IndexingScheduler {
         }
     }
 
+    /// Checks whether the control plane of the node is the leader of the cluster. A control plane
+    /// losing the leadership forgets its last applied plan, so that it schedules a new plan as soon
+    /// as it becomes the leader again.
+    async fn check_leadership(&mut self) -> bool {
+        let ready_members = self.cluster.ready_members().await;
+        let leader_opt = elect_leader(&ready_members);
+        let is_leader = leader_opt
+            .map(|leader| leader.chitchat_id() == *self.cluster.self_chitchat_id())
+            .unwrap_or(false);
+
+        if is_leader != self.state.is_leader {
+            if is_leader {
+                info!("Control plane is now the leader of the cluster.");
+            } else {
+                info!(
+                    leader_node_id=?leader_opt.map(|leader| &leader.node_id),
+                    "Control plane is no longer the leader of the cluster."
+                );
+                self.state.last_applied_physical_plan = None;
+                self.state.last_applied_plan_timestamp = None;
+            }
+            self.state.is_leader = is_leader;
+        }
+        is_leader
+    }
+
     async fn schedule_indexing_plan_if_needed(&mut self) -> anyhow::Result<()> {
+        if !self.check_leadership().await {
+            debug!("Control plane is not the leader: skip scheduling.");
+            return Ok(());
+        }
         let indexers: Vec<ClusterMember> = self.get_indexers_from_cluster_state().await;
         if indexers.is_empty() {
             warn!("No indexer available, cannot schedule an indexing plan.");
@@ -205,6 +248,9 @@ impl IndexingScheduler {
     /// - If node IDs differ, schedule a new indexing plan.
     /// - If indexing tasks differ, apply again the last plan.
     async fn control_running_plan(&mut self) -> anyhow::Result<()> {
+        if !self.check_leadership().await {
+            return Ok(());
+        }
         let last_applied_plan =
             if let Some(last_applied_plan) = self.state.last_applied_physical_plan.as_ref() {
                 last_applied_plan
@@ -251,6 +297,25 @@ impl IndexingScheduler {
         Ok(())
     }
 
+    /// Publishes the autoscaling signal of the indexers as metrics, so that an autoscaler can
+    /// scrape it from the leader.
+    async fn update_autoscaling_metrics(&self) {
+        let indexers = self.get_indexers_from_cluster_state().await;
+        let signal = compute_indexer_autoscaling_signal(&indexers);
+        CONTROL_PLANE_METRICS
+            .indexers
+            .set(signal.num_indexers as i64);
+        CONTROL_PLANE_METRICS
+            .desired_indexers
+            .set(signal.desired_num_indexers as i64);
+        CONTROL_PLANE_METRICS
+            .saturated_pipelines
+            .set(signal.num_saturated_pipelines as i64);
+        CONTROL_PLANE_METRICS
+            .source_lag
+            .set(signal.source_lag as i64);
+    }
+
     async fn get_indexers_from_cluster_state(&self) -> Vec<ClusterMember> {
         self.cluster
             .ready_members()
@@ -312,6 +377,68 @@ impl Handler<NotifyIndexChangeRequest> for IndexingScheduler {
     }
 }
 
+#[async_trait]
+impl Handler<GetIndexingPlanRequest> for IndexingScheduler {
+    type Reply = crate::Result<GetIndexingPlanResponse>;
+
+    async fn handle(
+        &mut self,
+        _: GetIndexingPlanRequest,
+        _: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        let node_indexing_tasks = self
+            .state
+            .last_applied_physical_plan
+            .iter()
+            .flat_map(|physical_plan| physical_plan.indexing_tasks_per_node())
+            .sorted_by_key(|(node_id, _)| *node_id)
+            .map(|(node_id, indexing_tasks)| NodeIndexingTasks {
+                node_id: node_id.clone(),
+                indexing_tasks: indexing_tasks
+                    .iter()
+                    .map(|indexing_task| PlannedIndexingTask {
+                        index_uid: indexing_task.index_uid.clone(),
+                        source_id: indexing_task.source_id.clone(),
+                    })
+                    .collect(),
+            })
+            .collect();
+        let last_applied_plan_elapsed_secs = self
+            .state
+            .last_applied_plan_timestamp
+            .map(|timestamp| timestamp.elapsed().as_secs());
+        let get_indexing_plan_response = GetIndexingPlanResponse {
+            node_indexing_tasks,
+            num_applied_plans: self.state.num_applied_physical_indexing_plan as u64,
+            last_applied_plan_elapsed_secs,
+        };
+        Ok(Ok(get_indexing_plan_response))
+    }
+}
+
+#[async_trait]
+impl Handler<GetIndexerAutoscalingRequest> for IndexingScheduler {
+    type Reply = crate::Result<GetIndexerAutoscalingResponse>;
+
+    async fn handle(
+        &mut self,
+        _: GetIndexerAutoscalingRequest,
+        _: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        let indexers = self.get_indexers_from_cluster_state().await;
+        let signal = compute_indexer_autoscaling_signal(&indexers);
+        let get_indexer_autoscaling_response = GetIndexerAutoscalingResponse {
+            desired_num_indexers: signal.desired_num_indexers as u64,
+            num_indexers: signal.num_indexers as u64,
+            num_pipelines: signal.num_pipelines,
+            num_saturated_pipelines: signal.num_saturated_pipelines,
+            num_idle_pipelines: signal.num_idle_pipelines,
+            source_lag: signal.source_lag,
+        };
+        Ok(Ok(get_indexer_autoscaling_response))
+    }
+}
+
 #[derive(Debug)]
 struct ControlPlanLoop;
 
@@ -327,6 +454,9 @@ impl Handler<ControlPlanLoop> for IndexingScheduler {
         if let Err(error) = self.control_running_plan().await {
             error!("Error when controlling the running plan: `{}`.", error);
         }
+        if self.state.is_leader {
+            self.update_autoscaling_metrics().await;
+        }
         ctx.schedule_self_msg(HEARTBEAT, ControlPlanLoop).await;
         Ok(())
     }
@@ -514,14 +644,16 @@ fn get_indexing_tasks_diff<'a>(
 
 #[cfg(test)]
 mod tests {
-    use std::collections::{HashMap, HashSet};
+    use std::collections::{BTreeMap, HashMap, HashSet};
     use std::num::NonZeroUsize;
     use std::sync::Arc;
     use std::time::Duration;
 
     use chitchat::transport::ChannelTransport;
     use quickwit_actors::{ActorHandle, Inbox, Universe, HEARTBEAT};
-    use quickwit_cluster::{create_cluster_for_test, grpc_addr_from_listen_addr_for_test, Cluster};
+    use quickwit_cluster::{
+        create_cluster_for_test, grpc_addr_from_listen_addr_for_test, Cluster, IndexerLoad,
+    };
     use quickwit_common::test_utils::wait_until_predicate;
     use quickwit_config::service::QuickwitService;
     use quickwit_config::{KafkaSourceParams, SourceConfig, SourceInputFormat, SourceParams};
@@ -533,9 +665,11 @@ mod tests {
     use serde_json::json;
 
     use super::IndexingScheduler;
+    use crate::leader_election::elect_leader;
     use crate::scheduler::{
         get_indexing_plans_diff, MIN_DURATION_BETWEEN_SCHEDULING, REFRESH_PLAN_LOOP_INTERVAL,
     };
+    use crate::{GetIndexerAutoscalingRequest, GetIndexingPlanRequest};
 
     fn index_metadata_for_test(
         index_id: &str,
@@ -560,6 +694,7 @@ mod tests {
             }),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         index_metadata
             .sources
@@ -676,6 +811,77 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_scheduler_get_indexing_plan() {
+        quickwit_common::setup_logging_for_tests();
+        let transport = ChannelTransport::default();
+        let cluster =
+            create_cluster_for_test(Vec::new(), &["indexer", "control_plane"], &transport, true)
+                .await
+                .unwrap();
+        cluster
+            .wait_for_ready_members(|members| members.len() == 1, Duration::from_secs(5))
+            .await
+            .unwrap();
+        let universe = Universe::with_accelerated_time();
+        let (_indexing_service_inboxes, scheduler_handler) =
+            start_scheduler(cluster.clone(), &[&cluster.clone()], &universe).await;
+        scheduler_handler.process_pending_and_observe().await;
+
+        let get_indexing_plan_response = scheduler_handler
+            .mailbox()
+            .ask_for_res(GetIndexingPlanRequest {})
+            .await
+            .unwrap();
+        assert_eq!(get_indexing_plan_response.num_applied_plans, 1);
+        assert!(get_indexing_plan_response
+            .last_applied_plan_elapsed_secs
+            .is_some());
+        assert_eq!(get_indexing_plan_response.node_indexing_tasks.len(), 1);
+
+        let node_indexing_tasks = &get_indexing_plan_response.node_indexing_tasks[0];
+        assert_eq!(node_indexing_tasks.node_id, cluster.self_node_id());
+        assert_eq!(node_indexing_tasks.indexing_tasks.len(), 3);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_scheduler_get_indexer_autoscaling() {
+        quickwit_common::setup_logging_for_tests();
+        let transport = ChannelTransport::default();
+        let cluster =
+            create_cluster_for_test(Vec::new(), &["indexer", "control_plane"], &transport, true)
+                .await
+                .unwrap();
+        cluster
+            .wait_for_ready_members(|members| members.len() == 1, Duration::from_secs(5))
+            .await
+            .unwrap();
+        let indexer_load = IndexerLoad {
+            num_pipelines: 3,
+            num_saturated_pipelines: 3,
+            ..Default::default()
+        };
+        cluster
+            .update_self_node_indexer_load(&indexer_load)
+            .await
+            .unwrap();
+        let universe = Universe::with_accelerated_time();
+        let (_indexing_service_inboxes, scheduler_handler) =
+            start_scheduler(cluster.clone(), &[&cluster.clone()], &universe).await;
+
+        let get_indexer_autoscaling_response = scheduler_handler
+            .mailbox()
+            .ask_for_res(GetIndexerAutoscalingRequest {})
+            .await
+            .unwrap();
+        assert_eq!(get_indexer_autoscaling_response.num_indexers, 1);
+        assert_eq!(get_indexer_autoscaling_response.num_pipelines, 3);
+        assert_eq!(get_indexer_autoscaling_response.num_saturated_pipelines, 3);
+        assert_eq!(get_indexer_autoscaling_response.desired_num_indexers, 2);
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_scheduler_scheduling_no_indexer() {
         quickwit_common::setup_logging_for_tests();
@@ -842,6 +1048,74 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_scheduler_standby_control_plane_takes_over() {
+        quickwit_common::setup_logging_for_tests();
+        let transport = ChannelTransport::default();
+        let cluster_1 = create_cluster_for_test(Vec::new(), &["control_plane"], &transport, true)
+            .await
+            .unwrap();
+        let peer_seeds = vec![cluster_1.gossip_advertise_addr().to_string()];
+        let cluster_2 =
+            create_cluster_for_test(peer_seeds.clone(), &["control_plane"], &transport, true)
+                .await
+                .unwrap();
+        let cluster_indexer = create_cluster_for_test(peer_seeds, &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        for cluster in [&cluster_1, &cluster_2] {
+            cluster
+                .wait_for_ready_members(|members| members.len() == 3, Duration::from_secs(5))
+                .await
+                .unwrap();
+        }
+        let ready_members = cluster_1.ready_members().await;
+        let leader_node_id = elect_leader(&ready_members).unwrap().node_id.clone();
+        let (leader_cluster, standby_cluster) = if leader_node_id == cluster_1.self_node_id() {
+            (cluster_1, cluster_2)
+        } else {
+            (cluster_2, cluster_1)
+        };
+        let universe = Universe::new();
+        let (indexing_service_inboxes, scheduler_handler) =
+            start_scheduler(standby_cluster.clone(), &[&cluster_indexer], &universe).await;
+        let indexing_service_inbox = indexing_service_inboxes[0].clone();
+        let scheduler_handler_arc = Arc::new(scheduler_handler);
+
+        // The standby control plane does not schedule any plan.
+        universe.sleep(HEARTBEAT * 2).await;
+        let scheduler_state = scheduler_handler_arc.process_pending_and_observe().await;
+        assert!(!scheduler_state.is_leader);
+        assert_eq!(scheduler_state.num_applied_physical_indexing_plan, 0);
+        assert!(indexing_service_inbox
+            .drain_for_test_typed::<ApplyIndexingPlanRequest>()
+            .is_empty());
+
+        // It takes over once the leader leaves the cluster.
+        leader_cluster.shutdown().await;
+
+        wait_until_predicate(
+            || {
+                let scheduler_handler_arc_clone = scheduler_handler_arc.clone();
+                async move {
+                    let scheduler_state = scheduler_handler_arc_clone
+                        .process_pending_and_observe()
+                        .await;
+                    scheduler_state.is_leader
+                        && scheduler_state.num_applied_physical_indexing_plan == 1
+                }
+            },
+            HEARTBEAT * 10,
+            Duration::from_millis(100),
+        )
+        .await
+        .unwrap();
+        let indexing_service_inbox_messages =
+            indexing_service_inbox.drain_for_test_typed::<ApplyIndexingPlanRequest>();
+        assert_eq!(indexing_service_inbox_messages.len(), 1);
+        universe.assert_quit().await;
+    }
+
     #[test]
     fn test_indexing_plans_diff() {
         {
@@ -24,11 +24,14 @@ use std::time::Duration;
 
 use quickwit_common::fs::{empty_dir, get_cache_directory_path};
 use quickwit_common::FileEntry;
-use quickwit_config::{validate_identifier, IndexConfig, QuickwitConfig, SourceConfig};
+use quickwit_config::{
+    validate_identifier, IndexConfig, JanitorConfig, QuickwitConfig, SourceConfig,
+};
 use quickwit_indexing::actors::INDEXING_DIR_NAME;
 use quickwit_indexing::check_source_connectivity;
 use quickwit_janitor::{
-    delete_splits_with_files, run_garbage_collect, SplitDeletionError, SplitRemovalInfo,
+    delete_splits_with_files, run_garbage_collect, run_orphan_files_reconciliation,
+    OrphanFilesRemovalInfo, SplitDeletionError, SplitRemovalInfo,
 };
 use quickwit_metastore::{
     quickwit_metastore_uri_resolver, IndexMetadata, ListSplitsQuery, Metastore, MetastoreError,
@@ -75,6 +78,8 @@ impl ServiceError for IndexServiceError {
 pub struct IndexService {
     metastore: Arc<dyn Metastore>,
     storage_resolver: StorageUriResolver,
+    index_trash_enabled: bool,
+    default_staged_grace_period: Duration,
 }
 
 impl IndexService {
@@ -83,18 +88,43 @@ impl IndexService {
         Self {
             metastore,
             storage_resolver,
+            index_trash_enabled: false,
+            default_staged_grace_period: JanitorConfig::default().gc_staged_split_grace_period(),
         }
     }
 
     pub async fn from_config(config: QuickwitConfig) -> anyhow::Result<Self> {
         let metastore = quickwit_metastore_uri_resolver()
-            .resolve(&config.metastore_uri)
+            .resolve_with_config(&config.metastore_config, &config.metastore_uri)
             .await?;
         let storage_resolver = quickwit_storage_uri_resolver().clone();
-        let index_service = Self::new(metastore, storage_resolver);
+        let index_trash_enabled = config
+            .janitor_config
+            .index_trash_retention_period_opt()
+            .is_some();
+        let index_service = Self::new(metastore, storage_resolver)
+            .with_index_trash_enabled(index_trash_enabled)
+            .with_default_staged_grace_period(config.janitor_config.gc_staged_split_grace_period());
         Ok(index_service)
     }
 
+    /// When enabled, [`IndexService::delete_index`] moves indexes to the trash instead of deleting
+    /// them permanently. The janitor deletes them once the trash retention period has elapsed.
+    pub fn with_index_trash_enabled(mut self, index_trash_enabled: bool) -> Self {
+        self.index_trash_enabled = index_trash_enabled;
+        self
+    }
+
+    /// Sets the staged split grace period used by [`IndexService::garbage_collect_index`] for the
+    /// indexes that do not override it.
+    pub fn with_default_staged_grace_period(
+        mut self,
+        default_staged_grace_period: Duration,
+    ) -> Self {
+        self.default_staged_grace_period = default_staged_grace_period;
+        self
+    }
+
     pub fn metastore(&self) -> Arc<dyn Metastore> {
         self.metastore.clone()
     }
@@ -111,7 +141,10 @@ impl IndexService {
 
         // Delete existing index if it exists.
         if overwrite {
-            match self.delete_index(&index_config.index_id, false).await {
+            match self
+                .delete_index_permanently(&index_config.index_id, false)
+                .await
+            {
                 Ok(_)
                 | Err(IndexServiceError::MetastoreError(MetastoreError::IndexDoesNotExist {
                     index_id: _,
@@ -137,18 +170,123 @@ impl IndexService {
         Ok(index_metadata)
     }
 
+    /// Updates the config of the index `index_id`.
+    ///
+    /// The index ID, index URI, and doc mapping of an index cannot be updated. If
+    /// `expected_config_version_opt` is set, the update is rejected when the index config was
+    /// updated in the meantime.
+    pub async fn update_index(
+        &self,
+        index_id: &str,
+        index_config: IndexConfig,
+        expected_config_version_opt: Option<u64>,
+    ) -> Result<IndexMetadata, IndexServiceError> {
+        let index_metadata = self.metastore.index_metadata(index_id).await?;
+        let current_index_config = index_metadata.index_config();
+
+        if index_config.index_id != current_index_config.index_id {
+            return Err(IndexServiceError::OperationNotAllowed(format!(
+                "index ID cannot be updated from `{}` to `{}`",
+                current_index_config.index_id, index_config.index_id
+            )));
+        }
+        if index_config.index_uri != current_index_config.index_uri {
+            return Err(IndexServiceError::OperationNotAllowed(format!(
+                "index URI cannot be updated from `{}` to `{}`",
+                current_index_config.index_uri, index_config.index_uri
+            )));
+        }
+        if index_config.doc_mapping != current_index_config.doc_mapping {
+            return Err(IndexServiceError::OperationNotAllowed(
+                "doc mapping cannot be updated".to_string(),
+            ));
+        }
+        let index_metadata = self
+            .metastore
+            .update_index_config(
+                index_metadata.index_uid,
+                index_config,
+                expected_config_version_opt,
+            )
+            .await?;
+        info!(
+            index_id = %index_id,
+            config_version = index_metadata.config_version,
+            "Index config successfully updated."
+        );
+        Ok(index_metadata)
+    }
+
     /// Deletes the index specified with `index_id`.
+    ///
+    /// If the index trash is enabled, the index is moved to the trash: its splits are kept but it
+    /// is no longer searchable, and it can be restored with [`IndexService::restore_index`] until
+    /// the janitor deletes it permanently. In that case, no file is deleted and the returned list
+    /// is empty.
+    ///
+    /// * `index_id` - The target index Id.
+    /// * `dry_run` - Should this only return a list of affected files without performing deletion.
+    pub async fn delete_index(
+        &self,
+        index_id: &str,
+        dry_run: bool,
+    ) -> Result<Vec<FileEntry>, IndexServiceError> {
+        if !self.index_trash_enabled {
+            return self.delete_index_permanently(index_id, dry_run).await;
+        }
+        if dry_run {
+            return Ok(Vec::new());
+        }
+        let index_uid = self.metastore.index_uid(index_id).await?;
+        self.metastore.trash_index(index_uid).await?;
+        info!(index_id = %index_id, "Index moved to the trash.");
+        Ok(Vec::new())
+    }
+
+    /// Restores the index `index_id` from the trash.
+    pub async fn restore_index(&self, index_id: &str) -> Result<IndexMetadata, IndexServiceError> {
+        let trashed_index_metadata = self.trashed_index_metadata(index_id).await?;
+        let index_metadata = self
+            .metastore
+            .restore_index(trashed_index_metadata.index_uid)
+            .await?;
+        info!(index_id = %index_id, "Index restored from the trash.");
+        Ok(index_metadata)
+    }
+
+    async fn trashed_index_metadata(
+        &self,
+        index_id: &str,
+    ) -> Result<IndexMetadata, MetastoreError> {
+        self.metastore
+            .list_trashed_indexes_metadatas()
+            .await?
+            .into_iter()
+            .find(|index_metadata| index_metadata.index_id() == index_id)
+            .ok_or_else(|| MetastoreError::IndexDoesNotExist {
+                index_id: index_id.to_string(),
+            })
+    }
+
+    /// Deletes the index specified with `index_id`, bypassing the trash. Trashed indexes can be
+    /// deleted this way too.
     /// This is equivalent to running `rm -rf <index path>` for a local index or
     /// `aws s3 rm --recursive <index path>` for a remote Amazon S3 index.
     ///
     /// * `index_id` - The target index Id.
     /// * `dry_run` - Should this only return a list of affected files without performing deletion.
-    pub async fn delete_index(
+    pub async fn delete_index_permanently(
         &self,
         index_id: &str,
         dry_run: bool,
     ) -> Result<Vec<FileEntry>, IndexServiceError> {
-        let index_metadata = self.metastore.index_metadata(index_id).await?;
+        let index_metadata = match self.metastore.index_metadata(index_id).await {
+            Ok(index_metadata) => index_metadata,
+            Err(MetastoreError::IndexDoesNotExist { .. }) => {
+                self.trashed_index_metadata(index_id).await?
+            }
+            Err(error) => return Err(error.into()),
+        };
         let index_uid = index_metadata.index_uid.clone();
         let index_uri = index_metadata.into_index_config().index_uri.clone();
         let storage = self.storage_resolver.resolve(&index_uri)?;
@@ -205,18 +343,24 @@ impl IndexService {
     /// Detect all dangling splits and associated files from the index and removes them.
     ///
     /// * `index_id` - The target index Id.
-    /// * `grace_period` -  Threshold period after which a staged split can be garbage collected.
+    /// * `grace_period_opt` -  Threshold period after which a staged split can be garbage
+    ///   collected. Defaults to the staged split grace period of the index.
     /// * `dry_run` - Should this only return a list of affected files without performing deletion.
     pub async fn garbage_collect_index(
         &self,
         index_id: &str,
-        grace_period: Duration,
+        grace_period_opt: Option<Duration>,
         dry_run: bool,
-    ) -> anyhow::Result<SplitRemovalInfo> {
+    ) -> Result<SplitRemovalInfo, IndexServiceError> {
         let index_metadata = self.metastore.index_metadata(index_id).await?;
         let index_uid = index_metadata.index_uid.clone();
         let index_config = index_metadata.into_index_config();
         let storage = self.storage_resolver.resolve(&index_config.index_uri)?;
+        let grace_period = grace_period_opt.unwrap_or_else(|| {
+            index_config
+                .garbage_collection_settings
+                .staged_split_grace_period(self.default_staged_grace_period)
+        });
 
         let deleted_entries = run_garbage_collect(
             index_uid,
@@ -229,11 +373,41 @@ impl IndexService {
             dry_run,
             None,
         )
-        .await?;
+        .await
+        .map_err(|error| IndexServiceError::Internal(format!("{error:#}")))?;
 
         Ok(deleted_entries)
     }
 
+    /// Detect the split files stored under the index URI which are unknown to the metastore and
+    /// removes them.
+    ///
+    /// * `index_id` - The target index Id.
+    /// * `grace_period` -  Threshold period after which an orphan file can be removed.
+    /// * `dry_run` - Should this only return a list of orphan files without performing deletion.
+    pub async fn reconcile_orphan_files(
+        &self,
+        index_id: &str,
+        grace_period: Duration,
+        dry_run: bool,
+    ) -> anyhow::Result<OrphanFilesRemovalInfo> {
+        let index_metadata = self.metastore.index_metadata(index_id).await?;
+        let index_uid = index_metadata.index_uid.clone();
+        let index_config = index_metadata.into_index_config();
+        let storage = self.storage_resolver.resolve(&index_config.index_uri)?;
+
+        let removal_info = run_orphan_files_reconciliation(
+            index_uid,
+            storage,
+            self.metastore.clone(),
+            grace_period,
+            dry_run,
+        )
+        .await?;
+
+        Ok(removal_info)
+    }
+
     /// Clears the index by applying the following actions:
     /// - mark all splits for deletion in the metastore.
     /// - delete the files of all splits marked for deletion using garbage collection.
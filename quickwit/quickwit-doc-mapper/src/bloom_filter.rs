@@ -0,0 +1,166 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::hash::Hasher;
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use siphasher::sip128::{Hasher128, SipHasher13};
+
+/// Target false positive rate of the bloom filters.
+const FALSE_POSITIVE_RATE: f64 = 0.01;
+
+const MIN_NUM_BITS: usize = 64;
+
+const MAX_NUM_HASHES: u32 = 16;
+
+/// Bloom filter recording the values of a field in a split.
+///
+/// A bloom filter answers whether a value may be present: it never yields false negatives but may
+/// yield false positives, at a rate of about 1% when it holds the number of values it was sized
+/// for. Values are hashed with SipHash-1-3 with zero keys, so that filters remain readable across
+/// nodes and versions.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct BloomFilter {
+    num_hashes: u32,
+    /// Bits of the filter, serialized in base64.
+    #[serde(serialize_with = "serialize_bits")]
+    #[serde(deserialize_with = "deserialize_bits")]
+    #[schema(value_type = String)]
+    bits: Vec<u8>,
+}
+
+impl BloomFilter {
+    /// Creates an empty bloom filter sized to hold `num_values` values.
+    pub fn with_capacity(num_values: usize) -> Self {
+        let ln_2 = std::f64::consts::LN_2;
+        let optimal_num_bits =
+            (-(num_values as f64) * FALSE_POSITIVE_RATE.ln() / (ln_2 * ln_2)).ceil() as usize;
+        let num_bytes = (optimal_num_bits.max(MIN_NUM_BITS) + 7) / 8;
+        let num_bits = num_bytes * 8;
+        let optimal_num_hashes = (num_bits as f64 / num_values.max(1) as f64 * ln_2).round();
+        let num_hashes = (optimal_num_hashes as u32).clamp(1, MAX_NUM_HASHES);
+        BloomFilter {
+            num_hashes,
+            bits: vec![0; num_bytes],
+        }
+    }
+
+    /// Returns the size of the filter in bytes.
+    pub fn num_bytes(&self) -> usize {
+        self.bits.len()
+    }
+
+    /// Records `value` in the filter.
+    pub fn insert(&mut self, value: &[u8]) {
+        for bit_ord in self.bit_ords(value) {
+            self.bits[bit_ord / 8] |= 1 << (bit_ord % 8);
+        }
+    }
+
+    /// Returns `false` if `value` was never inserted in the filter. Returns `true` if it may have
+    /// been.
+    pub fn may_contain(&self, value: &[u8]) -> bool {
+        self.bit_ords(value)
+            .all(|bit_ord| self.bits[bit_ord / 8] & (1 << (bit_ord % 8)) != 0)
+    }
+
+    // Enhanced double hashing: derives the `num_hashes` bit ordinals of a value from the two halves
+    // of a 128-bit hash.
+    fn bit_ords(&self, value: &[u8]) -> impl Iterator<Item = usize> {
+        let num_bits = self.bits.len() as u64 * 8;
+        let mut hasher = SipHasher13::new();
+        hasher.write(value);
+        let hash = hasher.finish128();
+        let mut bit_hash = hash.h1;
+        let mut delta = hash.h2;
+
+        (0..self.num_hashes as u64).map(move |hash_ord| {
+            let bit_ord = (bit_hash % num_bits) as usize;
+            bit_hash = bit_hash.wrapping_add(delta);
+            delta = delta.wrapping_add(hash_ord);
+            bit_ord
+        })
+    }
+}
+
+fn serialize_bits<S>(bits: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+where S: Serializer {
+    serializer.serialize_str(&BASE64_STANDARD.encode(bits))
+}
+
+fn deserialize_bits<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+where D: Deserializer<'de> {
+    let bits_base64 = String::deserialize(deserializer)?;
+    let bits = BASE64_STANDARD
+        .decode(bits_base64)
+        .map_err(serde::de::Error::custom)?;
+    if bits.is_empty() {
+        return Err(serde::de::Error::custom("Bloom filter must not be empty."));
+    }
+    Ok(bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bloom_filter() {
+        let mut bloom_filter = BloomFilter::with_capacity(1_000);
+        assert_eq!(bloom_filter.num_bytes(), 1_199);
+        assert_eq!(bloom_filter.num_hashes, 7);
+
+        for value_ord in 0..1_000 {
+            bloom_filter.insert(format!("trace-{value_ord}").as_bytes());
+        }
+        for value_ord in 0..1_000 {
+            assert!(bloom_filter.may_contain(format!("trace-{value_ord}").as_bytes()));
+        }
+        let num_false_positives = (1_000..11_000)
+            .filter(|value_ord| bloom_filter.may_contain(format!("trace-{value_ord}").as_bytes()))
+            .count();
+        assert!(num_false_positives < 200);
+    }
+
+    #[test]
+    fn test_bloom_filter_empty() {
+        let bloom_filter = BloomFilter::with_capacity(0);
+        assert_eq!(bloom_filter.num_bytes(), 8);
+        assert!(!bloom_filter.may_contain(b"trace-0"));
+    }
+
+    #[test]
+    fn test_bloom_filter_serde() {
+        let mut bloom_filter = BloomFilter::with_capacity(10);
+        bloom_filter.insert(b"trace-0");
+        let bloom_filter_json = serde_json::to_value(&bloom_filter).unwrap();
+        assert!(bloom_filter_json["bits"].is_string());
+
+        let deserialized_bloom_filter: BloomFilter =
+            serde_json::from_value(bloom_filter_json).unwrap();
+        assert_eq!(deserialized_bloom_filter, bloom_filter);
+        assert!(deserialized_bloom_filter.may_contain(b"trace-0"));
+
+        let error = serde_json::from_str::<BloomFilter>(r#"{"num_hashes": 7, "bits": ""}"#)
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("must not be empty"));
+    }
+}
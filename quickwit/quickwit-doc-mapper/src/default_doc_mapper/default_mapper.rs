@@ -21,15 +21,15 @@ use std::collections::{BTreeMap, BTreeSet};
 use std::num::NonZeroU32;
 
 use anyhow::{bail, Context};
-use quickwit_query::query_ast::QueryAst;
+use quickwit_query::query_ast::{parse_boosted_field, QueryAst};
 use serde::{Deserialize, Serialize};
 use serde_json::{self, Value as JsonValue};
 use tantivy::query::Query;
-use tantivy::schema::{Field, FieldType, Schema, Value as TantivyValue, STORED};
+use tantivy::schema::{Field, FieldType, Schema, TextOptions, Value as TantivyValue, STORED};
 use tantivy::Document;
 
 use super::field_mapping_entry::QuickwitTextTokenizer;
-use super::DefaultDocMapperBuilder;
+use super::{validate_field_mapping_name, CatchAllFieldOptions, DefaultDocMapperBuilder};
 use crate::default_doc_mapper::mapping_tree::{build_mapping_tree, MappingNode};
 use crate::default_doc_mapper::FieldMappingType;
 pub use crate::default_doc_mapper::QuickwitJsonOptions;
@@ -87,6 +87,10 @@ pub struct DefaultDocMapper {
     schema: Schema,
     /// List of field names used for tagging.
     tag_field_names: BTreeSet<String>,
+    /// List of field names whose values are recorded in bloom filters.
+    bloom_filter_field_names: BTreeSet<String>,
+    /// List of field names whose minimum and maximum values are recorded per split.
+    min_max_field_names: BTreeSet<String>,
     /// The partition key is a DSL used to route documents
     /// into specific splits.
     partition_key: RoutingExpr,
@@ -96,6 +100,15 @@ pub struct DefaultDocMapper {
     required_fields: Vec<Field>,
     /// Defines how unmapped fields should be handle.
     mode: Mode,
+    /// Text field concatenating the values of other text fields.
+    catch_all_field_opt: Option<CatchAllField>,
+}
+
+#[derive(Clone)]
+struct CatchAllField {
+    field: Field,
+    source_fields: Vec<Field>,
+    options: CatchAllFieldOptions,
 }
 
 impl DefaultDocMapper {
@@ -142,15 +155,54 @@ fn validate_tag_fields(tag_fields: &[String], schema: &Schema) -> anyhow::Result
     Ok(())
 }
 
+fn validate_bloom_filter_field(field_name: &str, schema: &Schema) -> anyhow::Result<()> {
+    let field = schema
+        .get_field(field_name)
+        .with_context(|| format!("Unknown bloom filter field: `{field_name}`"))?;
+    let tokenizer_opt = match schema.get_field_entry(field).field_type() {
+        FieldType::Str(options) => options
+            .get_indexing_options()
+            .map(|text_options| text_options.tokenizer()),
+        _ => None,
+    };
+    if tokenizer_opt != Some(QuickwitTextTokenizer::Raw.get_name()) {
+        bail!(
+            "Bloom filter field `{field_name}` must be an indexed text field with the `raw` \
+             tokenizer."
+        );
+    }
+    Ok(())
+}
+
+fn validate_min_max_field(field_name: &str, schema: &Schema) -> anyhow::Result<()> {
+    let field = schema
+        .get_field(field_name)
+        .with_context(|| format!("Unknown min/max field: `{field_name}`"))?;
+    let field_entry = schema.get_field_entry(field);
+    let is_numeric = matches!(
+        field_entry.field_type(),
+        FieldType::I64(_) | FieldType::U64(_) | FieldType::F64(_)
+    );
+    if !is_numeric || !field_entry.is_fast() {
+        bail!("Min/max field `{field_name}` must be a numeric fast field.");
+    }
+    Ok(())
+}
+
 fn validate_timestamp_field_if_any(builder: &DefaultDocMapperBuilder) -> anyhow::Result<()> {
     let Some(timestamp_field_name) = builder.timestamp_field.as_ref() else {
         return Ok(());
     };
-    let Some(timestamp_field_entry) = builder.field_mappings.iter().find(|mapping| {
-                &mapping.name == timestamp_field_name
-            }) else {
-                bail!("Missing timestamp field in field mappings: `{}`", timestamp_field_name);
-            };
+    let Some(timestamp_field_entry) = builder
+        .field_mappings
+        .iter()
+        .find(|mapping| &mapping.name == timestamp_field_name)
+    else {
+        bail!(
+            "Missing timestamp field in field mappings: `{}`",
+            timestamp_field_name
+        );
+    };
     if let FieldMappingType::DateTime(date_time_option, cardinality) =
         &timestamp_field_entry.mapping_type
     {
@@ -178,6 +230,24 @@ impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
         let mode = builder.mode()?;
         let mut schema_builder = Schema::builder();
         let field_mappings = build_mapping_tree(&builder.field_mappings, &mut schema_builder)?;
+        let catch_all_field_opt = if let Some(catch_all_options) = &builder.catch_all_field {
+            let catch_all_field_name = &catch_all_options.name;
+            validate_field_mapping_name(catch_all_field_name)?;
+
+            if catch_all_field_name.contains('.')
+                || field_mappings.branches.contains_key(catch_all_field_name)
+            {
+                bail!(
+                    "Catch-all field name `{catch_all_field_name}` conflicts with the field \
+                     mappings."
+                );
+            }
+            let field = schema_builder
+                .add_text_field(catch_all_field_name, TextOptions::from(catch_all_options));
+            Some((field, catch_all_options.clone()))
+        } else {
+            None
+        };
         let source_field = if builder.store_source {
             Some(schema_builder.add_json_field(SOURCE_FIELD_NAME, STORED))
         } else {
@@ -194,19 +264,48 @@ impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
 
         let schema = schema_builder.build();
 
+        let catch_all_field_opt = catch_all_field_opt
+            .map(|(field, options)| {
+                let source_fields = resolve_catch_all_source_fields(field, &options, &schema)?;
+                anyhow::Ok(CatchAllField {
+                    field,
+                    source_fields,
+                    options,
+                })
+            })
+            .transpose()?;
+
         // validate fast fields
         validate_tag_fields(&builder.tag_fields, &schema)?;
 
-        // Resolve default search fields
+        // Resolve default search fields. They can carry a boost factor, for instance `title^2`,
+        // which is kept in the field name and applied when the user query is parsed.
         let mut default_search_field_names = Vec::new();
-        for field_name in &builder.default_search_fields {
-            if default_search_field_names.contains(field_name) {
+        let mut resolved_field_names: BTreeSet<&str> = BTreeSet::new();
+        for boosted_field_name in &builder.default_search_fields {
+            let (field_name, _) = parse_boosted_field(boosted_field_name)?;
+            if !resolved_field_names.insert(field_name) {
                 bail!("Duplicated default search field: `{}`", field_name)
             }
-            schema
-                .get_field(field_name)
+            // Default search fields can target a path within a JSON field, for instance
+            // `body.message`.
+            let (field, json_path) = schema
+                .find_field(field_name)
                 .with_context(|| format!("Unknown default search field: `{field_name}`"))?;
-            default_search_field_names.push(field_name.clone());
+            if !json_path.is_empty()
+                && !matches!(
+                    schema.get_field_entry(field).field_type(),
+                    FieldType::JSON(_)
+                )
+            {
+                bail!("Unknown default search field: `{field_name}`");
+            }
+            default_search_field_names.push(boosted_field_name.clone());
+        }
+        if default_search_field_names.is_empty() {
+            if let Some(catch_all_field) = &catch_all_field_opt {
+                default_search_field_names.push(catch_all_field.options.name.clone());
+            }
         }
 
         // Resolve tag fields
@@ -221,6 +320,29 @@ impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
             tag_field_names.insert(tag_field_name.clone());
         }
 
+        // Resolve bloom filter fields
+        let mut bloom_filter_field_names: BTreeSet<String> = Default::default();
+        for bloom_filter_field_name in &builder.bloom_filter_fields {
+            if bloom_filter_field_names.contains(bloom_filter_field_name) {
+                bail!(
+                    "Duplicated bloom filter field: `{}`",
+                    bloom_filter_field_name
+                )
+            }
+            validate_bloom_filter_field(bloom_filter_field_name, &schema)?;
+            bloom_filter_field_names.insert(bloom_filter_field_name.clone());
+        }
+
+        // Resolve min/max fields
+        let mut min_max_field_names: BTreeSet<String> = Default::default();
+        for min_max_field_name in &builder.min_max_fields {
+            if min_max_field_names.contains(min_max_field_name) {
+                bail!("Duplicated min/max field: `{}`", min_max_field_name)
+            }
+            validate_min_max_field(min_max_field_name, &schema)?;
+            min_max_field_names.insert(min_max_field_name.clone());
+        }
+
         let required_fields = Vec::new();
         let partition_key = RoutingExpr::new(builder.partition_key.as_deref().unwrap_or(""))
             .context("Failed to interpret the partition key.")?;
@@ -232,14 +354,56 @@ impl TryFrom<DefaultDocMapperBuilder> for DefaultDocMapper {
             timestamp_field_name: builder.timestamp_field,
             field_mappings,
             tag_field_names,
+            bloom_filter_field_names,
+            min_max_field_names,
             required_fields,
             partition_key,
             max_num_partitions: builder.max_num_partitions,
             mode,
+            catch_all_field_opt,
         })
     }
 }
 
+/// Returns the text fields concatenated into the catch-all field: the fields listed in the
+/// options or, if none is listed, all the text fields of the schema.
+fn resolve_catch_all_source_fields(
+    catch_all_field: Field,
+    catch_all_options: &CatchAllFieldOptions,
+    schema: &Schema,
+) -> anyhow::Result<Vec<Field>> {
+    if catch_all_options.fields.is_empty() {
+        let source_fields = schema
+            .fields()
+            .filter(|(field, field_entry)| {
+                *field != catch_all_field && matches!(field_entry.field_type(), FieldType::Str(_))
+            })
+            .map(|(field, _)| field)
+            .collect();
+        return Ok(source_fields);
+    }
+    let mut source_fields = Vec::with_capacity(catch_all_options.fields.len());
+
+    for field_name in &catch_all_options.fields {
+        let field = schema
+            .get_field(field_name)
+            .with_context(|| format!("Unknown catch-all source field: `{field_name}`"))?;
+        if field == catch_all_field
+            || !matches!(
+                schema.get_field_entry(field).field_type(),
+                FieldType::Str(_)
+            )
+        {
+            bail!("Catch-all source field `{field_name}` must be a text field.");
+        }
+        if source_fields.contains(&field) {
+            bail!("Duplicated catch-all source field: `{field_name}`");
+        }
+        source_fields.push(field);
+    }
+    Ok(source_fields)
+}
+
 impl From<DefaultDocMapper> for DefaultDocMapperBuilder {
     fn from(default_doc_mapper: DefaultDocMapper) -> Self {
         let mode = default_doc_mapper.mode.mode_type();
@@ -260,11 +424,19 @@ impl From<DefaultDocMapper> for DefaultDocMapperBuilder {
                 .map(ToString::to_string),
             field_mappings: default_doc_mapper.field_mappings.into(),
             tag_fields: default_doc_mapper.tag_field_names.into_iter().collect(),
+            bloom_filter_fields: default_doc_mapper
+                .bloom_filter_field_names
+                .into_iter()
+                .collect(),
+            min_max_fields: default_doc_mapper.min_max_field_names.into_iter().collect(),
             default_search_fields: default_doc_mapper.default_search_field_names,
             mode,
             dynamic_mapping,
             partition_key: partition_key_opt,
             max_num_partitions: default_doc_mapper.max_num_partitions,
+            catch_all_field: default_doc_mapper
+                .catch_all_field_opt
+                .map(|catch_all_field| catch_all_field.options),
         }
     }
 }
@@ -332,6 +504,18 @@ impl DocMapper for DefaultDocMapper {
             &mut dynamic_json_obj,
         )?;
 
+        if let Some(catch_all_field) = &self.catch_all_field_opt {
+            let catch_all_values: Vec<String> = catch_all_field
+                .source_fields
+                .iter()
+                .flat_map(|&source_field| document.get_all(source_field))
+                .filter_map(|value| value.as_text())
+                .map(|text| text.to_string())
+                .collect();
+            for catch_all_value in catch_all_values {
+                document.add_text(catch_all_field.field, catch_all_value);
+            }
+        }
         if let Some(dynamic_field) = self.dynamic_field {
             if !dynamic_json_obj.is_empty() {
                 document.add_json_object(dynamic_field, dynamic_json_obj);
@@ -392,6 +576,14 @@ impl DocMapper for DefaultDocMapper {
         self.tag_field_names.clone()
     }
 
+    fn bloom_filter_field_names(&self) -> BTreeSet<String> {
+        self.bloom_filter_field_names.clone()
+    }
+
+    fn min_max_field_names(&self) -> BTreeSet<String> {
+        self.min_max_field_names.clone()
+    }
+
     fn max_num_partitions(&self) -> NonZeroU32 {
         self.max_num_partitions
     }
@@ -662,6 +854,100 @@ mod tests {
         assert_eq!(&builder.try_build().unwrap_err().to_string(), expected_msg);
     }
 
+    #[test]
+    fn test_build_doc_mapper_with_json_path_default_search_field() {
+        let doc_mapper = r#"{
+            "default_search_fields": ["body.message"],
+            "tag_fields": [],
+            "field_mappings": [
+                {
+                    "name": "body",
+                    "type": "json"
+                },
+                {
+                    "name": "title",
+                    "type": "text"
+                }
+            ]
+        }"#;
+        let builder = serde_json::from_str::<DefaultDocMapperBuilder>(doc_mapper).unwrap();
+        assert!(builder.try_build().is_ok());
+
+        let doc_mapper = r#"{
+            "default_search_fields": ["title.message"],
+            "tag_fields": [],
+            "field_mappings": [
+                {
+                    "name": "title",
+                    "type": "text"
+                }
+            ]
+        }"#;
+        let builder = serde_json::from_str::<DefaultDocMapperBuilder>(doc_mapper).unwrap();
+        assert_eq!(
+            builder.try_build().unwrap_err().to_string(),
+            "Unknown default search field: `title.message`"
+        );
+    }
+
+    #[test]
+    fn test_build_doc_mapper_with_boosted_default_search_fields() {
+        let doc_mapper = r#"{
+            "default_search_fields": ["title^5", "body^1.5", "tags"],
+            "field_mappings": [
+                {
+                    "name": "title",
+                    "type": "text"
+                },
+                {
+                    "name": "body",
+                    "type": "text"
+                },
+                {
+                    "name": "tags",
+                    "type": "text"
+                }
+            ]
+        }"#;
+        let builder = serde_json::from_str::<DefaultDocMapperBuilder>(doc_mapper).unwrap();
+        let doc_mapper = builder.try_build().unwrap();
+        assert_eq!(
+            doc_mapper.default_search_fields(),
+            &["title^5", "body^1.5", "tags"]
+        );
+
+        let doc_mapper = r#"{
+            "default_search_fields": ["title^5", "title"],
+            "field_mappings": [
+                {
+                    "name": "title",
+                    "type": "text"
+                }
+            ]
+        }"#;
+        let builder = serde_json::from_str::<DefaultDocMapperBuilder>(doc_mapper).unwrap();
+        assert_eq!(
+            builder.try_build().unwrap_err().to_string(),
+            "Duplicated default search field: `title`"
+        );
+
+        let doc_mapper = r#"{
+            "default_search_fields": ["title^high"],
+            "field_mappings": [
+                {
+                    "name": "title",
+                    "type": "text"
+                }
+            ]
+        }"#;
+        let builder = serde_json::from_str::<DefaultDocMapperBuilder>(doc_mapper).unwrap();
+        assert_eq!(
+            builder.try_build().unwrap_err().to_string(),
+            "Invalid boost `high` for search field `title`: the boost must be a non-negative \
+             number."
+        );
+    }
+
     #[test]
     fn test_fail_with_field_name_equal_to_source() {
         let doc_mapper = r#"{
@@ -810,6 +1096,149 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_build_doc_mapper_with_bloom_filter_fields() {
+        let doc_mapper = serde_json::from_str::<DefaultDocMapperBuilder>(
+            r#"{
+                "bloom_filter_fields": ["trace_id"],
+                "field_mappings": [
+                    {
+                        "name": "trace_id",
+                        "type": "text",
+                        "tokenizer": "raw"
+                    },
+                    {
+                        "name": "body",
+                        "type": "text"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap()
+        .try_build()
+        .unwrap();
+        assert_eq!(
+            doc_mapper.bloom_filter_field_names(),
+            ["trace_id".to_string()].into_iter().collect()
+        );
+        let bloom_filter_fields = doc_mapper
+            .split_pruning_fields()
+            .unwrap()
+            .bloom_filter_fields;
+        assert_eq!(bloom_filter_fields.len(), 1);
+        assert_eq!(bloom_filter_fields[0].name, "trace_id");
+
+        for (bloom_filter_fields, expected_error) in [
+            (
+                r#"["body"]"#,
+                "Bloom filter field `body` must be an indexed text field with the `raw` tokenizer.",
+            ),
+            (r#"["unknown"]"#, "Unknown bloom filter field: `unknown`"),
+            (
+                r#"["trace_id", "trace_id"]"#,
+                "Duplicated bloom filter field: `trace_id`",
+            ),
+        ] {
+            let doc_mapper_json = format!(
+                r#"{{
+                    "bloom_filter_fields": {bloom_filter_fields},
+                    "field_mappings": [
+                        {{
+                            "name": "trace_id",
+                            "type": "text",
+                            "tokenizer": "raw"
+                        }},
+                        {{
+                            "name": "body",
+                            "type": "text"
+                        }}
+                    ]
+                }}"#
+            );
+            let error = serde_json::from_str::<DefaultDocMapperBuilder>(&doc_mapper_json)
+                .unwrap()
+                .try_build()
+                .unwrap_err();
+            assert_eq!(error.to_string(), expected_error);
+        }
+    }
+
+    #[test]
+    fn test_build_doc_mapper_with_min_max_fields() {
+        let doc_mapper = serde_json::from_str::<DefaultDocMapperBuilder>(
+            r#"{
+                "min_max_fields": ["status_code"],
+                "field_mappings": [
+                    {
+                        "name": "status_code",
+                        "type": "u64",
+                        "fast": true
+                    },
+                    {
+                        "name": "latency",
+                        "type": "f64"
+                    },
+                    {
+                        "name": "body",
+                        "type": "text"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap()
+        .try_build()
+        .unwrap();
+        assert_eq!(
+            doc_mapper.min_max_field_names(),
+            ["status_code".to_string()].into_iter().collect()
+        );
+        let min_max_fields = doc_mapper.split_pruning_fields().unwrap().min_max_fields;
+        assert_eq!(min_max_fields.len(), 1);
+        assert_eq!(min_max_fields[0].name, "status_code");
+
+        for (min_max_fields, expected_error) in [
+            (
+                r#"["body"]"#,
+                "Min/max field `body` must be a numeric fast field.",
+            ),
+            (
+                r#"["latency"]"#,
+                "Min/max field `latency` must be a numeric fast field.",
+            ),
+            (r#"["unknown"]"#, "Unknown min/max field: `unknown`"),
+            (
+                r#"["status_code", "status_code"]"#,
+                "Duplicated min/max field: `status_code`",
+            ),
+        ] {
+            let doc_mapper_json = format!(
+                r#"{{
+                    "min_max_fields": {min_max_fields},
+                    "field_mappings": [
+                        {{
+                            "name": "status_code",
+                            "type": "u64",
+                            "fast": true
+                        }},
+                        {{
+                            "name": "latency",
+                            "type": "f64"
+                        }},
+                        {{
+                            "name": "body",
+                            "type": "text"
+                        }}
+                    ]
+                }}"#
+            );
+            let error = serde_json::from_str::<DefaultDocMapperBuilder>(&doc_mapper_json)
+                .unwrap()
+                .try_build()
+                .unwrap_err();
+            assert_eq!(error.to_string(), expected_error);
+        }
+    }
+
     // See #1132
     #[test]
     fn test_by_default_store_source_is_false_and_fields_are_stored_individually() {
@@ -923,6 +1352,229 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_required_fields_and_default_values() {
+        let default_doc_mapper: DefaultDocMapper = serde_json::from_str(
+            r#"{
+            "field_mappings": [
+                {
+                    "name": "tenant_id",
+                    "type": "u64",
+                    "required": true
+                },
+                {
+                    "name": "severity",
+                    "type": "text",
+                    "default_value": "INFO"
+                },
+                {
+                    "name": "resource",
+                    "type": "object",
+                    "field_mappings": [
+                        {
+                            "name": "service",
+                            "type": "text",
+                            "default_value": "unknown"
+                        }
+                    ]
+                }
+            ]
+        }"#,
+        )
+        .unwrap();
+        let parsing_err = default_doc_mapper
+            .doc_from_json_str(r#"{ "severity": "ERROR" }"#)
+            .err()
+            .unwrap();
+        assert_eq!(
+            parsing_err,
+            DocParsingError::RequiredField("tenant_id".to_string())
+        );
+        let parsing_err = default_doc_mapper
+            .doc_from_json_str(r#"{ "tenant_id": null }"#)
+            .err()
+            .unwrap();
+        assert_eq!(
+            parsing_err,
+            DocParsingError::RequiredField("tenant_id".to_string())
+        );
+        let schema = default_doc_mapper.schema();
+        let severity_field = schema.get_field("severity").unwrap();
+        let service_field = schema.get_field("resource.service").unwrap();
+
+        let (_, document) = default_doc_mapper
+            .doc_from_json_str(r#"{ "tenant_id": 1 }"#)
+            .unwrap();
+        assert_eq!(
+            document.get_first(severity_field).unwrap().as_text(),
+            Some("INFO")
+        );
+        assert_eq!(
+            document.get_first(service_field).unwrap().as_text(),
+            Some("unknown")
+        );
+        let (_, document) = default_doc_mapper
+            .doc_from_json_str(
+                r#"{ "tenant_id": 1, "severity": "ERROR", "resource": { "service": "api" } }"#,
+            )
+            .unwrap();
+        assert_eq!(
+            document.get_first(severity_field).unwrap().as_text(),
+            Some("ERROR")
+        );
+        assert_eq!(
+            document.get_first(service_field).unwrap().as_text(),
+            Some("api")
+        );
+    }
+
+    #[test]
+    fn test_catch_all_field() {
+        let default_doc_mapper: DefaultDocMapper = serde_json::from_str(
+            r#"{
+            "field_mappings": [
+                {
+                    "name": "title",
+                    "type": "text"
+                },
+                {
+                    "name": "severity",
+                    "type": "text",
+                    "tokenizer": "raw"
+                },
+                {
+                    "name": "attributes",
+                    "type": "object",
+                    "field_mappings": [
+                        {
+                            "name": "message",
+                            "type": "text"
+                        }
+                    ]
+                },
+                {
+                    "name": "count",
+                    "type": "u64"
+                }
+            ],
+            "catch_all_field": {
+                "name": "all"
+            }
+        }"#,
+        )
+        .unwrap();
+        assert_eq!(default_doc_mapper.default_search_fields(), &["all"]);
+
+        let schema = default_doc_mapper.schema();
+        let all_field = schema.get_field("all").unwrap();
+        let all_field_entry = schema.get_field_entry(all_field);
+        assert!(!all_field_entry.is_stored());
+
+        let (_, document) = default_doc_mapper
+            .doc_from_json_str(
+                r#"{ "title": "timeout", "severity": "ERROR", "attributes": { "message": "connection reset" }, "count": 3 }"#,
+            )
+            .unwrap();
+        let all_values: Vec<&str> = document
+            .get_all(all_field)
+            .flat_map(|value| value.as_text())
+            .collect();
+        assert_eq!(all_values, ["timeout", "ERROR", "connection reset"]);
+
+        let default_doc_mapper: DefaultDocMapper = serde_json::from_str(
+            r#"{
+            "field_mappings": [
+                {
+                    "name": "title",
+                    "type": "text"
+                },
+                {
+                    "name": "body",
+                    "type": "text"
+                }
+            ],
+            "default_search_fields": ["title"],
+            "catch_all_field": {
+                "name": "all",
+                "fields": ["body"]
+            }
+        }"#,
+        )
+        .unwrap();
+        assert_eq!(default_doc_mapper.default_search_fields(), &["title"]);
+
+        let all_field = default_doc_mapper.schema().get_field("all").unwrap();
+        let (_, document) = default_doc_mapper
+            .doc_from_json_str(r#"{ "title": "timeout", "body": "connection reset" }"#)
+            .unwrap();
+        let all_values: Vec<&str> = document
+            .get_all(all_field)
+            .flat_map(|value| value.as_text())
+            .collect();
+        assert_eq!(all_values, ["connection reset"]);
+
+        let serialized_doc_mapper = serde_json::to_value(&default_doc_mapper).unwrap();
+        assert_eq!(
+            serialized_doc_mapper["catch_all_field"],
+            json!({"name": "all", "fields": ["body"]})
+        );
+    }
+
+    #[test]
+    fn test_invalid_catch_all_field() {
+        for (catch_all_field_json, expected_error) in [
+            (r#"{"name": "title"}"#, "conflicts with the field mappings"),
+            (
+                r#"{"name": "all", "fields": ["body"]}"#,
+                "Unknown catch-all source field",
+            ),
+            (
+                r#"{"name": "all", "fields": ["count"]}"#,
+                "must be a text field",
+            ),
+        ] {
+            let doc_mapper_json = format!(
+                r#"{{
+                "field_mappings": [
+                    {{
+                        "name": "title",
+                        "type": "text"
+                    }},
+                    {{
+                        "name": "count",
+                        "type": "u64"
+                    }}
+                ],
+                "catch_all_field": {catch_all_field_json}
+            }}"#
+            );
+            let error = serde_json::from_str::<DefaultDocMapper>(&doc_mapper_json).unwrap_err();
+            assert!(
+                error.to_string().contains(expected_error),
+                "unexpected error: {error}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_invalid_default_value() {
+        let error = serde_json::from_str::<DefaultDocMapper>(
+            r#"{
+            "field_mappings": [
+                {
+                    "name": "count",
+                    "type": "u64",
+                    "default_value": "not a number"
+                }
+            ]
+        }"#,
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("Invalid default value for field `count`"));
+    }
+
     #[test]
     fn test_lenient_mode_simple() {
         let default_doc_mapper: DefaultDocMapper =
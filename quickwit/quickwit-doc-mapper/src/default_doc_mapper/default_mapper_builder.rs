@@ -22,7 +22,7 @@ use std::num::NonZeroU32;
 use anyhow::bail;
 use serde::{Deserialize, Serialize};
 
-use super::FieldMappingEntry;
+use super::{CatchAllFieldOptions, FieldMappingEntry};
 use crate::default_doc_mapper::default_mapper::Mode;
 use crate::default_doc_mapper::QuickwitJsonOptions;
 use crate::DefaultDocMapper;
@@ -52,6 +52,14 @@ pub struct DefaultDocMapperBuilder {
     /// Name of the fields that are tagged.
     #[serde(default)]
     pub tag_fields: Vec<String>,
+    /// Name of the fields whose values are recorded in bloom filters.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub bloom_filter_fields: Vec<String>,
+    /// Name of the numeric fast fields whose minimum and maximum values are recorded per split.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub min_max_fields: Vec<String>,
     /// The partition key is a DSL used to route documents
     /// into specific splits.
     #[serde(default)]
@@ -66,6 +74,10 @@ pub struct DefaultDocMapperBuilder {
     /// how the unmapped fields should be handled.
     #[serde(default)]
     pub dynamic_mapping: Option<QuickwitJsonOptions>,
+    /// Text field concatenating the values of text fields at indexing time.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub catch_all_field: Option<CatchAllFieldOptions>,
 }
 
 /// `Mode` describing how the unmapped field should be handled.
@@ -47,6 +47,10 @@ pub struct FieldMappingEntry {
     pub name: String,
     /// Property parameters which defines the type and the way the value must be indexed.
     pub(crate) mapping_type: FieldMappingType,
+    /// Documents missing the field are rejected.
+    pub required: bool,
+    /// Value indexed when the field is missing from a document.
+    pub default_value: Option<JsonValue>,
 }
 
 // Struct used for serialization and deserialization
@@ -179,6 +183,38 @@ pub struct QuickwitTextOptions {
     pub fast: FastFieldOptions,
 }
 
+/// Options of the catch-all text field, which concatenates the values of text fields at indexing
+/// time. The catch-all field is searched by default when the index declares no default search
+/// fields.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct CatchAllFieldOptions {
+    /// Name of the catch-all field.
+    pub name: String,
+    /// Text fields concatenated into the catch-all field. All the text fields are concatenated
+    /// when empty.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<String>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tokenizer: Option<QuickwitTextTokenizer>,
+}
+
+impl From<&CatchAllFieldOptions> for TextOptions {
+    fn from(catch_all_field_options: &CatchAllFieldOptions) -> Self {
+        // The catch-all field is neither stored nor fast: its values can be found in the
+        // concatenated fields.
+        let quickwit_text_options = QuickwitTextOptions {
+            tokenizer: catch_all_field_options.tokenizer.clone(),
+            record: Some(IndexRecordOption::WithFreqsAndPositions),
+            stored: false,
+            ..Default::default()
+        };
+        quickwit_text_options.into()
+    }
+}
+
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum FastFieldOptions {
@@ -417,8 +453,29 @@ fn deserialize_mapping_type(
 impl TryFrom<FieldMappingEntryForSerialization> for FieldMappingEntry {
     type Error = String;
 
-    fn try_from(value: FieldMappingEntryForSerialization) -> Result<Self, String> {
+    fn try_from(mut value: FieldMappingEntryForSerialization) -> Result<Self, String> {
         validate_field_mapping_name(&value.name).map_err(|err| err.to_string())?;
+        let required = match value.field_mapping_json.remove("required") {
+            Some(JsonValue::Bool(required)) => required,
+            Some(JsonValue::Null) | None => false,
+            Some(json_value) => {
+                return Err(format!(
+                    "Error while parsing field `{}`: `required` must be a boolean, got \
+                     `{json_value}`.",
+                    value.name
+                ));
+            }
+        };
+        let default_value = value
+            .field_mapping_json
+            .remove("default_value")
+            .filter(|json_value| !json_value.is_null());
+        if required && default_value.is_some() {
+            return Err(format!(
+                "Error while parsing field `{}`: a required field cannot have a default value.",
+                value.name
+            ));
+        }
         let quickwit_field_type =
             QuickwitFieldType::parse_type_id(&value.type_id).ok_or_else(|| {
                 format!(
@@ -434,6 +491,8 @@ impl TryFrom<FieldMappingEntryForSerialization> for FieldMappingEntry {
         Ok(FieldMappingEntry {
             name: value.name,
             mapping_type,
+            required,
+            default_value,
         })
     }
 }
@@ -472,7 +531,13 @@ impl From<FieldMappingEntry> for FieldMappingEntryForSerialization {
             .mapping_type
             .quickwit_field_type()
             .to_type_id();
-        let field_mapping_json = typed_mapping_to_json_params(field_mapping_entry.mapping_type);
+        let mut field_mapping_json = typed_mapping_to_json_params(field_mapping_entry.mapping_type);
+        if field_mapping_entry.required {
+            field_mapping_json.insert("required".to_string(), JsonValue::Bool(true));
+        }
+        if let Some(default_value) = field_mapping_entry.default_value {
+            field_mapping_json.insert("default_value".to_string(), default_value);
+        }
         FieldMappingEntryForSerialization {
             name: field_mapping_entry.name,
             type_id,
@@ -1432,4 +1497,51 @@ mod tests {
             })
         );
     }
+
+    #[test]
+    fn test_serialize_required_and_default_value() {
+        let entry = serde_json::from_str::<FieldMappingEntry>(
+            r#"
+            {
+                "name": "tenant_id",
+                "type": "u64",
+                "required": true
+            }"#,
+        )
+        .unwrap();
+        assert!(entry.required);
+        assert_eq!(entry.default_value, None);
+        let entry_json = serde_json::to_value(&entry).unwrap();
+        assert_eq!(entry_json["required"], json!(true));
+        assert!(entry_json.get("default_value").is_none());
+
+        let entry = serde_json::from_str::<FieldMappingEntry>(
+            r#"
+            {
+                "name": "severity",
+                "type": "text",
+                "default_value": "INFO"
+            }"#,
+        )
+        .unwrap();
+        assert!(!entry.required);
+        assert_eq!(entry.default_value, Some(json!("INFO")));
+        let entry_json = serde_json::to_value(&entry).unwrap();
+        assert!(entry_json.get("required").is_none());
+        assert_eq!(entry_json["default_value"], json!("INFO"));
+
+        let error = serde_json::from_str::<FieldMappingEntry>(
+            r#"
+            {
+                "name": "severity",
+                "type": "text",
+                "required": true,
+                "default_value": "INFO"
+            }"#,
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .contains("a required field cannot have a default value"));
+    }
 }
@@ -280,6 +280,12 @@ impl NumVal for f64 {
 pub(crate) struct MappingNode {
     pub branches: fnv::FnvHashMap<String, MappingTree>,
     branches_order: Vec<String>,
+    /// Fields that must be present in the documents.
+    required_branches: fnv::FnvHashSet<String>,
+    /// Values indexed for the fields missing from the documents.
+    default_values: fnv::FnvHashMap<String, JsonValue>,
+    /// True if the node or one of its descendants has required fields or default values.
+    has_presence_constraints: bool,
 }
 
 fn get_or_insert_path<'a>(
@@ -318,6 +324,8 @@ impl MappingNode {
             let field_mapping_entry = FieldMappingEntry {
                 name: field_name.clone(),
                 mapping_type: child_tree.clone().into(),
+                required: self.required_branches.contains(field_name),
+                default_value: self.default_values.get(field_name).cloned(),
             };
             field_mapping_entries.push(field_mapping_entry);
         }
@@ -332,8 +340,13 @@ impl MappingNode {
         path: &mut Vec<String>,
         dynamic_json_obj: &mut serde_json::Map<String, JsonValue>,
     ) -> Result<(), DocParsingError> {
+        let mut present_branches: Vec<String> = Vec::new();
+
         for (field_name, val) in json_obj {
             if let Some(child_tree) = self.branches.get(&field_name) {
+                if self.has_presence_constraints && !val.is_null() {
+                    present_branches.push(field_name.clone());
+                }
                 path.push(field_name);
                 child_tree.doc_from_json(val, mode, document, path, dynamic_json_obj)?;
                 path.pop();
@@ -355,6 +368,54 @@ impl MappingNode {
                 }
             }
         }
+        if self.has_presence_constraints {
+            self.populate_missing_branches(
+                &present_branches,
+                mode,
+                document,
+                path,
+                dynamic_json_obj,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Rejects the documents missing a required field and indexes the default values of the
+    /// other missing fields.
+    fn populate_missing_branches(
+        &self,
+        present_branches: &[String],
+        mode: ModeType,
+        document: &mut Document,
+        path: &mut Vec<String>,
+        dynamic_json_obj: &mut serde_json::Map<String, JsonValue>,
+    ) -> Result<(), DocParsingError> {
+        for field_name in &self.branches_order {
+            if present_branches.contains(field_name) {
+                continue;
+            }
+            let child_tree = &self.branches[field_name];
+
+            let missing_value = if self.required_branches.contains(field_name) {
+                let mut field_path = path.join(".");
+                if !field_path.is_empty() {
+                    field_path.push('.');
+                }
+                field_path.push_str(field_name);
+                return Err(DocParsingError::RequiredField(field_path));
+            } else if let Some(default_value) = self.default_values.get(field_name) {
+                default_value.clone()
+            } else if child_tree.has_presence_constraints() {
+                // The object is missing but some of its fields are required or have a default
+                // value.
+                JsonValue::Object(Default::default())
+            } else {
+                continue;
+            };
+            path.push(field_name.clone());
+            child_tree.doc_from_json(missing_value, mode, document, path, dynamic_json_obj)?;
+            path.pop();
+        }
         Ok(())
     }
 
@@ -437,6 +498,13 @@ impl MappingTree {
         }
     }
 
+    fn has_presence_constraints(&self) -> bool {
+        match self {
+            MappingTree::Leaf(_) => false,
+            MappingTree::Node(mapping_node) => mapping_node.has_presence_constraints,
+        }
+    }
+
     fn populate_json<'a>(
         &'a self,
         named_doc: &mut BTreeMap<String, Vec<TantivyValue>>,
@@ -474,12 +542,51 @@ fn build_mapping_tree_from_entries<'a>(
             bail!("Duplicated field definition `{}`.", entry.name);
         }
         let child_tree = build_mapping_from_field_type(&entry.mapping_type, field_path, schema)?;
+        if let Some(default_value) = &entry.default_value {
+            validate_default_value(&child_tree, default_value, field_path)?;
+            mapping_node
+                .default_values
+                .insert(entry.name.clone(), default_value.clone());
+            mapping_node.has_presence_constraints = true;
+        }
         field_path.pop();
+        if entry.required {
+            mapping_node.required_branches.insert(entry.name.clone());
+            mapping_node.has_presence_constraints = true;
+        }
+        if child_tree.has_presence_constraints() {
+            mapping_node.has_presence_constraints = true;
+        }
         mapping_node.insert(&entry.name, child_tree);
     }
     Ok(mapping_node)
 }
 
+fn validate_default_value(
+    mapping_tree: &MappingTree,
+    default_value: &JsonValue,
+    field_path: &[&str],
+) -> anyhow::Result<()> {
+    let mut path: Vec<String> = field_path
+        .iter()
+        .map(|field_name| field_name.to_string())
+        .collect();
+    mapping_tree
+        .doc_from_json(
+            default_value.clone(),
+            ModeType::Strict,
+            &mut Document::default(),
+            &mut path,
+            &mut serde_json::Map::new(),
+        )
+        .map_err(|error| {
+            anyhow::anyhow!(
+                "Invalid default value for field `{}`: {error}",
+                field_path.join(".")
+            )
+        })
+}
+
 fn get_numeric_options(quickwit_numeric_options: &QuickwitNumericOptions) -> NumericOptions {
     let mut numeric_options = NumericOptions::default();
     if quickwit_numeric_options.stored {
@@ -121,23 +121,26 @@ pub trait DocMapper: Send + Sync + Debug + DynClone + 'static {
         Default::default()
     }
 
-    /// Returns the tag `NameField`s on the current schema.
-    /// Returns an error if a tag field is not found in this schema.
-    fn tag_named_fields(&self) -> anyhow::Result<Vec<NamedField>> {
-        let index_schema = self.schema();
-        self.tag_field_names()
-            .iter()
-            .map(|field_name| {
-                index_schema
-                    .get_field(field_name)
-                    .context(format!("Field `{field_name}` must exist in the schema."))
-                    .map(|field| NamedField {
-                        name: field_name.clone(),
-                        field,
-                        field_type: index_schema.get_field_entry(field).field_type().clone(),
-                    })
-            })
-            .collect::<Result<Vec<_>, _>>()
+    /// Returns the names of the fields whose values are recorded in bloom filters.
+    fn bloom_filter_field_names(&self) -> BTreeSet<String> {
+        Default::default()
+    }
+
+    /// Returns the names of the numeric fast fields whose minimum and maximum values are recorded
+    /// per split.
+    fn min_max_field_names(&self) -> BTreeSet<String> {
+        Default::default()
+    }
+
+    /// Returns the tag, bloom filter, and min/max `NamedField`s on the current schema.
+    /// Returns an error if one of these fields is not found in this schema.
+    fn split_pruning_fields(&self) -> anyhow::Result<SplitPruningFields> {
+        let schema = self.schema();
+        Ok(SplitPruningFields {
+            tag_fields: named_fields(&schema, &self.tag_field_names())?,
+            bloom_filter_fields: named_fields(&schema, &self.bloom_filter_field_names())?,
+            min_max_fields: named_fields(&schema, &self.min_max_field_names())?,
+        })
     }
 
     /// Returns the maximum number of partitions.
@@ -155,6 +158,38 @@ pub struct NamedField {
     pub field_type: FieldType,
 }
 
+/// The fields whose values are summarized in the split metadata, so that searches can skip the
+/// splits that cannot match.
+#[derive(Clone, Debug, Default)]
+pub struct SplitPruningFields {
+    /// Fields whose values are recorded as split tags.
+    pub tag_fields: Vec<NamedField>,
+    /// Fields whose terms are recorded in bloom filters.
+    pub bloom_filter_fields: Vec<NamedField>,
+    /// Numeric fast fields whose minimum and maximum values are recorded.
+    pub min_max_fields: Vec<NamedField>,
+}
+
+/// Resolves `field_names` into `NamedField`s on `schema`.
+fn named_fields(
+    schema: &Schema,
+    field_names: &BTreeSet<String>,
+) -> anyhow::Result<Vec<NamedField>> {
+    field_names
+        .iter()
+        .map(|field_name| {
+            schema
+                .get_field(field_name)
+                .context(format!("Field `{field_name}` must exist in the schema."))
+                .map(|field| NamedField {
+                    name: field_name.clone(),
+                    field,
+                    field_type: schema.get_field_entry(field).field_type().clone(),
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()
+}
+
 clone_trait_object!(DocMapper);
 
 /// Information about what a DocMapper think should be warmed up before
@@ -225,7 +260,10 @@ mod tests {
         let json_doc = br#"{"title": "hello", "body": "world"}"#;
         doc_mapper.doc_from_json_bytes(json_doc).unwrap();
 
-        let DocParsingError::NotJsonObject(json_doc_sample) = doc_mapper.doc_from_json_bytes(br#"Not a JSON object"#).unwrap_err() else {
+        let DocParsingError::NotJsonObject(json_doc_sample) = doc_mapper
+            .doc_from_json_bytes(br#"Not a JSON object"#)
+            .unwrap_err()
+        else {
             panic!("Expected `DocParsingError::NotJsonObject` error");
         };
         assert_eq!(json_doc_sample, "Not a JSON object...");
@@ -237,7 +275,10 @@ mod tests {
         let json_doc = r#"{"title": "hello", "body": "world"}"#;
         doc_mapper.doc_from_json_str(json_doc).unwrap();
 
-        let DocParsingError::NotJsonObject(json_doc_sample) = doc_mapper.doc_from_json_str(r#"Not a JSON object"#).unwrap_err() else {
+        let DocParsingError::NotJsonObject(json_doc_sample) = doc_mapper
+            .doc_from_json_str(r#"Not a JSON object"#)
+            .unwrap_err()
+        else {
             panic!("Expected `DocParsingError::NotJsonObject` error");
         };
         assert_eq!(json_doc_sample, "Not a JSON object...");
@@ -313,6 +354,8 @@ mod tests {
                 QuickwitJsonOptions::default(),
                 Cardinality::SingleValue,
             ),
+            required: false,
+            default_value: None,
         });
         let doc_mapper = doc_mapper_builder.try_build().unwrap();
         let schema = doc_mapper.schema();
@@ -25,23 +25,30 @@
 //! to convert a json like documents to a document indexable by tantivy
 //! engine, aka tantivy::Document.
 
+mod bloom_filter;
 mod default_doc_mapper;
 mod doc_mapper;
 mod error;
 mod query_builder;
 mod routing_expression;
 
+/// Pruning splits with the ranges of values of their numeric fields.
+pub mod range_pruning;
 /// Pruning tags manipulation.
 pub mod tag_pruning;
 
+pub use bloom_filter::BloomFilter;
 pub use default_doc_mapper::{
-    DefaultDocMapper, DefaultDocMapperBuilder, FieldMappingEntry, ModeType, QuickwitJsonOptions,
+    CatchAllFieldOptions, DefaultDocMapper, DefaultDocMapperBuilder, FieldMappingEntry, ModeType,
+    QuickwitJsonOptions,
 };
 use default_doc_mapper::{
     FieldMappingEntryForSerialization, IndexRecordOptionSchema, QuickwitTextTokenizer,
 };
-pub use doc_mapper::{DocMapper, JsonObject, NamedField, WarmupInfo};
+pub use doc_mapper::{DocMapper, JsonObject, NamedField, SplitPruningFields, WarmupInfo};
 pub use error::{DocParsingError, QueryParserError};
+use range_pruning::FieldValueRange;
+pub use routing_expression::RoutingExpr;
 
 /// Field name reserved for storing the source document.
 pub const SOURCE_FIELD_NAME: &str = "_source";
@@ -65,6 +72,9 @@ pub(crate) enum Cardinality {
     QuickwitTextTokenizer,
     IndexRecordOptionSchema,
     FieldMappingEntryForSerialization,
+    CatchAllFieldOptions,
+    BloomFilter,
+    FieldValueRange,
 )))]
 /// Schema used for the OpenAPI generation which are apart of this crate.
 pub struct DocMapperApiSchemas;
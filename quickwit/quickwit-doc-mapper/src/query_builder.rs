@@ -20,7 +20,10 @@
 use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
 
-use quickwit_query::query_ast::{QueryAst, QueryAstVisitor, RangeQuery, TermSetQuery};
+use quickwit_query::query_ast::{
+    QueryAst, QueryAstVisitor, RangeQuery, TermSetQuery, LOG_LEVEL_FIELD_NAME,
+    SEVERITY_NUMBER_FIELD_NAME,
+};
 use tantivy::query::Query;
 use tantivy::schema::{Field, Schema};
 
@@ -48,6 +51,19 @@ pub(crate) fn build_query(
     search_fields: &[String],
     with_validation: bool,
 ) -> Result<(Box<dyn Query>, WarmupInfo), QueryParserError> {
+    // The `level:<level>` shortcut applies to indexes following the OpenTelemetry log data model,
+    // unless they define their own `level` field.
+    let expanded_query_ast;
+    let query_ast = if schema.get_field(SEVERITY_NUMBER_FIELD_NAME).is_ok()
+        && schema.get_field(LOG_LEVEL_FIELD_NAME).is_err()
+    {
+        expanded_query_ast = query_ast
+            .clone()
+            .expand_log_level_shortcut(SEVERITY_NUMBER_FIELD_NAME)?;
+        &expanded_query_ast
+    } else {
+        query_ast
+    };
     let mut range_query_fields = RangeQueryFields::default();
     // This cannot fail. The error type is Infallible.
     let _: Result<(), Infallible> = range_query_fields.visit(query_ast);
@@ -448,4 +464,25 @@ mod test {
         assert!(warmup_info.term_dict_field_names.is_empty());
         assert!(warmup_info.posting_field_names.is_empty());
     }
+
+    #[test]
+    fn test_build_query_log_level_shortcut() {
+        let query_ast = query_ast_from_user_text("level:error", None)
+            .parse_user_query(&[])
+            .unwrap();
+
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("severity_number", FAST | INDEXED);
+        let (query, warmup_info) =
+            build_query(&query_ast, schema_builder.build(), &[], true).unwrap();
+        assert!(format!("{query:?}").contains("FastFieldRangeWeight"));
+        assert!(warmup_info.fast_field_names.contains("severity_number"));
+
+        // Indexes defining their own `level` field are left alone.
+        let mut schema_builder = Schema::builder();
+        schema_builder.add_u64_field("severity_number", FAST | INDEXED);
+        schema_builder.add_text_field("level", TEXT);
+        let (query, _) = build_query(&query_ast, schema_builder.build(), &[], true).unwrap();
+        assert!(format!("{query:?}").contains(r#"TermQuery(Term(field=1, type=Str, "error"))"#));
+    }
 }
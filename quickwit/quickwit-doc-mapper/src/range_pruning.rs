@@ -0,0 +1,322 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+use quickwit_query::query_ast::QueryAst;
+use quickwit_query::JsonLiteral;
+use serde::{Deserialize, Serialize};
+
+/// Minimum and maximum values of a numeric fast field in a split.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum FieldValueRange {
+    I64 { min: i64, max: i64 },
+    U64 { min: u64, max: u64 },
+    F64 { min: f64, max: f64 },
+}
+
+// NaN values are never recorded in a range.
+impl Eq for FieldValueRange {}
+
+impl FieldValueRange {
+    /// Returns `false` if no value of the range is within the `lower_bound` and `upper_bound`
+    /// boundaries. Returns `true` if some may be or if a boundary is not numerical.
+    fn may_overlap(
+        &self,
+        lower_bound: &Bound<JsonLiteral>,
+        upper_bound: &Bound<JsonLiteral>,
+    ) -> bool {
+        match *self {
+            FieldValueRange::I64 { min, max } => {
+                let (Some(lower_bound), Some(upper_bound)) = (
+                    convert_bound(lower_bound, parse_i64, |val| val.ceil() as i64),
+                    convert_bound(upper_bound, parse_i64, |val| val.floor() as i64),
+                ) else {
+                    return true;
+                };
+                bounds_may_overlap(min, max, lower_bound, upper_bound)
+            }
+            FieldValueRange::U64 { min, max } => {
+                let (Some(lower_bound), Some(upper_bound)) = (
+                    convert_bound(lower_bound, parse_u64, |val| val.ceil() as u64),
+                    convert_bound(upper_bound, parse_u64, |val| val.floor() as u64),
+                ) else {
+                    return true;
+                };
+                bounds_may_overlap(min, max, lower_bound, upper_bound)
+            }
+            FieldValueRange::F64 { min, max } => {
+                let (Some(lower_bound), Some(upper_bound)) = (
+                    convert_bound(lower_bound, parse_f64, |val| val),
+                    convert_bound(upper_bound, parse_f64, |val| val),
+                ) else {
+                    return true;
+                };
+                bounds_may_overlap(min, max, lower_bound, upper_bound)
+            }
+        }
+    }
+
+    fn may_contain(&self, value: &str) -> bool {
+        let value = JsonLiteral::String(value.to_string());
+        self.may_overlap(&Bound::Included(value.clone()), &Bound::Included(value))
+    }
+}
+
+/// Returns `false` if the documents of a split whose numeric fields take their values in
+/// `field_ranges` are guaranteed not to match `query_ast`. Returns `true` otherwise.
+///
+/// The evaluation is conservative: clauses on fields without a recorded range, negated clauses
+/// and clauses that cannot be interpreted as numerical never exclude a split.
+pub fn may_match_field_ranges(
+    query_ast: &QueryAst,
+    field_ranges: &BTreeMap<String, FieldValueRange>,
+) -> bool {
+    match query_ast {
+        QueryAst::Bool(bool_query) => {
+            let mut required_clauses = bool_query
+                .must
+                .iter()
+                .chain(bool_query.filter.iter())
+                .peekable();
+            if required_clauses.peek().is_some() {
+                return required_clauses.all(|clause| may_match_field_ranges(clause, field_ranges));
+            }
+            if bool_query.should.is_empty() {
+                return true;
+            }
+            bool_query
+                .should
+                .iter()
+                .any(|clause| may_match_field_ranges(clause, field_ranges))
+        }
+        QueryAst::Term(term_query) => field_ranges
+            .get(&term_query.field)
+            .map(|field_range| field_range.may_contain(&term_query.value))
+            .unwrap_or(true),
+        QueryAst::TermSet(term_set_query) => {
+            term_set_query.terms_per_field.iter().any(|(field, terms)| {
+                match field_ranges.get(field) {
+                    Some(field_range) => terms.iter().any(|term| field_range.may_contain(term)),
+                    None => true,
+                }
+            })
+        }
+        QueryAst::FullText(full_text_query) => field_ranges
+            .get(&full_text_query.field)
+            .map(|field_range| field_range.may_contain(&full_text_query.text))
+            .unwrap_or(true),
+        QueryAst::Range(range_query) => field_ranges
+            .get(&range_query.field)
+            .map(|field_range| {
+                field_range.may_overlap(&range_query.lower_bound, &range_query.upper_bound)
+            })
+            .unwrap_or(true),
+        QueryAst::MatchNone => false,
+        QueryAst::MatchAll | QueryAst::UserInput(_) => true,
+        QueryAst::Boost { underlying, .. } => may_match_field_ranges(underlying, field_ranges),
+    }
+}
+
+fn bounds_may_overlap<T: PartialOrd>(
+    min: T,
+    max: T,
+    lower_bound: Bound<T>,
+    upper_bound: Bound<T>,
+) -> bool {
+    let above_lower_bound = match lower_bound {
+        Bound::Included(lower_bound) => max >= lower_bound,
+        Bound::Excluded(lower_bound) => max > lower_bound,
+        Bound::Unbounded => true,
+    };
+    let below_upper_bound = match upper_bound {
+        Bound::Included(upper_bound) => min <= upper_bound,
+        Bound::Excluded(upper_bound) => min < upper_bound,
+        Bound::Unbounded => true,
+    };
+    above_lower_bound && below_upper_bound
+}
+
+/// Converts a boundary to the type of a field. A value that is not exactly representable in this
+/// type is rounded with `round_f64` to the closest value within the boundary and the boundary
+/// becomes inclusive. Out of range values saturate, so that the converted boundary never excludes
+/// a value of the field the original one includes.
+///
+/// Returns `None` if the boundary value is not numerical.
+fn convert_bound<T>(
+    bound: &Bound<JsonLiteral>,
+    parse_exact: impl Fn(&JsonLiteral) -> Option<T>,
+    round_f64: impl Fn(f64) -> T,
+) -> Option<Bound<T>> {
+    let convert_value = |literal: &JsonLiteral| -> Option<(T, bool)> {
+        if let Some(value) = parse_exact(literal) {
+            return Some((value, true));
+        }
+        parse_f64(literal).map(|value_f64| (round_f64(value_f64), false))
+    };
+    match bound {
+        Bound::Included(literal) => convert_value(literal).map(|(value, _)| Bound::Included(value)),
+        Bound::Excluded(literal) => convert_value(literal).map(|(value, is_exact)| {
+            if is_exact {
+                Bound::Excluded(value)
+            } else {
+                Bound::Included(value)
+            }
+        }),
+        Bound::Unbounded => Some(Bound::Unbounded),
+    }
+}
+
+fn parse_i64(literal: &JsonLiteral) -> Option<i64> {
+    match literal {
+        JsonLiteral::Number(number) => number.as_i64(),
+        JsonLiteral::String(text) => text.parse().ok(),
+        JsonLiteral::Bool(_) => None,
+    }
+}
+
+fn parse_u64(literal: &JsonLiteral) -> Option<u64> {
+    match literal {
+        JsonLiteral::Number(number) => number.as_u64(),
+        JsonLiteral::String(text) => text.parse().ok(),
+        JsonLiteral::Bool(_) => None,
+    }
+}
+
+fn parse_f64(literal: &JsonLiteral) -> Option<f64> {
+    let value: f64 = match literal {
+        JsonLiteral::Number(number) => number.as_f64()?,
+        JsonLiteral::String(text) => text.parse().ok()?,
+        JsonLiteral::Bool(_) => return None,
+    };
+    if value.is_nan() {
+        return None;
+    }
+    Some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_query::query_ast::{QueryAst, UserInputQuery};
+    use quickwit_query::BooleanOperand;
+
+    use super::*;
+
+    fn may_match_field_ranges_helper(user_query: &str) -> bool {
+        let query_ast: QueryAst = UserInputQuery {
+            user_text: user_query.to_string(),
+            default_fields: None,
+            default_operator: BooleanOperand::And,
+        }
+        .into();
+        let parsed_query_ast = query_ast.parse_user_query(&[]).unwrap();
+        let field_ranges: BTreeMap<String, FieldValueRange> = [
+            (
+                "status_code".to_string(),
+                FieldValueRange::U64 { min: 200, max: 404 },
+            ),
+            (
+                "offset".to_string(),
+                FieldValueRange::I64 { min: 5, max: 10 },
+            ),
+            (
+                "latency".to_string(),
+                FieldValueRange::F64 { min: 0.5, max: 2.5 },
+            ),
+        ]
+        .into_iter()
+        .collect();
+        may_match_field_ranges(&parsed_query_ast, &field_ranges)
+    }
+
+    #[test]
+    fn test_may_match_field_ranges_range_queries() {
+        assert!(may_match_field_ranges_helper("status_code:>=404"));
+        assert!(!may_match_field_ranges_helper("status_code:>=500"));
+        assert!(!may_match_field_ranges_helper("status_code:>404"));
+        assert!(may_match_field_ranges_helper("status_code:>403.5"));
+        assert!(!may_match_field_ranges_helper("status_code:>404.5"));
+        assert!(!may_match_field_ranges_helper("status_code:<200"));
+        assert!(may_match_field_ranges_helper("status_code:[100 TO 200]"));
+        assert!(!may_match_field_ranges_helper("status_code:[500 TO 599]"));
+        assert!(may_match_field_ranges_helper("offset:<6"));
+        assert!(!may_match_field_ranges_helper("offset:<5"));
+        assert!(!may_match_field_ranges_helper("offset:>10.5"));
+        assert!(may_match_field_ranges_helper("latency:>2.4"));
+        assert!(!may_match_field_ranges_helper("latency:>2.5"));
+        assert!(!may_match_field_ranges_helper("latency:<0.5"));
+    }
+
+    #[test]
+    fn test_may_match_field_ranges_term_queries() {
+        assert!(may_match_field_ranges_helper("status_code:200"));
+        assert!(!may_match_field_ranges_helper("status_code:500"));
+        assert!(!may_match_field_ranges_helper("status_code: IN [500 503]"));
+        assert!(may_match_field_ranges_helper("status_code: IN [200 503]"));
+        assert!(may_match_field_ranges_helper("offset:5"));
+        assert!(!may_match_field_ranges_helper("offset:11"));
+        assert!(!may_match_field_ranges_helper("latency:3.0"));
+    }
+
+    #[test]
+    fn test_may_match_field_ranges_bool_queries() {
+        assert!(!may_match_field_ranges_helper(
+            "status_code:>=500 AND latency:>1"
+        ));
+        assert!(may_match_field_ranges_helper(
+            "status_code:>=500 OR latency:>1"
+        ));
+        assert!(!may_match_field_ranges_helper(
+            "status_code:>=500 OR latency:>3"
+        ));
+        assert!(may_match_field_ranges_helper("NOT status_code:>=200"));
+        assert!(may_match_field_ranges_helper(
+            "body:error AND NOT status_code:>=500"
+        ));
+        assert!(!may_match_field_ranges_helper(
+            "body:error AND status_code:>=500"
+        ));
+    }
+
+    #[test]
+    fn test_may_match_field_ranges_uninformative_queries() {
+        assert!(may_match_field_ranges_helper("*"));
+        // Fields without recorded ranges.
+        assert!(may_match_field_ranges_helper("body:>=500"));
+        assert!(may_match_field_ranges_helper("body:error"));
+        // Non-numerical values.
+        assert!(may_match_field_ranges_helper("status_code:>=abc"));
+        assert!(may_match_field_ranges_helper("status_code:abc"));
+    }
+
+    #[test]
+    fn test_field_value_range_serde() {
+        let field_range = FieldValueRange::I64 { min: -10, max: 10 };
+        let field_range_json = serde_json::to_value(field_range).unwrap();
+        assert_eq!(
+            field_range_json,
+            serde_json::json!({"type": "i64", "min": -10, "max": 10})
+        );
+        let deserialized_field_range: FieldValueRange =
+            serde_json::from_value(field_range_json).unwrap();
+        assert_eq!(deserialized_field_range, field_range);
+    }
+}
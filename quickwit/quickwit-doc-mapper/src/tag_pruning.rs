@@ -17,13 +17,15 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::Display;
 
 use quickwit_query::query_ast::QueryAst;
 use serde::{Deserialize, Serialize};
 use tantivy::query_grammar::Occur;
 
+use crate::BloomFilter;
+
 /// Returns true if and only if tag is of form `{field_name}:any_value`.
 pub fn match_tag_field_name(field_name: &str, tag: &str) -> bool {
     tag.len() > field_name.len()
@@ -214,6 +216,37 @@ impl TagFilterAst {
             TagFilterAst::Tag { is_present, tag } => tag_set.contains(tag) == *is_present,
         }
     }
+
+    /// Evaluates the tag filter predicate over the bloom filters of a split, keyed by field name.
+    ///
+    /// Bloom filters may yield false positives, so the predicate evaluates to false only if no
+    /// document of the split can match: negated terms and terms of fields without a bloom filter
+    /// always evaluate to true.
+    pub fn evaluate_bloom_filters(&self, bloom_filters: &BTreeMap<String, BloomFilter>) -> bool {
+        match self {
+            TagFilterAst::And(children) => children
+                .iter()
+                .all(|child_ast| child_ast.evaluate_bloom_filters(bloom_filters)),
+            TagFilterAst::Or(children) => children
+                .iter()
+                .any(|child_ast| child_ast.evaluate_bloom_filters(bloom_filters)),
+            TagFilterAst::Tag { is_present, tag } => {
+                // Field names cannot contain `:`, so the first one separates the field name from
+                // the value in term tags.
+                if let Some((field_name, value)) = tag.split_once(':') {
+                    !is_present
+                        || bloom_filters
+                            .get(field_name)
+                            .map(|bloom_filter| bloom_filter.may_contain(value.as_bytes()))
+                            .unwrap_or(true)
+                } else if let Some(field_name) = tag.strip_suffix('!') {
+                    bloom_filters.contains_key(field_name) == *is_present
+                } else {
+                    true
+                }
+            }
+        }
+    }
 }
 
 // Takes a tag AST and simplify it.
@@ -372,11 +405,14 @@ pub fn no_tag(tag: impl ToString) -> TagFilterAst {
 }
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeMap;
+
     use quickwit_query::query_ast::{QueryAst, UserInputQuery};
     use quickwit_query::BooleanOperand;
 
     use super::extract_tags_from_query;
     use crate::tag_pruning::TagFilterAst;
+    use crate::BloomFilter;
 
     fn extract_tags_from_query_helper(user_query: &str) -> Option<TagFilterAst> {
         let query_ast: QueryAst = UserInputQuery {
@@ -490,4 +526,28 @@ mod test {
         assert!(!super::match_tag_field_name("tagfield", "tagfiele:val"));
         assert!(!super::match_tag_field_name("tagfield", "t:val"));
     }
+
+    #[test]
+    fn test_evaluate_bloom_filters() {
+        let mut bloom_filter = BloomFilter::with_capacity(2);
+        bloom_filter.insert(b"abc");
+        bloom_filter.insert(b"de:f");
+        let bloom_filters: BTreeMap<String, BloomFilter> = [("trace_id".to_string(), bloom_filter)]
+            .into_iter()
+            .collect();
+
+        let evaluate = |user_query: &str| {
+            extract_tags_from_query_helper(user_query)
+                .unwrap()
+                .evaluate_bloom_filters(&bloom_filters)
+        };
+        assert!(evaluate("trace_id:abc"));
+        assert!(evaluate("trace_id:\"de:f\""));
+        assert!(!evaluate("trace_id:xyz"));
+        assert!(!evaluate("trace_id:xyz AND user:bart"));
+        assert!(evaluate("trace_id:xyz OR user:bart"));
+        assert!(evaluate("trace_id:abc OR trace_id:xyz"));
+        assert!(evaluate("user:bart AND NOT trace_id:abc"));
+        assert!(evaluate("user:bart AND NOT trace_id:xyz"));
+    }
 }
@@ -21,9 +21,10 @@ use std::time::Duration;
 
 use futures::Stream;
 use quickwit_cluster::ClusterChange;
+use quickwit_common::tower::make_endpoint;
 use quickwit_config::service::QuickwitService;
 use tokio_stream::StreamExt;
-use tonic::transport::{Channel, Endpoint, Uri};
+use tonic::transport::Channel;
 use tower::discover::Change;
 use tower::timeout::Timeout;
 use tracing::info;
@@ -45,13 +46,7 @@ pub async fn create_balance_channel_from_cluster_change_stream(
             match cluster_change {
                 ClusterChange::Add(node) if node.enabled_services().contains(&service) => {
                     let grpc_addr = node.grpc_advertise_addr();
-                    let uri = Uri::builder()
-                        .scheme("http")
-                        .authority(grpc_addr.to_string())
-                        .path_and_query("/")
-                        .build()
-                        .expect("");
-                    let endpoint = Endpoint::from(uri).connect_timeout(Duration::from_secs(5));
+                    let endpoint = make_endpoint(grpc_addr).connect_timeout(Duration::from_secs(5));
                     let change = Change::Insert(grpc_addr, endpoint);
                     info!(node_id=%node.node_id(), grpc_addr=?grpc_addr, "Adding node to {} pool.", service);
                     if channel_tx.send(change).await.is_err() {
@@ -366,7 +366,7 @@ impl Actor for DocProcessor {
             ActorExitStatus::DownstreamClosed
             | ActorExitStatus::Killed
             | ActorExitStatus::Failure(_)
-            | ActorExitStatus::Panicked => return Ok(()),
+            | ActorExitStatus::Panicked(_) => return Ok(()),
             ActorExitStatus::Quit | ActorExitStatus::Success => {
                 ctx.send_exit_with_success(&self.indexer_mailbox).await?;
             }
@@ -93,6 +93,9 @@ impl Handler<IndexedSplitBatchBuilder> for IndexSerializer {
             let split = split_builder.finalize()?;
             splits.push(split);
         }
+        // The splits are finalized: the memory used by the workbench can be handed over to the
+        // other pipelines.
+        drop(batch_builder.memory_guard_opt);
         let indexed_split_batch = IndexedSplitBatch {
             batch_parent_span: batch_builder.batch_parent_span,
             splits,
@@ -18,6 +18,7 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::hash_map::Entry;
+use std::collections::BTreeMap;
 use std::num::NonZeroU32;
 use std::ops::RangeInclusive;
 use std::sync::Arc;
@@ -46,8 +47,9 @@ use ulid::Ulid;
 
 use crate::actors::IndexSerializer;
 use crate::models::{
-    CommitTrigger, EmptySplit, IndexedSplitBatchBuilder, IndexedSplitBuilder, IndexingPipelineId,
-    NewPublishLock, ProcessedDoc, ProcessedDocBatch, PublishLock, ScratchDirectory,
+    CommitTrigger, EmptySplit, IndexedSplitBatchBuilder, IndexedSplitBuilder, IndexingMemoryBudget,
+    IndexingMemoryGuard, IndexingPipelineId, MemoryPressure, NewPublishLock, ProcessedDoc,
+    ProcessedDocBatch, PublishLock, ScratchDirectory,
 };
 
 // Random partition id used to gather partitions exceeding the maximum number of partitions.
@@ -80,6 +82,8 @@ struct IndexerState {
     schema: Schema,
     max_num_partitions: NonZeroU32,
     index_settings: IndexSettings,
+    split_labels: BTreeMap<String, String>,
+    memory_budget_opt: Option<IndexingMemoryBudget>,
 }
 
 impl IndexerState {
@@ -103,6 +107,7 @@ impl IndexerState {
             self.pipeline_id.clone(),
             partition_id,
             last_delete_opstamp,
+            self.split_labels.clone(),
             self.indexing_directory.clone(),
             index_builder,
             io_controls,
@@ -164,6 +169,17 @@ impl IndexerState {
                     .last_delete_opstamp(self.pipeline_id.index_uid.clone()),
             )
             .await?;
+        let workbench_id = Ulid::new();
+        // The memory used by the workbench is accounted for in the node indexing memory budget,
+        // if any, until its splits are finalized by the serializer.
+        let memory_guard_opt = match &self.memory_budget_opt {
+            Some(memory_budget) => {
+                ctx.protect_future(memory_budget.wait_for_available_memory())
+                    .await;
+                Some(memory_budget.register_workbench(workbench_id, ctx.mailbox().downgrade()))
+            }
+            None => None,
+        };
         let batch_parent_span = info_span!(target: "quickwit-indexing", "index_batch",
             index_id=%self.pipeline_id.index_uid.index_id(),
             source_id=%self.pipeline_id.source_id,
@@ -173,7 +189,7 @@ impl IndexerState {
         let workbench = IndexingWorkbench {
             batch_parent_span,
             _indexing_span: indexing_span,
-            workbench_id: Ulid::new(),
+            workbench_id,
             indexed_splits: FnvHashMap::with_capacity_and_hasher(250, Default::default()),
             other_indexed_split_opt: None,
             checkpoint_delta: IndexCheckpointDelta {
@@ -183,6 +199,7 @@ impl IndexerState {
             publish_lock: self.publish_lock.clone(),
             last_delete_opstamp,
             memory_usage: Byte::from_bytes(0),
+            memory_guard_opt,
         };
         Ok(workbench)
     }
@@ -228,6 +245,7 @@ impl IndexerState {
             publish_lock,
             last_delete_opstamp,
             memory_usage,
+            memory_guard_opt,
             ..
         } = self
             .get_or_create_workbench(indexing_workbench_opt, ctx)
@@ -262,6 +280,7 @@ impl IndexerState {
             if let Some(timestamp) = timestamp_opt {
                 record_timestamp(timestamp, &mut indexed_split.split_attrs.time_range);
             }
+            indexed_split.field_stats_collector.record_doc(&doc);
             let _protect_guard = ctx.protect_zone();
             indexed_split
                 .index_writer
@@ -272,6 +291,10 @@ impl IndexerState {
             ctx.record_progress();
         }
         *memory_usage = Byte::from_bytes(memory_usage.get_bytes() + memory_usage_delta);
+
+        if let Some(memory_guard) = memory_guard_opt {
+            memory_guard.update_memory_usage(memory_usage.get_bytes());
+        }
         Ok(())
     }
 }
@@ -295,6 +318,8 @@ struct IndexingWorkbench {
     last_delete_opstamp: u64,
     // Number of bytes declared as used by tantivy.
     memory_usage: Byte,
+    // Memory of the workbench accounted for in the node indexing memory budget.
+    memory_guard_opt: Option<IndexingMemoryGuard>,
 }
 
 pub struct Indexer {
@@ -338,7 +363,7 @@ impl Actor for Indexer {
             ActorExitStatus::DownstreamClosed
             | ActorExitStatus::Killed
             | ActorExitStatus::Failure(_)
-            | ActorExitStatus::Panicked => return Ok(()),
+            | ActorExitStatus::Panicked(_) => return Ok(()),
             ActorExitStatus::Quit | ActorExitStatus::Success => {
                 self.send_to_serializer(CommitTrigger::NoMoreDocs, ctx)
                     .await?;
@@ -376,6 +401,28 @@ impl Handler<CommitTimeout> for Indexer {
     }
 }
 
+#[async_trait]
+impl Handler<MemoryPressure> for Indexer {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        memory_pressure: MemoryPressure,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        // The workbench may have been committed since the node indexing memory budget asked for
+        // it, in which case we must ignore the message.
+        match &self.indexing_workbench_opt {
+            Some(indexing_workbench)
+                if indexing_workbench.workbench_id == memory_pressure.workbench_id => {}
+            _ => return Ok(()),
+        }
+        self.send_to_serializer(CommitTrigger::MemoryPressure, ctx)
+            .await?;
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl Handler<ProcessedDocBatch> for Indexer {
     type Reply = ();
@@ -435,6 +482,8 @@ impl Indexer {
                 schema,
                 index_settings,
                 max_num_partitions: doc_mapper.max_num_partitions(),
+                split_labels: BTreeMap::new(),
+                memory_budget_opt: None,
             },
             index_serializer_mailbox,
             indexing_workbench_opt: None,
@@ -442,6 +491,20 @@ impl Indexer {
         }
     }
 
+    /// Sets the labels attached to every split produced by the indexer.
+    pub fn with_split_labels(mut self, split_labels: BTreeMap<String, String>) -> Self {
+        self.indexer_state.split_labels = split_labels;
+        self
+    }
+
+    /// Accounts for the memory used by the indexer in the node indexing memory budget. Under
+    /// memory pressure, the indexer may be asked to commit its workbench before reaching any of
+    /// the commit triggers of its indexing settings.
+    pub fn with_memory_budget(mut self, memory_budget: IndexingMemoryBudget) -> Self {
+        self.indexer_state.memory_budget_opt = Some(memory_budget);
+        self
+    }
+
     fn memory_usage(&self) -> Byte {
         if let Some(workbench) = &self.indexing_workbench_opt {
             workbench.memory_usage
@@ -495,12 +558,16 @@ impl Indexer {
             checkpoint_delta,
             publish_lock,
             batch_parent_span,
+            memory_guard_opt,
             ..
         } = if let Some(indexing_workbench) = self.indexing_workbench_opt.take() {
             indexing_workbench
         } else {
             return Ok(());
         };
+        if let Some(memory_guard) = &memory_guard_opt {
+            memory_guard.mark_as_committed();
+        }
 
         let mut splits: Vec<IndexedSplitBuilder> = indexed_splits.into_values().collect();
         if let Some(other_split) = other_indexed_split_opt {
@@ -535,6 +602,7 @@ impl Indexer {
                 checkpoint_delta: Some(checkpoint_delta),
                 publish_lock,
                 commit_trigger,
+                memory_guard_opt,
             },
         )
         .await?;
@@ -1340,6 +1408,135 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_indexer_accounts_memory_in_budget() {
+        let universe = Universe::with_accelerated_time();
+        let pipeline_id = IndexingPipelineId {
+            index_uid: IndexUid::new("test-index"),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_ord: 0,
+        };
+        let doc_mapper: Arc<dyn DocMapper> =
+            Arc::new(serde_json::from_str::<DefaultDocMapper>(DOCMAPPER_SIMPLE_JSON).unwrap());
+        let body_field = doc_mapper.schema().get_field("body").unwrap();
+        let indexing_directory = ScratchDirectory::for_test();
+        let indexing_settings = IndexingSettings::for_test();
+        let memory_budget =
+            IndexingMemoryBudget::new(indexing_settings.resources.heap_size.get_bytes());
+        let mut metastore = MockMetastore::default();
+        metastore
+            .expect_last_delete_opstamp()
+            .times(1)
+            .returning(move |_| Ok(10));
+        metastore.expect_publish_splits().never();
+        let (index_serializer_mailbox, index_serializer_inbox) = universe.create_test_mailbox();
+        let indexer = Indexer::new(
+            pipeline_id,
+            doc_mapper,
+            Arc::new(metastore),
+            indexing_directory,
+            indexing_settings,
+            index_serializer_mailbox,
+        )
+        .with_memory_budget(memory_budget.clone());
+        let (indexer_mailbox, indexer_handle) = universe.spawn_builder().spawn(indexer);
+        indexer_mailbox
+            .send_message(ProcessedDocBatch {
+                docs: vec![ProcessedDoc {
+                    doc: doc!(body_field=>"doc 1"),
+                    timestamp_opt: None,
+                    partition: 0,
+                    num_bytes: 30,
+                }],
+                checkpoint_delta: SourceCheckpointDelta::from_range(0..1),
+                force_commit: true,
+            })
+            .await
+            .unwrap();
+        universe
+            .send_exit_with_success(&indexer_mailbox)
+            .await
+            .unwrap();
+        let (exit_status, _indexer_counters) = indexer_handle.join().await;
+        assert!(matches!(exit_status, ActorExitStatus::Success));
+        let output_messages: Vec<IndexedSplitBatchBuilder> =
+            index_serializer_inbox.drain_for_test_typed();
+        assert_eq!(output_messages.len(), 1);
+
+        let memory_guard = output_messages[0].memory_guard_opt.as_ref().unwrap();
+        assert!(memory_guard.memory_usage() > 0);
+        assert_eq!(memory_budget.in_use(), memory_guard.memory_usage());
+
+        drop(output_messages);
+        assert_eq!(memory_budget.in_use(), 0);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_indexer_commits_on_memory_pressure() {
+        let universe = Universe::with_accelerated_time();
+        let pipeline_id = IndexingPipelineId {
+            index_uid: IndexUid::new("test-index"),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_ord: 0,
+        };
+        let doc_mapper: Arc<dyn DocMapper> =
+            Arc::new(serde_json::from_str::<DefaultDocMapper>(DOCMAPPER_SIMPLE_JSON).unwrap());
+        let body_field = doc_mapper.schema().get_field("body").unwrap();
+        let indexing_directory = ScratchDirectory::for_test();
+        let indexing_settings = IndexingSettings::for_test();
+        // The budget is exhausted as soon as the workbench uses any memory.
+        let memory_budget = IndexingMemoryBudget::new(1);
+        let mut metastore = MockMetastore::default();
+        metastore
+            .expect_last_delete_opstamp()
+            .times(1)
+            .returning(move |_| Ok(10));
+        metastore.expect_publish_splits().never();
+        let (index_serializer_mailbox, index_serializer_inbox) = universe.create_test_mailbox();
+        let indexer = Indexer::new(
+            pipeline_id,
+            doc_mapper,
+            Arc::new(metastore),
+            indexing_directory,
+            indexing_settings,
+            index_serializer_mailbox,
+        )
+        .with_memory_budget(memory_budget.clone());
+        let (indexer_mailbox, indexer_handle) = universe.spawn_builder().spawn(indexer);
+        indexer_mailbox
+            .send_message(ProcessedDocBatch {
+                docs: vec![ProcessedDoc {
+                    doc: doc!(body_field=>"doc 1"),
+                    timestamp_opt: None,
+                    partition: 0,
+                    num_bytes: 30,
+                }],
+                checkpoint_delta: SourceCheckpointDelta::from_range(0..1),
+                force_commit: false,
+            })
+            .await
+            .unwrap();
+        // The memory pressure message is queued while the batch is processed.
+        indexer_handle.process_pending_and_observe().await;
+        let indexer_counters = indexer_handle.process_pending_and_observe().await.state;
+        assert_eq!(indexer_counters.num_split_batches_emitted, 1);
+        assert_eq!(indexer_counters.num_docs_in_workbench, 0);
+
+        let output_messages: Vec<IndexedSplitBatchBuilder> =
+            index_serializer_inbox.drain_for_test_typed();
+        assert_eq!(output_messages.len(), 1);
+        assert_eq!(
+            output_messages[0].commit_trigger,
+            CommitTrigger::MemoryPressure
+        );
+        drop(output_messages);
+        assert_eq!(memory_budget.in_use(), 0);
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_indexer_checkpoint_on_all_failed_docs() -> anyhow::Result<()> {
         let pipeline_id = IndexingPipelineId {
@@ -19,13 +19,14 @@
 
 use std::path::PathBuf;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use async_trait::async_trait;
 use quickwit_actors::{
     Actor, ActorContext, ActorExitStatus, ActorHandle, Handler, Health, Mailbox, QueueCapacity,
     Supervisable,
 };
+use quickwit_common::metrics::IntCounter;
 use quickwit_common::KillSwitch;
 use quickwit_config::{IndexingSettings, SourceConfig};
 use quickwit_doc_mapper::DocMapper;
@@ -42,13 +43,19 @@ use crate::actors::publisher::PublisherType;
 use crate::actors::sequencer::Sequencer;
 use crate::actors::uploader::UploaderType;
 use crate::actors::{Indexer, Packager, Publisher, Uploader};
-use crate::models::{IndexingPipelineId, IndexingStatistics, Observe, ScratchDirectory};
-use crate::source::{quickwit_supported_sources, SourceActor, SourceExecutionContext};
+use crate::models::{
+    Drain, IndexingMemoryBudget, IndexingPipelineId, IndexingStatistics, Observe, ScratchDirectory,
+};
+use crate::source::{quickwit_supported_sources, SourceActor, SourceExecutionContext, SourceLag};
 use crate::split_store::IndexingSplitStore;
 use crate::SplitsUpdateMailbox;
 
 const MAX_RETRY_DELAY: Duration = Duration::from_secs(600); // 10 min.
 
+/// Weight of the last observation in the moving average of the pipeline saturation. The pipeline
+/// is observed every second, so the average spans about ten seconds.
+const SATURATION_SMOOTHING_FACTOR: f64 = 0.1;
+
 /// Calculates the wait time based on retry count.
 // retry_count, wait_time
 // 0   2s
@@ -97,6 +104,49 @@ pub struct IndexingPipeline {
     handles: Option<IndexingPipelineHandles>,
     // Killswitch used for the actors in the pipeline. This is not the supervisor killswitch.
     kill_switch: KillSwitch,
+    // Set upon `Drain`: the pipeline is no longer respawned and exits once its actors are done.
+    is_draining: bool,
+    source_lag: SourceLag,
+    saturation_tracker: SaturationTracker,
+}
+
+/// Measures the saturation of a pipeline, i.e. the fraction of time its source spends waiting for
+/// the downstream actors to accept its batches, as a moving average.
+struct SaturationTracker {
+    source_backpressure_micros: IntCounter,
+    last_backpressure_micros: u64,
+    last_observed_at: Instant,
+    saturation: f64,
+}
+
+impl SaturationTracker {
+    fn new() -> Self {
+        let source_backpressure_micros = IntCounter::new(
+            "source_backpressure_micros",
+            "Amount of time the source spent in backpressure (in micros).",
+        )
+        .expect("The counter options should be valid.");
+        Self {
+            source_backpressure_micros,
+            last_backpressure_micros: 0,
+            last_observed_at: Instant::now(),
+            saturation: 0.0,
+        }
+    }
+
+    fn observe(&mut self, now: Instant) -> f64 {
+        let elapsed_micros = now.duration_since(self.last_observed_at).as_micros() as f64;
+        let backpressure_micros = self.source_backpressure_micros.get();
+        if elapsed_micros > 0.0 {
+            let delta_backpressure_micros =
+                backpressure_micros.saturating_sub(self.last_backpressure_micros) as f64;
+            let last_saturation = (delta_backpressure_micros / elapsed_micros).min(1.0);
+            self.saturation += SATURATION_SMOOTHING_FACTOR * (last_saturation - self.saturation);
+        }
+        self.last_backpressure_micros = backpressure_micros;
+        self.last_observed_at = now;
+        self.saturation
+    }
 }
 
 #[async_trait]
@@ -156,6 +206,9 @@ impl IndexingPipeline {
             handles: None,
             kill_switch: KillSwitch::default(),
             statistics: IndexingStatistics::default(),
+            is_draining: false,
+            source_lag: SourceLag::default(),
+            saturation_tracker: SaturationTracker::new(),
         }
     }
 
@@ -310,8 +363,8 @@ impl IndexingPipeline {
             .spawn(uploader);
 
         // Packager
-        let tag_fields = self.params.doc_mapper.tag_named_fields()?;
-        let packager = Packager::new("Packager", tag_fields, uploader_mailbox);
+        let split_pruning_fields = self.params.doc_mapper.split_pruning_fields()?;
+        let packager = Packager::new("Packager", split_pruning_fields, uploader_mailbox);
         let (packager_mailbox, packager_handle) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
@@ -325,14 +378,18 @@ impl IndexingPipeline {
             .spawn(index_serializer);
 
         // Indexer
-        let indexer = Indexer::new(
+        let mut indexer = Indexer::new(
             self.params.pipeline_id.clone(),
             self.params.doc_mapper.clone(),
             self.params.metastore.clone(),
             self.params.indexing_directory.clone(),
             self.params.indexing_settings.clone(),
             index_serializer_mailbox,
-        );
+        )
+        .with_split_labels(self.params.source_config.split_labels.clone());
+        if let Some(memory_budget) = &self.params.memory_budget_opt {
+            indexer = indexer.with_memory_budget(memory_budget.clone());
+        }
         let (indexer_mailbox, indexer_handle) = ctx
             .spawn_actor()
             .set_backpressure_micros_counter(
@@ -377,6 +434,7 @@ impl IndexingPipeline {
                     index_uid: self.params.pipeline_id.index_uid.clone(),
                     queues_dir_path: self.params.queues_dir_path.clone(),
                     source_config: self.params.source_config.clone(),
+                    source_lag: self.source_lag.clone(),
                 }),
                 source_checkpoint,
             ))
@@ -388,6 +446,9 @@ impl IndexingPipeline {
         let (_source_mailbox, source_handle) = ctx
             .spawn_actor()
             .set_mailboxes(source_mailbox, source_inbox)
+            .set_backpressure_micros_counter(
+                self.saturation_tracker.source_backpressure_micros.clone(),
+            )
             .set_kill_switch(self.kill_switch.clone())
             .spawn(actor_source);
 
@@ -447,6 +508,8 @@ impl Handler<Observe> for IndexingPipeline {
                 )
                 .set_generation(self.statistics.generation)
                 .set_num_spawn_attempts(self.statistics.num_spawn_attempts);
+            self.statistics.source_lag = self.source_lag.get();
+            self.statistics.saturation = self.saturation_tracker.observe(Instant::now());
         }
         ctx.schedule_self_msg(Duration::from_secs(1), Observe).await;
         Ok(())
@@ -465,6 +528,14 @@ impl Handler<Supervise> for IndexingPipeline {
         if self.handles.is_some() {
             match self.healthcheck() {
                 Health::Healthy => {}
+                Health::FailureOrUnhealthy if self.is_draining => {
+                    // Respawning the pipeline would pull from the source again. The splits that
+                    // were not published are indexed again by the next pipeline of the source.
+                    self.terminate().await;
+                    return Err(ActorExitStatus::from(anyhow::anyhow!(
+                        "Indexing pipeline failed while draining."
+                    )));
+                }
                 Health::FailureOrUnhealthy => {
                     self.terminate().await;
                     ctx.schedule_self_msg(quickwit_actors::HEARTBEAT, Spawn { retry_count: 0 })
@@ -515,6 +586,31 @@ impl Handler<Spawn> for IndexingPipeline {
     }
 }
 
+#[async_trait]
+impl Handler<Drain> for IndexingPipeline {
+    type Reply = ();
+
+    async fn handle(&mut self, _: Drain, _ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+        info!(
+            pipeline_id=?self.params.pipeline_id,
+            generation=self.generation(),
+            "Draining indexing pipeline."
+        );
+        self.is_draining = true;
+        let handles = match &self.handles {
+            Some(handles) => handles,
+            None => {
+                // The pipeline is not running, so there is nothing in flight to flush.
+                return Err(ActorExitStatus::Success);
+            }
+        };
+        // The source stops pulling and lets the downstream actors flush, publish, and exit in
+        // turn. The pipeline exits successfully once they are all done, see `Supervise`.
+        let _ = handles.source.mailbox().send_message(Drain).await;
+        Ok(())
+    }
+}
+
 pub struct IndexingPipelineParams {
     pub pipeline_id: IndexingPipelineId,
     pub doc_mapper: Arc<dyn DocMapper>,
@@ -528,10 +624,12 @@ pub struct IndexingPipelineParams {
     pub max_concurrent_split_uploads_index: usize,
     pub max_concurrent_split_uploads_merge: usize,
     pub merge_planner_mailbox: Mailbox<MergePlanner>,
+    pub memory_budget_opt: Option<IndexingMemoryBudget>,
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::num::NonZeroUsize;
     use std::path::PathBuf;
     use std::sync::Arc;
@@ -558,6 +656,36 @@ mod tests {
         assert_eq!(wait_duration_before_retry(9), MAX_RETRY_DELAY);
     }
 
+    #[test]
+    fn test_saturation_tracker() {
+        let mut saturation_tracker = SaturationTracker::new();
+        let mut now = saturation_tracker.last_observed_at;
+
+        now += Duration::from_secs(1);
+        assert_eq!(saturation_tracker.observe(now), 0.0);
+
+        // The source waits for the whole period.
+        saturation_tracker
+            .source_backpressure_micros
+            .inc_by(1_000_000);
+        now += Duration::from_secs(1);
+        let saturation = saturation_tracker.observe(now);
+        assert!((saturation - 0.1).abs() < 1e-9);
+
+        for _ in 0..100 {
+            saturation_tracker
+                .source_backpressure_micros
+                .inc_by(1_000_000);
+            now += Duration::from_secs(1);
+            saturation_tracker.observe(now);
+        }
+        assert!(saturation_tracker.saturation > 0.99);
+
+        // The source no longer waits.
+        now += Duration::from_secs(1);
+        assert!(saturation_tracker.observe(now) < 0.9);
+    }
+
     async fn test_indexing_pipeline_num_fails_before_success(
         mut num_fails: usize,
     ) -> anyhow::Result<bool> {
@@ -622,6 +750,7 @@ mod tests {
             source_params: SourceParams::file(PathBuf::from("data/test_corpus.json")),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         let storage = Arc::new(RamStorage::default());
         let split_store = IndexingSplitStore::create_without_local_store(storage.clone());
@@ -639,6 +768,7 @@ mod tests {
             max_concurrent_split_uploads_index: 4,
             max_concurrent_split_uploads_merge: 5,
             merge_planner_mailbox,
+            memory_budget_opt: None,
         };
         let pipeline = IndexingPipeline::new(pipeline_params);
         let (_pipeline_mailbox, pipeline_handle) = universe.spawn_builder().spawn(pipeline);
@@ -713,6 +843,7 @@ mod tests {
             source_params: SourceParams::file(PathBuf::from("data/test_corpus.json")),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         let storage = Arc::new(RamStorage::default());
         let split_store = IndexingSplitStore::create_without_local_store(storage.clone());
@@ -730,6 +861,7 @@ mod tests {
             max_concurrent_split_uploads_index: 4,
             max_concurrent_split_uploads_merge: 5,
             merge_planner_mailbox,
+            memory_budget_opt: None,
         };
         let pipeline = IndexingPipeline::new(pipeline_params);
         let (_pipeline_mailbox, pipeline_handler) = universe.spawn_builder().spawn(pipeline);
@@ -773,6 +905,7 @@ mod tests {
             source_params: SourceParams::Void(VoidSourceParams),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         let storage = Arc::new(RamStorage::default());
         let split_store = IndexingSplitStore::create_without_local_store(storage.clone());
@@ -784,7 +917,9 @@ mod tests {
             split_store: split_store.clone(),
             merge_policy: default_merge_policy(),
             max_concurrent_split_uploads: 2,
+            max_concurrent_merges: 2,
             merge_max_io_num_bytes_per_sec: None,
+            merges_paused: false,
         };
         let merge_pipeline = MergePipeline::new(merge_pipeline_params, universe.spawn_ctx());
         let merge_planner_mailbox = merge_pipeline.merge_planner_mailbox().clone();
@@ -803,6 +938,7 @@ mod tests {
             max_concurrent_split_uploads_index: 4,
             max_concurrent_split_uploads_merge: 5,
             merge_planner_mailbox: merge_planner_mailbox.clone(),
+            memory_budget_opt: None,
         };
         let indexing_pipeline = IndexingPipeline::new(indexing_pipeline_params);
         let (_indexing_pipeline_mailbox, indexing_pipeline_handler) =
@@ -830,6 +966,67 @@ mod tests {
         panic!("Pipeline was apparently not restarted.");
     }
 
+    #[tokio::test]
+    async fn test_indexing_pipeline_drain() {
+        let mut metastore = MockMetastore::default();
+        metastore
+            .expect_index_metadata()
+            .withf(|index_id| index_id == "test-index")
+            .returning(|_| {
+                Ok(IndexMetadata::for_test(
+                    "test-index",
+                    "ram:///indexes/test-index",
+                ))
+            });
+        let universe = Universe::with_accelerated_time();
+        let pipeline_id = IndexingPipelineId {
+            index_uid: IndexUid::new("test-index"),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_ord: 0,
+        };
+        let source_config = SourceConfig {
+            source_id: "test-source".to_string(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::Void(VoidSourceParams),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
+        };
+        let storage = Arc::new(RamStorage::default());
+        let split_store = IndexingSplitStore::create_without_local_store(storage.clone());
+        let (merge_planner_mailbox, _) = universe.create_test_mailbox();
+        let pipeline_params = IndexingPipelineParams {
+            pipeline_id,
+            doc_mapper: Arc::new(default_doc_mapper_for_test()),
+            source_config,
+            indexing_directory: ScratchDirectory::for_test(),
+            indexing_settings: IndexingSettings::for_test(),
+            metastore: Arc::new(metastore),
+            queues_dir_path: PathBuf::from("./queues"),
+            storage,
+            split_store,
+            max_concurrent_split_uploads_index: 4,
+            max_concurrent_split_uploads_merge: 5,
+            merge_planner_mailbox,
+            memory_budget_opt: None,
+        };
+        let pipeline = IndexingPipeline::new(pipeline_params);
+        let (pipeline_mailbox, pipeline_handle) = universe.spawn_builder().spawn(pipeline);
+        let obs = pipeline_handle.process_pending_and_observe().await;
+        assert_eq!(obs.generation, 1);
+
+        // The void source never ends: only draining the pipeline makes it exit successfully.
+        pipeline_mailbox.send_message(Drain).await.unwrap();
+        let (pipeline_exit_status, pipeline_statistics) = pipeline_handle.join().await;
+        assert!(pipeline_exit_status.is_success());
+        assert_eq!(pipeline_statistics.generation, 1);
+        assert!(universe.get::<SourceActor>().is_empty());
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_indexing_pipeline_all_failures_handling() -> anyhow::Result<()> {
         let mut metastore = MockMetastore::default();
@@ -883,6 +1080,7 @@ mod tests {
             source_params: SourceParams::file(PathBuf::from("data/test_corpus.json")),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         let storage = Arc::new(RamStorage::default());
         let split_store = IndexingSplitStore::create_without_local_store(storage.clone());
@@ -918,6 +1116,7 @@ mod tests {
             max_concurrent_split_uploads_index: 4,
             max_concurrent_split_uploads_merge: 5,
             merge_planner_mailbox,
+            memory_budget_opt: None,
         };
         let pipeline = IndexingPipeline::new(pipeline_params);
         let (_pipeline_mailbox, pipeline_handler) = universe.spawn_builder().spawn(pipeline);
@@ -18,18 +18,21 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
 
 use anyhow::Context;
 use async_trait::async_trait;
+use byte_unit::Byte;
 use futures::future::try_join_all;
 use itertools::Itertools;
 use quickwit_actors::{
     Actor, ActorContext, ActorExitStatus, ActorHandle, ActorState, Handler, Healthz, Mailbox,
     Observation,
 };
-use quickwit_cluster::Cluster;
+use quickwit_cluster::{Cluster, IndexerLoad};
 use quickwit_common::fs::get_cache_directory_path;
 use quickwit_config::{
     build_doc_mapper, IndexConfig, IndexerConfig, SourceConfig, INGEST_API_SOURCE_ID,
@@ -46,8 +49,9 @@ use tracing::{debug, error, info, warn};
 use super::merge_pipeline::{MergePipeline, MergePipelineParams};
 use super::MergePlanner;
 use crate::models::{
-    DetachIndexingPipeline, DetachMergePipeline, IndexingPipelineId, Observe, ObservePipeline,
-    ScratchDirectory, SpawnPipeline, WeakScratchDirectory,
+    DetachIndexingPipeline, DetachMergePipeline, Drain, IndexingMemoryBudget, IndexingPipelineId,
+    Observe, ObservePipeline, ScratchDirectory, SetMergeThroughputLimit, SetMergesPaused,
+    SpawnPipeline, WeakScratchDirectory,
 };
 use crate::split_store::{LocalSplitStore, SplitStoreQuota};
 use crate::{IndexingPipeline, IndexingPipelineParams, IndexingSplitStore, IndexingStatistics};
@@ -79,6 +83,8 @@ pub enum IndexingServiceError {
     SpawnPipelinesError {
         pipeline_ids: Vec<IndexingPipelineId>,
     },
+    #[error("Indexing service is draining its pipelines.")]
+    Draining,
 }
 
 impl ServiceError for IndexingServiceError {
@@ -90,10 +96,21 @@ impl ServiceError for IndexingServiceError {
             Self::MetastoreError(_) => ServiceErrorCode::Internal,
             Self::InvalidParams(_) => ServiceErrorCode::BadRequest,
             Self::SpawnPipelinesError { .. } => ServiceErrorCode::Internal,
+            Self::Draining => ServiceErrorCode::Unavailable,
         }
     }
 }
 
+/// A pipeline is saturated when its source waits for the downstream actors of the pipeline more
+/// than half of the time.
+const SATURATED_PIPELINE_THRESHOLD: f64 = 0.5;
+
+/// A pipeline is idle when its source hardly ever waits for the downstream actors of the pipeline.
+const IDLE_PIPELINE_THRESHOLD: f64 = 0.1;
+
+/// Interval at which the merge pipelines are paused or resumed according to the index metadata.
+const MERGES_PAUSED_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
 #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
 pub struct IndexingServiceCounters {
     pub num_running_pipelines: usize,
@@ -138,7 +155,15 @@ pub struct IndexingService {
     indexing_directories: HashMap<(IndexUid, SourceId), WeakScratchDirectory>,
     local_split_store: Arc<LocalSplitStore>,
     max_concurrent_split_uploads: usize,
+    // Shared by the indexers of all the pipelines when `max_indexing_memory_usage` is set.
+    indexing_memory_budget_opt: Option<IndexingMemoryBudget>,
+    max_concurrent_merges: usize,
     merge_pipeline_handles: HashMap<MergePipelineId, MergePipelineHandle>,
+    // Set upon `SetMergeThroughputLimit`, overrides the merge throughput limit of the index
+    // configs.
+    max_merge_write_throughput_override_opt: Option<Byte>,
+    // Set upon `Drain`: no pipeline is spawned afterwards.
+    is_draining: bool,
 }
 
 impl IndexingService {
@@ -170,7 +195,18 @@ impl IndexingService {
             counters: Default::default(),
             indexing_directories: HashMap::new(),
             max_concurrent_split_uploads: indexer_config.max_concurrent_split_uploads,
+            indexing_memory_budget_opt: indexer_config.max_indexing_memory_usage.map(
+                |max_indexing_memory_usage| {
+                    IndexingMemoryBudget::new(max_indexing_memory_usage.get_bytes())
+                },
+            ),
+            // Merges run on the blocking runtime, which has about as many threads as cores.
+            max_concurrent_merges: std::thread::available_parallelism()
+                .map(NonZeroUsize::get)
+                .unwrap_or(1),
             merge_pipeline_handles: HashMap::new(),
+            max_merge_write_throughput_override_opt: None,
+            is_draining: false,
         })
     }
 
@@ -226,6 +262,9 @@ impl IndexingService {
         source_config: SourceConfig,
         pipeline_ord: usize,
     ) -> Result<IndexingPipelineId, IndexingServiceError> {
+        if self.is_draining {
+            return Err(IndexingServiceError::Draining);
+        }
         let index_metadata = self.metastore.index_metadata(index_id.as_str()).await?;
         let pipeline_id = IndexingPipelineId {
             index_uid: index_metadata.index_uid,
@@ -233,12 +272,19 @@ impl IndexingService {
             node_id: self.node_id.clone(),
             pipeline_ord,
         };
-        let index_config = self
+        let index_metadata = self
             .index_metadata(ctx, pipeline_id.index_uid.index_id())
-            .await?
-            .into_index_config();
-        self.spawn_pipeline_inner(ctx, pipeline_id.clone(), index_config, source_config)
             .await?;
+        let merges_paused = index_metadata.merges_paused;
+        let index_config = index_metadata.into_index_config();
+        self.spawn_pipeline_inner(
+            ctx,
+            pipeline_id.clone(),
+            index_config,
+            source_config,
+            merges_paused,
+        )
+        .await?;
         Ok(pipeline_id)
     }
 
@@ -248,6 +294,7 @@ impl IndexingService {
         pipeline_id: IndexingPipelineId,
         index_config: IndexConfig,
         source_config: SourceConfig,
+        merges_paused: bool,
     ) -> Result<(), IndexingServiceError> {
         if self.indexing_pipeline_handles.contains_key(&pipeline_id) {
             return Err(IndexingServiceError::PipelineAlreadyExists {
@@ -285,6 +332,8 @@ impl IndexingService {
                 .resources
                 .max_merge_write_throughput,
             max_concurrent_split_uploads: self.max_concurrent_split_uploads,
+            max_concurrent_merges: self.max_concurrent_merges,
+            merges_paused,
         };
 
         let merge_planner_mailbox = self
@@ -309,6 +358,7 @@ impl IndexingService {
             max_concurrent_split_uploads_merge,
             queues_dir_path,
             merge_planner_mailbox,
+            memory_budget_opt: self.indexing_memory_budget_opt.clone(),
         };
         let pipeline = IndexingPipeline::new(pipeline_params);
         let (_pipeline_mailbox, pipeline_handle) = ctx.spawn_actor().spawn(pipeline);
@@ -328,6 +378,37 @@ impl IndexingService {
         Ok(index_metadata)
     }
 
+    /// Pauses or resumes the merge pipelines according to the metadata of their index.
+    async fn refresh_merges_paused(&mut self, ctx: &ActorContext<Self>) {
+        if self.merge_pipeline_handles.is_empty() {
+            return;
+        }
+        let indexes_metadatas = {
+            let _protect_guard = ctx.protect_zone();
+            match self.metastore.list_indexes_metadatas().await {
+                Ok(indexes_metadatas) => indexes_metadatas,
+                Err(error) => {
+                    warn!(error=?error, "Failed to refresh the paused merges.");
+                    return;
+                }
+            }
+        };
+        let merges_paused_per_index: HashMap<IndexUid, bool> = indexes_metadatas
+            .into_iter()
+            .map(|index_metadata| (index_metadata.index_uid, index_metadata.merges_paused))
+            .collect();
+
+        for (merge_pipeline_id, merge_pipeline_handle) in &self.merge_pipeline_handles {
+            if let Some(&paused) = merges_paused_per_index.get(&merge_pipeline_id.index_uid) {
+                let _ = merge_pipeline_handle
+                    .handle
+                    .mailbox()
+                    .send_message(SetMergesPaused { paused })
+                    .await;
+            }
+        }
+    }
+
     async fn handle_supervise(&mut self) -> Result<(), ActorExitStatus> {
         self.indexing_pipeline_handles
             .retain(
@@ -391,6 +472,7 @@ impl IndexingService {
             });
         self.counters.num_running_merge_pipelines = self.merge_pipeline_handles.len();
         self.update_cluster_running_indexing_tasks().await;
+        self.update_cluster_indexer_load().await;
         Ok(())
     }
 
@@ -441,7 +523,16 @@ impl IndexingService {
         }
         let merge_pipeline = MergePipeline::new(merge_pipeline_params, ctx.spawn_ctx());
         let merge_planner_mailbox = merge_pipeline.merge_planner_mailbox().clone();
-        let (_pipeline_mailbox, pipeline_handle) = ctx.spawn_actor().spawn(merge_pipeline);
+        let (pipeline_mailbox, pipeline_handle) = ctx.spawn_actor().spawn(merge_pipeline);
+
+        if self.max_merge_write_throughput_override_opt.is_some() {
+            let set_merge_throughput_limit = SetMergeThroughputLimit {
+                max_merge_write_throughput_opt: self.max_merge_write_throughput_override_opt,
+            };
+            let _ = pipeline_mailbox
+                .send_message(set_merge_throughput_limit)
+                .await;
+        }
         let merge_pipeline_mailbox_handle = MergePipelineHandle {
             mailbox: merge_planner_mailbox.clone(),
             handle: pipeline_handle,
@@ -463,6 +554,9 @@ impl IndexingService {
         ctx: &ActorContext<Self>,
         physical_indexing_plan_request: ApplyIndexingPlanRequest,
     ) -> Result<(), IndexingServiceError> {
+        if self.is_draining {
+            return Err(IndexingServiceError::Draining);
+        }
         let mut updated_pipeline_ids: HashSet<IndexingPipelineId> = HashSet::new();
         let mut pipeline_ordinals: HashMap<&IndexingTask, usize> = HashMap::new();
         for indexing_task in physical_indexing_plan_request.indexing_tasks.iter() {
@@ -499,6 +593,7 @@ impl IndexingService {
         .await;
 
         self.update_cluster_running_indexing_tasks().await;
+        self.update_cluster_indexer_load().await;
 
         if !failed_spawning_pipeline_ids.is_empty() {
             return Err(IndexingServiceError::SpawnPipelinesError {
@@ -544,6 +639,7 @@ impl IndexingService {
                             new_pipeline_id.clone(),
                             index_metadata.index_config.clone(),
                             source_config.clone(),
+                            index_metadata.merges_paused,
                         )
                         .await
                     {
@@ -629,6 +725,25 @@ impl IndexingService {
         }
     }
 
+    /// Advertises the load of the running pipelines in chitchat cluster state.
+    async fn update_cluster_indexer_load(&self) {
+        let indexer_load = indexer_load(
+            self.indexing_pipeline_handles
+                .values()
+                .map(|pipeline_handle| pipeline_handle.last_observation()),
+        );
+        if let Err(error) = self
+            .cluster
+            .update_self_node_indexer_load(&indexer_load)
+            .await
+        {
+            error!(
+                "Error when updating the cluster state with the indexer load: {}",
+                error
+            );
+        }
+    }
+
     /// Garbage collects ingest API queues of deleted indexes.
     async fn run_ingest_api_queues_gc(&mut self) -> anyhow::Result<()> {
         let Some(ingest_api_service) = &self.ingest_api_service_opt else {
@@ -713,6 +828,20 @@ impl Handler<DetachMergePipeline> for IndexingService {
     }
 }
 
+fn indexer_load(pipeline_statistics: impl Iterator<Item = IndexingStatistics>) -> IndexerLoad {
+    let mut indexer_load = IndexerLoad::default();
+    for statistics in pipeline_statistics {
+        indexer_load.num_pipelines += 1;
+        if statistics.saturation >= SATURATED_PIPELINE_THRESHOLD {
+            indexer_load.num_saturated_pipelines += 1;
+        } else if statistics.saturation < IDLE_PIPELINE_THRESHOLD {
+            indexer_load.num_idle_pipelines += 1;
+        }
+        indexer_load.source_lag += statistics.source_lag.unwrap_or(0);
+    }
+    indexer_load
+}
+
 #[derive(Debug)]
 struct SuperviseLoop;
 
@@ -732,6 +861,25 @@ impl Handler<SuperviseLoop> for IndexingService {
     }
 }
 
+#[derive(Debug)]
+struct RefreshMergesPaused;
+
+#[async_trait]
+impl Handler<RefreshMergesPaused> for IndexingService {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _message: RefreshMergesPaused,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        self.refresh_merges_paused(ctx).await;
+        ctx.schedule_self_msg(MERGES_PAUSED_REFRESH_INTERVAL, RefreshMergesPaused)
+            .await;
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl Actor for IndexingService {
     type ObservableState = IndexingServiceCounters;
@@ -742,6 +890,8 @@ impl Actor for IndexingService {
 
     async fn initialize(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
         self.run_ingest_api_queues_gc().await?;
+        ctx.schedule_self_msg(MERGES_PAUSED_REFRESH_INTERVAL, RefreshMergesPaused)
+            .await;
         self.handle(SuperviseLoop, ctx).await
     }
 }
@@ -790,6 +940,57 @@ impl Handler<ApplyIndexingPlanRequest> for IndexingService {
     }
 }
 
+#[async_trait]
+impl Handler<Drain> for IndexingService {
+    type Reply = IndexingServiceCounters;
+
+    /// Drains all the indexing pipelines and stops spawning new ones. The drain is complete once
+    /// the number of running pipelines drops to zero.
+    async fn handle(
+        &mut self,
+        _message: Drain,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        if !self.is_draining {
+            info!(
+                num_running_pipelines = self.counters.num_running_pipelines,
+                "Draining indexing pipelines."
+            );
+            self.is_draining = true;
+            for pipeline_handle in self.indexing_pipeline_handles.values() {
+                let _ = pipeline_handle.mailbox().send_message(Drain).await;
+            }
+        }
+        Ok(self.observable_state())
+    }
+}
+
+#[async_trait]
+impl Handler<SetMergeThroughputLimit> for IndexingService {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        message: SetMergeThroughputLimit,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        info!(
+            max_merge_write_throughput=?message.max_merge_write_throughput_opt,
+            "Overriding merge throughput limit."
+        );
+        self.max_merge_write_throughput_override_opt = message.max_merge_write_throughput_opt;
+
+        for merge_pipeline_handle in self.merge_pipeline_handles.values() {
+            let _ = merge_pipeline_handle
+                .handle
+                .mailbox()
+                .send_message(message)
+                .await;
+        }
+        Ok(())
+    }
+}
+
 #[async_trait]
 impl Handler<Healthz> for IndexingService {
     type Reply = bool;
@@ -806,11 +1007,12 @@ impl Handler<Healthz> for IndexingService {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::num::NonZeroUsize;
     use std::time::Duration;
 
     use chitchat::transport::ChannelTransport;
-    use quickwit_actors::{Health, ObservationType, Supervisable, Universe, HEARTBEAT};
+    use quickwit_actors::{AskError, Health, ObservationType, Supervisable, Universe, HEARTBEAT};
     use quickwit_cluster::create_cluster_for_test;
     use quickwit_common::rand::append_random_suffix;
     use quickwit_common::uri::Uri;
@@ -891,6 +1093,7 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         let spawn_pipeline_msg = SpawnPipeline {
             index_id: index_id.clone(),
@@ -948,6 +1151,78 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_indexing_service_drain() {
+        quickwit_common::setup_logging_for_tests();
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        let metastore_uri = Uri::from_well_formed("ram:///metastore");
+        let metastore = quickwit_metastore_uri_resolver()
+            .resolve(&metastore_uri)
+            .await
+            .unwrap();
+
+        let index_id = append_random_suffix("test-indexing-service-drain");
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(&index_id, &index_uri);
+        metastore.create_index(index_config).await.unwrap();
+
+        let universe = Universe::with_accelerated_time();
+        let (indexing_service, indexing_service_handle) =
+            spawn_indexing_service(&universe, metastore, cluster).await;
+        let source_config = SourceConfig {
+            source_id: "test-indexing-service-drain--source".to_string(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::void(),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
+        };
+        let spawn_pipeline_msg = SpawnPipeline {
+            index_id: index_id.clone(),
+            pipeline_ord: 0,
+            source_config,
+        };
+        indexing_service
+            .ask_for_res(spawn_pipeline_msg.clone())
+            .await
+            .unwrap();
+
+        let counters = indexing_service.ask(Drain).await.unwrap();
+        assert_eq!(counters.num_running_pipelines, 1);
+
+        for _ in 0..10 {
+            universe.sleep(HEARTBEAT).await;
+            let observation = indexing_service_handle.process_pending_and_observe().await;
+            if observation.num_running_pipelines == 0 {
+                assert_eq!(observation.num_successful_pipelines, 1);
+                assert_eq!(observation.num_failed_pipelines, 0);
+                break;
+            }
+        }
+        assert_eq!(
+            indexing_service_handle
+                .observe()
+                .await
+                .num_running_pipelines,
+            0
+        );
+        // No pipeline is spawned once the indexing service is draining.
+        let spawn_pipeline_error = indexing_service
+            .ask_for_res(spawn_pipeline_msg)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            spawn_pipeline_error,
+            AskError::ErrorReply(IndexingServiceError::Draining)
+        ));
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_indexing_service_supervise_pipelines() {
         quickwit_common::setup_logging_for_tests();
@@ -984,6 +1259,7 @@ mod tests {
             }),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         indexing_service
             .ask_for_res(SpawnPipeline {
@@ -1040,6 +1316,7 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         metastore
             .add_source(index_uid.clone(), source_config_1.clone())
@@ -1076,6 +1353,7 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         metastore
             .add_source(index_uid.clone(), source_config_2.clone())
@@ -1132,6 +1410,10 @@ mod tests {
             HashSet::<_>::from_iter(self_member.indexing_tasks.iter()),
             HashSet::from_iter(indexing_tasks.iter())
         );
+        assert_eq!(
+            self_member.indexer_load_opt.as_ref().unwrap().num_pipelines,
+            indexing_tasks.len() as u64
+        );
         let indexing_tasks = vec![
             IndexingTask {
                 index_uid: metadata.index_uid.to_string(),
@@ -1219,6 +1501,7 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         let index_uid = metastore.create_index(index_config).await.unwrap();
         metastore
@@ -1285,6 +1568,65 @@ mod tests {
         universe.quit().await;
     }
 
+    #[tokio::test]
+    async fn test_indexing_service_refresh_merges_paused() {
+        quickwit_common::setup_logging_for_tests();
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        let metastore_uri = Uri::from_well_formed("ram:///metastore");
+        let metastore = quickwit_metastore_uri_resolver()
+            .resolve(&metastore_uri)
+            .await
+            .unwrap();
+
+        let index_id = append_random_suffix("test-indexing-service-merges-paused");
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(&index_id, &index_uri);
+        let index_uid = metastore.create_index(index_config).await.unwrap();
+
+        let universe = Universe::with_accelerated_time();
+        let (indexing_service, _indexing_service_handle) =
+            spawn_indexing_service(&universe, metastore.clone(), cluster).await;
+        let source_config = SourceConfig {
+            source_id: "test-indexing-service--source".to_string(),
+            max_num_pipelines_per_indexer: NonZeroUsize::new(1).unwrap(),
+            desired_num_pipelines: NonZeroUsize::new(1).unwrap(),
+            enabled: true,
+            source_params: SourceParams::void(),
+            transform_config: None,
+            input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
+        };
+        let pipeline_id = indexing_service
+            .ask_for_res(SpawnPipeline {
+                index_id: index_id.clone(),
+                source_config,
+                pipeline_ord: 0,
+            })
+            .await
+            .unwrap();
+
+        metastore
+            .set_merges_paused(index_uid.clone(), true)
+            .await
+            .unwrap();
+        indexing_service
+            .send_message(RefreshMergesPaused)
+            .await
+            .unwrap();
+        let merge_pipeline_handle = indexing_service
+            .ask_for_res(DetachMergePipeline {
+                pipeline_id: MergePipelineId::from(&pipeline_id),
+            })
+            .await
+            .unwrap();
+        let observation = merge_pipeline_handle.process_pending_and_observe().await;
+        assert!(observation.merges_paused);
+        universe.quit().await;
+    }
+
     #[derive(Debug)]
     struct FreezePipeline;
     #[async_trait]
@@ -1336,6 +1678,7 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         index_metadata
             .sources
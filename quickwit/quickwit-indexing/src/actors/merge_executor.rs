@@ -17,7 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::RangeInclusive;
 use std::path::Path;
 use std::sync::Arc;
@@ -32,7 +32,7 @@ use quickwit_common::io::IoControls;
 use quickwit_common::runtimes::RuntimeType;
 use quickwit_directories::UnionDirectory;
 use quickwit_doc_mapper::DocMapper;
-use quickwit_metastore::{Metastore, SplitMetadata};
+use quickwit_metastore::{Metastore, SplitFieldStats, SplitMetadata};
 use quickwit_proto::metastore_api::DeleteTask;
 use quickwit_query::get_quickwit_tokenizer_manager;
 use quickwit_query::query_ast::QueryAst;
@@ -243,6 +243,8 @@ pub fn merge_split_attrs(
         .map(|split| split.delete_opstamp)
         .min()
         .unwrap_or(0);
+    let labels = merge_labels(splits);
+    let field_stats = merge_field_stats(splits);
     SplitAttrs {
         split_id: merge_split_id,
         partition_id,
@@ -253,9 +255,46 @@ pub fn merge_split_attrs(
         uncompressed_docs_size_in_bytes,
         delete_opstamp,
         num_merge_ops: max_merge_ops(splits) + 1,
+        labels,
+        field_stats,
+        bloom_filters: BTreeMap::new(),
+        field_ranges: BTreeMap::new(),
     }
 }
 
+/// Sums the field statistics of the splits. The statistics derived from the index are recomputed
+/// by the packager once the merged split is built.
+fn merge_field_stats(splits: &[SplitMetadata]) -> BTreeMap<String, SplitFieldStats> {
+    let mut field_stats: BTreeMap<String, SplitFieldStats> = BTreeMap::new();
+    for split in splits {
+        for (field_name, split_field_stats) in &split.field_stats {
+            field_stats
+                .entry(field_name.clone())
+                .or_default()
+                .add(split_field_stats);
+        }
+    }
+    field_stats
+}
+
+/// Returns the labels shared by all the splits, i.e. the labels with the same key and value on
+/// every split.
+fn merge_labels(splits: &[SplitMetadata]) -> BTreeMap<String, String> {
+    let Some((first_split, other_splits)) = splits.split_first() else {
+        return BTreeMap::new();
+    };
+    first_split
+        .labels
+        .iter()
+        .filter(|(key, value)| {
+            other_splits
+                .iter()
+                .all(|split| split.labels.get(*key) == Some(*value))
+        })
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
 fn max_merge_ops(splits: &[SplitMetadata]) -> usize {
     splits
         .iter()
@@ -417,6 +456,10 @@ impl MergeExecutor {
                 uncompressed_docs_size_in_bytes,
                 delete_opstamp: last_delete_opstamp,
                 num_merge_ops: split.num_merge_ops,
+                labels: BTreeMap::new(),
+                field_stats: split.field_stats.clone(),
+                bloom_filters: BTreeMap::new(),
+                field_ranges: BTreeMap::new(),
             },
             index: merged_index,
             split_scratch_directory: merge_scratch_directory,
@@ -17,14 +17,15 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
 use byte_unit::Byte;
 use quickwit_actors::{
-    Actor, ActorContext, ActorExitStatus, ActorHandle, Handler, Health, Inbox, Mailbox,
-    SpawnContext, Supervisable,
+    Actor, ActorContext, ActorExitStatus, ActorHandle, ActorPool, ActorPoolConfig, Handler, Health,
+    Inbox, Mailbox, SpawnContext, Supervisable,
 };
 use quickwit_common::io::IoControls;
 use quickwit_common::KillSwitch;
@@ -34,17 +35,21 @@ use tokio::join;
 use tracing::{debug, error, info, instrument};
 
 use crate::actors::indexing_pipeline::wait_duration_before_retry;
+use crate::actors::merge_planner::PlanMerge;
 use crate::actors::merge_split_downloader::MergeSplitDownloader;
 use crate::actors::publisher::PublisherType;
 use crate::actors::{MergeExecutor, MergePlanner, Packager, Publisher, Uploader, UploaderType};
 use crate::merge_policy::MergePolicy;
-use crate::models::{IndexingPipelineId, MergeStatistics, Observe, ScratchDirectory};
+use crate::models::{
+    IndexingPipelineId, MergeStatistics, Observe, ScratchDirectory, SetMergeThroughputLimit,
+    SetMergesPaused,
+};
 use crate::split_store::IndexingSplitStore;
 
 pub struct MergePipelineHandles {
     pub merge_planner: ActorHandle<MergePlanner>,
     pub merge_split_downloader: ActorHandle<MergeSplitDownloader>,
-    pub merge_executor: ActorHandle<MergeExecutor>,
+    pub merge_executor: ActorHandle<ActorPool<MergeExecutor>>,
     pub merge_packager: ActorHandle<Packager>,
     pub merge_uploader: ActorHandle<Uploader>,
     pub merge_publisher: ActorHandle<Publisher>,
@@ -67,6 +72,12 @@ pub struct MergePipeline {
     statistics: MergeStatistics,
     handles: Option<MergePipelineHandles>,
     kill_switch: KillSwitch,
+    // Throughput limit set at runtime, taking precedence over the one of the index config.
+    max_merge_write_throughput_override_opt: Option<Byte>,
+    // Shared by the split downloader and the merge executors of the current generation.
+    merge_io_controls_opt: Option<IoControls>,
+    // Shared with the merge planners of all the generations.
+    merges_paused: Arc<AtomicBool>,
 }
 
 #[async_trait]
@@ -74,7 +85,10 @@ impl Actor for MergePipeline {
     type ObservableState = MergeStatistics;
 
     fn observable_state(&self) -> Self::ObservableState {
-        self.statistics.clone()
+        MergeStatistics {
+            merges_paused: self.merges_paused.load(Ordering::Relaxed),
+            ..self.statistics.clone()
+        }
     }
 
     fn name(&self) -> String {
@@ -95,6 +109,7 @@ impl MergePipeline {
     pub fn new(params: MergePipelineParams, spawn_ctx: &SpawnContext) -> Self {
         let (merge_planner_mailbox, merge_planner_inbox) = spawn_ctx
             .create_mailbox::<MergePlanner>("MergePlanner", MergePlanner::queue_capacity());
+        let merges_paused = Arc::new(AtomicBool::new(params.merges_paused));
         Self {
             params,
             previous_generations_statistics: Default::default(),
@@ -103,9 +118,19 @@ impl MergePipeline {
             statistics: MergeStatistics::default(),
             merge_planner_inbox,
             merge_planner_mailbox,
+            max_merge_write_throughput_override_opt: None,
+            merge_io_controls_opt: None,
+            merges_paused,
         }
     }
 
+    fn max_merge_write_throughput(&self) -> f64 {
+        self.max_merge_write_throughput_override_opt
+            .or(self.params.merge_max_io_num_bytes_per_sec)
+            .map(|bytes_per_sec| bytes_per_sec.get_bytes() as f64)
+            .unwrap_or(f64::INFINITY)
+    }
+
     pub fn merge_planner_mailbox(&self) -> &Mailbox<MergePlanner> {
         &self.merge_planner_mailbox
     }
@@ -232,22 +257,19 @@ impl MergePipeline {
             .spawn(merge_uploader);
 
         // Merge Packager
-        let tag_fields = self.params.doc_mapper.tag_named_fields()?;
-        let merge_packager = Packager::new("MergePackager", tag_fields, merge_uploader_mailbox);
+        let split_pruning_fields = self.params.doc_mapper.split_pruning_fields()?;
+        let merge_packager = Packager::new(
+            "MergePackager",
+            split_pruning_fields,
+            merge_uploader_mailbox,
+        );
         let (merge_packager_mailbox, merge_packager_handler) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
             .spawn(merge_packager);
 
-        let max_merge_write_throughput: f64 = self
-            .params
-            .merge_max_io_num_bytes_per_sec
-            .as_ref()
-            .map(|bytes_per_sec| bytes_per_sec.get_bytes() as f64)
-            .unwrap_or(f64::INFINITY);
-
         let split_downloader_io_controls = IoControls::default()
-            .set_throughput_limit(max_merge_write_throughput)
+            .set_throughput_limit(self.max_merge_write_throughput())
             .set_index_and_component(
                 self.params.pipeline_id.index_uid.index_id(),
                 "split_downloader_merge",
@@ -258,6 +280,7 @@ impl MergePipeline {
         let merge_executor_io_controls = split_downloader_io_controls
             .clone()
             .set_index_and_component(self.params.pipeline_id.index_uid.index_id(), "merger");
+        self.merge_io_controls_opt = Some(merge_executor_io_controls.clone());
 
         let merge_executor = MergeExecutor::new(
             self.params.pipeline_id.clone(),
@@ -266,10 +289,13 @@ impl MergePipeline {
             merge_executor_io_controls,
             merge_packager_mailbox,
         );
+        // Merges are CPU-bound: the pool adds merge executors while merges are piling up, for
+        // instance when catching up after a burst of indexing, and removes them once idle.
+        let merge_executor_pool_config = ActorPoolConfig::new(1, self.params.max_concurrent_merges);
         let (merge_executor_mailbox, merge_executor_handler) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
-            .spawn(merge_executor);
+            .spawn_pool(merge_executor, merge_executor_pool_config);
 
         let merge_split_downloader = MergeSplitDownloader {
             scratch_directory: self.params.indexing_directory.clone(),
@@ -296,7 +322,8 @@ impl MergePipeline {
             published_splits,
             self.params.merge_policy.clone(),
             merge_split_downloader_mailbox,
-        );
+        )
+        .with_merges_paused(self.merges_paused.clone());
         let (_, merge_planner_handler) = ctx
             .spawn_actor()
             .set_kill_switch(self.kill_switch.clone())
@@ -423,6 +450,50 @@ impl Handler<Spawn> for MergePipeline {
     }
 }
 
+#[async_trait]
+impl Handler<SetMergeThroughputLimit> for MergePipeline {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        message: SetMergeThroughputLimit,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        self.max_merge_write_throughput_override_opt = message.max_merge_write_throughput_opt;
+
+        if let Some(merge_io_controls) = &self.merge_io_controls_opt {
+            merge_io_controls.update_throughput_limit(self.max_merge_write_throughput());
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Handler<SetMergesPaused> for MergePipeline {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        message: SetMergesPaused,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        if self.merges_paused.swap(message.paused, Ordering::Relaxed) == message.paused {
+            return Ok(());
+        }
+        let index_id = self.params.pipeline_id.index_uid.index_id();
+        let source_id = &self.params.pipeline_id.source_id;
+        if message.paused {
+            info!(index_id=%index_id, source_id=%source_id, "Pausing merges.");
+        } else {
+            info!(index_id=%index_id, source_id=%source_id, "Resuming merges.");
+            // If the queue of the merge planner is full, the pending message will trigger the
+            // planning of merges anyway.
+            let _ = self.merge_planner_mailbox.try_send_message(PlanMerge);
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone)]
 pub struct MergePipelineParams {
     pub pipeline_id: IndexingPipelineId,
@@ -432,7 +503,11 @@ pub struct MergePipelineParams {
     pub split_store: IndexingSplitStore,
     pub merge_policy: Arc<dyn MergePolicy>,
     pub max_concurrent_split_uploads: usize, //< TODO share with the indexing pipeline.
+    /// Maximum number of merge executors running concurrently.
+    pub max_concurrent_merges: usize,
     pub merge_max_io_num_bytes_per_sec: Option<Byte>,
+    /// Whether the merges of the index are paused when the pipeline is spawned.
+    pub merges_paused: bool,
 }
 
 #[cfg(test)]
@@ -474,7 +549,9 @@ mod tests {
             split_store,
             merge_policy: default_merge_policy(),
             max_concurrent_split_uploads: 2,
+            max_concurrent_merges: 2,
             merge_max_io_num_bytes_per_sec: None,
+            merges_paused: false,
         };
         let pipeline = MergePipeline::new(pipeline_params, universe.spawn_ctx());
         let (_pipeline_mailbox, pipeline_handler) = universe.spawn_builder().spawn(pipeline);
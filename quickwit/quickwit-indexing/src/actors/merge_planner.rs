@@ -19,6 +19,7 @@
 
 use std::cmp::Reverse;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
 use async_trait::async_trait;
@@ -48,6 +49,8 @@ pub struct MergePlanner {
     /// a merge operation is dropped after the publish of the merged split.
     /// Used for observability.
     ongoing_merge_operations_inventory: Inventory<MergeOperation>,
+    /// Shared with the merge pipeline. While set, no merge operation is planned.
+    merges_paused: Arc<AtomicBool>,
 }
 
 #[async_trait]
@@ -142,11 +145,19 @@ impl MergePlanner {
             merge_policy,
             merge_split_downloader_mailbox,
             ongoing_merge_operations_inventory: Inventory::default(),
+            merges_paused: Arc::new(AtomicBool::new(false)),
         };
         merge_planner.record_splits(published_splits);
         merge_planner
     }
 
+    /// Pauses the planning of merge operations while `merges_paused` is set. New splits are
+    /// still recorded and become candidates to merge operations once merges are resumed.
+    pub fn with_merges_paused(mut self, merges_paused: Arc<AtomicBool>) -> Self {
+        self.merges_paused = merges_paused;
+        self
+    }
+
     fn record_split(&mut self, new_split: SplitMetadata) {
         if self.merge_policy.is_mature(&new_split) {
             return;
@@ -197,6 +208,9 @@ impl MergePlanner {
     }
 
     async fn send_merge_ops(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+        if self.merges_paused.load(Ordering::Relaxed) {
+            return Ok(());
+        }
         // We do not want to simply schedule all available merge operations here.
         //
         // The reason is that in presence of partitioning, it is very possible
@@ -265,7 +279,7 @@ fn belongs_to_pipeline(pipeline_id: &IndexingPipelineId, split: &SplitMetadata)
 struct RefreshMetric;
 
 #[derive(Debug)]
-struct PlanMerge;
+pub(crate) struct PlanMerge;
 
 #[async_trait]
 impl Handler<RefreshMetric> for MergePlanner {
@@ -296,6 +310,7 @@ pub struct MergePlannerState {
 
 #[cfg(test)]
 mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
     use std::sync::Arc;
     use std::time::Duration;
 
@@ -310,6 +325,7 @@ mod tests {
     use tantivy::TrackedObject;
     use time::OffsetDateTime;
 
+    use super::PlanMerge;
     use crate::actors::MergePlanner;
     use crate::merge_policy::{
         merge_policy_from_settings, MergeOperation, MergePolicy, StableLogMergePolicy,
@@ -420,6 +436,58 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_merge_planner_paused() -> anyhow::Result<()> {
+        let universe = Universe::with_accelerated_time();
+        let (merge_split_downloader_mailbox, merge_split_downloader_inbox) =
+            universe.create_test_mailbox();
+        let pipeline_id = IndexingPipelineId {
+            index_uid: IndexUid::new("test-index"),
+            source_id: "test-source".to_string(),
+            node_id: "test-node".to_string(),
+            pipeline_ord: 0,
+        };
+        let merge_policy_config = ConstWriteAmplificationMergePolicyConfig {
+            merge_factor: 2,
+            max_merge_factor: 2,
+            ..Default::default()
+        };
+        let indexing_settings = IndexingSettings {
+            merge_policy: MergePolicyConfig::ConstWriteAmplification(merge_policy_config),
+            ..Default::default()
+        };
+        let merge_policy: Arc<dyn MergePolicy> = merge_policy_from_settings(&indexing_settings);
+        let merges_paused = Arc::new(AtomicBool::new(true));
+        let merge_planner = MergePlanner::new(
+            pipeline_id,
+            Vec::new(),
+            merge_policy,
+            merge_split_downloader_mailbox,
+        )
+        .with_merges_paused(merges_paused.clone());
+        let (merge_planner_mailbox, merge_planner_handle) =
+            universe.spawn_builder().spawn(merge_planner);
+        let message = NewSplits {
+            new_splits: vec![
+                split_metadata_for_test("1_a", 1, 10, 0),
+                split_metadata_for_test("1_b", 1, 10, 0),
+            ],
+        };
+        merge_planner_mailbox.send_message(message).await?;
+        merge_planner_handle.process_pending_and_observe().await;
+        assert!(merge_split_downloader_inbox.drain_for_test().is_empty());
+
+        merges_paused.store(false, Ordering::Relaxed);
+        merge_planner_mailbox.send_message(PlanMerge).await?;
+        merge_planner_handle.process_pending_and_observe().await;
+        let merge_ops: Vec<TrackedObject<MergeOperation>> =
+            merge_split_downloader_inbox.drain_for_test_typed();
+        assert_eq!(merge_ops.len(), 1);
+        assert_eq!(merge_ops[0].splits_as_slice().len(), 2);
+        universe.assert_quit().await;
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_merge_planner_priority() -> anyhow::Result<()> {
         let universe = Universe::with_accelerated_time();
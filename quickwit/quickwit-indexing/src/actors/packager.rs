@@ -17,7 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::io;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
@@ -29,10 +29,13 @@ use itertools::Itertools;
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, QueueCapacity};
 use quickwit_common::runtimes::RuntimeType;
 use quickwit_directories::write_hotcache;
+use quickwit_doc_mapper::range_pruning::FieldValueRange;
 use quickwit_doc_mapper::tag_pruning::append_to_tag_set;
-use quickwit_doc_mapper::NamedField;
+use quickwit_doc_mapper::{BloomFilter, NamedField, SplitPruningFields};
+use quickwit_metastore::SplitFieldStats;
+use tantivy::columnar::HasAssociatedColumnType;
 use tantivy::schema::FieldType;
-use tantivy::{InvertedIndexReader, ReloadPolicy, SegmentMeta};
+use tantivy::{InvertedIndexReader, ReloadPolicy, Searcher, SegmentMeta};
 use tokio::runtime::Handle;
 use tracing::{debug, info, instrument, warn};
 
@@ -43,6 +46,15 @@ const MAX_VALUES_PER_TAG_FIELD: usize = if cfg!(any(test, feature = "testsuite")
     1000
 };
 
+/// Maximum distinct values of a bloom filter field within a split. The bloom filter of a field
+/// holding that many values takes about 3.6KB, which keeps the split metadata, where the bloom
+/// filters are stored, small.
+const MAX_VALUES_PER_BLOOM_FILTER_FIELD: usize = if cfg!(any(test, feature = "testsuite")) {
+    6
+} else {
+    3_000
+};
+
 use crate::actors::Uploader;
 use crate::models::{
     EmptySplit, IndexedSplit, IndexedSplitBatch, PackagedSplit, PackagedSplitBatch,
@@ -55,6 +67,8 @@ use crate::models::{
 /// This includes the following steps:
 /// - commit: this step is CPU heavy
 /// - identifying the list of tags for the splits, and labelling it accordingly
+/// - building the bloom filters of the bloom filter fields
+/// - computing the minimum and maximum values of the min/max fields
 /// - creating a bundle file
 /// - computing the hotcache
 /// - appending it to the split file.
@@ -64,20 +78,20 @@ use crate::models::{
 pub struct Packager {
     actor_name: &'static str,
     uploader_mailbox: Mailbox<Uploader>,
-    /// List of tag fields ([`Vec<NamedField>`]) defined in the index config.
-    tag_fields: Vec<NamedField>,
+    /// Tag, bloom filter, and min/max fields defined in the index config.
+    split_pruning_fields: SplitPruningFields,
 }
 
 impl Packager {
     pub fn new(
         actor_name: &'static str,
-        tag_fields: Vec<NamedField>,
+        split_pruning_fields: SplitPruningFields,
         uploader_mailbox: Mailbox<Uploader>,
     ) -> Packager {
         Packager {
             actor_name,
             uploader_mailbox,
-            tag_fields,
+            split_pruning_fields,
         }
     }
 
@@ -89,7 +103,7 @@ impl Packager {
         let segment_metas = split.index.searchable_segment_metas()?;
         assert_eq!(segment_metas.len(), 1);
         let packaged_split =
-            create_packaged_split(&segment_metas[..], split, &self.tag_fields, ctx)?;
+            create_packaged_split(&segment_metas[..], split, &self.split_pruning_fields, ctx)?;
         Ok(packaged_split)
     }
 }
@@ -269,13 +283,48 @@ fn try_extract_terms(
     Ok(terms)
 }
 
+/// Builds the bloom filter of a field from its terms.
+///
+/// Returns an error if the field has more than `max_values` distinct values. The split then has no
+/// bloom filter for the field, which hurts split pruning but does not affect results.
+fn try_build_bloom_filter(
+    named_field: &NamedField,
+    inv_indexes: &[Arc<InvertedIndexReader>],
+    max_values: usize,
+) -> anyhow::Result<BloomFilter> {
+    let num_terms = inv_indexes
+        .iter()
+        .map(|inv_index| inv_index.terms().num_terms())
+        .sum::<usize>();
+    if num_terms > max_values {
+        bail!(
+            "Number of unique terms for bloom filter field {} > {}.",
+            named_field.name,
+            max_values
+        );
+    }
+    let mut bloom_filter = BloomFilter::with_capacity(num_terms);
+    for inv_index in inv_indexes {
+        let mut terms_streamer = inv_index.terms().stream()?;
+        while let Some((term_data, _)) = terms_streamer.next() {
+            bloom_filter.insert(term_data);
+        }
+    }
+    Ok(bloom_filter)
+}
+
 fn create_packaged_split(
     segment_metas: &[SegmentMeta],
-    split: IndexedSplit,
-    tag_fields: &[NamedField],
+    mut split: IndexedSplit,
+    split_pruning_fields: &SplitPruningFields,
     ctx: &ActorContext<Packager>,
 ) -> anyhow::Result<PackagedSplit> {
     info!(split_id = split.split_id(), "create-packaged-split");
+    let SplitPruningFields {
+        tag_fields,
+        bloom_filter_fields,
+        min_max_fields,
+    } = split_pruning_fields;
     let split_files = list_split_files(segment_metas, &split.split_scratch_directory)?;
 
     // Extracts tag values from inverted indexes only when a field cardinality is less
@@ -307,6 +356,59 @@ fn create_packaged_split(
 
     ctx.record_progress();
 
+    debug!(
+        split_id = split.split_id(),
+        bloom_filter_fields =? bloom_filter_fields,
+        "build-bloom-filters"
+    );
+    for named_field in bloom_filter_fields {
+        let inverted_indexes = index_reader
+            .searcher()
+            .segment_readers()
+            .iter()
+            .map(|segment| segment.inverted_index(named_field.field))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        match try_build_bloom_filter(
+            named_field,
+            &inverted_indexes,
+            MAX_VALUES_PER_BLOOM_FILTER_FIELD,
+        ) {
+            Ok(bloom_filter) => {
+                split
+                    .split_attrs
+                    .bloom_filters
+                    .insert(named_field.name.clone(), bloom_filter);
+            }
+            Err(bloom_filter_error) => {
+                warn!(
+                    err=?bloom_filter_error,
+                    "No bloom filter will be registered in the split metadata."
+                );
+            }
+        }
+    }
+    ctx.record_progress();
+
+    debug!(
+        split_id = split.split_id(),
+        min_max_fields =? min_max_fields,
+        "compute-field-ranges"
+    );
+    for named_field in min_max_fields {
+        if let Some(field_range) = compute_field_range(named_field, &index_reader.searcher())? {
+            split
+                .split_attrs
+                .field_ranges
+                .insert(named_field.name.clone(), field_range);
+        }
+    }
+    ctx.record_progress();
+
+    debug!(split_id = split.split_id(), "compute-field-stats");
+    update_index_field_stats(&index_reader.searcher(), &mut split.split_attrs.field_stats)?;
+    ctx.record_progress();
+
     debug!(split_id = split.split_id(), "build-hotcache");
     let mut hotcache_bytes = Vec::new();
     build_hotcache(split.split_scratch_directory.path(), &mut hotcache_bytes)?;
@@ -322,6 +424,98 @@ fn create_packaged_split(
     Ok(packaged_split)
 }
 
+/// Computes the minimum and maximum values of a numeric fast field over the segments of a split.
+///
+/// Returns `None` if the field has no value in the split.
+fn compute_field_range(
+    named_field: &NamedField,
+    searcher: &Searcher,
+) -> anyhow::Result<Option<FieldValueRange>> {
+    let field_range_opt = match named_field.field_type {
+        FieldType::I64(_) => fast_field_min_max::<i64>(searcher, &named_field.name)?
+            .map(|(min, max)| FieldValueRange::I64 { min, max }),
+        FieldType::U64(_) => fast_field_min_max::<u64>(searcher, &named_field.name)?
+            .map(|(min, max)| FieldValueRange::U64 { min, max }),
+        FieldType::F64(_) => fast_field_min_max::<f64>(searcher, &named_field.name)?
+            .map(|(min, max)| FieldValueRange::F64 { min, max }),
+        _ => bail!(
+            "Min/max field `{}` must be a numeric fast field.",
+            named_field.name
+        ),
+    };
+    Ok(field_range_opt)
+}
+
+fn fast_field_min_max<T: HasAssociatedColumnType>(
+    searcher: &Searcher,
+    field_name: &str,
+) -> anyhow::Result<Option<(T, T)>> {
+    let mut min_max_opt: Option<(T, T)> = None;
+
+    for segment_reader in searcher.segment_readers() {
+        let Some(column) = segment_reader.fast_fields().column_opt::<T>(field_name)? else {
+            continue;
+        };
+        if column.values.num_vals() == 0 {
+            continue;
+        }
+        let (segment_min, segment_max) = (column.min_value(), column.max_value());
+        min_max_opt = Some(match min_max_opt {
+            Some((min, max)) => (
+                if segment_min < min { segment_min } else { min },
+                if segment_max > max { segment_max } else { max },
+            ),
+            None => (segment_min, segment_max),
+        });
+    }
+    Ok(min_max_opt)
+}
+
+/// Computes the field statistics derived from the index: the number of indexed tokens and the
+/// size of the fast field data. Statistics inherited from merged splits are overwritten.
+fn update_index_field_stats(
+    searcher: &Searcher,
+    field_stats: &mut BTreeMap<String, SplitFieldStats>,
+) -> anyhow::Result<()> {
+    for stats in field_stats.values_mut() {
+        stats.num_tokens = 0;
+        stats.fast_field_num_bytes = 0;
+    }
+    let schema = searcher.schema();
+
+    for (field, field_entry) in schema.fields() {
+        if !field_entry.is_indexed() {
+            continue;
+        }
+        let mut num_tokens = 0;
+
+        for segment_reader in searcher.segment_readers() {
+            num_tokens += segment_reader.inverted_index(field)?.total_num_tokens();
+        }
+        if num_tokens > 0 {
+            field_stats
+                .entry(field_entry.name().to_string())
+                .or_default()
+                .num_tokens = num_tokens;
+        }
+    }
+    let space_usage = searcher.space_usage()?;
+
+    for segment_space_usage in space_usage.segments() {
+        for (field, field_usage) in segment_space_usage.fast_fields().fields() {
+            let fast_field_num_bytes = field_usage.total().get_bytes();
+
+            if fast_field_num_bytes > 0 {
+                field_stats
+                    .entry(schema.get_field_name(*field).to_string())
+                    .or_default()
+                    .fast_field_num_bytes += fast_field_num_bytes;
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Reads u64 from stored term data.
 fn u64_from_term_data(data: &[u8]) -> anyhow::Result<u64> {
     let u64_bytes: [u8; 8] = data[0..8]
@@ -332,6 +526,7 @@ fn u64_from_term_data(data: &[u8]) -> anyhow::Result<u64> {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::ops::RangeInclusive;
 
     use quickwit_actors::{ObservationType, Universe};
@@ -351,6 +546,7 @@ mod tests {
         let mut schema_builder = Schema::builder();
         let text_field = schema_builder.add_text_field("text", TEXT);
         let timestamp_field = schema_builder.add_u64_field("timestamp", FAST);
+        let count_field = schema_builder.add_i64_field("count", FAST);
         let tag_str = schema_builder.add_text_field("tag_str", STRING);
         let tag_many = schema_builder.add_text_field("tag_many", STRING);
         let tag_u64 =
@@ -372,6 +568,7 @@ mod tests {
                 let doc = doc!(
                     text_field => format!("timestamp is {timestamp:?}"),
                     timestamp_field => timestamp,
+                    count_field => num as i64,
                     tag_str => "value",
                     tag_many => format!("many-{num}"),
                     tag_u64 => 42u64,
@@ -413,6 +610,10 @@ mod tests {
                 replaced_split_ids: Vec::new(),
                 delete_opstamp: 0,
                 num_merge_ops: 0,
+                labels: BTreeMap::new(),
+                field_stats: BTreeMap::new(),
+                bloom_filters: BTreeMap::new(),
+                field_ranges: BTreeMap::new(),
             },
             index,
             split_scratch_directory,
@@ -451,7 +652,15 @@ mod tests {
                 "tag_str", "tag_many", "tag_u64", "tag_i64", "tag_f64", "tag_bool",
             ],
         );
-        let packager = Packager::new("TestPackager", tag_fields, mailbox);
+        let bloom_filter_fields =
+            get_tag_fields(indexed_split.index.schema(), &["tag_str", "tag_many"]);
+        let min_max_fields = get_tag_fields(indexed_split.index.schema(), &["count"]);
+        let split_pruning_fields = SplitPruningFields {
+            tag_fields,
+            bloom_filter_fields,
+            min_max_fields,
+        };
+        let packager = Packager::new("TestPackager", split_pruning_fields, mailbox);
         let (packager_mailbox, packager_handle) = universe.spawn_builder().spawn(packager);
         packager_mailbox
             .send_message(IndexedSplitBatch {
@@ -494,6 +703,24 @@ mod tests {
                     ..=DateTime::from_timestamp_secs(1628203640)
             )
         );
+        let field_stats = &split.split_attrs.field_stats;
+        assert!(field_stats["text"].num_tokens > 0);
+        assert_eq!(field_stats["text"].fast_field_num_bytes, 0);
+        assert_eq!(field_stats["timestamp"].num_tokens, 0);
+        assert!(field_stats["timestamp"].fast_field_num_bytes > 0);
+
+        // `tag_many` has too many distinct values to get a bloom filter.
+        let bloom_filters = &split.split_attrs.bloom_filters;
+        assert_eq!(bloom_filters.len(), 1);
+        assert!(bloom_filters["tag_str"].may_contain(b"value"));
+        assert!(!bloom_filters["tag_str"].may_contain(b"other-value"));
+
+        let field_ranges = &split.split_attrs.field_ranges;
+        assert_eq!(field_ranges.len(), 1);
+        assert_eq!(
+            field_ranges["count"],
+            FieldValueRange::I64 { min: 1, max: 9 }
+        );
         universe.assert_quit().await;
         Ok(())
     }
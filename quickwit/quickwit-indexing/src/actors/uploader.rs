@@ -23,20 +23,21 @@ use std::mem;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
-use anyhow::{bail, Context};
+use anyhow::bail;
 use async_trait::async_trait;
 use fail::fail_point;
 use itertools::Itertools;
-use once_cell::sync::OnceCell;
-use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, QueueCapacity};
+use quickwit_actors::{
+    Actor, ActorContext, ActorExitStatus, Handler, Mailbox, QueueCapacity, ResourceGuard,
+};
 use quickwit_metastore::checkpoint::IndexCheckpointDelta;
 use quickwit_metastore::{Metastore, SplitMetadata};
 use quickwit_proto::IndexUid;
 use quickwit_storage::SplitPayloadBuilder;
 use serde::Serialize;
 use tantivy::TrackedObject;
+use tokio::sync::oneshot;
 use tokio::sync::oneshot::Sender;
-use tokio::sync::{oneshot, Semaphore, SemaphorePermit};
 use tracing::{info, instrument, warn, Instrument, Span};
 
 use crate::actors::sequencer::{Sequencer, SequencerCommand};
@@ -48,14 +49,15 @@ use crate::models::{
 };
 use crate::split_store::IndexingSplitStore;
 
-/// The following two semaphores ensures that, we have at most `max_concurrent_split_uploads` split
-/// uploads can happen at the same time, as configured in the `IndexerConfig`.
+/// The following two universe resource budgets ensure that we have at most
+/// `max_concurrent_split_uploads` split uploads happening at the same time, as configured in the
+/// `IndexerConfig`.
 ///
-/// This "budget" is actually split into two semaphores: one for the indexing pipeline and the merge
+/// This "budget" is actually split into two: one for the indexing pipeline and the merge
 /// pipeline. The idea is that the merge pipeline is by nature a bit irregular, and we don't want it
 /// to stall the indexing pipeline, decreasing its throughput.
-static CONCURRENT_UPLOAD_PERMITS_INDEX: OnceCell<Semaphore> = OnceCell::new();
-static CONCURRENT_UPLOAD_PERMITS_MERGE: OnceCell<Semaphore> = OnceCell::new();
+const CONCURRENT_UPLOADS_INDEX_BUDGET: &str = "concurrent-uploads-index";
+const CONCURRENT_UPLOADS_MERGE_BUDGET: &str = "concurrent-uploads-merge";
 
 #[derive(Clone, Copy, Debug)]
 pub enum UploaderType {
@@ -187,39 +189,35 @@ impl Uploader {
             counters: Default::default(),
         }
     }
-    async fn acquire_semaphore(
-        &self,
-        ctx: &ActorContext<Self>,
-    ) -> anyhow::Result<SemaphorePermit<'static>> {
+    async fn acquire_upload_permit(&self, ctx: &ActorContext<Self>) -> ResourceGuard {
         let _guard = ctx.protect_zone();
-        let (concurrent_upload_permits_once_cell, concurrent_upload_permits_gauge) =
+        let (concurrent_uploads_budget_name, concurrent_upload_permits_gauge) =
             match self.uploader_type {
                 UploaderType::IndexUploader => (
-                    &CONCURRENT_UPLOAD_PERMITS_INDEX,
+                    CONCURRENT_UPLOADS_INDEX_BUDGET,
                     INDEXER_METRICS
                         .available_concurrent_upload_permits
                         .with_label_values(["indexer"]),
                 ),
                 UploaderType::MergeUploader => (
-                    &CONCURRENT_UPLOAD_PERMITS_MERGE,
+                    CONCURRENT_UPLOADS_MERGE_BUDGET,
                     INDEXER_METRICS
                         .available_concurrent_upload_permits
                         .with_label_values(["merger"]),
                 ),
                 UploaderType::DeleteUploader => (
-                    &CONCURRENT_UPLOAD_PERMITS_MERGE,
+                    CONCURRENT_UPLOADS_MERGE_BUDGET,
                     INDEXER_METRICS
                         .available_concurrent_upload_permits
                         .with_label_values(["merger"]),
                 ),
             };
-        let concurrent_upload_permits = concurrent_upload_permits_once_cell
-            .get_or_init(|| Semaphore::const_new(self.max_concurrent_split_uploads));
-        concurrent_upload_permits_gauge.set(concurrent_upload_permits.available_permits() as i64);
-        concurrent_upload_permits
-            .acquire()
-            .await
-            .context("The uploader semaphore is closed. (This should never happen.)")
+        let concurrent_uploads_budget = ctx.spawn_ctx().register_resource_budget(
+            concurrent_uploads_budget_name,
+            self.max_concurrent_split_uploads as u64,
+        );
+        concurrent_upload_permits_gauge.set(concurrent_uploads_budget.available() as i64);
+        concurrent_uploads_budget.acquire(1).await
     }
 }
 
@@ -272,7 +270,7 @@ impl Handler<PackagedSplitBatch> for Uploader {
             .get_split_update_sender(ctx)
             .await?;
 
-        // The permit will be added back manually to the semaphore the task after it is finished.
+        // The permit will be given back to the budget by the task after it is finished.
         // This is not a valid usage of protected zone here.
         //
         // Protected zone are supposed to be used when the cause for blocking is
@@ -280,7 +278,7 @@ impl Handler<PackagedSplitBatch> for Uploader {
         // For instance, when sending a message on a downstream actor with a saturated
         // mailbox.
         // This is meant to be fixed with ParallelActors.
-        let permit_guard = self.acquire_semaphore(ctx).await?;
+        let permit_guard = self.acquire_upload_permit(ctx).await;
         let kill_switch = ctx.kill_switch().clone();
         let split_ids = batch.split_ids();
         if kill_switch.is_dead() {
@@ -455,6 +453,7 @@ async fn upload_split(
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::path::PathBuf;
 
     use quickwit_actors::{ObservationType, Universe};
@@ -521,6 +520,10 @@ mod tests {
                         split_id: "test-split".to_string(),
                         delete_opstamp: 10,
                         num_merge_ops: 0,
+                        labels: BTreeMap::new(),
+                        field_stats: BTreeMap::new(),
+                        bloom_filters: BTreeMap::new(),
+                        field_ranges: BTreeMap::new(),
                     },
                     split_scratch_directory,
                     tags: Default::default(),
@@ -627,6 +630,10 @@ mod tests {
                 ],
                 delete_opstamp: 0,
                 num_merge_ops: 0,
+                labels: BTreeMap::new(),
+                field_stats: BTreeMap::new(),
+                bloom_filters: BTreeMap::new(),
+                field_ranges: BTreeMap::new(),
             },
             split_scratch_directory: split_scratch_directory_1,
             tags: Default::default(),
@@ -650,6 +657,10 @@ mod tests {
                 ],
                 delete_opstamp: 0,
                 num_merge_ops: 0,
+                labels: BTreeMap::new(),
+                field_stats: BTreeMap::new(),
+                bloom_filters: BTreeMap::new(),
+                field_ranges: BTreeMap::new(),
             },
             split_scratch_directory: split_scratch_directory_2,
             tags: Default::default(),
@@ -762,6 +773,10 @@ mod tests {
                         split_id: "test-split".to_string(),
                         delete_opstamp: 10,
                         num_merge_ops: 0,
+                        labels: BTreeMap::new(),
+                        field_stats: BTreeMap::new(),
+                        bloom_filters: BTreeMap::new(),
+                        field_ranges: BTreeMap::new(),
                     },
                     split_scratch_directory,
                     tags: Default::default(),
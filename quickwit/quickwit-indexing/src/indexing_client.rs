@@ -23,16 +23,23 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use quickwit_actors::Mailbox;
+use quickwit_common::tower::make_endpoint;
 use quickwit_config::service::QuickwitService;
 use quickwit_grpc_clients::service_client_pool::ServiceClient;
-use quickwit_proto::indexing_api::ApplyIndexingPlanRequest;
-use quickwit_proto::tonic::transport::{Channel, Endpoint, Uri};
+use quickwit_proto::indexing_api::{indexing_service_client, ApplyIndexingPlanRequest};
+use quickwit_proto::tonic::codegen::InterceptedService;
+use quickwit_proto::tonic::transport::Channel;
+use quickwit_proto::SpanContextInterceptor;
 
 use crate::IndexingService;
 
 #[derive(Clone)]
 enum IndexingServiceClientImpl {
-    Grpc(quickwit_proto::indexing_api::indexing_service_client::IndexingServiceClient<Channel>),
+    Grpc(
+        indexing_service_client::IndexingServiceClient<
+            InterceptedService<Channel, SpanContextInterceptor>,
+        >,
+    ),
     Local(Mailbox<IndexingService>),
 }
 
@@ -72,8 +79,8 @@ impl ServiceClient for IndexingServiceClient {
 
 impl IndexingServiceClient {
     pub fn from_grpc_client(
-        client: quickwit_proto::indexing_api::indexing_service_client::IndexingServiceClient<
-            Channel,
+        client: indexing_service_client::IndexingServiceClient<
+            InterceptedService<Channel, SpanContextInterceptor>,
         >,
         grpc_addr: SocketAddr,
     ) -> Self {
@@ -114,16 +121,14 @@ impl IndexingServiceClient {
 pub async fn create_indexing_service_client(
     grpc_addr: SocketAddr,
 ) -> anyhow::Result<IndexingServiceClient> {
-    let uri = Uri::builder()
-        .scheme("http")
-        .authority(grpc_addr.to_string().as_str())
-        .path_and_query("/")
-        .build()?;
-    let channel = Endpoint::from(uri)
+    let channel = make_endpoint(grpc_addr)
         .connect_timeout(Duration::from_secs(5))
         .connect_lazy();
     let client = IndexingServiceClient::from_grpc_client(
-        quickwit_proto::indexing_api::indexing_service_client::IndexingServiceClient::new(channel),
+        indexing_service_client::IndexingServiceClient::with_interceptor(
+            channel,
+            SpanContextInterceptor,
+        ),
         grpc_addr,
     );
     Ok(client)
@@ -134,8 +139,9 @@ mod tests {
     use quickwit_actors::Universe;
     use quickwit_grpc_clients::create_channel_from_duplex_stream;
     use quickwit_proto::indexing_api::indexing_service_server::IndexingServiceServer;
-    use quickwit_proto::indexing_api::ApplyIndexingPlanRequest;
+    use quickwit_proto::indexing_api::{indexing_service_client, ApplyIndexingPlanRequest};
     use quickwit_proto::tonic::transport::Server;
+    use quickwit_proto::SpanContextInterceptor;
 
     use super::IndexingServiceClient;
     use crate::grpc_adapter::GrpcIndexingAdapter;
@@ -171,10 +177,10 @@ mod tests {
         });
         let channel = create_channel_from_duplex_stream(client).await.unwrap();
         let grpc_addr = ([127, 0, 0, 1], 1).into();
-        let grpc_client =
-            quickwit_proto::indexing_api::indexing_service_client::IndexingServiceClient::new(
-                channel,
-            );
+        let grpc_client = indexing_service_client::IndexingServiceClient::with_interceptor(
+            channel,
+            SpanContextInterceptor,
+        );
         let mut client = IndexingServiceClient::from_grpc_client(grpc_client, grpc_addr);
         client
             .apply_indexing_plan(ApplyIndexingPlanRequest {
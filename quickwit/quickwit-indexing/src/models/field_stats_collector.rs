@@ -0,0 +1,130 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use quickwit_metastore::SplitFieldStats;
+use tantivy::schema::{Field, Schema};
+use tantivy::Document;
+
+/// Collects the per-field statistics of the documents added to a split: number of documents
+/// with a value, number of values and length of the text and bytes values.
+///
+/// The statistics derived from the index itself (tokens, fast field sizes) are computed by the
+/// packager once the split is built.
+#[derive(Debug, Default)]
+pub struct FieldStatsCollector {
+    num_docs: u64,
+    // Indexed by field ID.
+    fields: Vec<FieldStatsEntry>,
+}
+
+#[derive(Debug, Default)]
+struct FieldStatsEntry {
+    // Ordinal (starting at 1) of the last document that had a value for the field.
+    last_doc_ord: u64,
+    stats: SplitFieldStats,
+}
+
+impl FieldStatsCollector {
+    pub fn record_doc(&mut self, doc: &Document) {
+        self.num_docs += 1;
+
+        for field_value in doc.field_values() {
+            let field_id = field_value.field().field_id() as usize;
+
+            if field_id >= self.fields.len() {
+                self.fields.resize_with(field_id + 1, Default::default);
+            }
+            let entry = &mut self.fields[field_id];
+
+            if entry.last_doc_ord != self.num_docs {
+                entry.last_doc_ord = self.num_docs;
+                entry.stats.num_docs_with_value += 1;
+            }
+            entry.stats.num_values += 1;
+
+            let value = field_value.value();
+            let value_num_bytes = if let Some(text) = value.as_text() {
+                text.len()
+            } else if let Some(bytes) = value.as_bytes() {
+                bytes.len()
+            } else {
+                0
+            };
+            entry.stats.value_num_bytes += value_num_bytes as u64;
+        }
+    }
+
+    /// Returns the statistics of the fields that had at least one value, keyed by field name.
+    pub fn field_stats(&self, schema: &Schema) -> BTreeMap<String, SplitFieldStats> {
+        self.fields
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.stats.num_values > 0)
+            .map(|(field_id, entry)| {
+                let field = Field::from_field_id(field_id as u32);
+                let field_name = schema.get_field_name(field).to_string();
+                (field_name, entry.stats.clone())
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tantivy::doc;
+    use tantivy::schema::{FAST, STRING, TEXT};
+
+    use super::*;
+
+    #[test]
+    fn test_field_stats_collector() {
+        let mut schema_builder = Schema::builder();
+        let body_field = schema_builder.add_text_field("body", TEXT);
+        let tag_field = schema_builder.add_text_field("tag", STRING);
+        let count_field = schema_builder.add_u64_field("count", FAST);
+        schema_builder.add_text_field("unused", TEXT);
+        let schema = schema_builder.build();
+
+        let mut collector = FieldStatsCollector::default();
+        collector
+            .record_doc(&doc!(body_field => "hello world", tag_field => "a", tag_field => "bc"));
+        collector.record_doc(&doc!(body_field => "hello", count_field => 3u64));
+        collector.record_doc(&doc!(count_field => 4u64));
+
+        let field_stats = collector.field_stats(&schema);
+        assert_eq!(field_stats.len(), 3);
+
+        let body_stats = &field_stats["body"];
+        assert_eq!(body_stats.num_docs_with_value, 2);
+        assert_eq!(body_stats.num_values, 2);
+        assert_eq!(body_stats.value_num_bytes, 16);
+
+        let tag_stats = &field_stats["tag"];
+        assert_eq!(tag_stats.num_docs_with_value, 1);
+        assert_eq!(tag_stats.num_values, 2);
+        assert_eq!(tag_stats.value_num_bytes, 3);
+
+        let count_stats = &field_stats["count"];
+        assert_eq!(count_stats.num_docs_with_value, 2);
+        assert_eq!(count_stats.num_values, 2);
+        assert_eq!(count_stats.value_num_bytes, 0);
+    }
+}
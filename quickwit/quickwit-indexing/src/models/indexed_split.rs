@@ -17,6 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::BTreeMap;
 use std::fmt;
 use std::path::Path;
 
@@ -29,12 +30,16 @@ use tracing::{instrument, Span};
 
 use crate::controlled_directory::ControlledDirectory;
 use crate::merge_policy::MergeOperation;
-use crate::models::{IndexingPipelineId, PublishLock, ScratchDirectory, SplitAttrs};
+use crate::models::{
+    FieldStatsCollector, IndexingMemoryGuard, IndexingPipelineId, PublishLock, ScratchDirectory,
+    SplitAttrs,
+};
 use crate::new_split_id;
 
 pub struct IndexedSplitBuilder {
     pub split_attrs: SplitAttrs,
     pub index_writer: tantivy::SingleSegmentIndexWriter,
+    pub field_stats_collector: FieldStatsCollector,
     pub split_scratch_directory: ScratchDirectory,
     pub controlled_directory_opt: Option<ControlledDirectory>,
 }
@@ -79,6 +84,7 @@ impl IndexedSplitBuilder {
         pipeline_id: IndexingPipelineId,
         partition_id: u64,
         last_delete_opstamp: u64,
+        labels: BTreeMap<String, String>,
         scratch_directory: ScratchDirectory,
         index_builder: IndexBuilder,
         io_controls: IoControls,
@@ -108,8 +114,13 @@ impl IndexedSplitBuilder {
                 time_range: None,
                 delete_opstamp: last_delete_opstamp,
                 num_merge_ops: 0,
+                labels,
+                field_stats: BTreeMap::new(),
+                bloom_filters: BTreeMap::new(),
+                field_ranges: BTreeMap::new(),
             },
             index_writer,
+            field_stats_collector: FieldStatsCollector::default(),
             split_scratch_directory,
             controlled_directory_opt: Some(controlled_directory),
         })
@@ -130,8 +141,9 @@ impl IndexedSplitBuilder {
             num_merge_ops=%self.split_attrs.num_merge_ops,
         )
     )]
-    pub fn finalize(self) -> anyhow::Result<IndexedSplit> {
+    pub fn finalize(mut self) -> anyhow::Result<IndexedSplit> {
         let index = self.index_writer.finalize()?;
+        self.split_attrs.field_stats = self.field_stats_collector.field_stats(&index.schema());
         Ok(IndexedSplit {
             split_attrs: self.split_attrs,
             index,
@@ -168,6 +180,7 @@ pub enum CommitTrigger {
     NoMoreDocs,
     NumDocsLimit,
     MemoryLimit,
+    MemoryPressure,
     ForceCommit,
 }
 
@@ -178,6 +191,8 @@ pub struct IndexedSplitBatchBuilder {
     pub checkpoint_delta: Option<IndexCheckpointDelta>,
     pub publish_lock: PublishLock,
     pub commit_trigger: CommitTrigger,
+    /// Memory accounted for in the node indexing memory budget until the splits are finalized.
+    pub memory_guard_opt: Option<IndexingMemoryGuard>,
 }
 
 /// Sends notifications to the Publisher that the last batch of splits was emtpy.
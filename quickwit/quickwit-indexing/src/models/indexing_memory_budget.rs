@@ -0,0 +1,330 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use quickwit_actors::{TrySendError, WeakMailbox};
+use tokio::sync::Notify;
+use tracing::info;
+use ulid::Ulid;
+
+use crate::actors::Indexer;
+
+/// Asks an indexer to commit its workbench because the node indexing memory budget is exhausted.
+#[derive(Clone, Copy, Debug)]
+pub struct MemoryPressure {
+    pub workbench_id: Ulid,
+}
+
+/// Memory budget shared by the indexers of all the pipelines running on a node.
+///
+/// Instead of reserving a fixed amount of memory per indexer, workbenches report the memory
+/// actually used by their index writers. When the total reaches the capacity of the budget, the
+/// indexer building the largest workbench is asked to commit it. The memory of a committed
+/// workbench remains accounted for until its splits are finalized by the serializer.
+#[derive(Clone)]
+pub struct IndexingMemoryBudget {
+    inner: Arc<InnerIndexingMemoryBudget>,
+}
+
+struct InnerIndexingMemoryBudget {
+    capacity: u64,
+    workbenches: Mutex<Workbenches>,
+    released: Notify,
+}
+
+#[derive(Default)]
+struct Workbenches {
+    in_use: u64,
+    entries: HashMap<Ulid, WorkbenchEntry>,
+}
+
+struct WorkbenchEntry {
+    memory_usage: u64,
+    // Mailbox of the indexer building the workbench. It is taken once the workbench is committed
+    // or asked to commit, so that the workbench is not picked again.
+    indexer_mailbox_opt: Option<WeakMailbox<Indexer>>,
+}
+
+impl fmt::Debug for IndexingMemoryBudget {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("IndexingMemoryBudget")
+            .field("capacity", &self.capacity())
+            .field("in_use", &self.in_use())
+            .finish()
+    }
+}
+
+impl IndexingMemoryBudget {
+    pub fn new(capacity: u64) -> Self {
+        let inner = InnerIndexingMemoryBudget {
+            capacity,
+            workbenches: Mutex::new(Workbenches::default()),
+            released: Notify::new(),
+        };
+        IndexingMemoryBudget {
+            inner: Arc::new(inner),
+        }
+    }
+
+    pub fn capacity(&self) -> u64 {
+        self.inner.capacity
+    }
+
+    /// Returns the memory used by the workbenches that are being built or serialized.
+    pub fn in_use(&self) -> u64 {
+        self.inner.workbenches.lock().unwrap().in_use
+    }
+
+    /// Waits until the memory in use drops below the capacity of the budget. Meanwhile, the
+    /// largest workbench is asked to commit so that its memory gets released.
+    pub async fn wait_for_available_memory(&self) {
+        loop {
+            // The notification future must be created before checking the budget, otherwise a
+            // release happening in between would be missed.
+            let released = self.inner.released.notified();
+            {
+                let mut workbenches = self.inner.workbenches.lock().unwrap();
+                if workbenches.in_use < self.inner.capacity {
+                    return;
+                }
+                self.flush_largest_workbench(&mut workbenches);
+            }
+            released.await;
+        }
+    }
+
+    /// Starts tracking the memory used by the workbench `workbench_id`, built by the indexer
+    /// owning `indexer_mailbox`. The workbench is tracked until the returned guard is dropped.
+    pub fn register_workbench(
+        &self,
+        workbench_id: Ulid,
+        indexer_mailbox: WeakMailbox<Indexer>,
+    ) -> IndexingMemoryGuard {
+        let entry = WorkbenchEntry {
+            memory_usage: 0,
+            indexer_mailbox_opt: Some(indexer_mailbox),
+        };
+        self.inner
+            .workbenches
+            .lock()
+            .unwrap()
+            .entries
+            .insert(workbench_id, entry);
+        IndexingMemoryGuard {
+            budget: self.clone(),
+            workbench_id,
+        }
+    }
+
+    /// Asks the indexer building the largest workbench to commit it if the budget is exhausted.
+    fn flush_largest_workbench(&self, workbenches: &mut Workbenches) {
+        if workbenches.in_use < self.inner.capacity {
+            return;
+        }
+        let Some((&workbench_id, entry)) = workbenches
+            .entries
+            .iter_mut()
+            .filter(|(_, entry)| entry.indexer_mailbox_opt.is_some())
+            .max_by_key(|(_, entry)| entry.memory_usage)
+        else {
+            return;
+        };
+        let Some(indexer_mailbox) = entry
+            .indexer_mailbox_opt
+            .take()
+            .and_then(|weak_mailbox| weak_mailbox.upgrade())
+        else {
+            return;
+        };
+        info!(
+            workbench_id=%workbench_id,
+            memory_usage=entry.memory_usage,
+            memory_in_use=workbenches.in_use,
+            memory_capacity=self.inner.capacity,
+            "indexing-memory-pressure"
+        );
+        if let Err(TrySendError::Full(_)) =
+            indexer_mailbox.try_send_message(MemoryPressure { workbench_id })
+        {
+            // The indexer is busy: we will ask again on the next update.
+            entry.indexer_mailbox_opt = Some(indexer_mailbox.downgrade());
+        }
+    }
+}
+
+/// Memory accounted for a workbench in an [`IndexingMemoryBudget`]. The memory is released when
+/// the guard is dropped.
+#[must_use]
+pub struct IndexingMemoryGuard {
+    budget: IndexingMemoryBudget,
+    workbench_id: Ulid,
+}
+
+impl fmt::Debug for IndexingMemoryGuard {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("IndexingMemoryGuard")
+            .field("workbench_id", &self.workbench_id)
+            .field("memory_usage", &self.memory_usage())
+            .finish()
+    }
+}
+
+impl IndexingMemoryGuard {
+    /// Returns the memory accounted for the workbench.
+    pub fn memory_usage(&self) -> u64 {
+        self.budget
+            .inner
+            .workbenches
+            .lock()
+            .unwrap()
+            .entries
+            .get(&self.workbench_id)
+            .map(|entry| entry.memory_usage)
+            .unwrap_or(0)
+    }
+
+    /// Records the memory used by the workbench. If the budget is exhausted, the largest
+    /// workbench is asked to commit.
+    pub fn update_memory_usage(&self, memory_usage: u64) {
+        let mut workbenches = self.budget.inner.workbenches.lock().unwrap();
+        let Some(entry) = workbenches.entries.get_mut(&self.workbench_id) else {
+            return;
+        };
+        let previous_memory_usage = std::mem::replace(&mut entry.memory_usage, memory_usage);
+        workbenches.in_use = workbenches.in_use + memory_usage - previous_memory_usage;
+
+        if memory_usage < previous_memory_usage {
+            self.budget.inner.released.notify_waiters();
+        }
+        self.budget.flush_largest_workbench(&mut workbenches);
+    }
+
+    /// Marks the workbench as committed: it is no longer a candidate for a flush but its memory
+    /// remains accounted for until the guard is dropped.
+    pub fn mark_as_committed(&self) {
+        if let Some(entry) = self
+            .budget
+            .inner
+            .workbenches
+            .lock()
+            .unwrap()
+            .entries
+            .get_mut(&self.workbench_id)
+        {
+            entry.indexer_mailbox_opt = None;
+        }
+    }
+}
+
+impl Drop for IndexingMemoryGuard {
+    fn drop(&mut self) {
+        let mut workbenches = self.budget.inner.workbenches.lock().unwrap();
+        if let Some(entry) = workbenches.entries.remove(&self.workbench_id) {
+            workbenches.in_use -= entry.memory_usage;
+        }
+        self.budget.inner.released.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use quickwit_actors::Universe;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_indexing_memory_budget_flushes_largest_workbench() {
+        let universe = Universe::with_accelerated_time();
+        let (indexer_mailbox_1, indexer_inbox_1) = universe.create_test_mailbox::<Indexer>();
+        let (indexer_mailbox_2, indexer_inbox_2) = universe.create_test_mailbox::<Indexer>();
+
+        let budget = IndexingMemoryBudget::new(100);
+        let workbench_id_1 = Ulid::new();
+        let guard_1 = budget.register_workbench(workbench_id_1, indexer_mailbox_1.downgrade());
+        let workbench_id_2 = Ulid::new();
+        let guard_2 = budget.register_workbench(workbench_id_2, indexer_mailbox_2.downgrade());
+
+        guard_1.update_memory_usage(60);
+        guard_2.update_memory_usage(30);
+        assert_eq!(budget.in_use(), 90);
+        assert!(indexer_inbox_1.drain_for_test().is_empty());
+        assert!(indexer_inbox_2.drain_for_test().is_empty());
+
+        guard_2.update_memory_usage(40);
+        assert_eq!(budget.in_use(), 100);
+        let messages: Vec<MemoryPressure> = indexer_inbox_1.drain_for_test_typed();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].workbench_id, workbench_id_1);
+        assert!(indexer_inbox_2.drain_for_test().is_empty());
+
+        // The first workbench was already asked to commit, so the second one is next.
+        guard_2.update_memory_usage(45);
+        assert!(indexer_inbox_1.drain_for_test().is_empty());
+        let messages: Vec<MemoryPressure> = indexer_inbox_2.drain_for_test_typed();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].workbench_id, workbench_id_2);
+
+        // Both workbenches are committed: no one can be flushed anymore.
+        guard_2.mark_as_committed();
+        guard_2.update_memory_usage(50);
+        assert!(indexer_inbox_2.drain_for_test().is_empty());
+        assert_eq!(guard_2.memory_usage(), 50);
+
+        drop(guard_1);
+        assert_eq!(budget.in_use(), 50);
+        drop(guard_2);
+        assert_eq!(budget.in_use(), 0);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_indexing_memory_budget_wait_for_available_memory() {
+        let universe = Universe::with_accelerated_time();
+        let (indexer_mailbox, indexer_inbox) = universe.create_test_mailbox::<Indexer>();
+
+        let budget = IndexingMemoryBudget::new(100);
+        budget.wait_for_available_memory().await;
+
+        let workbench_id = Ulid::new();
+        let guard = budget.register_workbench(workbench_id, indexer_mailbox.downgrade());
+        guard.update_memory_usage(120);
+        let messages: Vec<MemoryPressure> = indexer_inbox.drain_for_test_typed();
+        assert_eq!(messages.len(), 1);
+
+        let budget_clone = budget.clone();
+        let wait_handle =
+            tokio::spawn(async move { budget_clone.wait_for_available_memory().await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!wait_handle.is_finished());
+
+        drop(guard);
+        tokio::time::timeout(Duration::from_secs(1), wait_handle)
+            .await
+            .unwrap()
+            .unwrap();
+        universe.assert_quit().await;
+    }
+}
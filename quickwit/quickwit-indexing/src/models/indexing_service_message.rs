@@ -17,6 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use byte_unit::Byte;
 use quickwit_config::SourceConfig;
 
 use super::IndexingPipelineId;
@@ -29,6 +30,19 @@ pub struct SpawnPipeline {
     pub pipeline_ord: usize,
 }
 
+/// Overrides the merge write throughput limit of all the merge pipelines of the node, including the
+/// ones spawned afterwards. `None` restores the limits set in the index configs.
+#[derive(Clone, Copy, Debug)]
+pub struct SetMergeThroughputLimit {
+    pub max_merge_write_throughput_opt: Option<Byte>,
+}
+
+/// Pauses or resumes the planning of new merges. Ongoing merges are not interrupted.
+#[derive(Clone, Copy, Debug)]
+pub struct SetMergesPaused {
+    pub paused: bool,
+}
+
 #[derive(Clone, Debug)]
 pub struct ShutdownPipelines {
     pub index_id: String,
@@ -48,6 +48,11 @@ pub struct IndexingStatistics {
     pub generation: usize,
     /// Number of successive pipeline spawn attempts.
     pub num_spawn_attempts: usize,
+    /// Number of records available in the source and not indexed yet, if the source measures it.
+    pub source_lag: Option<u64>,
+    /// Fraction of time the source spends waiting for the downstream actors of the pipeline,
+    /// averaged over the last seconds. A saturated pipeline cannot keep up with its source.
+    pub saturation: f64,
 }
 
 impl IndexingStatistics {
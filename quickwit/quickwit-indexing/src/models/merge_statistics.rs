@@ -36,6 +36,8 @@ pub struct MergeStatistics {
     pub num_spawn_attempts: usize,
     /// Number of merges currently in progress.
     pub num_ongoing_merges: usize,
+    /// Whether the planning of new merges is paused.
+    pub merges_paused: bool,
 }
 
 impl MergeStatistics {
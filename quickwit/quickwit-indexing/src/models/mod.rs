@@ -19,7 +19,9 @@
 
 #![allow(rustdoc::invalid_html_tags)]
 
+mod field_stats_collector;
 mod indexed_split;
+mod indexing_memory_budget;
 mod indexing_pipeline_id;
 mod indexing_service_message;
 mod indexing_statistics;
@@ -34,13 +36,16 @@ mod raw_doc_batch;
 mod scratch_directory;
 mod split_attrs;
 
+pub use field_stats_collector::FieldStatsCollector;
 pub use indexed_split::{
     CommitTrigger, EmptySplit, IndexedSplit, IndexedSplitBatch, IndexedSplitBatchBuilder,
     IndexedSplitBuilder,
 };
+pub use indexing_memory_budget::{IndexingMemoryBudget, IndexingMemoryGuard, MemoryPressure};
 pub use indexing_pipeline_id::IndexingPipelineId;
 pub use indexing_service_message::{
-    DetachIndexingPipeline, DetachMergePipeline, ObservePipeline, SpawnPipeline,
+    DetachIndexingPipeline, DetachMergePipeline, ObservePipeline, SetMergeThroughputLimit,
+    SetMergesPaused, SpawnPipeline,
 };
 pub use indexing_statistics::IndexingStatistics;
 pub use merge_planner_message::NewSplits;
@@ -56,3 +61,8 @@ pub use split_attrs::{create_split_metadata, SplitAttrs};
 
 #[derive(Clone, Copy, Debug)]
 pub struct Observe;
+
+/// Asks an indexing pipeline to stop pulling from its source, flush and publish its in-flight
+/// splits, and then exit successfully.
+#[derive(Clone, Copy, Debug)]
+pub struct Drain;
@@ -17,11 +17,13 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::ops::{Range, RangeInclusive};
 
-use quickwit_metastore::SplitMetadata;
+use quickwit_doc_mapper::range_pruning::FieldValueRange;
+use quickwit_doc_mapper::BloomFilter;
+use quickwit_metastore::{SplitFieldStats, SplitMetadata};
 use tantivy::DateTime;
 use time::OffsetDateTime;
 
@@ -61,6 +63,20 @@ pub struct SplitAttrs {
 
     // Number of merge operation the split has been through so far.
     pub num_merge_ops: usize,
+
+    /// Key-value labels attached to the split.
+    pub labels: BTreeMap<String, String>,
+
+    /// Per-field statistics, keyed by field name.
+    pub field_stats: BTreeMap<String, SplitFieldStats>,
+
+    /// Bloom filters of the bloom filter fields, keyed by field name. They are built by the
+    /// packager.
+    pub bloom_filters: BTreeMap<String, BloomFilter>,
+
+    /// Minimum and maximum values of the min/max fields, keyed by field name. They are computed
+    /// by the packager.
+    pub field_ranges: BTreeMap<String, FieldValueRange>,
 }
 
 impl fmt::Debug for SplitAttrs {
@@ -76,6 +92,7 @@ impl fmt::Debug for SplitAttrs {
             )
             .field("num_docs", &self.num_docs)
             .field("num_merge_ops", &self.num_merge_ops)
+            .field("labels", &self.labels)
             .finish()
     }
 }
@@ -102,5 +119,9 @@ pub fn create_split_metadata(
         footer_offsets,
         delete_opstamp: split_attrs.delete_opstamp,
         num_merge_ops: split_attrs.num_merge_ops,
+        labels: split_attrs.labels.clone(),
+        field_stats: split_attrs.field_stats.clone(),
+        bloom_filters: split_attrs.bloom_filters.clone(),
+        field_ranges: split_attrs.field_ranges.clone(),
     }
 }
@@ -170,6 +170,7 @@ impl TypedSourceFactory for FileSourceFactory {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::io::Write;
     use std::num::NonZeroUsize;
     use std::path::PathBuf;
@@ -203,6 +204,7 @@ mod tests {
                     source_params: SourceParams::File(params.clone()),
                     transform_config: None,
                     input_format: SourceInputFormat::Json,
+                    split_labels: BTreeMap::new(),
                 },
             ),
             params,
@@ -269,6 +271,7 @@ mod tests {
                     source_params: SourceParams::File(params.clone()),
                     transform_config: None,
                     input_format: SourceInputFormat::Json,
+                    split_labels: BTreeMap::new(),
                 },
             ),
             params,
@@ -359,6 +362,7 @@ mod tests {
                     source_params: SourceParams::File(params.clone()),
                     transform_config: None,
                     input_format: SourceInputFormat::Json,
+                    split_labels: BTreeMap::new(),
                 },
             ),
             params,
@@ -217,6 +217,7 @@ impl TypedSourceFactory for IngestApiSourceFactory {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::num::NonZeroUsize;
     use std::time::Duration;
 
@@ -267,6 +268,7 @@ mod tests {
             source_params: SourceParams::IngestApi,
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         }
     }
 
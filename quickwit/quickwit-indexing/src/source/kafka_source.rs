@@ -101,6 +101,8 @@ struct KafkaMessage {
     payload_len: u64,
     partition: i32,
     offset: i64,
+    /// Offset of the next message to be produced in the partition, if known by the consumer.
+    high_watermark_opt: Option<i64>,
 }
 
 impl From<BorrowedMessage<'_>> for KafkaMessage {
@@ -110,6 +112,7 @@ impl From<BorrowedMessage<'_>> for KafkaMessage {
             payload_len: message.payload_len() as u64,
             partition: message.partition(),
             offset: message.offset(),
+            high_watermark_opt: None,
         }
     }
 }
@@ -212,6 +215,8 @@ pub struct KafkaSourceState {
     pub num_invalid_messages: u64,
     /// Number of rebalances the consumer went through.
     pub num_rebalances: usize,
+    /// Number of messages available in each partition after the last message received.
+    pub partition_lags: HashMap<i32, u64>,
 }
 
 /// A `KafkaSource` consumes a topic and forwards its messages to an `Indexer`.
@@ -302,7 +307,7 @@ impl KafkaSource {
             payload_len,
             partition,
             offset,
-            ..
+            high_watermark_opt,
         } = message;
 
         if let Some(doc) = doc_opt {
@@ -313,6 +318,11 @@ impl KafkaSource {
         self.state.num_bytes_processed += payload_len;
         self.state.num_messages_processed += 1;
 
+        if let Some(high_watermark) = high_watermark_opt {
+            let partition_lag = (high_watermark - offset - 1).max(0) as u64;
+            self.state.partition_lags.insert(partition, partition_lag);
+        }
+
         let partition_id = self
             .state
             .assigned_partitions
@@ -366,6 +376,7 @@ impl KafkaSource {
 
         self.state.assigned_partitions.clear();
         self.state.current_positions.clear();
+        self.state.partition_lags.clear();
         self.state.num_inactive_partitions = 0;
 
         let mut next_offsets: Vec<(i32, Offset)> = Vec::with_capacity(partitions.len());
@@ -527,6 +538,9 @@ impl Source for KafkaSource {
             let message = batch.build();
             ctx.send_message(doc_processor_mailbox, message).await?;
         }
+        self.ctx
+            .source_lag
+            .set(self.state.partition_lags.values().sum());
         if self.should_exit() {
             info!(topic = %self.topic, "Reached end of topic.");
             ctx.send_exit_with_success(doc_processor_mailbox).await?;
@@ -572,6 +586,7 @@ impl Source for KafkaSource {
             "num_messages_processed": self.state.num_messages_processed,
             "num_invalid_messages": self.state.num_invalid_messages,
             "num_rebalances": self.state.num_rebalances,
+            "lag": self.state.partition_lags.values().sum::<u64>(),
         })
     }
 }
@@ -601,7 +616,17 @@ fn spawn_consumer_poll_loop(
         while !events_tx.is_closed() {
             if let Some(message_res) = consumer.poll(Some(Duration::from_secs(1))) {
                 let event = match message_res {
-                    Ok(message) => KafkaEvent::Message(message.into()),
+                    Ok(message) => {
+                        // The watermarks are cached by the client from the last fetch responses:
+                        // no request is sent to the brokers.
+                        let high_watermark_opt = consumer
+                            .get_watermark_offsets(&topic, message.partition())
+                            .ok()
+                            .map(|(_low_watermark, high_watermark)| high_watermark);
+                        let mut kafka_message = KafkaMessage::from(message);
+                        kafka_message.high_watermark_opt = high_watermark_opt;
+                        KafkaEvent::Message(kafka_message)
+                    }
                     Err(KafkaError::PartitionEOF(partition)) => KafkaEvent::PartitionEOF(partition),
                     Err(error) => KafkaEvent::Error(anyhow!(error)),
                 };
@@ -759,6 +784,7 @@ fn message_payload_to_doc(message: &BorrowedMessage) -> Option<Bytes> {
 
 #[cfg(all(test, feature = "kafka-broker-tests"))]
 mod kafka_broker_tests {
+    use std::collections::BTreeMap;
     use std::num::NonZeroUsize;
     use std::path::PathBuf;
 
@@ -776,7 +802,7 @@ mod kafka_broker_tests {
 
     use super::*;
     use crate::new_split_id;
-    use crate::source::{quickwit_supported_sources, SourceActor};
+    use crate::source::{quickwit_supported_sources, SourceActor, SourceLag};
 
     fn create_admin_client() -> anyhow::Result<AdminClient<DefaultClientContext>> {
         let admin_client = ClientConfig::new()
@@ -881,6 +907,7 @@ mod kafka_broker_tests {
             }),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         (source_id, source_config)
     }
@@ -986,6 +1013,7 @@ mod kafka_broker_tests {
             payload_len: 7,
             partition: 1,
             offset: 0,
+            high_watermark_opt: None,
         };
         kafka_source
             .process_message(message, &mut batch)
@@ -1007,6 +1035,7 @@ mod kafka_broker_tests {
             payload_len: 8,
             partition: 1,
             offset: 1,
+            high_watermark_opt: Some(10),
         };
         kafka_source
             .process_message(message, &mut batch)
@@ -1029,6 +1058,7 @@ mod kafka_broker_tests {
             payload_len: 8,
             partition: 2,
             offset: 42,
+            high_watermark_opt: Some(43),
         };
         kafka_source
             .process_message(message, &mut batch)
@@ -1045,6 +1075,8 @@ mod kafka_broker_tests {
         assert_eq!(kafka_source.state.num_bytes_processed, 23);
         assert_eq!(kafka_source.state.num_messages_processed, 3);
         assert_eq!(kafka_source.state.num_invalid_messages, 1);
+        assert_eq!(kafka_source.state.partition_lags.get(&1), Some(&8));
+        assert_eq!(kafka_source.state.partition_lags.get(&2), Some(&0));
 
         let mut expected_checkpoint_delta = SourceCheckpointDelta::default();
         expected_checkpoint_delta
@@ -1061,6 +1093,7 @@ mod kafka_broker_tests {
             payload_len: 8,
             partition: 3,
             offset: 42,
+            high_watermark_opt: None,
         };
         kafka_source
             .process_message(message, &mut batch)
@@ -1090,6 +1123,7 @@ mod kafka_broker_tests {
             index_uid,
             queues_dir_path: PathBuf::from("./queues"),
             source_config,
+            source_lag: SourceLag::default(),
         });
         let ignored_checkpoint = SourceCheckpoint::default();
         let mut kafka_source = KafkaSource::try_new(ctx, params, ignored_checkpoint)
@@ -1273,6 +1307,7 @@ mod kafka_broker_tests {
                 "num_messages_processed": 0,
                 "num_invalid_messages": 0,
                 "num_rebalances": 0,
+                "lag": 0,
             });
             assert_eq!(exit_state, expected_state);
         }
@@ -1305,6 +1340,7 @@ mod kafka_broker_tests {
                         index_uid,
                         queues_dir_path: PathBuf::from("./queues"),
                         source_config,
+                        source_lag: SourceLag::default(),
                     }),
                     SourceCheckpoint::default(),
                 )
@@ -1353,6 +1389,7 @@ mod kafka_broker_tests {
                 "num_messages_processed": 9,
                 "num_invalid_messages": 3,
                 "num_rebalances": 0,
+                "lag": 0,
             });
             assert_eq!(exit_state, expected_state);
         }
@@ -1374,6 +1411,7 @@ mod kafka_broker_tests {
                         index_uid,
                         queues_dir_path: PathBuf::from("./queues"),
                         source_config,
+                        source_lag: SourceLag::default(),
                     }),
                     SourceCheckpoint::default(),
                 )
@@ -1419,6 +1457,7 @@ mod kafka_broker_tests {
                 "num_messages_processed": 5,
                 "num_invalid_messages": 2,
                 "num_rebalances": 0,
+                "lag": 0,
             });
             assert_eq!(exit_state, expected_exit_state);
         }
@@ -70,7 +70,7 @@ mod vec_source;
 mod void_source;
 
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use anyhow::bail;
@@ -92,11 +92,12 @@ use quickwit_proto::IndexUid;
 use serde_json::Value as JsonValue;
 pub use source_factory::{SourceFactory, SourceLoader, TypedSourceFactory};
 use tokio::runtime::Handle;
-use tracing::error;
+use tracing::{error, info};
 pub use vec_source::{VecSource, VecSourceFactory};
 pub use void_source::{VoidSource, VoidSourceFactory};
 
 use crate::actors::DocProcessor;
+use crate::models::Drain;
 use crate::source::ingest_api_source::IngestApiSourceFactory;
 
 /// Runtime configuration used during execution of a source actor.
@@ -106,6 +107,23 @@ pub struct SourceExecutionContext {
     // Ingest API queues directory path.
     pub queues_dir_path: PathBuf,
     pub source_config: SourceConfig,
+    pub source_lag: SourceLag,
+}
+
+/// Number of records available in a source and not emitted yet, as last measured by the source.
+/// It is shared by the source with its indexing pipeline. Sources unable to measure their lag
+/// leave it unset.
+#[derive(Clone, Debug, Default)]
+pub struct SourceLag(Arc<Mutex<Option<u64>>>);
+
+impl SourceLag {
+    pub fn set(&self, lag: u64) {
+        *self.0.lock().unwrap() = Some(lag);
+    }
+
+    pub fn get(&self) -> Option<u64> {
+        *self.0.lock().unwrap()
+    }
 }
 
 impl SourceExecutionContext {
@@ -121,6 +139,7 @@ impl SourceExecutionContext {
             index_uid,
             queues_dir_path,
             source_config,
+            source_lag: SourceLag::default(),
         })
     }
 }
@@ -282,6 +301,24 @@ impl Handler<Loop> for SourceActor {
     }
 }
 
+#[async_trait]
+impl Handler<Drain> for SourceActor {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _message: Drain,
+        ctx: &SourceContext,
+    ) -> Result<(), ActorExitStatus> {
+        info!(source=%self.source.name(), "drain-source");
+        // The batches already emitted are processed before the command, so that the indexer
+        // commits them on exit.
+        ctx.send_exit_with_success(&self.doc_processor_mailbox)
+            .await?;
+        Err(ActorExitStatus::Success)
+    }
+}
+
 pub fn quickwit_supported_sources() -> &'static SourceLoader {
     static SOURCE_LOADER: OnceCell<SourceLoader> = OnceCell::new();
     SOURCE_LOADER.get_or_init(|| {
@@ -372,6 +409,7 @@ impl Handler<SuggestTruncate> for SourceActor {
 #[cfg(test)]
 mod tests {
 
+    use std::collections::BTreeMap;
     use std::num::NonZeroUsize;
 
     use quickwit_config::{SourceInputFormat, VecSourceParams};
@@ -389,6 +427,7 @@ mod tests {
                 source_params: SourceParams::void(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             };
             check_source_connectivity(&source_config).await?;
         }
@@ -401,6 +440,7 @@ mod tests {
                 source_params: SourceParams::Vec(VecSourceParams::default()),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             };
             check_source_connectivity(&source_config).await?;
         }
@@ -413,6 +453,7 @@ mod tests {
                 source_params: SourceParams::file("file-does-not-exist.json"),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             };
             assert!(check_source_connectivity(&source_config).await.is_err());
         }
@@ -425,6 +466,7 @@ mod tests {
                 source_params: SourceParams::file("data/test_corpus.json"),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             };
             assert!(check_source_connectivity(&source_config).await.is_ok());
         }
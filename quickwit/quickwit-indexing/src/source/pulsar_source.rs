@@ -567,6 +567,7 @@ mod pulsar_broker_tests {
             }),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         (source_id, source_config)
     }
@@ -119,6 +119,7 @@ impl SourceLoader {
 #[cfg(test)]
 mod tests {
 
+    use std::collections::BTreeMap;
     use std::num::NonZeroUsize;
     use std::path::PathBuf;
 
@@ -141,6 +142,7 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         source_loader
             .load_source(
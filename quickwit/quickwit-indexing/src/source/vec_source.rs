@@ -125,6 +125,7 @@ impl Source for VecSource {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeMap;
     use std::num::NonZeroUsize;
     use std::path::PathBuf;
 
@@ -164,6 +165,7 @@ mod tests {
                     source_params: SourceParams::Vec(params.clone()),
                     transform_config: None,
                     input_format: SourceInputFormat::Json,
+                    split_labels: BTreeMap::new(),
                 },
             ),
             params,
@@ -224,6 +226,7 @@ mod tests {
                     source_params: SourceParams::Vec(params.clone()),
                     transform_config: None,
                     input_format: SourceInputFormat::Json,
+                    split_labels: BTreeMap::new(),
                 },
             ),
             params,
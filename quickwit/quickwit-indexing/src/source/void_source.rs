@@ -71,6 +71,7 @@ impl TypedSourceFactory for VoidSourceFactory {
 #[cfg(test)]
 mod tests {
 
+    use std::collections::BTreeMap;
     use std::num::NonZeroUsize;
     use std::path::PathBuf;
 
@@ -94,6 +95,7 @@ mod tests {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         let metastore = metastore_for_test();
         let ctx = SourceExecutionContext::for_test(
@@ -126,6 +128,7 @@ mod tests {
                     source_params: SourceParams::void(),
                     transform_config: None,
                     input_format: SourceInputFormat::Json,
+                    split_labels: BTreeMap::new(),
                 },
             ),
             VoidSourceParams,
@@ -37,6 +37,8 @@ pub enum IngestServiceError {
     Internal(String),
     #[error("Invalid position: {0}.")]
     InvalidPosition(String),
+    #[error("Invalid commit type `{0}`. Expected 0 (auto), 1 (wait_for), or 2 (force).")]
+    InvalidCommitType(u32),
     #[error("Io Error {0}")]
     IoError(String),
     #[error("Rate limited")]
@@ -51,6 +53,7 @@ impl From<AskError<IngestServiceError>> for IngestServiceError {
             AskError::ErrorReply(error) => error,
             AskError::MessageNotDelivered => IngestServiceError::Unavailable,
             AskError::ProcessMessageError => IngestServiceError::Internal(error.to_string()),
+            AskError::Timeout => IngestServiceError::Unavailable,
         }
     }
 }
@@ -98,6 +101,7 @@ impl ServiceError for IngestServiceError {
             IngestServiceError::IndexNotFound { .. } => ServiceErrorCode::NotFound,
             IngestServiceError::Internal { .. } => ServiceErrorCode::Internal,
             IngestServiceError::InvalidPosition(_) => ServiceErrorCode::BadRequest,
+            IngestServiceError::InvalidCommitType(_) => ServiceErrorCode::BadRequest,
             IngestServiceError::IoError { .. } => ServiceErrorCode::Internal,
             IngestServiceError::RateLimited => ServiceErrorCode::RateLimited,
             IngestServiceError::Unavailable => ServiceErrorCode::Internal,
@@ -123,6 +127,7 @@ impl From<IngestServiceError> for tonic::Status {
             IngestServiceError::IndexNotFound { .. } => tonic::Code::NotFound,
             IngestServiceError::Internal(_) => tonic::Code::Internal,
             IngestServiceError::InvalidPosition(_) => tonic::Code::InvalidArgument,
+            IngestServiceError::InvalidCommitType(_) => tonic::Code::InvalidArgument,
             IngestServiceError::IoError { .. } => tonic::Code::Internal,
             IngestServiceError::RateLimited => tonic::Code::ResourceExhausted,
             IngestServiceError::Unavailable => tonic::Code::Unavailable,
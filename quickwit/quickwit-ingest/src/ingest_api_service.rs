@@ -157,6 +157,9 @@ impl IngestApiService {
                 index_id: index_id.to_string(),
             });
         }
+        // The commit type is validated before appending any document so that an invalid request
+        // is rejected as a whole.
+        let commit = CommitType::try_from(request.commit)?;
         let disk_usage = self.queues.disk_usage();
 
         if disk_usage > self.disk_limit {
@@ -182,7 +185,6 @@ impl IngestApiService {
                 .queues
                 .append_batch(&doc_batch.index_id, records_it, ctx)
                 .await?;
-            let commit = CommitType::from(request.commit);
             if let Some(max_position) = max_position {
                 if commit != CommitType::Auto {
                     if commit == CommitType::Force {
@@ -296,6 +298,25 @@ impl Handler<GetMemoryCapacity> for IngestApiService {
     }
 }
 
+/// Asks for the position of the last record of a queue.
+#[derive(Debug)]
+pub struct GetLastPosition {
+    pub queue_id: String,
+}
+
+#[async_trait]
+impl Handler<GetLastPosition> for IngestApiService {
+    type Reply = crate::Result<Option<u64>>;
+
+    async fn handle(
+        &mut self,
+        request: GetLastPosition,
+        _ctx: &ActorContext<Self>,
+    ) -> Result<Self::Reply, ActorExitStatus> {
+        Ok(self.queues.last_position(&request.queue_id))
+    }
+}
+
 #[async_trait]
 impl Handler<CreateQueueRequest> for IngestApiService {
     type Reply = crate::Result<()>;
@@ -408,7 +429,7 @@ mod tests {
     use std::time::Duration;
 
     use bytes::Bytes;
-    use quickwit_actors::Universe;
+    use quickwit_actors::{AskError, Universe};
     use quickwit_config::IngestApiConfig;
 
     use super::*;
@@ -547,4 +568,46 @@ mod tests {
         universe.assert_quit().await;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_ingest_api_service_with_invalid_commit_type() -> anyhow::Result<()> {
+        let universe = Universe::with_accelerated_time();
+        let temp_dir = tempfile::tempdir()?;
+        let queues_dir_path = temp_dir.path();
+
+        let ingest_api_service =
+            init_ingest_api(&universe, queues_dir_path, &IngestApiConfig::default()).await?;
+
+        let create_queue_req = CreateQueueIfNotExistsRequest {
+            queue_id: "index-1".to_string(),
+        };
+        ingest_api_service.ask_for_res(create_queue_req).await?;
+
+        let mut batch = DocBatchBuilder::new("index-1".to_string());
+        batch.ingest_doc(Bytes::from_static(b"Test1"));
+
+        let ingest_request = IngestRequest {
+            doc_batches: vec![batch.build()],
+            commit: 42,
+        };
+        let ingest_error = ingest_api_service
+            .ask_for_res(ingest_request)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            ingest_error,
+            AskError::ErrorReply(IngestServiceError::InvalidCommitType(42))
+        ));
+        // The actor survives the invalid request and nothing was appended to the queue.
+        let fetch_request = FetchRequest {
+            index_id: "index-1".to_string(),
+            start_after: None,
+            num_bytes_limit: None,
+        };
+        let fetch_response = ingest_api_service.ask_for_res(fetch_request).await.unwrap();
+        assert!(fetch_response.first_position.is_none());
+
+        universe.assert_quit().await;
+        Ok(())
+    }
 }
@@ -34,7 +34,9 @@ use std::path::{Path, PathBuf};
 
 use anyhow::{bail, Context};
 pub use errors::IngestServiceError;
-pub use ingest_api_service::{GetMemoryCapacity, GetPartitionId, IngestApiService};
+pub use ingest_api_service::{
+    GetLastPosition, GetMemoryCapacity, GetPartitionId, IngestApiService,
+};
 pub use ingest_service::*;
 pub use memory_capacity::MemoryCapacity;
 use once_cell::sync::OnceCell;
@@ -50,6 +52,10 @@ pub use doc_batch::*;
 
 pub const QUEUES_DIR_NAME: &str = "queues";
 
+/// Encoded file descriptor set of the ingest service proto.
+pub const FILE_DESCRIPTOR_SET: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/ingest_service_descriptor.bin"));
+
 pub type Result<T> = std::result::Result<T, IngestServiceError>;
 
 type IngestApiServiceMailboxes = HashMap<PathBuf, Mailbox<IngestApiService>>;
@@ -130,13 +136,15 @@ pub enum CommitType {
     Force = 2,
 }
 
-impl From<u32> for CommitType {
-    fn from(value: u32) -> Self {
+impl TryFrom<u32> for CommitType {
+    type Error = IngestServiceError;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
         match value {
-            0 => CommitType::Auto,
-            1 => CommitType::WaitFor,
-            2 => CommitType::Force,
-            _ => panic!("Unknown commit type {value}"),
+            0 => Ok(CommitType::Auto),
+            1 => Ok(CommitType::WaitFor),
+            2 => Ok(CommitType::Force),
+            _ => Err(IngestServiceError::InvalidCommitType(value)),
         }
     }
 }
@@ -190,6 +190,21 @@ impl Queues {
         })
     }
 
+    /// Returns the position of the last record of the queue, or `None` if the queue is empty.
+    ///
+    /// Unlike `fetch`, this walks the positions of the records without copying them.
+    pub fn last_position(&self, queue_id: &str) -> crate::Result<Option<u64>> {
+        let real_queue_id = format!("{QUICKWIT_CF_PREFIX}{queue_id}");
+
+        let records = self.record_log.range(&real_queue_id, ..).ok_or_else(|| {
+            crate::IngestServiceError::IndexNotFound {
+                index_id: queue_id.to_string(),
+            }
+        })?;
+        let last_position_opt = records.last().map(|(position, _)| position);
+        Ok(last_position_opt)
+    }
+
     // Streams messages from the start of the Stream.
     pub fn tail(&self, queue_id: &str) -> crate::Result<FetchResponse> {
         self.fetch(queue_id, None, None)
@@ -395,6 +410,23 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn test_last_position() {
+        let (mut queues, ctx) = QueuesForTest::new().await;
+        queues.create_queue(TEST_QUEUE_ID, &ctx).await.unwrap();
+        assert_eq!(queues.last_position(TEST_QUEUE_ID).unwrap(), None);
+
+        queues.append(TEST_QUEUE_ID, b"hello", &ctx).await.unwrap();
+        queues.append(TEST_QUEUE_ID, b"happy", &ctx).await.unwrap();
+        assert_eq!(queues.last_position(TEST_QUEUE_ID).unwrap(), Some(1));
+
+        queues.reload().await;
+        assert_eq!(queues.last_position(TEST_QUEUE_ID).unwrap(), Some(1));
+
+        let error = queues.last_position(TEST_QUEUE_ID2).unwrap_err();
+        assert!(matches!(error, IngestServiceError::IndexNotFound { .. }));
+    }
+
     // Note this test is specific to the current implementation of truncate.
     //
     // The truncate contract is actually not as accurate as what we are testing here.
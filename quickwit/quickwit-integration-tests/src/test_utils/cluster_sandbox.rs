@@ -35,7 +35,7 @@ use quickwit_config::QuickwitConfig;
 use quickwit_metastore::SplitState;
 use quickwit_rest_client::models::IngestSource;
 use quickwit_rest_client::rest_client::{CommitType, QuickwitClient, Transport, DEFAULT_BASE_URL};
-use quickwit_serve::{serve_quickwit, ListSplitsQueryParams};
+use quickwit_serve::{serve_quickwit, ListSplitsQueryParams, RuntimeHooks};
 use reqwest::Url;
 use tempfile::TempDir;
 use tokio::sync::watch::{self, Receiver, Sender};
@@ -146,7 +146,12 @@ impl ClusterSandbox {
         let shutdown_trigger = ClusterShutdownTrigger::new();
         let shutdown_signal = shutdown_trigger.shutdown_signal();
         let join_handles = vec![tokio::spawn(async move {
-            let result = serve_quickwit(node_config_clone.quickwit_config, shutdown_signal).await?;
+            let result = serve_quickwit(
+                node_config_clone.quickwit_config,
+                RuntimeHooks::default(),
+                shutdown_signal,
+            )
+            .await?;
             Result::<_, anyhow::Error>::Ok(result)
         })];
         wait_for_server_ready(node_config.quickwit_config.grpc_listen_addr).await?;
@@ -176,8 +181,12 @@ impl ClusterSandbox {
             let node_config_clone = node_config.clone();
             let shutdown_signal = shutdown_trigger.shutdown_signal();
             join_handles.push(tokio::spawn(async move {
-                let result =
-                    serve_quickwit(node_config_clone.quickwit_config, shutdown_signal).await?;
+                let result = serve_quickwit(
+                    node_config_clone.quickwit_config,
+                    RuntimeHooks::default(),
+                    shutdown_signal,
+                )
+                .await?;
                 Result::<_, anyhow::Error>::Ok(result)
             }));
         }
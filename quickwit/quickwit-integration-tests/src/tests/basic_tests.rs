@@ -24,9 +24,13 @@ use std::time::Duration;
 
 use hyper::{Body, Method, Request, StatusCode};
 use quickwit_config::service::QuickwitService;
+use quickwit_proto::tonic::Code;
 use quickwit_rest_client::models::IngestSource;
 use quickwit_rest_client::rest_client::CommitType;
 use quickwit_serve::SearchRequestQueryString;
+use tonic_health::proto::health_check_response::ServingStatus;
+use tonic_health::proto::health_client::HealthClient;
+use tonic_health::proto::HealthCheckRequest;
 
 use crate::test_utils::{ingest_with_retry, ClusterSandbox};
 
@@ -133,6 +137,38 @@ async fn test_standalone_server() {
     sandbox.shutdown().await.unwrap();
 }
 
+#[tokio::test]
+async fn test_grpc_health_check() {
+    quickwit_common::setup_logging_for_tests();
+    let sandbox = ClusterSandbox::start_standalone_node().await.unwrap();
+    let node_config = sandbox.node_configs.first().unwrap();
+    let grpc_endpoint = format!("http://{}", node_config.quickwit_config.grpc_listen_addr);
+    let mut health_client = HealthClient::connect(grpc_endpoint).await.unwrap();
+
+    for service in ["", "quickwit.SearchService", "ingest_service.IngestService"] {
+        let health_check_response = health_client
+            .check(HealthCheckRequest {
+                service: service.to_string(),
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(
+            health_check_response.status,
+            ServingStatus::Serving as i32,
+            "service `{service}` should be serving"
+        );
+    }
+    let status = health_client
+        .check(HealthCheckRequest {
+            service: "quickwit.UnknownService".to_string(),
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::NotFound);
+    sandbox.shutdown().await.unwrap();
+}
+
 #[tokio::test]
 async fn test_multi_nodes_cluster() {
     quickwit_common::setup_logging_for_tests();
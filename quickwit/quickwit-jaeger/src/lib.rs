@@ -17,7 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Write;
 use std::mem;
 use std::ops::RangeInclusive;
@@ -25,24 +25,25 @@ use std::sync::Arc;
 use std::time::Instant;
 
 use async_trait::async_trait;
-use base64::prelude::{Engine, BASE64_STANDARD};
 use itertools::Itertools;
 use prost::Message;
 use prost_types::{Duration as WellKnownDuration, Timestamp as WellKnownTimestamp};
 use quickwit_config::JaegerConfig;
 use quickwit_opentelemetry::otlp::{
-    Event as QwEvent, Link as QwLink, Span as QwSpan, SpanFingerprint, SpanKind as QwSpanKind,
-    SpanStatus as QwSpanStatus, TraceId, OTEL_TRACE_INDEX_ID,
+    decode_otlp_id, Event as QwEvent, Link as QwLink, Span as QwSpan, SpanFingerprint,
+    SpanKind as QwSpanKind, SpanStatus as QwSpanStatus, TraceId, OTEL_TRACE_INDEX_ID,
 };
 use quickwit_proto::jaeger::api_v2::{
-    KeyValue as JaegerKeyValue, Log as JaegerLog, Process as JaegerProcess, Span as JaegerSpan,
-    SpanRef as JaegerSpanRef, SpanRefType as JaegerSpanRefType, ValueType,
+    DependencyLink as JaegerDependencyLink, KeyValue as JaegerKeyValue, Log as JaegerLog,
+    Process as JaegerProcess, Span as JaegerSpan, SpanRef as JaegerSpanRef,
+    SpanRefType as JaegerSpanRefType, ValueType,
 };
+use quickwit_proto::jaeger::storage::v1::dependencies_reader_plugin_server::DependenciesReaderPlugin;
 use quickwit_proto::jaeger::storage::v1::span_reader_plugin_server::SpanReaderPlugin;
 use quickwit_proto::jaeger::storage::v1::{
-    FindTraceIDsRequest, FindTraceIDsResponse, FindTracesRequest, GetOperationsRequest,
-    GetOperationsResponse, GetServicesRequest, GetServicesResponse, GetTraceRequest, Operation,
-    SpansResponseChunk, TraceQueryParameters,
+    FindTraceIDsRequest, FindTraceIDsResponse, FindTracesRequest, GetDependenciesRequest,
+    GetDependenciesResponse, GetOperationsRequest, GetOperationsResponse, GetServicesRequest,
+    GetServicesResponse, GetTraceRequest, Operation, SpansResponseChunk, TraceQueryParameters,
 };
 use quickwit_proto::{ListTermsRequest, SearchRequest};
 use quickwit_search::{FindTraceIdsCollector, SearchService};
@@ -60,6 +61,9 @@ use tracing::{debug, error, instrument, warn, Span as RuntimeSpan};
 use crate::metrics::JAEGER_SERVICE_METRICS;
 
 mod metrics;
+mod zipkin;
+
+pub use zipkin::{ZipkinService, ZipkinTraceQuery};
 
 // OpenTelemetry to Jaeger Transformation
 // <https://opentelemetry.io/docs/reference/specification/trace/sdk_exporters/jaeger/>
@@ -70,6 +74,12 @@ type JaegerResult<T> = Result<T, Status>;
 
 type SpanStream = ReceiverStream<Result<SpansResponseChunk, Status>>;
 
+/// Number of traces returned by `FindTraces` when the request does not set a limit. This is the
+/// default limit of the Jaeger UI search form. The results are not paginated: the Jaeger storage
+/// API has no page token, Jaeger UI raises the limit instead.
+const DEFAULT_NUM_TRACES: usize = 20;
+
+#[derive(Clone)]
 pub struct JaegerService {
     search_service: Arc<dyn SearchService>,
     lookback_period_secs: i64,
@@ -234,26 +244,51 @@ impl JaegerService {
         trace_query: TraceQueryParameters,
     ) -> Result<(Vec<TraceId>, TimeIntervalSecs), Status> {
         let index_id = OTEL_TRACE_INDEX_ID.to_string();
-        let span_kind_opt = None;
+        let mut tags = trace_query.tags;
+        // The Jaeger UI sends the span kind as a regular `span.kind` tag.
+        let span_kind_opt = tags
+            .remove("span.kind")
+            .map(|span_kind| span_kind.parse::<QwSpanKind>())
+            .transpose()
+            .map_err(Status::invalid_argument)?;
         let min_span_start_timestamp_secs_opt = trace_query.start_time_min.map(|ts| ts.seconds);
         let max_span_start_timestamp_secs_opt = trace_query.start_time_max.map(|ts| ts.seconds);
         let min_span_duration_millis_opt = trace_query
             .duration_min
             .and_then(|d| to_duration_millis(&d));
+        // Unlike the min duration, a sub-millisecond max duration is a valid filter: it matches
+        // the spans lasting less than a millisecond.
         let max_span_duration_millis_opt = trace_query
             .duration_max
-            .and_then(|d| to_duration_millis(&d));
+            .filter(|d| d.seconds > 0 || d.nanos > 0)
+            .map(|d| to_duration_millis(&d).unwrap_or(0));
+
+        if let (Some(min_span_duration_millis), Some(max_span_duration_millis)) =
+            (min_span_duration_millis_opt, max_span_duration_millis_opt)
+        {
+            if min_span_duration_millis > max_span_duration_millis {
+                return Err(Status::invalid_argument(format!(
+                    "Min duration ({min_span_duration_millis}ms) is greater than max duration \
+                     ({max_span_duration_millis}ms)."
+                )));
+            }
+        }
         let query = build_search_query(
             &trace_query.service_name,
             span_kind_opt,
             &trace_query.operation_name,
-            trace_query.tags,
+            tags,
             min_span_start_timestamp_secs_opt,
             max_span_start_timestamp_secs_opt,
             min_span_duration_millis_opt,
             max_span_duration_millis_opt,
         );
-        let aggregation_query = build_aggregations_query(trace_query.num_traces as usize);
+        let num_traces = if trace_query.num_traces > 0 {
+            trace_query.num_traces as usize
+        } else {
+            DEFAULT_NUM_TRACES
+        };
+        let aggregation_query = build_aggregations_query(num_traces);
         let max_hits = 0;
         let search_request = SearchRequest {
             index_id,
@@ -261,7 +296,9 @@ impl JaegerService {
             aggregation_request: Some(aggregation_query),
             max_hits,
             start_timestamp: min_span_start_timestamp_secs_opt,
-            end_timestamp: max_span_start_timestamp_secs_opt,
+            // The max start time sent by Jaeger is inclusive whereas the end timestamp of a search
+            // request is exclusive.
+            end_timestamp: max_span_start_timestamp_secs_opt.map(|ts| ts + 1),
             ..Default::default()
         };
         let search_response = self.search_service.root_search(search_request).await?;
@@ -275,6 +312,50 @@ impl JaegerService {
         Ok(trace_ids)
     }
 
+    #[instrument("get_dependencies", skip_all)]
+    async fn get_dependencies_inner(
+        &self,
+        request: GetDependenciesRequest,
+    ) -> JaegerResult<GetDependenciesResponse> {
+        debug!(request=?request, "`get_dependencies` request");
+
+        let end_timestamp = request
+            .end_time
+            .map(|end_time| end_time.seconds)
+            .unwrap_or_else(|| OffsetDateTime::now_utc().unix_timestamp());
+        let start_timestamp = request
+            .start_time
+            .map(|start_time| start_time.seconds)
+            .unwrap_or(end_timestamp - self.lookback_period_secs);
+
+        let search_request = SearchRequest {
+            index_id: OTEL_TRACE_INDEX_ID.to_string(),
+            query_ast: query_ast_json("*")?,
+            start_timestamp: Some(start_timestamp),
+            end_timestamp: Some(end_timestamp),
+            max_hits: self.max_fetch_spans,
+            ..Default::default()
+        };
+        let search_response = self.search_service.root_search(search_request).await?;
+
+        if search_response.num_hits > search_response.hits.len() as u64 {
+            warn!(
+                num_hits=%search_response.num_hits,
+                max_fetch_spans=%self.max_fetch_spans,
+                "The dependency graph is computed from a subset of the spans of the time range."
+            );
+        }
+        let dependency_spans: Vec<DependencySpan> = search_response
+            .hits
+            .iter()
+            .map(|hit| json_deserialize(&hit.json, "span"))
+            .collect::<Result<_, _>>()?;
+        let dependencies = build_dependency_links(&dependency_spans);
+        debug!(dependencies=?dependencies, "`get_dependencies` response");
+        let response = GetDependenciesResponse { dependencies };
+        Ok(response)
+    }
+
     #[instrument("stream_spans", skip_all, fields(num_traces=%trace_ids.len(), num_spans=Empty, num_bytes=Empty))]
     async fn stream_spans(
         &self,
@@ -288,17 +369,7 @@ impl JaegerService {
             return Ok(ReceiverStream::new(rx));
         }
         let num_traces = trace_ids.len() as u64;
-        let mut query = String::new();
-
-        for (i, trace_id) in trace_ids.iter().enumerate() {
-            if i > 0 {
-                query.push_str(" OR ");
-            }
-            query.push_str("trace_id:");
-            write!(query, "{}", trace_id.base64_display())
-                .expect("Writing to string should not fail.");
-        }
-        debug!(query=%query, "Fetch spans query");
+        let query = build_fetch_spans_query(trace_ids);
 
         let search_request = SearchRequest {
             index_id: OTEL_TRACE_INDEX_ID.to_string(),
@@ -500,6 +571,67 @@ impl SpanReaderPlugin for JaegerService {
     }
 }
 
+#[async_trait]
+impl DependenciesReaderPlugin for JaegerService {
+    async fn get_dependencies(
+        &self,
+        request: Request<GetDependenciesRequest>,
+    ) -> Result<Response<GetDependenciesResponse>, Status> {
+        metrics!(
+            self.get_dependencies_inner(request.into_inner()).await,
+            [get_dependencies, OTEL_TRACE_INDEX_ID]
+        );
+    }
+}
+
+/// The fields of a span needed to build the dependency graph.
+#[derive(Debug, Deserialize)]
+struct DependencySpan {
+    trace_id: TraceId,
+    span_id: String,
+    parent_span_id: Option<String>,
+    service_name: String,
+}
+
+/// Counts the calls between services, i.e. the spans whose parent span belongs to another
+/// service. Spans whose parent span is not part of `spans` are ignored.
+fn build_dependency_links(spans: &[DependencySpan]) -> Vec<JaegerDependencyLink> {
+    let service_names: HashMap<(TraceId, &str), &str> = spans
+        .iter()
+        .map(|span| {
+            (
+                (span.trace_id, span.span_id.as_str()),
+                span.service_name.as_str(),
+            )
+        })
+        .collect();
+    let mut call_counts: BTreeMap<(&str, &str), u64> = BTreeMap::new();
+
+    for span in spans {
+        let parent_service_name_opt = span.parent_span_id.as_ref().and_then(|parent_span_id| {
+            service_names.get(&(span.trace_id, parent_span_id.as_str()))
+        });
+        if let Some(parent_service_name) = parent_service_name_opt {
+            if *parent_service_name != span.service_name {
+                *call_counts
+                    .entry((*parent_service_name, span.service_name.as_str()))
+                    .or_default() += 1;
+            }
+        }
+    }
+    call_counts
+        .into_iter()
+        .map(
+            |((parent_service_name, child_service_name), call_count)| JaegerDependencyLink {
+                parent: parent_service_name.to_string(),
+                child: child_service_name.to_string(),
+                call_count,
+                source: String::new(),
+            },
+        )
+        .collect()
+}
+
 fn extract_term(term_bytes: &[u8]) -> String {
     tantivy::Term::wrap(term_bytes)
         .value()
@@ -653,6 +785,27 @@ fn build_search_query(
     query
 }
 
+fn build_fetch_spans_query(trace_ids: &[TraceId]) -> String {
+    let mut query = String::new();
+
+    for (i, trace_id) in trace_ids.iter().enumerate() {
+        if i > 0 {
+            query.push_str(" OR ");
+        }
+        // Trace IDs are stored in Base64 or in hex depending on the OTLP ID encoding of the
+        // indexer that ingested the spans.
+        write!(
+            query,
+            "trace_id:{} OR trace_id:{}",
+            trace_id.base64_display(),
+            trace_id.to_hex()
+        )
+        .expect("Writing to string should not fail.");
+    }
+    debug!(query=%query, "Fetch spans query");
+    query
+}
+
 fn build_aggregations_query(num_traces: usize) -> String {
     let query = serde_json::to_string(&FindTraceIdsCollector {
         num_traces,
@@ -667,7 +820,7 @@ fn build_aggregations_query(num_traces: usize) -> String {
 fn qw_span_to_jaeger_span(qw_span_json: &str) -> Result<JaegerSpan, Status> {
     let mut qw_span: QwSpan = json_deserialize(qw_span_json, "span")?;
     let trace_id = qw_span.trace_id.to_vec();
-    let span_id = base64_decode(qw_span.span_id.as_bytes(), "span ID")?;
+    let span_id = decode_id(&qw_span.span_id, "span ID")?;
 
     let start_time = Some(to_well_known_timestamp(qw_span.span_start_timestamp_nanos));
     let duration = Some(to_well_known_duration(
@@ -890,7 +1043,7 @@ fn otlp_links_to_jaeger_references(
     let mut references = Vec::with_capacity(parent_span_id_opt.is_some() as usize + links.len());
 
     if let Some(parent_span_id) = parent_span_id_opt {
-        let parent_span_id = base64_decode(parent_span_id.as_bytes(), "parent span ID")?;
+        let parent_span_id = decode_id(&parent_span_id, "parent span ID")?;
         let reference = JaegerSpanRef {
             trace_id: trace_id.to_vec(),
             span_id: parent_span_id,
@@ -902,7 +1055,7 @@ fn otlp_links_to_jaeger_references(
     // Parent ID, if any."
     for link in links {
         let trace_id = link.link_trace_id.to_vec();
-        let span_id = base64_decode(link.link_span_id.as_bytes(), "link span ID")?;
+        let span_id = decode_id(&link.link_span_id, "link span ID")?;
         let reference = JaegerSpanRef {
             trace_id,
             span_id,
@@ -960,13 +1113,13 @@ fn collect_trace_ids(trace_ids_json: &str) -> Result<(Vec<TraceId>, TimeInterval
     Ok((trace_ids, start..=end))
 }
 
-fn base64_decode(encoded: &[u8], label: &'static str) -> Result<Vec<u8>, Status> {
-    match BASE64_STANDARD.decode(encoded) {
-        Ok(decoded) => Ok(decoded),
-        Err(error) => {
-            error!("Failed to base64 decode {label}: {error:?}",);
+fn decode_id(encoded_id: &str, label: &'static str) -> Result<Vec<u8>, Status> {
+    match decode_otlp_id(encoded_id) {
+        Some(decoded_id) => Ok(decoded_id),
+        None => {
+            error!("Failed to decode {label} `{encoded_id}`.");
             Err(Status::internal(format!(
-                "Failed to base64 decode: {error:?}"
+                "Failed to decode {label} `{encoded_id}`."
             )))
         }
     }
@@ -987,6 +1140,7 @@ where T: Deserialize<'a> {
 
 #[cfg(test)]
 mod tests {
+    use base64::prelude::{Engine, BASE64_STANDARD};
     use quickwit_proto::jaeger::api_v2::ValueType;
     use quickwit_search::{encode_term_for_test, MockSearchService, QuickwitAggregations};
     use serde_json::json;
@@ -1747,6 +1901,7 @@ mod tests {
                 )]),
                 link_dropped_attributes_count: 7,
             }],
+            promoted_attributes: HashMap::new(),
         };
         let qw_span_json = serde_json::to_string(&qw_span).unwrap();
         let jaeger_span = qw_span_to_jaeger_span(&qw_span_json).unwrap();
@@ -1945,6 +2100,31 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_otlp_links_to_jaeger_references_hex_ids() {
+        let parent_span_id = "0303030303030303".to_string();
+        let links = vec![QwLink {
+            link_trace_id: TraceId::new([4; 16]),
+            link_trace_state: String::new(),
+            link_span_id: "0505050505050505".to_string(),
+            link_attributes: HashMap::new(),
+            link_dropped_attributes_count: 0,
+        }];
+        let jaeger_references =
+            otlp_links_to_jaeger_references(&[1; 16], Some(parent_span_id), links).unwrap();
+        assert_eq!(jaeger_references[0].span_id, vec![3; 8]);
+        assert_eq!(jaeger_references[1].span_id, vec![5; 8]);
+
+        let links = vec![QwLink {
+            link_trace_id: TraceId::new([4; 16]),
+            link_trace_state: String::new(),
+            link_span_id: "not a span ID".to_string(),
+            link_attributes: HashMap::new(),
+            link_dropped_attributes_count: 0,
+        }];
+        otlp_links_to_jaeger_references(&[1; 16], None, links).unwrap_err();
+    }
+
     #[test]
     fn test_collect_trace_ids() {
         {
@@ -2010,4 +2190,284 @@ mod tests {
         let response = jaeger.get_services(request).await.unwrap().into_inner();
         assert_eq!(response.services, &["service1", "service2", "service3"]);
     }
+
+    #[tokio::test]
+    async fn test_find_trace_ids() {
+        let mut service = MockSearchService::new();
+        service
+            .expect_root_search()
+            .withf(|req| {
+                let aggregations: QuickwitAggregations =
+                    serde_json::from_str(req.aggregation_request.as_ref().unwrap()).unwrap();
+                let QuickwitAggregations::FindTraceIdsAggregation(collector) = aggregations else {
+                    panic!("Expected find trace IDs aggregation!");
+                };
+                req.index_id == "otel-trace-v0"
+                    && req.query_ast.contains("span_kind:2")
+                    && req.query_ast.contains("span_attributes.http.method")
+                    && req.query_ast.contains("span_attributes.http.status_code")
+                    && req.query_ast.contains("span_duration_millis:[* TO 0]")
+                    && req.start_timestamp == Some(1_000)
+                    && req.end_timestamp == Some(2_001)
+                    && collector.num_traces == DEFAULT_NUM_TRACES
+            })
+            .return_once(|_| {
+                Ok(quickwit_proto::SearchResponse {
+                    num_hits: 0,
+                    hits: Vec::new(),
+                    elapsed_time_micros: 0,
+                    errors: Vec::new(),
+                    aggregation: None,
+                })
+            });
+
+        let service = Arc::new(service);
+        let jaeger = JaegerService::new(JaegerConfig::default(), service);
+
+        let trace_query = TraceQueryParameters {
+            service_name: "quickwit".to_string(),
+            operation_name: String::new(),
+            tags: HashMap::from_iter([
+                ("span.kind".to_string(), "server".to_string()),
+                ("http.method".to_string(), "GET".to_string()),
+                ("http.status_code".to_string(), "200".to_string()),
+            ]),
+            start_time_min: Some(WellKnownTimestamp {
+                seconds: 1_000,
+                nanos: 0,
+            }),
+            start_time_max: Some(WellKnownTimestamp {
+                seconds: 2_000,
+                nanos: 0,
+            }),
+            duration_min: None,
+            duration_max: Some(WellKnownDuration {
+                seconds: 0,
+                nanos: 500_000,
+            }),
+            num_traces: 0,
+        };
+        let (trace_ids, _) = jaeger.find_trace_ids(trace_query).await.unwrap();
+        assert!(trace_ids.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_find_trace_ids_invalid_query() {
+        let service = Arc::new(MockSearchService::new());
+        let jaeger = JaegerService::new(JaegerConfig::default(), service);
+        {
+            let trace_query = TraceQueryParameters {
+                duration_min: Some(WellKnownDuration {
+                    seconds: 2,
+                    nanos: 0,
+                }),
+                duration_max: Some(WellKnownDuration {
+                    seconds: 1,
+                    nanos: 0,
+                }),
+                ..Default::default()
+            };
+            let status = jaeger.find_trace_ids(trace_query).await.unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        }
+        {
+            let trace_query = TraceQueryParameters {
+                tags: HashMap::from_iter([("span.kind".to_string(), "foo".to_string())]),
+                ..Default::default()
+            };
+            let status = jaeger.find_trace_ids(trace_query).await.unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_operations() {
+        let mut service = MockSearchService::new();
+        service
+            .expect_root_list_terms()
+            .withf(|req| {
+                req.index_id == "otel-trace-v0"
+                    && req.field == "span_fingerprint"
+                    && req.start_key.as_deref() == Some(b"service1\x002\x00".as_slice())
+                    && req.end_key.as_deref() == Some(b"service1\x002\x01".as_slice())
+            })
+            .return_once(|_| {
+                Ok(quickwit_proto::ListTermsResponse {
+                    num_hits: 2,
+                    terms: vec![
+                        encode_term_for_test!(SpanFingerprint::new(
+                            "service1",
+                            QwSpanKind::from(2),
+                            "leaf_search"
+                        )
+                        .as_str()),
+                        encode_term_for_test!(SpanFingerprint::new(
+                            "service1",
+                            QwSpanKind::from(2),
+                            "root_search"
+                        )
+                        .as_str()),
+                    ],
+                    elapsed_time_micros: 0,
+                    errors: Vec::new(),
+                })
+            });
+
+        let service = Arc::new(service);
+        let jaeger = JaegerService::new(JaegerConfig::default(), service);
+
+        let request = tonic::Request::new(GetOperationsRequest {
+            service: "service1".to_string(),
+            span_kind: "server".to_string(),
+        });
+        let response = jaeger.get_operations(request).await.unwrap().into_inner();
+        assert_eq!(
+            response.operations,
+            [
+                Operation {
+                    name: "leaf_search".to_string(),
+                    span_kind: "server".to_string(),
+                },
+                Operation {
+                    name: "root_search".to_string(),
+                    span_kind: "server".to_string(),
+                }
+            ]
+        );
+    }
+
+    #[test]
+    fn test_build_dependency_links() {
+        let spans: Vec<DependencySpan> = [
+            ("AQEBAQEBAQEBAQEBAQEBAQ==", "AQEBAQEBAQE=", None, "frontend"),
+            (
+                "AQEBAQEBAQEBAQEBAQEBAQ==",
+                "AgICAgICAgI=",
+                Some("AQEBAQEBAQE="),
+                "quickwit",
+            ),
+            (
+                "AQEBAQEBAQEBAQEBAQEBAQ==",
+                "AwMDAwMDAwM=",
+                Some("AgICAgICAgI="),
+                "quickwit",
+            ),
+            (
+                "AQEBAQEBAQEBAQEBAQEBAQ==",
+                "BAQEBAQEBAQ=",
+                Some("AgICAgICAgI="),
+                "postgres",
+            ),
+            ("AgICAgICAgICAgICAgICAg==", "AQEBAQEBAQE=", None, "frontend"),
+            (
+                "AgICAgICAgICAgICAgICAg==",
+                "AgICAgICAgI=",
+                Some("AQEBAQEBAQE="),
+                "quickwit",
+            ),
+            // The parent span of this span is missing.
+            (
+                "AgICAgICAgICAgICAgICAg==",
+                "BQUFBQUFBQU=",
+                Some("BgYGBgYGBgY="),
+                "postgres",
+            ),
+        ]
+        .into_iter()
+        .map(
+            |(trace_id, span_id, parent_span_id, service_name)| DependencySpan {
+                trace_id: trace_id.parse().unwrap(),
+                span_id: span_id.to_string(),
+                parent_span_id: parent_span_id.map(ToString::to_string),
+                service_name: service_name.to_string(),
+            },
+        )
+        .collect();
+        let dependency_links = build_dependency_links(&spans);
+        assert_eq!(
+            dependency_links,
+            [
+                JaegerDependencyLink {
+                    parent: "frontend".to_string(),
+                    child: "quickwit".to_string(),
+                    call_count: 2,
+                    source: String::new(),
+                },
+                JaegerDependencyLink {
+                    parent: "quickwit".to_string(),
+                    child: "postgres".to_string(),
+                    call_count: 1,
+                    source: String::new(),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_dependencies() {
+        let mut service = MockSearchService::new();
+        service
+            .expect_root_search()
+            .withf(|req| {
+                req.index_id == "otel-trace-v0"
+                    && req.start_timestamp == Some(1_000)
+                    && req.end_timestamp == Some(2_000)
+            })
+            .return_once(|_| {
+                let hits = [
+                    json!({
+                        "trace_id": "AQEBAQEBAQEBAQEBAQEBAQ==",
+                        "span_id": "AQEBAQEBAQE=",
+                        "parent_span_id": null,
+                        "service_name": "frontend",
+                        "span_name": "GET /search",
+                    }),
+                    json!({
+                        "trace_id": "AQEBAQEBAQEBAQEBAQEBAQ==",
+                        "span_id": "AgICAgICAgI=",
+                        "parent_span_id": "AQEBAQEBAQE=",
+                        "service_name": "quickwit",
+                        "span_name": "root_search",
+                    }),
+                ]
+                .into_iter()
+                .map(|hit_json| quickwit_proto::Hit {
+                    json: hit_json.to_string(),
+                    partial_hit: None,
+                    snippet: None,
+                })
+                .collect();
+                Ok(quickwit_proto::SearchResponse {
+                    num_hits: 2,
+                    hits,
+                    elapsed_time_micros: 0,
+                    errors: Vec::new(),
+                    aggregation: None,
+                })
+            });
+
+        let service = Arc::new(service);
+        let jaeger = JaegerService::new(JaegerConfig::default(), service);
+
+        let request = tonic::Request::new(GetDependenciesRequest {
+            start_time: Some(WellKnownTimestamp {
+                seconds: 1_000,
+                nanos: 0,
+            }),
+            end_time: Some(WellKnownTimestamp {
+                seconds: 2_000,
+                nanos: 0,
+            }),
+        });
+        let response = jaeger.get_dependencies(request).await.unwrap().into_inner();
+        assert_eq!(
+            response.dependencies,
+            [JaegerDependencyLink {
+                parent: "frontend".to_string(),
+                child: "quickwit".to_string(),
+                call_count: 1,
+                source: String::new(),
+            }]
+        );
+    }
 }
@@ -0,0 +1,303 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use itertools::Itertools;
+use prost_types::{Duration as WellKnownDuration, Timestamp as WellKnownTimestamp};
+use quickwit_config::JaegerConfig;
+use quickwit_opentelemetry::otlp::{Span as QwSpan, TraceId, ZipkinSpan, OTEL_TRACE_INDEX_ID};
+use quickwit_proto::jaeger::storage::v1::{
+    GetOperationsRequest, GetServicesRequest, TraceQueryParameters,
+};
+use quickwit_proto::SearchRequest;
+use quickwit_search::SearchService;
+use serde::Deserialize;
+use time::OffsetDateTime;
+use tonic::Status;
+use tracing::debug;
+
+use crate::{
+    build_fetch_spans_query, json_deserialize, query_ast_json, JaegerService, TimeIntervalSecs,
+};
+
+/// Value of the service and span name parameters meaning "any" in Zipkin UI.
+const ZIPKIN_ANY: &str = "all";
+
+const DEFAULT_NUM_TRACES: usize = 10;
+
+/// Parameters of the `/api/v2/traces` endpoint of the Zipkin API.
+///
+/// <https://zipkin.io/zipkin-api/#/default/get_traces>
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZipkinTraceQuery {
+    pub service_name: Option<String>,
+    pub span_name: Option<String>,
+    /// Conditions on the annotations and tags of the spans joined with `and`, for instance
+    /// `error and http.method=GET`.
+    pub annotation_query: Option<String>,
+    /// Minimum span duration in microseconds.
+    pub min_duration: Option<u64>,
+    /// Maximum span duration in microseconds.
+    pub max_duration: Option<u64>,
+    /// End of the time range in milliseconds since epoch. Defaults to now.
+    pub end_ts: Option<i64>,
+    /// Length of the time range in milliseconds. Defaults to the Jaeger lookback period.
+    pub lookback: Option<i64>,
+    pub limit: Option<usize>,
+}
+
+/// Serves the query API of Zipkin UI from the trace index, reusing the trace search of the Jaeger
+/// service.
+#[derive(Clone)]
+pub struct ZipkinService {
+    jaeger_service: JaegerService,
+}
+
+impl ZipkinService {
+    pub fn new(config: JaegerConfig, search_service: Arc<dyn SearchService>) -> Self {
+        Self {
+            jaeger_service: JaegerService::new(config, search_service),
+        }
+    }
+
+    pub async fn get_services(&self) -> Result<Vec<String>, Status> {
+        let response = self
+            .jaeger_service
+            .get_services_inner(GetServicesRequest {})
+            .await?;
+        Ok(response.services)
+    }
+
+    pub async fn get_span_names(&self, service_name: &str) -> Result<Vec<String>, Status> {
+        let request = GetOperationsRequest {
+            service: service_name.to_string(),
+            span_kind: String::new(),
+        };
+        let response = self.jaeger_service.get_operations_inner(request).await?;
+        let span_names = response
+            .operations
+            .into_iter()
+            .map(|operation| operation.name)
+            .sorted()
+            .dedup()
+            .collect();
+        Ok(span_names)
+    }
+
+    pub async fn find_traces(
+        &self,
+        trace_query: ZipkinTraceQuery,
+    ) -> Result<Vec<Vec<ZipkinSpan>>, Status> {
+        debug!(trace_query=?trace_query, "Zipkin `find_traces` request");
+
+        let now_millis = OffsetDateTime::now_utc().unix_timestamp() * 1_000;
+        let lookback_millis = self.jaeger_service.lookback_period_secs * 1_000;
+        let trace_query = to_trace_query_parameters(trace_query, now_millis, lookback_millis);
+        let (trace_ids, span_timestamps_range) =
+            self.jaeger_service.find_trace_ids(trace_query).await?;
+        let max_trace_duration_secs = self.jaeger_service.max_trace_duration_secs;
+        let start = span_timestamps_range.start() - max_trace_duration_secs;
+        let end = span_timestamps_range.end() + max_trace_duration_secs;
+        let qw_spans = self.fetch_spans(&trace_ids, start..=end).await?;
+
+        let mut spans_per_trace: HashMap<TraceId, Vec<ZipkinSpan>> = HashMap::new();
+        for qw_span in qw_spans {
+            spans_per_trace
+                .entry(qw_span.trace_id)
+                .or_default()
+                .push(ZipkinSpan::from_span(qw_span));
+        }
+        // Keep the order of the trace IDs, most recent first.
+        let traces = trace_ids
+            .iter()
+            .filter_map(|trace_id| spans_per_trace.remove(trace_id))
+            .collect();
+        Ok(traces)
+    }
+
+    pub async fn get_trace(&self, trace_id: TraceId) -> Result<Vec<ZipkinSpan>, Status> {
+        let end = OffsetDateTime::now_utc().unix_timestamp();
+        let start = end - self.jaeger_service.lookback_period_secs;
+        let qw_spans = self.fetch_spans(&[trace_id], start..=end).await?;
+        let spans = qw_spans.into_iter().map(ZipkinSpan::from_span).collect();
+        Ok(spans)
+    }
+
+    async fn fetch_spans(
+        &self,
+        trace_ids: &[TraceId],
+        search_window: TimeIntervalSecs,
+    ) -> Result<Vec<QwSpan>, Status> {
+        if trace_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+        let query = build_fetch_spans_query(trace_ids);
+        let search_request = SearchRequest {
+            index_id: OTEL_TRACE_INDEX_ID.to_string(),
+            query_ast: query_ast_json(&query)?,
+            start_timestamp: Some(*search_window.start()),
+            end_timestamp: Some(*search_window.end()),
+            max_hits: self.jaeger_service.max_fetch_spans,
+            ..Default::default()
+        };
+        let search_response = self
+            .jaeger_service
+            .search_service
+            .root_search(search_request)
+            .await?;
+        search_response
+            .hits
+            .iter()
+            .map(|hit| json_deserialize(&hit.json, "span"))
+            .collect()
+    }
+}
+
+fn to_trace_query_parameters(
+    trace_query: ZipkinTraceQuery,
+    now_millis: i64,
+    default_lookback_millis: i64,
+) -> TraceQueryParameters {
+    let service_name = trace_query
+        .service_name
+        .filter(|service_name| service_name != ZIPKIN_ANY)
+        .unwrap_or_default();
+    let operation_name = trace_query
+        .span_name
+        .filter(|span_name| span_name != ZIPKIN_ANY)
+        .unwrap_or_default();
+    let tags = trace_query
+        .annotation_query
+        .as_deref()
+        .map(parse_annotation_query)
+        .unwrap_or_default();
+    let end_millis = trace_query.end_ts.unwrap_or(now_millis);
+    let start_millis = end_millis - trace_query.lookback.unwrap_or(default_lookback_millis);
+    TraceQueryParameters {
+        service_name,
+        operation_name,
+        tags,
+        start_time_min: Some(to_well_known_timestamp(start_millis)),
+        start_time_max: Some(to_well_known_timestamp(end_millis)),
+        duration_min: trace_query.min_duration.map(to_well_known_duration),
+        duration_max: trace_query.max_duration.map(to_well_known_duration),
+        num_traces: trace_query.limit.unwrap_or(DEFAULT_NUM_TRACES) as i32,
+    }
+}
+
+/// Translates the `key=value` conditions of an annotation query into Jaeger tags. A condition
+/// without a value matches the name of the events, which is what Zipkin annotations become.
+fn parse_annotation_query(annotation_query: &str) -> HashMap<String, String> {
+    annotation_query
+        .split(" and ")
+        .map(str::trim)
+        .filter(|condition| !condition.is_empty())
+        .map(|condition| match condition.split_once('=') {
+            Some((key, value)) => (key.trim().to_string(), value.trim().to_string()),
+            None => ("event".to_string(), condition.to_string()),
+        })
+        .collect()
+}
+
+fn to_well_known_timestamp(timestamp_millis: i64) -> WellKnownTimestamp {
+    WellKnownTimestamp {
+        seconds: timestamp_millis.div_euclid(1_000),
+        nanos: (timestamp_millis.rem_euclid(1_000) * 1_000_000) as i32,
+    }
+}
+
+fn to_well_known_duration(duration_micros: u64) -> WellKnownDuration {
+    WellKnownDuration {
+        seconds: (duration_micros / 1_000_000) as i64,
+        nanos: (duration_micros % 1_000_000 * 1_000) as i32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_annotation_query() {
+        assert!(parse_annotation_query("").is_empty());
+
+        let tags = parse_annotation_query("wr and http.method=GET and  error = timeout ");
+        assert_eq!(tags.len(), 3);
+        assert_eq!(tags["event"], "wr");
+        assert_eq!(tags["http.method"], "GET");
+        assert_eq!(tags["error"], "timeout");
+    }
+
+    #[test]
+    fn test_to_trace_query_parameters() {
+        let trace_query = ZipkinTraceQuery {
+            service_name: Some("all".to_string()),
+            span_name: Some("get /api".to_string()),
+            annotation_query: Some("http.method=GET".to_string()),
+            min_duration: Some(1_500_000),
+            max_duration: None,
+            end_ts: Some(10_000_500),
+            lookback: Some(3_600_000),
+            limit: None,
+        };
+        let trace_query_parameters = to_trace_query_parameters(trace_query, 0, 0);
+        assert_eq!(trace_query_parameters.service_name, "");
+        assert_eq!(trace_query_parameters.operation_name, "get /api");
+        assert_eq!(trace_query_parameters.tags["http.method"], "GET");
+        assert_eq!(
+            trace_query_parameters.start_time_min,
+            Some(WellKnownTimestamp {
+                seconds: 6_400,
+                nanos: 500_000_000
+            })
+        );
+        assert_eq!(
+            trace_query_parameters.start_time_max,
+            Some(WellKnownTimestamp {
+                seconds: 10_000,
+                nanos: 500_000_000
+            })
+        );
+        assert_eq!(
+            trace_query_parameters.duration_min,
+            Some(WellKnownDuration {
+                seconds: 1,
+                nanos: 500_000_000
+            })
+        );
+        assert!(trace_query_parameters.duration_max.is_none());
+        assert_eq!(trace_query_parameters.num_traces, 10);
+
+        let trace_query_parameters =
+            to_trace_query_parameters(ZipkinTraceQuery::default(), 5_000_000, 1_000_000);
+        assert_eq!(trace_query_parameters.service_name, "");
+        assert!(trace_query_parameters.tags.is_empty());
+        assert_eq!(
+            trace_query_parameters.start_time_min.unwrap().seconds,
+            4_000
+        );
+        assert_eq!(
+            trace_query_parameters.start_time_max.unwrap().seconds,
+            5_000
+        );
+    }
+}
@@ -22,8 +22,8 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use quickwit_actors::{
-    Actor, ActorContext, ActorExitStatus, ActorHandle, Handler, Supervisor, SupervisorState,
-    HEARTBEAT,
+    Actor, ActorContext, ActorExitStatus, ActorHandle, Handler, ScheduledMessageHandle, Supervisor,
+    SupervisorState, HEARTBEAT,
 };
 use quickwit_common::io::IoControls;
 use quickwit_common::uri::Uri;
@@ -43,6 +43,7 @@ use tokio::join;
 use tracing::info;
 
 use super::delete_task_planner::DeleteTaskPlanner;
+use crate::delete_task_scheduler::DeleteTaskScheduler;
 
 struct DeletePipelineHandle {
     pub delete_task_planner: ActorHandle<Supervisor<DeleteTaskPlanner>>,
@@ -73,7 +74,11 @@ pub struct DeleteTaskPipeline {
     delete_service_dir_path: PathBuf,
     handles: Option<DeletePipelineHandle>,
     max_concurrent_split_uploads: usize,
+    scheduler: DeleteTaskScheduler,
+    dry_run: bool,
     state: DeleteTaskPipelineState,
+    // Next scheduled `Observe` message, cancelled when the pipeline shuts down.
+    next_observe_opt: Option<ScheduledMessageHandle>,
 }
 
 #[async_trait]
@@ -99,6 +104,9 @@ impl Actor for DeleteTaskPipeline {
         _exit_status: &ActorExitStatus,
         _ctx: &ActorContext<Self>,
     ) -> anyhow::Result<()> {
+        if let Some(next_observe) = self.next_observe_opt.take() {
+            next_observe.cancel();
+        }
         if let Some(handles) = self.handles.take() {
             join!(
                 handles.delete_task_planner.quit(),
@@ -132,10 +140,26 @@ impl DeleteTaskPipeline {
             delete_service_dir_path,
             handles: Default::default(),
             max_concurrent_split_uploads,
+            scheduler: DeleteTaskScheduler::default(),
+            dry_run: false,
             state: DeleteTaskPipelineState::default(),
+            next_observe_opt: None,
         }
     }
 
+    /// Sets the scheduler shared by the delete task pipelines of the janitor.
+    pub fn with_scheduler(mut self, scheduler: DeleteTaskScheduler) -> Self {
+        self.scheduler = scheduler;
+        self
+    }
+
+    /// In dry run mode, the pipeline only reports the splits that would undergo a delete
+    /// operation.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
     pub async fn spawn_pipeline(&mut self, ctx: &ActorContext<Self>) -> anyhow::Result<()> {
         info!(
             index_id=%self.index_uid.index_id(),
@@ -168,8 +192,8 @@ impl DeleteTaskPipeline {
 
         let doc_mapper =
             build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)?;
-        let tag_fields = doc_mapper.tag_named_fields()?;
-        let packager = Packager::new("MergePackager", tag_fields, uploader_mailbox);
+        let split_pruning_fields = doc_mapper.split_pruning_fields()?;
+        let packager = Packager::new("MergePackager", split_pruning_fields, uploader_mailbox);
         let (packager_mailbox, packager_supervisor_handler) = ctx.spawn_actor().supervise(packager);
         let index_pipeline_id = IndexingPipelineId {
             index_uid: self.index_uid.clone(),
@@ -228,7 +252,9 @@ impl DeleteTaskPipeline {
             self.search_job_placer.clone(),
             merge_policy,
             downloader_mailbox,
-        );
+            self.scheduler.clone(),
+        )
+        .with_dry_run(self.dry_run);
         let (_, task_planner_supervisor_handler) = ctx.spawn_actor().supervise(task_planner);
         self.handles = Some(DeletePipelineHandle {
             delete_task_planner: task_planner_supervisor_handler,
@@ -279,7 +305,8 @@ impl Handler<Observe> for DeleteTaskPipeline {
             }
         }
         // Supervisors supervise every `HEARTBEAT`. We can wait a bit more to observe supervisors.
-        ctx.schedule_self_msg(HEARTBEAT, Observe).await;
+        let next_observe = ctx.schedule_self_msg(HEARTBEAT, Observe).await;
+        self.next_observe_opt = Some(next_observe);
         Ok(())
     }
 }
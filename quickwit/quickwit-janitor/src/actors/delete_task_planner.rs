@@ -24,8 +24,8 @@ use std::time::Duration;
 use async_trait::async_trait;
 use itertools::Itertools;
 use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler, Mailbox, QueueCapacity};
-use quickwit_common::extract_time_range;
 use quickwit_common::uri::Uri;
+use quickwit_common::{extract_time_range, PrettySample};
 use quickwit_doc_mapper::tag_pruning::extract_tags_from_query;
 use quickwit_indexing::actors::MergeSplitDownloader;
 use quickwit_indexing::merge_policy::{MergeOperation, MergePolicy};
@@ -37,11 +37,15 @@ use quickwit_proto::{IndexUid, SearchRequest};
 use quickwit_search::{jobs_to_leaf_request, SearchJob, SearchJobPlacer};
 use serde::Serialize;
 use tantivy::Inventory;
+use tokio::sync::OwnedSemaphorePermit;
 use tracing::{debug, info};
 
+use crate::delete_task_scheduler::{DeletePipelineStatus, DeleteTaskScheduler};
 use crate::metrics::JANITOR_METRICS;
 
 const PLANNER_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+/// Interval at which the planner checks whether the delete operations of its turn are published.
+const ONGOING_DELETE_OPERATIONS_CHECK_INTERVAL: Duration = Duration::from_secs(1);
 const NUM_STALE_SPLITS_TO_FETCH: usize = 1000;
 
 /// The `DeleteTaskPlanner` plans delete operations on splits for a given index.
@@ -72,6 +76,10 @@ const NUM_STALE_SPLITS_TO_FETCH: usize = 1000;
 ///      (`leaf_request`) one by one to check if there is a match. + As soon as a hit is returned
 ///      for a given query, the split is sent to the `MergeExecutor`. + If no delete queries match
 ///      documents, update the split `delete_opstamp` to the last `opstamp`.
+///
+/// The planning rounds are paced by the [`DeleteTaskScheduler`] shared by the delete task pipelines
+/// of the janitor: the planner waits for its turn before planning delete operations, and ends its
+/// turn once they are published so that the other indexes get theirs.
 #[derive(Clone)]
 pub struct DeleteTaskPlanner {
     index_uid: IndexUid,
@@ -86,6 +94,10 @@ pub struct DeleteTaskPlanner {
     /// the delete operation.
     /// The inventory is used to avoid sending twice the same delete operation.
     ongoing_delete_operations_inventory: Inventory<MergeOperation>,
+    scheduler: DeleteTaskScheduler,
+    /// The turn granted by the scheduler, held until the planned delete operations are published.
+    turn_opt: Option<Arc<OwnedSemaphorePermit>>,
+    dry_run: bool,
 }
 
 #[async_trait]
@@ -126,6 +138,7 @@ impl DeleteTaskPlanner {
         search_job_placer: SearchJobPlacer,
         merge_policy: Arc<dyn MergePolicy>,
         merge_split_downloader_mailbox: Mailbox<MergeSplitDownloader>,
+        scheduler: DeleteTaskScheduler,
     ) -> Self {
         Self {
             index_uid,
@@ -136,75 +149,140 @@ impl DeleteTaskPlanner {
             merge_policy,
             merge_split_downloader_mailbox,
             ongoing_delete_operations_inventory: Inventory::new(),
+            scheduler,
+            turn_opt: None,
+            dry_run: false,
         }
     }
 
+    /// In dry run mode, the planner only logs and reports through its metrics the splits that
+    /// would undergo a delete operation. Neither the splits nor their `delete_opstamp` are
+    /// updated.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
     /// Send delete operations for a given `index_id`.
     async fn send_delete_operations(&mut self, ctx: &ActorContext<Self>) -> anyhow::Result<()> {
         // Loop until there is no more stale splits.
-        loop {
-            let last_delete_opstamp = self
-                .metastore
-                .last_delete_opstamp(self.index_uid.clone())
-                .await?;
-            let stale_splits = self
-                .get_relevant_stale_splits(self.index_uid.clone(), last_delete_opstamp, ctx)
-                .await?;
-            ctx.record_progress();
-            info!(
-                index_id = self.index_uid.index_id(),
-                last_delete_opstamp = last_delete_opstamp,
-                num_stale_splits = stale_splits.len()
-            );
+        while self.plan_delete_round(ctx).await? > 0 {}
+        Ok(())
+    }
 
-            if stale_splits.is_empty() {
-                break;
-            }
+    /// Fetches a batch of stale splits, sends delete operations for the splits with documents to
+    /// delete, and returns the number of stale splits.
+    async fn plan_delete_round(&mut self, ctx: &ActorContext<Self>) -> anyhow::Result<usize> {
+        let last_delete_opstamp = self
+            .metastore
+            .last_delete_opstamp(self.index_uid.clone())
+            .await?;
+        let stale_splits = self
+            .get_relevant_stale_splits(self.index_uid.clone(), last_delete_opstamp, ctx)
+            .await?;
+        ctx.record_progress();
+        info!(
+            index_id = self.index_uid.index_id(),
+            last_delete_opstamp = last_delete_opstamp,
+            num_stale_splits = stale_splits.len()
+        );
+        self.scheduler.update_progress(&self.index_uid, |progress| {
+            progress.last_delete_opstamp = last_delete_opstamp;
+            progress.num_stale_splits = stale_splits.len();
+        });
 
-            let (splits_with_deletes, splits_without_deletes) =
-                self.partition_splits_by_deletes(&stale_splits, ctx).await?;
+        if stale_splits.is_empty() {
+            return Ok(0);
+        }
 
-            info!(
-                "{} splits with deletes, {} splits without deletes.",
-                splits_with_deletes.len(),
-                splits_without_deletes.len()
-            );
-            ctx.record_progress();
+        let (splits_with_deletes, splits_without_deletes) =
+            self.partition_splits_by_deletes(&stale_splits, ctx).await?;
 
-            // Updates `delete_opstamp` of splits that won't undergo delete operations.
-            let split_ids_without_delete = splits_without_deletes
-                .iter()
-                .map(|split| split.split_id())
-                .collect_vec();
-            ctx.protect_future(self.metastore.update_splits_delete_opstamp(
-                self.index_uid.clone(),
-                &split_ids_without_delete,
-                last_delete_opstamp,
-            ))
-            .await?;
+        info!(
+            "{} splits with deletes, {} splits without deletes.",
+            splits_with_deletes.len(),
+            splits_without_deletes.len()
+        );
+        ctx.record_progress();
 
-            // Sends delete operations.
-            for split_with_deletes in splits_with_deletes {
-                let delete_operation = MergeOperation::new_delete_and_merge_operation(
-                    split_with_deletes.split_metadata,
-                );
-                info!(delete_operation=?delete_operation, "Planned delete operation.");
-                let tracked_delete_operation = self
-                    .ongoing_delete_operations_inventory
-                    .track(delete_operation);
-                ctx.send_message(
-                    &self.merge_split_downloader_mailbox,
-                    tracked_delete_operation,
-                )
-                .await?;
-                JANITOR_METRICS
-                    .ongoing_num_delete_operations_total
-                    .with_label_values([self.index_uid.index_id()])
-                    .set(self.ongoing_delete_operations_inventory.list().len() as i64);
-            }
+        if self.dry_run {
+            self.report_dry_run(&splits_with_deletes);
+            return Ok(0);
         }
+        // Updates `delete_opstamp` of splits that won't undergo delete operations.
+        let split_ids_without_delete = splits_without_deletes
+            .iter()
+            .map(|split| split.split_id())
+            .collect_vec();
+        ctx.protect_future(self.metastore.update_splits_delete_opstamp(
+            self.index_uid.clone(),
+            &split_ids_without_delete,
+            last_delete_opstamp,
+        ))
+        .await?;
+
+        // Sends delete operations.
+        for split_with_deletes in splits_with_deletes {
+            let delete_operation =
+                MergeOperation::new_delete_and_merge_operation(split_with_deletes.split_metadata);
+            info!(delete_operation=?delete_operation, "Planned delete operation.");
+            let tracked_delete_operation = self
+                .ongoing_delete_operations_inventory
+                .track(delete_operation);
+            ctx.send_message(
+                &self.merge_split_downloader_mailbox,
+                tracked_delete_operation,
+            )
+            .await?;
+            JANITOR_METRICS
+                .ongoing_num_delete_operations_total
+                .with_label_values([self.index_uid.index_id()])
+                .set(self.ongoing_delete_operations_inventory.list().len() as i64);
+            JANITOR_METRICS
+                .planned_delete_operations_total
+                .with_label_values([self.index_uid.index_id(), "false"])
+                .inc();
+            self.scheduler.update_progress(&self.index_uid, |progress| {
+                progress.num_planned_delete_operations += 1;
+            });
+        }
+        Ok(stale_splits.len())
+    }
 
-        Ok(())
+    fn report_dry_run(&self, splits_with_deletes: &[Split]) {
+        let split_ids: Vec<&str> = splits_with_deletes
+            .iter()
+            .map(|split| split.split_id())
+            .collect();
+        let num_bytes: u64 = splits_with_deletes
+            .iter()
+            .map(|split| split.split_metadata.footer_offsets.end)
+            .sum();
+        let num_docs: usize = splits_with_deletes
+            .iter()
+            .map(|split| split.split_metadata.num_docs)
+            .sum();
+        info!(
+            index_id=%self.index_uid.index_id(),
+            num_bytes=num_bytes,
+            num_docs=num_docs,
+            split_ids=?PrettySample::new(&split_ids, 5),
+            "Delete task dry run: {} splits would undergo a delete operation.",
+            split_ids.len()
+        );
+        JANITOR_METRICS
+            .planned_delete_operations_total
+            .with_label_values([self.index_uid.index_id(), "true"])
+            .inc_by(split_ids.len() as u64);
+    }
+
+    fn end_turn(&mut self) {
+        self.turn_opt = None;
+        let num_ongoing_delete_operations = self.ongoing_delete_operations_inventory.list().len();
+        self.scheduler.update_progress(&self.index_uid, |progress| {
+            progress.status = DeletePipelineStatus::Idle;
+            progress.num_ongoing_delete_operations = num_ongoing_delete_operations;
+        });
     }
 
     /// Identifies splits that contain documents to delete and
@@ -392,9 +470,50 @@ impl Handler<PlanDeleteLoop> for DeleteTaskPlanner {
         _: PlanDeleteLoop,
         ctx: &ActorContext<Self>,
     ) -> Result<(), ActorExitStatus> {
-        self.handle(PlanDeleteOperations, ctx).await?;
-        ctx.schedule_self_msg(PLANNER_REFRESH_INTERVAL, PlanDeleteLoop)
-            .await;
+        if self.turn_opt.is_none() {
+            let turn = ctx
+                .protect_future(self.scheduler.acquire_turn(&self.index_uid))
+                .await;
+            self.turn_opt = Some(Arc::new(turn));
+        }
+        let num_stale_splits = self.plan_delete_round(ctx).await?;
+
+        if num_stale_splits == 0 {
+            self.end_turn();
+            ctx.schedule_self_msg(PLANNER_REFRESH_INTERVAL, PlanDeleteLoop)
+                .await;
+        } else {
+            self.handle(EndTurnWhenDone, ctx).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Ends the turn of the planner once the delete operations it planned are published, and queues
+/// up for the next one.
+#[derive(Debug)]
+struct EndTurnWhenDone;
+
+#[async_trait]
+impl Handler<EndTurnWhenDone> for DeleteTaskPlanner {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _: EndTurnWhenDone,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        let num_ongoing_delete_operations = self.ongoing_delete_operations_inventory.list().len();
+        self.scheduler.update_progress(&self.index_uid, |progress| {
+            progress.num_ongoing_delete_operations = num_ongoing_delete_operations;
+        });
+        if num_ongoing_delete_operations > 0 {
+            ctx.schedule_self_msg(ONGOING_DELETE_OPERATIONS_CHECK_INTERVAL, EndTurnWhenDone)
+                .await;
+            return Ok(());
+        }
+        self.end_turn();
+        ctx.schedule_self_msg(Duration::ZERO, PlanDeleteLoop).await;
         Ok(())
     }
 }
@@ -509,6 +628,7 @@ mod tests {
             search_job_placer,
             Arc::new(NopMergePolicy),
             downloader_mailbox,
+            DeleteTaskScheduler::default(),
         );
         let (delete_planner_mailbox, delete_planner_handle) = test_sandbox
             .universe()
@@ -17,7 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -32,12 +32,16 @@ use serde::Serialize;
 use tracing::{error, info, warn};
 
 use super::delete_task_pipeline::DeleteTaskPipeline;
+use crate::delete_task_scheduler::{DeletePipelineProgress, DeleteTaskScheduler};
+use crate::JanitorSharding;
 
 pub const DELETE_SERVICE_TASK_DIR_NAME: &str = "delete_task_service";
 
 #[derive(Debug, Clone, Serialize)]
 pub struct DeleteTaskServiceState {
     pub num_running_pipelines: usize,
+    /// Progress of the delete task pipelines keyed by index UID.
+    pub pipelines: BTreeMap<String, DeletePipelineProgress>,
 }
 
 pub struct DeleteTaskService {
@@ -47,6 +51,9 @@ pub struct DeleteTaskService {
     data_dir_path: PathBuf,
     pipeline_handles_by_index_uid: HashMap<IndexUid, ActorHandle<DeleteTaskPipeline>>,
     max_concurrent_split_uploads: usize,
+    sharding: JanitorSharding,
+    scheduler: DeleteTaskScheduler,
+    dry_run: bool,
 }
 
 impl DeleteTaskService {
@@ -64,8 +71,30 @@ impl DeleteTaskService {
             data_dir_path,
             pipeline_handles_by_index_uid: Default::default(),
             max_concurrent_split_uploads,
+            sharding: JanitorSharding::single_node(),
+            scheduler: DeleteTaskScheduler::default(),
+            dry_run: false,
         }
     }
+
+    /// Bounds the number of delete task pipelines applying delete operations at the same time.
+    pub fn with_max_concurrent_pipelines(mut self, max_concurrent_pipelines: usize) -> Self {
+        self.scheduler = DeleteTaskScheduler::new(max_concurrent_pipelines);
+        self
+    }
+
+    /// In dry run mode, the delete task pipelines only report the splits that would undergo a
+    /// delete operation.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Restricts the delete task pipelines to the indexes assigned to this janitor node.
+    pub fn with_sharding(mut self, sharding: JanitorSharding) -> Self {
+        self.sharding = sharding;
+        self
+    }
 }
 
 #[async_trait]
@@ -75,6 +104,7 @@ impl Actor for DeleteTaskService {
     fn observable_state(&self) -> Self::ObservableState {
         DeleteTaskServiceState {
             num_running_pipelines: self.pipeline_handles_by_index_uid.len(),
+            pipelines: self.scheduler.progress(),
         }
     }
 
@@ -98,6 +128,7 @@ impl DeleteTaskService {
             .list_indexes_metadatas()
             .await?
             .into_iter()
+            .filter(|index_metadata| self.sharding.owns_index(&index_metadata.index_uid))
             .map(|index_metadata| {
                 (
                     index_metadata.index_uid.clone(),
@@ -109,11 +140,11 @@ impl DeleteTaskService {
         let pipeline_index_uids: HashSet<IndexUid> =
             self.pipeline_handles_by_index_uid.keys().cloned().collect();
 
-        // Remove pipelines on deleted indexes.
+        // Remove pipelines on deleted indexes or on indexes assigned to another janitor node.
         for deleted_index_uid in pipeline_index_uids.difference(&index_uids) {
             info!(
                 deleted_index_id = deleted_index_uid.index_id(),
-                "Remove deleted index from delete task pipelines."
+                "Remove deleted or unassigned index from delete task pipelines."
             );
             let pipeline_handle = self
                 .pipeline_handles_by_index_uid
@@ -121,6 +152,7 @@ impl DeleteTaskService {
                 .expect("Handle must be present.");
             // Kill the pipeline, this avoids to wait a long time for a delete operation to finish.
             pipeline_handle.kill().await;
+            self.scheduler.remove_index(deleted_index_uid);
         }
 
         // Start new pipelines and add them to the handles hashmap.
@@ -159,7 +191,9 @@ impl DeleteTaskService {
             index_storage,
             delete_task_service_dir,
             self.max_concurrent_split_uploads,
-        );
+        )
+        .with_scheduler(self.scheduler.clone())
+        .with_dry_run(self.dry_run);
         let (_pipeline_mailbox, pipeline_handler) = ctx.spawn_actor().spawn(pipeline);
         self.pipeline_handles_by_index_uid
             .insert(index_metadata.index_uid, pipeline_handler);
@@ -17,7 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -25,18 +25,19 @@ use async_trait::async_trait;
 use futures::StreamExt;
 use itertools::Itertools;
 use quickwit_actors::{Actor, ActorContext, Handler};
-use quickwit_metastore::Metastore;
+use quickwit_common::PrettySample;
+use quickwit_config::JanitorConfig;
+use quickwit_metastore::{IndexMetadata, Metastore};
+use quickwit_proto::IndexUid;
 use quickwit_storage::StorageUriResolver;
 use serde::Serialize;
+use time::OffsetDateTime;
 use tracing::{error, info};
 
-use crate::garbage_collection::run_garbage_collect;
+use crate::garbage_collection::{purge_trashed_index, run_garbage_collect};
+use crate::metrics::JANITOR_METRICS;
+use crate::JanitorSharding;
 
-const RUN_INTERVAL: Duration = Duration::from_secs(60); // 1 minutes
-/// Staged files needs to be deleted if there was a failure.
-/// TODO ideally we want clean up all staged splits every time we restart the indexing pipeline, but
-/// the grace period strategy should do the job for the moment.
-const STAGED_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60 * 24); // 24 hours
 /// We cannot safely delete splits right away as a in-flight queries could actually
 /// have selected this split.
 /// We deal this probably by introducing a grace period. A split is first marked as delete,
@@ -63,48 +64,198 @@ pub struct GarbageCollectorCounters {
     pub num_failed_storage_resolution: usize,
     /// The number of splits that were unable to be removed.
     pub num_failed_splits: usize,
+    /// The number of trashed indexes permanently deleted after their retention period.
+    pub num_purged_trashed_indexes: usize,
+    /// The number of trashed indexes that failed to be permanently deleted.
+    pub num_failed_trashed_index_purges: usize,
 }
 
 #[derive(Debug)]
 struct Loop;
 
 /// An actor for collecting garbage periodically from an index.
+///
+/// Each index is garbage collected at its own interval, which defaults to the janitor
+/// configuration. The elapsed time is measured as the sum of the periods between two loops, so
+/// that the time spent collecting garbage does not delay the following runs.
 pub struct GarbageCollector {
     metastore: Arc<dyn Metastore>,
     storage_resolver: StorageUriResolver,
+    index_trash_retention_period_opt: Option<Duration>,
+    sharding: JanitorSharding,
+    default_interval: Duration,
+    /// Staged files needs to be deleted if there was a failure.
+    /// TODO ideally we want clean up all staged splits every time we restart the indexing
+    /// pipeline, but the grace period strategy should do the job for the moment.
+    default_staged_grace_period: Duration,
+    /// Time elapsed since the first loop.
+    uptime: Duration,
+    /// Uptime at which the next garbage collection of each index is due.
+    next_gc_uptimes: HashMap<IndexUid, Duration>,
+    dry_run: bool,
     counters: GarbageCollectorCounters,
 }
 
 impl GarbageCollector {
     pub fn new(metastore: Arc<dyn Metastore>, storage_resolver: StorageUriResolver) -> Self {
+        let janitor_config = JanitorConfig::default();
         Self {
             metastore,
             storage_resolver,
+            index_trash_retention_period_opt: None,
+            sharding: JanitorSharding::single_node(),
+            default_interval: janitor_config.gc_interval(),
+            default_staged_grace_period: janitor_config.gc_staged_split_grace_period(),
+            uptime: Duration::ZERO,
+            next_gc_uptimes: HashMap::new(),
+            dry_run: false,
             counters: GarbageCollectorCounters::default(),
         }
     }
 
-    /// Gc Loop handler logic.
-    /// Should not return an error to prevent the actor from crashing.
-    async fn handle_inner(&mut self, ctx: &ActorContext<Self>) {
-        info!("garbage-collect-operation");
-        self.counters.num_passes += 1;
+    /// Sets the garbage collection interval and the staged split grace period applied to the
+    /// indexes that do not override them.
+    pub fn with_default_gc_settings(
+        mut self,
+        default_interval: Duration,
+        default_staged_grace_period: Duration,
+    ) -> Self {
+        self.default_interval = default_interval;
+        self.default_staged_grace_period = default_staged_grace_period;
+        self
+    }
+
+    /// Enables the permanent deletion of trashed indexes once they have been in the trash for
+    /// longer than `index_trash_retention_period`.
+    pub fn with_index_trash_retention_period(
+        mut self,
+        index_trash_retention_period: Duration,
+    ) -> Self {
+        self.index_trash_retention_period_opt = Some(index_trash_retention_period);
+        self
+    }
 
-        let index_metadatas = match self.metastore.list_indexes_metadatas().await {
+    /// In dry run mode, the garbage collector only logs and reports through its metrics the splits
+    /// and the trashed indexes it would delete.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Restricts the garbage collection to the indexes assigned to this janitor node.
+    pub fn with_sharding(mut self, sharding: JanitorSharding) -> Self {
+        self.sharding = sharding;
+        self
+    }
+
+    /// Permanently deletes the trashed indexes whose retention period has expired.
+    async fn purge_trashed_indexes(
+        &mut self,
+        index_trash_retention_period: Duration,
+        ctx: &ActorContext<Self>,
+    ) {
+        let trashed_index_metadatas = match ctx
+            .protect_future(self.metastore.list_trashed_indexes_metadatas())
+            .await
+        {
             Ok(metadatas) => metadatas,
             Err(error) => {
-                error!(error=?error, "Failed to list indexes from the metastore.");
+                error!(error=?error, "Failed to list trashed indexes from the metastore.");
                 return;
             }
         };
+        let expiration_timestamp = OffsetDateTime::now_utc().unix_timestamp()
+            - index_trash_retention_period.as_secs() as i64;
+
+        for index_metadata in trashed_index_metadatas {
+            let is_expired = index_metadata
+                .trash_timestamp
+                .map(|trash_timestamp| trash_timestamp <= expiration_timestamp)
+                .unwrap_or(false);
+            if !is_expired || !self.sharding.owns_index(&index_metadata.index_uid) {
+                continue;
+            }
+            if self.dry_run {
+                info!(
+                    index_id=%index_metadata.index_id(),
+                    "Garbage collection dry run: trashed index would be purged."
+                );
+                continue;
+            }
+            let storage = match self.storage_resolver.resolve(index_metadata.index_uri()) {
+                Ok(storage) => storage,
+                Err(error) => {
+                    self.counters.num_failed_storage_resolution += 1;
+                    error!(index=%index_metadata.index_id(), error=?error, "Failed to resolve the index storage Uri.");
+                    continue;
+                }
+            };
+            let index_uid = index_metadata.index_uid;
+            match purge_trashed_index(
+                index_uid.clone(),
+                storage,
+                self.metastore.clone(),
+                Some(ctx),
+            )
+            .await
+            {
+                Ok(deleted_file_entries) => {
+                    info!(
+                        index_id=%index_uid.index_id(),
+                        num_deleted_splits=deleted_file_entries.len(),
+                        "Janitor purged trashed index."
+                    );
+                    self.counters.num_purged_trashed_indexes += 1;
+                    self.counters.num_deleted_files += deleted_file_entries.len();
+                    self.counters.num_deleted_bytes += deleted_file_entries
+                        .iter()
+                        .map(|entry| entry.file_size_in_bytes as usize)
+                        .sum::<usize>();
+                }
+                Err(error) => {
+                    self.counters.num_failed_trashed_index_purges += 1;
+                    error!(index_id=%index_uid.index_id(), error=?error, "Failed to purge trashed index.");
+                }
+            }
+        }
+    }
+
+    /// Gc Loop handler logic. Returns the delay until the next loop.
+    /// Should not return an error to prevent the actor from crashing.
+    async fn handle_inner(&mut self, ctx: &ActorContext<Self>) -> Duration {
+        info!("garbage-collect-operation");
+        self.counters.num_passes += 1;
+
+        if let Some(index_trash_retention_period) = self.index_trash_retention_period_opt {
+            self.purge_trashed_indexes(index_trash_retention_period, ctx)
+                .await;
+        }
+
+        let index_metadatas: Vec<IndexMetadata> =
+            match self.metastore.list_indexes_metadatas().await {
+                Ok(metadatas) => metadatas
+                    .into_iter()
+                    .filter(|index_metadata| self.sharding.owns_index(&index_metadata.index_uid))
+                    .collect(),
+                Err(error) => {
+                    error!(error=?error, "Failed to list indexes from the metastore.");
+                    return self.default_interval;
+                }
+            };
+        let index_metadatas = self.select_due_indexes(index_metadatas);
+        let next_loop_delay = self.next_loop_delay();
         info!(index_ids=%index_metadatas.iter().map(|im| im.index_id()).join(", "), "Garbage collecting indexes.");
 
         let index_ids_to_storage_iter = index_metadatas
             .into_iter()
             .filter_map(|index_metadata| {
                 let index_uri = index_metadata.index_uri();
+                let staged_grace_period = index_metadata
+                    .index_config
+                    .garbage_collection_settings
+                    .staged_split_grace_period(self.default_staged_grace_period);
                 match self.storage_resolver.resolve(index_uri) {
-                    Ok(storage) => Some((index_metadata.index_uid, storage)),
+                    Ok(storage) => Some((index_metadata.index_uid, storage, staged_grace_period)),
                     Err(error) => {
                         self.counters.num_failed_storage_resolution += 1;
                         error!(index=%index_metadata.index_id(), error=?error, "Failed to resolve the index storage Uri.");
@@ -113,17 +264,18 @@ impl GarbageCollector {
                 }
             });
 
+        let dry_run = self.dry_run;
         let run_gc_tasks: Vec<_> = index_ids_to_storage_iter
-            .map(|(index_uid, storage)| {
+            .map(|(index_uid, storage, staged_grace_period)| {
                 let moved_metastore = self.metastore.clone();
                 async move {
                     let run_gc_result = run_garbage_collect(
                         index_uid.clone(),
                         storage,
                         moved_metastore,
-                        STAGED_GRACE_PERIOD,
+                        staged_grace_period,
                         DELETION_GRACE_PERIOD,
-                        false,
+                        dry_run,
                         Some(ctx),
                     )
                     .await;
@@ -149,7 +301,39 @@ impl GarbageCollector {
                 }
             };
 
-            if !deleted_file_entries.is_empty() {
+            if deleted_file_entries.is_empty() {
+                continue;
+            }
+            let num_deleted_bytes: u64 = deleted_file_entries
+                .iter()
+                .map(|entry| entry.file_size_in_bytes)
+                .sum();
+            let labels = [
+                index_uid.index_id(),
+                if self.dry_run { "true" } else { "false" },
+            ];
+            JANITOR_METRICS
+                .garbage_collected_splits_total
+                .with_label_values(labels)
+                .inc_by(deleted_file_entries.len() as u64);
+            JANITOR_METRICS
+                .garbage_collected_bytes_total
+                .with_label_values(labels)
+                .inc_by(num_deleted_bytes);
+
+            if self.dry_run {
+                let file_names: Vec<&str> = deleted_file_entries
+                    .iter()
+                    .map(|entry| entry.file_name.as_str())
+                    .collect();
+                info!(
+                    index_id=%index_uid.index_id(),
+                    num_bytes=num_deleted_bytes,
+                    files=?PrettySample::new(&file_names, 5),
+                    "Garbage collection dry run: {} splits would be deleted.",
+                    file_names.len()
+                );
+            } else {
                 let num_deleted_splits = deleted_file_entries.len();
                 let deleted_files: HashSet<&str> = deleted_file_entries
                     .iter()
@@ -171,6 +355,47 @@ impl GarbageCollector {
                     .sum::<usize>();
             }
         }
+        next_loop_delay
+    }
+
+    /// Returns the indexes whose garbage collection is due and schedules their next run.
+    fn select_due_indexes(&mut self, index_metadatas: Vec<IndexMetadata>) -> Vec<IndexMetadata> {
+        let index_uids: HashSet<&IndexUid> = index_metadatas
+            .iter()
+            .map(|index_metadata| &index_metadata.index_uid)
+            .collect();
+        self.next_gc_uptimes
+            .retain(|index_uid, _| index_uids.contains(index_uid));
+
+        let mut due_index_metadatas = Vec::with_capacity(index_metadatas.len());
+
+        for index_metadata in index_metadatas {
+            let is_due = self
+                .next_gc_uptimes
+                .get(&index_metadata.index_uid)
+                .map(|next_gc_uptime| *next_gc_uptime <= self.uptime)
+                .unwrap_or(true);
+            if !is_due {
+                continue;
+            }
+            let interval = index_metadata
+                .index_config
+                .garbage_collection_settings
+                .interval(self.default_interval);
+            self.next_gc_uptimes
+                .insert(index_metadata.index_uid.clone(), self.uptime + interval);
+            due_index_metadatas.push(index_metadata);
+        }
+        due_index_metadatas
+    }
+
+    /// Returns the delay until the next index is due, bounded by the default interval so that
+    /// new indexes and trashed indexes are processed in a timely manner.
+    fn next_loop_delay(&self) -> Duration {
+        self.next_gc_uptimes
+            .values()
+            .map(|next_gc_uptime| next_gc_uptime.saturating_sub(self.uptime))
+            .fold(self.default_interval, Duration::min)
     }
 }
 
@@ -204,27 +429,32 @@ impl Handler<Loop> for GarbageCollector {
         _: Loop,
         ctx: &ActorContext<Self>,
     ) -> Result<(), quickwit_actors::ActorExitStatus> {
-        self.handle_inner(ctx).await;
-        ctx.schedule_self_msg(RUN_INTERVAL, Loop).await;
+        let next_loop_delay = self.handle_inner(ctx).await;
+        self.uptime += next_loop_delay;
+        ctx.schedule_self_msg(next_loop_delay, Loop).await;
         Ok(())
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::num::NonZeroU64;
     use std::ops::Bound;
     use std::path::Path;
 
     use quickwit_actors::Universe;
+    use quickwit_config::GarbageCollectionSettings;
     use quickwit_metastore::{
         IndexMetadata, ListSplitsQuery, MetastoreError, MockMetastore, Split, SplitMetadata,
-        SplitState,
+        SplitState, SplitsPage,
     };
     use quickwit_storage::MockStorage;
-    use time::OffsetDateTime;
 
     use super::*;
 
+    const RUN_INTERVAL: Duration = Duration::from_secs(60);
+    const STAGED_GRACE_PERIOD: Duration = Duration::from_secs(60 * 60 * 24);
+
     fn make_splits(split_ids: &[&str], split_state: SplitState) -> Vec<Split> {
         split_ids
             .iter()
@@ -261,10 +491,8 @@ mod tests {
             });
 
         let mut mock_metastore = MockMetastore::default();
-        mock_metastore
-            .expect_list_splits()
-            .times(2)
-            .returning(|query: ListSplitsQuery| {
+        mock_metastore.expect_list_splits_page().times(2).returning(
+            |query: ListSplitsQuery, page_size| {
                 assert_eq!(query.index_uid.to_string(), "test-index:1111111111111");
                 let splits = match query.split_states[0] {
                     SplitState::Staged => make_splits(&["a"], SplitState::Staged),
@@ -288,8 +516,9 @@ mod tests {
                     }
                     _ => panic!("only Staged and MarkedForDeletion expected."),
                 };
-                Ok(splits)
-            });
+                Ok(SplitsPage::new(splits, page_size))
+            },
+        );
         mock_metastore
             .expect_mark_splits_for_deletion()
             .times(1)
@@ -337,9 +566,9 @@ mod tests {
                 )])
             });
         mock_metastore
-            .expect_list_splits()
+            .expect_list_splits_page()
             .times(2)
-            .returning(|query| {
+            .returning(|query, page_size| {
                 assert_eq!(query.index_uid.index_id(), "test-index");
                 let splits = match query.split_states[0] {
                     SplitState::Staged => make_splits(&["a"], SplitState::Staged),
@@ -348,7 +577,7 @@ mod tests {
                     }
                     _ => panic!("only Staged and MarkedForDeletion expected."),
                 };
-                Ok(splits)
+                Ok(SplitsPage::new(splits, page_size))
             });
         mock_metastore
             .expect_mark_splits_for_deletion()
@@ -384,6 +613,114 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_garbage_collect_skips_indexes_assigned_to_other_janitor_nodes() {
+        let storage_resolver = StorageUriResolver::for_test();
+        let index_metadata = IndexMetadata::for_test("test-index", "ram://indexes/test-index");
+        let node_ids = vec!["node-1".to_string(), "node-2".to_string()];
+        let sharding = node_ids
+            .iter()
+            .map(|node_id| JanitorSharding::for_node_ids(node_id.clone(), node_ids.clone()))
+            .find(|sharding| !sharding.owns_index(&index_metadata.index_uid))
+            .unwrap();
+
+        let mut mock_metastore = MockMetastore::default();
+        mock_metastore
+            .expect_list_indexes_metadatas()
+            .times(1)
+            .returning(move || Ok(vec![index_metadata.clone()]));
+        mock_metastore.expect_list_splits_page().times(0);
+
+        let garbage_collect_actor =
+            GarbageCollector::new(Arc::new(mock_metastore), storage_resolver)
+                .with_sharding(sharding);
+        let universe = Universe::with_accelerated_time();
+        let (_mailbox, handler) = universe.spawn_builder().spawn(garbage_collect_actor);
+
+        let state_after_initialization = handler.process_pending_and_observe().await.state;
+        assert_eq!(state_after_initialization.num_passes, 1);
+        assert_eq!(state_after_initialization.num_successful_gc_run_on_index, 0);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_garbage_collect_purges_expired_trashed_indexes() {
+        let storage_resolver = StorageUriResolver::for_test();
+        let mut mock_metastore = MockMetastore::default();
+        mock_metastore
+            .expect_list_indexes_metadatas()
+            .times(1)
+            .returning(|| Ok(Vec::new()));
+        mock_metastore
+            .expect_list_trashed_indexes_metadatas()
+            .times(1)
+            .returning(|| {
+                let now_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+                let mut expired_index_metadata =
+                    IndexMetadata::for_test("expired-index", "ram://indexes/expired-index");
+                expired_index_metadata.trash_timestamp = Some(now_timestamp - 2 * 24 * 3600);
+                let mut recent_index_metadata =
+                    IndexMetadata::for_test("recent-index", "ram://indexes/recent-index");
+                recent_index_metadata.trash_timestamp = Some(now_timestamp - 3600);
+                Ok(vec![expired_index_metadata, recent_index_metadata])
+            });
+        mock_metastore
+            .expect_list_splits_page()
+            .times(2)
+            .returning(|query, page_size| {
+                assert_eq!(query.index_uid.index_id(), "expired-index");
+                let splits = match query.split_states.as_slice() {
+                    [SplitState::Staged, SplitState::Published] => {
+                        make_splits(&["a"], SplitState::Published)
+                    }
+                    [SplitState::MarkedForDeletion] => {
+                        make_splits(&["a", "b"], SplitState::MarkedForDeletion)
+                    }
+                    _ => panic!("only Staged, Published and MarkedForDeletion expected."),
+                };
+                Ok(SplitsPage::new(splits, page_size))
+            });
+        mock_metastore
+            .expect_mark_splits_for_deletion()
+            .times(1)
+            .returning(|index_uid, split_ids| {
+                assert_eq!(index_uid.index_id(), "expired-index");
+                assert_eq!(split_ids, vec!["a"]);
+                Ok(())
+            });
+        mock_metastore
+            .expect_delete_splits()
+            .times(1)
+            .returning(|index_uid, split_ids| {
+                assert_eq!(index_uid.index_id(), "expired-index");
+                assert_eq!(split_ids.len(), 2);
+                Ok(())
+            });
+        mock_metastore
+            .expect_delete_index()
+            .times(1)
+            .returning(|index_uid| {
+                assert_eq!(index_uid.index_id(), "expired-index");
+                Ok(())
+            });
+
+        let garbage_collect_actor =
+            GarbageCollector::new(Arc::new(mock_metastore), storage_resolver)
+                .with_index_trash_retention_period(Duration::from_secs(24 * 3600));
+        let universe = Universe::with_accelerated_time();
+        let (_mailbox, handler) = universe.spawn_builder().spawn(garbage_collect_actor);
+
+        let state_after_initialization = handler.process_pending_and_observe().await.state;
+        assert_eq!(state_after_initialization.num_passes, 1);
+        assert_eq!(state_after_initialization.num_purged_trashed_indexes, 1);
+        assert_eq!(
+            state_after_initialization.num_failed_trashed_index_purges,
+            0
+        );
+        assert_eq!(state_after_initialization.num_deleted_files, 2);
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_garbage_collect_get_calls_repeatedly() {
         let storage_resolver = StorageUriResolver::for_test();
@@ -398,9 +735,9 @@ mod tests {
                 )])
             });
         mock_metastore
-            .expect_list_splits()
+            .expect_list_splits_page()
             .times(6)
-            .returning(|query| {
+            .returning(|query, page_size| {
                 assert_eq!(query.index_uid.index_id(), "test-index");
                 let splits = match query.split_states[0] {
                     SplitState::Staged => make_splits(&["a"], SplitState::Staged),
@@ -409,7 +746,7 @@ mod tests {
                     }
                     _ => panic!("only Staged and MarkedForDeletion expected."),
                 };
-                Ok(splits)
+                Ok(SplitsPage::new(splits, page_size))
             });
         mock_metastore
             .expect_mark_splits_for_deletion()
@@ -501,6 +838,109 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_garbage_collect_dry_run() {
+        let storage_resolver = StorageUriResolver::for_test();
+        let mut mock_metastore = MockMetastore::default();
+        mock_metastore
+            .expect_list_indexes_metadatas()
+            .times(1)
+            .returning(|| {
+                Ok(vec![IndexMetadata::for_test(
+                    "test-index",
+                    "ram://indexes/test-index",
+                )])
+            });
+        mock_metastore
+            .expect_list_splits_page()
+            .times(2)
+            .returning(|query, page_size| {
+                let splits = match query.split_states[0] {
+                    SplitState::Staged => make_splits(&["a"], SplitState::Staged),
+                    SplitState::MarkedForDeletion => {
+                        make_splits(&["b"], SplitState::MarkedForDeletion)
+                    }
+                    _ => panic!("only Staged and MarkedForDeletion expected."),
+                };
+                Ok(SplitsPage::new(splits, page_size))
+            });
+        mock_metastore.expect_mark_splits_for_deletion().times(0);
+        mock_metastore.expect_delete_splits().times(0);
+
+        let garbage_collect_actor =
+            GarbageCollector::new(Arc::new(mock_metastore), storage_resolver).with_dry_run(true);
+        let universe = Universe::with_accelerated_time();
+        let (_mailbox, handle) = universe.spawn_builder().spawn(garbage_collect_actor);
+
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_passes, 1);
+        assert_eq!(counters.num_successful_gc_run_on_index, 1);
+        assert_eq!(counters.num_deleted_files, 0);
+        assert_eq!(counters.num_deleted_bytes, 0);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_garbage_collect_honors_index_gc_settings() {
+        let storage_resolver = StorageUriResolver::for_test();
+        let mut mock_metastore = MockMetastore::default();
+        mock_metastore
+            .expect_list_indexes_metadatas()
+            .returning(|| {
+                let index_metadata_a =
+                    IndexMetadata::for_test("test-index-a", "ram://indexes/test-index-a");
+                let mut index_metadata_b =
+                    IndexMetadata::for_test("test-index-b", "ram://indexes/test-index-b");
+                index_metadata_b.index_config.garbage_collection_settings =
+                    GarbageCollectionSettings {
+                        interval_secs: NonZeroU64::new(180),
+                        staged_split_grace_period_secs: NonZeroU64::new(3600),
+                    };
+                Ok(vec![index_metadata_a, index_metadata_b])
+            });
+        mock_metastore
+            .expect_list_splits_page()
+            .returning(|query, _page_size| {
+                if query.split_states[0] == SplitState::Staged {
+                    let staged_grace_period = if query.index_uid.index_id() == "test-index-b" {
+                        Duration::from_secs(3600)
+                    } else {
+                        STAGED_GRACE_PERIOD
+                    };
+                    let expected_timestamp = OffsetDateTime::now_utc().unix_timestamp()
+                        - staged_grace_period.as_secs() as i64;
+                    match query.update_timestamp.end {
+                        Bound::Included(timestamp) => {
+                            assert!((expected_timestamp - timestamp).abs() <= 5)
+                        }
+                        _ => panic!("Expected an inclusive upper bound on the update timestamp."),
+                    }
+                }
+                Ok(SplitsPage::default())
+            });
+
+        let garbage_collect_actor =
+            GarbageCollector::new(Arc::new(mock_metastore), storage_resolver);
+        let universe = Universe::with_accelerated_time();
+        let (_mailbox, handle) = universe.spawn_builder().spawn(garbage_collect_actor);
+
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_passes, 1);
+        assert_eq!(counters.num_successful_gc_run_on_index, 2);
+
+        // Only the index with the default interval is garbage collected in between.
+        for expected_num_runs in [3, 4] {
+            universe.sleep(RUN_INTERVAL).await;
+            let counters = handle.process_pending_and_observe().await.state;
+            assert_eq!(counters.num_successful_gc_run_on_index, expected_num_runs);
+        }
+        universe.sleep(RUN_INTERVAL).await;
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_passes, 4);
+        assert_eq!(counters.num_successful_gc_run_on_index, 6);
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_garbage_collect_fails_to_resolve_storage() {
         let storage_resolver = StorageUriResolver::for_test();
@@ -545,9 +985,9 @@ mod tests {
                 ])
             });
         mock_metastore
-            .expect_list_splits()
+            .expect_list_splits_page()
             .times(3)
-            .returning(|query| {
+            .returning(|query, page_size| {
                 assert!(["test-index-1", "test-index-2"].contains(&query.index_uid.index_id()));
 
                 if query.index_uid.index_id() == "test-index-2" {
@@ -563,7 +1003,7 @@ mod tests {
                     }
                     _ => panic!("only Staged and MarkedForDeletion expected."),
                 };
-                Ok(splits)
+                Ok(SplitsPage::new(splits, page_size))
             });
         mock_metastore
             .expect_mark_splits_for_deletion()
@@ -614,9 +1054,9 @@ mod tests {
                 ])
             });
         mock_metastore
-            .expect_list_splits()
+            .expect_list_splits_page()
             .times(4)
-            .returning(|query| {
+            .returning(|query, page_size| {
                 assert!(["test-index-1", "test-index-2"].contains(&query.index_uid.index_id()));
                 let splits = match query.split_states[0] {
                     SplitState::Staged => make_splits(&["a"], SplitState::Staged),
@@ -625,7 +1065,7 @@ mod tests {
                     }
                     _ => panic!("only Staged and MarkedForDeletion expected."),
                 };
-                Ok(splits)
+                Ok(SplitsPage::new(splits, page_size))
             });
         mock_metastore
             .expect_mark_splits_for_deletion()
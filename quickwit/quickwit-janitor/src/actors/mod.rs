@@ -22,7 +22,9 @@ mod delete_task_planner;
 mod delete_task_service;
 mod garbage_collector;
 mod retention_policy_executor;
+mod rollup_executor;
 
 pub use delete_task_service::DeleteTaskService;
 pub use garbage_collector::GarbageCollector;
 pub use retention_policy_executor::RetentionPolicyExecutor;
+pub use rollup_executor::RollupExecutor;
@@ -24,13 +24,15 @@ use std::time::Duration;
 use async_trait::async_trait;
 use itertools::Itertools;
 use quickwit_actors::{Actor, ActorContext, Handler};
-use quickwit_config::IndexConfig;
+use quickwit_config::{ClusterSettings, IndexConfig};
 use quickwit_metastore::Metastore;
 use quickwit_proto::IndexUid;
+use quickwit_search::SearchService;
 use serde::Serialize;
 use tracing::{debug, error, info};
 
-use crate::retention_policy_execution::run_execute_retention_policy;
+use crate::retention_policy_execution::{run_execute_retention_policy, run_expire_documents};
+use crate::JanitorSharding;
 
 const RUN_INTERVAL: Duration = Duration::from_secs(60 * 60); // 1 hours
 
@@ -44,6 +46,9 @@ pub struct RetentionPolicyExecutorCounters {
 
     /// The number of expired splits.
     pub num_expired_splits: usize,
+
+    /// The number of delete tasks created to delete the expired documents.
+    pub num_expiration_delete_tasks: usize,
 }
 
 #[derive(Debug)]
@@ -59,22 +64,41 @@ struct Execute {
 /// in a cache and periodically update this list.
 pub struct RetentionPolicyExecutor {
     metastore: Arc<dyn Metastore>,
+    search_service: Arc<dyn SearchService>,
     /// A map of index_id to index metadata that are managed by this executor.
     /// This act as local cache that is periodically updated while taking into
     /// account deleted indexes, updated or removed retention policy on indexes.
     index_configs: HashMap<String, IndexConfig>,
+    sharding: JanitorSharding,
+    dry_run: bool,
     counters: RetentionPolicyExecutorCounters,
 }
 
 impl RetentionPolicyExecutor {
-    pub fn new(metastore: Arc<dyn Metastore>) -> Self {
+    pub fn new(metastore: Arc<dyn Metastore>, search_service: Arc<dyn SearchService>) -> Self {
         Self {
             metastore,
+            search_service,
             index_configs: HashMap::new(),
+            sharding: JanitorSharding::single_node(),
+            dry_run: false,
             counters: RetentionPolicyExecutorCounters::default(),
         }
     }
 
+    /// Restricts the retention policy execution to the indexes assigned to this janitor node.
+    pub fn with_sharding(mut self, sharding: JanitorSharding) -> Self {
+        self.sharding = sharding;
+        self
+    }
+
+    /// In dry run mode, the retention policies of all the indexes are evaluated as dry runs: the
+    /// expired splits are only logged and reported through the metrics.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
     /// Indexes refresh Loop handler logic.
     /// Should not return an error to prevent the actor from crashing.
     async fn handle_refresh_loop(&mut self, ctx: &ActorContext<Self>) {
@@ -90,6 +114,15 @@ impl RetentionPolicyExecutor {
         };
         debug!(index_ids=%index_metadatas.iter().map(|im| im.index_id()).join(", "), "Retention policy refresh.");
 
+        // The indexes without a retention policy are subject to the default one of the cluster.
+        let default_retention_policy_opt = match self.metastore.cluster_settings().await {
+            Ok(cluster_settings) => cluster_settings.default_retention_policy,
+            Err(error) => {
+                error!(error=?error, "Failed to fetch the cluster settings from the metastore.");
+                return;
+            }
+        };
+
         let deleted_indexes = compute_deleted_indexes(
             self.index_configs.keys().map(String::as_str),
             index_metadatas
@@ -105,14 +138,19 @@ impl RetentionPolicyExecutor {
 
         for index_metadata in index_metadatas {
             let index_uid = index_metadata.index_uid.clone();
-            let index_config = index_metadata.into_index_config();
+            let mut index_config = index_metadata.into_index_config();
+
+            if index_config.retention_policy.is_none() {
+                index_config.retention_policy = default_retention_policy_opt.clone();
+            }
             // We only care about indexes with a retention policy configured.
             let retention_policy = match &index_config.retention_policy {
-                Some(policy) => policy,
-                None => {
+                Some(policy) if self.sharding.owns_index(&index_uid) => policy,
+                _ => {
                     // Remove the index from the cache if it exist.
-                    // In case where the retention policy was removed this index might have
-                    // been inserted in the cache from a previous iteration.
+                    // In case where the retention policy was removed or the index was assigned to
+                    // another janitor node, this index might have been inserted in the cache from
+                    // a previous iteration.
                     self.index_configs.remove(&index_config.index_id);
                     continue;
                 }
@@ -184,6 +222,11 @@ impl Handler<Execute> for RetentionPolicyExecutor {
         message: Execute,
         ctx: &ActorContext<Self>,
     ) -> Result<(), quickwit_actors::ActorExitStatus> {
+        if !self.sharding.owns_index(&message.index_uid) {
+            debug!(index_id=%message.index_uid.index_id(), "The index was assigned to another janitor node.");
+            self.index_configs.remove(message.index_uid.index_id());
+            return Ok(());
+        }
         info!(index_id=%message.index_uid.index_id(), "retention-policy-execute-operation");
         self.counters.num_execution_passes += 1;
 
@@ -199,11 +242,13 @@ impl Handler<Execute> for RetentionPolicyExecutor {
             .retention_policy
             .as_ref()
             .expect("Expected index to have retention policy configure.");
+        let dry_run = self.dry_run || retention_policy.dry_run();
+        let effective_retention_policy = retention_policy.clone().with_dry_run(dry_run);
 
         let execution_result = run_execute_retention_policy(
             message.index_uid.clone(),
             self.metastore.clone(),
-            retention_policy,
+            &effective_retention_policy,
             ctx,
         )
         .await;
@@ -213,6 +258,22 @@ impl Handler<Execute> for RetentionPolicyExecutor {
                 error!(index_id=%message.index_uid.index_id(), error=?error, "Failed to execute the retention policy on the index.")
             }
         }
+        let expiration_result = run_expire_documents(
+            message.index_uid.clone(),
+            self.metastore.clone(),
+            self.search_service.clone(),
+            index_config,
+            &effective_retention_policy,
+            ctx,
+        )
+        .await;
+        match expiration_result {
+            Ok(Some(_)) => self.counters.num_expiration_delete_tasks += 1,
+            Ok(None) => {}
+            Err(error) => {
+                error!(index_id=%message.index_uid.index_id(), error=?error, "Failed to delete the expired documents of the index.")
+            }
+        }
 
         if let Ok(next_interval) = retention_policy.duration_until_next_evaluation() {
             info!(index_id=?index_config.index_id, scheduled_in=?next_interval, "retention-policy-schedule-operation");
@@ -229,7 +290,7 @@ impl Handler<Execute> for RetentionPolicyExecutor {
 }
 
 /// Extract the list of deleted indexes.
-fn compute_deleted_indexes<'a>(
+pub(super) fn compute_deleted_indexes<'a>(
     cached_indexes: impl Iterator<Item = &'a str>,
     indexes: impl Iterator<Item = &'a str>,
 ) -> HashSet<String> {
@@ -249,6 +310,9 @@ mod tests {
     use quickwit_actors::Universe;
     use quickwit_config::RetentionPolicy;
     use quickwit_metastore::{IndexMetadata, MockMetastore, Split, SplitMetadata, SplitState};
+    use quickwit_proto::metastore_api::DeleteTask;
+    use quickwit_proto::SearchResponse;
+    use quickwit_search::MockSearchService;
 
     use super::*;
 
@@ -292,7 +356,8 @@ mod tests {
         let mut index = IndexConfig::for_test(index_id, &format!("ram://indexes/{index_id}"));
         if let Some(retention_period) = retention_period_opt {
             index.retention_policy = Some(RetentionPolicy::new(
-                retention_period.to_string(),
+                Some(retention_period.to_string()),
+                None,
                 SCHEDULE_EXPR.to_string(),
             ))
         }
@@ -324,7 +389,7 @@ mod tests {
     // Uses the retention policy scheduler to calculate
     // how much time to advance for the execution to take place.
     fn shift_time_by() -> Duration {
-        let scheduler = RetentionPolicy::new("".to_string(), SCHEDULE_EXPR.to_string());
+        let scheduler = RetentionPolicy::new(None, None, SCHEDULE_EXPR.to_string());
         scheduler.duration_until_next_evaluation().unwrap() + Duration::from_secs(1)
     }
 
@@ -373,7 +438,14 @@ mod tests {
                 ]))
             });
 
-        let retention_policy_executor = RetentionPolicyExecutor::new(Arc::new(mock_metastore));
+        mock_metastore
+            .expect_cluster_settings()
+            .returning(|| Ok(ClusterSettings::default()));
+
+        let retention_policy_executor = RetentionPolicyExecutor::new(
+            Arc::new(mock_metastore),
+            Arc::new(MockSearchService::new()),
+        );
         let universe = Universe::with_accelerated_time();
         let (mailbox, handle) = universe.spawn_builder().spawn(retention_policy_executor);
 
@@ -411,6 +483,65 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_retention_executor_default_retention_policy() -> anyhow::Result<()> {
+        let mut mock_metastore = MockMetastore::default();
+        mock_metastore
+            .expect_list_splits()
+            .times(..)
+            .returning(|_| Ok(Vec::new()));
+        mock_metastore
+            .expect_list_indexes_metadatas()
+            .returning(|| Ok(make_indexes(&[("a", Some("1 hour")), ("b", None)])));
+
+        let mut sequence = Sequence::new();
+        mock_metastore
+            .expect_cluster_settings()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|| {
+                Ok(ClusterSettings {
+                    default_retention_policy: Some(RetentionPolicy::new(
+                        Some("3 days".to_string()),
+                        None,
+                        SCHEDULE_EXPR.to_string(),
+                    )),
+                    ..Default::default()
+                })
+            });
+        mock_metastore
+            .expect_cluster_settings()
+            .times(1)
+            .in_sequence(&mut sequence)
+            .returning(|| Ok(ClusterSettings::default()));
+
+        let retention_policy_executor = RetentionPolicyExecutor::new(
+            Arc::new(mock_metastore),
+            Arc::new(MockSearchService::new()),
+        );
+        let universe = Universe::with_accelerated_time();
+        let (mailbox, handle) = universe.spawn_builder().spawn(retention_policy_executor);
+
+        handle.process_pending_and_observe().await;
+        mailbox
+            .ask(AssertState(vec![
+                ("a", Some("1 hour")),
+                ("b", Some("3 days")),
+            ]))
+            .await?;
+
+        // Removing the default retention policy stops the retention of the indexes relying on it.
+        universe.sleep(RUN_INTERVAL + Duration::from_secs(5)).await;
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_refresh_passes, 2);
+        mailbox
+            .ask(AssertState(vec![("a", Some("1 hour"))]))
+            .await?;
+        universe.assert_quit().await;
+
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_retention_policy_execution_calls_dependencies() -> anyhow::Result<()> {
         let mut mock_metastore = MockMetastore::default();
@@ -453,7 +584,14 @@ mod tests {
                 Ok(())
             });
 
-        let retention_policy_executor = RetentionPolicyExecutor::new(Arc::new(mock_metastore));
+        mock_metastore
+            .expect_cluster_settings()
+            .returning(|| Ok(ClusterSettings::default()));
+
+        let retention_policy_executor = RetentionPolicyExecutor::new(
+            Arc::new(mock_metastore),
+            Arc::new(MockSearchService::new()),
+        );
         let universe = Universe::with_accelerated_time();
         let (_mailbox, handle) = universe.spawn_builder().spawn(retention_policy_executor);
 
@@ -469,4 +607,155 @@ mod tests {
 
         Ok(())
     }
+
+    fn make_expiring_indexes() -> Vec<IndexMetadata> {
+        let mut index_config = IndexConfig::for_test("a", "ram://indexes/a");
+        index_config.retention_policy = Some(
+            RetentionPolicy::new(None, None, SCHEDULE_EXPR.to_string())
+                .with_expiration_field("response_date".to_string()),
+        );
+        vec![IndexMetadata::new(index_config)]
+    }
+
+    fn make_count_response(num_hits: u64) -> SearchResponse {
+        SearchResponse {
+            num_hits,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retention_policy_execution_deletes_expired_documents() -> anyhow::Result<()> {
+        let mut mock_metastore = MockMetastore::default();
+        mock_metastore
+            .expect_list_indexes_metadatas()
+            .times(..)
+            .returning(|| Ok(make_expiring_indexes()));
+        mock_metastore.expect_list_splits().never();
+        mock_metastore
+            .expect_last_delete_opstamp()
+            .times(1)
+            .returning(|_| Ok(0));
+        mock_metastore
+            .expect_list_stale_splits()
+            .times(1)
+            .returning(|_, _, _| Ok(Vec::new()));
+        mock_metastore
+            .expect_create_delete_task()
+            .times(1)
+            .returning(|delete_query| {
+                let index_uid = IndexUid::from(delete_query.index_uid.clone());
+                assert_eq!(index_uid.index_id(), "a");
+                assert!(delete_query.query_ast.contains("response_date"));
+                Ok(DeleteTask {
+                    create_timestamp: 0,
+                    opstamp: 1,
+                    delete_query: Some(delete_query),
+                })
+            });
+
+        mock_metastore
+            .expect_cluster_settings()
+            .returning(|| Ok(ClusterSettings::default()));
+
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .times(1)
+            .returning(|search_request| {
+                assert_eq!(search_request.index_id, "a");
+                assert_eq!(search_request.max_hits, 0);
+                assert!(search_request.query_ast.contains("response_date"));
+                Ok(make_count_response(3))
+            });
+
+        let retention_policy_executor =
+            RetentionPolicyExecutor::new(Arc::new(mock_metastore), Arc::new(mock_search_service));
+        let universe = Universe::with_accelerated_time();
+        let (_mailbox, handle) = universe.spawn_builder().spawn(retention_policy_executor);
+
+        universe.sleep(shift_time_by()).await;
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_execution_passes, 1);
+        assert_eq!(counters.num_expired_splits, 0);
+        assert_eq!(counters.num_expiration_delete_tasks, 1);
+        universe.assert_quit().await;
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_retention_policy_expiration_skips_redundant_delete_tasks() -> anyhow::Result<()> {
+        let mut mock_metastore = MockMetastore::default();
+        mock_metastore
+            .expect_list_indexes_metadatas()
+            .times(..)
+            .returning(|| Ok(make_expiring_indexes()));
+        mock_metastore
+            .expect_cluster_settings()
+            .returning(|| Ok(ClusterSettings::default()));
+
+        // First pass: 3 documents expired and no delete task is pending.
+        // Second pass: the delete task created by the first pass is still being applied.
+        // Third pass: the delete task was applied and no document expired since.
+        let mut last_delete_opstamp_sequence = Sequence::new();
+        for last_delete_opstamp in [0, 1, 1] {
+            mock_metastore
+                .expect_last_delete_opstamp()
+                .times(1)
+                .in_sequence(&mut last_delete_opstamp_sequence)
+                .returning(move |_| Ok(last_delete_opstamp));
+        }
+        let mut stale_splits_sequence = Sequence::new();
+        for num_stale_splits in [0, 1, 0] {
+            mock_metastore
+                .expect_list_stale_splits()
+                .times(1)
+                .in_sequence(&mut stale_splits_sequence)
+                .returning(move |_, _, _| {
+                    let stale_splits = (0..num_stale_splits)
+                        .map(|split_ord| make_split(&format!("split-{split_ord}"), None))
+                        .collect();
+                    Ok(stale_splits)
+                });
+        }
+        mock_metastore
+            .expect_create_delete_task()
+            .times(1)
+            .returning(|delete_query| {
+                Ok(DeleteTask {
+                    create_timestamp: 0,
+                    opstamp: 1,
+                    delete_query: Some(delete_query),
+                })
+            });
+
+        let mut mock_search_service = MockSearchService::new();
+        let mut count_sequence = Sequence::new();
+        for num_expired_docs in [3, 0] {
+            mock_search_service
+                .expect_root_search()
+                .times(1)
+                .in_sequence(&mut count_sequence)
+                .returning(move |_| Ok(make_count_response(num_expired_docs)));
+        }
+
+        let retention_policy_executor =
+            RetentionPolicyExecutor::new(Arc::new(mock_metastore), Arc::new(mock_search_service));
+        let universe = Universe::with_accelerated_time();
+        let (_mailbox, handle) = universe.spawn_builder().spawn(retention_policy_executor);
+
+        for (num_execution_passes, num_expiration_delete_tasks) in [(1, 1), (2, 1), (3, 1)] {
+            universe.sleep(shift_time_by()).await;
+            let counters = handle.process_pending_and_observe().await.state;
+            assert_eq!(counters.num_execution_passes, num_execution_passes);
+            assert_eq!(
+                counters.num_expiration_delete_tasks,
+                num_expiration_delete_tasks
+            );
+        }
+        universe.assert_quit().await;
+
+        Ok(())
+    }
 }
@@ -0,0 +1,407 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use itertools::Itertools;
+use quickwit_actors::{Actor, ActorContext, Handler};
+use quickwit_config::IndexConfig;
+use quickwit_ingest::IngestServiceClient;
+use quickwit_metastore::Metastore;
+use quickwit_proto::IndexUid;
+use quickwit_search::SearchService;
+use serde::Serialize;
+use tracing::{debug, error, info};
+
+use super::retention_policy_executor::compute_deleted_indexes;
+use crate::rollup_execution::run_rollup;
+use crate::JanitorSharding;
+
+const RUN_INTERVAL: Duration = Duration::from_secs(60 * 60); // 1 hours
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct RollupExecutorCounters {
+    /// The number of refresh the config passes.
+    pub num_refresh_passes: usize,
+
+    /// The number of execution passes.
+    pub num_execution_passes: usize,
+
+    /// The number of rolled up documents produced.
+    pub num_rollup_docs: usize,
+}
+
+#[derive(Debug)]
+struct Loop;
+
+#[derive(Debug)]
+struct Execute {
+    index_uid: IndexUid,
+}
+
+/// An actor for scheduling the rollup of the indexes that have a rollup policy configured. Like
+/// the [`RetentionPolicyExecutor`](super::RetentionPolicyExecutor), it keeps these indexes in a
+/// cache that is periodically refreshed.
+pub struct RollupExecutor {
+    metastore: Arc<dyn Metastore>,
+    search_service: Arc<dyn SearchService>,
+    ingest_service: IngestServiceClient,
+    /// A map of index_id to index config of the indexes managed by this executor.
+    index_configs: HashMap<String, IndexConfig>,
+    sharding: JanitorSharding,
+    dry_run: bool,
+    counters: RollupExecutorCounters,
+}
+
+impl RollupExecutor {
+    pub fn new(
+        metastore: Arc<dyn Metastore>,
+        search_service: Arc<dyn SearchService>,
+        ingest_service: IngestServiceClient,
+    ) -> Self {
+        Self {
+            metastore,
+            search_service,
+            ingest_service,
+            index_configs: HashMap::new(),
+            sharding: JanitorSharding::single_node(),
+            dry_run: false,
+            counters: RollupExecutorCounters::default(),
+        }
+    }
+
+    /// Restricts the rollups to the indexes assigned to this janitor node.
+    pub fn with_sharding(mut self, sharding: JanitorSharding) -> Self {
+        self.sharding = sharding;
+        self
+    }
+
+    /// In dry run mode, the rolled up documents are only counted and reported through the
+    /// metrics, they are not ingested into the target indexes.
+    pub fn with_dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Indexes refresh Loop handler logic.
+    /// Should not return an error to prevent the actor from crashing.
+    async fn handle_refresh_loop(&mut self, ctx: &ActorContext<Self>) {
+        debug!("rollup-refresh-indexes-operation");
+        self.counters.num_refresh_passes += 1;
+
+        let index_metadatas = match self.metastore.list_indexes_metadatas().await {
+            Ok(metadatas) => metadatas,
+            Err(error) => {
+                error!(error=?error, "Failed to list indexes from the metastore.");
+                return;
+            }
+        };
+        let deleted_indexes = compute_deleted_indexes(
+            self.index_configs.keys().map(String::as_str),
+            index_metadatas
+                .iter()
+                .map(|index_metadata| index_metadata.index_id()),
+        );
+        if !deleted_indexes.is_empty() {
+            debug!(index_ids=%deleted_indexes.iter().join(", "), "Deleting indexes from cache.");
+            for index_id in deleted_indexes {
+                self.index_configs.remove(&index_id);
+            }
+        }
+
+        for index_metadata in index_metadatas {
+            let index_uid = index_metadata.index_uid.clone();
+            let index_config = index_metadata.into_index_config();
+            let rollup_policy = match &index_config.rollup_policy {
+                Some(policy) if self.sharding.owns_index(&index_uid) => policy,
+                _ => {
+                    self.index_configs.remove(&index_config.index_id);
+                    continue;
+                }
+            };
+
+            if let Some(value) = self.index_configs.get_mut(&index_config.index_id) {
+                // Update the cache index entry in case the rollup policy was updated.
+                *value = index_config;
+                continue;
+            }
+
+            if let Ok(next_interval) = rollup_policy.duration_until_next_evaluation() {
+                let message = Execute { index_uid };
+                info!(index_id=?index_config.index_id, scheduled_in=?next_interval, "rollup-schedule-operation");
+                self.index_configs
+                    .insert(index_config.index_id.clone(), index_config);
+                ctx.schedule_self_msg(next_interval, message).await;
+            } else {
+                error!(index_id=%index_config.index_id, "Couldn't extract the index next schedule time.")
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl Actor for RollupExecutor {
+    type ObservableState = RollupExecutorCounters;
+
+    fn observable_state(&self) -> Self::ObservableState {
+        self.counters.clone()
+    }
+
+    fn name(&self) -> String {
+        "RollupExecutor".to_string()
+    }
+
+    async fn initialize(
+        &mut self,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), quickwit_actors::ActorExitStatus> {
+        self.handle(Loop, ctx).await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Handler<Loop> for RollupExecutor {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _: Loop,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), quickwit_actors::ActorExitStatus> {
+        self.handle_refresh_loop(ctx).await;
+        ctx.schedule_self_msg(RUN_INTERVAL, Loop).await;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Handler<Execute> for RollupExecutor {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        message: Execute,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), quickwit_actors::ActorExitStatus> {
+        if !self.sharding.owns_index(&message.index_uid) {
+            debug!(index_id=%message.index_uid.index_id(), "The index was assigned to another janitor node.");
+            self.index_configs.remove(message.index_uid.index_id());
+            return Ok(());
+        }
+        info!(index_id=%message.index_uid.index_id(), "rollup-execute-operation");
+        self.counters.num_execution_passes += 1;
+
+        let index_config = match self.index_configs.get(message.index_uid.index_id()) {
+            Some(config) => config,
+            None => {
+                debug!(index_id=%message.index_uid.index_id(), "The index might have been deleted.");
+                return Ok(());
+            }
+        };
+        let rollup_policy = index_config
+            .rollup_policy
+            .as_ref()
+            .expect("Expected index to have rollup policy configured.");
+        let timestamp_field = index_config
+            .doc_mapping
+            .timestamp_field
+            .as_ref()
+            .expect("Expected index with rollup policy to have a timestamp field.");
+
+        let execution_result = run_rollup(
+            message.index_uid.clone(),
+            timestamp_field,
+            rollup_policy,
+            self.metastore.clone(),
+            self.search_service.clone(),
+            &mut self.ingest_service,
+            self.dry_run,
+            ctx,
+        )
+        .await;
+        match execution_result {
+            Ok(num_rollup_docs) => self.counters.num_rollup_docs += num_rollup_docs,
+            Err(error) => {
+                error!(index_id=%message.index_uid.index_id(), error=?error, "Failed to roll up the index.")
+            }
+        }
+
+        if let Ok(next_interval) = rollup_policy.duration_until_next_evaluation() {
+            info!(index_id=?index_config.index_id, scheduled_in=?next_interval, "rollup-schedule-operation");
+            ctx.schedule_self_msg(next_interval, message).await;
+        } else {
+            // Since we have failed to schedule next execution for this index,
+            // we remove it from the cache for it to be retried next time it gets
+            // added back by the RollupExecutor cache refresh loop.
+            self.index_configs.remove(message.index_uid.index_id());
+            error!(index_id=%message.index_uid.index_id(), "Couldn't extract the index next schedule interval.");
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_actors::Universe;
+    use quickwit_config::RollupPolicy;
+    use quickwit_ingest::IngestResponse;
+    use quickwit_metastore::{IndexMetadata, MockMetastore, Split, SplitMetadata, SplitState};
+    use quickwit_proto::SearchResponse;
+    use quickwit_search::MockSearchService;
+    use serde_json::json;
+    use time::OffsetDateTime;
+
+    use super::*;
+
+    const SCHEDULE_EXPR: &str = "hourly";
+
+    fn make_index_metadata(index_id: &str, with_rollup_policy: bool) -> IndexMetadata {
+        let mut index_config =
+            IndexConfig::for_test(index_id, &format!("ram://indexes/{index_id}"));
+        if with_rollup_policy {
+            index_config.rollup_policy = Some(RollupPolicy::new(
+                format!("{index_id}-rollup"),
+                "1 hour".to_string(),
+                "1 minute".to_string(),
+                vec!["owner".to_string()],
+                SCHEDULE_EXPR.to_string(),
+            ));
+        }
+        IndexMetadata::new(index_config)
+    }
+
+    // Uses the rollup policy scheduler to calculate
+    // how much time to advance for the execution to take place.
+    fn shift_time_by() -> Duration {
+        let rollup_policy = RollupPolicy::new(
+            "target".to_string(),
+            "1 hour".to_string(),
+            "1 minute".to_string(),
+            Vec::new(),
+            SCHEDULE_EXPR.to_string(),
+        );
+        rollup_policy.duration_until_next_evaluation().unwrap() + Duration::from_secs(1)
+    }
+
+    #[tokio::test]
+    async fn test_rollup_execution_calls_dependencies() -> anyhow::Result<()> {
+        let now_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+        let split_start_timestamp = now_timestamp - 3 * 3600;
+        let first_bucket_timestamp = split_start_timestamp - split_start_timestamp % 60;
+
+        let mut mock_metastore = MockMetastore::default();
+        mock_metastore
+            .expect_list_indexes_metadatas()
+            .times(..)
+            .returning(|| {
+                Ok(vec![
+                    make_index_metadata("a", true),
+                    make_index_metadata("b", false),
+                ])
+            });
+        mock_metastore
+            .expect_index_metadata()
+            .times(1)
+            .returning(|index_id| {
+                assert_eq!(index_id, "a-rollup");
+                Ok(make_index_metadata(index_id, false))
+            });
+        mock_metastore
+            .expect_list_splits()
+            .times(2)
+            .returning(move |query| {
+                assert_eq!(query.split_states, &[SplitState::Published]);
+                let splits = match query.index_uid.index_id() {
+                    "a" => vec![Split {
+                        split_metadata: SplitMetadata {
+                            split_id: "split-1".to_string(),
+                            time_range: Some(split_start_timestamp..=split_start_timestamp + 3600),
+                            ..Default::default()
+                        },
+                        split_state: SplitState::Published,
+                        update_timestamp: 0,
+                        publish_timestamp: Some(100),
+                    }],
+                    "a-rollup" => Vec::new(),
+                    unknown => panic!("Unknown index: `{unknown}`."),
+                };
+                Ok(splits)
+            });
+
+        // The two hours between the split and the min age are rolled up with two searches, or three
+        // when a minute boundary is crossed in the meantime.
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .times(2..=3)
+            .returning(move |search_request| {
+                assert_eq!(search_request.index_id, "a");
+                let buckets = if search_request.start_timestamp == Some(first_bucket_timestamp) {
+                    json!([{
+                        "key": (first_bucket_timestamp * 1_000) as f64,
+                        "doc_count": 3,
+                        "group_0": {"buckets": [{"key": "team-a", "doc_count": 3}]},
+                    }])
+                } else {
+                    json!([])
+                };
+                Ok(SearchResponse {
+                    aggregation: Some(json!({ "rollup": { "buckets": buckets } }).to_string()),
+                    ..Default::default()
+                })
+            });
+
+        let mut mock_ingest_service = IngestServiceClient::mock();
+        mock_ingest_service
+            .expect_ingest()
+            .times(1)
+            .returning(|ingest_request| {
+                assert_eq!(ingest_request.doc_batches.len(), 1);
+                assert_eq!(ingest_request.doc_batches[0].index_id, "a-rollup");
+                assert_eq!(ingest_request.doc_batches[0].num_docs(), 1);
+                Ok(IngestResponse {
+                    num_docs_for_processing: 1,
+                })
+            });
+
+        let rollup_executor = RollupExecutor::new(
+            Arc::new(mock_metastore),
+            Arc::new(mock_search_service),
+            IngestServiceClient::new(mock_ingest_service),
+        );
+        let universe = Universe::with_accelerated_time();
+        let (_mailbox, handle) = universe.spawn_builder().spawn(rollup_executor);
+
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_execution_passes, 0);
+        assert_eq!(counters.num_rollup_docs, 0);
+
+        universe.sleep(shift_time_by()).await;
+        let counters = handle.process_pending_and_observe().await.state;
+        assert_eq!(counters.num_execution_passes, 1);
+        assert_eq!(counters.num_rollup_docs, 1);
+        universe.assert_quit().await;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,169 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use quickwit_proto::IndexUid;
+use serde::Serialize;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// The scheduling status of a delete task pipeline.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeletePipelineStatus {
+    /// The pipeline has no delete operations to apply.
+    #[default]
+    Idle,
+    /// The pipeline has stale splits and waits for its turn to apply delete operations.
+    Waiting,
+    /// The pipeline is applying delete operations.
+    Running,
+}
+
+/// Progress of the delete task pipeline of an index.
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct DeletePipelineProgress {
+    pub status: DeletePipelineStatus,
+    /// The opstamp of the last delete task known to the pipeline.
+    pub last_delete_opstamp: u64,
+    /// The number of stale splits found during the last planning round.
+    pub num_stale_splits: usize,
+    /// The number of delete operations planned and not yet published.
+    pub num_ongoing_delete_operations: usize,
+    /// The number of delete operations planned since the pipeline started.
+    pub num_planned_delete_operations: usize,
+    /// The number of turns the pipeline was granted by the scheduler.
+    pub num_turns: usize,
+}
+
+/// Bounds the number of delete task pipelines applying delete operations at the same time.
+///
+/// Delete task pipelines compete for IO: a pipeline must acquire a turn before planning delete
+/// operations and holds it until they are published. Turns are granted in the order they were
+/// requested, so the indexes with pending delete operations are served in a round-robin fashion.
+#[derive(Clone)]
+pub struct DeleteTaskScheduler {
+    turns: Arc<Semaphore>,
+    progress: Arc<Mutex<BTreeMap<IndexUid, DeletePipelineProgress>>>,
+}
+
+impl Default for DeleteTaskScheduler {
+    fn default() -> Self {
+        Self::new(Semaphore::MAX_PERMITS)
+    }
+}
+
+impl DeleteTaskScheduler {
+    pub fn new(max_concurrent_pipelines: usize) -> Self {
+        Self {
+            turns: Arc::new(Semaphore::new(max_concurrent_pipelines)),
+            progress: Arc::default(),
+        }
+    }
+
+    /// Waits for the turn of the index. The turn ends when the returned permit is dropped.
+    pub(crate) async fn acquire_turn(&self, index_uid: &IndexUid) -> OwnedSemaphorePermit {
+        self.update_progress(index_uid, |progress| {
+            progress.status = DeletePipelineStatus::Waiting
+        });
+        let permit = self
+            .turns
+            .clone()
+            .acquire_owned()
+            .await
+            .expect("The semaphore should not be closed.");
+        self.update_progress(index_uid, |progress| {
+            progress.status = DeletePipelineStatus::Running;
+            progress.num_turns += 1;
+        });
+        permit
+    }
+
+    pub(crate) fn update_progress<F>(&self, index_uid: &IndexUid, update: F)
+    where F: FnOnce(&mut DeletePipelineProgress) {
+        let mut progress_guard = self
+            .progress
+            .lock()
+            .expect("Delete task scheduler lock is poisoned.");
+        let progress = progress_guard.entry(index_uid.clone()).or_default();
+        update(progress);
+    }
+
+    pub(crate) fn remove_index(&self, index_uid: &IndexUid) {
+        self.progress
+            .lock()
+            .expect("Delete task scheduler lock is poisoned.")
+            .remove(index_uid);
+    }
+
+    /// Returns the progress of the delete task pipelines keyed by index UID.
+    pub fn progress(&self) -> BTreeMap<String, DeletePipelineProgress> {
+        self.progress
+            .lock()
+            .expect("Delete task scheduler lock is poisoned.")
+            .iter()
+            .map(|(index_uid, progress)| (index_uid.to_string(), progress.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_delete_task_scheduler_grants_turns_in_order() {
+        let scheduler = DeleteTaskScheduler::new(1);
+        let index_uid_1 = IndexUid::new("test-index-1");
+        let index_uid_2 = IndexUid::new("test-index-2");
+
+        let permit = scheduler.acquire_turn(&index_uid_1).await;
+
+        let scheduler_clone = scheduler.clone();
+        let index_uid_2_clone = index_uid_2.clone();
+        let waiting_task =
+            tokio::spawn(async move { scheduler_clone.acquire_turn(&index_uid_2_clone).await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        let progress = scheduler.progress();
+        assert_eq!(
+            progress[&index_uid_1.to_string()].status,
+            DeletePipelineStatus::Running
+        );
+        assert_eq!(
+            progress[&index_uid_2.to_string()].status,
+            DeletePipelineStatus::Waiting
+        );
+        drop(permit);
+
+        let _permit = waiting_task.await.unwrap();
+        let progress = scheduler.progress();
+        assert_eq!(
+            progress[&index_uid_2.to_string()].status,
+            DeletePipelineStatus::Running
+        );
+        assert_eq!(progress[&index_uid_2.to_string()].num_turns, 1);
+
+        scheduler.remove_index(&index_uid_1);
+        assert!(!scheduler.progress().contains_key(&index_uid_1.to_string()));
+    }
+}
@@ -22,19 +22,23 @@ use std::path::Path;
 use std::sync::Arc;
 use std::time::Duration;
 
-use futures::Future;
+use futures::{Future, TryStreamExt};
 use quickwit_actors::ActorContext;
 use quickwit_common::{FileEntry, PrettySample};
-use quickwit_metastore::{ListSplitsQuery, Metastore, MetastoreError, SplitMetadata, SplitState};
+use quickwit_metastore::{
+    stream_splits, ListSplitsQuery, Metastore, MetastoreError, SplitMetadata, SplitState,
+};
 use quickwit_proto::IndexUid;
 use quickwit_storage::Storage;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use time::OffsetDateTime;
 use tracing::{error, instrument};
 
 use crate::actors::GarbageCollector;
 
-/// The maximum number of splits that should be deleted in one go by the GC.
+/// The maximum number of splits that should be listed, marked for deletion, or deleted in one go
+/// by the GC.
 const DELETE_SPLITS_BATCH_SIZE: usize = 1000;
 
 /// SplitDeletionError denotes error that can happen when deleting split
@@ -63,6 +67,7 @@ where
 }
 
 /// Information on what splits have and have not been cleaned up by the GC.
+#[derive(Debug, Serialize, Deserialize, utoipa::ToSchema)]
 pub struct SplitRemovalInfo {
     /// The set of splits that have been removed.
     pub removed_split_entries: Vec<FileEntry>,
@@ -94,32 +99,34 @@ pub async fn run_garbage_collect(
     let grace_period_timestamp =
         OffsetDateTime::now_utc().unix_timestamp() - staged_grace_period.as_secs() as i64;
 
-    let query = ListSplitsQuery::for_index(index_uid.clone())
+    let staged_query = ListSplitsQuery::for_index(index_uid.clone())
         .with_split_state(SplitState::Staged)
         .with_update_timestamp_lte(grace_period_timestamp);
 
-    let deletable_staged_splits: Vec<SplitMetadata> =
-        protect_future(ctx_opt, metastore.list_splits(query))
-            .await?
-            .into_iter()
-            .map(|meta| meta.split_metadata)
-            .collect();
+    // We delete splits marked for deletion that have an update timestamp anterior
+    // to `now - deletion_grace_period`.
+    let updated_before_timestamp =
+        OffsetDateTime::now_utc().unix_timestamp() - deletion_grace_period.as_secs() as i64;
 
     if dry_run {
-        let query = ListSplitsQuery::for_index(index_uid.clone())
-            .with_split_state(SplitState::MarkedForDeletion);
+        let marked_for_deletion_query = ListSplitsQuery::for_index(index_uid.clone())
+            .with_split_state(SplitState::MarkedForDeletion)
+            .with_update_timestamp_lte(updated_before_timestamp);
 
-        let mut splits_marked_for_deletion = protect_future(ctx_opt, metastore.list_splits(query))
-            .await?
-            .into_iter()
-            .map(|meta| meta.split_metadata)
-            .collect::<Vec<_>>();
-        splits_marked_for_deletion.extend(deletable_staged_splits);
+        let mut candidate_entries: Vec<FileEntry> = Vec::new();
 
-        let candidate_entries: Vec<FileEntry> = splits_marked_for_deletion
-            .iter()
-            .map(FileEntry::from)
-            .collect();
+        for query in [marked_for_deletion_query, staged_query] {
+            let mut splits_stream =
+                stream_splits(metastore.clone(), query, DELETE_SPLITS_BATCH_SIZE);
+
+            while let Some(splits) = protect_future(ctx_opt, splits_stream.try_next()).await? {
+                candidate_entries.extend(
+                    splits
+                        .iter()
+                        .map(|split| FileEntry::from(&split.split_metadata)),
+                );
+            }
+        }
         return Ok(SplitRemovalInfo {
             removed_split_entries: candidate_entries,
             failed_split_ids: Vec::new(),
@@ -127,22 +134,7 @@ pub async fn run_garbage_collect(
     }
 
     // Schedule all eligible staged splits for delete
-    let split_ids: Vec<&str> = deletable_staged_splits
-        .iter()
-        .map(|meta| meta.split_id())
-        .collect();
-    if !split_ids.is_empty() {
-        protect_future(
-            ctx_opt,
-            metastore.mark_splits_for_deletion(index_uid.clone(), &split_ids),
-        )
-        .await?;
-    }
-
-    // We delete splits marked for deletion that have an update timestamp anterior
-    // to `now - deletion_grace_period`.
-    let updated_before_timestamp =
-        OffsetDateTime::now_utc().unix_timestamp() - deletion_grace_period.as_secs() as i64;
+    mark_splits_for_deletion(index_uid.clone(), staged_query, metastore.clone(), ctx_opt).await?;
 
     let deleted_files = delete_splits_marked_for_deletion(
         index_uid,
@@ -171,16 +163,18 @@ async fn delete_splits_marked_for_deletion(
 ) -> SplitRemovalInfo {
     let mut failed_split_ids = Vec::new();
     let mut removed_split_files = Vec::new();
-    loop {
-        let query = ListSplitsQuery::for_index(index_uid.clone())
-            .with_split_state(SplitState::MarkedForDeletion)
-            .with_update_timestamp_lte(updated_before_timestamp)
-            .with_limit(DELETE_SPLITS_BATCH_SIZE);
 
-        let list_splits_result = protect_future(ctx_opt, metastore.list_splits(query)).await;
+    let query = ListSplitsQuery::for_index(index_uid.clone())
+        .with_split_state(SplitState::MarkedForDeletion)
+        .with_update_timestamp_lte(updated_before_timestamp);
+    let mut splits_stream = stream_splits(metastore.clone(), query, DELETE_SPLITS_BATCH_SIZE);
+
+    loop {
+        let list_splits_result = protect_future(ctx_opt, splits_stream.try_next()).await;
 
         let splits_to_delete = match list_splits_result {
-            Ok(splits) => splits,
+            Ok(Some(splits)) => splits,
+            Ok(None) => break,
             Err(error) => {
                 error!(error = ?error, "Failed to fetch deletable splits.");
                 break;
@@ -192,8 +186,7 @@ async fn delete_splits_marked_for_deletion(
             .map(|split| split.split_metadata)
             .collect::<Vec<_>>();
 
-        let num_splits_to_delete = splits_to_delete.len();
-        if num_splits_to_delete == 0 {
+        if splits_to_delete.is_empty() {
             break;
         }
 
@@ -223,10 +216,6 @@ async fn delete_splits_marked_for_deletion(
                 break;
             }
         }
-
-        if num_splits_to_delete < DELETE_SPLITS_BATCH_SIZE {
-            break;
-        }
     }
 
     SplitRemovalInfo {
@@ -235,6 +224,29 @@ async fn delete_splits_marked_for_deletion(
     }
 }
 
+/// Marks the splits matching `query` for deletion, page by page.
+async fn mark_splits_for_deletion(
+    index_uid: IndexUid,
+    query: ListSplitsQuery,
+    metastore: Arc<dyn Metastore>,
+    ctx_opt: Option<&ActorContext<GarbageCollector>>,
+) -> Result<(), MetastoreError> {
+    let mut splits_stream = stream_splits(metastore.clone(), query, DELETE_SPLITS_BATCH_SIZE);
+
+    while let Some(splits) = protect_future(ctx_opt, splits_stream.try_next()).await? {
+        let split_ids: Vec<&str> = splits.iter().map(|split| split.split_id()).collect();
+
+        if !split_ids.is_empty() {
+            protect_future(
+                ctx_opt,
+                metastore.mark_splits_for_deletion(index_uid.clone(), &split_ids),
+            )
+            .await?;
+        }
+    }
+    Ok(())
+}
+
 /// Delete a list of splits from the storage and the metastore.
 /// It should leave the index and the metastore in good state.
 ///
@@ -325,6 +337,48 @@ pub async fn delete_splits_with_files(
     Ok(deleted_file_entries)
 }
 
+/// Permanently deletes a trashed index: all its splits and associated files are removed, then
+/// the index itself is deleted from the metastore.
+///
+/// * `index_uid` - The target trashed index UID.
+/// * `storage - The storage managing the target index.
+/// * `metastore` - The metastore managing the target index.
+/// * `ctx_opt` - A context for reporting progress (only useful within quickwit actor).
+pub(crate) async fn purge_trashed_index(
+    index_uid: IndexUid,
+    storage: Arc<dyn Storage>,
+    metastore: Arc<dyn Metastore>,
+    ctx_opt: Option<&ActorContext<GarbageCollector>>,
+) -> anyhow::Result<Vec<FileEntry>> {
+    // Schedule staged and published splits for deletion.
+    let query = ListSplitsQuery::for_index(index_uid.clone())
+        .with_split_states([SplitState::Staged, SplitState::Published]);
+    mark_splits_for_deletion(index_uid.clone(), query, metastore.clone(), ctx_opt).await?;
+
+    let query = ListSplitsQuery::for_index(index_uid.clone())
+        .with_split_state(SplitState::MarkedForDeletion);
+    let mut splits_stream = stream_splits(metastore.clone(), query, DELETE_SPLITS_BATCH_SIZE);
+    let mut deleted_file_entries = Vec::new();
+
+    while let Some(splits) = protect_future(ctx_opt, splits_stream.try_next()).await? {
+        let splits_to_delete: Vec<SplitMetadata> = splits
+            .into_iter()
+            .map(|split| split.split_metadata)
+            .collect();
+        let file_entries = delete_splits_with_files(
+            index_uid.clone(),
+            storage.clone(),
+            metastore.clone(),
+            splits_to_delete,
+            ctx_opt,
+        )
+        .await?;
+        deleted_file_entries.extend(file_entries);
+    }
+    protect_future(ctx_opt, metastore.delete_index(index_uid)).await?;
+    Ok(deleted_file_entries)
+}
+
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -332,7 +386,7 @@ mod tests {
 
     use quickwit_config::IndexConfig;
     use quickwit_metastore::{
-        metastore_for_test, ListSplitsQuery, MockMetastore, SplitMetadata, SplitState,
+        metastore_for_test, ListSplitsQuery, MockMetastore, SplitMetadata, SplitState, SplitsPage,
     };
     use quickwit_proto::IndexUid;
     use quickwit_storage::storage_for_test;
@@ -468,9 +522,9 @@ mod tests {
         let storage = storage_for_test();
         let mut metastore = MockMetastore::new();
         metastore
-            .expect_list_splits()
+            .expect_list_splits_page()
             .times(2)
-            .returning(|_| Ok(Vec::new()));
+            .returning(|_, _| Ok(SplitsPage::default()));
         run_garbage_collect(
             IndexUid::new("index-test-gc-deletes"),
             storage.clone(),
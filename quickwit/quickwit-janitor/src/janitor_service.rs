@@ -23,12 +23,13 @@ use quickwit_actors::{
 };
 use serde_json::{json, Value as JsonValue};
 
-use crate::actors::{DeleteTaskService, GarbageCollector, RetentionPolicyExecutor};
+use crate::actors::{DeleteTaskService, GarbageCollector, RetentionPolicyExecutor, RollupExecutor};
 
 pub struct JanitorService {
     delete_task_service_handle: ActorHandle<DeleteTaskService>,
     garbage_collector_handle: ActorHandle<GarbageCollector>,
     retention_policy_executor_handle: ActorHandle<RetentionPolicyExecutor>,
+    rollup_executor_handle: ActorHandle<RollupExecutor>,
 }
 
 impl JanitorService {
@@ -36,11 +37,13 @@ impl JanitorService {
         delete_task_service_handle: ActorHandle<DeleteTaskService>,
         garbage_collector_handle: ActorHandle<GarbageCollector>,
         retention_policy_executor_handle: ActorHandle<RetentionPolicyExecutor>,
+        rollup_executor_handle: ActorHandle<RollupExecutor>,
     ) -> Self {
         Self {
             delete_task_service_handle,
             garbage_collector_handle,
             retention_policy_executor_handle,
+            rollup_executor_handle,
         }
     }
 
@@ -49,6 +52,7 @@ impl JanitorService {
             &self.delete_task_service_handle,
             &self.garbage_collector_handle,
             &self.retention_policy_executor_handle,
+            &self.rollup_executor_handle,
         ]
     }
 
@@ -0,0 +1,183 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::{Arc, RwLock};
+
+use futures::StreamExt;
+use quickwit_cluster::{Cluster, ClusterMember};
+use quickwit_common::rendezvous_hasher::sort_by_rendez_vous_hash;
+use quickwit_config::service::QuickwitService;
+use quickwit_proto::IndexUid;
+use tracing::info;
+
+/// Splits the indexes between the janitor nodes of the cluster so that the garbage collection,
+/// retention policy, and delete task pipelines of an index run on a single node.
+///
+/// Each index is assigned to a janitor node with rendezvous hashing on the index UID: when a
+/// janitor node joins or leaves the cluster, only the indexes it owns, or is about to own, move.
+/// When the sharding is created with [`JanitorSharding::from_cluster`], the set of janitor nodes
+/// is dynamically updated with cluster members changes.
+#[derive(Clone)]
+pub struct JanitorSharding {
+    self_node_id: String,
+    janitor_node_ids: Arc<RwLock<Vec<String>>>,
+}
+
+impl JanitorSharding {
+    /// Creates a [`JanitorSharding`] for a janitor running alone, which owns every index.
+    pub fn single_node() -> Self {
+        Self::for_node_ids("janitor".to_string(), Vec::new())
+    }
+
+    /// Creates a [`JanitorSharding`] for the node `self_node_id` from a static list of janitor
+    /// node IDs.
+    pub fn for_node_ids(self_node_id: String, janitor_node_ids: Vec<String>) -> Self {
+        let janitor_node_ids = with_self_node_id(&self_node_id, janitor_node_ids);
+        Self {
+            self_node_id,
+            janitor_node_ids: Arc::new(RwLock::new(janitor_node_ids)),
+        }
+    }
+
+    /// Creates a [`JanitorSharding`] that keeps track of the ready janitor nodes of the cluster.
+    pub async fn from_cluster(cluster: &Cluster) -> Self {
+        let self_node_id = cluster.self_node_id().to_string();
+        let sharding = Self::for_node_ids(
+            self_node_id.clone(),
+            janitor_node_ids(&cluster.ready_members().await),
+        );
+        let sharding_clone = sharding.clone();
+        let mut ready_members_watcher = cluster.ready_members_watcher().await;
+
+        tokio::spawn(async move {
+            while let Some(ready_members) = ready_members_watcher.next().await {
+                let janitor_node_ids =
+                    with_self_node_id(&self_node_id, janitor_node_ids(&ready_members));
+                let mut current_janitor_node_ids = sharding_clone
+                    .janitor_node_ids
+                    .write()
+                    .expect("Janitor sharding lock is poisoned.");
+
+                if *current_janitor_node_ids != janitor_node_ids {
+                    info!(
+                        janitor_node_ids=?janitor_node_ids,
+                        "Rebalancing indexes between janitor nodes."
+                    );
+                    *current_janitor_node_ids = janitor_node_ids;
+                }
+            }
+        });
+        sharding
+    }
+
+    /// Returns whether the index is assigned to this janitor node.
+    pub fn owns_index(&self, index_uid: &IndexUid) -> bool {
+        let mut janitor_node_ids = self
+            .janitor_node_ids
+            .read()
+            .expect("Janitor sharding lock is poisoned.")
+            .clone();
+        if janitor_node_ids.len() <= 1 {
+            return true;
+        }
+        sort_by_rendez_vous_hash(&mut janitor_node_ids, index_uid);
+        janitor_node_ids[0] == self.self_node_id
+    }
+}
+
+fn janitor_node_ids(members: &[ClusterMember]) -> Vec<String> {
+    members
+        .iter()
+        .filter(|member| member.enabled_services.contains(&QuickwitService::Janitor))
+        .map(|member| member.node_id.clone())
+        .collect()
+}
+
+/// The node running the janitor always takes part in the sharding, even before it is ready, so
+/// that the indexes are never left without owner.
+fn with_self_node_id(self_node_id: &str, mut janitor_node_ids: Vec<String>) -> Vec<String> {
+    if !janitor_node_ids
+        .iter()
+        .any(|node_id| node_id == self_node_id)
+    {
+        janitor_node_ids.push(self_node_id.to_string());
+    }
+    janitor_node_ids.sort();
+    janitor_node_ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_janitor_sharding_single_node_owns_all_indexes() {
+        let sharding = JanitorSharding::single_node();
+        assert!(sharding.owns_index(&IndexUid::new("test-index-1")));
+        assert!(sharding.owns_index(&IndexUid::new("test-index-2")));
+    }
+
+    #[test]
+    fn test_janitor_sharding_assigns_each_index_to_one_node() {
+        let node_ids = vec![
+            "node-1".to_string(),
+            "node-2".to_string(),
+            "node-3".to_string(),
+        ];
+        let shardings: Vec<JanitorSharding> = node_ids
+            .iter()
+            .map(|node_id| JanitorSharding::for_node_ids(node_id.clone(), node_ids.clone()))
+            .collect();
+
+        let mut num_owned_indexes = vec![0; shardings.len()];
+
+        for i in 0..100 {
+            let index_uid = IndexUid::new(format!("test-index-{i}"));
+            let owners: Vec<usize> = shardings
+                .iter()
+                .enumerate()
+                .filter(|(_, sharding)| sharding.owns_index(&index_uid))
+                .map(|(node_ord, _)| node_ord)
+                .collect();
+            assert_eq!(owners.len(), 1);
+            num_owned_indexes[owners[0]] += 1;
+        }
+        assert!(num_owned_indexes
+            .iter()
+            .all(|num_owned_indexes| *num_owned_indexes > 0));
+    }
+
+    #[test]
+    fn test_janitor_sharding_only_moves_indexes_of_leaving_node() {
+        let sharding_before = JanitorSharding::for_node_ids(
+            "node-1".to_string(),
+            vec!["node-2".to_string(), "node-3".to_string()],
+        );
+        let sharding_after =
+            JanitorSharding::for_node_ids("node-1".to_string(), vec!["node-2".to_string()]);
+
+        for i in 0..100 {
+            let index_uid = IndexUid::new(format!("test-index-{i}"));
+            // Node 1 keeps all its indexes when node 3 leaves.
+            if sharding_before.owns_index(&index_uid) {
+                assert!(sharding_after.owns_index(&index_uid));
+            }
+        }
+    }
+}
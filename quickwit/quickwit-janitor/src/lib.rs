@@ -20,62 +20,113 @@
 #![deny(clippy::disallowed_methods)]
 
 use std::sync::Arc;
+use std::time::Duration;
 
 use quickwit_actors::{Mailbox, Universe};
+use quickwit_cluster::Cluster;
 use quickwit_common::FileEntry;
 use quickwit_config::QuickwitConfig;
+use quickwit_ingest::IngestServiceClient;
 use quickwit_metastore::Metastore;
-use quickwit_search::SearchJobPlacer;
+use quickwit_search::{SearchJobPlacer, SearchService};
 use quickwit_storage::StorageUriResolver;
-use tracing::info;
+use tracing::{info, warn};
 
 pub mod actors;
+mod delete_task_scheduler;
 pub mod error;
 mod garbage_collection;
 mod janitor_service;
+mod janitor_sharding;
 mod metrics;
+mod orphan_files_reconciliation;
 mod retention_policy_execution;
+mod rollup_execution;
 
 pub use janitor_service::JanitorService;
+pub use janitor_sharding::JanitorSharding;
 
+pub use self::delete_task_scheduler::{
+    DeletePipelineProgress, DeletePipelineStatus, DeleteTaskScheduler,
+};
 pub use self::garbage_collection::{
     delete_splits_with_files, run_garbage_collect, SplitDeletionError, SplitRemovalInfo,
 };
-use crate::actors::{DeleteTaskService, GarbageCollector, RetentionPolicyExecutor};
+pub use self::orphan_files_reconciliation::{
+    run_orphan_files_reconciliation, OrphanFilesRemovalInfo,
+};
+use crate::actors::{DeleteTaskService, GarbageCollector, RetentionPolicyExecutor, RollupExecutor};
 
 #[derive(utoipa::OpenApi)]
-#[openapi(components(schemas(FileEntry)))]
+#[openapi(components(schemas(FileEntry, SplitRemovalInfo)))]
 /// Schema used for the OpenAPI generation which are apart of this crate.
 pub struct JanitorApiSchemas;
 
 pub async fn start_janitor_service(
     universe: &Universe,
     config: &QuickwitConfig,
+    cluster: &Cluster,
     metastore: Arc<dyn Metastore>,
     search_job_placer: SearchJobPlacer,
     storage_uri_resolver: StorageUriResolver,
+    search_service: Arc<dyn SearchService>,
+    ingest_service: IngestServiceClient,
 ) -> anyhow::Result<Mailbox<JanitorService>> {
     info!("Starting janitor service.");
-    let garbage_collector = GarbageCollector::new(metastore.clone(), storage_uri_resolver.clone());
+    let dry_run = config.janitor_config.dry_run;
+
+    if dry_run {
+        warn!(
+            "The janitor runs in dry run mode: garbage collection, retention policies, and delete \
+             tasks only report what they would remove, and rollups do not ingest documents."
+        );
+    }
+    let janitor_sharding = JanitorSharding::from_cluster(cluster).await;
+
+    let garbage_collector = GarbageCollector::new(metastore.clone(), storage_uri_resolver.clone())
+        .with_index_trash_retention_period(
+            config
+                .janitor_config
+                .index_trash_retention_period_opt()
+                .unwrap_or(Duration::ZERO),
+        )
+        .with_default_gc_settings(
+            config.janitor_config.gc_interval(),
+            config.janitor_config.gc_staged_split_grace_period(),
+        )
+        .with_sharding(janitor_sharding.clone())
+        .with_dry_run(dry_run);
     let (_, garbage_collector_handle) = universe.spawn_builder().spawn(garbage_collector);
 
-    let retention_policy_executor = RetentionPolicyExecutor::new(metastore.clone());
+    let retention_policy_executor =
+        RetentionPolicyExecutor::new(metastore.clone(), search_service.clone())
+            .with_sharding(janitor_sharding.clone())
+            .with_dry_run(dry_run);
     let (_, retention_policy_executor_handle) =
         universe.spawn_builder().spawn(retention_policy_executor);
 
+    let rollup_executor = RollupExecutor::new(metastore.clone(), search_service, ingest_service)
+        .with_sharding(janitor_sharding.clone())
+        .with_dry_run(dry_run);
+    let (_, rollup_executor_handle) = universe.spawn_builder().spawn(rollup_executor);
+
     let delete_task_service = DeleteTaskService::new(
         metastore,
         search_job_placer,
         storage_uri_resolver,
         config.data_dir_path.clone(),
         config.indexer_config.max_concurrent_split_uploads,
-    );
+    )
+    .with_sharding(janitor_sharding)
+    .with_max_concurrent_pipelines(config.janitor_config.max_concurrent_delete_pipelines.get())
+    .with_dry_run(dry_run);
     let (_, delete_task_service_handle) = universe.spawn_builder().spawn(delete_task_service);
 
     let janitor_service = JanitorService::new(
         delete_task_service_handle,
         garbage_collector_handle,
         retention_policy_executor_handle,
+        rollup_executor_handle,
     );
     let (janitor_service_mailbox, _janitor_service_handle) =
         universe.spawn_builder().spawn(janitor_service);
@@ -18,10 +18,18 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use once_cell::sync::Lazy;
-use quickwit_common::metrics::{new_gauge_vec, IntGaugeVec};
+use quickwit_common::metrics::{new_counter_vec, new_gauge_vec, IntCounterVec, IntGaugeVec};
 
 pub struct JanitorMetrics {
     pub ongoing_num_delete_operations_total: IntGaugeVec<1>,
+    pub retention_policy_expired_splits_total: IntCounterVec<2>,
+    pub retention_policy_expired_bytes_total: IntCounterVec<2>,
+    pub retention_policy_expiration_delete_tasks_total: IntCounterVec<1>,
+    pub orphan_files_total: IntCounterVec<2>,
+    pub garbage_collected_splits_total: IntCounterVec<2>,
+    pub garbage_collected_bytes_total: IntCounterVec<2>,
+    pub planned_delete_operations_total: IntCounterVec<2>,
+    pub rollup_docs_total: IntCounterVec<2>,
 }
 
 impl Default for JanitorMetrics {
@@ -33,6 +41,56 @@ impl Default for JanitorMetrics {
                 "quickwit_janitor",
                 ["index"],
             ),
+            retention_policy_expired_splits_total: new_counter_vec(
+                "retention_policy_expired_splits_total",
+                "Number of splits expired by the retention policy (per index and dry run).",
+                "quickwit_janitor",
+                ["index", "dry_run"],
+            ),
+            retention_policy_expired_bytes_total: new_counter_vec(
+                "retention_policy_expired_bytes_total",
+                "Number of bytes expired by the retention policy (per index and dry run).",
+                "quickwit_janitor",
+                ["index", "dry_run"],
+            ),
+            retention_policy_expiration_delete_tasks_total: new_counter_vec(
+                "retention_policy_expiration_delete_tasks_total",
+                "Number of delete tasks created to delete the expired documents (per index).",
+                "quickwit_janitor",
+                ["index"],
+            ),
+            orphan_files_total: new_counter_vec(
+                "orphan_files_total",
+                "Number of orphan split files found by the reconciliation (per index and dry run).",
+                "quickwit_janitor",
+                ["index", "dry_run"],
+            ),
+            garbage_collected_splits_total: new_counter_vec(
+                "garbage_collected_splits_total",
+                "Number of splits deleted by the garbage collector (per index and dry run).",
+                "quickwit_janitor",
+                ["index", "dry_run"],
+            ),
+            garbage_collected_bytes_total: new_counter_vec(
+                "garbage_collected_bytes_total",
+                "Number of bytes deleted by the garbage collector (per index and dry run).",
+                "quickwit_janitor",
+                ["index", "dry_run"],
+            ),
+            planned_delete_operations_total: new_counter_vec(
+                "planned_delete_operations_total",
+                "Number of delete operations planned by the delete task pipelines (per index and \
+                 dry run).",
+                "quickwit_janitor",
+                ["index", "dry_run"],
+            ),
+            rollup_docs_total: new_counter_vec(
+                "rollup_docs_total",
+                "Number of rolled up documents produced by the rollup policy (per index and dry \
+                 run).",
+                "quickwit_janitor",
+                ["index", "dry_run"],
+            ),
         }
     }
 }
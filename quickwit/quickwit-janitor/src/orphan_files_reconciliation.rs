@@ -0,0 +1,258 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use quickwit_common::FileEntry;
+use quickwit_metastore::Metastore;
+use quickwit_proto::IndexUid;
+use quickwit_storage::{FileMetadata, Storage};
+use time::OffsetDateTime;
+use tracing::{error, info};
+
+use crate::metrics::JANITOR_METRICS;
+
+/// Information on the orphan files found, and possibly removed, by a reconciliation run.
+#[derive(Debug, Default)]
+pub struct OrphanFilesRemovalInfo {
+    /// The set of orphan files that have been removed, or that would have been removed in dry run
+    /// mode.
+    pub removed_file_entries: Vec<FileEntry>,
+    /// The set of orphan files that were attempted to be removed, but were unsuccessful.
+    pub failed_file_paths: Vec<PathBuf>,
+}
+
+/// Lists the split files stored under the index URI and compares them to the splits recorded in
+/// the metastore. Split files without a matching split in the metastore, whatever its state, are
+/// orphans: they are typically left behind by crashed uploads or interrupted deletions.
+///
+/// * `index_uid` - The target index UID.
+/// * `storage - The storage managing the target index.
+/// * `metastore` - The metastore managing the target index.
+/// * `grace_period` - Threshold period after which an orphan file can be safely deleted. Files
+///   whose last modification date is unknown are only considered once the grace period is zero.
+/// * `dry_run` - Should this only return a list of orphan files without performing deletion.
+pub async fn run_orphan_files_reconciliation(
+    index_uid: IndexUid,
+    storage: Arc<dyn Storage>,
+    metastore: Arc<dyn Metastore>,
+    grace_period: Duration,
+    dry_run: bool,
+) -> anyhow::Result<OrphanFilesRemovalInfo> {
+    // The files are listed before the splits so that a split staged in between is never
+    // mistaken for an orphan.
+    let files_metadata = storage.list_files_metadata().await?;
+    let split_ids: HashSet<String> = metastore
+        .list_all_splits(index_uid.clone())
+        .await?
+        .into_iter()
+        .map(|split| split.split_metadata.split_id)
+        .collect();
+
+    let now_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+    let grace_period_timestamp = now_timestamp - grace_period.as_secs() as i64;
+
+    let orphan_files: Vec<FileMetadata> = files_metadata
+        .into_iter()
+        .filter(|file_metadata| {
+            let last_modified_timestamp = file_metadata
+                .last_modified_timestamp_opt
+                .unwrap_or(now_timestamp);
+            last_modified_timestamp <= grace_period_timestamp
+        })
+        .filter(
+            |file_metadata| match split_id_from_path(&file_metadata.path) {
+                Some(split_id) => !split_ids.contains(split_id),
+                None => false,
+            },
+        )
+        .collect();
+
+    let dry_run_label = if dry_run { "true" } else { "false" };
+
+    if dry_run || orphan_files.is_empty() {
+        JANITOR_METRICS
+            .orphan_files_total
+            .with_label_values([index_uid.index_id(), dry_run_label])
+            .inc_by(orphan_files.len() as u64);
+        return Ok(OrphanFilesRemovalInfo {
+            removed_file_entries: orphan_files.iter().map(file_entry).collect(),
+            failed_file_paths: Vec::new(),
+        });
+    }
+    info!(
+        index_id = %index_uid.index_id(),
+        num_orphan_files = orphan_files.len(),
+        "Deleting orphan split files."
+    );
+    let paths: Vec<&Path> = orphan_files
+        .iter()
+        .map(|file_metadata| file_metadata.path.as_path())
+        .collect();
+
+    let removal_info = match storage.bulk_delete(&paths).await {
+        Ok(()) => OrphanFilesRemovalInfo {
+            removed_file_entries: orphan_files.iter().map(file_entry).collect(),
+            failed_file_paths: Vec::new(),
+        },
+        Err(bulk_delete_error) => {
+            let num_failed_files =
+                bulk_delete_error.failures.len() + bulk_delete_error.unattempted.len();
+            error!(
+                error = ?bulk_delete_error.error,
+                index_id = %index_uid.index_id(),
+                num_failed_files = num_failed_files,
+                "Failed to delete orphan split files."
+            );
+            let successes: HashSet<PathBuf> = bulk_delete_error.successes.into_iter().collect();
+            let mut removal_info = OrphanFilesRemovalInfo::default();
+
+            for file_metadata in &orphan_files {
+                if successes.contains(&file_metadata.path) {
+                    removal_info
+                        .removed_file_entries
+                        .push(file_entry(file_metadata));
+                } else {
+                    removal_info
+                        .failed_file_paths
+                        .push(file_metadata.path.clone());
+                }
+            }
+            removal_info
+        }
+    };
+    JANITOR_METRICS
+        .orphan_files_total
+        .with_label_values([index_uid.index_id(), dry_run_label])
+        .inc_by(removal_info.removed_file_entries.len() as u64);
+    Ok(removal_info)
+}
+
+/// Returns the split ID of a split file located at the root of the index directory.
+fn split_id_from_path(path: &Path) -> Option<&str> {
+    let mut components = path.components();
+
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(file_name)), None) => file_name.to_str()?.strip_suffix(".split"),
+        _ => None,
+    }
+}
+
+fn file_entry(file_metadata: &FileMetadata) -> FileEntry {
+    FileEntry {
+        file_name: file_metadata.path.to_string_lossy().to_string(),
+        file_size_in_bytes: file_metadata.num_bytes,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::time::Duration;
+
+    use quickwit_config::IndexConfig;
+    use quickwit_metastore::{metastore_for_test, SplitMetadata};
+    use quickwit_storage::storage_for_test;
+
+    use super::*;
+
+    #[test]
+    fn test_split_id_from_path() {
+        assert_eq!(
+            split_id_from_path(Path::new("split-1.split")),
+            Some("split-1")
+        );
+        assert_eq!(split_id_from_path(Path::new("metastore.json")), None);
+        assert_eq!(split_id_from_path(Path::new("delete/split-1.split")), None);
+    }
+
+    #[tokio::test]
+    async fn test_run_orphan_files_reconciliation() {
+        let storage = storage_for_test();
+        let metastore = metastore_for_test();
+
+        let index_id = "test-orphan-files--index";
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(index_id, &index_uri);
+        let index_uid = metastore.create_index(index_config).await.unwrap();
+
+        let split_metadata = SplitMetadata {
+            split_id: "split-1".to_string(),
+            index_uid: index_uid.clone(),
+            ..Default::default()
+        };
+        metastore
+            .stage_splits(index_uid.clone(), vec![split_metadata])
+            .await
+            .unwrap();
+
+        for path in ["split-1.split", "split-2.split", "metastore.json"] {
+            storage
+                .put(Path::new(path), Box::new(b"split"[..].to_vec()))
+                .await
+                .unwrap();
+        }
+        // The RAM storage does not keep track of modification dates, so the orphan file is
+        // protected by any non-zero grace period.
+        let removal_info = run_orphan_files_reconciliation(
+            index_uid.clone(),
+            storage.clone(),
+            metastore.clone(),
+            Duration::from_secs(60),
+            false,
+        )
+        .await
+        .unwrap();
+        assert!(removal_info.removed_file_entries.is_empty());
+
+        let removal_info = run_orphan_files_reconciliation(
+            index_uid.clone(),
+            storage.clone(),
+            metastore.clone(),
+            Duration::ZERO,
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(removal_info.removed_file_entries.len(), 1);
+        assert_eq!(
+            removal_info.removed_file_entries[0].file_name,
+            "split-2.split"
+        );
+        assert!(storage.exists(Path::new("split-2.split")).await.unwrap());
+
+        let removal_info = run_orphan_files_reconciliation(
+            index_uid,
+            storage.clone(),
+            metastore,
+            Duration::ZERO,
+            false,
+        )
+        .await
+        .unwrap();
+        assert_eq!(removal_info.removed_file_entries.len(), 1);
+        assert!(removal_info.failed_file_paths.is_empty());
+        assert!(!storage.exists(Path::new("split-2.split")).await.unwrap());
+        assert!(storage.exists(Path::new("split-1.split")).await.unwrap());
+        assert!(storage.exists(Path::new("metastore.json")).await.unwrap());
+    }
+}
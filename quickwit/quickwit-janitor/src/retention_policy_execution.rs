@@ -17,22 +17,34 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::ops::Bound;
 use std::sync::Arc;
 
+use anyhow::Context;
 use quickwit_actors::ActorContext;
 use quickwit_common::PrettySample;
-use quickwit_config::RetentionPolicy;
+use quickwit_config::{build_doc_mapper, IndexConfig, RetentionPolicy};
 use quickwit_metastore::{ListSplitsQuery, Metastore, SplitMetadata, SplitState};
-use quickwit_proto::IndexUid;
+use quickwit_proto::metastore_api::{DeleteQuery, DeleteTask};
+use quickwit_proto::{IndexUid, SearchRequest};
+use quickwit_query::query_ast::{QueryAst, RangeQuery};
+use quickwit_query::JsonLiteral;
+use quickwit_search::SearchService;
+use tantivy::schema::{FieldType, Schema};
+use time::format_description::well_known::Rfc3339;
 use time::OffsetDateTime;
-use tracing::{info, warn};
+use tracing::{debug, info, warn};
 
 use crate::actors::RetentionPolicyExecutor;
+use crate::metrics::JANITOR_METRICS;
 
 /// Detect all expired splits based a retention policy and
 /// only mark them as `MarkedForDeletion`. Actual split deletion
 /// is taken care of by the garbage collector.
 ///
+/// When the retention policy is a dry run, the expired splits are
+/// only reported and left untouched.
+///
 /// * `index_id` - The target index id.
 /// * `metastore` - The metastore managing the target index.
 /// * `retention_policy` - The retention policy to used to evaluate the splits.
@@ -43,48 +55,330 @@ pub async fn run_execute_retention_policy(
     retention_policy: &RetentionPolicy,
     ctx: &ActorContext<RetentionPolicyExecutor>,
 ) -> anyhow::Result<Vec<SplitMetadata>> {
-    // Select splits that are published and older than the retention period.
-    let retention_period = retention_policy.retention_period()?;
-    let current_timestamp = OffsetDateTime::now_utc().unix_timestamp();
-    let max_retention_timestamp = current_timestamp - retention_period.as_secs() as i64;
-    let query = ListSplitsQuery::for_index(index_uid.clone())
-        .with_split_state(SplitState::Published)
-        .with_time_range_end_lte(max_retention_timestamp);
-
-    let (expired_splits, ignored_splits): (Vec<SplitMetadata>, Vec<SplitMetadata>) = ctx
+    let max_retention_timestamp_opt =
+        retention_policy
+            .retention_period()?
+            .map(|retention_period| {
+                let current_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+                current_timestamp - retention_period.as_secs() as i64
+            });
+    let max_num_bytes_opt = retention_policy
+        .max_size()
+        .map(|max_size| max_size.get_bytes());
+
+    // The policy may only expire individual documents.
+    if max_retention_timestamp_opt.is_none() && max_num_bytes_opt.is_none() {
+        return Ok(Vec::new());
+    }
+
+    // Select the published splits. Enforcing a size limit requires all of them, otherwise only the
+    // splits older than the retention period are needed.
+    let mut query =
+        ListSplitsQuery::for_index(index_uid.clone()).with_split_state(SplitState::Published);
+    if let (Some(max_retention_timestamp), None) = (max_retention_timestamp_opt, max_num_bytes_opt)
+    {
+        query = query.with_time_range_end_lte(max_retention_timestamp);
+    }
+    let published_splits: Vec<SplitMetadata> = ctx
         .protect_future(metastore.list_splits(query))
         .await?
         .into_iter()
         .map(|split| split.split_metadata)
-        .partition(|split_metadata| split_metadata.time_range.is_some());
+        .collect();
 
-    if !ignored_splits.is_empty() {
-        let ignored_split_ids: Vec<String> = ignored_splits
-            .into_iter()
-            .map(|split_metadata| split_metadata.split_id)
-            .collect();
-        warn!(
-            index_id=%index_uid.index_id(),
-            split_ids=?PrettySample::new(&ignored_split_ids, 5),
-            "Retention policy could not be applied to {} splits because they lack a timestamp range.",
-            ignored_split_ids.len()
-        );
-    }
+    let expired_splits = select_expired_splits(
+        &index_uid,
+        published_splits,
+        max_retention_timestamp_opt,
+        max_num_bytes_opt,
+    );
     if expired_splits.is_empty() {
         return Ok(expired_splits);
     }
-    // Mark the expired splits for deletion.
     let expired_split_ids: Vec<&str> = expired_splits
         .iter()
         .map(|split_metadata| split_metadata.split_id())
         .collect();
+    let expired_num_bytes: u64 = expired_splits
+        .iter()
+        .map(|split_metadata| split_metadata.footer_offsets.end)
+        .sum();
+    let dry_run = retention_policy.dry_run();
+
+    if dry_run {
+        info!(
+            index_id=%index_uid.index_id(),
+            split_ids=?PrettySample::new(&expired_split_ids, 5),
+            "Retention policy dry run: {} splits would be marked for deletion.",
+            expired_split_ids.len()
+        );
+    } else {
+        info!(
+            index_id=%index_uid.index_id(),
+            split_ids=?PrettySample::new(&expired_split_ids, 5),
+            "Marking {} splits for deletion based on retention policy.",
+            expired_split_ids.len()
+        );
+        ctx.protect_future(
+            metastore.mark_splits_for_deletion(index_uid.clone(), &expired_split_ids),
+        )
+        .await?;
+    }
+    let labels = [index_uid.index_id(), if dry_run { "true" } else { "false" }];
+    JANITOR_METRICS
+        .retention_policy_expired_splits_total
+        .with_label_values(labels)
+        .inc_by(expired_split_ids.len() as u64);
+    JANITOR_METRICS
+        .retention_policy_expired_bytes_total
+        .with_label_values(labels)
+        .inc_by(expired_num_bytes);
+    Ok(expired_splits)
+}
+
+/// Creates a delete task removing the documents whose expiration date, held by the expiration
+/// field of the retention policy, is in the past. The documents are then deleted by the delete
+/// task pipeline of the index.
+///
+/// No delete task is created while the delete tasks of the index are still being applied, or when
+/// no document has expired. When the retention policy is a dry run, the delete task is only logged.
+///
+/// * `index_uid` - The target index id.
+/// * `metastore` - The metastore managing the target index.
+/// * `search_service` - The search service used to count the expired documents.
+/// * `index_config` - The config of the target index.
+/// * `retention_policy` - The retention policy defining the expiration field.
+/// * `ctx_opt` - A context for reporting progress (only useful within quickwit actor).
+pub async fn run_expire_documents(
+    index_uid: IndexUid,
+    metastore: Arc<dyn Metastore>,
+    search_service: Arc<dyn SearchService>,
+    index_config: &IndexConfig,
+    retention_policy: &RetentionPolicy,
+    ctx: &ActorContext<RetentionPolicyExecutor>,
+) -> anyhow::Result<Option<DeleteTask>> {
+    let expiration_field = match retention_policy.expiration_field() {
+        Some(expiration_field) => expiration_field,
+        None => return Ok(None),
+    };
+    let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)?;
+    validate_expiration_field(&doc_mapper.schema(), expiration_field)?;
+
+    // The published splits that have not caught up with the last delete task yet still contain the
+    // documents expired by the previous pass: a new delete task would only pile up on it.
+    let last_delete_opstamp = ctx
+        .protect_future(metastore.last_delete_opstamp(index_uid.clone()))
+        .await?;
+    let stale_splits = ctx
+        .protect_future(metastore.list_stale_splits(index_uid.clone(), last_delete_opstamp, 1))
+        .await?;
+    if !stale_splits.is_empty() {
+        info!(
+            index_id=%index_uid.index_id(),
+            opstamp=last_delete_opstamp,
+            "Skipping the expiration of the documents: the delete tasks of the index are still being applied."
+        );
+        return Ok(None);
+    }
+    let now = OffsetDateTime::now_utc().format(&Rfc3339)?;
+    let query_ast = QueryAst::Range(RangeQuery {
+        field: expiration_field.to_string(),
+        lower_bound: Bound::Unbounded,
+        upper_bound: Bound::Excluded(JsonLiteral::String(now.clone())),
+    });
+    let query_ast_json = serde_json::to_string(&query_ast)?;
+    let count_request = SearchRequest {
+        index_id: index_uid.index_id().to_string(),
+        query_ast: query_ast_json.clone(),
+        max_hits: 0,
+        ..Default::default()
+    };
+    let num_expired_docs = ctx
+        .protect_future(search_service.root_search(count_request))
+        .await?
+        .num_hits;
+
+    if num_expired_docs == 0 {
+        debug!(
+            index_id=%index_uid.index_id(),
+            expiration_field=%expiration_field,
+            "No document expired before {now}."
+        );
+        return Ok(None);
+    }
+    let dry_run = retention_policy.dry_run();
+
+    if dry_run {
+        info!(
+            index_id=%index_uid.index_id(),
+            expiration_field=%expiration_field,
+            "Retention policy dry run: {num_expired_docs} documents expired before {now} would be deleted."
+        );
+        return Ok(None);
+    }
+    let delete_query = DeleteQuery {
+        index_uid: index_uid.to_string(),
+        start_timestamp: None,
+        end_timestamp: None,
+        query_ast: query_ast_json,
+    };
+    let delete_task = ctx
+        .protect_future(metastore.create_delete_task(delete_query))
+        .await?;
     info!(
         index_id=%index_uid.index_id(),
-        split_ids=?PrettySample::new(&expired_split_ids, 5),
-        "Marking {} splits for deletion based on retention policy.",
-        expired_split_ids.len()
+        expiration_field=%expiration_field,
+        opstamp=delete_task.opstamp,
+        "Created delete task for the {num_expired_docs} documents expired before {now}."
     );
-    ctx.protect_future(metastore.mark_splits_for_deletion(index_uid, &expired_split_ids))
-        .await?;
-    Ok(expired_splits)
+    JANITOR_METRICS
+        .retention_policy_expiration_delete_tasks_total
+        .with_label_values([index_uid.index_id()])
+        .inc();
+    Ok(Some(delete_task))
+}
+
+/// Checks that the expiration field can be queried with a range query.
+fn validate_expiration_field(schema: &Schema, expiration_field: &str) -> anyhow::Result<()> {
+    let field = schema.get_field(expiration_field).with_context(|| {
+        format!("Expiration field `{expiration_field}` does not exist in the doc mapping.")
+    })?;
+    match schema.get_field_entry(field).field_type() {
+        FieldType::Date(date_options) if date_options.is_fast() => Ok(()),
+        _ => anyhow::bail!("Expiration field `{expiration_field}` must be a fast datetime field."),
+    }
+}
+
+/// Selects the splits to expire: first the splits whose time range ends before
+/// `max_retention_timestamp_opt`, then the oldest remaining splits until the total size of the
+/// index fits within `max_num_bytes_opt`.
+fn select_expired_splits(
+    index_uid: &IndexUid,
+    splits: Vec<SplitMetadata>,
+    max_retention_timestamp_opt: Option<i64>,
+    max_num_bytes_opt: Option<u64>,
+) -> Vec<SplitMetadata> {
+    let mut expired_splits = Vec::new();
+    let mut retained_splits = Vec::new();
+    let mut ignored_split_ids = Vec::new();
+
+    for split_metadata in splits {
+        match (max_retention_timestamp_opt, &split_metadata.time_range) {
+            (Some(max_retention_timestamp), Some(time_range))
+                if *time_range.end() <= max_retention_timestamp =>
+            {
+                expired_splits.push(split_metadata)
+            }
+            (Some(_), None) => {
+                ignored_split_ids.push(split_metadata.split_id.clone());
+                retained_splits.push(split_metadata);
+            }
+            _ => retained_splits.push(split_metadata),
+        }
+    }
+    if !ignored_split_ids.is_empty() {
+        warn!(
+            index_id=%index_uid.index_id(),
+            split_ids=?PrettySample::new(&ignored_split_ids, 5),
+            "Retention policy period could not be applied to {} splits because they lack a timestamp range.",
+            ignored_split_ids.len()
+        );
+    }
+    let max_num_bytes = match max_num_bytes_opt {
+        Some(max_num_bytes) => max_num_bytes,
+        None => return expired_splits,
+    };
+    let mut num_bytes: u64 = retained_splits
+        .iter()
+        .map(|split_metadata| split_metadata.footer_offsets.end)
+        .sum();
+
+    // Splits are expired from the oldest to the most recent one.
+    retained_splits.sort_by_key(|split_metadata| {
+        let split_timestamp = split_metadata
+            .time_range
+            .as_ref()
+            .map(|time_range| *time_range.end())
+            .unwrap_or(split_metadata.create_timestamp);
+        (split_timestamp, split_metadata.split_id.clone())
+    });
+    for split_metadata in retained_splits {
+        if num_bytes <= max_num_bytes {
+            break;
+        }
+        num_bytes -= split_metadata.footer_offsets.end;
+        expired_splits.push(split_metadata);
+    }
+    expired_splits
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::RangeInclusive;
+
+    use super::*;
+
+    fn make_split(
+        split_id: &str,
+        time_range: Option<RangeInclusive<i64>>,
+        num_bytes: u64,
+    ) -> SplitMetadata {
+        SplitMetadata {
+            split_id: split_id.to_string(),
+            footer_offsets: num_bytes - 10..num_bytes,
+            time_range,
+            ..Default::default()
+        }
+    }
+
+    fn split_ids(splits: &[SplitMetadata]) -> Vec<&str> {
+        splits.iter().map(|split| split.split_id()).collect()
+    }
+
+    #[test]
+    fn test_select_expired_splits() {
+        let index_uid = IndexUid::new("test-index");
+        let splits = vec![
+            make_split("split-1", Some(1000..=2000), 100),
+            make_split("split-2", Some(3000..=4000), 100),
+            make_split("split-3", Some(5000..=6000), 100),
+            make_split("split-4", None, 100),
+        ];
+        {
+            let expired_splits = select_expired_splits(&index_uid, splits.clone(), None, None);
+            assert!(expired_splits.is_empty());
+        }
+        {
+            let expired_splits =
+                select_expired_splits(&index_uid, splits.clone(), Some(4000), None);
+            assert_eq!(split_ids(&expired_splits), ["split-1", "split-2"]);
+        }
+        {
+            let expired_splits = select_expired_splits(&index_uid, splits.clone(), None, Some(250));
+            assert_eq!(split_ids(&expired_splits), ["split-4", "split-1"]);
+        }
+        {
+            let expired_splits = select_expired_splits(&index_uid, splits.clone(), None, Some(400));
+            assert!(expired_splits.is_empty());
+        }
+        {
+            let expired_splits = select_expired_splits(&index_uid, splits, Some(2000), Some(150));
+            assert_eq!(
+                split_ids(&expired_splits),
+                ["split-1", "split-4", "split-2"]
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_expiration_field() {
+        let index_config = IndexConfig::for_test("test-index", "ram:///indexes/test-index");
+        let doc_mapper =
+            build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings).unwrap();
+        let schema = doc_mapper.schema();
+
+        validate_expiration_field(&schema, "response_date").unwrap();
+        validate_expiration_field(&schema, "body").unwrap_err();
+        validate_expiration_field(&schema, "expires_at").unwrap_err();
+    }
 }
@@ -0,0 +1,444 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::ops::RangeInclusive;
+use std::sync::Arc;
+
+use anyhow::Context;
+use quickwit_actors::ActorContext;
+use quickwit_config::RollupPolicy;
+use quickwit_ingest::{
+    CommitType, DocBatchBuilder, IngestRequest, IngestService, IngestServiceClient,
+};
+use quickwit_metastore::{ListSplitsQuery, Metastore, MetastoreResult, SplitState};
+use quickwit_proto::{IndexUid, SearchRequest};
+use quickwit_query::query_ast::QueryAst;
+use quickwit_search::SearchService;
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use time::OffsetDateTime;
+use tracing::info;
+
+use crate::actors::RollupExecutor;
+use crate::metrics::JANITOR_METRICS;
+
+/// Name of the field holding the number of documents of a time bucket and group in the rolled up
+/// documents.
+const ROLLUP_COUNT_FIELD: &str = "count";
+
+const ROLLUP_AGGREGATION_NAME: &str = "rollup";
+
+/// Maximum number of time buckets aggregated by a single search request.
+const MAX_TIME_BUCKETS_PER_SEARCH: i64 = 60;
+
+/// Maximum number of values per group-by field and time bucket. The documents holding the least
+/// frequent values beyond this limit are not rolled up.
+const MAX_GROUPS_PER_FIELD: usize = 1_000;
+
+/// Counts the documents of an index older than the rollup min age per time bucket and per group,
+/// and ingests the counts into the rollup target index.
+///
+/// The rollup resumes after the most recent time bucket found in the target index, so each time
+/// bucket is rolled up once as long as the target index is only fed by the rollup.
+///
+/// * `index_uid` - The rolled up index UID.
+/// * `timestamp_field` - The timestamp field of the rolled up index.
+/// * `rollup_policy` - The rollup policy of the rolled up index.
+/// * `metastore` - The metastore managing the rolled up and target indexes.
+/// * `search_service` - The search service used to aggregate the documents.
+/// * `ingest_service` - The ingest service used to feed the target index.
+/// * `dry_run` - Should this only count the rolled up documents without ingesting them.
+/// * `ctx` - A context for reporting progress (only useful within quickwit actor).
+#[allow(clippy::too_many_arguments)]
+pub async fn run_rollup(
+    index_uid: IndexUid,
+    timestamp_field: &str,
+    rollup_policy: &RollupPolicy,
+    metastore: Arc<dyn Metastore>,
+    search_service: Arc<dyn SearchService>,
+    ingest_service: &mut IngestServiceClient,
+    dry_run: bool,
+    ctx: &ActorContext<RollupExecutor>,
+) -> anyhow::Result<usize> {
+    let min_age = rollup_policy.min_age()?;
+    let interval_secs = rollup_policy.interval()?.as_secs() as i64;
+
+    let target_index_metadata = ctx
+        .protect_future(metastore.index_metadata(&rollup_policy.target_index_id))
+        .await
+        .context("Failed to fetch the metadata of the rollup target index.")?;
+    let target_timestamp_field = target_index_metadata
+        .index_config
+        .doc_mapping
+        .timestamp_field
+        .clone()
+        .context("The rollup target index must declare a timestamp field.")?;
+
+    // Only the time buckets ending before the min age are rolled up.
+    let now_timestamp = OffsetDateTime::now_utc().unix_timestamp();
+    let end_timestamp = align_timestamp(now_timestamp - min_age.as_secs() as i64, interval_secs);
+
+    let target_time_range_opt = ctx
+        .protect_future(published_time_range(
+            &*metastore,
+            target_index_metadata.index_uid.clone(),
+        ))
+        .await?;
+    let start_timestamp = match target_time_range_opt {
+        // The rolled up documents are timestamped with the start of their time bucket.
+        Some(target_time_range) => target_time_range.end() + interval_secs,
+        None => {
+            let time_range_opt = ctx
+                .protect_future(published_time_range(&*metastore, index_uid.clone()))
+                .await?;
+            match time_range_opt {
+                Some(time_range) => align_timestamp(*time_range.start(), interval_secs),
+                None => return Ok(0),
+            }
+        }
+    };
+    let mut num_rollup_docs = 0;
+    let mut bucket_start_timestamp = start_timestamp;
+
+    while bucket_start_timestamp < end_timestamp {
+        let bucket_end_timestamp =
+            end_timestamp.min(bucket_start_timestamp + MAX_TIME_BUCKETS_PER_SEARCH * interval_secs);
+        let search_request = rollup_search_request(
+            index_uid.index_id(),
+            timestamp_field,
+            &rollup_policy.group_by,
+            interval_secs,
+            bucket_start_timestamp,
+            bucket_end_timestamp,
+        )?;
+        let search_response = ctx
+            .protect_future(search_service.root_search(search_request))
+            .await?;
+        let rollup_docs = match search_response.aggregation {
+            Some(aggregation_json) => {
+                let aggregation: JsonValue = serde_json::from_str(&aggregation_json)?;
+                rollup_docs(
+                    &aggregation,
+                    &target_timestamp_field,
+                    &rollup_policy.group_by,
+                )
+            }
+            None => Vec::new(),
+        };
+        if !dry_run && !rollup_docs.is_empty() {
+            let mut doc_batch_builder =
+                DocBatchBuilder::new(rollup_policy.target_index_id.clone()).json_writer();
+
+            for rollup_doc in &rollup_docs {
+                doc_batch_builder.ingest_doc(rollup_doc)?;
+            }
+            let ingest_request = IngestRequest {
+                doc_batches: vec![doc_batch_builder.build()],
+                commit: CommitType::Force as u32,
+            };
+            ctx.protect_future(ingest_service.ingest(ingest_request))
+                .await?;
+        }
+        num_rollup_docs += rollup_docs.len();
+        bucket_start_timestamp = bucket_end_timestamp;
+    }
+    if num_rollup_docs > 0 {
+        info!(
+            index_id=%index_uid.index_id(),
+            target_index_id=%rollup_policy.target_index_id,
+            dry_run=dry_run,
+            "Rolled up {} time buckets into {} documents.",
+            (end_timestamp - start_timestamp) / interval_secs,
+            num_rollup_docs
+        );
+    }
+    JANITOR_METRICS
+        .rollup_docs_total
+        .with_label_values([index_uid.index_id(), if dry_run { "true" } else { "false" }])
+        .inc_by(num_rollup_docs as u64);
+    Ok(num_rollup_docs)
+}
+
+/// Returns the time range covered by the published splits of an index.
+async fn published_time_range(
+    metastore: &dyn Metastore,
+    index_uid: IndexUid,
+) -> MetastoreResult<Option<RangeInclusive<i64>>> {
+    let query = ListSplitsQuery::for_index(index_uid).with_split_state(SplitState::Published);
+    let time_range_opt = metastore
+        .list_splits(query)
+        .await?
+        .into_iter()
+        .filter_map(|split| split.split_metadata.time_range)
+        .reduce(|left, right| *left.start().min(right.start())..=*left.end().max(right.end()));
+    Ok(time_range_opt)
+}
+
+/// Rounds a timestamp down to the start of its time bucket.
+fn align_timestamp(timestamp: i64, interval_secs: i64) -> i64 {
+    timestamp.div_euclid(interval_secs) * interval_secs
+}
+
+/// Builds the search request counting the documents per time bucket and per group, the groups
+/// being nested in the order of the group-by fields.
+fn rollup_search_request(
+    index_id: &str,
+    timestamp_field: &str,
+    group_by: &[String],
+    interval_secs: i64,
+    start_timestamp: i64,
+    end_timestamp: i64,
+) -> anyhow::Result<SearchRequest> {
+    let mut sub_aggregations = JsonMap::new();
+
+    for (group_ord, field_name) in group_by.iter().enumerate().rev() {
+        let mut terms_aggregation = json!({
+            "terms": {
+                "field": field_name,
+                "size": MAX_GROUPS_PER_FIELD,
+            }
+        });
+        if !sub_aggregations.is_empty() {
+            terms_aggregation["aggs"] = JsonValue::Object(sub_aggregations);
+        }
+        sub_aggregations = JsonMap::new();
+        sub_aggregations.insert(group_aggregation_name(group_ord), terms_aggregation);
+    }
+    let mut date_histogram_aggregation = json!({
+        "date_histogram": {
+            "field": timestamp_field,
+            "fixed_interval": format!("{interval_secs}s"),
+        }
+    });
+    if !sub_aggregations.is_empty() {
+        date_histogram_aggregation["aggs"] = JsonValue::Object(sub_aggregations);
+    }
+    let aggregation_request = json!({ ROLLUP_AGGREGATION_NAME: date_histogram_aggregation });
+
+    Ok(SearchRequest {
+        index_id: index_id.to_string(),
+        query_ast: serde_json::to_string(&QueryAst::MatchAll)?,
+        start_timestamp: Some(start_timestamp),
+        end_timestamp: Some(end_timestamp),
+        max_hits: 0,
+        aggregation_request: Some(serde_json::to_string(&aggregation_request)?),
+        ..Default::default()
+    })
+}
+
+fn group_aggregation_name(group_ord: usize) -> String {
+    format!("group_{group_ord}")
+}
+
+/// Flattens the buckets of the rollup aggregation into one document per time bucket and group.
+fn rollup_docs(
+    aggregation: &JsonValue,
+    timestamp_field: &str,
+    group_by: &[String],
+) -> Vec<JsonValue> {
+    let mut rollup_docs = Vec::new();
+
+    for time_bucket in buckets(&aggregation[ROLLUP_AGGREGATION_NAME]) {
+        // The keys of the date histogram buckets are expressed in milliseconds.
+        let timestamp_millis = match time_bucket["key"].as_f64() {
+            Some(timestamp_millis) => timestamp_millis as i64,
+            None => continue,
+        };
+        let mut rollup_doc = JsonMap::new();
+        insert_field(
+            &mut rollup_doc,
+            timestamp_field,
+            json!(timestamp_millis / 1_000),
+        );
+        collect_group_docs(time_bucket, group_by, 0, rollup_doc, &mut rollup_docs);
+    }
+    rollup_docs
+}
+
+fn collect_group_docs(
+    bucket: &JsonValue,
+    group_by: &[String],
+    group_ord: usize,
+    rollup_doc: JsonMap<String, JsonValue>,
+    rollup_docs: &mut Vec<JsonValue>,
+) {
+    match group_by.get(group_ord) {
+        Some(field_name) => {
+            for group_bucket in buckets(&bucket[group_aggregation_name(group_ord)]) {
+                let mut group_rollup_doc = rollup_doc.clone();
+                insert_field(
+                    &mut group_rollup_doc,
+                    field_name,
+                    group_bucket["key"].clone(),
+                );
+                collect_group_docs(
+                    group_bucket,
+                    group_by,
+                    group_ord + 1,
+                    group_rollup_doc,
+                    rollup_docs,
+                );
+            }
+        }
+        None => {
+            let doc_count = bucket["doc_count"].as_u64().unwrap_or(0);
+
+            // The date histogram also returns the empty time buckets.
+            if doc_count > 0 {
+                let mut rollup_doc = rollup_doc;
+                rollup_doc.insert(ROLLUP_COUNT_FIELD.to_string(), json!(doc_count));
+                rollup_docs.push(JsonValue::Object(rollup_doc));
+            }
+        }
+    }
+}
+
+fn buckets(aggregation: &JsonValue) -> &[JsonValue] {
+    aggregation["buckets"]
+        .as_array()
+        .map(Vec::as_slice)
+        .unwrap_or_default()
+}
+
+/// Inserts a value into a document at the path of a field, creating the intermediate objects of
+/// the object fields.
+fn insert_field(doc: &mut JsonMap<String, JsonValue>, field_path: &str, value: JsonValue) {
+    match field_path.split_once('.') {
+        Some((object_name, sub_field_path)) => {
+            let object = doc
+                .entry(object_name)
+                .or_insert_with(|| JsonValue::Object(JsonMap::new()));
+
+            if let JsonValue::Object(object) = object {
+                insert_field(object, sub_field_path, value);
+            }
+        }
+        None => {
+            doc.insert(field_path.to_string(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align_timestamp() {
+        assert_eq!(align_timestamp(0, 60), 0);
+        assert_eq!(align_timestamp(59, 60), 0);
+        assert_eq!(align_timestamp(60, 60), 60);
+        assert_eq!(align_timestamp(-1, 60), -60);
+    }
+
+    #[test]
+    fn test_rollup_search_request() {
+        let group_by = vec!["service".to_string(), "resource.host".to_string()];
+        let search_request =
+            rollup_search_request("test-index", "timestamp", &group_by, 60, 0, 3600).unwrap();
+        assert_eq!(search_request.index_id, "test-index");
+        assert_eq!(search_request.start_timestamp, Some(0));
+        assert_eq!(search_request.end_timestamp, Some(3600));
+        assert_eq!(search_request.max_hits, 0);
+
+        let aggregation_request: JsonValue =
+            serde_json::from_str(&search_request.aggregation_request.unwrap()).unwrap();
+        assert_eq!(
+            aggregation_request,
+            json!({
+                "rollup": {
+                    "date_histogram": {
+                        "field": "timestamp",
+                        "fixed_interval": "60s",
+                    },
+                    "aggs": {
+                        "group_0": {
+                            "terms": {
+                                "field": "service",
+                                "size": MAX_GROUPS_PER_FIELD,
+                            },
+                            "aggs": {
+                                "group_1": {
+                                    "terms": {
+                                        "field": "resource.host",
+                                        "size": MAX_GROUPS_PER_FIELD,
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_rollup_docs() {
+        let aggregation = json!({
+            "rollup": {
+                "buckets": [
+                    {
+                        "key": 60000.0,
+                        "key_as_string": "1970-01-01T00:01:00Z",
+                        "doc_count": 3,
+                        "group_0": {
+                            "buckets": [
+                                {
+                                    "key": "api",
+                                    "doc_count": 2,
+                                    "group_1": {
+                                        "buckets": [
+                                            { "key": "host-1", "doc_count": 2 }
+                                        ]
+                                    }
+                                },
+                                {
+                                    "key": "db",
+                                    "doc_count": 1,
+                                    "group_1": {
+                                        "buckets": [
+                                            { "key": "host-2", "doc_count": 1 }
+                                        ]
+                                    }
+                                }
+                            ]
+                        }
+                    },
+                    {
+                        "key": 120000.0,
+                        "key_as_string": "1970-01-01T00:02:00Z",
+                        "doc_count": 0,
+                        "group_0": { "buckets": [] }
+                    }
+                ]
+            }
+        });
+        let group_by = vec!["service".to_string(), "resource.host".to_string()];
+        assert_eq!(
+            rollup_docs(&aggregation, "ts", &group_by),
+            [
+                json!({"ts": 60, "service": "api", "resource": {"host": "host-1"}, "count": 2}),
+                json!({"ts": 60, "service": "db", "resource": {"host": "host-2"}, "count": 1}),
+            ]
+        );
+        assert_eq!(
+            rollup_docs(&aggregation, "ts", &[]),
+            [json!({"ts": 60, "count": 3})]
+        );
+    }
+}
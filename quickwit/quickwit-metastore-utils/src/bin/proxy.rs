@@ -108,6 +108,16 @@ impl MetastoreApiService for MetastoreProxyService {
         let resp = lock.client.list_indexes_metadatas(request).await?;
         Ok(resp)
     }
+    /// Updates an index config.
+    async fn update_index_config(
+        &self,
+        request: tonic::Request<UpdateIndexConfigRequest>,
+    ) -> Result<tonic::Response<IndexMetadataResponse>, tonic::Status> {
+        let mut lock = self.inner.lock().await;
+        lock.record(request.get_ref().clone()).await.unwrap();
+        let resp = lock.client.update_index_config(request).await?;
+        Ok(resp)
+    }
     /// Deletes an index
     async fn delete_index(
         &self,
@@ -118,6 +128,46 @@ impl MetastoreApiService for MetastoreProxyService {
         let resp = lock.client.delete_index(request).await?;
         Ok(resp)
     }
+    /// Moves an index to the trash.
+    async fn trash_index(
+        &self,
+        request: tonic::Request<TrashIndexRequest>,
+    ) -> Result<tonic::Response<TrashIndexResponse>, tonic::Status> {
+        let mut lock = self.inner.lock().await;
+        lock.record(request.get_ref().clone()).await.unwrap();
+        let resp = lock.client.trash_index(request).await?;
+        Ok(resp)
+    }
+    /// Takes an index out of the trash.
+    async fn restore_index(
+        &self,
+        request: tonic::Request<RestoreIndexRequest>,
+    ) -> Result<tonic::Response<IndexMetadataResponse>, tonic::Status> {
+        let mut lock = self.inner.lock().await;
+        lock.record(request.get_ref().clone()).await.unwrap();
+        let resp = lock.client.restore_index(request).await?;
+        Ok(resp)
+    }
+    /// Gets the metadatas of the trashed indexes.
+    async fn list_trashed_indexes_metadatas(
+        &self,
+        request: tonic::Request<ListIndexesMetadatasRequest>,
+    ) -> Result<tonic::Response<ListIndexesMetadatasResponse>, tonic::Status> {
+        let mut lock = self.inner.lock().await;
+        lock.record(request.get_ref().clone()).await.unwrap();
+        let resp = lock.client.list_trashed_indexes_metadatas(request).await?;
+        Ok(resp)
+    }
+    /// Pauses or resumes the merges of an index.
+    async fn set_merges_paused(
+        &self,
+        request: tonic::Request<SetMergesPausedRequest>,
+    ) -> Result<tonic::Response<SetMergesPausedResponse>, tonic::Status> {
+        let mut lock = self.inner.lock().await;
+        lock.record(request.get_ref().clone()).await.unwrap();
+        let resp = lock.client.set_merges_paused(request).await?;
+        Ok(resp)
+    }
     /// Gets all splits from index.
     async fn list_all_splits(
         &self,
@@ -138,6 +188,16 @@ impl MetastoreApiService for MetastoreProxyService {
         let resp = lock.client.list_splits(request).await?;
         Ok(resp)
     }
+    /// Gets a page of splits from index, sorted by split ID.
+    async fn list_splits_page(
+        &self,
+        request: tonic::Request<ListSplitsPageRequest>,
+    ) -> Result<tonic::Response<ListSplitsPageResponse>, tonic::Status> {
+        let mut lock = self.inner.lock().await;
+        lock.record(request.get_ref().clone()).await.unwrap();
+        let resp = lock.client.list_splits_page(request).await?;
+        Ok(resp)
+    }
     /// Stages several splits.
     async fn stage_splits(
         &self,
@@ -158,6 +218,16 @@ impl MetastoreApiService for MetastoreProxyService {
         let resp = lock.client.publish_splits(request).await?;
         Ok(resp)
     }
+    /// Publishes splits to several indexes atomically.
+    async fn publish_splits_across_indexes(
+        &self,
+        request: tonic::Request<PublishSplitsAcrossIndexesRequest>,
+    ) -> Result<tonic::Response<SplitResponse>, tonic::Status> {
+        let mut lock = self.inner.lock().await;
+        lock.record(request.get_ref().clone()).await.unwrap();
+        let resp = lock.client.publish_splits_across_indexes(request).await?;
+        Ok(resp)
+    }
     /// Marks splits for deletion.
     async fn mark_splits_for_deletion(
         &self,
@@ -218,6 +288,16 @@ impl MetastoreApiService for MetastoreProxyService {
         let resp = lock.client.reset_source_checkpoint(request).await?;
         Ok(resp)
     }
+    /// Rewinds source checkpoint.
+    async fn rewind_source_checkpoint(
+        &self,
+        request: tonic::Request<RewindSourceCheckpointRequest>,
+    ) -> Result<tonic::Response<SourceResponse>, tonic::Status> {
+        let mut lock = self.inner.lock().await;
+        lock.record(request.get_ref().clone()).await.unwrap();
+        let resp = lock.client.rewind_source_checkpoint(request).await?;
+        Ok(resp)
+    }
     /// Gets last opstamp for a given `index_id`.
     async fn last_delete_opstamp(
         &self,
@@ -258,6 +338,16 @@ impl MetastoreApiService for MetastoreProxyService {
         let resp = lock.client.list_delete_tasks(request).await?;
         Ok(resp)
     }
+    /// Cancels a delete task that has not been applied to any split yet.
+    async fn cancel_delete_task(
+        &self,
+        request: tonic::Request<CancelDeleteTaskRequest>,
+    ) -> Result<tonic::Response<DeleteTask>, tonic::Status> {
+        let mut lock = self.inner.lock().await;
+        lock.record(request.get_ref().clone()).await.unwrap();
+        let resp = lock.client.cancel_delete_task(request).await?;
+        Ok(resp)
+    }
     //// Lists splits with `split.delete_opstamp` < `delete_opstamp` for a given `index_id`.
     async fn list_stale_splits(
         &self,
@@ -268,6 +358,56 @@ impl MetastoreApiService for MetastoreProxyService {
         let resp = lock.client.list_stale_splits(request).await?;
         Ok(resp)
     }
+    /// Appends an event to the audit log of an index.
+    async fn append_audit_event(
+        &self,
+        request: tonic::Request<AppendAuditEventRequest>,
+    ) -> Result<tonic::Response<AppendAuditEventResponse>, tonic::Status> {
+        let mut lock = self.inner.lock().await;
+        lock.record(request.get_ref().clone()).await.unwrap();
+        let resp = lock.client.append_audit_event(request).await?;
+        Ok(resp)
+    }
+    /// Lists the audit events of an index, oldest first.
+    async fn list_audit_events(
+        &self,
+        request: tonic::Request<ListAuditEventsRequest>,
+    ) -> Result<tonic::Response<ListAuditEventsResponse>, tonic::Status> {
+        let mut lock = self.inner.lock().await;
+        lock.record(request.get_ref().clone()).await.unwrap();
+        let resp = lock.client.list_audit_events(request).await?;
+        Ok(resp)
+    }
+    /// Returns the cluster settings.
+    async fn cluster_settings(
+        &self,
+        request: tonic::Request<ClusterSettingsRequest>,
+    ) -> Result<tonic::Response<ClusterSettingsResponse>, tonic::Status> {
+        let mut lock = self.inner.lock().await;
+        lock.record(request.get_ref().clone()).await.unwrap();
+        let resp = lock.client.cluster_settings(request).await?;
+        Ok(resp)
+    }
+    /// Replaces the cluster settings.
+    async fn update_cluster_settings(
+        &self,
+        request: tonic::Request<UpdateClusterSettingsRequest>,
+    ) -> Result<tonic::Response<UpdateClusterSettingsResponse>, tonic::Status> {
+        let mut lock = self.inner.lock().await;
+        lock.record(request.get_ref().clone()).await.unwrap();
+        let resp = lock.client.update_cluster_settings(request).await?;
+        Ok(resp)
+    }
+    /// Lists the tenants owning at least one index.
+    async fn list_tenants(
+        &self,
+        request: tonic::Request<ListTenantsRequest>,
+    ) -> Result<tonic::Response<ListTenantsResponse>, tonic::Status> {
+        let mut lock = self.inner.lock().await;
+        lock.record(request.get_ref().clone()).await.unwrap();
+        let resp = lock.client.list_tenants(request).await?;
+        Ok(resp)
+    }
 }
 
 #[derive(Debug, StructOpt)]
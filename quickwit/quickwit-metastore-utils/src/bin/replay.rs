@@ -40,21 +40,39 @@ async fn replay_grpc_request(
         GrpcRequest::ListIndexesMetadatasRequest(req) => {
             client.list_indexes_metadatas(req).await?;
         }
+        GrpcRequest::UpdateIndexConfigRequest(req) => {
+            client.update_index_config(req).await?;
+        }
         GrpcRequest::DeleteIndexRequest(req) => {
             client.delete_index(req).await?;
         }
+        GrpcRequest::TrashIndexRequest(req) => {
+            client.trash_index(req).await?;
+        }
+        GrpcRequest::RestoreIndexRequest(req) => {
+            client.restore_index(req).await?;
+        }
+        GrpcRequest::SetMergesPausedRequest(req) => {
+            client.set_merges_paused(req).await?;
+        }
         GrpcRequest::ListAllSplitsRequest(req) => {
             client.list_all_splits(req).await?;
         }
         GrpcRequest::ListSplitsRequest(req) => {
             client.list_splits(req).await?;
         }
+        GrpcRequest::ListSplitsPageRequest(req) => {
+            client.list_splits_page(req).await?;
+        }
         GrpcRequest::StageSplitsRequest(req) => {
             client.stage_splits(req).await?;
         }
         GrpcRequest::PublishSplitsRequest(req) => {
             client.publish_splits(req).await?;
         }
+        GrpcRequest::PublishSplitsAcrossIndexesRequest(req) => {
+            client.publish_splits_across_indexes(req).await?;
+        }
         GrpcRequest::MarkSplitsForDeletionRequest(req) => {
             client.mark_splits_for_deletion(req).await?;
         }
@@ -76,6 +94,9 @@ async fn replay_grpc_request(
         GrpcRequest::ResetSourceCheckpointRequest(req) => {
             client.reset_source_checkpoint(req).await?;
         }
+        GrpcRequest::RewindSourceCheckpointRequest(req) => {
+            client.rewind_source_checkpoint(req).await?;
+        }
         GrpcRequest::DeleteQuery(req) => {
             client.create_delete_task(req).await?;
         }
@@ -85,9 +106,27 @@ async fn replay_grpc_request(
         GrpcRequest::ListDeleteTasksRequest(req) => {
             client.list_delete_tasks(req).await?;
         }
+        GrpcRequest::CancelDeleteTaskRequest(req) => {
+            client.cancel_delete_task(req).await?;
+        }
         GrpcRequest::ListStaleSplitsRequest(req) => {
             client.list_stale_splits(req).await?;
         }
+        GrpcRequest::AppendAuditEventRequest(req) => {
+            client.append_audit_event(req).await?;
+        }
+        GrpcRequest::ListAuditEventsRequest(req) => {
+            client.list_audit_events(req).await?;
+        }
+        GrpcRequest::ClusterSettingsRequest(req) => {
+            client.cluster_settings(req).await?;
+        }
+        GrpcRequest::UpdateClusterSettingsRequest(req) => {
+            client.update_cluster_settings(req).await?;
+        }
+        GrpcRequest::ListTenantsRequest(req) => {
+            client.list_tenants(req).await?;
+        }
     }
     Ok(())
 }
@@ -68,11 +68,17 @@ generate_req_enum!(
     CreateIndexRequest,
     IndexMetadataRequest,
     ListIndexesMetadatasRequest,
+    UpdateIndexConfigRequest,
     DeleteIndexRequest,
+    TrashIndexRequest,
+    RestoreIndexRequest,
+    SetMergesPausedRequest,
     ListAllSplitsRequest,
     ListSplitsRequest,
+    ListSplitsPageRequest,
     StageSplitsRequest,
     PublishSplitsRequest,
+    PublishSplitsAcrossIndexesRequest,
     MarkSplitsForDeletionRequest,
     DeleteSplitsRequest,
     AddSourceRequest,
@@ -80,8 +86,15 @@ generate_req_enum!(
     DeleteSourceRequest,
     LastDeleteOpstampRequest,
     ResetSourceCheckpointRequest,
+    RewindSourceCheckpointRequest,
     DeleteQuery,
     UpdateSplitsDeleteOpstampRequest,
     ListDeleteTasksRequest,
+    CancelDeleteTaskRequest,
     ListStaleSplitsRequest,
+    AppendAuditEventRequest,
+    ListAuditEventsRequest,
+    ClusterSettingsRequest,
+    UpdateClusterSettingsRequest,
+    ListTenantsRequest,
 );
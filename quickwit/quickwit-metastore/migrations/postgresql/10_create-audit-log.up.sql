@@ -0,0 +1,9 @@
+-- The audit log is not tied to the `indexes` table with a foreign key so that
+-- the history of an index outlives the index itself.
+CREATE TABLE IF NOT EXISTS audit_log (
+    id BIGSERIAL PRIMARY KEY,
+    index_id VARCHAR(50) NOT NULL,
+    audit_event_json TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS audit_log_index_id_idx ON audit_log (index_id);
@@ -0,0 +1,5 @@
+-- The cluster settings are stored as a single row.
+CREATE TABLE IF NOT EXISTS cluster_settings (
+    id INT PRIMARY KEY CHECK (id = 1),
+    cluster_settings_json TEXT NOT NULL
+);
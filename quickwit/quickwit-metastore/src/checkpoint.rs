@@ -170,6 +170,24 @@ impl IndexCheckpoint {
         self.per_source.remove(source_id).is_some()
     }
 
+    /// Rewinds the checkpoint of the source identified by `source_id` to the positions of
+    /// `target_checkpoint`. Returns whether a mutation occurred.
+    ///
+    /// See [`SourceCheckpoint::try_rewind`] for more details.
+    pub(crate) fn try_rewind_source(
+        &mut self,
+        source_id: &str,
+        target_checkpoint: &SourceCheckpoint,
+    ) -> Result<bool, IncompatibleCheckpointRewind> {
+        let mut source_checkpoint = self.per_source.get(source_id).cloned().unwrap_or_default();
+        if !source_checkpoint.try_rewind(target_checkpoint)? {
+            return Ok(false);
+        }
+        self.per_source
+            .insert(source_id.to_string(), source_checkpoint);
+        Ok(true)
+    }
+
     /// Returns the checkpoint associated to a given source.
     ///
     /// All registered source have an associated checkpoint (that is possibly empty).
@@ -280,6 +298,22 @@ pub struct IncompatibleCheckpointDelta {
     pub delta_position_from: Position,
 }
 
+/// Error returned when trying to rewind a checkpoint to a position located after the current
+/// position of a partition.
+#[derive(Clone, Debug, Error, Eq, PartialEq, Serialize, Deserialize)]
+#[error(
+    "IncompatibleChkptRewind at partition: {partition_id:?} cur_pos:{current_position:?} \
+     target_pos:{target_position:?}"
+)]
+pub struct IncompatibleCheckpointRewind {
+    /// One PartitionId for which the incompatibility has been detected.
+    pub partition_id: PartitionId,
+    /// The current position within this partition.
+    pub current_position: Position,
+    /// The position the partition was supposed to be rewound to.
+    pub target_position: Position,
+}
+
 #[derive(Clone, Debug, Error, Serialize, Deserialize, PartialEq, Eq)]
 pub enum PartitionDeltaError {
     #[error(transparent)]
@@ -363,6 +397,45 @@ impl SourceCheckpoint {
         }
         Ok(())
     }
+
+    /// Try and rewind the checkpoint to the positions of `target_checkpoint`. Returns whether a
+    /// mutation occurred.
+    ///
+    /// Only the partitions present in the target checkpoint are rewound, the others are left
+    /// untouched. A partition rewound to [`Position::Beginning`] is removed from the checkpoint.
+    ///
+    /// If a target position is located after the current position of its partition, returns an
+    /// error without modifying the original checkpoint.
+    pub fn try_rewind(
+        &mut self,
+        target_checkpoint: &SourceCheckpoint,
+    ) -> Result<bool, IncompatibleCheckpointRewind> {
+        for (partition_id, target_position) in &target_checkpoint.per_partition {
+            let current_position = self
+                .per_partition
+                .get(partition_id)
+                .unwrap_or(&Position::Beginning);
+            if target_position > current_position {
+                return Err(IncompatibleCheckpointRewind {
+                    partition_id: partition_id.clone(),
+                    current_position: current_position.clone(),
+                    target_position: target_position.clone(),
+                });
+            }
+        }
+        let mut mutation_occurred = false;
+        for (partition_id, target_position) in &target_checkpoint.per_partition {
+            if *target_position == Position::Beginning {
+                mutation_occurred |= self.per_partition.remove(partition_id).is_some();
+            } else {
+                let previous_position_opt = self
+                    .per_partition
+                    .insert(partition_id.clone(), target_position.clone());
+                mutation_occurred |= previous_position_opt.as_ref() != Some(target_position);
+            }
+        }
+        Ok(mutation_occurred)
+    }
 }
 
 impl fmt::Debug for SourceCheckpoint {
@@ -501,6 +574,16 @@ impl SourceCheckpointDelta {
         source_checkpoint
     }
 
+    /// Returns the checkpoint associated with the starting point of the delta.
+    pub fn get_starting_source_checkpoint(&self) -> SourceCheckpoint {
+        self.per_partition
+            .iter()
+            .map(|(partition_id, partition_delta)| {
+                (partition_id.clone(), partition_delta.from.clone())
+            })
+            .collect()
+    }
+
     /// Records a `(from, to]` partition delta for a given partition.
     pub fn record_partition_delta(
         &mut self,
@@ -832,6 +915,52 @@ mod tests {
             .is_none());
     }
 
+    #[test]
+    fn test_rewind_checkpoint() {
+        let mut checkpoint: SourceCheckpoint = vec![
+            (PartitionId::from("a"), Position::from(42u64)),
+            (PartitionId::from("b"), Position::from(12u64)),
+            (PartitionId::from("c"), Position::from(7u64)),
+        ]
+        .into_iter()
+        .collect();
+        let target_checkpoint: SourceCheckpoint = vec![
+            (PartitionId::from("a"), Position::from(10u64)),
+            (PartitionId::from("b"), Position::Beginning),
+        ]
+        .into_iter()
+        .collect();
+        assert!(checkpoint.try_rewind(&target_checkpoint).unwrap());
+        assert_eq!(
+            format!("{checkpoint:?}"),
+            "Ckpt(a:00000000000000000010 c:00000000000000000007)"
+        );
+        assert!(!checkpoint.try_rewind(&target_checkpoint).unwrap());
+
+        let invalid_target_checkpoint: SourceCheckpoint = vec![
+            (PartitionId::from("a"), Position::from(5u64)),
+            (PartitionId::from("c"), Position::from(8u64)),
+        ]
+        .into_iter()
+        .collect();
+        let rewind_error = checkpoint
+            .try_rewind(&invalid_target_checkpoint)
+            .unwrap_err();
+        assert_eq!(
+            rewind_error,
+            IncompatibleCheckpointRewind {
+                partition_id: PartitionId::from("c"),
+                current_position: Position::from(7u64),
+                target_position: Position::from(8u64),
+            }
+        );
+        // The checkpoint is left untouched on error.
+        assert_eq!(
+            checkpoint.position_for_partition(&PartitionId::from("a")),
+            Some(&Position::from(10u64))
+        );
+    }
+
     #[test]
     fn test_get_source_checkpoint() {
         let partition = PartitionId::from("a");
@@ -846,5 +975,12 @@ mod tests {
             checkpoint.position_for_partition(&partition).unwrap(),
             &Position::from(43u64)
         );
+        let starting_checkpoint: SourceCheckpoint = delta.get_starting_source_checkpoint();
+        assert_eq!(
+            starting_checkpoint
+                .position_for_partition(&partition)
+                .unwrap(),
+            &Position::from(42u64)
+        );
     }
 }
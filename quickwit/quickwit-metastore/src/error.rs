@@ -21,7 +21,7 @@ use quickwit_proto::{ServiceError, ServiceErrorCode};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
-use crate::checkpoint::IncompatibleCheckpointDelta;
+use crate::checkpoint::{IncompatibleCheckpointDelta, IncompatibleCheckpointRewind};
 
 /// Metastore error kinds.
 #[allow(missing_docs)]
@@ -39,6 +39,16 @@ pub enum MetastoreError {
     #[error("Index `{index_id}` does not exist.")]
     IndexDoesNotExist { index_id: String },
 
+    #[error(
+        "Index `{index_id}` config is at version {current_version}, expected version \
+         {expected_version}. The index config was updated concurrently."
+    )]
+    IndexConfigVersionConflict {
+        index_id: String,
+        expected_version: u64,
+        current_version: u64,
+    },
+
     /// Any generic internal error.
     /// The message can be helpful to users, but the detail of the error
     /// are judged uncoverable and not useful for error handling.
@@ -63,6 +73,9 @@ pub enum MetastoreError {
     #[error("Publish checkpoint delta overlaps with the current checkpoint: {0:?}.")]
     IncompatibleCheckpointDelta(#[from] IncompatibleCheckpointDelta),
 
+    #[error("Checkpoint cannot be rewound past its current position: {0:?}.")]
+    IncompatibleCheckpointRewind(#[from] IncompatibleCheckpointRewind),
+
     #[error("Source `{source_id}` of type `{source_type}` already exists.")]
     SourceAlreadyExists {
         source_id: String,
@@ -72,6 +85,12 @@ pub enum MetastoreError {
     #[error("Source `{source_id}` does not exist.")]
     SourceDoesNotExist { source_id: String },
 
+    #[error("Delete task `{opstamp}` does not exist.")]
+    DeleteTaskDoesNotExist { opstamp: u64 },
+
+    #[error("Delete task `{opstamp}` has already been applied and cannot be cancelled.")]
+    DeleteTaskNotCancellable { opstamp: u64 },
+
     #[error("Database error: `{message}`.")]
     DbError { message: String },
 
@@ -110,9 +129,13 @@ impl ServiceError for MetastoreError {
     fn status_code(&self) -> ServiceErrorCode {
         match self {
             Self::ConnectionError { .. } => ServiceErrorCode::Internal,
+            Self::DeleteTaskDoesNotExist { .. } => ServiceErrorCode::NotFound,
+            Self::DeleteTaskNotCancellable { .. } => ServiceErrorCode::BadRequest,
             Self::Forbidden { .. } => ServiceErrorCode::MethodNotAllowed,
             Self::IncompatibleCheckpointDelta(_) => ServiceErrorCode::BadRequest,
+            Self::IncompatibleCheckpointRewind(_) => ServiceErrorCode::BadRequest,
             Self::IndexAlreadyExists { .. } => ServiceErrorCode::BadRequest,
+            Self::IndexConfigVersionConflict { .. } => ServiceErrorCode::Conflict,
             Self::IndexDoesNotExist { .. } => ServiceErrorCode::NotFound,
             Self::InternalError { .. } => ServiceErrorCode::Internal,
             Self::InvalidManifest { .. } => ServiceErrorCode::Internal,
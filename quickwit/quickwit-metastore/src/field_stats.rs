@@ -0,0 +1,192 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::SplitMetadata;
+
+/// Statistics about a field of a split, computed when the split is built.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SplitFieldStats {
+    /// Number of documents with at least one value for the field.
+    #[serde(default)]
+    pub num_docs_with_value: u64,
+
+    /// Number of values of the field. Multivalued fields may have several values per document.
+    #[serde(default)]
+    pub num_values: u64,
+
+    /// Sum of the lengths (in bytes) of the text and bytes values of the field.
+    #[serde(default)]
+    pub value_num_bytes: u64,
+
+    /// Number of tokens indexed for the field.
+    #[serde(default)]
+    pub num_tokens: u64,
+
+    /// Size (in bytes) of the fast field data of the field.
+    #[serde(default)]
+    pub fast_field_num_bytes: u64,
+}
+
+impl SplitFieldStats {
+    /// Adds the statistics of `other` to `self`.
+    pub fn add(&mut self, other: &SplitFieldStats) {
+        self.num_docs_with_value += other.num_docs_with_value;
+        self.num_values += other.num_values;
+        self.value_num_bytes += other.value_num_bytes;
+        self.num_tokens += other.num_tokens;
+        self.fast_field_num_bytes += other.fast_field_num_bytes;
+    }
+}
+
+/// Statistics about a field of an index, aggregated over a set of splits.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct IndexFieldStats {
+    /// Name of the field.
+    pub field_name: String,
+
+    /// Number of splits carrying statistics for the field.
+    pub num_splits: usize,
+
+    /// Number of documents with at least one value for the field.
+    pub num_docs_with_value: u64,
+
+    /// Number of documents without any value for the field.
+    pub num_null_docs: u64,
+
+    /// Number of values of the field.
+    pub num_values: u64,
+
+    /// Number of tokens indexed for the field.
+    pub num_tokens: u64,
+
+    /// Average length (in bytes) of the text and bytes values of the field.
+    pub avg_value_num_bytes: f64,
+
+    /// Size (in bytes) of the fast field data of the field.
+    pub fast_field_num_bytes: u64,
+}
+
+/// Aggregates the field statistics of `splits` per field, sorted by field name.
+///
+/// Splits created before field statistics were collected carry no statistics and are ignored.
+pub fn aggregate_field_stats<'a>(
+    splits: impl IntoIterator<Item = &'a SplitMetadata>,
+) -> Vec<IndexFieldStats> {
+    let mut num_docs: u64 = 0;
+    let mut field_stats: BTreeMap<&str, (usize, SplitFieldStats)> = BTreeMap::new();
+
+    for split in splits {
+        if split.field_stats.is_empty() {
+            continue;
+        }
+        num_docs += split.num_docs as u64;
+
+        for (field_name, split_field_stats) in &split.field_stats {
+            let (num_splits, stats) = field_stats.entry(field_name.as_str()).or_default();
+            *num_splits += 1;
+            stats.add(split_field_stats);
+        }
+    }
+    field_stats
+        .into_iter()
+        .map(|(field_name, (num_splits, stats))| {
+            let avg_value_num_bytes = if stats.num_values > 0 {
+                stats.value_num_bytes as f64 / stats.num_values as f64
+            } else {
+                0.0
+            };
+            IndexFieldStats {
+                field_name: field_name.to_string(),
+                num_splits,
+                num_docs_with_value: stats.num_docs_with_value,
+                num_null_docs: num_docs.saturating_sub(stats.num_docs_with_value),
+                num_values: stats.num_values,
+                num_tokens: stats.num_tokens,
+                avg_value_num_bytes,
+                fast_field_num_bytes: stats.fast_field_num_bytes,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aggregate_field_stats() {
+        let mut split_1 = SplitMetadata::for_test("split-1".to_string());
+        split_1.num_docs = 10;
+        split_1.field_stats.insert(
+            "body".to_string(),
+            SplitFieldStats {
+                num_docs_with_value: 8,
+                num_values: 8,
+                value_num_bytes: 80,
+                num_tokens: 30,
+                fast_field_num_bytes: 0,
+            },
+        );
+        split_1.field_stats.insert(
+            "severity".to_string(),
+            SplitFieldStats {
+                num_docs_with_value: 10,
+                num_values: 10,
+                value_num_bytes: 40,
+                num_tokens: 10,
+                fast_field_num_bytes: 12,
+            },
+        );
+        let mut split_2 = SplitMetadata::for_test("split-2".to_string());
+        split_2.num_docs = 5;
+        split_2.field_stats.insert(
+            "body".to_string(),
+            SplitFieldStats {
+                num_docs_with_value: 5,
+                num_values: 12,
+                value_num_bytes: 20,
+                num_tokens: 15,
+                fast_field_num_bytes: 0,
+            },
+        );
+        // Splits without statistics are ignored.
+        let mut split_3 = SplitMetadata::for_test("split-3".to_string());
+        split_3.num_docs = 100;
+
+        let index_field_stats = aggregate_field_stats([&split_1, &split_2, &split_3]);
+        assert_eq!(index_field_stats.len(), 2);
+
+        assert_eq!(index_field_stats[0].field_name, "body");
+        assert_eq!(index_field_stats[0].num_splits, 2);
+        assert_eq!(index_field_stats[0].num_docs_with_value, 13);
+        assert_eq!(index_field_stats[0].num_null_docs, 2);
+        assert_eq!(index_field_stats[0].num_values, 20);
+        assert_eq!(index_field_stats[0].num_tokens, 45);
+        assert_eq!(index_field_stats[0].avg_value_num_bytes, 5.0);
+
+        assert_eq!(index_field_stats[1].field_name, "severity");
+        assert_eq!(index_field_stats[1].num_splits, 1);
+        assert_eq!(index_field_stats[1].num_null_docs, 5);
+        assert_eq!(index_field_stats[1].fast_field_num_bytes, 12);
+    }
+}
@@ -32,6 +32,7 @@ mod tests;
 #[allow(missing_docs)]
 pub mod checkpoint;
 mod error;
+mod field_stats;
 mod metastore;
 mod metastore_resolver;
 mod metrics;
@@ -41,6 +42,11 @@ mod split_metadata_version;
 use std::ops::Range;
 
 pub use error::{MetastoreError, MetastoreResolverError, MetastoreResult};
+pub use field_stats::{aggregate_field_stats, IndexFieldStats, SplitFieldStats};
+pub use metastore::audit_log_metastore::{
+    checkpoint_rewind_target_at, current_audit_actor, default_audit_actor, set_default_audit_actor,
+    with_audit_actor, AuditEvent, AuditLogMetastore, AuditOperation, AUDIT_ACTOR_METADATA_KEY,
+};
 pub use metastore::file_backed_metastore::FileBackedMetastore;
 pub use metastore::grpc_metastore::{GrpcMetastoreAdapter, MetastoreGrpcClient};
 pub(crate) use metastore::index_metadata::serialize::{IndexMetadataV0_6, VersionedIndexMetadata};
@@ -48,21 +54,29 @@ pub use metastore::metastore_event_publisher::{MetastoreEvent, MetastoreEventPub
 #[cfg(feature = "postgres")]
 pub use metastore::postgresql_metastore::PostgresqlMetastore;
 pub use metastore::retrying_metastore::RetryingMetastore;
+pub use metastore::tenant_metastore::TenantMetastore;
 #[cfg(any(test, feature = "testsuite"))]
 pub use metastore::MockMetastore;
-pub use metastore::{file_backed_metastore, IndexMetadata, ListSplitsQuery, Metastore};
+pub use metastore::{
+    file_backed_metastore, stream_splits, IndexMetadata, ListSplitsQuery, Metastore, SplitsPage,
+    SplitsPublication,
+};
 pub use metastore_resolver::{
     quickwit_metastore_uri_resolver, MetastoreFactory, MetastoreUriResolver,
 };
 use quickwit_common::is_disjoint;
 use quickwit_doc_mapper::tag_pruning::TagFilterAst;
+use quickwit_proto::metastore_api::DeleteTask;
 pub use split_metadata::{Split, SplitMetadata, SplitState};
 pub(crate) use split_metadata_version::{SplitMetadataV0_6, VersionedSplitMetadata};
 
 #[derive(utoipa::OpenApi)]
 #[openapi(components(schemas(
+    AuditEvent,
     Split,
     SplitState,
+    SplitFieldStats,
+    IndexFieldStats,
     VersionedIndexMetadata,
     IndexMetadataV0_6,
     VersionedSplitMetadata,
@@ -90,6 +104,34 @@ pub fn split_tag_filter(split: &Split, tags_filter_opt: Option<&TagFilterAst>) -
         .unwrap_or(true)
 }
 
+/// Returns `false` if the bloom filters of the split guarantee that none of its documents match
+/// the tags filter. If `tags_filter_opt` is None, returns always true.
+pub fn split_bloom_filter(
+    split_metadata: &SplitMetadata,
+    tags_filter_opt: Option<&TagFilterAst>,
+) -> bool {
+    if split_metadata.bloom_filters.is_empty() {
+        return true;
+    }
+    tags_filter_opt
+        .map(|tags_filter_ast| {
+            tags_filter_ast.evaluate_bloom_filters(&split_metadata.bloom_filters)
+        })
+        .unwrap_or(true)
+}
+
+/// Returns `true` if the delete task has already been applied to the split, i.e. the delete task
+/// pipeline has processed the split with the delete task.
+///
+/// Splits produced by indexing after the delete task was created are stamped with its opstamp
+/// without the delete task ever being applied to them and are therefore not taken into account.
+pub fn split_delete_task_applied(split: &Split, delete_task: &DeleteTask) -> bool {
+    let split_metadata = &split.split_metadata;
+    split_metadata.delete_opstamp >= delete_task.opstamp
+        && (split_metadata.num_merge_ops > 0
+            || split_metadata.create_timestamp < delete_task.create_timestamp)
+}
+
 #[cfg(test)]
 mod backward_compatibility_tests;
 
@@ -0,0 +1,846 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use once_cell::sync::OnceCell;
+use quickwit_common::net::get_hostname;
+use quickwit_common::uri::Uri;
+use quickwit_config::{ClusterSettings, IndexConfig, SourceConfig};
+use quickwit_proto::metastore_api::{DeleteQuery, DeleteTask};
+use quickwit_proto::IndexUid;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tracing::error;
+
+use crate::checkpoint::{IndexCheckpointDelta, SourceCheckpoint};
+use crate::{
+    IndexMetadata, ListSplitsQuery, Metastore, MetastoreResult, Split, SplitMetadata, SplitsPage,
+    SplitsPublication,
+};
+
+/// An entry of the audit log, recording a mutation applied to an index.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct AuditEvent {
+    /// UTC Unix timestamp (in seconds) at which the mutation was applied.
+    pub timestamp: i64,
+    /// Identity of the actor that applied the mutation.
+    pub actor: String,
+    /// UID of the mutated index.
+    #[schema(value_type = String)]
+    pub index_uid: IndexUid,
+    /// The mutation applied to the index, identified by the `operation` field.
+    #[serde(flatten)]
+    #[schema(value_type = Object)]
+    pub operation: AuditOperation,
+}
+
+impl AuditEvent {
+    /// Creates an audit event timestamped with the current time.
+    pub fn new(actor: impl Into<String>, index_uid: IndexUid, operation: AuditOperation) -> Self {
+        Self {
+            timestamp: OffsetDateTime::now_utc().unix_timestamp(),
+            actor: actor.into(),
+            index_uid,
+            operation,
+        }
+    }
+}
+
+/// Mutations recorded in the audit log.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum AuditOperation {
+    /// The index was created.
+    CreateIndex,
+    /// The index config was updated.
+    UpdateIndexConfig {
+        /// Version of the index config after the update.
+        config_version: u64,
+    },
+    /// The index was deleted.
+    DeleteIndex,
+    /// The index was moved to the trash.
+    TrashIndex,
+    /// The index was taken out of the trash.
+    RestoreIndex,
+    /// The merges of the index were paused or resumed.
+    SetMergesPaused {
+        /// Whether the merges were paused or resumed.
+        paused: bool,
+    },
+    /// Splits were staged.
+    StageSplits {
+        /// IDs of the staged splits.
+        split_ids: Vec<String>,
+    },
+    /// Splits were published.
+    PublishSplits {
+        /// IDs of the published splits.
+        split_ids: Vec<String>,
+        /// IDs of the splits replaced by the published splits.
+        replaced_split_ids: Vec<String>,
+        /// Checkpoint delta applied along with the publication.
+        #[serde(default)]
+        #[serde(skip_serializing_if = "Option::is_none")]
+        checkpoint_delta: Option<IndexCheckpointDelta>,
+    },
+    /// Splits were marked for deletion.
+    MarkSplitsForDeletion {
+        /// IDs of the splits marked for deletion.
+        split_ids: Vec<String>,
+    },
+    /// Splits were deleted.
+    DeleteSplits {
+        /// IDs of the deleted splits.
+        split_ids: Vec<String>,
+    },
+    /// A source was added.
+    AddSource {
+        /// ID of the added source.
+        source_id: String,
+    },
+    /// A source was enabled or disabled.
+    ToggleSource {
+        /// ID of the toggled source.
+        source_id: String,
+        /// Whether the source was enabled or disabled.
+        enable: bool,
+    },
+    /// The checkpoint of a source was reset.
+    ResetSourceCheckpoint {
+        /// ID of the source.
+        source_id: String,
+    },
+    /// The checkpoint of a source was rewound.
+    RewindSourceCheckpoint {
+        /// ID of the source.
+        source_id: String,
+        /// Positions the checkpoint was rewound to.
+        target_checkpoint: SourceCheckpoint,
+    },
+    /// A source was deleted.
+    DeleteSource {
+        /// ID of the deleted source.
+        source_id: String,
+    },
+    /// A delete task was created.
+    CreateDeleteTask {
+        /// Opstamp of the created delete task.
+        opstamp: u64,
+    },
+    /// A delete task was cancelled.
+    CancelDeleteTask {
+        /// Opstamp of the cancelled delete task.
+        opstamp: u64,
+    },
+    /// The delete opstamp of splits was updated.
+    UpdateSplitsDeleteOpstamp {
+        /// IDs of the updated splits.
+        split_ids: Vec<String>,
+        /// New delete opstamp of the splits.
+        delete_opstamp: u64,
+    },
+}
+
+impl AuditOperation {
+    /// Returns whether the operation left the index untouched, for instance when marking an empty
+    /// list of splits for deletion. Such operations are not recorded.
+    fn is_noop(&self) -> bool {
+        match self {
+            AuditOperation::StageSplits { split_ids }
+            | AuditOperation::MarkSplitsForDeletion { split_ids }
+            | AuditOperation::DeleteSplits { split_ids }
+            | AuditOperation::UpdateSplitsDeleteOpstamp { split_ids, .. } => split_ids.is_empty(),
+            // Publishing no split may still advance the checkpoint of a source.
+            _ => false,
+        }
+    }
+}
+
+fn to_owned_split_ids(split_ids: &[&str]) -> Vec<String> {
+    split_ids
+        .iter()
+        .map(|split_id| split_id.to_string())
+        .collect()
+}
+
+/// Returns the positions to rewind the checkpoint of the source `source_id` of the index
+/// `index_uid` to, in order to restore the checkpoint as of `timestamp` (UTC Unix timestamp in
+/// seconds), based on the checkpoint deltas recorded in the audit log.
+///
+/// For each partition, the target position is the starting position of the first checkpoint
+/// delta published at or after `timestamp`. The partitions that did not move since then are
+/// absent from the returned checkpoint.
+pub fn checkpoint_rewind_target_at(
+    audit_events: &[AuditEvent],
+    index_uid: &IndexUid,
+    source_id: &str,
+    timestamp: i64,
+) -> SourceCheckpoint {
+    let mut per_partition = BTreeMap::new();
+
+    for audit_event in audit_events {
+        if audit_event.timestamp < timestamp || audit_event.index_uid != *index_uid {
+            continue;
+        }
+        let AuditOperation::PublishSplits {
+            checkpoint_delta: Some(checkpoint_delta),
+            ..
+        } = &audit_event.operation
+        else {
+            continue;
+        };
+        if checkpoint_delta.source_id != source_id {
+            continue;
+        }
+        for (partition_id, position) in checkpoint_delta
+            .source_delta
+            .get_starting_source_checkpoint()
+            .iter()
+        {
+            per_partition.entry(partition_id).or_insert(position);
+        }
+    }
+    per_partition.into_iter().collect()
+}
+
+/// gRPC metadata key carrying the actor on whose behalf a request is sent to the metastore service
+/// of another node.
+pub const AUDIT_ACTOR_METADATA_KEY: &str = "x-quickwit-audit-actor";
+
+tokio::task_local! {
+    static AUDIT_ACTOR: String;
+}
+
+static DEFAULT_AUDIT_ACTOR: OnceCell<String> = OnceCell::new();
+
+/// Runs `future`, recording the mutations it applies through an [`AuditLogMetastore`] on behalf
+/// of `actor` instead of the default actor of the metastore. The servers run the requests of an
+/// authenticated principal with it, and the [`crate::MetastoreGrpcClient`] forwards the actor to
+/// the metastore service in the [`AUDIT_ACTOR_METADATA_KEY`] metadata of its requests.
+pub async fn with_audit_actor<F: Future>(actor: String, future: F) -> F::Output {
+    AUDIT_ACTOR.scope(actor, future).await
+}
+
+/// Returns the actor set by [`with_audit_actor`] for the current task, if any.
+pub fn current_audit_actor() -> Option<String> {
+    AUDIT_ACTOR.try_with(String::clone).ok()
+}
+
+/// Sets the actor recorded for the mutations applied outside of [`with_audit_actor`] by this
+/// process, for instance the ID of the node. Only the first call has an effect, and it must happen
+/// before the metastore is resolved.
+pub fn set_default_audit_actor(actor: impl Into<String>) {
+    let _ = DEFAULT_AUDIT_ACTOR.set(actor.into());
+}
+
+/// Returns the actor recorded for the mutations applied outside of [`with_audit_actor`] by this
+/// process: the actor set with [`set_default_audit_actor`], or the hostname of the machine.
+pub fn default_audit_actor() -> String {
+    DEFAULT_AUDIT_ACTOR
+        .get_or_init(|| get_hostname().unwrap_or_else(|_| "unknown".to_string()))
+        .clone()
+}
+
+/// Wraps a metastore and records every successful mutation in the audit log of the underlying
+/// metastore.
+///
+/// Failing to record an event is logged but does not fail the mutation, which has already been
+/// applied at that point.
+pub struct AuditLogMetastore {
+    underlying: Arc<dyn Metastore>,
+    actor: String,
+}
+
+impl AuditLogMetastore {
+    /// Creates a new audit log metastore recording mutations on behalf of `actor`, unless they
+    /// are applied within [`with_audit_actor`].
+    pub fn new(metastore: Arc<dyn Metastore>, actor: impl Into<String>) -> Self {
+        Self {
+            underlying: metastore,
+            actor: actor.into(),
+        }
+    }
+
+    async fn record(&self, index_uid: IndexUid, operation: AuditOperation) {
+        if operation.is_noop() {
+            return;
+        }
+        let actor = current_audit_actor().unwrap_or_else(|| self.actor.clone());
+        let audit_event = AuditEvent::new(actor, index_uid, operation);
+        if let Err(error) = self
+            .underlying
+            .append_audit_event(audit_event.clone())
+            .await
+        {
+            error!(audit_event=?audit_event, error=?error, "Failed to append event to audit log.");
+        }
+    }
+}
+
+impl fmt::Debug for AuditLogMetastore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuditLogMetastore")
+            .field("uri", self.underlying.uri())
+            .field("actor", &self.actor)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Metastore for AuditLogMetastore {
+    fn uri(&self) -> &Uri {
+        self.underlying.uri()
+    }
+
+    async fn check_connectivity(&self) -> anyhow::Result<()> {
+        self.underlying.check_connectivity().await
+    }
+
+    // Index API
+    async fn create_index(&self, index_config: IndexConfig) -> MetastoreResult<IndexUid> {
+        let index_uid = self.underlying.create_index(index_config).await?;
+        self.record(index_uid.clone(), AuditOperation::CreateIndex)
+            .await;
+        Ok(index_uid)
+    }
+
+    async fn index_exists(&self, index_id: &str) -> MetastoreResult<bool> {
+        self.underlying.index_exists(index_id).await
+    }
+
+    async fn index_metadata(&self, index_id: &str) -> MetastoreResult<IndexMetadata> {
+        self.underlying.index_metadata(index_id).await
+    }
+
+    async fn list_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
+        self.underlying.list_indexes_metadatas().await
+    }
+
+    async fn update_index_config(
+        &self,
+        index_uid: IndexUid,
+        index_config: IndexConfig,
+        expected_config_version_opt: Option<u64>,
+    ) -> MetastoreResult<IndexMetadata> {
+        let index_metadata = self
+            .underlying
+            .update_index_config(index_uid.clone(), index_config, expected_config_version_opt)
+            .await?;
+        let operation = AuditOperation::UpdateIndexConfig {
+            config_version: index_metadata.config_version,
+        };
+        self.record(index_uid, operation).await;
+        Ok(index_metadata)
+    }
+
+    async fn delete_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
+        self.underlying.delete_index(index_uid.clone()).await?;
+        self.record(index_uid, AuditOperation::DeleteIndex).await;
+        Ok(())
+    }
+
+    async fn trash_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
+        self.underlying.trash_index(index_uid.clone()).await?;
+        self.record(index_uid, AuditOperation::TrashIndex).await;
+        Ok(())
+    }
+
+    async fn restore_index(&self, index_uid: IndexUid) -> MetastoreResult<IndexMetadata> {
+        let index_metadata = self.underlying.restore_index(index_uid.clone()).await?;
+        self.record(index_uid, AuditOperation::RestoreIndex).await;
+        Ok(index_metadata)
+    }
+
+    async fn list_trashed_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
+        self.underlying.list_trashed_indexes_metadatas().await
+    }
+
+    async fn set_merges_paused(&self, index_uid: IndexUid, paused: bool) -> MetastoreResult<()> {
+        self.underlying
+            .set_merges_paused(index_uid.clone(), paused)
+            .await?;
+        self.record(index_uid, AuditOperation::SetMergesPaused { paused })
+            .await;
+        Ok(())
+    }
+
+    // Tenant API
+
+    async fn list_tenants(&self) -> MetastoreResult<Vec<String>> {
+        self.underlying.list_tenants().await
+    }
+
+    // Split API
+
+    async fn stage_splits(
+        &self,
+        index_uid: IndexUid,
+        split_metadata_list: Vec<SplitMetadata>,
+    ) -> MetastoreResult<()> {
+        let split_ids = split_metadata_list
+            .iter()
+            .map(|split_metadata| split_metadata.split_id.clone())
+            .collect();
+        self.underlying
+            .stage_splits(index_uid.clone(), split_metadata_list)
+            .await?;
+        self.record(index_uid, AuditOperation::StageSplits { split_ids })
+            .await;
+        Ok(())
+    }
+
+    async fn publish_splits<'a>(
+        &self,
+        index_uid: IndexUid,
+        split_ids: &[&'a str],
+        replaced_split_ids: &[&'a str],
+        checkpoint_delta_opt: Option<IndexCheckpointDelta>,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .publish_splits(
+                index_uid.clone(),
+                split_ids,
+                replaced_split_ids,
+                checkpoint_delta_opt.clone(),
+            )
+            .await?;
+        let operation = AuditOperation::PublishSplits {
+            split_ids: to_owned_split_ids(split_ids),
+            replaced_split_ids: to_owned_split_ids(replaced_split_ids),
+            checkpoint_delta: checkpoint_delta_opt,
+        };
+        self.record(index_uid, operation).await;
+        Ok(())
+    }
+
+    async fn publish_splits_across_indexes(
+        &self,
+        publications: Vec<SplitsPublication>,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .publish_splits_across_indexes(publications.clone())
+            .await?;
+        for publication in publications {
+            let operation = AuditOperation::PublishSplits {
+                split_ids: publication.staged_split_ids,
+                replaced_split_ids: publication.replaced_split_ids,
+                checkpoint_delta: publication.checkpoint_delta_opt,
+            };
+            self.record(publication.index_uid, operation).await;
+        }
+        Ok(())
+    }
+
+    async fn list_splits(&self, query: ListSplitsQuery) -> MetastoreResult<Vec<Split>> {
+        self.underlying.list_splits(query).await
+    }
+
+    async fn list_splits_page(
+        &self,
+        query: ListSplitsQuery,
+        page_size: usize,
+    ) -> MetastoreResult<SplitsPage> {
+        self.underlying.list_splits_page(query, page_size).await
+    }
+
+    async fn list_all_splits(&self, index_uid: IndexUid) -> MetastoreResult<Vec<Split>> {
+        self.underlying.list_all_splits(index_uid).await
+    }
+
+    async fn mark_splits_for_deletion<'a>(
+        &self,
+        index_uid: IndexUid,
+        split_ids: &[&'a str],
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .mark_splits_for_deletion(index_uid.clone(), split_ids)
+            .await?;
+        let operation = AuditOperation::MarkSplitsForDeletion {
+            split_ids: to_owned_split_ids(split_ids),
+        };
+        self.record(index_uid, operation).await;
+        Ok(())
+    }
+
+    async fn delete_splits<'a>(
+        &self,
+        index_uid: IndexUid,
+        split_ids: &[&'a str],
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .delete_splits(index_uid.clone(), split_ids)
+            .await?;
+        let operation = AuditOperation::DeleteSplits {
+            split_ids: to_owned_split_ids(split_ids),
+        };
+        self.record(index_uid, operation).await;
+        Ok(())
+    }
+
+    // Source API
+
+    async fn add_source(&self, index_uid: IndexUid, source: SourceConfig) -> MetastoreResult<()> {
+        let source_id = source.source_id.clone();
+        self.underlying
+            .add_source(index_uid.clone(), source)
+            .await?;
+        self.record(index_uid, AuditOperation::AddSource { source_id })
+            .await;
+        Ok(())
+    }
+
+    async fn toggle_source(
+        &self,
+        index_uid: IndexUid,
+        source_id: &str,
+        enable: bool,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .toggle_source(index_uid.clone(), source_id, enable)
+            .await?;
+        let operation = AuditOperation::ToggleSource {
+            source_id: source_id.to_string(),
+            enable,
+        };
+        self.record(index_uid, operation).await;
+        Ok(())
+    }
+
+    async fn reset_source_checkpoint(
+        &self,
+        index_uid: IndexUid,
+        source_id: &str,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .reset_source_checkpoint(index_uid.clone(), source_id)
+            .await?;
+        let operation = AuditOperation::ResetSourceCheckpoint {
+            source_id: source_id.to_string(),
+        };
+        self.record(index_uid, operation).await;
+        Ok(())
+    }
+
+    async fn rewind_source_checkpoint(
+        &self,
+        index_uid: IndexUid,
+        source_id: &str,
+        target_checkpoint: SourceCheckpoint,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .rewind_source_checkpoint(index_uid.clone(), source_id, target_checkpoint.clone())
+            .await?;
+        let operation = AuditOperation::RewindSourceCheckpoint {
+            source_id: source_id.to_string(),
+            target_checkpoint,
+        };
+        self.record(index_uid, operation).await;
+        Ok(())
+    }
+
+    async fn delete_source(&self, index_uid: IndexUid, source_id: &str) -> MetastoreResult<()> {
+        self.underlying
+            .delete_source(index_uid.clone(), source_id)
+            .await?;
+        let operation = AuditOperation::DeleteSource {
+            source_id: source_id.to_string(),
+        };
+        self.record(index_uid, operation).await;
+        Ok(())
+    }
+
+    // Delete task API
+    async fn create_delete_task(&self, delete_query: DeleteQuery) -> MetastoreResult<DeleteTask> {
+        let index_uid: IndexUid = delete_query.index_uid.clone().into();
+        let delete_task = self.underlying.create_delete_task(delete_query).await?;
+        let operation = AuditOperation::CreateDeleteTask {
+            opstamp: delete_task.opstamp,
+        };
+        self.record(index_uid, operation).await;
+        Ok(delete_task)
+    }
+
+    async fn list_delete_tasks(
+        &self,
+        index_uid: IndexUid,
+        opstamp_start: u64,
+    ) -> MetastoreResult<Vec<DeleteTask>> {
+        self.underlying
+            .list_delete_tasks(index_uid, opstamp_start)
+            .await
+    }
+
+    async fn cancel_delete_task(
+        &self,
+        index_uid: IndexUid,
+        opstamp: u64,
+    ) -> MetastoreResult<DeleteTask> {
+        let delete_task = self
+            .underlying
+            .cancel_delete_task(index_uid.clone(), opstamp)
+            .await?;
+        let operation = AuditOperation::CancelDeleteTask { opstamp };
+        self.record(index_uid, operation).await;
+        Ok(delete_task)
+    }
+
+    async fn last_delete_opstamp(&self, index_uid: IndexUid) -> MetastoreResult<u64> {
+        self.underlying.last_delete_opstamp(index_uid).await
+    }
+
+    async fn update_splits_delete_opstamp<'a>(
+        &self,
+        index_uid: IndexUid,
+        split_ids: &[&'a str],
+        delete_opstamp: u64,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .update_splits_delete_opstamp(index_uid.clone(), split_ids, delete_opstamp)
+            .await?;
+        let operation = AuditOperation::UpdateSplitsDeleteOpstamp {
+            split_ids: to_owned_split_ids(split_ids),
+            delete_opstamp,
+        };
+        self.record(index_uid, operation).await;
+        Ok(())
+    }
+
+    async fn list_stale_splits(
+        &self,
+        index_uid: IndexUid,
+        delete_opstamp: u64,
+        num_splits: usize,
+    ) -> MetastoreResult<Vec<Split>> {
+        self.underlying
+            .list_stale_splits(index_uid, delete_opstamp, num_splits)
+            .await
+    }
+
+    // Audit log API
+
+    async fn append_audit_event(&self, audit_event: AuditEvent) -> MetastoreResult<()> {
+        self.underlying.append_audit_event(audit_event).await
+    }
+
+    async fn list_audit_events(&self, index_id: &str) -> MetastoreResult<Vec<AuditEvent>> {
+        self.underlying.list_audit_events(index_id).await
+    }
+
+    // Cluster settings API
+
+    async fn cluster_settings(&self) -> MetastoreResult<ClusterSettings> {
+        self.underlying.cluster_settings().await
+    }
+
+    async fn update_cluster_settings(
+        &self,
+        cluster_settings: ClusterSettings,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .update_cluster_settings(cluster_settings)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_config::SourceParams;
+
+    use super::*;
+    use crate::checkpoint::{PartitionId, Position, SourceCheckpointDelta};
+    use crate::metastore_for_test;
+    use crate::tests::test_suite::DefaultForTest;
+
+    #[async_trait]
+    impl DefaultForTest for AuditLogMetastore {
+        async fn default_for_test() -> Self {
+            AuditLogMetastore::new(metastore_for_test(), "test-node")
+        }
+    }
+
+    metastore_test_suite!(crate::metastore::audit_log_metastore::AuditLogMetastore);
+
+    #[tokio::test]
+    async fn test_audit_log_metastore_records_mutations() {
+        let metastore = AuditLogMetastore::default_for_test().await;
+
+        let index_id = "test-audit-log-metastore";
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let source_id = "test-source";
+
+        let index_uid = metastore
+            .create_index(IndexConfig::for_test(index_id, &index_uri))
+            .await
+            .unwrap();
+        metastore
+            .add_source(
+                index_uid.clone(),
+                SourceConfig::for_test(source_id, SourceParams::void()),
+            )
+            .await
+            .unwrap();
+        metastore
+            .toggle_source(index_uid.clone(), source_id, false)
+            .await
+            .unwrap();
+        metastore
+            .delete_source(index_uid.clone(), source_id)
+            .await
+            .unwrap();
+        // Failed mutations are not recorded.
+        metastore
+            .delete_source(index_uid.clone(), source_id)
+            .await
+            .unwrap_err();
+        metastore.delete_index(index_uid.clone()).await.unwrap();
+
+        let audit_events = metastore.list_audit_events(index_id).await.unwrap();
+        assert!(audit_events
+            .iter()
+            .all(|audit_event| audit_event.actor == "test-node"
+                && audit_event.index_uid == index_uid));
+
+        let operations: Vec<AuditOperation> = audit_events
+            .into_iter()
+            .map(|audit_event| audit_event.operation)
+            .collect();
+        assert_eq!(
+            operations,
+            [
+                AuditOperation::CreateIndex,
+                AuditOperation::AddSource {
+                    source_id: source_id.to_string()
+                },
+                AuditOperation::ToggleSource {
+                    source_id: source_id.to_string(),
+                    enable: false,
+                },
+                AuditOperation::DeleteSource {
+                    source_id: source_id.to_string()
+                },
+                AuditOperation::DeleteIndex,
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_metastore_records_audit_actor() {
+        let metastore = AuditLogMetastore::default_for_test().await;
+
+        let index_id = "test-audit-log-metastore-actor";
+        let index_uri = format!("ram:///indexes/{index_id}");
+
+        let index_uid = with_audit_actor(
+            "team-a".to_string(),
+            metastore.create_index(IndexConfig::for_test(index_id, &index_uri)),
+        )
+        .await
+        .unwrap();
+        metastore.delete_index(index_uid).await.unwrap();
+
+        let actors: Vec<String> = metastore
+            .list_audit_events(index_id)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|audit_event| audit_event.actor)
+            .collect();
+        assert_eq!(actors, ["team-a", "test-node"]);
+    }
+
+    #[test]
+    fn test_checkpoint_rewind_target_at() {
+        let index_uid = IndexUid::from_parts("test-index", "11111111111111111111111111");
+        let publish_event = |timestamp: i64, source_id: &str, partition_id: &str, from: u64| {
+            let source_delta = SourceCheckpointDelta::from_partition_delta(
+                PartitionId::from(partition_id),
+                Position::from(from),
+                Position::from(from + 10),
+            )
+            .unwrap();
+            AuditEvent {
+                timestamp,
+                actor: "test-node".to_string(),
+                index_uid: index_uid.clone(),
+                operation: AuditOperation::PublishSplits {
+                    split_ids: vec!["test-split".to_string()],
+                    replaced_split_ids: Vec::new(),
+                    checkpoint_delta: Some(IndexCheckpointDelta {
+                        source_id: source_id.to_string(),
+                        source_delta,
+                    }),
+                },
+            }
+        };
+        let audit_events = vec![
+            publish_event(10, "test-source", "partition-1", 0),
+            publish_event(20, "test-source", "partition-1", 10),
+            publish_event(20, "other-source", "partition-2", 0),
+            publish_event(30, "test-source", "partition-2", 0),
+            publish_event(40, "test-source", "partition-1", 20),
+        ];
+        let target_checkpoint =
+            checkpoint_rewind_target_at(&audit_events, &index_uid, "test-source", 15);
+        let expected_checkpoint: SourceCheckpoint = vec![
+            (PartitionId::from("partition-1"), Position::from(10u64)),
+            (PartitionId::from("partition-2"), Position::from(0u64)),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(target_checkpoint, expected_checkpoint);
+
+        let target_checkpoint =
+            checkpoint_rewind_target_at(&audit_events, &index_uid, "test-source", 50);
+        assert!(target_checkpoint.is_empty());
+    }
+
+    #[test]
+    fn test_audit_event_serde() {
+        let audit_event = AuditEvent {
+            timestamp: 1640577000,
+            actor: "test-node".to_string(),
+            index_uid: IndexUid::from_parts("test-index", "11111111111111111111111111"),
+            operation: AuditOperation::ToggleSource {
+                source_id: "test-source".to_string(),
+                enable: true,
+            },
+        };
+        let audit_event_json = serde_json::to_value(&audit_event).unwrap();
+        assert_eq!(
+            audit_event_json,
+            serde_json::json!({
+                "timestamp": 1640577000,
+                "actor": "test-node",
+                "index_uid": "test-index:11111111111111111111111111",
+                "operation": "toggle_source",
+                "source_id": "test-source",
+                "enable": true,
+            })
+        );
+        let deserialized_audit_event: AuditEvent =
+            serde_json::from_value(audit_event_json).unwrap();
+        assert_eq!(deserialized_audit_event, audit_event);
+    }
+}
@@ -27,7 +27,7 @@ use std::collections::HashMap;
 use std::fmt::Debug;
 
 use quickwit_common::PrettySample;
-use quickwit_config::SourceConfig;
+use quickwit_config::{IndexConfig, SourceConfig};
 use quickwit_proto::metastore_api::{DeleteQuery, DeleteTask};
 use quickwit_proto::IndexUid;
 use serde::{Deserialize, Serialize};
@@ -35,10 +35,10 @@ use serialize::VersionedFileBackedIndex;
 use time::OffsetDateTime;
 use tracing::{info, warn};
 
-use crate::checkpoint::IndexCheckpointDelta;
+use crate::checkpoint::{IndexCheckpointDelta, SourceCheckpoint};
 use crate::{
-    split_tag_filter, IndexMetadata, ListSplitsQuery, MetastoreError, MetastoreResult, Split,
-    SplitMetadata, SplitState,
+    split_delete_task_applied, split_tag_filter, IndexMetadata, ListSplitsQuery, MetastoreError,
+    MetastoreResult, Split, SplitMetadata, SplitState,
 };
 
 /// A `FileBackedIndex` object carries an index metadata and its split metadata.
@@ -122,9 +122,16 @@ enum DeleteSplitOutcome {
 impl FileBackedIndex {
     /// Constructor.
     pub fn new(metadata: IndexMetadata, splits: Vec<Split>, delete_tasks: Vec<DeleteTask>) -> Self {
+        // Splits may carry the opstamp of a delete task that has since been cancelled: taking
+        // them into account prevents the opstamp from being reused for a new delete task.
         let last_opstamp = delete_tasks
             .iter()
             .map(|delete_task| delete_task.opstamp)
+            .chain(
+                splits
+                    .iter()
+                    .map(|split| split.split_metadata.delete_opstamp),
+            )
             .max()
             .unwrap_or(0) as usize;
         Self {
@@ -271,9 +278,9 @@ impl FileBackedIndex {
         for &split_id in split_ids {
             // Check for the existence of split.
             let Some(metadata) = self.splits.get_mut(split_id) else {
-                    split_not_found_ids.push(split_id.to_string());
-                    continue;
-                };
+                split_not_found_ids.push(split_id.to_string());
+                continue;
+            };
             if metadata.split_state == SplitState::Staged {
                 metadata.split_state = SplitState::Published;
                 metadata.update_timestamp = now_timestamp;
@@ -318,15 +325,28 @@ impl FileBackedIndex {
         let limit = query.limit.unwrap_or(usize::MAX);
         let offset = query.offset.unwrap_or_default();
 
-        let splits: Vec<Split> = self
+        let mut matching_splits: Vec<&Split> = self
             .splits
             .values()
             .filter(|split| split_query_predicate(split, &query))
+            .collect();
+
+        if query.sort_by_split_id {
+            matching_splits.sort_unstable_by(|left, right| left.split_id().cmp(right.split_id()));
+        }
+        let mut splits: Vec<Split> = matching_splits
+            .into_iter()
             .skip(offset)
             .take(limit)
             .cloned()
             .collect();
 
+        if !query.with_bloom_filters {
+            for split in &mut splits {
+                split.split_metadata.bloom_filters.clear();
+            }
+        }
+
         Ok(splits)
     }
 
@@ -376,6 +396,31 @@ impl FileBackedIndex {
         Ok(())
     }
 
+    /// Updates the index config.
+    pub(crate) fn update_index_config(
+        &mut self,
+        index_config: IndexConfig,
+        expected_config_version_opt: Option<u64>,
+    ) -> MetastoreResult<()> {
+        self.metadata
+            .update_index_config(index_config, expected_config_version_opt)
+    }
+
+    /// Moves the index to the trash. Returns whether a mutation occurred.
+    pub(crate) fn trash(&mut self) -> bool {
+        self.metadata.trash()
+    }
+
+    /// Takes the index out of the trash. Returns whether a mutation occurred.
+    pub(crate) fn restore(&mut self) -> bool {
+        self.metadata.restore()
+    }
+
+    /// Pauses or resumes the merges of the index. Returns whether a mutation occurred.
+    pub(crate) fn set_merges_paused(&mut self, paused: bool) -> bool {
+        self.metadata.set_merges_paused(paused)
+    }
+
     /// Adds a source.
     pub(crate) fn add_source(&mut self, source: SourceConfig) -> MetastoreResult<()> {
         self.metadata.add_source(source)
@@ -395,6 +440,16 @@ impl FileBackedIndex {
         Ok(self.metadata.checkpoint.reset_source(source_id))
     }
 
+    /// Rewinds the checkpoint of a source. Returns whether a mutation occurred.
+    pub(crate) fn rewind_source_checkpoint(
+        &mut self,
+        source_id: &str,
+        target_checkpoint: &SourceCheckpoint,
+    ) -> MetastoreResult<bool> {
+        self.metadata
+            .rewind_source_checkpoint(source_id, target_checkpoint)
+    }
+
     /// Creates [`DeleteTask`] from a [`DeleteQuery`].
     pub(crate) fn create_delete_task(
         &mut self,
@@ -447,6 +502,24 @@ impl FileBackedIndex {
             .collect();
         Ok(delete_tasks)
     }
+
+    /// Cancels the delete task with opstamp `opstamp` if it has not been applied to any split yet.
+    pub(crate) fn cancel_delete_task(&mut self, opstamp: u64) -> MetastoreResult<DeleteTask> {
+        let position = self
+            .delete_tasks
+            .iter()
+            .position(|delete_task| delete_task.opstamp == opstamp)
+            .ok_or(MetastoreError::DeleteTaskDoesNotExist { opstamp })?;
+        let delete_task = &self.delete_tasks[position];
+        if self
+            .splits
+            .values()
+            .any(|split| split_delete_task_applied(split, delete_task))
+        {
+            return Err(MetastoreError::DeleteTaskNotCancellable { opstamp });
+        }
+        Ok(self.delete_tasks.remove(position))
+    }
 }
 
 /// Stamper provides Opstamps, which is just an auto-increment id to label
@@ -482,6 +555,20 @@ fn split_query_predicate(split: &&Split, query: &ListSplitsQuery) -> bool {
         return false;
     }
 
+    if !query
+        .labels
+        .iter()
+        .all(|(key, value)| split.split_metadata.labels.get(key) == Some(value))
+    {
+        return false;
+    }
+
+    if let Some(after_split_id) = query.after_split_id.as_deref() {
+        if split.split_id() <= after_split_id {
+            return false;
+        }
+    }
+
     if !query
         .delete_opstamp
         .contains(&split.split_metadata.delete_opstamp)
@@ -24,11 +24,13 @@ use std::time::Duration;
 use async_trait::async_trait;
 use once_cell::sync::OnceCell;
 use quickwit_common::uri::Uri;
+use quickwit_config::MetastoreConfig;
 use quickwit_storage::{quickwit_storage_uri_resolver, StorageResolverError, StorageUriResolver};
 use regex::Regex;
 use tokio::sync::Mutex;
 use tracing::debug;
 
+use crate::metastore::file_backed_metastore::store_operations::check_indexes_states_exist;
 use crate::metastore::instrumented_metastore::InstrumentedMetastore;
 use crate::{
     FileBackedMetastore, Metastore, MetastoreError, MetastoreFactory, MetastoreResolverError,
@@ -147,6 +149,19 @@ impl MetastoreFactory for FileBackedMetastoreFactory {
             .await;
         Ok(unique_metastore_for_uri)
     }
+
+    /// Only checks that the underlying storage is reachable: unlike `resolve`, it does not
+    /// initialize the indexes states file.
+    async fn check_connectivity(
+        &self,
+        _metastore_config: &MetastoreConfig,
+        uri: &Uri,
+    ) -> anyhow::Result<()> {
+        let (uri_stripped, _polling_interval_opt) = extract_polling_interval_from_uri(uri.as_str());
+        let uri = Uri::from_well_formed(uri_stripped);
+        let storage = self.storage_uri_resolver.resolve(&uri)?;
+        check_indexes_states_exist(storage).await
+    }
 }
 
 #[cfg(test)]
@@ -26,32 +26,38 @@ mod file_backed_metastore_factory;
 mod lazy_file_backed_index;
 mod store_operations;
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::Duration;
 
 use async_trait::async_trait;
 use futures::future::try_join_all;
 use quickwit_common::uri::Uri;
-use quickwit_config::{IndexConfig, SourceConfig};
+use quickwit_config::{ClusterSettings, IndexConfig, SourceConfig};
 use quickwit_proto::metastore_api::{DeleteQuery, DeleteTask};
 use quickwit_proto::IndexUid;
 use quickwit_storage::Storage;
 use tokio::sync::{Mutex, OwnedMutexGuard, RwLock};
+use tracing::error;
 
 use self::file_backed_index::FileBackedIndex;
 pub use self::file_backed_metastore_factory::FileBackedMetastoreFactory;
 use self::lazy_file_backed_index::LazyFileBackedIndex;
 use self::store_operations::{
-    check_indexes_states_exist, delete_index, fetch_index, fetch_or_init_indexes_states,
-    index_exists, put_index, put_indexes_states,
+    check_indexes_states_exist, delete_index, fetch_audit_events, fetch_cluster_settings,
+    fetch_index, fetch_or_init_indexes_states, index_exists, put_audit_events,
+    put_cluster_settings, put_index, put_indexes_states,
 };
-use crate::checkpoint::IndexCheckpointDelta;
+use crate::checkpoint::{IndexCheckpointDelta, SourceCheckpoint};
 use crate::{
-    IndexMetadata, ListSplitsQuery, Metastore, MetastoreError, MetastoreResult, Split,
-    SplitMetadata, SplitState,
+    AuditEvent, IndexMetadata, ListSplitsQuery, Metastore, MetastoreError, MetastoreResult, Split,
+    SplitMetadata, SplitState, SplitsPublication,
 };
 
+/// Number of events kept in the audit log of an index. The whole audit log file is rewritten on
+/// every append, so its size must remain bounded.
+const MAX_AUDIT_EVENTS_PER_INDEX: usize = 10_000;
+
 /// State of an index tracked by the metastore.
 pub(crate) enum IndexState {
     /// Index is being created but its metadata has not been created on the storage yet.
@@ -114,6 +120,10 @@ pub struct FileBackedMetastore {
     storage: Arc<dyn Storage>,
     per_index_metastores: Arc<RwLock<HashMap<String, IndexState>>>,
     polling_interval_opt: Option<Duration>,
+    // Serializes the read-modify-write cycles of the audit log files.
+    audit_log_mutex: Arc<Mutex<()>>,
+    // Number of events kept in the audit log of an index, the oldest ones being dropped first.
+    max_audit_events_per_index: usize,
 }
 
 impl FileBackedMetastore {
@@ -124,6 +134,8 @@ impl FileBackedMetastore {
             storage,
             per_index_metastores: Default::default(),
             polling_interval_opt: None,
+            audit_log_mutex: Default::default(),
+            max_audit_events_per_index: MAX_AUDIT_EVENTS_PER_INDEX,
         }
     }
 
@@ -152,6 +164,8 @@ impl FileBackedMetastore {
             storage,
             per_index_metastores,
             polling_interval_opt,
+            audit_log_mutex: Default::default(),
+            max_audit_events_per_index: MAX_AUDIT_EVENTS_PER_INDEX,
         })
     }
 
@@ -174,18 +188,30 @@ impl FileBackedMetastore {
                 return Ok(value);
             }
         };
+        self.put_locked_index(&mut locked_index, index).await?;
+        Ok(value)
+    }
+
+    /// Persists `index` on the storage and, on success, replaces the content of `locked_index`
+    /// with it. On failure, the cached index is discarded.
+    async fn put_locked_index(
+        &self,
+        locked_index: &mut FileBackedIndex,
+        index: FileBackedIndex,
+    ) -> MetastoreResult<()> {
         locked_index.set_recently_modified();
         let put_result = put_index(&*self.storage, &index).await;
         match put_result {
             Ok(()) => {
                 *locked_index = index;
-                Ok(value)
+                Ok(())
             }
             Err(err) => {
                 // For some of the error type here, we cannot know for sure
                 // whether the content was written or not.
                 //
                 // Just to be sure, let's discard the cache.
+                let index_id = index.index_id();
                 let mut per_index_metastores_wlock = self.per_index_metastores.write().await;
 
                 // At this point, we hold both locks.
@@ -205,6 +231,39 @@ impl FileBackedMetastore {
         }
     }
 
+    /// Restores the indexes published by a failed call to `publish_splits_across_indexes`. If a
+    /// restore fails too, the publication is partially applied and an error is returned.
+    async fn rollback_publications(
+        &self,
+        published_indexes: Vec<(OwnedMutexGuard<FileBackedIndex>, FileBackedIndex)>,
+    ) -> MetastoreResult<()> {
+        let mut failed_index_ids = Vec::new();
+
+        for (mut locked_index, previous_index) in published_indexes {
+            if let Err(error) = self
+                .put_locked_index(&mut locked_index, previous_index)
+                .await
+            {
+                error!(
+                    index_id=%locked_index.index_id(),
+                    error=?error,
+                    "Failed to roll back split publication."
+                );
+                failed_index_ids.push(locked_index.index_id().to_string());
+            }
+        }
+        if !failed_index_ids.is_empty() {
+            return Err(MetastoreError::InternalError {
+                message: "Failed to roll back split publication across indexes.".to_string(),
+                cause: format!(
+                    "Splits remain published to indexes `{}`.",
+                    failed_index_ids.join(", ")
+                ),
+            });
+        }
+        Ok(())
+    }
+
     async fn read<T, F>(&self, index_uid: IndexUid, view: F) -> MetastoreResult<T>
     where F: FnOnce(&FileBackedIndex) -> MetastoreResult<T> {
         let index_id = index_uid.index_id();
@@ -291,6 +350,54 @@ impl FileBackedMetastore {
         Ok(index_mutex)
     }
 
+    /// Returns the metadata of an index, whether it is trashed or not.
+    async fn any_index_metadata(&self, index_id: &str) -> MetastoreResult<IndexMetadata> {
+        self.read_any(index_id, |index| Ok(index.metadata().clone()))
+            .await
+    }
+
+    /// Lists the metadata of all the indexes, including the trashed ones.
+    async fn list_any_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
+        // Done in two steps:
+        // 1) Get index IDs and release the lock on `per_index_metastores`.
+        // 2) Get each index metadata. Note that each get will take a read lock on
+        // `per_index_metastores`. Lock is released in 1) to let a concurrent task/thread to
+        // take a write lock on `per_index_metastores`.
+        let index_ids: Vec<String> = {
+            let per_index_metastores_rlock = self.per_index_metastores.read().await;
+            per_index_metastores_rlock
+                .iter()
+                .flat_map(|(index_id, index_state)| match index_state {
+                    IndexState::Alive(_) => Some(index_id),
+                    _ => None,
+                })
+                .cloned()
+                .collect()
+        };
+        let indexes_metadatas: Vec<IndexMetadata> =
+            try_join_all(index_ids.iter().map(|index_id| async move {
+                match self.any_index_metadata(index_id).await {
+                    Ok(index_metadata) => Ok(Some(index_metadata)),
+                    Err(MetastoreError::IndexDoesNotExist { index_id: _ }) => Ok(None),
+                    Err(MetastoreError::InternalError { message, cause }) => {
+                        // Indexes can be in a transition state `Creating` or `Deleting`.
+                        // This is fine to ignore them.
+                        if cause.contains("is in transitioning state") {
+                            Ok(None)
+                        } else {
+                            Err(MetastoreError::InternalError { message, cause })
+                        }
+                    }
+                    Err(error) => Err(error),
+                }
+            }))
+            .await?
+            .into_iter()
+            .flatten()
+            .collect();
+        Ok(indexes_metadatas)
+    }
+
     /// Helper used for testing to obtain the data associated with the given index.
     #[cfg(test)]
     async fn get_index(&self, index_uid: IndexUid) -> MetastoreResult<FileBackedIndex> {
@@ -415,9 +522,55 @@ impl Metastore for FileBackedMetastore {
         delete_res
     }
 
+    async fn trash_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
+        self.mutate(index_uid, |index| {
+            if index.trash() {
+                Ok(MutationOccurred::Yes(()))
+            } else {
+                Ok(MutationOccurred::No(()))
+            }
+        })
+        .await
+    }
+
+    async fn restore_index(&self, index_uid: IndexUid) -> MetastoreResult<IndexMetadata> {
+        self.mutate(index_uid, |index| {
+            if index.restore() {
+                Ok(MutationOccurred::Yes(index.metadata().clone()))
+            } else {
+                Ok(MutationOccurred::No(index.metadata().clone()))
+            }
+        })
+        .await
+    }
+
+    async fn set_merges_paused(&self, index_uid: IndexUid, paused: bool) -> MetastoreResult<()> {
+        self.mutate(index_uid, |index| {
+            if index.set_merges_paused(paused) {
+                Ok(MutationOccurred::Yes(()))
+            } else {
+                Ok(MutationOccurred::No(()))
+            }
+        })
+        .await
+    }
+
     /// -------------------------------------------------------------------------------
     /// Mutations over a single index
 
+    async fn update_index_config(
+        &self,
+        index_uid: IndexUid,
+        index_config: IndexConfig,
+        expected_config_version_opt: Option<u64>,
+    ) -> MetastoreResult<IndexMetadata> {
+        self.mutate(index_uid, |index| {
+            index.update_index_config(index_config, expected_config_version_opt)?;
+            Ok(MutationOccurred::Yes(index.metadata().clone()))
+        })
+        .await
+    }
+
     async fn stage_splits(
         &self,
         index_uid: IndexUid,
@@ -461,6 +614,63 @@ impl Metastore for FileBackedMetastore {
         Ok(())
     }
 
+    async fn publish_splits_across_indexes(
+        &self,
+        publications: Vec<SplitsPublication>,
+    ) -> MetastoreResult<()> {
+        // Indexes are locked in a consistent order to prevent concurrent calls from deadlocking.
+        let mut publications_per_index: BTreeMap<String, Vec<SplitsPublication>> = BTreeMap::new();
+        for publication in publications {
+            publications_per_index
+                .entry(publication.index_uid.index_id().to_string())
+                .or_default()
+                .push(publication);
+        }
+        let mut locked_indexes = Vec::with_capacity(publications_per_index.len());
+
+        for (index_id, publications) in publications_per_index {
+            let locked_index = self.get_locked_index(&index_id).await?;
+            let mut index = locked_index.clone();
+
+            for publication in publications {
+                if locked_index.index_uid() != publication.index_uid {
+                    return Err(MetastoreError::IndexDoesNotExist { index_id });
+                }
+                let staged_split_ids: Vec<&str> = publication
+                    .staged_split_ids
+                    .iter()
+                    .map(String::as_str)
+                    .collect();
+                let replaced_split_ids: Vec<&str> = publication
+                    .replaced_split_ids
+                    .iter()
+                    .map(String::as_str)
+                    .collect();
+                index.publish_splits(
+                    &staged_split_ids,
+                    &replaced_split_ids,
+                    publication.checkpoint_delta_opt,
+                )?;
+            }
+            locked_indexes.push((locked_index, index));
+        }
+        // All the publications are valid at this point. The file-backed metastore cannot write
+        // several index files atomically, so the indexes written before a storage error are
+        // restored to their previous state.
+        let mut published_indexes = Vec::with_capacity(locked_indexes.len());
+
+        for (mut locked_index, index) in locked_indexes {
+            let previous_index = locked_index.clone();
+
+            if let Err(error) = self.put_locked_index(&mut locked_index, index).await {
+                self.rollback_publications(published_indexes).await?;
+                return Err(error);
+            }
+            published_indexes.push((locked_index, previous_index));
+        }
+        Ok(())
+    }
+
     async fn mark_splits_for_deletion<'a>(
         &self,
         index_uid: IndexUid,
@@ -542,6 +752,21 @@ impl Metastore for FileBackedMetastore {
         Ok(())
     }
 
+    async fn rewind_source_checkpoint(
+        &self,
+        index_uid: IndexUid,
+        source_id: &str,
+        target_checkpoint: SourceCheckpoint,
+    ) -> MetastoreResult<()> {
+        self.mutate(index_uid, |index| {
+            index
+                .rewind_source_checkpoint(source_id, &target_checkpoint)
+                .map(MutationOccurred::from)
+        })
+        .await?;
+        Ok(())
+    }
+
     /// -------------------------------------------------------------------------------
     /// Read-only accessors
 
@@ -552,47 +777,31 @@ impl Metastore for FileBackedMetastore {
     }
 
     async fn index_metadata(&self, index_id: &str) -> MetastoreResult<IndexMetadata> {
-        self.read_any(index_id, |index| Ok(index.metadata().clone()))
-            .await
+        let index_metadata = self.any_index_metadata(index_id).await?;
+        if index_metadata.is_trashed() {
+            return Err(MetastoreError::IndexDoesNotExist {
+                index_id: index_id.to_string(),
+            });
+        }
+        Ok(index_metadata)
     }
 
     async fn list_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
-        // Done in two steps:
-        // 1) Get index IDs and release the lock on `per_index_metastores`.
-        // 2) Get each index metadata. Note that each get will take a read lock on
-        // `per_index_metastores`. Lock is released in 1) to let a concurrent task/thread to
-        // take a write lock on `per_index_metastores`.
-        let index_ids: Vec<String> = {
-            let per_index_metastores_rlock = self.per_index_metastores.read().await;
-            per_index_metastores_rlock
-                .iter()
-                .flat_map(|(index_id, index_state)| match index_state {
-                    IndexState::Alive(_) => Some(index_id),
-                    _ => None,
-                })
-                .cloned()
-                .collect()
-        };
-        let indexes_metadatas: Vec<IndexMetadata> =
-            try_join_all(index_ids.iter().map(|index_id| async move {
-                match self.index_metadata(index_id).await {
-                    Ok(index_metadata) => Ok(Some(index_metadata)),
-                    Err(MetastoreError::IndexDoesNotExist { index_id: _ }) => Ok(None),
-                    Err(MetastoreError::InternalError { message, cause }) => {
-                        // Indexes can be in a transition state `Creating` or `Deleting`.
-                        // This is fine to ignore them.
-                        if cause.contains("is in transitioning state") {
-                            Ok(None)
-                        } else {
-                            Err(MetastoreError::InternalError { message, cause })
-                        }
-                    }
-                    Err(error) => Err(error),
-                }
-            }))
+        let indexes_metadatas = self
+            .list_any_indexes_metadatas()
             .await?
             .into_iter()
-            .flatten()
+            .filter(|index_metadata| !index_metadata.is_trashed())
+            .collect();
+        Ok(indexes_metadatas)
+    }
+
+    async fn list_trashed_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
+        let indexes_metadatas = self
+            .list_any_indexes_metadatas()
+            .await?
+            .into_iter()
+            .filter(|index_metadata| index_metadata.is_trashed())
             .collect();
         Ok(indexes_metadatas)
     }
@@ -653,6 +862,51 @@ impl Metastore for FileBackedMetastore {
             .await??;
         Ok(delete_tasks)
     }
+
+    async fn cancel_delete_task(
+        &self,
+        index_uid: IndexUid,
+        opstamp: u64,
+    ) -> MetastoreResult<DeleteTask> {
+        let delete_task = self
+            .mutate(index_uid, |index| {
+                index.cancel_delete_task(opstamp).map(MutationOccurred::Yes)
+            })
+            .await?;
+        Ok(delete_task)
+    }
+
+    // Audit log API
+
+    async fn append_audit_event(&self, audit_event: AuditEvent) -> MetastoreResult<()> {
+        let index_id = audit_event.index_uid.index_id().to_string();
+        let _audit_log_guard = self.audit_log_mutex.lock().await;
+        let mut audit_events = fetch_audit_events(&*self.storage, &index_id).await?;
+        audit_events.push(audit_event);
+
+        let num_expired_events = audit_events
+            .len()
+            .saturating_sub(self.max_audit_events_per_index);
+        audit_events.drain(..num_expired_events);
+        put_audit_events(&*self.storage, &index_id, &audit_events).await
+    }
+
+    async fn list_audit_events(&self, index_id: &str) -> MetastoreResult<Vec<AuditEvent>> {
+        fetch_audit_events(&*self.storage, index_id).await
+    }
+
+    // Cluster settings API
+
+    async fn cluster_settings(&self) -> MetastoreResult<ClusterSettings> {
+        fetch_cluster_settings(&*self.storage).await
+    }
+
+    async fn update_cluster_settings(
+        &self,
+        cluster_settings: ClusterSettings,
+    ) -> MetastoreResult<()> {
+        put_cluster_settings(&*self.storage, &cluster_settings).await
+    }
 }
 
 async fn get_index_mutex(
@@ -694,6 +948,7 @@ mod tests {
     use std::collections::HashMap;
     use std::ops::RangeInclusive;
     use std::path::Path;
+    use std::sync::atomic::{AtomicBool, Ordering};
     use std::sync::Arc;
 
     use futures::executor::block_on;
@@ -712,7 +967,8 @@ mod tests {
     use super::*;
     use crate::tests::test_suite::DefaultForTest;
     use crate::{
-        IndexMetadata, ListSplitsQuery, Metastore, MetastoreError, SplitMetadata, SplitState,
+        AuditOperation, IndexMetadata, ListSplitsQuery, Metastore, MetastoreError, SplitMetadata,
+        SplitState,
     };
 
     #[tokio::test]
@@ -866,6 +1122,101 @@ mod tests {
         assert!(!split.is_empty());
     }
 
+    #[tokio::test]
+    async fn test_file_backed_metastore_publish_splits_across_indexes_rolls_back() {
+        let mut mock_storage = MockStorage::default();
+        let ram_storage = RamStorage::default();
+        let ram_storage_clone = ram_storage.clone();
+        let fail_index_b_put = Arc::new(AtomicBool::new(false));
+        let fail_index_b_put_clone = fail_index_b_put.clone();
+
+        mock_storage.expect_exists().returning(|_| Ok(false));
+        mock_storage
+            .expect_put()
+            .returning(move |path, put_payload| {
+                if path == meta_path("index-b") && fail_index_b_put_clone.load(Ordering::SeqCst) {
+                    return Err(StorageErrorKind::Io
+                        .with_error(anyhow::anyhow!("Oops. Some network problem maybe?")));
+                }
+                block_on(ram_storage_clone.put(path, put_payload))
+            });
+        mock_storage
+            .expect_get_all()
+            .returning(move |path| block_on(ram_storage.get_all(path)));
+        let metastore = FileBackedMetastore::for_test(Arc::new(mock_storage));
+
+        let mut publications = Vec::new();
+
+        for index_id in ["index-a", "index-b"] {
+            let index_uri = format!("ram:///indexes/{index_id}");
+            let index_config = IndexConfig::for_test(index_id, &index_uri);
+            let index_uid = metastore.create_index(index_config).await.unwrap();
+
+            let split_id = format!("{index_id}--split");
+            let split_metadata = SplitMetadata {
+                split_id: split_id.clone(),
+                index_uid: index_uid.clone(),
+                ..Default::default()
+            };
+            metastore
+                .stage_splits(index_uid.clone(), vec![split_metadata])
+                .await
+                .unwrap();
+            publications.push(SplitsPublication {
+                index_uid,
+                staged_split_ids: vec![split_id],
+                replaced_split_ids: Vec::new(),
+                checkpoint_delta_opt: None,
+            });
+        }
+        let index_uids: Vec<IndexUid> = publications
+            .iter()
+            .map(|publication| publication.index_uid.clone())
+            .collect();
+
+        fail_index_b_put.store(true, Ordering::SeqCst);
+        let error = metastore
+            .publish_splits_across_indexes(publications)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, MetastoreError::InternalError { .. }));
+
+        fail_index_b_put.store(false, Ordering::SeqCst);
+        for index_uid in index_uids {
+            let splits = metastore.list_all_splits(index_uid).await.unwrap();
+            assert_eq!(splits.len(), 1);
+            assert_eq!(splits[0].split_state, SplitState::Staged);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_backed_metastore_audit_log_retention() {
+        let mut metastore = FileBackedMetastore::default_for_test().await;
+        metastore.max_audit_events_per_index = 3;
+
+        let index_uid = IndexUid::new("test-index");
+
+        for opstamp in 0..5 {
+            let audit_event = AuditEvent::new(
+                "test-node",
+                index_uid.clone(),
+                AuditOperation::CreateDeleteTask { opstamp },
+            );
+            metastore.append_audit_event(audit_event).await.unwrap();
+        }
+        let opstamps: Vec<u64> = metastore
+            .list_audit_events(index_uid.index_id())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|audit_event| match audit_event.operation {
+                AuditOperation::CreateDeleteTask { opstamp } => opstamp,
+                operation => panic!("Unexpected operation `{operation:?}`."),
+            })
+            .collect();
+        assert_eq!(opstamps, [2, 3, 4]);
+    }
+
     #[tokio::test]
     async fn test_file_backed_metastore_get_index_checks_for_inconsistent_index_id(
     ) -> crate::MetastoreResult<()> {
@@ -22,12 +22,13 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
+use quickwit_config::ClusterSettings;
 use quickwit_storage::{Storage, StorageError, StorageErrorKind};
 use serde::{Deserialize, Serialize};
 
 use super::{IndexState, LazyFileBackedIndex};
 use crate::metastore::file_backed_metastore::file_backed_index::FileBackedIndex;
-use crate::{MetastoreError, MetastoreResult};
+use crate::{AuditEvent, MetastoreError, MetastoreResult};
 
 /// Indexes states file managed by [`FileBackedMetastore`](crate::FileBackedMetastore).
 const INDEXES_STATES_FILENAME: &str = "indexes_states.json";
@@ -35,6 +36,13 @@ const INDEXES_STATES_FILENAME: &str = "indexes_states.json";
 /// Index metadata file managed by [`FileBackedMetastore`](crate::FileBackedMetastore).
 const META_FILENAME: &str = "metastore.json";
 
+/// Audit log file managed by [`FileBackedMetastore`](crate::FileBackedMetastore). Unlike the
+/// index metadata file, it is not removed when the index is deleted.
+const AUDIT_LOG_FILENAME: &str = "audit-log.json";
+
+/// Cluster settings file managed by [`FileBackedMetastore`](crate::FileBackedMetastore).
+const CLUSTER_SETTINGS_FILENAME: &str = "cluster-settings.json";
+
 /// Index state used for serialization/deserialization only.
 #[derive(Serialize, Deserialize)]
 enum IndexStateValue {
@@ -58,6 +66,11 @@ pub(crate) fn meta_path(index_id: &str) -> PathBuf {
     Path::new(index_id).join(META_FILENAME)
 }
 
+/// Path to the audit log file from the given index ID.
+fn audit_log_path(index_id: &str) -> PathBuf {
+    Path::new(index_id).join(AUDIT_LOG_FILENAME)
+}
+
 fn convert_error(index_id: &str, storage_err: StorageError) -> MetastoreError {
     match storage_err.kind() {
         StorageErrorKind::DoesNotExist => MetastoreError::IndexDoesNotExist {
@@ -258,3 +271,90 @@ pub(crate) async fn delete_index(storage: &dyn Storage, index_id: &str) -> Metas
 
     Ok(())
 }
+
+/// Fetches the audit events of the given index ID, oldest first. Returns an empty list if no
+/// event was ever recorded for this index.
+pub(crate) async fn fetch_audit_events(
+    storage: &dyn Storage,
+    index_id: &str,
+) -> MetastoreResult<Vec<AuditEvent>> {
+    let audit_log_path = audit_log_path(index_id);
+    let exists = storage
+        .exists(&audit_log_path)
+        .await
+        .map_err(|storage_err| convert_error(index_id, storage_err))?;
+    if !exists {
+        return Ok(Vec::new());
+    }
+    let content = storage
+        .get_all(&audit_log_path)
+        .await
+        .map_err(|storage_err| convert_error(index_id, storage_err))?;
+    serde_json::from_slice(&content[..]).map_err(|serde_err| MetastoreError::InvalidManifest {
+        message: serde_err.to_string(),
+    })
+}
+
+/// Serializes the audit events of the given index ID and stores them on the storage.
+pub(crate) async fn put_audit_events(
+    storage: &dyn Storage,
+    index_id: &str,
+    audit_events: &[AuditEvent],
+) -> MetastoreResult<()> {
+    let content: Vec<u8> = serde_json::to_vec_pretty(audit_events).map_err(|serde_err| {
+        MetastoreError::InternalError {
+            message: "Failed to serialize audit log".to_string(),
+            cause: serde_err.to_string(),
+        }
+    })?;
+    storage
+        .put(&audit_log_path(index_id), Box::new(content))
+        .await
+        .map_err(|storage_err| convert_error(index_id, storage_err))?;
+    Ok(())
+}
+
+/// Fetches the cluster settings. Returns the default settings if they were never updated.
+pub(crate) async fn fetch_cluster_settings(
+    storage: &dyn Storage,
+) -> MetastoreResult<ClusterSettings> {
+    let cluster_settings_path = Path::new(CLUSTER_SETTINGS_FILENAME);
+    let exists = storage
+        .exists(cluster_settings_path)
+        .await
+        .map_err(|storage_err| convert_error("cluster-settings", storage_err))?;
+    if !exists {
+        return Ok(ClusterSettings::default());
+    }
+    let content = storage
+        .get_all(cluster_settings_path)
+        .await
+        .map_err(|storage_err| MetastoreError::InternalError {
+            message: format!("Failed to get `{CLUSTER_SETTINGS_FILENAME}` file."),
+            cause: storage_err.to_string(),
+        })?;
+    serde_json::from_slice(&content[..]).map_err(|serde_err| MetastoreError::InvalidManifest {
+        message: serde_err.to_string(),
+    })
+}
+
+/// Serializes the cluster settings and stores them on the storage.
+pub(crate) async fn put_cluster_settings(
+    storage: &dyn Storage,
+    cluster_settings: &ClusterSettings,
+) -> MetastoreResult<()> {
+    let content: Vec<u8> = serde_json::to_vec_pretty(cluster_settings).map_err(|serde_err| {
+        MetastoreError::InternalError {
+            message: "Failed to serialize cluster settings".to_string(),
+            cause: serde_err.to_string(),
+        }
+    })?;
+    storage
+        .put(Path::new(CLUSTER_SETTINGS_FILENAME), Box::new(content))
+        .await
+        .map_err(|storage_err| MetastoreError::InternalError {
+            message: format!("Failed to put `{CLUSTER_SETTINGS_FILENAME}` file."),
+            cause: storage_err.to_string(),
+        })?;
+    Ok(())
+}
@@ -21,24 +21,31 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use itertools::Itertools;
-use quickwit_config::IndexConfig;
+use quickwit_config::{ClusterSettings, IndexConfig};
 use quickwit_proto::metastore_api::metastore_api_service_server::{self as grpc};
 use quickwit_proto::metastore_api::{
-    AddSourceRequest, CreateIndexRequest, CreateIndexResponse, DeleteIndexRequest,
-    DeleteIndexResponse, DeleteQuery, DeleteSourceRequest, DeleteSplitsRequest, DeleteTask,
-    IndexMetadataRequest, IndexMetadataResponse, LastDeleteOpstampRequest,
-    LastDeleteOpstampResponse, ListAllSplitsRequest, ListDeleteTasksRequest,
-    ListDeleteTasksResponse, ListIndexesMetadatasRequest, ListIndexesMetadatasResponse,
-    ListSplitsRequest, ListSplitsResponse, ListStaleSplitsRequest, MarkSplitsForDeletionRequest,
-    PublishSplitsRequest, ResetSourceCheckpointRequest, SourceResponse, SplitResponse,
-    StageSplitsRequest, ToggleSourceRequest, UpdateSplitsDeleteOpstampRequest,
+    AddSourceRequest, AppendAuditEventRequest, AppendAuditEventResponse, CancelDeleteTaskRequest,
+    ClusterSettingsRequest, ClusterSettingsResponse, CreateIndexRequest, CreateIndexResponse,
+    DeleteIndexRequest, DeleteIndexResponse, DeleteQuery, DeleteSourceRequest, DeleteSplitsRequest,
+    DeleteTask, IndexMetadataRequest, IndexMetadataResponse, LastDeleteOpstampRequest,
+    LastDeleteOpstampResponse, ListAllSplitsRequest, ListAuditEventsRequest,
+    ListAuditEventsResponse, ListDeleteTasksRequest, ListDeleteTasksResponse,
+    ListIndexesMetadatasRequest, ListIndexesMetadatasResponse, ListSplitsPageRequest,
+    ListSplitsPageResponse, ListSplitsRequest, ListSplitsResponse, ListStaleSplitsRequest,
+    ListTenantsRequest, ListTenantsResponse, MarkSplitsForDeletionRequest,
+    PublishSplitsAcrossIndexesRequest, PublishSplitsRequest, ResetSourceCheckpointRequest,
+    RestoreIndexRequest, RewindSourceCheckpointRequest, SetMergesPausedRequest,
+    SetMergesPausedResponse, SourceResponse, SplitResponse, StageSplitsRequest,
+    ToggleSourceRequest, TrashIndexRequest, TrashIndexResponse, UpdateClusterSettingsRequest,
+    UpdateClusterSettingsResponse, UpdateIndexConfigRequest, UpdateSplitsDeleteOpstampRequest,
     UpdateSplitsDeleteOpstampResponse,
 };
 use quickwit_proto::tonic::{Request, Response, Status};
 use quickwit_proto::{set_parent_span_from_request_metadata, tonic};
 use tracing::instrument;
 
-use crate::{ListSplitsQuery, Metastore, MetastoreError};
+use crate::checkpoint::SourceCheckpoint;
+use crate::{AuditEvent, ListSplitsQuery, Metastore, MetastoreError, SplitsPublication};
 
 #[allow(missing_docs)]
 #[derive(Clone)]
@@ -117,6 +124,39 @@ impl grpc::MetastoreApiService for GrpcMetastoreAdapter {
         Ok(tonic::Response::new(list_indexes_metadatas_reply))
     }
 
+    #[instrument(skip(self, request))]
+    async fn update_index_config(
+        &self,
+        request: tonic::Request<UpdateIndexConfigRequest>,
+    ) -> Result<tonic::Response<IndexMetadataResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let update_index_config_request = request.into_inner();
+        let index_config = serde_json::from_str::<IndexConfig>(
+            &update_index_config_request.index_config_serialized_json,
+        )
+        .map_err(|error| MetastoreError::JsonDeserializeError {
+            struct_name: "IndexConfig".to_string(),
+            message: error.to_string(),
+        })?;
+        let index_metadata = self
+            .0
+            .update_index_config(
+                update_index_config_request.index_uid.into(),
+                index_config,
+                update_index_config_request.expected_config_version,
+            )
+            .await?;
+        let index_metadata_reply = serde_json::to_string(&index_metadata)
+            .map(|index_metadata_serialized_json| IndexMetadataResponse {
+                index_metadata_serialized_json,
+            })
+            .map_err(|error| MetastoreError::JsonSerializeError {
+                struct_name: "IndexMetadata".to_string(),
+                message: error.to_string(),
+            })?;
+        Ok(tonic::Response::new(index_metadata_reply))
+    }
+
     #[instrument(skip(self, request))]
     async fn delete_index(
         &self,
@@ -132,6 +172,81 @@ impl grpc::MetastoreApiService for GrpcMetastoreAdapter {
         Ok(tonic::Response::new(delete_reply))
     }
 
+    #[instrument(skip(self, request))]
+    async fn trash_index(
+        &self,
+        request: tonic::Request<TrashIndexRequest>,
+    ) -> Result<tonic::Response<TrashIndexResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let trash_request = request.into_inner();
+        let trash_reply = self
+            .0
+            .trash_index(trash_request.index_uid.into())
+            .await
+            .map(|_| TrashIndexResponse {})?;
+        Ok(tonic::Response::new(trash_reply))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn restore_index(
+        &self,
+        request: tonic::Request<RestoreIndexRequest>,
+    ) -> Result<tonic::Response<IndexMetadataResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let restore_request = request.into_inner();
+        let index_metadata = self
+            .0
+            .restore_index(restore_request.index_uid.into())
+            .await?;
+        let index_metadata_reply = serde_json::to_string(&index_metadata)
+            .map(|index_metadata_serialized_json| IndexMetadataResponse {
+                index_metadata_serialized_json,
+            })
+            .map_err(|error| MetastoreError::JsonSerializeError {
+                struct_name: "IndexMetadata".to_string(),
+                message: error.to_string(),
+            })?;
+        Ok(tonic::Response::new(index_metadata_reply))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn list_trashed_indexes_metadatas(
+        &self,
+        request: tonic::Request<ListIndexesMetadatasRequest>,
+    ) -> Result<tonic::Response<ListIndexesMetadatasResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let indexes_metadatas = self.0.list_trashed_indexes_metadatas().await?;
+        let list_indexes_metadatas_reply = serde_json::to_string(&indexes_metadatas)
+            .map(
+                |indexes_metadatas_serialized_json| ListIndexesMetadatasResponse {
+                    indexes_metadatas_serialized_json,
+                },
+            )
+            .map_err(|error| MetastoreError::JsonSerializeError {
+                struct_name: "Vec<IndexMetadata>".to_string(),
+                message: error.to_string(),
+            })?;
+        Ok(tonic::Response::new(list_indexes_metadatas_reply))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn set_merges_paused(
+        &self,
+        request: tonic::Request<SetMergesPausedRequest>,
+    ) -> Result<tonic::Response<SetMergesPausedResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let set_merges_paused_request = request.into_inner();
+        let set_merges_paused_reply = self
+            .0
+            .set_merges_paused(
+                set_merges_paused_request.index_uid.into(),
+                set_merges_paused_request.paused,
+            )
+            .await
+            .map(|_| SetMergesPausedResponse {})?;
+        Ok(tonic::Response::new(set_merges_paused_reply))
+    }
+
     #[instrument(skip(self, request))]
     async fn list_all_splits(
         &self,
@@ -179,6 +294,32 @@ impl grpc::MetastoreApiService for GrpcMetastoreAdapter {
         Ok(tonic::Response::new(list_splits_reply))
     }
 
+    #[instrument(skip(self, request))]
+    async fn list_splits_page(
+        &self,
+        request: tonic::Request<ListSplitsPageRequest>,
+    ) -> Result<tonic::Response<ListSplitsPageResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let list_splits_page_request = request.into_inner();
+        let query: ListSplitsQuery = serde_json::from_str(&list_splits_page_request.filter_json)
+            .map_err(|error| MetastoreError::JsonDeserializeError {
+                struct_name: "ListSplitsQuery".to_string(),
+                message: error.to_string(),
+            })?;
+        let page_size = list_splits_page_request.page_size as usize;
+        let splits_page = self.0.list_splits_page(query, page_size).await?;
+        let list_splits_page_reply = serde_json::to_string(&splits_page.splits)
+            .map(|splits_serialized_json| ListSplitsPageResponse {
+                splits_serialized_json,
+                next_page_token: splits_page.next_page_token,
+            })
+            .map_err(|error| MetastoreError::JsonSerializeError {
+                struct_name: "Vec<Split>".to_string(),
+                message: error.to_string(),
+            })?;
+        Ok(tonic::Response::new(list_splits_page_reply))
+    }
+
     #[instrument(skip(self, request))]
     async fn stage_splits(
         &self,
@@ -236,6 +377,37 @@ impl grpc::MetastoreApiService for GrpcMetastoreAdapter {
         Ok(tonic::Response::new(publish_splits_reply))
     }
 
+    #[instrument(skip(self, request))]
+    async fn publish_splits_across_indexes(
+        &self,
+        request: tonic::Request<PublishSplitsAcrossIndexesRequest>,
+    ) -> Result<tonic::Response<SplitResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let publications = request
+            .into_inner()
+            .publications
+            .into_iter()
+            .map(|publish_request| {
+                let checkpoint_delta_opt = publish_request
+                    .index_checkpoint_delta_serialized_json
+                    .map(|json| serde_json::from_str(&json))
+                    .transpose()
+                    .map_err(|error| MetastoreError::JsonDeserializeError {
+                        struct_name: "IndexCheckpointDelta".to_string(),
+                        message: error.to_string(),
+                    })?;
+                Ok(SplitsPublication {
+                    index_uid: publish_request.index_uid.into(),
+                    staged_split_ids: publish_request.split_ids,
+                    replaced_split_ids: publish_request.replaced_split_ids,
+                    checkpoint_delta_opt,
+                })
+            })
+            .collect::<Result<Vec<_>, MetastoreError>>()?;
+        self.0.publish_splits_across_indexes(publications).await?;
+        Ok(tonic::Response::new(SplitResponse {}))
+    }
+
     #[instrument(skip(self, request))]
     async fn mark_splits_for_deletion(
         &self,
@@ -351,6 +523,31 @@ impl grpc::MetastoreApiService for GrpcMetastoreAdapter {
         Ok(tonic::Response::new(reply))
     }
 
+    #[instrument(skip(self, request))]
+    async fn rewind_source_checkpoint(
+        &self,
+        request: tonic::Request<RewindSourceCheckpointRequest>,
+    ) -> Result<tonic::Response<SourceResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let request = request.into_inner();
+        let target_checkpoint =
+            serde_json::from_str::<SourceCheckpoint>(&request.target_checkpoint_serialized_json)
+                .map_err(|error| MetastoreError::JsonDeserializeError {
+                    struct_name: "SourceCheckpoint".to_string(),
+                    message: error.to_string(),
+                })?;
+        let reply = self
+            .0
+            .rewind_source_checkpoint(
+                request.index_uid.into(),
+                &request.source_id,
+                target_checkpoint,
+            )
+            .await
+            .map(|_| SourceResponse {})?;
+        Ok(tonic::Response::new(reply))
+    }
+
     #[instrument(skip(self, request))]
     async fn last_delete_opstamp(
         &self,
@@ -418,6 +615,20 @@ impl grpc::MetastoreApiService for GrpcMetastoreAdapter {
         Ok(tonic::Response::new(reply))
     }
 
+    #[instrument(skip(self, request))]
+    async fn cancel_delete_task(
+        &self,
+        request: tonic::Request<CancelDeleteTaskRequest>,
+    ) -> Result<tonic::Response<DeleteTask>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let request = request.into_inner();
+        let delete_task = self
+            .0
+            .cancel_delete_task(request.index_uid.into(), request.opstamp)
+            .await?;
+        Ok(tonic::Response::new(delete_task))
+    }
+
     #[instrument(skip(self, request))]
     async fn list_stale_splits(
         &self,
@@ -443,4 +654,97 @@ impl grpc::MetastoreApiService for GrpcMetastoreAdapter {
             })?;
         Ok(tonic::Response::new(reply))
     }
+
+    #[instrument(skip(self, request))]
+    async fn append_audit_event(
+        &self,
+        request: tonic::Request<AppendAuditEventRequest>,
+    ) -> Result<tonic::Response<AppendAuditEventResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let append_audit_event_request = request.into_inner();
+        let audit_event: AuditEvent = serde_json::from_str(
+            &append_audit_event_request.audit_event_serialized_json,
+        )
+        .map_err(|error| MetastoreError::JsonDeserializeError {
+            struct_name: "AuditEvent".to_string(),
+            message: error.to_string(),
+        })?;
+        let append_audit_event_reply = self
+            .0
+            .append_audit_event(audit_event)
+            .await
+            .map(|_| AppendAuditEventResponse {})?;
+        Ok(tonic::Response::new(append_audit_event_reply))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn list_audit_events(
+        &self,
+        request: tonic::Request<ListAuditEventsRequest>,
+    ) -> Result<tonic::Response<ListAuditEventsResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let list_audit_events_request = request.into_inner();
+        let audit_events = self
+            .0
+            .list_audit_events(&list_audit_events_request.index_id)
+            .await?;
+        let reply = serde_json::to_string(&audit_events)
+            .map(|audit_events_serialized_json| ListAuditEventsResponse {
+                audit_events_serialized_json,
+            })
+            .map_err(|error| MetastoreError::JsonSerializeError {
+                struct_name: "Vec<AuditEvent>".to_string(),
+                message: error.to_string(),
+            })?;
+        Ok(tonic::Response::new(reply))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn cluster_settings(
+        &self,
+        request: tonic::Request<ClusterSettingsRequest>,
+    ) -> Result<tonic::Response<ClusterSettingsResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let cluster_settings = self.0.cluster_settings().await?;
+        let reply = serde_json::to_string(&cluster_settings)
+            .map(|cluster_settings_serialized_json| ClusterSettingsResponse {
+                cluster_settings_serialized_json,
+            })
+            .map_err(|error| MetastoreError::JsonSerializeError {
+                struct_name: "ClusterSettings".to_string(),
+                message: error.to_string(),
+            })?;
+        Ok(tonic::Response::new(reply))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn update_cluster_settings(
+        &self,
+        request: tonic::Request<UpdateClusterSettingsRequest>,
+    ) -> Result<tonic::Response<UpdateClusterSettingsResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let update_cluster_settings_request = request.into_inner();
+        let cluster_settings: ClusterSettings =
+            serde_json::from_str(&update_cluster_settings_request.cluster_settings_serialized_json)
+                .map_err(|error| MetastoreError::JsonDeserializeError {
+                    struct_name: "ClusterSettings".to_string(),
+                    message: error.to_string(),
+                })?;
+        let update_cluster_settings_reply = self
+            .0
+            .update_cluster_settings(cluster_settings)
+            .await
+            .map(|_| UpdateClusterSettingsResponse {})?;
+        Ok(tonic::Response::new(update_cluster_settings_reply))
+    }
+
+    #[instrument(skip(self, request))]
+    async fn list_tenants(
+        &self,
+        request: tonic::Request<ListTenantsRequest>,
+    ) -> Result<tonic::Response<ListTenantsResponse>, tonic::Status> {
+        set_parent_span_from_request_metadata(request.metadata());
+        let tenant_ids = self.0.list_tenants().await?;
+        Ok(tonic::Response::new(ListTenantsResponse { tenant_ids }))
+    }
 }
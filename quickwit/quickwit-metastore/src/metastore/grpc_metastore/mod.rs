@@ -28,25 +28,31 @@ pub use grpc_adapter::GrpcMetastoreAdapter;
 use itertools::Itertools;
 use quickwit_common::tower::BalanceChannel;
 use quickwit_common::uri::Uri as QuickwitUri;
-use quickwit_config::{IndexConfig, SourceConfig};
+use quickwit_config::{ClusterSettings, IndexConfig, SourceConfig};
 use quickwit_proto::metastore_api::metastore_api_service_client::MetastoreApiServiceClient;
 use quickwit_proto::metastore_api::{
-    AddSourceRequest, CreateIndexRequest, DeleteIndexRequest, DeleteQuery, DeleteSourceRequest,
-    DeleteSplitsRequest, DeleteTask, IndexMetadataRequest, LastDeleteOpstampRequest,
-    ListAllSplitsRequest, ListDeleteTasksRequest, ListIndexesMetadatasRequest, ListSplitsRequest,
-    ListStaleSplitsRequest, MarkSplitsForDeletionRequest, PublishSplitsRequest,
-    ResetSourceCheckpointRequest, StageSplitsRequest, ToggleSourceRequest,
-    UpdateSplitsDeleteOpstampRequest,
+    AddSourceRequest, AppendAuditEventRequest, CancelDeleteTaskRequest, ClusterSettingsRequest,
+    CreateIndexRequest, DeleteIndexRequest, DeleteQuery, DeleteSourceRequest, DeleteSplitsRequest,
+    DeleteTask, IndexMetadataRequest, LastDeleteOpstampRequest, ListAllSplitsRequest,
+    ListAuditEventsRequest, ListDeleteTasksRequest, ListIndexesMetadatasRequest,
+    ListSplitsPageRequest, ListSplitsRequest, ListStaleSplitsRequest, ListTenantsRequest,
+    MarkSplitsForDeletionRequest, PublishSplitsAcrossIndexesRequest, PublishSplitsRequest,
+    ResetSourceCheckpointRequest, RestoreIndexRequest, RewindSourceCheckpointRequest,
+    SetMergesPausedRequest, StageSplitsRequest, ToggleSourceRequest, TrashIndexRequest,
+    UpdateClusterSettingsRequest, UpdateIndexConfigRequest, UpdateSplitsDeleteOpstampRequest,
 };
 use quickwit_proto::tonic::codegen::InterceptedService;
-use quickwit_proto::tonic::Status;
+use quickwit_proto::tonic::metadata::AsciiMetadataValue;
+use quickwit_proto::tonic::service::Interceptor;
+use quickwit_proto::tonic::{Request, Status};
 use quickwit_proto::{IndexUid, SpanContextInterceptor};
 use tower::timeout::error::Elapsed;
 
-use crate::checkpoint::IndexCheckpointDelta;
+use crate::checkpoint::{IndexCheckpointDelta, SourceCheckpoint};
 use crate::{
-    IndexMetadata, ListSplitsQuery, Metastore, MetastoreError, MetastoreResult, Split,
-    SplitMetadata,
+    current_audit_actor, default_audit_actor, AuditEvent, IndexMetadata, ListSplitsQuery,
+    Metastore, MetastoreError, MetastoreResult, Split, SplitMetadata, SplitsPage,
+    SplitsPublication, AUDIT_ACTOR_METADATA_KEY,
 };
 
 // URI describing in a generic way the metastore services resource present in the cluster (=
@@ -54,9 +60,31 @@ use crate::{
 // is only useful for debugging.
 const GRPC_METASTORE_BASE_URI: &str = "grpc://metastore.service.cluster";
 
-type Transport = InterceptedService<BalanceChannel<SocketAddr>, SpanContextInterceptor>;
+type Transport = InterceptedService<BalanceChannel<SocketAddr>, MetastoreInterceptor>;
 type MetastoreGrpcClientImpl = MetastoreApiServiceClient<Transport>;
 
+/// Injects the span context, the cluster key, and the actor on whose behalf the request is sent
+/// into the metadata of the metastore requests, so that the metastore service records the
+/// mutations in the audit log on behalf of this actor.
+#[derive(Clone, Debug)]
+struct MetastoreInterceptor;
+
+impl Interceptor for MetastoreInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let mut request = SpanContextInterceptor.call(request)?;
+        let actor = current_audit_actor().unwrap_or_else(default_audit_actor);
+
+        // Actors that are not valid metadata values are recorded as the default actor of the
+        // metastore service.
+        if let Ok(actor) = actor.parse::<AsciiMetadataValue>() {
+            request
+                .metadata_mut()
+                .insert(AUDIT_ACTOR_METADATA_KEY, actor);
+        }
+        Ok(request)
+    }
+}
+
 /// The [`MetastoreGrpcClient`] sends gRPC requests to cluster members running a [`Metastore`]
 /// service, those nodes will execute the queries on the metastore.
 /// The [`MetastoreGrpcClient`] use tonic load balancer to balance requests between nodes and
@@ -81,7 +109,7 @@ impl MetastoreGrpcClient {
     ) -> anyhow::Result<Self> {
         let underlying = MetastoreApiServiceClient::with_interceptor(
             balance_channel.clone(),
-            SpanContextInterceptor,
+            MetastoreInterceptor,
         );
         let uri = QuickwitUri::from_well_formed(GRPC_METASTORE_BASE_URI);
         Ok(Self {
@@ -112,7 +140,7 @@ impl MetastoreGrpcClient {
         let balance_channel = BalanceChannel::from_channel(dummy_addr, channel);
         let underlying = MetastoreApiServiceClient::with_interceptor(
             balance_channel.clone(),
-            SpanContextInterceptor,
+            MetastoreInterceptor,
         );
         Ok(Self {
             underlying,
@@ -196,6 +224,41 @@ impl Metastore for MetastoreGrpcClient {
         Ok(index_metadata)
     }
 
+    /// Updates an index config.
+    async fn update_index_config(
+        &self,
+        index_uid: IndexUid,
+        index_config: IndexConfig,
+        expected_config_version_opt: Option<u64>,
+    ) -> MetastoreResult<IndexMetadata> {
+        let index_config_serialized_json =
+            serde_json::to_string(&index_config).map_err(|error| {
+                MetastoreError::JsonSerializeError {
+                    struct_name: "IndexConfig".to_string(),
+                    message: error.to_string(),
+                }
+            })?;
+        let request = UpdateIndexConfigRequest {
+            index_uid: index_uid.to_string(),
+            index_config_serialized_json,
+            expected_config_version: expected_config_version_opt,
+        };
+        let response = self
+            .underlying
+            .clone()
+            .update_index_config(request)
+            .await
+            .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+        let index_metadata = serde_json::from_str(
+            &response.into_inner().index_metadata_serialized_json,
+        )
+        .map_err(|error| MetastoreError::JsonDeserializeError {
+            struct_name: "IndexMetadata".to_string(),
+            message: error.to_string(),
+        })?;
+        Ok(index_metadata)
+    }
+
     /// Deletes an index.
     async fn delete_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
         let request = DeleteIndexRequest {
@@ -210,6 +273,82 @@ impl Metastore for MetastoreGrpcClient {
         Ok(())
     }
 
+    /// Moves an index to the trash.
+    async fn trash_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
+        let request = TrashIndexRequest {
+            index_uid: index_uid.to_string(),
+        };
+        self.underlying
+            .clone()
+            .trash_index(request)
+            .await
+            .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+        Ok(())
+    }
+
+    /// Takes an index out of the trash.
+    async fn restore_index(&self, index_uid: IndexUid) -> MetastoreResult<IndexMetadata> {
+        let request = RestoreIndexRequest {
+            index_uid: index_uid.to_string(),
+        };
+        let response = self
+            .underlying
+            .clone()
+            .restore_index(request)
+            .await
+            .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+        let index_metadata = serde_json::from_str(
+            &response.into_inner().index_metadata_serialized_json,
+        )
+        .map_err(|error| MetastoreError::JsonDeserializeError {
+            struct_name: "IndexMetadata".to_string(),
+            message: error.to_string(),
+        })?;
+        Ok(index_metadata)
+    }
+
+    /// Lists the trashed indexes.
+    async fn list_trashed_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
+        let response = self
+            .underlying
+            .clone()
+            .list_trashed_indexes_metadatas(ListIndexesMetadatasRequest {})
+            .await
+            .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+        let indexes_metadatas =
+            serde_json::from_str(&response.into_inner().indexes_metadatas_serialized_json)
+                .map_err(|error| MetastoreError::JsonDeserializeError {
+                    struct_name: "Vec<IndexMetadata>".to_string(),
+                    message: error.to_string(),
+                })?;
+        Ok(indexes_metadatas)
+    }
+
+    /// Pauses or resumes the merges of an index.
+    async fn set_merges_paused(&self, index_uid: IndexUid, paused: bool) -> MetastoreResult<()> {
+        let request = SetMergesPausedRequest {
+            index_uid: index_uid.to_string(),
+            paused,
+        };
+        self.underlying
+            .clone()
+            .set_merges_paused(request)
+            .await
+            .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+        Ok(())
+    }
+
+    /// Lists the tenants owning at least one index.
+    async fn list_tenants(&self) -> MetastoreResult<Vec<String>> {
+        let response = self
+            .underlying
+            .clone()
+            .list_tenants(ListTenantsRequest {})
+            .await
+            .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+        Ok(response.into_inner().tenant_ids)
+    }
+
     /// Stages several splits.
     async fn stage_splits(
         &self,
@@ -268,6 +407,40 @@ impl Metastore for MetastoreGrpcClient {
         Ok(())
     }
 
+    /// Publishes splits to several indexes atomically.
+    async fn publish_splits_across_indexes(
+        &self,
+        publications: Vec<SplitsPublication>,
+    ) -> MetastoreResult<()> {
+        let publications = publications
+            .into_iter()
+            .map(|publication| {
+                let index_checkpoint_delta_serialized_json = publication
+                    .checkpoint_delta_opt
+                    .map(|checkpoint_delta| serde_json::to_string(&checkpoint_delta))
+                    .transpose()
+                    .map_err(|error| MetastoreError::JsonSerializeError {
+                        struct_name: "IndexCheckpointDelta".to_string(),
+                        message: error.to_string(),
+                    })?;
+                Ok(PublishSplitsRequest {
+                    index_uid: publication.index_uid.into(),
+                    split_ids: publication.staged_split_ids,
+                    replaced_split_ids: publication.replaced_split_ids,
+                    index_checkpoint_delta_serialized_json,
+                })
+            })
+            .collect::<MetastoreResult<Vec<_>>>()?;
+        let request = PublishSplitsAcrossIndexesRequest { publications };
+        self.underlying
+            .clone()
+            .publish_splits_across_indexes(request)
+            .await
+            .map(|tonic_response| tonic_response.into_inner())
+            .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+        Ok(())
+    }
+
     /// Lists the splits.
     async fn list_splits(&self, query: ListSplitsQuery) -> MetastoreResult<Vec<Split>> {
         let filter_json =
@@ -294,6 +467,42 @@ impl Metastore for MetastoreGrpcClient {
         Ok(splits)
     }
 
+    /// Lists a page of splits.
+    async fn list_splits_page(
+        &self,
+        query: ListSplitsQuery,
+        page_size: usize,
+    ) -> MetastoreResult<SplitsPage> {
+        let filter_json =
+            serde_json::to_string(&query).map_err(|error| MetastoreError::JsonSerializeError {
+                struct_name: "ListSplitsQuery".to_string(),
+                message: error.to_string(),
+            })?;
+
+        let request = ListSplitsPageRequest {
+            filter_json,
+            page_size: page_size as u64,
+        };
+        let response = self
+            .underlying
+            .clone()
+            .list_splits_page(request)
+            .await
+            .map(|tonic_response| tonic_response.into_inner())
+            .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+        let splits: Vec<Split> =
+            serde_json::from_str(&response.splits_serialized_json).map_err(|error| {
+                MetastoreError::JsonDeserializeError {
+                    struct_name: "Vec<Split>".to_string(),
+                    message: error.to_string(),
+                }
+            })?;
+        Ok(SplitsPage {
+            splits,
+            next_page_token: response.next_page_token,
+        })
+    }
+
     /// Lists all the splits without filtering.
     async fn list_all_splits(&self, index_uid: IndexUid) -> MetastoreResult<Vec<Split>> {
         let request = ListAllSplitsRequest {
@@ -437,6 +646,33 @@ impl Metastore for MetastoreGrpcClient {
         Ok(())
     }
 
+    async fn rewind_source_checkpoint(
+        &self,
+        index_uid: IndexUid,
+        source_id: &str,
+        target_checkpoint: SourceCheckpoint,
+    ) -> MetastoreResult<()> {
+        let target_checkpoint_serialized_json =
+            serde_json::to_string(&target_checkpoint).map_err(|error| {
+                MetastoreError::JsonSerializeError {
+                    struct_name: "SourceCheckpoint".to_string(),
+                    message: error.to_string(),
+                }
+            })?;
+        let request = RewindSourceCheckpointRequest {
+            index_uid: index_uid.into(),
+            source_id: source_id.to_string(),
+            target_checkpoint_serialized_json,
+        };
+        self.underlying
+            .clone()
+            .rewind_source_checkpoint(request)
+            .await
+            .map(|tonic_response| tonic_response.into_inner())
+            .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+        Ok(())
+    }
+
     async fn last_delete_opstamp(&self, index_uid: IndexUid) -> MetastoreResult<u64> {
         let request = LastDeleteOpstampRequest {
             index_uid: index_uid.into(),
@@ -510,6 +746,25 @@ impl Metastore for MetastoreGrpcClient {
         Ok(delete_tasks)
     }
 
+    async fn cancel_delete_task(
+        &self,
+        index_uid: IndexUid,
+        opstamp: u64,
+    ) -> MetastoreResult<DeleteTask> {
+        let request = CancelDeleteTaskRequest {
+            index_uid: index_uid.into(),
+            opstamp,
+        };
+        let delete_task = self
+            .underlying
+            .clone()
+            .cancel_delete_task(request)
+            .await
+            .map(|tonic_response| tonic_response.into_inner())
+            .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+        Ok(delete_task)
+    }
+
     async fn list_stale_splits(
         &self,
         index_uid: IndexUid,
@@ -537,6 +792,85 @@ impl Metastore for MetastoreGrpcClient {
             })?;
         Ok(splits)
     }
+
+    async fn append_audit_event(&self, audit_event: AuditEvent) -> MetastoreResult<()> {
+        let audit_event_serialized_json = serde_json::to_string(&audit_event).map_err(|error| {
+            MetastoreError::JsonSerializeError {
+                struct_name: "AuditEvent".to_string(),
+                message: error.to_string(),
+            }
+        })?;
+        let request = AppendAuditEventRequest {
+            audit_event_serialized_json,
+        };
+        self.underlying
+            .clone()
+            .append_audit_event(request)
+            .await
+            .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+        Ok(())
+    }
+
+    async fn list_audit_events(&self, index_id: &str) -> MetastoreResult<Vec<AuditEvent>> {
+        let request = ListAuditEventsRequest {
+            index_id: index_id.to_string(),
+        };
+        let response = self
+            .underlying
+            .clone()
+            .list_audit_events(request)
+            .await
+            .map(|tonic_response| tonic_response.into_inner())
+            .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+        let audit_events: Vec<AuditEvent> =
+            serde_json::from_str(&response.audit_events_serialized_json).map_err(|error| {
+                MetastoreError::JsonDeserializeError {
+                    struct_name: "Vec<AuditEvent>".to_string(),
+                    message: error.to_string(),
+                }
+            })?;
+        Ok(audit_events)
+    }
+
+    async fn cluster_settings(&self) -> MetastoreResult<ClusterSettings> {
+        let response = self
+            .underlying
+            .clone()
+            .cluster_settings(ClusterSettingsRequest {})
+            .await
+            .map(|tonic_response| tonic_response.into_inner())
+            .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+        let cluster_settings: ClusterSettings =
+            serde_json::from_str(&response.cluster_settings_serialized_json).map_err(|error| {
+                MetastoreError::JsonDeserializeError {
+                    struct_name: "ClusterSettings".to_string(),
+                    message: error.to_string(),
+                }
+            })?;
+        Ok(cluster_settings)
+    }
+
+    async fn update_cluster_settings(
+        &self,
+        cluster_settings: ClusterSettings,
+    ) -> MetastoreResult<()> {
+        let cluster_settings_serialized_json =
+            serde_json::to_string(&cluster_settings).map_err(|error| {
+                MetastoreError::JsonSerializeError {
+                    struct_name: "ClusterSettings".to_string(),
+                    message: error.to_string(),
+                }
+            })?;
+        let request = UpdateClusterSettingsRequest {
+            cluster_settings_serialized_json,
+        };
+        self.underlying
+            .clone()
+            .update_cluster_settings(request)
+            .await
+            .map_err(|tonic_error| parse_grpc_error(&tonic_error))?;
+        Ok(())
+    }
 }
 
 /// Parse tonic error and returns [`MetastoreError`].
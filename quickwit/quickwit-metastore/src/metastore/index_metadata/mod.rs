@@ -49,6 +49,12 @@ pub struct IndexMetadata {
     pub create_timestamp: i64,
     /// Sources
     pub sources: HashMap<String, SourceConfig>,
+    /// Version of the index config, incremented on every index config update.
+    pub config_version: u64,
+    /// Time at which the index was moved to the trash, if it was.
+    pub trash_timestamp: Option<i64>,
+    /// Whether the merges of the index are paused.
+    pub merges_paused: bool,
 }
 
 impl IndexMetadata {
@@ -60,6 +66,9 @@ impl IndexMetadata {
             checkpoint: Default::default(),
             create_timestamp: OffsetDateTime::now_utc().unix_timestamp(),
             sources: HashMap::default(),
+            config_version: 0,
+            trash_timestamp: None,
+            merges_paused: false,
         }
     }
 
@@ -89,6 +98,54 @@ impl IndexMetadata {
         &self.index_config().index_uri
     }
 
+    /// Replaces the index config and bumps the config version. Returns an error if
+    /// `expected_config_version_opt` is set and does not match the current config version, which
+    /// means the index config was updated concurrently.
+    pub(crate) fn update_index_config(
+        &mut self,
+        index_config: IndexConfig,
+        expected_config_version_opt: Option<u64>,
+    ) -> MetastoreResult<()> {
+        if let Some(expected_config_version) = expected_config_version_opt {
+            if expected_config_version != self.config_version {
+                return Err(MetastoreError::IndexConfigVersionConflict {
+                    index_id: self.index_id().to_string(),
+                    expected_version: expected_config_version,
+                    current_version: self.config_version,
+                });
+            }
+        }
+        self.index_config = index_config;
+        self.config_version += 1;
+        Ok(())
+    }
+
+    /// Returns whether the index was moved to the trash.
+    pub fn is_trashed(&self) -> bool {
+        self.trash_timestamp.is_some()
+    }
+
+    /// Moves the index to the trash. Returns whether the index was modified.
+    pub(crate) fn trash(&mut self) -> bool {
+        if self.is_trashed() {
+            return false;
+        }
+        self.trash_timestamp = Some(OffsetDateTime::now_utc().unix_timestamp());
+        true
+    }
+
+    /// Takes the index out of the trash. Returns whether the index was modified.
+    pub(crate) fn restore(&mut self) -> bool {
+        self.trash_timestamp.take().is_some()
+    }
+
+    /// Pauses or resumes the merges of the index. Returns whether the index was modified.
+    pub(crate) fn set_merges_paused(&mut self, paused: bool) -> bool {
+        let mutation_occurred = self.merges_paused != paused;
+        self.merges_paused = paused;
+        mutation_occurred
+    }
+
     /// Adds a source to the index. Returns an error if the source_id already exists.
     pub fn add_source(&mut self, source: SourceConfig) -> MetastoreResult<()> {
         let entry = self.sources.entry(source.source_id.clone());
@@ -126,6 +183,24 @@ impl IndexMetadata {
         self.checkpoint.remove_source(source_id);
         Ok(true)
     }
+
+    /// Rewinds the checkpoint of a source to the positions of `target_checkpoint`. Returns whether
+    /// the index was modified.
+    pub(crate) fn rewind_source_checkpoint(
+        &mut self,
+        source_id: &str,
+        target_checkpoint: &SourceCheckpoint,
+    ) -> MetastoreResult<bool> {
+        if !self.sources.contains_key(source_id) {
+            return Err(MetastoreError::SourceDoesNotExist {
+                source_id: source_id.to_string(),
+            });
+        }
+        let mutation_occurred = self
+            .checkpoint
+            .try_rewind_source(source_id, target_checkpoint)?;
+        Ok(mutation_occurred)
+    }
 }
 
 impl TestableForRegression for IndexMetadata {
@@ -148,6 +223,9 @@ impl TestableForRegression for IndexMetadata {
             checkpoint,
             create_timestamp: 1789,
             sources: Default::default(),
+            config_version: 3,
+            trash_timestamp: None,
+            merges_paused: false,
         };
         index_metadata
             .add_source(SourceConfig::sample_for_regression())
@@ -160,5 +238,8 @@ impl TestableForRegression for IndexMetadata {
         assert_eq!(self.checkpoint, other.checkpoint);
         assert_eq!(self.create_timestamp, other.create_timestamp);
         assert_eq!(self.sources, other.sources);
+        assert_eq!(self.config_version, other.config_version);
+        assert_eq!(self.trash_timestamp, other.trash_timestamp);
+        assert_eq!(self.merges_paused, other.merges_paused);
     }
 }
@@ -19,6 +19,7 @@
 
 use std::collections::HashMap;
 
+use quickwit_common::is_false;
 use quickwit_config::{IndexConfig, SourceConfig};
 use quickwit_proto::IndexUid;
 use serde::{self, Deserialize, Serialize};
@@ -64,6 +65,9 @@ impl From<IndexMetadata> for IndexMetadataV0_6 {
             checkpoint: index_metadata.checkpoint,
             create_timestamp: index_metadata.create_timestamp,
             sources,
+            config_version: index_metadata.config_version,
+            trash_timestamp: index_metadata.trash_timestamp,
+            merges_paused: index_metadata.merges_paused,
         }
     }
 }
@@ -82,6 +86,14 @@ pub(crate) struct IndexMetadataV0_6 {
     pub create_timestamp: i64,
     #[schema(value_type = Vec<VersionedSourceConfig>)]
     pub sources: Vec<SourceConfig>,
+    #[serde(default)]
+    pub config_version: u64,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub trash_timestamp: Option<i64>,
+    #[serde(default)]
+    #[serde(skip_serializing_if = "is_false")]
+    pub merges_paused: bool,
 }
 
 impl TryFrom<IndexMetadataV0_6> for IndexMetadata {
@@ -105,6 +117,9 @@ impl TryFrom<IndexMetadataV0_6> for IndexMetadata {
             checkpoint: v0_6.checkpoint,
             create_timestamp: v0_6.create_timestamp,
             sources,
+            config_version: v0_6.config_version,
+            trash_timestamp: v0_6.trash_timestamp,
+            merges_paused: v0_6.merges_paused,
         })
     }
 }
@@ -19,12 +19,15 @@
 
 use async_trait::async_trait;
 use quickwit_common::uri::Uri;
-use quickwit_config::{IndexConfig, SourceConfig};
+use quickwit_config::{ClusterSettings, IndexConfig, SourceConfig};
 use quickwit_proto::metastore_api::{DeleteQuery, DeleteTask};
 use quickwit_proto::IndexUid;
 
-use crate::checkpoint::IndexCheckpointDelta;
-use crate::{IndexMetadata, ListSplitsQuery, Metastore, MetastoreResult, Split, SplitMetadata};
+use crate::checkpoint::{IndexCheckpointDelta, SourceCheckpoint};
+use crate::{
+    AuditEvent, IndexMetadata, ListSplitsQuery, Metastore, MetastoreResult, Split, SplitMetadata,
+    SplitsPage, SplitsPublication,
+};
 
 macro_rules! instrument {
     ($expr:expr, [$operation:ident, $($label:expr),*]) => {
@@ -113,6 +116,20 @@ impl Metastore for InstrumentedMetastore {
         );
     }
 
+    async fn update_index_config(
+        &self,
+        index_uid: IndexUid,
+        index_config: IndexConfig,
+        expected_config_version_opt: Option<u64>,
+    ) -> MetastoreResult<IndexMetadata> {
+        instrument!(
+            self.underlying
+                .update_index_config(index_uid.clone(), index_config, expected_config_version_opt)
+                .await,
+            [update_index_config, index_uid.index_id()]
+        );
+    }
+
     async fn delete_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
         instrument!(
             self.underlying.delete_index(index_uid.clone()).await,
@@ -120,6 +137,42 @@ impl Metastore for InstrumentedMetastore {
         );
     }
 
+    async fn trash_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
+        instrument!(
+            self.underlying.trash_index(index_uid.clone()).await,
+            [trash_index, index_uid.index_id()]
+        );
+    }
+
+    async fn restore_index(&self, index_uid: IndexUid) -> MetastoreResult<IndexMetadata> {
+        instrument!(
+            self.underlying.restore_index(index_uid.clone()).await,
+            [restore_index, index_uid.index_id()]
+        );
+    }
+
+    async fn list_trashed_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
+        instrument!(
+            self.underlying.list_trashed_indexes_metadatas().await,
+            [list_trashed_indexes_metadatas, ""]
+        );
+    }
+
+    async fn set_merges_paused(&self, index_uid: IndexUid, paused: bool) -> MetastoreResult<()> {
+        instrument!(
+            self.underlying
+                .set_merges_paused(index_uid.clone(), paused)
+                .await,
+            [set_merges_paused, index_uid.index_id()]
+        );
+    }
+
+    // Tenant API
+
+    async fn list_tenants(&self) -> MetastoreResult<Vec<String>> {
+        instrument!(self.underlying.list_tenants().await, [list_tenants, ""]);
+    }
+
     // Split API
 
     async fn stage_splits(
@@ -155,6 +208,18 @@ impl Metastore for InstrumentedMetastore {
         );
     }
 
+    async fn publish_splits_across_indexes(
+        &self,
+        publications: Vec<SplitsPublication>,
+    ) -> MetastoreResult<()> {
+        instrument!(
+            self.underlying
+                .publish_splits_across_indexes(publications)
+                .await,
+            [publish_splits_across_indexes, ""]
+        );
+    }
+
     async fn list_splits(&self, query: ListSplitsQuery) -> MetastoreResult<Vec<Split>> {
         instrument!(
             self.underlying.list_splits(query.clone()).await,
@@ -162,6 +227,19 @@ impl Metastore for InstrumentedMetastore {
         );
     }
 
+    async fn list_splits_page(
+        &self,
+        query: ListSplitsQuery,
+        page_size: usize,
+    ) -> MetastoreResult<SplitsPage> {
+        instrument!(
+            self.underlying
+                .list_splits_page(query.clone(), page_size)
+                .await,
+            [list_splits_page, query.index_uid.index_id()]
+        );
+    }
+
     async fn list_all_splits(&self, index_uid: IndexUid) -> MetastoreResult<Vec<Split>> {
         instrument!(
             self.underlying.list_all_splits(index_uid.clone()).await,
@@ -231,6 +309,20 @@ impl Metastore for InstrumentedMetastore {
         );
     }
 
+    async fn rewind_source_checkpoint(
+        &self,
+        index_uid: IndexUid,
+        source_id: &str,
+        target_checkpoint: SourceCheckpoint,
+    ) -> MetastoreResult<()> {
+        instrument!(
+            self.underlying
+                .rewind_source_checkpoint(index_uid.clone(), source_id, target_checkpoint)
+                .await,
+            [rewind_source_checkpoint, index_uid.index_id()]
+        );
+    }
+
     async fn delete_source(&self, index_uid: IndexUid, source_id: &str) -> MetastoreResult<()> {
         instrument!(
             self.underlying
@@ -262,6 +354,19 @@ impl Metastore for InstrumentedMetastore {
         );
     }
 
+    async fn cancel_delete_task(
+        &self,
+        index_uid: IndexUid,
+        opstamp: u64,
+    ) -> MetastoreResult<DeleteTask> {
+        instrument!(
+            self.underlying
+                .cancel_delete_task(index_uid.clone(), opstamp)
+                .await,
+            [cancel_delete_task, index_uid.index_id()]
+        );
+    }
+
     async fn last_delete_opstamp(&self, index_uid: IndexUid) -> MetastoreResult<u64> {
         instrument!(
             self.underlying.last_delete_opstamp(index_uid.clone()).await,
@@ -296,6 +401,44 @@ impl Metastore for InstrumentedMetastore {
             [list_stale_splits, index_uid.index_id()]
         );
     }
+
+    // Audit log API
+
+    async fn append_audit_event(&self, audit_event: AuditEvent) -> MetastoreResult<()> {
+        let index_id = audit_event.index_uid.index_id().to_string();
+        instrument!(
+            self.underlying.append_audit_event(audit_event).await,
+            [append_audit_event, &index_id]
+        );
+    }
+
+    async fn list_audit_events(&self, index_id: &str) -> MetastoreResult<Vec<AuditEvent>> {
+        instrument!(
+            self.underlying.list_audit_events(index_id).await,
+            [list_audit_events, index_id]
+        );
+    }
+
+    // Cluster settings API
+
+    async fn cluster_settings(&self) -> MetastoreResult<ClusterSettings> {
+        instrument!(
+            self.underlying.cluster_settings().await,
+            [cluster_settings, ""]
+        );
+    }
+
+    async fn update_cluster_settings(
+        &self,
+        cluster_settings: ClusterSettings,
+    ) -> MetastoreResult<()> {
+        instrument!(
+            self.underlying
+                .update_cluster_settings(cluster_settings)
+                .await,
+            [update_cluster_settings, ""]
+        );
+    }
 }
 
 #[cfg(test)]
@@ -23,13 +23,16 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use quickwit_common::pubsub::{Event, EventBroker};
 use quickwit_common::uri::Uri;
-use quickwit_config::{IndexConfig, SourceConfig};
+use quickwit_config::{ClusterSettings, IndexConfig, SourceConfig};
 use quickwit_proto::metastore_api::{DeleteQuery, DeleteTask};
 use quickwit_proto::IndexUid;
 use tracing::info;
 
-use crate::checkpoint::IndexCheckpointDelta;
-use crate::{IndexMetadata, ListSplitsQuery, Metastore, MetastoreResult, Split, SplitMetadata};
+use crate::checkpoint::{IndexCheckpointDelta, SourceCheckpoint};
+use crate::{
+    AuditEvent, IndexMetadata, ListSplitsQuery, Metastore, MetastoreResult, Split, SplitMetadata,
+    SplitsPage, SplitsPublication,
+};
 
 /// Metastore events dispatched to subscribers.
 #[derive(Debug, Clone, Eq, PartialEq)]
@@ -39,6 +42,16 @@ pub enum MetastoreEvent {
         /// Index ID of the deleted index.
         index_uid: IndexUid,
     },
+    /// Trash index event.
+    TrashIndex {
+        /// Index ID of the trashed index.
+        index_uid: IndexUid,
+    },
+    /// Restore index event.
+    RestoreIndex {
+        /// Index ID of the restored index.
+        index_uid: IndexUid,
+    },
     /// Add source event.
     AddSource {
         /// Index ID of the added source.
@@ -117,6 +130,17 @@ impl Metastore for MetastoreEventPublisher {
         self.underlying.list_indexes_metadatas().await
     }
 
+    async fn update_index_config(
+        &self,
+        index_uid: IndexUid,
+        index_config: IndexConfig,
+        expected_config_version_opt: Option<u64>,
+    ) -> MetastoreResult<IndexMetadata> {
+        self.underlying
+            .update_index_config(index_uid, index_config, expected_config_version_opt)
+            .await
+    }
+
     async fn delete_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
         let event = MetastoreEvent::DeleteIndex {
             index_uid: index_uid.clone(),
@@ -126,6 +150,38 @@ impl Metastore for MetastoreEventPublisher {
         Ok(())
     }
 
+    async fn trash_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
+        let event = MetastoreEvent::TrashIndex {
+            index_uid: index_uid.clone(),
+        };
+        self.underlying.trash_index(index_uid).await?;
+        self.event_broker.publish(event);
+        Ok(())
+    }
+
+    async fn restore_index(&self, index_uid: IndexUid) -> MetastoreResult<IndexMetadata> {
+        let event = MetastoreEvent::RestoreIndex {
+            index_uid: index_uid.clone(),
+        };
+        let index_metadata = self.underlying.restore_index(index_uid).await?;
+        self.event_broker.publish(event);
+        Ok(index_metadata)
+    }
+
+    async fn list_trashed_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
+        self.underlying.list_trashed_indexes_metadatas().await
+    }
+
+    async fn set_merges_paused(&self, index_uid: IndexUid, paused: bool) -> MetastoreResult<()> {
+        self.underlying.set_merges_paused(index_uid, paused).await
+    }
+
+    // Tenant API
+
+    async fn list_tenants(&self) -> MetastoreResult<Vec<String>> {
+        self.underlying.list_tenants().await
+    }
+
     // Split API
 
     async fn stage_splits(
@@ -155,10 +211,27 @@ impl Metastore for MetastoreEventPublisher {
             .await
     }
 
+    async fn publish_splits_across_indexes(
+        &self,
+        publications: Vec<SplitsPublication>,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .publish_splits_across_indexes(publications)
+            .await
+    }
+
     async fn list_splits(&self, query: ListSplitsQuery) -> MetastoreResult<Vec<Split>> {
         self.underlying.list_splits(query).await
     }
 
+    async fn list_splits_page(
+        &self,
+        query: ListSplitsQuery,
+        page_size: usize,
+    ) -> MetastoreResult<SplitsPage> {
+        self.underlying.list_splits_page(query, page_size).await
+    }
+
     async fn list_all_splits(&self, index_uid: IndexUid) -> MetastoreResult<Vec<Split>> {
         self.underlying.list_all_splits(index_uid).await
     }
@@ -222,6 +295,17 @@ impl Metastore for MetastoreEventPublisher {
             .await
     }
 
+    async fn rewind_source_checkpoint(
+        &self,
+        index_uid: IndexUid,
+        source_id: &str,
+        target_checkpoint: SourceCheckpoint,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .rewind_source_checkpoint(index_uid, source_id, target_checkpoint)
+            .await
+    }
+
     async fn delete_source(&self, index_uid: IndexUid, source_id: &str) -> MetastoreResult<()> {
         let event = MetastoreEvent::DeleteSource {
             index_uid: index_uid.clone(),
@@ -247,6 +331,14 @@ impl Metastore for MetastoreEventPublisher {
             .await
     }
 
+    async fn cancel_delete_task(
+        &self,
+        index_uid: IndexUid,
+        opstamp: u64,
+    ) -> MetastoreResult<DeleteTask> {
+        self.underlying.cancel_delete_task(index_uid, opstamp).await
+    }
+
     async fn last_delete_opstamp(&self, index_uid: IndexUid) -> MetastoreResult<u64> {
         self.underlying.last_delete_opstamp(index_uid).await
     }
@@ -272,6 +364,31 @@ impl Metastore for MetastoreEventPublisher {
             .list_stale_splits(index_uid, delete_opstamp, num_splits)
             .await
     }
+
+    // Audit log API
+
+    async fn append_audit_event(&self, audit_event: AuditEvent) -> MetastoreResult<()> {
+        self.underlying.append_audit_event(audit_event).await
+    }
+
+    async fn list_audit_events(&self, index_id: &str) -> MetastoreResult<Vec<AuditEvent>> {
+        self.underlying.list_audit_events(index_id).await
+    }
+
+    // Cluster settings API
+
+    async fn cluster_settings(&self) -> MetastoreResult<ClusterSettings> {
+        self.underlying.cluster_settings().await
+    }
+
+    async fn update_cluster_settings(
+        &self,
+        cluster_settings: ClusterSettings,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .update_cluster_settings(cluster_settings)
+            .await
+    }
 }
 
 #[cfg(test)]
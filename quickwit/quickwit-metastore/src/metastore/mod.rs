@@ -17,6 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+pub mod audit_log_metastore;
 pub mod file_backed_metastore;
 pub mod grpc_metastore;
 pub(crate) mod index_metadata;
@@ -27,18 +28,25 @@ pub mod postgresql_metastore;
 #[cfg(feature = "postgres")]
 mod postgresql_model;
 pub mod retrying_metastore;
+pub mod tenant_metastore;
 
+use std::collections::BTreeMap;
 use std::ops::{Bound, RangeInclusive};
+use std::sync::Arc;
 
 use async_trait::async_trait;
+pub use audit_log_metastore::AuditEvent;
+use futures::stream::BoxStream;
+use futures::{stream, StreamExt};
 pub use index_metadata::IndexMetadata;
+use itertools::Itertools;
 use quickwit_common::uri::Uri;
-use quickwit_config::{IndexConfig, SourceConfig};
+use quickwit_config::{ClusterSettings, IndexConfig, SourceConfig};
 use quickwit_doc_mapper::tag_pruning::TagFilterAst;
 use quickwit_proto::metastore_api::{DeleteQuery, DeleteTask};
 use quickwit_proto::IndexUid;
 
-use crate::checkpoint::IndexCheckpointDelta;
+use crate::checkpoint::{IndexCheckpointDelta, SourceCheckpoint};
 use crate::{MetastoreError, MetastoreResult, Split, SplitMetadata, SplitState};
 
 /// Metastore meant to manage Quickwit's indexes, their splits and delete tasks.
@@ -141,6 +149,19 @@ pub trait Metastore: Send + Sync + 'static {
     /// [`IndexMetadata`].
     async fn list_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>>;
 
+    /// Updates the config of an index and returns the updated [`IndexMetadata`].
+    ///
+    /// Every update bumps the index config version. If `expected_config_version_opt` is set and
+    /// does not match the current version, the update is rejected with an
+    /// [`MetastoreError::IndexConfigVersionConflict`] error so that concurrent updates cannot
+    /// silently overwrite each other.
+    async fn update_index_config(
+        &self,
+        index_uid: IndexUid,
+        index_config: IndexConfig,
+        expected_config_version_opt: Option<u64>,
+    ) -> MetastoreResult<IndexMetadata>;
+
     /// Deletes an index.
     ///
     /// This API removes the specified  from the metastore, but does not remove the index from the
@@ -148,6 +169,47 @@ pub trait Metastore: Send + Sync + 'static {
     /// specified.
     async fn delete_index(&self, index_uid: IndexUid) -> MetastoreResult<()>;
 
+    /// Moves an index to the trash.
+    ///
+    /// A trashed index keeps its splits and sources but is no longer returned by
+    /// [`Metastore::index_metadata`] and [`Metastore::list_indexes_metadatas`], so it is excluded
+    /// from search and indexing until it is restored or permanently deleted.
+    async fn trash_index(&self, index_uid: IndexUid) -> MetastoreResult<()>;
+
+    /// Takes an index out of the trash and returns its [`IndexMetadata`].
+    async fn restore_index(&self, index_uid: IndexUid) -> MetastoreResult<IndexMetadata>;
+
+    /// Lists the indexes that were moved to the trash.
+    async fn list_trashed_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>>;
+
+    /// Pauses or resumes the merges of an index.
+    ///
+    /// The indexers stop planning new merges for a paused index until the merges are resumed.
+    /// Merges already in progress run to completion.
+    async fn set_merges_paused(&self, index_uid: IndexUid, paused: bool) -> MetastoreResult<()>;
+
+    // Tenant API
+
+    /// Lists the tenants owning at least one index.
+    ///
+    /// Tenant-scoped indexes are managed through a
+    /// [`TenantMetastore`](crate::TenantMetastore), which namespaces their index IDs with the
+    /// tenant ID.
+    async fn list_tenants(&self) -> MetastoreResult<Vec<String>> {
+        let tenant_ids = self
+            .list_indexes_metadatas()
+            .await?
+            .iter()
+            .filter_map(|index_metadata| {
+                tenant_metastore::split_tenant_index_id(index_metadata.index_id())
+                    .map(|(tenant_id, _)| tenant_id.to_string())
+            })
+            .sorted()
+            .dedup()
+            .collect();
+        Ok(tenant_ids)
+    }
+
     // Split API
 
     /// Stages multiple splits.
@@ -183,6 +245,18 @@ pub trait Metastore: Send + Sync + 'static {
         checkpoint_delta_opt: Option<IndexCheckpointDelta>,
     ) -> MetastoreResult<()>;
 
+    /// Publishes splits to several indexes atomically: either all the publications are applied or
+    /// none of them is.
+    ///
+    /// Each publication follows the same rules as [`Metastore::publish_splits`]. This makes it
+    /// possible for a pipeline writing to several indexes to advance their checkpoints together
+    /// without risking a partial failure desynchronizing them. The file-backed metastore cannot
+    /// write several indexes at once: on a storage error, it restores the indexes it already wrote.
+    async fn publish_splits_across_indexes(
+        &self,
+        publications: Vec<SplitsPublication>,
+    ) -> MetastoreResult<()>;
+
     /// Lists the splits.
     ///
     /// Returns a list of splits that intersects the given `time_range`, `split_state`, and `tag`.
@@ -190,6 +264,23 @@ pub trait Metastore: Send + Sync + 'static {
     /// An error will occur if an index that does not exist in the storage is specified.
     async fn list_splits(&self, query: ListSplitsQuery) -> MetastoreResult<Vec<Split>>;
 
+    /// Lists a page of splits.
+    ///
+    /// Returns at most `page_size` splits matching `query`, sorted by split ID. If more splits may
+    /// follow, the page carries a `next_page_token` that can be passed back via
+    /// [`ListSplitsQuery::with_after_split_id`] to fetch the next page. The query's `limit` and
+    /// `offset` are ignored.
+    async fn list_splits_page(
+        &self,
+        query: ListSplitsQuery,
+        page_size: usize,
+    ) -> MetastoreResult<SplitsPage> {
+        let mut query = query.sort_by_split_id().with_limit(page_size);
+        query.offset = None;
+        let splits = self.list_splits(query).await?;
+        Ok(SplitsPage::new(splits, page_size))
+    }
+
     /// Lists all the splits without filtering.
     ///
     /// Returns a list of all splits currently known to the metastore regardless of their state.
@@ -275,6 +366,20 @@ pub trait Metastore: Send + Sync + 'static {
         source_id: &str,
     ) -> MetastoreResult<()>;
 
+    /// Rewinds the checkpoint of a source identified by `index_uid` and `source_id` to the
+    /// positions of `target_checkpoint`, so that the source replays the documents located after
+    /// them. The partitions missing from `target_checkpoint` are left untouched.
+    ///
+    /// Fails with
+    /// [`IncompatibleCheckpointRewind`](crate::MetastoreError::IncompatibleCheckpointRewind) if a
+    /// target position is located after the current position of its partition.
+    async fn rewind_source_checkpoint(
+        &self,
+        index_uid: IndexUid,
+        source_id: &str,
+        target_checkpoint: SourceCheckpoint,
+    ) -> MetastoreResult<()>;
+
     /// Deletes a source. Fails with
     /// [`SourceDoesNotExist`](crate::MetastoreError::SourceDoesNotExist) if the specified source
     /// does not exist.
@@ -305,6 +410,42 @@ pub trait Metastore: Send + Sync + 'static {
         index_uid: IndexUid,
         opstamp_start: u64,
     ) -> MetastoreResult<Vec<DeleteTask>>;
+
+    /// Cancels the delete task with opstamp `opstamp` and returns it.
+    ///
+    /// A delete task can only be cancelled as long as it has not been applied to any split (see
+    /// [`crate::split_delete_task_applied`]), otherwise
+    /// [`MetastoreError::DeleteTaskNotCancellable`] is returned.
+    async fn cancel_delete_task(
+        &self,
+        index_uid: IndexUid,
+        opstamp: u64,
+    ) -> MetastoreResult<DeleteTask>;
+
+    // Audit log API
+
+    /// Appends an [`AuditEvent`] to the audit log of the index the event refers to.
+    ///
+    /// The audit log is append-only: events are never updated nor removed, including when the
+    /// index is deleted.
+    async fn append_audit_event(&self, audit_event: AuditEvent) -> MetastoreResult<()>;
+
+    /// Lists the audit events recorded for the index `index_id`, oldest first. The events of all
+    /// the incarnations of the index are returned, even if the index no longer exists.
+    async fn list_audit_events(&self, index_id: &str) -> MetastoreResult<Vec<AuditEvent>>;
+
+    // Cluster settings API
+
+    /// Returns the cluster-scoped [`ClusterSettings`], or the default settings if they were never
+    /// updated.
+    async fn cluster_settings(&self) -> MetastoreResult<ClusterSettings>;
+
+    /// Replaces the cluster-scoped [`ClusterSettings`]. The nodes pick up the new settings the
+    /// next time they refresh them.
+    async fn update_cluster_settings(
+        &self,
+        cluster_settings: ClusterSettings,
+    ) -> MetastoreResult<()>;
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -325,6 +466,10 @@ pub struct ListSplitsQuery {
     /// A specific set of tag(s) to filter by.
     pub tags: Option<TagFilterAst>,
 
+    /// Labels the splits must carry, with the same values.
+    #[serde(default)]
+    pub labels: BTreeMap<String, String>,
+
     /// The time range to filter by.
     pub time_range: FilterRange<i64>,
 
@@ -336,6 +481,19 @@ pub struct ListSplitsQuery {
 
     /// The create timestamp range to filter by.
     pub create_timestamp: FilterRange<i64>,
+
+    /// If set, only splits with a split ID strictly greater than this one are returned.
+    #[serde(default)]
+    pub after_split_id: Option<String>,
+
+    /// Whether the splits should be sorted by split ID.
+    #[serde(default)]
+    pub sort_by_split_id: bool,
+
+    /// Whether the bloom filters of the splits should be returned. They are left out of the
+    /// split metadata otherwise.
+    #[serde(default)]
+    pub with_bloom_filters: bool,
 }
 
 #[allow(unused_attributes)]
@@ -348,10 +506,14 @@ impl ListSplitsQuery {
             offset: None,
             split_states: Vec::new(),
             tags: None,
+            labels: BTreeMap::new(),
             time_range: Default::default(),
             delete_opstamp: Default::default(),
             update_timestamp: Default::default(),
             create_timestamp: Default::default(),
+            after_split_id: None,
+            sort_by_split_id: false,
+            with_bloom_filters: false,
         }
     }
 
@@ -367,6 +529,24 @@ impl ListSplitsQuery {
         self
     }
 
+    /// Select splits whose split ID is strictly greater than the provided one.
+    pub fn with_after_split_id(mut self, split_id: impl Into<String>) -> Self {
+        self.after_split_id = Some(split_id.into());
+        self
+    }
+
+    /// Sorts the splits by split ID.
+    pub fn sort_by_split_id(mut self) -> Self {
+        self.sort_by_split_id = true;
+        self
+    }
+
+    /// Returns the bloom filters of the splits along with their metadata.
+    pub fn with_bloom_filters(mut self) -> Self {
+        self.with_bloom_filters = true;
+        self
+    }
+
     /// Select splits which have the given split state.
     pub fn with_split_state(mut self, state: SplitState) -> Self {
         self.split_states.push(state);
@@ -385,6 +565,12 @@ impl ListSplitsQuery {
         self
     }
 
+    /// Select splits which carry the label `key` with the value `value`.
+    pub fn with_label(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.labels.insert(key.into(), value.into());
+        self
+    }
+
     /// Set the field's lower bound to match values that are
     /// *less than or equal to* the provided value.
     pub fn with_time_range_end_lte(mut self, v: i64) -> Self {
@@ -498,6 +684,69 @@ impl ListSplitsQuery {
     }
 }
 
+/// A set of splits to publish to a single index with
+/// [`Metastore::publish_splits_across_indexes`].
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct SplitsPublication {
+    /// The index to publish the splits to.
+    pub index_uid: IndexUid,
+    /// The staged splits to publish.
+    pub staged_split_ids: Vec<String>,
+    /// The published splits to mark for deletion.
+    pub replaced_split_ids: Vec<String>,
+    /// The checkpoint delta to apply to the index checkpoint, if any.
+    pub checkpoint_delta_opt: Option<IndexCheckpointDelta>,
+}
+
+/// A page of splits returned by [`Metastore::list_splits_page`].
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub struct SplitsPage {
+    /// The splits of the page, sorted by split ID.
+    pub splits: Vec<Split>,
+    /// The split ID to resume listing from, or `None` if this is the last page.
+    pub next_page_token: Option<String>,
+}
+
+impl SplitsPage {
+    /// Builds a page from a list of splits sorted by split ID. The page is considered full, and
+    /// therefore possibly followed by other splits, when it contains `page_size` splits.
+    pub fn new(splits: Vec<Split>, page_size: usize) -> Self {
+        let next_page_token = if page_size > 0 && splits.len() >= page_size {
+            splits.last().map(|split| split.split_id().to_string())
+        } else {
+            None
+        };
+        Self {
+            splits,
+            next_page_token,
+        }
+    }
+}
+
+/// Streams the splits matching `query` page by page, sorted by split ID, so that listing the
+/// splits of large indexes does not require holding all of them in memory at once.
+pub fn stream_splits(
+    metastore: Arc<dyn Metastore>,
+    query: ListSplitsQuery,
+    page_size: usize,
+) -> BoxStream<'static, MetastoreResult<Vec<Split>>> {
+    stream::try_unfold(Some(query), move |query_opt| {
+        let metastore = metastore.clone();
+        async move {
+            let query = match query_opt {
+                Some(query) => query,
+                None => return Ok(None),
+            };
+            let splits_page = metastore.list_splits_page(query.clone(), page_size).await?;
+            let next_query_opt = splits_page
+                .next_page_token
+                .map(|split_id| query.with_after_split_id(split_id));
+            Ok(Some((splits_page.splits, next_query_opt)))
+        }
+    })
+    .boxed()
+}
+
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 /// A range containing the upper and lower bounds to filter documents by.
 pub struct FilterRange<T> {
@@ -659,3 +908,51 @@ mod list_splits_query_tests {
         assert!(!filter.overlaps_with(75..=124));
     }
 }
+
+#[cfg(test)]
+mod stream_splits_tests {
+    use futures::TryStreamExt;
+
+    use super::*;
+    use crate::metastore_for_test;
+
+    #[tokio::test]
+    async fn test_stream_splits() {
+        let metastore = metastore_for_test();
+
+        let index_config = IndexConfig::for_test("test-index", "ram:///indexes/test-index");
+        let index_uid = metastore.create_index(index_config).await.unwrap();
+
+        let split_metadatas: Vec<SplitMetadata> = (0..5)
+            .map(|split_ord| SplitMetadata {
+                split_id: format!("split-{split_ord}"),
+                index_uid: index_uid.clone(),
+                ..Default::default()
+            })
+            .collect();
+        metastore
+            .stage_splits(index_uid.clone(), split_metadatas)
+            .await
+            .unwrap();
+
+        let query = ListSplitsQuery::for_index(index_uid);
+        let pages: Vec<Vec<String>> = stream_splits(metastore, query, 2)
+            .map_ok(|splits| {
+                splits
+                    .iter()
+                    .map(|split| split.split_id().to_string())
+                    .collect()
+            })
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            pages,
+            [
+                vec!["split-0", "split-1"],
+                vec!["split-2", "split-3"],
+                vec!["split-4"],
+            ]
+        );
+    }
+}
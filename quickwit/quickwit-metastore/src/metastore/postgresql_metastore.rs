@@ -21,13 +21,14 @@ use std::collections::HashMap;
 use std::fmt::{Display, Write};
 use std::ops::Bound;
 use std::sync::Arc;
-use std::time::Duration;
 
 use async_trait::async_trait;
 use itertools::Itertools;
 use quickwit_common::uri::Uri;
 use quickwit_common::PrettySample;
-use quickwit_config::{IndexConfig, SourceConfig};
+use quickwit_config::{
+    ClusterSettings, IndexConfig, MetastoreConfig, PostgresMetastoreConfig, SourceConfig,
+};
 use quickwit_doc_mapper::tag_pruning::TagFilterAst;
 use quickwit_proto::metastore_api::{DeleteQuery, DeleteTask};
 use quickwit_proto::IndexUid;
@@ -38,21 +39,20 @@ use tokio::sync::Mutex;
 use tracing::log::LevelFilter;
 use tracing::{debug, error, info, instrument, warn};
 
-use crate::checkpoint::IndexCheckpointDelta;
+use crate::checkpoint::{IndexCheckpointDelta, SourceCheckpoint};
 use crate::metastore::instrumented_metastore::InstrumentedMetastore;
 use crate::metastore::postgresql_model::{
     DeleteTask as PgDeleteTask, Index as PgIndex, Split as PgSplit,
 };
 use crate::metastore::FilterRange;
 use crate::{
-    IndexMetadata, ListSplitsQuery, Metastore, MetastoreError, MetastoreFactory,
-    MetastoreResolverError, MetastoreResult, Split, SplitMetadata, SplitState,
+    split_delete_task_applied, AuditEvent, IndexMetadata, ListSplitsQuery, Metastore,
+    MetastoreError, MetastoreFactory, MetastoreResolverError, MetastoreResult, Split,
+    SplitMetadata, SplitState, SplitsPublication,
 };
 
 static MIGRATOR: Migrator = sqlx::migrate!("migrations/postgresql");
 
-const CONNECTION_POOL_MAX_SIZE: u32 = 10;
-
 // https://www.postgresql.org/docs/current/errcodes-appendix.html
 mod pg_error_code {
     pub const FOREIGN_KEY_VIOLATION: &str = "23503";
@@ -60,13 +60,22 @@ mod pg_error_code {
 }
 
 /// Establishes a connection to the given database URI.
-async fn establish_connection(connection_uri: &Uri) -> MetastoreResult<Pool<Postgres>> {
+async fn establish_connection(
+    postgres_metastore_config: &PostgresMetastoreConfig,
+    connection_uri: &Uri,
+) -> MetastoreResult<Pool<Postgres>> {
     let pool_options = PgPoolOptions::new()
-        .max_connections(CONNECTION_POOL_MAX_SIZE)
-        .idle_timeout(Duration::from_secs(1))
-        .acquire_timeout(Duration::from_secs(2));
+        .max_connections(postgres_metastore_config.max_connections.get())
+        .min_connections(postgres_metastore_config.min_connections)
+        .idle_timeout(postgres_metastore_config.idle_connection_timeout())
+        .acquire_timeout(postgres_metastore_config.acquire_connection_timeout());
     let mut pg_connect_options: PgConnectOptions = connection_uri.as_str().parse()?;
     pg_connect_options.log_statements(LevelFilter::Info);
+    if let Some(statement_timeout) = postgres_metastore_config.statement_timeout_opt() {
+        let statement_timeout_millis = statement_timeout.as_millis().to_string();
+        pg_connect_options =
+            pg_connect_options.options([("statement_timeout", statement_timeout_millis.as_str())]);
+    }
     pool_options
         .connect_with(pg_connect_options)
         .await
@@ -101,18 +110,46 @@ async fn run_postgres_migrations(pool: &Pool<Postgres>) -> MetastoreResult<()> {
 pub struct PostgresqlMetastore {
     uri: Uri,
     connection_pool: Pool<Postgres>,
+    // Pool used for the read-only queries of the search path. It points to the read replica if
+    // one is configured, and to the primary otherwise.
+    read_connection_pool: Pool<Postgres>,
 }
 
 impl PostgresqlMetastore {
     /// Creates a meta store given a database URI.
-    pub async fn new(connection_uri: Uri) -> MetastoreResult<Self> {
-        let connection_pool = establish_connection(&connection_uri).await?;
+    pub async fn new(
+        postgres_metastore_config: &PostgresMetastoreConfig,
+        connection_uri: Uri,
+    ) -> MetastoreResult<Self> {
+        let connection_pool =
+            establish_connection(postgres_metastore_config, &connection_uri).await?;
         run_postgres_migrations(&connection_pool).await?;
+
+        let read_connection_pool = if let Some(read_replica_uri) =
+            &postgres_metastore_config.read_replica_uri
+        {
+            info!(read_replica_uri=%read_replica_uri, "Routing read-only metastore queries to read replica.");
+            establish_connection(postgres_metastore_config, read_replica_uri).await?
+        } else {
+            connection_pool.clone()
+        };
         Ok(PostgresqlMetastore {
             uri: connection_uri,
             connection_pool,
+            read_connection_pool,
         })
     }
+
+    /// Lists the metadata of all the indexes, including the trashed ones.
+    async fn list_any_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
+        let pg_indexes = sqlx::query_as::<_, PgIndex>("SELECT * FROM indexes")
+            .fetch_all(&self.connection_pool)
+            .await?;
+        pg_indexes
+            .into_iter()
+            .map(|pg_index| pg_index.index_metadata())
+            .collect()
+    }
 }
 
 /// Returns an Index object given an index_id or None if it does not exist.
@@ -266,6 +303,23 @@ fn build_query_filter(mut sql: String, query: &ListSplitsQuery) -> String {
         val.to_string()
     });
 
+    let mut next_param_idx = 2;
+    if query.after_split_id.is_some() {
+        let _ = write!(sql, " AND split_id > ${next_param_idx}");
+        next_param_idx += 1;
+    }
+
+    if !query.labels.is_empty() {
+        let _ = write!(
+            sql,
+            " AND (split_metadata_json::jsonb -> 'labels') @> ${next_param_idx}::jsonb"
+        );
+    }
+
+    if query.sort_by_split_id {
+        sql.push_str(" ORDER BY split_id ASC");
+    }
+
     if let Some(limit) = query.limit {
         let _ = write!(sql, " LIMIT {limit}");
     }
@@ -386,6 +440,137 @@ where
     Ok(mutation_occurred)
 }
 
+/// Publishes the staged splits and marks the replaced splits for deletion for a single index within
+/// the given transaction. The caller is responsible for committing or rolling back the transaction.
+async fn publish_splits_in_tx(
+    tx: &mut Transaction<'_, Postgres>,
+    index_uid: &IndexUid,
+    staged_split_ids: &[&str],
+    replaced_split_ids: &[&str],
+    checkpoint_delta_opt: Option<IndexCheckpointDelta>,
+) -> MetastoreResult<()> {
+    let mut index_metadata = index_metadata(tx, index_uid.index_id()).await?;
+    if index_metadata.index_uid != *index_uid {
+        return Err(MetastoreError::IndexDoesNotExist {
+            index_id: index_uid.index_id().to_string(),
+        });
+    }
+    if let Some(checkpoint_delta) = checkpoint_delta_opt {
+        index_metadata
+            .checkpoint
+            .try_apply_delta(checkpoint_delta)?;
+    }
+    let index_metadata_json = serde_json::to_string(&index_metadata).map_err(|error| {
+        MetastoreError::JsonSerializeError {
+            struct_name: "IndexMetadata".to_string(),
+            message: error.to_string(),
+        }
+    })?;
+
+    const PUBLISH_SPLITS_QUERY: &str = r#"
+    -- Select the splits to update, regardless of their state.
+    -- The left join make it possible to identify the splits that do not exist.
+    WITH input_splits AS (
+        SELECT input_splits.split_id, input_splits.expected_split_state, splits.actual_split_state
+        FROM (
+            SELECT split_id, 'Staged' AS expected_split_state
+            FROM UNNEST($3) AS staged_splits(split_id)
+            UNION
+            SELECT split_id, 'Published' AS expected_split_state
+            FROM UNNEST($4) AS published_splits(split_id)
+        ) input_splits
+        LEFT JOIN (
+            SELECT split_id, split_state AS actual_split_state
+            FROM splits
+            WHERE
+                index_uid = $1
+                AND (split_id = ANY($3) OR split_id = ANY($4))
+            FOR UPDATE
+            ) AS splits
+        USING (split_id)
+    ),
+    -- Update the index metadata with the new checkpoint.
+    updated_index_metadata AS (
+        UPDATE indexes
+        SET
+            index_metadata_json = $2
+        WHERE
+            index_uid = $1
+            AND NOT EXISTS (
+                SELECT 1
+                FROM input_splits
+                WHERE
+                    actual_split_state != expected_split_state
+                )
+    ),
+    -- Publish the staged splits and mark the published splits for deletion.
+    updated_splits AS (
+        UPDATE splits
+        SET
+            split_state = CASE split_state
+                WHEN 'Staged' THEN 'Published'
+                ELSE 'MarkedForDeletion'
+            END,
+            update_timestamp = (CURRENT_TIMESTAMP AT TIME ZONE 'UTC'),
+            publish_timestamp = (CURRENT_TIMESTAMP AT TIME ZONE 'UTC')
+        FROM input_splits
+        WHERE
+            splits.index_uid = $1
+            AND splits.split_id = input_splits.split_id
+            AND NOT EXISTS (
+                SELECT 1
+                FROM input_splits
+                WHERE
+                    actual_split_state != expected_split_state
+            )
+    )
+    -- Report the outcome of the update query.
+    SELECT
+        COUNT(1) FILTER (WHERE actual_split_state = 'Staged' AND expected_split_state = 'Staged'),
+        COUNT(1) FILTER (WHERE actual_split_state = 'Published' AND expected_split_state = 'Published'),
+        COALESCE(ARRAY_AGG(split_id) FILTER (WHERE actual_split_state IS NULL), ARRAY[]::TEXT[]),
+        COALESCE(ARRAY_AGG(split_id) FILTER (WHERE actual_split_state != 'Staged' AND expected_split_state = 'Staged'), ARRAY[]::TEXT[]),
+        COALESCE(ARRAY_AGG(split_id) FILTER (WHERE actual_split_state != 'Published' AND expected_split_state = 'Published'), ARRAY[]::TEXT[])
+        FROM input_splits
+"#;
+    let (
+        num_published_splits,
+        num_marked_splits,
+        not_found_split_ids,
+        not_staged_split_ids,
+        not_marked_split_ids,
+    ): (i64, i64, Vec<String>, Vec<String>, Vec<String>) = sqlx::query_as(PUBLISH_SPLITS_QUERY)
+        .bind(index_uid.to_string())
+        .bind(index_metadata_json)
+        .bind(staged_split_ids)
+        .bind(replaced_split_ids)
+        .fetch_one(tx)
+        .await
+        .map_err(|error| convert_sqlx_err(index_uid.index_id(), error))?;
+
+    if !not_found_split_ids.is_empty() {
+        return Err(MetastoreError::SplitsDoNotExist {
+            split_ids: not_found_split_ids,
+        });
+    }
+    if !not_staged_split_ids.is_empty() {
+        return Err(MetastoreError::SplitsNotStaged {
+            split_ids: not_staged_split_ids,
+        });
+    }
+    if !not_marked_split_ids.is_empty() {
+        return Err(MetastoreError::SplitsNotDeletable {
+            split_ids: not_marked_split_ids,
+        });
+    }
+    info!(
+        index_id=%index_uid.index_id(),
+        "Published {} splits and marked {} splits for deletion successfully.",
+        num_published_splits, num_marked_splits
+    );
+    Ok(())
+}
+
 #[async_trait]
 impl Metastore for PostgresqlMetastore {
     async fn check_connectivity(&self) -> anyhow::Result<()> {
@@ -395,13 +580,24 @@ impl Metastore for PostgresqlMetastore {
 
     #[instrument(skip(self))]
     async fn list_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
-        let pg_indexes = sqlx::query_as::<_, PgIndex>("SELECT * FROM indexes")
-            .fetch_all(&self.connection_pool)
-            .await?;
-        pg_indexes
+        let indexes_metadatas = self
+            .list_any_indexes_metadatas()
+            .await?
             .into_iter()
-            .map(|pg_index| pg_index.index_metadata())
-            .collect()
+            .filter(|index_metadata| !index_metadata.is_trashed())
+            .collect();
+        Ok(indexes_metadatas)
+    }
+
+    #[instrument(skip(self))]
+    async fn list_trashed_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
+        let indexes_metadatas = self
+            .list_any_indexes_metadatas()
+            .await?
+            .into_iter()
+            .filter(|index_metadata| index_metadata.is_trashed())
+            .collect();
+        Ok(indexes_metadatas)
     }
 
     #[instrument(skip(self), fields(index_id=&index_config.index_id))]
@@ -425,6 +621,32 @@ impl Metastore for PostgresqlMetastore {
         Ok(index_metadata.index_uid)
     }
 
+    #[instrument(skip(self, index_config), fields(index_id=index_uid.index_id()))]
+    async fn update_index_config(
+        &self,
+        index_uid: IndexUid,
+        index_config: IndexConfig,
+        expected_config_version_opt: Option<u64>,
+    ) -> MetastoreResult<IndexMetadata> {
+        run_with_tx!(self.connection_pool, tx, {
+            let mut updated_index_metadata_opt: Option<IndexMetadata> = None;
+            mutate_index_metadata::<MetastoreError, _>(
+                tx,
+                index_uid,
+                |index_metadata: &mut IndexMetadata| {
+                    index_metadata
+                        .update_index_config(index_config, expected_config_version_opt)?;
+                    updated_index_metadata_opt = Some(index_metadata.clone());
+                    Ok(true)
+                },
+            )
+            .await?;
+            let updated_index_metadata =
+                updated_index_metadata_opt.expect("The index metadata should have been updated.");
+            Ok(updated_index_metadata)
+        })
+    }
+
     #[instrument(skip(self), fields(index_id=index_uid.index_id()))]
     async fn delete_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
         let delete_res = sqlx::query("DELETE FROM indexes WHERE index_uid = $1")
@@ -439,6 +661,52 @@ impl Metastore for PostgresqlMetastore {
         Ok(())
     }
 
+    #[instrument(skip(self), fields(index_id=index_uid.index_id()))]
+    async fn trash_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
+        run_with_tx!(self.connection_pool, tx, {
+            mutate_index_metadata::<MetastoreError, _>(
+                tx,
+                index_uid,
+                |index_metadata: &mut IndexMetadata| Ok(index_metadata.trash()),
+            )
+            .await?;
+            Ok(())
+        })
+    }
+
+    #[instrument(skip(self), fields(index_id=index_uid.index_id()))]
+    async fn restore_index(&self, index_uid: IndexUid) -> MetastoreResult<IndexMetadata> {
+        run_with_tx!(self.connection_pool, tx, {
+            let mut restored_index_metadata_opt: Option<IndexMetadata> = None;
+            mutate_index_metadata::<MetastoreError, _>(
+                tx,
+                index_uid,
+                |index_metadata: &mut IndexMetadata| {
+                    let mutation_occurred = index_metadata.restore();
+                    restored_index_metadata_opt = Some(index_metadata.clone());
+                    Ok(mutation_occurred)
+                },
+            )
+            .await?;
+            let restored_index_metadata =
+                restored_index_metadata_opt.expect("The index metadata should have been restored.");
+            Ok(restored_index_metadata)
+        })
+    }
+
+    #[instrument(skip(self), fields(index_id=index_uid.index_id()))]
+    async fn set_merges_paused(&self, index_uid: IndexUid, paused: bool) -> MetastoreResult<()> {
+        run_with_tx!(self.connection_pool, tx, {
+            mutate_index_metadata::<MetastoreError, _>(
+                tx,
+                index_uid,
+                |index_metadata: &mut IndexMetadata| Ok(index_metadata.set_merges_paused(paused)),
+            )
+            .await?;
+            Ok(())
+        })
+    }
+
     #[instrument(skip(self, split_metadata_list), fields(split_ids))]
     async fn stage_splits(
         &self,
@@ -544,126 +812,46 @@ impl Metastore for PostgresqlMetastore {
         checkpoint_delta_opt: Option<IndexCheckpointDelta>,
     ) -> MetastoreResult<()> {
         run_with_tx!(self.connection_pool, tx, {
-            let mut index_metadata = index_metadata(tx, index_uid.index_id()).await?;
-            if index_metadata.index_uid != index_uid {
-                return Err(MetastoreError::IndexDoesNotExist {
-                    index_id: index_uid.index_id().to_string(),
-                });
-            }
-            if let Some(checkpoint_delta) = checkpoint_delta_opt {
-                index_metadata
-                    .checkpoint
-                    .try_apply_delta(checkpoint_delta)?;
-            }
-            let index_metadata_json = serde_json::to_string(&index_metadata).map_err(|error| {
-                MetastoreError::JsonSerializeError {
-                    struct_name: "IndexMetadata".to_string(),
-                    message: error.to_string(),
-                }
-            })?;
-
-            const PUBLISH_SPLITS_QUERY: &str = r#"
-            -- Select the splits to update, regardless of their state.
-            -- The left join make it possible to identify the splits that do not exist.
-            WITH input_splits AS (
-                SELECT input_splits.split_id, input_splits.expected_split_state, splits.actual_split_state
-                FROM (
-                    SELECT split_id, 'Staged' AS expected_split_state
-                    FROM UNNEST($3) AS staged_splits(split_id)
-                    UNION
-                    SELECT split_id, 'Published' AS expected_split_state
-                    FROM UNNEST($4) AS published_splits(split_id)
-                ) input_splits
-                LEFT JOIN (
-                    SELECT split_id, split_state AS actual_split_state
-                    FROM splits
-                    WHERE
-                        index_uid = $1
-                        AND (split_id = ANY($3) OR split_id = ANY($4))
-                    FOR UPDATE
-                    ) AS splits
-                USING (split_id)
-            ),
-            -- Update the index metadata with the new checkpoint.
-            updated_index_metadata AS (
-                UPDATE indexes
-                SET
-                    index_metadata_json = $2
-                WHERE
-                    index_uid = $1
-                    AND NOT EXISTS (
-                        SELECT 1
-                        FROM input_splits
-                        WHERE
-                            actual_split_state != expected_split_state
-                        )
-            ),
-            -- Publish the staged splits and mark the published splits for deletion.
-            updated_splits AS (
-                UPDATE splits
-                SET
-                    split_state = CASE split_state
-                        WHEN 'Staged' THEN 'Published'
-                        ELSE 'MarkedForDeletion'
-                    END,
-                    update_timestamp = (CURRENT_TIMESTAMP AT TIME ZONE 'UTC'),
-                    publish_timestamp = (CURRENT_TIMESTAMP AT TIME ZONE 'UTC')
-                FROM input_splits
-                WHERE
-                    splits.index_uid = $1
-                    AND splits.split_id = input_splits.split_id
-                    AND NOT EXISTS (
-                        SELECT 1
-                        FROM input_splits
-                        WHERE
-                            actual_split_state != expected_split_state
-                    )
+            publish_splits_in_tx(
+                tx,
+                &index_uid,
+                staged_split_ids,
+                replaced_split_ids,
+                checkpoint_delta_opt,
             )
-            -- Report the outcome of the update query.
-            SELECT
-                COUNT(1) FILTER (WHERE actual_split_state = 'Staged' AND expected_split_state = 'Staged'),
-                COUNT(1) FILTER (WHERE actual_split_state = 'Published' AND expected_split_state = 'Published'),
-                COALESCE(ARRAY_AGG(split_id) FILTER (WHERE actual_split_state IS NULL), ARRAY[]::TEXT[]),
-                COALESCE(ARRAY_AGG(split_id) FILTER (WHERE actual_split_state != 'Staged' AND expected_split_state = 'Staged'), ARRAY[]::TEXT[]),
-                COALESCE(ARRAY_AGG(split_id) FILTER (WHERE actual_split_state != 'Published' AND expected_split_state = 'Published'), ARRAY[]::TEXT[])
-                FROM input_splits
-        "#;
-            let (
-                num_published_splits,
-                num_marked_splits,
-                not_found_split_ids,
-                not_staged_split_ids,
-                not_marked_split_ids,
-            ): (i64, i64, Vec<String>, Vec<String>, Vec<String>) =
-                sqlx::query_as(PUBLISH_SPLITS_QUERY)
-                    .bind(index_uid.to_string())
-                    .bind(index_metadata_json)
-                    .bind(staged_split_ids)
-                    .bind(replaced_split_ids)
-                    .fetch_one(tx)
-                    .await
-                    .map_err(|error| convert_sqlx_err(index_uid.index_id(), error))?;
-
-            if !not_found_split_ids.is_empty() {
-                return Err(MetastoreError::SplitsDoNotExist {
-                    split_ids: not_found_split_ids,
-                });
-            }
-            if !not_staged_split_ids.is_empty() {
-                return Err(MetastoreError::SplitsNotStaged {
-                    split_ids: not_staged_split_ids,
-                });
-            }
-            if !not_marked_split_ids.is_empty() {
-                return Err(MetastoreError::SplitsNotDeletable {
-                    split_ids: not_marked_split_ids,
-                });
+            .await
+        })
+    }
+
+    #[instrument(skip(self, publications), fields(num_publications=publications.len()))]
+    async fn publish_splits_across_indexes(
+        &self,
+        mut publications: Vec<SplitsPublication>,
+    ) -> MetastoreResult<()> {
+        // Locking the indexes in a consistent order prevents concurrent calls from deadlocking.
+        // The sort is stable, so publications targeting the same index keep their relative order.
+        publications.sort_by(|left, right| left.index_uid.cmp(&right.index_uid));
+        run_with_tx!(self.connection_pool, tx, {
+            for publication in publications {
+                let staged_split_ids: Vec<&str> = publication
+                    .staged_split_ids
+                    .iter()
+                    .map(String::as_str)
+                    .collect();
+                let replaced_split_ids: Vec<&str> = publication
+                    .replaced_split_ids
+                    .iter()
+                    .map(String::as_str)
+                    .collect();
+                publish_splits_in_tx(
+                    tx,
+                    &publication.index_uid,
+                    &staged_split_ids,
+                    &replaced_split_ids,
+                    publication.checkpoint_delta_opt,
+                )
+                .await?;
             }
-            info!(
-                index_id=%index_uid.index_id(),
-                "Published {} splits and marked {} splits for deletion successfully.",
-                num_published_splits, num_marked_splits
-            );
             Ok(())
         })
     }
@@ -673,12 +861,24 @@ impl Metastore for PostgresqlMetastore {
         let sql_base = "SELECT * FROM splits".to_string();
         let sql = build_query_filter(sql_base, &query);
 
-        let pg_splits = sqlx::query_as::<_, PgSplit>(&sql)
-            .bind(query.index_uid.to_string())
-            .fetch_all(&self.connection_pool)
-            .await?;
+        let mut sql_query = sqlx::query_as::<_, PgSplit>(&sql).bind(query.index_uid.to_string());
+
+        if let Some(after_split_id) = query.after_split_id.as_ref() {
+            sql_query = sql_query.bind(after_split_id);
+        }
+        if !query.labels.is_empty() {
+            let labels_json = serde_json::to_string(&query.labels).map_err(|error| {
+                MetastoreError::JsonSerializeError {
+                    struct_name: "BTreeMap<String, String>".to_string(),
+                    message: error.to_string(),
+                }
+            })?;
+            sql_query = sql_query.bind(labels_json);
+        }
+        let pg_splits = sql_query.fetch_all(&self.read_connection_pool).await?;
 
         // If no splits were returned, maybe the index does not exist in the first place?
+        // This check locks the index row, so it must run against the primary.
         if pg_splits.is_empty()
             && index_opt_for_uid(&self.connection_pool, query.index_uid.clone())
                 .await?
@@ -690,7 +890,13 @@ impl Metastore for PostgresqlMetastore {
         }
         pg_splits
             .into_iter()
-            .map(|pg_split| pg_split.try_into())
+            .map(|pg_split| {
+                let mut split: Split = pg_split.try_into()?;
+                if !query.with_bloom_filters {
+                    split.split_metadata.bloom_filters.clear();
+                }
+                Ok(split)
+            })
             .collect()
     }
 
@@ -854,12 +1060,18 @@ impl Metastore for PostgresqlMetastore {
 
     #[instrument(skip(self), fields(index_id=index_id))]
     async fn index_metadata(&self, index_id: &str) -> MetastoreResult<IndexMetadata> {
-        index_opt(&self.connection_pool, index_id)
+        let index_metadata = index_opt(&self.connection_pool, index_id)
             .await?
             .ok_or_else(|| MetastoreError::IndexDoesNotExist {
                 index_id: index_id.to_string(),
             })?
-            .index_metadata()
+            .index_metadata()?;
+        if index_metadata.is_trashed() {
+            return Err(MetastoreError::IndexDoesNotExist {
+                index_id: index_id.to_string(),
+            });
+        }
+        Ok(index_metadata)
     }
 
     #[instrument(skip(self, source), fields(index_id=index_uid.index_id(), source_id=source.source_id))]
@@ -920,6 +1132,22 @@ impl Metastore for PostgresqlMetastore {
         })
     }
 
+    #[instrument(skip(self, target_checkpoint), fields(index_id=index_uid.index_id(), source_id=source_id))]
+    async fn rewind_source_checkpoint(
+        &self,
+        index_uid: IndexUid,
+        source_id: &str,
+        target_checkpoint: SourceCheckpoint,
+    ) -> MetastoreResult<()> {
+        run_with_tx!(self.connection_pool, tx, {
+            mutate_index_metadata(tx, index_uid, |index_metadata| {
+                index_metadata.rewind_source_checkpoint(source_id, &target_checkpoint)
+            })
+            .await?;
+            Ok(())
+        })
+    }
+
     fn uri(&self) -> &Uri {
         &self.uri
     }
@@ -1048,6 +1276,60 @@ impl Metastore for PostgresqlMetastore {
             .collect()
     }
 
+    /// Cancels the delete task with opstamp `opstamp` if it has not been applied to any split yet.
+    #[instrument(skip(self), fields(index_id=index_uid.index_id()))]
+    async fn cancel_delete_task(
+        &self,
+        index_uid: IndexUid,
+        opstamp: u64,
+    ) -> MetastoreResult<DeleteTask> {
+        run_with_tx!(self.connection_pool, tx, {
+            let pg_delete_task_opt: Option<PgDeleteTask> = sqlx::query_as::<_, PgDeleteTask>(
+                r#"
+                SELECT * FROM delete_tasks
+                WHERE
+                    index_uid = $1
+                    AND opstamp = $2
+                FOR UPDATE
+                "#,
+            )
+            .bind(index_uid.to_string())
+            .bind(opstamp as i64)
+            .fetch_optional(&mut *tx)
+            .await?;
+            let delete_task: DeleteTask = pg_delete_task_opt
+                .ok_or(MetastoreError::DeleteTaskDoesNotExist { opstamp })?
+                .try_into()?;
+            // Lock the splits that may have been stamped with the delete task so that the delete
+            // task pipeline cannot apply it concurrently.
+            let pg_splits: Vec<PgSplit> = sqlx::query_as::<_, PgSplit>(
+                r#"
+                SELECT * FROM splits
+                WHERE
+                    index_uid = $1
+                    AND delete_opstamp >= $2
+                FOR UPDATE
+                "#,
+            )
+            .bind(index_uid.to_string())
+            .bind(opstamp as i64)
+            .fetch_all(&mut *tx)
+            .await?;
+            for pg_split in pg_splits {
+                let split: Split = pg_split.try_into()?;
+                if split_delete_task_applied(&split, &delete_task) {
+                    return Err(MetastoreError::DeleteTaskNotCancellable { opstamp });
+                }
+            }
+            sqlx::query("DELETE FROM delete_tasks WHERE index_uid = $1 AND opstamp = $2")
+                .bind(index_uid.to_string())
+                .bind(opstamp as i64)
+                .execute(tx)
+                .await?;
+            Ok(delete_task)
+        })
+    }
+
     /// Returns `num_splits` published splits with `split.delete_opstamp` < `delete_opstamp`.
     /// Results are ordered by ascending `split.delete_opstamp` and `split.publish_timestamp`
     /// values.
@@ -1092,6 +1374,90 @@ impl Metastore for PostgresqlMetastore {
             .map(|pg_split| pg_split.try_into())
             .collect()
     }
+
+    #[instrument(skip(self, audit_event), fields(index_id=audit_event.index_uid.index_id()))]
+    async fn append_audit_event(&self, audit_event: AuditEvent) -> MetastoreResult<()> {
+        let audit_event_json = serde_json::to_string(&audit_event).map_err(|error| {
+            MetastoreError::JsonSerializeError {
+                struct_name: "AuditEvent".to_string(),
+                message: error.to_string(),
+            }
+        })?;
+        sqlx::query("INSERT INTO audit_log (index_id, audit_event_json) VALUES ($1, $2)")
+            .bind(audit_event.index_uid.index_id())
+            .bind(&audit_event_json)
+            .execute(&self.connection_pool)
+            .await?;
+        Ok(())
+    }
+
+    #[instrument(skip(self))]
+    async fn list_audit_events(&self, index_id: &str) -> MetastoreResult<Vec<AuditEvent>> {
+        let audit_event_jsons: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT audit_event_json
+            FROM audit_log
+            WHERE index_id = $1
+            ORDER BY id ASC
+            "#,
+        )
+        .bind(index_id)
+        .fetch_all(&self.connection_pool)
+        .await?;
+        audit_event_jsons
+            .iter()
+            .map(|audit_event_json| {
+                serde_json::from_str(audit_event_json).map_err(|error| {
+                    MetastoreError::JsonDeserializeError {
+                        struct_name: "AuditEvent".to_string(),
+                        message: error.to_string(),
+                    }
+                })
+            })
+            .collect()
+    }
+
+    #[instrument(skip(self))]
+    async fn cluster_settings(&self) -> MetastoreResult<ClusterSettings> {
+        let cluster_settings_json_opt: Option<String> =
+            sqlx::query_scalar("SELECT cluster_settings_json FROM cluster_settings WHERE id = 1")
+                .fetch_optional(&self.connection_pool)
+                .await?;
+        let cluster_settings_json = match cluster_settings_json_opt {
+            Some(cluster_settings_json) => cluster_settings_json,
+            None => return Ok(ClusterSettings::default()),
+        };
+        serde_json::from_str(&cluster_settings_json).map_err(|error| {
+            MetastoreError::JsonDeserializeError {
+                struct_name: "ClusterSettings".to_string(),
+                message: error.to_string(),
+            }
+        })
+    }
+
+    #[instrument(skip(self, cluster_settings))]
+    async fn update_cluster_settings(
+        &self,
+        cluster_settings: ClusterSettings,
+    ) -> MetastoreResult<()> {
+        let cluster_settings_json = serde_json::to_string(&cluster_settings).map_err(|error| {
+            MetastoreError::JsonSerializeError {
+                struct_name: "ClusterSettings".to_string(),
+                message: error.to_string(),
+            }
+        })?;
+        sqlx::query(
+            r#"
+            INSERT INTO cluster_settings (id, cluster_settings_json)
+            VALUES (1, $1)
+            ON CONFLICT (id) DO UPDATE SET cluster_settings_json = EXCLUDED.cluster_settings_json
+            "#,
+        )
+        .bind(&cluster_settings_json)
+        .execute(&self.connection_pool)
+        .await?;
+        Ok(())
+    }
 }
 
 // We use dollar-quoted strings in Postgresql.
@@ -1185,20 +1551,44 @@ impl PostgresqlMetastoreFactory {
 #[async_trait]
 impl MetastoreFactory for PostgresqlMetastoreFactory {
     async fn resolve(&self, uri: &Uri) -> Result<Arc<dyn Metastore>, MetastoreResolverError> {
+        self.resolve_with_config(&MetastoreConfig::default(), uri)
+            .await
+    }
+
+    /// The metastore is cached per URI: the configuration passed the first time a URI is
+    /// resolved prevails.
+    async fn resolve_with_config(
+        &self,
+        metastore_config: &MetastoreConfig,
+        uri: &Uri,
+    ) -> Result<Arc<dyn Metastore>, MetastoreResolverError> {
         if let Some(metastore) = self.get_from_cache(uri).await {
             debug!("using metastore from cache");
             return Ok(metastore);
         }
         debug!("metastore not found in cache");
-        let postgresql_metastore = PostgresqlMetastore::new(uri.clone())
-            .await
-            .map_err(MetastoreResolverError::FailedToOpenMetastore)?;
+        let postgresql_metastore =
+            PostgresqlMetastore::new(&metastore_config.postgres_config, uri.clone())
+                .await
+                .map_err(MetastoreResolverError::FailedToOpenMetastore)?;
         let instrumented_metastore = InstrumentedMetastore::new(Box::new(postgresql_metastore));
         let unique_metastore_for_uri = self
             .cache_metastore(uri.clone(), Arc::new(instrumented_metastore))
             .await;
         Ok(unique_metastore_for_uri)
     }
+
+    /// Only establishes a connection to the database: unlike `resolve_with_config`, it does not
+    /// run the migrations.
+    async fn check_connectivity(
+        &self,
+        metastore_config: &MetastoreConfig,
+        uri: &Uri,
+    ) -> anyhow::Result<()> {
+        let connection_pool = establish_connection(&metastore_config.postgres_config, uri).await?;
+        connection_pool.acquire().await?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -1222,7 +1612,7 @@ impl crate::tests::test_suite::DefaultForTest for PostgresqlMetastore {
             .expect("Environment variable `TEST_DATABASE_URL` should be set.")
             .parse()
             .expect("Environment variable `TEST_DATABASE_URL` should be a valid URI.");
-        PostgresqlMetastore::new(uri)
+        PostgresqlMetastore::new(&PostgresMetastoreConfig::default(), uri)
             .await
             .expect("Failed to initialize test PostgreSQL metastore.")
     }
@@ -1397,7 +1787,7 @@ mod tests {
              <= to_timestamp(63)"
         );
 
-        let query = ListSplitsQuery::for_index(index_uid)
+        let query = ListSplitsQuery::for_index(index_uid.clone())
             .with_time_range_start_gt(90)
             .with_tags_filter(TagFilterAst::Tag {
                 is_present: true,
@@ -1409,5 +1799,34 @@ mod tests {
             " WHERE index_uid = $1 AND ($$tag-1$$ = ANY(tags)) AND (time_range_end > 90 OR \
              time_range_end IS NULL)"
         );
+
+        let query = ListSplitsQuery::for_index(index_uid.clone())
+            .with_split_state(SplitState::Published)
+            .with_after_split_id("split-1")
+            .sort_by_split_id()
+            .with_limit(10);
+        let sql = build_query_filter(String::new(), &query);
+        assert_eq!(
+            sql,
+            " WHERE index_uid = $1 AND split_state IN ('Published') AND split_id > $2 ORDER BY \
+             split_id ASC LIMIT 10"
+        );
+
+        let query = ListSplitsQuery::for_index(index_uid.clone())
+            .with_label("backfill", "2023-01")
+            .with_after_split_id("split-1");
+        let sql = build_query_filter(String::new(), &query);
+        assert_eq!(
+            sql,
+            " WHERE index_uid = $1 AND split_id > $2 AND (split_metadata_json::jsonb -> 'labels') \
+             @> $3::jsonb"
+        );
+
+        let query = ListSplitsQuery::for_index(index_uid).with_label("backfill", "2023-01");
+        let sql = build_query_filter(String::new(), &query);
+        assert_eq!(
+            sql,
+            " WHERE index_uid = $1 AND (split_metadata_json::jsonb -> 'labels') @> $2::jsonb"
+        );
     }
 }
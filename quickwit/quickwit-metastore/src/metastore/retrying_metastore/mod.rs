@@ -24,13 +24,16 @@ mod test;
 
 use async_trait::async_trait;
 use quickwit_common::uri::Uri;
-use quickwit_config::{IndexConfig, SourceConfig};
+use quickwit_config::{ClusterSettings, IndexConfig, SourceConfig};
 use quickwit_proto::metastore_api::{DeleteQuery, DeleteTask};
 use quickwit_proto::IndexUid;
 
 use self::retry::{retry, RetryParams};
-use crate::checkpoint::IndexCheckpointDelta;
-use crate::{IndexMetadata, ListSplitsQuery, Metastore, MetastoreResult, Split, SplitMetadata};
+use crate::checkpoint::{IndexCheckpointDelta, SourceCheckpoint};
+use crate::{
+    AuditEvent, IndexMetadata, ListSplitsQuery, Metastore, MetastoreResult, Split, SplitMetadata,
+    SplitsPage, SplitsPublication,
+};
 
 /// Retry layer for a [`Metastore`].
 /// This is a band-aid solution for now. This will be removed after retry can be usable on
@@ -92,6 +95,24 @@ impl Metastore for RetryingMetastore {
         .await
     }
 
+    async fn update_index_config(
+        &self,
+        index_uid: IndexUid,
+        index_config: IndexConfig,
+        expected_config_version_opt: Option<u64>,
+    ) -> MetastoreResult<IndexMetadata> {
+        retry(&self.retry_params, || async {
+            self.inner
+                .update_index_config(
+                    index_uid.clone(),
+                    index_config.clone(),
+                    expected_config_version_opt,
+                )
+                .await
+        })
+        .await
+    }
+
     async fn delete_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
         retry(&self.retry_params, || async {
             self.inner.delete_index(index_uid.clone()).await
@@ -99,6 +120,43 @@ impl Metastore for RetryingMetastore {
         .await
     }
 
+    async fn trash_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
+        retry(&self.retry_params, || async {
+            self.inner.trash_index(index_uid.clone()).await
+        })
+        .await
+    }
+
+    async fn restore_index(&self, index_uid: IndexUid) -> MetastoreResult<IndexMetadata> {
+        retry(&self.retry_params, || async {
+            self.inner.restore_index(index_uid.clone()).await
+        })
+        .await
+    }
+
+    async fn list_trashed_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
+        retry(&self.retry_params, || async {
+            self.inner.list_trashed_indexes_metadatas().await
+        })
+        .await
+    }
+
+    async fn set_merges_paused(&self, index_uid: IndexUid, paused: bool) -> MetastoreResult<()> {
+        retry(&self.retry_params, || async {
+            self.inner
+                .set_merges_paused(index_uid.clone(), paused)
+                .await
+        })
+        .await
+    }
+
+    async fn list_tenants(&self) -> MetastoreResult<Vec<String>> {
+        retry(&self.retry_params, || async {
+            self.inner.list_tenants().await
+        })
+        .await
+    }
+
     async fn stage_splits(
         &self,
         index_uid: IndexUid,
@@ -132,6 +190,15 @@ impl Metastore for RetryingMetastore {
         .await
     }
 
+    async fn publish_splits_across_indexes(
+        &self,
+        publications: Vec<SplitsPublication>,
+    ) -> MetastoreResult<()> {
+        // Not retried: a transient error may be returned after the publications were applied, and
+        // replaying them would then fail because their splits are no longer staged.
+        self.inner.publish_splits_across_indexes(publications).await
+    }
+
     async fn list_splits(&self, query: ListSplitsQuery) -> MetastoreResult<Vec<Split>> {
         retry(&self.retry_params, || async {
             self.inner.list_splits(query.clone()).await
@@ -139,6 +206,17 @@ impl Metastore for RetryingMetastore {
         .await
     }
 
+    async fn list_splits_page(
+        &self,
+        query: ListSplitsQuery,
+        page_size: usize,
+    ) -> MetastoreResult<SplitsPage> {
+        retry(&self.retry_params, || async {
+            self.inner.list_splits_page(query.clone(), page_size).await
+        })
+        .await
+    }
+
     async fn list_all_splits(&self, index_uid: IndexUid) -> MetastoreResult<Vec<Split>> {
         retry(&self.retry_params, || async {
             self.inner.list_all_splits(index_uid.clone()).await
@@ -220,6 +298,20 @@ impl Metastore for RetryingMetastore {
         .await
     }
 
+    async fn rewind_source_checkpoint(
+        &self,
+        index_uid: IndexUid,
+        source_id: &str,
+        target_checkpoint: SourceCheckpoint,
+    ) -> MetastoreResult<()> {
+        retry(&self.retry_params, || async {
+            self.inner
+                .rewind_source_checkpoint(index_uid.clone(), source_id, target_checkpoint.clone())
+                .await
+        })
+        .await
+    }
+
     async fn delete_source(&self, index_uid: IndexUid, source_id: &str) -> MetastoreResult<()> {
         retry(&self.retry_params, || async {
             self.inner.delete_source(index_uid.clone(), source_id).await
@@ -267,4 +359,50 @@ impl Metastore for RetryingMetastore {
         })
         .await
     }
+
+    async fn cancel_delete_task(
+        &self,
+        index_uid: IndexUid,
+        opstamp: u64,
+    ) -> MetastoreResult<DeleteTask> {
+        retry(&self.retry_params, || async {
+            self.inner
+                .cancel_delete_task(index_uid.clone(), opstamp)
+                .await
+        })
+        .await
+    }
+
+    async fn append_audit_event(&self, audit_event: AuditEvent) -> MetastoreResult<()> {
+        retry(&self.retry_params, || async {
+            self.inner.append_audit_event(audit_event.clone()).await
+        })
+        .await
+    }
+
+    async fn list_audit_events(&self, index_id: &str) -> MetastoreResult<Vec<AuditEvent>> {
+        retry(&self.retry_params, || async {
+            self.inner.list_audit_events(index_id).await
+        })
+        .await
+    }
+
+    async fn cluster_settings(&self) -> MetastoreResult<ClusterSettings> {
+        retry(&self.retry_params, || async {
+            self.inner.cluster_settings().await
+        })
+        .await
+    }
+
+    async fn update_cluster_settings(
+        &self,
+        cluster_settings: ClusterSettings,
+    ) -> MetastoreResult<()> {
+        retry(&self.retry_params, || async {
+            self.inner
+                .update_cluster_settings(cluster_settings.clone())
+                .await
+        })
+        .await
+    }
 }
@@ -21,15 +21,15 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 
 use async_trait::async_trait;
 use quickwit_common::uri::Uri;
-use quickwit_config::{IndexConfig, SourceConfig};
+use quickwit_config::{ClusterSettings, IndexConfig, SourceConfig};
 use quickwit_proto::metastore_api::{DeleteQuery, DeleteTask};
 use quickwit_proto::IndexUid;
 
 use super::retry::RetryParams;
-use crate::checkpoint::IndexCheckpointDelta;
+use crate::checkpoint::{IndexCheckpointDelta, SourceCheckpoint};
 use crate::{
-    IndexMetadata, ListSplitsQuery, Metastore, MetastoreError, MetastoreResult, RetryingMetastore,
-    Split, SplitMetadata,
+    AuditEvent, IndexMetadata, ListSplitsQuery, Metastore, MetastoreError, MetastoreResult,
+    RetryingMetastore, Split, SplitMetadata, SplitsPublication,
 };
 
 struct RetryTestMetastore {
@@ -105,10 +105,47 @@ impl Metastore for RetryTestMetastore {
         }
     }
 
+    async fn update_index_config(
+        &self,
+        _index_uid: IndexUid,
+        index_config: IndexConfig,
+        _expected_config_version_opt: Option<u64>,
+    ) -> MetastoreResult<IndexMetadata> {
+        let result = self.try_success();
+        match result {
+            Ok(_) => Ok(IndexMetadata::new(index_config)),
+            Err(err) => Err(err),
+        }
+    }
+
     async fn delete_index(&self, _index_uid: IndexUid) -> MetastoreResult<()> {
         self.try_success()
     }
 
+    async fn trash_index(&self, _index_uid: IndexUid) -> MetastoreResult<()> {
+        self.try_success()
+    }
+
+    async fn restore_index(&self, index_uid: IndexUid) -> MetastoreResult<IndexMetadata> {
+        let result = self.try_success();
+        match result {
+            Ok(_) => Ok(IndexMetadata::for_test(index_uid.index_id(), "")),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn list_trashed_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
+        let result = self.try_success();
+        match result {
+            Ok(_) => Ok(Vec::new()),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn set_merges_paused(&self, _index_uid: IndexUid, _paused: bool) -> MetastoreResult<()> {
+        self.try_success()
+    }
+
     async fn stage_splits(
         &self,
         _index_uid: IndexUid,
@@ -127,6 +164,13 @@ impl Metastore for RetryTestMetastore {
         self.try_success()
     }
 
+    async fn publish_splits_across_indexes(
+        &self,
+        _publications: Vec<SplitsPublication>,
+    ) -> MetastoreResult<()> {
+        self.try_success()
+    }
+
     async fn list_splits(&self, _query: ListSplitsQuery) -> MetastoreResult<Vec<Split>> {
         let result = self.try_success();
         match result {
@@ -172,6 +216,15 @@ impl Metastore for RetryTestMetastore {
         self.try_success()
     }
 
+    async fn rewind_source_checkpoint(
+        &self,
+        _index_uid: IndexUid,
+        _source_id: &str,
+        _target_checkpoint: SourceCheckpoint,
+    ) -> MetastoreResult<()> {
+        self.try_success()
+    }
+
     async fn delete_source(&self, _index_uid: IndexUid, _source_id: &str) -> MetastoreResult<()> {
         self.try_success()
     }
@@ -216,6 +269,49 @@ impl Metastore for RetryTestMetastore {
             Err(err) => Err(err),
         }
     }
+
+    async fn cancel_delete_task(
+        &self,
+        _index_uid: IndexUid,
+        opstamp: u64,
+    ) -> MetastoreResult<DeleteTask> {
+        let result = self.try_success();
+        match result {
+            Ok(_) => Ok(DeleteTask {
+                create_timestamp: 0,
+                opstamp,
+                delete_query: None,
+            }),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn append_audit_event(&self, _audit_event: AuditEvent) -> MetastoreResult<()> {
+        self.try_success()
+    }
+
+    async fn list_audit_events(&self, _index_id: &str) -> MetastoreResult<Vec<AuditEvent>> {
+        let result = self.try_success();
+        match result {
+            Ok(_) => Ok(Vec::new()),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn cluster_settings(&self) -> MetastoreResult<ClusterSettings> {
+        let result = self.try_success();
+        match result {
+            Ok(_) => Ok(ClusterSettings::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    async fn update_cluster_settings(
+        &self,
+        _cluster_settings: ClusterSettings,
+    ) -> MetastoreResult<()> {
+        self.try_success()
+    }
 }
 
 #[tokio::test]
@@ -309,3 +405,24 @@ async fn test_mixed_retryable_metastore_errors() {
         }
     )
 }
+
+#[tokio::test]
+async fn test_publish_splits_across_indexes_is_not_retried() {
+    let metastore: RetryingMetastore = RetryTestMetastore::new_retrying_with_errors(
+        5,
+        &[MetastoreError::ConnectionError {
+            message: "".to_string(),
+        }],
+    );
+
+    let error = metastore
+        .publish_splits_across_indexes(Vec::new())
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        MetastoreError::ConnectionError {
+            message: "".to_string()
+        }
+    )
+}
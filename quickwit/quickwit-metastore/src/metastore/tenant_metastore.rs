@@ -0,0 +1,723 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use quickwit_common::uri::Uri;
+use quickwit_config::{validate_identifier, ClusterSettings, IndexConfig, SourceConfig};
+use quickwit_proto::metastore_api::{DeleteQuery, DeleteTask};
+use quickwit_proto::IndexUid;
+
+use crate::checkpoint::{IndexCheckpointDelta, SourceCheckpoint};
+use crate::{
+    AuditEvent, IndexMetadata, ListSplitsQuery, Metastore, MetastoreError, MetastoreResult, Split,
+    SplitMetadata, SplitsPage, SplitsPublication,
+};
+
+/// Separates the tenant ID from the index ID in the index IDs stored by the underlying
+/// metastore. The separator is not a valid identifier character, so a tenant-scoped index ID
+/// never collides with the ID of an index created outside of any tenant.
+pub const TENANT_SEPARATOR: char = '.';
+
+/// Returns the index ID under which the index `index_id` of tenant `tenant_id` is stored in the
+/// underlying metastore.
+pub fn tenant_index_id(tenant_id: &str, index_id: &str) -> String {
+    format!("{tenant_id}{TENANT_SEPARATOR}{index_id}")
+}
+
+/// Splits a tenant-scoped index ID into its tenant ID and index ID. Returns `None` if the index
+/// does not belong to any tenant.
+pub fn split_tenant_index_id(tenant_index_id: &str) -> Option<(&str, &str)> {
+    tenant_index_id.split_once(TENANT_SEPARATOR)
+}
+
+/// Returns the URI under which the index stored at `index_uri` of tenant `tenant_id` is stored:
+/// the last component of the URI is moved into a directory named after the tenant. URIs that are
+/// already in such a directory are left untouched.
+pub fn tenant_index_uri(tenant_id: &str, index_uri: &Uri) -> anyhow::Result<Uri> {
+    let (Some(parent_uri), Some(file_name)) = (index_uri.parent(), index_uri.file_name()) else {
+        return index_uri.join(tenant_id);
+    };
+    if parent_uri.file_name() == Some(Path::new(tenant_id)) {
+        return Ok(index_uri.clone());
+    }
+    parent_uri.join(tenant_id)?.join(file_name)
+}
+
+/// Wraps a metastore and scopes all the indexes it manages to a single tenant.
+///
+/// Index IDs passed to and returned by a [`TenantMetastore`] are relative to the tenant: two
+/// tenants can own an index with the same ID, and a tenant cannot see nor modify the indexes of
+/// other tenants. Under the hood, index IDs are namespaced with the tenant ID in the underlying
+/// metastore (see [`tenant_index_id`]) and index URIs are moved into a directory named after the
+/// tenant (see [`tenant_index_uri`]), so the splits of two tenants never share a location.
+pub struct TenantMetastore {
+    underlying: Arc<dyn Metastore>,
+    tenant_id: String,
+}
+
+impl TenantMetastore {
+    /// Creates a metastore scoped to the tenant `tenant_id`.
+    pub fn new(
+        metastore: Arc<dyn Metastore>,
+        tenant_id: impl Into<String>,
+    ) -> anyhow::Result<Self> {
+        let tenant_id = tenant_id.into();
+        validate_identifier("Tenant", &tenant_id)?;
+        Ok(Self {
+            underlying: metastore,
+            tenant_id,
+        })
+    }
+
+    /// Returns the tenant ID of this metastore.
+    pub fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    fn scope_index_id(&self, index_id: &str) -> String {
+        tenant_index_id(&self.tenant_id, index_id)
+    }
+
+    fn scope_index_config(&self, index_config: &mut IndexConfig) -> MetastoreResult<()> {
+        index_config.index_id = self.scope_index_id(&index_config.index_id);
+        index_config.index_uri = tenant_index_uri(&self.tenant_id, &index_config.index_uri)
+            .map_err(|error| MetastoreError::InternalError {
+                message: "Failed to scope the index URI to the tenant.".to_string(),
+                cause: error.to_string(),
+            })?;
+        Ok(())
+    }
+
+    fn scope_index_uid(&self, index_uid: IndexUid) -> IndexUid {
+        IndexUid::from_parts(
+            self.scope_index_id(index_uid.index_id()),
+            index_uid.incarnation_id(),
+        )
+    }
+
+    fn unscope_index_id<'a>(&self, index_id: &'a str) -> &'a str {
+        match split_tenant_index_id(index_id) {
+            Some((tenant_id, unscoped_index_id)) if tenant_id == self.tenant_id => {
+                unscoped_index_id
+            }
+            _ => index_id,
+        }
+    }
+
+    fn unscope_index_uid(&self, index_uid: IndexUid) -> IndexUid {
+        IndexUid::from_parts(
+            self.unscope_index_id(index_uid.index_id()),
+            index_uid.incarnation_id(),
+        )
+    }
+
+    fn unscope_index_metadata(&self, mut index_metadata: IndexMetadata) -> IndexMetadata {
+        index_metadata.index_uid = self.unscope_index_uid(index_metadata.index_uid);
+        index_metadata.index_config.index_id = self
+            .unscope_index_id(&index_metadata.index_config.index_id)
+            .to_string();
+        index_metadata
+    }
+
+    fn unscope_split(&self, mut split: Split) -> Split {
+        split.split_metadata.index_uid = self.unscope_index_uid(split.split_metadata.index_uid);
+        split
+    }
+
+    fn unscope_delete_task(&self, mut delete_task: DeleteTask) -> DeleteTask {
+        if let Some(delete_query) = delete_task.delete_query.as_mut() {
+            delete_query.index_uid = self
+                .unscope_index_uid(delete_query.index_uid.clone().into())
+                .into();
+        }
+        delete_task
+    }
+
+    fn unscope_error(&self, error: MetastoreError) -> MetastoreError {
+        match error {
+            MetastoreError::IndexAlreadyExists { index_id } => MetastoreError::IndexAlreadyExists {
+                index_id: self.unscope_index_id(&index_id).to_string(),
+            },
+            MetastoreError::IndexDoesNotExist { index_id } => MetastoreError::IndexDoesNotExist {
+                index_id: self.unscope_index_id(&index_id).to_string(),
+            },
+            MetastoreError::IndexConfigVersionConflict {
+                index_id,
+                expected_version,
+                current_version,
+            } => MetastoreError::IndexConfigVersionConflict {
+                index_id: self.unscope_index_id(&index_id).to_string(),
+                expected_version,
+                current_version,
+            },
+            _ => error,
+        }
+    }
+}
+
+impl fmt::Debug for TenantMetastore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TenantMetastore")
+            .field("uri", self.underlying.uri())
+            .field("tenant_id", &self.tenant_id)
+            .finish()
+    }
+}
+
+#[async_trait]
+impl Metastore for TenantMetastore {
+    fn uri(&self) -> &Uri {
+        self.underlying.uri()
+    }
+
+    async fn check_connectivity(&self) -> anyhow::Result<()> {
+        self.underlying.check_connectivity().await
+    }
+
+    // Index API
+
+    async fn create_index(&self, mut index_config: IndexConfig) -> MetastoreResult<IndexUid> {
+        self.scope_index_config(&mut index_config)?;
+        self.underlying
+            .create_index(index_config)
+            .await
+            .map(|index_uid| self.unscope_index_uid(index_uid))
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn index_metadata(&self, index_id: &str) -> MetastoreResult<IndexMetadata> {
+        self.underlying
+            .index_metadata(&self.scope_index_id(index_id))
+            .await
+            .map(|index_metadata| self.unscope_index_metadata(index_metadata))
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn list_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
+        let indexes_metadatas = self
+            .underlying
+            .list_indexes_metadatas()
+            .await?
+            .into_iter()
+            .filter(|index_metadata| {
+                split_tenant_index_id(index_metadata.index_id())
+                    .map(|(tenant_id, _)| tenant_id == self.tenant_id)
+                    .unwrap_or(false)
+            })
+            .map(|index_metadata| self.unscope_index_metadata(index_metadata))
+            .collect();
+        Ok(indexes_metadatas)
+    }
+
+    async fn update_index_config(
+        &self,
+        index_uid: IndexUid,
+        mut index_config: IndexConfig,
+        expected_config_version_opt: Option<u64>,
+    ) -> MetastoreResult<IndexMetadata> {
+        self.scope_index_config(&mut index_config)?;
+        self.underlying
+            .update_index_config(
+                self.scope_index_uid(index_uid),
+                index_config,
+                expected_config_version_opt,
+            )
+            .await
+            .map(|index_metadata| self.unscope_index_metadata(index_metadata))
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn delete_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
+        self.underlying
+            .delete_index(self.scope_index_uid(index_uid))
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn trash_index(&self, index_uid: IndexUid) -> MetastoreResult<()> {
+        self.underlying
+            .trash_index(self.scope_index_uid(index_uid))
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn restore_index(&self, index_uid: IndexUid) -> MetastoreResult<IndexMetadata> {
+        self.underlying
+            .restore_index(self.scope_index_uid(index_uid))
+            .await
+            .map(|index_metadata| self.unscope_index_metadata(index_metadata))
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn list_trashed_indexes_metadatas(&self) -> MetastoreResult<Vec<IndexMetadata>> {
+        let indexes_metadatas = self
+            .underlying
+            .list_trashed_indexes_metadatas()
+            .await?
+            .into_iter()
+            .filter(|index_metadata| {
+                split_tenant_index_id(index_metadata.index_id())
+                    .map(|(tenant_id, _)| tenant_id == self.tenant_id)
+                    .unwrap_or(false)
+            })
+            .map(|index_metadata| self.unscope_index_metadata(index_metadata))
+            .collect();
+        Ok(indexes_metadatas)
+    }
+
+    async fn set_merges_paused(&self, index_uid: IndexUid, paused: bool) -> MetastoreResult<()> {
+        self.underlying
+            .set_merges_paused(self.scope_index_uid(index_uid), paused)
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn list_tenants(&self) -> MetastoreResult<Vec<String>> {
+        if self.list_indexes_metadatas().await?.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(vec![self.tenant_id.clone()])
+    }
+
+    // Split API
+
+    async fn stage_splits(
+        &self,
+        index_uid: IndexUid,
+        split_metadata_list: Vec<SplitMetadata>,
+    ) -> MetastoreResult<()> {
+        let split_metadata_list = split_metadata_list
+            .into_iter()
+            .map(|mut split_metadata| {
+                split_metadata.index_uid = self.scope_index_uid(split_metadata.index_uid);
+                split_metadata
+            })
+            .collect();
+        self.underlying
+            .stage_splits(self.scope_index_uid(index_uid), split_metadata_list)
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn publish_splits<'a>(
+        &self,
+        index_uid: IndexUid,
+        split_ids: &[&'a str],
+        replaced_split_ids: &[&'a str],
+        checkpoint_delta_opt: Option<IndexCheckpointDelta>,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .publish_splits(
+                self.scope_index_uid(index_uid),
+                split_ids,
+                replaced_split_ids,
+                checkpoint_delta_opt,
+            )
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn publish_splits_across_indexes(
+        &self,
+        publications: Vec<SplitsPublication>,
+    ) -> MetastoreResult<()> {
+        let publications = publications
+            .into_iter()
+            .map(|mut publication| {
+                publication.index_uid = self.scope_index_uid(publication.index_uid);
+                publication
+            })
+            .collect();
+        self.underlying
+            .publish_splits_across_indexes(publications)
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn list_splits(&self, mut query: ListSplitsQuery) -> MetastoreResult<Vec<Split>> {
+        query.index_uid = self.scope_index_uid(query.index_uid);
+        self.underlying
+            .list_splits(query)
+            .await
+            .map(|splits| {
+                splits
+                    .into_iter()
+                    .map(|split| self.unscope_split(split))
+                    .collect()
+            })
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn list_splits_page(
+        &self,
+        mut query: ListSplitsQuery,
+        page_size: usize,
+    ) -> MetastoreResult<SplitsPage> {
+        query.index_uid = self.scope_index_uid(query.index_uid);
+        let splits_page = self
+            .underlying
+            .list_splits_page(query, page_size)
+            .await
+            .map_err(|error| self.unscope_error(error))?;
+        Ok(SplitsPage {
+            splits: splits_page
+                .splits
+                .into_iter()
+                .map(|split| self.unscope_split(split))
+                .collect(),
+            next_page_token: splits_page.next_page_token,
+        })
+    }
+
+    async fn list_all_splits(&self, index_uid: IndexUid) -> MetastoreResult<Vec<Split>> {
+        self.list_splits(ListSplitsQuery::for_index(index_uid))
+            .await
+    }
+
+    async fn list_stale_splits(
+        &self,
+        index_uid: IndexUid,
+        delete_opstamp: u64,
+        num_splits: usize,
+    ) -> MetastoreResult<Vec<Split>> {
+        self.underlying
+            .list_stale_splits(self.scope_index_uid(index_uid), delete_opstamp, num_splits)
+            .await
+            .map(|splits| {
+                splits
+                    .into_iter()
+                    .map(|split| self.unscope_split(split))
+                    .collect()
+            })
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn mark_splits_for_deletion<'a>(
+        &self,
+        index_uid: IndexUid,
+        split_ids: &[&'a str],
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .mark_splits_for_deletion(self.scope_index_uid(index_uid), split_ids)
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn delete_splits<'a>(
+        &self,
+        index_uid: IndexUid,
+        split_ids: &[&'a str],
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .delete_splits(self.scope_index_uid(index_uid), split_ids)
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    // Source API
+
+    async fn add_source(&self, index_uid: IndexUid, source: SourceConfig) -> MetastoreResult<()> {
+        self.underlying
+            .add_source(self.scope_index_uid(index_uid), source)
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn toggle_source(
+        &self,
+        index_uid: IndexUid,
+        source_id: &str,
+        enable: bool,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .toggle_source(self.scope_index_uid(index_uid), source_id, enable)
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn reset_source_checkpoint(
+        &self,
+        index_uid: IndexUid,
+        source_id: &str,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .reset_source_checkpoint(self.scope_index_uid(index_uid), source_id)
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn rewind_source_checkpoint(
+        &self,
+        index_uid: IndexUid,
+        source_id: &str,
+        target_checkpoint: SourceCheckpoint,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .rewind_source_checkpoint(
+                self.scope_index_uid(index_uid),
+                source_id,
+                target_checkpoint,
+            )
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn delete_source(&self, index_uid: IndexUid, source_id: &str) -> MetastoreResult<()> {
+        self.underlying
+            .delete_source(self.scope_index_uid(index_uid), source_id)
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    // Delete tasks API
+
+    async fn create_delete_task(
+        &self,
+        mut delete_query: DeleteQuery,
+    ) -> MetastoreResult<DeleteTask> {
+        delete_query.index_uid = self.scope_index_uid(delete_query.index_uid.into()).into();
+        self.underlying
+            .create_delete_task(delete_query)
+            .await
+            .map(|delete_task| self.unscope_delete_task(delete_task))
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn last_delete_opstamp(&self, index_uid: IndexUid) -> MetastoreResult<u64> {
+        self.underlying
+            .last_delete_opstamp(self.scope_index_uid(index_uid))
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn update_splits_delete_opstamp<'a>(
+        &self,
+        index_uid: IndexUid,
+        split_ids: &[&'a str],
+        delete_opstamp: u64,
+    ) -> MetastoreResult<()> {
+        self.underlying
+            .update_splits_delete_opstamp(
+                self.scope_index_uid(index_uid),
+                split_ids,
+                delete_opstamp,
+            )
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn list_delete_tasks(
+        &self,
+        index_uid: IndexUid,
+        opstamp_start: u64,
+    ) -> MetastoreResult<Vec<DeleteTask>> {
+        self.underlying
+            .list_delete_tasks(self.scope_index_uid(index_uid), opstamp_start)
+            .await
+            .map(|delete_tasks| {
+                delete_tasks
+                    .into_iter()
+                    .map(|delete_task| self.unscope_delete_task(delete_task))
+                    .collect()
+            })
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn cancel_delete_task(
+        &self,
+        index_uid: IndexUid,
+        opstamp: u64,
+    ) -> MetastoreResult<DeleteTask> {
+        self.underlying
+            .cancel_delete_task(self.scope_index_uid(index_uid), opstamp)
+            .await
+            .map(|delete_task| self.unscope_delete_task(delete_task))
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    // Audit log API
+
+    async fn append_audit_event(&self, mut audit_event: AuditEvent) -> MetastoreResult<()> {
+        audit_event.index_uid = self.scope_index_uid(audit_event.index_uid);
+        self.underlying
+            .append_audit_event(audit_event)
+            .await
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    async fn list_audit_events(&self, index_id: &str) -> MetastoreResult<Vec<AuditEvent>> {
+        self.underlying
+            .list_audit_events(&self.scope_index_id(index_id))
+            .await
+            .map(|audit_events| {
+                audit_events
+                    .into_iter()
+                    .map(|mut audit_event| {
+                        audit_event.index_uid = self.unscope_index_uid(audit_event.index_uid);
+                        audit_event
+                    })
+                    .collect()
+            })
+            .map_err(|error| self.unscope_error(error))
+    }
+
+    // Cluster settings API
+
+    async fn cluster_settings(&self) -> MetastoreResult<ClusterSettings> {
+        self.underlying.cluster_settings().await
+    }
+
+    async fn update_cluster_settings(
+        &self,
+        _cluster_settings: ClusterSettings,
+    ) -> MetastoreResult<()> {
+        Err(MetastoreError::Forbidden {
+            message: format!(
+                "Tenant `{}` cannot update the cluster settings, which apply to all the tenants.",
+                self.tenant_id
+            ),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metastore_for_test;
+    use crate::tests::test_suite::DefaultForTest;
+
+    #[async_trait]
+    impl DefaultForTest for TenantMetastore {
+        async fn default_for_test() -> Self {
+            TenantMetastore::new(metastore_for_test(), "test-tenant").unwrap()
+        }
+    }
+
+    metastore_test_suite!(
+        crate::metastore::tenant_metastore::TenantMetastore,
+        without_cluster_settings
+    );
+
+    #[tokio::test]
+    async fn test_tenant_metastore_rejects_cluster_settings_update() {
+        let metastore = metastore_for_test();
+        let tenant_metastore = TenantMetastore::new(metastore.clone(), "tenant-a").unwrap();
+
+        let error = tenant_metastore
+            .update_cluster_settings(ClusterSettings::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, MetastoreError::Forbidden { .. }));
+        assert_eq!(
+            tenant_metastore.cluster_settings().await.unwrap(),
+            metastore.cluster_settings().await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tenant_metastore_isolation() {
+        let metastore = metastore_for_test();
+        let tenant_a = TenantMetastore::new(metastore.clone(), "tenant-a").unwrap();
+        let tenant_b = TenantMetastore::new(metastore.clone(), "tenant-b").unwrap();
+
+        let index_config = IndexConfig::for_test("test-index", "ram:///indexes/test-index");
+        let index_uid_a = tenant_a.create_index(index_config.clone()).await.unwrap();
+        assert_eq!(index_uid_a.index_id(), "test-index");
+
+        let index_uid_b = tenant_b.create_index(index_config).await.unwrap();
+        assert_eq!(index_uid_b.index_id(), "test-index");
+
+        let index_metadata_a = tenant_a.index_metadata("test-index").await.unwrap();
+        assert_eq!(index_metadata_a.index_uid, index_uid_a);
+        assert_eq!(index_metadata_a.index_id(), "test-index");
+        assert_eq!(
+            index_metadata_a.index_uri(),
+            &Uri::from_well_formed("ram:///indexes/tenant-a/test-index")
+        );
+        let indexes_metadatas_b = tenant_b.list_indexes_metadatas().await.unwrap();
+        assert_eq!(indexes_metadatas_b.len(), 1);
+        assert_eq!(indexes_metadatas_b[0].index_uid, index_uid_b);
+        assert_eq!(
+            indexes_metadatas_b[0].index_uri(),
+            &Uri::from_well_formed("ram:///indexes/tenant-b/test-index")
+        );
+
+        let error = TenantMetastore::new(metastore.clone(), "tenant-c")
+            .unwrap()
+            .index_metadata("test-index")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error,
+            MetastoreError::IndexDoesNotExist {
+                index_id: "test-index".to_string()
+            }
+        );
+        assert!(!metastore.index_exists("test-index").await.unwrap());
+        assert!(metastore.index_exists("tenant-a.test-index").await.unwrap());
+
+        assert_eq!(
+            metastore.list_tenants().await.unwrap(),
+            vec!["tenant-a".to_string(), "tenant-b".to_string()]
+        );
+        assert_eq!(
+            tenant_a.list_tenants().await.unwrap(),
+            vec!["tenant-a".to_string()]
+        );
+        tenant_a.delete_index(index_uid_a).await.unwrap();
+        assert_eq!(
+            metastore.list_tenants().await.unwrap(),
+            vec!["tenant-b".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_tenant_index_uri() {
+        let tenant_index_uri_helper = |index_uri: &str| {
+            tenant_index_uri("tenant-a", &Uri::from_well_formed(index_uri))
+                .unwrap()
+                .into_string()
+        };
+        assert_eq!(
+            tenant_index_uri_helper("s3://bucket/indexes/test-index"),
+            "s3://bucket/indexes/tenant-a/test-index"
+        );
+        assert_eq!(
+            tenant_index_uri_helper("s3://bucket/indexes/tenant-a/test-index"),
+            "s3://bucket/indexes/tenant-a/test-index"
+        );
+        assert_eq!(
+            tenant_index_uri_helper("file:///indexes/test-index"),
+            "file:///indexes/tenant-a/test-index"
+        );
+        assert_eq!(
+            tenant_index_uri_helper("s3://bucket"),
+            "s3://bucket/tenant-a"
+        );
+    }
+
+    #[test]
+    fn test_tenant_metastore_rejects_invalid_tenant_id() {
+        TenantMetastore::new(metastore_for_test(), "tenant.a").unwrap_err();
+    }
+}
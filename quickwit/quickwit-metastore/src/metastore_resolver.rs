@@ -23,11 +23,15 @@ use std::sync::Arc;
 use async_trait::async_trait;
 use once_cell::sync::OnceCell;
 use quickwit_common::uri::{Protocol, Uri};
+use quickwit_config::MetastoreConfig;
 
 use crate::metastore::file_backed_metastore::FileBackedMetastoreFactory;
 #[cfg(feature = "postgres")]
 use crate::metastore::postgresql_metastore::PostgresqlMetastoreFactory;
-use crate::{Metastore, MetastoreResolverError};
+use crate::{
+    default_audit_actor, AuditLogMetastore, Metastore, MetastoreError, MetastoreResolverError,
+    TenantMetastore,
+};
 
 /// A metastore factory builds a [`Metastore`] object from an URI.
 #[cfg_attr(any(test, feature = "testsuite"), mockall::automock)]
@@ -35,6 +39,27 @@ use crate::{Metastore, MetastoreResolverError};
 pub trait MetastoreFactory: Send + Sync + 'static {
     /// Returns the appropriate [`Metastore`] object for the URI.
     async fn resolve(&self, uri: &Uri) -> Result<Arc<dyn Metastore>, MetastoreResolverError>;
+
+    /// Returns the appropriate [`Metastore`] object for the URI, applying the settings of the
+    /// metastore configuration relevant to this factory.
+    async fn resolve_with_config(
+        &self,
+        _metastore_config: &MetastoreConfig,
+        uri: &Uri,
+    ) -> Result<Arc<dyn Metastore>, MetastoreResolverError> {
+        self.resolve(uri).await
+    }
+
+    /// Checks that the metastore at the given URI is reachable without creating or initializing
+    /// anything. The default implementation resolves the metastore and checks its connectivity.
+    async fn check_connectivity(
+        &self,
+        metastore_config: &MetastoreConfig,
+        uri: &Uri,
+    ) -> anyhow::Result<()> {
+        let metastore = self.resolve_with_config(metastore_config, uri).await?;
+        metastore.check_connectivity().await
+    }
 }
 
 #[derive(Default)]
@@ -133,17 +158,57 @@ impl MetastoreUriResolver {
         MetastoreUriResolverBuilder::default()
     }
 
-    /// Resolves the given URI.
+    /// Resolves the given URI. Unlike [`Self::resolve_with_config`], the mutations applied through
+    /// the returned metastore are not recorded in the audit log.
     pub async fn resolve(&self, uri: &Uri) -> Result<Arc<dyn Metastore>, MetastoreResolverError> {
-        let resolver = self
-            .per_protocol_resolver
-            .get(&uri.protocol())
-            .ok_or_else(|| {
-                MetastoreResolverError::ProtocolUnsupported(uri.protocol().to_string())
-            })?;
+        let resolver = self.factory(uri)?;
         let metastore = resolver.resolve(uri).await?;
         Ok(metastore)
     }
+
+    /// Resolves the given URI with the given metastore configuration. The mutations applied through
+    /// the returned metastore are recorded in the audit log, and the metastore is scoped to the
+    /// tenant of the configuration, if any.
+    pub async fn resolve_with_config(
+        &self,
+        metastore_config: &MetastoreConfig,
+        uri: &Uri,
+    ) -> Result<Arc<dyn Metastore>, MetastoreResolverError> {
+        let resolver = self.factory(uri)?;
+        let metastore = resolver.resolve_with_config(metastore_config, uri).await?;
+        // The audit log records the actual index UIDs, below the tenant scoping.
+        let metastore: Arc<dyn Metastore> =
+            Arc::new(AuditLogMetastore::new(metastore, default_audit_actor()));
+
+        if let Some(tenant_id) = &metastore_config.tenant_id {
+            let tenant_metastore =
+                TenantMetastore::new(metastore, tenant_id.clone()).map_err(|error| {
+                    MetastoreResolverError::FailedToOpenMetastore(MetastoreError::InternalError {
+                        message: "Failed to scope the metastore to the tenant.".to_string(),
+                        cause: error.to_string(),
+                    })
+                })?;
+            return Ok(Arc::new(tenant_metastore));
+        }
+        Ok(metastore)
+    }
+
+    /// Checks that the metastore at the given URI is reachable without creating or initializing
+    /// it.
+    pub async fn check_connectivity(
+        &self,
+        metastore_config: &MetastoreConfig,
+        uri: &Uri,
+    ) -> anyhow::Result<()> {
+        let resolver = self.factory(uri)?;
+        resolver.check_connectivity(metastore_config, uri).await
+    }
+
+    fn factory(&self, uri: &Uri) -> Result<&Arc<dyn MetastoreFactory>, MetastoreResolverError> {
+        self.per_protocol_resolver
+            .get(&uri.protocol())
+            .ok_or_else(|| MetastoreResolverError::ProtocolUnsupported(uri.protocol().to_string()))
+    }
 }
 
 #[cfg(test)]
@@ -151,6 +216,7 @@ mod tests {
     use std::str::FromStr;
 
     use quickwit_common::uri::Uri;
+    use quickwit_config::{IndexConfig, MetastoreConfig};
 
     use crate::quickwit_metastore_uri_resolver;
 
@@ -163,6 +229,39 @@ mod tests {
         metastore_resolver.resolve(&metastore_uri).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_metastore_resolver_check_connectivity_does_not_init_file_backed_metastore() {
+        let metastore_resolver = quickwit_metastore_uri_resolver();
+        let tmp_dir = tempfile::tempdir().unwrap();
+        let metastore_path = tmp_dir.path().join("metastore");
+        let metastore_uri = Uri::from_str(&format!("file://{}", metastore_path.display())).unwrap();
+        metastore_resolver
+            .check_connectivity(&MetastoreConfig::default(), &metastore_uri)
+            .await
+            .unwrap();
+        assert!(!metastore_path.exists());
+    }
+
+    #[tokio::test]
+    async fn test_metastore_resolver_scopes_metastore_to_tenant() {
+        let metastore_resolver = quickwit_metastore_uri_resolver();
+        let metastore_uri = Uri::for_test("ram:///test-metastore-resolver-tenant");
+        let metastore_config = MetastoreConfig {
+            tenant_id: Some("tenant-a".to_string()),
+            ..Default::default()
+        };
+        let tenant_metastore = metastore_resolver
+            .resolve_with_config(&metastore_config, &metastore_uri)
+            .await
+            .unwrap();
+        let index_config = IndexConfig::for_test("test-index", "ram:///indexes/test-index");
+        tenant_metastore.create_index(index_config).await.unwrap();
+
+        let metastore = metastore_resolver.resolve(&metastore_uri).await.unwrap();
+        assert!(!metastore.index_exists("test-index").await.unwrap());
+        assert!(metastore.index_exists("tenant-a.test-index").await.unwrap());
+    }
+
     #[cfg(feature = "postgres")]
     #[tokio::test]
     async fn test_postgres_and_postgresql_protocol_accepted() {
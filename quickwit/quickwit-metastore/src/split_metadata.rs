@@ -17,17 +17,20 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::fmt;
 use std::ops::{Range, RangeInclusive};
 use std::str::FromStr;
 
 use quickwit_common::FileEntry;
+use quickwit_doc_mapper::range_pruning::FieldValueRange;
+use quickwit_doc_mapper::BloomFilter;
 use quickwit_proto::IndexUid;
 use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 
 use crate::split_metadata_version::VersionedSplitMetadata;
+use crate::SplitFieldStats;
 
 /// Carries split metadata.
 #[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
@@ -113,6 +116,25 @@ pub struct SplitMetadata {
     /// [`MAX_VALUES_PER_TAG_FIELD`]: https://github.com/quickwit-oss/quickwit/blob/main/quickwit-indexing/src/actors/packager.rs#L36
     pub tags: BTreeSet<String>,
 
+    /// Arbitrary key-value labels attached to the split, for instance a source batch ID or a
+    /// backfill tag. Labels are set from the `split_labels` of the source that produced the split
+    /// and are kept by merges only when all the merged splits share them.
+    pub labels: BTreeMap<String, String>,
+
+    /// Per-field statistics computed when the split was built, keyed by field name. Splits
+    /// created before statistics were collected have no statistics.
+    pub field_stats: BTreeMap<String, SplitFieldStats>,
+
+    /// Bloom filters recording the values of the fields listed in the
+    /// [`DocMapping`](quickwit_config::DocMapping) `bloom_filter_fields` attribute, keyed by field
+    /// name. A field has no bloom filter when it has too many distinct values in the split.
+    pub bloom_filters: BTreeMap<String, BloomFilter>,
+
+    /// Minimum and maximum values of the fields listed in the
+    /// [`DocMapping`](quickwit_config::DocMapping) `min_max_fields` attribute, keyed by field
+    /// name. A field has no range when it has no value in the split.
+    pub field_ranges: BTreeMap<String, FieldValueRange>,
+
     /// Contains the range of bytes of the footer that needs to be downloaded
     /// in order to open a split.
     ///
@@ -187,6 +209,12 @@ impl quickwit_config::TestableForRegression for SplitMetadata {
             time_range: Some(121000..=130198),
             create_timestamp: 3,
             tags: ["234".to_string(), "aaa".to_string()].into_iter().collect(),
+            labels: [("backfill".to_string(), "2023-01".to_string())]
+                .into_iter()
+                .collect(),
+            field_stats: BTreeMap::new(),
+            bloom_filters: BTreeMap::new(),
+            field_ranges: BTreeMap::new(),
             footer_offsets: 1000..2000,
             num_merge_ops: 3,
         }
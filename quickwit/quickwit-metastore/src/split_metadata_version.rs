@@ -17,14 +17,16 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::ops::{Range, RangeInclusive};
 
+use quickwit_doc_mapper::range_pruning::FieldValueRange;
+use quickwit_doc_mapper::BloomFilter;
 use quickwit_proto::IndexUid;
 use serde::{Deserialize, Serialize};
 
 use crate::split_metadata::utc_now_timestamp;
-use crate::SplitMetadata;
+use crate::{SplitFieldStats, SplitMetadata};
 
 #[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
 pub(crate) struct SplitMetadataV0_6 {
@@ -72,6 +74,30 @@ pub(crate) struct SplitMetadataV0_6 {
     /// A set of tags for categorizing and searching group of splits.
     pub tags: BTreeSet<String>,
 
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[schema(value_type = Object)]
+    /// Arbitrary key-value labels attached to the split.
+    pub labels: BTreeMap<String, String>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[schema(value_type = Object)]
+    /// Per-field statistics computed when the split was built.
+    pub field_stats: BTreeMap<String, SplitFieldStats>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[schema(value_type = Object)]
+    /// Bloom filters recording the values of some fields of the split.
+    pub bloom_filters: BTreeMap<String, BloomFilter>,
+
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    #[schema(value_type = Object)]
+    /// Minimum and maximum values of some numeric fields of the split.
+    pub field_ranges: BTreeMap<String, FieldValueRange>,
+
     #[schema(value_type = Object)]
     /// Contains the range of bytes of the footer that needs to be downloaded
     /// in order to open a split.
@@ -118,6 +144,10 @@ impl From<SplitMetadataV0_6> for SplitMetadata {
             time_range: v3.time_range,
             create_timestamp: v3.create_timestamp,
             tags: v3.tags,
+            labels: v3.labels,
+            field_stats: v3.field_stats,
+            bloom_filters: v3.bloom_filters,
+            field_ranges: v3.field_ranges,
             footer_offsets: v3.footer_offsets,
             num_merge_ops: v3.num_merge_ops,
         }
@@ -138,6 +168,10 @@ impl From<SplitMetadata> for SplitMetadataV0_6 {
             time_range: split.time_range,
             create_timestamp: split.create_timestamp,
             tags: split.tags,
+            labels: split.labels,
+            field_stats: split.field_stats,
+            bloom_filters: split.bloom_filters,
+            field_ranges: split.field_ranges,
             footer_offsets: split.footer_offsets,
             num_merge_ops: split.num_merge_ops,
         }
@@ -19,17 +19,22 @@
 
 #[cfg(test)]
 pub mod test_suite {
-    use std::collections::BTreeSet;
+    use std::collections::{BTreeMap, BTreeSet};
     use std::num::NonZeroUsize;
     use std::sync::Arc;
     use std::time::Duration;
 
     use async_trait::async_trait;
+    use byte_unit::Byte;
     use futures::future::try_join_all;
     use itertools::Itertools;
     use quickwit_common::rand::append_random_suffix;
-    use quickwit_config::{IndexConfig, SourceConfig, SourceInputFormat, SourceParams};
+    use quickwit_config::{
+        ClusterSettings, IndexConfig, RetentionPolicy, SourceConfig, SourceInputFormat,
+        SourceParams,
+    };
     use quickwit_doc_mapper::tag_pruning::{no_tag, tag, TagFilterAst};
+    use quickwit_doc_mapper::BloomFilter;
     use quickwit_proto::metastore_api::DeleteQuery;
     use quickwit_proto::{qast_helper, IndexUid};
     use time::OffsetDateTime;
@@ -39,7 +44,10 @@ pub mod test_suite {
     use crate::checkpoint::{
         IndexCheckpointDelta, PartitionId, Position, SourceCheckpoint, SourceCheckpointDelta,
     };
-    use crate::{ListSplitsQuery, Metastore, MetastoreError, Split, SplitMetadata, SplitState};
+    use crate::{
+        AuditEvent, AuditOperation, ListSplitsQuery, Metastore, MetastoreError, Split,
+        SplitMetadata, SplitState, SplitsPublication,
+    };
 
     #[async_trait]
     pub trait DefaultForTest {
@@ -104,7 +112,12 @@ pub mod test_suite {
         let index_metadata = metastore.index_metadata(&index_id).await.unwrap();
 
         assert_eq!(index_metadata.index_id(), index_id);
-        assert_eq!(index_metadata.index_uri(), &index_uri);
+        // Tenant metastores store the index in a directory named after the tenant.
+        assert!(index_metadata
+            .index_uri()
+            .as_str()
+            .starts_with("ram:///indexes/"));
+        assert!(index_metadata.index_uri().as_str().ends_with(&index_id));
 
         let error = metastore.create_index(index_config).await.unwrap_err();
         assert!(matches!(error, MetastoreError::IndexAlreadyExists { .. }));
@@ -146,7 +159,12 @@ pub mod test_suite {
         let index_metadata = metastore.index_metadata(&index_id).await.unwrap();
 
         assert_eq!(index_metadata.index_id(), index_id);
-        assert_eq!(index_metadata.index_uri(), &index_uri);
+        // Tenant metastores store the index in a directory named after the tenant.
+        assert!(index_metadata
+            .index_uri()
+            .as_str()
+            .starts_with("ram:///indexes/"));
+        assert!(index_metadata.index_uri().as_str().ends_with(&index_id));
 
         cleanup_index(&metastore, index_uid).await;
     }
@@ -237,6 +255,300 @@ pub mod test_suite {
         cleanup_index(&metastore, index_uid).await;
     }
 
+    pub async fn test_metastore_trash_and_restore_index<
+        MetastoreToTest: Metastore + DefaultForTest,
+    >() {
+        let metastore = MetastoreToTest::default_for_test().await;
+
+        let index_id = append_random_suffix("test-trash-index");
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+        let error = metastore
+            .trash_index(IndexUid::new("index-not-found"))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, MetastoreError::IndexDoesNotExist { .. }));
+
+        let index_uid = metastore.create_index(index_config).await.unwrap();
+        let split_metadata = SplitMetadata {
+            split_id: format!("{index_id}--split"),
+            index_uid: index_uid.clone(),
+            ..Default::default()
+        };
+        metastore
+            .stage_splits(index_uid.clone(), vec![split_metadata])
+            .await
+            .unwrap();
+
+        metastore.trash_index(index_uid.clone()).await.unwrap();
+        // Trashing an index twice is a no-op.
+        metastore.trash_index(index_uid.clone()).await.unwrap();
+
+        let error = metastore.index_metadata(&index_id).await.unwrap_err();
+        assert!(matches!(error, MetastoreError::IndexDoesNotExist { .. }));
+        assert!(!metastore.index_exists(&index_id).await.unwrap());
+        assert!(!metastore
+            .list_indexes_metadatas()
+            .await
+            .unwrap()
+            .iter()
+            .any(|index_metadata| index_metadata.index_id() == index_id));
+
+        let trashed_indexes_metadatas = metastore.list_trashed_indexes_metadatas().await.unwrap();
+        let trashed_index_metadata = trashed_indexes_metadatas
+            .iter()
+            .find(|index_metadata| index_metadata.index_id() == index_id)
+            .unwrap();
+        assert_eq!(trashed_index_metadata.index_uid, index_uid);
+        assert!(trashed_index_metadata.is_trashed());
+
+        // The splits of a trashed index are kept.
+        let splits = metastore.list_all_splits(index_uid.clone()).await.unwrap();
+        assert_eq!(splits.len(), 1);
+
+        let index_metadata = metastore.restore_index(index_uid.clone()).await.unwrap();
+        assert!(!index_metadata.is_trashed());
+
+        let index_metadata = metastore.index_metadata(&index_id).await.unwrap();
+        assert_eq!(index_metadata.index_uid, index_uid);
+        assert!(!metastore
+            .list_trashed_indexes_metadatas()
+            .await
+            .unwrap()
+            .iter()
+            .any(|index_metadata| index_metadata.index_id() == index_id));
+
+        cleanup_index(&metastore, index_uid).await;
+    }
+
+    pub async fn test_metastore_set_merges_paused<MetastoreToTest: Metastore + DefaultForTest>() {
+        let metastore = MetastoreToTest::default_for_test().await;
+
+        let index_id = append_random_suffix("test-set-merges-paused");
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+        let error = metastore
+            .set_merges_paused(IndexUid::new("index-not-found"), true)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, MetastoreError::IndexDoesNotExist { .. }));
+
+        let index_uid = metastore.create_index(index_config).await.unwrap();
+        let index_metadata = metastore.index_metadata(&index_id).await.unwrap();
+        assert!(!index_metadata.merges_paused);
+
+        metastore
+            .set_merges_paused(index_uid.clone(), true)
+            .await
+            .unwrap();
+        // Pausing the merges twice is a no-op.
+        metastore
+            .set_merges_paused(index_uid.clone(), true)
+            .await
+            .unwrap();
+        let index_metadata = metastore.index_metadata(&index_id).await.unwrap();
+        assert!(index_metadata.merges_paused);
+
+        metastore
+            .set_merges_paused(index_uid.clone(), false)
+            .await
+            .unwrap();
+        let index_metadata = metastore.index_metadata(&index_id).await.unwrap();
+        assert!(!index_metadata.merges_paused);
+
+        cleanup_index(&metastore, index_uid).await;
+    }
+
+    pub async fn test_metastore_update_index_config<MetastoreToTest: Metastore + DefaultForTest>() {
+        let metastore = MetastoreToTest::default_for_test().await;
+
+        let index_id = append_random_suffix("test-update-index-config");
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+        let error = metastore
+            .update_index_config(IndexUid::new("index-not-found"), index_config.clone(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, MetastoreError::IndexDoesNotExist { .. }));
+
+        let index_uid = metastore.create_index(index_config.clone()).await.unwrap();
+        let index_metadata = metastore.index_metadata(&index_id).await.unwrap();
+        assert_eq!(index_metadata.config_version, 0);
+
+        let mut new_index_config = index_config.clone();
+        new_index_config.search_settings.default_search_fields = vec!["body".to_string()];
+        let index_metadata = metastore
+            .update_index_config(index_uid.clone(), new_index_config.clone(), Some(0))
+            .await
+            .unwrap();
+        assert_eq!(index_metadata.config_version, 1);
+        assert_eq!(
+            index_metadata
+                .index_config
+                .search_settings
+                .default_search_fields,
+            vec!["body".to_string()]
+        );
+
+        // A stale version is rejected and leaves the index config untouched.
+        let error = metastore
+            .update_index_config(index_uid.clone(), index_config.clone(), Some(0))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error,
+            MetastoreError::IndexConfigVersionConflict {
+                index_id: index_id.clone(),
+                expected_version: 0,
+                current_version: 1,
+            }
+        );
+        let index_metadata = metastore.index_metadata(&index_id).await.unwrap();
+        assert_eq!(index_metadata.config_version, 1);
+        assert_eq!(
+            index_metadata
+                .index_config
+                .search_settings
+                .default_search_fields,
+            vec!["body".to_string()]
+        );
+
+        // Without an expected version, the update is unconditional.
+        let index_metadata = metastore
+            .update_index_config(index_uid.clone(), index_config, None)
+            .await
+            .unwrap();
+        assert_eq!(index_metadata.config_version, 2);
+        assert!(index_metadata
+            .index_config
+            .search_settings
+            .default_search_fields
+            .is_empty());
+
+        cleanup_index(&metastore, index_uid).await;
+    }
+
+    pub async fn test_metastore_audit_log<MetastoreToTest: Metastore + DefaultForTest>() {
+        let metastore = MetastoreToTest::default_for_test().await;
+
+        // Metastores recording mutations on their own also append events. Only the events
+        // appended by this test are checked.
+        let filter_test_audit_events = |audit_events: Vec<AuditEvent>| {
+            audit_events
+                .into_iter()
+                .filter(|audit_event| audit_event.actor.ends_with("test-actor"))
+                .collect::<Vec<_>>()
+        };
+
+        let index_id = append_random_suffix("test-audit-log");
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+        assert!(metastore
+            .list_audit_events(&index_id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let index_uid = metastore.create_index(index_config.clone()).await.unwrap();
+        let create_index_event =
+            AuditEvent::new("test-actor", index_uid.clone(), AuditOperation::CreateIndex);
+        metastore
+            .append_audit_event(create_index_event.clone())
+            .await
+            .unwrap();
+        let add_source_event = AuditEvent::new(
+            "test-actor",
+            index_uid.clone(),
+            AuditOperation::AddSource {
+                source_id: "test-source".to_string(),
+            },
+        );
+        metastore
+            .append_audit_event(add_source_event.clone())
+            .await
+            .unwrap();
+        let delete_index_event =
+            AuditEvent::new("test-actor", index_uid.clone(), AuditOperation::DeleteIndex);
+        metastore.delete_index(index_uid.clone()).await.unwrap();
+        metastore
+            .append_audit_event(delete_index_event.clone())
+            .await
+            .unwrap();
+
+        // The audit log outlives the index and spans its incarnations.
+        assert_eq!(
+            filter_test_audit_events(metastore.list_audit_events(&index_id).await.unwrap()),
+            [
+                create_index_event.clone(),
+                add_source_event.clone(),
+                delete_index_event.clone()
+            ]
+        );
+        let new_index_uid = metastore.create_index(index_config).await.unwrap();
+        let recreate_index_event = AuditEvent::new(
+            "other-test-actor",
+            new_index_uid.clone(),
+            AuditOperation::CreateIndex,
+        );
+        metastore
+            .append_audit_event(recreate_index_event.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            filter_test_audit_events(metastore.list_audit_events(&index_id).await.unwrap()),
+            [
+                create_index_event,
+                add_source_event,
+                delete_index_event,
+                recreate_index_event
+            ]
+        );
+
+        let other_index_id = append_random_suffix("test-audit-log-other");
+        assert!(metastore
+            .list_audit_events(&other_index_id)
+            .await
+            .unwrap()
+            .is_empty());
+
+        cleanup_index(&metastore, new_index_uid).await;
+    }
+
+    pub async fn test_metastore_cluster_settings<MetastoreToTest: Metastore + DefaultForTest>() {
+        let metastore = MetastoreToTest::default_for_test().await;
+
+        let cluster_settings = ClusterSettings {
+            default_retention_policy: Some(RetentionPolicy::new(
+                Some("30 days".to_string()),
+                None,
+                "daily".to_string(),
+            )),
+            rate_limits: None,
+            max_merge_write_throughput: Some(Byte::from_bytes(50_000_000)),
+        };
+        metastore
+            .update_cluster_settings(cluster_settings.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            metastore.cluster_settings().await.unwrap(),
+            cluster_settings
+        );
+
+        metastore
+            .update_cluster_settings(ClusterSettings::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            metastore.cluster_settings().await.unwrap(),
+            ClusterSettings::default()
+        );
+    }
+
     pub async fn test_metastore_add_source<MetastoreToTest: Metastore + DefaultForTest>() {
         let metastore = MetastoreToTest::default_for_test().await;
 
@@ -256,6 +568,7 @@ pub mod test_suite {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
 
         assert_eq!(
@@ -327,6 +640,7 @@ pub mod test_suite {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
         metastore
             .add_source(index_uid.clone(), source.clone())
@@ -372,6 +686,7 @@ pub mod test_suite {
             source_params: SourceParams::void(),
             transform_config: None,
             input_format: SourceInputFormat::Json,
+            split_labels: BTreeMap::new(),
         };
 
         let index_config = IndexConfig::for_test(&index_id, index_uri.as_str());
@@ -450,6 +765,7 @@ pub mod test_suite {
                 source_params: SourceParams::void(),
                 transform_config: None,
                 input_format: SourceInputFormat::Json,
+                split_labels: BTreeMap::new(),
             };
             metastore
                 .add_source(index_uid.clone(), source.clone())
@@ -524,6 +840,129 @@ pub mod test_suite {
         cleanup_index(&metastore, index_uid).await;
     }
 
+    pub async fn test_metastore_rewind_checkpoint<MetastoreToTest: Metastore + DefaultForTest>() {
+        let metastore = MetastoreToTest::default_for_test().await;
+
+        let index_id = append_random_suffix("test-rewind-checkpoint");
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(&index_id, &index_uri);
+        let index_uid = metastore.create_index(index_config).await.unwrap();
+
+        let source_id = format!("{index_id}--source");
+        let source = SourceConfig::for_test(&source_id, SourceParams::void());
+        metastore
+            .add_source(index_uid.clone(), source)
+            .await
+            .unwrap();
+
+        let split_id = format!("{index_id}--split");
+        let split_metadata = SplitMetadata {
+            split_id: split_id.clone(),
+            index_uid: index_uid.clone(),
+            ..Default::default()
+        };
+        metastore
+            .stage_splits(index_uid.clone(), vec![split_metadata])
+            .await
+            .unwrap();
+        let mut source_delta = SourceCheckpointDelta::default();
+        for partition_id in ["partition-0", "partition-1"] {
+            source_delta
+                .record_partition_delta(
+                    PartitionId::from(partition_id),
+                    Position::Beginning,
+                    Position::from(100u64),
+                )
+                .unwrap();
+        }
+        let checkpoint_delta = IndexCheckpointDelta {
+            source_id: source_id.clone(),
+            source_delta,
+        };
+        metastore
+            .publish_splits(index_uid.clone(), &[&split_id], &[], Some(checkpoint_delta))
+            .await
+            .unwrap();
+
+        let target_checkpoint: SourceCheckpoint =
+            vec![(PartitionId::from("partition-0"), Position::from(42u64))]
+                .into_iter()
+                .collect();
+        metastore
+            .rewind_source_checkpoint(index_uid.clone(), &source_id, target_checkpoint)
+            .await
+            .unwrap();
+
+        let index_metadata = metastore.index_metadata(&index_id).await.unwrap();
+        let source_checkpoint = index_metadata
+            .checkpoint
+            .source_checkpoint(&source_id)
+            .unwrap();
+        assert_eq!(
+            source_checkpoint.position_for_partition(&PartitionId::from("partition-0")),
+            Some(&Position::from(42u64))
+        );
+        assert_eq!(
+            source_checkpoint.position_for_partition(&PartitionId::from("partition-1")),
+            Some(&Position::from(100u64))
+        );
+
+        let target_checkpoint: SourceCheckpoint =
+            vec![(PartitionId::from("partition-1"), Position::Beginning)]
+                .into_iter()
+                .collect();
+        metastore
+            .rewind_source_checkpoint(index_uid.clone(), &source_id, target_checkpoint)
+            .await
+            .unwrap();
+
+        let index_metadata = metastore.index_metadata(&index_id).await.unwrap();
+        let source_checkpoint = index_metadata
+            .checkpoint
+            .source_checkpoint(&source_id)
+            .unwrap();
+        assert_eq!(source_checkpoint.num_partitions(), 1);
+
+        // A checkpoint cannot be rewound forward.
+        let target_checkpoint: SourceCheckpoint =
+            vec![(PartitionId::from("partition-0"), Position::from(43u64))]
+                .into_iter()
+                .collect();
+        assert!(matches!(
+            metastore
+                .rewind_source_checkpoint(index_uid.clone(), &source_id, target_checkpoint)
+                .await
+                .unwrap_err(),
+            MetastoreError::IncompatibleCheckpointRewind(_)
+        ));
+
+        assert!(matches!(
+            metastore
+                .rewind_source_checkpoint(
+                    index_uid.clone(),
+                    "source-not-found",
+                    SourceCheckpoint::default()
+                )
+                .await
+                .unwrap_err(),
+            MetastoreError::SourceDoesNotExist { .. }
+        ));
+
+        assert!(matches!(
+            metastore
+                .rewind_source_checkpoint(
+                    IndexUid::new("index-not-found"),
+                    &source_id,
+                    SourceCheckpoint::default()
+                )
+                .await
+                .unwrap_err(),
+            MetastoreError::IndexDoesNotExist { .. }
+        ));
+
+        cleanup_index(&metastore, index_uid).await;
+    }
+
     pub async fn test_metastore_publish_splits_empty_splits_array_is_allowed<
         MetastoreToTest: Metastore + DefaultForTest,
     >() {
@@ -1067,17 +1506,134 @@ pub mod test_suite {
             });
             join_handles.push(join_handle);
         }
-        try_join_all(join_handles).await.unwrap();
+        try_join_all(join_handles).await.unwrap();
+
+        let index_metadata = metastore.index_metadata(&index_id).await.unwrap();
+        let source_checkpoint = index_metadata
+            .checkpoint
+            .source_checkpoint(&source_id)
+            .unwrap();
+
+        assert_eq!(source_checkpoint.num_partitions(), 10);
+
+        cleanup_index(metastore.as_ref(), index_uid).await
+    }
+
+    pub async fn test_metastore_publish_splits_across_indexes<
+        MetastoreToTest: Metastore + DefaultForTest,
+    >() {
+        let metastore = MetastoreToTest::default_for_test().await;
+
+        let source_id = "test-source".to_string();
+        let mut index_uids = Vec::new();
+        let mut split_ids = Vec::new();
+
+        for _ in 0..2 {
+            let index_id = append_random_suffix("test-publish-splits-across-indexes");
+            let index_uri = format!("ram:///indexes/{index_id}");
+            let index_config = IndexConfig::for_test(&index_id, &index_uri);
+            let index_uid = metastore.create_index(index_config).await.unwrap();
+
+            let split_id = format!("{index_id}--split");
+            let split_metadata = SplitMetadata {
+                split_id: split_id.clone(),
+                index_uid: index_uid.clone(),
+                ..Default::default()
+            };
+            metastore
+                .stage_splits(index_uid.clone(), vec![split_metadata])
+                .await
+                .unwrap();
+            index_uids.push(index_uid);
+            split_ids.push(split_id);
+        }
 
-        let index_metadata = metastore.index_metadata(&index_id).await.unwrap();
-        let source_checkpoint = index_metadata
-            .checkpoint
-            .source_checkpoint(&source_id)
-            .unwrap();
+        // A failing publication rolls back the whole operation.
+        {
+            let publications = vec![
+                SplitsPublication {
+                    index_uid: index_uids[0].clone(),
+                    staged_split_ids: vec![split_ids[0].clone()],
+                    replaced_split_ids: Vec::new(),
+                    checkpoint_delta_opt: Some(IndexCheckpointDelta::for_test(&source_id, 0..10)),
+                },
+                SplitsPublication {
+                    index_uid: index_uids[1].clone(),
+                    staged_split_ids: vec!["split-does-not-exist".to_string()],
+                    replaced_split_ids: Vec::new(),
+                    checkpoint_delta_opt: Some(IndexCheckpointDelta::for_test(&source_id, 0..10)),
+                },
+            ];
+            let error = metastore
+                .publish_splits_across_indexes(publications)
+                .await
+                .unwrap_err();
+            assert!(matches!(error, MetastoreError::SplitsDoNotExist { .. }));
 
-        assert_eq!(source_checkpoint.num_partitions(), 10);
+            for index_uid in &index_uids {
+                let splits = metastore.list_all_splits(index_uid.clone()).await.unwrap();
+                assert_eq!(splits.len(), 1);
+                assert_eq!(splits[0].split_state, SplitState::Staged);
 
-        cleanup_index(metastore.as_ref(), index_uid).await
+                let index_metadata = metastore
+                    .index_metadata(index_uid.index_id())
+                    .await
+                    .unwrap();
+                assert!(index_metadata.checkpoint.is_empty());
+            }
+        }
+
+        // Publishing to an index that does not exist fails.
+        {
+            let publications = vec![SplitsPublication {
+                index_uid: IndexUid::new("index-does-not-exist"),
+                staged_split_ids: Vec::new(),
+                replaced_split_ids: Vec::new(),
+                checkpoint_delta_opt: None,
+            }];
+            let error = metastore
+                .publish_splits_across_indexes(publications)
+                .await
+                .unwrap_err();
+            assert!(matches!(error, MetastoreError::IndexDoesNotExist { .. }));
+        }
+
+        // Valid publications are all applied.
+        {
+            let publications = index_uids
+                .iter()
+                .zip(&split_ids)
+                .map(|(index_uid, split_id)| SplitsPublication {
+                    index_uid: index_uid.clone(),
+                    staged_split_ids: vec![split_id.clone()],
+                    replaced_split_ids: Vec::new(),
+                    checkpoint_delta_opt: Some(IndexCheckpointDelta::for_test(&source_id, 0..10)),
+                })
+                .collect();
+            metastore
+                .publish_splits_across_indexes(publications)
+                .await
+                .unwrap();
+
+            for index_uid in &index_uids {
+                let splits = metastore.list_all_splits(index_uid.clone()).await.unwrap();
+                assert_eq!(splits.len(), 1);
+                assert_eq!(splits[0].split_state, SplitState::Published);
+
+                let index_metadata = metastore
+                    .index_metadata(index_uid.index_id())
+                    .await
+                    .unwrap();
+                assert!(index_metadata
+                    .checkpoint
+                    .source_checkpoint(&source_id)
+                    .is_some());
+            }
+        }
+
+        for index_uid in index_uids {
+            cleanup_index(&metastore, index_uid).await;
+        }
     }
 
     pub async fn test_metastore_replace_splits<MetastoreToTest: Metastore + DefaultForTest>() {
@@ -1626,6 +2182,166 @@ pub mod test_suite {
         cleanup_index(&metastore, index_uid).await;
     }
 
+    pub async fn test_metastore_list_splits_page<MetastoreToTest: Metastore + DefaultForTest>() {
+        let metastore = MetastoreToTest::default_for_test().await;
+
+        let index_id = append_random_suffix("test-list-splits-page");
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+        let index_uid = metastore.create_index(index_config).await.unwrap();
+
+        let split_ids: Vec<String> = (0..5)
+            .map(|split_ord| format!("{index_id}--split-{split_ord}"))
+            .collect();
+        let split_metadatas: Vec<SplitMetadata> = split_ids
+            .iter()
+            .map(|split_id| SplitMetadata {
+                split_id: split_id.clone(),
+                index_uid: index_uid.clone(),
+                ..Default::default()
+            })
+            .collect();
+        metastore
+            .stage_splits(index_uid.clone(), split_metadatas)
+            .await
+            .unwrap();
+
+        let query = ListSplitsQuery::for_index(index_uid.clone());
+        let splits_page = metastore.list_splits_page(query.clone(), 2).await.unwrap();
+        assert_eq!(
+            collect_split_ids(&splits_page.splits),
+            &[&split_ids[0], &split_ids[1]]
+        );
+        assert_eq!(splits_page.next_page_token.as_ref(), Some(&split_ids[1]));
+
+        let query = query.with_after_split_id(splits_page.next_page_token.unwrap());
+        let splits_page = metastore.list_splits_page(query.clone(), 2).await.unwrap();
+        assert_eq!(
+            collect_split_ids(&splits_page.splits),
+            &[&split_ids[2], &split_ids[3]]
+        );
+        assert_eq!(splits_page.next_page_token.as_ref(), Some(&split_ids[3]));
+
+        let query = query.with_after_split_id(splits_page.next_page_token.unwrap());
+        let splits_page = metastore.list_splits_page(query, 2).await.unwrap();
+        assert_eq!(collect_split_ids(&splits_page.splits), &[&split_ids[4]]);
+        assert!(splits_page.next_page_token.is_none());
+
+        cleanup_index(&metastore, index_uid).await;
+    }
+
+    pub async fn test_metastore_list_splits_by_labels<
+        MetastoreToTest: Metastore + DefaultForTest,
+    >() {
+        let metastore = MetastoreToTest::default_for_test().await;
+
+        let index_id = append_random_suffix("test-list-splits-by-labels");
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+        let index_uid = metastore.create_index(index_config).await.unwrap();
+
+        let split_id_1 = format!("{index_id}--split-1");
+        let split_metadata_1 = SplitMetadata {
+            split_id: split_id_1.clone(),
+            index_uid: index_uid.clone(),
+            labels: BTreeMap::from_iter([
+                ("backfill".to_string(), "2023-01".to_string()),
+                ("batch_id".to_string(), "1".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let split_id_2 = format!("{index_id}--split-2");
+        let split_metadata_2 = SplitMetadata {
+            split_id: split_id_2.clone(),
+            index_uid: index_uid.clone(),
+            labels: BTreeMap::from_iter([
+                ("backfill".to_string(), "2023-01".to_string()),
+                ("batch_id".to_string(), "2".to_string()),
+            ]),
+            ..Default::default()
+        };
+        let split_id_3 = format!("{index_id}--split-3");
+        let split_metadata_3 = SplitMetadata {
+            split_id: split_id_3,
+            index_uid: index_uid.clone(),
+            ..Default::default()
+        };
+        metastore
+            .stage_splits(
+                index_uid.clone(),
+                vec![split_metadata_1.clone(), split_metadata_2, split_metadata_3],
+            )
+            .await
+            .unwrap();
+
+        let query = ListSplitsQuery::for_index(index_uid.clone()).with_label("backfill", "2023-01");
+        let splits = metastore.list_splits(query).await.unwrap();
+        assert_eq!(collect_split_ids(&splits), &[&split_id_1, &split_id_2]);
+        let split_1 = splits
+            .iter()
+            .find(|split| split.split_id() == split_id_1)
+            .unwrap();
+        assert_eq!(split_1.split_metadata.labels, split_metadata_1.labels);
+
+        let query = ListSplitsQuery::for_index(index_uid.clone())
+            .with_label("backfill", "2023-01")
+            .with_label("batch_id", "2");
+        let splits = metastore.list_splits(query).await.unwrap();
+        assert_eq!(collect_split_ids(&splits), &[&split_id_2]);
+
+        let query = ListSplitsQuery::for_index(index_uid.clone()).with_label("backfill", "2023-02");
+        let splits = metastore.list_splits(query).await.unwrap();
+        assert!(splits.is_empty());
+
+        let query = ListSplitsQuery::for_index(index_uid.clone());
+        let splits = metastore.list_splits(query).await.unwrap();
+        assert_eq!(splits.len(), 3);
+
+        cleanup_index(&metastore, index_uid).await;
+    }
+
+    pub async fn test_metastore_list_splits_with_bloom_filters<
+        MetastoreToTest: Metastore + DefaultForTest,
+    >() {
+        let metastore = MetastoreToTest::default_for_test().await;
+
+        let index_id = append_random_suffix("test-list-splits-with-bloom-filters");
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(&index_id, &index_uri);
+
+        let index_uid = metastore.create_index(index_config).await.unwrap();
+
+        let mut bloom_filter = BloomFilter::with_capacity(10);
+        bloom_filter.insert(b"trace-1");
+        let split_id = format!("{index_id}--split-1");
+        let split_metadata = SplitMetadata {
+            split_id: split_id.clone(),
+            index_uid: index_uid.clone(),
+            bloom_filters: BTreeMap::from_iter([("trace_id".to_string(), bloom_filter)]),
+            ..Default::default()
+        };
+        metastore
+            .stage_splits(index_uid.clone(), vec![split_metadata.clone()])
+            .await
+            .unwrap();
+
+        let query = ListSplitsQuery::for_index(index_uid.clone());
+        let splits = metastore.list_splits(query).await.unwrap();
+        assert_eq!(collect_split_ids(&splits), &[&split_id]);
+        assert!(splits[0].split_metadata.bloom_filters.is_empty());
+
+        let query = ListSplitsQuery::for_index(index_uid.clone()).with_bloom_filters();
+        let splits = metastore.list_splits(query).await.unwrap();
+        assert_eq!(
+            splits[0].split_metadata.bloom_filters,
+            split_metadata.bloom_filters
+        );
+
+        cleanup_index(&metastore, index_uid).await;
+    }
+
     pub async fn test_metastore_list_splits<MetastoreToTest: Metastore + DefaultForTest>() {
         let metastore = MetastoreToTest::default_for_test().await;
 
@@ -2333,6 +3049,96 @@ pub mod test_suite {
         cleanup_index(&metastore, index_uid_2).await;
     }
 
+    pub async fn test_metastore_cancel_delete_task<MetastoreToTest: Metastore + DefaultForTest>() {
+        let metastore = MetastoreToTest::default_for_test().await;
+        let index_id = append_random_suffix("test-cancel-delete-task");
+        let index_uri = format!("ram:///indexes/{index_id}");
+        let index_config = IndexConfig::for_test(&index_id, &index_uri);
+        let index_uid = metastore.create_index(index_config).await.unwrap();
+        let delete_query = DeleteQuery {
+            index_uid: index_uid.to_string(),
+            query_ast: qast_helper("my_field:my_value", &[]),
+            start_timestamp: Some(1),
+            end_timestamp: Some(2),
+        };
+        let delete_task_1 = metastore
+            .create_delete_task(delete_query.clone())
+            .await
+            .unwrap();
+        let delete_task_2 = metastore
+            .create_delete_task(delete_query.clone())
+            .await
+            .unwrap();
+
+        // A split that existed before the delete tasks were created.
+        let split_id_1 = format!("{index_id}--split-1");
+        let split_metadata_1 = SplitMetadata {
+            split_id: split_id_1.clone(),
+            index_uid: index_uid.clone(),
+            create_timestamp: delete_task_1.create_timestamp - 10,
+            delete_opstamp: 0,
+            ..Default::default()
+        };
+        // A split produced by indexing after the delete tasks were created.
+        let split_id_2 = format!("{index_id}--split-2");
+        let split_metadata_2 = SplitMetadata {
+            split_id: split_id_2.clone(),
+            index_uid: index_uid.clone(),
+            create_timestamp: delete_task_2.create_timestamp + 10,
+            delete_opstamp: delete_task_2.opstamp,
+            ..Default::default()
+        };
+        metastore
+            .stage_splits(index_uid.clone(), vec![split_metadata_1, split_metadata_2])
+            .await
+            .unwrap();
+        metastore
+            .publish_splits(index_uid.clone(), &[&split_id_1, &split_id_2], &[], None)
+            .await
+            .unwrap();
+
+        let error = metastore
+            .cancel_delete_task(index_uid.clone(), delete_task_2.opstamp + 1)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            MetastoreError::DeleteTaskDoesNotExist { .. }
+        ));
+
+        // The delete task pipeline applies the first delete task to the first split.
+        metastore
+            .update_splits_delete_opstamp(index_uid.clone(), &[&split_id_1], delete_task_1.opstamp)
+            .await
+            .unwrap();
+        let error = metastore
+            .cancel_delete_task(index_uid.clone(), delete_task_1.opstamp)
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            MetastoreError::DeleteTaskNotCancellable { .. }
+        ));
+
+        let cancelled_delete_task = metastore
+            .cancel_delete_task(index_uid.clone(), delete_task_2.opstamp)
+            .await
+            .unwrap();
+        assert_eq!(cancelled_delete_task, delete_task_2);
+
+        let delete_tasks = metastore
+            .list_delete_tasks(index_uid.clone(), 0)
+            .await
+            .unwrap();
+        assert_eq!(delete_tasks.len(), 1);
+        assert_eq!(delete_tasks[0].opstamp, delete_task_1.opstamp);
+
+        let delete_task_3 = metastore.create_delete_task(delete_query).await.unwrap();
+        assert!(delete_task_3.opstamp > delete_task_2.opstamp);
+
+        cleanup_index(&metastore, index_uid).await;
+    }
+
     pub async fn test_metastore_list_stale_splits<MetastoreToTest: Metastore + DefaultForTest>() {
         let metastore = MetastoreToTest::default_for_test().await;
         let current_timestamp = OffsetDateTime::now_utc().unix_timestamp();
@@ -2647,7 +3453,8 @@ pub mod test_suite {
 }
 
 macro_rules! metastore_test_suite {
-    ($metastore_type:ty) => {
+    // The tenant metastores reject the updates of the cluster settings, shared by all the tenants.
+    ($metastore_type:ty, without_cluster_settings) => {
         #[cfg(test)]
         mod common_tests {
 
@@ -2689,6 +3496,30 @@ macro_rules! metastore_test_suite {
                 crate::tests::test_suite::test_metastore_delete_index::<$metastore_type>().await;
             }
 
+            #[tokio::test]
+            async fn test_metastore_trash_and_restore_index() {
+                let _ = tracing_subscriber::fmt::try_init();
+                crate::tests::test_suite::test_metastore_trash_and_restore_index::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            async fn test_metastore_set_merges_paused() {
+                let _ = tracing_subscriber::fmt::try_init();
+                crate::tests::test_suite::test_metastore_set_merges_paused::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            async fn test_metastore_update_index_config() {
+                let _ = tracing_subscriber::fmt::try_init();
+                crate::tests::test_suite::test_metastore_update_index_config::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            async fn test_metastore_audit_log() {
+                let _ = tracing_subscriber::fmt::try_init();
+                crate::tests::test_suite::test_metastore_audit_log::<$metastore_type>().await;
+            }
+
             // Split API tests
             //
             //  - stage_splits
@@ -2708,6 +3539,12 @@ macro_rules! metastore_test_suite {
                 crate::tests::test_suite::test_metastore_publish_splits_concurrency::<$metastore_type>().await;
             }
 
+            #[tokio::test]
+            async fn test_metastore_publish_splits_across_indexes() {
+                let _ = tracing_subscriber::fmt::try_init();
+                crate::tests::test_suite::test_metastore_publish_splits_across_indexes::<$metastore_type>().await;
+            }
+
             #[tokio::test]
             async fn test_metastore_publish_splits_empty_splits_array_is_allowed() {
                 crate::tests::test_suite::test_metastore_publish_splits_empty_splits_array_is_allowed::<$metastore_type>().await;
@@ -2744,6 +3581,24 @@ macro_rules! metastore_test_suite {
                 crate::tests::test_suite::test_metastore_list_splits::<$metastore_type>().await;
             }
 
+            #[tokio::test]
+            async fn test_metastore_list_splits_page() {
+                let _ = tracing_subscriber::fmt::try_init();
+                crate::tests::test_suite::test_metastore_list_splits_page::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            async fn test_metastore_list_splits_by_labels() {
+                let _ = tracing_subscriber::fmt::try_init();
+                crate::tests::test_suite::test_metastore_list_splits_by_labels::<$metastore_type>().await;
+            }
+
+            #[tokio::test]
+            async fn test_metastore_list_splits_with_bloom_filters() {
+                let _ = tracing_subscriber::fmt::try_init();
+                crate::tests::test_suite::test_metastore_list_splits_with_bloom_filters::<$metastore_type>().await;
+            }
+
             #[tokio::test]
             async fn test_metastore_split_update_timestamp() {
                 let _ = tracing_subscriber::fmt::try_init();
@@ -2776,6 +3631,12 @@ macro_rules! metastore_test_suite {
                 crate::tests::test_suite::test_metastore_reset_checkpoint::<$metastore_type>().await;
             }
 
+            #[tokio::test]
+            async fn test_metastore_rewind_checkpoint() {
+                let _ = tracing_subscriber::fmt::try_init();
+                crate::tests::test_suite::test_metastore_rewind_checkpoint::<$metastore_type>().await;
+            }
+
             #[tokio::test]
             async fn test_metastore_create_delete_task() {
                 let _ = tracing_subscriber::fmt::try_init();
@@ -2800,6 +3661,12 @@ macro_rules! metastore_test_suite {
                 crate::tests::test_suite::test_metastore_list_delete_tasks::<$metastore_type>().await;
             }
 
+            #[tokio::test]
+            async fn test_metastore_cancel_delete_task() {
+                let _ = tracing_subscriber::fmt::try_init();
+                crate::tests::test_suite::test_metastore_cancel_delete_task::<$metastore_type>().await;
+            }
+
             #[tokio::test]
             async fn test_metastore_list_stale_splits() {
                 let _ = tracing_subscriber::fmt::try_init();
@@ -2818,5 +3685,17 @@ macro_rules! metastore_test_suite {
                 crate::tests::test_suite::test_metastore_stage_splits::<$metastore_type>().await;
             }
         }
-    }
+    };
+    ($metastore_type:ty) => {
+        metastore_test_suite!($metastore_type, without_cluster_settings);
+
+        #[cfg(test)]
+        mod cluster_settings_tests {
+            #[tokio::test]
+            async fn test_metastore_cluster_settings() {
+                let _ = tracing_subscriber::fmt::try_init();
+                crate::tests::test_suite::test_metastore_cluster_settings::<$metastore_type>().await;
+            }
+        }
+    };
 }
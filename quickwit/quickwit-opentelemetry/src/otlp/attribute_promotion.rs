@@ -0,0 +1,280 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Context;
+use quickwit_config::{
+    build_doc_mapper, IndexConfig, OtlpPromotedAttribute, OtlpPromotedAttributeType,
+};
+use quickwit_doc_mapper::FieldMappingEntry;
+use serde_json::{json, Value as JsonValue};
+
+#[derive(Debug)]
+struct PromotedField {
+    attribute_key: String,
+    field_name: String,
+    field_type: OtlpPromotedAttributeType,
+}
+
+/// Copies selected log and span attributes into dedicated fast fields of the OTLP logs and trace
+/// indexes.
+#[derive(Debug, Clone, Default)]
+pub struct OtlpAttributePromoter {
+    promoted_fields: Arc<Vec<PromotedField>>,
+}
+
+impl OtlpAttributePromoter {
+    pub fn new(promoted_attributes: &[OtlpPromotedAttribute]) -> Self {
+        let promoted_fields = promoted_attributes
+            .iter()
+            .map(|promoted_attribute| PromotedField {
+                attribute_key: promoted_attribute.key.clone(),
+                field_name: promoted_attribute.field_name(),
+                field_type: promoted_attribute.field_type,
+            })
+            .collect();
+        Self {
+            promoted_fields: Arc::new(promoted_fields),
+        }
+    }
+
+    /// Adds the fields receiving the promoted attributes to the doc mapping of an OTLP logs or
+    /// trace index config.
+    pub fn add_field_mappings(&self, index_config: &mut IndexConfig) -> anyhow::Result<()> {
+        if self.promoted_fields.is_empty() {
+            return Ok(());
+        }
+        for promoted_field in self.promoted_fields.iter() {
+            let field_mapping_json = match promoted_field.field_type {
+                OtlpPromotedAttributeType::Text => json!({
+                    "name": promoted_field.field_name,
+                    "type": "text",
+                    "tokenizer": "raw",
+                    "fast": true,
+                }),
+                OtlpPromotedAttributeType::I64 => json!({
+                    "name": promoted_field.field_name,
+                    "type": "i64",
+                    "fast": true,
+                }),
+                OtlpPromotedAttributeType::U64 => json!({
+                    "name": promoted_field.field_name,
+                    "type": "u64",
+                    "fast": true,
+                }),
+                OtlpPromotedAttributeType::F64 => json!({
+                    "name": promoted_field.field_name,
+                    "type": "f64",
+                    "fast": true,
+                }),
+                OtlpPromotedAttributeType::Bool => json!({
+                    "name": promoted_field.field_name,
+                    "type": "bool",
+                    "fast": true,
+                }),
+            };
+            let field_mapping: FieldMappingEntry = serde_json::from_value(field_mapping_json)
+                .with_context(|| {
+                    format!(
+                        "Failed to build field mapping for promoted attribute `{}`.",
+                        promoted_field.attribute_key
+                    )
+                })?;
+            index_config.doc_mapping.field_mappings.push(field_mapping);
+        }
+        build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings).with_context(
+            || {
+                format!(
+                    "Failed to promote attributes in the doc mapping of index `{}`.",
+                    index_config.index_id
+                )
+            },
+        )?;
+        Ok(())
+    }
+
+    /// Returns the names of the fields receiving the promoted attributes that are missing from the
+    /// doc mapping of an index config.
+    pub fn missing_fields<'a>(&'a self, index_config: &IndexConfig) -> Vec<&'a str> {
+        self.promoted_fields
+            .iter()
+            .map(|promoted_field| promoted_field.field_name.as_str())
+            .filter(|field_name| {
+                !index_config
+                    .doc_mapping
+                    .field_mappings
+                    .iter()
+                    .any(|field_mapping| field_mapping.name == *field_name)
+            })
+            .collect()
+    }
+
+    /// Extracts the promoted attributes of a log record or span, looking them up in the attributes
+    /// of the record first, then in the attributes of its resource. Attributes whose value cannot
+    /// be converted to the type of their field are skipped.
+    pub(crate) fn promote(
+        &self,
+        attributes: &HashMap<String, JsonValue>,
+        resource_attributes: &HashMap<String, JsonValue>,
+    ) -> HashMap<String, JsonValue> {
+        let mut promoted_attributes = HashMap::new();
+
+        for promoted_field in self.promoted_fields.iter() {
+            let attribute_value_opt = attributes
+                .get(&promoted_field.attribute_key)
+                .or_else(|| resource_attributes.get(&promoted_field.attribute_key));
+            let field_value_opt = attribute_value_opt.and_then(|attribute_value| {
+                convert_value(attribute_value, promoted_field.field_type)
+            });
+            if let Some(field_value) = field_value_opt {
+                promoted_attributes.insert(promoted_field.field_name.clone(), field_value);
+            }
+        }
+        promoted_attributes
+    }
+}
+
+fn convert_value(value: &JsonValue, field_type: OtlpPromotedAttributeType) -> Option<JsonValue> {
+    match (field_type, value) {
+        (OtlpPromotedAttributeType::Text, JsonValue::String(_)) => Some(value.clone()),
+        (OtlpPromotedAttributeType::Text, JsonValue::Number(number)) => {
+            Some(JsonValue::String(number.to_string()))
+        }
+        (OtlpPromotedAttributeType::Text, JsonValue::Bool(boolean)) => {
+            Some(JsonValue::String(boolean.to_string()))
+        }
+        (OtlpPromotedAttributeType::I64, JsonValue::Number(number)) => {
+            number.as_i64().map(Into::into)
+        }
+        (OtlpPromotedAttributeType::I64, JsonValue::String(text)) => {
+            text.parse::<i64>().ok().map(Into::into)
+        }
+        (OtlpPromotedAttributeType::U64, JsonValue::Number(number)) => {
+            number.as_u64().map(Into::into)
+        }
+        (OtlpPromotedAttributeType::U64, JsonValue::String(text)) => {
+            text.parse::<u64>().ok().map(Into::into)
+        }
+        (OtlpPromotedAttributeType::F64, JsonValue::Number(number)) => {
+            number.as_f64().map(Into::into)
+        }
+        (OtlpPromotedAttributeType::F64, JsonValue::String(text)) => text
+            .parse::<f64>()
+            .ok()
+            .filter(|float| float.is_finite())
+            .map(Into::into),
+        (OtlpPromotedAttributeType::Bool, JsonValue::Bool(_)) => Some(value.clone()),
+        (OtlpPromotedAttributeType::Bool, JsonValue::String(text)) => {
+            text.parse::<bool>().ok().map(Into::into)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_common::uri::Uri;
+    use quickwit_config::{load_index_config_from_user_config, ConfigFormat};
+
+    use super::*;
+    use crate::otlp::OTEL_TRACE_INDEX_CONFIG;
+
+    fn promoted_attribute(
+        key: &str,
+        field_name_opt: Option<&str>,
+        field_type: OtlpPromotedAttributeType,
+    ) -> OtlpPromotedAttribute {
+        OtlpPromotedAttribute {
+            key: key.to_string(),
+            field_name: field_name_opt.map(|field_name| field_name.to_string()),
+            field_type,
+        }
+    }
+
+    #[test]
+    fn test_otlp_attribute_promoter_add_field_mappings() {
+        let default_index_root_uri = Uri::from_well_formed("ram:///indexes");
+        let mut index_config = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            OTEL_TRACE_INDEX_CONFIG.as_bytes(),
+            &default_index_root_uri,
+        )
+        .unwrap();
+        let num_field_mappings = index_config.doc_mapping.field_mappings.len();
+
+        let attribute_promoter = OtlpAttributePromoter::new(&[
+            promoted_attribute("http.status_code", None, OtlpPromotedAttributeType::I64),
+            promoted_attribute("k8s.pod.name", Some("pod"), OtlpPromotedAttributeType::Text),
+        ]);
+        assert_eq!(
+            attribute_promoter.missing_fields(&index_config),
+            ["http_status_code", "pod"]
+        );
+        attribute_promoter
+            .add_field_mappings(&mut index_config)
+            .unwrap();
+        assert_eq!(
+            index_config.doc_mapping.field_mappings.len(),
+            num_field_mappings + 2
+        );
+        assert!(attribute_promoter.missing_fields(&index_config).is_empty());
+
+        let attribute_promoter = OtlpAttributePromoter::new(&[promoted_attribute(
+            "service.name",
+            None,
+            OtlpPromotedAttributeType::Text,
+        )]);
+        attribute_promoter
+            .add_field_mappings(&mut index_config)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_otlp_attribute_promoter_promote() {
+        let attribute_promoter = OtlpAttributePromoter::new(&[
+            promoted_attribute("http.status_code", None, OtlpPromotedAttributeType::I64),
+            promoted_attribute("k8s.pod.name", Some("pod"), OtlpPromotedAttributeType::Text),
+            promoted_attribute("sampled", None, OtlpPromotedAttributeType::Bool),
+            promoted_attribute("ratio", None, OtlpPromotedAttributeType::F64),
+        ]);
+        let attributes = HashMap::from_iter([
+            ("http.status_code".to_string(), json!("200")),
+            ("k8s.pod.name".to_string(), json!("pod-from-record")),
+            ("sampled".to_string(), json!("not-a-bool")),
+        ]);
+        let resource_attributes = HashMap::from_iter([
+            ("k8s.pod.name".to_string(), json!("pod-from-resource")),
+            ("ratio".to_string(), json!(0.5)),
+        ]);
+        let promoted_attributes = attribute_promoter.promote(&attributes, &resource_attributes);
+        assert_eq!(
+            promoted_attributes,
+            HashMap::from_iter([
+                ("http_status_code".to_string(), json!(200)),
+                ("pod".to_string(), json!("pod-from-record")),
+                ("ratio".to_string(), json!(0.5)),
+            ])
+        );
+        assert!(OtlpAttributePromoter::default()
+            .promote(&attributes, &resource_attributes)
+            .is_empty());
+    }
+}
@@ -0,0 +1,136 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use quickwit_proto::opentelemetry::proto::common::v1::any_value::Value as OtlpValue;
+use quickwit_proto::opentelemetry::proto::common::v1::KeyValue as OtlpKeyValue;
+use tonic::Request;
+
+/// gRPC metadata key or HTTP header of the OTLP export requests that overrides the index receiving
+/// their logs or spans.
+pub const OTLP_INDEX_HEADER: &str = "x-qw-index";
+
+/// Decides which index receives the logs or spans of an OTLP export request. By order of
+/// precedence, the target index is:
+/// - the index set by the `x-qw-index` header of the request;
+/// - `{default_index_id}-{tenant}` when a tenant attribute is configured, `tenant` being the value
+///   of this attribute on the resource emitting the logs or spans;
+/// - the default index.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct OtlpIndexRouter {
+    default_index_id: String,
+    tenant_attribute_opt: Option<String>,
+}
+
+impl OtlpIndexRouter {
+    pub fn new(default_index_id: impl Into<String>, tenant_attribute_opt: Option<String>) -> Self {
+        Self {
+            default_index_id: default_index_id.into(),
+            tenant_attribute_opt,
+        }
+    }
+
+    pub fn default_index_id(&self) -> &str {
+        &self.default_index_id
+    }
+
+    /// Returns the index receiving the logs or spans emitted by a resource.
+    pub(crate) fn route(
+        &self,
+        header_index_id_opt: Option<&str>,
+        resource_attributes: &[OtlpKeyValue],
+    ) -> String {
+        if let Some(header_index_id) = header_index_id_opt {
+            return header_index_id.to_string();
+        }
+        if let Some(tenant) = self.extract_tenant(resource_attributes) {
+            return format!("{}-{tenant}", self.default_index_id);
+        }
+        self.default_index_id.clone()
+    }
+
+    fn extract_tenant<'a>(&self, resource_attributes: &'a [OtlpKeyValue]) -> Option<&'a str> {
+        let tenant_attribute = self.tenant_attribute_opt.as_ref()?;
+        resource_attributes
+            .iter()
+            .find(|attribute| &attribute.key == tenant_attribute)
+            .and_then(|attribute| attribute.value.as_ref())
+            .and_then(|any_value| match &any_value.value {
+                Some(OtlpValue::StringValue(tenant)) if !tenant.is_empty() => Some(tenant.as_str()),
+                _ => None,
+            })
+    }
+}
+
+/// Extracts the index set by the `x-qw-index` metadata key of a gRPC request, if any.
+pub(crate) fn extract_header_index_id<T>(request: &Request<T>) -> Option<String> {
+    request
+        .metadata()
+        .get(OTLP_INDEX_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|index_id| !index_id.is_empty())
+        .map(|index_id| index_id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_proto::opentelemetry::proto::common::v1::AnyValue as OtlpAnyValue;
+
+    use super::*;
+
+    fn string_attribute(key: &str, value: &str) -> OtlpKeyValue {
+        OtlpKeyValue {
+            key: key.to_string(),
+            value: Some(OtlpAnyValue {
+                value: Some(OtlpValue::StringValue(value.to_string())),
+            }),
+        }
+    }
+
+    #[test]
+    fn test_otlp_index_router() {
+        let resource_attributes = vec![
+            string_attribute("service.name", "my-service"),
+            string_attribute("tenant.id", "acme"),
+        ];
+        let index_router = OtlpIndexRouter::new("otel-logs-v0", None);
+        assert_eq!(
+            index_router.route(None, &resource_attributes),
+            "otel-logs-v0"
+        );
+        assert_eq!(
+            index_router.route(Some("my-logs"), &resource_attributes),
+            "my-logs"
+        );
+
+        let index_router = OtlpIndexRouter::new("otel-logs-v0", Some("tenant.id".to_string()));
+        assert_eq!(
+            index_router.route(None, &resource_attributes),
+            "otel-logs-v0-acme"
+        );
+        assert_eq!(index_router.route(None, &[]), "otel-logs-v0");
+        assert_eq!(
+            index_router.route(Some("my-logs"), &resource_attributes),
+            "my-logs"
+        );
+        assert_eq!(
+            index_router.route(None, &[string_attribute("tenant.id", "")]),
+            "otel-logs-v0"
+        );
+    }
+}
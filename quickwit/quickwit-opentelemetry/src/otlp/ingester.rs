@@ -0,0 +1,303 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::Duration;
+
+use quickwit_config::{OtlpCommitMode, OtlpIngestConfig};
+use quickwit_ingest::{
+    CommitType, DocBatch, IngestRequest, IngestService, IngestServiceClient, IngestServiceError,
+};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, instrument};
+
+/// Capacity of the channel buffering the requests waiting to be coalesced.
+const BATCH_CHANNEL_CAPACITY: usize = 1_000;
+
+type IngestResult = Result<(), IngestServiceError>;
+
+struct PendingIngest {
+    doc_batches: Vec<DocBatch>,
+    response_tx: oneshot::Sender<IngestResult>,
+}
+
+impl PendingIngest {
+    fn num_bytes(&self) -> usize {
+        self.doc_batches
+            .iter()
+            .map(|doc_batch| doc_batch.num_bytes())
+            .sum()
+    }
+}
+
+/// Sends the documents of the OTLP services to the ingest API. Requests are acknowledged
+/// according to the configured commit mode, and the documents of concurrent requests are
+/// optionally coalesced into a single ingest request.
+#[derive(Clone)]
+pub(crate) struct OtlpIngester {
+    ingest_service: IngestServiceClient,
+    commit_type: CommitType,
+    batch_tx_opt: Option<mpsc::Sender<PendingIngest>>,
+}
+
+impl OtlpIngester {
+    /// Creates an ingester acknowledging the requests as soon as the documents are appended to
+    /// the write-ahead log, without batching.
+    pub fn new(ingest_service: IngestServiceClient) -> Self {
+        Self {
+            ingest_service,
+            commit_type: CommitType::Auto,
+            batch_tx_opt: None,
+        }
+    }
+
+    /// Configures the commit mode and batching of the ingester. When batching is enabled, this
+    /// spawns the task coalescing the requests, so this must be called from within a Tokio
+    /// runtime.
+    pub fn with_config(self, ingest_config: &OtlpIngestConfig) -> Self {
+        let commit_type = match ingest_config.commit {
+            OtlpCommitMode::Async => CommitType::Auto,
+            OtlpCommitMode::Sync => CommitType::WaitFor,
+        };
+        let batch_delay = ingest_config.batch_delay();
+        let batch_tx_opt = if batch_delay.is_zero() {
+            None
+        } else {
+            let (batch_tx, batch_rx) = mpsc::channel(BATCH_CHANNEL_CAPACITY);
+            let max_batch_num_bytes = ingest_config.max_batch_size.get_bytes() as usize;
+            tokio::spawn(coalesce_ingests(
+                self.ingest_service.clone(),
+                commit_type,
+                batch_delay,
+                max_batch_num_bytes,
+                batch_rx,
+            ));
+            Some(batch_tx)
+        };
+        Self {
+            ingest_service: self.ingest_service,
+            commit_type,
+            batch_tx_opt,
+        }
+    }
+
+    /// Ingests `doc_batches` and returns once the request is acknowledged.
+    pub async fn ingest(&self, doc_batches: Vec<DocBatch>) -> IngestResult {
+        let Some(batch_tx) = &self.batch_tx_opt else {
+            return ingest(self.ingest_service.clone(), doc_batches, self.commit_type).await;
+        };
+        let (response_tx, response_rx) = oneshot::channel();
+        let pending_ingest = PendingIngest {
+            doc_batches,
+            response_tx,
+        };
+        batch_tx
+            .send(pending_ingest)
+            .await
+            .map_err(|_| IngestServiceError::Unavailable)?;
+        response_rx
+            .await
+            .map_err(|_| IngestServiceError::Unavailable)?
+    }
+}
+
+async fn ingest(
+    mut ingest_service: IngestServiceClient,
+    doc_batches: Vec<DocBatch>,
+    commit_type: CommitType,
+) -> IngestResult {
+    let ingest_request = IngestRequest {
+        doc_batches,
+        commit: commit_type as u32,
+    };
+    ingest_service.ingest(ingest_request).await?;
+    Ok(())
+}
+
+/// Coalesces the requests received within `batch_delay` of each other, up to
+/// `max_batch_num_bytes`, and sends them to the ingest API as a single request.
+async fn coalesce_ingests(
+    ingest_service: IngestServiceClient,
+    commit_type: CommitType,
+    batch_delay: Duration,
+    max_batch_num_bytes: usize,
+    mut batch_rx: mpsc::Receiver<PendingIngest>,
+) {
+    while let Some(pending_ingest) = batch_rx.recv().await {
+        let deadline = tokio::time::Instant::now() + batch_delay;
+        let mut batch_num_bytes = pending_ingest.num_bytes();
+        let mut pending_ingests = vec![pending_ingest];
+
+        while batch_num_bytes < max_batch_num_bytes {
+            match tokio::time::timeout_at(deadline, batch_rx.recv()).await {
+                Ok(Some(pending_ingest)) => {
+                    batch_num_bytes += pending_ingest.num_bytes();
+                    pending_ingests.push(pending_ingest);
+                }
+                Ok(None) | Err(_) => break,
+            }
+        }
+        // In sync mode, the ingest request does not return before the next commit, so we keep
+        // coalescing the following requests in the meantime.
+        tokio::spawn(flush_pending_ingests(
+            ingest_service.clone(),
+            commit_type,
+            pending_ingests,
+        ));
+    }
+}
+
+#[instrument(skip_all, fields(num_requests = pending_ingests.len()))]
+async fn flush_pending_ingests(
+    ingest_service: IngestServiceClient,
+    commit_type: CommitType,
+    pending_ingests: Vec<PendingIngest>,
+) {
+    let doc_batches: Vec<DocBatch> = pending_ingests
+        .iter()
+        .flat_map(|pending_ingest| pending_ingest.doc_batches.iter().cloned())
+        .collect();
+    match ingest(ingest_service.clone(), doc_batches, commit_type).await {
+        Err(IngestServiceError::IndexNotFound { index_id }) if pending_ingests.len() > 1 => {
+            // The ingest API checks that the indexes exist before appending any document, so we
+            // can safely retry the requests one by one and only fail those targeting the missing
+            // index.
+            debug!(index_id=%index_id, "Retrying coalesced requests one by one.");
+            for pending_ingest in pending_ingests {
+                let ingest_service = ingest_service.clone();
+                tokio::spawn(async move {
+                    let ingest_result =
+                        ingest(ingest_service, pending_ingest.doc_batches, commit_type).await;
+                    let _ = pending_ingest.response_tx.send(ingest_result);
+                });
+            }
+        }
+        ingest_result => {
+            for pending_ingest in pending_ingests {
+                let _ = pending_ingest.response_tx.send(ingest_result.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use quickwit_ingest::{DocBatchBuilder, IngestResponse};
+
+    use super::*;
+
+    fn doc_batch_for_test(index_id: &str) -> DocBatch {
+        let mut doc_batch_builder = DocBatchBuilder::new(index_id.to_string());
+        doc_batch_builder.ingest_doc(&b"{}"[..]);
+        doc_batch_builder.build()
+    }
+
+    fn ingest_service_for_test(
+        ingest_requests: Arc<Mutex<Vec<IngestRequest>>>,
+    ) -> IngestServiceClient {
+        let mut mock_ingest_service = IngestServiceClient::mock();
+        mock_ingest_service
+            .expect_ingest()
+            .returning(move |ingest_request| {
+                if let Some(doc_batch) = ingest_request
+                    .doc_batches
+                    .iter()
+                    .find(|doc_batch| doc_batch.index_id == "missing-index")
+                {
+                    return Err(IngestServiceError::IndexNotFound {
+                        index_id: doc_batch.index_id.clone(),
+                    });
+                }
+                let num_docs: usize = ingest_request
+                    .doc_batches
+                    .iter()
+                    .map(|doc_batch| doc_batch.num_docs())
+                    .sum();
+                ingest_requests.lock().unwrap().push(ingest_request);
+                Ok(IngestResponse {
+                    num_docs_for_processing: num_docs as u64,
+                })
+            });
+        IngestServiceClient::new(mock_ingest_service)
+    }
+
+    #[tokio::test]
+    async fn test_otlp_ingester_commit_mode() {
+        let ingest_requests = Arc::new(Mutex::new(Vec::new()));
+        let ingest_service = ingest_service_for_test(ingest_requests.clone());
+
+        let ingester = OtlpIngester::new(ingest_service.clone());
+        ingester
+            .ingest(vec![doc_batch_for_test("test-index")])
+            .await
+            .unwrap();
+
+        let ingest_config = OtlpIngestConfig {
+            commit: OtlpCommitMode::Sync,
+            ..Default::default()
+        };
+        let ingester = OtlpIngester::new(ingest_service).with_config(&ingest_config);
+        ingester
+            .ingest(vec![doc_batch_for_test("test-index")])
+            .await
+            .unwrap();
+
+        let ingest_requests = ingest_requests.lock().unwrap();
+        assert_eq!(ingest_requests.len(), 2);
+        assert_eq!(ingest_requests[0].commit, CommitType::Auto as u32);
+        assert_eq!(ingest_requests[1].commit, CommitType::WaitFor as u32);
+    }
+
+    #[tokio::test]
+    async fn test_otlp_ingester_coalesces_concurrent_requests() {
+        let ingest_requests = Arc::new(Mutex::new(Vec::new()));
+        let ingest_service = ingest_service_for_test(ingest_requests.clone());
+        let ingest_config = OtlpIngestConfig {
+            batch_delay_millis: 100,
+            ..Default::default()
+        };
+        let ingester = OtlpIngester::new(ingest_service).with_config(&ingest_config);
+
+        let (first_result, second_result, missing_index_result) = tokio::join!(
+            ingester.ingest(vec![doc_batch_for_test("test-index")]),
+            ingester.ingest(vec![doc_batch_for_test("test-index")]),
+            ingester.ingest(vec![doc_batch_for_test("missing-index")]),
+        );
+        // The coalesced request fails, so the requests are retried one by one.
+        first_result.unwrap();
+        second_result.unwrap();
+        assert!(matches!(
+            missing_index_result.unwrap_err(),
+            IngestServiceError::IndexNotFound { .. }
+        ));
+        assert_eq!(ingest_requests.lock().unwrap().len(), 2);
+
+        let (first_result, second_result) = tokio::join!(
+            ingester.ingest(vec![doc_batch_for_test("test-index")]),
+            ingester.ingest(vec![doc_batch_for_test("test-index")]),
+        );
+        first_result.unwrap();
+        second_result.unwrap();
+
+        let ingest_requests = ingest_requests.lock().unwrap();
+        assert_eq!(ingest_requests.len(), 3);
+        assert_eq!(ingest_requests[2].doc_batches.len(), 2);
+    }
+}
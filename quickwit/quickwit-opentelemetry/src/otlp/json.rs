@@ -0,0 +1,588 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+//! Decoding of the OTLP/JSON encoding of the export requests.
+//!
+//! The OTLP/JSON encoding follows the Protobuf JSON mapping with a few exceptions: trace and span
+//! IDs are hex-encoded instead of base64-encoded, and enum values are integers. Since the serde
+//! implementations derived on the generated Protobuf types do not follow this mapping, the
+//! requests are decoded by hand. Both the lowerCamelCase and the original field names are
+//! accepted, as recommended by the specification.
+//!
+//! <https://opentelemetry.io/docs/specs/otlp/#json-protobuf-encoding>
+
+use base64::prelude::{Engine, BASE64_STANDARD};
+use quickwit_proto::opentelemetry::proto::collector::logs::v1::ExportLogsServiceRequest;
+use quickwit_proto::opentelemetry::proto::collector::trace::v1::ExportTraceServiceRequest;
+use quickwit_proto::opentelemetry::proto::common::v1::any_value::Value as OtlpValue;
+use quickwit_proto::opentelemetry::proto::common::v1::{
+    AnyValue as OtlpAnyValue, ArrayValue as OtlpArrayValue, InstrumentationScope,
+    KeyValue as OtlpKeyValue, KeyValueList as OtlpKeyValueList,
+};
+use quickwit_proto::opentelemetry::proto::logs::v1::{
+    LogRecord as OtlpLogRecord, ResourceLogs, ScopeLogs, SeverityNumber,
+};
+use quickwit_proto::opentelemetry::proto::resource::v1::Resource as OtlpResource;
+use quickwit_proto::opentelemetry::proto::trace::v1::span::{
+    Event as OtlpEvent, Link as OtlpLink, SpanKind as OtlpSpanKind,
+};
+use quickwit_proto::opentelemetry::proto::trace::v1::status::StatusCode as OtlpStatusCode;
+use quickwit_proto::opentelemetry::proto::trace::v1::{
+    ResourceSpans, ScopeSpans, Span as OtlpSpan, Status as OtlpStatus,
+};
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+use super::decode_hex;
+
+type JsonObject = JsonMap<String, JsonValue>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum OtlpJsonError {
+    #[error("Failed to parse JSON payload: {0}.")]
+    Json(#[from] serde_json::Error),
+    #[error("Invalid value for field `{field}`: {message}.")]
+    InvalidField {
+        field: &'static str,
+        message: String,
+    },
+}
+
+fn invalid_field(field: &'static str, message: impl ToString) -> OtlpJsonError {
+    OtlpJsonError::InvalidField {
+        field,
+        message: message.to_string(),
+    }
+}
+
+/// Decodes an OTLP/JSON-encoded logs export request.
+pub fn parse_otlp_json_logs_request(
+    payload: &[u8],
+) -> Result<ExportLogsServiceRequest, OtlpJsonError> {
+    let json_value: JsonValue = serde_json::from_slice(payload)?;
+    let request_object = as_object(&json_value, "request")?;
+    let resource_logs = parse_array(request_object, "resourceLogs", parse_resource_logs)?;
+    Ok(ExportLogsServiceRequest { resource_logs })
+}
+
+/// Decodes an OTLP/JSON-encoded trace export request.
+pub fn parse_otlp_json_trace_request(
+    payload: &[u8],
+) -> Result<ExportTraceServiceRequest, OtlpJsonError> {
+    let json_value: JsonValue = serde_json::from_slice(payload)?;
+    let request_object = as_object(&json_value, "request")?;
+    let resource_spans = parse_array(request_object, "resourceSpans", parse_resource_spans)?;
+    Ok(ExportTraceServiceRequest { resource_spans })
+}
+
+fn parse_resource_logs(json_value: &JsonValue) -> Result<ResourceLogs, OtlpJsonError> {
+    let object = as_object(json_value, "resourceLogs")?;
+    let resource_logs = ResourceLogs {
+        resource: parse_optional(object, "resource", parse_resource)?,
+        scope_logs: parse_array(object, "scopeLogs", parse_scope_logs)?,
+        schema_url: parse_string(object, "schemaUrl")?,
+    };
+    Ok(resource_logs)
+}
+
+fn parse_scope_logs(json_value: &JsonValue) -> Result<ScopeLogs, OtlpJsonError> {
+    let object = as_object(json_value, "scopeLogs")?;
+    let scope_logs = ScopeLogs {
+        scope: parse_optional(object, "scope", parse_scope)?,
+        log_records: parse_array(object, "logRecords", parse_log_record)?,
+        schema_url: parse_string(object, "schemaUrl")?,
+    };
+    Ok(scope_logs)
+}
+
+fn parse_log_record(json_value: &JsonValue) -> Result<OtlpLogRecord, OtlpJsonError> {
+    let object = as_object(json_value, "logRecords")?;
+    let log_record = OtlpLogRecord {
+        time_unix_nano: parse_u64(object, "timeUnixNano")?,
+        observed_time_unix_nano: parse_u64(object, "observedTimeUnixNano")?,
+        severity_number: parse_enum(object, "severityNumber", |name| {
+            SeverityNumber::from_str_name(name).map(|severity_number| severity_number as i32)
+        })?,
+        severity_text: parse_string(object, "severityText")?,
+        body: parse_optional(object, "body", parse_any_value)?,
+        attributes: parse_array(object, "attributes", parse_key_value)?,
+        dropped_attributes_count: parse_u32(object, "droppedAttributesCount")?,
+        flags: parse_u32(object, "flags")?,
+        trace_id: parse_hex_id(object, "traceId")?,
+        span_id: parse_hex_id(object, "spanId")?,
+    };
+    Ok(log_record)
+}
+
+fn parse_resource_spans(json_value: &JsonValue) -> Result<ResourceSpans, OtlpJsonError> {
+    let object = as_object(json_value, "resourceSpans")?;
+    let resource_spans = ResourceSpans {
+        resource: parse_optional(object, "resource", parse_resource)?,
+        scope_spans: parse_array(object, "scopeSpans", parse_scope_spans)?,
+        schema_url: parse_string(object, "schemaUrl")?,
+    };
+    Ok(resource_spans)
+}
+
+fn parse_scope_spans(json_value: &JsonValue) -> Result<ScopeSpans, OtlpJsonError> {
+    let object = as_object(json_value, "scopeSpans")?;
+    let scope_spans = ScopeSpans {
+        scope: parse_optional(object, "scope", parse_scope)?,
+        spans: parse_array(object, "spans", parse_span)?,
+        schema_url: parse_string(object, "schemaUrl")?,
+    };
+    Ok(scope_spans)
+}
+
+fn parse_span(json_value: &JsonValue) -> Result<OtlpSpan, OtlpJsonError> {
+    let object = as_object(json_value, "spans")?;
+    let span = OtlpSpan {
+        trace_id: parse_hex_id(object, "traceId")?,
+        span_id: parse_hex_id(object, "spanId")?,
+        trace_state: parse_string(object, "traceState")?,
+        parent_span_id: parse_hex_id(object, "parentSpanId")?,
+        name: parse_string(object, "name")?,
+        kind: parse_enum(object, "kind", |name| {
+            OtlpSpanKind::from_str_name(name).map(|span_kind| span_kind as i32)
+        })?,
+        start_time_unix_nano: parse_u64(object, "startTimeUnixNano")?,
+        end_time_unix_nano: parse_u64(object, "endTimeUnixNano")?,
+        attributes: parse_array(object, "attributes", parse_key_value)?,
+        dropped_attributes_count: parse_u32(object, "droppedAttributesCount")?,
+        events: parse_array(object, "events", parse_event)?,
+        dropped_events_count: parse_u32(object, "droppedEventsCount")?,
+        links: parse_array(object, "links", parse_link)?,
+        dropped_links_count: parse_u32(object, "droppedLinksCount")?,
+        status: parse_optional(object, "status", parse_status)?,
+    };
+    Ok(span)
+}
+
+fn parse_event(json_value: &JsonValue) -> Result<OtlpEvent, OtlpJsonError> {
+    let object = as_object(json_value, "events")?;
+    let event = OtlpEvent {
+        time_unix_nano: parse_u64(object, "timeUnixNano")?,
+        name: parse_string(object, "name")?,
+        attributes: parse_array(object, "attributes", parse_key_value)?,
+        dropped_attributes_count: parse_u32(object, "droppedAttributesCount")?,
+    };
+    Ok(event)
+}
+
+fn parse_link(json_value: &JsonValue) -> Result<OtlpLink, OtlpJsonError> {
+    let object = as_object(json_value, "links")?;
+    let link = OtlpLink {
+        trace_id: parse_hex_id(object, "traceId")?,
+        span_id: parse_hex_id(object, "spanId")?,
+        trace_state: parse_string(object, "traceState")?,
+        attributes: parse_array(object, "attributes", parse_key_value)?,
+        dropped_attributes_count: parse_u32(object, "droppedAttributesCount")?,
+    };
+    Ok(link)
+}
+
+fn parse_status(json_value: &JsonValue) -> Result<OtlpStatus, OtlpJsonError> {
+    let object = as_object(json_value, "status")?;
+    let status = OtlpStatus {
+        message: parse_string(object, "message")?,
+        code: parse_enum(object, "code", |name| {
+            OtlpStatusCode::from_str_name(name).map(|status_code| status_code as i32)
+        })?,
+    };
+    Ok(status)
+}
+
+fn parse_resource(json_value: &JsonValue) -> Result<OtlpResource, OtlpJsonError> {
+    let object = as_object(json_value, "resource")?;
+    let resource = OtlpResource {
+        attributes: parse_array(object, "attributes", parse_key_value)?,
+        dropped_attributes_count: parse_u32(object, "droppedAttributesCount")?,
+    };
+    Ok(resource)
+}
+
+fn parse_scope(json_value: &JsonValue) -> Result<InstrumentationScope, OtlpJsonError> {
+    let object = as_object(json_value, "scope")?;
+    let scope = InstrumentationScope {
+        name: parse_string(object, "name")?,
+        version: parse_string(object, "version")?,
+        attributes: parse_array(object, "attributes", parse_key_value)?,
+        dropped_attributes_count: parse_u32(object, "droppedAttributesCount")?,
+    };
+    Ok(scope)
+}
+
+fn parse_key_value(json_value: &JsonValue) -> Result<OtlpKeyValue, OtlpJsonError> {
+    let object = as_object(json_value, "attributes")?;
+    let key_value = OtlpKeyValue {
+        key: parse_string(object, "key")?,
+        value: parse_optional(object, "value", parse_any_value)?,
+    };
+    Ok(key_value)
+}
+
+fn parse_any_value(json_value: &JsonValue) -> Result<OtlpAnyValue, OtlpJsonError> {
+    let object = as_object(json_value, "value")?;
+
+    let value = if let Some(value) = get_field(object, "stringValue") {
+        let value = value
+            .as_str()
+            .ok_or_else(|| invalid_field("stringValue", "expected a string"))?;
+        Some(OtlpValue::StringValue(value.to_string()))
+    } else if let Some(value) = get_field(object, "boolValue") {
+        let value = value
+            .as_bool()
+            .ok_or_else(|| invalid_field("boolValue", "expected a boolean"))?;
+        Some(OtlpValue::BoolValue(value))
+    } else if let Some(value) = get_field(object, "intValue") {
+        Some(OtlpValue::IntValue(as_i64(value, "intValue")?))
+    } else if let Some(value) = get_field(object, "doubleValue") {
+        Some(OtlpValue::DoubleValue(as_f64(value, "doubleValue")?))
+    } else if let Some(value) = get_field(object, "arrayValue") {
+        let array_object = as_object(value, "arrayValue")?;
+        let values = parse_array(array_object, "values", parse_any_value)?;
+        Some(OtlpValue::ArrayValue(OtlpArrayValue { values }))
+    } else if let Some(value) = get_field(object, "kvlistValue") {
+        let kvlist_object = as_object(value, "kvlistValue")?;
+        let values = parse_array(kvlist_object, "values", parse_key_value)?;
+        Some(OtlpValue::KvlistValue(OtlpKeyValueList { values }))
+    } else if let Some(value) = get_field(object, "bytesValue") {
+        let b64_value = value
+            .as_str()
+            .ok_or_else(|| invalid_field("bytesValue", "expected a base64-encoded string"))?;
+        let value = BASE64_STANDARD
+            .decode(b64_value)
+            .map_err(|error| invalid_field("bytesValue", error))?;
+        Some(OtlpValue::BytesValue(value))
+    } else {
+        None
+    };
+    Ok(OtlpAnyValue { value })
+}
+
+/// Looks up a field by its lowerCamelCase name, falling back to its original snake_case name.
+fn get_field<'a>(object: &'a JsonObject, field: &'static str) -> Option<&'a JsonValue> {
+    let value_opt = match object.get(field) {
+        Some(value) => Some(value),
+        None => object.get(&camel_to_snake_case(field)),
+    };
+    value_opt.filter(|value| !value.is_null())
+}
+
+fn camel_to_snake_case(field: &str) -> String {
+    let mut snake_case = String::with_capacity(field.len() + 4);
+
+    for character in field.chars() {
+        if character.is_ascii_uppercase() {
+            snake_case.push('_');
+            snake_case.push(character.to_ascii_lowercase());
+        } else {
+            snake_case.push(character);
+        }
+    }
+    snake_case
+}
+
+fn as_object<'a>(
+    json_value: &'a JsonValue,
+    field: &'static str,
+) -> Result<&'a JsonObject, OtlpJsonError> {
+    json_value
+        .as_object()
+        .ok_or_else(|| invalid_field(field, "expected an object"))
+}
+
+fn parse_optional<T>(
+    object: &JsonObject,
+    field: &'static str,
+    parse_fn: fn(&JsonValue) -> Result<T, OtlpJsonError>,
+) -> Result<Option<T>, OtlpJsonError> {
+    get_field(object, field).map(parse_fn).transpose()
+}
+
+fn parse_array<T>(
+    object: &JsonObject,
+    field: &'static str,
+    parse_fn: fn(&JsonValue) -> Result<T, OtlpJsonError>,
+) -> Result<Vec<T>, OtlpJsonError> {
+    match get_field(object, field) {
+        Some(JsonValue::Array(values)) => values.iter().map(parse_fn).collect(),
+        Some(_) => Err(invalid_field(field, "expected an array")),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn parse_string(object: &JsonObject, field: &'static str) -> Result<String, OtlpJsonError> {
+    match get_field(object, field) {
+        Some(JsonValue::String(value)) => Ok(value.clone()),
+        Some(_) => Err(invalid_field(field, "expected a string")),
+        None => Ok(String::new()),
+    }
+}
+
+/// Parses a 64-bit integer, which the Protobuf JSON mapping encodes as a string but is also
+/// commonly sent as a number.
+fn parse_u64(object: &JsonObject, field: &'static str) -> Result<u64, OtlpJsonError> {
+    match get_field(object, field) {
+        Some(JsonValue::Number(number)) => number
+            .as_u64()
+            .ok_or_else(|| invalid_field(field, "expected an unsigned integer")),
+        Some(JsonValue::String(value)) => value
+            .parse()
+            .map_err(|_| invalid_field(field, "expected an unsigned integer")),
+        Some(_) => Err(invalid_field(field, "expected an unsigned integer")),
+        None => Ok(0),
+    }
+}
+
+fn parse_u32(object: &JsonObject, field: &'static str) -> Result<u32, OtlpJsonError> {
+    let value = parse_u64(object, field)?;
+    u32::try_from(value).map_err(|_| invalid_field(field, "value is out of range"))
+}
+
+/// Parses an enum value, encoded as an integer or, more rarely, as the name of the variant.
+fn parse_enum(
+    object: &JsonObject,
+    field: &'static str,
+    from_str_name: fn(&str) -> Option<i32>,
+) -> Result<i32, OtlpJsonError> {
+    match get_field(object, field) {
+        Some(JsonValue::Number(number)) => number
+            .as_i64()
+            .and_then(|value| i32::try_from(value).ok())
+            .ok_or_else(|| invalid_field(field, "expected an enum value")),
+        Some(JsonValue::String(name)) => {
+            from_str_name(name).ok_or_else(|| invalid_field(field, "unknown enum value"))
+        }
+        Some(_) => Err(invalid_field(field, "expected an enum value")),
+        None => Ok(0),
+    }
+}
+
+fn parse_hex_id(object: &JsonObject, field: &'static str) -> Result<Vec<u8>, OtlpJsonError> {
+    match get_field(object, field) {
+        Some(JsonValue::String(hex_id)) => {
+            decode_hex(hex_id).ok_or_else(|| invalid_field(field, "expected a hex-encoded ID"))
+        }
+        Some(_) => Err(invalid_field(field, "expected a hex-encoded ID")),
+        None => Ok(Vec::new()),
+    }
+}
+
+fn as_i64(json_value: &JsonValue, field: &'static str) -> Result<i64, OtlpJsonError> {
+    match json_value {
+        JsonValue::Number(number) => number
+            .as_i64()
+            .ok_or_else(|| invalid_field(field, "expected an integer")),
+        JsonValue::String(value) => value
+            .parse()
+            .map_err(|_| invalid_field(field, "expected an integer")),
+        _ => Err(invalid_field(field, "expected an integer")),
+    }
+}
+
+fn as_f64(json_value: &JsonValue, field: &'static str) -> Result<f64, OtlpJsonError> {
+    match json_value {
+        JsonValue::Number(number) => number
+            .as_f64()
+            .ok_or_else(|| invalid_field(field, "expected a number")),
+        // The Protobuf JSON mapping encodes the special values as strings.
+        JsonValue::String(value) => match value.as_str() {
+            "NaN" => Ok(f64::NAN),
+            "Infinity" => Ok(f64::INFINITY),
+            "-Infinity" => Ok(f64::NEG_INFINITY),
+            _ => value
+                .parse()
+                .map_err(|_| invalid_field(field, "expected a number")),
+        },
+        _ => Err(invalid_field(field, "expected a number")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_camel_to_snake_case() {
+        assert_eq!(camel_to_snake_case("name"), "name");
+        assert_eq!(
+            camel_to_snake_case("startTimeUnixNano"),
+            "start_time_unix_nano"
+        );
+    }
+
+    #[test]
+    fn test_decode_hex() {
+        assert_eq!(decode_hex("").unwrap(), Vec::<u8>::new());
+        assert_eq!(decode_hex("00ff10").unwrap(), vec![0, 255, 16]);
+        assert!(decode_hex("0").is_none());
+        assert!(decode_hex("zz").is_none());
+        assert!(decode_hex("é0").is_none());
+    }
+
+    #[test]
+    fn test_parse_otlp_json_logs_request() {
+        let payload = r#"{
+            "resourceLogs": [{
+                "resource": {
+                    "attributes": [{"key": "service.name", "value": {"stringValue": "quickwit"}}]
+                },
+                "scopeLogs": [{
+                    "scope": {"name": "my-scope", "version": "1.0"},
+                    "logRecords": [{
+                        "timeUnixNano": "1544712660300000000",
+                        "observedTimeUnixNano": 1544712660300000001,
+                        "severityNumber": 10,
+                        "severityText": "Information",
+                        "traceId": "5b8efff798038103d269b633813fc60c",
+                        "spanId": "eee19b7ec3c1b174",
+                        "body": {"stringValue": "Example log record"},
+                        "attributes": [
+                            {"key": "int", "value": {"intValue": "42"}},
+                            {"key": "double", "value": {"doubleValue": 12.5}},
+                            {"key": "bool", "value": {"boolValue": true}},
+                            {"key": "array", "value": {"arrayValue": {"values": [{"intValue": 1}]}}},
+                            {"key": "map", "value": {"kvlistValue": {"values": [{"key": "foo", "value": {"stringValue": "bar"}}]}}},
+                            {"key": "bytes", "value": {"bytesValue": "AQI="}}
+                        ]
+                    }]
+                }]
+            }]
+        }"#;
+        let request = parse_otlp_json_logs_request(payload.as_bytes()).unwrap();
+        assert_eq!(request.resource_logs.len(), 1);
+
+        let resource_logs = &request.resource_logs[0];
+        let resource = resource_logs.resource.as_ref().unwrap();
+        assert_eq!(resource.attributes[0].key, "service.name");
+
+        let scope_logs = &resource_logs.scope_logs[0];
+        assert_eq!(scope_logs.scope.as_ref().unwrap().name, "my-scope");
+
+        let log_record = &scope_logs.log_records[0];
+        assert_eq!(log_record.time_unix_nano, 1544712660300000000);
+        assert_eq!(log_record.observed_time_unix_nano, 1544712660300000001);
+        assert_eq!(log_record.severity_number, 10);
+        assert_eq!(log_record.severity_text, "Information");
+        assert_eq!(log_record.trace_id.len(), 16);
+        assert_eq!(
+            log_record.span_id,
+            vec![238, 225, 155, 126, 195, 193, 177, 116]
+        );
+        assert_eq!(
+            log_record.body.as_ref().unwrap().value,
+            Some(OtlpValue::StringValue("Example log record".to_string()))
+        );
+        let attribute_values: Vec<Option<OtlpValue>> = log_record
+            .attributes
+            .iter()
+            .map(|attribute| attribute.value.clone().unwrap().value)
+            .collect();
+        assert_eq!(
+            attribute_values,
+            vec![
+                Some(OtlpValue::IntValue(42)),
+                Some(OtlpValue::DoubleValue(12.5)),
+                Some(OtlpValue::BoolValue(true)),
+                Some(OtlpValue::ArrayValue(OtlpArrayValue {
+                    values: vec![OtlpAnyValue {
+                        value: Some(OtlpValue::IntValue(1))
+                    }]
+                })),
+                Some(OtlpValue::KvlistValue(OtlpKeyValueList {
+                    values: vec![OtlpKeyValue {
+                        key: "foo".to_string(),
+                        value: Some(OtlpAnyValue {
+                            value: Some(OtlpValue::StringValue("bar".to_string()))
+                        })
+                    }]
+                })),
+                Some(OtlpValue::BytesValue(vec![1, 2])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_otlp_json_trace_request() {
+        let payload = r#"{
+            "resourceSpans": [{
+                "scopeSpans": [{
+                    "spans": [{
+                        "traceId": "5b8efff798038103d269b633813fc60c",
+                        "spanId": "eee19b7ec3c1b174",
+                        "parent_span_id": "eee19b7ec3c1b173",
+                        "name": "I'm a server span",
+                        "kind": "SPAN_KIND_SERVER",
+                        "startTimeUnixNano": "1544712660000000000",
+                        "endTimeUnixNano": "1544712661000000000",
+                        "events": [{"timeUnixNano": "1544712660500000000", "name": "event"}],
+                        "links": [{"traceId": "5b8efff798038103d269b633813fc60c", "spanId": "eee19b7ec3c1b172"}],
+                        "status": {"code": 2, "message": "Oops"}
+                    }]
+                }]
+            }]
+        }"#;
+        let request = parse_otlp_json_trace_request(payload.as_bytes()).unwrap();
+        let resource_spans = &request.resource_spans[0];
+        assert!(resource_spans.resource.is_none());
+
+        let span = &resource_spans.scope_spans[0].spans[0];
+        assert_eq!(span.trace_id.len(), 16);
+        assert_eq!(span.span_id.len(), 8);
+        assert_eq!(span.parent_span_id.len(), 8);
+        assert_eq!(span.name, "I'm a server span");
+        assert_eq!(span.kind, OtlpSpanKind::Server as i32);
+        assert_eq!(span.start_time_unix_nano, 1544712660000000000);
+        assert_eq!(span.end_time_unix_nano, 1544712661000000000);
+        assert_eq!(span.events[0].name, "event");
+        assert_eq!(span.events[0].time_unix_nano, 1544712660500000000);
+        assert_eq!(span.links[0].span_id.len(), 8);
+
+        let status = span.status.as_ref().unwrap();
+        assert_eq!(status.code, OtlpStatusCode::Error as i32);
+        assert_eq!(status.message, "Oops");
+    }
+
+    #[test]
+    fn test_parse_otlp_json_request_errors() {
+        let error = parse_otlp_json_trace_request(b"not json").unwrap_err();
+        assert!(matches!(error, OtlpJsonError::Json(_)));
+
+        let error = parse_otlp_json_trace_request(br#"{"resourceSpans": {}}"#).unwrap_err();
+        assert!(matches!(
+            error,
+            OtlpJsonError::InvalidField {
+                field: "resourceSpans",
+                ..
+            }
+        ));
+
+        let error = parse_otlp_json_trace_request(
+            br#"{"resourceSpans": [{"scopeSpans": [{"spans": [{"traceId": "xyz"}]}]}]}"#,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            OtlpJsonError::InvalidField {
+                field: "traceId",
+                ..
+            }
+        ));
+    }
+}
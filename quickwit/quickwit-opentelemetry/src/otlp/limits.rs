@@ -0,0 +1,166 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::time::Duration;
+
+use prost::Message;
+use prost_types::{Any, Duration as WellKnownDuration};
+use tonic::{Code, Status};
+
+const RETRY_INFO_TYPE_URL: &str = "type.googleapis.com/google.rpc.RetryInfo";
+
+/// `google.rpc.Status` message carried by the `grpc-status-details-bin` trailer of a response.
+#[derive(Clone, PartialEq, Message)]
+struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    code: i32,
+    #[prost(string, tag = "2")]
+    message: String,
+    #[prost(message, repeated, tag = "3")]
+    details: Vec<Any>,
+}
+
+/// `google.rpc.RetryInfo` message telling the client when to retry a throttled request. OTLP
+/// exporters only retry the requests failing with `RESOURCE_EXHAUSTED` that carry this detail.
+#[derive(Clone, PartialEq, Message)]
+struct RetryInfo {
+    #[prost(message, optional, tag = "1")]
+    retry_delay: Option<WellKnownDuration>,
+}
+
+/// Rejects the requests whose payload exceeds the configured maximum size. These requests fail
+/// with `RESOURCE_EXHAUSTED` without retry information so that exporters drop them instead of
+/// retrying them forever.
+pub(crate) fn check_payload_size(payload_size: usize, max_payload_size: u64) -> Result<(), Status> {
+    if payload_size as u64 > max_payload_size {
+        let message = format!(
+            "Request payload of {payload_size} bytes exceeds the limit of {max_payload_size} \
+             bytes."
+        );
+        return Err(Status::resource_exhausted(message));
+    }
+    Ok(())
+}
+
+/// Rejects the requests containing more than `max_items` log records or spans. Like oversized
+/// payloads, these requests must not be retried.
+pub(crate) fn check_num_items(
+    num_items: usize,
+    max_items: usize,
+    items_label: &str,
+) -> Result<(), Status> {
+    if num_items > max_items {
+        let message = format!(
+            "Request contains {num_items} {items_label}, exceeding the limit of {max_items}."
+        );
+        return Err(Status::resource_exhausted(message));
+    }
+    Ok(())
+}
+
+/// Attaches a retry delay to the `RESOURCE_EXHAUSTED` statuses returned when the ingest queue is
+/// full or when a rate limit is exceeded, so that exporters back off and retry the request later.
+/// Other statuses are returned unchanged.
+pub fn with_retry_after(status: Status, retry_after: Duration) -> Status {
+    if status.code() != Code::ResourceExhausted {
+        return status;
+    }
+    let retry_info = RetryInfo {
+        retry_delay: Some(WellKnownDuration {
+            seconds: retry_after.as_secs() as i64,
+            nanos: retry_after.subsec_nanos() as i32,
+        }),
+    };
+    let rpc_status = RpcStatus {
+        code: Code::ResourceExhausted as i32,
+        message: status.message().to_string(),
+        details: vec![Any {
+            type_url: RETRY_INFO_TYPE_URL.to_string(),
+            value: retry_info.encode_to_vec(),
+        }],
+    };
+    Status::with_details(
+        Code::ResourceExhausted,
+        status.message(),
+        rpc_status.encode_to_vec().into(),
+    )
+}
+
+/// Returns the retry delay attached to `status` by [`with_retry_after`], if any.
+pub fn extract_retry_after(status: &Status) -> Option<Duration> {
+    if status.details().is_empty() {
+        return None;
+    }
+    let rpc_status = RpcStatus::decode(status.details()).ok()?;
+    let retry_info_any = rpc_status
+        .details
+        .into_iter()
+        .find(|detail| detail.type_url == RETRY_INFO_TYPE_URL)?;
+    let retry_delay = RetryInfo::decode(&retry_info_any.value[..])
+        .ok()?
+        .retry_delay?;
+    Some(Duration::new(
+        retry_delay.seconds.max(0) as u64,
+        retry_delay.nanos.max(0) as u32,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_payload_size() {
+        check_payload_size(1_000, 1_000).unwrap();
+
+        let status = check_payload_size(1_001, 1_000).unwrap_err();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert!(extract_retry_after(&status).is_none());
+    }
+
+    #[test]
+    fn test_check_num_items() {
+        check_num_items(10, 10, "spans").unwrap();
+
+        let status = check_num_items(11, 10, "spans").unwrap_err();
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(
+            status.message(),
+            "Request contains 11 spans, exceeding the limit of 10."
+        );
+    }
+
+    #[test]
+    fn test_with_retry_after() {
+        let status = with_retry_after(Status::unavailable("Oops."), Duration::from_secs(5));
+        assert_eq!(status.code(), Code::Unavailable);
+        assert!(extract_retry_after(&status).is_none());
+
+        let status = with_retry_after(
+            Status::resource_exhausted("Rate limited"),
+            Duration::from_millis(2_500),
+        );
+        assert_eq!(status.code(), Code::ResourceExhausted);
+        assert_eq!(status.message(), "Rate limited");
+        assert_eq!(
+            extract_retry_after(&status),
+            Some(Duration::from_millis(2_500))
+        );
+    }
+}
@@ -18,13 +18,13 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::cmp::{Ord, Ordering, PartialEq, PartialOrd};
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use async_trait::async_trait;
 use base64::prelude::{Engine, BASE64_STANDARD};
-use quickwit_ingest::{
-    CommitType, DocBatch, DocBatchBuilder, IngestRequest, IngestService, IngestServiceClient,
-};
+use prost::Message;
+use quickwit_config::{OtlpIdEncoding, OtlpIngestConfig, OtlpLimitsConfig};
+use quickwit_ingest::{DocBatch, DocBatchBuilder, IngestServiceClient};
 use quickwit_proto::opentelemetry::proto::collector::logs::v1::logs_service_server::LogsService;
 use quickwit_proto::opentelemetry::proto::collector::logs::v1::{
     ExportLogsPartialSuccess, ExportLogsServiceRequest, ExportLogsServiceResponse,
@@ -35,9 +35,12 @@ use tonic::{Request, Response, Status};
 use tracing::field::Empty;
 use tracing::{error, instrument, warn, Span as RuntimeSpan};
 
-use super::{parse_log_record_body, TraceId};
-use crate::otlp::extract_attributes;
+use super::index_routing::extract_header_index_id;
+use super::{parse_log_record_body, OtlpAttributePromoter, OtlpIndexRouter, OtlpProtocol, TraceId};
+use crate::otlp::ingester::OtlpIngester;
+use crate::otlp::limits::{check_num_items, check_payload_size, with_retry_after};
 use crate::otlp::metrics::OTLP_SERVICE_METRICS;
+use crate::otlp::{extract_attributes, ingest_otlp_doc};
 
 pub const OTEL_LOGS_INDEX_ID: &str = "otel-logs-v0";
 
@@ -68,8 +71,11 @@ doc_mapping:
     - name: severity_text
       type: text
       tokenizer: raw
+      indexed: true
+      fast: true
     - name: severity_number
       type: u64
+      fast: true
     - name: body
       type: json
     - name: attributes
@@ -115,7 +121,7 @@ indexing_settings:
   commit_timeout_secs: 5
 
 search_settings:
-  default_search_fields: []
+  default_search_fields: [body.message]
 "#;
 
 pub type Base64 = String;
@@ -140,6 +146,10 @@ pub struct LogRecord {
     pub scope_version: Option<String>,
     pub scope_attributes: HashMap<String, JsonValue>,
     pub scope_dropped_attributes_count: u32,
+    /// Log record or resource attributes copied into dedicated fields of the index, keyed by
+    /// field name.
+    #[serde(flatten)]
+    pub promoted_attributes: HashMap<String, JsonValue>,
 }
 
 /// A wrapper around `LogRecord` that implements `Ord` to allow insertion of log records into a
@@ -173,7 +183,7 @@ impl PartialEq for OrdLogRecord {
 impl Eq for OrdLogRecord {}
 
 struct ParsedLogRecords {
-    doc_batch: DocBatch,
+    doc_batches: Vec<DocBatch>,
     num_log_records: u64,
     num_parse_errors: u64,
     error_message: String,
@@ -181,28 +191,101 @@ struct ParsedLogRecords {
 
 #[derive(Clone)]
 pub struct OtlpGrpcLogsService {
-    ingest_service: IngestServiceClient,
+    ingester: OtlpIngester,
+    index_router: OtlpIndexRouter,
+    attribute_promoter: OtlpAttributePromoter,
+    id_encoding: OtlpIdEncoding,
+    limits: OtlpLimitsConfig,
 }
 
 impl OtlpGrpcLogsService {
     // TODO: remove and use registry
     pub fn new(ingest_service: IngestServiceClient) -> Self {
-        Self { ingest_service }
+        Self {
+            ingester: OtlpIngester::new(ingest_service),
+            index_router: OtlpIndexRouter::new(OTEL_LOGS_INDEX_ID, None),
+            attribute_promoter: OtlpAttributePromoter::default(),
+            id_encoding: OtlpIdEncoding::default(),
+            limits: OtlpLimitsConfig::default(),
+        }
+    }
+
+    /// Routes the log records to the indexes chosen by `index_router` instead of the
+    /// `otel-logs-v0` index.
+    pub fn with_index_router(mut self, index_router: OtlpIndexRouter) -> Self {
+        self.index_router = index_router;
+        self
+    }
+
+    /// Copies the attributes selected by `attribute_promoter` into dedicated fields of the log
+    /// records.
+    pub fn with_attribute_promoter(mut self, attribute_promoter: OtlpAttributePromoter) -> Self {
+        self.attribute_promoter = attribute_promoter;
+        self
+    }
+
+    /// Stores the trace and span IDs of the log records in `id_encoding` instead of Base64.
+    pub fn with_id_encoding(mut self, id_encoding: OtlpIdEncoding) -> Self {
+        self.id_encoding = id_encoding;
+        self
+    }
+
+    /// Rejects the requests exceeding `limits` instead of the default limits.
+    pub fn with_limits(mut self, limits: OtlpLimitsConfig) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Acknowledges the requests and batches the ingested documents according to
+    /// `ingest_config` instead of acknowledging them right after they are appended to the
+    /// write-ahead log. Must be called from within a Tokio runtime.
+    pub fn with_ingest_config(mut self, ingest_config: &OtlpIngestConfig) -> Self {
+        self.ingester = self.ingester.with_config(ingest_config);
+        self
+    }
+
+    /// Maximum size of the payload of a request, once decompressed.
+    pub fn max_payload_size(&self) -> u64 {
+        self.limits.max_payload_size.get_bytes() as u64
     }
 
     async fn export_inner(
         &mut self,
         request: ExportLogsServiceRequest,
-        labels: [&'static str; 4],
+        header_index_id_opt: Option<String>,
+        labels: [&str; 4],
     ) -> Result<ExportLogsServiceResponse, Status> {
+        let num_log_records_in_request = request
+            .resource_logs
+            .iter()
+            .flat_map(|resource_logs| &resource_logs.scope_logs)
+            .map(|scope_logs| scope_logs.log_records.len())
+            .sum();
+        check_num_items(
+            num_log_records_in_request,
+            self.limits.max_log_records_per_request.get(),
+            "log records",
+        )?;
         let ParsedLogRecords {
-            doc_batch,
+            doc_batches,
             num_log_records,
             num_parse_errors,
             error_message,
         } = tokio::task::spawn_blocking({
             let parent_span = RuntimeSpan::current();
-            || Self::parse_logs(request, parent_span)
+            let index_router = self.index_router.clone();
+            let attribute_promoter = self.attribute_promoter.clone();
+            let id_encoding = self.id_encoding;
+            move || {
+                Self::parse_logs(
+                    request,
+                    &index_router,
+                    &attribute_promoter,
+                    header_index_id_opt.as_deref(),
+                    id_encoding,
+                    parent_span,
+                )
+            }
         })
         .await
         .map_err(|join_error| {
@@ -212,8 +295,11 @@ impl OtlpGrpcLogsService {
         if num_log_records == num_parse_errors {
             return Err(tonic::Status::internal(error_message));
         }
-        let num_bytes = doc_batch.num_bytes() as u64;
-        self.store_logs(doc_batch).await?;
+        let num_bytes = doc_batches
+            .iter()
+            .map(|doc_batch| doc_batch.num_bytes() as u64)
+            .sum();
+        self.store_logs(doc_batches).await?;
 
         OTLP_SERVICE_METRICS
             .ingested_log_records_total
@@ -237,14 +323,27 @@ impl OtlpGrpcLogsService {
     #[instrument(skip_all, parent = parent_span, fields(num_spans = Empty, num_bytes = Empty, num_parse_errors = Empty))]
     fn parse_logs(
         request: ExportLogsServiceRequest,
+        index_router: &OtlpIndexRouter,
+        attribute_promoter: &OtlpAttributePromoter,
+        header_index_id_opt: Option<&str>,
+        id_encoding: OtlpIdEncoding,
         parent_span: RuntimeSpan,
     ) -> Result<ParsedLogRecords, Status> {
-        let mut log_records = BTreeSet::new();
+        let mut log_records_per_index: BTreeMap<String, BTreeSet<OrdLogRecord>> = BTreeMap::new();
         let mut num_log_records = 0;
         let mut num_parse_errors = 0;
         let mut error_message = String::new();
 
         for resource_log in request.resource_logs {
+            let index_id = index_router.route(
+                header_index_id_opt,
+                resource_log
+                    .resource
+                    .as_ref()
+                    .map(|rsrc| &rsrc.attributes[..])
+                    .unwrap_or_default(),
+            );
+            let log_records = log_records_per_index.entry(index_id).or_default();
             let mut resource_attributes = extract_attributes(
                 resource_log
                     .resource
@@ -315,6 +414,8 @@ impl OtlpGrpcLogsService {
                     let body = log_record.body.and_then(parse_log_record_body);
                     let attributes = extract_attributes(log_record.attributes);
                     let dropped_attributes_count = log_record.dropped_attributes_count;
+                    let promoted_attributes =
+                        attribute_promoter.promote(&attributes, &resource_attributes);
 
                     let log_record = LogRecord {
                         timestamp_secs,
@@ -335,27 +436,35 @@ impl OtlpGrpcLogsService {
                         scope_version: scope_version.cloned(),
                         scope_attributes: scope_attributes.clone(),
                         scope_dropped_attributes_count,
+                        promoted_attributes,
                     };
                     log_records.insert(OrdLogRecord(log_record));
                 }
             }
         }
-        let mut doc_batch = DocBatchBuilder::new(OTEL_LOGS_INDEX_ID.to_string()).json_writer();
-        for log_record in log_records {
-            if let Err(error) = doc_batch.ingest_doc(&log_record.0) {
-                error!(error=?error, "Failed to JSON serialize span.");
-                error_message = format!("Failed to JSON serialize span: {error:?}");
-                num_parse_errors += 1;
+        let mut doc_batches = Vec::with_capacity(log_records_per_index.len());
+        let mut num_bytes = 0;
+
+        for (index_id, log_records) in log_records_per_index {
+            let mut doc_batch = DocBatchBuilder::new(index_id).json_writer();
+            for log_record in log_records {
+                if let Err(error) = ingest_otlp_doc(&mut doc_batch, &log_record.0, id_encoding) {
+                    error!(error=?error, "Failed to JSON serialize span.");
+                    error_message = format!("Failed to JSON serialize span: {error:?}");
+                    num_parse_errors += 1;
+                }
             }
+            let doc_batch = doc_batch.build();
+            num_bytes += doc_batch.num_bytes();
+            doc_batches.push(doc_batch);
         }
-        let doc_batch = doc_batch.build();
         let current_span = RuntimeSpan::current();
         current_span.record("num_log_records", num_log_records);
-        current_span.record("num_bytes", doc_batch.num_bytes());
+        current_span.record("num_bytes", num_bytes);
         current_span.record("num_parse_errors", num_parse_errors);
 
         let parsed_spans = ParsedLogRecords {
-            doc_batch,
+            doc_batches,
             num_log_records,
             num_parse_errors,
             error_message,
@@ -363,29 +472,56 @@ impl OtlpGrpcLogsService {
         Ok(parsed_spans)
     }
 
-    #[instrument(skip_all, fields(num_bytes = doc_batch.num_bytes()))]
-    async fn store_logs(&mut self, doc_batch: DocBatch) -> Result<(), tonic::Status> {
-        let ingest_request = IngestRequest {
-            doc_batches: vec![doc_batch],
-            commit: CommitType::Auto as u32,
-        };
-        self.ingest_service.ingest(ingest_request).await?;
+    #[instrument(skip_all, fields(num_doc_batches = doc_batches.len()))]
+    async fn store_logs(&mut self, doc_batches: Vec<DocBatch>) -> Result<(), tonic::Status> {
+        let retry_after = self.limits.retry_after();
+        self.ingester
+            .ingest(doc_batches)
+            .await
+            .map_err(|error| with_retry_after(error.into(), retry_after))?;
         Ok(())
     }
 
+    /// Ingests the log records of an export request received over `protocol`. The gRPC service
+    /// delegates to this method, which is also used to serve the OTLP HTTP endpoints.
+    /// `header_index_id_opt` is the index set by the `x-qw-index` header of the request, if any.
+    pub async fn export_with_protocol(
+        &self,
+        request: ExportLogsServiceRequest,
+        protocol: OtlpProtocol,
+        header_index_id_opt: Option<String>,
+    ) -> Result<ExportLogsServiceResponse, Status> {
+        self.clone()
+            .export_instrumented(request, protocol, header_index_id_opt)
+            .await
+    }
+
     async fn export_instrumented(
         &mut self,
         request: ExportLogsServiceRequest,
+        protocol: OtlpProtocol,
+        header_index_id_opt: Option<String>,
     ) -> Result<ExportLogsServiceResponse, Status> {
         let start = std::time::Instant::now();
 
-        let labels = ["logs", OTEL_LOGS_INDEX_ID, "grpc", "protobuf"];
+        let index_label = header_index_id_opt
+            .clone()
+            .unwrap_or_else(|| self.index_router.default_index_id().to_string());
+        let labels = [
+            "logs",
+            &index_label,
+            protocol.transport(),
+            protocol.format(),
+        ];
 
         OTLP_SERVICE_METRICS
             .requests_total
             .with_label_values(labels)
             .inc();
-        let (export_res, is_error) = match self.export_inner(request, labels).await {
+        let (export_res, is_error) = match self
+            .export_inner(request, header_index_id_opt, labels)
+            .await
+        {
             ok @ Ok(_) => (ok, "false"),
             err @ Err(_) => {
                 OTLP_SERVICE_METRICS
@@ -396,7 +532,13 @@ impl OtlpGrpcLogsService {
             }
         };
         let elapsed = start.elapsed().as_secs_f64();
-        let labels = ["logs", OTEL_LOGS_INDEX_ID, "grpc", "protobuf", is_error];
+        let labels = [
+            "logs",
+            &index_label,
+            protocol.transport(),
+            protocol.format(),
+            is_error,
+        ];
         OTLP_SERVICE_METRICS
             .request_duration_seconds
             .with_label_values(labels)
@@ -413,9 +555,11 @@ impl LogsService for OtlpGrpcLogsService {
         &self,
         request: Request<ExportLogsServiceRequest>,
     ) -> Result<Response<ExportLogsServiceResponse>, Status> {
+        let header_index_id_opt = extract_header_index_id(&request);
         let request = request.into_inner();
+        check_payload_size(request.encoded_len(), self.max_payload_size())?;
         self.clone()
-            .export_instrumented(request)
+            .export_instrumented(request, OtlpProtocol::Grpc, header_index_id_opt)
             .await
             .map(Response::new)
     }
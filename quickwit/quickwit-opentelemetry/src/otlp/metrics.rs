@@ -18,7 +18,9 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use once_cell::sync::Lazy;
-use quickwit_common::metrics::{new_counter_vec, new_histogram_vec, HistogramVec, IntCounterVec};
+use quickwit_common::metrics::{
+    new_counter_vec, new_gauge, new_histogram_vec, HistogramVec, IntCounterVec, IntGauge,
+};
 
 pub struct OtlpServiceMetrics {
     pub requests_total: IntCounterVec<4>,
@@ -26,7 +28,10 @@ pub struct OtlpServiceMetrics {
     pub request_duration_seconds: HistogramVec<5>,
     pub ingested_log_records_total: IntCounterVec<4>,
     pub ingested_spans_total: IntCounterVec<4>,
+    pub ingested_metric_data_points_total: IntCounterVec<4>,
     pub ingested_bytes_total: IntCounterVec<4>,
+    pub tail_sampling_decisions_total: IntCounterVec<1>,
+    pub tail_sampling_buffered_traces: IntGauge,
 }
 
 impl Default for OtlpServiceMetrics {
@@ -62,12 +67,29 @@ impl Default for OtlpServiceMetrics {
                 "quickwit_otlp",
                 ["service", "index", "transport", "format"],
             ),
+            ingested_metric_data_points_total: new_counter_vec(
+                "ingested_metric_data_points_total",
+                "Number of metric data points ingested",
+                "quickwit_otlp",
+                ["service", "index", "transport", "format"],
+            ),
             ingested_bytes_total: new_counter_vec(
                 "ingested_bytes_total",
                 "Number of bytes ingested",
                 "quickwit_otlp",
                 ["service", "index", "transport", "format"],
             ),
+            tail_sampling_decisions_total: new_counter_vec(
+                "tail_sampling_decisions_total",
+                "Number of traces sampled or dropped by the tail sampler",
+                "quickwit_otlp",
+                ["decision"],
+            ),
+            tail_sampling_buffered_traces: new_gauge(
+                "tail_sampling_buffered_traces",
+                "Number of traces buffered by the tail sampler awaiting a decision",
+                "quickwit_otlp",
+            ),
         }
     }
 }
@@ -18,11 +18,14 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::collections::HashMap;
+use std::fmt::Write;
 use std::str::FromStr;
 
 use base64::display::Base64Display;
 use base64::engine::GeneralPurpose;
 use base64::prelude::{Engine, BASE64_STANDARD};
+use quickwit_config::OtlpIdEncoding;
+use quickwit_ingest::JsonDocBatchBuilder;
 use quickwit_proto::opentelemetry::proto::common::v1::any_value::Value as OtlpValue;
 use quickwit_proto::opentelemetry::proto::common::v1::{
     AnyValue as OtlpAnyValue, ArrayValue as OtlpArrayValue, KeyValue as OtlpKeyValue,
@@ -30,15 +33,65 @@ use quickwit_proto::opentelemetry::proto::common::v1::{
 use serde::{self, de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::{Number as JsonNumber, Value as JsonValue};
 
+mod attribute_promotion;
+mod index_routing;
+mod ingester;
+mod json;
+mod limits;
 mod logs;
 mod metrics;
+mod otel_metrics;
+mod span_metrics;
+mod tail_sampling;
 mod trace;
+mod zipkin;
 
+pub use attribute_promotion::OtlpAttributePromoter;
+pub use index_routing::{OtlpIndexRouter, OTLP_INDEX_HEADER};
+pub use json::{parse_otlp_json_logs_request, parse_otlp_json_trace_request, OtlpJsonError};
+pub use limits::{extract_retry_after, with_retry_after};
 pub use logs::{OtlpGrpcLogsService, OTEL_LOGS_INDEX_CONFIG, OTEL_LOGS_INDEX_ID};
+pub use otel_metrics::{
+    Exemplar, MetricDataPoint, MetricType, OtlpGrpcMetricsService, OTEL_METRICS_INDEX_CONFIG,
+    OTEL_METRICS_INDEX_ID,
+};
+pub use span_metrics::{SpanMetrics, SPAN_METRICS};
+pub use tail_sampling::{TailSampler, TailSamplerCounters};
 pub use trace::{
     Event, Link, OtlpGrpcTraceService, Span, SpanFingerprint, SpanKind, SpanStatus,
     OTEL_TRACE_INDEX_CONFIG, OTEL_TRACE_INDEX_ID,
 };
+pub use zipkin::{
+    parse_zipkin_json_spans, ZipkinAnnotation, ZipkinEndpoint, ZipkinSpan, ZipkinSpanKind,
+};
+
+/// Transport and encoding over which an OTLP export request was received.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum OtlpProtocol {
+    Grpc,
+    HttpProtobuf,
+    HttpJson,
+    HttpZipkinJson,
+}
+
+impl OtlpProtocol {
+    fn transport(&self) -> &'static str {
+        match self {
+            OtlpProtocol::Grpc => "grpc",
+            OtlpProtocol::HttpProtobuf | OtlpProtocol::HttpJson | OtlpProtocol::HttpZipkinJson => {
+                "http"
+            }
+        }
+    }
+
+    fn format(&self) -> &'static str {
+        match self {
+            OtlpProtocol::Grpc | OtlpProtocol::HttpProtobuf => "protobuf",
+            OtlpProtocol::HttpJson => "json",
+            OtlpProtocol::HttpZipkinJson => "zipkin_json",
+        }
+    }
+}
 
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
 pub struct TraceId([u8; 16]);
@@ -46,6 +99,8 @@ pub struct TraceId([u8; 16]);
 impl TraceId {
     pub const BASE64_LENGTH: usize = 24;
 
+    pub const HEX_LENGTH: usize = 32;
+
     pub fn new(bytes: [u8; 16]) -> Self {
         Self(bytes)
     }
@@ -61,6 +116,10 @@ impl TraceId {
     pub fn base64_display(&self) -> Base64Display<'_, '_, GeneralPurpose> {
         Base64Display::new(&self.0, &BASE64_STANDARD)
     }
+
+    pub fn to_hex(&self) -> String {
+        encode_hex(&self.0)
+    }
 }
 
 impl Serialize for TraceId {
@@ -85,6 +144,8 @@ pub enum TryFromTraceIdError {
     InvalidLength(usize),
     #[error("Invalid Base64 trace ID: {0}.")]
     InvalidBase64(#[from] base64::DecodeError),
+    #[error("Invalid hex trace ID.")]
+    InvalidHex,
 }
 
 impl TryFrom<&[u8]> for TraceId {
@@ -109,7 +170,13 @@ impl TryFrom<Vec<u8>> for TraceId {
 impl FromStr for TraceId {
     type Err = TryFromTraceIdError;
 
-    fn from_str(b64_trace_id: &str) -> Result<Self, Self::Err> {
+    fn from_str(b64_or_hex_trace_id: &str) -> Result<Self, Self::Err> {
+        if b64_or_hex_trace_id.len() == Self::HEX_LENGTH {
+            return decode_hex(b64_or_hex_trace_id)
+                .ok_or(TryFromTraceIdError::InvalidHex)
+                .and_then(Self::try_from);
+        }
+        let b64_trace_id = b64_or_hex_trace_id;
         if b64_trace_id.len() != Self::BASE64_LENGTH {
             return Err(TryFromTraceIdError::from(
                 base64::DecodeError::InvalidLength,
@@ -124,6 +191,70 @@ impl FromStr for TraceId {
     }
 }
 
+/// Decodes a trace or span ID encoded in Base64 or in hex. Trace and span IDs are respectively 16
+/// and 8 bytes long, so their Base64 encodings are always padded and cannot be mistaken for hex.
+pub fn decode_otlp_id(encoded_id: &str) -> Option<Vec<u8>> {
+    if encoded_id.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return decode_hex(encoded_id);
+    }
+    BASE64_STANDARD.decode(encoded_id).ok()
+}
+
+pub(crate) fn decode_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.is_ascii() || hex.len() % 2 != 0 {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|start| u8::from_str_radix(&hex[start..start + 2], 16).ok())
+        .collect()
+}
+
+pub(crate) fn encode_hex(bytes: &[u8]) -> String {
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        write!(hex, "{byte:02x}").expect("Writing to a string should not fail.");
+    }
+    hex
+}
+
+/// Adds a log record or span to the doc batch. Log records and spans hold their trace and span IDs
+/// in Base64, so the IDs are re-encoded before serialization when hex is requested.
+pub(crate) fn ingest_otlp_doc<T: Serialize>(
+    doc_batch_builder: &mut JsonDocBatchBuilder,
+    doc: &T,
+    id_encoding: OtlpIdEncoding,
+) -> serde_json::Result<usize> {
+    match id_encoding {
+        OtlpIdEncoding::Base64 => doc_batch_builder.ingest_doc(doc),
+        OtlpIdEncoding::Hex => {
+            let mut json_doc = serde_json::to_value(doc)?;
+            if let JsonValue::Object(json_obj) = &mut json_doc {
+                hex_encode_ids(json_obj, &["trace_id", "span_id", "parent_span_id"]);
+
+                if let Some(JsonValue::Array(links)) = json_obj.get_mut("links") {
+                    for link in links {
+                        if let JsonValue::Object(link_obj) = link {
+                            hex_encode_ids(link_obj, &["link_trace_id", "link_span_id"]);
+                        }
+                    }
+                }
+            }
+            doc_batch_builder.ingest_doc(json_doc)
+        }
+    }
+}
+
+fn hex_encode_ids(json_obj: &mut serde_json::Map<String, JsonValue>, id_fields: &[&str]) {
+    for id_field in id_fields {
+        if let Some(JsonValue::String(id)) = json_obj.get_mut(*id_field) {
+            if let Ok(decoded_id) = BASE64_STANDARD.decode(id.as_bytes()) {
+                *id = encode_hex(&decoded_id);
+            }
+        }
+    }
+}
+
 // An `Attribute` is a key-value pair, which MUST have the following properties:
 // - The attribute key MUST be a non-null and non-empty string.
 // - The attribute value is either:
@@ -195,6 +326,7 @@ pub(crate) fn parse_log_record_body(body: OtlpAnyValue) -> Option<JsonValue> {
 
 #[cfg(test)]
 mod tests {
+    use quickwit_ingest::{DocBatchBuilder, DocCommand};
     use quickwit_proto::opentelemetry::proto::common::v1::any_value::Value as OtlpAnyValueValue;
     use quickwit_proto::opentelemetry::proto::common::v1::ArrayValue as OtlpArrayValue;
     use serde_json::json;
@@ -232,6 +364,76 @@ mod tests {
             error,
             TryFromTraceIdError::InvalidBase64(base64::DecodeError::InvalidLength)
         ));
+
+        let trace_id: TraceId = "01010101010101010101010101010101".parse().unwrap();
+        assert_eq!(trace_id, expected_trace_id);
+
+        let trace_id: TraceId = "0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A0A".parse().unwrap();
+        assert_eq!(trace_id, TraceId([10; 16]));
+
+        let error = "0101010101010101010101010101010z"
+            .parse::<TraceId>()
+            .unwrap_err();
+        assert!(matches!(error, TryFromTraceIdError::InvalidHex));
+    }
+
+    #[test]
+    fn test_trace_id_to_hex() {
+        let trace_id = TraceId([10; 16]);
+        assert_eq!(trace_id.to_hex(), "0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a0a");
+        assert_eq!(trace_id.to_hex().parse::<TraceId>().unwrap(), trace_id);
+    }
+
+    #[test]
+    fn test_decode_otlp_id() {
+        assert_eq!(decode_otlp_id("AgICAgICAgI=").unwrap(), [2; 8]);
+        assert_eq!(decode_otlp_id("0202020202020202").unwrap(), [2; 8]);
+        assert_eq!(decode_otlp_id("AQEBAQEBAQEBAQEBAQEBAQ==").unwrap(), [1; 16]);
+        assert_eq!(
+            decode_otlp_id("01010101010101010101010101010101").unwrap(),
+            [1; 16]
+        );
+        assert!(decode_otlp_id("0202020202020").is_none());
+        assert!(decode_otlp_id("not an ID").is_none());
+    }
+
+    #[test]
+    fn test_ingest_otlp_doc() {
+        let doc = json!({
+            "trace_id": "AQEBAQEBAQEBAQEBAQEBAQ==",
+            "span_id": "AgICAgICAgI=",
+            "parent_span_id": null,
+            "span_name": "AgICAgICAgI=",
+            "links": [{
+                "link_trace_id": "BAQEBAQEBAQEBAQEBAQEBA==",
+                "link_span_id": "BQUFBQUFBQU=",
+            }],
+        });
+        let mut doc_batch_builder = DocBatchBuilder::new("test-index".to_string()).json_writer();
+        ingest_otlp_doc(&mut doc_batch_builder, &doc, OtlpIdEncoding::Base64).unwrap();
+        ingest_otlp_doc(&mut doc_batch_builder, &doc, OtlpIdEncoding::Hex).unwrap();
+        let doc_batch = doc_batch_builder.build();
+        let docs: Vec<JsonValue> = doc_batch
+            .iter()
+            .map(|doc_command| match doc_command {
+                DocCommand::Ingest { payload } => serde_json::from_slice(&payload).unwrap(),
+                _ => panic!("Expected an ingest command."),
+            })
+            .collect();
+        assert_eq!(docs[0], doc);
+        assert_eq!(
+            docs[1],
+            json!({
+                "trace_id": "01010101010101010101010101010101",
+                "span_id": "0202020202020202",
+                "parent_span_id": null,
+                "span_name": "AgICAgICAgI=",
+                "links": [{
+                    "link_trace_id": "04040404040404040404040404040404",
+                    "link_span_id": "0505050505050505",
+                }],
+            })
+        );
     }
 
     #[test]
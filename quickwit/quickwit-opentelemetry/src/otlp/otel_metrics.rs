@@ -0,0 +1,755 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+
+use async_trait::async_trait;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use quickwit_ingest::{
+    CommitType, DocBatch, DocBatchBuilder, IngestRequest, IngestService, IngestServiceClient,
+};
+use quickwit_proto::opentelemetry::proto::collector::metrics::v1::metrics_service_server::MetricsService;
+use quickwit_proto::opentelemetry::proto::collector::metrics::v1::{
+    ExportMetricsPartialSuccess, ExportMetricsServiceRequest, ExportMetricsServiceResponse,
+};
+use quickwit_proto::opentelemetry::proto::metrics::v1::metric::Data as OtlpMetricData;
+use quickwit_proto::opentelemetry::proto::metrics::v1::{
+    exemplar, number_data_point, AggregationTemporality, Exemplar as OtlpExemplar,
+    HistogramDataPoint, NumberDataPoint,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use tonic::{Request, Response, Status};
+use tracing::field::Empty;
+use tracing::{error, instrument, Span as RuntimeSpan};
+
+use super::TraceId;
+use crate::otlp::extract_attributes;
+use crate::otlp::metrics::OTLP_SERVICE_METRICS;
+
+pub const OTEL_METRICS_INDEX_ID: &str = "otel-metrics-v0";
+
+pub const OTEL_METRICS_INDEX_CONFIG: &str = r#"
+version: 0.6
+
+index_id: otel-metrics-v0
+
+doc_mapping:
+  mode: strict
+  field_mappings:
+    - name: timestamp_secs
+      type: datetime
+      input_formats: [unix_timestamp]
+      indexed: false
+      fast: true
+      precision: seconds
+      stored: false
+    - name: timestamp_nanos
+      type: u64
+      indexed: false
+    - name: start_timestamp_nanos
+      type: u64
+      indexed: false
+    - name: service_name
+      type: text
+      tokenizer: raw
+      fast: true
+    - name: metric_name
+      type: text
+      tokenizer: raw
+      fast: true
+    - name: metric_description
+      type: text
+      indexed: false
+    - name: metric_unit
+      type: text
+      tokenizer: raw
+    - name: metric_type
+      type: text
+      tokenizer: raw
+    - name: aggregation_temporality
+      type: text
+      tokenizer: raw
+    - name: is_monotonic
+      type: bool
+    - name: value
+      type: f64
+      fast: true
+    - name: count
+      type: u64
+      fast: true
+    - name: sum
+      type: f64
+      fast: true
+    - name: min
+      type: f64
+      fast: true
+    - name: max
+      type: f64
+      fast: true
+    - name: bucket_counts
+      type: array<u64>
+      indexed: false
+    - name: explicit_bounds
+      type: array<f64>
+      indexed: false
+    - name: exemplars
+      type: array<json>
+      tokenizer: raw
+    - name: attributes
+      type: json
+      tokenizer: raw
+    - name: flags
+      type: u64
+      indexed: false
+    - name: resource_attributes
+      type: json
+      tokenizer: raw
+    - name: resource_dropped_attributes_count
+      type: u64
+      indexed: false
+    - name: scope_name
+      type: text
+      indexed: false
+    - name: scope_version
+      type: text
+      indexed: false
+
+  timestamp_field: timestamp_secs
+
+indexing_settings:
+  commit_timeout_secs: 5
+
+search_settings:
+  default_search_fields: []
+"#;
+
+pub type Base64 = String;
+
+/// The types of metrics that can be ingested. Exponential histograms and summaries are not
+/// supported yet.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricType {
+    Gauge,
+    Sum,
+    Histogram,
+}
+
+/// A metric data point, as stored in the metrics index. Gauges and sums set `value`, histograms
+/// set `count`, `sum`, `min`, `max`, `bucket_counts`, and `explicit_bounds`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MetricDataPoint {
+    pub timestamp_secs: u64,
+    pub timestamp_nanos: u64,
+    pub start_timestamp_nanos: Option<u64>,
+    pub service_name: String,
+    pub metric_name: String,
+    pub metric_description: Option<String>,
+    pub metric_unit: Option<String>,
+    pub metric_type: MetricType,
+    pub aggregation_temporality: Option<String>,
+    pub is_monotonic: Option<bool>,
+    pub value: Option<f64>,
+    pub count: Option<u64>,
+    pub sum: Option<f64>,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub bucket_counts: Vec<u64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub explicit_bounds: Vec<f64>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exemplars: Vec<Exemplar>,
+    pub attributes: HashMap<String, JsonValue>,
+    pub flags: u32,
+    pub resource_attributes: HashMap<String, JsonValue>,
+    pub resource_dropped_attributes_count: u32,
+    pub scope_name: Option<String>,
+    pub scope_version: Option<String>,
+}
+
+/// A measurement recorded alongside a data point, linking the data point to a trace.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Exemplar {
+    pub timestamp_nanos: u64,
+    pub value: Option<f64>,
+    pub trace_id: Option<TraceId>,
+    pub span_id: Option<Base64>,
+    pub attributes: HashMap<String, JsonValue>,
+}
+
+impl Exemplar {
+    fn try_from_otlp(exemplar: OtlpExemplar) -> Result<Self, Status> {
+        let value = exemplar.value.map(|value| match value {
+            exemplar::Value::AsDouble(value) => value,
+            exemplar::Value::AsInt(value) => value as f64,
+        });
+        let trace_id = if exemplar.trace_id.iter().any(|&byte| byte != 0) {
+            let trace_id = TraceId::try_from(exemplar.trace_id)
+                .map_err(|error| Status::invalid_argument(error.to_string()))?;
+            Some(trace_id)
+        } else {
+            None
+        };
+        let span_id = if exemplar.span_id.iter().any(|&byte| byte != 0) {
+            Some(BASE64_STANDARD.encode(exemplar.span_id))
+        } else {
+            None
+        };
+        Ok(Exemplar {
+            timestamp_nanos: exemplar.time_unix_nano,
+            value,
+            trace_id,
+            span_id,
+            attributes: extract_attributes(exemplar.filtered_attributes),
+        })
+    }
+}
+
+/// Attributes shared by all the data points of a metric.
+struct MetricContext<'a> {
+    service_name: &'a str,
+    metric_name: &'a str,
+    metric_description: Option<&'a String>,
+    metric_unit: Option<&'a String>,
+    resource_attributes: &'a HashMap<String, JsonValue>,
+    resource_dropped_attributes_count: u32,
+    scope_name: Option<&'a String>,
+    scope_version: Option<&'a String>,
+}
+
+impl MetricContext<'_> {
+    fn new_data_point(
+        &self,
+        metric_type: MetricType,
+        time_unix_nano: u64,
+        start_time_unix_nano: u64,
+        attributes: HashMap<String, JsonValue>,
+        flags: u32,
+    ) -> MetricDataPoint {
+        MetricDataPoint {
+            timestamp_secs: time_unix_nano / 1_000_000_000,
+            timestamp_nanos: time_unix_nano,
+            start_timestamp_nanos: Some(start_time_unix_nano).filter(|nanos| *nanos != 0),
+            service_name: self.service_name.to_string(),
+            metric_name: self.metric_name.to_string(),
+            metric_description: self.metric_description.cloned(),
+            metric_unit: self.metric_unit.cloned(),
+            metric_type,
+            aggregation_temporality: None,
+            is_monotonic: None,
+            value: None,
+            count: None,
+            sum: None,
+            min: None,
+            max: None,
+            bucket_counts: Vec::new(),
+            explicit_bounds: Vec::new(),
+            exemplars: Vec::new(),
+            attributes,
+            flags,
+            resource_attributes: self.resource_attributes.clone(),
+            resource_dropped_attributes_count: self.resource_dropped_attributes_count,
+            scope_name: self.scope_name.cloned(),
+            scope_version: self.scope_version.cloned(),
+        }
+    }
+
+    fn number_data_point(
+        &self,
+        metric_type: MetricType,
+        data_point: NumberDataPoint,
+    ) -> Result<MetricDataPoint, Status> {
+        let mut metric_data_point = self.new_data_point(
+            metric_type,
+            data_point.time_unix_nano,
+            data_point.start_time_unix_nano,
+            extract_attributes(data_point.attributes),
+            data_point.flags,
+        );
+        metric_data_point.value = data_point.value.map(|value| match value {
+            number_data_point::Value::AsDouble(value) => value,
+            number_data_point::Value::AsInt(value) => value as f64,
+        });
+        metric_data_point.exemplars = data_point
+            .exemplars
+            .into_iter()
+            .map(Exemplar::try_from_otlp)
+            .collect::<Result<_, _>>()?;
+        Ok(metric_data_point)
+    }
+
+    fn histogram_data_point(
+        &self,
+        data_point: HistogramDataPoint,
+    ) -> Result<MetricDataPoint, Status> {
+        let mut metric_data_point = self.new_data_point(
+            MetricType::Histogram,
+            data_point.time_unix_nano,
+            data_point.start_time_unix_nano,
+            extract_attributes(data_point.attributes),
+            data_point.flags,
+        );
+        metric_data_point.count = Some(data_point.count);
+        metric_data_point.sum = data_point.sum;
+        metric_data_point.min = data_point.min;
+        metric_data_point.max = data_point.max;
+        metric_data_point.bucket_counts = data_point.bucket_counts;
+        metric_data_point.explicit_bounds = data_point.explicit_bounds;
+        metric_data_point.exemplars = data_point
+            .exemplars
+            .into_iter()
+            .map(Exemplar::try_from_otlp)
+            .collect::<Result<_, _>>()?;
+        Ok(metric_data_point)
+    }
+}
+
+fn aggregation_temporality_name(aggregation_temporality: i32) -> Option<String> {
+    match AggregationTemporality::from_i32(aggregation_temporality) {
+        Some(AggregationTemporality::Delta) => Some("delta".to_string()),
+        Some(AggregationTemporality::Cumulative) => Some("cumulative".to_string()),
+        Some(AggregationTemporality::Unspecified) | None => None,
+    }
+}
+
+struct ParsedMetrics {
+    doc_batch: DocBatch,
+    num_data_points: u64,
+    num_parse_errors: u64,
+    error_message: String,
+}
+
+#[derive(Clone)]
+pub struct OtlpGrpcMetricsService {
+    ingest_service: IngestServiceClient,
+}
+
+impl OtlpGrpcMetricsService {
+    // TODO: remove and use registry
+    pub fn new(ingest_service: IngestServiceClient) -> Self {
+        Self { ingest_service }
+    }
+
+    async fn export_inner(
+        &mut self,
+        request: ExportMetricsServiceRequest,
+        labels: [&'static str; 4],
+    ) -> Result<ExportMetricsServiceResponse, Status> {
+        let ParsedMetrics {
+            doc_batch,
+            num_data_points,
+            num_parse_errors,
+            error_message,
+        } = tokio::task::spawn_blocking({
+            let parent_span = RuntimeSpan::current();
+            || Self::parse_metrics(request, parent_span)
+        })
+        .await
+        .map_err(|join_error| {
+            error!("Failed to parse metrics: {join_error:?}");
+            Status::internal("Failed to parse metrics.")
+        })??;
+        if num_data_points == num_parse_errors {
+            return Err(tonic::Status::internal(error_message));
+        }
+        let num_bytes = doc_batch.num_bytes() as u64;
+        self.store_metrics(doc_batch).await?;
+
+        OTLP_SERVICE_METRICS
+            .ingested_metric_data_points_total
+            .with_label_values(labels)
+            .inc_by(num_data_points - num_parse_errors);
+        OTLP_SERVICE_METRICS
+            .ingested_bytes_total
+            .with_label_values(labels)
+            .inc_by(num_bytes);
+
+        let response = ExportMetricsServiceResponse {
+            // `rejected_data_points=0` and `error_message=""` is consided a "full" success.
+            partial_success: Some(ExportMetricsPartialSuccess {
+                rejected_data_points: num_parse_errors as i64,
+                error_message,
+            }),
+        };
+        Ok(response)
+    }
+
+    #[instrument(skip_all, parent = parent_span, fields(num_data_points = Empty, num_bytes = Empty, num_parse_errors = Empty))]
+    fn parse_metrics(
+        request: ExportMetricsServiceRequest,
+        parent_span: RuntimeSpan,
+    ) -> Result<ParsedMetrics, Status> {
+        let mut metric_data_points = Vec::new();
+        let mut num_data_points = 0;
+        let mut num_parse_errors = 0;
+        let mut error_message = String::new();
+
+        for resource_metrics in request.resource_metrics {
+            let mut resource_attributes = extract_attributes(
+                resource_metrics
+                    .resource
+                    .clone()
+                    .map(|rsrc| rsrc.attributes)
+                    .unwrap_or_else(Vec::new),
+            );
+            let resource_dropped_attributes_count = resource_metrics
+                .resource
+                .map(|rsrc| rsrc.dropped_attributes_count)
+                .unwrap_or(0);
+
+            let service_name = match resource_attributes.remove("service.name") {
+                Some(JsonValue::String(value)) => value.to_string(),
+                _ => "unknown_service".to_string(),
+            };
+            for scope_metrics in resource_metrics.scope_metrics {
+                let scope_name = scope_metrics
+                    .scope
+                    .as_ref()
+                    .map(|scope| &scope.name)
+                    .filter(|name| !name.is_empty());
+                let scope_version = scope_metrics
+                    .scope
+                    .as_ref()
+                    .map(|scope| &scope.version)
+                    .filter(|version| !version.is_empty());
+
+                for metric in scope_metrics.metrics {
+                    let metric_context = MetricContext {
+                        service_name: &service_name,
+                        metric_name: &metric.name,
+                        metric_description: Some(&metric.description)
+                            .filter(|description| !description.is_empty()),
+                        metric_unit: Some(&metric.unit).filter(|unit| !unit.is_empty()),
+                        resource_attributes: &resource_attributes,
+                        resource_dropped_attributes_count,
+                        scope_name,
+                        scope_version,
+                    };
+                    match metric.data {
+                        Some(OtlpMetricData::Gauge(gauge)) => {
+                            for data_point in gauge.data_points {
+                                num_data_points += 1;
+                                metric_data_points.push(
+                                    metric_context
+                                        .number_data_point(MetricType::Gauge, data_point)?,
+                                );
+                            }
+                        }
+                        Some(OtlpMetricData::Sum(sum)) => {
+                            let aggregation_temporality =
+                                aggregation_temporality_name(sum.aggregation_temporality);
+                            for data_point in sum.data_points {
+                                num_data_points += 1;
+                                let mut metric_data_point = metric_context
+                                    .number_data_point(MetricType::Sum, data_point)?;
+                                metric_data_point.aggregation_temporality =
+                                    aggregation_temporality.clone();
+                                metric_data_point.is_monotonic = Some(sum.is_monotonic);
+                                metric_data_points.push(metric_data_point);
+                            }
+                        }
+                        Some(OtlpMetricData::Histogram(histogram)) => {
+                            let aggregation_temporality =
+                                aggregation_temporality_name(histogram.aggregation_temporality);
+                            for data_point in histogram.data_points {
+                                num_data_points += 1;
+                                let mut metric_data_point =
+                                    metric_context.histogram_data_point(data_point)?;
+                                metric_data_point.aggregation_temporality =
+                                    aggregation_temporality.clone();
+                                metric_data_points.push(metric_data_point);
+                            }
+                        }
+                        Some(OtlpMetricData::ExponentialHistogram(exponential_histogram)) => {
+                            let num_rejected = exponential_histogram.data_points.len() as u64;
+                            num_data_points += num_rejected;
+                            num_parse_errors += num_rejected;
+                            error_message = format!(
+                                "Exponential histograms are not supported (metric `{}`).",
+                                metric.name
+                            );
+                        }
+                        Some(OtlpMetricData::Summary(summary)) => {
+                            let num_rejected = summary.data_points.len() as u64;
+                            num_data_points += num_rejected;
+                            num_parse_errors += num_rejected;
+                            error_message =
+                                format!("Summaries are not supported (metric `{}`).", metric.name);
+                        }
+                        None => {}
+                    }
+                }
+            }
+        }
+        // Sorting the data points by service and metric improves the compression and the pruning
+        // of the splits.
+        metric_data_points.sort_by(|left, right| {
+            left.service_name
+                .cmp(&right.service_name)
+                .then_with(|| left.metric_name.cmp(&right.metric_name))
+                .then(left.timestamp_nanos.cmp(&right.timestamp_nanos))
+        });
+        let mut doc_batch = DocBatchBuilder::new(OTEL_METRICS_INDEX_ID.to_string()).json_writer();
+        for metric_data_point in metric_data_points {
+            if let Err(error) = doc_batch.ingest_doc(&metric_data_point) {
+                error!(error=?error, "Failed to JSON serialize metric data point.");
+                error_message = format!("Failed to JSON serialize metric data point: {error:?}");
+                num_parse_errors += 1;
+            }
+        }
+        let doc_batch = doc_batch.build();
+        let current_span = RuntimeSpan::current();
+        current_span.record("num_data_points", num_data_points);
+        current_span.record("num_bytes", doc_batch.num_bytes());
+        current_span.record("num_parse_errors", num_parse_errors);
+
+        let parsed_metrics = ParsedMetrics {
+            doc_batch,
+            num_data_points,
+            num_parse_errors,
+            error_message,
+        };
+        Ok(parsed_metrics)
+    }
+
+    #[instrument(skip_all, fields(num_bytes = doc_batch.num_bytes()))]
+    async fn store_metrics(&mut self, doc_batch: DocBatch) -> Result<(), tonic::Status> {
+        let ingest_request = IngestRequest {
+            doc_batches: vec![doc_batch],
+            commit: CommitType::Auto as u32,
+        };
+        self.ingest_service.ingest(ingest_request).await?;
+        Ok(())
+    }
+
+    async fn export_instrumented(
+        &mut self,
+        request: ExportMetricsServiceRequest,
+    ) -> Result<ExportMetricsServiceResponse, Status> {
+        let start = std::time::Instant::now();
+
+        let labels = ["metrics", OTEL_METRICS_INDEX_ID, "grpc", "protobuf"];
+
+        OTLP_SERVICE_METRICS
+            .requests_total
+            .with_label_values(labels)
+            .inc();
+        let (export_res, is_error) = match self.export_inner(request, labels).await {
+            ok @ Ok(_) => (ok, "false"),
+            err @ Err(_) => {
+                OTLP_SERVICE_METRICS
+                    .request_errors_total
+                    .with_label_values(labels)
+                    .inc();
+                (err, "true")
+            }
+        };
+        let elapsed = start.elapsed().as_secs_f64();
+        let labels = [
+            "metrics",
+            OTEL_METRICS_INDEX_ID,
+            "grpc",
+            "protobuf",
+            is_error,
+        ];
+        OTLP_SERVICE_METRICS
+            .request_duration_seconds
+            .with_label_values(labels)
+            .observe(elapsed);
+
+        export_res
+    }
+}
+
+#[async_trait]
+impl MetricsService for OtlpGrpcMetricsService {
+    #[instrument(name = "ingest_metrics", skip_all)]
+    async fn export(
+        &self,
+        request: Request<ExportMetricsServiceRequest>,
+    ) -> Result<Response<ExportMetricsServiceResponse>, Status> {
+        let request = request.into_inner();
+        self.clone()
+            .export_instrumented(request)
+            .await
+            .map(Response::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_ingest::DocCommand;
+    use quickwit_proto::opentelemetry::proto::common::v1::any_value::Value as OtlpAnyValueValue;
+    use quickwit_proto::opentelemetry::proto::common::v1::{
+        AnyValue as OtlpAnyValue, KeyValue as OtlpKeyValue,
+    };
+    use quickwit_proto::opentelemetry::proto::metrics::v1::{
+        Gauge, Histogram, Metric, ResourceMetrics, ScopeMetrics, Sum, Summary, SummaryDataPoint,
+    };
+    use quickwit_proto::opentelemetry::proto::resource::v1::Resource as OtlpResource;
+    use serde_json::json;
+
+    use super::*;
+
+    fn string_key_value(key: &str, value: &str) -> OtlpKeyValue {
+        OtlpKeyValue {
+            key: key.to_string(),
+            value: Some(OtlpAnyValue {
+                value: Some(OtlpAnyValueValue::StringValue(value.to_string())),
+            }),
+        }
+    }
+
+    fn parse_docs(doc_batch: &DocBatch) -> Vec<JsonValue> {
+        doc_batch
+            .iter()
+            .map(|doc_command| match doc_command {
+                DocCommand::Ingest { payload } => serde_json::from_slice(&payload).unwrap(),
+                DocCommand::Commit => panic!("Expected an ingest command."),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_metrics() {
+        let metrics = vec![
+            Metric {
+                name: "memory_usage".to_string(),
+                description: "Memory usage".to_string(),
+                unit: "By".to_string(),
+                data: Some(OtlpMetricData::Gauge(Gauge {
+                    data_points: vec![NumberDataPoint {
+                        attributes: vec![string_key_value("host", "host-1")],
+                        time_unix_nano: 2_000_000_000,
+                        value: Some(number_data_point::Value::AsInt(1024)),
+                        ..Default::default()
+                    }],
+                })),
+            },
+            Metric {
+                name: "num_requests".to_string(),
+                data: Some(OtlpMetricData::Sum(Sum {
+                    data_points: vec![NumberDataPoint {
+                        start_time_unix_nano: 1_000_000_000,
+                        time_unix_nano: 2_000_000_000,
+                        value: Some(number_data_point::Value::AsDouble(42.0)),
+                        ..Default::default()
+                    }],
+                    aggregation_temporality: AggregationTemporality::Cumulative as i32,
+                    is_monotonic: true,
+                })),
+                ..Default::default()
+            },
+            Metric {
+                name: "request_duration".to_string(),
+                data: Some(OtlpMetricData::Histogram(Histogram {
+                    data_points: vec![HistogramDataPoint {
+                        time_unix_nano: 2_000_000_000,
+                        count: 3,
+                        sum: Some(6.0),
+                        min: Some(1.0),
+                        max: Some(3.0),
+                        bucket_counts: vec![1, 2],
+                        explicit_bounds: vec![2.0],
+                        exemplars: vec![OtlpExemplar {
+                            time_unix_nano: 1_500_000_000,
+                            trace_id: vec![1; 16],
+                            span_id: vec![2; 8],
+                            value: Some(exemplar::Value::AsDouble(3.0)),
+                            ..Default::default()
+                        }],
+                        ..Default::default()
+                    }],
+                    aggregation_temporality: AggregationTemporality::Delta as i32,
+                })),
+                ..Default::default()
+            },
+            Metric {
+                name: "request_duration_quantiles".to_string(),
+                data: Some(OtlpMetricData::Summary(Summary {
+                    data_points: vec![SummaryDataPoint::default()],
+                })),
+                ..Default::default()
+            },
+        ];
+        let request = ExportMetricsServiceRequest {
+            resource_metrics: vec![ResourceMetrics {
+                resource: Some(OtlpResource {
+                    attributes: vec![string_key_value("service.name", "quickwit")],
+                    dropped_attributes_count: 0,
+                }),
+                scope_metrics: vec![ScopeMetrics {
+                    scope: None,
+                    metrics,
+                    schema_url: String::new(),
+                }],
+                schema_url: String::new(),
+            }],
+        };
+        let ParsedMetrics {
+            doc_batch,
+            num_data_points,
+            num_parse_errors,
+            error_message,
+        } = OtlpGrpcMetricsService::parse_metrics(request, RuntimeSpan::current()).unwrap();
+        assert_eq!(num_data_points, 4);
+        assert_eq!(num_parse_errors, 1);
+        assert_eq!(
+            error_message,
+            "Summaries are not supported (metric `request_duration_quantiles`)."
+        );
+        let docs = parse_docs(&doc_batch);
+        assert_eq!(docs.len(), 3);
+
+        assert_eq!(docs[0]["service_name"], json!("quickwit"));
+        assert_eq!(docs[0]["metric_name"], json!("memory_usage"));
+        assert_eq!(docs[0]["metric_type"], json!("gauge"));
+        assert_eq!(docs[0]["metric_unit"], json!("By"));
+        assert_eq!(docs[0]["timestamp_secs"], json!(2));
+        assert_eq!(docs[0]["value"], json!(1024.0));
+        assert_eq!(docs[0]["attributes"], json!({"host": "host-1"}));
+
+        assert_eq!(docs[1]["metric_name"], json!("num_requests"));
+        assert_eq!(docs[1]["metric_type"], json!("sum"));
+        assert_eq!(docs[1]["start_timestamp_nanos"], json!(1_000_000_000));
+        assert_eq!(docs[1]["aggregation_temporality"], json!("cumulative"));
+        assert_eq!(docs[1]["is_monotonic"], json!(true));
+        assert_eq!(docs[1]["value"], json!(42.0));
+
+        assert_eq!(docs[2]["metric_name"], json!("request_duration"));
+        assert_eq!(docs[2]["metric_type"], json!("histogram"));
+        assert_eq!(docs[2]["aggregation_temporality"], json!("delta"));
+        assert_eq!(docs[2]["count"], json!(3));
+        assert_eq!(docs[2]["sum"], json!(6.0));
+        assert_eq!(docs[2]["bucket_counts"], json!([1, 2]));
+        assert_eq!(docs[2]["explicit_bounds"], json!([2.0]));
+        assert_eq!(
+            docs[2]["exemplars"],
+            json!([{
+                "timestamp_nanos": 1_500_000_000,
+                "value": 3.0,
+                "trace_id": TraceId::new([1; 16]),
+                "span_id": BASE64_STANDARD.encode([2u8; 8]),
+                "attributes": {},
+            }])
+        );
+    }
+}
@@ -0,0 +1,151 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use once_cell::sync::Lazy;
+use quickwit_common::metrics::{new_counter_vec, new_histogram_vec, HistogramVec, IntCounterVec};
+use quickwit_proto::opentelemetry::proto::trace::v1::status::StatusCode as OtlpStatusCode;
+
+use super::Span;
+
+/// Request, error, and duration (RED) metrics of the services emitting the spans ingested via
+/// OTLP. The number of errors is given by the calls with the `error` status code.
+pub struct SpanMetrics {
+    pub calls_total: IntCounterVec<3>,
+    pub duration_seconds: HistogramVec<2>,
+}
+
+impl Default for SpanMetrics {
+    fn default() -> Self {
+        Self {
+            calls_total: new_counter_vec(
+                "calls_total",
+                "Number of spans emitted by a service",
+                "quickwit_otlp_span_metrics",
+                ["service_name", "span_kind", "status_code"],
+            ),
+            duration_seconds: new_histogram_vec(
+                "duration_seconds",
+                "Duration of the spans emitted by a service",
+                "quickwit_otlp_span_metrics",
+                ["service_name", "span_kind"],
+            ),
+        }
+    }
+}
+
+/// `SPAN_METRICS` exposes the RED metrics derived from the ingested spans.
+pub static SPAN_METRICS: Lazy<SpanMetrics> = Lazy::new(SpanMetrics::default);
+
+/// Accounts for `span` in the RED metrics of its service.
+pub(crate) fn record_span_metrics(span: &Span) {
+    let span_kind = match span.span_kind {
+        1 => "internal",
+        2 => "server",
+        3 => "client",
+        4 => "producer",
+        5 => "consumer",
+        _ => "unspecified",
+    };
+    let status_code = match span
+        .span_status
+        .as_ref()
+        .map(|span_status| span_status.code)
+    {
+        Some(code) if code == OtlpStatusCode::Ok as i32 => "ok",
+        Some(code) if code == OtlpStatusCode::Error as i32 => "error",
+        _ => "unset",
+    };
+    SPAN_METRICS
+        .calls_total
+        .with_label_values([&span.service_name, span_kind, status_code])
+        .inc();
+
+    let duration_nanos = span
+        .span_end_timestamp_nanos
+        .saturating_sub(span.span_start_timestamp_nanos);
+    SPAN_METRICS
+        .duration_seconds
+        .with_label_values([&span.service_name, span_kind])
+        .observe(duration_nanos as f64 / 1_000_000_000.0);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::otlp::{SpanStatus, TraceId};
+
+    #[test]
+    fn test_record_span_metrics() {
+        let mut span = Span {
+            trace_id: TraceId::new([1; 16]),
+            trace_state: None,
+            service_name: "span-metrics-service".to_string(),
+            resource_attributes: HashMap::new(),
+            resource_dropped_attributes_count: 0,
+            scope_name: None,
+            scope_version: None,
+            scope_attributes: HashMap::new(),
+            scope_dropped_attributes_count: 0,
+            span_id: "AQEBAQEBAQE=".to_string(),
+            span_kind: 2,
+            span_name: "leaf_search".to_string(),
+            span_fingerprint: None,
+            span_start_timestamp_nanos: 1_000_000_000,
+            span_end_timestamp_nanos: 1_250_000_000,
+            span_start_timestamp_secs: Some(1),
+            span_duration_millis: Some(250),
+            span_attributes: HashMap::new(),
+            span_dropped_attributes_count: 0,
+            span_dropped_events_count: 0,
+            span_dropped_links_count: 0,
+            span_status: None,
+            parent_span_id: None,
+            events: Vec::new(),
+            event_names: Vec::new(),
+            links: Vec::new(),
+            promoted_attributes: HashMap::new(),
+        };
+        record_span_metrics(&span);
+
+        span.span_status = Some(SpanStatus {
+            code: OtlpStatusCode::Error as i32,
+            message: None,
+        });
+        record_span_metrics(&span);
+        record_span_metrics(&span);
+
+        let calls_total = |status_code: &str| {
+            SPAN_METRICS
+                .calls_total
+                .with_label_values(["span-metrics-service", "server", status_code])
+                .get()
+        };
+        assert_eq!(calls_total("unset"), 1);
+        assert_eq!(calls_total("error"), 2);
+        assert_eq!(calls_total("ok"), 0);
+
+        let duration_seconds = SPAN_METRICS
+            .duration_seconds
+            .with_label_values(["span-metrics-service", "server"]);
+        assert_eq!(duration_seconds.get_sample_count(), 3);
+        assert_eq!(duration_seconds.get_sample_sum(), 0.75);
+    }
+}
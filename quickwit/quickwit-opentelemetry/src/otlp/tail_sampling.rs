@@ -0,0 +1,535 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use quickwit_actors::{Actor, ActorContext, ActorExitStatus, Handler};
+use quickwit_config::{OtlpIdEncoding, OtlpTailSamplingConfig, OtlpTailSamplingPolicy};
+use quickwit_ingest::{CommitType, IngestRequest, IngestService, IngestServiceClient};
+use serde::Serialize;
+use tracing::error;
+
+use crate::otlp::metrics::OTLP_SERVICE_METRICS;
+use crate::otlp::trace::build_span_doc_batches;
+use crate::otlp::{Span, TraceId};
+
+/// Interval at which the tail sampler decides the traces whose decision wait has elapsed.
+const DECISION_TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// OTLP status code of the spans that ended with an error.
+const SPAN_STATUS_CODE_ERROR: i32 = 2;
+
+/// Spans of an export request, grouped by index, awaiting a sampling decision.
+#[derive(Debug)]
+pub(crate) struct SampleSpans {
+    pub spans_per_index: BTreeMap<String, Vec<Span>>,
+}
+
+#[derive(Debug)]
+struct DecisionTick;
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct TailSamplerCounters {
+    /// Number of traces awaiting a sampling decision.
+    pub num_buffered_traces: usize,
+    /// Number of traces matching at least one sampling policy.
+    pub num_sampled_traces: u64,
+    /// Number of traces matching none of the sampling policies.
+    pub num_dropped_traces: u64,
+    /// Number of traces decided before the end of their decision wait because the buffer was
+    /// full.
+    pub num_early_decisions: u64,
+    /// Number of spans of the sampled traces.
+    pub num_sampled_spans: u64,
+    /// Number of sampled spans that could not be ingested.
+    pub num_failed_spans: u64,
+}
+
+struct PendingTrace {
+    arrival_tick: u64,
+    spans: Vec<Span>,
+}
+
+type TraceKey = (String, TraceId);
+
+/// Buffers the spans received by the OTLP trace service per trace for a configurable decision
+/// wait, then only ingests the traces matching at least one of the sampling policies.
+///
+/// The decision is made by the node receiving the spans, so all the spans of a trace must be
+/// sent to the same node for the error and latency policies to see the complete trace. Spans
+/// received after the decision on their trace are buffered and decided as a new trace.
+pub struct TailSampler {
+    ingest_service: IngestServiceClient,
+    policies: Vec<OtlpTailSamplingPolicy>,
+    decision_wait_ticks: u64,
+    max_buffered_traces: usize,
+    current_tick: u64,
+    pending_traces: HashMap<TraceKey, PendingTrace>,
+    /// Keys of the pending traces in the order of arrival of their first span.
+    arrival_order: VecDeque<TraceKey>,
+    counters: TailSamplerCounters,
+    id_encoding: OtlpIdEncoding,
+}
+
+impl TailSampler {
+    pub fn new(
+        tail_sampling_config: &OtlpTailSamplingConfig,
+        ingest_service: IngestServiceClient,
+    ) -> Self {
+        Self {
+            ingest_service,
+            policies: tail_sampling_config.policies.clone(),
+            // Decision ticks are one second apart.
+            decision_wait_ticks: tail_sampling_config.decision_wait_secs.get(),
+            max_buffered_traces: tail_sampling_config.max_buffered_traces.get(),
+            current_tick: 0,
+            pending_traces: HashMap::new(),
+            arrival_order: VecDeque::new(),
+            counters: TailSamplerCounters::default(),
+            id_encoding: OtlpIdEncoding::default(),
+        }
+    }
+
+    /// Stores the trace and span IDs of the sampled spans in `id_encoding` instead of Base64.
+    pub fn with_id_encoding(mut self, id_encoding: OtlpIdEncoding) -> Self {
+        self.id_encoding = id_encoding;
+        self
+    }
+
+    fn buffer_spans(&mut self, spans_per_index: BTreeMap<String, Vec<Span>>) {
+        for (index_id, spans) in spans_per_index {
+            for span in spans {
+                let trace_key = (index_id.clone(), span.trace_id);
+                let current_tick = self.current_tick;
+                let pending_trace =
+                    self.pending_traces
+                        .entry(trace_key.clone())
+                        .or_insert_with(|| {
+                            self.arrival_order.push_back(trace_key);
+                            PendingTrace {
+                                arrival_tick: current_tick,
+                                spans: Vec::new(),
+                            }
+                        });
+                pending_trace.spans.push(span);
+            }
+        }
+    }
+
+    /// Pops the oldest pending trace and returns its spans if it is sampled.
+    fn decide_oldest_trace(&mut self) -> Option<(String, Vec<Span>)> {
+        let trace_key = self.arrival_order.pop_front()?;
+        let pending_trace = self.pending_traces.remove(&trace_key)?;
+        let (index_id, trace_id) = trace_key;
+
+        if is_sampled(&self.policies, &trace_id, &pending_trace.spans) {
+            self.counters.num_sampled_traces += 1;
+            self.counters.num_sampled_spans += pending_trace.spans.len() as u64;
+            OTLP_SERVICE_METRICS
+                .tail_sampling_decisions_total
+                .with_label_values(["sampled"])
+                .inc();
+            Some((index_id, pending_trace.spans))
+        } else {
+            self.counters.num_dropped_traces += 1;
+            OTLP_SERVICE_METRICS
+                .tail_sampling_decisions_total
+                .with_label_values(["dropped"])
+                .inc();
+            None
+        }
+    }
+
+    /// Decides the traces whose decision wait has elapsed, and the oldest traces exceeding the
+    /// buffer capacity. With `decide_all`, every pending trace is decided.
+    fn decide_traces(&mut self, decide_all: bool) -> BTreeMap<String, Vec<Span>> {
+        let mut sampled_spans_per_index: BTreeMap<String, Vec<Span>> = BTreeMap::new();
+
+        while let Some(oldest_trace_key) = self.arrival_order.front() {
+            let is_expired = self
+                .pending_traces
+                .get(oldest_trace_key)
+                .map(|pending_trace| {
+                    pending_trace.arrival_tick + self.decision_wait_ticks <= self.current_tick
+                })
+                .unwrap_or(true);
+            let is_overflowing = self.pending_traces.len() > self.max_buffered_traces;
+
+            if !decide_all && !is_expired && !is_overflowing {
+                break;
+            }
+            if is_overflowing && !is_expired {
+                self.counters.num_early_decisions += 1;
+            }
+            if let Some((index_id, spans)) = self.decide_oldest_trace() {
+                sampled_spans_per_index
+                    .entry(index_id)
+                    .or_default()
+                    .extend(spans);
+            }
+        }
+        self.counters.num_buffered_traces = self.pending_traces.len();
+        OTLP_SERVICE_METRICS
+            .tail_sampling_buffered_traces
+            .set(self.pending_traces.len() as i64);
+        sampled_spans_per_index
+    }
+
+    async fn ingest_sampled_spans(
+        &mut self,
+        sampled_spans_per_index: BTreeMap<String, Vec<Span>>,
+        ctx: &ActorContext<Self>,
+    ) {
+        if sampled_spans_per_index.is_empty() {
+            return;
+        }
+        let num_sampled_spans: u64 = sampled_spans_per_index
+            .values()
+            .map(|spans| spans.len() as u64)
+            .sum();
+        let span_doc_batches = build_span_doc_batches(sampled_spans_per_index, self.id_encoding);
+        self.counters.num_failed_spans += span_doc_batches.num_serialization_errors;
+
+        let ingest_request = IngestRequest {
+            doc_batches: span_doc_batches.doc_batches,
+            commit: CommitType::Auto as u32,
+        };
+        if let Err(error) = ctx
+            .protect_future(self.ingest_service.ingest(ingest_request))
+            .await
+        {
+            error!(error=?error, num_spans=num_sampled_spans, "Failed to ingest sampled spans.");
+            self.counters.num_failed_spans +=
+                num_sampled_spans - span_doc_batches.num_serialization_errors;
+        }
+    }
+}
+
+/// Returns whether a trace matches at least one of the sampling policies.
+fn is_sampled(policies: &[OtlpTailSamplingPolicy], trace_id: &TraceId, spans: &[Span]) -> bool {
+    policies.iter().any(|policy| match policy {
+        OtlpTailSamplingPolicy::Error => spans.iter().any(|span| {
+            span.span_status
+                .as_ref()
+                .map(|span_status| span_status.code == SPAN_STATUS_CODE_ERROR)
+                .unwrap_or(false)
+        }),
+        OtlpTailSamplingPolicy::Latency { threshold_millis } => {
+            let trace_start_nanos_opt = spans
+                .iter()
+                .map(|span| span.span_start_timestamp_nanos)
+                .min();
+            let trace_end_nanos_opt = spans.iter().map(|span| span.span_end_timestamp_nanos).max();
+            match (trace_start_nanos_opt, trace_end_nanos_opt) {
+                (Some(trace_start_nanos), Some(trace_end_nanos)) => {
+                    trace_end_nanos.saturating_sub(trace_start_nanos)
+                        >= threshold_millis.saturating_mul(1_000_000)
+                }
+                _ => false,
+            }
+        }
+        OtlpTailSamplingPolicy::Probabilistic { sampling_rate } => {
+            *sampling_rate >= 1.0 || trace_id_ratio(trace_id) < *sampling_rate
+        }
+    })
+}
+
+/// Maps a trace ID to a number in `[0, 1)` from its last 8 bytes, which are random for W3C trace
+/// IDs, so that the probabilistic policy makes the same decision for a trace on every node.
+fn trace_id_ratio(trace_id: &TraceId) -> f64 {
+    let mut random_bytes = [0u8; 8];
+    random_bytes.copy_from_slice(&trace_id.as_bytes()[8..]);
+    (u64::from_be_bytes(random_bytes) >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[async_trait]
+impl Actor for TailSampler {
+    type ObservableState = TailSamplerCounters;
+
+    fn observable_state(&self) -> Self::ObservableState {
+        self.counters.clone()
+    }
+
+    fn name(&self) -> String {
+        "TailSampler".to_string()
+    }
+
+    async fn initialize(&mut self, ctx: &ActorContext<Self>) -> Result<(), ActorExitStatus> {
+        ctx.schedule_self_msg(DECISION_TICK_INTERVAL, DecisionTick)
+            .await;
+        Ok(())
+    }
+
+    async fn finalize(
+        &mut self,
+        exit_status: &ActorExitStatus,
+        ctx: &ActorContext<Self>,
+    ) -> anyhow::Result<()> {
+        match exit_status {
+            ActorExitStatus::DownstreamClosed
+            | ActorExitStatus::Killed
+            | ActorExitStatus::Failure(_)
+            | ActorExitStatus::Panicked(_) => return Ok(()),
+            ActorExitStatus::Quit | ActorExitStatus::Success => {
+                let sampled_spans_per_index = self.decide_traces(true);
+                self.ingest_sampled_spans(sampled_spans_per_index, ctx)
+                    .await;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Handler<SampleSpans> for TailSampler {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        message: SampleSpans,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        self.buffer_spans(message.spans_per_index);
+        let sampled_spans_per_index = self.decide_traces(false);
+        self.ingest_sampled_spans(sampled_spans_per_index, ctx)
+            .await;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Handler<DecisionTick> for TailSampler {
+    type Reply = ();
+
+    async fn handle(
+        &mut self,
+        _message: DecisionTick,
+        ctx: &ActorContext<Self>,
+    ) -> Result<(), ActorExitStatus> {
+        self.current_tick += 1;
+        let sampled_spans_per_index = self.decide_traces(false);
+        self.ingest_sampled_spans(sampled_spans_per_index, ctx)
+            .await;
+        ctx.schedule_self_msg(DECISION_TICK_INTERVAL, DecisionTick)
+            .await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::{NonZeroU64, NonZeroUsize};
+    use std::sync::{Arc, Mutex};
+
+    use quickwit_actors::Universe;
+    use quickwit_ingest::IngestResponse;
+
+    use super::*;
+    use crate::otlp::SpanStatus;
+
+    fn span_for_test(trace_id: TraceId, duration_millis: u64, status_code: i32) -> Span {
+        let span_start_timestamp_nanos = 1_000_000_000;
+        let span_end_timestamp_nanos = span_start_timestamp_nanos + duration_millis * 1_000_000;
+        Span {
+            trace_id,
+            trace_state: None,
+            service_name: "quickwit".to_string(),
+            resource_attributes: HashMap::new(),
+            resource_dropped_attributes_count: 0,
+            scope_name: None,
+            scope_version: None,
+            scope_attributes: HashMap::new(),
+            scope_dropped_attributes_count: 0,
+            span_id: "AQEBAQEBAQE=".to_string(),
+            span_kind: 1,
+            span_name: "publish_split".to_string(),
+            span_fingerprint: None,
+            span_start_timestamp_nanos,
+            span_end_timestamp_nanos,
+            span_start_timestamp_secs: Some(1),
+            span_duration_millis: Some(duration_millis),
+            span_attributes: HashMap::new(),
+            span_dropped_attributes_count: 0,
+            span_dropped_events_count: 0,
+            span_dropped_links_count: 0,
+            span_status: Some(SpanStatus {
+                code: status_code,
+                message: None,
+            }),
+            parent_span_id: None,
+            events: Vec::new(),
+            event_names: Vec::new(),
+            links: Vec::new(),
+            promoted_attributes: HashMap::new(),
+        }
+    }
+
+    fn tail_sampling_config_for_test(
+        max_buffered_traces: usize,
+        policies: Vec<OtlpTailSamplingPolicy>,
+    ) -> OtlpTailSamplingConfig {
+        OtlpTailSamplingConfig {
+            decision_wait_secs: NonZeroU64::new(10).unwrap(),
+            max_buffered_traces: NonZeroUsize::new(max_buffered_traces).unwrap(),
+            policies,
+        }
+    }
+
+    fn ingest_service_for_test(num_ingested_docs: Arc<Mutex<usize>>) -> IngestServiceClient {
+        let mut mock_ingest_service = IngestServiceClient::mock();
+        mock_ingest_service
+            .expect_ingest()
+            .returning(move |ingest_request| {
+                let num_docs: usize = ingest_request
+                    .doc_batches
+                    .iter()
+                    .map(|doc_batch| doc_batch.num_docs())
+                    .sum();
+                *num_ingested_docs.lock().unwrap() += num_docs;
+                Ok(IngestResponse {
+                    num_docs_for_processing: num_docs as u64,
+                })
+            });
+        IngestServiceClient::new(mock_ingest_service)
+    }
+
+    #[tokio::test]
+    async fn test_tail_sampler_decides_traces_after_decision_wait() {
+        let universe = Universe::with_accelerated_time();
+        let num_ingested_docs = Arc::new(Mutex::new(0));
+        let tail_sampling_config = tail_sampling_config_for_test(
+            100,
+            vec![
+                OtlpTailSamplingPolicy::Error,
+                OtlpTailSamplingPolicy::Latency {
+                    threshold_millis: 500,
+                },
+            ],
+        );
+        let tail_sampler = TailSampler::new(
+            &tail_sampling_config,
+            ingest_service_for_test(num_ingested_docs.clone()),
+        );
+        let (tail_sampler_mailbox, tail_sampler_handle) =
+            universe.spawn_builder().spawn(tail_sampler);
+
+        let error_trace_id = TraceId::new([1; 16]);
+        let slow_trace_id = TraceId::new([2; 16]);
+        let ok_trace_id = TraceId::new([3; 16]);
+        let spans = vec![
+            span_for_test(error_trace_id, 10, 0),
+            span_for_test(error_trace_id, 10, SPAN_STATUS_CODE_ERROR),
+            span_for_test(slow_trace_id, 1_000, 1),
+            span_for_test(ok_trace_id, 10, 1),
+        ];
+        tail_sampler_mailbox
+            .send_message(SampleSpans {
+                spans_per_index: BTreeMap::from_iter([("otel-trace-v0".to_string(), spans)]),
+            })
+            .await
+            .unwrap();
+        let counters = tail_sampler_handle
+            .process_pending_and_observe()
+            .await
+            .state;
+        assert_eq!(counters.num_buffered_traces, 3);
+        assert_eq!(counters.num_sampled_traces, 0);
+        assert_eq!(*num_ingested_docs.lock().unwrap(), 0);
+
+        universe.sleep(Duration::from_secs(11)).await;
+        let counters = tail_sampler_handle
+            .process_pending_and_observe()
+            .await
+            .state;
+        assert_eq!(counters.num_buffered_traces, 0);
+        assert_eq!(counters.num_sampled_traces, 2);
+        assert_eq!(counters.num_dropped_traces, 1);
+        assert_eq!(counters.num_sampled_spans, 3);
+        assert_eq!(counters.num_early_decisions, 0);
+        assert_eq!(*num_ingested_docs.lock().unwrap(), 3);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_tail_sampler_decides_oldest_traces_when_full() {
+        let universe = Universe::with_accelerated_time();
+        let num_ingested_docs = Arc::new(Mutex::new(0));
+        let tail_sampling_config =
+            tail_sampling_config_for_test(2, vec![OtlpTailSamplingPolicy::Error]);
+        let tail_sampler = TailSampler::new(
+            &tail_sampling_config,
+            ingest_service_for_test(num_ingested_docs.clone()),
+        );
+        let (tail_sampler_mailbox, tail_sampler_handle) =
+            universe.spawn_builder().spawn(tail_sampler);
+
+        for trace_byte in 1..=3 {
+            let span = span_for_test(TraceId::new([trace_byte; 16]), 10, SPAN_STATUS_CODE_ERROR);
+            tail_sampler_mailbox
+                .send_message(SampleSpans {
+                    spans_per_index: BTreeMap::from_iter([(
+                        "otel-trace-v0".to_string(),
+                        vec![span],
+                    )]),
+                })
+                .await
+                .unwrap();
+        }
+        let counters = tail_sampler_handle
+            .process_pending_and_observe()
+            .await
+            .state;
+        assert_eq!(counters.num_buffered_traces, 2);
+        assert_eq!(counters.num_sampled_traces, 1);
+        assert_eq!(counters.num_early_decisions, 1);
+        assert_eq!(*num_ingested_docs.lock().unwrap(), 1);
+
+        // Pending traces are decided when the sampler quits.
+        let (_exit_status, counters) = tail_sampler_handle.quit().await;
+        assert_eq!(counters.num_buffered_traces, 0);
+        assert_eq!(counters.num_sampled_traces, 3);
+        assert_eq!(*num_ingested_docs.lock().unwrap(), 3);
+        universe.assert_quit().await;
+    }
+
+    #[test]
+    fn test_is_sampled_probabilistic() {
+        let trace_id = TraceId::new([0; 16]);
+        let spans = [span_for_test(trace_id, 10, 1)];
+
+        let policies = [OtlpTailSamplingPolicy::Probabilistic { sampling_rate: 0.0 }];
+        assert!(!is_sampled(&policies, &trace_id, &spans));
+
+        let policies = [OtlpTailSamplingPolicy::Probabilistic { sampling_rate: 1.0 }];
+        assert!(is_sampled(&policies, &trace_id, &spans));
+        assert!(is_sampled(&policies, &TraceId::new([u8::MAX; 16]), &spans));
+
+        let policies = [OtlpTailSamplingPolicy::Probabilistic { sampling_rate: 0.5 }];
+        let num_sampled_traces = (0..=u8::MAX)
+            .filter(|trace_byte| {
+                let mut trace_id_bytes = [0; 16];
+                trace_id_bytes[8] = *trace_byte;
+                is_sampled(&policies, &TraceId::new(trace_id_bytes), &spans)
+            })
+            .count();
+        assert_eq!(num_sampled_traces, 128);
+    }
+}
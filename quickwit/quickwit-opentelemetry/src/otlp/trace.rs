@@ -18,14 +18,15 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 use std::cmp::{Ord, Ordering, PartialEq, PartialOrd};
-use std::collections::{BTreeSet, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::str::FromStr;
 
 use async_trait::async_trait;
 use base64::prelude::{Engine, BASE64_STANDARD};
-use quickwit_ingest::{
-    CommitType, DocBatch, DocBatchBuilder, IngestRequest, IngestService, IngestServiceClient,
-};
+use prost::Message;
+use quickwit_actors::Mailbox;
+use quickwit_config::{OtlpIdEncoding, OtlpIngestConfig, OtlpLimitsConfig};
+use quickwit_ingest::{DocBatch, DocBatchBuilder, IngestServiceClient};
 use quickwit_proto::opentelemetry::proto::collector::trace::v1::trace_service_server::TraceService;
 use quickwit_proto::opentelemetry::proto::collector::trace::v1::{
     ExportTracePartialSuccess, ExportTraceServiceRequest, ExportTraceServiceResponse,
@@ -39,8 +40,16 @@ use tonic::{Request, Response, Status};
 use tracing::field::Empty;
 use tracing::{error, instrument, warn, Span as RuntimeSpan};
 
+use crate::otlp::index_routing::extract_header_index_id;
+use crate::otlp::ingester::OtlpIngester;
+use crate::otlp::limits::{check_num_items, check_payload_size, with_retry_after};
 use crate::otlp::metrics::OTLP_SERVICE_METRICS;
-use crate::otlp::{extract_attributes, TraceId};
+use crate::otlp::span_metrics::record_span_metrics;
+use crate::otlp::tail_sampling::{SampleSpans, TailSampler};
+use crate::otlp::{
+    extract_attributes, ingest_otlp_doc, OtlpAttributePromoter, OtlpIndexRouter, OtlpProtocol,
+    TraceId,
+};
 
 pub const OTEL_TRACE_INDEX_ID: &str = "otel-trace-v0";
 
@@ -190,6 +199,9 @@ pub struct Span {
     pub event_names: Vec<String>,
     #[serde(default)]
     pub links: Vec<Link>,
+    /// Span or resource attributes copied into dedicated fields of the index, keyed by field name.
+    #[serde(flatten)]
+    pub promoted_attributes: HashMap<String, JsonValue>,
 }
 
 impl Span {
@@ -274,6 +286,7 @@ impl Span {
             events,
             event_names,
             links,
+            promoted_attributes: HashMap::new(),
         };
         Ok(span)
     }
@@ -430,7 +443,7 @@ impl SpanFingerprint {
         start_key.push(SPAN_FINGERPRINT_SEPARATOR as u8);
 
         if let Some(span_kind) = span_kind_opt {
-            start_key.push(span_kind.0 as u8);
+            start_key.push(span_kind.as_char() as u8);
             start_key.push(SPAN_FINGERPRINT_SEPARATOR as u8);
         }
         Some(start_key)
@@ -444,7 +457,7 @@ impl SpanFingerprint {
 
         if let Some(span_kind) = span_kind_opt {
             end_key.push(SPAN_FINGERPRINT_SEPARATOR as u8);
-            end_key.push(span_kind.0 as u8);
+            end_key.push(span_kind.as_char() as u8);
         }
         end_key.push(SPAN_FINGERPRINT_SEPARATOR as u8 + 1);
         Some(end_key)
@@ -546,36 +559,179 @@ pub struct Link {
 }
 
 struct ParsedSpans {
-    doc_batch: DocBatch,
+    payload: ParsedSpansPayload,
     num_spans: u64,
     num_parse_errors: u64,
     error_message: String,
 }
 
+/// The spans of an export request are serialized into doc batches right away, unless tail
+/// sampling is enabled, in which case they are handed over to the tail sampler.
+enum ParsedSpansPayload {
+    DocBatches(Vec<DocBatch>),
+    SpansToSample(BTreeMap<String, Vec<Span>>),
+}
+
+pub(crate) struct SpanDocBatches {
+    pub doc_batches: Vec<DocBatch>,
+    pub num_bytes: usize,
+    pub num_serialization_errors: u64,
+    pub error_message_opt: Option<String>,
+}
+
+/// Serializes spans into one doc batch per index.
+pub(crate) fn build_span_doc_batches<I, S>(
+    spans_per_index: I,
+    id_encoding: OtlpIdEncoding,
+) -> SpanDocBatches
+where
+    I: IntoIterator<Item = (String, S)>,
+    S: IntoIterator<Item = Span>,
+{
+    let mut doc_batches = Vec::new();
+    let mut num_bytes = 0;
+    let mut num_serialization_errors = 0;
+    let mut error_message_opt = None;
+
+    for (index_id, spans) in spans_per_index {
+        let mut doc_batch_builder = DocBatchBuilder::new(index_id).json_writer();
+        for span in spans {
+            if let Err(error) = ingest_otlp_doc(&mut doc_batch_builder, &span, id_encoding) {
+                error!(error=?error, "Failed to JSON serialize span.");
+                error_message_opt = Some(format!("Failed to JSON serialize span: {error:?}"));
+                num_serialization_errors += 1;
+            }
+        }
+        let doc_batch = doc_batch_builder.build();
+        num_bytes += doc_batch.num_bytes();
+        doc_batches.push(doc_batch);
+    }
+    SpanDocBatches {
+        doc_batches,
+        num_bytes,
+        num_serialization_errors,
+        error_message_opt,
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct OtlpGrpcTraceService {
-    ingest_service: IngestServiceClient,
+    ingester: OtlpIngester,
+    index_router: OtlpIndexRouter,
+    attribute_promoter: OtlpAttributePromoter,
+    tail_sampler_mailbox_opt: Option<Mailbox<TailSampler>>,
+    id_encoding: OtlpIdEncoding,
+    span_metrics_enabled: bool,
+    limits: OtlpLimitsConfig,
 }
 
 impl OtlpGrpcTraceService {
     // TODO: remove and use registry
     pub fn new(ingest_service: IngestServiceClient) -> Self {
-        Self { ingest_service }
+        Self {
+            ingester: OtlpIngester::new(ingest_service),
+            index_router: OtlpIndexRouter::new(OTEL_TRACE_INDEX_ID, None),
+            attribute_promoter: OtlpAttributePromoter::default(),
+            tail_sampler_mailbox_opt: None,
+            id_encoding: OtlpIdEncoding::default(),
+            span_metrics_enabled: false,
+            limits: OtlpLimitsConfig::default(),
+        }
+    }
+
+    /// Routes the spans to the indexes chosen by `index_router` instead of the `otel-trace-v0`
+    /// index.
+    pub fn with_index_router(mut self, index_router: OtlpIndexRouter) -> Self {
+        self.index_router = index_router;
+        self
+    }
+
+    /// Copies the attributes selected by `attribute_promoter` into dedicated fields of the spans.
+    pub fn with_attribute_promoter(mut self, attribute_promoter: OtlpAttributePromoter) -> Self {
+        self.attribute_promoter = attribute_promoter;
+        self
+    }
+
+    /// Hands the spans over to the tail sampler instead of ingesting them right away.
+    pub fn with_tail_sampler(mut self, tail_sampler_mailbox: Mailbox<TailSampler>) -> Self {
+        self.tail_sampler_mailbox_opt = Some(tail_sampler_mailbox);
+        self
+    }
+
+    /// Stores the trace and span IDs of the spans in `id_encoding` instead of Base64. The tail
+    /// sampler, if any, must be configured with the same encoding.
+    pub fn with_id_encoding(mut self, id_encoding: OtlpIdEncoding) -> Self {
+        self.id_encoding = id_encoding;
+        self
+    }
+
+    /// Derives request, error, and duration metrics per service from the spans, before they are
+    /// sampled.
+    pub fn with_span_metrics(mut self, span_metrics_enabled: bool) -> Self {
+        self.span_metrics_enabled = span_metrics_enabled;
+        self
+    }
+
+    /// Rejects the requests exceeding `limits` instead of the default limits.
+    pub fn with_limits(mut self, limits: OtlpLimitsConfig) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Acknowledges the requests and batches the ingested documents according to
+    /// `ingest_config` instead of acknowledging them right after they are appended to the
+    /// write-ahead log. Must be called from within a Tokio runtime.
+    pub fn with_ingest_config(mut self, ingest_config: &OtlpIngestConfig) -> Self {
+        self.ingester = self.ingester.with_config(ingest_config);
+        self
+    }
+
+    /// Maximum size of the payload of a request, once decompressed.
+    pub fn max_payload_size(&self) -> u64 {
+        self.limits.max_payload_size.get_bytes() as u64
     }
 
     async fn export_inner(
         &mut self,
         request: ExportTraceServiceRequest,
-        labels: [&'static str; 4],
+        header_index_id_opt: Option<String>,
+        labels: [&str; 4],
     ) -> Result<ExportTraceServiceResponse, Status> {
+        let num_spans_in_request = request
+            .resource_spans
+            .iter()
+            .flat_map(|resource_spans| &resource_spans.scope_spans)
+            .map(|scope_spans| scope_spans.spans.len())
+            .sum();
+        check_num_items(
+            num_spans_in_request,
+            self.limits.max_spans_per_request.get(),
+            "spans",
+        )?;
         let ParsedSpans {
-            doc_batch,
+            payload,
             num_spans,
             num_parse_errors,
             error_message,
         } = tokio::task::spawn_blocking({
             let parent_span = RuntimeSpan::current();
-            || Self::parse_spans(request, parent_span)
+            let index_router = self.index_router.clone();
+            let attribute_promoter = self.attribute_promoter.clone();
+            let tail_sampling_enabled = self.tail_sampler_mailbox_opt.is_some();
+            let id_encoding = self.id_encoding;
+            let span_metrics_enabled = self.span_metrics_enabled;
+            move || {
+                Self::parse_spans(
+                    request,
+                    &index_router,
+                    &attribute_promoter,
+                    header_index_id_opt.as_deref(),
+                    tail_sampling_enabled,
+                    id_encoding,
+                    span_metrics_enabled,
+                    parent_span,
+                )
+            }
         })
         .await
         .map_err(|join_error| {
@@ -585,17 +741,32 @@ impl OtlpGrpcTraceService {
         if num_spans == num_parse_errors {
             return Err(tonic::Status::internal(error_message));
         }
-        let num_bytes = doc_batch.num_bytes() as u64;
-        self.store_spans(doc_batch).await?;
+        match payload {
+            ParsedSpansPayload::DocBatches(doc_batches) => {
+                let num_bytes = doc_batches
+                    .iter()
+                    .map(|doc_batch| doc_batch.num_bytes() as u64)
+                    .sum();
+                self.store_spans(doc_batches).await?;
 
+                OTLP_SERVICE_METRICS
+                    .ingested_bytes_total
+                    .with_label_values(labels)
+                    .inc_by(num_bytes);
+            }
+            ParsedSpansPayload::SpansToSample(spans_per_index) => {
+                if let Some(tail_sampler_mailbox) = &self.tail_sampler_mailbox_opt {
+                    tail_sampler_mailbox
+                        .send_message(SampleSpans { spans_per_index })
+                        .await
+                        .map_err(|_| Status::unavailable("The tail sampler is not running."))?;
+                }
+            }
+        }
         OTLP_SERVICE_METRICS
             .ingested_spans_total
             .with_label_values(labels)
             .inc_by(num_spans);
-        OTLP_SERVICE_METRICS
-            .ingested_bytes_total
-            .with_label_values(labels)
-            .inc_by(num_bytes);
 
         let response = ExportTraceServiceResponse {
             // `rejected_spans=0` and `error_message=""` is consided a "full" success.
@@ -610,14 +781,29 @@ impl OtlpGrpcTraceService {
     #[instrument(skip_all, parent = parent_span, fields(num_spans = Empty, num_bytes = Empty, num_parse_errors = Empty))]
     fn parse_spans(
         request: ExportTraceServiceRequest,
+        index_router: &OtlpIndexRouter,
+        attribute_promoter: &OtlpAttributePromoter,
+        header_index_id_opt: Option<&str>,
+        tail_sampling_enabled: bool,
+        id_encoding: OtlpIdEncoding,
+        span_metrics_enabled: bool,
         parent_span: RuntimeSpan,
     ) -> Result<ParsedSpans, Status> {
-        let mut ordered_spans = BTreeSet::new();
+        let mut ordered_spans_per_index: BTreeMap<String, BTreeSet<OrdSpan>> = BTreeMap::new();
         let mut num_spans = 0;
         let mut num_parse_errors = 0;
         let mut error_message = String::new();
 
         for resource_spans in request.resource_spans {
+            let index_id = index_router.route(
+                header_index_id_opt,
+                resource_spans
+                    .resource
+                    .as_ref()
+                    .map(|resource| &resource.attributes[..])
+                    .unwrap_or_default(),
+            );
+            let ordered_spans = ordered_spans_per_index.entry(index_id).or_default();
             let resource = resource_spans
                 .resource
                 .map(Resource::from_otlp)
@@ -626,28 +812,54 @@ impl OtlpGrpcTraceService {
                 let scope = scope_spans.scope.map(Scope::from_otlp).unwrap_or_default();
                 for span in scope_spans.spans {
                     num_spans += 1;
-                    let span = Span::from_otlp(span, &resource, &scope)?;
+                    let mut span = Span::from_otlp(span, &resource, &scope)?;
+                    span.promoted_attributes = attribute_promoter
+                        .promote(&span.span_attributes, &span.resource_attributes);
+                    if span_metrics_enabled {
+                        record_span_metrics(&span);
+                    }
                     ordered_spans.insert(OrdSpan(span));
                 }
             }
         }
-        let mut doc_batch_builder =
-            DocBatchBuilder::new(OTEL_TRACE_INDEX_ID.to_string()).json_writer();
-        for span in ordered_spans {
-            if let Err(error) = doc_batch_builder.ingest_doc(&span.0) {
-                error!(error=?error, "Failed to JSON serialize span.");
-                error_message = format!("Failed to JSON serialize span: {error:?}");
-                num_parse_errors += 1;
+        let (payload, num_bytes) = if tail_sampling_enabled {
+            let spans_per_index = ordered_spans_per_index
+                .into_iter()
+                .map(|(index_id, ordered_spans)| {
+                    let spans = ordered_spans
+                        .into_iter()
+                        .map(|ordered_span| ordered_span.0)
+                        .collect();
+                    (index_id, spans)
+                })
+                .collect();
+            (ParsedSpansPayload::SpansToSample(spans_per_index), 0)
+        } else {
+            let spans_per_index =
+                ordered_spans_per_index
+                    .into_iter()
+                    .map(|(index_id, ordered_spans)| {
+                        let spans = ordered_spans.into_iter().map(|ordered_span| ordered_span.0);
+                        (index_id, spans)
+                    });
+            let span_doc_batches = build_span_doc_batches(spans_per_index, id_encoding);
+            num_parse_errors += span_doc_batches.num_serialization_errors;
+
+            if let Some(serialization_error_message) = span_doc_batches.error_message_opt {
+                error_message = serialization_error_message;
             }
-        }
-        let doc_batch = doc_batch_builder.build();
+            (
+                ParsedSpansPayload::DocBatches(span_doc_batches.doc_batches),
+                span_doc_batches.num_bytes,
+            )
+        };
         let current_span = RuntimeSpan::current();
         current_span.record("num_spans", num_spans);
-        current_span.record("num_bytes", doc_batch.num_bytes());
+        current_span.record("num_bytes", num_bytes);
         current_span.record("num_parse_errors", num_parse_errors);
 
         let parsed_spans = ParsedSpans {
-            doc_batch,
+            payload,
             num_spans,
             num_parse_errors,
             error_message,
@@ -655,29 +867,56 @@ impl OtlpGrpcTraceService {
         Ok(parsed_spans)
     }
 
-    #[instrument(skip_all, fields(num_bytes = doc_batch.num_bytes()))]
-    async fn store_spans(&mut self, doc_batch: DocBatch) -> Result<(), tonic::Status> {
-        let ingest_request = IngestRequest {
-            doc_batches: vec![doc_batch],
-            commit: CommitType::Auto as u32,
-        };
-        self.ingest_service.ingest(ingest_request).await?;
+    #[instrument(skip_all, fields(num_doc_batches = doc_batches.len()))]
+    async fn store_spans(&mut self, doc_batches: Vec<DocBatch>) -> Result<(), tonic::Status> {
+        let retry_after = self.limits.retry_after();
+        self.ingester
+            .ingest(doc_batches)
+            .await
+            .map_err(|error| with_retry_after(error.into(), retry_after))?;
         Ok(())
     }
 
+    /// Ingests the spans of an export request received over `protocol`. The gRPC service
+    /// delegates to this method, which is also used to serve the OTLP HTTP endpoints.
+    /// `header_index_id_opt` is the index set by the `x-qw-index` header of the request, if any.
+    pub async fn export_with_protocol(
+        &self,
+        request: ExportTraceServiceRequest,
+        protocol: OtlpProtocol,
+        header_index_id_opt: Option<String>,
+    ) -> Result<ExportTraceServiceResponse, Status> {
+        self.clone()
+            .export_instrumented(request, protocol, header_index_id_opt)
+            .await
+    }
+
     async fn export_instrumented(
         &mut self,
         request: ExportTraceServiceRequest,
+        protocol: OtlpProtocol,
+        header_index_id_opt: Option<String>,
     ) -> Result<ExportTraceServiceResponse, Status> {
         let start = std::time::Instant::now();
 
-        let labels = ["trace", OTEL_TRACE_INDEX_ID, "grpc", "protobuf"];
+        let index_label = header_index_id_opt
+            .clone()
+            .unwrap_or_else(|| self.index_router.default_index_id().to_string());
+        let labels = [
+            "trace",
+            &index_label,
+            protocol.transport(),
+            protocol.format(),
+        ];
 
         OTLP_SERVICE_METRICS
             .requests_total
             .with_label_values(labels)
             .inc();
-        let (export_res, is_error) = match self.export_inner(request, labels).await {
+        let (export_res, is_error) = match self
+            .export_inner(request, header_index_id_opt, labels)
+            .await
+        {
             ok @ Ok(_) => (ok, "false"),
             err @ Err(_) => {
                 OTLP_SERVICE_METRICS
@@ -688,7 +927,13 @@ impl OtlpGrpcTraceService {
             }
         };
         let elapsed = start.elapsed().as_secs_f64();
-        let labels = ["trace", OTEL_TRACE_INDEX_ID, "grpc", "protobuf", is_error];
+        let labels = [
+            "trace",
+            &index_label,
+            protocol.transport(),
+            protocol.format(),
+            is_error,
+        ];
         OTLP_SERVICE_METRICS
             .request_duration_seconds
             .with_label_values(labels)
@@ -705,9 +950,11 @@ impl TraceService for OtlpGrpcTraceService {
         &self,
         request: Request<ExportTraceServiceRequest>,
     ) -> Result<Response<ExportTraceServiceResponse>, Status> {
+        let header_index_id_opt = extract_header_index_id(&request);
         let request = request.into_inner();
+        check_payload_size(request.encoded_len(), self.max_payload_size())?;
         self.clone()
-            .export_instrumented(request)
+            .export_instrumented(request, OtlpProtocol::Grpc, header_index_id_opt)
             .await
             .map(Response::new)
     }
@@ -727,6 +974,25 @@ mod tests {
 
     use super::*;
 
+    #[test]
+    fn test_span_fingerprint_keys() {
+        let fingerprint = SpanFingerprint::new("quickwit", SpanKind(2), "leaf_search");
+        let server_start_key = SpanFingerprint::start_key("quickwit", Some(SpanKind(2))).unwrap();
+        let server_end_key = SpanFingerprint::end_key("quickwit", Some(SpanKind(2))).unwrap();
+        assert!(server_start_key.as_slice() <= fingerprint.as_str().as_bytes());
+        assert!(fingerprint.as_str().as_bytes() < server_end_key.as_slice());
+
+        let client_start_key = SpanFingerprint::start_key("quickwit", Some(SpanKind(3))).unwrap();
+        assert!(fingerprint.as_str().as_bytes() < client_start_key.as_slice());
+
+        let start_key = SpanFingerprint::start_key("quickwit", None).unwrap();
+        let end_key = SpanFingerprint::end_key("quickwit", None).unwrap();
+        assert!(start_key.as_slice() <= fingerprint.as_str().as_bytes());
+        assert!(fingerprint.as_str().as_bytes() < end_key.as_slice());
+
+        assert!(SpanFingerprint::start_key("", None).is_none());
+    }
+
     #[test]
     fn test_resource_from_otlp() {
         let otlp_resource = OtlpResource {
@@ -0,0 +1,500 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashMap};
+
+use quickwit_proto::opentelemetry::proto::collector::trace::v1::ExportTraceServiceRequest;
+use quickwit_proto::opentelemetry::proto::common::v1::any_value::Value as OtlpValue;
+use quickwit_proto::opentelemetry::proto::common::v1::{
+    AnyValue as OtlpAnyValue, KeyValue as OtlpKeyValue,
+};
+use quickwit_proto::opentelemetry::proto::resource::v1::Resource as OtlpResource;
+use quickwit_proto::opentelemetry::proto::trace::v1::span::{
+    Event as OtlpEvent, SpanKind as OtlpSpanKind,
+};
+use quickwit_proto::opentelemetry::proto::trace::v1::status::StatusCode as OtlpStatusCode;
+use quickwit_proto::opentelemetry::proto::trace::v1::{
+    ResourceSpans, ScopeSpans, Span as OtlpSpan, Status as OtlpStatus,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use super::{decode_hex, decode_otlp_id, encode_hex, OtlpJsonError, Span};
+
+// Zipkin to OpenTelemetry transformation
+// <https://opentelemetry.io/docs/specs/otel/trace/sdk_exporters/zipkin/>
+
+const SERVICE_NAME_KEY: &str = "service.name";
+
+const PEER_SERVICE_KEY: &str = "peer.service";
+const NET_PEER_IP_KEY: &str = "net.peer.ip";
+const NET_PEER_PORT_KEY: &str = "net.peer.port";
+
+/// Tag carrying the error message of the spans that ended with an error.
+const ERROR_TAG: &str = "error";
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ZipkinSpanKind {
+    Client,
+    Server,
+    Producer,
+    Consumer,
+}
+
+#[derive(Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZipkinEndpoint {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub service_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipv4: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipv6: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+}
+
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+pub struct ZipkinAnnotation {
+    /// Annotation timestamp in microseconds.
+    pub timestamp: u64,
+    pub value: String,
+}
+
+/// Span of the Zipkin v2 JSON model.
+///
+/// <https://zipkin.io/zipkin-api/#/default/post_spans>
+#[derive(Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZipkinSpan {
+    /// Hex-encoded 8 or 16-byte trace ID.
+    pub trace_id: String,
+    /// Hex-encoded 8-byte span ID.
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ZipkinSpanKind>,
+    /// Span start timestamp in microseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<u64>,
+    /// Span duration in microseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub duration: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub local_endpoint: Option<ZipkinEndpoint>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_endpoint: Option<ZipkinEndpoint>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<ZipkinAnnotation>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+}
+
+impl ZipkinSpan {
+    /// Converts a span stored in the trace index into a Zipkin span.
+    pub fn from_span(span: Span) -> Self {
+        let trace_id = span.trace_id.to_hex();
+        let id = to_hex_id(&span.span_id);
+        let parent_id = span.parent_span_id.as_deref().map(to_hex_id);
+        let kind = match span.span_kind {
+            2 => Some(ZipkinSpanKind::Server),
+            3 => Some(ZipkinSpanKind::Client),
+            4 => Some(ZipkinSpanKind::Producer),
+            5 => Some(ZipkinSpanKind::Consumer),
+            _ => None,
+        };
+        let timestamp = span.span_start_timestamp_nanos / 1_000;
+        let duration = span
+            .span_end_timestamp_nanos
+            .saturating_sub(span.span_start_timestamp_nanos)
+            / 1_000;
+        let local_endpoint = ZipkinEndpoint {
+            service_name: Some(span.service_name),
+            ..Default::default()
+        };
+        let mut span_attributes = span.span_attributes;
+        let remote_endpoint = ZipkinEndpoint {
+            service_name: take_string_attribute(&mut span_attributes, PEER_SERVICE_KEY),
+            ipv4: take_string_attribute(&mut span_attributes, NET_PEER_IP_KEY),
+            ipv6: None,
+            port: span_attributes
+                .remove(NET_PEER_PORT_KEY)
+                .and_then(|port| port.as_u64())
+                .and_then(|port| u16::try_from(port).ok()),
+        };
+        let annotations = span
+            .events
+            .into_iter()
+            .map(|event| ZipkinAnnotation {
+                timestamp: event.event_timestamp_nanos / 1_000,
+                value: event.event_name,
+            })
+            .collect();
+        let mut tags: BTreeMap<String, String> = span_attributes
+            .into_iter()
+            .map(|(key, value)| match value {
+                JsonValue::String(value) => (key, value),
+                _ => (key, value.to_string()),
+            })
+            .collect();
+        if let Some(span_status) = span.span_status {
+            if span_status.code == OtlpStatusCode::Error as i32 {
+                tags.insert(
+                    ERROR_TAG.to_string(),
+                    span_status.message.unwrap_or_default(),
+                );
+            }
+        }
+        Self {
+            trace_id,
+            id,
+            parent_id,
+            name: Some(span.span_name),
+            kind,
+            timestamp: Some(timestamp),
+            duration: Some(duration),
+            local_endpoint: Some(local_endpoint),
+            remote_endpoint: Some(remote_endpoint)
+                .filter(|endpoint| *endpoint != Default::default()),
+            annotations,
+            tags,
+        }
+    }
+}
+
+/// Decodes a Zipkin v2 JSON-encoded list of spans into an OTLP trace export request. The spans are
+/// grouped into one resource per local service.
+pub fn parse_zipkin_json_spans(payload: &[u8]) -> Result<ExportTraceServiceRequest, OtlpJsonError> {
+    let zipkin_spans: Vec<ZipkinSpan> = serde_json::from_slice(payload)?;
+    let mut spans_per_service: BTreeMap<Option<String>, Vec<OtlpSpan>> = BTreeMap::new();
+
+    for zipkin_span in zipkin_spans {
+        let service_name_opt = zipkin_span
+            .local_endpoint
+            .as_ref()
+            .and_then(|local_endpoint| local_endpoint.service_name.clone());
+        let span = zipkin_span_to_otlp(zipkin_span)?;
+        spans_per_service
+            .entry(service_name_opt)
+            .or_default()
+            .push(span);
+    }
+    let resource_spans = spans_per_service
+        .into_iter()
+        .map(|(service_name_opt, spans)| {
+            let attributes = service_name_opt
+                .map(|service_name| string_key_value(SERVICE_NAME_KEY, service_name))
+                .into_iter()
+                .collect();
+            ResourceSpans {
+                resource: Some(OtlpResource {
+                    attributes,
+                    dropped_attributes_count: 0,
+                }),
+                scope_spans: vec![ScopeSpans {
+                    scope: None,
+                    spans,
+                    schema_url: String::new(),
+                }],
+                schema_url: String::new(),
+            }
+        })
+        .collect();
+    Ok(ExportTraceServiceRequest { resource_spans })
+}
+
+fn zipkin_span_to_otlp(zipkin_span: ZipkinSpan) -> Result<OtlpSpan, OtlpJsonError> {
+    let trace_id = match decode_hex(&zipkin_span.trace_id) {
+        // 8-byte trace IDs are left-padded with zeros.
+        Some(trace_id) if trace_id.len() == 8 => [vec![0; 8], trace_id].concat(),
+        Some(trace_id) if trace_id.len() == 16 => trace_id,
+        _ => return Err(invalid_id("traceId")),
+    };
+    let span_id = decode_span_id(&zipkin_span.id).ok_or_else(|| invalid_id("id"))?;
+    let parent_span_id = match zipkin_span.parent_id.as_deref() {
+        Some(parent_id) => decode_span_id(parent_id).ok_or_else(|| invalid_id("parentId"))?,
+        None => Vec::new(),
+    };
+    let kind = match zipkin_span.kind {
+        Some(ZipkinSpanKind::Server) => OtlpSpanKind::Server,
+        Some(ZipkinSpanKind::Client) => OtlpSpanKind::Client,
+        Some(ZipkinSpanKind::Producer) => OtlpSpanKind::Producer,
+        Some(ZipkinSpanKind::Consumer) => OtlpSpanKind::Consumer,
+        None => OtlpSpanKind::Internal,
+    };
+    let start_time_unix_nano = zipkin_span.timestamp.unwrap_or_default() * 1_000;
+    let end_time_unix_nano =
+        start_time_unix_nano + zipkin_span.duration.unwrap_or_default() * 1_000;
+
+    let mut tags = zipkin_span.tags;
+    let status = tags.remove(ERROR_TAG).map(|message| OtlpStatus {
+        message,
+        code: OtlpStatusCode::Error as i32,
+    });
+    let mut attributes: Vec<OtlpKeyValue> = tags
+        .into_iter()
+        .map(|(key, value)| string_key_value(key, value))
+        .collect();
+
+    if let Some(remote_endpoint) = zipkin_span.remote_endpoint {
+        if let Some(service_name) = remote_endpoint.service_name {
+            attributes.push(string_key_value(PEER_SERVICE_KEY, service_name));
+        }
+        if let Some(ip) = remote_endpoint.ipv4.or(remote_endpoint.ipv6) {
+            attributes.push(string_key_value(NET_PEER_IP_KEY, ip));
+        }
+        if let Some(port) = remote_endpoint.port {
+            attributes.push(OtlpKeyValue {
+                key: NET_PEER_PORT_KEY.to_string(),
+                value: Some(OtlpAnyValue {
+                    value: Some(OtlpValue::IntValue(port as i64)),
+                }),
+            });
+        }
+    }
+    let events = zipkin_span
+        .annotations
+        .into_iter()
+        .map(|annotation| OtlpEvent {
+            time_unix_nano: annotation.timestamp * 1_000,
+            name: annotation.value,
+            attributes: Vec::new(),
+            dropped_attributes_count: 0,
+        })
+        .collect();
+    let span = OtlpSpan {
+        trace_id,
+        span_id,
+        trace_state: String::new(),
+        parent_span_id,
+        name: zipkin_span.name.unwrap_or_default(),
+        kind: kind as i32,
+        start_time_unix_nano,
+        end_time_unix_nano,
+        attributes,
+        dropped_attributes_count: 0,
+        events,
+        dropped_events_count: 0,
+        links: Vec::new(),
+        dropped_links_count: 0,
+        status,
+    };
+    Ok(span)
+}
+
+fn decode_span_id(hex_span_id: &str) -> Option<Vec<u8>> {
+    decode_hex(hex_span_id).filter(|span_id| span_id.len() == 8)
+}
+
+fn invalid_id(field: &'static str) -> OtlpJsonError {
+    OtlpJsonError::InvalidField {
+        field,
+        message: "expected a hex-encoded ID".to_string(),
+    }
+}
+
+fn string_key_value(key: impl ToString, value: String) -> OtlpKeyValue {
+    OtlpKeyValue {
+        key: key.to_string(),
+        value: Some(OtlpAnyValue {
+            value: Some(OtlpValue::StringValue(value)),
+        }),
+    }
+}
+
+/// Re-encodes a span ID stored in Base64 or in hex as hex.
+fn to_hex_id(span_id: &str) -> String {
+    match decode_otlp_id(span_id) {
+        Some(span_id) => encode_hex(&span_id),
+        None => span_id.to_string(),
+    }
+}
+
+fn take_string_attribute(attributes: &mut HashMap<String, JsonValue>, key: &str) -> Option<String> {
+    match attributes.remove(key) {
+        Some(JsonValue::String(value)) => Some(value),
+        Some(value) => {
+            attributes.insert(key.to_string(), value);
+            None
+        }
+        None => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::otlp::TraceId;
+
+    #[test]
+    fn test_parse_zipkin_json_spans() {
+        let payload = json!([
+            {
+                "traceId": "0101010101010101",
+                "id": "0202020202020202",
+                "name": "get /api",
+                "kind": "SERVER",
+                "timestamp": 1_000,
+                "duration": 500,
+                "localEndpoint": {"serviceName": "frontend"},
+                "remoteEndpoint": {"serviceName": "browser", "ipv4": "10.0.0.1", "port": 8080},
+                "annotations": [{"timestamp": 1_200, "value": "wr"}],
+                "tags": {"http.method": "GET", "error": "timeout"}
+            },
+            {
+                "traceId": "01010101010101010101010101010101",
+                "id": "0303030303030303",
+                "parentId": "0202020202020202",
+                "name": "select",
+                "localEndpoint": {"serviceName": "backend"}
+            }
+        ])
+        .to_string();
+        let request = parse_zipkin_json_spans(payload.as_bytes()).unwrap();
+        assert_eq!(request.resource_spans.len(), 2);
+
+        let backend_resource_spans = &request.resource_spans[0];
+        assert_eq!(
+            backend_resource_spans.resource.as_ref().unwrap().attributes,
+            [string_key_value(SERVICE_NAME_KEY, "backend".to_string())]
+        );
+        let backend_span = &backend_resource_spans.scope_spans[0].spans[0];
+        assert_eq!(backend_span.trace_id, [1; 16]);
+        assert_eq!(backend_span.span_id, [3; 8]);
+        assert_eq!(backend_span.parent_span_id, [2; 8]);
+        assert_eq!(backend_span.kind, OtlpSpanKind::Internal as i32);
+        assert!(backend_span.status.is_none());
+
+        let frontend_resource_spans = &request.resource_spans[1];
+        let frontend_span = &frontend_resource_spans.scope_spans[0].spans[0];
+        assert_eq!(frontend_span.trace_id, [vec![0; 8], vec![1; 8]].concat());
+        assert_eq!(frontend_span.span_id, [2; 8]);
+        assert!(frontend_span.parent_span_id.is_empty());
+        assert_eq!(frontend_span.name, "get /api");
+        assert_eq!(frontend_span.kind, OtlpSpanKind::Server as i32);
+        assert_eq!(frontend_span.start_time_unix_nano, 1_000_000);
+        assert_eq!(frontend_span.end_time_unix_nano, 1_500_000);
+        assert_eq!(
+            frontend_span.status,
+            Some(OtlpStatus {
+                message: "timeout".to_string(),
+                code: OtlpStatusCode::Error as i32,
+            })
+        );
+        let attribute_keys: Vec<&str> = frontend_span
+            .attributes
+            .iter()
+            .map(|attribute| attribute.key.as_str())
+            .collect();
+        assert_eq!(
+            attribute_keys,
+            [
+                "http.method",
+                "peer.service",
+                "net.peer.ip",
+                "net.peer.port"
+            ]
+        );
+        assert_eq!(frontend_span.events.len(), 1);
+        assert_eq!(frontend_span.events[0].name, "wr");
+        assert_eq!(frontend_span.events[0].time_unix_nano, 1_200_000);
+    }
+
+    #[test]
+    fn test_parse_zipkin_json_spans_invalid_ids() {
+        for (trace_id, span_id, field) in [
+            ("010101", "0202020202020202", "traceId"),
+            ("0101010101010101", "02020202", "id"),
+            ("0101010101010101", "not-hex-not-hex!", "id"),
+        ] {
+            let payload = json!([{"traceId": trace_id, "id": span_id}]).to_string();
+            let error = parse_zipkin_json_spans(payload.as_bytes()).unwrap_err();
+            assert!(
+                matches!(error, OtlpJsonError::InvalidField { field: invalid_field, .. } if invalid_field == field)
+            );
+        }
+        let error = parse_zipkin_json_spans(b"{}").unwrap_err();
+        assert!(matches!(error, OtlpJsonError::Json(_)));
+    }
+
+    #[test]
+    fn test_zipkin_span_from_span() {
+        let span: Span = serde_json::from_value(json!({
+            "trace_id": "AQEBAQEBAQEBAQEBAQEBAQ==",
+            "trace_state": null,
+            "service_name": "frontend",
+            "resource_attributes": {},
+            "resource_dropped_attributes_count": 0,
+            "scope_name": null,
+            "scope_version": null,
+            "scope_attributes": {},
+            "scope_dropped_attributes_count": 0,
+            "span_id": "AgICAgICAgI=",
+            "span_kind": 3,
+            "span_name": "get /api",
+            "span_fingerprint": null,
+            "span_start_timestamp_nanos": 1_000_000,
+            "span_end_timestamp_nanos": 1_500_000,
+            "span_start_timestamp_secs": 0,
+            "span_duration_millis": 0,
+            "span_attributes": {
+                "http.status_code": 500,
+                "peer.service": "backend",
+                "net.peer.port": 8080,
+            },
+            "span_dropped_attributes_count": 0,
+            "span_dropped_events_count": 0,
+            "span_dropped_links_count": 0,
+            "span_status": {"code": 2, "message": "timeout"},
+            "parent_span_id": "0303030303030303",
+            "events": [{
+                "event_timestamp_nanos": 1_200_000,
+                "event_name": "wr",
+                "event_attributes": {},
+                "event_dropped_attributes_count": 0,
+            }],
+        }))
+        .unwrap();
+        assert_eq!(span.trace_id, TraceId::new([1; 16]));
+
+        let zipkin_span = ZipkinSpan::from_span(span);
+        assert_eq!(
+            serde_json::to_value(zipkin_span).unwrap(),
+            json!({
+                "traceId": "01010101010101010101010101010101",
+                "id": "0202020202020202",
+                "parentId": "0303030303030303",
+                "name": "get /api",
+                "kind": "CLIENT",
+                "timestamp": 1_000,
+                "duration": 500,
+                "localEndpoint": {"serviceName": "frontend"},
+                "remoteEndpoint": {"serviceName": "backend", "port": 8080},
+                "annotations": [{"timestamp": 1_200, "value": "wr"}],
+                "tags": {"error": "timeout", "http.status_code": "500"},
+            })
+        );
+    }
+}
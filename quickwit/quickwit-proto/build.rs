@@ -17,11 +17,15 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::env;
 use std::path::PathBuf;
 
 use glob::glob;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // The file descriptor sets are served by the gRPC reflection service.
+    let descriptor_dir_path = PathBuf::from(env::var("OUT_DIR")?);
+
     // Quickwit proto
     let protos = find_protos("protos/quickwit");
 
@@ -43,6 +47,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         )
         .type_attribute("OutputFormat", "#[serde(rename_all = \"snake_case\")]")
         .type_attribute("SortOrder", "#[serde(rename_all = \"lowercase\")]")
+        .file_descriptor_set_path(descriptor_dir_path.join("quickwit_descriptor.bin"))
         .out_dir("src/")
         .compile_with_config(prost_config, &protos, &["protos/quickwit"])?;
 
@@ -53,6 +58,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     prost_config.type_attribute("Operation", "#[derive(Eq, Ord, PartialOrd)]");
 
     tonic_build::configure()
+        .file_descriptor_set_path(descriptor_dir_path.join("jaeger_descriptor.bin"))
         .out_dir("src/")
         .compile_with_config(
             prost_config,
@@ -67,6 +73,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let protos = find_protos("protos/third-party/otlp");
     tonic_build::configure()
         .type_attribute(".", "#[derive(Serialize, Deserialize)]")
+        .file_descriptor_set_path(descriptor_dir_path.join("otlp_descriptor.bin"))
         .out_dir("src/")
         .compile_with_config(prost_config, &protos, &["protos/third-party/otlp"])?;
     Ok(())
@@ -24,9 +24,18 @@
 use anyhow::anyhow;
 use ulid::Ulid;
 mod quickwit;
+mod quickwit_cluster_api;
 mod quickwit_indexing_api;
 mod quickwit_metastore_api;
 
+/// Encoded file descriptor set of the Quickwit protos.
+pub const FILE_DESCRIPTOR_SET: &[u8] =
+    include_bytes!(concat!(env!("OUT_DIR"), "/quickwit_descriptor.bin"));
+
+pub mod cluster_api {
+    pub use crate::quickwit_cluster_api::*;
+}
+
 pub mod indexing_api {
     pub use crate::quickwit_indexing_api::*;
 }
@@ -36,6 +45,10 @@ pub mod metastore_api {
 }
 
 pub mod jaeger {
+    /// Encoded file descriptor set of the Jaeger protos.
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        include_bytes!(concat!(env!("OUT_DIR"), "/jaeger_descriptor.bin"));
+
     pub mod api_v2 {
         include!("jaeger.api_v2.rs");
     }
@@ -47,6 +60,10 @@ pub mod jaeger {
 }
 
 pub mod opentelemetry {
+    /// Encoded file descriptor set of the OpenTelemetry protos.
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        include_bytes!(concat!(env!("OUT_DIR"), "/otlp_descriptor.bin"));
+
     #[cfg(not(doctest))]
     pub mod proto {
 
@@ -56,11 +73,11 @@ pub mod opentelemetry {
                     include!("opentelemetry.proto.collector.logs.v1.rs");
                 }
             }
-            // pub mod metrics {
-            //     pub mod v1 {
-            //         include!("opentelemetry.proto.collector.metrics.v1.rs");
-            //     }
-            // }
+            pub mod metrics {
+                pub mod v1 {
+                    include!("opentelemetry.proto.collector.metrics.v1.rs");
+                }
+            }
             pub mod trace {
                 pub mod v1 {
                     include!("opentelemetry.proto.collector.trace.v1.rs");
@@ -77,14 +94,11 @@ pub mod opentelemetry {
                 include!("opentelemetry.proto.logs.v1.rs");
             }
         }
-        // pub mod metrics {
-        //     pub mod experimental {
-        //         include!("opentelemetry.proto.metrics.experimental.rs");
-        //     }
-        //     pub mod v1 {
-        //         tonic::include_proto!("opentelemetry.proto.metrics.v1");
-        //     }
-        // }
+        pub mod metrics {
+            pub mod v1 {
+                include!("opentelemetry.proto.metrics.v1.rs");
+            }
+        }
         pub mod resource {
             pub mod v1 {
                 include!("opentelemetry.proto.resource.v1.rs");
@@ -125,10 +139,13 @@ use quickwit_query::query_ast::QueryAst;
 #[derive(Clone, Copy)]
 pub enum ServiceErrorCode {
     BadRequest,
+    Conflict,
     Internal,
     MethodNotAllowed,
     NotFound,
     RateLimited,
+    Unauthorized,
+    Forbidden,
     Unavailable,
     UnsupportedMediaType,
     NotSupportedYet, //< Used for API that is available in elasticsearch but is not yet available in Quickwit.
@@ -138,10 +155,13 @@ impl ServiceErrorCode {
     pub fn to_grpc_status_code(self) -> tonic::Code {
         match self {
             ServiceErrorCode::BadRequest => tonic::Code::InvalidArgument,
+            ServiceErrorCode::Conflict => tonic::Code::Aborted,
             ServiceErrorCode::Internal => tonic::Code::Internal,
             ServiceErrorCode::MethodNotAllowed => tonic::Code::InvalidArgument,
             ServiceErrorCode::NotFound => tonic::Code::NotFound,
             ServiceErrorCode::RateLimited => tonic::Code::ResourceExhausted,
+            ServiceErrorCode::Unauthorized => tonic::Code::Unauthenticated,
+            ServiceErrorCode::Forbidden => tonic::Code::PermissionDenied,
             ServiceErrorCode::Unavailable => tonic::Code::Unavailable,
             ServiceErrorCode::UnsupportedMediaType => tonic::Code::InvalidArgument,
             ServiceErrorCode::NotSupportedYet => tonic::Code::Unimplemented,
@@ -150,10 +170,13 @@ impl ServiceErrorCode {
     pub fn to_http_status_code(self) -> http::StatusCode {
         match self {
             ServiceErrorCode::BadRequest => http::StatusCode::BAD_REQUEST,
+            ServiceErrorCode::Conflict => http::StatusCode::CONFLICT,
             ServiceErrorCode::Internal => http::StatusCode::INTERNAL_SERVER_ERROR,
             ServiceErrorCode::MethodNotAllowed => http::StatusCode::METHOD_NOT_ALLOWED,
             ServiceErrorCode::NotFound => http::StatusCode::NOT_FOUND,
             ServiceErrorCode::RateLimited => http::StatusCode::TOO_MANY_REQUESTS,
+            ServiceErrorCode::Unauthorized => http::StatusCode::UNAUTHORIZED,
+            ServiceErrorCode::Forbidden => http::StatusCode::FORBIDDEN,
             ServiceErrorCode::Unavailable => http::StatusCode::SERVICE_UNAVAILABLE,
             ServiceErrorCode::UnsupportedMediaType => http::StatusCode::UNSUPPORTED_MEDIA_TYPE,
             ServiceErrorCode::NotSupportedYet => http::StatusCode::NOT_IMPLEMENTED,
@@ -274,7 +297,8 @@ impl<'a> Extractor for MutMetadataMap<'a> {
     }
 }
 
-/// [`tonic::service::interceptor::Interceptor`] which injects the span context into [`tonic::metadata::MetadataMap`].
+/// [`tonic::service::interceptor::Interceptor`] which injects the span context and the cluster key
+/// into [`tonic::metadata::MetadataMap`].
 #[derive(Clone, Debug)]
 pub struct SpanContextInterceptor;
 
@@ -286,6 +310,7 @@ impl Interceptor for SpanContextInterceptor {
                 &mut MutMetadataMap(request.metadata_mut()),
             )
         });
+        quickwit_common::tower::inject_cluster_key_metadata(request.metadata_mut());
         Ok(request)
     }
 }
@@ -351,6 +351,14 @@ pub struct SearchStreamRequest {
     /// Fields to extract snippet on.
     #[prost(string, repeated, tag = "10")]
     pub snippet_fields: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+    /// If true, the splits are searched and their results returned in increasing split ID order, so
+    /// that an interrupted stream can be resumed with `start_after_split_id`.
+    #[prost(bool, tag = "12")]
+    pub sort_by_split_id: bool,
+    /// If set, only the splits with an ID greater than this one are searched. Only supported with
+    /// `sort_by_split_id`.
+    #[prost(string, optional, tag = "13")]
+    pub start_after_split_id: ::core::option::Option<::prost::alloc::string::String>,
 }
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
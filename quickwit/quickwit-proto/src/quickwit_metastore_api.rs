@@ -26,6 +26,18 @@ pub struct ListIndexesMetadatasResponse {
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateIndexConfigRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub index_config_serialized_json: ::prost::alloc::string::String,
+    /// Version the index config is expected to be at. The check is skipped if absent.
+    #[prost(uint64, optional, tag = "3")]
+    pub expected_config_version: ::core::option::Option<u64>,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct DeleteIndexRequest {
     #[prost(string, tag = "1")]
     pub index_uid: ::prost::alloc::string::String,
@@ -41,6 +53,37 @@ pub struct DeleteIndexResponse {}
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TrashIndexRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct TrashIndexResponse {}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RestoreIndexRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetMergesPausedRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(bool, tag = "2")]
+    pub paused: bool,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct SetMergesPausedResponse {}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct IndexMetadataRequest {
     #[prost(string, tag = "1")]
     pub index_id: ::prost::alloc::string::String,
@@ -76,6 +119,25 @@ pub struct ListSplitsResponse {
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListSplitsPageRequest {
+    #[prost(string, tag = "1")]
+    pub filter_json: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub page_size: u64,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListSplitsPageResponse {
+    #[prost(string, tag = "1")]
+    pub splits_serialized_json: ::prost::alloc::string::String,
+    /// Split ID to resume listing from. Absent if this is the last page.
+    #[prost(string, optional, tag = "2")]
+    pub next_page_token: ::core::option::Option<::prost::alloc::string::String>,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct StageSplitsRequest {
     #[prost(string, tag = "1")]
     pub index_uid: ::prost::alloc::string::String,
@@ -100,6 +162,13 @@ pub struct PublishSplitsRequest {
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct PublishSplitsAcrossIndexesRequest {
+    #[prost(message, repeated, tag = "1")]
+    pub publications: ::prost::alloc::vec::Vec<PublishSplitsRequest>,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct MarkSplitsForDeletionRequest {
     #[prost(string, tag = "2")]
     pub index_uid: ::prost::alloc::string::String,
@@ -160,6 +229,17 @@ pub struct ResetSourceCheckpointRequest {
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
 #[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RewindSourceCheckpointRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(string, tag = "2")]
+    pub source_id: ::prost::alloc::string::String,
+    #[prost(string, tag = "3")]
+    pub target_checkpoint_serialized_json: ::prost::alloc::string::String,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct SourceResponse {}
 #[derive(Serialize, Deserialize, utoipa::ToSchema)]
 #[allow(clippy::derive_partial_eq_without_eq)]
@@ -249,6 +329,73 @@ pub struct ListDeleteTasksResponse {
     #[prost(message, repeated, tag = "1")]
     pub delete_tasks: ::prost::alloc::vec::Vec<DeleteTask>,
 }
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct CancelDeleteTaskRequest {
+    #[prost(string, tag = "1")]
+    pub index_uid: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "2")]
+    pub opstamp: u64,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AppendAuditEventRequest {
+    #[prost(string, tag = "1")]
+    pub audit_event_serialized_json: ::prost::alloc::string::String,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct AppendAuditEventResponse {}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListAuditEventsRequest {
+    #[prost(string, tag = "1")]
+    pub index_id: ::prost::alloc::string::String,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListAuditEventsResponse {
+    #[prost(string, tag = "1")]
+    pub audit_events_serialized_json: ::prost::alloc::string::String,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClusterSettingsRequest {}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ClusterSettingsResponse {
+    #[prost(string, tag = "1")]
+    pub cluster_settings_serialized_json: ::prost::alloc::string::String,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateClusterSettingsRequest {
+    #[prost(string, tag = "1")]
+    pub cluster_settings_serialized_json: ::prost::alloc::string::String,
+}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct UpdateClusterSettingsResponse {}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListTenantsRequest {}
+#[derive(Serialize, Deserialize, utoipa::ToSchema)]
+#[allow(clippy::derive_partial_eq_without_eq)]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct ListTenantsResponse {
+    #[prost(string, repeated, tag = "1")]
+    pub tenant_ids: ::prost::alloc::vec::Vec<::prost::alloc::string::String>,
+}
 /// Generated client implementations.
 pub mod metastore_api_service_client {
     #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
@@ -381,6 +528,26 @@ pub mod metastore_api_service_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// Updates an index config.
+        pub async fn update_index_config(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateIndexConfigRequest>,
+        ) -> Result<tonic::Response<super::IndexMetadataResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit_metastore_api.MetastoreApiService/update_index_config",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
         /// Deletes an index
         pub async fn delete_index(
             &mut self,
@@ -401,6 +568,86 @@ pub mod metastore_api_service_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// Moves an index to the trash.
+        pub async fn trash_index(
+            &mut self,
+            request: impl tonic::IntoRequest<super::TrashIndexRequest>,
+        ) -> Result<tonic::Response<super::TrashIndexResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit_metastore_api.MetastoreApiService/trash_index",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Takes an index out of the trash.
+        pub async fn restore_index(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RestoreIndexRequest>,
+        ) -> Result<tonic::Response<super::IndexMetadataResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit_metastore_api.MetastoreApiService/restore_index",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Gets the metadatas of the trashed indexes.
+        pub async fn list_trashed_indexes_metadatas(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListIndexesMetadatasRequest>,
+        ) -> Result<tonic::Response<super::ListIndexesMetadatasResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit_metastore_api.MetastoreApiService/list_trashed_indexes_metadatas",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Pauses or resumes the merges of an index.
+        pub async fn set_merges_paused(
+            &mut self,
+            request: impl tonic::IntoRequest<super::SetMergesPausedRequest>,
+        ) -> Result<tonic::Response<super::SetMergesPausedResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit_metastore_api.MetastoreApiService/set_merges_paused",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
         /// Gets all splits from index.
         pub async fn list_all_splits(
             &mut self,
@@ -441,6 +688,26 @@ pub mod metastore_api_service_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// Gets a page of splits from index, sorted by split ID.
+        pub async fn list_splits_page(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListSplitsPageRequest>,
+        ) -> Result<tonic::Response<super::ListSplitsPageResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit_metastore_api.MetastoreApiService/list_splits_page",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
         /// Stages several splits.
         pub async fn stage_splits(
             &mut self,
@@ -481,6 +748,26 @@ pub mod metastore_api_service_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// Publishes splits to several indexes atomically.
+        pub async fn publish_splits_across_indexes(
+            &mut self,
+            request: impl tonic::IntoRequest<super::PublishSplitsAcrossIndexesRequest>,
+        ) -> Result<tonic::Response<super::SplitResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit_metastore_api.MetastoreApiService/publish_splits_across_indexes",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
         /// Marks splits for deletion.
         pub async fn mark_splits_for_deletion(
             &mut self,
@@ -601,6 +888,26 @@ pub mod metastore_api_service_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// Rewinds source checkpoint.
+        pub async fn rewind_source_checkpoint(
+            &mut self,
+            request: impl tonic::IntoRequest<super::RewindSourceCheckpointRequest>,
+        ) -> Result<tonic::Response<super::SourceResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit_metastore_api.MetastoreApiService/rewind_source_checkpoint",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
         /// Gets last opstamp for a given `index_id`.
         pub async fn last_delete_opstamp(
             &mut self,
@@ -684,6 +991,26 @@ pub mod metastore_api_service_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
+        /// Cancels a delete task that has not been applied to any split yet.
+        pub async fn cancel_delete_task(
+            &mut self,
+            request: impl tonic::IntoRequest<super::CancelDeleteTaskRequest>,
+        ) -> Result<tonic::Response<super::DeleteTask>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit_metastore_api.MetastoreApiService/cancel_delete_task",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
         /// / Lists splits with `split.delete_opstamp` < `delete_opstamp` for a given `index_id`.
         pub async fn list_stale_splits(
             &mut self,
@@ -704,13 +1031,113 @@ pub mod metastore_api_service_client {
             );
             self.inner.unary(request.into_request(), path, codec).await
         }
-    }
-}
-/// Generated server implementations.
-pub mod metastore_api_service_server {
-    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
-    use tonic::codegen::*;
-    /// Generated trait containing gRPC methods that should be implemented for use with MetastoreApiServiceServer.
+        /// Appends an event to the audit log of an index.
+        pub async fn append_audit_event(
+            &mut self,
+            request: impl tonic::IntoRequest<super::AppendAuditEventRequest>,
+        ) -> Result<tonic::Response<super::AppendAuditEventResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit_metastore_api.MetastoreApiService/append_audit_event",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Lists the audit events of an index, oldest first.
+        pub async fn list_audit_events(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListAuditEventsRequest>,
+        ) -> Result<tonic::Response<super::ListAuditEventsResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit_metastore_api.MetastoreApiService/list_audit_events",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Returns the cluster settings.
+        pub async fn cluster_settings(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ClusterSettingsRequest>,
+        ) -> Result<tonic::Response<super::ClusterSettingsResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit_metastore_api.MetastoreApiService/cluster_settings",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Replaces the cluster settings.
+        pub async fn update_cluster_settings(
+            &mut self,
+            request: impl tonic::IntoRequest<super::UpdateClusterSettingsRequest>,
+        ) -> Result<tonic::Response<super::UpdateClusterSettingsResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit_metastore_api.MetastoreApiService/update_cluster_settings",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+        /// Lists the tenants owning at least one index.
+        pub async fn list_tenants(
+            &mut self,
+            request: impl tonic::IntoRequest<super::ListTenantsRequest>,
+        ) -> Result<tonic::Response<super::ListTenantsResponse>, tonic::Status> {
+            self.inner
+                .ready()
+                .await
+                .map_err(|e| {
+                    tonic::Status::new(
+                        tonic::Code::Unknown,
+                        format!("Service was not ready: {}", e.into()),
+                    )
+                })?;
+            let codec = tonic::codec::ProstCodec::default();
+            let path = http::uri::PathAndQuery::from_static(
+                "/quickwit_metastore_api.MetastoreApiService/list_tenants",
+            );
+            self.inner.unary(request.into_request(), path, codec).await
+        }
+    }
+}
+/// Generated server implementations.
+pub mod metastore_api_service_server {
+    #![allow(unused_variables, dead_code, missing_docs, clippy::let_unit_value)]
+    use tonic::codegen::*;
+    /// Generated trait containing gRPC methods that should be implemented for use with MetastoreApiServiceServer.
     #[async_trait]
     pub trait MetastoreApiService: Send + Sync + 'static {
         /// Creates an index.
@@ -728,11 +1155,36 @@ pub mod metastore_api_service_server {
             &self,
             request: tonic::Request<super::ListIndexesMetadatasRequest>,
         ) -> Result<tonic::Response<super::ListIndexesMetadatasResponse>, tonic::Status>;
+        /// Updates an index config.
+        async fn update_index_config(
+            &self,
+            request: tonic::Request<super::UpdateIndexConfigRequest>,
+        ) -> Result<tonic::Response<super::IndexMetadataResponse>, tonic::Status>;
         /// Deletes an index
         async fn delete_index(
             &self,
             request: tonic::Request<super::DeleteIndexRequest>,
         ) -> Result<tonic::Response<super::DeleteIndexResponse>, tonic::Status>;
+        /// Moves an index to the trash.
+        async fn trash_index(
+            &self,
+            request: tonic::Request<super::TrashIndexRequest>,
+        ) -> Result<tonic::Response<super::TrashIndexResponse>, tonic::Status>;
+        /// Takes an index out of the trash.
+        async fn restore_index(
+            &self,
+            request: tonic::Request<super::RestoreIndexRequest>,
+        ) -> Result<tonic::Response<super::IndexMetadataResponse>, tonic::Status>;
+        /// Gets the metadatas of the trashed indexes.
+        async fn list_trashed_indexes_metadatas(
+            &self,
+            request: tonic::Request<super::ListIndexesMetadatasRequest>,
+        ) -> Result<tonic::Response<super::ListIndexesMetadatasResponse>, tonic::Status>;
+        /// Pauses or resumes the merges of an index.
+        async fn set_merges_paused(
+            &self,
+            request: tonic::Request<super::SetMergesPausedRequest>,
+        ) -> Result<tonic::Response<super::SetMergesPausedResponse>, tonic::Status>;
         /// Gets all splits from index.
         async fn list_all_splits(
             &self,
@@ -743,6 +1195,11 @@ pub mod metastore_api_service_server {
             &self,
             request: tonic::Request<super::ListSplitsRequest>,
         ) -> Result<tonic::Response<super::ListSplitsResponse>, tonic::Status>;
+        /// Gets a page of splits from index, sorted by split ID.
+        async fn list_splits_page(
+            &self,
+            request: tonic::Request<super::ListSplitsPageRequest>,
+        ) -> Result<tonic::Response<super::ListSplitsPageResponse>, tonic::Status>;
         /// Stages several splits.
         async fn stage_splits(
             &self,
@@ -753,6 +1210,11 @@ pub mod metastore_api_service_server {
             &self,
             request: tonic::Request<super::PublishSplitsRequest>,
         ) -> Result<tonic::Response<super::SplitResponse>, tonic::Status>;
+        /// Publishes splits to several indexes atomically.
+        async fn publish_splits_across_indexes(
+            &self,
+            request: tonic::Request<super::PublishSplitsAcrossIndexesRequest>,
+        ) -> Result<tonic::Response<super::SplitResponse>, tonic::Status>;
         /// Marks splits for deletion.
         async fn mark_splits_for_deletion(
             &self,
@@ -783,6 +1245,11 @@ pub mod metastore_api_service_server {
             &self,
             request: tonic::Request<super::ResetSourceCheckpointRequest>,
         ) -> Result<tonic::Response<super::SourceResponse>, tonic::Status>;
+        /// Rewinds source checkpoint.
+        async fn rewind_source_checkpoint(
+            &self,
+            request: tonic::Request<super::RewindSourceCheckpointRequest>,
+        ) -> Result<tonic::Response<super::SourceResponse>, tonic::Status>;
         /// Gets last opstamp for a given `index_id`.
         async fn last_delete_opstamp(
             &self,
@@ -806,11 +1273,41 @@ pub mod metastore_api_service_server {
             &self,
             request: tonic::Request<super::ListDeleteTasksRequest>,
         ) -> Result<tonic::Response<super::ListDeleteTasksResponse>, tonic::Status>;
+        /// Cancels a delete task that has not been applied to any split yet.
+        async fn cancel_delete_task(
+            &self,
+            request: tonic::Request<super::CancelDeleteTaskRequest>,
+        ) -> Result<tonic::Response<super::DeleteTask>, tonic::Status>;
         /// / Lists splits with `split.delete_opstamp` < `delete_opstamp` for a given `index_id`.
         async fn list_stale_splits(
             &self,
             request: tonic::Request<super::ListStaleSplitsRequest>,
         ) -> Result<tonic::Response<super::ListSplitsResponse>, tonic::Status>;
+        /// Appends an event to the audit log of an index.
+        async fn append_audit_event(
+            &self,
+            request: tonic::Request<super::AppendAuditEventRequest>,
+        ) -> Result<tonic::Response<super::AppendAuditEventResponse>, tonic::Status>;
+        /// Lists the audit events of an index, oldest first.
+        async fn list_audit_events(
+            &self,
+            request: tonic::Request<super::ListAuditEventsRequest>,
+        ) -> Result<tonic::Response<super::ListAuditEventsResponse>, tonic::Status>;
+        /// Returns the cluster settings.
+        async fn cluster_settings(
+            &self,
+            request: tonic::Request<super::ClusterSettingsRequest>,
+        ) -> Result<tonic::Response<super::ClusterSettingsResponse>, tonic::Status>;
+        /// Replaces the cluster settings.
+        async fn update_cluster_settings(
+            &self,
+            request: tonic::Request<super::UpdateClusterSettingsRequest>,
+        ) -> Result<tonic::Response<super::UpdateClusterSettingsResponse>, tonic::Status>;
+        /// Lists the tenants owning at least one index.
+        async fn list_tenants(
+            &self,
+            request: tonic::Request<super::ListTenantsRequest>,
+        ) -> Result<tonic::Response<super::ListTenantsResponse>, tonic::Status>;
     }
     #[derive(Debug)]
     pub struct MetastoreApiServiceServer<T: MetastoreApiService> {
@@ -991,25 +1488,25 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/quickwit_metastore_api.MetastoreApiService/delete_index" => {
+                "/quickwit_metastore_api.MetastoreApiService/update_index_config" => {
                     #[allow(non_camel_case_types)]
-                    struct delete_indexSvc<T: MetastoreApiService>(pub Arc<T>);
+                    struct update_index_configSvc<T: MetastoreApiService>(pub Arc<T>);
                     impl<
                         T: MetastoreApiService,
-                    > tonic::server::UnaryService<super::DeleteIndexRequest>
-                    for delete_indexSvc<T> {
-                        type Response = super::DeleteIndexResponse;
+                    > tonic::server::UnaryService<super::UpdateIndexConfigRequest>
+                    for update_index_configSvc<T> {
+                        type Response = super::IndexMetadataResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::DeleteIndexRequest>,
+                            request: tonic::Request<super::UpdateIndexConfigRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
                             let fut = async move {
-                                (*inner).delete_index(request).await
+                                (*inner).update_index_config(request).await
                             };
                             Box::pin(fut)
                         }
@@ -1019,7 +1516,7 @@ pub mod metastore_api_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = delete_indexSvc(inner);
+                        let method = update_index_configSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1031,25 +1528,25 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/quickwit_metastore_api.MetastoreApiService/list_all_splits" => {
+                "/quickwit_metastore_api.MetastoreApiService/delete_index" => {
                     #[allow(non_camel_case_types)]
-                    struct list_all_splitsSvc<T: MetastoreApiService>(pub Arc<T>);
+                    struct delete_indexSvc<T: MetastoreApiService>(pub Arc<T>);
                     impl<
                         T: MetastoreApiService,
-                    > tonic::server::UnaryService<super::ListAllSplitsRequest>
-                    for list_all_splitsSvc<T> {
-                        type Response = super::ListSplitsResponse;
+                    > tonic::server::UnaryService<super::DeleteIndexRequest>
+                    for delete_indexSvc<T> {
+                        type Response = super::DeleteIndexResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ListAllSplitsRequest>,
+                            request: tonic::Request<super::DeleteIndexRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
                             let fut = async move {
-                                (*inner).list_all_splits(request).await
+                                (*inner).delete_index(request).await
                             };
                             Box::pin(fut)
                         }
@@ -1059,7 +1556,7 @@ pub mod metastore_api_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = list_all_splitsSvc(inner);
+                        let method = delete_indexSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1071,24 +1568,26 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/quickwit_metastore_api.MetastoreApiService/list_splits" => {
+                "/quickwit_metastore_api.MetastoreApiService/trash_index" => {
                     #[allow(non_camel_case_types)]
-                    struct list_splitsSvc<T: MetastoreApiService>(pub Arc<T>);
+                    struct trash_indexSvc<T: MetastoreApiService>(pub Arc<T>);
                     impl<
                         T: MetastoreApiService,
-                    > tonic::server::UnaryService<super::ListSplitsRequest>
-                    for list_splitsSvc<T> {
-                        type Response = super::ListSplitsResponse;
+                    > tonic::server::UnaryService<super::TrashIndexRequest>
+                    for trash_indexSvc<T> {
+                        type Response = super::TrashIndexResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ListSplitsRequest>,
+                            request: tonic::Request<super::TrashIndexRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).list_splits(request).await };
+                            let fut = async move {
+                                (*inner).trash_index(request).await
+                            };
                             Box::pin(fut)
                         }
                     }
@@ -1097,7 +1596,7 @@ pub mod metastore_api_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = list_splitsSvc(inner);
+                        let method = trash_indexSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1109,25 +1608,25 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/quickwit_metastore_api.MetastoreApiService/stage_splits" => {
+                "/quickwit_metastore_api.MetastoreApiService/restore_index" => {
                     #[allow(non_camel_case_types)]
-                    struct stage_splitsSvc<T: MetastoreApiService>(pub Arc<T>);
+                    struct restore_indexSvc<T: MetastoreApiService>(pub Arc<T>);
                     impl<
                         T: MetastoreApiService,
-                    > tonic::server::UnaryService<super::StageSplitsRequest>
-                    for stage_splitsSvc<T> {
-                        type Response = super::SplitResponse;
+                    > tonic::server::UnaryService<super::RestoreIndexRequest>
+                    for restore_indexSvc<T> {
+                        type Response = super::IndexMetadataResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::StageSplitsRequest>,
+                            request: tonic::Request<super::RestoreIndexRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
                             let fut = async move {
-                                (*inner).stage_splits(request).await
+                                (*inner).restore_index(request).await
                             };
                             Box::pin(fut)
                         }
@@ -1137,7 +1636,7 @@ pub mod metastore_api_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = stage_splitsSvc(inner);
+                        let method = restore_indexSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1149,25 +1648,25 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/quickwit_metastore_api.MetastoreApiService/publish_splits" => {
+                "/quickwit_metastore_api.MetastoreApiService/list_trashed_indexes_metadatas" => {
                     #[allow(non_camel_case_types)]
-                    struct publish_splitsSvc<T: MetastoreApiService>(pub Arc<T>);
+                    struct list_trashed_indexes_metadatasSvc<T: MetastoreApiService>(pub Arc<T>);
                     impl<
                         T: MetastoreApiService,
-                    > tonic::server::UnaryService<super::PublishSplitsRequest>
-                    for publish_splitsSvc<T> {
-                        type Response = super::SplitResponse;
+                    > tonic::server::UnaryService<super::ListIndexesMetadatasRequest>
+                    for list_trashed_indexes_metadatasSvc<T> {
+                        type Response = super::ListIndexesMetadatasResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::PublishSplitsRequest>,
+                            request: tonic::Request<super::ListIndexesMetadatasRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
                             let fut = async move {
-                                (*inner).publish_splits(request).await
+                                (*inner).list_trashed_indexes_metadatas(request).await
                             };
                             Box::pin(fut)
                         }
@@ -1177,7 +1676,7 @@ pub mod metastore_api_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = publish_splitsSvc(inner);
+                        let method = list_trashed_indexes_metadatasSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1189,27 +1688,25 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/quickwit_metastore_api.MetastoreApiService/mark_splits_for_deletion" => {
+                "/quickwit_metastore_api.MetastoreApiService/set_merges_paused" => {
                     #[allow(non_camel_case_types)]
-                    struct mark_splits_for_deletionSvc<T: MetastoreApiService>(
-                        pub Arc<T>,
-                    );
+                    struct set_merges_pausedSvc<T: MetastoreApiService>(pub Arc<T>);
                     impl<
                         T: MetastoreApiService,
-                    > tonic::server::UnaryService<super::MarkSplitsForDeletionRequest>
-                    for mark_splits_for_deletionSvc<T> {
-                        type Response = super::SplitResponse;
+                    > tonic::server::UnaryService<super::SetMergesPausedRequest>
+                    for set_merges_pausedSvc<T> {
+                        type Response = super::SetMergesPausedResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::MarkSplitsForDeletionRequest>,
+                            request: tonic::Request<super::SetMergesPausedRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
                             let fut = async move {
-                                (*inner).mark_splits_for_deletion(request).await
+                                (*inner).set_merges_paused(request).await
                             };
                             Box::pin(fut)
                         }
@@ -1219,7 +1716,7 @@ pub mod metastore_api_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = mark_splits_for_deletionSvc(inner);
+                        let method = set_merges_pausedSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1231,25 +1728,25 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/quickwit_metastore_api.MetastoreApiService/delete_splits" => {
+                "/quickwit_metastore_api.MetastoreApiService/list_all_splits" => {
                     #[allow(non_camel_case_types)]
-                    struct delete_splitsSvc<T: MetastoreApiService>(pub Arc<T>);
+                    struct list_all_splitsSvc<T: MetastoreApiService>(pub Arc<T>);
                     impl<
                         T: MetastoreApiService,
-                    > tonic::server::UnaryService<super::DeleteSplitsRequest>
-                    for delete_splitsSvc<T> {
-                        type Response = super::SplitResponse;
+                    > tonic::server::UnaryService<super::ListAllSplitsRequest>
+                    for list_all_splitsSvc<T> {
+                        type Response = super::ListSplitsResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::DeleteSplitsRequest>,
+                            request: tonic::Request<super::ListAllSplitsRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
                             let fut = async move {
-                                (*inner).delete_splits(request).await
+                                (*inner).list_all_splits(request).await
                             };
                             Box::pin(fut)
                         }
@@ -1259,7 +1756,7 @@ pub mod metastore_api_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = delete_splitsSvc(inner);
+                        let method = list_all_splitsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1271,24 +1768,24 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/quickwit_metastore_api.MetastoreApiService/add_source" => {
+                "/quickwit_metastore_api.MetastoreApiService/list_splits" => {
                     #[allow(non_camel_case_types)]
-                    struct add_sourceSvc<T: MetastoreApiService>(pub Arc<T>);
+                    struct list_splitsSvc<T: MetastoreApiService>(pub Arc<T>);
                     impl<
                         T: MetastoreApiService,
-                    > tonic::server::UnaryService<super::AddSourceRequest>
-                    for add_sourceSvc<T> {
-                        type Response = super::SourceResponse;
+                    > tonic::server::UnaryService<super::ListSplitsRequest>
+                    for list_splitsSvc<T> {
+                        type Response = super::ListSplitsResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::AddSourceRequest>,
+                            request: tonic::Request<super::ListSplitsRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
-                            let fut = async move { (*inner).add_source(request).await };
+                            let fut = async move { (*inner).list_splits(request).await };
                             Box::pin(fut)
                         }
                     }
@@ -1297,7 +1794,7 @@ pub mod metastore_api_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = add_sourceSvc(inner);
+                        let method = list_splitsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1309,25 +1806,25 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/quickwit_metastore_api.MetastoreApiService/toggle_source" => {
+                "/quickwit_metastore_api.MetastoreApiService/list_splits_page" => {
                     #[allow(non_camel_case_types)]
-                    struct toggle_sourceSvc<T: MetastoreApiService>(pub Arc<T>);
+                    struct list_splits_pageSvc<T: MetastoreApiService>(pub Arc<T>);
                     impl<
                         T: MetastoreApiService,
-                    > tonic::server::UnaryService<super::ToggleSourceRequest>
-                    for toggle_sourceSvc<T> {
-                        type Response = super::SourceResponse;
+                    > tonic::server::UnaryService<super::ListSplitsPageRequest>
+                    for list_splits_pageSvc<T> {
+                        type Response = super::ListSplitsPageResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ToggleSourceRequest>,
+                            request: tonic::Request<super::ListSplitsPageRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
                             let fut = async move {
-                                (*inner).toggle_source(request).await
+                                (*inner).list_splits_page(request).await
                             };
                             Box::pin(fut)
                         }
@@ -1337,7 +1834,7 @@ pub mod metastore_api_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = toggle_sourceSvc(inner);
+                        let method = list_splits_pageSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1349,25 +1846,25 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/quickwit_metastore_api.MetastoreApiService/delete_source" => {
+                "/quickwit_metastore_api.MetastoreApiService/stage_splits" => {
                     #[allow(non_camel_case_types)]
-                    struct delete_sourceSvc<T: MetastoreApiService>(pub Arc<T>);
+                    struct stage_splitsSvc<T: MetastoreApiService>(pub Arc<T>);
                     impl<
                         T: MetastoreApiService,
-                    > tonic::server::UnaryService<super::DeleteSourceRequest>
-                    for delete_sourceSvc<T> {
-                        type Response = super::SourceResponse;
+                    > tonic::server::UnaryService<super::StageSplitsRequest>
+                    for stage_splitsSvc<T> {
+                        type Response = super::SplitResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::DeleteSourceRequest>,
+                            request: tonic::Request<super::StageSplitsRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
                             let fut = async move {
-                                (*inner).delete_source(request).await
+                                (*inner).stage_splits(request).await
                             };
                             Box::pin(fut)
                         }
@@ -1377,7 +1874,7 @@ pub mod metastore_api_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = delete_sourceSvc(inner);
+                        let method = stage_splitsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1389,27 +1886,25 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/quickwit_metastore_api.MetastoreApiService/reset_source_checkpoint" => {
+                "/quickwit_metastore_api.MetastoreApiService/publish_splits" => {
                     #[allow(non_camel_case_types)]
-                    struct reset_source_checkpointSvc<T: MetastoreApiService>(
-                        pub Arc<T>,
-                    );
+                    struct publish_splitsSvc<T: MetastoreApiService>(pub Arc<T>);
                     impl<
                         T: MetastoreApiService,
-                    > tonic::server::UnaryService<super::ResetSourceCheckpointRequest>
-                    for reset_source_checkpointSvc<T> {
-                        type Response = super::SourceResponse;
+                    > tonic::server::UnaryService<super::PublishSplitsRequest>
+                    for publish_splitsSvc<T> {
+                        type Response = super::SplitResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::ResetSourceCheckpointRequest>,
+                            request: tonic::Request<super::PublishSplitsRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
                             let fut = async move {
-                                (*inner).reset_source_checkpoint(request).await
+                                (*inner).publish_splits(request).await
                             };
                             Box::pin(fut)
                         }
@@ -1419,7 +1914,7 @@ pub mod metastore_api_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = reset_source_checkpointSvc(inner);
+                        let method = publish_splitsSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1431,25 +1926,25 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/quickwit_metastore_api.MetastoreApiService/last_delete_opstamp" => {
+                "/quickwit_metastore_api.MetastoreApiService/publish_splits_across_indexes" => {
                     #[allow(non_camel_case_types)]
-                    struct last_delete_opstampSvc<T: MetastoreApiService>(pub Arc<T>);
+                    struct publish_splits_across_indexesSvc<T: MetastoreApiService>(pub Arc<T>);
                     impl<
                         T: MetastoreApiService,
-                    > tonic::server::UnaryService<super::LastDeleteOpstampRequest>
-                    for last_delete_opstampSvc<T> {
-                        type Response = super::LastDeleteOpstampResponse;
+                    > tonic::server::UnaryService<super::PublishSplitsAcrossIndexesRequest>
+                    for publish_splits_across_indexesSvc<T> {
+                        type Response = super::SplitResponse;
                         type Future = BoxFuture<
                             tonic::Response<Self::Response>,
                             tonic::Status,
                         >;
                         fn call(
                             &mut self,
-                            request: tonic::Request<super::LastDeleteOpstampRequest>,
+                            request: tonic::Request<super::PublishSplitsAcrossIndexesRequest>,
                         ) -> Self::Future {
                             let inner = self.0.clone();
                             let fut = async move {
-                                (*inner).last_delete_opstamp(request).await
+                                (*inner).publish_splits_across_indexes(request).await
                             };
                             Box::pin(fut)
                         }
@@ -1459,7 +1954,7 @@ pub mod metastore_api_service_server {
                     let inner = self.inner.clone();
                     let fut = async move {
                         let inner = inner.0;
-                        let method = last_delete_opstampSvc(inner);
+                        let method = publish_splits_across_indexesSvc(inner);
                         let codec = tonic::codec::ProstCodec::default();
                         let mut grpc = tonic::server::Grpc::new(codec)
                             .apply_compression_config(
@@ -1471,11 +1966,333 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
-                "/quickwit_metastore_api.MetastoreApiService/create_delete_task" => {
+                "/quickwit_metastore_api.MetastoreApiService/mark_splits_for_deletion" => {
                     #[allow(non_camel_case_types)]
-                    struct create_delete_taskSvc<T: MetastoreApiService>(pub Arc<T>);
-                    impl<
-                        T: MetastoreApiService,
+                    struct mark_splits_for_deletionSvc<T: MetastoreApiService>(
+                        pub Arc<T>,
+                    );
+                    impl<
+                        T: MetastoreApiService,
+                    > tonic::server::UnaryService<super::MarkSplitsForDeletionRequest>
+                    for mark_splits_for_deletionSvc<T> {
+                        type Response = super::SplitResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::MarkSplitsForDeletionRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).mark_splits_for_deletion(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = mark_splits_for_deletionSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit_metastore_api.MetastoreApiService/delete_splits" => {
+                    #[allow(non_camel_case_types)]
+                    struct delete_splitsSvc<T: MetastoreApiService>(pub Arc<T>);
+                    impl<
+                        T: MetastoreApiService,
+                    > tonic::server::UnaryService<super::DeleteSplitsRequest>
+                    for delete_splitsSvc<T> {
+                        type Response = super::SplitResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteSplitsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).delete_splits(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = delete_splitsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit_metastore_api.MetastoreApiService/add_source" => {
+                    #[allow(non_camel_case_types)]
+                    struct add_sourceSvc<T: MetastoreApiService>(pub Arc<T>);
+                    impl<
+                        T: MetastoreApiService,
+                    > tonic::server::UnaryService<super::AddSourceRequest>
+                    for add_sourceSvc<T> {
+                        type Response = super::SourceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AddSourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move { (*inner).add_source(request).await };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = add_sourceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit_metastore_api.MetastoreApiService/toggle_source" => {
+                    #[allow(non_camel_case_types)]
+                    struct toggle_sourceSvc<T: MetastoreApiService>(pub Arc<T>);
+                    impl<
+                        T: MetastoreApiService,
+                    > tonic::server::UnaryService<super::ToggleSourceRequest>
+                    for toggle_sourceSvc<T> {
+                        type Response = super::SourceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ToggleSourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).toggle_source(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = toggle_sourceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit_metastore_api.MetastoreApiService/delete_source" => {
+                    #[allow(non_camel_case_types)]
+                    struct delete_sourceSvc<T: MetastoreApiService>(pub Arc<T>);
+                    impl<
+                        T: MetastoreApiService,
+                    > tonic::server::UnaryService<super::DeleteSourceRequest>
+                    for delete_sourceSvc<T> {
+                        type Response = super::SourceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::DeleteSourceRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).delete_source(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = delete_sourceSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit_metastore_api.MetastoreApiService/reset_source_checkpoint" => {
+                    #[allow(non_camel_case_types)]
+                    struct reset_source_checkpointSvc<T: MetastoreApiService>(
+                        pub Arc<T>,
+                    );
+                    impl<
+                        T: MetastoreApiService,
+                    > tonic::server::UnaryService<super::ResetSourceCheckpointRequest>
+                    for reset_source_checkpointSvc<T> {
+                        type Response = super::SourceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ResetSourceCheckpointRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).reset_source_checkpoint(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = reset_source_checkpointSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit_metastore_api.MetastoreApiService/rewind_source_checkpoint" => {
+                    #[allow(non_camel_case_types)]
+                    struct rewind_source_checkpointSvc<T: MetastoreApiService>(pub Arc<T>);
+                    impl<
+                        T: MetastoreApiService,
+                    > tonic::server::UnaryService<super::RewindSourceCheckpointRequest>
+                    for rewind_source_checkpointSvc<T> {
+                        type Response = super::SourceResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::RewindSourceCheckpointRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).rewind_source_checkpoint(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = rewind_source_checkpointSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit_metastore_api.MetastoreApiService/last_delete_opstamp" => {
+                    #[allow(non_camel_case_types)]
+                    struct last_delete_opstampSvc<T: MetastoreApiService>(pub Arc<T>);
+                    impl<
+                        T: MetastoreApiService,
+                    > tonic::server::UnaryService<super::LastDeleteOpstampRequest>
+                    for last_delete_opstampSvc<T> {
+                        type Response = super::LastDeleteOpstampResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::LastDeleteOpstampRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).last_delete_opstamp(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = last_delete_opstampSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit_metastore_api.MetastoreApiService/create_delete_task" => {
+                    #[allow(non_camel_case_types)]
+                    struct create_delete_taskSvc<T: MetastoreApiService>(pub Arc<T>);
+                    impl<
+                        T: MetastoreApiService,
                     > tonic::server::UnaryService<super::DeleteQuery>
                     for create_delete_taskSvc<T> {
                         type Response = super::DeleteTask;
@@ -1596,6 +2413,46 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/quickwit_metastore_api.MetastoreApiService/cancel_delete_task" => {
+                    #[allow(non_camel_case_types)]
+                    struct cancel_delete_taskSvc<T: MetastoreApiService>(pub Arc<T>);
+                    impl<
+                        T: MetastoreApiService,
+                    > tonic::server::UnaryService<super::CancelDeleteTaskRequest>
+                    for cancel_delete_taskSvc<T> {
+                        type Response = super::DeleteTask;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::CancelDeleteTaskRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).cancel_delete_task(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = cancel_delete_taskSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 "/quickwit_metastore_api.MetastoreApiService/list_stale_splits" => {
                     #[allow(non_camel_case_types)]
                     struct list_stale_splitsSvc<T: MetastoreApiService>(pub Arc<T>);
@@ -1636,6 +2493,206 @@ pub mod metastore_api_service_server {
                     };
                     Box::pin(fut)
                 }
+                "/quickwit_metastore_api.MetastoreApiService/append_audit_event" => {
+                    #[allow(non_camel_case_types)]
+                    struct append_audit_eventSvc<T: MetastoreApiService>(pub Arc<T>);
+                    impl<
+                        T: MetastoreApiService,
+                    > tonic::server::UnaryService<super::AppendAuditEventRequest>
+                    for append_audit_eventSvc<T> {
+                        type Response = super::AppendAuditEventResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::AppendAuditEventRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).append_audit_event(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = append_audit_eventSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit_metastore_api.MetastoreApiService/list_audit_events" => {
+                    #[allow(non_camel_case_types)]
+                    struct list_audit_eventsSvc<T: MetastoreApiService>(pub Arc<T>);
+                    impl<
+                        T: MetastoreApiService,
+                    > tonic::server::UnaryService<super::ListAuditEventsRequest>
+                    for list_audit_eventsSvc<T> {
+                        type Response = super::ListAuditEventsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListAuditEventsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).list_audit_events(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = list_audit_eventsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit_metastore_api.MetastoreApiService/cluster_settings" => {
+                    #[allow(non_camel_case_types)]
+                    struct cluster_settingsSvc<T: MetastoreApiService>(pub Arc<T>);
+                    impl<
+                        T: MetastoreApiService,
+                    > tonic::server::UnaryService<super::ClusterSettingsRequest>
+                    for cluster_settingsSvc<T> {
+                        type Response = super::ClusterSettingsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ClusterSettingsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).cluster_settings(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = cluster_settingsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit_metastore_api.MetastoreApiService/update_cluster_settings" => {
+                    #[allow(non_camel_case_types)]
+                    struct update_cluster_settingsSvc<T: MetastoreApiService>(pub Arc<T>);
+                    impl<
+                        T: MetastoreApiService,
+                    > tonic::server::UnaryService<super::UpdateClusterSettingsRequest>
+                    for update_cluster_settingsSvc<T> {
+                        type Response = super::UpdateClusterSettingsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::UpdateClusterSettingsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).update_cluster_settings(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = update_cluster_settingsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
+                "/quickwit_metastore_api.MetastoreApiService/list_tenants" => {
+                    #[allow(non_camel_case_types)]
+                    struct list_tenantsSvc<T: MetastoreApiService>(pub Arc<T>);
+                    impl<
+                        T: MetastoreApiService,
+                    > tonic::server::UnaryService<super::ListTenantsRequest>
+                    for list_tenantsSvc<T> {
+                        type Response = super::ListTenantsResponse;
+                        type Future = BoxFuture<
+                            tonic::Response<Self::Response>,
+                            tonic::Status,
+                        >;
+                        fn call(
+                            &mut self,
+                            request: tonic::Request<super::ListTenantsRequest>,
+                        ) -> Self::Future {
+                            let inner = self.0.clone();
+                            let fut = async move {
+                                (*inner).list_tenants(request).await
+                            };
+                            Box::pin(fut)
+                        }
+                    }
+                    let accept_compression_encodings = self.accept_compression_encodings;
+                    let send_compression_encodings = self.send_compression_encodings;
+                    let inner = self.inner.clone();
+                    let fut = async move {
+                        let inner = inner.0;
+                        let method = list_tenantsSvc(inner);
+                        let codec = tonic::codec::ProstCodec::default();
+                        let mut grpc = tonic::server::Grpc::new(codec)
+                            .apply_compression_config(
+                                accept_compression_encodings,
+                                send_compression_encodings,
+                            );
+                        let res = grpc.unary(method, req).await;
+                        Ok(res)
+                    };
+                    Box::pin(fut)
+                }
                 _ => {
                     Box::pin(async move {
                         Ok(
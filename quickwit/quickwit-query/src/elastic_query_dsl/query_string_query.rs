@@ -42,6 +42,12 @@ pub(crate) struct QueryStringQuery {
     default_operator: BooleanOperand,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     boost: Option<NotNaNf32>,
+    /// Accepted for compatibility with clients such as Grafana, but ignored.
+    #[serde(default, skip_serializing_if = "is_default")]
+    analyze_wildcard: bool,
+    /// Accepted for compatibility with clients such as Grafana, but ignored.
+    #[serde(default, skip_serializing_if = "is_default")]
+    lenient: bool,
 }
 
 impl ConvertableToQueryAst for QueryStringQuery {
@@ -61,6 +67,14 @@ mod tests {
     use crate::query_ast::{QueryAst, UserInputQuery};
     use crate::BooleanOperand;
 
+    #[test]
+    fn test_deserialize_query_string_query_grafana() {
+        let query_string_query: QueryStringQuery =
+            serde_json::from_str(r#"{"analyze_wildcard": true, "query": "level:error"}"#).unwrap();
+        assert_eq!(query_string_query.query, "level:error");
+        assert!(query_string_query.analyze_wildcard);
+    }
+
     #[test]
     fn test_build_query_string_query_with_default_field_non_empty() {
         let query_string_query = crate::elastic_query_dsl::QueryStringQuery {
@@ -68,6 +82,8 @@ mod tests {
             fields: Some(vec!["hello".to_string()]),
             default_operator: crate::BooleanOperand::Or,
             boost: None,
+            analyze_wildcard: false,
+            lenient: false,
         };
         let QueryAst::UserInput(user_input_query) = query_string_query.convert_to_query_ast().unwrap() else {
             panic!();
@@ -86,6 +102,8 @@ mod tests {
             fields: Some(Vec::new()),
             default_operator: crate::BooleanOperand::And,
             boost: None,
+            analyze_wildcard: false,
+            lenient: false,
         };
         let QueryAst::UserInput(user_input_query) = query_string_query.convert_to_query_ast().unwrap() else {
             panic!();
@@ -100,6 +118,8 @@ mod tests {
             fields: Some(Vec::new()),
             default_operator: crate::BooleanOperand::Or,
             boost: None,
+            analyze_wildcard: false,
+            lenient: false,
         };
         let QueryAst::UserInput(user_input_query) = query_string_query.convert_to_query_ast().unwrap() else {
             panic!();
@@ -115,6 +135,8 @@ mod tests {
             fields: None,
             default_operator: crate::BooleanOperand::Or,
             boost: None,
+            analyze_wildcard: false,
+            lenient: false,
         };
         let QueryAst::UserInput(user_input_query) = query_string_query.convert_to_query_ast().unwrap() else {
             panic!();
@@ -19,7 +19,10 @@
 
 use std::ops::Bound;
 
+use anyhow::Context;
 use serde::{Deserialize, Serialize};
+use tantivy::time::format_description::well_known::Rfc3339;
+use tantivy::time::OffsetDateTime;
 
 use crate::elastic_query_dsl::one_field_map::OneFieldMap;
 use crate::elastic_query_dsl::ConvertableToQueryAst;
@@ -40,6 +43,44 @@ pub struct RangeQueryParams {
     lte: Option<JsonLiteral>,
     #[serde(default)]
     boost: Option<NotNaNf32>,
+    /// Format of the bounds of a range over a datetime field. Only `epoch_millis` and
+    /// `epoch_second` change the interpretation of the bounds, other formats expect RFC 3339
+    /// bounds.
+    #[serde(default)]
+    format: Option<String>,
+}
+
+/// Converts an epoch timestamp, as a number or a string, into an RFC 3339 datetime.
+fn convert_epoch_to_rfc3339(
+    bound: JsonLiteral,
+    nanos_per_unit: i128,
+) -> anyhow::Result<JsonLiteral> {
+    let epoch_opt: Option<i64> = match &bound {
+        JsonLiteral::Number(number) => number.as_i64(),
+        JsonLiteral::String(text) => text.parse().ok(),
+        JsonLiteral::Bool(_) => None,
+    };
+    let epoch =
+        epoch_opt.with_context(|| format!("Expected an epoch timestamp, got {bound:?}."))?;
+    let date_time = OffsetDateTime::from_unix_timestamp_nanos(epoch as i128 * nanos_per_unit)
+        .with_context(|| format!("Epoch timestamp `{epoch}` is out of range."))?;
+    let rfc3339 = date_time.format(&Rfc3339)?;
+    Ok(JsonLiteral::String(rfc3339))
+}
+
+fn convert_bound(
+    bound_opt: Option<JsonLiteral>,
+    format_opt: Option<&str>,
+) -> anyhow::Result<Option<JsonLiteral>> {
+    let Some(bound) = bound_opt else {
+        return Ok(None);
+    };
+    let converted_bound = match format_opt {
+        Some("epoch_millis") => convert_epoch_to_rfc3339(bound, 1_000_000)?,
+        Some("epoch_second") => convert_epoch_to_rfc3339(bound, 1_000_000_000)?,
+        _ => bound,
+    };
+    Ok(Some(converted_bound))
 }
 
 pub type RangeQuery = OneFieldMap<RangeQueryParams>;
@@ -53,7 +94,13 @@ impl ConvertableToQueryAst for RangeQuery {
             lt,
             lte,
             boost,
+            format,
         } = self.value;
+        let format_opt = format.as_deref();
+        let gt = convert_bound(gt, format_opt)?;
+        let gte = convert_bound(gte, format_opt)?;
+        let lt = convert_bound(lt, format_opt)?;
+        let lte = convert_bound(lte, format_opt)?;
         let range_query_ast = crate::query_ast::RangeQuery {
             field,
             lower_bound: match (gt, gte) {
@@ -77,3 +124,70 @@ impl ConvertableToQueryAst for RangeQuery {
         Ok(ast.boost(boost))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::ops::Bound;
+
+    use super::RangeQuery;
+    use crate::elastic_query_dsl::ConvertableToQueryAst;
+    use crate::query_ast::QueryAst;
+    use crate::JsonLiteral;
+
+    #[test]
+    fn test_range_query_epoch_millis() {
+        let range_query: RangeQuery = serde_json::from_str(
+            r#"{"timestamp": {"gte": 1680000000000, "lte": "1680000060000", "format": "epoch_millis"}}"#,
+        )
+        .unwrap();
+        let QueryAst::Range(range_query_ast) = range_query.convert_to_query_ast().unwrap() else {
+            panic!()
+        };
+        assert_eq!(range_query_ast.field, "timestamp");
+        assert_eq!(
+            range_query_ast.lower_bound,
+            Bound::Included(JsonLiteral::String("2023-03-28T10:40:00Z".to_string()))
+        );
+        assert_eq!(
+            range_query_ast.upper_bound,
+            Bound::Included(JsonLiteral::String("2023-03-28T10:41:00Z".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_range_query_epoch_second() {
+        let range_query: RangeQuery =
+            serde_json::from_str(r#"{"timestamp": {"gt": 1680000000, "format": "epoch_second"}}"#)
+                .unwrap();
+        let QueryAst::Range(range_query_ast) = range_query.convert_to_query_ast().unwrap() else {
+            panic!()
+        };
+        assert_eq!(
+            range_query_ast.lower_bound,
+            Bound::Excluded(JsonLiteral::String("2023-03-28T10:40:00Z".to_string()))
+        );
+        assert_eq!(range_query_ast.upper_bound, Bound::Unbounded);
+
+        let range_query: RangeQuery =
+            serde_json::from_str(r#"{"timestamp": {"gt": "yesterday", "format": "epoch_second"}}"#)
+                .unwrap();
+        range_query.convert_to_query_ast().unwrap_err();
+    }
+
+    #[test]
+    fn test_range_query_without_format() {
+        let range_query: RangeQuery =
+            serde_json::from_str(r#"{"count": {"gte": 10, "lt": 20}}"#).unwrap();
+        let QueryAst::Range(range_query_ast) = range_query.convert_to_query_ast().unwrap() else {
+            panic!()
+        };
+        assert_eq!(
+            range_query_ast.lower_bound,
+            Bound::Included(JsonLiteral::Number(10.into()))
+        );
+        assert_eq!(
+            range_query_ast.upper_bound,
+            Bound::Excluded(JsonLiteral::Number(20.into()))
+        );
+    }
+}
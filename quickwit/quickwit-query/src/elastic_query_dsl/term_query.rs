@@ -17,23 +17,108 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use serde::de::{self, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::elastic_query_dsl::one_field_map::OneFieldMap;
 use crate::elastic_query_dsl::{ConvertableToQueryAst, ElasticQueryDslInner};
 use crate::not_nan_f32::NotNaNf32;
 use crate::query_ast::{self, QueryAst};
+use crate::JsonLiteral;
 
 pub type TermQuery = OneFieldMap<TermQueryValue>;
 
-#[derive(PartialEq, Eq, Debug, Serialize, Deserialize, Clone)]
-#[serde(deny_unknown_fields)]
+/// The value of a term query can be passed as a string, a number, or a boolean, either directly
+/// or in the `value` parameter.
+///
+/// `{"field": {"value": 42, "boost": 2.0}}`
+/// `{"field": 42}`
+#[derive(PartialEq, Eq, Debug, Serialize, Clone)]
 pub struct TermQueryValue {
     pub value: String,
     #[serde(default)]
     pub boost: Option<NotNaNf32>,
 }
 
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TermQueryParams {
+    value: JsonLiteral,
+    #[serde(default)]
+    boost: Option<NotNaNf32>,
+}
+
+fn json_literal_to_string(json_literal: JsonLiteral) -> String {
+    match json_literal {
+        JsonLiteral::Number(number) => number.to_string(),
+        JsonLiteral::String(text) => text,
+        JsonLiteral::Bool(boolean) => boolean.to_string(),
+    }
+}
+
+struct TermQueryValueVisitor;
+
+impl TermQueryValueVisitor {
+    fn value_without_params(value: impl ToString) -> TermQueryValue {
+        TermQueryValue {
+            value: value.to_string(),
+            boost: None,
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for TermQueryValueVisitor {
+    type Value = TermQueryValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("value or map containing the parameters of a term query.")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+    where E: de::Error {
+        Ok(Self::value_without_params(value))
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E>
+    where E: de::Error {
+        Ok(Self::value_without_params(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Self::Value, E>
+    where E: de::Error {
+        Ok(Self::value_without_params(value))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Self::Value, E>
+    where E: de::Error {
+        Ok(Self::value_without_params(value))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> Result<Self::Value, E>
+    where E: de::Error {
+        Ok(Self::value_without_params(value))
+    }
+
+    fn visit_map<M>(self, map: M) -> Result<Self::Value, M::Error>
+    where M: MapAccess<'de> {
+        let TermQueryParams { value, boost } =
+            Deserialize::deserialize(de::value::MapAccessDeserializer::new(map))?;
+        Ok(TermQueryValue {
+            value: json_literal_to_string(value),
+            boost,
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for TermQueryValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where D: Deserializer<'de> {
+        deserializer.deserialize_any(TermQueryValueVisitor)
+    }
+}
+
 #[cfg(test)]
 pub fn term_query_from_field_value(field: impl ToString, value: impl ToString) -> TermQuery {
     TermQuery {
@@ -68,6 +153,37 @@ mod tests {
     use super::TermQuery;
     use crate::elastic_query_dsl::term_query::term_query_from_field_value;
 
+    #[test]
+    fn test_term_query_shorthand() {
+        let term_query: TermQuery = serde_json::from_str(r#"{ "product_id": "61809" }"#).unwrap();
+        assert_eq!(
+            &term_query,
+            &term_query_from_field_value("product_id", "61809")
+        );
+        let term_query: TermQuery = serde_json::from_str(r#"{ "product_id": 61809 }"#).unwrap();
+        assert_eq!(
+            &term_query,
+            &term_query_from_field_value("product_id", "61809")
+        );
+        let term_query: TermQuery =
+            serde_json::from_str(r#"{ "is_active": { "value": true } }"#).unwrap();
+        assert_eq!(
+            &term_query,
+            &term_query_from_field_value("is_active", "true")
+        );
+    }
+
+    #[test]
+    fn test_term_query_nice_errors() {
+        let deser_error = serde_json::from_str::<TermQuery>(
+            r#"{ "product_id": { "value": "61809", "wrong_param": 2 } }"#,
+        )
+        .unwrap_err();
+        assert!(deser_error
+            .to_string()
+            .contains("unknown field `wrong_param`"));
+    }
+
     #[test]
     fn test_term_query_simple() {
         let term_query_json = r#"{ "product_id": { "value": "61809" } }"#;
@@ -19,6 +19,7 @@
 
 use serde::{Deserialize, Serialize};
 
+/// An `f32` that cannot be NaN, used for query boosts.
 #[derive(Serialize, Deserialize, Debug, Copy, Clone, PartialEq)]
 #[serde(into = "f32", try_from = "f32")]
 pub struct NotNaNf32(f32);
@@ -0,0 +1,375 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::ops::Bound;
+
+use anyhow::Context;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use tantivy::tokenizer::TextAnalyzer;
+
+use super::{BoolQuery, FullTextMode, FullTextQuery, QueryAst, RangeQuery};
+use crate::{get_quickwit_tokenizer_manager, BooleanOperand, InvalidQuery, JsonLiteral};
+
+/// Tokenizer used for the full-text queries that do not name one. The mapping of the field, which
+/// would otherwise determine it, is not known when matching a raw document.
+const DEFAULT_TOKENIZER_NAME: &str = "default";
+
+impl QueryAst {
+    /// Evaluates the query against a JSON document without indexing it, for instance to filter
+    /// documents as they are ingested.
+    ///
+    /// The evaluation approximates that of an indexed document: full-text queries tokenize the
+    /// field values with their query tokenizer, or the `default` tokenizer, and range queries
+    /// compare numbers numerically and strings lexicographically. User queries must have been
+    /// parsed beforehand.
+    pub fn matches_json_doc(
+        &self,
+        json_doc: &JsonMap<String, JsonValue>,
+    ) -> Result<bool, InvalidQuery> {
+        match self {
+            QueryAst::Bool(bool_query) => matches_bool_query(bool_query, json_doc),
+            QueryAst::Term(term_query) => Ok(field_values(json_doc, &term_query.field)
+                .into_iter()
+                .filter_map(value_as_text)
+                .any(|text| text == term_query.value)),
+            QueryAst::TermSet(term_set_query) => {
+                Ok(term_set_query.terms_per_field.iter().any(|(field, terms)| {
+                    field_values(json_doc, field)
+                        .into_iter()
+                        .filter_map(value_as_text)
+                        .any(|text| terms.contains(&text))
+                }))
+            }
+            QueryAst::FullText(full_text_query) => {
+                matches_full_text_query(full_text_query, json_doc)
+            }
+            QueryAst::Range(range_query) => Ok(matches_range_query(range_query, json_doc)),
+            QueryAst::UserInput(_) => Err(InvalidQuery::UserQueryNotParsed),
+            QueryAst::MatchAll => Ok(true),
+            QueryAst::MatchNone => Ok(false),
+            QueryAst::Boost { underlying, .. } => underlying.matches_json_doc(json_doc),
+        }
+    }
+}
+
+fn matches_bool_query(
+    bool_query: &BoolQuery,
+    json_doc: &JsonMap<String, JsonValue>,
+) -> Result<bool, InvalidQuery> {
+    for clause in bool_query.must.iter().chain(&bool_query.filter) {
+        if !clause.matches_json_doc(json_doc)? {
+            return Ok(false);
+        }
+    }
+    for clause in &bool_query.must_not {
+        if clause.matches_json_doc(json_doc)? {
+            return Ok(false);
+        }
+    }
+    // Like in Elasticsearch, `should` clauses are optional when there is a `must` or `filter`
+    // clause.
+    if bool_query.must.is_empty() && bool_query.filter.is_empty() && !bool_query.should.is_empty() {
+        for clause in &bool_query.should {
+            if clause.matches_json_doc(json_doc)? {
+                return Ok(true);
+            }
+        }
+        return Ok(false);
+    }
+    Ok(true)
+}
+
+fn matches_full_text_query(
+    full_text_query: &FullTextQuery,
+    json_doc: &JsonMap<String, JsonValue>,
+) -> Result<bool, InvalidQuery> {
+    let tokenizer_name = full_text_query
+        .params
+        .tokenizer
+        .as_deref()
+        .unwrap_or(DEFAULT_TOKENIZER_NAME);
+    let text_analyzer = get_quickwit_tokenizer_manager()
+        .get(tokenizer_name)
+        .with_context(|| format!("No tokenizer named `{tokenizer_name}` is registered."))?;
+    let query_tokens = tokenize(&text_analyzer, &full_text_query.text);
+
+    if query_tokens.is_empty() {
+        return Ok(!full_text_query.params.zero_terms_query.is_none());
+    }
+    let value_tokens: Vec<Vec<String>> = field_values(json_doc, &full_text_query.field)
+        .into_iter()
+        .filter_map(value_as_text)
+        .map(|text| tokenize(&text_analyzer, &text))
+        .collect();
+
+    let matches = match full_text_query.params.mode {
+        FullTextMode::Bool { operator } => {
+            let field_tokens: HashSet<&str> =
+                value_tokens.iter().flatten().map(String::as_str).collect();
+            let mut query_tokens_iter = query_tokens.iter();
+            match operator {
+                BooleanOperand::And => {
+                    query_tokens_iter.all(|token| field_tokens.contains(token.as_str()))
+                }
+                BooleanOperand::Or => {
+                    query_tokens_iter.any(|token| field_tokens.contains(token.as_str()))
+                }
+            }
+        }
+        FullTextMode::Phrase { slop } => value_tokens
+            .iter()
+            .any(|tokens| contains_phrase(tokens, &query_tokens, slop)),
+    };
+    Ok(matches)
+}
+
+fn matches_range_query(range_query: &RangeQuery, json_doc: &JsonMap<String, JsonValue>) -> bool {
+    field_values(json_doc, &range_query.field)
+        .into_iter()
+        .any(|value| {
+            let above_lower_bound = match &range_query.lower_bound {
+                Bound::Included(lower_bound) => matches!(
+                    compare_to_literal(value, lower_bound),
+                    Some(Ordering::Greater | Ordering::Equal)
+                ),
+                Bound::Excluded(lower_bound) => {
+                    compare_to_literal(value, lower_bound) == Some(Ordering::Greater)
+                }
+                Bound::Unbounded => true,
+            };
+            let below_upper_bound = match &range_query.upper_bound {
+                Bound::Included(upper_bound) => matches!(
+                    compare_to_literal(value, upper_bound),
+                    Some(Ordering::Less | Ordering::Equal)
+                ),
+                Bound::Excluded(upper_bound) => {
+                    compare_to_literal(value, upper_bound) == Some(Ordering::Less)
+                }
+                Bound::Unbounded => true,
+            };
+            above_lower_bound && below_upper_bound
+        })
+}
+
+fn compare_to_literal(value: &JsonValue, literal: &JsonLiteral) -> Option<Ordering> {
+    match (value, literal) {
+        (JsonValue::Number(number), JsonLiteral::Number(literal_number)) => {
+            number.as_f64()?.partial_cmp(&literal_number.as_f64()?)
+        }
+        (JsonValue::Number(number), JsonLiteral::String(literal_text)) => number
+            .as_f64()?
+            .partial_cmp(&literal_text.parse::<f64>().ok()?),
+        (JsonValue::String(text), JsonLiteral::String(literal_text)) => {
+            match (text.parse::<f64>(), literal_text.parse::<f64>()) {
+                (Ok(number), Ok(literal_number)) => number.partial_cmp(&literal_number),
+                _ => Some(text.as_str().cmp(literal_text.as_str())),
+            }
+        }
+        (JsonValue::String(text), JsonLiteral::Number(literal_number)) => text
+            .parse::<f64>()
+            .ok()?
+            .partial_cmp(&literal_number.as_f64()?),
+        (JsonValue::Bool(value), JsonLiteral::Bool(literal_value)) => {
+            Some(value.cmp(literal_value))
+        }
+        _ => None,
+    }
+}
+
+/// Returns the values of a field, given its path, flattening arrays. The segments of the path
+/// can be nested objects or keys containing dots, escaped or not.
+fn field_values<'a>(
+    json_doc: &'a JsonMap<String, JsonValue>,
+    field_path: &str,
+) -> Vec<&'a JsonValue> {
+    let path_segments = split_field_path(field_path);
+    let path_segments: Vec<&str> = path_segments.iter().map(String::as_str).collect();
+    let mut values = Vec::new();
+    collect_object_values(json_doc, &path_segments, &mut values);
+    values
+}
+
+/// Splits a field path on its unescaped dots.
+fn split_field_path(field_path: &str) -> Vec<String> {
+    let mut path_segments = Vec::new();
+    let mut path_segment = String::new();
+    let mut chars = field_path.chars();
+
+    while let Some(character) = chars.next() {
+        match character {
+            '\\' => path_segment.extend(chars.next()),
+            '.' => path_segments.push(std::mem::take(&mut path_segment)),
+            _ => path_segment.push(character),
+        }
+    }
+    path_segments.push(path_segment);
+    path_segments
+}
+
+fn collect_object_values<'a>(
+    json_obj: &'a JsonMap<String, JsonValue>,
+    path_segments: &[&str],
+    values: &mut Vec<&'a JsonValue>,
+) {
+    for num_segments in 1..=path_segments.len() {
+        let key = path_segments[..num_segments].join(".");
+
+        if let Some(child_value) = json_obj.get(&key) {
+            collect_values(child_value, &path_segments[num_segments..], values);
+        }
+    }
+}
+
+fn collect_values<'a>(
+    json_value: &'a JsonValue,
+    path_segments: &[&str],
+    values: &mut Vec<&'a JsonValue>,
+) {
+    match json_value {
+        JsonValue::Array(items) => {
+            for item in items {
+                collect_values(item, path_segments, values);
+            }
+        }
+        JsonValue::Object(json_obj) if !path_segments.is_empty() => {
+            collect_object_values(json_obj, path_segments, values);
+        }
+        JsonValue::Null | JsonValue::Object(_) => {}
+        _ if path_segments.is_empty() => values.push(json_value),
+        _ => {}
+    }
+}
+
+fn value_as_text(json_value: &JsonValue) -> Option<String> {
+    match json_value {
+        JsonValue::String(text) => Some(text.clone()),
+        JsonValue::Number(number) => Some(number.to_string()),
+        JsonValue::Bool(value) => Some(value.to_string()),
+        JsonValue::Null | JsonValue::Array(_) | JsonValue::Object(_) => None,
+    }
+}
+
+fn tokenize(text_analyzer: &TextAnalyzer, text: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut token_stream = text_analyzer.token_stream(text);
+    token_stream.process(&mut |token| tokens.push(token.text.clone()));
+    tokens
+}
+
+/// Returns whether `tokens` contains the tokens of `phrase` in order, with at most `slop` tokens
+/// in between in total.
+fn contains_phrase(tokens: &[String], phrase: &[String], slop: u32) -> bool {
+    (0..tokens.len())
+        .filter(|&start| tokens[start] == phrase[0])
+        .any(|start| {
+            let mut position = start;
+            let mut remaining_slop = slop as usize;
+
+            for phrase_token in &phrase[1..] {
+                if position + 1 >= tokens.len() {
+                    return false;
+                }
+                let max_position = (position + 1 + remaining_slop).min(tokens.len() - 1);
+                let offset_opt = tokens[position + 1..=max_position]
+                    .iter()
+                    .position(|token| token == phrase_token);
+                match offset_opt {
+                    Some(offset) => {
+                        remaining_slop -= offset;
+                        position += offset + 1;
+                    }
+                    None => return false,
+                }
+            }
+            true
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::query_ast::{QueryAst, UserInputQuery};
+    use crate::BooleanOperand;
+
+    fn matches(user_text: &str, json_doc: serde_json::Value) -> bool {
+        let query_ast: QueryAst = UserInputQuery {
+            user_text: user_text.to_string(),
+            default_fields: None,
+            default_operator: BooleanOperand::And,
+        }
+        .into();
+        let json_doc = json_doc.as_object().unwrap();
+        query_ast
+            .parse_user_query(&["body".to_string()])
+            .unwrap()
+            .matches_json_doc(json_doc)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_matches_json_doc_full_text() {
+        let json_doc = json!({"body": "Connection reset by peer", "severity_text": "ERROR"});
+        assert!(matches("connection", json_doc.clone()));
+        assert!(matches("reset peer", json_doc.clone()));
+        assert!(!matches("reset timeout", json_doc.clone()));
+        assert!(matches("\"reset by peer\"", json_doc.clone()));
+        assert!(!matches("\"peer by reset\"", json_doc.clone()));
+        assert!(matches("severity_text:ERROR", json_doc.clone()));
+        assert!(matches("*", json_doc));
+    }
+
+    #[test]
+    fn test_matches_json_doc_bool() {
+        let json_doc = json!({"body": "disk full", "service": "indexer"});
+        assert!(matches("disk AND service:indexer", json_doc.clone()));
+        assert!(matches("disk OR service:searcher", json_doc.clone()));
+        assert!(!matches("disk AND -service:indexer", json_doc.clone()));
+        assert!(matches("-service:searcher", json_doc));
+    }
+
+    #[test]
+    fn test_matches_json_doc_nested_fields() {
+        let json_doc = json!({
+            "resource": {"service": {"name": "quickwit"}},
+            "attributes": {"http.status_code": 503},
+            "tags": ["blue", "green"],
+        });
+        assert!(matches("resource.service.name:quickwit", json_doc.clone()));
+        assert!(matches("attributes.http.status_code:503", json_doc.clone()));
+        assert!(matches("tags:green", json_doc.clone()));
+        assert!(!matches("tags:red", json_doc.clone()));
+        assert!(!matches("resource.service:quickwit", json_doc));
+
+        let json_doc = json!({"attributes": {"server.status": "up"}});
+        assert!(matches(r#"attributes.server\.status:up"#, json_doc));
+    }
+
+    #[test]
+    fn test_matches_json_doc_range() {
+        let json_doc = json!({"latency_ms": 250, "date": "2023-05-01"});
+        assert!(matches("latency_ms:[100 TO 300]", json_doc.clone()));
+        assert!(matches("latency_ms:[250 TO 300}", json_doc.clone()));
+        assert!(!matches("latency_ms:{250 TO 300]", json_doc.clone()));
+        assert!(matches("date:[2023-04-30 TO 2023-05-02]", json_doc.clone()));
+        assert!(!matches("missing:[0 TO 10]", json_doc));
+    }
+}
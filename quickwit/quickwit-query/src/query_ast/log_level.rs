@@ -0,0 +1,236 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::ops::Bound;
+
+use crate::query_ast::{
+    BoolQuery, FullTextQuery, QueryAst, QueryAstTransformer, RangeQuery, TermQuery, TermSetQuery,
+};
+use crate::{InvalidQuery, JsonLiteral};
+
+/// Name of the pseudo-field of the `level:<level>` query shortcut.
+pub const LOG_LEVEL_FIELD_NAME: &str = "level";
+
+/// Name of the field holding the OpenTelemetry severity number of a log record.
+pub const SEVERITY_NUMBER_FIELD_NAME: &str = "severity_number";
+
+/// Returns the range of severity numbers of a log level, as defined by the OpenTelemetry log data
+/// model. Level names are case-insensitive and accept the aliases used by Loki.
+fn severity_number_range(level: &str) -> Option<(u64, u64)> {
+    let severity_number_range = match level.to_ascii_lowercase().as_str() {
+        "trace" => (1, 4),
+        "debug" => (5, 8),
+        "info" | "information" => (9, 12),
+        "warn" | "warning" => (13, 16),
+        "error" | "err" => (17, 20),
+        "fatal" | "critical" | "crit" => (21, 24),
+        _ => return None,
+    };
+    Some(severity_number_range)
+}
+
+fn severity_number_range_query(
+    level: &str,
+    severity_number_field: &str,
+) -> Result<QueryAst, InvalidQuery> {
+    let (lower_bound, upper_bound) =
+        severity_number_range(level).ok_or_else(|| InvalidQuery::InvalidSearchTerm {
+            expected_value_type: "log level",
+            field_name: LOG_LEVEL_FIELD_NAME.to_string(),
+            value: level.to_string(),
+        })?;
+    let range_query = RangeQuery {
+        field: severity_number_field.to_string(),
+        lower_bound: Bound::Included(JsonLiteral::Number(lower_bound.into())),
+        upper_bound: Bound::Included(JsonLiteral::Number(upper_bound.into())),
+    };
+    Ok(range_query.into())
+}
+
+struct LogLevelShortcutExpander<'a> {
+    severity_number_field: &'a str,
+}
+
+impl<'a> QueryAstTransformer for LogLevelShortcutExpander<'a> {
+    type Err = InvalidQuery;
+
+    fn transform_full_text(
+        &mut self,
+        full_text_query: FullTextQuery,
+    ) -> Result<Option<QueryAst>, InvalidQuery> {
+        if full_text_query.field != LOG_LEVEL_FIELD_NAME {
+            return Ok(Some(full_text_query.into()));
+        }
+        severity_number_range_query(&full_text_query.text, self.severity_number_field).map(Some)
+    }
+
+    fn transform_term(&mut self, term_query: TermQuery) -> Result<Option<QueryAst>, InvalidQuery> {
+        if term_query.field != LOG_LEVEL_FIELD_NAME {
+            return Ok(Some(term_query.into()));
+        }
+        severity_number_range_query(&term_query.value, self.severity_number_field).map(Some)
+    }
+
+    fn transform_term_set(
+        &mut self,
+        term_set_query: TermSetQuery,
+    ) -> Result<Option<QueryAst>, InvalidQuery> {
+        let mut terms_per_field = term_set_query.terms_per_field;
+        let Some(levels) = terms_per_field.remove(LOG_LEVEL_FIELD_NAME) else {
+            return Ok(Some(TermSetQuery { terms_per_field }.into()));
+        };
+        // A term set query is a union of term queries.
+        let mut should = Vec::with_capacity(levels.len() + 1);
+        for level in &levels {
+            should.push(severity_number_range_query(
+                level,
+                self.severity_number_field,
+            )?);
+        }
+        if !terms_per_field.is_empty() {
+            should.push(TermSetQuery { terms_per_field }.into());
+        }
+        let bool_query = BoolQuery {
+            should,
+            ..Default::default()
+        };
+        Ok(Some(bool_query.into()))
+    }
+}
+
+impl QueryAst {
+    /// Rewrites the `level:<level>` clauses of the query into range queries over the severity
+    /// numbers of the level, for instance `level:error` into `severity_number:[17 TO 20]`.
+    ///
+    /// The query must have been parsed beforehand. See [`QueryAst::parse_user_query`].
+    pub fn expand_log_level_shortcut(
+        self,
+        severity_number_field: &str,
+    ) -> Result<QueryAst, InvalidQuery> {
+        let mut expander = LogLevelShortcutExpander {
+            severity_number_field,
+        };
+        let expanded_query_ast = expander
+            .transform(self)?
+            .expect("Expanding the log level shortcut should never remove the query.");
+        Ok(expanded_query_ast)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+    use std::ops::Bound;
+
+    use super::SEVERITY_NUMBER_FIELD_NAME;
+    use crate::query_ast::{
+        BoolQuery, QueryAst, RangeQuery, TermQuery, TermSetQuery, UserInputQuery,
+    };
+    use crate::{InvalidQuery, JsonLiteral};
+
+    fn expand_user_query(user_text: &str) -> Result<QueryAst, InvalidQuery> {
+        let query_ast: QueryAst = UserInputQuery {
+            user_text: user_text.to_string(),
+            default_fields: None,
+            default_operator: Default::default(),
+        }
+        .into();
+        query_ast
+            .parse_user_query(&[])
+            .unwrap()
+            .expand_log_level_shortcut(SEVERITY_NUMBER_FIELD_NAME)
+    }
+
+    fn severity_number_range(lower_bound: u64, upper_bound: u64) -> QueryAst {
+        RangeQuery {
+            field: SEVERITY_NUMBER_FIELD_NAME.to_string(),
+            lower_bound: Bound::Included(JsonLiteral::Number(lower_bound.into())),
+            upper_bound: Bound::Included(JsonLiteral::Number(upper_bound.into())),
+        }
+        .into()
+    }
+
+    #[test]
+    fn test_expand_log_level_shortcut() {
+        assert_eq!(
+            expand_user_query("level:error").unwrap(),
+            severity_number_range(17, 20)
+        );
+        assert_eq!(
+            expand_user_query("level:WARNING").unwrap(),
+            severity_number_range(13, 16)
+        );
+        let query_ast = expand_user_query("level:info service_name:foo").unwrap();
+        let QueryAst::Bool(bool_query) = query_ast else { panic!() };
+        assert_eq!(bool_query.should.len(), 2);
+        assert_eq!(bool_query.should[0], severity_number_range(9, 12));
+        assert!(matches!(bool_query.should[1], QueryAst::FullText(_)));
+
+        let error = expand_user_query("level:verbose").unwrap_err();
+        assert!(matches!(error, InvalidQuery::InvalidSearchTerm { .. }));
+    }
+
+    #[test]
+    fn test_expand_log_level_shortcut_term_queries() {
+        let term_query: QueryAst = TermQuery {
+            field: "level".to_string(),
+            value: "debug".to_string(),
+        }
+        .into();
+        assert_eq!(
+            term_query
+                .expand_log_level_shortcut(SEVERITY_NUMBER_FIELD_NAME)
+                .unwrap(),
+            severity_number_range(5, 8)
+        );
+        let term_set_query: QueryAst = TermSetQuery {
+            terms_per_field: HashMap::from_iter([
+                (
+                    "level".to_string(),
+                    HashSet::from_iter(["fatal".to_string()]),
+                ),
+                (
+                    "service_name".to_string(),
+                    HashSet::from_iter(["foo".to_string()]),
+                ),
+            ]),
+        }
+        .into();
+        let expected_query: QueryAst = BoolQuery {
+            should: vec![
+                severity_number_range(21, 24),
+                TermSetQuery {
+                    terms_per_field: HashMap::from_iter([(
+                        "service_name".to_string(),
+                        HashSet::from_iter(["foo".to_string()]),
+                    )]),
+                }
+                .into(),
+            ],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(
+            term_set_query
+                .expand_log_level_shortcut(SEVERITY_NUMBER_FIELD_NAME)
+                .unwrap(),
+            expected_query
+        );
+    }
+}
@@ -23,6 +23,8 @@ use tantivy::schema::Schema as TantivySchema;
 
 mod bool_query;
 mod full_text_query;
+mod json_doc_matcher;
+mod log_level;
 mod range_query;
 mod tantivy_query_ast;
 mod term_query;
@@ -33,12 +35,13 @@ mod visitor;
 
 pub use bool_query::BoolQuery;
 pub use full_text_query::{FullTextMode, FullTextParams, FullTextQuery};
+pub use log_level::{LOG_LEVEL_FIELD_NAME, SEVERITY_NUMBER_FIELD_NAME};
 pub use range_query::RangeQuery;
 use tantivy_query_ast::TantivyQueryAst;
 pub use term_query::TermQuery;
 pub use term_set_query::TermSetQuery;
-pub use user_input_query::UserInputQuery;
-pub use visitor::QueryAstVisitor;
+pub use user_input_query::{parse_boosted_field, UserInputQuery};
+pub use visitor::{QueryAstTransformer, QueryAstVisitor};
 
 use crate::{InvalidQuery, NotNaNf32};
 
@@ -57,18 +57,69 @@ impl UserInputQuery {
     /// The default_search_fields argument on the other hand, is the default search fields defined
     /// in the `DocMapper`.
     pub fn parse_user_query(&self, default_search_fields: &[String]) -> anyhow::Result<QueryAst> {
-        let search_fields = self
+        let search_fields: Vec<SearchField> = self
             .default_fields
             .as_ref()
             .map(|search_fields| &search_fields[..])
-            .unwrap_or(default_search_fields);
+            .unwrap_or(default_search_fields)
+            .iter()
+            .map(|search_field| SearchField::parse(search_field))
+            .collect::<anyhow::Result<_>>()?;
         let user_input_ast = tantivy::query_grammar::parse_query(&self.user_text)
             .map_err(|_| anyhow::anyhow!("Failed to parse query: `{}`.", &self.user_text))?;
         let default_occur = match self.default_operator {
             BooleanOperand::And => Occur::Must,
             BooleanOperand::Or => Occur::Should,
         };
-        convert_user_input_ast_to_query_ast(user_input_ast, default_occur, search_fields)
+        convert_user_input_ast_to_query_ast(user_input_ast, default_occur, &search_fields)
+    }
+}
+
+/// Splits a search field of the form `field^boost`, for instance `title^2.5`, into the field name
+/// and its boost factor. Search fields without a `^` suffix have no boost.
+pub fn parse_boosted_field(boosted_field: &str) -> anyhow::Result<(&str, Option<f32>)> {
+    let Some((field_name, boost_str)) = boosted_field.rsplit_once('^') else {
+        return Ok((boosted_field, None));
+    };
+    let boost: f32 = boost_str
+        .parse()
+        .ok()
+        .filter(|boost: &f32| boost.is_finite() && *boost >= 0.0)
+        .with_context(|| {
+            format!(
+                "Invalid boost `{boost_str}` for search field `{field_name}`: the boost must be a \
+                 non-negative number."
+            )
+        })?;
+    Ok((field_name, Some(boost)))
+}
+
+/// A default search field along with its optional boost factor.
+#[derive(Clone, Copy)]
+struct SearchField<'a> {
+    name: &'a str,
+    boost_opt: Option<NotNaNf32>,
+}
+
+impl<'a> SearchField<'a> {
+    fn parse(search_field: &'a str) -> anyhow::Result<Self> {
+        let (name, boost_opt) = parse_boosted_field(search_field)?;
+        let boost_opt = boost_opt
+            .map(NotNaNf32::try_from)
+            .transpose()
+            .map_err(|err_msg: &str| anyhow::anyhow!(err_msg))?;
+        Ok(SearchField { name, boost_opt })
+    }
+
+    /// Boosts `query_ast` by the boost factor of the field, if any.
+    fn boost(&self, query_ast: QueryAst) -> QueryAst {
+        match self.boost_opt {
+            Some(boost) if boost != NotNaNf32::ONE => QueryAst::Boost {
+                underlying: Box::new(query_ast),
+                boost,
+            },
+            _ => query_ast,
+        }
     }
 }
 
@@ -92,7 +143,7 @@ impl BuildTantivyAst for UserInputQuery {
 fn convert_user_input_ast_to_query_ast(
     user_input_ast: UserInputAst,
     default_occur: Occur,
-    default_search_fields: &[String],
+    default_search_fields: &[SearchField],
 ) -> anyhow::Result<QueryAst> {
     match user_input_ast {
         UserInputAst::Clause(clause) => {
@@ -141,10 +192,15 @@ fn convert_user_input_ast_to_query_ast(
                 Ok(range_query.into())
             }
             UserInputLeaf::Set { field, elements } => {
+                // Term set queries match a set of terms without scoring them: the boosts of the
+                // default search fields are ignored.
                 let field_names: Vec<String> = if let Some(field) = field.as_ref() {
                     vec![field.to_string()]
                 } else {
-                    default_search_fields.to_vec()
+                    default_search_fields
+                        .iter()
+                        .map(|search_field| search_field.name.to_string())
+                        .collect()
                 };
                 if field_names.is_empty() {
                     anyhow::bail!("Set query need to target a specific field.");
@@ -177,22 +233,22 @@ fn convert_user_input_ast_to_query_ast(
 
 fn convert_user_input_literal(
     user_input_literal: UserInputLiteral,
-    default_search_fields: &[String],
+    default_search_fields: &[SearchField],
 ) -> anyhow::Result<QueryAst> {
     let UserInputLiteral {
         field_name,
         phrase,
         slop,
     } = user_input_literal;
-    let field_names: Vec<String> = if let Some(field_name) = field_name {
-        vec![field_name]
+    let search_fields: Vec<SearchField> = if let Some(field_name) = field_name.as_deref() {
+        vec![SearchField {
+            name: field_name,
+            boost_opt: None,
+        }]
     } else {
-        default_search_fields
-            .iter()
-            .map(|field_name| field_name.to_string())
-            .collect()
+        default_search_fields.to_vec()
     };
-    if field_names.is_empty() {
+    if search_fields.is_empty() {
         anyhow::bail!("Query requires a default search field and none was supplied.");
     }
     let full_text_params = FullTextParams {
@@ -200,15 +256,15 @@ fn convert_user_input_literal(
         mode: FullTextMode::Phrase { slop },
         zero_terms_query: crate::MatchAllOrNone::MatchNone,
     };
-    let mut phrase_queries: Vec<QueryAst> = field_names
-        .into_iter()
-        .map(|field_name| {
-            query_ast::FullTextQuery {
-                field: field_name,
+    let mut phrase_queries: Vec<QueryAst> = search_fields
+        .iter()
+        .map(|search_field| {
+            let full_text_query = query_ast::FullTextQuery {
+                field: search_field.name.to_string(),
                 text: phrase.clone(),
                 params: full_text_params.clone(),
-            }
-            .into()
+            };
+            search_field.boost(full_text_query.into())
         })
         .collect();
     if phrase_queries.is_empty() {
@@ -226,8 +282,10 @@ fn convert_user_input_literal(
 
 #[cfg(test)]
 mod tests {
-    use crate::query_ast::{BoolQuery, BuildTantivyAst, FullTextMode, QueryAst, UserInputQuery};
-    use crate::{BooleanOperand, InvalidQuery};
+    use crate::query_ast::{
+        parse_boosted_field, BoolQuery, BuildTantivyAst, FullTextMode, QueryAst, UserInputQuery,
+    };
+    use crate::{BooleanOperand, InvalidQuery, NotNaNf32};
 
     #[test]
     fn test_user_input_query_not_parsed_error() {
@@ -330,6 +388,77 @@ mod tests {
         assert_eq!(should.len(), 2);
     }
 
+    #[test]
+    fn test_parse_boosted_field() {
+        assert_eq!(parse_boosted_field("title").unwrap(), ("title", None));
+        assert_eq!(
+            parse_boosted_field("title^2").unwrap(),
+            ("title", Some(2.0))
+        );
+        assert_eq!(
+            parse_boosted_field("body.message^0.5").unwrap(),
+            ("body.message", Some(0.5))
+        );
+        assert_eq!(
+            parse_boosted_field("title^abc").unwrap_err().to_string(),
+            "Invalid boost `abc` for search field `title`: the boost must be a non-negative \
+             number."
+        );
+        parse_boosted_field("title^-1").unwrap_err();
+        parse_boosted_field("title^NaN").unwrap_err();
+    }
+
+    #[test]
+    fn test_user_input_query_boosted_default_fields() {
+        let ast = UserInputQuery {
+            user_text: "hello title:world".to_string(),
+            default_fields: None,
+            default_operator: BooleanOperand::And,
+        }
+        .parse_user_query(&["body".to_string(), "title^5".to_string()])
+        .unwrap();
+        let QueryAst::Bool(BoolQuery { must, .. }) = ast else { panic!() };
+        assert_eq!(must.len(), 2);
+
+        let QueryAst::Bool(BoolQuery { should, .. }) = &must[0] else { panic!() };
+        assert_eq!(should.len(), 2);
+        let QueryAst::FullText(full_text_query) = &should[0] else { panic!() };
+        assert_eq!(&full_text_query.field, "body");
+        let QueryAst::Boost { underlying, boost } = &should[1] else { panic!() };
+        assert_eq!(*boost, NotNaNf32::try_from(5.0).unwrap());
+        let QueryAst::FullText(full_text_query) = &**underlying else { panic!() };
+        assert_eq!(&full_text_query.field, "title");
+
+        // Fields targeted explicitly in the query are not boosted.
+        let QueryAst::FullText(full_text_query) = &must[1] else { panic!() };
+        assert_eq!(&full_text_query.field, "title");
+        assert_eq!(&full_text_query.text, "world");
+    }
+
+    #[test]
+    fn test_user_input_query_per_query_boosted_fields() {
+        let ast = UserInputQuery {
+            user_text: "hello".to_string(),
+            default_fields: Some(vec!["body^2".to_string()]),
+            default_operator: BooleanOperand::And,
+        }
+        .parse_user_query(&["title^5".to_string()])
+        .unwrap();
+        let QueryAst::Boost { underlying, boost } = ast else { panic!() };
+        assert_eq!(boost, NotNaNf32::try_from(2.0).unwrap());
+        let QueryAst::FullText(full_text_query) = *underlying else { panic!() };
+        assert_eq!(&full_text_query.field, "body");
+
+        let invalid_err = UserInputQuery {
+            user_text: "hello".to_string(),
+            default_fields: Some(vec!["body^x".to_string()]),
+            default_operator: BooleanOperand::And,
+        }
+        .parse_user_query(&[])
+        .unwrap_err();
+        assert!(invalid_err.to_string().contains("Invalid boost `x`"));
+    }
+
     #[test]
     fn test_user_input_query_field_specified_in_user_input() {
         let ast = UserInputQuery {
@@ -88,3 +88,213 @@ pub trait QueryAstVisitor<'a> {
         Ok(())
     }
 }
+
+/// Simple trait to implement a rewriter over the QueryAst, for instance to alias fields, inject
+/// filters, or strip clauses.
+///
+/// Each method takes ownership of a node and returns the node replacing it, or `None` to remove
+/// it. A removed clause is dropped from its boolean query, and a boolean query whose clauses were
+/// all removed is removed in turn. By default, nodes are rebuilt as is after transforming their
+/// children.
+///
+/// User input queries are opaque to the transformer: they must be parsed beforehand with
+/// [`QueryAst::parse_user_query`] for their clauses to be transformed.
+pub trait QueryAstTransformer {
+    type Err;
+
+    fn transform(&mut self, query_ast: QueryAst) -> Result<Option<QueryAst>, Self::Err> {
+        match query_ast {
+            QueryAst::Bool(bool_query) => self.transform_bool(bool_query),
+            QueryAst::Term(term_query) => self.transform_term(term_query),
+            QueryAst::TermSet(term_set_query) => self.transform_term_set(term_set_query),
+            QueryAst::FullText(full_text_query) => self.transform_full_text(full_text_query),
+            QueryAst::Range(range_query) => self.transform_range(range_query),
+            QueryAst::MatchAll => self.transform_match_all(),
+            QueryAst::MatchNone => self.transform_match_none(),
+            QueryAst::Boost { underlying, boost } => self.transform_boost(*underlying, boost),
+            QueryAst::UserInput(user_text_query) => self.transform_user_text(user_text_query),
+        }
+    }
+
+    fn transform_bool(&mut self, bool_query: BoolQuery) -> Result<Option<QueryAst>, Self::Err> {
+        let BoolQuery {
+            must,
+            must_not,
+            should,
+            filter,
+        } = bool_query;
+        let num_clauses = must.len() + must_not.len() + should.len() + filter.len();
+        let bool_query = BoolQuery {
+            must: transform_all(self, must)?,
+            must_not: transform_all(self, must_not)?,
+            should: transform_all(self, should)?,
+            filter: transform_all(self, filter)?,
+        };
+        let is_emptied = num_clauses > 0
+            && bool_query.must.is_empty()
+            && bool_query.must_not.is_empty()
+            && bool_query.should.is_empty()
+            && bool_query.filter.is_empty();
+        if is_emptied {
+            return Ok(None);
+        }
+        Ok(Some(bool_query.into()))
+    }
+
+    fn transform_term(&mut self, term_query: TermQuery) -> Result<Option<QueryAst>, Self::Err> {
+        Ok(Some(term_query.into()))
+    }
+
+    fn transform_term_set(
+        &mut self,
+        term_set_query: TermSetQuery,
+    ) -> Result<Option<QueryAst>, Self::Err> {
+        Ok(Some(term_set_query.into()))
+    }
+
+    fn transform_full_text(
+        &mut self,
+        full_text_query: FullTextQuery,
+    ) -> Result<Option<QueryAst>, Self::Err> {
+        Ok(Some(full_text_query.into()))
+    }
+
+    fn transform_match_all(&mut self) -> Result<Option<QueryAst>, Self::Err> {
+        Ok(Some(QueryAst::MatchAll))
+    }
+
+    fn transform_match_none(&mut self) -> Result<Option<QueryAst>, Self::Err> {
+        Ok(Some(QueryAst::MatchNone))
+    }
+
+    fn transform_boost(
+        &mut self,
+        underlying: QueryAst,
+        boost: NotNaNf32,
+    ) -> Result<Option<QueryAst>, Self::Err> {
+        let underlying_opt = self.transform(underlying)?;
+        Ok(underlying_opt.map(|underlying| QueryAst::Boost {
+            underlying: Box::new(underlying),
+            boost,
+        }))
+    }
+
+    fn transform_range(&mut self, range_query: RangeQuery) -> Result<Option<QueryAst>, Self::Err> {
+        Ok(Some(range_query.into()))
+    }
+
+    fn transform_user_text(
+        &mut self,
+        user_text_query: UserInputQuery,
+    ) -> Result<Option<QueryAst>, Self::Err> {
+        Ok(Some(user_text_query.into()))
+    }
+}
+
+fn transform_all<T: QueryAstTransformer + ?Sized>(
+    transformer: &mut T,
+    asts: Vec<QueryAst>,
+) -> Result<Vec<QueryAst>, T::Err> {
+    let mut transformed_asts = Vec::with_capacity(asts.len());
+    for ast in asts {
+        if let Some(transformed_ast) = transformer.transform(ast)? {
+            transformed_asts.push(transformed_ast);
+        }
+    }
+    Ok(transformed_asts)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use super::*;
+
+    fn parse_user_query(user_text: &str) -> QueryAst {
+        let query_ast: QueryAst = UserInputQuery {
+            user_text: user_text.to_string(),
+            default_fields: None,
+            default_operator: Default::default(),
+        }
+        .into();
+        query_ast.parse_user_query(&[]).unwrap()
+    }
+
+    struct FieldAliaser;
+
+    impl QueryAstTransformer for FieldAliaser {
+        type Err = Infallible;
+
+        fn transform_full_text(
+            &mut self,
+            mut full_text_query: FullTextQuery,
+        ) -> Result<Option<QueryAst>, Infallible> {
+            if full_text_query.field == "msg" {
+                full_text_query.field = "body".to_string();
+            }
+            Ok(Some(full_text_query.into()))
+        }
+    }
+
+    struct ForbiddenFieldStripper;
+
+    impl QueryAstTransformer for ForbiddenFieldStripper {
+        type Err = Infallible;
+
+        fn transform_full_text(
+            &mut self,
+            full_text_query: FullTextQuery,
+        ) -> Result<Option<QueryAst>, Infallible> {
+            if full_text_query.field == "secret" {
+                return Ok(None);
+            }
+            Ok(Some(full_text_query.into()))
+        }
+    }
+
+    #[test]
+    fn test_query_ast_transformer_rewrites_nodes() {
+        let query_ast = parse_user_query("msg:hello AND (service:foo OR msg:world^2)");
+        let transformed_query_ast = FieldAliaser.transform(query_ast).unwrap().unwrap();
+        assert_eq!(
+            transformed_query_ast,
+            parse_user_query("body:hello AND (service:foo OR body:world^2)")
+        );
+    }
+
+    #[test]
+    fn test_query_ast_transformer_removes_nodes() {
+        let query_ast = parse_user_query("service:foo AND secret:bar");
+        let transformed_query_ast = ForbiddenFieldStripper
+            .transform(query_ast)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            transformed_query_ast,
+            BoolQuery {
+                must: vec![parse_user_query("service:foo")],
+                ..Default::default()
+            }
+            .into()
+        );
+        // Removing all the clauses of a boolean query, including boosted ones, removes it.
+        let query_ast = parse_user_query("secret:foo OR secret:bar^2");
+        assert!(ForbiddenFieldStripper
+            .transform(query_ast)
+            .unwrap()
+            .is_none());
+
+        let query_ast = parse_user_query("secret:foo");
+        assert!(ForbiddenFieldStripper
+            .transform(query_ast)
+            .unwrap()
+            .is_none());
+
+        // Empty boolean queries are left untouched.
+        let query_ast: QueryAst = BoolQuery::default().into();
+        assert_eq!(
+            ForbiddenFieldStripper.transform(query_ast.clone()).unwrap(),
+            Some(query_ast)
+        );
+    }
+}
@@ -25,9 +25,15 @@ use quickwit_common::FileEntry;
 use quickwit_config::{ConfigFormat, SourceConfig};
 use quickwit_indexing::actors::IndexingServiceCounters;
 pub use quickwit_ingest::CommitType;
-use quickwit_metastore::{IndexMetadata, Split};
-use quickwit_search::SearchResponseRest;
-use quickwit_serve::{ListSplitsQueryParams, SearchRequestQueryString};
+use quickwit_metastore::checkpoint::SourceCheckpoint;
+use quickwit_metastore::{IndexFieldStats, IndexMetadata, Split};
+use quickwit_proto::metastore_api::DeleteTask;
+use quickwit_search::{SearchResponseRest, SqlResponse};
+use quickwit_serve::{
+    CheckpointRewindTarget, DeleteQueryRequest, DeleteTaskStatus, ListSplitsQueryParams,
+    NodeDecommissionStatus, SearchRequestQueryString, SourceCheck, SplitRemovalInfo,
+    SqlRequestQueryString,
+};
 use reqwest::header::{HeaderMap, HeaderValue, CONTENT_TYPE};
 use reqwest::{Client, Method, StatusCode, Url};
 use serde::Serialize;
@@ -139,6 +145,17 @@ impl QuickwitClient {
         Ok(search_response)
     }
 
+    pub async fn sql(&self, sql_query: SqlRequestQueryString) -> Result<SqlResponse, Error> {
+        let bytes = serde_json::to_vec(&sql_query).unwrap();
+        let body = Bytes::from(bytes);
+        let response = self
+            .transport
+            .send::<()>(Method::POST, "sql", None, None, Some(body))
+            .await?;
+        let sql_response = response.deserialize().await?;
+        Ok(sql_response)
+    }
+
     pub fn indexes(&self) -> IndexClient {
         IndexClient::new(&self.transport)
     }
@@ -151,6 +168,10 @@ impl QuickwitClient {
         SourceClient::new(&self.transport, index_id)
     }
 
+    pub fn delete_tasks<'a, 'b: 'a>(&'a self, index_id: &'b str) -> DeleteTaskClient {
+        DeleteTaskClient::new(&self.transport, index_id)
+    }
+
     pub fn cluster(&self) -> ClusterClient {
         ClusterClient::new(&self.transport)
     }
@@ -163,6 +184,10 @@ impl QuickwitClient {
         NodeHealthClient::new(&self.transport)
     }
 
+    pub fn indexing(&self) -> IndexingClient {
+        IndexingClient::new(&self.transport)
+    }
+
     pub async fn ingest(
         &self,
         index_id: &str,
@@ -273,6 +298,89 @@ impl<'a> IndexClient<'a> {
         Ok(index_metadata)
     }
 
+    pub async fn update(
+        &self,
+        index_id: &str,
+        body: Bytes,
+        config_format: ConfigFormat,
+        expected_config_version_opt: Option<u64>,
+    ) -> Result<IndexMetadata, Error> {
+        let header_map = header_from_config_format(config_format);
+        let path = format!("indexes/{index_id}");
+        let response = self
+            .transport
+            .send(
+                Method::PUT,
+                &path,
+                Some(header_map),
+                Some(&[("expected_config_version", expected_config_version_opt)]),
+                Some(body),
+            )
+            .await?;
+        let index_metadata = response.deserialize().await?;
+        Ok(index_metadata)
+    }
+
+    pub async fn restore(&self, index_id: &str) -> Result<IndexMetadata, Error> {
+        let path = format!("indexes/{index_id}/restore");
+        let response = self
+            .transport
+            .send::<()>(Method::PUT, &path, None, None, None)
+            .await?;
+        let index_metadata = response.deserialize().await?;
+        Ok(index_metadata)
+    }
+
+    pub async fn garbage_collect(
+        &self,
+        index_id: &str,
+        dry_run: bool,
+    ) -> Result<SplitRemovalInfo, Error> {
+        let path = format!("indexes/{index_id}/garbage-collect");
+        let response = self
+            .transport
+            .send::<()>(
+                Method::PUT,
+                &path,
+                None,
+                Some(&[("dry_run", dry_run)]),
+                None,
+            )
+            .await?;
+        let removal_info = response.deserialize().await?;
+        Ok(removal_info)
+    }
+
+    pub async fn field_stats(&self, index_id: &str) -> Result<Vec<IndexFieldStats>, Error> {
+        let path = format!("indexes/{index_id}/field-stats");
+        let response = self
+            .transport
+            .send::<()>(Method::GET, &path, None, None, None)
+            .await?;
+        let field_stats = response.deserialize().await?;
+        Ok(field_stats)
+    }
+
+    pub async fn pause_merges(&self, index_id: &str) -> Result<(), Error> {
+        let path = format!("indexes/{index_id}/merges/pause");
+        let response = self
+            .transport
+            .send::<()>(Method::PUT, &path, None, None, None)
+            .await?;
+        response.check().await?;
+        Ok(())
+    }
+
+    pub async fn resume_merges(&self, index_id: &str) -> Result<(), Error> {
+        let path = format!("indexes/{index_id}/merges/resume");
+        let response = self
+            .transport
+            .send::<()>(Method::PUT, &path, None, None, None)
+            .await?;
+        response.check().await?;
+        Ok(())
+    }
+
     pub async fn clear(&self, index_id: &str) -> Result<(), Error> {
         let path = format!("indexes/{index_id}/clear");
         let response = self
@@ -397,6 +505,16 @@ impl<'a, 'b> SourceClient<'a, 'b> {
         Ok(source_config)
     }
 
+    pub async fn check(&self, source_id: &str) -> Result<SourceCheck, Error> {
+        let path = format!("{}/{source_id}/check", self.sources_root_url());
+        let response = self
+            .transport
+            .send::<()>(Method::GET, &path, None, None, None)
+            .await?;
+        let source_check = response.deserialize().await?;
+        Ok(source_check)
+    }
+
     pub async fn toggle(&self, source_id: &str, enable: bool) -> Result<(), Error> {
         let json_value = json!({ "enable": enable });
         let json_bytes = serde_json::to_vec(&json_value).expect("Serialization should never fail.");
@@ -425,6 +543,28 @@ impl<'a, 'b> SourceClient<'a, 'b> {
         Ok(())
     }
 
+    pub async fn rewind_checkpoint(
+        &self,
+        source_id: &str,
+        rewind_target: &CheckpointRewindTarget,
+    ) -> Result<SourceCheckpoint, Error> {
+        let json_bytes =
+            serde_json::to_vec(rewind_target).expect("Serialization should never fail.");
+        let path = format!("{}/{source_id}/rewind-checkpoint", self.sources_root_url());
+        let response = self
+            .transport
+            .send::<()>(
+                Method::PUT,
+                &path,
+                None,
+                None,
+                Some(Bytes::from(json_bytes)),
+            )
+            .await?;
+        let source_checkpoint = response.deserialize().await?;
+        Ok(source_checkpoint)
+    }
+
     pub async fn list(&self) -> Result<Vec<SourceConfig>, Error> {
         let response = self
             .transport
@@ -445,6 +585,73 @@ impl<'a, 'b> SourceClient<'a, 'b> {
     }
 }
 
+/// Client for delete task APIs.
+pub struct DeleteTaskClient<'a, 'b> {
+    transport: &'a Transport,
+    index_id: &'b str,
+}
+
+impl<'a, 'b> DeleteTaskClient<'a, 'b> {
+    pub fn new(transport: &'a Transport, index_id: &'b str) -> Self {
+        Self {
+            transport,
+            index_id,
+        }
+    }
+
+    fn delete_tasks_root_url(&self) -> String {
+        format!("{}/delete-tasks", self.index_id)
+    }
+
+    pub async fn list(&self) -> Result<Vec<DeleteTaskStatus>, Error> {
+        let response = self
+            .transport
+            .send::<()>(Method::GET, &self.delete_tasks_root_url(), None, None, None)
+            .await?;
+        let delete_task_statuses = response.deserialize().await?;
+        Ok(delete_task_statuses)
+    }
+
+    pub async fn create(
+        &self,
+        delete_query_request: DeleteQueryRequest,
+    ) -> Result<DeleteTask, Error> {
+        let body = Bytes::from(serde_json::to_vec(&delete_query_request)?);
+        let response = self
+            .transport
+            .send::<()>(
+                Method::POST,
+                &self.delete_tasks_root_url(),
+                None,
+                None,
+                Some(body),
+            )
+            .await?;
+        let delete_task = response.deserialize().await?;
+        Ok(delete_task)
+    }
+
+    pub async fn get(&self, opstamp: u64) -> Result<DeleteTaskStatus, Error> {
+        let path = format!("{}/{opstamp}", self.delete_tasks_root_url());
+        let response = self
+            .transport
+            .send::<()>(Method::GET, &path, None, None, None)
+            .await?;
+        let delete_task_status = response.deserialize().await?;
+        Ok(delete_task_status)
+    }
+
+    pub async fn cancel(&self, opstamp: u64) -> Result<DeleteTask, Error> {
+        let path = format!("{}/{opstamp}", self.delete_tasks_root_url());
+        let response = self
+            .transport
+            .send::<()>(Method::DELETE, &path, None, None, None)
+            .await?;
+        let delete_task = response.deserialize().await?;
+        Ok(delete_task)
+    }
+}
+
 /// Client for Cluster APIs.
 pub struct ClusterClient<'a> {
     transport: &'a Transport,
@@ -463,6 +670,41 @@ impl<'a> ClusterClient<'a> {
         let cluster_snapshot = response.deserialize().await?;
         Ok(cluster_snapshot)
     }
+
+    /// Lists the tenants owning at least one index.
+    pub async fn tenants(&self) -> Result<Vec<String>, Error> {
+        let response = self
+            .transport
+            .send::<()>(Method::GET, "tenants", None, None, None)
+            .await?;
+        let tenant_ids = response.deserialize().await?;
+        Ok(tenant_ids)
+    }
+
+    /// Requests the decommission of a node. The node can be terminated once its decommission
+    /// status is `decommissioned`.
+    pub async fn decommission_node(&self, node_id: &str) -> Result<NodeDecommissionStatus, Error> {
+        let path = format!("cluster/nodes/{node_id}/decommission");
+        let response = self
+            .transport
+            .send::<()>(Method::PUT, &path, None, None, None)
+            .await?;
+        let decommission_status = response.deserialize().await?;
+        Ok(decommission_status)
+    }
+
+    pub async fn node_decommission_status(
+        &self,
+        node_id: &str,
+    ) -> Result<NodeDecommissionStatus, Error> {
+        let path = format!("cluster/nodes/{node_id}/decommission");
+        let response = self
+            .transport
+            .send::<()>(Method::GET, &path, None, None, None)
+            .await?;
+        let decommission_status = response.deserialize().await?;
+        Ok(decommission_status)
+    }
 }
 
 /// Client for Node-level Stats APIs.
@@ -485,6 +727,28 @@ impl<'a> NodeStatsClient<'a> {
     }
 }
 
+/// Client for Node-level Indexing APIs.
+pub struct IndexingClient<'a> {
+    transport: &'a Transport,
+}
+
+impl<'a> IndexingClient<'a> {
+    pub fn new(transport: &'a Transport) -> Self {
+        Self { transport }
+    }
+
+    /// Starts draining the indexing pipelines of the node. The drain is complete once the node
+    /// reports no running pipeline.
+    pub async fn drain(&self) -> Result<IndexingServiceCounters, Error> {
+        let response = self
+            .transport
+            .send::<()>(Method::POST, "indexing/drain", None, None, None)
+            .await?;
+        let indexing_stats = response.deserialize().await?;
+        Ok(indexing_stats)
+    }
+}
+
 /// Client for Node-level Health APIs.
 pub struct NodeHealthClient<'a> {
     transport: &'a Transport,
@@ -532,12 +796,18 @@ mod test {
     use std::str::FromStr;
 
     use bytes::Bytes;
+    use quickwit_cluster::DecommissionStatus;
     use quickwit_config::{ConfigFormat, SourceConfig};
     use quickwit_indexing::mock_split;
     use quickwit_ingest::CommitType;
-    use quickwit_metastore::IndexMetadata;
-    use quickwit_search::SearchResponseRest;
-    use quickwit_serve::{ListSplitsQueryParams, SearchRequestQueryString};
+    use quickwit_metastore::checkpoint::{PartitionId, Position};
+    use quickwit_metastore::{IndexFieldStats, IndexMetadata};
+    use quickwit_proto::metastore_api::{DeleteQuery, DeleteTask};
+    use quickwit_search::{SearchResponseRest, SqlResponse};
+    use quickwit_serve::{
+        CheckpointRewindTarget, DeleteQueryRequest, DeleteTaskState, DeleteTaskStatus,
+        ListSplitsQueryParams, SearchRequestQueryString, SqlRequestQueryString,
+    };
     use reqwest::header::CONTENT_TYPE;
     use reqwest::{StatusCode, Url};
     use serde_json::json;
@@ -548,7 +818,7 @@ mod test {
     };
     use wiremock::{Mock, MockServer, ResponseTemplate};
 
-    use super::{QuickwitClient, Transport};
+    use super::{IndexingServiceCounters, NodeDecommissionStatus, QuickwitClient, Transport};
     use crate::error::Error;
     use crate::models::IngestSource;
 
@@ -610,6 +880,43 @@ mod test {
         );
     }
 
+    #[tokio::test]
+    async fn test_sql_endpoint() {
+        let mock_server = MockServer::start().await;
+        let server_url = Url::parse(&mock_server.uri()).unwrap();
+        let qw_client = QuickwitClient::new(Transport::new(server_url));
+        let sql_query = SqlRequestQueryString {
+            query: "SELECT count(*) FROM my-index".to_string(),
+            ..Default::default()
+        };
+        Mock::given(method("POST"))
+            .and(path("/api/v1/sql"))
+            .and(body_json(
+                json!({"query": "SELECT count(*) FROM my-index", "format": "pretty_json"}),
+            ))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(json!({
+                "columns": ["count(*)"],
+                "rows": [[42]],
+                "num_hits": 42,
+                "elapsed_time_micros": 100,
+                "errors": []
+            })))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let expected_sql_response = SqlResponse {
+            columns: vec!["count(*)".to_string()],
+            rows: vec![vec![json!(42)]],
+            num_hits: 42,
+            elapsed_time_micros: 100,
+            errors: Vec::new(),
+        };
+        assert_eq!(
+            qw_client.sql(sql_query).await.unwrap(),
+            expected_sql_response
+        );
+    }
+
     fn get_ndjson_filepath(ndjson_dataset_filename: &str) -> String {
         format!(
             "{}/resources/tests/{}",
@@ -802,6 +1109,151 @@ mod test {
             index_metadata
         );
 
+        // PUT update index
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/indexes/test-index"))
+            .and(query_param("expected_config_version", "0"))
+            .and(body_json(index_config_to_create.clone()))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(index_metadata.clone()),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let put_body = Bytes::from(serde_json::to_vec(&index_config_to_create).unwrap());
+        assert_eq!(
+            qw_client
+                .indexes()
+                .update("test-index", put_body, ConfigFormat::Json, Some(0))
+                .await
+                .unwrap(),
+            index_metadata
+        );
+
+        // PUT update index returns a conflict error
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/indexes/test-index"))
+            .respond_with(ResponseTemplate::new(StatusCode::CONFLICT))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        qw_client
+            .indexes()
+            .update("test-index", Bytes::new(), ConfigFormat::Json, Some(0))
+            .await
+            .unwrap_err();
+
+        // PUT restore index
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/indexes/test-index/restore"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(index_metadata.clone()),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client.indexes().restore("test-index").await.unwrap(),
+            index_metadata
+        );
+
+        // PUT restore index returns an error
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/indexes/test-index/restore"))
+            .respond_with(ResponseTemplate::new(StatusCode::NOT_FOUND))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        qw_client.indexes().restore("test-index").await.unwrap_err();
+
+        // PUT garbage collect index
+        let removal_info = SplitRemovalInfo {
+            removed_split_entries: vec![FileEntry {
+                file_name: "split-1.split".to_string(),
+                file_size_in_bytes: 42,
+            }],
+            failed_split_ids: Vec::new(),
+        };
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/indexes/test-index/garbage-collect"))
+            .and(query_param("dry_run", "true"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(&removal_info))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let removal_info = qw_client
+            .indexes()
+            .garbage_collect("test-index", true)
+            .await
+            .unwrap();
+        assert_eq!(removal_info.removed_split_entries.len(), 1);
+        assert_eq!(
+            removal_info.removed_split_entries[0].file_name,
+            "split-1.split"
+        );
+        assert!(removal_info.failed_split_ids.is_empty());
+
+        // GET field stats
+        let field_stats = vec![IndexFieldStats {
+            field_name: "body".to_string(),
+            num_splits: 2,
+            num_docs_with_value: 13,
+            num_null_docs: 2,
+            num_values: 20,
+            num_tokens: 45,
+            avg_value_num_bytes: 5.0,
+            fast_field_num_bytes: 0,
+        }];
+        Mock::given(method("GET"))
+            .and(path("/api/v1/indexes/test-index/field-stats"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(&field_stats))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client.indexes().field_stats("test-index").await.unwrap(),
+            field_stats
+        );
+
+        // PUT pause merges
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/indexes/test-index/merges/pause"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        qw_client
+            .indexes()
+            .pause_merges("test-index")
+            .await
+            .unwrap();
+
+        // PUT resume merges
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/indexes/test-index/merges/resume"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        qw_client
+            .indexes()
+            .resume_merges("test-index")
+            .await
+            .unwrap();
+
+        // PUT resume merges returns an error
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/indexes/test-index/merges/resume"))
+            .respond_with(ResponseTemplate::new(StatusCode::NOT_FOUND))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        qw_client
+            .indexes()
+            .resume_merges("test-index")
+            .await
+            .unwrap_err();
+
         // PUT clear index
         Mock::given(method("PUT"))
             .and(path("/api/v1/indexes/my-index/clear"))
@@ -943,6 +1395,28 @@ mod test {
             vec![source_config.clone()]
         );
 
+        // GET check source
+        Mock::given(method("GET"))
+            .and(path("/api/v1/indexes/my-index/sources/my-source-1/check"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(
+                json!({"source_id": "my-source-1", "connected": false, "error": "Timeout."}),
+            ))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client
+                .sources("my-index")
+                .check("my-source-1")
+                .await
+                .unwrap(),
+            SourceCheck {
+                source_id: "my-source-1".to_string(),
+                connected: false,
+                error: Some("Timeout.".to_string()),
+            }
+        );
+
         // Toggle source
         Mock::given(method("PUT"))
             .and(path("/api/v1/indexes/my-index/sources/my-source-1/toggle"))
@@ -1001,6 +1475,29 @@ mod test {
             .await
             .unwrap_err();
 
+        // PUT rewind checkpoint
+        Mock::given(method("PUT"))
+            .and(path(
+                "/api/v1/indexes/my-index/sources/my-source/rewind-checkpoint",
+            ))
+            .and(body_json(json!({"timestamp": 1640577000})))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK)
+                    .set_body_json(json!({"partition-0": "00000000000000000042"})),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let source_checkpoint = qw_client
+            .sources("my-index")
+            .rewind_checkpoint("my-source", &CheckpointRewindTarget::Timestamp(1640577000))
+            .await
+            .unwrap();
+        assert_eq!(
+            source_checkpoint.position_for_partition(&PartitionId::from("partition-0")),
+            Some(&Position::from(42u64))
+        );
+
         // DELETE source
         Mock::given(method("DELETE"))
             .and(path("/api/v1/indexes/my-index/sources/my-source"))
@@ -1028,6 +1525,206 @@ mod test {
             .unwrap_err();
     }
 
+    #[tokio::test]
+    async fn test_delete_tasks_endpoints() {
+        let mock_server = MockServer::start().await;
+        let server_url = Url::parse(&mock_server.uri()).unwrap();
+        let qw_client = QuickwitClient::new(Transport::new(server_url));
+        let delete_task = DeleteTask {
+            create_timestamp: 1,
+            opstamp: 1,
+            delete_query: Some(DeleteQuery {
+                index_uid: "my-index:0".to_string(),
+                start_timestamp: None,
+                end_timestamp: None,
+                query_ast: r#"{"type":"match_all"}"#.to_string(),
+            }),
+        };
+        let delete_task_status = DeleteTaskStatus {
+            delete_task: delete_task.clone(),
+            state: DeleteTaskState::Pending,
+            num_matched_splits: 2,
+            num_processed_splits: 0,
+            num_remaining_splits: 2,
+            num_remaining_docs: 10,
+            remaining_size_in_bytes: 100,
+        };
+        // GET delete tasks
+        Mock::given(method("GET"))
+            .and(path("/api/v1/my-index/delete-tasks"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK)
+                    .set_body_json(vec![delete_task_status.clone()]),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client.delete_tasks("my-index").list().await.unwrap(),
+            vec![delete_task_status.clone()]
+        );
+
+        // POST a delete task
+        Mock::given(method("POST"))
+            .and(path("/api/v1/my-index/delete-tasks"))
+            .and(body_json(json!({
+                "query": "level:debug",
+                "search_fields": [],
+                "start_timestamp": null,
+                "end_timestamp": null,
+            })))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(delete_task.clone()))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        let delete_query_request = DeleteQueryRequest {
+            query: "level:debug".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            qw_client
+                .delete_tasks("my-index")
+                .create(delete_query_request)
+                .await
+                .unwrap(),
+            delete_task
+        );
+
+        // GET a delete task
+        Mock::given(method("GET"))
+            .and(path("/api/v1/my-index/delete-tasks/1"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(delete_task_status.clone()),
+            )
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client.delete_tasks("my-index").get(1).await.unwrap(),
+            delete_task_status
+        );
+
+        // DELETE a delete task
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/my-index/delete-tasks/1"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(delete_task.clone()))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client.delete_tasks("my-index").cancel(1).await.unwrap(),
+            delete_task
+        );
+
+        // DELETE a delete task that has already been applied
+        Mock::given(method("DELETE"))
+            .and(path("/api/v1/my-index/delete-tasks/2"))
+            .respond_with(ResponseTemplate::new(StatusCode::BAD_REQUEST))
+            .up_to_n_times(1)
+            .mount(&mock_server)
+            .await;
+        qw_client
+            .delete_tasks("my-index")
+            .cancel(2)
+            .await
+            .unwrap_err();
+    }
+
+    #[tokio::test]
+    async fn test_cluster_decommission_endpoints() {
+        let mock_server = MockServer::start().await;
+        let server_url = Url::parse(&mock_server.uri()).unwrap();
+        let qw_client = QuickwitClient::new(Transport::new(server_url));
+
+        // PUT api/v1/cluster/nodes/{node_id}/decommission
+        let decommissioning = NodeDecommissionStatus {
+            node_id: "node-1".to_string(),
+            decommission_status: DecommissionStatus::Decommissioning,
+        };
+        Mock::given(method("PUT"))
+            .and(path("/api/v1/cluster/nodes/node-1/decommission"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(decommissioning.clone()),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client
+                .cluster()
+                .decommission_node("node-1")
+                .await
+                .unwrap(),
+            decommissioning
+        );
+
+        // GET api/v1/cluster/nodes/{node_id}/decommission
+        let decommissioned = NodeDecommissionStatus {
+            node_id: "node-1".to_string(),
+            decommission_status: DecommissionStatus::Decommissioned,
+        };
+        Mock::given(method("GET"))
+            .and(path("/api/v1/cluster/nodes/node-1/decommission"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(decommissioned.clone()),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client
+                .cluster()
+                .node_decommission_status("node-1")
+                .await
+                .unwrap(),
+            decommissioned
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tenants_endpoint() {
+        let mock_server = MockServer::start().await;
+        let server_url = Url::parse(&mock_server.uri()).unwrap();
+        let qw_client = QuickwitClient::new(Transport::new(server_url));
+
+        // GET api/v1/tenants
+        Mock::given(method("GET"))
+            .and(path("/api/v1/tenants"))
+            .respond_with(ResponseTemplate::new(StatusCode::OK).set_body_json(json!(["acme"])))
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client.cluster().tenants().await.unwrap(),
+            vec!["acme".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_indexing_endpoints() {
+        let mock_server = MockServer::start().await;
+        let server_url = Url::parse(&mock_server.uri()).unwrap();
+        let qw_client = QuickwitClient::new(Transport::new(server_url));
+
+        // POST api/v1/indexing/drain
+        let indexing_counters = IndexingServiceCounters {
+            num_running_pipelines: 2,
+            ..Default::default()
+        };
+        Mock::given(method("POST"))
+            .and(path("/api/v1/indexing/drain"))
+            .respond_with(
+                ResponseTemplate::new(StatusCode::OK).set_body_json(indexing_counters.clone()),
+            )
+            .expect(1)
+            .mount(&mock_server)
+            .await;
+        assert_eq!(
+            qw_client.indexing().drain().await.unwrap(),
+            indexing_counters
+        );
+    }
+
     #[tokio::test]
     async fn test_health_endpoints() {
         let mock_server = MockServer::start().await;
@@ -24,11 +24,10 @@ use std::time::Duration;
 
 use async_trait::async_trait;
 use futures::{StreamExt, TryStreamExt};
-use http::Uri;
+use quickwit_common::tower::make_endpoint;
 use quickwit_config::service::QuickwitService;
 use quickwit_grpc_clients::service_client_pool::ServiceClient;
 use quickwit_proto::tonic::codegen::InterceptedService;
-use quickwit_proto::tonic::transport::Endpoint;
 use quickwit_proto::{tonic, LeafSearchStreamResponse, SpanContextInterceptor};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tonic::transport::Channel;
@@ -245,13 +244,8 @@ impl SearchServiceClient {
 pub async fn create_search_service_client(
     grpc_addr: SocketAddr,
 ) -> anyhow::Result<SearchServiceClient> {
-    let uri = Uri::builder()
-        .scheme("http")
-        .authority(grpc_addr.to_string().as_str())
-        .path_and_query("/")
-        .build()?;
     // Create a channel with connect_lazy to automatically reconnect to the node.
-    let channel = Endpoint::from(uri)
+    let channel = make_endpoint(grpc_addr)
         .connect_timeout(Duration::from_secs(5))
         .connect_lazy();
     let client = quickwit_proto::search_service_client::SearchServiceClient::with_interceptor(
@@ -306,6 +306,8 @@ mod tests {
             fast_field: "fast".to_string(),
             output_format: 0,
             partition_by_field: None,
+            sort_by_split_id: false,
+            start_after_split_id: None,
         };
         LeafSearchStreamRequest {
             request: Some(search_request),
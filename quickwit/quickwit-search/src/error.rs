@@ -44,6 +44,20 @@ pub enum SearchError {
     InvalidQuery(String),
 }
 
+impl SearchError {
+    /// Returns the kind of the error, used to label the search error metrics.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SearchError::IndexDoesNotExist { .. } => "index_does_not_exist",
+            SearchError::InternalError(_) => "internal",
+            SearchError::StorageResolverError(_) => "storage_resolver",
+            SearchError::InvalidAggregationRequest(_) => "invalid_aggregation_request",
+            SearchError::InvalidArgument(_) => "invalid_argument",
+            SearchError::InvalidQuery(_) => "invalid_query",
+        }
+    }
+}
+
 impl ServiceError for SearchError {
     fn status_code(&self) -> ServiceErrorCode {
         match self {
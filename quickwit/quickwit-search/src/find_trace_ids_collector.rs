@@ -233,7 +233,7 @@ impl SegmentCollector for FindTraceIdsSegmentCollector {
     }
 
     fn harvest(self) -> Self::Fruit {
-        let mut buffer = String::with_capacity(TraceId::BASE64_LENGTH);
+        let mut buffer = String::with_capacity(TraceId::HEX_LENGTH);
         self.select_trace_ids
             .harvest()
             .into_iter()
@@ -244,10 +244,12 @@ impl SegmentCollector for FindTraceIdsSegmentCollector {
                     .ord_to_str(trace_id_term_ord.term_ord, &mut buffer)
                     .expect("Failed to lookup trace ID in the column term dictionary");
                 debug_assert!(found_term);
-                debug_assert_eq!(buffer.len(), TraceId::BASE64_LENGTH);
+                debug_assert!(
+                    buffer.len() == TraceId::BASE64_LENGTH || buffer.len() == TraceId::HEX_LENGTH
+                );
                 let trace_id = buffer[..]
                     .parse()
-                    .expect("The term dict should store Base64 trace IDs.");
+                    .expect("The term dict should store Base64 or hex trace IDs.");
                 Span::new(trace_id, span_timestamp)
             })
             .collect()
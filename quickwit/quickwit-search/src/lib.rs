@@ -31,12 +31,15 @@ mod filters;
 mod find_trace_ids_collector;
 mod leaf;
 mod leaf_cache;
+mod promql;
 mod retry;
 mod root;
 mod search_job_placer;
 mod search_response_rest;
 mod search_stream;
+mod searcher_load;
 mod service;
+mod sql;
 mod thread_pool;
 
 mod metrics;
@@ -49,7 +52,6 @@ use metrics::SEARCH_METRICS;
 use quickwit_doc_mapper::DocMapper;
 use quickwit_query::query_ast::QueryAst;
 use root::{finalize_aggregation, validate_request};
-use service::SearcherContext;
 use tantivy::schema::NamedFieldDocument;
 
 /// Refer to this as `crate::Result<T>`.
@@ -61,25 +63,34 @@ use std::sync::Arc;
 use anyhow::Context;
 pub use find_trace_ids_collector::FindTraceIdsCollector;
 use itertools::Itertools;
-use quickwit_config::{build_doc_mapper, QuickwitConfig, SearcherConfig};
+use quickwit_config::{build_doc_mapper, SearcherConfig};
+use quickwit_doc_mapper::range_pruning::may_match_field_ranges;
 use quickwit_doc_mapper::tag_pruning::extract_tags_from_query;
-use quickwit_metastore::{ListSplitsQuery, Metastore, SplitMetadata, SplitState};
+use quickwit_metastore::{
+    split_bloom_filter, ListSplitsQuery, Metastore, SplitMetadata, SplitState,
+};
 use quickwit_proto::{
     Hit, IndexUid, PartialHit, SearchRequest, SearchResponse, SplitIdAndFooterOffsets,
 };
 use quickwit_storage::StorageUriResolver;
 use tantivy::DocAddress;
+use tracing::debug;
 
 pub use crate::client::{create_search_service_client, SearchServiceClient};
 pub use crate::cluster_client::ClusterClient;
 pub use crate::error::{parse_grpc_error, SearchError};
 use crate::fetch_docs::fetch_docs;
 use crate::leaf::{leaf_list_terms, leaf_search};
+pub use crate::promql::{
+    promql_query, promql_query_range, PromqlData, PromqlResponse, PromqlResultType, PromqlSeries,
+};
 pub use crate::root::{jobs_to_leaf_request, root_list_terms, root_search, SearchJob};
 pub use crate::search_job_placer::SearchJobPlacer;
 pub use crate::search_response_rest::SearchResponseRest;
 pub use crate::search_stream::root_search_stream;
-pub use crate::service::{MockSearchService, SearchService, SearchServiceImpl};
+pub use crate::searcher_load::{start_searcher_load_reporting, SearcherLoadTracker};
+pub use crate::service::{MockSearchService, SearchService, SearchServiceImpl, SearcherContext};
+pub use crate::sql::{sql_index_id, sql_search, SqlResponse};
 use crate::thread_pool::run_cpu_intensive;
 
 /// GlobalDocAddress serves as a hit address.
@@ -147,15 +158,31 @@ async fn list_relevant_splits(
             search_request.query_ast
         ))
     })?;
-    if let Some(tags_filter) = extract_tags_from_query(query_ast) {
-        query = query.with_tags_filter(tags_filter);
+    let tags_filter_opt = extract_tags_from_query(query_ast.clone());
+    if let Some(tags_filter) = tags_filter_opt.clone() {
+        query = query.with_tags_filter(tags_filter).with_bloom_filters();
     }
 
     let split_metas = metastore.list_splits(query).await?;
-    Ok(split_metas
+    let num_splits = split_metas.len();
+    // The bloom filters and field ranges are evaluated here rather than by the metastore, which
+    // cannot query them.
+    let relevant_split_metas: Vec<SplitMetadata> = split_metas
         .into_iter()
         .map(|metadata| metadata.split_metadata)
-        .collect::<Vec<_>>())
+        .filter(|split_metadata| split_bloom_filter(split_metadata, tags_filter_opt.as_ref()))
+        .filter(|split_metadata| {
+            split_metadata.field_ranges.is_empty()
+                || may_match_field_ranges(&query_ast, &split_metadata.field_ranges)
+        })
+        .collect();
+    if relevant_split_metas.len() < num_splits {
+        debug!(
+            num_pruned_splits = num_splits - relevant_split_metas.len(),
+            "Pruned splits with bloom filters and field ranges."
+        );
+    }
+    Ok(relevant_split_metas)
 }
 
 /// Converts a Tantivy `NamedFieldDocument` into a json string using the
@@ -272,10 +299,10 @@ pub async fn single_node_search(
 
 /// Starts a search node, aka a `searcher`.
 pub async fn start_searcher_service(
-    quickwit_config: &QuickwitConfig,
     metastore: Arc<dyn Metastore>,
     storage_uri_resolver: StorageUriResolver,
     search_job_placer: SearchJobPlacer,
+    searcher_context: Arc<SearcherContext>,
 ) -> anyhow::Result<Arc<dyn SearchService>> {
     let cluster_client = ClusterClient::new(search_job_placer.clone());
     let search_service = Arc::new(SearchServiceImpl::new(
@@ -283,7 +310,7 @@ pub async fn start_searcher_service(
         storage_uri_resolver,
         cluster_client,
         search_job_placer,
-        quickwit_config.searcher_config.clone(),
+        searcher_context,
     ));
     Ok(search_service)
 }
@@ -19,15 +19,84 @@
 
 // See https://prometheus.io/docs/practices/naming/
 
+use std::time::Duration;
+
 use once_cell::sync::Lazy;
 use quickwit_common::metrics::{
-    new_counter, new_gauge, new_histogram, Histogram, IntCounter, IntGauge,
+    new_counter, new_counter_vec, new_gauge, new_histogram, new_histogram_vec,
+    new_histogram_vec_with_buckets, Histogram, HistogramVec, IntCounter, IntCounterVec, IntGauge,
 };
 
 pub struct SearchMetrics {
     pub leaf_searches_splits_total: IntCounter,
     pub leaf_search_split_duration_secs: Histogram,
     pub active_search_threads_count: IntGauge,
+    pub root_search_requests_total: IntCounterVec<1>,
+    pub root_search_errors_total: IntCounterVec<2>,
+    pub root_search_duration_seconds: HistogramVec<2>,
+    pub root_search_targeted_splits: HistogramVec<1>,
+    pub leaf_search_requests_total: IntCounterVec<1>,
+    pub leaf_search_errors_total: IntCounterVec<2>,
+    pub leaf_search_duration_seconds: HistogramVec<2>,
+}
+
+impl SearchMetrics {
+    /// Records the outcome and the duration of a root search request on an index.
+    pub(crate) fn record_root_search<T>(
+        &self,
+        index_id: &str,
+        elapsed: Duration,
+        result: &crate::Result<T>,
+    ) {
+        record_search_request(
+            &self.root_search_requests_total,
+            &self.root_search_errors_total,
+            &self.root_search_duration_seconds,
+            index_id,
+            elapsed,
+            result,
+        );
+    }
+
+    /// Records the outcome and the duration of a leaf search request on an index.
+    pub(crate) fn record_leaf_search<T>(
+        &self,
+        index_id: &str,
+        elapsed: Duration,
+        result: &crate::Result<T>,
+    ) {
+        record_search_request(
+            &self.leaf_search_requests_total,
+            &self.leaf_search_errors_total,
+            &self.leaf_search_duration_seconds,
+            index_id,
+            elapsed,
+            result,
+        );
+    }
+}
+
+fn record_search_request<T>(
+    requests_total: &IntCounterVec<1>,
+    errors_total: &IntCounterVec<2>,
+    duration_seconds: &HistogramVec<2>,
+    index_id: &str,
+    elapsed: Duration,
+    result: &crate::Result<T>,
+) {
+    requests_total.with_label_values([index_id]).inc();
+
+    let is_error = if let Err(error) = result {
+        errors_total
+            .with_label_values([index_id, error.kind()])
+            .inc();
+        "true"
+    } else {
+        "false"
+    };
+    duration_seconds
+        .with_label_values([index_id, is_error])
+        .observe(elapsed.as_secs_f64());
 }
 
 impl Default for SearchMetrics {
@@ -49,6 +118,52 @@ impl Default for SearchMetrics {
                 "Number of threads in use in the CPU thread pool",
                 "quickwit_search",
             ),
+            root_search_requests_total: new_counter_vec(
+                "root_search_requests_total",
+                "Number of root search requests.",
+                "quickwit_search",
+                ["index"],
+            ),
+            root_search_errors_total: new_counter_vec(
+                "root_search_errors_total",
+                "Number of failed root search requests.",
+                "quickwit_search",
+                ["index", "error_kind"],
+            ),
+            root_search_duration_seconds: new_histogram_vec(
+                "root_search_duration_seconds",
+                "Duration of root search requests, from the listing of the splits to the fetching \
+                 of the documents.",
+                "quickwit_search",
+                ["index", "error"],
+            ),
+            root_search_targeted_splits: new_histogram_vec_with_buckets(
+                "root_search_targeted_splits",
+                "Number of splits targeted by a root search request.",
+                "quickwit_search",
+                ["index"],
+                vec![
+                    1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0, 200.0, 500.0, 1_000.0, 2_000.0, 5_000.0,
+                ],
+            ),
+            leaf_search_requests_total: new_counter_vec(
+                "leaf_search_requests_total",
+                "Number of leaf search requests.",
+                "quickwit_search",
+                ["index"],
+            ),
+            leaf_search_errors_total: new_counter_vec(
+                "leaf_search_errors_total",
+                "Number of failed leaf search requests.",
+                "quickwit_search",
+                ["index", "error_kind"],
+            ),
+            leaf_search_duration_seconds: new_histogram_vec(
+                "leaf_search_duration_seconds",
+                "Duration of leaf search requests over the splits assigned to the node.",
+                "quickwit_search",
+                ["index", "error"],
+            ),
         }
     }
 }
@@ -0,0 +1,867 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+mod parser;
+
+use std::collections::BTreeMap;
+
+use anyhow::{bail, Context};
+use quickwit_proto::{SearchRequest, SearchResponse};
+use quickwit_query::query_ast::{BoolQuery, QueryAst, TermQuery};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+use tantivy::time::format_description::well_known::Rfc3339;
+use tantivy::time::OffsetDateTime;
+
+use self::parser::{
+    parse_duration_secs, parse_metrics_expr, AggregationOperator, MetricsExpr, RangeFunction,
+    MAX_DURATION_SECS,
+};
+use crate::{SearchError, SearchService};
+
+/// Maximum number of points returned for each series by a range query.
+const MAX_POINTS_PER_SERIES: i64 = 11_000;
+
+/// Maximum number of buckets of the date histogram computed for each series.
+const MAX_HISTOGRAM_BUCKETS: i64 = 11_000;
+
+/// Maximum number of distinct values returned for each label of the `by` clause.
+const MAX_LABEL_VALUES: u64 = 100;
+
+/// Earliest timestamp accepted by the query APIs, `0000-01-01T00:00:00Z`.
+const MIN_TIMESTAMP_SECS: i64 = -62_167_219_200;
+
+/// Latest timestamp accepted by the query APIs, `9999-12-31T23:59:59Z`.
+const MAX_TIMESTAMP_SECS: i64 = 253_402_300_799;
+
+/// Window over which an instant query without range function aggregates the values.
+const DEFAULT_LOOKBACK_SECS: i64 = 5 * 60;
+
+const HISTOGRAM_AGGREGATION_NAME: &str = "histogram";
+
+const STATS_AGGREGATION_NAME: &str = "stats";
+
+/// PromqlResponse represents the response returned by the Prometheus-compatible query API and
+/// follows the format of the Prometheus HTTP API.
+#[derive(Serialize, Deserialize, PartialEq, Debug, utoipa::ToSchema)]
+pub struct PromqlResponse {
+    /// Always `success`, errors are returned with an error status code.
+    pub status: String,
+    /// Result of the query.
+    pub data: PromqlData,
+}
+
+/// Result of a metrics query.
+#[derive(Serialize, Deserialize, PartialEq, Debug, utoipa::ToSchema)]
+pub struct PromqlData {
+    /// `matrix` for range queries, `vector` for instant queries.
+    #[serde(rename = "resultType")]
+    pub result_type: PromqlResultType,
+    /// One entry per series.
+    pub result: Vec<PromqlSeries>,
+}
+
+/// Type of the result of a metrics query.
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy, utoipa::ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PromqlResultType {
+    /// Series of points returned by range queries.
+    Matrix,
+    /// Single point per series returned by instant queries.
+    Vector,
+}
+
+/// A series, identified by the values of the labels of the `by` clause.
+#[derive(Serialize, Deserialize, PartialEq, Debug, utoipa::ToSchema)]
+pub struct PromqlSeries {
+    /// Label values of the series.
+    #[schema(value_type = Object)]
+    pub metric: BTreeMap<String, String>,
+    /// Points of the series as `[<timestamp in seconds>, "<value>"]` pairs, for range queries.
+    #[schema(value_type = Vec<Vec<Object>>)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<(i64, String)>,
+    /// Point of the series, for instant queries.
+    #[schema(value_type = Option<Vec<Object>>)]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<(i64, String)>,
+}
+
+/// Evaluates a metrics query over the range `[start, end]` with a resolution of `step`, as the
+/// Prometheus `query_range` API.
+///
+/// `start` and `end` are expressed in seconds since epoch or as RFC 3339 dates, `step` in
+/// seconds or as a duration such as `1m`. Points are evaluated at the multiples of `step`.
+pub async fn promql_query_range(
+    index_id: &str,
+    timestamp_field: &str,
+    query: &str,
+    start: &str,
+    end: &str,
+    step: &str,
+    search_service: &dyn SearchService,
+) -> crate::Result<PromqlResponse> {
+    let start_timestamp = parse_timestamp(start).map_err(invalid_argument)?;
+    let end_timestamp = parse_timestamp(end).map_err(invalid_argument)?;
+    let step_secs = parse_step(step).map_err(invalid_argument)?;
+
+    if start_timestamp > end_timestamp {
+        return Err(SearchError::InvalidArgument(
+            "`end` must not be before `start`.".to_string(),
+        ));
+    }
+    let first_timestamp = start_timestamp
+        .checked_add(step_secs - 1)
+        .ok_or_else(|| {
+            SearchError::InvalidArgument("`start` and `step` are out of range.".to_string())
+        })?
+        .div_euclid(step_secs)
+        * step_secs;
+    let last_timestamp = end_timestamp.div_euclid(step_secs) * step_secs;
+
+    let promql_plan = parse_metrics_expr(query)
+        .and_then(|metrics_expr| {
+            let window_secs = metrics_expr.window_secs().unwrap_or(step_secs);
+            PromqlPlan::try_new(
+                index_id,
+                timestamp_field,
+                metrics_expr,
+                first_timestamp,
+                last_timestamp,
+                step_secs,
+                window_secs,
+            )
+        })
+        .map_err(invalid_query)?;
+    promql_plan
+        .execute(PromqlResultType::Matrix, search_service)
+        .await
+}
+
+/// Evaluates a metrics query at the instant `time`, as the Prometheus `query` API. `time`
+/// defaults to the current time.
+///
+/// The evaluation time is rounded down to a multiple of the greatest common divisor of the
+/// range window and one minute.
+pub async fn promql_query(
+    index_id: &str,
+    timestamp_field: &str,
+    query: &str,
+    time_opt: Option<&str>,
+    search_service: &dyn SearchService,
+) -> crate::Result<PromqlResponse> {
+    let timestamp = match time_opt {
+        Some(time) => parse_timestamp(time).map_err(invalid_argument)?,
+        None => OffsetDateTime::now_utc().unix_timestamp(),
+    };
+    let promql_plan = parse_metrics_expr(query)
+        .and_then(|metrics_expr| {
+            let window_secs = metrics_expr.window_secs().unwrap_or(DEFAULT_LOOKBACK_SECS);
+            let step_secs = gcd(window_secs, 60);
+            let timestamp = timestamp - timestamp.rem_euclid(step_secs);
+            PromqlPlan::try_new(
+                index_id,
+                timestamp_field,
+                metrics_expr,
+                timestamp,
+                timestamp,
+                step_secs,
+                window_secs,
+            )
+        })
+        .map_err(invalid_query)?;
+    promql_plan
+        .execute(PromqlResultType::Vector, search_service)
+        .await
+}
+
+fn invalid_argument(error: anyhow::Error) -> SearchError {
+    SearchError::InvalidArgument(error.to_string())
+}
+
+fn invalid_query(error: anyhow::Error) -> SearchError {
+    SearchError::InvalidQuery(format!("Invalid PromQL query: {error}"))
+}
+
+/// Parses a timestamp expressed in seconds since epoch or as a RFC 3339 date. Timestamps must lie
+/// between the years 0000 and 9999.
+fn parse_timestamp(timestamp: &str) -> anyhow::Result<i64> {
+    let timestamp_secs = if let Ok(timestamp_secs) = timestamp.parse::<f64>() {
+        if !timestamp_secs.is_finite() {
+            bail!("Invalid timestamp `{timestamp}`.");
+        }
+        // Out of range values saturate and are rejected below.
+        timestamp_secs.floor() as i64
+    } else {
+        OffsetDateTime::parse(timestamp, &Rfc3339)
+            .with_context(|| {
+                format!(
+                    "Invalid timestamp `{timestamp}`. Expected seconds since epoch or a RFC 3339 \
+                     date."
+                )
+            })?
+            .unix_timestamp()
+    };
+    if !(MIN_TIMESTAMP_SECS..=MAX_TIMESTAMP_SECS).contains(&timestamp_secs) {
+        bail!("Timestamp `{timestamp}` is out of range.");
+    }
+    Ok(timestamp_secs)
+}
+
+/// Parses a step expressed in seconds or as a duration. Steps are rounded up to the second and
+/// cannot exceed [`MAX_DURATION_SECS`].
+fn parse_step(step: &str) -> anyhow::Result<i64> {
+    let Ok(step_secs) = step.parse::<f64>() else {
+        return parse_duration_secs(step).map(|step_secs| step_secs as i64);
+    };
+    if !step_secs.is_finite() || step_secs <= 0.0 {
+        bail!("Invalid step `{step}`. Step must be a positive number of seconds.");
+    }
+    let step_secs = step_secs.ceil();
+
+    if step_secs > MAX_DURATION_SECS as f64 {
+        bail!("Step `{step}` exceeds the maximum of {MAX_DURATION_SECS} seconds.");
+    }
+    Ok(step_secs as i64)
+}
+
+fn gcd(mut left: i64, mut right: i64) -> i64 {
+    while right != 0 {
+        (left, right) = (right, left % right);
+    }
+    left
+}
+
+/// Statistics of the values of the documents of a bucket or window. When counting documents,
+/// `sum` holds the number of documents.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ValueStats {
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Default for ValueStats {
+    fn default() -> Self {
+        Self {
+            count: 0,
+            sum: 0.0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+        }
+    }
+}
+
+impl ValueStats {
+    fn merge(&mut self, other: &ValueStats) {
+        self.count += other.count;
+        self.sum += other.sum;
+        self.min = self.min.min(other.min);
+        self.max = self.max.max(other.max);
+    }
+}
+
+/// A metrics query translated into a search request computing a date histogram per series,
+/// along with the information required to evaluate the points from the histogram buckets.
+#[derive(Debug)]
+struct PromqlPlan {
+    search_request: SearchRequest,
+    metrics_expr: MetricsExpr,
+    first_timestamp: i64,
+    last_timestamp: i64,
+    step_secs: i64,
+    window_secs: i64,
+}
+
+fn label_aggregation_name(level: usize) -> String {
+    format!("label_{level}")
+}
+
+impl PromqlPlan {
+    fn try_new(
+        index_id: &str,
+        timestamp_field: &str,
+        metrics_expr: MetricsExpr,
+        first_timestamp: i64,
+        last_timestamp: i64,
+        step_secs: i64,
+        window_secs: i64,
+    ) -> anyhow::Result<Self> {
+        let field_opt = metrics_expr.selector.field_opt.as_ref();
+
+        match (
+            metrics_expr.range_function_opt,
+            metrics_expr.aggregation_opt,
+        ) {
+            (Some(_), Some(AggregationOperator::Sum) | None) => {}
+            (Some(_), Some(_)) => bail!("Only `sum` can aggregate the result of a range function."),
+            (None, Some(AggregationOperator::Count)) => {}
+            (None, _) if field_opt.is_none() => bail!(
+                "A selector without metric name can only be aggregated with `count` or passed to \
+                 `count_over_time`, `rate` or `increase`."
+            ),
+            (None, _) => {}
+        }
+        if let Some((range_function, _)) = metrics_expr.range_function_opt {
+            let counts_documents = matches!(
+                range_function,
+                RangeFunction::Rate | RangeFunction::Increase | RangeFunction::CountOverTime
+            );
+            if field_opt.is_none() && !counts_documents {
+                bail!("`{range_function}` requires a metric name.");
+            }
+        }
+        let num_points = if last_timestamp >= first_timestamp {
+            (last_timestamp - first_timestamp) / step_secs + 1
+        } else {
+            0
+        };
+        if num_points > MAX_POINTS_PER_SERIES {
+            bail!(
+                "Exceeded maximum resolution of {MAX_POINTS_PER_SERIES} points per series. Try \
+                 decreasing the query resolution."
+            );
+        }
+        let interval_secs = gcd(step_secs, window_secs);
+        let num_buckets = (last_timestamp - first_timestamp + window_secs) / interval_secs;
+
+        if num_buckets > MAX_HISTOGRAM_BUCKETS {
+            bail!(
+                "The range window and the step require more than {MAX_HISTOGRAM_BUCKETS} buckets \
+                 per series. Try using a step and a window that are multiples of each other."
+            );
+        }
+        let mut histogram_aggregation = json!({
+            "date_histogram": {
+                "field": timestamp_field,
+                "fixed_interval": format!("{interval_secs}s"),
+            }
+        });
+        if let Some(field) = field_opt {
+            histogram_aggregation["aggs"] = json!({
+                STATS_AGGREGATION_NAME: {
+                    "stats": { "field": field }
+                }
+            });
+        }
+        let mut aggregations = JsonMap::new();
+        aggregations.insert(
+            HISTOGRAM_AGGREGATION_NAME.to_string(),
+            histogram_aggregation,
+        );
+
+        for (level, label) in metrics_expr.group_by.iter().enumerate().rev() {
+            let label_aggregation = json!({
+                "terms": {
+                    "field": label,
+                    "size": MAX_LABEL_VALUES,
+                },
+                "aggs": aggregations,
+            });
+            aggregations = JsonMap::new();
+            aggregations.insert(label_aggregation_name(level), label_aggregation);
+        }
+        let search_request = SearchRequest {
+            index_id: index_id.to_string(),
+            query_ast: serde_json::to_string(&selector_query_ast(&metrics_expr))?,
+            start_timestamp: Some(first_timestamp - window_secs),
+            end_timestamp: Some(last_timestamp),
+            max_hits: 0,
+            aggregation_request: Some(serde_json::to_string(&aggregations)?),
+            ..Default::default()
+        };
+        Ok(Self {
+            search_request,
+            metrics_expr,
+            first_timestamp,
+            last_timestamp,
+            step_secs,
+            window_secs,
+        })
+    }
+
+    async fn execute(
+        self,
+        result_type: PromqlResultType,
+        search_service: &dyn SearchService,
+    ) -> crate::Result<PromqlResponse> {
+        // An empty range has no points to evaluate.
+        let search_response = if self.first_timestamp <= self.last_timestamp {
+            search_service
+                .root_search(self.search_request.clone())
+                .await?
+        } else {
+            SearchResponse::default()
+        };
+        let aggregation_results: JsonValue = match &search_response.aggregation {
+            Some(aggregation_json) => serde_json::from_str(aggregation_json)?,
+            None => JsonValue::Object(JsonMap::new()),
+        };
+        let mut series_buckets = Vec::new();
+        collect_series_buckets(
+            &aggregation_results,
+            &self.metrics_expr.group_by,
+            self.metrics_expr.selector.field_opt.is_some(),
+            0,
+            &mut BTreeMap::new(),
+            &mut series_buckets,
+        );
+        let mut result = Vec::with_capacity(series_buckets.len());
+
+        for (metric, buckets) in series_buckets {
+            let values = self.evaluate_points(&buckets);
+
+            let series = match result_type {
+                PromqlResultType::Matrix if values.is_empty() => continue,
+                PromqlResultType::Matrix => PromqlSeries {
+                    metric,
+                    values,
+                    value: None,
+                },
+                PromqlResultType::Vector => {
+                    let Some(value) = values.into_iter().next() else {
+                        continue;
+                    };
+                    PromqlSeries {
+                        metric,
+                        values: Vec::new(),
+                        value: Some(value),
+                    }
+                }
+            };
+            result.push(series);
+        }
+        Ok(PromqlResponse {
+            status: "success".to_string(),
+            data: PromqlData {
+                result_type,
+                result,
+            },
+        })
+    }
+
+    /// Evaluates the points of a series from its histogram buckets, sorted by timestamp. The
+    /// point at timestamp `t` is computed from the buckets of the window `[t - window, t)`.
+    fn evaluate_points(&self, buckets: &[(i64, ValueStats)]) -> Vec<(i64, String)> {
+        let mut points = Vec::new();
+
+        for timestamp in
+            (self.first_timestamp..=self.last_timestamp).step_by(self.step_secs as usize)
+        {
+            let window_start = buckets.partition_point(|(bucket_timestamp, _)| {
+                *bucket_timestamp < timestamp - self.window_secs
+            });
+            let window_end =
+                buckets.partition_point(|(bucket_timestamp, _)| *bucket_timestamp < timestamp);
+            let mut window_stats = ValueStats::default();
+
+            for (_, bucket_stats) in &buckets[window_start..window_end] {
+                window_stats.merge(bucket_stats);
+            }
+            if let Some(value) = self.evaluate_window(&window_stats) {
+                points.push((timestamp, value.to_string()));
+            }
+        }
+        points
+    }
+
+    /// Computes the value of a point. Returns `None` if the window holds no values, except for
+    /// functions counting the values.
+    fn evaluate_window(&self, window_stats: &ValueStats) -> Option<f64> {
+        let count = window_stats.count as f64;
+        let is_empty = window_stats.count == 0;
+
+        let value = match self.metrics_expr.range_function_opt {
+            Some((RangeFunction::Rate, window_secs)) => window_stats.sum / window_secs as f64,
+            Some((RangeFunction::Increase, _)) => window_stats.sum,
+            Some((RangeFunction::CountOverTime, _)) => count,
+            _ if is_empty => return None,
+            Some((RangeFunction::SumOverTime, _)) => window_stats.sum,
+            Some((RangeFunction::AvgOverTime, _)) => window_stats.sum / count,
+            Some((RangeFunction::MinOverTime, _)) => window_stats.min,
+            Some((RangeFunction::MaxOverTime, _)) => window_stats.max,
+            None => match self.metrics_expr.aggregation_opt {
+                Some(AggregationOperator::Sum) => window_stats.sum,
+                Some(AggregationOperator::Avg) | None => window_stats.sum / count,
+                Some(AggregationOperator::Min) => window_stats.min,
+                Some(AggregationOperator::Max) => window_stats.max,
+                Some(AggregationOperator::Count) => count,
+            },
+        };
+        Some(value)
+    }
+}
+
+/// Builds the query matching the documents selected by the label matchers.
+fn selector_query_ast(metrics_expr: &MetricsExpr) -> QueryAst {
+    let mut bool_query = BoolQuery::default();
+
+    for matcher in &metrics_expr.selector.matchers {
+        let term_query: QueryAst = TermQuery {
+            field: matcher.label.clone(),
+            value: matcher.value.clone(),
+        }
+        .into();
+        if matcher.negated {
+            bool_query.must_not.push(term_query);
+        } else {
+            bool_query.must.push(term_query);
+        }
+    }
+    if bool_query.must.is_empty() {
+        // A boolean query with only `must_not` clauses matches no documents.
+        if bool_query.must_not.is_empty() {
+            return QueryAst::MatchAll;
+        }
+        bool_query.must.push(QueryAst::MatchAll);
+    }
+    bool_query.into()
+}
+
+/// Flattens the nested label buckets into one list of histogram buckets per series.
+fn collect_series_buckets(
+    aggregation_results: &JsonValue,
+    group_by: &[String],
+    has_field: bool,
+    level: usize,
+    labels: &mut BTreeMap<String, String>,
+    series_buckets: &mut Vec<(BTreeMap<String, String>, Vec<(i64, ValueStats)>)>,
+) {
+    if level == group_by.len() {
+        let histogram_results = &aggregation_results[HISTOGRAM_AGGREGATION_NAME];
+        let Some(buckets) = histogram_results["buckets"].as_array() else {
+            return;
+        };
+        let histogram_buckets = buckets
+            .iter()
+            .filter_map(|bucket| {
+                let timestamp_millis = bucket["key"].as_f64()?;
+                let doc_count = bucket["doc_count"].as_u64().unwrap_or(0);
+                let stats = &bucket[STATS_AGGREGATION_NAME];
+                let bucket_stats = if has_field {
+                    ValueStats {
+                        count: stats["count"].as_u64().unwrap_or(0),
+                        sum: stats["sum"].as_f64().unwrap_or(0.0),
+                        min: stats["min"].as_f64().unwrap_or(f64::INFINITY),
+                        max: stats["max"].as_f64().unwrap_or(f64::NEG_INFINITY),
+                    }
+                } else {
+                    ValueStats {
+                        count: doc_count,
+                        sum: doc_count as f64,
+                        ..Default::default()
+                    }
+                };
+                let timestamp = (timestamp_millis / 1_000.0).floor() as i64;
+                Some((timestamp, bucket_stats))
+            })
+            .collect();
+        series_buckets.push((labels.clone(), histogram_buckets));
+        return;
+    }
+    let label_results = &aggregation_results[label_aggregation_name(level)];
+    let Some(buckets) = label_results["buckets"].as_array() else {
+        return;
+    };
+    for bucket in buckets {
+        let label_value = match &bucket["key"] {
+            JsonValue::String(key) => key.clone(),
+            key => key.to_string(),
+        };
+        labels.insert(group_by[level].clone(), label_value);
+        collect_series_buckets(
+            bucket,
+            group_by,
+            has_field,
+            level + 1,
+            labels,
+            series_buckets,
+        );
+        labels.remove(&group_by[level]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockSearchService;
+
+    fn plan(query: &str, first_timestamp: i64, last_timestamp: i64) -> anyhow::Result<PromqlPlan> {
+        let metrics_expr = parse_metrics_expr(query)?;
+        let window_secs = metrics_expr.window_secs().unwrap_or(60);
+        PromqlPlan::try_new(
+            "metrics",
+            "timestamp",
+            metrics_expr,
+            first_timestamp,
+            last_timestamp,
+            60,
+            window_secs,
+        )
+    }
+
+    #[test]
+    fn test_parse_timestamp_and_step() {
+        assert_eq!(parse_timestamp("1681292400").unwrap(), 1_681_292_400);
+        assert_eq!(parse_timestamp("1681292400.781").unwrap(), 1_681_292_400);
+        assert_eq!(
+            parse_timestamp("2023-04-12T09:40:00Z").unwrap(),
+            1_681_292_400
+        );
+        parse_timestamp("yesterday").unwrap_err();
+        parse_timestamp("NaN").unwrap_err();
+
+        assert_eq!(parse_step("15").unwrap(), 15);
+        assert_eq!(parse_step("0.5").unwrap(), 1);
+        assert_eq!(parse_step("2m").unwrap(), 120);
+        parse_step("0").unwrap_err();
+        parse_step("-15").unwrap_err();
+
+        // Out of range timestamps and steps are rejected rather than saturated.
+        assert_eq!(parse_timestamp("253402300799").unwrap(), MAX_TIMESTAMP_SECS);
+        parse_timestamp("253402300800").unwrap_err();
+        parse_timestamp("-62167219201").unwrap_err();
+        parse_timestamp("1e300").unwrap_err();
+        parse_step("1e300").unwrap_err();
+        parse_step("100000000000000000w").unwrap_err();
+    }
+
+    #[test]
+    fn test_promql_plan_search_request() {
+        let promql_plan = plan(
+            r#"sum by (service) (rate(bytes{env="prod", status!="200"}[5m]))"#,
+            1_200,
+            3_600,
+        )
+        .unwrap();
+        let search_request = promql_plan.search_request;
+        assert_eq!(search_request.index_id, "metrics");
+        assert_eq!(search_request.max_hits, 0);
+        assert_eq!(search_request.start_timestamp, Some(900));
+        assert_eq!(search_request.end_timestamp, Some(3_600));
+
+        let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast).unwrap();
+        let expected_query_ast: QueryAst = BoolQuery {
+            must: vec![TermQuery {
+                field: "env".to_string(),
+                value: "prod".to_string(),
+            }
+            .into()],
+            must_not: vec![TermQuery {
+                field: "status".to_string(),
+                value: "200".to_string(),
+            }
+            .into()],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(query_ast, expected_query_ast);
+
+        let aggregation_request: JsonValue =
+            serde_json::from_str(search_request.aggregation_request.as_ref().unwrap()).unwrap();
+        let expected_aggregation_request = json!({
+            "label_0": {
+                "terms": {"field": "service", "size": MAX_LABEL_VALUES},
+                "aggs": {
+                    "histogram": {
+                        "date_histogram": {"field": "timestamp", "fixed_interval": "60s"},
+                        "aggs": {
+                            "stats": {"stats": {"field": "bytes"}}
+                        }
+                    }
+                }
+            }
+        });
+        assert_eq!(aggregation_request, expected_aggregation_request);
+    }
+
+    #[test]
+    fn test_promql_plan_errors() {
+        let plan_error = |query: &str| plan(query, 0, 3_600).unwrap_err().to_string();
+        assert_eq!(
+            plan_error("avg(rate(bytes[5m]))"),
+            "Only `sum` can aggregate the result of a range function."
+        );
+        assert_eq!(
+            plan_error(r#"avg_over_time({service="api"}[5m])"#),
+            "`avg_over_time` requires a metric name."
+        );
+        assert_eq!(
+            plan_error(r#"sum({service="api"})"#),
+            "A selector without metric name can only be aggregated with `count` or passed to \
+             `count_over_time`, `rate` or `increase`."
+        );
+        assert_eq!(
+            plan(r#"count_over_time({service="api"}[7s])"#, 0, 86_400)
+                .unwrap_err()
+                .to_string(),
+            "The range window and the step require more than 11000 buckets per series. Try using \
+             a step and a window that are multiples of each other."
+        );
+        assert_eq!(
+            plan("bytes", 0, 86_400 * 10).unwrap_err().to_string(),
+            "Exceeded maximum resolution of 11000 points per series. Try decreasing the query \
+             resolution."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_promql_query_range() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .withf(|search_request| {
+                search_request.start_timestamp == Some(0)
+                    && search_request.end_timestamp == Some(180)
+            })
+            .returning(|_| {
+                let aggregation = json!({
+                    "label_0": {
+                        "buckets": [
+                            {
+                                "key": "api",
+                                "doc_count": 6,
+                                "histogram": {
+                                    "buckets": [
+                                        {"key": 0.0, "doc_count": 2},
+                                        {"key": 60_000.0, "doc_count": 0},
+                                        {"key": 120_000.0, "doc_count": 4}
+                                    ]
+                                }
+                            },
+                            {
+                                "key": 404,
+                                "doc_count": 3,
+                                "histogram": {
+                                    "buckets": [{"key": 60_000.0, "doc_count": 3}]
+                                }
+                            }
+                        ]
+                    }
+                });
+                Ok(SearchResponse {
+                    num_hits: 9,
+                    aggregation: Some(aggregation.to_string()),
+                    ..Default::default()
+                })
+            });
+        let promql_response = promql_query_range(
+            "metrics",
+            "timestamp",
+            r#"sum by (service) (count_over_time({env="prod"}[1m]))"#,
+            "30",
+            "180",
+            "1m",
+            &mock_search_service,
+        )
+        .await
+        .unwrap();
+        let expected_promql_response = PromqlResponse {
+            status: "success".to_string(),
+            data: PromqlData {
+                result_type: PromqlResultType::Matrix,
+                result: vec![
+                    PromqlSeries {
+                        metric: BTreeMap::from([("service".to_string(), "api".to_string())]),
+                        values: vec![
+                            (60, "2".to_string()),
+                            (120, "0".to_string()),
+                            (180, "4".to_string()),
+                        ],
+                        value: None,
+                    },
+                    PromqlSeries {
+                        metric: BTreeMap::from([("service".to_string(), "404".to_string())]),
+                        values: vec![
+                            (60, "0".to_string()),
+                            (120, "3".to_string()),
+                            (180, "0".to_string()),
+                        ],
+                        value: None,
+                    },
+                ],
+            },
+        };
+        assert_eq!(promql_response, expected_promql_response);
+    }
+
+    #[tokio::test]
+    async fn test_promql_query_instant() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .withf(|search_request| {
+                search_request.start_timestamp == Some(300)
+                    && search_request.end_timestamp == Some(600)
+            })
+            .returning(|_| {
+                let aggregation = json!({
+                    "histogram": {
+                        "buckets": [
+                            {
+                                "key": 300_000.0,
+                                "doc_count": 2,
+                                "stats": {"count": 2, "sum": 30.0, "min": 10.0, "max": 20.0}
+                            },
+                            {
+                                "key": 360_000.0,
+                                "doc_count": 0,
+                                "stats": {"count": 0, "sum": 0.0, "min": null, "max": null}
+                            },
+                            {
+                                "key": 540_000.0,
+                                "doc_count": 1,
+                                "stats": {"count": 1, "sum": 60.0, "min": 60.0, "max": 60.0}
+                            }
+                        ]
+                    }
+                });
+                Ok(SearchResponse {
+                    num_hits: 3,
+                    aggregation: Some(aggregation.to_string()),
+                    ..Default::default()
+                })
+            });
+        let promql_response = promql_query(
+            "metrics",
+            "timestamp",
+            "max(latency)",
+            Some("1970-01-01T00:10:30Z"),
+            &mock_search_service,
+        )
+        .await
+        .unwrap();
+        let result = &promql_response.data.result;
+        assert_eq!(promql_response.data.result_type, PromqlResultType::Vector);
+        assert_eq!(result.len(), 1);
+        assert!(result[0].metric.is_empty());
+        assert_eq!(result[0].value, Some((600, "60".to_string())));
+
+        let promql_response_json = serde_json::to_value(&promql_response).unwrap();
+        let expected_promql_response_json = json!({
+            "status": "success",
+            "data": {
+                "resultType": "vector",
+                "result": [{"metric": {}, "value": [600, "60"]}]
+            }
+        });
+        assert_eq!(promql_response_json, expected_promql_response_json);
+    }
+}
@@ -0,0 +1,521 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+use anyhow::{bail, Context};
+
+/// A parsed metrics query, in the subset of PromQL supported by Quickwit:
+///
+/// ```text
+/// [<aggregation> [by (<label>, ...)]] (<range function>(<selector>[<window>]))
+/// [<aggregation> [by (<label>, ...)]] (<selector>)
+/// ```
+///
+/// The metric name of the selector is a numeric fast field, and the labels are fast fields.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct MetricsExpr {
+    pub aggregation_opt: Option<AggregationOperator>,
+    /// Labels of the `by` clause of the aggregation, identifying the returned series.
+    pub group_by: Vec<String>,
+    pub range_function_opt: Option<(RangeFunction, u64)>,
+    pub selector: Selector,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AggregationOperator {
+    Sum,
+    Avg,
+    Min,
+    Max,
+    Count,
+}
+
+impl AggregationOperator {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "sum" => Some(Self::Sum),
+            "avg" => Some(Self::Avg),
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            "count" => Some(Self::Count),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum RangeFunction {
+    Rate,
+    Increase,
+    SumOverTime,
+    AvgOverTime,
+    MinOverTime,
+    MaxOverTime,
+    CountOverTime,
+}
+
+impl RangeFunction {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "rate" => Some(Self::Rate),
+            "increase" => Some(Self::Increase),
+            "sum_over_time" => Some(Self::SumOverTime),
+            "avg_over_time" => Some(Self::AvgOverTime),
+            "min_over_time" => Some(Self::MinOverTime),
+            "max_over_time" => Some(Self::MaxOverTime),
+            "count_over_time" => Some(Self::CountOverTime),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RangeFunction {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Rate => "rate",
+            Self::Increase => "increase",
+            Self::SumOverTime => "sum_over_time",
+            Self::AvgOverTime => "avg_over_time",
+            Self::MinOverTime => "min_over_time",
+            Self::MaxOverTime => "max_over_time",
+            Self::CountOverTime => "count_over_time",
+        };
+        write!(formatter, "{name}")
+    }
+}
+
+impl MetricsExpr {
+    /// Returns the window of the range function, in seconds.
+    pub fn window_secs(&self) -> Option<i64> {
+        self.range_function_opt
+            .map(|(_, window_secs)| window_secs as i64)
+    }
+}
+
+/// Selects the documents matching the label matchers. Without metric name, the documents are
+/// counted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Selector {
+    pub field_opt: Option<String>,
+    pub matchers: Vec<LabelMatcher>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LabelMatcher {
+    pub label: String,
+    pub value: String,
+    pub negated: bool,
+}
+
+/// Longest duration accepted in queries: ten thousand years, the span of the accepted timestamps.
+pub(crate) const MAX_DURATION_SECS: u64 = 10_000 * 366 * 24 * 60 * 60;
+
+/// Parses a duration such as `30s`, `5m` or `1h30m` into seconds.
+pub(crate) fn parse_duration_secs(duration: &str) -> anyhow::Result<u64> {
+    let mut duration_secs = 0;
+    let mut chars = duration.chars().peekable();
+
+    if chars.peek().is_none() {
+        bail!("Expected a duration, got an empty string.");
+    }
+    while chars.peek().is_some() {
+        let mut amount = String::new();
+        while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+            amount.push(digit);
+        }
+        let amount: u64 = amount
+            .parse()
+            .with_context(|| format!("Invalid duration `{duration}`."))?;
+        let unit_secs = match chars.next() {
+            Some('s') => 1,
+            Some('m') => 60,
+            Some('h') => 60 * 60,
+            Some('d') => 24 * 60 * 60,
+            Some('w') => 7 * 24 * 60 * 60,
+            _ => bail!(
+                "Invalid duration `{duration}`. Supported units are `s`, `m`, `h`, `d` and `w`."
+            ),
+        };
+        duration_secs = amount
+            .checked_mul(unit_secs)
+            .and_then(|amount_secs| duration_secs.checked_add(amount_secs))
+            .filter(|duration_secs| *duration_secs <= MAX_DURATION_SECS)
+            .with_context(|| {
+                format!("Duration `{duration}` exceeds the maximum of {MAX_DURATION_SECS} seconds.")
+            })?;
+    }
+    if duration_secs == 0 {
+        bail!("Duration `{duration}` must be positive.");
+    }
+    Ok(duration_secs)
+}
+
+/// Parses a metrics query.
+pub(crate) fn parse_metrics_expr(query: &str) -> anyhow::Result<MetricsExpr> {
+    let mut parser = Parser {
+        chars: query.chars().collect(),
+        position: 0,
+    };
+    let metrics_expr = parser.parse_metrics_expr()?;
+    parser.skip_whitespaces();
+
+    if let Some(next_char) = parser.peek() {
+        bail!("Unexpected character `{next_char}` after the end of the query.");
+    }
+    Ok(metrics_expr)
+}
+
+struct Parser {
+    chars: Vec<char>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn skip_whitespaces(&mut self) {
+        while self.peek().map_or(false, char::is_whitespace) {
+            self.position += 1;
+        }
+    }
+
+    /// Skips whitespaces and consumes the next character if it is `expected_char`.
+    fn consume(&mut self, expected_char: char) -> bool {
+        self.skip_whitespaces();
+        if self.peek() == Some(expected_char) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, expected_char: char) -> anyhow::Result<()> {
+        if !self.consume(expected_char) {
+            match self.peek() {
+                Some(next_char) => bail!("Expected `{expected_char}`, got `{next_char}`."),
+                None => bail!("Expected `{expected_char}`, got end of query."),
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the next identifier without consuming it.
+    fn peek_identifier(&mut self) -> Option<String> {
+        self.skip_whitespaces();
+        let identifier: String = self.chars[self.position..]
+            .iter()
+            .take_while(|next_char| {
+                next_char.is_alphanumeric() || matches!(next_char, '_' | '.' | ':')
+            })
+            .collect();
+        if identifier.is_empty() {
+            return None;
+        }
+        Some(identifier)
+    }
+
+    fn parse_identifier(&mut self) -> anyhow::Result<String> {
+        let Some(identifier) = self.peek_identifier() else {
+            match self.peek() {
+                Some(next_char) => bail!("Expected an identifier, got `{next_char}`."),
+                None => bail!("Expected an identifier, got end of query."),
+            }
+        };
+        self.position += identifier.chars().count();
+        Ok(identifier)
+    }
+
+    /// Returns true if the next identifier is followed by `(` or by the keyword `by`.
+    fn is_followed_by_call(&self, identifier: &str) -> bool {
+        let mut position = self.position + identifier.chars().count();
+        while self
+            .chars
+            .get(position)
+            .map_or(false, |next_char| next_char.is_whitespace())
+        {
+            position += 1;
+        }
+        let rest: String = self.chars[position..].iter().take(3).collect();
+        rest.starts_with('(') || rest.starts_with("by ") || rest.starts_with("by(")
+    }
+
+    fn parse_metrics_expr(&mut self) -> anyhow::Result<MetricsExpr> {
+        let aggregation_opt = self.peek_identifier().and_then(|identifier| {
+            AggregationOperator::from_name(&identifier)
+                .filter(|_| self.is_followed_by_call(&identifier))
+                .map(|aggregation| (aggregation, identifier))
+        });
+        let Some((aggregation, identifier)) = aggregation_opt else {
+            return self.parse_inner_expr(None);
+        };
+        self.position += identifier.chars().count();
+
+        let mut group_by = self.parse_group_by()?;
+        self.expect('(')?;
+        let mut metrics_expr = self.parse_inner_expr(Some(aggregation))?;
+        self.expect(')')?;
+
+        // The `by` clause may also follow the aggregated expression.
+        if group_by.is_empty() {
+            group_by = self.parse_group_by()?;
+        }
+        metrics_expr.group_by = group_by;
+        Ok(metrics_expr)
+    }
+
+    fn parse_group_by(&mut self) -> anyhow::Result<Vec<String>> {
+        if self.peek_identifier().as_deref() != Some("by") {
+            return Ok(Vec::new());
+        }
+        self.position += 2;
+        self.expect('(')?;
+        let mut labels = vec![self.parse_identifier()?];
+        while self.consume(',') {
+            labels.push(self.parse_identifier()?);
+        }
+        self.expect(')')?;
+        Ok(labels)
+    }
+
+    fn parse_inner_expr(
+        &mut self,
+        aggregation_opt: Option<AggregationOperator>,
+    ) -> anyhow::Result<MetricsExpr> {
+        let range_function_opt = self.peek_identifier().and_then(|identifier| {
+            RangeFunction::from_name(&identifier)
+                .filter(|_| self.is_followed_by_call(&identifier))
+                .map(|range_function| (range_function, identifier))
+        });
+        let Some((range_function, identifier)) = range_function_opt else {
+            let selector = self.parse_selector()?;
+            if self.consume('[') {
+                bail!("A range selector must be passed to a range function such as `rate`.");
+            }
+            return Ok(MetricsExpr {
+                aggregation_opt,
+                group_by: Vec::new(),
+                range_function_opt: None,
+                selector,
+            });
+        };
+        self.position += identifier.chars().count();
+        self.expect('(')?;
+        let selector = self.parse_selector()?;
+        self.expect('[')?;
+        self.skip_whitespaces();
+        let window: String = self.chars[self.position..]
+            .iter()
+            .take_while(|next_char| next_char.is_ascii_alphanumeric())
+            .collect();
+        self.position += window.len();
+        let window_secs = parse_duration_secs(&window)?;
+        self.expect(']')?;
+        self.expect(')')?;
+
+        Ok(MetricsExpr {
+            aggregation_opt,
+            group_by: Vec::new(),
+            range_function_opt: Some((range_function, window_secs)),
+            selector,
+        })
+    }
+
+    fn parse_selector(&mut self) -> anyhow::Result<Selector> {
+        let field_opt = self.peek_identifier();
+        if let Some(field) = &field_opt {
+            self.position += field.chars().count();
+        }
+        let mut matchers = Vec::new();
+
+        if self.consume('{') && !self.consume('}') {
+            loop {
+                matchers.push(self.parse_label_matcher()?);
+                if self.consume('}') {
+                    break;
+                }
+                self.expect(',')?;
+            }
+        }
+        if field_opt.is_none() && matchers.is_empty() {
+            bail!("Expected a metric name or label matchers.");
+        }
+        Ok(Selector {
+            field_opt,
+            matchers,
+        })
+    }
+
+    fn parse_label_matcher(&mut self) -> anyhow::Result<LabelMatcher> {
+        let label = self.parse_identifier()?;
+        self.skip_whitespaces();
+        let operator: String = self.chars[self.position..]
+            .iter()
+            .take_while(|next_char| matches!(next_char, '=' | '!' | '~'))
+            .collect();
+        self.position += operator.len();
+        let negated = match operator.as_str() {
+            "=" => false,
+            "!=" => true,
+            "=~" | "!~" => bail!("Regular expression matchers are not supported."),
+            _ => bail!("Expected `=` or `!=` after label `{label}`."),
+        };
+        let value = self.parse_string()?;
+        Ok(LabelMatcher {
+            label,
+            value,
+            negated,
+        })
+    }
+
+    fn parse_string(&mut self) -> anyhow::Result<String> {
+        self.skip_whitespaces();
+        let quote = match self.peek() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => bail!("Expected a quoted label value."),
+        };
+        self.position += 1;
+        let mut value = String::new();
+
+        loop {
+            let Some(next_char) = self.peek() else {
+                bail!("Unterminated label value `{quote}{value}`.");
+            };
+            self.position += 1;
+            match next_char {
+                '\\' => {
+                    let escaped_char = self.peek().context("Unterminated label value.")?;
+                    self.position += 1;
+                    value.push(escaped_char);
+                }
+                _ if next_char == quote => return Ok(value),
+                _ => value.push(next_char),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration_secs() {
+        assert_eq!(parse_duration_secs("30s").unwrap(), 30);
+        assert_eq!(parse_duration_secs("5m").unwrap(), 300);
+        assert_eq!(parse_duration_secs("1h30m").unwrap(), 5_400);
+        assert_eq!(parse_duration_secs("1w").unwrap(), 604_800);
+        parse_duration_secs("").unwrap_err();
+        parse_duration_secs("0s").unwrap_err();
+        parse_duration_secs("5").unwrap_err();
+        parse_duration_secs("5y").unwrap_err();
+        parse_duration_secs("10000000000000000000w").unwrap_err();
+        parse_duration_secs("18446744073709551615s1s").unwrap_err();
+        parse_duration_secs("600000w").unwrap_err();
+        assert_eq!(
+            parse_duration_secs(&format!("{MAX_DURATION_SECS}s")).unwrap(),
+            MAX_DURATION_SECS
+        );
+        parse_duration_secs(&format!("{}s", MAX_DURATION_SECS + 1)).unwrap_err();
+    }
+
+    #[test]
+    fn test_parse_metrics_expr_aggregation() {
+        let metrics_expr =
+            parse_metrics_expr(r#"sum by (service, host) (rate(bytes{status!="200"}[5m]))"#)
+                .unwrap();
+        assert_eq!(
+            metrics_expr,
+            MetricsExpr {
+                aggregation_opt: Some(AggregationOperator::Sum),
+                group_by: vec!["service".to_string(), "host".to_string()],
+                range_function_opt: Some((RangeFunction::Rate, 300)),
+                selector: Selector {
+                    field_opt: Some("bytes".to_string()),
+                    matchers: vec![LabelMatcher {
+                        label: "status".to_string(),
+                        value: "200".to_string(),
+                        negated: true,
+                    }],
+                },
+            }
+        );
+        let metrics_expr =
+            parse_metrics_expr(r#"avg(attributes.latency{service="api", env='prod'}) by (host)"#)
+                .unwrap();
+        assert_eq!(metrics_expr.aggregation_opt, Some(AggregationOperator::Avg));
+        assert_eq!(metrics_expr.group_by, vec!["host".to_string()]);
+        assert_eq!(metrics_expr.range_function_opt, None);
+        assert_eq!(
+            metrics_expr.selector.field_opt.as_deref(),
+            Some("attributes.latency")
+        );
+        assert_eq!(metrics_expr.selector.matchers.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_metrics_expr_count_documents() {
+        let metrics_expr =
+            parse_metrics_expr(r#"count_over_time({severity_text="ERROR"}[1m])"#).unwrap();
+        assert_eq!(
+            metrics_expr,
+            MetricsExpr {
+                aggregation_opt: None,
+                group_by: Vec::new(),
+                range_function_opt: Some((RangeFunction::CountOverTime, 60)),
+                selector: Selector {
+                    field_opt: None,
+                    matchers: vec![LabelMatcher {
+                        label: "severity_text".to_string(),
+                        value: "ERROR".to_string(),
+                        negated: false,
+                    }],
+                },
+            }
+        );
+        // A metric name may be an aggregation operator if it is not followed by a parenthesis.
+        let metrics_expr = parse_metrics_expr("sum").unwrap();
+        assert_eq!(metrics_expr.aggregation_opt, None);
+        assert_eq!(metrics_expr.selector.field_opt.as_deref(), Some("sum"));
+    }
+
+    #[test]
+    fn test_parse_metrics_expr_errors() {
+        let parse_error = |query: &str| parse_metrics_expr(query).unwrap_err().to_string();
+        assert_eq!(
+            parse_error(r#"rate(bytes{service=~"api.*"}[5m])"#),
+            "Regular expression matchers are not supported."
+        );
+        assert_eq!(
+            parse_error("bytes[5m]"),
+            "A range selector must be passed to a range function such as `rate`."
+        );
+        assert_eq!(parse_error("rate(bytes)"), "Expected `[`, got `)`.");
+        assert_eq!(
+            parse_error("sum(bytes) + 1"),
+            "Unexpected character `+` after the end of the query."
+        );
+        assert_eq!(
+            parse_error(r#"bytes{service="api}"#),
+            "Unterminated label value `\"api}`."
+        );
+    }
+}
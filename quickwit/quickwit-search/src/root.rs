@@ -247,6 +247,10 @@ pub async fn root_search(
 
     let split_metadatas: Vec<SplitMetadata> =
         list_relevant_splits(index_uid, &search_request, metastore).await?;
+    crate::SEARCH_METRICS
+        .root_search_targeted_splits
+        .with_label_values([&search_request.index_id])
+        .observe(split_metadatas.len() as f64);
 
     let split_offsets_map: HashMap<String, SplitIdAndFooterOffsets> = split_metadatas
         .iter()
@@ -21,10 +21,15 @@ use std::cmp::Reverse;
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 use std::net::SocketAddr;
+use std::sync::{Arc, RwLock};
 
 use anyhow::bail;
+use quickwit_cluster::{ClusterMember, SearcherLoad};
 use quickwit_common::rendezvous_hasher::sort_by_rendez_vous_hash;
+use quickwit_config::service::QuickwitService;
 use quickwit_grpc_clients::service_client_pool::ServiceClientPool;
+use tokio_stream::wrappers::WatchStream;
+use tokio_stream::StreamExt;
 use tracing::error;
 
 use crate::SearchServiceClient;
@@ -44,18 +49,87 @@ pub trait Job {
     fn cost(&self) -> u32;
 }
 
+/// Penalty applied to a node for each leaf search it is executing.
+const INFLIGHT_LEAF_SEARCH_PENALTY: f64 = 0.25;
+
+/// Leaf search latency doubling the load of a node.
+const REFERENCE_LEAF_SEARCH_LATENCY_MILLIS: f64 = 1_000.0;
+
+/// Discount applied to a node that searched the split recently and likely has it in its caches.
+const CACHE_AFFINITY_DISCOUNT: f64 = 0.5;
+
 /// Search job placer.
 /// It assigns jobs to search clients.
+///
+/// Jobs are placed with rendezvous hashing on their split ID. When the live loads of the
+/// searchers are known, the placer also takes them into account to steer jobs away from busy or
+/// slow nodes and toward the nodes that have the split in their caches. When the availability zone
+/// of the node is known, jobs are placed on the searchers of the same zone whenever possible.
 #[derive(Clone, Default)]
 pub struct SearchJobPlacer {
     /// Search clients pool.
     clients_pool: ServiceClientPool<SearchServiceClient>,
+    /// Live loads advertised by the searchers.
+    searcher_loads: Arc<RwLock<HashMap<SocketAddr, SearcherLoad>>>,
+    /// Availability zones advertised by the searchers.
+    searcher_availability_zones: Arc<RwLock<HashMap<SocketAddr, String>>>,
+    /// Availability zone of the node placing the jobs.
+    availability_zone_opt: Option<String>,
 }
 
 impl SearchJobPlacer {
     /// Returns an [`SearchJobPlacer`] from a search service client pool.
     pub fn new(clients_pool: ServiceClientPool<SearchServiceClient>) -> Self {
-        Self { clients_pool }
+        Self {
+            clients_pool,
+            searcher_loads: Default::default(),
+            searcher_availability_zones: Default::default(),
+            availability_zone_opt: None,
+        }
+    }
+
+    /// Sets the availability zone of the node placing the jobs.
+    pub fn with_availability_zone(mut self, availability_zone_opt: Option<String>) -> Self {
+        self.availability_zone_opt = availability_zone_opt;
+        self
+    }
+
+    /// Makes the placer follow the loads and the availability zones advertised by the searchers
+    /// of the cluster.
+    pub fn with_searcher_members(
+        self,
+        mut ready_members_watcher: WatchStream<Vec<ClusterMember>>,
+    ) -> Self {
+        let searcher_loads = self.searcher_loads.clone();
+        let searcher_availability_zones = self.searcher_availability_zones.clone();
+        tokio::spawn(async move {
+            while let Some(ready_members) = ready_members_watcher.next().await {
+                let mut new_searcher_loads: HashMap<SocketAddr, SearcherLoad> = HashMap::new();
+                let mut new_searcher_availability_zones: HashMap<SocketAddr, String> =
+                    HashMap::new();
+
+                for member in ready_members {
+                    if !member.enabled_services.contains(&QuickwitService::Searcher) {
+                        continue;
+                    }
+                    let grpc_addr = member.grpc_advertise_addr;
+
+                    if let Some(searcher_load) = member.searcher_load_opt {
+                        new_searcher_loads.insert(grpc_addr, searcher_load);
+                    }
+                    if let Some(availability_zone) = member.availability_zone_opt {
+                        new_searcher_availability_zones.insert(grpc_addr, availability_zone);
+                    }
+                }
+                *searcher_loads
+                    .write()
+                    .expect("Lock should not be poisoned.") = new_searcher_loads;
+                *searcher_availability_zones
+                    .write()
+                    .expect("Lock should not be poisoned.") = new_searcher_availability_zones;
+            }
+        });
+        self
     }
 
     /// Returns a copy of the entire clients map.
@@ -74,6 +148,44 @@ fn job_order_key<J: Job>(job: &J) -> (Reverse<u32>, &str) {
 struct Node {
     pub peer_grpc_addr: SocketAddr,
     pub load: u64,
+    /// Multiplier derived from the live load of the node, 1.0 when the node is idle or its load is
+    /// unknown.
+    pub live_load_factor: f64,
+    pub cached_split_ids: HashSet<String>,
+}
+
+impl Node {
+    fn new(peer_grpc_addr: SocketAddr, searcher_load_opt: Option<&SearcherLoad>) -> Self {
+        let (live_load_factor, cached_split_ids) = match searcher_load_opt {
+            Some(searcher_load) => {
+                let live_load_factor = 1.0
+                    + searcher_load.num_inflight_leaf_searches as f64
+                        * INFLIGHT_LEAF_SEARCH_PENALTY
+                    + searcher_load.leaf_search_latency_millis as f64
+                        / REFERENCE_LEAF_SEARCH_LATENCY_MILLIS;
+                let cached_split_ids = searcher_load.cached_split_ids.iter().cloned().collect();
+                (live_load_factor, cached_split_ids)
+            }
+            None => (1.0, HashSet::new()),
+        };
+        Self {
+            peer_grpc_addr,
+            load: 0,
+            live_load_factor,
+            cached_split_ids,
+        }
+    }
+
+    /// Score of the node for the job, the lower the better: the load the node would have after
+    /// running the job, weighted by its live load and discounted if it has the split in cache.
+    fn placement_score<J: Job>(&self, job: &J) -> f64 {
+        let score = (self.load + job.cost() as u64) as f64 * self.live_load_factor;
+        if self.cached_split_ids.contains(job.split_id()) {
+            score * CACHE_AFFINITY_DISCOUNT
+        } else {
+            score
+        }
+    }
 }
 
 impl Hash for Node {
@@ -101,6 +213,10 @@ impl SearchJobPlacer {
         {
             // TODO optimize the case where there are few jobs and many clients.
             let clients = self.clients();
+            let searcher_loads = self
+                .searcher_loads
+                .read()
+                .expect("Lock should not be poisoned.");
 
             // when exclude_addresses excludes all addresses we discard it
             let empty_set = HashSet::default();
@@ -114,12 +230,24 @@ impl SearchJobPlacer {
                 .into_iter()
                 .filter(|(grpc_addr, _)| !exclude_addresses_if_not_saturated.contains(grpc_addr))
             {
-                nodes.push(Node {
-                    peer_grpc_addr: grpc_addr,
-                    load: 0,
-                });
+                nodes.push(Node::new(grpc_addr, searcher_loads.get(&grpc_addr)));
                 socket_to_client.insert(grpc_addr, client);
             }
+
+            // Keep the jobs within the availability zone of the node to avoid inter-zone transfer
+            // costs, unless none of the searchers of the zone is available.
+            if let Some(availability_zone) = &self.availability_zone_opt {
+                let searcher_availability_zones = self
+                    .searcher_availability_zones
+                    .read()
+                    .expect("Lock should not be poisoned.");
+                let is_same_zone = |node: &Node| {
+                    searcher_availability_zones.get(&node.peer_grpc_addr) == Some(availability_zone)
+                };
+                if nodes.iter().any(is_same_zone) {
+                    nodes.retain(is_same_zone);
+                }
+            }
         }
 
         if nodes.is_empty() {
@@ -134,12 +262,20 @@ impl SearchJobPlacer {
 
         for job in jobs {
             sort_by_rendez_vous_hash(&mut nodes, job.split_id());
-            // choose one of the the first two nodes based on least loaded
-            let chosen_node_index: usize = if nodes.len() >= 2 {
-                usize::from(nodes[0].load > nodes[1].load)
-            } else {
-                0
-            };
+            // Choose the best scored node among the first two nodes and the nodes that have the
+            // split in cache. On ties, the rendez-vous hashing order wins.
+            let chosen_node_index: usize = nodes
+                .iter()
+                .enumerate()
+                .filter(|(node_index, node)| {
+                    *node_index < 2 || node.cached_split_ids.contains(job.split_id())
+                })
+                .min_by(|(_, left), (_, right)| {
+                    left.placement_score(&job)
+                        .total_cmp(&right.placement_score(&job))
+                })
+                .map(|(node_index, _)| node_index)
+                .expect("Nodes should not be empty.");
 
             // update node load for next round
             nodes[chosen_node_index].load += job.cost() as u64;
@@ -193,12 +329,14 @@ mod tests {
 
     use chitchat::transport::{ChannelTransport, Transport};
     use itertools::Itertools;
-    use quickwit_cluster::{create_cluster_for_test, grpc_addr_from_listen_addr_for_test, Cluster};
+    use quickwit_cluster::{
+        create_cluster_for_test, grpc_addr_from_listen_addr_for_test, Cluster, SearcherLoad,
+    };
     use quickwit_grpc_clients::service_client_pool::ServiceClientPool;
 
     use crate::client::create_search_service_client;
     use crate::root::SearchJob;
-    use crate::SearchJobPlacer;
+    use crate::{MockSearchService, SearchJobPlacer, SearchServiceClient};
 
     async fn create_cluster_simple_for_test(
         transport: &dyn Transport,
@@ -296,4 +434,107 @@ mod tests {
         );
         Ok(())
     }
+
+    fn search_job_placer_for_test(num_nodes: u16) -> (SearchJobPlacer, Vec<SocketAddr>) {
+        let grpc_addrs: Vec<SocketAddr> = (0..num_nodes)
+            .map(|node_ord| ([127, 0, 0, 1], 1000 + node_ord).into())
+            .collect();
+        let clients = grpc_addrs
+            .iter()
+            .map(|grpc_addr| {
+                SearchServiceClient::from_service(Arc::new(MockSearchService::new()), *grpc_addr)
+            })
+            .collect();
+        let job_placer = SearchJobPlacer::new(ServiceClientPool::for_clients_list(clients));
+        (job_placer, grpc_addrs)
+    }
+
+    fn assigned_addr(job_placer: &SearchJobPlacer, split_id: &str) -> SocketAddr {
+        job_placer
+            .assign_job(SearchJob::for_test(split_id, 1), &HashSet::new())
+            .unwrap()
+            .grpc_addr()
+    }
+
+    #[test]
+    fn test_search_job_placer_avoids_loaded_nodes() {
+        let (job_placer, grpc_addrs) = search_job_placer_for_test(2);
+        let rendezvous_addr = assigned_addr(&job_placer, "split-1");
+        let other_addr = *grpc_addrs
+            .iter()
+            .find(|grpc_addr| **grpc_addr != rendezvous_addr)
+            .unwrap();
+
+        // An idle node keeps the jobs rendez-vous hashing assigns to it.
+        job_placer
+            .searcher_loads
+            .write()
+            .unwrap()
+            .insert(rendezvous_addr, SearcherLoad::default());
+        assert_eq!(assigned_addr(&job_placer, "split-1"), rendezvous_addr);
+
+        // A busy and slow node hands them over to the other candidate.
+        job_placer.searcher_loads.write().unwrap().insert(
+            rendezvous_addr,
+            SearcherLoad {
+                num_inflight_leaf_searches: 8,
+                leaf_search_latency_millis: 500,
+                cached_split_ids: Vec::new(),
+            },
+        );
+        assert_eq!(assigned_addr(&job_placer, "split-1"), other_addr);
+    }
+
+    #[test]
+    fn test_search_job_placer_prefers_nodes_with_split_in_cache() {
+        let (job_placer, grpc_addrs) = search_job_placer_for_test(5);
+
+        for grpc_addr in &grpc_addrs {
+            let cached_split_ids = if *grpc_addr == grpc_addrs[3] {
+                vec!["split-1".to_string()]
+            } else {
+                Vec::new()
+            };
+            let searcher_load = SearcherLoad {
+                num_inflight_leaf_searches: 1,
+                leaf_search_latency_millis: 100,
+                cached_split_ids,
+            };
+            job_placer
+                .searcher_loads
+                .write()
+                .unwrap()
+                .insert(*grpc_addr, searcher_load);
+        }
+        assert_eq!(assigned_addr(&job_placer, "split-1"), grpc_addrs[3]);
+    }
+
+    #[test]
+    fn test_search_job_placer_prefers_nodes_in_same_availability_zone() {
+        let (job_placer, grpc_addrs) = search_job_placer_for_test(4);
+        let job_placer = job_placer.with_availability_zone(Some("us-east-1a".to_string()));
+
+        for (node_ord, grpc_addr) in grpc_addrs.iter().enumerate() {
+            let availability_zone = if node_ord == 2 {
+                "us-east-1a"
+            } else {
+                "us-east-1b"
+            };
+            job_placer
+                .searcher_availability_zones
+                .write()
+                .unwrap()
+                .insert(*grpc_addr, availability_zone.to_string());
+        }
+        for split_ord in 0..10 {
+            let split_id = format!("split-{split_ord}");
+            assert_eq!(assigned_addr(&job_placer, &split_id), grpc_addrs[2]);
+        }
+        // Jobs go to the other zones when the searchers of the same zone are excluded.
+        let excluded_addrs = HashSet::from_iter([grpc_addrs[2]]);
+        let client = job_placer
+            .assign_job(SearchJob::for_test("split-1", 1), &excluded_addrs)
+            .unwrap();
+        assert_ne!(client.grpc_addr(), grpc_addrs[2]);
+    }
 }
@@ -489,6 +489,8 @@ mod tests {
             fast_field: "ts".to_string(),
             output_format: 0,
             partition_by_field: None,
+            sort_by_split_id: false,
+            start_after_split_id: None,
         };
         let splits = test_sandbox
             .metastore()
@@ -565,6 +567,8 @@ mod tests {
             fast_field: "ts".to_string(),
             output_format: 0,
             partition_by_field: None,
+            sort_by_split_id: false,
+            start_after_split_id: None,
         };
         let splits = test_sandbox
             .metastore()
@@ -620,6 +624,8 @@ mod tests {
             fast_field: "app".to_string(),
             output_format: 0,
             partition_by_field: None,
+            sort_by_split_id: false,
+            start_after_split_id: None,
         };
         let splits = test_sandbox
             .metastore()
@@ -708,6 +714,8 @@ mod tests {
             fast_field: "fast_field".to_string(),
             output_format: 1,
             partition_by_field: Some(String::from("partition_by_fast_field")),
+            sort_by_split_id: false,
+            start_after_split_id: None,
         };
         let splits = test_sandbox
             .metastore()
@@ -19,13 +19,16 @@
 
 use std::collections::HashSet;
 
-use bytes::Bytes;
-use futures::{StreamExt, TryStreamExt};
+use futures::stream::BoxStream;
+use futures::{future, stream, StreamExt};
 use quickwit_common::uri::Uri;
 use quickwit_config::build_doc_mapper;
-use quickwit_metastore::Metastore;
-use quickwit_proto::{LeafSearchStreamRequest, SearchRequest, SearchStreamRequest};
+use quickwit_metastore::{Metastore, SplitMetadata};
+use quickwit_proto::{
+    LeafSearchStreamRequest, LeafSearchStreamResponse, SearchRequest, SearchStreamRequest,
+};
 use quickwit_query::query_ast::QueryAst;
+use tokio_stream::wrappers::ReceiverStream;
 use tokio_stream::StreamMap;
 use tracing::*;
 
@@ -33,6 +36,15 @@ use crate::cluster_client::ClusterClient;
 use crate::root::SearchJob;
 use crate::{list_relevant_splits, SearchError, SearchJobPlacer, SearchServiceClient};
 
+/// Maximum number of splits searched ahead of the split being returned by a stream sorted by split
+/// ID.
+const MAX_NUM_PREFETCHED_SORTED_SPLIT_STREAMS: usize = 8;
+
+/// Capacity of the channel buffering the responses of a split searched ahead. A leaf returns one
+/// response per split, so the search of a split searched ahead waits for its turn once its response
+/// is buffered.
+const SORTED_SPLIT_STREAM_CHANNEL_CAPACITY: usize = 1;
+
 /// Perform a distributed search stream.
 #[instrument(skip(metastore, cluster_client, search_job_placer))]
 pub async fn root_search_stream(
@@ -40,7 +52,7 @@ pub async fn root_search_stream(
     metastore: &dyn Metastore,
     cluster_client: ClusterClient,
     search_job_placer: &SearchJobPlacer,
-) -> crate::Result<impl futures::Stream<Item = crate::Result<Bytes>>> {
+) -> crate::Result<BoxStream<'static, crate::Result<LeafSearchStreamResponse>>> {
     // TODO: building a search request should not be necessary for listing splits.
     // This needs some refactoring: relevant splits, metadata_map, jobs...
 
@@ -64,13 +76,35 @@ pub async fn root_search_stream(
     search_stream_request.query_ast = serde_json::to_string(&query_ast_resolved)?;
 
     let search_request = SearchRequest::try_from(search_stream_request.clone())?;
-    let split_metadatas = list_relevant_splits(index_uid, &search_request, metastore).await?;
+    let mut split_metadatas = list_relevant_splits(index_uid, &search_request, metastore).await?;
 
     let doc_mapper_str = serde_json::to_string(&doc_mapper).map_err(|err| {
         SearchError::InternalError(format!("Failed to serialize doc mapper: Cause {err}"))
     })?;
 
     let index_uri: &Uri = &index_config.index_uri;
+
+    if search_stream_request.sort_by_split_id {
+        if let Some(start_after_split_id) = &search_stream_request.start_after_split_id {
+            split_metadatas
+                .retain(|split_metadata| &split_metadata.split_id > start_after_split_id);
+        }
+        split_metadatas.sort_by(|left, right| left.split_id.cmp(&right.split_id));
+        let sorted_stream = sorted_leaf_search_stream(
+            search_stream_request,
+            doc_mapper_str,
+            index_uri.to_string(),
+            split_metadatas,
+            cluster_client,
+            search_job_placer.clone(),
+        );
+        return Ok(sorted_stream.boxed());
+    }
+    if search_stream_request.start_after_split_id.is_some() {
+        return Err(SearchError::InvalidArgument(
+            "`start_after_split_id` requires `sort_by_split_id`.".to_string(),
+        ));
+    }
     let leaf_search_jobs: Vec<SearchJob> = split_metadatas.iter().map(SearchJob::from).collect();
 
     let assigned_leaf_search_jobs: Vec<(SearchServiceClient, Vec<SearchJob>)> =
@@ -90,9 +124,57 @@ pub async fn root_search_stream(
             .await;
         stream_map.insert(leaf_ord, leaf_stream);
     }
-    Ok(stream_map
-        .map(|(_leaf_ord, result)| result)
-        .map_ok(|leaf_response| Bytes::from(leaf_response.data)))
+    Ok(stream_map.map(|(_leaf_ord, result)| result).boxed())
+}
+
+/// Searches the splits one by one and returns their results in the order of the splits. The
+/// responses of the current split are forwarded as they arrive, while the next splits are searched
+/// ahead into bounded channels.
+fn sorted_leaf_search_stream(
+    search_stream_request: SearchStreamRequest,
+    doc_mapper_str: String,
+    index_uri: String,
+    split_metadatas: Vec<SplitMetadata>,
+    cluster_client: ClusterClient,
+    search_job_placer: SearchJobPlacer,
+) -> impl futures::Stream<Item = crate::Result<LeafSearchStreamResponse>> + Send + 'static {
+    stream::iter(split_metadatas)
+        .map(move |split_metadata| {
+            let search_job = SearchJob::from(&split_metadata);
+            let leaf_request = jobs_to_leaf_request(
+                &search_stream_request,
+                &doc_mapper_str,
+                &index_uri,
+                vec![search_job.clone()],
+            );
+            let client_res = search_job_placer.assign_job(search_job, &HashSet::default());
+            let cluster_client = cluster_client.clone();
+            let (result_sender, result_receiver) =
+                tokio::sync::mpsc::channel(SORTED_SPLIT_STREAM_CHANNEL_CAPACITY);
+            tokio::spawn(async move {
+                let mut leaf_stream = match client_res {
+                    Ok(client) => {
+                        cluster_client
+                            .leaf_search_stream(leaf_request, client)
+                            .await
+                    }
+                    Err(error) => {
+                        // It is ok to ignore the error, the stream was dropped.
+                        let _ = result_sender.send(Err(SearchError::from(error))).await;
+                        return;
+                    }
+                };
+                while let Some(result) = leaf_stream.next().await {
+                    // If the stream was dropped, stop consuming the leaf stream.
+                    if result_sender.send(result).await.is_err() {
+                        break;
+                    }
+                }
+            });
+            future::ready(ReceiverStream::new(result_receiver))
+        })
+        .buffered(MAX_NUM_PREFETCHED_SORTED_SPLIT_STREAMS)
+        .flatten()
 }
 
 fn jobs_to_leaf_request(
@@ -111,8 +193,10 @@ fn jobs_to_leaf_request(
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Arc;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
 
+    use futures::TryStreamExt;
     use quickwit_grpc_clients::service_client_pool::ServiceClientPool;
     use quickwit_indexing::mock_split;
     use quickwit_metastore::{IndexMetadata, MockMetastore};
@@ -168,14 +252,14 @@ mod tests {
         let search_job_placer = SearchJobPlacer::new(client_pool);
 
         let cluster_client = ClusterClient::new(search_job_placer.clone());
-        let result: Vec<Bytes> =
+        let result: Vec<LeafSearchStreamResponse> =
             root_search_stream(request, &metastore, cluster_client, &search_job_placer)
                 .await?
                 .try_collect()
                 .await?;
         assert_eq!(result.len(), 2);
-        assert_eq!(&result[0], &b"123"[..]);
-        assert_eq!(&result[1], &b"456"[..]);
+        assert_eq!(&result[0].data, b"123");
+        assert_eq!(&result[1].data, b"456");
         Ok(())
     }
 
@@ -229,8 +313,137 @@ mod tests {
             root_search_stream(request, &metastore, cluster_client, &search_job_placer).await?;
         let result: Vec<_> = stream.try_collect().await?;
         assert_eq!(result.len(), 2);
-        assert_eq!(&result[0], &b"123"[..]);
-        assert_eq!(&result[1], &b"456"[..]);
+        assert_eq!(&result[0].data, b"123");
+        assert_eq!(&result[1].data, b"456");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_root_search_stream_sorted_by_split_id() -> anyhow::Result<()> {
+        let request = quickwit_proto::SearchStreamRequest {
+            index_id: "test-index".to_string(),
+            query_ast: qast_helper("test", &["body"]),
+            fast_field: "timestamp".to_string(),
+            output_format: OutputFormat::Csv as i32,
+            sort_by_split_id: true,
+            start_after_split_id: Some("split1".to_string()),
+            ..Default::default()
+        };
+        let mut metastore = MockMetastore::new();
+        metastore
+            .expect_index_metadata()
+            .returning(|_index_id: &str| {
+                Ok(IndexMetadata::for_test(
+                    "test-index",
+                    "ram:///indexes/test-index",
+                ))
+            });
+        metastore.expect_list_splits().returning(|_filter| {
+            Ok(vec![
+                mock_split("split3"),
+                mock_split("split1"),
+                mock_split("split2"),
+            ])
+        });
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_leaf_search_stream()
+            .times(2)
+            .returning(|leaf_search_req: quickwit_proto::LeafSearchStreamRequest| {
+                assert_eq!(leaf_search_req.split_offsets.len(), 1);
+                let split_id = leaf_search_req.split_offsets[0].split_id.clone();
+                assert_ne!(split_id, "split1");
+                let (result_sender, result_receiver) = tokio::sync::mpsc::unbounded_channel();
+                result_sender
+                    .send(Ok(quickwit_proto::LeafSearchStreamResponse {
+                        data: split_id.as_bytes().to_vec(),
+                        split_id,
+                    }))
+                    .unwrap();
+                Ok(UnboundedReceiverStream::new(result_receiver))
+            });
+        let client_pool =
+            ServiceClientPool::for_clients_list(vec![SearchServiceClient::from_service(
+                Arc::new(mock_search_service),
+                ([127, 0, 0, 1], 1000).into(),
+            )]);
+        let search_job_placer = SearchJobPlacer::new(client_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+        let stream =
+            root_search_stream(request, &metastore, cluster_client, &search_job_placer).await?;
+        let result: Vec<_> = stream.try_collect().await?;
+        let split_ids: Vec<&str> = result
+            .iter()
+            .map(|leaf_response| leaf_response.split_id.as_str())
+            .collect();
+        assert_eq!(split_ids, ["split2", "split3"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_root_search_stream_sorted_by_split_id_forwards_responses() -> anyhow::Result<()> {
+        let request = quickwit_proto::SearchStreamRequest {
+            index_id: "test-index".to_string(),
+            query_ast: qast_helper("test", &["body"]),
+            fast_field: "timestamp".to_string(),
+            output_format: OutputFormat::Csv as i32,
+            sort_by_split_id: true,
+            ..Default::default()
+        };
+        let mut metastore = MockMetastore::new();
+        metastore
+            .expect_index_metadata()
+            .returning(|_index_id: &str| {
+                Ok(IndexMetadata::for_test(
+                    "test-index",
+                    "ram:///indexes/test-index",
+                ))
+            });
+        metastore
+            .expect_list_splits()
+            .returning(|_filter| Ok(vec![mock_split("split2"), mock_split("split1")]));
+        // The leaf stream of `split1` stays open until its sender is dropped.
+        let split1_sender_opt = Arc::new(Mutex::new(None));
+        let split1_sender_opt_clone = split1_sender_opt.clone();
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_leaf_search_stream()
+            .times(2)
+            .returning(
+                move |leaf_search_req: quickwit_proto::LeafSearchStreamRequest| {
+                    let split_id = leaf_search_req.split_offsets[0].split_id.clone();
+                    let (result_sender, result_receiver) = tokio::sync::mpsc::unbounded_channel();
+                    result_sender
+                        .send(Ok(quickwit_proto::LeafSearchStreamResponse {
+                            data: split_id.as_bytes().to_vec(),
+                            split_id: split_id.clone(),
+                        }))
+                        .unwrap();
+                    if split_id == "split1" {
+                        *split1_sender_opt_clone.lock().unwrap() = Some(result_sender);
+                    }
+                    Ok(UnboundedReceiverStream::new(result_receiver))
+                },
+            );
+        let client_pool =
+            ServiceClientPool::for_clients_list(vec![SearchServiceClient::from_service(
+                Arc::new(mock_search_service),
+                ([127, 0, 0, 1], 1000).into(),
+            )]);
+        let search_job_placer = SearchJobPlacer::new(client_pool);
+        let cluster_client = ClusterClient::new(search_job_placer.clone());
+        let mut stream =
+            root_search_stream(request, &metastore, cluster_client, &search_job_placer).await?;
+
+        let leaf_response = tokio::time::timeout(Duration::from_secs(5), stream.next())
+            .await?
+            .unwrap()?;
+        assert_eq!(leaf_response.split_id, "split1");
+
+        split1_sender_opt.lock().unwrap().take();
+        let leaf_response = stream.next().await.unwrap()?;
+        assert_eq!(leaf_response.split_id, "split2");
+        assert!(stream.next().await.is_none());
         Ok(())
     }
 
@@ -0,0 +1,186 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use lru::LruCache;
+use quickwit_cluster::{Cluster, SearcherLoad};
+use tracing::warn;
+
+use crate::SearcherContext;
+
+/// Maximum number of split IDs advertised by a searcher. Keeps its Chitchat state small.
+const MAX_NUM_ADVERTISED_SPLITS: usize = 100;
+
+/// Weight of the latest leaf search latency in the moving average.
+const LATENCY_SMOOTHING_FACTOR: f64 = 0.2;
+
+/// Interval at which a searcher advertises its load to the cluster.
+const SEARCHER_LOAD_REPORTING_INTERVAL: Duration = if cfg!(test) {
+    Duration::from_millis(100)
+} else {
+    Duration::from_secs(1)
+};
+
+/// Tracks the live load of the searcher: the leaf searches in flight, their latency, and the
+/// splits searched recently.
+pub struct SearcherLoadTracker {
+    num_inflight_leaf_searches: AtomicU64,
+    inner: Mutex<InnerSearcherLoadTracker>,
+}
+
+struct InnerSearcherLoadTracker {
+    leaf_search_latency_millis: f64,
+    recent_split_ids: LruCache<String, ()>,
+}
+
+impl Default for SearcherLoadTracker {
+    fn default() -> Self {
+        let inner = InnerSearcherLoadTracker {
+            leaf_search_latency_millis: 0.0,
+            recent_split_ids: LruCache::new(
+                NonZeroUsize::new(MAX_NUM_ADVERTISED_SPLITS).expect("Capacity should be non-zero."),
+            ),
+        };
+        Self {
+            num_inflight_leaf_searches: AtomicU64::new(0),
+            inner: Mutex::new(inner),
+        }
+    }
+}
+
+impl SearcherLoadTracker {
+    /// Records the start of a leaf search on the given splits. The search is considered in
+    /// flight until the returned guard is dropped.
+    pub(crate) fn start_leaf_search<'a>(
+        &self,
+        split_ids: impl Iterator<Item = &'a str>,
+    ) -> InflightLeafSearchGuard<'_> {
+        self.num_inflight_leaf_searches
+            .fetch_add(1, Ordering::Relaxed);
+        let mut inner = self.inner.lock().expect("Lock should not be poisoned.");
+        for split_id in split_ids {
+            inner.recent_split_ids.put(split_id.to_string(), ());
+        }
+        InflightLeafSearchGuard {
+            tracker: self,
+            start: Instant::now(),
+        }
+    }
+
+    fn finish_leaf_search(&self, elapsed: Duration) {
+        self.num_inflight_leaf_searches
+            .fetch_sub(1, Ordering::Relaxed);
+        let mut inner = self.inner.lock().expect("Lock should not be poisoned.");
+        let elapsed_millis = elapsed.as_secs_f64() * 1_000.0;
+        inner.leaf_search_latency_millis = LATENCY_SMOOTHING_FACTOR * elapsed_millis
+            + (1.0 - LATENCY_SMOOTHING_FACTOR) * inner.leaf_search_latency_millis;
+    }
+
+    /// Returns the number of leaf searches being executed by the searcher.
+    pub fn num_inflight_leaf_searches(&self) -> u64 {
+        self.num_inflight_leaf_searches.load(Ordering::Relaxed)
+    }
+
+    /// Returns the current load of the searcher.
+    pub fn searcher_load(&self) -> SearcherLoad {
+        let inner = self.inner.lock().expect("Lock should not be poisoned.");
+        SearcherLoad {
+            num_inflight_leaf_searches: self.num_inflight_leaf_searches(),
+            leaf_search_latency_millis: inner.leaf_search_latency_millis.round() as u64,
+            cached_split_ids: inner
+                .recent_split_ids
+                .iter()
+                .map(|(split_id, _)| split_id.clone())
+                .collect(),
+        }
+    }
+}
+
+/// Marks a leaf search as in flight for the lifetime of the guard.
+pub(crate) struct InflightLeafSearchGuard<'a> {
+    tracker: &'a SearcherLoadTracker,
+    start: Instant,
+}
+
+impl Drop for InflightLeafSearchGuard<'_> {
+    fn drop(&mut self) {
+        self.tracker.finish_leaf_search(self.start.elapsed());
+    }
+}
+
+/// Periodically advertises the load of the searcher to the other nodes of the cluster, which
+/// take it into account when placing leaf search jobs.
+pub fn start_searcher_load_reporting(cluster: Cluster, searcher_context: Arc<SearcherContext>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SEARCHER_LOAD_REPORTING_INTERVAL);
+        loop {
+            interval.tick().await;
+            let searcher_load = searcher_context.searcher_load_tracker.searcher_load();
+            if let Err(error) = cluster.update_self_node_searcher_load(&searcher_load).await {
+                warn!(error=?error, "Failed to advertise searcher load.");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_searcher_load_tracker() {
+        let tracker = SearcherLoadTracker::default();
+        assert_eq!(tracker.searcher_load(), SearcherLoad::default());
+
+        let guard_1 = tracker.start_leaf_search(["split-1", "split-2"].into_iter());
+        let guard_2 = tracker.start_leaf_search(["split-3"].into_iter());
+        let searcher_load = tracker.searcher_load();
+        assert_eq!(searcher_load.num_inflight_leaf_searches, 2);
+        // Most recently searched splits come first.
+        assert_eq!(
+            searcher_load.cached_split_ids,
+            vec!["split-3", "split-2", "split-1"]
+        );
+        std::thread::sleep(Duration::from_millis(50));
+        drop(guard_1);
+        drop(guard_2);
+        let searcher_load = tracker.searcher_load();
+        assert_eq!(searcher_load.num_inflight_leaf_searches, 0);
+        assert!(searcher_load.leaf_search_latency_millis >= 10);
+    }
+
+    #[test]
+    fn test_searcher_load_tracker_caps_advertised_splits() {
+        let tracker = SearcherLoadTracker::default();
+        let split_ids: Vec<String> = (0..MAX_NUM_ADVERTISED_SPLITS + 10)
+            .map(|split_ord| format!("split-{split_ord}"))
+            .collect();
+        drop(tracker.start_leaf_search(split_ids.iter().map(String::as_str)));
+        let searcher_load = tracker.searcher_load();
+        assert_eq!(
+            searcher_load.cached_split_ids.len(),
+            MAX_NUM_ADVERTISED_SPLITS
+        );
+        assert_eq!(searcher_load.cached_split_ids[0], "split-109");
+    }
+}
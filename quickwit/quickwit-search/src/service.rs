@@ -19,9 +19,9 @@
 
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Instant;
 
 use async_trait::async_trait;
-use bytes::Bytes;
 use quickwit_common::uri::Uri;
 use quickwit_config::SearcherConfig;
 use quickwit_doc_mapper::DocMapper;
@@ -33,12 +33,14 @@ use quickwit_proto::{
 };
 use quickwit_storage::{Cache, MemorySizedCache, QuickwitCache, StorageUriResolver};
 use tantivy::aggregation::AggregationLimits;
-use tokio::sync::Semaphore;
+use tokio::sync::{Mutex, Semaphore};
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tracing::info;
 
 use crate::leaf_cache::LeafSearchCache;
+use crate::metrics::SEARCH_METRICS;
 use crate::search_stream::{leaf_search_stream, root_search_stream};
+use crate::searcher_load::SearcherLoadTracker;
 use crate::{
     fetch_docs, leaf_list_terms, leaf_search, root_list_terms, root_search, ClusterClient,
     SearchError, SearchJobPlacer,
@@ -87,7 +89,9 @@ pub trait SearchService: 'static + Send + Sync {
     async fn root_search_stream(
         &self,
         request: SearchStreamRequest,
-    ) -> crate::Result<Pin<Box<dyn futures::Stream<Item = crate::Result<Bytes>> + Send>>>;
+    ) -> crate::Result<
+        Pin<Box<dyn futures::Stream<Item = crate::Result<LeafSearchStreamResponse>> + Send>>,
+    >;
 
     /// Performs a leaf search on a given set of splits and returns a stream.
     async fn leaf_search_stream(
@@ -122,9 +126,8 @@ impl SearchServiceImpl {
         storage_uri_resolver: StorageUriResolver,
         cluster_client: ClusterClient,
         search_job_placer: SearchJobPlacer,
-        searcher_config: SearcherConfig,
+        searcher_context: Arc<SearcherContext>,
     ) -> Self {
-        let searcher_context = Arc::new(SearcherContext::new(searcher_config));
         SearchServiceImpl {
             metastore,
             storage_uri_resolver,
@@ -145,6 +148,8 @@ fn deserialize_doc_mapper(doc_mapper_str: &str) -> crate::Result<Arc<dyn DocMapp
 #[async_trait]
 impl SearchService for SearchServiceImpl {
     async fn root_search(&self, search_request: SearchRequest) -> crate::Result<SearchResponse> {
+        let index_id = search_request.index_id.clone();
+        let start = Instant::now();
         let search_result = root_search(
             &self.searcher_context,
             search_request,
@@ -152,9 +157,9 @@ impl SearchService for SearchServiceImpl {
             &self.cluster_client,
             &self.search_job_placer,
         )
-        .await?;
-
-        Ok(search_result)
+        .await;
+        SEARCH_METRICS.record_root_search(&index_id, start.elapsed(), &search_result);
+        search_result
     }
 
     async fn leaf_search(
@@ -171,16 +176,25 @@ impl SearchService for SearchServiceImpl {
         let split_ids = leaf_search_request.split_offsets;
         let doc_mapper = deserialize_doc_mapper(&leaf_search_request.doc_mapper)?;
 
-        let leaf_search_response = leaf_search(
+        let _inflight_leaf_search_guard = self
+            .searcher_context
+            .searcher_load_tracker
+            .start_leaf_search(split_ids.iter().map(|split| split.split_id.as_str()));
+        let start = Instant::now();
+        let leaf_search_result = leaf_search(
             self.searcher_context.clone(),
             &search_request,
             storage.clone(),
             &split_ids[..],
             doc_mapper,
         )
-        .await?;
-
-        Ok(leaf_search_response)
+        .await;
+        SEARCH_METRICS.record_leaf_search(
+            &search_request.index_id,
+            start.elapsed(),
+            &leaf_search_result,
+        );
+        leaf_search_result
     }
 
     async fn fetch_docs(
@@ -208,7 +222,9 @@ impl SearchService for SearchServiceImpl {
     async fn root_search_stream(
         &self,
         stream_request: SearchStreamRequest,
-    ) -> crate::Result<Pin<Box<dyn futures::Stream<Item = crate::Result<Bytes>> + Send>>> {
+    ) -> crate::Result<
+        Pin<Box<dyn futures::Stream<Item = crate::Result<LeafSearchStreamResponse>> + Send>>,
+    > {
         let data = root_search_stream(
             stream_request,
             self.metastore.as_ref(),
@@ -295,12 +311,16 @@ pub struct SearcherContext {
     pub fast_fields_cache: Arc<dyn Cache>,
     /// Counting semaphore to limit concurrent leaf search split requests.
     pub leaf_search_split_semaphore: Semaphore,
+    /// Number of permits of `leaf_search_split_semaphore`, which can be changed at runtime.
+    max_num_concurrent_split_searches: Mutex<usize>,
     /// Split footer cache.
     pub split_footer_cache: MemorySizedCache<String>,
     /// Counting semaphore to limit concurrent split stream requests.
     pub split_stream_semaphore: Semaphore,
     /// Recent sub-query cache.
     pub leaf_search_cache: LeafSearchCache,
+    /// Live load of the searcher, advertised to the cluster.
+    pub searcher_load_tracker: SearcherLoadTracker,
 }
 
 impl std::fmt::Debug for SearcherContext {
@@ -325,6 +345,8 @@ impl SearcherContext {
         );
         let leaf_search_split_semaphore =
             Semaphore::new(searcher_config.max_num_concurrent_split_searches);
+        let max_num_concurrent_split_searches =
+            Mutex::new(searcher_config.max_num_concurrent_split_searches);
         let split_stream_semaphore =
             Semaphore::new(searcher_config.max_num_concurrent_split_streams);
         let fast_field_cache_capacity =
@@ -342,9 +364,91 @@ impl SearcherContext {
             aggregation_limits,
             fast_fields_cache: storage_long_term_cache,
             leaf_search_split_semaphore,
+            max_num_concurrent_split_searches,
             split_footer_cache: global_split_footer_cache,
             split_stream_semaphore,
             leaf_search_cache,
+            searcher_load_tracker: SearcherLoadTracker::default(),
+        }
+    }
+
+    /// Changes the maximum number of splits searched concurrently. Lowering it waits for the
+    /// ongoing split searches in excess to complete.
+    pub async fn set_max_num_concurrent_split_searches(
+        &self,
+        max_num_concurrent_split_searches: usize,
+    ) {
+        let mut current_max_num_concurrent_split_searches =
+            self.max_num_concurrent_split_searches.lock().await;
+
+        if max_num_concurrent_split_searches > *current_max_num_concurrent_split_searches {
+            self.leaf_search_split_semaphore.add_permits(
+                max_num_concurrent_split_searches - *current_max_num_concurrent_split_searches,
+            );
+        } else if max_num_concurrent_split_searches < *current_max_num_concurrent_split_searches {
+            let num_permits_to_remove =
+                *current_max_num_concurrent_split_searches - max_num_concurrent_split_searches;
+            self.leaf_search_split_semaphore
+                .acquire_many(num_permits_to_remove as u32)
+                .await
+                .expect("The leaf search split semaphore should never be closed.")
+                .forget();
         }
+        *current_max_num_concurrent_split_searches = max_num_concurrent_split_searches;
+    }
+
+    /// Returns the maximum number of splits searched concurrently.
+    pub async fn max_num_concurrent_split_searches(&self) -> usize {
+        *self.max_num_concurrent_split_searches.lock().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_searcher_context_set_max_num_concurrent_split_searches() {
+        let searcher_config = SearcherConfig {
+            max_num_concurrent_split_searches: 4,
+            ..Default::default()
+        };
+        let searcher_context = SearcherContext::new(searcher_config);
+
+        searcher_context
+            .set_max_num_concurrent_split_searches(6)
+            .await;
+        assert_eq!(
+            searcher_context.max_num_concurrent_split_searches().await,
+            6
+        );
+        assert_eq!(
+            searcher_context
+                .leaf_search_split_semaphore
+                .available_permits(),
+            6
+        );
+        let permit = searcher_context
+            .leaf_search_split_semaphore
+            .acquire_many(5)
+            .await
+            .unwrap();
+        let set_max_future = searcher_context.set_max_num_concurrent_split_searches(2);
+        tokio::pin!(set_max_future);
+        // Lowering the limit waits for the split searches in excess to complete.
+        assert!(futures::poll!(&mut set_max_future).is_pending());
+
+        drop(permit);
+        set_max_future.await;
+        assert_eq!(
+            searcher_context.max_num_concurrent_split_searches().await,
+            2
+        );
+        assert_eq!(
+            searcher_context
+                .leaf_search_split_semaphore
+                .available_permits(),
+            2
+        );
     }
 }
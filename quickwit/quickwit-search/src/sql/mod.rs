@@ -0,0 +1,794 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+mod parser;
+
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::ops::Bound;
+
+use anyhow::bail;
+use quickwit_proto::{query_ast_from_user_text, SearchRequest, SearchResponse, SortOrder};
+use quickwit_query::query_ast::{BoolQuery, QueryAst, RangeQuery, TermQuery, TermSetQuery};
+use quickwit_query::JsonLiteral;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map as JsonMap, Value as JsonValue};
+
+use self::parser::{
+    parse_sql, AggregateFunction, Column, ComparisonOperator, Expr, Literal, SelectStatement,
+};
+use crate::{SearchError, SearchService};
+
+/// Number of rows returned by a query without `LIMIT` clause selecting documents.
+const DEFAULT_LIMIT: u64 = 100;
+
+/// Maximum number of distinct values returned for each column of the `GROUP BY` clause. Groups
+/// are sorted by decreasing number of documents before being truncated.
+const MAX_GROUP_BY_VALUES: u64 = 1_000;
+
+/// SqlResponse represents the response returned by the REST SQL API and is meant to be
+/// serialized into JSON.
+#[derive(Serialize, Deserialize, PartialEq, Debug, utoipa::ToSchema)]
+pub struct SqlResponse {
+    /// Names of the selected columns.
+    pub columns: Vec<String>,
+    /// Rows of the result, holding one value per column.
+    #[schema(value_type = Vec<Vec<Object>>)]
+    pub rows: Vec<Vec<JsonValue>>,
+    /// Overall number of documents matching the `WHERE` clause.
+    pub num_hits: u64,
+    /// Elapsed time.
+    pub elapsed_time_micros: u64,
+    /// Search errors.
+    pub errors: Vec<String>,
+}
+
+/// Runs a SQL `SELECT` statement by translating it into a search request.
+///
+/// Queries without aggregates return the selected fields of the matching documents. Queries
+/// with aggregates or a `GROUP BY` clause are translated into terms and metric aggregations,
+/// which requires the grouped and aggregated fields to be fast fields.
+pub async fn sql_search(
+    sql: &str,
+    search_service: &dyn SearchService,
+) -> crate::Result<SqlResponse> {
+    let sql_plan = parse_sql(sql)
+        .and_then(SqlPlan::try_from_select_statement)
+        .map_err(|error| SearchError::InvalidQuery(format!("Invalid SQL query: {error}")))?;
+    let search_response = search_service
+        .root_search(sql_plan.search_request.clone())
+        .await?;
+    sql_plan.build_response(search_response)
+}
+
+/// Returns the index targeted by the `FROM` clause of a SQL `SELECT` statement.
+pub fn sql_index_id(sql: &str) -> crate::Result<String> {
+    let select_statement = parse_sql(sql)
+        .map_err(|error| SearchError::InvalidQuery(format!("Invalid SQL query: {error}")))?;
+    Ok(select_statement.index_id)
+}
+
+/// A SQL statement translated into a search request, along with the information required to
+/// convert the search response into rows.
+#[derive(Debug)]
+struct SqlPlan {
+    search_request: SearchRequest,
+    columns: Vec<String>,
+    projection: Projection,
+}
+
+#[derive(Debug)]
+enum Projection {
+    /// Fields extracted from the matching documents. `SELECT *` returns all the top-level
+    /// fields of the documents.
+    Documents { fields: Vec<String> },
+    /// Group keys and aggregates, sorted and paginated once the search response is received.
+    Aggregates {
+        num_group_by: usize,
+        values: Vec<AggregateValue>,
+        order_by: Vec<(usize, bool)>,
+        offset: usize,
+        limit: usize,
+    },
+}
+
+#[derive(Debug)]
+enum AggregateValue {
+    GroupKey(usize),
+    DocCount,
+    Metric(String),
+}
+
+fn group_by_aggregation_name(level: usize) -> String {
+    format!("group_by_{level}")
+}
+
+impl SqlPlan {
+    fn try_from_select_statement(select_statement: SelectStatement) -> anyhow::Result<Self> {
+        let query_ast = match &select_statement.filter_opt {
+            Some(expr) => expr_to_query_ast(expr),
+            None => QueryAst::MatchAll,
+        };
+        let search_request = SearchRequest {
+            index_id: select_statement.index_id.clone(),
+            query_ast: serde_json::to_string(&query_ast)?,
+            ..Default::default()
+        };
+        let has_aggregates = select_statement
+            .projection
+            .iter()
+            .any(|select_item| matches!(select_item.column, Column::Aggregate { .. }));
+
+        if has_aggregates || !select_statement.group_by.is_empty() {
+            Self::plan_aggregates(select_statement, search_request)
+        } else {
+            Self::plan_documents(select_statement, search_request)
+        }
+    }
+
+    fn plan_documents(
+        select_statement: SelectStatement,
+        mut search_request: SearchRequest,
+    ) -> anyhow::Result<Self> {
+        let mut columns = Vec::with_capacity(select_statement.projection.len());
+        let mut fields = Vec::with_capacity(select_statement.projection.len());
+
+        for select_item in &select_statement.projection {
+            if let Column::Field(field) = &select_item.column {
+                columns.push(select_item.name());
+                fields.push(field.clone());
+            }
+        }
+        if select_statement.order_by.len() > 1 {
+            bail!("Sorting documents by more than one field is not supported.");
+        }
+        if let Some(order_by_item) = select_statement.order_by.first() {
+            let Column::Field(field) = &order_by_item.column else {
+                bail!("Sorting documents by an aggregate is not supported.");
+            };
+            // The sort field may be referred to by its alias.
+            let sort_by_field = select_statement
+                .projection
+                .iter()
+                .find_map(|select_item| match &select_item.column {
+                    Column::Field(aliased_field)
+                        if select_item.alias_opt.as_ref() == Some(field) =>
+                    {
+                        Some(aliased_field.clone())
+                    }
+                    _ => None,
+                })
+                .unwrap_or_else(|| field.clone());
+            let sort_order = if order_by_item.ascending {
+                SortOrder::Asc
+            } else {
+                SortOrder::Desc
+            };
+            search_request.sort_by_field = Some(sort_by_field);
+            search_request.sort_order = Some(sort_order as i32);
+        }
+        search_request.max_hits = select_statement.limit_opt.unwrap_or(DEFAULT_LIMIT);
+        search_request.start_offset = select_statement.offset_opt.unwrap_or(0);
+
+        Ok(Self {
+            search_request,
+            columns,
+            projection: Projection::Documents { fields },
+        })
+    }
+
+    fn plan_aggregates(
+        select_statement: SelectStatement,
+        mut search_request: SearchRequest,
+    ) -> anyhow::Result<Self> {
+        if select_statement.projection.is_empty() {
+            bail!("`SELECT *` cannot be combined with aggregates or `GROUP BY`.");
+        }
+        let mut columns = Vec::with_capacity(select_statement.projection.len());
+        let mut values = Vec::with_capacity(select_statement.projection.len());
+        let mut metric_aggregations = JsonMap::new();
+
+        for select_item in &select_statement.projection {
+            let value = match &select_item.column {
+                Column::Field(field) => {
+                    let level_opt = select_statement
+                        .group_by
+                        .iter()
+                        .position(|group_by_field| group_by_field == field);
+                    let Some(level) = level_opt else {
+                        bail!(
+                            "Column `{field}` must appear in the `GROUP BY` clause or be used in \
+                             an aggregate function."
+                        );
+                    };
+                    AggregateValue::GroupKey(level)
+                }
+                Column::Aggregate {
+                    function: AggregateFunction::Count,
+                    field_opt: None,
+                } => AggregateValue::DocCount,
+                Column::Aggregate {
+                    function,
+                    field_opt: Some(field),
+                } => {
+                    let metric_type = match function {
+                        AggregateFunction::Count => "value_count",
+                        AggregateFunction::Sum => "sum",
+                        AggregateFunction::Avg => "avg",
+                        AggregateFunction::Min => "min",
+                        AggregateFunction::Max => "max",
+                    };
+                    let metric_name = format!("metric_{}", metric_aggregations.len());
+                    metric_aggregations.insert(
+                        metric_name.clone(),
+                        json!({ metric_type: { "field": field } }),
+                    );
+                    AggregateValue::Metric(metric_name)
+                }
+            };
+            columns.push(select_item.name());
+            values.push(value);
+        }
+        let mut aggregations = metric_aggregations;
+
+        for (level, field) in select_statement.group_by.iter().enumerate().rev() {
+            let mut group_by_aggregation = json!({
+                "terms": {
+                    "field": field,
+                    "size": MAX_GROUP_BY_VALUES,
+                }
+            });
+            if !aggregations.is_empty() {
+                group_by_aggregation["aggs"] = JsonValue::Object(aggregations);
+            }
+            aggregations = JsonMap::new();
+            aggregations.insert(group_by_aggregation_name(level), group_by_aggregation);
+        }
+        let mut order_by = Vec::with_capacity(select_statement.order_by.len());
+
+        for order_by_item in &select_statement.order_by {
+            let column_index_opt = select_statement
+                .projection
+                .iter()
+                .position(|select_item| select_item.is_referred_to_by(&order_by_item.column));
+            let Some(column_index) = column_index_opt else {
+                bail!(
+                    "`ORDER BY {}` must refer to a column of the `SELECT` list.",
+                    order_by_item.column
+                );
+            };
+            order_by.push((column_index, order_by_item.ascending));
+        }
+        if !aggregations.is_empty() {
+            search_request.aggregation_request =
+                Some(serde_json::to_string(&JsonValue::Object(aggregations))?);
+        }
+        search_request.max_hits = 0;
+
+        let projection = Projection::Aggregates {
+            num_group_by: select_statement.group_by.len(),
+            values,
+            order_by,
+            offset: select_statement.offset_opt.unwrap_or(0) as usize,
+            limit: select_statement
+                .limit_opt
+                .map(|limit| limit as usize)
+                .unwrap_or(usize::MAX),
+        };
+        Ok(Self {
+            search_request,
+            columns,
+            projection,
+        })
+    }
+
+    fn build_response(self, search_response: SearchResponse) -> crate::Result<SqlResponse> {
+        let (columns, rows) = match self.projection {
+            Projection::Documents { fields } => {
+                let documents = search_response
+                    .hits
+                    .iter()
+                    .map(|hit| serde_json::from_str::<JsonValue>(&hit.json))
+                    .collect::<Result<Vec<_>, _>>()?;
+                if fields.is_empty() {
+                    document_rows(&documents)
+                } else {
+                    let rows: Vec<Vec<JsonValue>> = documents
+                        .iter()
+                        .map(|document| {
+                            fields
+                                .iter()
+                                .map(|field| extract_field(document, field))
+                                .collect()
+                        })
+                        .collect();
+                    (self.columns, rows)
+                }
+            }
+            Projection::Aggregates {
+                num_group_by,
+                values,
+                order_by,
+                offset,
+                limit,
+            } => {
+                let mut aggregation_results: JsonValue = match &search_response.aggregation {
+                    Some(aggregation_json) => serde_json::from_str(aggregation_json)?,
+                    None => JsonValue::Object(JsonMap::new()),
+                };
+                // Without `GROUP BY`, the whole result set forms a single group.
+                if num_group_by == 0 {
+                    aggregation_results["doc_count"] = search_response.num_hits.into();
+                }
+                let mut rows = Vec::new();
+                collect_aggregate_rows(
+                    &aggregation_results,
+                    0,
+                    num_group_by,
+                    &values,
+                    &mut Vec::new(),
+                    &mut rows,
+                );
+                rows.sort_by(|left_row, right_row| {
+                    order_by
+                        .iter()
+                        .map(|&(column_index, ascending)| {
+                            let ordering = compare_json_values(
+                                &left_row[column_index],
+                                &right_row[column_index],
+                            );
+                            if ascending {
+                                ordering
+                            } else {
+                                ordering.reverse()
+                            }
+                        })
+                        .find(|ordering| ordering.is_ne())
+                        .unwrap_or(Ordering::Equal)
+                });
+                let rows: Vec<Vec<JsonValue>> = rows.into_iter().skip(offset).take(limit).collect();
+                (self.columns, rows)
+            }
+        };
+        Ok(SqlResponse {
+            columns,
+            rows,
+            num_hits: search_response.num_hits,
+            elapsed_time_micros: search_response.elapsed_time_micros,
+            errors: search_response.errors,
+        })
+    }
+}
+
+fn literal_to_json_literal(literal: &Literal) -> JsonLiteral {
+    match literal {
+        Literal::String(text) => JsonLiteral::String(text.clone()),
+        Literal::Number(number) => JsonLiteral::Number(number.clone()),
+        Literal::Bool(boolean) => JsonLiteral::Bool(*boolean),
+    }
+}
+
+fn negate(query_ast: QueryAst) -> QueryAst {
+    // A boolean query with only `must_not` clauses matches no documents.
+    BoolQuery {
+        must: vec![QueryAst::MatchAll],
+        must_not: vec![query_ast],
+        ..Default::default()
+    }
+    .into()
+}
+
+fn expr_to_query_ast(expr: &Expr) -> QueryAst {
+    match expr {
+        Expr::And(left_expr, right_expr) => BoolQuery {
+            must: vec![expr_to_query_ast(left_expr), expr_to_query_ast(right_expr)],
+            ..Default::default()
+        }
+        .into(),
+        Expr::Or(left_expr, right_expr) => BoolQuery {
+            should: vec![expr_to_query_ast(left_expr), expr_to_query_ast(right_expr)],
+            ..Default::default()
+        }
+        .into(),
+        Expr::Not(expr) => negate(expr_to_query_ast(expr)),
+        Expr::Comparison {
+            field,
+            operator,
+            value,
+        } => {
+            let term_query = || -> QueryAst {
+                TermQuery {
+                    field: field.clone(),
+                    value: value.to_string(),
+                }
+                .into()
+            };
+            let bound = || literal_to_json_literal(value);
+            let (lower_bound, upper_bound) = match operator {
+                ComparisonOperator::Eq => return term_query(),
+                ComparisonOperator::NotEq => return negate(term_query()),
+                ComparisonOperator::Lt => (Bound::Unbounded, Bound::Excluded(bound())),
+                ComparisonOperator::Lte => (Bound::Unbounded, Bound::Included(bound())),
+                ComparisonOperator::Gt => (Bound::Excluded(bound()), Bound::Unbounded),
+                ComparisonOperator::Gte => (Bound::Included(bound()), Bound::Unbounded),
+            };
+            RangeQuery {
+                field: field.clone(),
+                lower_bound,
+                upper_bound,
+            }
+            .into()
+        }
+        Expr::In { field, values } => {
+            let terms: HashSet<String> = values.iter().map(Literal::to_string).collect();
+            TermSetQuery {
+                terms_per_field: HashMap::from([(field.clone(), terms)]),
+            }
+            .into()
+        }
+        Expr::Between { field, low, high } => RangeQuery {
+            field: field.clone(),
+            lower_bound: Bound::Included(literal_to_json_literal(low)),
+            upper_bound: Bound::Included(literal_to_json_literal(high)),
+        }
+        .into(),
+        Expr::Query(user_text) => query_ast_from_user_text(user_text, None),
+    }
+}
+
+/// Returns the value of a field of a document. Dots in the field path either belong to the
+/// field name or separate the keys of nested objects.
+fn extract_field(document: &JsonValue, field_path: &str) -> JsonValue {
+    if let Some(value) = document.get(field_path) {
+        return value.clone();
+    }
+    let mut value = document;
+    for key in field_path.split('.') {
+        let Some(nested_value) = value.get(key) else {
+            return JsonValue::Null;
+        };
+        value = nested_value;
+    }
+    value.clone()
+}
+
+/// Returns the top-level fields of the documents as columns, in order of first appearance.
+fn document_rows(documents: &[JsonValue]) -> (Vec<String>, Vec<Vec<JsonValue>>) {
+    let mut columns: Vec<String> = Vec::new();
+
+    for document in documents {
+        if let Some(document_object) = document.as_object() {
+            for field in document_object.keys() {
+                if !columns.contains(field) {
+                    columns.push(field.clone());
+                }
+            }
+        }
+    }
+    let rows: Vec<Vec<JsonValue>> = documents
+        .iter()
+        .map(|document| {
+            columns
+                .iter()
+                .map(|column| document.get(column).cloned().unwrap_or(JsonValue::Null))
+                .collect()
+        })
+        .collect();
+    (columns, rows)
+}
+
+/// Flattens the nested group by buckets into one row per group.
+fn collect_aggregate_rows(
+    aggregation_results: &JsonValue,
+    level: usize,
+    num_group_by: usize,
+    values: &[AggregateValue],
+    group_keys: &mut Vec<JsonValue>,
+    rows: &mut Vec<Vec<JsonValue>>,
+) {
+    if level == num_group_by {
+        let row: Vec<JsonValue> = values
+            .iter()
+            .map(|value| match value {
+                AggregateValue::GroupKey(level) => group_keys[*level].clone(),
+                AggregateValue::DocCount => aggregation_results["doc_count"].clone(),
+                AggregateValue::Metric(metric_name) => {
+                    aggregation_results[metric_name]["value"].clone()
+                }
+            })
+            .collect();
+        rows.push(row);
+        return;
+    }
+    let group_by_results = &aggregation_results[group_by_aggregation_name(level)];
+    let Some(buckets) = group_by_results["buckets"].as_array() else {
+        return;
+    };
+    for bucket in buckets {
+        group_keys.push(bucket["key"].clone());
+        collect_aggregate_rows(bucket, level + 1, num_group_by, values, group_keys, rows);
+        group_keys.pop();
+    }
+}
+
+/// Orders JSON values of the same type by value, and values of different types by type, nulls
+/// last.
+fn compare_json_values(left: &JsonValue, right: &JsonValue) -> Ordering {
+    fn type_rank(value: &JsonValue) -> u8 {
+        match value {
+            JsonValue::Bool(_) => 0,
+            JsonValue::Number(_) => 1,
+            JsonValue::String(_) => 2,
+            JsonValue::Array(_) => 3,
+            JsonValue::Object(_) => 4,
+            JsonValue::Null => 5,
+        }
+    }
+    match (left, right) {
+        (JsonValue::Bool(left), JsonValue::Bool(right)) => left.cmp(right),
+        (JsonValue::Number(left), JsonValue::Number(right)) => {
+            let left = left.as_f64().unwrap_or_default();
+            let right = right.as_f64().unwrap_or_default();
+            left.total_cmp(&right)
+        }
+        (JsonValue::String(left), JsonValue::String(right)) => left.cmp(right),
+        _ => type_rank(left).cmp(&type_rank(right)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_proto::Hit;
+
+    use super::*;
+    use crate::MockSearchService;
+
+    fn plan(sql: &str) -> anyhow::Result<SqlPlan> {
+        parse_sql(sql).and_then(SqlPlan::try_from_select_statement)
+    }
+
+    #[test]
+    fn test_sql_plan_documents() {
+        let sql_plan = plan(
+            "SELECT timestamp AS ts, body FROM logs WHERE severity_text = 'ERROR' AND \
+             latency_millis > 100 ORDER BY ts DESC LIMIT 10 OFFSET 20",
+        )
+        .unwrap();
+        assert_eq!(sql_plan.columns, vec!["ts".to_string(), "body".to_string()]);
+
+        let search_request = sql_plan.search_request;
+        assert_eq!(search_request.index_id, "logs");
+        assert_eq!(search_request.max_hits, 10);
+        assert_eq!(search_request.start_offset, 20);
+        assert_eq!(search_request.sort_by_field.as_deref(), Some("timestamp"));
+        assert_eq!(search_request.sort_order, Some(SortOrder::Desc as i32));
+        assert!(search_request.aggregation_request.is_none());
+
+        let query_ast: QueryAst = serde_json::from_str(&search_request.query_ast).unwrap();
+        let expected_query_ast: QueryAst = BoolQuery {
+            must: vec![
+                TermQuery {
+                    field: "severity_text".to_string(),
+                    value: "ERROR".to_string(),
+                }
+                .into(),
+                RangeQuery {
+                    field: "latency_millis".to_string(),
+                    lower_bound: Bound::Excluded(JsonLiteral::Number(100.into())),
+                    upper_bound: Bound::Unbounded,
+                }
+                .into(),
+            ],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(query_ast, expected_query_ast);
+    }
+
+    #[test]
+    fn test_sql_plan_group_by() {
+        let sql_plan = plan(
+            "SELECT service, severity_text, count(*), avg(latency_millis) FROM logs GROUP BY \
+             service, severity_text",
+        )
+        .unwrap();
+        let search_request = sql_plan.search_request;
+        assert_eq!(search_request.max_hits, 0);
+
+        let aggregation_request: JsonValue =
+            serde_json::from_str(search_request.aggregation_request.as_ref().unwrap()).unwrap();
+        let expected_aggregation_request = json!({
+            "group_by_0": {
+                "terms": {"field": "service", "size": MAX_GROUP_BY_VALUES},
+                "aggs": {
+                    "group_by_1": {
+                        "terms": {"field": "severity_text", "size": MAX_GROUP_BY_VALUES},
+                        "aggs": {
+                            "metric_0": {"avg": {"field": "latency_millis"}}
+                        }
+                    }
+                }
+            }
+        });
+        assert_eq!(aggregation_request, expected_aggregation_request);
+    }
+
+    #[test]
+    fn test_sql_plan_errors() {
+        let plan_error = |sql: &str| plan(sql).unwrap_err().to_string();
+        assert_eq!(
+            plan_error("SELECT service, count(*) FROM logs"),
+            "Column `service` must appear in the `GROUP BY` clause or be used in an aggregate \
+             function."
+        );
+        assert_eq!(
+            plan_error("SELECT * FROM logs GROUP BY service"),
+            "`SELECT *` cannot be combined with aggregates or `GROUP BY`."
+        );
+        assert_eq!(
+            plan_error("SELECT service FROM logs GROUP BY service ORDER BY count(*)"),
+            "`ORDER BY count(*)` must refer to a column of the `SELECT` list."
+        );
+        assert_eq!(
+            plan_error("SELECT * FROM logs ORDER BY timestamp, body"),
+            "Sorting documents by more than one field is not supported."
+        );
+    }
+
+    #[test]
+    fn test_expr_to_query_ast_not_in() {
+        let select_statement =
+            parse_sql("SELECT * FROM logs WHERE service NOT IN ('a', 'b')").unwrap();
+        let query_ast = expr_to_query_ast(select_statement.filter_opt.as_ref().unwrap());
+        let expected_query_ast = negate(
+            TermSetQuery {
+                terms_per_field: HashMap::from([(
+                    "service".to_string(),
+                    HashSet::from(["a".to_string(), "b".to_string()]),
+                )]),
+            }
+            .into(),
+        );
+        assert_eq!(query_ast, expected_query_ast);
+    }
+
+    #[tokio::test]
+    async fn test_sql_search_documents() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_root_search().returning(|_| {
+            let hits = [
+                r#"{"body": "foo", "attributes": {"status": 500}}"#,
+                r#"{"body": "bar", "resource": "baz"}"#,
+            ]
+            .into_iter()
+            .map(|json| Hit {
+                json: json.to_string(),
+                ..Default::default()
+            })
+            .collect();
+            Ok(SearchResponse {
+                num_hits: 2,
+                hits,
+                ..Default::default()
+            })
+        });
+        let sql_response = sql_search(
+            "SELECT body, attributes.status FROM logs",
+            &mock_search_service,
+        )
+        .await
+        .unwrap();
+        assert_eq!(sql_response.columns, vec!["body", "attributes.status"]);
+        assert_eq!(
+            sql_response.rows,
+            vec![
+                vec![json!("foo"), json!(500)],
+                vec![json!("bar"), JsonValue::Null]
+            ]
+        );
+        assert_eq!(sql_response.num_hits, 2);
+
+        let sql_response = sql_search("SELECT * FROM logs", &mock_search_service)
+            .await
+            .unwrap();
+        assert_eq!(sql_response.columns, vec!["body", "attributes", "resource"]);
+        assert_eq!(
+            sql_response.rows,
+            vec![
+                vec![json!("foo"), json!({"status": 500}), JsonValue::Null],
+                vec![json!("bar"), JsonValue::Null, json!("baz")]
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sql_search_group_by() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service.expect_root_search().returning(|_| {
+            let aggregation = json!({
+                "group_by_0": {
+                    "buckets": [
+                        {"key": "api", "doc_count": 10, "metric_0": {"value": 12.5}},
+                        {"key": "db", "doc_count": 5, "metric_0": {"value": 30.0}},
+                        {"key": "web", "doc_count": 5, "metric_0": {"value": null}}
+                    ]
+                }
+            });
+            Ok(SearchResponse {
+                num_hits: 20,
+                aggregation: Some(aggregation.to_string()),
+                ..Default::default()
+            })
+        });
+        let sql_response = sql_search(
+            "SELECT service, count(*) AS num_logs, max(latency) FROM logs GROUP BY service ORDER \
+             BY num_logs, service DESC LIMIT 2",
+            &mock_search_service,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            sql_response.columns,
+            vec!["service", "num_logs", "max(latency)"]
+        );
+        assert_eq!(
+            sql_response.rows,
+            vec![
+                vec![json!("web"), json!(5), JsonValue::Null],
+                vec![json!("db"), json!(5), json!(30.0)],
+            ]
+        );
+        assert_eq!(sql_response.num_hits, 20);
+    }
+
+    #[tokio::test]
+    async fn test_sql_search_count_without_group_by() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .withf(|search_request| search_request.aggregation_request.is_none())
+            .returning(|_| {
+                Ok(SearchResponse {
+                    num_hits: 42,
+                    ..Default::default()
+                })
+            });
+        let sql_response = sql_search(
+            "SELECT count(*) FROM logs WHERE QUERY('body:error')",
+            &mock_search_service,
+        )
+        .await
+        .unwrap();
+        assert_eq!(sql_response.columns, vec!["count(*)"]);
+        assert_eq!(sql_response.rows, vec![vec![json!(42)]]);
+    }
+
+    #[tokio::test]
+    async fn test_sql_search_invalid_query() {
+        let mock_search_service = MockSearchService::new();
+        let error = sql_search("SELECT FROM logs", &mock_search_service)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, SearchError::InvalidQuery(_)));
+        assert_eq!(
+            error.to_string(),
+            "Invalid SQL query: Expected an identifier, got `FROM`."
+        );
+    }
+}
@@ -0,0 +1,813 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+
+use anyhow::{bail, Context};
+use serde_json::Number as JsonNumber;
+
+/// A parsed `SELECT` statement.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SelectStatement {
+    /// Selected columns. Empty for `SELECT *`.
+    pub projection: Vec<SelectItem>,
+    pub index_id: String,
+    pub filter_opt: Option<Expr>,
+    pub group_by: Vec<String>,
+    pub order_by: Vec<OrderByItem>,
+    pub limit_opt: Option<u64>,
+    pub offset_opt: Option<u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct SelectItem {
+    pub column: Column,
+    pub alias_opt: Option<String>,
+}
+
+impl SelectItem {
+    /// Returns the name of the column in the SQL response.
+    pub fn name(&self) -> String {
+        self.alias_opt
+            .clone()
+            .unwrap_or_else(|| self.column.to_string())
+    }
+
+    /// Returns true if `column`, as written in an `ORDER BY` clause, refers to this item by
+    /// expression or by alias.
+    pub fn is_referred_to_by(&self, column: &Column) -> bool {
+        if &self.column == column {
+            return true;
+        }
+        matches!(column, Column::Field(name) if self.alias_opt.as_ref() == Some(name))
+    }
+}
+
+/// A field or an aggregate of a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Column {
+    Field(String),
+    /// Aggregate over a field. `COUNT(*)` has no field.
+    Aggregate {
+        function: AggregateFunction,
+        field_opt: Option<String>,
+    },
+}
+
+impl fmt::Display for Column {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Column::Field(field) => write!(formatter, "{field}"),
+            Column::Aggregate {
+                function,
+                field_opt: Some(field),
+            } => write!(formatter, "{function}({field})"),
+            Column::Aggregate {
+                function,
+                field_opt: None,
+            } => write!(formatter, "{function}(*)"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AggregateFunction {
+    Count,
+    Sum,
+    Avg,
+    Min,
+    Max,
+}
+
+impl AggregateFunction {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword.to_ascii_lowercase().as_str() {
+            "count" => Some(Self::Count),
+            "sum" => Some(Self::Sum),
+            "avg" => Some(Self::Avg),
+            "min" => Some(Self::Min),
+            "max" => Some(Self::Max),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for AggregateFunction {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Self::Count => "count",
+            Self::Sum => "sum",
+            Self::Avg => "avg",
+            Self::Min => "min",
+            Self::Max => "max",
+        };
+        formatter.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct OrderByItem {
+    pub column: Column,
+    pub ascending: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ComparisonOperator {
+    Eq,
+    NotEq,
+    Lt,
+    Lte,
+    Gt,
+    Gte,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Literal {
+    String(String),
+    Number(JsonNumber),
+    Bool(bool),
+}
+
+impl fmt::Display for Literal {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Literal::String(text) => formatter.write_str(text),
+            Literal::Number(number) => write!(formatter, "{number}"),
+            Literal::Bool(boolean) => write!(formatter, "{boolean}"),
+        }
+    }
+}
+
+/// A boolean expression of the `WHERE` clause.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    Comparison {
+        field: String,
+        operator: ComparisonOperator,
+        value: Literal,
+    },
+    In {
+        field: String,
+        values: Vec<Literal>,
+    },
+    Between {
+        field: String,
+        low: Literal,
+        high: Literal,
+    },
+    /// `QUERY('...')` runs a query expressed in the Quickwit query language.
+    Query(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    /// Unquoted identifier or keyword.
+    Word(String),
+    /// Identifier quoted with double quotes or backticks.
+    QuotedIdentifier(String),
+    String(String),
+    Number(String),
+    Operator(ComparisonOperator),
+    Comma,
+    LeftParen,
+    RightParen,
+    Star,
+    Semicolon,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(formatter, "`{word}`"),
+            Token::QuotedIdentifier(identifier) => write!(formatter, "`\"{identifier}\"`"),
+            Token::String(text) => write!(formatter, "`'{text}'`"),
+            Token::Number(number) => write!(formatter, "`{number}`"),
+            Token::Operator(operator) => {
+                let operator_str = match operator {
+                    ComparisonOperator::Eq => "=",
+                    ComparisonOperator::NotEq => "!=",
+                    ComparisonOperator::Lt => "<",
+                    ComparisonOperator::Lte => "<=",
+                    ComparisonOperator::Gt => ">",
+                    ComparisonOperator::Gte => ">=",
+                };
+                write!(formatter, "`{operator_str}`")
+            }
+            Token::Comma => formatter.write_str("`,`"),
+            Token::LeftParen => formatter.write_str("`(`"),
+            Token::RightParen => formatter.write_str("`)`"),
+            Token::Star => formatter.write_str("`*`"),
+            Token::Semicolon => formatter.write_str("`;`"),
+        }
+    }
+}
+
+/// Words that cannot be used as unquoted identifiers.
+const RESERVED_KEYWORDS: &[&str] = &[
+    "and", "as", "asc", "between", "by", "desc", "false", "from", "group", "having", "in", "limit",
+    "not", "offset", "or", "order", "select", "true", "where",
+];
+
+fn is_reserved_keyword(word: &str) -> bool {
+    RESERVED_KEYWORDS.contains(&word.to_ascii_lowercase().as_str())
+}
+
+fn tokenize(sql: &str) -> anyhow::Result<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = sql.chars().peekable();
+
+    while let Some(&next_char) = chars.peek() {
+        match next_char {
+            _ if next_char.is_whitespace() => {
+                chars.next();
+            }
+            ',' | '(' | ')' | '*' | ';' | '=' => {
+                chars.next();
+                let token = match next_char {
+                    ',' => Token::Comma,
+                    '(' => Token::LeftParen,
+                    ')' => Token::RightParen,
+                    '*' => Token::Star,
+                    ';' => Token::Semicolon,
+                    _ => Token::Operator(ComparisonOperator::Eq),
+                };
+                tokens.push(token);
+            }
+            '<' | '>' | '!' => {
+                chars.next();
+                let operator = match (next_char, chars.peek()) {
+                    ('<', Some('=')) => ComparisonOperator::Lte,
+                    ('<', Some('>')) | ('!', Some('=')) => ComparisonOperator::NotEq,
+                    ('>', Some('=')) => ComparisonOperator::Gte,
+                    ('<', _) => ComparisonOperator::Lt,
+                    ('>', _) => ComparisonOperator::Gt,
+                    _ => bail!("Unexpected character `!`."),
+                };
+                if matches!(
+                    operator,
+                    ComparisonOperator::Lte | ComparisonOperator::Gte | ComparisonOperator::NotEq
+                ) {
+                    chars.next();
+                }
+                tokens.push(Token::Operator(operator));
+            }
+            '\'' => {
+                chars.next();
+                let mut text = String::new();
+                loop {
+                    match chars.next() {
+                        // A quote is escaped by doubling it.
+                        Some('\'') if chars.peek() == Some(&'\'') => {
+                            chars.next();
+                            text.push('\'');
+                        }
+                        Some('\'') => break,
+                        Some(character) => text.push(character),
+                        None => bail!("Unterminated string literal `'{text}`."),
+                    }
+                }
+                tokens.push(Token::String(text));
+            }
+            '"' | '`' => {
+                chars.next();
+                let mut identifier = String::new();
+                loop {
+                    match chars.next() {
+                        Some(character) if character == next_char => break,
+                        Some(character) => identifier.push(character),
+                        None => bail!("Unterminated quoted identifier `{next_char}{identifier}`."),
+                    }
+                }
+                tokens.push(Token::QuotedIdentifier(identifier));
+            }
+            '-' | '0'..='9' => {
+                let mut number = String::new();
+                number.push(next_char);
+                chars.next();
+                while let Some(&character) = chars.peek() {
+                    let is_exponent_sign = matches!(character, '+' | '-')
+                        && matches!(number.chars().last(), Some('e' | 'E'));
+                    if character.is_ascii_digit()
+                        || matches!(character, '.' | 'e' | 'E')
+                        || is_exponent_sign
+                    {
+                        number.push(character);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Number(number));
+            }
+            _ if next_char.is_alphabetic() || next_char == '_' => {
+                let mut word = String::new();
+                while let Some(&character) = chars.peek() {
+                    if character.is_alphanumeric() || matches!(character, '_' | '.' | '-') {
+                        word.push(character);
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                tokens.push(Token::Word(word));
+            }
+            _ => bail!("Unexpected character `{next_char}`."),
+        }
+    }
+    Ok(tokens)
+}
+
+/// Parses a SQL `SELECT` statement.
+pub(crate) fn parse_sql(sql: &str) -> anyhow::Result<SelectStatement> {
+    let tokens = tokenize(sql)?;
+    let mut parser = Parser {
+        tokens,
+        position: 0,
+    };
+    let select_statement = parser.parse_select_statement()?;
+    parser.consume(&Token::Semicolon);
+    if let Some(token) = parser.peek() {
+        bail!("Unexpected {token} after the end of the statement.");
+    }
+    Ok(select_statement)
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next_token(&mut self) -> anyhow::Result<Token> {
+        let token = self
+            .tokens
+            .get(self.position)
+            .cloned()
+            .context("Unexpected end of statement.")?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    /// Consumes the next token if it is equal to `token`.
+    fn consume(&mut self, token: &Token) -> bool {
+        if self.peek() == Some(token) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect(&mut self, token: &Token) -> anyhow::Result<()> {
+        let next_token = self.next_token()?;
+        if &next_token != token {
+            bail!("Expected {token}, got {next_token}.");
+        }
+        Ok(())
+    }
+
+    fn peek_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Word(word)) if word.eq_ignore_ascii_case(keyword))
+    }
+
+    /// Consumes the next token if it is the keyword `keyword`.
+    fn consume_keyword(&mut self, keyword: &str) -> bool {
+        if self.peek_keyword(keyword) {
+            self.position += 1;
+            return true;
+        }
+        false
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> anyhow::Result<()> {
+        if !self.consume_keyword(keyword) {
+            match self.peek() {
+                Some(token) => bail!("Expected `{}`, got {token}.", keyword.to_uppercase()),
+                None => bail!(
+                    "Expected `{}`, got end of statement.",
+                    keyword.to_uppercase()
+                ),
+            }
+        }
+        Ok(())
+    }
+
+    fn peek_identifier(&self) -> bool {
+        match self.peek() {
+            Some(Token::Word(word)) => !is_reserved_keyword(word),
+            Some(Token::QuotedIdentifier(_)) => true,
+            _ => false,
+        }
+    }
+
+    fn parse_identifier(&mut self) -> anyhow::Result<String> {
+        match self.next_token()? {
+            Token::Word(word) if !is_reserved_keyword(&word) => Ok(word),
+            Token::QuotedIdentifier(identifier) => Ok(identifier),
+            token => bail!("Expected an identifier, got {token}."),
+        }
+    }
+
+    fn parse_unsigned_integer(&mut self) -> anyhow::Result<u64> {
+        match self.next_token()? {
+            Token::Number(number) => number
+                .parse()
+                .with_context(|| format!("Expected a positive integer, got `{number}`.")),
+            token => bail!("Expected a positive integer, got {token}."),
+        }
+    }
+
+    fn parse_select_statement(&mut self) -> anyhow::Result<SelectStatement> {
+        self.expect_keyword("select")?;
+        let projection = if self.consume(&Token::Star) {
+            Vec::new()
+        } else {
+            self.parse_comma_separated(Self::parse_select_item)?
+        };
+        self.expect_keyword("from")?;
+        let index_id = self.parse_identifier()?;
+
+        let filter_opt = if self.consume_keyword("where") {
+            Some(self.parse_expr()?)
+        } else {
+            None
+        };
+        let group_by = if self.consume_keyword("group") {
+            self.expect_keyword("by")?;
+            self.parse_comma_separated(Self::parse_identifier)?
+        } else {
+            Vec::new()
+        };
+        if self.peek_keyword("having") {
+            bail!("`HAVING` is not supported.");
+        }
+        let order_by = if self.consume_keyword("order") {
+            self.expect_keyword("by")?;
+            self.parse_comma_separated(Self::parse_order_by_item)?
+        } else {
+            Vec::new()
+        };
+        let limit_opt = if self.consume_keyword("limit") {
+            Some(self.parse_unsigned_integer()?)
+        } else {
+            None
+        };
+        let offset_opt = if self.consume_keyword("offset") {
+            Some(self.parse_unsigned_integer()?)
+        } else {
+            None
+        };
+        Ok(SelectStatement {
+            projection,
+            index_id,
+            filter_opt,
+            group_by,
+            order_by,
+            limit_opt,
+            offset_opt,
+        })
+    }
+
+    fn parse_comma_separated<T>(
+        &mut self,
+        parse_item: impl Fn(&mut Self) -> anyhow::Result<T>,
+    ) -> anyhow::Result<Vec<T>> {
+        let mut items = vec![parse_item(self)?];
+        while self.consume(&Token::Comma) {
+            items.push(parse_item(self)?);
+        }
+        Ok(items)
+    }
+
+    fn parse_column(&mut self) -> anyhow::Result<Column> {
+        let function_opt = match (self.peek(), self.tokens.get(self.position + 1)) {
+            (Some(Token::Word(word)), Some(Token::LeftParen)) => {
+                let function = AggregateFunction::from_keyword(word)
+                    .with_context(|| format!("Unsupported function `{word}`."))?;
+                Some(function)
+            }
+            _ => None,
+        };
+        let Some(function) = function_opt else {
+            return Ok(Column::Field(self.parse_identifier()?));
+        };
+        self.position += 1;
+        self.expect(&Token::LeftParen)?;
+        let field_opt = if self.consume(&Token::Star) {
+            if function != AggregateFunction::Count {
+                bail!("`{function}(*)` is not supported.");
+            }
+            None
+        } else {
+            Some(self.parse_identifier()?)
+        };
+        self.expect(&Token::RightParen)?;
+        Ok(Column::Aggregate {
+            function,
+            field_opt,
+        })
+    }
+
+    fn parse_select_item(&mut self) -> anyhow::Result<SelectItem> {
+        let column = self.parse_column()?;
+        let alias_opt = if self.consume_keyword("as") {
+            Some(self.parse_identifier()?)
+        } else if self.peek_identifier() {
+            Some(self.parse_identifier()?)
+        } else {
+            None
+        };
+        Ok(SelectItem { column, alias_opt })
+    }
+
+    fn parse_order_by_item(&mut self) -> anyhow::Result<OrderByItem> {
+        let column = self.parse_column()?;
+        let ascending = if self.consume_keyword("desc") {
+            false
+        } else {
+            self.consume_keyword("asc");
+            true
+        };
+        Ok(OrderByItem { column, ascending })
+    }
+
+    fn parse_expr(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.parse_and_expr()?;
+        while self.consume_keyword("or") {
+            let right_expr = self.parse_and_expr()?;
+            expr = Expr::Or(Box::new(expr), Box::new(right_expr));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and_expr(&mut self) -> anyhow::Result<Expr> {
+        let mut expr = self.parse_not_expr()?;
+        while self.consume_keyword("and") {
+            let right_expr = self.parse_not_expr()?;
+            expr = Expr::And(Box::new(expr), Box::new(right_expr));
+        }
+        Ok(expr)
+    }
+
+    fn parse_not_expr(&mut self) -> anyhow::Result<Expr> {
+        if self.consume_keyword("not") {
+            let expr = self.parse_not_expr()?;
+            return Ok(Expr::Not(Box::new(expr)));
+        }
+        self.parse_primary_expr()
+    }
+
+    fn parse_primary_expr(&mut self) -> anyhow::Result<Expr> {
+        if self.consume(&Token::LeftParen) {
+            let expr = self.parse_expr()?;
+            self.expect(&Token::RightParen)?;
+            return Ok(expr);
+        }
+        if self.peek_keyword("query")
+            && self.tokens.get(self.position + 1) == Some(&Token::LeftParen)
+        {
+            self.position += 2;
+            let user_text = match self.next_token()? {
+                Token::String(text) => text,
+                token => bail!("Expected a string literal, got {token}."),
+            };
+            self.expect(&Token::RightParen)?;
+            return Ok(Expr::Query(user_text));
+        }
+        let field = self.parse_identifier()?;
+        let negated = self.consume_keyword("not");
+
+        let expr = if self.consume_keyword("in") {
+            self.expect(&Token::LeftParen)?;
+            let values = self.parse_comma_separated(Self::parse_literal)?;
+            self.expect(&Token::RightParen)?;
+            Expr::In { field, values }
+        } else if self.consume_keyword("between") {
+            let low = self.parse_literal()?;
+            self.expect_keyword("and")?;
+            let high = self.parse_literal()?;
+            Expr::Between { field, low, high }
+        } else if negated {
+            bail!("Expected `IN` or `BETWEEN` after `NOT`.");
+        } else {
+            let operator = match self.next_token()? {
+                Token::Operator(operator) => operator,
+                token => bail!("Expected a comparison operator after `{field}`, got {token}."),
+            };
+            let value = self.parse_literal()?;
+            Expr::Comparison {
+                field,
+                operator,
+                value,
+            }
+        };
+        if negated {
+            return Ok(Expr::Not(Box::new(expr)));
+        }
+        Ok(expr)
+    }
+
+    fn parse_literal(&mut self) -> anyhow::Result<Literal> {
+        match self.next_token()? {
+            Token::String(text) => Ok(Literal::String(text)),
+            Token::Number(number) => {
+                let json_number: JsonNumber = serde_json::from_str(&number)
+                    .with_context(|| format!("Invalid number `{number}`."))?;
+                Ok(Literal::Number(json_number))
+            }
+            Token::Word(word) if word.eq_ignore_ascii_case("true") => Ok(Literal::Bool(true)),
+            Token::Word(word) if word.eq_ignore_ascii_case("false") => Ok(Literal::Bool(false)),
+            token => bail!("Expected a literal, got {token}."),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(name: &str) -> Column {
+        Column::Field(name.to_string())
+    }
+
+    #[test]
+    fn test_parse_sql_select_star() {
+        let select_statement = parse_sql("select * from `otel-logs-v0_6` limit 10;").unwrap();
+        assert_eq!(
+            select_statement,
+            SelectStatement {
+                projection: Vec::new(),
+                index_id: "otel-logs-v0_6".to_string(),
+                filter_opt: None,
+                group_by: Vec::new(),
+                order_by: Vec::new(),
+                limit_opt: Some(10),
+                offset_opt: None,
+            }
+        );
+        let select_statement = parse_sql("SELECT * FROM otel-logs-v0_6").unwrap();
+        assert_eq!(select_statement.index_id, "otel-logs-v0_6");
+    }
+
+    #[test]
+    fn test_parse_sql_group_by() {
+        let select_statement = parse_sql(
+            "SELECT service_name AS service, COUNT(*), avg(attributes.duration) avg_duration FROM \
+             logs GROUP BY service_name ORDER BY COUNT(*) DESC, service LIMIT 5 OFFSET 10",
+        )
+        .unwrap();
+        assert_eq!(
+            select_statement.projection,
+            vec![
+                SelectItem {
+                    column: field("service_name"),
+                    alias_opt: Some("service".to_string()),
+                },
+                SelectItem {
+                    column: Column::Aggregate {
+                        function: AggregateFunction::Count,
+                        field_opt: None
+                    },
+                    alias_opt: None,
+                },
+                SelectItem {
+                    column: Column::Aggregate {
+                        function: AggregateFunction::Avg,
+                        field_opt: Some("attributes.duration".to_string())
+                    },
+                    alias_opt: Some("avg_duration".to_string()),
+                },
+            ]
+        );
+        assert_eq!(select_statement.projection[1].name(), "count(*)");
+        assert_eq!(select_statement.projection[2].name(), "avg_duration");
+        assert_eq!(select_statement.group_by, vec!["service_name".to_string()]);
+        assert_eq!(
+            select_statement.order_by,
+            vec![
+                OrderByItem {
+                    column: Column::Aggregate {
+                        function: AggregateFunction::Count,
+                        field_opt: None
+                    },
+                    ascending: false,
+                },
+                OrderByItem {
+                    column: field("service"),
+                    ascending: true,
+                },
+            ]
+        );
+        assert_eq!(select_statement.limit_opt, Some(5));
+        assert_eq!(select_statement.offset_opt, Some(10));
+    }
+
+    #[test]
+    fn test_parse_sql_where() {
+        let select_statement = parse_sql(
+            "SELECT body FROM logs WHERE severity_text = 'ERROR' AND NOT (status != 200 OR \
+             latency >= -1.5e3) AND service NOT IN ('a', 'it''s') AND ts BETWEEN 1 AND 2 OR \
+             QUERY('body:failed')",
+        )
+        .unwrap();
+        let comparison = |field: &str, operator, value| Expr::Comparison {
+            field: field.to_string(),
+            operator,
+            value,
+        };
+        let expected_expr = Expr::Or(
+            Box::new(Expr::And(
+                Box::new(Expr::And(
+                    Box::new(Expr::And(
+                        Box::new(comparison(
+                            "severity_text",
+                            ComparisonOperator::Eq,
+                            Literal::String("ERROR".to_string()),
+                        )),
+                        Box::new(Expr::Not(Box::new(Expr::Or(
+                            Box::new(comparison(
+                                "status",
+                                ComparisonOperator::NotEq,
+                                Literal::Number(200.into()),
+                            )),
+                            Box::new(comparison(
+                                "latency",
+                                ComparisonOperator::Gte,
+                                Literal::Number(JsonNumber::from_f64(-1500.0).unwrap()),
+                            )),
+                        )))),
+                    )),
+                    Box::new(Expr::Not(Box::new(Expr::In {
+                        field: "service".to_string(),
+                        values: vec![
+                            Literal::String("a".to_string()),
+                            Literal::String("it's".to_string()),
+                        ],
+                    }))),
+                )),
+                Box::new(Expr::Between {
+                    field: "ts".to_string(),
+                    low: Literal::Number(1.into()),
+                    high: Literal::Number(2.into()),
+                }),
+            )),
+            Box::new(Expr::Query("body:failed".to_string())),
+        );
+        assert_eq!(select_statement.filter_opt, Some(expected_expr));
+    }
+
+    #[test]
+    fn test_parse_sql_errors() {
+        let parse_error = |sql: &str| parse_sql(sql).unwrap_err().to_string();
+        assert_eq!(
+            parse_error("SELECT *"),
+            "Expected `FROM`, got end of statement."
+        );
+        assert_eq!(
+            parse_error("SELECT * FROM logs WHERE body = 'foo"),
+            "Unterminated string literal `'foo`."
+        );
+        assert_eq!(
+            parse_error("SELECT * FROM logs LIMIT 10 foo"),
+            "Unexpected `foo` after the end of the statement."
+        );
+        assert_eq!(
+            parse_error("SELECT median(latency) FROM logs"),
+            "Unsupported function `median`."
+        );
+        assert_eq!(
+            parse_error("SELECT sum(*) FROM logs"),
+            "`sum(*)` is not supported."
+        );
+        assert_eq!(
+            parse_error("SELECT count(*) FROM logs GROUP BY service HAVING count(*) > 1"),
+            "`HAVING` is not supported."
+        );
+        assert_eq!(
+            parse_error("SELECT * FROM logs WHERE status 200"),
+            "Expected a comparison operator after `status`, got `200`."
+        );
+    }
+}
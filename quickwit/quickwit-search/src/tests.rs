@@ -756,6 +756,134 @@ async fn test_single_node_split_pruning_by_tags() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Indexes one split per element of `docs_per_split`, then checks the number of splits selected
+/// for each query of `expected_num_splits_per_query` and that pruning does not alter the hits of
+/// `search_query`.
+async fn test_split_pruning_util(
+    index_id: &str,
+    doc_mapping_yaml: &str,
+    docs_per_split: Vec<Vec<JsonValue>>,
+    expected_num_splits_per_query: &[(&str, usize)],
+    (search_query, expected_num_hits): (&str, u64),
+) -> anyhow::Result<()> {
+    let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"]).await?;
+    let index_uid = test_sandbox.index_uid();
+
+    for docs in docs_per_split {
+        test_sandbox.add_documents(docs).await?;
+    }
+    for (query, expected_num_splits) in expected_num_splits_per_query {
+        let selected_splits = list_relevant_splits(
+            index_uid.clone(),
+            &SearchRequest {
+                index_id: index_id.to_string(),
+                query_ast: qast_helper(query, &[]),
+                ..Default::default()
+            },
+            &*test_sandbox.metastore(),
+        )
+        .await?;
+        assert_eq!(
+            selected_splits.len(),
+            *expected_num_splits,
+            "query: {query}"
+        );
+    }
+    let search_request = SearchRequest {
+        index_id: index_id.to_string(),
+        query_ast: qast_helper(search_query, &[]),
+        max_hits: 10,
+        ..Default::default()
+    };
+    let search_response = single_node_search(
+        search_request,
+        &*test_sandbox.metastore(),
+        test_sandbox.storage_uri_resolver(),
+    )
+    .await?;
+    assert_eq!(search_response.num_hits, expected_num_hits);
+    test_sandbox.assert_quit().await;
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_single_node_split_pruning_by_bloom_filters() -> anyhow::Result<()> {
+    let doc_mapping_yaml = r#"
+            bloom_filter_fields:
+              - trace_id
+            field_mappings:
+              - name: trace_id
+                type: text
+                tokenizer: raw
+              - name: body
+                type: text
+        "#;
+    let docs_per_split = (0..2)
+        .map(|split_ord| {
+            (0..3)
+                .map(|doc_ord| {
+                    json!({"trace_id": format!("trace-{split_ord}-{doc_ord}"), "body": "hello"})
+                })
+                .collect()
+        })
+        .collect();
+    test_split_pruning_util(
+        "single-node-pruning-by-bloom-filters",
+        doc_mapping_yaml,
+        docs_per_split,
+        &[
+            ("trace_id:trace-0-1", 1),
+            ("trace_id:trace-1-2 AND body:hello", 1),
+            ("trace_id:trace-0-1 OR trace_id:trace-1-2", 2),
+            ("trace_id:unknown", 0),
+            ("trace_id:unknown OR body:hello", 2),
+            ("body:hello AND NOT trace_id:trace-0-1", 2),
+        ],
+        ("trace_id:trace-1-2", 1),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_single_node_split_pruning_by_field_ranges() -> anyhow::Result<()> {
+    let doc_mapping_yaml = r#"
+            min_max_fields:
+              - status_code
+            field_mappings:
+              - name: status_code
+                type: u64
+                fast: true
+              - name: body
+                type: text
+        "#;
+    let docs_per_split = [[200, 201, 204], [404, 500, 503]]
+        .iter()
+        .map(|status_codes| {
+            status_codes
+                .iter()
+                .map(|status_code| json!({"status_code": status_code, "body": "hello"}))
+                .collect()
+        })
+        .collect();
+    test_split_pruning_util(
+        "single-node-pruning-by-field-ranges",
+        doc_mapping_yaml,
+        docs_per_split,
+        &[
+            ("status_code:>=500", 1),
+            ("status_code:[200 TO 299] AND body:hello", 1),
+            ("status_code:>=204", 2),
+            ("status_code:201", 1),
+            ("status_code:>503", 0),
+            ("status_code:>503 OR body:hello", 2),
+            ("body:hello AND NOT status_code:>=500", 2),
+        ],
+        ("status_code:>=500", 2),
+    )
+    .await
+}
+
 async fn test_search_dynamic_util(test_sandbox: &TestSandbox, query: &str) -> Vec<u32> {
     let splits = test_sandbox
         .metastore()
@@ -0,0 +1,301 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use warp::{Filter, Rejection};
+
+use super::runtime_settings::{
+    LogLevel, RuntimeSettings, RuntimeSettingsError, RuntimeSettingsService,
+};
+use crate::format::extract_format_from_qs;
+use crate::json_api_response::make_json_api_response;
+
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    paths(
+        get_settings_endpoint,
+        update_settings_endpoint,
+        get_log_level_endpoint,
+        update_log_level_endpoint
+    ),
+    components(schemas(RuntimeSettings, LogLevel))
+)]
+pub struct AdminApi;
+
+/// Admin settings handler.
+pub(crate) fn admin_settings_handler(
+    runtime_settings_service: Arc<RuntimeSettingsService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    get_settings_handler(runtime_settings_service.clone())
+        .or(update_settings_handler(runtime_settings_service.clone()))
+        .or(get_log_level_handler(runtime_settings_service.clone()))
+        .or(update_log_level_handler(runtime_settings_service))
+}
+
+fn get_settings_handler(
+    runtime_settings_service: Arc<RuntimeSettingsService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("admin" / "settings")
+        .and(warp::get())
+        .and(warp::any().map(move || runtime_settings_service.clone()))
+        .then(get_settings_endpoint)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Admin",
+    path = "/admin/settings",
+    responses(
+        (status = 200, description = "Successfully fetched the runtime settings of the node.", body = RuntimeSettings)
+    )
+)]
+/// Get Runtime Settings
+///
+/// Returns the settings overridden at runtime on the node. Settings absent from the response
+/// take their value from the node config.
+async fn get_settings_endpoint(
+    runtime_settings_service: Arc<RuntimeSettingsService>,
+) -> Result<RuntimeSettings, RuntimeSettingsError> {
+    Ok(runtime_settings_service.settings().await)
+}
+
+fn update_settings_handler(
+    runtime_settings_service: Arc<RuntimeSettingsService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("admin" / "settings")
+        .and(warp::put())
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::body::json())
+        .and(warp::any().map(move || runtime_settings_service.clone()))
+        .then(update_settings_endpoint)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Admin",
+    path = "/admin/settings",
+    request_body = RuntimeSettings,
+    responses(
+        (status = 200, description = "Successfully updated the runtime settings of the node.", body = RuntimeSettings)
+    )
+)]
+/// Update Runtime Settings
+///
+/// Replaces the settings overridden at runtime on the node, without restarting it. The settings
+/// are persisted in the data directory of the node and reapplied on startup. Settings absent
+/// from the request are restored to their value from the node config.
+async fn update_settings_endpoint(
+    settings: RuntimeSettings,
+    runtime_settings_service: Arc<RuntimeSettingsService>,
+) -> Result<RuntimeSettings, RuntimeSettingsError> {
+    runtime_settings_service.update(settings).await
+}
+
+fn get_log_level_handler(
+    runtime_settings_service: Arc<RuntimeSettingsService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("admin" / "log-level")
+        .and(warp::get())
+        .and(warp::any().map(move || runtime_settings_service.clone()))
+        .then(get_log_level_endpoint)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Admin",
+    path = "/admin/log-level",
+    responses(
+        (status = 200, description = "Successfully fetched the log filter of the node.", body = LogLevel)
+    )
+)]
+/// Get Log Level
+///
+/// Returns the filter of the log events currently applied on the node, whether it was set on
+/// startup or changed at runtime.
+async fn get_log_level_endpoint(
+    runtime_settings_service: Arc<RuntimeSettingsService>,
+) -> Result<LogLevel, RuntimeSettingsError> {
+    Ok(runtime_settings_service.log_level())
+}
+
+fn update_log_level_handler(
+    runtime_settings_service: Arc<RuntimeSettingsService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("admin" / "log-level")
+        .and(warp::put())
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::body::json())
+        .and(warp::any().map(move || runtime_settings_service.clone()))
+        .then(update_log_level_endpoint)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Admin",
+    path = "/admin/log-level",
+    request_body = LogLevel,
+    responses(
+        (status = 200, description = "Successfully updated the log filter of the node.", body = LogLevel)
+    )
+)]
+/// Update Log Level
+///
+/// Replaces the filter of the log events of the node, without restarting it. The filter is
+/// persisted with the other runtime settings, which are left unchanged.
+async fn update_log_level_endpoint(
+    log_level: LogLevel,
+    runtime_settings_service: Arc<RuntimeSettingsService>,
+) -> Result<LogLevel, RuntimeSettingsError> {
+    runtime_settings_service.update_log_level(log_level).await
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_config::SearcherConfig;
+    use quickwit_search::SearcherContext;
+    use serde_json::Value as JsonValue;
+
+    use super::*;
+    use crate::admin_api::RuntimeHooks;
+    use crate::recover_fn;
+
+    #[tokio::test]
+    async fn test_admin_settings_handler() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let searcher_context = Arc::new(SearcherContext::new(SearcherConfig::default()));
+        let runtime_settings_service = RuntimeSettingsService::load(
+            data_dir.path(),
+            100,
+            searcher_context.clone(),
+            None,
+            RuntimeHooks::default(),
+        )
+        .await
+        .unwrap();
+        let admin_handler =
+            admin_settings_handler(Arc::new(runtime_settings_service)).recover(recover_fn);
+
+        let resp = warp::test::request()
+            .path("/admin/settings")
+            .reply(&admin_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let settings_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(settings_json, serde_json::json!({}));
+
+        let resp = warp::test::request()
+            .method("PUT")
+            .path("/admin/settings")
+            .json(&serde_json::json!({"max_num_concurrent_split_searches": 4}))
+            .reply(&admin_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(
+            searcher_context.max_num_concurrent_split_searches().await,
+            4
+        );
+
+        let resp = warp::test::request()
+            .path("/admin/settings")
+            .reply(&admin_handler)
+            .await;
+        let settings_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            settings_json,
+            serde_json::json!({"max_num_concurrent_split_searches": 4})
+        );
+
+        // The log level cannot be changed without the corresponding hook.
+        let resp = warp::test::request()
+            .method("PUT")
+            .path("/admin/settings")
+            .json(&serde_json::json!({"log_level": "debug"}))
+            .reply(&admin_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+
+        let resp = warp::test::request()
+            .method("PUT")
+            .path("/admin/settings")
+            .json(&serde_json::json!({"unknown_setting": 1}))
+            .reply(&admin_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_admin_log_level_handler() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let searcher_context = Arc::new(SearcherContext::new(SearcherConfig::default()));
+        let runtime_hooks = RuntimeHooks {
+            set_log_filter_opt: Some(Arc::new(|_| Ok(()))),
+            current_log_filter_opt: Some(Arc::new(|| Some("quickwit=info".to_string()))),
+            ..Default::default()
+        };
+        let runtime_settings_service = RuntimeSettingsService::load(
+            data_dir.path(),
+            100,
+            searcher_context,
+            None,
+            runtime_hooks,
+        )
+        .await
+        .unwrap();
+        let admin_handler =
+            admin_settings_handler(Arc::new(runtime_settings_service)).recover(recover_fn);
+
+        let resp = warp::test::request()
+            .path("/admin/log-level")
+            .reply(&admin_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let log_level_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            log_level_json,
+            serde_json::json!({"filter": "quickwit=info"})
+        );
+
+        let resp = warp::test::request()
+            .method("PUT")
+            .path("/admin/log-level")
+            .json(&serde_json::json!({"filter": "quickwit=debug"}))
+            .reply(&admin_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let resp = warp::test::request()
+            .path("/admin/settings")
+            .reply(&admin_handler)
+            .await;
+        let settings_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            settings_json,
+            serde_json::json!({"log_level": "quickwit=debug"})
+        );
+    }
+}
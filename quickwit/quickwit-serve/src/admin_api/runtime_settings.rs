@@ -0,0 +1,533 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::Context;
+use byte_unit::Byte;
+use quickwit_actors::Mailbox;
+use quickwit_indexing::actors::IndexingService;
+use quickwit_indexing::models::SetMergeThroughputLimit;
+use quickwit_proto::{ServiceError, ServiceErrorCode};
+use quickwit_search::SearcherContext;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+/// Name of the file, in the data directory, where the runtime settings are persisted.
+const RUNTIME_SETTINGS_FILE_NAME: &str = "runtime_settings.json";
+
+/// Settings of a node that can be changed at runtime through the admin API. Unset settings take
+/// their value from the node config.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct RuntimeSettings {
+    /// Merge write throughput limit of all the merge pipelines of the node, overriding the one of
+    /// the index configs.
+    #[schema(value_type = String)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_merge_write_throughput: Option<Byte>,
+    /// Maximum number of splits searched concurrently by the node.
+    #[schema(value_type = usize)]
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_num_concurrent_split_searches: Option<NonZeroUsize>,
+    /// Filter of the log events, written with the `RUST_LOG` syntax, for instance
+    /// `quickwit=debug,tantivy=info`.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_level: Option<String>,
+    /// Whether to log a warning when a task blocks a runtime thread for too long.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub busy_detector_enabled: Option<bool>,
+}
+
+/// Filter of the log events of a node, changed at runtime through the admin API.
+#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(deny_unknown_fields)]
+pub struct LogLevel {
+    /// Filter of the log events, written with the `RUST_LOG` syntax, for instance
+    /// `quickwit=debug,tantivy=info`. Updating it to `null` restores the filter set on startup.
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+type LogFilterHook = dyn Fn(Option<&str>) -> anyhow::Result<()> + Send + Sync;
+type CurrentLogFilterHook = dyn Fn() -> Option<String> + Send + Sync;
+type BusyDetectorHook = dyn Fn(bool) + Send + Sync;
+
+/// Hooks changing the settings owned by the binary running the node rather than by its services.
+#[derive(Clone, Default)]
+pub struct RuntimeHooks {
+    /// Replaces the filter of the log events. `None` restores the filter set on startup.
+    pub set_log_filter_opt: Option<Arc<LogFilterHook>>,
+    /// Returns the filter of the log events currently applied.
+    pub current_log_filter_opt: Option<Arc<CurrentLogFilterHook>>,
+    /// Enables or disables the busy detector. It is enabled by default.
+    pub set_busy_detector_enabled_opt: Option<Arc<BusyDetectorHook>>,
+}
+
+impl fmt::Debug for RuntimeHooks {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("RuntimeHooks")
+            .field("set_log_filter", &self.set_log_filter_opt.is_some())
+            .field("current_log_filter", &self.current_log_filter_opt.is_some())
+            .field(
+                "set_busy_detector_enabled",
+                &self.set_busy_detector_enabled_opt.is_some(),
+            )
+            .finish()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum RuntimeSettingsError {
+    #[error("Invalid runtime settings: {0}.")]
+    InvalidSettings(String),
+    #[error("Failed to persist runtime settings: {0}.")]
+    Internal(String),
+}
+
+impl ServiceError for RuntimeSettingsError {
+    fn status_code(&self) -> ServiceErrorCode {
+        match self {
+            RuntimeSettingsError::InvalidSettings(_) => ServiceErrorCode::BadRequest,
+            RuntimeSettingsError::Internal(_) => ServiceErrorCode::Internal,
+        }
+    }
+}
+
+/// Applies the runtime settings of the node and persists them in its data directory, so that
+/// they survive restarts.
+pub(crate) struct RuntimeSettingsService {
+    settings_path: PathBuf,
+    default_max_num_concurrent_split_searches: usize,
+    searcher_context: Arc<SearcherContext>,
+    indexing_service_opt: Option<Mailbox<IndexingService>>,
+    hooks: RuntimeHooks,
+    settings: Mutex<RuntimeSettings>,
+    /// Merge write throughput limit set in the cluster settings, applied when the node does not
+    /// override it.
+    cluster_max_merge_write_throughput: Mutex<Option<Byte>>,
+}
+
+impl RuntimeSettingsService {
+    /// Loads the settings persisted in the data directory, if any, and applies them.
+    pub async fn load(
+        data_dir_path: &Path,
+        default_max_num_concurrent_split_searches: usize,
+        searcher_context: Arc<SearcherContext>,
+        indexing_service_opt: Option<Mailbox<IndexingService>>,
+        hooks: RuntimeHooks,
+    ) -> anyhow::Result<Self> {
+        let settings_path = data_dir_path.join(RUNTIME_SETTINGS_FILE_NAME);
+        let runtime_settings_service = Self {
+            settings_path,
+            default_max_num_concurrent_split_searches,
+            searcher_context,
+            indexing_service_opt,
+            hooks,
+            settings: Mutex::default(),
+            cluster_max_merge_write_throughput: Mutex::default(),
+        };
+        let settings_json = match tokio::fs::read(&runtime_settings_service.settings_path).await {
+            Ok(settings_json) => settings_json,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(runtime_settings_service);
+            }
+            Err(error) => {
+                return Err(error).with_context(|| {
+                    format!(
+                        "Failed to read runtime settings file `{}`.",
+                        runtime_settings_service.settings_path.display()
+                    )
+                });
+            }
+        };
+        let settings: RuntimeSettings = match serde_json::from_slice(&settings_json) {
+            Ok(settings) => settings,
+            Err(error) => {
+                warn!(error=%error, "Failed to parse persisted runtime settings, ignoring them.");
+                return Ok(runtime_settings_service);
+            }
+        };
+        info!(settings=?settings, "Applying persisted runtime settings.");
+
+        if let Err(error) = runtime_settings_service.apply(&settings).await {
+            warn!(error=%error, "Failed to apply persisted runtime settings.");
+        }
+        *runtime_settings_service.settings.lock().await = settings;
+        Ok(runtime_settings_service)
+    }
+
+    pub async fn settings(&self) -> RuntimeSettings {
+        self.settings.lock().await.clone()
+    }
+
+    /// Replaces the runtime settings of the node. The settings left unset are restored to their
+    /// value from the node config.
+    pub async fn update(
+        &self,
+        new_settings: RuntimeSettings,
+    ) -> Result<RuntimeSettings, RuntimeSettingsError> {
+        let mut settings = self.settings.lock().await;
+        self.replace(&mut settings, new_settings).await
+    }
+
+    /// Returns the filter of the log events currently applied on the node.
+    pub fn log_level(&self) -> LogLevel {
+        let filter = self
+            .hooks
+            .current_log_filter_opt
+            .as_ref()
+            .and_then(|current_log_filter| current_log_filter());
+        LogLevel { filter }
+    }
+
+    /// Replaces the filter of the log events of the node and persists it like the other runtime
+    /// settings, which are left unchanged.
+    pub async fn update_log_level(
+        &self,
+        log_level: LogLevel,
+    ) -> Result<LogLevel, RuntimeSettingsError> {
+        let mut settings = self.settings.lock().await;
+        let new_settings = RuntimeSettings {
+            log_level: log_level.filter,
+            ..settings.clone()
+        };
+        self.replace(&mut settings, new_settings).await?;
+        Ok(self.log_level())
+    }
+
+    /// Sets the merge write throughput limit of the cluster settings. It only applies when the
+    /// runtime settings of the node do not override it.
+    pub async fn set_cluster_max_merge_write_throughput(
+        &self,
+        max_merge_write_throughput_opt: Option<Byte>,
+    ) {
+        let settings = self.settings.lock().await;
+        {
+            let mut cluster_max_merge_write_throughput =
+                self.cluster_max_merge_write_throughput.lock().await;
+
+            if *cluster_max_merge_write_throughput == max_merge_write_throughput_opt {
+                return;
+            }
+            *cluster_max_merge_write_throughput = max_merge_write_throughput_opt;
+        }
+        self.apply_max_merge_write_throughput(&settings).await;
+    }
+
+    async fn replace(
+        &self,
+        settings: &mut RuntimeSettings,
+        new_settings: RuntimeSettings,
+    ) -> Result<RuntimeSettings, RuntimeSettingsError> {
+        self.apply(&new_settings).await?;
+
+        let settings_json = serde_json::to_vec_pretty(&new_settings)
+            .map_err(|error| RuntimeSettingsError::Internal(error.to_string()))?;
+        // Write to a temporary file first so that a crash cannot leave a truncated file behind.
+        let tmp_settings_path = self.settings_path.with_extension("json.tmp");
+        tokio::fs::write(&tmp_settings_path, settings_json)
+            .await
+            .map_err(|error| RuntimeSettingsError::Internal(error.to_string()))?;
+        tokio::fs::rename(&tmp_settings_path, &self.settings_path)
+            .await
+            .map_err(|error| RuntimeSettingsError::Internal(error.to_string()))?;
+
+        info!(settings=?new_settings, "Updated runtime settings.");
+        *settings = new_settings.clone();
+        Ok(new_settings)
+    }
+
+    async fn apply(&self, settings: &RuntimeSettings) -> Result<(), RuntimeSettingsError> {
+        // The log filter is applied first: it is the only setting that can be rejected.
+        match (&self.hooks.set_log_filter_opt, &settings.log_level) {
+            (Some(set_log_filter), log_level_opt) => set_log_filter(log_level_opt.as_deref())
+                .map_err(|error| {
+                    RuntimeSettingsError::InvalidSettings(format!("log level: {error}"))
+                })?,
+            (None, Some(_)) => {
+                return Err(RuntimeSettingsError::InvalidSettings(
+                    "the log level cannot be changed on this node".to_string(),
+                ));
+            }
+            (None, None) => {}
+        }
+        match (
+            &self.hooks.set_busy_detector_enabled_opt,
+            settings.busy_detector_enabled,
+        ) {
+            (Some(set_busy_detector_enabled), busy_detector_enabled_opt) => {
+                set_busy_detector_enabled(busy_detector_enabled_opt.unwrap_or(true))
+            }
+            (None, Some(_)) => {
+                return Err(RuntimeSettingsError::InvalidSettings(
+                    "the busy detector cannot be toggled on this node".to_string(),
+                ));
+            }
+            (None, None) => {}
+        }
+        self.apply_max_merge_write_throughput(settings).await;
+
+        let max_num_concurrent_split_searches = settings
+            .max_num_concurrent_split_searches
+            .map(NonZeroUsize::get)
+            .unwrap_or(self.default_max_num_concurrent_split_searches);
+        self.searcher_context
+            .set_max_num_concurrent_split_searches(max_num_concurrent_split_searches)
+            .await;
+        Ok(())
+    }
+
+    async fn apply_max_merge_write_throughput(&self, settings: &RuntimeSettings) {
+        let Some(indexing_service) = &self.indexing_service_opt else {
+            return;
+        };
+        let max_merge_write_throughput_opt = settings
+            .max_merge_write_throughput
+            .or(*self.cluster_max_merge_write_throughput.lock().await);
+        let set_merge_throughput_limit = SetMergeThroughputLimit {
+            max_merge_write_throughput_opt,
+        };
+        let _ = indexing_service.ask(set_merge_throughput_limit).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Mutex as SyncMutex;
+
+    use quickwit_config::SearcherConfig;
+
+    use super::*;
+
+    struct TestHooks {
+        log_filter: Arc<SyncMutex<Option<String>>>,
+        busy_detector_enabled: Arc<AtomicBool>,
+        hooks: RuntimeHooks,
+    }
+
+    fn test_hooks() -> TestHooks {
+        let log_filter = Arc::new(SyncMutex::new(None));
+        let busy_detector_enabled = Arc::new(AtomicBool::new(true));
+        let log_filter_clone = log_filter.clone();
+        let current_log_filter_clone = log_filter.clone();
+        let busy_detector_enabled_clone = busy_detector_enabled.clone();
+        let hooks = RuntimeHooks {
+            set_log_filter_opt: Some(Arc::new(move |log_filter_opt: Option<&str>| {
+                if log_filter_opt == Some("invalid") {
+                    anyhow::bail!("invalid filter");
+                }
+                *log_filter_clone.lock().unwrap() = log_filter_opt.map(str::to_string);
+                Ok(())
+            })),
+            current_log_filter_opt: Some(Arc::new(move || {
+                let log_filter_opt = current_log_filter_clone.lock().unwrap().clone();
+                Some(log_filter_opt.unwrap_or_else(|| "quickwit=info".to_string()))
+            })),
+            set_busy_detector_enabled_opt: Some(Arc::new(move |enabled| {
+                busy_detector_enabled_clone.store(enabled, Ordering::Relaxed);
+            })),
+        };
+        TestHooks {
+            log_filter,
+            busy_detector_enabled,
+            hooks,
+        }
+    }
+
+    fn searcher_context() -> Arc<SearcherContext> {
+        let searcher_config = SearcherConfig {
+            max_num_concurrent_split_searches: 10,
+            ..Default::default()
+        };
+        Arc::new(SearcherContext::new(searcher_config))
+    }
+
+    #[tokio::test]
+    async fn test_runtime_settings_service() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let test_hooks = test_hooks();
+        let searcher_context = searcher_context();
+        let runtime_settings_service = RuntimeSettingsService::load(
+            data_dir.path(),
+            10,
+            searcher_context.clone(),
+            None,
+            test_hooks.hooks.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            runtime_settings_service.settings().await,
+            RuntimeSettings::default()
+        );
+        let settings = RuntimeSettings {
+            max_merge_write_throughput: Some(Byte::from_bytes(10_000_000)),
+            max_num_concurrent_split_searches: Some(NonZeroUsize::new(4).unwrap()),
+            log_level: Some("quickwit=debug".to_string()),
+            busy_detector_enabled: Some(false),
+        };
+        runtime_settings_service
+            .update(settings.clone())
+            .await
+            .unwrap();
+        assert_eq!(
+            searcher_context.max_num_concurrent_split_searches().await,
+            4
+        );
+        assert_eq!(
+            test_hooks.log_filter.lock().unwrap().as_deref(),
+            Some("quickwit=debug")
+        );
+        assert!(!test_hooks.busy_detector_enabled.load(Ordering::Relaxed));
+
+        let error = runtime_settings_service
+            .update(RuntimeSettings {
+                log_level: Some("invalid".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, RuntimeSettingsError::InvalidSettings(_)));
+        assert_eq!(runtime_settings_service.settings().await, settings);
+
+        // The settings are persisted and applied on startup.
+        let searcher_context = searcher_context();
+        let runtime_settings_service = RuntimeSettingsService::load(
+            data_dir.path(),
+            10,
+            searcher_context.clone(),
+            None,
+            test_hooks.hooks.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(runtime_settings_service.settings().await, settings);
+        assert_eq!(
+            searcher_context.max_num_concurrent_split_searches().await,
+            4
+        );
+
+        // Unset settings are restored to their default value.
+        runtime_settings_service
+            .update(RuntimeSettings::default())
+            .await
+            .unwrap();
+        assert_eq!(
+            searcher_context.max_num_concurrent_split_searches().await,
+            10
+        );
+        assert!(test_hooks.log_filter.lock().unwrap().is_none());
+        assert!(test_hooks.busy_detector_enabled.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn test_runtime_settings_service_update_log_level() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let test_hooks = test_hooks();
+        let searcher_context = searcher_context();
+        let runtime_settings_service = RuntimeSettingsService::load(
+            data_dir.path(),
+            10,
+            searcher_context.clone(),
+            None,
+            test_hooks.hooks.clone(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            runtime_settings_service.log_level().filter.as_deref(),
+            Some("quickwit=info")
+        );
+        runtime_settings_service
+            .update(RuntimeSettings {
+                max_num_concurrent_split_searches: Some(NonZeroUsize::new(4).unwrap()),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+
+        let log_level = runtime_settings_service
+            .update_log_level(LogLevel {
+                filter: Some("quickwit=debug".to_string()),
+            })
+            .await
+            .unwrap();
+        assert_eq!(log_level.filter.as_deref(), Some("quickwit=debug"));
+        // The other runtime settings are left unchanged.
+        assert_eq!(
+            runtime_settings_service.settings().await,
+            RuntimeSettings {
+                max_num_concurrent_split_searches: Some(NonZeroUsize::new(4).unwrap()),
+                log_level: Some("quickwit=debug".to_string()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            searcher_context.max_num_concurrent_split_searches().await,
+            4
+        );
+
+        let log_level = runtime_settings_service
+            .update_log_level(LogLevel::default())
+            .await
+            .unwrap();
+        assert_eq!(log_level.filter.as_deref(), Some("quickwit=info"));
+        assert!(runtime_settings_service
+            .settings()
+            .await
+            .log_level
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_runtime_settings_service_without_hooks() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let runtime_settings_service = RuntimeSettingsService::load(
+            data_dir.path(),
+            10,
+            searcher_context(),
+            None,
+            RuntimeHooks::default(),
+        )
+        .await
+        .unwrap();
+        let error = runtime_settings_service
+            .update(RuntimeSettings {
+                log_level: Some("quickwit=debug".to_string()),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Invalid runtime settings: the log level cannot be changed on this node."
+        );
+    }
+}
@@ -0,0 +1,369 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use anyhow::Context;
+use quickwit_common::uri::Uri;
+use quickwit_config::{load_index_config_from_user_config, AuditLogConfig, AuthRole, ConfigFormat};
+use quickwit_core::{IndexService, IndexServiceError};
+use quickwit_ingest::{
+    CommitType, DocBatchBuilder, IngestRequest, IngestService, IngestServiceClient,
+};
+use quickwit_metastore::MetastoreError;
+use serde::Serialize;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::mpsc;
+use tracing::{error, info};
+
+/// Config of the index receiving the audit records when `audit_log.index_id` is set.
+pub(crate) const AUDIT_LOG_INDEX_CONFIG: &str = r#"
+version: 0.6
+
+index_id: audit-log-v0
+
+doc_mapping:
+  mode: strict
+  field_mappings:
+    - name: timestamp
+      type: datetime
+      input_formats: [unix_timestamp]
+      fast: true
+      precision: milliseconds
+    - name: protocol
+      type: text
+      tokenizer: raw
+    - name: principal
+      type: text
+      tokenizer: raw
+      fast: true
+    - name: action
+      type: text
+      tokenizer: raw
+      fast: true
+    - name: index_ids
+      type: array<text>
+      tokenizer: raw
+      fast: true
+    - name: method
+      type: text
+      tokenizer: raw
+    - name: path
+      type: text
+      tokenizer: raw
+    - name: query
+      type: text
+    - name: status
+      type: u64
+      fast: true
+    - name: grpc_status
+      type: u64
+      fast: true
+    - name: elapsed_time_micros
+      type: u64
+      fast: true
+
+  timestamp_field: timestamp
+
+indexing_settings:
+  commit_timeout_secs: 10
+
+search_settings:
+  default_search_fields: [principal, index_ids, path, query]
+"#;
+
+/// Capacity of the queue of audit records waiting to be written. Records are dropped when the
+/// queue is full rather than slowing down the requests.
+const AUDIT_RECORD_QUEUE_CAPACITY: usize = 10_000;
+
+/// Maximum number of audit records written at once.
+const MAX_AUDIT_BATCH_SIZE: usize = 1_000;
+
+/// Delay after which a partial batch of audit records is written.
+const AUDIT_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Record of a search, ingest, or admin request, written to the audit log once the request has
+/// been answered.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub(crate) struct AuditRecord {
+    /// UTC Unix timestamp (in milliseconds) at which the request was received.
+    pub timestamp: i64,
+    /// `rest` or `grpc`.
+    pub protocol: &'static str,
+    /// Authenticated principal, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub principal: Option<String>,
+    pub action: AuthRole,
+    /// Indexes or index ID patterns targeted by the request, `*` standing for all the indexes.
+    /// Empty for the requests naming their indexes in their body.
+    pub index_ids: Vec<String>,
+    pub method: String,
+    pub path: String,
+    /// Query string or body of search requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub query: Option<String>,
+    /// HTTP status code of the response.
+    pub status: u16,
+    /// gRPC status code of the response, for the gRPC requests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grpc_status: Option<u32>,
+    pub elapsed_time_micros: u64,
+}
+
+/// Queues the audit records to be written to the file and index configured in the
+/// [`AuditLogConfig`].
+#[derive(Clone)]
+pub(crate) struct AuditLogger {
+    record_tx: mpsc::Sender<AuditRecord>,
+    record_queries: bool,
+}
+
+impl AuditLogger {
+    /// Opens the audit log file and creates the audit log index, then starts the task writing
+    /// the audit records. Returns `None` when the audit log is disabled.
+    pub async fn start(
+        audit_log_config: &AuditLogConfig,
+        index_service: &IndexService,
+        default_index_root_uri: &Uri,
+        ingest_service: IngestServiceClient,
+    ) -> anyhow::Result<Option<Self>> {
+        if !audit_log_config.is_enabled() {
+            return Ok(None);
+        }
+        let file_opt = if let Some(file_path) = &audit_log_config.file_path {
+            Some(open_audit_log_file(file_path).await?)
+        } else {
+            None
+        };
+        let index_sink_opt = if let Some(index_id) = &audit_log_config.index_id {
+            create_audit_log_index(index_service, default_index_root_uri, index_id).await?;
+            Some((index_id.clone(), ingest_service))
+        } else {
+            None
+        };
+        info!(
+            file_path=?audit_log_config.file_path,
+            index_id=?audit_log_config.index_id,
+            "Audit log is enabled."
+        );
+        let (record_tx, record_rx) = mpsc::channel(AUDIT_RECORD_QUEUE_CAPACITY);
+        let writer = AuditLogWriter {
+            file_opt,
+            index_sink_opt,
+        };
+        tokio::spawn(writer.run(record_rx));
+
+        Ok(Some(Self {
+            record_tx,
+            record_queries: audit_log_config.record_queries,
+        }))
+    }
+
+    /// Whether the audit records of search requests contain their query.
+    pub fn record_queries(&self) -> bool {
+        self.record_queries
+    }
+
+    /// Queues a record for writing. The record is dropped if the queue is full, so that the
+    /// audit log never holds requests back.
+    pub fn log(&self, record: AuditRecord) {
+        if self.record_tx.try_send(record).is_err() {
+            crate::SERVE_METRICS.audit_records_dropped_total.inc();
+        }
+    }
+
+    #[cfg(test)]
+    pub fn for_test(record_queries: bool) -> (Self, mpsc::Receiver<AuditRecord>) {
+        let (record_tx, record_rx) = mpsc::channel(AUDIT_RECORD_QUEUE_CAPACITY);
+        let audit_logger = Self {
+            record_tx,
+            record_queries,
+        };
+        (audit_logger, record_rx)
+    }
+}
+
+/// Returns the current UTC Unix timestamp in milliseconds.
+pub(crate) fn unix_timestamp_millis_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or_default()
+}
+
+async fn open_audit_log_file(file_path: &Path) -> anyhow::Result<File> {
+    if let Some(parent_dir) = file_path.parent() {
+        tokio::fs::create_dir_all(parent_dir)
+            .await
+            .with_context(|| {
+                format!("Failed to create the directory of the audit log file `{file_path:?}`.")
+            })?;
+    }
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(file_path)
+        .await
+        .with_context(|| format!("Failed to open audit log file `{file_path:?}`."))
+}
+
+async fn create_audit_log_index(
+    index_service: &IndexService,
+    default_index_root_uri: &Uri,
+    index_id: &str,
+) -> anyhow::Result<()> {
+    let mut index_config = load_index_config_from_user_config(
+        ConfigFormat::Yaml,
+        AUDIT_LOG_INDEX_CONFIG.as_bytes(),
+        default_index_root_uri,
+    )?;
+    if index_config.index_id != index_id {
+        index_config.index_uri = default_index_root_uri.join(index_id)?;
+        index_config.index_id = index_id.to_string();
+    }
+    match index_service.create_index(index_config, false).await {
+        Ok(_)
+        | Err(IndexServiceError::MetastoreError(MetastoreError::IndexAlreadyExists { .. })) => {
+            Ok(())
+        }
+        Err(error) => Err(error.into()),
+    }
+}
+
+struct AuditLogWriter {
+    file_opt: Option<File>,
+    index_sink_opt: Option<(String, IngestServiceClient)>,
+}
+
+impl AuditLogWriter {
+    async fn run(mut self, mut record_rx: mpsc::Receiver<AuditRecord>) {
+        let mut batch = Vec::with_capacity(MAX_AUDIT_BATCH_SIZE);
+
+        while let Some(record) = record_rx.recv().await {
+            batch.push(record);
+            let flush_deadline = tokio::time::Instant::now() + AUDIT_FLUSH_INTERVAL;
+
+            while batch.len() < MAX_AUDIT_BATCH_SIZE {
+                match tokio::time::timeout_at(flush_deadline, record_rx.recv()).await {
+                    Ok(Some(record)) => batch.push(record),
+                    Ok(None) | Err(_) => break,
+                }
+            }
+            self.write_batch(&batch).await;
+            batch.clear();
+        }
+    }
+
+    async fn write_batch(&mut self, batch: &[AuditRecord]) {
+        if let Some(file) = &mut self.file_opt {
+            let mut lines = Vec::new();
+
+            for record in batch {
+                serde_json::to_writer(&mut lines, record)
+                    .expect("Audit records should be JSON serializable.");
+                lines.push(b'\n');
+            }
+            let write_result = async {
+                file.write_all(&lines).await?;
+                file.flush().await
+            }
+            .await;
+            if let Err(error) = write_result {
+                error!(error=?error, "Failed to write audit records to file.");
+            }
+        }
+        if let Some((index_id, ingest_service)) = &mut self.index_sink_opt {
+            let mut doc_batch_builder = DocBatchBuilder::new(index_id.clone()).json_writer();
+
+            for record in batch {
+                doc_batch_builder
+                    .ingest_doc(record)
+                    .expect("Audit records should be JSON serializable.");
+            }
+            let ingest_request = IngestRequest {
+                doc_batches: vec![doc_batch_builder.build()],
+                commit: CommitType::Auto as u32,
+            };
+            if let Err(error) = ingest_service.ingest(ingest_request).await {
+                error!(index_id=%index_id, error=?error, "Failed to ingest audit records.");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn audit_record(principal: &str) -> AuditRecord {
+        AuditRecord {
+            timestamp: 1_686_000_000_000,
+            protocol: "rest",
+            principal: Some(principal.to_string()),
+            action: AuthRole::Search,
+            index_ids: vec!["my-index".to_string()],
+            method: "GET".to_string(),
+            path: "/api/v1/my-index/search".to_string(),
+            query: Some("query=error".to_string()),
+            status: 200,
+            grpc_status: None,
+            elapsed_time_micros: 1_500,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_audit_log_writer_file() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let file_path = temp_dir.path().join("audit").join("audit.log");
+        let file = open_audit_log_file(&file_path).await.unwrap();
+        let writer = AuditLogWriter {
+            file_opt: Some(file),
+            index_sink_opt: None,
+        };
+        let (record_tx, record_rx) = mpsc::channel(10);
+        let writer_handle = tokio::spawn(writer.run(record_rx));
+
+        record_tx.send(audit_record("alice")).await.unwrap();
+        record_tx.send(audit_record("bob")).await.unwrap();
+        drop(record_tx);
+        writer_handle.await.unwrap();
+
+        let content = tokio::fs::read_to_string(&file_path).await.unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0],
+            r#"{"timestamp":1686000000000,"protocol":"rest","principal":"alice","action":"search","index_ids":["my-index"],"method":"GET","path":"/api/v1/my-index/search","query":"query=error","status":200,"elapsed_time_micros":1500}"#
+        );
+        assert!(lines[1].contains(r#""principal":"bob""#));
+    }
+
+    #[test]
+    fn test_audit_log_index_config() {
+        let index_config = load_index_config_from_user_config(
+            ConfigFormat::Yaml,
+            AUDIT_LOG_INDEX_CONFIG.as_bytes(),
+            &Uri::for_test("ram:///indexes"),
+        )
+        .unwrap();
+        assert_eq!(index_config.index_id, "audit-log-v0");
+    }
+}
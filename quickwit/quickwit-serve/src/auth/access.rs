@@ -0,0 +1,398 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use hyper::{HeaderMap, Method};
+use quickwit_config::{AuthRole, IndexerConfig};
+use quickwit_opentelemetry::otlp::{OTEL_METRICS_INDEX_ID, OTEL_TRACE_INDEX_ID, OTLP_INDEX_HEADER};
+
+use super::Access;
+
+/// Tells the access required by REST and gRPC requests from their method, path, and headers.
+#[derive(Clone, Debug)]
+pub(crate) struct AccessPolicy {
+    otlp_logs_index_id: String,
+    otlp_trace_index_id: String,
+    otlp_tenant_routing: bool,
+}
+
+impl AccessPolicy {
+    pub fn new(indexer_config: &IndexerConfig) -> Self {
+        Self {
+            otlp_logs_index_id: indexer_config.otlp_logs_index_id.clone(),
+            otlp_trace_index_id: indexer_config.otlp_trace_index_id.clone(),
+            otlp_tenant_routing: indexer_config.otlp_tenant_attribute.is_some(),
+        }
+    }
+
+    /// Returns the access required by a REST request. Health checks, metrics, UI, API docs, and
+    /// version remain reachable without credentials.
+    pub fn rest_access(&self, method: &Method, path: &str, headers: &HeaderMap) -> Access {
+        let segments: Vec<&str> = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+
+        match segments.as_slice() {
+            [] | ["health", ..] | ["metrics"] | ["openapi.json"] | ["ui", ..] => Access::Public,
+            ["api", "v1", "version"] => Access::Public,
+            ["api", "v1", api_v1_segments @ ..] => self.api_v1_access(method, api_v1_segments),
+            ["v1", "logs"] => self.otlp_access(&self.otlp_logs_index_id, headers),
+            ["v1", "traces"] => self.otlp_access(&self.otlp_trace_index_id, headers),
+            ["zipkin", "api", "v2", "spans"] if *method == Method::POST => {
+                Access::Indexes(AuthRole::Ingest, vec![self.otlp_trace_index_id.clone()])
+            }
+            ["zipkin", ..] => {
+                Access::Indexes(AuthRole::Search, vec![OTEL_TRACE_INDEX_ID.to_string()])
+            }
+            _ => Access::Cluster(AuthRole::Admin),
+        }
+    }
+
+    fn api_v1_access(&self, method: &Method, segments: &[&str]) -> Access {
+        let index_access =
+            |role: AuthRole, index_id: &str| Access::Indexes(role, vec![index_id.to_string()]);
+
+        match segments {
+            // The indexes targeted by these requests are named in their body.
+            ["_elastic", "_msearch"] | ["sql"] => Access::Handler(AuthRole::Search),
+            ["_elastic", "_bulk"] | ["_elastic", _, "_bulk"] => Access::Handler(AuthRole::Ingest),
+            ["_elastic", index_id, ..] if !index_id.starts_with('_') => {
+                index_access(AuthRole::Search, index_id)
+            }
+            ["_elastic", ..] => Access::Cluster(AuthRole::Search),
+            ["traces", ..] => Access::Indexes(
+                AuthRole::Search,
+                vec![
+                    self.otlp_trace_index_id.clone(),
+                    self.otlp_logs_index_id.clone(),
+                ],
+            ),
+            // The list of indexes is filtered by the handler.
+            ["indexes"] if *method == Method::GET => Access::Handler(AuthRole::Admin),
+            ["indexes", index_id, ..] => index_access(AuthRole::Admin, index_id),
+            ["ui", "indexes"] if *method == Method::GET => Access::Handler(AuthRole::Admin),
+            ["ui", "indexes", index_id, ..] => index_access(AuthRole::Admin, index_id),
+            [index_id, "ingest"] => index_access(AuthRole::Ingest, index_id),
+            [index_id, "search", ..] | [index_id, "tail", ..] | [index_id, "prometheus", ..] => {
+                index_access(AuthRole::Search, index_id)
+            }
+            [index_id, "delete-tasks", ..] => index_access(AuthRole::Admin, index_id),
+            _ => Access::Cluster(AuthRole::Admin),
+        }
+    }
+
+    /// Whether the handler of a REST request restricts the documents it searches or deletes to the
+    /// tenant of the principal: search, search stream, Elasticsearch-compatible search, and delete
+    /// task creation requests.
+    pub fn rest_filters_tenant(&self, method: &Method, path: &str) -> bool {
+        let segments: Vec<&str> = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+
+        match segments.as_slice() {
+            ["api", "v1", "traces", ..] => false,
+            ["api", "v1", "_elastic", "_msearch"] => true,
+            ["api", "v1", "_elastic", index_id, "_search"] => !index_id.starts_with('_'),
+            ["api", "v1", _, "search"]
+            | ["api", "v1", _, "search", "stream"]
+            | ["api", "v1", _, "search", "stream", "sse"] => true,
+            ["api", "v1", _, "delete-tasks"] => *method == Method::POST,
+            _ => false,
+        }
+    }
+
+    /// Returns the access required by a gRPC request from its path, `/{service}/{method}`. The
+    /// internal services, used for the communication between nodes, require the cluster key, and
+    /// the services unknown to the node are never reachable.
+    pub fn grpc_access(&self, path: &str, headers: &HeaderMap) -> Access {
+        let service = path.trim_start_matches('/').split('/').next().unwrap_or("");
+
+        match service {
+            "opentelemetry.proto.collector.logs.v1.LogsService" => {
+                self.otlp_access(&self.otlp_logs_index_id, headers)
+            }
+            "opentelemetry.proto.collector.trace.v1.TraceService" => {
+                self.otlp_access(&self.otlp_trace_index_id, headers)
+            }
+            "opentelemetry.proto.collector.metrics.v1.MetricsService" => {
+                Access::Indexes(AuthRole::Ingest, vec![OTEL_METRICS_INDEX_ID.to_string()])
+            }
+            "jaeger.storage.v1.SpanReaderPlugin" | "jaeger.storage.v1.DependenciesReaderPlugin" => {
+                Access::Indexes(AuthRole::Search, vec![OTEL_TRACE_INDEX_ID.to_string()])
+            }
+            "grpc.health.v1.Health" => Access::Public,
+            "grpc.reflection.v1alpha.ServerReflection" => Access::Cluster(AuthRole::Admin),
+            "quickwit_cluster_api.ClusterApiService"
+            | "quickwit_metastore_api.MetastoreApiService"
+            | "control_plane_service.ControlPlaneService"
+            | "quickwit_indexing_api.IndexingService"
+            | "ingest_service.IngestService"
+            | "quickwit.SearchService" => Access::Internal,
+            _ => Access::Denied,
+        }
+    }
+
+    /// OTLP export requests may be routed to the index set by their `x-qw-index` header or to
+    /// the per-tenant indexes derived from the default one.
+    fn otlp_access(&self, default_index_id: &str, headers: &HeaderMap) -> Access {
+        let header_index_id_opt = headers
+            .get(OTLP_INDEX_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|index_id| !index_id.is_empty());
+        let index_id = if let Some(header_index_id) = header_index_id_opt {
+            header_index_id.to_string()
+        } else if self.otlp_tenant_routing {
+            format!("{default_index_id}*")
+        } else {
+            default_index_id.to_string()
+        };
+        Access::Indexes(AuthRole::Ingest, vec![index_id])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use hyper::header::HeaderValue;
+
+    use super::*;
+
+    fn index_access(role: AuthRole, index_id: &str) -> Access {
+        Access::Indexes(role, vec![index_id.to_string()])
+    }
+
+    #[test]
+    fn test_rest_access() {
+        let access_policy = AccessPolicy::new(&IndexerConfig::default());
+        let headers = HeaderMap::new();
+
+        for (method, path, expected_access) in [
+            (Method::GET, "/", Access::Public),
+            (Method::GET, "/health/readyz", Access::Public),
+            (Method::GET, "/metrics", Access::Public),
+            (Method::GET, "/ui/search", Access::Public),
+            (Method::GET, "/openapi.json", Access::Public),
+            (Method::GET, "/api/v1/version", Access::Public),
+            (
+                Method::GET,
+                "/api/v1/my-index/search",
+                index_access(AuthRole::Search, "my-index"),
+            ),
+            (
+                Method::POST,
+                "/api/v1/my-index/search/stream",
+                index_access(AuthRole::Search, "my-index"),
+            ),
+            (
+                Method::POST,
+                "/api/v1/my-index/ingest",
+                index_access(AuthRole::Ingest, "my-index"),
+            ),
+            (
+                Method::POST,
+                "/api/v1/_elastic/_bulk",
+                Access::Handler(AuthRole::Ingest),
+            ),
+            (
+                Method::POST,
+                "/api/v1/_elastic/my-index/_bulk",
+                Access::Handler(AuthRole::Ingest),
+            ),
+            (
+                Method::POST,
+                "/api/v1/_elastic/_msearch",
+                Access::Handler(AuthRole::Search),
+            ),
+            (
+                Method::POST,
+                "/api/v1/_elastic/logs-*/_search",
+                index_access(AuthRole::Search, "logs-*"),
+            ),
+            (
+                Method::GET,
+                "/api/v1/_elastic/_field_caps",
+                Access::Cluster(AuthRole::Search),
+            ),
+            (
+                Method::POST,
+                "/api/v1/sql",
+                Access::Handler(AuthRole::Search),
+            ),
+            (
+                Method::GET,
+                "/api/v1/my-index/tail/stream",
+                index_access(AuthRole::Search, "my-index"),
+            ),
+            (
+                Method::GET,
+                "/api/v1/indexes",
+                Access::Handler(AuthRole::Admin),
+            ),
+            (
+                Method::POST,
+                "/api/v1/indexes",
+                Access::Cluster(AuthRole::Admin),
+            ),
+            (
+                Method::DELETE,
+                "/api/v1/indexes/my-index",
+                index_access(AuthRole::Admin, "my-index"),
+            ),
+            (
+                Method::GET,
+                "/api/v1/ui/indexes",
+                Access::Handler(AuthRole::Admin),
+            ),
+            (
+                Method::GET,
+                "/api/v1/ui/indexes/my-index/split-timeline",
+                index_access(AuthRole::Admin, "my-index"),
+            ),
+            (
+                Method::GET,
+                "/api/v1/my-index/delete-tasks",
+                index_access(AuthRole::Admin, "my-index"),
+            ),
+            (
+                Method::GET,
+                "/api/v1/cluster",
+                Access::Cluster(AuthRole::Admin),
+            ),
+            (
+                Method::GET,
+                "/api/v1/config",
+                Access::Cluster(AuthRole::Admin),
+            ),
+            (
+                Method::POST,
+                "/v1/logs",
+                index_access(AuthRole::Ingest, "otel-logs-v0"),
+            ),
+            (
+                Method::POST,
+                "/zipkin/api/v2/spans",
+                index_access(AuthRole::Ingest, "otel-trace-v0"),
+            ),
+            (
+                Method::GET,
+                "/zipkin/api/v2/services",
+                index_access(AuthRole::Search, "otel-trace-v0"),
+            ),
+        ] {
+            assert_eq!(
+                access_policy.rest_access(&method, path, &headers),
+                expected_access,
+                "{method} {path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_rest_filters_tenant() {
+        let access_policy = AccessPolicy::new(&IndexerConfig::default());
+
+        for (method, path, expected_filters_tenant) in [
+            (Method::GET, "/api/v1/my-index/search", true),
+            (Method::POST, "/api/v1/my-index/search", true),
+            (Method::GET, "/api/v1/my-index/search/stream", true),
+            (Method::GET, "/api/v1/my-index/search/stream/sse", true),
+            (Method::POST, "/api/v1/_elastic/my-index/_search", true),
+            (Method::POST, "/api/v1/_elastic/_msearch", true),
+            (Method::POST, "/api/v1/my-index/delete-tasks", true),
+            (Method::GET, "/api/v1/my-index/delete-tasks", false),
+            (Method::GET, "/api/v1/_elastic/_search", false),
+            (Method::GET, "/api/v1/my-index/tail/stream", false),
+            (Method::POST, "/api/v1/sql", false),
+            (Method::GET, "/api/v1/traces/search", false),
+            (Method::DELETE, "/api/v1/indexes/my-index", false),
+        ] {
+            assert_eq!(
+                access_policy.rest_filters_tenant(&method, path),
+                expected_filters_tenant,
+                "{method} {path}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_otlp_access() {
+        let mut indexer_config = IndexerConfig::default();
+        indexer_config.otlp_tenant_attribute = Some("tenant".to_string());
+        let access_policy = AccessPolicy::new(&indexer_config);
+
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            access_policy.rest_access(&Method::POST, "/v1/traces", &headers),
+            index_access(AuthRole::Ingest, "otel-trace-v0*")
+        );
+        headers.insert(OTLP_INDEX_HEADER, HeaderValue::from_static("team-a-logs"));
+        assert_eq!(
+            access_policy.rest_access(&Method::POST, "/v1/logs", &headers),
+            index_access(AuthRole::Ingest, "team-a-logs")
+        );
+        assert_eq!(
+            access_policy.grpc_access(
+                "/opentelemetry.proto.collector.logs.v1.LogsService/Export",
+                &headers
+            ),
+            index_access(AuthRole::Ingest, "team-a-logs")
+        );
+    }
+
+    #[test]
+    fn test_grpc_access() {
+        let access_policy = AccessPolicy::new(&IndexerConfig::default());
+        let headers = HeaderMap::new();
+
+        assert_eq!(
+            access_policy.grpc_access("/jaeger.storage.v1.SpanReaderPlugin/FindTraces", &headers),
+            index_access(AuthRole::Search, "otel-trace-v0")
+        );
+        assert_eq!(
+            access_policy.grpc_access(
+                "/opentelemetry.proto.collector.metrics.v1.MetricsService/Export",
+                &headers
+            ),
+            index_access(AuthRole::Ingest, "otel-metrics-v0")
+        );
+        assert_eq!(
+            access_policy.grpc_access("/quickwit.SearchService/LeafSearch", &headers),
+            Access::Internal
+        );
+        assert_eq!(
+            access_policy.grpc_access(
+                "/quickwit_metastore_api.MetastoreApiService/ListSplits",
+                &headers
+            ),
+            Access::Internal
+        );
+        assert_eq!(
+            access_policy.grpc_access("/grpc.health.v1.Health/Check", &headers),
+            Access::Public
+        );
+        assert_eq!(
+            access_policy.grpc_access("/jaeger.storage.v1.SpanWriterPlugin/WriteSpan", &headers),
+            Access::Denied
+        );
+        assert_eq!(
+            access_policy.grpc_access("/unknown", &headers),
+            Access::Denied
+        );
+    }
+}
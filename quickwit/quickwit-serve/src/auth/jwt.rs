@@ -0,0 +1,554 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use base64::prelude::{Engine, BASE64_URL_SAFE_NO_PAD};
+use quickwit_config::{AuthGrant, JwtConfig};
+use ring::hmac;
+use ring::signature::{
+    RsaPublicKeyComponents, UnparsedPublicKey, ECDSA_P256_SHA256_FIXED, RSA_PKCS1_2048_8192_SHA256,
+};
+use serde::Deserialize;
+use serde_json::Value as JsonValue;
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use super::{AuthError, Principal, TenantScope};
+
+/// Keys fetched from a JWKS endpoint are refreshed after this delay.
+const JWKS_MAX_AGE: Duration = Duration::from_secs(3600);
+
+/// Minimum delay between two fetches of the JWKS. Tokens signed with an unknown key ID trigger a
+/// refresh, which must not let clients hammer the identity provider.
+const JWKS_MIN_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+const JWKS_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Deserialize)]
+struct JwtHeader {
+    alg: String,
+    #[serde(default)]
+    kid: Option<String>,
+}
+
+/// A JSON Web Key, restricted to the parameters of RSA and P-256 public keys.
+#[derive(Clone, Debug, Deserialize)]
+pub(crate) struct Jwk {
+    kty: String,
+    #[serde(default)]
+    kid: Option<String>,
+    #[serde(default)]
+    crv: Option<String>,
+    #[serde(default)]
+    n: Option<String>,
+    #[serde(default)]
+    e: Option<String>,
+    #[serde(default)]
+    x: Option<String>,
+    #[serde(default)]
+    y: Option<String>,
+}
+
+impl Jwk {
+    fn supports(&self, alg: &str) -> bool {
+        match alg {
+            "RS256" => self.kty == "RSA",
+            "ES256" => self.kty == "EC" && self.crv.as_deref() == Some("P-256"),
+            _ => false,
+        }
+    }
+
+    fn verify(&self, alg: &str, message: &[u8], signature: &[u8]) -> bool {
+        match alg {
+            "RS256" => {
+                let (Some(n), Some(e)) = (decode_param(&self.n), decode_param(&self.e)) else {
+                    return false;
+                };
+                RsaPublicKeyComponents { n, e }
+                    .verify(&RSA_PKCS1_2048_8192_SHA256, message, signature)
+                    .is_ok()
+            }
+            "ES256" => {
+                let (Some(x), Some(y)) = (decode_param(&self.x), decode_param(&self.y)) else {
+                    return false;
+                };
+                // Uncompressed SEC1 encoding of the public point.
+                let mut public_key = Vec::with_capacity(1 + x.len() + y.len());
+                public_key.push(0x04);
+                public_key.extend_from_slice(&x);
+                public_key.extend_from_slice(&y);
+                UnparsedPublicKey::new(&ECDSA_P256_SHA256_FIXED, public_key)
+                    .verify(message, signature)
+                    .is_ok()
+            }
+            _ => false,
+        }
+    }
+}
+
+fn decode_param(param_opt: &Option<String>) -> Option<Vec<u8>> {
+    param_opt
+        .as_ref()
+        .and_then(|param| BASE64_URL_SAFE_NO_PAD.decode(param).ok())
+}
+
+#[derive(Deserialize)]
+struct JwkSet {
+    keys: Vec<Jwk>,
+}
+
+#[derive(Deserialize)]
+struct OpenIdConfiguration {
+    jwks_uri: String,
+}
+
+#[derive(Default)]
+struct CachedKeys {
+    keys: Arc<Vec<Jwk>>,
+    fetched_at_opt: Option<Instant>,
+    last_attempt_at_opt: Option<Instant>,
+}
+
+/// Signing keys of an identity provider, fetched lazily from its JWKS endpoint.
+struct JwksCache {
+    issuer: String,
+    jwks_uri_opt: Option<String>,
+    http_client: reqwest::Client,
+    cached_keys: Mutex<CachedKeys>,
+}
+
+impl JwksCache {
+    async fn keys(&self, kid_opt: Option<&str>) -> Arc<Vec<Jwk>> {
+        let mut cached_keys = self.cached_keys.lock().await;
+
+        let is_stale = cached_keys
+            .fetched_at_opt
+            .map(|fetched_at| fetched_at.elapsed() > JWKS_MAX_AGE)
+            .unwrap_or(true);
+        let is_kid_unknown = kid_opt
+            .map(|kid| {
+                !cached_keys
+                    .keys
+                    .iter()
+                    .any(|key| key.kid.as_deref() == Some(kid))
+            })
+            .unwrap_or(false);
+        let can_refresh = cached_keys
+            .last_attempt_at_opt
+            .map(|last_attempt_at| last_attempt_at.elapsed() >= JWKS_MIN_REFRESH_INTERVAL)
+            .unwrap_or(true);
+
+        if (is_stale || is_kid_unknown) && can_refresh {
+            cached_keys.last_attempt_at_opt = Some(Instant::now());
+
+            match self.fetch_keys().await {
+                Ok(keys) => {
+                    info!(issuer=%self.issuer, num_keys=keys.len(), "Fetched JWKS.");
+                    cached_keys.keys = Arc::new(keys);
+                    cached_keys.fetched_at_opt = Some(Instant::now());
+                }
+                Err(error) => {
+                    warn!(issuer=%self.issuer, error=?error, "Failed to fetch JWKS.");
+                }
+            }
+        }
+        cached_keys.keys.clone()
+    }
+
+    async fn fetch_keys(&self) -> anyhow::Result<Vec<Jwk>> {
+        let jwks_uri = if let Some(jwks_uri) = &self.jwks_uri_opt {
+            jwks_uri.clone()
+        } else {
+            let discovery_uri = format!(
+                "{}/.well-known/openid-configuration",
+                self.issuer.trim_end_matches('/')
+            );
+            self.http_client
+                .get(discovery_uri)
+                .send()
+                .await?
+                .error_for_status()?
+                .json::<OpenIdConfiguration>()
+                .await?
+                .jwks_uri
+        };
+        let jwk_set = self
+            .http_client
+            .get(jwks_uri)
+            .send()
+            .await?
+            .error_for_status()?
+            .json::<JwkSet>()
+            .await?;
+        Ok(jwk_set.keys)
+    }
+}
+
+enum SigningKeys {
+    Hmac(hmac::Key),
+    Jwks(JwksCache),
+}
+
+/// Validates JWT bearer tokens: signature (HS256, RS256, or ES256), issuer, audience, and
+/// validity period. The roles of the principal are read from the configured roles claim, and its
+/// tenant from the tenant claim, if configured.
+pub(crate) struct JwtValidator {
+    config: JwtConfig,
+    signing_keys: SigningKeys,
+    tenant_field: String,
+}
+
+impl JwtValidator {
+    pub fn new(config: JwtConfig, tenant_field: String) -> Self {
+        let signing_keys = if let Some(hmac_secret) = &config.hmac_secret {
+            SigningKeys::Hmac(hmac::Key::new(hmac::HMAC_SHA256, hmac_secret.as_bytes()))
+        } else {
+            let http_client = reqwest::Client::builder()
+                .timeout(JWKS_FETCH_TIMEOUT)
+                .build()
+                .expect("The HTTP client should be valid.");
+            SigningKeys::Jwks(JwksCache {
+                issuer: config.issuer.clone(),
+                jwks_uri_opt: config.jwks_uri.clone(),
+                http_client,
+                cached_keys: Mutex::default(),
+            })
+        };
+        Self {
+            config,
+            signing_keys,
+            tenant_field,
+        }
+    }
+
+    #[cfg(test)]
+    fn with_keys(config: JwtConfig, keys: Vec<Jwk>) -> Self {
+        let mut jwt_validator = Self::new(config, "tenant_id".to_string());
+        if let SigningKeys::Jwks(jwks_cache) = &mut jwt_validator.signing_keys {
+            let now = Instant::now();
+            jwks_cache.cached_keys = Mutex::new(CachedKeys {
+                keys: Arc::new(keys),
+                fetched_at_opt: Some(now),
+                last_attempt_at_opt: Some(now),
+            });
+        }
+        jwt_validator
+    }
+
+    pub async fn validate(&self, token: &str) -> Result<Principal, AuthError> {
+        let parts: Vec<&str> = token.split('.').collect();
+        let [header_b64, claims_b64, signature_b64] = parts[..] else {
+            return Err(invalid_token("malformed token"));
+        };
+        let header: JwtHeader = decode_json(header_b64)?;
+        let signature = BASE64_URL_SAFE_NO_PAD
+            .decode(signature_b64)
+            .map_err(|_| invalid_token("malformed signature"))?;
+        let message = &token.as_bytes()[..header_b64.len() + 1 + claims_b64.len()];
+
+        let is_signature_valid = match (&self.signing_keys, header.alg.as_str()) {
+            (SigningKeys::Hmac(key), "HS256") => hmac::verify(key, message, &signature).is_ok(),
+            (SigningKeys::Jwks(jwks_cache), alg @ ("RS256" | "ES256")) => {
+                let keys = jwks_cache.keys(header.kid.as_deref()).await;
+                keys.iter()
+                    .filter(|key| key.supports(alg))
+                    .filter(|key| match (&header.kid, &key.kid) {
+                        (Some(token_kid), Some(key_kid)) => token_kid == key_kid,
+                        _ => true,
+                    })
+                    .any(|key| key.verify(alg, message, &signature))
+            }
+            (_, alg) => {
+                return Err(invalid_token(&format!("unsupported algorithm `{alg}`")));
+            }
+        };
+        if !is_signature_valid {
+            return Err(invalid_token("invalid signature"));
+        }
+        let claims: JsonValue = decode_json(claims_b64)?;
+        self.validate_claims(&claims, unix_timestamp_now())?;
+
+        let name = claims
+            .get("sub")
+            .and_then(JsonValue::as_str)
+            .unwrap_or("anonymous")
+            .to_string();
+        let grants = parse_grants(claims.get(&self.config.roles_claim));
+        let tenant_scope_opt = self
+            .config
+            .tenant_claim
+            .as_ref()
+            .and_then(|tenant_claim| claims.get(tenant_claim))
+            .and_then(JsonValue::as_str)
+            .map(|tenant_id| TenantScope {
+                field_name: self.tenant_field.clone(),
+                tenant_id: tenant_id.to_string(),
+            });
+        Ok(Principal {
+            name,
+            grants,
+            tenant_scope_opt,
+        })
+    }
+
+    fn validate_claims(&self, claims: &JsonValue, now: i64) -> Result<(), AuthError> {
+        let leeway = self.config.leeway_secs as i64;
+
+        if claims.get("iss").and_then(JsonValue::as_str) != Some(self.config.issuer.as_str()) {
+            return Err(invalid_token("unexpected issuer"));
+        }
+        let expires_at = numeric_date(claims.get("exp"))
+            .ok_or_else(|| invalid_token("missing expiration time"))?;
+        if now > expires_at + leeway {
+            return Err(invalid_token("token has expired"));
+        }
+        if let Some(not_before) = numeric_date(claims.get("nbf")) {
+            if now + leeway < not_before {
+                return Err(invalid_token("token is not valid yet"));
+            }
+        }
+        if let Some(expected_audience) = &self.config.audience {
+            let is_audience_valid = match claims.get("aud") {
+                Some(JsonValue::String(audience)) => audience == expected_audience,
+                Some(JsonValue::Array(audiences)) => audiences
+                    .iter()
+                    .any(|audience| audience.as_str() == Some(expected_audience)),
+                _ => false,
+            };
+            if !is_audience_valid {
+                return Err(invalid_token("unexpected audience"));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn invalid_token(reason: &str) -> AuthError {
+    AuthError::InvalidCredentials(format!("invalid JWT: {reason}"))
+}
+
+fn decode_json<T: for<'de> Deserialize<'de>>(part_b64: &str) -> Result<T, AuthError> {
+    let part = BASE64_URL_SAFE_NO_PAD
+        .decode(part_b64)
+        .map_err(|_| invalid_token("malformed token"))?;
+    serde_json::from_slice(&part).map_err(|_| invalid_token("malformed token"))
+}
+
+fn numeric_date(value_opt: Option<&JsonValue>) -> Option<i64> {
+    value_opt
+        .and_then(JsonValue::as_f64)
+        .map(|value| value as i64)
+}
+
+fn unix_timestamp_now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+/// Reads the grants, such as `search` or `ingest:team-a-*`, from a claim holding either an array
+/// of strings or a space-separated string, such as the `scope` claim. Values that are not
+/// Quickwit grants are ignored.
+fn parse_grants(claim_opt: Option<&JsonValue>) -> Vec<AuthGrant> {
+    let grants: Vec<&str> = match claim_opt {
+        Some(JsonValue::String(grants)) => grants.split_whitespace().collect(),
+        Some(JsonValue::Array(grants)) => grants.iter().filter_map(JsonValue::as_str).collect(),
+        _ => Vec::new(),
+    };
+    grants
+        .into_iter()
+        .filter_map(|grant| grant.parse().ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ring::rand::SystemRandom;
+    use ring::signature::{EcdsaKeyPair, KeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+    use serde_json::json;
+
+    use super::*;
+
+    const HMAC_SECRET: &str = "0123456789abcdef0123456789abcdef";
+
+    fn jwt_config() -> JwtConfig {
+        serde_json::from_value(json!({
+            "issuer": "https://accounts.example.com",
+            "audience": "quickwit",
+        }))
+        .unwrap()
+    }
+
+    fn encode_token(
+        header: JsonValue,
+        claims: JsonValue,
+        sign: impl Fn(&[u8]) -> Vec<u8>,
+    ) -> String {
+        let message = format!(
+            "{}.{}",
+            BASE64_URL_SAFE_NO_PAD.encode(header.to_string()),
+            BASE64_URL_SAFE_NO_PAD.encode(claims.to_string())
+        );
+        let signature = sign(message.as_bytes());
+        format!("{message}.{}", BASE64_URL_SAFE_NO_PAD.encode(signature))
+    }
+
+    fn hs256_token(claims: JsonValue) -> String {
+        hs256_token_with_secret(HMAC_SECRET, claims)
+    }
+
+    fn hs256_token_with_secret(secret: &str, claims: JsonValue) -> String {
+        let key = hmac::Key::new(hmac::HMAC_SHA256, secret.as_bytes());
+        encode_token(json!({"alg": "HS256", "typ": "JWT"}), claims, |message| {
+            hmac::sign(&key, message).as_ref().to_vec()
+        })
+    }
+
+    fn valid_claims() -> JsonValue {
+        json!({
+            "iss": "https://accounts.example.com",
+            "aud": ["quickwit", "grafana"],
+            "sub": "alice",
+            "exp": unix_timestamp_now() + 600,
+            "roles": ["search:team-a-*", "ingest", "viewer"],
+        })
+    }
+
+    #[tokio::test]
+    async fn test_jwt_validator_hs256() {
+        let mut config = jwt_config();
+        config.hmac_secret = Some(HMAC_SECRET.to_string());
+        config.tenant_claim = Some("org_id".to_string());
+        let jwt_validator = JwtValidator::new(config, "tenant_id".to_string());
+
+        let principal = jwt_validator
+            .validate(&hs256_token(valid_claims()))
+            .await
+            .unwrap();
+        assert_eq!(principal.name, "alice");
+        let grants: Vec<String> = principal.grants.iter().map(ToString::to_string).collect();
+        assert_eq!(grants, ["search:team-a-*", "ingest"]);
+        assert!(principal.tenant_scope_opt.is_none());
+
+        let mut claims = valid_claims();
+        claims["org_id"] = json!("acme");
+        let principal = jwt_validator.validate(&hs256_token(claims)).await.unwrap();
+        assert_eq!(
+            principal.tenant_scope_opt,
+            Some(TenantScope {
+                field_name: "tenant_id".to_string(),
+                tenant_id: "acme".to_string(),
+            })
+        );
+
+        let token = hs256_token_with_secret("another-secret", valid_claims());
+        let error = jwt_validator.validate(&token).await.unwrap_err();
+        assert!(error.to_string().contains("invalid signature"));
+
+        let unsigned_token = encode_token(json!({"alg": "none"}), valid_claims(), |_| Vec::new());
+        let error = jwt_validator.validate(&unsigned_token).await.unwrap_err();
+        assert!(error.to_string().contains("unsupported algorithm `none`"));
+
+        let error = jwt_validator.validate("not-a-token").await.unwrap_err();
+        assert!(error.to_string().contains("malformed token"));
+    }
+
+    #[tokio::test]
+    async fn test_jwt_validator_checks_claims() {
+        let mut config = jwt_config();
+        config.hmac_secret = Some(HMAC_SECRET.to_string());
+        let jwt_validator = JwtValidator::new(config, "tenant_id".to_string());
+
+        for (claim, value, expected_error) in [
+            (
+                "iss",
+                json!("https://evil.example.com"),
+                "unexpected issuer",
+            ),
+            ("aud", json!("grafana"), "unexpected audience"),
+            (
+                "exp",
+                json!(unix_timestamp_now() - 120),
+                "token has expired",
+            ),
+            (
+                "nbf",
+                json!(unix_timestamp_now() + 120),
+                "token is not valid yet",
+            ),
+            ("exp", JsonValue::Null, "missing expiration time"),
+        ] {
+            let mut claims = valid_claims();
+            claims[claim] = value;
+            let error = jwt_validator
+                .validate(&hs256_token(claims))
+                .await
+                .unwrap_err();
+            assert!(
+                error.to_string().contains(expected_error),
+                "expected `{expected_error}`, got `{error}`"
+            );
+        }
+        // Expired tokens are accepted within the leeway.
+        let mut claims = valid_claims();
+        claims["exp"] = json!(unix_timestamp_now() - 30);
+        jwt_validator.validate(&hs256_token(claims)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_jwt_validator_es256() {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &rng).unwrap();
+        let key_pair =
+            EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, pkcs8.as_ref()).unwrap();
+        let public_key = key_pair.public_key().as_ref();
+        let jwk: Jwk = serde_json::from_value(json!({
+            "kty": "EC",
+            "kid": "key-1",
+            "crv": "P-256",
+            "x": BASE64_URL_SAFE_NO_PAD.encode(&public_key[1..33]),
+            "y": BASE64_URL_SAFE_NO_PAD.encode(&public_key[33..]),
+        }))
+        .unwrap();
+        let jwt_validator = JwtValidator::with_keys(jwt_config(), vec![jwk]);
+
+        let mut claims = valid_claims();
+        claims["roles"] = json!("admin openid read:user");
+        let sign = |message: &[u8]| key_pair.sign(&rng, message).unwrap().as_ref().to_vec();
+        let token = encode_token(
+            json!({"alg": "ES256", "kid": "key-1"}),
+            claims.clone(),
+            sign,
+        );
+        let principal = jwt_validator.validate(&token).await.unwrap();
+        assert_eq!(principal.grants, [AuthGrant::from_str("admin").unwrap()]);
+
+        // HS256 tokens must not be accepted when keys come from a JWKS.
+        let error = jwt_validator
+            .validate(&hs256_token(claims))
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("unsupported algorithm `HS256`"));
+    }
+}
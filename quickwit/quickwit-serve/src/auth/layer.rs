@@ -0,0 +1,1168 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use futures::future::BoxFuture;
+use http_body::{LengthLimitError, Limited};
+use hyper::header::{HeaderValue, AUTHORIZATION, CONTENT_LENGTH, CONTENT_TYPE, RETRY_AFTER};
+use hyper::{Body, HeaderMap, Method, Request, Response, StatusCode, Uri};
+use quickwit_config::AuthRole;
+use quickwit_metastore::{with_audit_actor, AUDIT_ACTOR_METADATA_KEY};
+use quickwit_opentelemetry::otlp::with_retry_after;
+use quickwit_proto::tonic::Status;
+use quickwit_proto::{ServiceError, ServiceErrorCode};
+use tower::{Layer, Service};
+use tracing::debug;
+use warp::Reply;
+
+use super::{authorize_tenant, Access, AccessPolicy, AuthError, Authenticator, Principal};
+use crate::audit_log::{unix_timestamp_millis_now, AuditLogger, AuditRecord};
+use crate::format::BodyFormat;
+use crate::json_api_response::{make_json_api_response, ApiError, JsonApiResponse};
+use crate::rate_limit::{RateLimitError, RateLimitPermit, RateLimiter};
+
+/// Search request bodies larger than this are not recorded in the audit log.
+const MAX_AUDITED_BODY_NUM_BYTES: u64 = 16 * 1024;
+
+/// Bodies of the requests subject to an ingest bytes limit are buffered up to this size, the
+/// content length limit of the ingest endpoints, when they do not carry a `Content-Length` header.
+const MAX_INGEST_BODY_NUM_BYTES: usize = 10 * 1024 * 1024; // 10MiB
+
+/// Error raised while buffering the body of a request subject to an ingest bytes limit.
+#[derive(Debug, thiserror::Error)]
+enum IngestBodyError {
+    #[error("Payload exceeds the limit of {0} bytes.")]
+    PayloadTooLarge(usize),
+    #[error("Failed to read payload: {0}")]
+    Read(String),
+}
+
+impl IngestBodyError {
+    fn rest_response(&self) -> Response<Body> {
+        let status_code = match self {
+            IngestBodyError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            IngestBodyError::Read(_) => StatusCode::BAD_REQUEST,
+        };
+        let api_error = ApiError {
+            service_code: ServiceErrorCode::BadRequest,
+            message: self.to_string(),
+        };
+        JsonApiResponse::new::<(), _>(&Err(api_error), status_code, &BodyFormat::default())
+            .into_response()
+    }
+
+    fn grpc_status(&self) -> Status {
+        match self {
+            // Like the other limits of the ingest services, and unlike rate limits, the status
+            // carries no retry delay.
+            IngestBodyError::PayloadTooLarge(_) => Status::resource_exhausted(self.to_string()),
+            IngestBodyError::Read(_) => Status::invalid_argument(self.to_string()),
+        }
+    }
+}
+
+/// Authentication, authorization, audit, and rate limit settings shared by the REST and gRPC
+/// layers.
+#[derive(Clone)]
+struct RequestGuard {
+    authenticator_opt: Option<Arc<Authenticator>>,
+    access_policy: Arc<AccessPolicy>,
+    audit_logger_opt: Option<AuditLogger>,
+    rate_limiter_opt: Option<Arc<RateLimiter>>,
+}
+
+/// Authenticates, authorizes, rate limits, and audits the requests received by the REST server,
+/// and inserts their [`Principal`] into their extensions for the handlers checking the indexes
+/// named in the body. Requests are also audited and rate limited when authentication is disabled.
+/// Rate limited requests are rejected with a `429` status and a `Retry-After` header.
+#[derive(Clone)]
+pub(crate) struct RestAuthLayer {
+    guard: RequestGuard,
+}
+
+impl RestAuthLayer {
+    pub fn new(
+        authenticator_opt: Option<Arc<Authenticator>>,
+        access_policy: AccessPolicy,
+        audit_logger_opt: Option<AuditLogger>,
+    ) -> Self {
+        Self {
+            guard: RequestGuard {
+                authenticator_opt,
+                access_policy: Arc::new(access_policy),
+                audit_logger_opt,
+                rate_limiter_opt: None,
+            },
+        }
+    }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.guard.rate_limiter_opt = Some(rate_limiter);
+        self
+    }
+}
+
+impl<S> Layer<S> for RestAuthLayer {
+    type Service = RestAuth<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RestAuth {
+            inner,
+            guard: self.guard.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct RestAuth<S> {
+    inner: S,
+    guard: RequestGuard,
+}
+
+impl<S> Service<Request<Body>> for RestAuth<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>> + Clone + Send + 'static,
+    S::Future: Send,
+{
+    type Response = Response<Body>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // The inner service polled ready is the one that must handle the request.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let guard = self.guard.clone();
+        let access = guard.access_policy.rest_access(
+            request.method(),
+            request.uri().path(),
+            request.headers(),
+        );
+        let filters_tenant = guard
+            .access_policy
+            .rest_filters_tenant(request.method(), request.uri().path());
+        let request_info = RequestInfo::new(&request, "rest");
+
+        Box::pin(async move {
+            let (request, query_opt) = if guard.records_query(&access) {
+                extract_query(request).await
+            } else {
+                (request, None)
+            };
+            let auth_result = request_info
+                .authorize(&guard, &access, filters_tenant)
+                .await;
+            let principal_name_opt = principal_name(&auth_result);
+
+            let response = match auth_result {
+                Ok(principal_opt) => {
+                    let measure_result = guard
+                        .measure_ingest_bytes(request, &request_info, &access, &principal_opt)
+                        .await;
+                    let (mut request, ingest_num_bytes) = match measure_result {
+                        Ok(measured_request) => measured_request,
+                        Err(error) => {
+                            let response = error.rest_response();
+                            guard.audit(
+                                &request_info,
+                                &access,
+                                principal_name_opt,
+                                query_opt,
+                                &response,
+                            );
+                            return Ok(response);
+                        }
+                    };
+                    match guard.acquire_rate_limit_permit(
+                        &request_info,
+                        &access,
+                        &principal_opt,
+                        ingest_num_bytes,
+                    ) {
+                        Ok(_rate_limit_permit_opt) => {
+                            match principal_opt {
+                                // The index mutations applied by the request are recorded in the
+                                // audit log on behalf of its principal.
+                                Some(principal) => {
+                                    let actor = principal.name.clone();
+                                    request.extensions_mut().insert(principal);
+                                    with_audit_actor(actor, inner.call(request)).await?
+                                }
+                                None => inner.call(request).await?,
+                            }
+                        }
+                        Err(error) => {
+                            let retry_after_secs = error.retry_after_secs();
+                            let mut response =
+                                make_json_api_response::<(), _>(Err(error), BodyFormat::default())
+                                    .into_response();
+                            response
+                                .headers_mut()
+                                .insert(RETRY_AFTER, HeaderValue::from(retry_after_secs));
+                            response
+                        }
+                    }
+                }
+                Err(error) => make_json_api_response::<(), _>(Err(error), BodyFormat::default())
+                    .into_response(),
+            };
+            guard.audit(
+                &request_info,
+                &access,
+                principal_name_opt,
+                query_opt,
+                &response,
+            );
+            Ok(response)
+        })
+    }
+}
+
+/// Authenticates, authorizes, rate limits, and audits the requests received by the gRPC server.
+/// Rejected requests are answered with a trailers-only response carrying the `UNAUTHENTICATED`,
+/// `PERMISSION_DENIED`, or `RESOURCE_EXHAUSTED` status, the latter with a retry delay.
+#[derive(Clone)]
+pub(crate) struct GrpcAuthLayer {
+    guard: RequestGuard,
+}
+
+impl GrpcAuthLayer {
+    pub fn new(
+        authenticator_opt: Option<Arc<Authenticator>>,
+        access_policy: AccessPolicy,
+        audit_logger_opt: Option<AuditLogger>,
+    ) -> Self {
+        Self {
+            guard: RequestGuard {
+                authenticator_opt,
+                access_policy: Arc::new(access_policy),
+                audit_logger_opt,
+                rate_limiter_opt: None,
+            },
+        }
+    }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.guard.rate_limiter_opt = Some(rate_limiter);
+        self
+    }
+}
+
+impl<S> Layer<S> for GrpcAuthLayer {
+    type Service = GrpcAuth<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        GrpcAuth {
+            inner,
+            guard: self.guard.clone(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct GrpcAuth<S> {
+    inner: S,
+    guard: RequestGuard,
+}
+
+impl<S, ResBody> Service<Request<Body>> for GrpcAuth<S>
+where
+    S: Service<Request<Body>, Response = Response<ResBody>> + Clone + Send + 'static,
+    S::Future: Send,
+    ResBody: Default + Send + 'static,
+{
+    type Response = Response<ResBody>;
+    type Error = S::Error;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let guard = self.guard.clone();
+        let access = guard
+            .access_policy
+            .grpc_access(request.uri().path(), request.headers());
+        let request_info = RequestInfo::new(&request, "grpc");
+        // The internal requests, which only the nodes of the cluster are granted, carry the actor
+        // on whose behalf the node sends them, for instance the principal of the REST request
+        // that triggered a metastore request.
+        let forwarded_actor_opt = if access == Access::Internal {
+            request
+                .headers()
+                .get(AUDIT_ACTOR_METADATA_KEY)
+                .and_then(|value| value.to_str().ok())
+                .map(|actor| actor.to_string())
+        } else {
+            None
+        };
+
+        Box::pin(async move {
+            // The gRPC services do not filter documents per tenant.
+            let auth_result = request_info.authorize(&guard, &access, false).await;
+            let principal_name_opt = principal_name(&auth_result);
+
+            let response = match auth_result {
+                Ok(principal_opt) => {
+                    let measure_result = guard
+                        .measure_ingest_bytes(request, &request_info, &access, &principal_opt)
+                        .await;
+                    let (mut request, ingest_num_bytes) = match measure_result {
+                        Ok(measured_request) => measured_request,
+                        Err(error) => {
+                            let response = grpc_status_response(error.grpc_status());
+                            guard.audit(
+                                &request_info,
+                                &access,
+                                principal_name_opt,
+                                None,
+                                &response,
+                            );
+                            return Ok(response);
+                        }
+                    };
+                    match guard.acquire_rate_limit_permit(
+                        &request_info,
+                        &access,
+                        &principal_opt,
+                        ingest_num_bytes,
+                    ) {
+                        Ok(_rate_limit_permit_opt) => {
+                            // The index mutations applied by the request are recorded in the
+                            // audit log on behalf of the forwarded actor or of its principal.
+                            let actor_opt = forwarded_actor_opt.or_else(|| {
+                                principal_opt
+                                    .as_ref()
+                                    .map(|principal| principal.name.clone())
+                            });
+                            if let Some(principal) = principal_opt {
+                                request.extensions_mut().insert(principal);
+                            }
+                            match actor_opt {
+                                Some(actor) => with_audit_actor(actor, inner.call(request)).await?,
+                                None => inner.call(request).await?,
+                            }
+                        }
+                        Err(error) => {
+                            let status = Status::resource_exhausted(error.to_string());
+                            grpc_status_response(with_retry_after(status, error.retry_after()))
+                        }
+                    }
+                }
+                Err(error) => grpc_error_response(&error),
+            };
+            guard.audit(&request_info, &access, principal_name_opt, None, &response);
+            Ok(response)
+        })
+    }
+}
+
+impl RequestGuard {
+    /// Returns the request along with the number of bytes it ingests. The body of the requests
+    /// subject to an ingest bytes limit is buffered and measured when they do not carry a
+    /// `Content-Length` header, like chunked REST requests and gRPC requests, so that they cannot
+    /// ingest for free. Bodies larger than the ingest payload limit are rejected.
+    async fn measure_ingest_bytes(
+        &self,
+        request: Request<Body>,
+        request_info: &RequestInfo,
+        access: &Access,
+        principal_opt: &Option<Principal>,
+    ) -> Result<(Request<Body>, u64), IngestBodyError> {
+        if let Some(content_length) = request_info.content_length_opt {
+            return Ok((request, content_length));
+        }
+        let Some(rate_limiter) = &self.rate_limiter_opt else {
+            return Ok((request, 0));
+        };
+        let principal_name_opt = principal_opt
+            .as_ref()
+            .map(|principal| principal.name.as_str());
+
+        if !rate_limiter.limits_ingest_bytes(principal_name_opt, access) {
+            return Ok((request, 0));
+        }
+        let (parts, body) = request.into_parts();
+        let limited_body = Limited::new(body, MAX_INGEST_BODY_NUM_BYTES);
+        let body_bytes = hyper::body::to_bytes(limited_body).await.map_err(|error| {
+            if error.is::<LengthLimitError>() {
+                IngestBodyError::PayloadTooLarge(MAX_INGEST_BODY_NUM_BYTES)
+            } else {
+                IngestBodyError::Read(error.to_string())
+            }
+        })?;
+        let num_bytes = body_bytes.len() as u64;
+        Ok((
+            Request::from_parts(parts, Body::from(body_bytes)),
+            num_bytes,
+        ))
+    }
+
+    fn acquire_rate_limit_permit(
+        &self,
+        request_info: &RequestInfo,
+        access: &Access,
+        principal_opt: &Option<Principal>,
+        ingest_num_bytes: u64,
+    ) -> Result<Option<RateLimitPermit>, RateLimitError> {
+        let Some(rate_limiter) = &self.rate_limiter_opt else {
+            return Ok(None);
+        };
+        let principal_name_opt = principal_opt
+            .as_ref()
+            .map(|principal| principal.name.as_str());
+        let rate_limit_result = rate_limiter.acquire(principal_name_opt, access, ingest_num_bytes);
+
+        if let Err(error) = &rate_limit_result {
+            debug!(
+                method=%request_info.method,
+                path=%request_info.uri.path(),
+                error=%error,
+                "Rate limited {} request.",
+                request_info.protocol
+            );
+            crate::SERVE_METRICS.rate_limited_requests_total.inc();
+        }
+        rate_limit_result.map(Some)
+    }
+
+    fn records_query(&self, access: &Access) -> bool {
+        let is_search = matches!(
+            access,
+            Access::Indexes(AuthRole::Search, _)
+                | Access::Cluster(AuthRole::Search)
+                | Access::Handler(AuthRole::Search)
+        );
+        is_search
+            && self
+                .audit_logger_opt
+                .as_ref()
+                .map(AuditLogger::record_queries)
+                .unwrap_or(false)
+    }
+
+    fn audit<ResBody>(
+        &self,
+        request_info: &RequestInfo,
+        access: &Access,
+        principal_name_opt: Option<String>,
+        query_opt: Option<String>,
+        response: &Response<ResBody>,
+    ) {
+        let Some(audit_logger) = &self.audit_logger_opt else {
+            return;
+        };
+        let (action, index_ids) = match access {
+            // The internal requests of the cluster are not audited.
+            Access::Public | Access::Internal | Access::Denied => return,
+            Access::Indexes(role, index_ids) => (*role, index_ids.clone()),
+            Access::Cluster(role) => (*role, vec!["*".to_string()]),
+            Access::Handler(role) => (*role, Vec::new()),
+        };
+        let grpc_status_opt = response
+            .headers()
+            .get("grpc-status")
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        let record = AuditRecord {
+            timestamp: request_info.timestamp,
+            protocol: request_info.protocol,
+            principal: principal_name_opt,
+            action,
+            index_ids,
+            method: request_info.method.to_string(),
+            path: request_info.uri.path().to_string(),
+            query: query_opt,
+            status: response.status().as_u16(),
+            grpc_status: grpc_status_opt,
+            elapsed_time_micros: request_info.start.elapsed().as_micros() as u64,
+        };
+        audit_logger.log(record);
+    }
+}
+
+/// Parts of a request needed to authorize and audit it, copied out of the request so that the
+/// request body, which is not `Sync`, is not borrowed across await points.
+struct RequestInfo {
+    method: Method,
+    uri: Uri,
+    authorization_opt: Option<String>,
+    /// Value of the `Content-Length` header, counted against the ingest rate limits. Chunked and
+    /// gRPC requests do not carry it.
+    content_length_opt: Option<u64>,
+    protocol: &'static str,
+    timestamp: i64,
+    start: Instant,
+}
+
+impl RequestInfo {
+    fn new<B>(request: &Request<B>, protocol: &'static str) -> Self {
+        let authorization_opt = request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .map(|authorization| authorization.to_string());
+        let content_length_opt = request
+            .headers()
+            .get(CONTENT_LENGTH)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.parse().ok());
+        Self {
+            method: request.method().clone(),
+            uri: request.uri().clone(),
+            authorization_opt,
+            content_length_opt,
+            protocol,
+            timestamp: unix_timestamp_millis_now(),
+            start: Instant::now(),
+        }
+    }
+
+    async fn authorize(
+        &self,
+        guard: &RequestGuard,
+        access: &Access,
+        filters_tenant: bool,
+    ) -> Result<Option<Principal>, AuthError> {
+        let auth_result = match &guard.authenticator_opt {
+            Some(authenticator) => authenticator
+                .authorize(access, self.authorization_opt.as_deref())
+                .await
+                .and_then(|principal_opt| {
+                    if let Some(principal) = &principal_opt {
+                        authorize_tenant(principal, access, self.uri.path(), filters_tenant)?;
+                    }
+                    Ok(principal_opt)
+                }),
+            None if *access == Access::Denied => Err(AuthError::NotExposed),
+            None => return Ok(None),
+        };
+
+        if let Err(error) = &auth_result {
+            debug!(
+                method=%self.method,
+                path=%self.uri.path(),
+                error=%error,
+                "Rejected {} request.",
+                self.protocol
+            );
+            crate::SERVE_METRICS.http_auth_failures_total.inc();
+        }
+        auth_result
+    }
+}
+
+fn principal_name(auth_result: &Result<Option<Principal>, AuthError>) -> Option<String> {
+    match auth_result {
+        Ok(principal_opt) => principal_opt
+            .as_ref()
+            .map(|principal| principal.name.clone()),
+        Err(AuthError::Forbidden { principal, .. })
+        | Err(AuthError::TenantRestricted { principal, .. }) => Some(principal.clone()),
+        Err(_) => None,
+    }
+}
+
+/// Extracts the query of a search request: its body for `POST` requests, its query string
+/// otherwise. The body is buffered and handed back in the returned request.
+async fn extract_query(request: Request<Body>) -> (Request<Body>, Option<String>) {
+    if request.method() != Method::POST {
+        let query_opt = request.uri().query().map(|query| query.to_string());
+        return (request, query_opt);
+    }
+    let content_length_opt: Option<u64> = request
+        .headers()
+        .get(CONTENT_LENGTH)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok());
+
+    match content_length_opt {
+        Some(content_length) if content_length <= MAX_AUDITED_BODY_NUM_BYTES => {}
+        _ => return (request, None),
+    }
+    let (parts, body) = request.into_parts();
+    match hyper::body::to_bytes(body).await {
+        Ok(body_bytes) => {
+            let query = String::from_utf8_lossy(&body_bytes).into_owned();
+            (
+                Request::from_parts(parts, Body::from(body_bytes)),
+                Some(query),
+            )
+        }
+        Err(_) => (Request::from_parts(parts, Body::empty()), None),
+    }
+}
+
+fn grpc_error_response<ResBody: Default>(error: &AuthError) -> Response<ResBody> {
+    let status = match error.status_code() {
+        ServiceErrorCode::Forbidden => Status::permission_denied(error.to_string()),
+        _ => Status::unauthenticated(error.to_string()),
+    };
+    grpc_status_response(status)
+}
+
+fn grpc_status_response<ResBody: Default>(status: Status) -> Response<ResBody> {
+    let mut headers = HeaderMap::new();
+    headers.insert(CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+    // The status message is always a valid header value once percent-encoded.
+    let _ = status.add_header(&mut headers);
+
+    let mut response = Response::new(ResBody::default());
+    *response.headers_mut() = headers;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+    use std::time::Duration;
+
+    use byte_unit::Byte;
+    use quickwit_config::{IndexerConfig, RateLimitConfig, RateLimits};
+    use quickwit_opentelemetry::otlp::{extract_retry_after, OTLP_INDEX_HEADER};
+
+    use super::*;
+    use crate::auth::tests::{authenticator, ACME_KEY, CLUSTER_KEY, SEARCH_KEY, TEAM_A_KEY};
+
+    #[derive(Clone)]
+    struct EchoPrincipal;
+
+    impl Service<Request<Body>> for EchoPrincipal {
+        type Response = Response<Body>;
+        type Error = std::convert::Infallible;
+        type Future = futures::future::Ready<Result<Response<Body>, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: Request<Body>) -> Self::Future {
+            let principal_name = request
+                .extensions()
+                .get::<Principal>()
+                .map(|principal| principal.name.clone())
+                .unwrap_or_default();
+            futures::future::ready(Ok(Response::new(Body::from(principal_name))))
+        }
+    }
+
+    /// Answers with the actor on whose behalf the request is handled.
+    #[derive(Clone)]
+    struct EchoAuditActor;
+
+    impl Service<Request<Body>> for EchoAuditActor {
+        type Response = Response<Body>;
+        type Error = std::convert::Infallible;
+        type Future = BoxFuture<'static, Result<Response<Body>, Self::Error>>;
+
+        fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _request: Request<Body>) -> Self::Future {
+            Box::pin(async {
+                let actor = quickwit_metastore::current_audit_actor().unwrap_or_default();
+                Ok(Response::new(Body::from(actor)))
+            })
+        }
+    }
+
+    fn request(path: &str, key_opt: Option<&str>) -> Request<Body> {
+        let mut request_builder = Request::builder().uri(path);
+        if let Some(key) = key_opt {
+            request_builder = request_builder.header(AUTHORIZATION, format!("Bearer {key}"));
+        }
+        request_builder.body(Body::empty()).unwrap()
+    }
+
+    async fn body_string(response: Response<Body>) -> String {
+        let body_bytes = hyper::body::to_bytes(response.into_body()).await.unwrap();
+        String::from_utf8(body_bytes.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rest_auth_layer() {
+        let access_policy = AccessPolicy::new(&IndexerConfig::default());
+        let layer = RestAuthLayer::new(Some(Arc::new(authenticator())), access_policy, None);
+        let mut service = layer.layer(EchoPrincipal);
+
+        let response = service.call(request("/health/livez", None)).await.unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(body_string(response).await, "");
+
+        let response = service
+            .call(request("/api/v1/team-a-logs/search", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 401);
+
+        let response = service
+            .call(request("/api/v1/team-a-logs/search", Some(TEAM_A_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(body_string(response).await, "team-a");
+
+        let response = service
+            .call(request("/api/v1/team-b-logs/search", Some(TEAM_A_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+        assert!(body_string(response)
+            .await
+            .contains("is not granted the `search` role on index `team-b-logs`"));
+
+        let response = service
+            .call(request("/api/v1/indexes/team-a-logs", Some(SEARCH_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+
+        // Principals restricted to a tenant only reach the handlers filtering per tenant.
+        let response = service
+            .call(request("/api/v1/shared-logs/search", Some(ACME_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(body_string(response).await, "acme");
+
+        let response = service
+            .call(request("/api/v1/shared-logs/tail/stream", Some(ACME_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 403);
+        assert!(body_string(response)
+            .await
+            .contains("is restricted to a tenant"));
+    }
+
+    #[tokio::test]
+    async fn test_grpc_auth_layer() {
+        let access_policy = AccessPolicy::new(&IndexerConfig::default());
+        let layer = GrpcAuthLayer::new(Some(Arc::new(authenticator())), access_policy, None);
+        let mut service = layer.layer(EchoPrincipal);
+
+        let response = service
+            .call(request("/grpc.health.v1.Health/Check", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert!(response.headers().get("grpc-status").is_none());
+
+        let response = service
+            .call(request("/quickwit.SearchService/LeafSearch", None))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["grpc-status"], "16");
+
+        let response = service
+            .call(request(
+                "/quickwit.SearchService/LeafSearch",
+                Some(SEARCH_KEY),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["grpc-status"], "7");
+
+        let response = service
+            .call(request(
+                "/quickwit.SearchService/LeafSearch",
+                Some(CLUSTER_KEY),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(body_string(response).await, "cluster");
+
+        let response = service
+            .call(request(
+                "/jaeger.storage.v1.SpanWriterPlugin/WriteSpan",
+                Some(CLUSTER_KEY),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["grpc-status"], "7");
+
+        let response = service
+            .call(request(
+                "/jaeger.storage.v1.SpanReaderPlugin/GetServices",
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+        assert_eq!(response.headers()["grpc-status"], "16");
+
+        let response = service
+            .call(request(
+                "/jaeger.storage.v1.SpanReaderPlugin/GetServices",
+                Some(TEAM_A_KEY),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["grpc-status"], "7");
+
+        let response = service
+            .call(request(
+                "/jaeger.storage.v1.SpanReaderPlugin/GetServices",
+                Some(SEARCH_KEY),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(body_string(response).await, "grafana");
+    }
+
+    #[tokio::test]
+    async fn test_grpc_auth_layer_forwards_audit_actor() {
+        let access_policy = AccessPolicy::new(&IndexerConfig::default());
+        let layer = GrpcAuthLayer::new(Some(Arc::new(authenticator())), access_policy, None);
+        let mut service = layer.layer(EchoAuditActor);
+
+        let metastore_path = "/quickwit_metastore_api.MetastoreApiService/CreateIndex";
+        let response = service
+            .call(request(metastore_path, Some(CLUSTER_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(body_string(response).await, "cluster");
+
+        let mut forwarded_request = request(metastore_path, Some(CLUSTER_KEY));
+        forwarded_request
+            .headers_mut()
+            .insert(AUDIT_ACTOR_METADATA_KEY, HeaderValue::from_static("team-a"));
+        let response = service.call(forwarded_request).await.unwrap();
+        assert_eq!(body_string(response).await, "team-a");
+
+        // Only the internal requests of the cluster can act on behalf of another actor.
+        let mut spoofed_request = request(
+            "/jaeger.storage.v1.SpanReaderPlugin/GetServices",
+            Some(SEARCH_KEY),
+        );
+        spoofed_request
+            .headers_mut()
+            .insert(AUDIT_ACTOR_METADATA_KEY, HeaderValue::from_static("team-a"));
+        let response = service.call(spoofed_request).await.unwrap();
+        assert_eq!(body_string(response).await, "grafana");
+    }
+
+    #[tokio::test]
+    async fn test_rest_auth_layer_audit_log() {
+        let access_policy = AccessPolicy::new(&IndexerConfig::default());
+        let (audit_logger, mut record_rx) = AuditLogger::for_test(true);
+        let layer = RestAuthLayer::new(
+            Some(Arc::new(authenticator())),
+            access_policy,
+            Some(audit_logger),
+        );
+        let mut service = layer.layer(EchoPrincipal);
+
+        service.call(request("/health/livez", None)).await.unwrap();
+        assert!(record_rx.try_recv().is_err());
+
+        service
+            .call(request(
+                "/api/v1/team-a-logs/search?query=severity:ERROR",
+                Some(TEAM_A_KEY),
+            ))
+            .await
+            .unwrap();
+        let record = record_rx.try_recv().unwrap();
+        assert_eq!(record.protocol, "rest");
+        assert_eq!(record.principal.as_deref(), Some("team-a"));
+        assert_eq!(record.action, AuthRole::Search);
+        assert_eq!(record.index_ids, ["team-a-logs"]);
+        assert_eq!(record.method, "GET");
+        assert_eq!(record.path, "/api/v1/team-a-logs/search");
+        assert_eq!(record.query.as_deref(), Some("query=severity:ERROR"));
+        assert_eq!(record.status, 200);
+
+        let search_request = Request::post("/api/v1/team-b-logs/search")
+            .header(AUTHORIZATION, format!("Bearer {TEAM_A_KEY}"))
+            .header(CONTENT_LENGTH, 21)
+            .body(Body::from(r#"{"query": "severity"}"#))
+            .unwrap();
+        service.call(search_request).await.unwrap();
+        let record = record_rx.try_recv().unwrap();
+        assert_eq!(record.principal.as_deref(), Some("team-a"));
+        assert_eq!(record.index_ids, ["team-b-logs"]);
+        assert_eq!(record.query.as_deref(), Some(r#"{"query": "severity"}"#));
+        assert_eq!(record.status, 403);
+
+        service
+            .call(request("/api/v1/indexes/team-a-logs", None))
+            .await
+            .unwrap();
+        let record = record_rx.try_recv().unwrap();
+        assert!(record.principal.is_none());
+        assert_eq!(record.action, AuthRole::Admin);
+        assert!(record.query.is_none());
+        assert_eq!(record.status, 401);
+    }
+
+    fn rate_limiter() -> Arc<RateLimiter> {
+        let mut rate_limit_config = RateLimitConfig::default();
+        rate_limit_config.principals.insert(
+            "*".to_string(),
+            RateLimits {
+                requests_per_sec: Some(NonZeroU32::new(1).unwrap()),
+                ..Default::default()
+            },
+        );
+        Arc::new(RateLimiter::new(rate_limit_config))
+    }
+
+    #[tokio::test]
+    async fn test_rest_auth_layer_rate_limit() {
+        let access_policy = AccessPolicy::new(&IndexerConfig::default());
+        let (audit_logger, mut record_rx) = AuditLogger::for_test(false);
+        let layer = RestAuthLayer::new(
+            Some(Arc::new(authenticator())),
+            access_policy,
+            Some(audit_logger),
+        )
+        .with_rate_limiter(rate_limiter());
+        let mut service = layer.layer(EchoPrincipal);
+
+        let response = service
+            .call(request("/api/v1/team-a-logs/search", Some(TEAM_A_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        let response = service
+            .call(request("/api/v1/team-a-logs/search", Some(TEAM_A_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 429);
+        assert_eq!(response.headers()[RETRY_AFTER], "1");
+        assert!(body_string(response)
+            .await
+            .contains("Rate limit `requests_per_sec` of principal `team-a` exceeded"));
+
+        // Each principal has its own bucket, and public routes are not rate limited.
+        let response = service
+            .call(request("/api/v1/team-a-logs/search", Some(SEARCH_KEY)))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        let response = service.call(request("/health/livez", None)).await.unwrap();
+        assert_eq!(response.status(), 200);
+
+        let statuses: Vec<u16> = std::iter::from_fn(|| record_rx.try_recv().ok())
+            .map(|record| record.status)
+            .collect();
+        assert_eq!(statuses, [200, 429, 200]);
+    }
+
+    #[tokio::test]
+    async fn test_grpc_auth_layer_rate_limit() {
+        let access_policy = AccessPolicy::new(&IndexerConfig::default());
+        let layer = GrpcAuthLayer::new(Some(Arc::new(authenticator())), access_policy, None)
+            .with_rate_limiter(rate_limiter());
+        let mut service = layer.layer(EchoPrincipal);
+
+        let path = "/jaeger.storage.v1.SpanReaderPlugin/GetServices";
+        let response = service.call(request(path, Some(SEARCH_KEY))).await.unwrap();
+        assert!(response.headers().get("grpc-status").is_none());
+
+        let response = service.call(request(path, Some(SEARCH_KEY))).await.unwrap();
+        assert_eq!(response.headers()["grpc-status"], "8");
+
+        let status = Status::from_header_map(response.headers()).unwrap();
+        assert!(extract_retry_after(&status).unwrap() <= Duration::from_secs(1));
+    }
+
+    fn ingest_rate_limiter() -> Arc<RateLimiter> {
+        let mut rate_limit_config = RateLimitConfig::default();
+        rate_limit_config.indexes.insert(
+            "team-a-*".to_string(),
+            RateLimits {
+                ingest_bytes_per_sec: Some(Byte::from_bytes(1_000)),
+                ..Default::default()
+            },
+        );
+        Arc::new(RateLimiter::new(rate_limit_config))
+    }
+
+    /// Builds a request without a `Content-Length` header, sending a body of `num_bytes` bytes in
+    /// two chunks.
+    fn chunked_request(path: &str, key: &str, num_bytes: usize) -> Request<Body> {
+        let chunks = vec![
+            Ok::<_, std::io::Error>(vec![b'a'; num_bytes / 2]),
+            Ok(vec![b'a'; num_bytes - num_bytes / 2]),
+        ];
+        Request::builder()
+            .method(Method::POST)
+            .uri(path)
+            .header(AUTHORIZATION, format!("Bearer {key}"))
+            .header(OTLP_INDEX_HEADER, "team-a-logs")
+            .body(Body::wrap_stream(futures::stream::iter(chunks)))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_rest_auth_layer_rate_limit_chunked_ingest() {
+        let access_policy = AccessPolicy::new(&IndexerConfig::default());
+        let layer = RestAuthLayer::new(Some(Arc::new(authenticator())), access_policy, None)
+            .with_rate_limiter(ingest_rate_limiter());
+        let mut service = layer.layer(EchoPrincipal);
+
+        let path = "/api/v1/team-a-logs/ingest";
+        let response = service
+            .call(chunked_request(path, TEAM_A_KEY, 800))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 200);
+
+        let response = service
+            .call(chunked_request(path, TEAM_A_KEY, 800))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 429);
+        assert!(body_string(response)
+            .await
+            .contains("Rate limit `ingest_bytes_per_sec` of index `team-a-logs` exceeded"));
+    }
+
+    #[tokio::test]
+    async fn test_rest_auth_layer_rejects_unreadable_chunked_ingest() {
+        let access_policy = AccessPolicy::new(&IndexerConfig::default());
+        let layer = RestAuthLayer::new(Some(Arc::new(authenticator())), access_policy, None)
+            .with_rate_limiter(ingest_rate_limiter());
+        let mut service = layer.layer(EchoPrincipal);
+
+        let path = "/api/v1/team-a-logs/ingest";
+        let response = service
+            .call(chunked_request(
+                path,
+                TEAM_A_KEY,
+                MAX_INGEST_BODY_NUM_BYTES + 1,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), 413);
+        assert!(body_string(response)
+            .await
+            .contains("Payload exceeds the limit of 10485760 bytes."));
+
+        let chunks = vec![
+            Ok(vec![b'a'; 100]),
+            Err(std::io::Error::new(
+                std::io::ErrorKind::ConnectionReset,
+                "connection reset",
+            )),
+        ];
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(path)
+            .header(AUTHORIZATION, format!("Bearer {TEAM_A_KEY}"))
+            .body(Body::wrap_stream(futures::stream::iter(chunks)))
+            .unwrap();
+        let response = service.call(request).await.unwrap();
+        assert_eq!(response.status(), 400);
+        assert!(body_string(response)
+            .await
+            .contains("Failed to read payload"));
+    }
+
+    #[tokio::test]
+    async fn test_grpc_auth_layer_rate_limit_ingest() {
+        let access_policy = AccessPolicy::new(&IndexerConfig::default());
+        let layer = GrpcAuthLayer::new(Some(Arc::new(authenticator())), access_policy, None)
+            .with_rate_limiter(ingest_rate_limiter());
+        let mut service = layer.layer(EchoPrincipal);
+
+        let path = "/opentelemetry.proto.collector.logs.v1.LogsService/Export";
+        let response = service
+            .call(chunked_request(path, TEAM_A_KEY, 800))
+            .await
+            .unwrap();
+        assert!(response.headers().get("grpc-status").is_none());
+        assert_eq!(body_string(response).await, "team-a");
+
+        let response = service
+            .call(chunked_request(path, TEAM_A_KEY, 800))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["grpc-status"], "8");
+
+        let response = service
+            .call(chunked_request(
+                path,
+                TEAM_A_KEY,
+                MAX_INGEST_BODY_NUM_BYTES + 1,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["grpc-status"], "8");
+        let status = Status::from_header_map(response.headers()).unwrap();
+        assert!(extract_retry_after(&status).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_grpc_auth_layer_audit_log_without_authentication() {
+        let access_policy = AccessPolicy::new(&IndexerConfig::default());
+        let (audit_logger, mut record_rx) = AuditLogger::for_test(true);
+        let layer = GrpcAuthLayer::new(None, access_policy, Some(audit_logger));
+        let mut service = layer.layer(EchoPrincipal);
+
+        let response = service
+            .call(request(
+                "/jaeger.storage.v1.SpanReaderPlugin/GetServices",
+                None,
+            ))
+            .await
+            .unwrap();
+        assert!(response.headers().get("grpc-status").is_none());
+
+        let record = record_rx.try_recv().unwrap();
+        assert_eq!(record.protocol, "grpc");
+        assert!(record.principal.is_none());
+        assert_eq!(record.action, AuthRole::Search);
+        assert!(record.query.is_none());
+        assert_eq!(record.status, 200);
+        assert!(record.grpc_status.is_none());
+
+        let response = service
+            .call(request("/quickwit.SearchService/LeafSearch", None))
+            .await
+            .unwrap();
+        assert!(response.headers().get("grpc-status").is_none());
+        assert!(record_rx.try_recv().is_err());
+
+        let response = service
+            .call(request(
+                "/jaeger.storage.v1.SpanWriterPlugin/WriteSpan",
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.headers()["grpc-status"], "7");
+        assert!(record_rx.try_recv().is_err());
+    }
+}
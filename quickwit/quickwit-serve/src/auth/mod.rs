@@ -0,0 +1,680 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+mod access;
+mod jwt;
+mod layer;
+
+use std::convert::Infallible;
+
+use quickwit_config::{AuthConfig, AuthGrant, AuthRole};
+use quickwit_proto::{ServiceError, ServiceErrorCode};
+use quickwit_query::query_ast::{BoolQuery, QueryAst, TermQuery};
+use ring::constant_time::verify_slices_are_equal;
+use warp::Filter;
+
+pub(crate) use self::access::AccessPolicy;
+use self::jwt::JwtValidator;
+pub(crate) use self::layer::{GrpcAuthLayer, RestAuthLayer};
+
+/// Name of the principal authenticated by the cluster key.
+const CLUSTER_PRINCIPAL_NAME: &str = "cluster";
+
+/// Identity of the caller of an authenticated request, along with the roles it is granted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Principal {
+    pub name: String,
+    pub grants: Vec<AuthGrant>,
+    pub tenant_scope_opt: Option<TenantScope>,
+}
+
+/// Tenant a principal is restricted to: its searches and delete tasks only match the documents
+/// whose `field_name` field holds `tenant_id`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TenantScope {
+    pub field_name: String,
+    pub tenant_id: String,
+}
+
+impl TenantScope {
+    /// AND-s the tenant filter into `query_ast`.
+    pub fn restrict(&self, query_ast: QueryAst) -> QueryAst {
+        let tenant_filter = TermQuery {
+            field: self.field_name.clone(),
+            value: self.tenant_id.clone(),
+        };
+        BoolQuery {
+            must: vec![query_ast],
+            filter: vec![tenant_filter.into()],
+            ..Default::default()
+        }
+        .into()
+    }
+}
+
+impl Principal {
+    /// Whether the principal is granted `role` on every index matching `index_id_pattern`.
+    pub fn is_granted(&self, role: AuthRole, index_id_pattern: &str) -> bool {
+        self.grants.iter().any(|grant| {
+            grant.implies(role) && pattern_covers(&grant.index_id_pattern, index_id_pattern)
+        })
+    }
+
+    fn is_granted_on_any_index(&self, role: AuthRole) -> bool {
+        self.grants.iter().any(|grant| grant.implies(role))
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    #[error("Missing credentials: expected an `Authorization: Bearer <token>` header.")]
+    MissingCredentials,
+    #[error("Invalid credentials: {0}.")]
+    InvalidCredentials(String),
+    #[error("Principal `{principal}` is not granted the `{}` role on {resource}.", .role.as_str())]
+    Forbidden {
+        principal: String,
+        role: AuthRole,
+        resource: String,
+    },
+    #[error(
+        "Principal `{principal}` is restricted to a tenant and cannot call `{path}`, which does \
+         not filter documents per tenant."
+    )]
+    TenantRestricted { principal: String, path: String },
+    #[error("The requested service is not exposed by this node.")]
+    NotExposed,
+}
+
+impl ServiceError for AuthError {
+    fn status_code(&self) -> ServiceErrorCode {
+        match self {
+            AuthError::MissingCredentials | AuthError::InvalidCredentials(_) => {
+                ServiceErrorCode::Unauthorized
+            }
+            AuthError::Forbidden { .. }
+            | AuthError::TenantRestricted { .. }
+            | AuthError::NotExposed => ServiceErrorCode::Forbidden,
+        }
+    }
+}
+
+/// What a request accesses, as far as it can be told from its method, path, and headers.
+#[derive(Debug, Eq, PartialEq)]
+pub(crate) enum Access {
+    /// Reachable without credentials.
+    Public,
+    /// Requires the role on each of the indexes. Index IDs may be patterns.
+    Indexes(AuthRole, Vec<String>),
+    /// Requires the role on all the indexes.
+    Cluster(AuthRole),
+    /// Requires the role on at least one index. The handler checks the indexes named in the
+    /// request body with [`authorize_index`].
+    Handler(AuthRole),
+    /// Internal service of the cluster. Requires the `admin` role on all the indexes, which the
+    /// cluster key grants.
+    Internal,
+    /// Never reachable, even when authentication is disabled.
+    Denied,
+}
+
+impl Access {
+    fn role_opt(&self) -> Option<AuthRole> {
+        match self {
+            Access::Public | Access::Denied => None,
+            Access::Indexes(role, _) | Access::Cluster(role) | Access::Handler(role) => Some(*role),
+            Access::Internal => Some(AuthRole::Admin),
+        }
+    }
+}
+
+/// Authenticates requests with static API keys, the cluster key, and JWT bearer tokens, and checks
+/// that their principal is granted the access they require.
+pub(crate) struct Authenticator {
+    api_keys: Vec<(String, Principal)>,
+    jwt_validator_opt: Option<JwtValidator>,
+}
+
+impl Authenticator {
+    /// Returns `None` when authentication is disabled.
+    pub fn from_config(auth_config: &AuthConfig) -> Option<Self> {
+        if !auth_config.is_enabled() {
+            return None;
+        }
+        let api_keys = auth_config
+            .api_keys
+            .iter()
+            .map(|api_key| {
+                let tenant_scope_opt = api_key.tenant_id.as_ref().map(|tenant_id| TenantScope {
+                    field_name: auth_config.tenant_field.clone(),
+                    tenant_id: tenant_id.clone(),
+                });
+                let principal = Principal {
+                    name: api_key.name.clone(),
+                    grants: api_key.roles.clone(),
+                    tenant_scope_opt,
+                };
+                (api_key.key.clone(), principal)
+            })
+            .chain(auth_config.cluster_key.iter().map(|cluster_key| {
+                let principal = Principal {
+                    name: CLUSTER_PRINCIPAL_NAME.to_string(),
+                    grants: vec![AuthGrant {
+                        role: AuthRole::Admin,
+                        index_id_pattern: "*".to_string(),
+                    }],
+                    tenant_scope_opt: None,
+                };
+                (cluster_key.clone(), principal)
+            }))
+            .collect();
+        let jwt_validator_opt = auth_config
+            .jwt
+            .clone()
+            .map(|jwt_config| JwtValidator::new(jwt_config, auth_config.tenant_field.clone()));
+        Some(Self {
+            api_keys,
+            jwt_validator_opt,
+        })
+    }
+
+    async fn authenticate(&self, authorization_opt: Option<&str>) -> Result<Principal, AuthError> {
+        let authorization = authorization_opt.ok_or(AuthError::MissingCredentials)?;
+        let token = authorization
+            .split_once(' ')
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+            .map(|(_, token)| token.trim())
+            .ok_or_else(|| {
+                AuthError::InvalidCredentials("unsupported authorization scheme".to_string())
+            })?;
+        // Every key is compared in constant time so that the response time does not leak the
+        // position of the key in the list.
+        let mut principal_opt = None;
+
+        for (key, principal) in &self.api_keys {
+            if verify_slices_are_equal(key.as_bytes(), token.as_bytes()).is_ok() {
+                principal_opt = Some(principal);
+            }
+        }
+        if let Some(principal) = principal_opt {
+            return Ok(principal.clone());
+        }
+        if let Some(jwt_validator) = &self.jwt_validator_opt {
+            if token.matches('.').count() == 2 {
+                return jwt_validator.validate(token).await;
+            }
+        }
+        Err(AuthError::InvalidCredentials("unknown API key".to_string()))
+    }
+
+    /// Authenticates the caller of a request and checks that it is granted `access`. Returns
+    /// `None` for public requests.
+    pub async fn authorize(
+        &self,
+        access: &Access,
+        authorization_opt: Option<&str>,
+    ) -> Result<Option<Principal>, AuthError> {
+        match access {
+            Access::Public => return Ok(None),
+            Access::Denied => return Err(AuthError::NotExposed),
+            _ => {}
+        }
+        let principal = self.authenticate(authorization_opt).await?;
+
+        let forbidden = |role: AuthRole, resource: String| AuthError::Forbidden {
+            principal: principal.name.clone(),
+            role,
+            resource,
+        };
+        match access {
+            Access::Public | Access::Denied => {}
+            Access::Indexes(role, index_ids) => {
+                for index_id in index_ids {
+                    if !principal.is_granted(*role, index_id) {
+                        return Err(forbidden(*role, format!("index `{index_id}`")));
+                    }
+                }
+            }
+            Access::Cluster(role) => {
+                if !principal.is_granted(*role, "*") {
+                    return Err(forbidden(*role, "all indexes".to_string()));
+                }
+            }
+            Access::Internal => {
+                if !principal.is_granted(AuthRole::Admin, "*") {
+                    return Err(forbidden(AuthRole::Admin, "all indexes".to_string()));
+                }
+            }
+            Access::Handler(role) => {
+                if !principal.is_granted_on_any_index(*role) {
+                    return Err(forbidden(*role, "any index".to_string()));
+                }
+            }
+        }
+        Ok(Some(principal))
+    }
+}
+
+/// Extracts the principal authenticated by the [`RestAuthLayer`], if any.
+pub(crate) fn with_principal(
+) -> impl Filter<Extract = (Option<Principal>,), Error = Infallible> + Clone {
+    warp::ext::optional::<Principal>()
+}
+
+/// Checks that a principal restricted to a tenant only sends ingest requests and requests whose
+/// handler filters documents per tenant, as told by `filters_tenant`.
+pub(crate) fn authorize_tenant(
+    principal: &Principal,
+    access: &Access,
+    path: &str,
+    filters_tenant: bool,
+) -> Result<(), AuthError> {
+    if principal.tenant_scope_opt.is_none()
+        || filters_tenant
+        || matches!(access.role_opt(), None | Some(AuthRole::Ingest))
+    {
+        return Ok(());
+    }
+    Err(AuthError::TenantRestricted {
+        principal: principal.name.clone(),
+        path: path.to_string(),
+    })
+}
+
+/// Restricts `query_ast` to the documents of the tenant of the principal of a request, if any.
+pub(crate) fn restrict_to_tenant(
+    principal_opt: Option<&Principal>,
+    query_ast: QueryAst,
+) -> QueryAst {
+    match principal_opt.and_then(|principal| principal.tenant_scope_opt.as_ref()) {
+        Some(tenant_scope) => tenant_scope.restrict(query_ast),
+        None => query_ast,
+    }
+}
+
+/// Checks that the principal of a request is granted `role` on an index named in the request
+/// body. Requests without principal went through when authentication is disabled.
+pub(crate) fn authorize_index(
+    principal_opt: Option<&Principal>,
+    role: AuthRole,
+    index_id: &str,
+) -> Result<(), AuthError> {
+    match principal_opt {
+        Some(principal) if !principal.is_granted(role, index_id) => Err(AuthError::Forbidden {
+            principal: principal.name.clone(),
+            role,
+            resource: format!("index `{index_id}`"),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Whether `pattern` matches `index_id`, `*` matching any sequence of characters.
+pub(crate) fn matches_pattern(pattern: &str, index_id: &str) -> bool {
+    let pattern = pattern.as_bytes();
+    let index_id = index_id.as_bytes();
+    let (mut pattern_pos, mut index_id_pos) = (0, 0);
+    // Position of the last `*` in the pattern and of the character of the index ID it resumes
+    // matching from when backtracking.
+    let mut backtrack_opt: Option<(usize, usize)> = None;
+
+    while index_id_pos < index_id.len() {
+        if pattern_pos < pattern.len() && pattern[pattern_pos] == b'*' {
+            backtrack_opt = Some((pattern_pos, index_id_pos));
+            pattern_pos += 1;
+        } else if pattern_pos < pattern.len() && pattern[pattern_pos] == index_id[index_id_pos] {
+            pattern_pos += 1;
+            index_id_pos += 1;
+        } else if let Some((star_pos, resume_pos)) = backtrack_opt {
+            backtrack_opt = Some((star_pos, resume_pos + 1));
+            pattern_pos = star_pos + 1;
+            index_id_pos = resume_pos + 1;
+        } else {
+            return false;
+        }
+    }
+    pattern[pattern_pos..].iter().all(|ch| *ch == b'*')
+}
+
+/// Whether every index ID matched by `requested_pattern` is matched by `grant_pattern`. The
+/// check is conservative for requested patterns: only `*` and the grant patterns ending with
+/// their only `*` can cover them.
+fn pattern_covers(grant_pattern: &str, requested_pattern: &str) -> bool {
+    if !requested_pattern.contains('*') {
+        return matches_pattern(grant_pattern, requested_pattern);
+    }
+    if grant_pattern == "*" || grant_pattern == requested_pattern {
+        return true;
+    }
+    match grant_pattern.strip_suffix('*') {
+        Some(grant_prefix) if !grant_prefix.contains('*') => {
+            requested_pattern.starts_with(grant_prefix)
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_config::{ApiKeyConfig, JwtConfig};
+
+    use super::*;
+
+    pub(super) const SEARCH_KEY: &str = "search-0123456789abcdef";
+    pub(super) const ADMIN_KEY: &str = "admin-0123456789abcdef";
+    pub(super) const TEAM_A_KEY: &str = "team-a-0123456789abcdef";
+    pub(super) const ACME_KEY: &str = "acme-0123456789abcdef";
+    pub(super) const CLUSTER_KEY: &str = "cluster-0123456789abcdef";
+
+    pub(super) fn authenticator() -> Authenticator {
+        let auth_config = AuthConfig {
+            api_keys: vec![
+                ApiKeyConfig {
+                    name: "grafana".to_string(),
+                    key: SEARCH_KEY.to_string(),
+                    roles: vec!["search".parse().unwrap()],
+                    tenant_id: None,
+                },
+                ApiKeyConfig {
+                    name: "ops".to_string(),
+                    key: ADMIN_KEY.to_string(),
+                    roles: vec!["admin".parse().unwrap()],
+                    tenant_id: None,
+                },
+                ApiKeyConfig {
+                    name: "team-a".to_string(),
+                    key: TEAM_A_KEY.to_string(),
+                    roles: vec![
+                        "search:team-a-*".parse().unwrap(),
+                        "ingest:team-a-logs".parse().unwrap(),
+                    ],
+                    tenant_id: None,
+                },
+                ApiKeyConfig {
+                    name: "acme".to_string(),
+                    key: ACME_KEY.to_string(),
+                    roles: vec!["admin:shared-*".parse().unwrap()],
+                    tenant_id: Some("acme".to_string()),
+                },
+            ],
+            cluster_key: Some(CLUSTER_KEY.to_string()),
+            ..Default::default()
+        };
+        Authenticator::from_config(&auth_config).unwrap()
+    }
+
+    fn bearer(key: &str) -> Option<String> {
+        Some(format!("Bearer {key}"))
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("*", "logs"));
+        assert!(matches_pattern("logs", "logs"));
+        assert!(!matches_pattern("logs", "logs-a"));
+        assert!(matches_pattern("logs-*", "logs-a"));
+        assert!(matches_pattern("logs-*", "logs-"));
+        assert!(!matches_pattern("logs-*", "logs"));
+        assert!(matches_pattern("*-logs", "team-a-logs"));
+        assert!(matches_pattern("team-*-logs", "team-a-logs"));
+        assert!(matches_pattern("team-*-logs", "team-a-b-logs"));
+        assert!(!matches_pattern("team-*-logs", "team-a-traces"));
+        assert!(matches_pattern("t*a*s", "team-a-logs"));
+    }
+
+    #[test]
+    fn test_pattern_covers() {
+        assert!(pattern_covers("*", "logs-*"));
+        assert!(pattern_covers("logs-*", "logs-*"));
+        assert!(pattern_covers("logs-*", "logs-team-*"));
+        assert!(!pattern_covers("logs-*", "logs*"));
+        assert!(!pattern_covers("logs-a", "logs-*"));
+        assert!(!pattern_covers("*-logs", "team-*-logs"));
+    }
+
+    #[tokio::test]
+    async fn test_authenticator_authenticate() {
+        let authenticator = authenticator();
+
+        let principal = authenticator
+            .authenticate(bearer(SEARCH_KEY).as_deref())
+            .await
+            .unwrap();
+        assert_eq!(principal.name, "grafana");
+
+        let principal = authenticator
+            .authenticate(Some(&format!("bearer {ADMIN_KEY}")))
+            .await
+            .unwrap();
+        assert_eq!(principal.name, "ops");
+
+        let principal = authenticator
+            .authenticate(bearer(CLUSTER_KEY).as_deref())
+            .await
+            .unwrap();
+        assert_eq!(principal.name, "cluster");
+        assert!(principal.is_granted(AuthRole::Admin, "*"));
+
+        let error = authenticator.authenticate(None).await.unwrap_err();
+        assert!(matches!(error, AuthError::MissingCredentials));
+
+        let error = authenticator
+            .authenticate(Some(&format!("Basic {SEARCH_KEY}")))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AuthError::InvalidCredentials(_)));
+
+        let error = authenticator
+            .authenticate(bearer("search-0123456789abcdeX").as_deref())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, AuthError::InvalidCredentials(_)));
+    }
+
+    #[tokio::test]
+    async fn test_authenticator_authorize() {
+        let authenticator = authenticator();
+        let team_a = bearer(TEAM_A_KEY);
+
+        let principal_opt = authenticator
+            .authorize(&Access::Public, None)
+            .await
+            .unwrap();
+        assert!(principal_opt.is_none());
+
+        let access = Access::Indexes(AuthRole::Search, vec!["team-a-logs".to_string()]);
+        let principal = authenticator
+            .authorize(&access, team_a.as_deref())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(principal.name, "team-a");
+
+        let access = Access::Indexes(AuthRole::Search, vec!["team-b-logs".to_string()]);
+        let error = authenticator
+            .authorize(&access, team_a.as_deref())
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Principal `team-a` is not granted the `search` role on index `team-b-logs`."
+        );
+
+        let access = Access::Indexes(AuthRole::Ingest, vec!["team-a-traces".to_string()]);
+        authenticator
+            .authorize(&access, team_a.as_deref())
+            .await
+            .unwrap_err();
+
+        authenticator
+            .authorize(&Access::Handler(AuthRole::Ingest), team_a.as_deref())
+            .await
+            .unwrap();
+        authenticator
+            .authorize(&Access::Handler(AuthRole::Admin), team_a.as_deref())
+            .await
+            .unwrap_err();
+        authenticator
+            .authorize(&Access::Cluster(AuthRole::Search), team_a.as_deref())
+            .await
+            .unwrap_err();
+        authenticator
+            .authorize(
+                &Access::Cluster(AuthRole::Search),
+                bearer(SEARCH_KEY).as_deref(),
+            )
+            .await
+            .unwrap();
+        authenticator
+            .authorize(
+                &Access::Cluster(AuthRole::Ingest),
+                bearer(ADMIN_KEY).as_deref(),
+            )
+            .await
+            .unwrap();
+
+        let error = authenticator
+            .authorize(
+                &Access::Cluster(AuthRole::Admin),
+                bearer(SEARCH_KEY).as_deref(),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(error.status_code().to_http_status_code(), 403);
+    }
+
+    #[tokio::test]
+    async fn test_authorize_index() {
+        let principal = authenticator()
+            .authenticate(bearer(TEAM_A_KEY).as_deref())
+            .await
+            .unwrap();
+        authorize_index(Some(&principal), AuthRole::Ingest, "team-a-logs").unwrap();
+        authorize_index(Some(&principal), AuthRole::Ingest, "team-b-logs").unwrap_err();
+        authorize_index(None, AuthRole::Ingest, "team-b-logs").unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_authorize_tenant() {
+        let authenticator = authenticator();
+        let principal = authenticator
+            .authenticate(bearer(ACME_KEY).as_deref())
+            .await
+            .unwrap();
+        assert_eq!(
+            principal.tenant_scope_opt,
+            Some(TenantScope {
+                field_name: "tenant_id".to_string(),
+                tenant_id: "acme".to_string(),
+            })
+        );
+        let search_access = Access::Indexes(AuthRole::Search, vec!["shared-logs".to_string()]);
+        authorize_tenant(
+            &principal,
+            &search_access,
+            "/api/v1/shared-logs/search",
+            true,
+        )
+        .unwrap();
+
+        let error = authorize_tenant(
+            &principal,
+            &search_access,
+            "/api/v1/shared-logs/tail",
+            false,
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Principal `acme` is restricted to a tenant and cannot call \
+             `/api/v1/shared-logs/tail`, which does not filter documents per tenant."
+        );
+        assert_eq!(error.status_code().to_http_status_code(), 403);
+
+        let ingest_access = Access::Indexes(AuthRole::Ingest, vec!["shared-logs".to_string()]);
+        authorize_tenant(
+            &principal,
+            &ingest_access,
+            "/api/v1/shared-logs/ingest",
+            false,
+        )
+        .unwrap();
+
+        // Principals not restricted to a tenant are not concerned.
+        let principal = authenticator
+            .authenticate(bearer(SEARCH_KEY).as_deref())
+            .await
+            .unwrap();
+        assert!(principal.tenant_scope_opt.is_none());
+        authorize_tenant(
+            &principal,
+            &search_access,
+            "/api/v1/shared-logs/tail",
+            false,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_restrict_to_tenant() {
+        let query_ast = QueryAst::MatchAll;
+        assert_eq!(restrict_to_tenant(None, query_ast.clone()), query_ast);
+
+        let mut principal = Principal {
+            name: "acme".to_string(),
+            grants: Vec::new(),
+            tenant_scope_opt: None,
+        };
+        assert_eq!(
+            restrict_to_tenant(Some(&principal), query_ast.clone()),
+            query_ast
+        );
+        principal.tenant_scope_opt = Some(TenantScope {
+            field_name: "tenant_id".to_string(),
+            tenant_id: "acme".to_string(),
+        });
+        let expected_query_ast: QueryAst = BoolQuery {
+            must: vec![QueryAst::MatchAll],
+            filter: vec![TermQuery {
+                field: "tenant_id".to_string(),
+                value: "acme".to_string(),
+            }
+            .into()],
+            ..Default::default()
+        }
+        .into();
+        assert_eq!(
+            restrict_to_tenant(Some(&principal), query_ast),
+            expected_query_ast
+        );
+    }
+
+    #[test]
+    fn test_authenticator_disabled() {
+        assert!(Authenticator::from_config(&AuthConfig::default()).is_none());
+
+        let jwt_config: JwtConfig =
+            serde_json::from_str(r#"{"issuer": "https://accounts.example.com"}"#).unwrap();
+        let auth_config = AuthConfig {
+            jwt: Some(jwt_config),
+            ..Default::default()
+        };
+        assert!(Authenticator::from_config(&auth_config).is_some());
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use quickwit_config::{ClusterSettings, RateLimitConfig};
+use quickwit_metastore::{Metastore, MetastoreError, MetastoreResult};
+use quickwit_proto::{ServiceError, ServiceErrorCode};
+use tokio::sync::Mutex;
+use tracing::{info, warn};
+
+use crate::admin_api::RuntimeSettingsService;
+use crate::rate_limit::RateLimiter;
+
+/// Interval at which the nodes fetch the cluster settings from the metastore.
+const CLUSTER_SETTINGS_REFRESH_INTERVAL: Duration = if cfg!(any(test, feature = "testsuite")) {
+    Duration::from_millis(50)
+} else {
+    Duration::from_secs(30)
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum ClusterSettingsError {
+    #[error("Invalid cluster settings: {0}.")]
+    InvalidSettings(String),
+    #[error(transparent)]
+    Metastore(#[from] MetastoreError),
+}
+
+impl ServiceError for ClusterSettingsError {
+    fn status_code(&self) -> ServiceErrorCode {
+        match self {
+            ClusterSettingsError::InvalidSettings(_) => ServiceErrorCode::BadRequest,
+            ClusterSettingsError::Metastore(error) => error.status_code(),
+        }
+    }
+}
+
+/// Keeps the cluster settings of the node in sync with the ones stored in the metastore and
+/// applies them to the services of the node. The settings updated on another node are picked up
+/// at the next refresh.
+pub(crate) struct ClusterSettingsService {
+    metastore: Arc<dyn Metastore>,
+    node_rate_limit_config: RateLimitConfig,
+    rate_limiter: Arc<RateLimiter>,
+    runtime_settings_service: Arc<RuntimeSettingsService>,
+    settings: Mutex<ClusterSettings>,
+}
+
+impl ClusterSettingsService {
+    pub fn new(
+        metastore: Arc<dyn Metastore>,
+        node_rate_limit_config: RateLimitConfig,
+        rate_limiter: Arc<RateLimiter>,
+        runtime_settings_service: Arc<RuntimeSettingsService>,
+    ) -> Self {
+        Self {
+            metastore,
+            node_rate_limit_config,
+            rate_limiter,
+            runtime_settings_service,
+            settings: Mutex::default(),
+        }
+    }
+
+    /// Returns the cluster settings stored in the metastore.
+    pub async fn settings(&self) -> Result<ClusterSettings, ClusterSettingsError> {
+        let settings = self.metastore.cluster_settings().await?;
+        Ok(settings)
+    }
+
+    /// Replaces the cluster settings stored in the metastore and applies them to the node right
+    /// away. The settings left unset fall back to the node and index configs.
+    pub async fn update(
+        &self,
+        new_settings: ClusterSettings,
+    ) -> Result<ClusterSettings, ClusterSettingsError> {
+        new_settings
+            .validate()
+            .map_err(|error| ClusterSettingsError::InvalidSettings(error.to_string()))?;
+        self.metastore
+            .update_cluster_settings(new_settings.clone())
+            .await?;
+        self.apply(new_settings.clone()).await;
+        Ok(new_settings)
+    }
+
+    /// Fetches the cluster settings from the metastore and applies them if they changed.
+    pub async fn refresh(&self) -> MetastoreResult<()> {
+        let settings = self.metastore.cluster_settings().await?;
+        self.apply(settings).await;
+        Ok(())
+    }
+
+    async fn apply(&self, new_settings: ClusterSettings) {
+        let mut settings = self.settings.lock().await;
+
+        if *settings == new_settings {
+            return;
+        }
+        let rate_limit_config = new_settings
+            .rate_limits
+            .clone()
+            .unwrap_or_else(|| self.node_rate_limit_config.clone());
+        self.rate_limiter.set_config(rate_limit_config);
+        self.runtime_settings_service
+            .set_cluster_max_merge_write_throughput(new_settings.max_merge_write_throughput)
+            .await;
+        info!(settings=?new_settings, "Applied cluster settings.");
+        *settings = new_settings;
+    }
+}
+
+/// Periodically refreshes the cluster settings of the node.
+pub(crate) async fn cluster_settings_refresh_task(
+    cluster_settings_service: Arc<ClusterSettingsService>,
+) {
+    let mut interval = tokio::time::interval(CLUSTER_SETTINGS_REFRESH_INTERVAL);
+
+    loop {
+        interval.tick().await;
+
+        if let Err(error) = cluster_settings_service.refresh().await {
+            warn!(error=?error, "Failed to refresh cluster settings.");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::NonZeroU32;
+
+    use byte_unit::Byte;
+    use quickwit_config::{AuthRole, RateLimits, SearcherConfig};
+    use quickwit_metastore::metastore_for_test;
+    use quickwit_search::SearcherContext;
+
+    use super::*;
+    use crate::admin_api::RuntimeHooks;
+    use crate::auth::Access;
+
+    #[tokio::test]
+    async fn test_cluster_settings_service() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let metastore = metastore_for_test();
+        let rate_limiter = Arc::new(RateLimiter::new(RateLimitConfig::default()));
+        let searcher_context = Arc::new(SearcherContext::new(SearcherConfig::default()));
+        let runtime_settings_service = RuntimeSettingsService::load(
+            data_dir.path(),
+            10,
+            searcher_context,
+            None,
+            RuntimeHooks::default(),
+        )
+        .await
+        .map(Arc::new)
+        .unwrap();
+        let cluster_settings_service = ClusterSettingsService::new(
+            metastore.clone(),
+            RateLimitConfig::default(),
+            rate_limiter.clone(),
+            runtime_settings_service,
+        );
+        cluster_settings_service.refresh().await.unwrap();
+        assert_eq!(
+            cluster_settings_service.settings().await.unwrap(),
+            ClusterSettings::default()
+        );
+
+        let error = cluster_settings_service
+            .update(ClusterSettings {
+                max_merge_write_throughput: Some(Byte::from_bytes(0)),
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ClusterSettingsError::InvalidSettings(_)));
+
+        // The settings updated by another node are applied on refresh.
+        let mut rate_limits = RateLimitConfig::default();
+        rate_limits.principals.insert(
+            "*".to_string(),
+            RateLimits {
+                requests_per_sec: Some(NonZeroU32::new(1).unwrap()),
+                ..Default::default()
+            },
+        );
+        let settings = ClusterSettings {
+            rate_limits: Some(rate_limits),
+            ..Default::default()
+        };
+        metastore
+            .update_cluster_settings(settings.clone())
+            .await
+            .unwrap();
+        let access = Access::Cluster(AuthRole::Admin);
+        rate_limiter.acquire(Some("vector"), &access, 0).unwrap();
+        rate_limiter.acquire(Some("vector"), &access, 0).unwrap();
+
+        cluster_settings_service.refresh().await.unwrap();
+        assert_eq!(cluster_settings_service.settings().await.unwrap(), settings);
+        rate_limiter.acquire(Some("vector"), &access, 0).unwrap();
+        rate_limiter
+            .acquire(Some("vector"), &access, 0)
+            .unwrap_err();
+
+        // Unsetting the cluster rate limits restores the ones of the node config.
+        cluster_settings_service
+            .update(ClusterSettings::default())
+            .await
+            .unwrap();
+        rate_limiter.acquire(Some("vector"), &access, 0).unwrap();
+    }
+}
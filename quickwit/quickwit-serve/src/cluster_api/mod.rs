@@ -17,6 +17,12 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+mod cluster_settings;
 mod rest_handler;
 
-pub use rest_handler::{cluster_handler, ClusterApi};
+pub(crate) use cluster_settings::{cluster_settings_refresh_task, ClusterSettingsService};
+pub(crate) use rest_handler::cluster_settings_handler;
+pub use rest_handler::{
+    cluster_handler, cluster_nodes_handler, decommission_node_handler, indexer_autoscaling_handler,
+    indexing_tasks_handler, node_decommission_status_handler, ClusterApi, NodeDecommissionStatus,
+};
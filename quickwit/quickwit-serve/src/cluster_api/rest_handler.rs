@@ -17,21 +17,248 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::{BTreeMap, HashMap};
 use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 
-use quickwit_cluster::{Cluster, ClusterSnapshot, NodeIdSchema};
+use quickwit_cluster::{Cluster, ClusterNode, ClusterSnapshot, DecommissionStatus, NodeIdSchema};
+use quickwit_config::service::QuickwitService;
+use quickwit_config::ClusterSettings;
+use quickwit_control_plane::{
+    ControlPlaneError, ControlPlaneService, ControlPlaneServiceClient,
+    GetIndexerAutoscalingRequest, GetIndexerAutoscalingResponse, GetIndexingPlanRequest,
+};
+use quickwit_proto::{ServiceError, ServiceErrorCode};
+use serde::{Deserialize, Serialize};
 use warp::{Filter, Rejection};
 
+use super::cluster_settings::{ClusterSettingsError, ClusterSettingsService};
 use crate::format::extract_format_from_qs;
 use crate::json_api_response::make_json_api_response;
+use crate::with_arg;
+
+/// Maximum duration to wait for the control plane to respond.
+const CONTROL_PLANE_REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
 
 #[derive(utoipa::OpenApi)]
 #[openapi(
-    paths(get_cluster),
-    components(schemas(ClusterSnapshot, NodeIdSchema,))
+    paths(
+        get_cluster,
+        get_cluster_nodes,
+        get_indexing_tasks,
+        get_indexer_autoscaling,
+        decommission_node,
+        get_node_decommission_status,
+        get_cluster_settings,
+        update_cluster_settings,
+    ),
+    components(schemas(
+        ClusterSnapshot,
+        NodeIdSchema,
+        ClusterNodeInfo,
+        IndexingPipelines,
+        IndexingTasksResponse,
+        IndexerIndexingTasks,
+        GetIndexerAutoscalingResponse,
+        DecommissionStatus,
+        NodeDecommissionStatus,
+    ))
 )]
 pub struct ClusterApi;
 
+#[derive(Debug, thiserror::Error)]
+pub enum ClusterApiError {
+    #[error("Node `{0}` is not a live member of the cluster.")]
+    NodeNotFound(String),
+}
+
+impl ServiceError for ClusterApiError {
+    fn status_code(&self) -> ServiceErrorCode {
+        match self {
+            ClusterApiError::NodeNotFound(_) => ServiceErrorCode::NotFound,
+        }
+    }
+}
+
+/// A node of the cluster, as seen by the node handling the request.
+#[derive(Debug, Eq, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct ClusterNodeInfo {
+    /// The unique ID of the node in the cluster.
+    pub node_id: String,
+    /// A numeric identifier incremented every time the node leaves and rejoins the cluster.
+    pub generation_id: u64,
+    /// The services enabled on the node.
+    pub enabled_services: Vec<String>,
+    /// The socket address peers use to gossip with the node.
+    #[schema(value_type = String)]
+    pub gossip_advertise_addr: SocketAddr,
+    /// The socket address peers use to communicate with the node via gRPC.
+    #[schema(value_type = String)]
+    pub grpc_advertise_addr: SocketAddr,
+    /// The availability zone the node runs in, if configured.
+    pub availability_zone: Option<String>,
+    /// Whether the node is ready to handle requests.
+    pub is_ready: bool,
+    /// Whether the node runs normally, is being decommissioned, or can be terminated safely.
+    pub decommission_status: DecommissionStatus,
+    /// The indexing pipelines running on the node.
+    pub indexing_pipelines: Vec<IndexingPipelines>,
+}
+
+/// The decommission status of a node.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct NodeDecommissionStatus {
+    /// The unique ID of the node in the cluster.
+    pub node_id: String,
+    /// Whether the node runs normally, is being decommissioned, or can be terminated safely.
+    pub decommission_status: DecommissionStatus,
+}
+
+impl From<&ClusterNode> for ClusterNodeInfo {
+    fn from(node: &ClusterNode) -> Self {
+        let chitchat_id = node.chitchat_id();
+        let mut enabled_services: Vec<String> = node
+            .enabled_services()
+            .iter()
+            .map(|service| service.as_str().to_string())
+            .collect();
+        enabled_services.sort();
+        let indexing_pipelines = group_indexing_pipelines(
+            node.indexing_tasks()
+                .iter()
+                .map(|indexing_task| (&indexing_task.index_uid, &indexing_task.source_id)),
+        );
+        Self {
+            node_id: chitchat_id.node_id.clone(),
+            generation_id: chitchat_id.generation_id,
+            enabled_services,
+            gossip_advertise_addr: chitchat_id.gossip_advertise_addr,
+            grpc_advertise_addr: node.grpc_advertise_addr(),
+            availability_zone: node.availability_zone_opt().map(str::to_string),
+            is_ready: node.is_ready(),
+            decommission_status: node.decommission_status(),
+            indexing_pipelines,
+        }
+    }
+}
+
+/// Pipelines indexing a source of an index.
+#[derive(Debug, Eq, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct IndexingPipelines {
+    pub index_uid: String,
+    pub source_id: String,
+    pub num_pipelines: usize,
+}
+
+/// The indexing pipelines planned by the control plane and running on the indexers.
+#[derive(Debug, Eq, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct IndexingTasksResponse {
+    /// The number of indexing plans applied by the control plane since it started.
+    pub num_applied_plans: u64,
+    /// The number of seconds elapsed since the control plane applied the last indexing plan.
+    /// Absent if no plan was applied yet.
+    pub last_applied_plan_elapsed_secs: Option<u64>,
+    /// The indexers running or planned to run indexing pipelines, ordered by node ID.
+    pub indexers: Vec<IndexerIndexingTasks>,
+}
+
+/// The indexing pipelines planned and running on an indexer.
+#[derive(Debug, Eq, PartialEq, Serialize, utoipa::ToSchema)]
+pub struct IndexerIndexingTasks {
+    pub node_id: String,
+    /// Whether the indexer is a ready member of the cluster. The control plane only plans
+    /// pipelines on ready indexers.
+    pub is_ready: bool,
+    /// The pipelines assigned to the indexer by the last indexing plan.
+    pub planned_pipelines: Vec<IndexingPipelines>,
+    /// The pipelines running on the indexer.
+    pub running_pipelines: Vec<IndexingPipelines>,
+}
+
+fn group_indexing_pipelines<'a>(
+    indexing_tasks: impl Iterator<Item = (&'a String, &'a String)>,
+) -> Vec<IndexingPipelines> {
+    let mut num_pipelines_per_source: BTreeMap<(&String, &String), usize> = BTreeMap::new();
+
+    for index_source_id in indexing_tasks {
+        *num_pipelines_per_source.entry(index_source_id).or_default() += 1;
+    }
+    num_pipelines_per_source
+        .into_iter()
+        .map(
+            |((index_uid, source_id), num_pipelines)| IndexingPipelines {
+                index_uid: index_uid.clone(),
+                source_id: source_id.clone(),
+                num_pipelines,
+            },
+        )
+        .collect()
+}
+
+/// Cluster nodes handler.
+pub fn cluster_nodes_handler(
+    cluster: Cluster,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("cluster" / "nodes")
+        .and(warp::get())
+        .and(with_arg(cluster))
+        .then(get_cluster_nodes)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+/// Indexing tasks handler.
+pub fn indexing_tasks_handler(
+    cluster: Cluster,
+    control_plane_service: ControlPlaneServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("cluster" / "indexing-tasks")
+        .and(warp::get())
+        .and(with_arg(cluster))
+        .and(with_arg(control_plane_service))
+        .then(get_indexing_tasks)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+/// Indexer autoscaling handler.
+pub fn indexer_autoscaling_handler(
+    control_plane_service: ControlPlaneServiceClient,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("cluster" / "indexer-autoscaling")
+        .and(warp::get())
+        .and(with_arg(control_plane_service))
+        .then(get_indexer_autoscaling)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+/// Node decommission handler.
+pub fn decommission_node_handler(
+    cluster: Cluster,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("cluster" / "nodes" / String / "decommission")
+        .and(warp::put())
+        .and(with_arg(cluster))
+        .then(decommission_node)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+/// Node decommission status handler.
+pub fn node_decommission_status_handler(
+    cluster: Cluster,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("cluster" / "nodes" / String / "decommission")
+        .and(warp::get())
+        .and(with_arg(cluster))
+        .then(get_node_decommission_status)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
 /// Cluster handler.
 pub fn cluster_handler(
     cluster: Cluster,
@@ -59,3 +286,517 @@ async fn get_cluster(cluster: Cluster) -> Result<ClusterSnapshot, Infallible> {
     let snapshot = cluster.snapshot().await;
     Ok(snapshot)
 }
+
+#[utoipa::path(
+    get,
+    tag = "Cluster Info",
+    path = "/cluster/nodes",
+    responses(
+        (status = 200, description = "Successfully fetched the cluster nodes.", body = [ClusterNodeInfo])
+    )
+)]
+/// Get the live nodes of the cluster.
+///
+/// Returns the enabled services, the readiness, and the running indexing pipelines of each live
+/// node of the cluster.
+async fn get_cluster_nodes(cluster: Cluster) -> Result<Vec<ClusterNodeInfo>, Infallible> {
+    let cluster_nodes = cluster
+        .live_nodes()
+        .await
+        .iter()
+        .map(ClusterNodeInfo::from)
+        .collect();
+    Ok(cluster_nodes)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Cluster Info",
+    path = "/cluster/indexing-tasks",
+    responses(
+        (status = 200, description = "Successfully fetched the indexing tasks.", body = IndexingTasksResponse)
+    )
+)]
+/// Get the indexing tasks of the cluster.
+///
+/// Returns the indexing pipelines assigned to each indexer by the last indexing plan of the
+/// control plane, next to the pipelines actually running on the indexer.
+async fn get_indexing_tasks(
+    cluster: Cluster,
+    mut control_plane_service: ControlPlaneServiceClient,
+) -> Result<IndexingTasksResponse, ControlPlaneError> {
+    let indexing_plan = tokio::time::timeout(
+        CONTROL_PLANE_REQUEST_TIMEOUT,
+        control_plane_service.get_indexing_plan(GetIndexingPlanRequest {}),
+    )
+    .await
+    .map_err(|_| ControlPlaneError::Unavailable("request timed out".to_string()))??;
+    let live_indexers: Vec<ClusterNodeInfo> = cluster
+        .live_nodes()
+        .await
+        .iter()
+        .filter(|node| node.enabled_services().contains(&QuickwitService::Indexer))
+        .map(ClusterNodeInfo::from)
+        .collect();
+    let mut planned_pipelines_per_node_id: HashMap<String, Vec<IndexingPipelines>> = indexing_plan
+        .node_indexing_tasks
+        .iter()
+        .map(|node_indexing_tasks| {
+            let planned_pipelines = group_indexing_pipelines(
+                node_indexing_tasks
+                    .indexing_tasks
+                    .iter()
+                    .map(|indexing_task| (&indexing_task.index_uid, &indexing_task.source_id)),
+            );
+            (node_indexing_tasks.node_id.clone(), planned_pipelines)
+        })
+        .collect();
+    let mut indexers: Vec<IndexerIndexingTasks> = live_indexers
+        .into_iter()
+        .map(|indexer| IndexerIndexingTasks {
+            planned_pipelines: planned_pipelines_per_node_id
+                .remove(&indexer.node_id)
+                .unwrap_or_default(),
+            node_id: indexer.node_id,
+            is_ready: indexer.is_ready,
+            running_pipelines: indexer.indexing_pipelines,
+        })
+        .collect();
+    // Indexers that left the cluster since the last plan was applied.
+    indexers.extend(planned_pipelines_per_node_id.into_iter().map(
+        |(node_id, planned_pipelines)| IndexerIndexingTasks {
+            node_id,
+            is_ready: false,
+            planned_pipelines,
+            running_pipelines: Vec::new(),
+        },
+    ));
+    indexers.sort_by(|left, right| left.node_id.cmp(&right.node_id));
+
+    let indexing_tasks_response = IndexingTasksResponse {
+        num_applied_plans: indexing_plan.num_applied_plans,
+        last_applied_plan_elapsed_secs: indexing_plan.last_applied_plan_elapsed_secs,
+        indexers,
+    };
+    Ok(indexing_tasks_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Cluster Info",
+    path = "/cluster/indexer-autoscaling",
+    responses(
+        (status = 200, description = "Successfully fetched the indexer autoscaling signal.", body = GetIndexerAutoscalingResponse)
+    )
+)]
+/// Get the indexer autoscaling signal of the cluster.
+///
+/// Returns the number of indexers the cluster should run to keep up with its sources, as computed
+/// by the control plane from the lag of the sources and the saturation of the indexing pipelines.
+async fn get_indexer_autoscaling(
+    mut control_plane_service: ControlPlaneServiceClient,
+) -> Result<GetIndexerAutoscalingResponse, ControlPlaneError> {
+    tokio::time::timeout(
+        CONTROL_PLANE_REQUEST_TIMEOUT,
+        control_plane_service.get_indexer_autoscaling(GetIndexerAutoscalingRequest {}),
+    )
+    .await
+    .map_err(|_| ControlPlaneError::Unavailable("request timed out".to_string()))?
+}
+
+async fn find_live_node(cluster: &Cluster, node_id: &str) -> Result<ClusterNode, ClusterApiError> {
+    cluster
+        .live_nodes()
+        .await
+        .into_iter()
+        .find(|node| node.node_id() == node_id)
+        .ok_or_else(|| ClusterApiError::NodeNotFound(node_id.to_string()))
+}
+
+#[utoipa::path(
+    put,
+    tag = "Cluster Info",
+    path = "/cluster/nodes/{node_id}/decommission",
+    responses(
+        (status = 200, description = "Successfully requested the decommission of the node.", body = NodeDecommissionStatus)
+    ),
+    params(
+        ("node_id" = String, Path, description = "The ID of the node to decommission."),
+    )
+)]
+/// Decommission a node.
+///
+/// Requests the decommission of a live node of the cluster. The node leaves the set of ready
+/// nodes: the control plane moves its indexing pipelines to the other indexers and the searchers
+/// stop sending it leaf search jobs. The node then drains its indexing pipelines and waits for its
+/// ongoing searches to complete. The request returns immediately: the node can be terminated once
+/// its decommission status is `decommissioned`.
+async fn decommission_node(
+    node_id: String,
+    cluster: Cluster,
+) -> Result<NodeDecommissionStatus, ClusterApiError> {
+    let node = find_live_node(&cluster, &node_id).await?;
+    cluster.request_node_decommission(node.chitchat_id()).await;
+    Ok(NodeDecommissionStatus {
+        node_id,
+        decommission_status: node.decommission_status(),
+    })
+}
+
+#[utoipa::path(
+    get,
+    tag = "Cluster Info",
+    path = "/cluster/nodes/{node_id}/decommission",
+    responses(
+        (status = 200, description = "Successfully fetched the decommission status of the node.", body = NodeDecommissionStatus)
+    ),
+    params(
+        ("node_id" = String, Path, description = "The ID of the node."),
+    )
+)]
+/// Get the decommission status of a node.
+async fn get_node_decommission_status(
+    node_id: String,
+    cluster: Cluster,
+) -> Result<NodeDecommissionStatus, ClusterApiError> {
+    let node = find_live_node(&cluster, &node_id).await?;
+    Ok(NodeDecommissionStatus {
+        node_id,
+        decommission_status: node.decommission_status(),
+    })
+}
+
+/// Cluster settings handler.
+pub(crate) fn cluster_settings_handler(
+    cluster_settings_service: Arc<ClusterSettingsService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    get_cluster_settings_handler(cluster_settings_service.clone())
+        .or(update_cluster_settings_handler(cluster_settings_service))
+}
+
+fn get_cluster_settings_handler(
+    cluster_settings_service: Arc<ClusterSettingsService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("cluster" / "settings")
+        .and(warp::get())
+        .and(with_arg(cluster_settings_service))
+        .then(get_cluster_settings)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Cluster Info",
+    path = "/cluster/settings",
+    responses(
+        (status = 200, description = "Successfully fetched the cluster settings.", body = ClusterSettings)
+    )
+)]
+/// Get the cluster settings.
+///
+/// Returns the settings shared by all the nodes of the cluster. Settings absent from the response
+/// take their value from the node and index configs.
+async fn get_cluster_settings(
+    cluster_settings_service: Arc<ClusterSettingsService>,
+) -> Result<ClusterSettings, ClusterSettingsError> {
+    cluster_settings_service.settings().await
+}
+
+fn update_cluster_settings_handler(
+    cluster_settings_service: Arc<ClusterSettingsService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("cluster" / "settings")
+        .and(warp::put())
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::body::json())
+        .and(with_arg(cluster_settings_service))
+        .then(update_cluster_settings)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Cluster Info",
+    path = "/cluster/settings",
+    request_body = ClusterSettings,
+    responses(
+        (status = 200, description = "Successfully updated the cluster settings.", body = ClusterSettings)
+    )
+)]
+/// Update the cluster settings.
+///
+/// Replaces the settings shared by all the nodes of the cluster. The settings are stored in the
+/// metastore, applied right away on the node handling the request, and picked up by the other
+/// nodes within 30 seconds. Settings absent from the request are restored to their value from
+/// the node and index configs.
+async fn update_cluster_settings(
+    settings: ClusterSettings,
+    cluster_settings_service: Arc<ClusterSettingsService>,
+) -> Result<ClusterSettings, ClusterSettingsError> {
+    cluster_settings_service.update(settings).await
+}
+
+#[cfg(test)]
+mod tests {
+    use chitchat::transport::ChannelTransport;
+    use futures::StreamExt;
+    use quickwit_cluster::{create_cluster_for_test, grpc_addr_from_listen_addr_for_test};
+    use quickwit_common::test_utils::wait_until_predicate;
+    use quickwit_control_plane::{GetIndexingPlanResponse, NodeIndexingTasks, PlannedIndexingTask};
+    use serde_json::{json, Value as JsonValue};
+
+    use super::*;
+
+    fn planned_indexing_task(index_uid: &str, source_id: &str) -> PlannedIndexingTask {
+        PlannedIndexingTask {
+            index_uid: index_uid.to_string(),
+            source_id: source_id.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cluster_nodes_handler() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        // Wait for the self node to be part of the live nodes.
+        cluster
+            .ready_nodes_change_stream()
+            .await
+            .next()
+            .await
+            .unwrap();
+
+        let handler = cluster_nodes_handler(cluster.clone());
+        let response = warp::test::request()
+            .path("/cluster/nodes")
+            .reply(&handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        let nodes: JsonValue = serde_json::from_slice(response.body()).unwrap();
+        let expected_nodes = json!([{
+            "node_id": cluster.self_node_id(),
+            "generation_id": cluster.self_chitchat_id().generation_id,
+            "enabled_services": ["indexer"],
+            "gossip_advertise_addr": cluster.gossip_advertise_addr().to_string(),
+            "grpc_advertise_addr": grpc_addr_from_listen_addr_for_test(
+                cluster.gossip_advertise_addr()
+            ).to_string(),
+            "availability_zone": null,
+            "is_ready": true,
+            "decommission_status": "active",
+            "indexing_pipelines": [],
+        }]);
+        assert_eq!(nodes, expected_nodes);
+    }
+
+    #[tokio::test]
+    async fn test_decommission_node_handlers() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["searcher"], &transport, true)
+            .await
+            .unwrap();
+        // Wait for the self node to be part of the live nodes.
+        cluster
+            .ready_nodes_change_stream()
+            .await
+            .next()
+            .await
+            .unwrap();
+
+        let handler = decommission_node_handler(cluster.clone())
+            .or(node_decommission_status_handler(cluster.clone()));
+        let response = warp::test::request()
+            .path("/cluster/nodes/unknown-node/decommission")
+            .method("PUT")
+            .reply(&handler)
+            .await;
+        assert_eq!(response.status(), 404);
+
+        let response = warp::test::request()
+            .path(&format!(
+                "/cluster/nodes/{}/decommission",
+                cluster.self_node_id()
+            ))
+            .method("PUT")
+            .reply(&handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        let decommission_status: NodeDecommissionStatus =
+            serde_json::from_slice(response.body()).unwrap();
+        assert_eq!(
+            decommission_status.decommission_status,
+            DecommissionStatus::Active
+        );
+        assert!(cluster.is_self_node_decommission_requested().await);
+
+        cluster
+            .set_self_node_decommission_status(DecommissionStatus::Decommissioned)
+            .await;
+        let cluster_clone = cluster.clone();
+        wait_until_predicate(
+            move || {
+                let cluster_clone = cluster_clone.clone();
+                async move {
+                    cluster_clone.live_nodes().await.iter().all(|node| {
+                        node.decommission_status() == DecommissionStatus::Decommissioned
+                    })
+                }
+            },
+            Duration::from_secs(5),
+            Duration::from_millis(25),
+        )
+        .await
+        .unwrap();
+        let response = warp::test::request()
+            .path(&format!(
+                "/cluster/nodes/{}/decommission",
+                cluster.self_node_id()
+            ))
+            .reply(&handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        let decommission_status: JsonValue = serde_json::from_slice(response.body()).unwrap();
+        let expected_decommission_status = json!({
+            "node_id": cluster.self_node_id(),
+            "decommission_status": "decommissioned",
+        });
+        assert_eq!(decommission_status, expected_decommission_status);
+    }
+
+    #[tokio::test]
+    async fn test_indexing_tasks_handler() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        cluster
+            .ready_nodes_change_stream()
+            .await
+            .next()
+            .await
+            .unwrap();
+
+        let self_node_id = cluster.self_node_id().to_string();
+        let mut mock_control_plane_service = ControlPlaneServiceClient::mock();
+        mock_control_plane_service
+            .expect_get_indexing_plan()
+            .return_once(move |_| {
+                Ok(GetIndexingPlanResponse {
+                    node_indexing_tasks: vec![
+                        NodeIndexingTasks {
+                            node_id: self_node_id,
+                            indexing_tasks: vec![
+                                planned_indexing_task("index-1:0", "source-1"),
+                                planned_indexing_task("index-2:0", "source-2"),
+                                planned_indexing_task("index-1:0", "source-1"),
+                            ],
+                        },
+                        NodeIndexingTasks {
+                            node_id: "departed-indexer".to_string(),
+                            indexing_tasks: vec![planned_indexing_task("index-3:0", "source-3")],
+                        },
+                    ],
+                    num_applied_plans: 2,
+                    last_applied_plan_elapsed_secs: Some(10),
+                })
+            });
+        let handler = indexing_tasks_handler(
+            cluster.clone(),
+            ControlPlaneServiceClient::from(mock_control_plane_service),
+        );
+        let response = warp::test::request()
+            .path("/cluster/indexing-tasks")
+            .reply(&handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        let indexing_tasks: JsonValue = serde_json::from_slice(response.body()).unwrap();
+        let expected_indexing_tasks = json!({
+            "num_applied_plans": 2,
+            "last_applied_plan_elapsed_secs": 10,
+            "indexers": [
+                {
+                    "node_id": "departed-indexer",
+                    "is_ready": false,
+                    "planned_pipelines": [
+                        {"index_uid": "index-3:0", "source_id": "source-3", "num_pipelines": 1},
+                    ],
+                    "running_pipelines": [],
+                },
+                {
+                    "node_id": cluster.self_node_id(),
+                    "is_ready": true,
+                    "planned_pipelines": [
+                        {"index_uid": "index-1:0", "source_id": "source-1", "num_pipelines": 2},
+                        {"index_uid": "index-2:0", "source_id": "source-2", "num_pipelines": 1},
+                    ],
+                    "running_pipelines": [],
+                },
+            ],
+        });
+        assert_eq!(indexing_tasks, expected_indexing_tasks);
+    }
+
+    #[tokio::test]
+    async fn test_indexing_tasks_handler_control_plane_unavailable() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        let mut mock_control_plane_service = ControlPlaneServiceClient::mock();
+        mock_control_plane_service
+            .expect_get_indexing_plan()
+            .return_once(|_| {
+                Err(ControlPlaneError::Unavailable(
+                    "no control plane".to_string(),
+                ))
+            });
+        let handler = indexing_tasks_handler(
+            cluster,
+            ControlPlaneServiceClient::from(mock_control_plane_service),
+        );
+        let response = warp::test::request()
+            .path("/cluster/indexing-tasks")
+            .reply(&handler)
+            .await;
+        assert_eq!(response.status(), 503);
+    }
+
+    #[tokio::test]
+    async fn test_indexer_autoscaling_handler() {
+        let mut mock_control_plane_service = ControlPlaneServiceClient::mock();
+        mock_control_plane_service
+            .expect_get_indexer_autoscaling()
+            .return_once(|_| {
+                Ok(GetIndexerAutoscalingResponse {
+                    desired_num_indexers: 3,
+                    num_indexers: 2,
+                    num_pipelines: 4,
+                    num_saturated_pipelines: 2,
+                    num_idle_pipelines: 0,
+                    source_lag: 1_000,
+                })
+            });
+        let handler = indexer_autoscaling_handler(ControlPlaneServiceClient::from(
+            mock_control_plane_service,
+        ));
+        let response = warp::test::request()
+            .path("/cluster/indexer-autoscaling")
+            .reply(&handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        let indexer_autoscaling: JsonValue = serde_json::from_slice(response.body()).unwrap();
+        let expected_indexer_autoscaling = json!({
+            "desired_num_indexers": 3,
+            "num_indexers": 2,
+            "num_pipelines": 4,
+            "num_saturated_pipelines": 2,
+            "num_idle_pipelines": 0,
+            "source_lag": 1_000,
+        });
+        assert_eq!(indexer_autoscaling, expected_indexer_autoscaling);
+    }
+}
@@ -19,29 +19,46 @@
 
 use std::sync::Arc;
 
+use quickwit_common::extract_time_range;
 use quickwit_config::build_doc_mapper;
+use quickwit_doc_mapper::tag_pruning::extract_tags_from_query;
 use quickwit_janitor::error::JanitorError;
-use quickwit_metastore::{Metastore, MetastoreError};
+use quickwit_metastore::{
+    split_delete_task_applied, split_tag_filter, split_time_range_filter, ListSplitsQuery,
+    Metastore, MetastoreError, Split, SplitState,
+};
 use quickwit_proto::metastore_api::{DeleteQuery, DeleteTask};
 use quickwit_proto::{query_ast_from_user_text, IndexUid, SearchRequest};
 use quickwit_query::query_ast::QueryAst;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use warp::{Filter, Rejection};
 
+use crate::auth::{restrict_to_tenant, with_principal, Principal};
 use crate::format::extract_format_from_qs;
 use crate::json_api_response::make_json_api_response;
 use crate::with_arg;
 
 #[derive(utoipa::OpenApi)]
 #[openapi(
-    paths(get_delete_tasks, post_delete_request),
-    components(schemas(DeleteQueryRequest, DeleteTask, DeleteQuery,))
+    paths(
+        get_delete_tasks,
+        get_delete_task,
+        post_delete_request,
+        cancel_delete_task
+    ),
+    components(schemas(
+        DeleteQueryRequest,
+        DeleteTask,
+        DeleteQuery,
+        DeleteTaskState,
+        DeleteTaskStatus,
+    ))
 )]
 pub struct DeleteTaskApi;
 
 /// This struct represents the delete query passed to
 /// the rest API.
-#[derive(Deserialize, Debug, Eq, PartialEq, Default, utoipa::ToSchema)]
+#[derive(Serialize, Deserialize, Debug, Eq, PartialEq, Default, utoipa::ToSchema)]
 #[serde(deny_unknown_fields)]
 pub struct DeleteQueryRequest {
     /// Query text. The query language is that of tantivy.
@@ -55,11 +72,107 @@ pub struct DeleteQueryRequest {
     pub end_timestamp: Option<i64>,
 }
 
+/// State of a delete task.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Eq, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DeleteTaskState {
+    /// The delete task has not been applied to any split yet and can still be cancelled.
+    Pending,
+    /// The delete task has been applied to some of the published splits.
+    InProgress,
+    /// All the published splits are up to date with the delete task.
+    Completed,
+}
+
+/// A delete task along with the progress of its application to the published splits of the
+/// index.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, utoipa::ToSchema)]
+pub struct DeleteTaskStatus {
+    #[serde(flatten)]
+    pub delete_task: DeleteTask,
+    pub state: DeleteTaskState,
+    /// Number of published splits whose time range and tags match the delete query.
+    pub num_matched_splits: usize,
+    /// Number of matched splits the delete task has already been applied to.
+    pub num_processed_splits: usize,
+    /// Number of matched splits the delete task remains to be applied to.
+    pub num_remaining_splits: usize,
+    /// Number of documents in the remaining splits.
+    pub num_remaining_docs: usize,
+    /// Uncompressed size of the documents in the remaining splits.
+    pub remaining_size_in_bytes: u64,
+}
+
+impl DeleteTaskStatus {
+    /// Computes the status of a delete task from the splits of its index.
+    fn compute(delete_task: DeleteTask, splits: &[Split]) -> Self {
+        let delete_query = delete_task
+            .delete_query
+            .as_ref()
+            .expect("Delete task must have a delete query.");
+        let time_range =
+            extract_time_range(delete_query.start_timestamp, delete_query.end_timestamp);
+        let tags_filter = serde_json::from_str(&delete_query.query_ast)
+            .ok()
+            .and_then(extract_tags_from_query);
+
+        let mut is_applied = false;
+        let mut has_stale_splits = false;
+        let mut num_processed_splits = 0;
+        let mut num_remaining_splits = 0;
+        let mut num_remaining_docs = 0;
+        let mut remaining_size_in_bytes = 0;
+
+        for split in splits {
+            let is_applied_to_split = split_delete_task_applied(split, &delete_task);
+            is_applied |= is_applied_to_split;
+
+            if split.split_state != SplitState::Published {
+                continue;
+            }
+            let is_stale_split = split.split_metadata.delete_opstamp < delete_task.opstamp;
+            has_stale_splits |= is_stale_split;
+
+            if !split_time_range_filter(split, time_range.as_ref())
+                || !split_tag_filter(split, tags_filter.as_ref())
+            {
+                continue;
+            }
+            if is_applied_to_split {
+                num_processed_splits += 1;
+            } else if is_stale_split {
+                num_remaining_splits += 1;
+                num_remaining_docs += split.split_metadata.num_docs;
+                remaining_size_in_bytes += split.split_metadata.uncompressed_docs_size_in_bytes;
+            }
+        }
+        let state = if !has_stale_splits {
+            DeleteTaskState::Completed
+        } else if !is_applied {
+            DeleteTaskState::Pending
+        } else {
+            DeleteTaskState::InProgress
+        };
+        Self {
+            delete_task,
+            state,
+            num_matched_splits: num_processed_splits + num_remaining_splits,
+            num_processed_splits,
+            num_remaining_splits,
+            num_remaining_docs,
+            remaining_size_in_bytes,
+        }
+    }
+}
+
 /// Delete query API handlers.
 pub fn delete_task_api_handlers(
     metastore: Arc<dyn Metastore>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
-    get_delete_tasks_handler(metastore.clone()).or(post_delete_tasks_handler(metastore.clone()))
+    get_delete_tasks_handler(metastore.clone())
+        .or(get_delete_task_handler(metastore.clone()))
+        .or(post_delete_tasks_handler(metastore.clone()))
+        .or(cancel_delete_task_handler(metastore))
 }
 
 pub fn get_delete_tasks_handler(
@@ -78,7 +191,7 @@ pub fn get_delete_tasks_handler(
     tag = "Delete Tasks",
     path = "/{index_id}/delete-tasks",
     responses(
-        (status = 200, description = "Successfully fetched delete tasks.", body = [DeleteTask])
+        (status = 200, description = "Successfully fetched delete tasks.", body = [DeleteTaskStatus])
     ),
     params(
         ("index_id" = String, Path, description = "The index ID to retrieve delete tasks for."),
@@ -86,7 +199,8 @@ pub fn get_delete_tasks_handler(
 )]
 /// Get Delete Tasks
 ///
-/// Returns delete tasks in json format for a given `index_id`.
+/// Returns delete tasks in json format for a given `index_id`, along with their state and
+/// progress.
 // Note that `_delete_task_service_mailbox` is not used...
 // Explanation: we don't want to expose any delete tasks endpoints without a running
 // `DeleteTaskService`. This is ensured by requiring a `Mailbox<DeleteTaskService>` in
@@ -94,10 +208,61 @@ pub fn get_delete_tasks_handler(
 pub async fn get_delete_tasks(
     index_id: String,
     metastore: Arc<dyn Metastore>,
-) -> Result<Vec<DeleteTask>, MetastoreError> {
+) -> Result<Vec<DeleteTaskStatus>, MetastoreError> {
+    let index_uid: IndexUid = metastore.index_metadata(&index_id).await?.index_uid;
+    let delete_tasks = metastore.list_delete_tasks(index_uid.clone(), 0).await?;
+    let splits = metastore
+        .list_splits(ListSplitsQuery::for_index(index_uid))
+        .await?;
+    let delete_task_statuses = delete_tasks
+        .into_iter()
+        .map(|delete_task| DeleteTaskStatus::compute(delete_task, &splits))
+        .collect();
+    Ok(delete_task_statuses)
+}
+
+pub fn get_delete_task_handler(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!(String / "delete-tasks" / u64)
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(get_delete_task)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Delete Tasks",
+    path = "/{index_id}/delete-tasks/{opstamp}",
+    responses(
+        (status = 200, description = "Successfully fetched delete task.", body = DeleteTaskStatus)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the delete task."),
+        ("opstamp" = u64, Path, description = "The opstamp of the delete task."),
+    )
+)]
+/// Get Delete Task
+///
+/// Returns the state and progress of a delete task.
+pub async fn get_delete_task(
+    index_id: String,
+    opstamp: u64,
+    metastore: Arc<dyn Metastore>,
+) -> Result<DeleteTaskStatus, MetastoreError> {
     let index_uid: IndexUid = metastore.index_metadata(&index_id).await?.index_uid;
-    let delete_tasks = metastore.list_delete_tasks(index_uid, 0).await?;
-    Ok(delete_tasks)
+    let delete_task = metastore
+        .list_delete_tasks(index_uid.clone(), opstamp.saturating_sub(1))
+        .await?
+        .into_iter()
+        .find(|delete_task| delete_task.opstamp == opstamp)
+        .ok_or(MetastoreError::DeleteTaskDoesNotExist { opstamp })?;
+    let splits = metastore
+        .list_splits(ListSplitsQuery::for_index(index_uid))
+        .await?;
+    Ok(DeleteTaskStatus::compute(delete_task, &splits))
 }
 
 pub fn post_delete_tasks_handler(
@@ -106,6 +271,7 @@ pub fn post_delete_tasks_handler(
     warp::path!(String / "delete-tasks")
         .and(warp::body::json())
         .and(warp::post())
+        .and(with_principal())
         .and(with_arg(metastore))
         .then(post_delete_request)
         .and(extract_format_from_qs())
@@ -127,10 +293,12 @@ pub fn post_delete_tasks_handler(
 /// Create Delete Task
 ///
 /// This operation will not be immediately executed, instead it will be added to a queue
-/// and cleaned up in the near future.
+/// and cleaned up in the near future. The deletion of a principal restricted to a tenant only
+/// applies to the documents of its tenant.
 pub async fn post_delete_request(
     index_id: String,
     delete_request: DeleteQueryRequest,
+    principal_opt: Option<Principal>,
     metastore: Arc<dyn Metastore>,
 ) -> Result<DeleteTask, JanitorError> {
     let metadata = metastore.index_metadata(&index_id).await?;
@@ -138,6 +306,7 @@ pub async fn post_delete_request(
     let query_ast = query_ast_from_user_text(&delete_request.query, Some(Vec::new()))
         .parse_user_query(&[])
         .map_err(|err| JanitorError::InvalidDeleteQuery(err.to_string()))?;
+    let query_ast = restrict_to_tenant(principal_opt.as_ref(), query_ast);
     let query_ast_json = serde_json::to_string(&query_ast).map_err(|_err| {
         JanitorError::InternalError("Failed to serialized delete query ast".to_string())
     })?;
@@ -164,12 +333,51 @@ pub async fn post_delete_request(
     Ok(delete_task)
 }
 
+pub fn cancel_delete_task_handler(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!(String / "delete-tasks" / u64)
+        .and(warp::delete())
+        .and(with_arg(metastore))
+        .then(cancel_delete_task)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    delete,
+    tag = "Delete Tasks",
+    path = "/{index_id}/delete-tasks/{opstamp}",
+    responses(
+        (status = 200, description = "Successfully cancelled delete task.", body = DeleteTask)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the delete task."),
+        ("opstamp" = u64, Path, description = "The opstamp of the delete task to cancel."),
+    )
+)]
+/// Cancel Delete Task
+///
+/// Cancels a delete task. Only pending delete tasks, which have not been applied to any split
+/// yet, can be cancelled.
+pub async fn cancel_delete_task(
+    index_id: String,
+    opstamp: u64,
+    metastore: Arc<dyn Metastore>,
+) -> Result<DeleteTask, MetastoreError> {
+    let index_uid: IndexUid = metastore.index_metadata(&index_id).await?.index_uid;
+    let delete_task = metastore.cancel_delete_task(index_uid, opstamp).await?;
+    Ok(delete_task)
+}
+
 #[cfg(test)]
 mod tests {
     use quickwit_indexing::TestSandbox;
     use quickwit_proto::metastore_api::DeleteTask;
+    use serde_json::json;
     use warp::Filter;
 
+    use super::{DeleteTaskState, DeleteTaskStatus};
     use crate::rest::recover_fn;
 
     #[tokio::test]
@@ -187,6 +395,13 @@ mod tests {
         let test_sandbox = TestSandbox::create(index_id, doc_mapping_yaml, "{}", &["body"])
             .await
             .unwrap();
+        test_sandbox
+            .add_documents(vec![
+                json!({"body": "myterm", "ts": 5}),
+                json!({"body": "otherterm", "ts": 6}),
+            ])
+            .await
+            .unwrap();
         let metastore = test_sandbox.metastore();
         let delete_query_api_handlers =
             super::delete_task_api_handlers(metastore).recover(recover_fn);
@@ -200,7 +415,7 @@ mod tests {
         assert_eq!(resp.status(), 200);
         let created_delete_task: DeleteTask = serde_json::from_slice(resp.body()).unwrap();
         assert_eq!(created_delete_task.opstamp, 1);
-        let created_delete_query = created_delete_task.delete_query.unwrap();
+        let created_delete_query = created_delete_task.delete_query.clone().unwrap();
         assert_eq!(
             created_delete_query.index_uid,
             test_sandbox.index_uid().to_string()
@@ -229,8 +444,49 @@ mod tests {
             .reply(&delete_query_api_handlers)
             .await;
         assert_eq!(resp.status(), 200);
-        let delete_tasks: Vec<DeleteTask> = serde_json::from_slice(resp.body()).unwrap();
-        assert_eq!(delete_tasks.len(), 1);
+        let delete_task_statuses: Vec<DeleteTaskStatus> =
+            serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(delete_task_statuses.len(), 1);
+        let delete_task_status = &delete_task_statuses[0];
+        assert_eq!(delete_task_status.delete_task, created_delete_task);
+        assert_eq!(delete_task_status.state, DeleteTaskState::Pending);
+        assert_eq!(delete_task_status.num_matched_splits, 1);
+        assert_eq!(delete_task_status.num_processed_splits, 0);
+        assert_eq!(delete_task_status.num_remaining_splits, 1);
+        assert_eq!(delete_task_status.num_remaining_docs, 2);
+
+        // GET a delete task.
+        let resp = warp::test::request()
+            .path("/test-delete-task-rest/delete-tasks/1")
+            .reply(&delete_query_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let delete_task_status: DeleteTaskStatus = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(delete_task_status.delete_task.opstamp, 1);
+        assert_eq!(delete_task_status.state, DeleteTaskState::Pending);
+
+        // DELETE (cancel) a delete task.
+        let resp = warp::test::request()
+            .path("/test-delete-task-rest/delete-tasks/1")
+            .method("DELETE")
+            .reply(&delete_query_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let cancelled_delete_task: DeleteTask = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(cancelled_delete_task, created_delete_task);
+
+        let resp = warp::test::request()
+            .path("/test-delete-task-rest/delete-tasks/1")
+            .reply(&delete_query_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        let resp = warp::test::request()
+            .path("/test-delete-task-rest/delete-tasks/1")
+            .method("DELETE")
+            .reply(&delete_query_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 404);
         test_sandbox.assert_quit().await;
     }
 }
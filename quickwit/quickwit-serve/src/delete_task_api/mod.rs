@@ -19,4 +19,6 @@
 
 mod handler;
 
-pub use handler::{delete_task_api_handlers, DeleteTaskApi};
+pub use handler::{
+    delete_task_api_handlers, DeleteQueryRequest, DeleteTaskApi, DeleteTaskState, DeleteTaskStatus,
+};
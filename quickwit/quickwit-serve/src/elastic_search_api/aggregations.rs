@@ -0,0 +1,149 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use serde_json::{Map as JsonMap, Value as JsonValue};
+
+/// Rewrites the aggregations sent by Elasticsearch clients, such as the Grafana Elasticsearch
+/// datasource, into the dialect supported by Quickwit:
+/// - `date_histogram`: the deprecated `interval` parameter is renamed `fixed_interval`, and the
+///   `epoch_millis` format is dropped, keys being always returned as epoch milliseconds.
+/// - `terms`: the deprecated `_term` order is renamed `_key`.
+/// - numbers passed as strings, such as `"size": "10"`, are converted to numbers.
+pub(crate) fn normalize_aggregations(aggregations: &mut JsonMap<String, JsonValue>) {
+    for aggregation in aggregations.values_mut() {
+        let Some(aggregation) = aggregation.as_object_mut() else {
+            continue;
+        };
+        for (aggregation_type, params) in aggregation.iter_mut() {
+            let Some(params) = params.as_object_mut() else {
+                continue;
+            };
+            match aggregation_type.as_str() {
+                "aggs" | "aggregations" => normalize_aggregations(params),
+                "date_histogram" => normalize_date_histogram(params),
+                "terms" => normalize_terms(params),
+                _ => {}
+            }
+        }
+    }
+}
+
+fn normalize_date_histogram(params: &mut JsonMap<String, JsonValue>) {
+    if !params.contains_key("fixed_interval") {
+        if let Some(interval) = params.remove("interval") {
+            params.insert("fixed_interval".to_string(), interval);
+        }
+    }
+    if params.get("format").and_then(JsonValue::as_str) == Some("epoch_millis") {
+        params.remove("format");
+    }
+    parse_numbers_in_strings(params, &["min_doc_count"]);
+}
+
+fn normalize_terms(params: &mut JsonMap<String, JsonValue>) {
+    if let Some(order) = params.get_mut("order").and_then(JsonValue::as_object_mut) {
+        if let Some(direction) = order.remove("_term") {
+            order.insert("_key".to_string(), direction);
+        }
+    }
+    parse_numbers_in_strings(params, &["size", "shard_size", "min_doc_count"]);
+}
+
+fn parse_numbers_in_strings(params: &mut JsonMap<String, JsonValue>, keys: &[&str]) {
+    for key in keys {
+        if let Some(value) = params.get_mut(*key) {
+            if let Some(number) = value.as_str().and_then(|text| text.parse::<u64>().ok()) {
+                *value = JsonValue::from(number);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_normalize_aggregations_grafana() {
+        let mut aggregations = json!({
+            "3": {
+                "terms": {
+                    "field": "service_name",
+                    "size": "10",
+                    "order": {"_term": "asc"},
+                    "min_doc_count": "1"
+                },
+                "aggs": {
+                    "2": {
+                        "date_histogram": {
+                            "field": "timestamp",
+                            "interval": "30s",
+                            "min_doc_count": "0",
+                            "extended_bounds": {"min": 1680000000000u64, "max": 1680003600000u64},
+                            "format": "epoch_millis"
+                        }
+                    }
+                }
+            }
+        });
+        normalize_aggregations(aggregations.as_object_mut().unwrap());
+        let expected_aggregations = json!({
+            "3": {
+                "terms": {
+                    "field": "service_name",
+                    "size": 10,
+                    "order": {"_key": "asc"},
+                    "min_doc_count": 1
+                },
+                "aggs": {
+                    "2": {
+                        "date_histogram": {
+                            "field": "timestamp",
+                            "fixed_interval": "30s",
+                            "min_doc_count": 0,
+                            "extended_bounds": {"min": 1680000000000u64, "max": 1680003600000u64}
+                        }
+                    }
+                }
+            }
+        });
+        assert_eq!(aggregations, expected_aggregations);
+    }
+
+    #[test]
+    fn test_normalize_aggregations_leaves_supported_params_alone() {
+        let mut aggregations = json!({
+            "histogram": {
+                "date_histogram": {
+                    "field": "timestamp",
+                    "fixed_interval": "1m",
+                    "interval": "1h"
+                }
+            },
+            "avg_duration": {
+                "avg": {"field": "duration", "missing": "10"}
+            }
+        });
+        let expected_aggregations = aggregations.clone();
+        normalize_aggregations(aggregations.as_object_mut().unwrap());
+        assert_eq!(aggregations, expected_aggregations);
+    }
+}
@@ -20,6 +20,7 @@
 use std::collections::HashMap;
 
 use bytes::Bytes;
+use quickwit_config::AuthRole;
 use quickwit_ingest::{
     CommitType, DocBatchBuilder, IngestRequest, IngestResponse, IngestService, IngestServiceClient,
     IngestServiceError,
@@ -28,6 +29,7 @@ use quickwit_proto::{ServiceError, ServiceErrorCode};
 use thiserror::Error;
 use warp::{Filter, Rejection};
 
+use crate::auth::{authorize_index, with_principal, AuthError, Principal};
 use crate::elastic_search_api::filter::{elastic_bulk_filter, elastic_index_bulk_filter};
 use crate::elastic_search_api::model::{BulkAction, ElasticIngestOptions};
 use crate::format::extract_format_from_qs;
@@ -43,6 +45,8 @@ pub enum IngestRestApiError {
     BulkInvalidSource(String),
     #[error(transparent)]
     IngestApi(#[from] IngestServiceError),
+    #[error(transparent)]
+    Forbidden(#[from] AuthError),
 }
 
 impl ServiceError for IngestRestApiError {
@@ -51,6 +55,7 @@ impl ServiceError for IngestRestApiError {
             Self::BulkInvalidAction(_) => ServiceErrorCode::BadRequest,
             Self::BulkInvalidSource(_) => ServiceErrorCode::BadRequest,
             Self::IngestApi(ingest_api_error) => ingest_api_error.status_code(),
+            Self::Forbidden(auth_error) => auth_error.status_code(),
         }
     }
 }
@@ -60,9 +65,10 @@ pub fn es_compat_bulk_handler(
     ingest_service: IngestServiceClient,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     elastic_bulk_filter()
+        .and(with_principal())
         .and(with_arg(ingest_service))
-        .then(|body, ingest_option, ingest_service| {
-            elastic_ingest_bulk(None, body, ingest_option, ingest_service)
+        .then(|body, ingest_option, principal_opt, ingest_service| {
+            elastic_ingest_bulk(None, body, ingest_option, principal_opt, ingest_service)
         })
         .and(extract_format_from_qs())
         .map(make_json_api_response)
@@ -73,10 +79,19 @@ pub fn es_compat_index_bulk_handler(
     ingest_service: IngestServiceClient,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     elastic_index_bulk_filter()
+        .and(with_principal())
         .and(with_arg(ingest_service))
-        .then(|index, body, ingest_option, ingest_service| {
-            elastic_ingest_bulk(Some(index), body, ingest_option, ingest_service)
-        })
+        .then(
+            |index, body, ingest_option, principal_opt, ingest_service| {
+                elastic_ingest_bulk(
+                    Some(index),
+                    body,
+                    ingest_option,
+                    principal_opt,
+                    ingest_service,
+                )
+            },
+        )
         .and(extract_format_from_qs())
         .map(make_json_api_response)
 }
@@ -85,6 +100,7 @@ async fn elastic_ingest_bulk(
     index: Option<String>,
     body: Bytes,
     ingest_options: ElasticIngestOptions,
+    principal_opt: Option<Principal>,
     mut ingest_service: IngestServiceClient,
 ) -> Result<IngestResponse, IngestRestApiError> {
     let mut doc_batch_builders = HashMap::new();
@@ -113,6 +129,9 @@ async fn elastic_ingest_bulk(
 
         doc_batch_builder.ingest_doc(source);
     }
+    for index_id in doc_batch_builders.keys() {
+        authorize_index(principal_opt.as_ref(), AuthRole::Ingest, index_id)?;
+    }
     let doc_batches = doc_batch_builders
         .into_values()
         .map(|builder| builder.build())
@@ -137,6 +156,7 @@ mod tests {
     };
     use quickwit_search::MockSearchService;
 
+    use crate::auth::Principal;
     use crate::elastic_search_api::elastic_api_handlers;
     use crate::ingest_api::setup_ingest_service;
 
@@ -186,6 +206,35 @@ mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_bulk_api_returns_403_if_index_is_not_granted() {
+        let search_service = Arc::new(MockSearchService::new());
+        let (universe, _temp_dir, ingest_service, _) =
+            setup_ingest_service(&["my-index-1", "my-index-2"], &IngestApiConfig::default()).await;
+        let elastic_api_handlers = elastic_api_handlers(search_service, ingest_service);
+        let principal = Principal {
+            name: "team-a".to_string(),
+            grants: vec!["ingest:my-index-1".parse().unwrap()],
+            tenant_scope_opt: None,
+        };
+        let payload = r#"
+            { "create" : { "_index" : "my-index-1", "_id" : "1"} }
+            {"id": 1, "message": "push"}
+            { "create" : { "_index" : "my-index-2", "_id" : "1"} }
+            {"id": 1, "message": "push"}"#;
+        let resp = warp::test::request()
+            .path("/_elastic/_bulk")
+            .method("POST")
+            .extension(principal)
+            .body(payload)
+            .reply(&elastic_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 403);
+        let body = String::from_utf8(resp.body().to_vec()).unwrap();
+        assert!(body.contains("is not granted the `ingest` role on index `my-index-2`"));
+        universe.assert_quit().await;
+    }
+
     #[tokio::test]
     async fn test_bulk_index_api_returns_200() {
         let search_service = Arc::new(MockSearchService::new());
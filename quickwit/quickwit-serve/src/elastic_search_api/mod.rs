@@ -17,6 +17,7 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+mod aggregations;
 mod bulk;
 mod filter;
 mod model;
@@ -33,6 +34,19 @@ use rest_handler::{
 use serde::{Deserialize, Serialize};
 use warp::{Filter, Rejection};
 
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    paths(
+        filter::elastic_search_filter,
+        filter::elastic_index_search_filter,
+        filter::elastic_multi_search_filter,
+        filter::elastic_bulk_filter,
+        filter::elastic_index_bulk_filter,
+    ),
+    components(schemas(model::ElasticRefresh))
+)]
+pub struct ElasticCompatibleApi;
+
 /// Setup Elasticsearch API handlers
 ///
 /// This is where all newly supported Elasticsearch handlers
@@ -87,6 +101,7 @@ mod tests {
     use quickwit_search::MockSearchService;
 
     use super::model::ElasticSearchError;
+    use crate::auth::Principal;
     use crate::elastic_search_api::model::MultiSearchResponse;
 
     fn ingest_service_client() -> IngestServiceClient {
@@ -294,4 +309,83 @@ mod tests {
             .unwrap()
             .starts_with("Invalid argument: Searching only one index is supported for now."));
     }
+
+    #[tokio::test]
+    async fn test_msearch_api_return_403_with_index_not_granted() {
+        let mock_search_service = MockSearchService::new();
+        let es_search_api_handler =
+            super::elastic_api_handlers(Arc::new(mock_search_service), ingest_service_client());
+        let principal = Principal {
+            name: "team-a".to_string(),
+            grants: vec!["search:team-a-*".parse().unwrap()],
+            tenant_scope_opt: None,
+        };
+        let msearch_payload = r#"
+            {"index":"team-a-logs"}
+            {"query":{"query_string":{"query":"test"}}}
+            {"index":"team-b-logs"}
+            {"query":{"query_string":{"query":"test"}}}
+            "#;
+        let resp = warp::test::request()
+            .path("/_elastic/_msearch")
+            .method("POST")
+            .extension(principal)
+            .body(msearch_payload)
+            .reply(&es_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 403);
+        let es_error: ElasticSearchError = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            es_error.error.reason.unwrap(),
+            "Principal `team-a` is not granted the `search` role on index `team-b-logs`."
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_api_grafana_request() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .with(predicate::function(
+                |search_request: &quickwit_proto::SearchRequest| {
+                    let aggregation_request = search_request.aggregation_request.as_ref().unwrap();
+                    search_request.index_id == "my-index"
+                        && search_request.max_hits == 0
+                        && search_request.query_ast.contains("2023-03-28T10:40:00Z")
+                        && aggregation_request.contains(r#""fixed_interval":"30s""#)
+                        && !aggregation_request.contains("epoch_millis")
+                },
+            ))
+            .returning(|_| Ok(Default::default()));
+        let es_search_api_handler =
+            super::elastic_api_handlers(Arc::new(mock_search_service), ingest_service_client());
+        let search_payload = r#"{
+            "size": 0,
+            "query": {
+                "bool": {
+                    "filter": [
+                        {"range": {"timestamp": {"gte": 1680000000000, "lte": 1680003600000, "format": "epoch_millis"}}},
+                        {"query_string": {"analyze_wildcard": true, "query": "severity_text:ERROR"}}
+                    ]
+                }
+            },
+            "aggs": {
+                "2": {
+                    "date_histogram": {
+                        "interval": "30s",
+                        "field": "timestamp",
+                        "min_doc_count": 0,
+                        "format": "epoch_millis"
+                    }
+                }
+            }
+        }"#;
+        let resp = warp::test::request()
+            .path("/_elastic/my-index/_search")
+            .method("POST")
+            .body(search_payload)
+            .reply(&es_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+    }
 }
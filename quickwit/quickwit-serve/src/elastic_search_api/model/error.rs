@@ -23,6 +23,8 @@ use quickwit_proto::ServiceError;
 use quickwit_search::SearchError;
 use serde::{Deserialize, Serialize};
 
+use crate::auth::AuthError;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ElasticSearchError {
     #[serde(with = "http_serde::status_code")]
@@ -30,12 +32,12 @@ pub struct ElasticSearchError {
     pub error: ErrorCause,
 }
 
-impl From<SearchError> for ElasticSearchError {
-    fn from(search_error: SearchError) -> Self {
-        let status = search_error.status_code().to_http_status_code();
+impl ElasticSearchError {
+    fn from_service_error<E: ServiceError>(error: E) -> Self {
+        let status = error.status_code().to_http_status_code();
         // Fill only reason field to keep it simple.
         let reason = ErrorCause {
-            reason: Some(search_error.to_string()),
+            reason: Some(error.to_string()),
             caused_by: None,
             root_cause: vec![],
             stack_trace: None,
@@ -49,3 +51,15 @@ impl From<SearchError> for ElasticSearchError {
         }
     }
 }
+
+impl From<SearchError> for ElasticSearchError {
+    fn from(search_error: SearchError) -> Self {
+        Self::from_service_error(search_error)
+    }
+}
+
+impl From<AuthError> for ElasticSearchError {
+    fn from(auth_error: AuthError) -> Self {
+        Self::from_service_error(auth_error)
+    }
+}
@@ -28,17 +28,20 @@ use futures_util::StreamExt;
 use hyper::StatusCode;
 use itertools::Itertools;
 use quickwit_common::truncate_str;
+use quickwit_config::AuthRole;
 use quickwit_proto::{SearchResponse, ServiceErrorCode, SortOrder};
 use quickwit_query::query_ast::{QueryAst, UserInputQuery};
 use quickwit_query::BooleanOperand;
 use quickwit_search::{SearchError, SearchService};
 use warp::{Filter, Rejection};
 
+use super::aggregations::normalize_aggregations;
 use super::filter::elastic_multi_search_filter;
 use super::model::{
     ElasticSearchError, MultiSearchHeader, MultiSearchQueryParams, MultiSearchResponse,
     MultiSearchSingleResponse, SearchBody, SearchQueryParams,
 };
+use crate::auth::{authorize_index, restrict_to_tenant, with_principal, Principal};
 use crate::elastic_search_api::filter::elastic_index_search_filter;
 use crate::format::BodyFormat;
 use crate::json_api_response::{make_json_api_response, ApiError, JsonApiResponse};
@@ -65,6 +68,7 @@ pub fn es_compat_index_search_handler(
     search_service: Arc<dyn SearchService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     elastic_index_search_filter()
+        .and(with_principal())
         .and(with_arg(search_service))
         .then(es_compat_index_search)
         .map(make_elastic_api_response)
@@ -75,6 +79,7 @@ pub fn es_compat_index_multi_search_handler(
     search_service: Arc<dyn SearchService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     elastic_multi_search_filter()
+        .and(with_principal())
         .and(with_arg(search_service))
         .then(es_compat_index_multi_search)
         .map(|result: Result<MultiSearchResponse, ElasticSearchError>| {
@@ -89,7 +94,8 @@ pub fn es_compat_index_multi_search_handler(
 fn build_request_for_es_api(
     index_id: String,
     search_params: SearchQueryParams,
-    search_body: SearchBody,
+    mut search_body: SearchBody,
+    principal_opt: Option<&Principal>,
 ) -> Result<quickwit_proto::SearchRequest, ElasticSearchError> {
     let default_operator = search_params.default_operator.unwrap_or(BooleanOperand::Or);
     // The query string, if present, takes priority over what can be in the request
@@ -108,9 +114,11 @@ fn build_request_for_es_api(
     } else {
         QueryAst::MatchAll
     };
+    let query_ast = restrict_to_tenant(principal_opt, query_ast);
     let aggregation_request: Option<String> = if search_body.aggs.is_empty() {
         None
     } else {
+        normalize_aggregations(&mut search_body.aggs);
         serde_json::to_string(&search_body.aggs).ok()
     };
 
@@ -161,10 +169,12 @@ async fn es_compat_index_search(
     index_id: String,
     search_params: SearchQueryParams,
     search_body: SearchBody,
+    principal_opt: Option<Principal>,
     search_service: Arc<dyn SearchService>,
 ) -> Result<ElasticSearchResponse, ElasticSearchError> {
     let start_instant = Instant::now();
-    let search_request = build_request_for_es_api(index_id, search_params, search_body)?;
+    let search_request =
+        build_request_for_es_api(index_id, search_params, search_body, principal_opt.as_ref())?;
     let search_response: SearchResponse = search_service.root_search(search_request).await?;
     let elapsed = start_instant.elapsed();
     let mut search_response_rest: ElasticSearchResponse =
@@ -195,6 +205,7 @@ fn convert_hit(hit: quickwit_proto::Hit) -> ElasticHit {
 async fn es_compat_index_multi_search(
     payload: Bytes,
     multi_search_params: MultiSearchQueryParams,
+    principal_opt: Option<Principal>,
     search_service: Arc<dyn SearchService>,
 ) -> Result<MultiSearchResponse, ElasticSearchError> {
     let mut search_requests = Vec::new();
@@ -224,6 +235,7 @@ async fn es_compat_index_multi_search(
             )));
         }
         let index_id = request_header.index[0].clone();
+        authorize_index(principal_opt.as_ref(), AuthRole::Search, &index_id)?;
         let search_body = payload_lines
             .next()
             .ok_or_else(|| {
@@ -239,7 +251,12 @@ async fn es_compat_index_multi_search(
                 })
             })?;
         let search_query_params = SearchQueryParams::from(request_header);
-        let es_request = build_request_for_es_api(index_id, search_query_params, search_body)?;
+        let es_request = build_request_for_es_api(
+            index_id,
+            search_query_params,
+            search_body,
+            principal_opt.as_ref(),
+        )?;
         search_requests.push(es_request);
     }
     let futures = search_requests.into_iter().map(|search_request| async {
@@ -19,10 +19,13 @@
 
 use std::collections::BTreeSet;
 use std::net::SocketAddr;
+use std::path::Path;
 use std::sync::Arc;
 
+use anyhow::Context;
 use quickwit_common::tower::BoxFutureInfaillible;
 use quickwit_config::service::QuickwitService;
+use quickwit_config::{GrpcCompression, GrpcTlsConfig};
 use quickwit_control_plane::control_plane_service_grpc_server::ControlPlaneServiceGrpcServer;
 use quickwit_control_plane::ControlPlaneServiceGrpcServerAdapter;
 use quickwit_indexing::grpc_adapter::GrpcIndexingAdapter;
@@ -30,20 +33,31 @@ use quickwit_ingest::ingest_service_grpc_server::IngestServiceGrpcServer;
 use quickwit_ingest::IngestServiceGrpcServerAdapter;
 use quickwit_jaeger::JaegerService;
 use quickwit_metastore::GrpcMetastoreAdapter;
-use quickwit_opentelemetry::otlp::{OtlpGrpcLogsService, OtlpGrpcTraceService};
+use quickwit_opentelemetry::otlp::OtlpGrpcMetricsService;
 use quickwit_proto::indexing_api::indexing_service_server::IndexingServiceServer;
+use quickwit_proto::jaeger::storage::v1::dependencies_reader_plugin_server::DependenciesReaderPluginServer;
 use quickwit_proto::jaeger::storage::v1::span_reader_plugin_server::SpanReaderPluginServer;
 use quickwit_proto::metastore_api::metastore_api_service_server::MetastoreApiServiceServer;
 use quickwit_proto::opentelemetry::proto::collector::logs::v1::logs_service_server::LogsServiceServer;
+use quickwit_proto::opentelemetry::proto::collector::metrics::v1::metrics_service_server::MetricsServiceServer;
 use quickwit_proto::opentelemetry::proto::collector::trace::v1::trace_service_server::TraceServiceServer;
 use quickwit_proto::search_service_server::SearchServiceServer;
 use quickwit_proto::tonic;
 use quickwit_proto::tonic::codegen::CompressionEncoding;
-use tonic::transport::Server;
+use tonic::transport::{
+    Certificate, ClientTlsConfig, Identity, NamedService, Server, ServerTlsConfig,
+};
+use tonic_health::ServingStatus;
+use tonic_reflection::server::{ServerReflection, ServerReflectionServer};
 use tracing::*;
 
+use crate::auth::{AccessPolicy, Authenticator, GrpcAuthLayer};
 use crate::search_api::GrpcSearchAdapter;
-use crate::QuickwitServices;
+use crate::{otlp_logs_service, otlp_trace_service, QuickwitServices};
+
+const HEALTH_SERVICE_NAME: &str = "grpc.health.v1.Health";
+
+const REFLECTION_SERVICE_NAME: &str = "grpc.reflection.v1alpha.ServerReflection";
 
 /// Starts gRPC services given a gRPC address.
 pub(crate) async fn start_grpc_server(
@@ -53,13 +67,43 @@ pub(crate) async fn start_grpc_server(
     shutdown_signal: BoxFutureInfaillible<()>,
 ) -> anyhow::Result<()> {
     let mut enabled_grpc_services = BTreeSet::new();
-    let mut server = Server::builder();
+    // The OTLP and Jaeger services are authorized against the roles of the principal, the internal
+    // services, used for the communication between nodes, require the cluster key.
+    let authenticator_opt = Authenticator::from_config(&services.config.auth_config);
+    if authenticator_opt.is_some() {
+        info!("Authentication and authorization of the gRPC API are enabled.");
+    }
+    // The layer is installed even when authentication and audit are disabled: rate limits can be
+    // set at any time through the cluster settings.
+    let access_policy = AccessPolicy::new(&services.config.indexer_config);
+    let auth_layer = GrpcAuthLayer::new(
+        authenticator_opt.map(Arc::new),
+        access_policy,
+        services.audit_logger_opt.clone(),
+    )
+    .with_rate_limiter(services.rate_limiter.clone());
+    let mut server_builder = Server::builder();
+
+    if let Some(grpc_tls_config) = &services.config.grpc_tls_config {
+        server_builder = server_builder
+            .tls_config(server_tls_config(grpc_tls_config).await?)
+            .context("Failed to configure TLS on the gRPC server.")?;
+        info!("TLS is enabled on the gRPC server.");
+    }
+    let mut server = server_builder.layer(auth_layer);
 
+    // Mount gRPC gossip service if the nodes gossip over gRPC.
+    let gossip_grpc_service =
+        if let Some(grpc_gossip_transport) = &services.grpc_gossip_transport_opt {
+            enabled_grpc_services.insert("gossip");
+            Some(grpc_gossip_transport.grpc_service())
+        } else {
+            None
+        };
     // Mount gRPC metastore service if `QuickwitService::Metastore` is enabled on node.
-    let metastore_grpc_service = if services.services.contains(&QuickwitService::Metastore) {
+    let metastore_grpc_service = if let Some(metastore_server) = &services.metastore_server_opt {
         enabled_grpc_services.insert("metastore");
-        let metastore = services.metastore.clone();
-        let grpc_metastore = GrpcMetastoreAdapter::from(metastore);
+        let grpc_metastore = GrpcMetastoreAdapter::from(metastore_server.clone());
         Some(MetastoreApiServiceServer::new(grpc_metastore))
     } else {
         None
@@ -86,27 +130,32 @@ pub(crate) async fn start_grpc_server(
         None
     };
     // Mount gRPC control plane service if `QuickwitService::ControlPlane` is enabled on node.
-    let control_plane_grpc_service = if services.services.contains(&QuickwitService::ControlPlane) {
-        if let Some(control_plane_client) = &services.control_plane_service {
+    let control_plane_grpc_service =
+        if let Some(control_plane_server) = &services.control_plane_server_opt {
             enabled_grpc_services.insert("control-plane");
-            let adapter = ControlPlaneServiceGrpcServerAdapter::new(control_plane_client.clone());
+            let adapter = ControlPlaneServiceGrpcServerAdapter::new(control_plane_server.clone());
             Some(ControlPlaneServiceGrpcServer::new(adapter))
         } else {
             None
-        }
-    } else {
-        None
-    };
+        };
     // Mount gRPC OpenTelemetry OTLP trace service if `QuickwitService::Indexer` is enabled on node.
     let enable_opentelemetry_otlp_grpc_service =
         services.config.indexer_config.enable_otlp_endpoint;
+    let otlp_compression_encoding_opt =
+        compression_encoding(services.config.indexer_config.otlp_grpc_compression);
     let otlp_trace_service = if enable_opentelemetry_otlp_grpc_service
         && services.services.contains(&QuickwitService::Indexer)
     {
         enabled_grpc_services.insert("otlp-trace");
         let ingest_service = services.ingest_service.clone();
-        let trace_service = TraceServiceServer::new(OtlpGrpcTraceService::new(ingest_service))
-            .accept_compressed(CompressionEncoding::Gzip);
+        let mut trace_service = TraceServiceServer::new(otlp_trace_service(
+            ingest_service,
+            &services.config.indexer_config,
+            services.otlp_tail_sampler_opt.clone(),
+        ));
+        if let Some(compression_encoding) = otlp_compression_encoding_opt {
+            trace_service = trace_service.accept_compressed(compression_encoding);
+        }
         Some(trace_service)
     } else {
         None
@@ -116,12 +165,31 @@ pub(crate) async fn start_grpc_server(
     {
         enabled_grpc_services.insert("otlp-logs");
         let ingest_service = services.ingest_service.clone();
-        let logs_service = LogsServiceServer::new(OtlpGrpcLogsService::new(ingest_service))
-            .accept_compressed(CompressionEncoding::Gzip);
+        let mut logs_service = LogsServiceServer::new(otlp_logs_service(
+            ingest_service,
+            &services.config.indexer_config,
+        ));
+        if let Some(compression_encoding) = otlp_compression_encoding_opt {
+            logs_service = logs_service.accept_compressed(compression_encoding);
+        }
         Some(logs_service)
     } else {
         None
     };
+    let otlp_metrics_grpc_service = if enable_opentelemetry_otlp_grpc_service
+        && services.services.contains(&QuickwitService::Indexer)
+    {
+        enabled_grpc_services.insert("otlp-metrics");
+        let ingest_service = services.ingest_service.clone();
+        let mut metrics_service =
+            MetricsServiceServer::new(OtlpGrpcMetricsService::new(ingest_service));
+        if let Some(compression_encoding) = otlp_compression_encoding_opt {
+            metrics_service = metrics_service.accept_compressed(compression_encoding);
+        }
+        Some(metrics_service)
+    } else {
+        None
+    };
     // Mount gRPC search service if `QuickwitService::Searcher` is enabled on node.
     let search_grpc_service = if services.services.contains(&QuickwitService::Searcher) {
         enabled_grpc_services.insert("search");
@@ -132,26 +200,70 @@ pub(crate) async fn start_grpc_server(
         None
     };
     let enable_jaeger_endpoint = services.config.jaeger_config.enable_endpoint;
-    let jaeger_grpc_service =
-        if enable_jaeger_endpoint && services.services.contains(&QuickwitService::Searcher) {
-            enabled_grpc_services.insert("jaeger");
-            let search_service = services.search_service.clone();
-            Some(SpanReaderPluginServer::new(JaegerService::new(
-                services.config.jaeger_config.clone(),
-                search_service,
-            )))
-        } else {
-            None
-        };
+    let (jaeger_grpc_service, jaeger_dependencies_grpc_service) = if enable_jaeger_endpoint
+        && services.services.contains(&QuickwitService::Searcher)
+    {
+        enabled_grpc_services.insert("jaeger");
+        let search_service = services.search_service.clone();
+        let jaeger_service =
+            JaegerService::new(services.config.jaeger_config.clone(), search_service);
+        let mut span_reader_service = SpanReaderPluginServer::new(jaeger_service.clone());
+        let mut dependencies_reader_service = DependenciesReaderPluginServer::new(jaeger_service);
+        if let Some(compression_encoding) =
+            compression_encoding(services.config.jaeger_config.grpc_compression)
+        {
+            span_reader_service = span_reader_service
+                .accept_compressed(compression_encoding)
+                .send_compressed(compression_encoding);
+            dependencies_reader_service = dependencies_reader_service
+                .accept_compressed(compression_encoding)
+                .send_compressed(compression_encoding);
+        }
+        (Some(span_reader_service), Some(dependencies_reader_service))
+    } else {
+        (None, None)
+    };
+    // The health and reflection services only report the services mounted on the node.
+    let mounted_service_names: Vec<&'static str> = [
+        mounted_service_name(&gossip_grpc_service),
+        mounted_service_name(&metastore_grpc_service),
+        mounted_service_name(&control_plane_grpc_service),
+        mounted_service_name(&indexing_grpc_service),
+        mounted_service_name(&ingest_api_grpc_service),
+        mounted_service_name(&otlp_log_grpc_service),
+        mounted_service_name(&otlp_metrics_grpc_service),
+        mounted_service_name(&otlp_trace_service),
+        mounted_service_name(&search_grpc_service),
+        mounted_service_name(&jaeger_grpc_service),
+        mounted_service_name(&jaeger_dependencies_grpc_service),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    enabled_grpc_services.insert("health");
+    let (mut health_reporter, health_grpc_service) = tonic_health::server::health_reporter();
+    for service_name in &mounted_service_names {
+        health_reporter
+            .set_service_status(service_name, ServingStatus::Serving)
+            .await;
+    }
+    enabled_grpc_services.insert("reflection");
+    let reflection_grpc_service = reflection_service(&mounted_service_names)?;
+
     let server_router = server
+        .add_service(health_grpc_service)
+        .add_service(reflection_grpc_service)
+        .add_optional_service(gossip_grpc_service)
         .add_optional_service(metastore_grpc_service)
         .add_optional_service(control_plane_grpc_service)
         .add_optional_service(indexing_grpc_service)
         .add_optional_service(ingest_api_grpc_service)
         .add_optional_service(otlp_log_grpc_service)
+        .add_optional_service(otlp_metrics_grpc_service)
         .add_optional_service(otlp_trace_service)
         .add_optional_service(search_grpc_service)
-        .add_optional_service(jaeger_grpc_service);
+        .add_optional_service(jaeger_grpc_service)
+        .add_optional_service(jaeger_dependencies_grpc_service);
 
     info!(
         enabled_grpc_services=?enabled_grpc_services,
@@ -163,3 +275,66 @@ pub(crate) async fn start_grpc_server(
     serve_res?;
     Ok(())
 }
+
+fn mounted_service_name<S: NamedService>(service_opt: &Option<S>) -> Option<&'static str> {
+    service_opt.as_ref().map(|_| S::NAME)
+}
+
+/// Builds the gRPC reflection service, which lets clients such as `grpcurl` list and describe the
+/// services mounted on the node.
+fn reflection_service(
+    mounted_service_names: &[&'static str],
+) -> anyhow::Result<ServerReflectionServer<impl ServerReflection>> {
+    let mut reflection_service_builder = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(quickwit_proto::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(quickwit_proto::jaeger::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(quickwit_proto::opentelemetry::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(quickwit_control_plane::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(quickwit_ingest::FILE_DESCRIPTOR_SET)
+        .register_encoded_file_descriptor_set(
+            tonic_health::proto::GRPC_HEALTH_V1_FILE_DESCRIPTOR_SET,
+        )
+        .with_service_name(HEALTH_SERVICE_NAME)
+        .with_service_name(REFLECTION_SERVICE_NAME);
+
+    for service_name in mounted_service_names {
+        reflection_service_builder = reflection_service_builder.with_service_name(*service_name);
+    }
+    let reflection_service = reflection_service_builder
+        .build()
+        .context("Failed to build gRPC reflection service.")?;
+    Ok(reflection_service)
+}
+
+/// Loads the certificate and the private key presented by the gRPC server of the node.
+async fn server_tls_config(grpc_tls_config: &GrpcTlsConfig) -> anyhow::Result<ServerTlsConfig> {
+    let cert_pem = read_pem_file(&grpc_tls_config.cert_path).await?;
+    let key_pem = read_pem_file(&grpc_tls_config.key_path).await?;
+    let identity = Identity::from_pem(cert_pem, key_pem);
+    Ok(ServerTlsConfig::new().identity(identity))
+}
+
+/// Loads the certificate authority the node trusts when connecting to the gRPC servers of the
+/// other nodes.
+pub(crate) async fn client_tls_config(
+    grpc_tls_config: &GrpcTlsConfig,
+) -> anyhow::Result<ClientTlsConfig> {
+    let ca_cert_pem = read_pem_file(&grpc_tls_config.ca_cert_path).await?;
+    let client_tls_config = ClientTlsConfig::new()
+        .ca_certificate(Certificate::from_pem(ca_cert_pem))
+        .domain_name(grpc_tls_config.server_name.clone());
+    Ok(client_tls_config)
+}
+
+async fn read_pem_file(path: &Path) -> anyhow::Result<Vec<u8>> {
+    tokio::fs::read(path)
+        .await
+        .with_context(|| format!("Failed to read PEM file `{}`.", path.display()))
+}
+
+fn compression_encoding(grpc_compression: GrpcCompression) -> Option<CompressionEncoding> {
+    match grpc_compression {
+        GrpcCompression::None => None,
+        GrpcCompression::Gzip => Some(CompressionEncoding::Gzip),
+    }
+}
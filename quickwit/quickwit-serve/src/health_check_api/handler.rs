@@ -17,28 +17,43 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use quickwit_actors::{Healthz, Mailbox};
+use std::time::Duration;
+
+use quickwit_actors::{Healthz, Mailbox, SpawnContext};
 use quickwit_cluster::Cluster;
 use quickwit_indexing::IndexingService;
 use quickwit_janitor::JanitorService;
+use serde::{Deserialize, Serialize};
+use tokio::sync::watch;
 use tracing::error;
 use warp::hyper::StatusCode;
 use warp::reply::with_status;
 use warp::{Filter, Rejection};
 
+use super::readiness::{DependencyStatus, ReadinessReport};
 use crate::with_arg;
 
 #[derive(utoipa::OpenApi)]
-#[openapi(paths(get_liveness, get_readiness))]
+#[openapi(
+    paths(get_liveness, get_readiness, get_actors),
+    components(schemas(ReadinessResponse, ReadinessReport, DependencyStatus))
+)]
 pub struct HealthCheckApi;
 
+/// Timeout applied to the observation of each actor.
+const ACTOR_OBSERVATION_TIMEOUT: Duration = Duration::from_secs(1);
+
 /// Health check handlers.
 pub(crate) fn health_check_handlers(
     cluster: Cluster,
     indexer_service_opt: Option<Mailbox<IndexingService>>,
     janitor_service_opt: Option<Mailbox<JanitorService>>,
+    readiness_report_rx: watch::Receiver<ReadinessReport>,
+    spawn_ctx: SpawnContext,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
-    liveness_handler(indexer_service_opt, janitor_service_opt).or(readiness_handler(cluster))
+    liveness_handler(indexer_service_opt, janitor_service_opt)
+        .or(readiness_handler(cluster, readiness_report_rx))
+        .or(actors_handler(spawn_ctx))
 }
 
 fn liveness_handler(
@@ -54,13 +69,25 @@ fn liveness_handler(
 
 fn readiness_handler(
     cluster: Cluster,
+    readiness_report_rx: watch::Receiver<ReadinessReport>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     warp::path!("health" / "readyz")
         .and(warp::get())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
         .and(with_arg(cluster))
+        .and(with_arg(readiness_report_rx))
         .then(get_readiness)
 }
 
+fn actors_handler(
+    spawn_ctx: SpawnContext,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("health" / "actors")
+        .and(warp::get())
+        .and(with_arg(spawn_ctx))
+        .then(get_actors)
+}
+
 #[utoipa::path(
     get,
     tag = "Node Health",
@@ -97,6 +124,22 @@ async fn get_liveness(
     with_status(warp::reply::json(&is_live), status_code)
 }
 
+#[derive(Debug, Default, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(deny_unknown_fields)]
+struct ReadinessQueryString {
+    /// If set, the response details the status of each dependency of the node.
+    #[serde(default)]
+    verbose: bool,
+}
+
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+struct ReadinessResponse {
+    is_ready: bool,
+    /// Status of the dependencies of the node as of their last check.
+    dependencies: ReadinessReport,
+}
+
 #[utoipa::path(
     get,
     tag = "Node Health",
@@ -105,23 +148,77 @@ async fn get_liveness(
         (status = 200, description = "The service is ready.", body = bool),
         (status = 503, description = "The service is not ready.", body = bool),
     ),
+    params(ReadinessQueryString),
 )]
 /// Get Node Readiness
-async fn get_readiness(cluster: Cluster) -> impl warp::Reply {
-    let is_ready = cluster.is_self_node_ready().await;
+///
+/// The node is ready if it is a ready member of the cluster, which requires its metastore and
+/// storage to be available, and if the sources of the indexing pipelines it runs are available.
+/// The dependencies are checked periodically in the background: the endpoint returns the results
+/// of the last checks.
+async fn get_readiness(
+    query_string: ReadinessQueryString,
+    cluster: Cluster,
+    readiness_report_rx: watch::Receiver<ReadinessReport>,
+) -> impl warp::Reply {
+    let readiness_report = readiness_report_rx.borrow().clone();
+    let is_ready = cluster.is_self_node_ready().await && readiness_report.are_sources_available();
     let status_code = if is_ready {
         StatusCode::OK
     } else {
         StatusCode::SERVICE_UNAVAILABLE
     };
+    if query_string.verbose {
+        let readiness_response = ReadinessResponse {
+            is_ready,
+            dependencies: readiness_report,
+        };
+        return with_status(warp::reply::json(&readiness_response), status_code);
+    }
     with_status(warp::reply::json(&is_ready), status_code)
 }
 
+#[utoipa::path(
+    get,
+    tag = "Node Health",
+    path = "/actors",
+    responses(
+        (status = 200, description = "Successfully observed the actors of the node.", body = Object),
+    ),
+)]
+/// Get Node Actors
+///
+/// Returns the state of the actors running on the node, arranged as a hierarchy: the actors of a
+/// pipeline are listed as children of the pipeline, and supervised actors as children of their
+/// supervisor.
+async fn get_actors(spawn_ctx: SpawnContext) -> impl warp::Reply {
+    let actor_observations = spawn_ctx.observe(ACTOR_OBSERVATION_TIMEOUT).await;
+    warp::reply::json(&actor_observations)
+}
+
 #[cfg(test)]
 mod tests {
 
+    use std::collections::BTreeMap;
+
     use chitchat::transport::ChannelTransport;
+    use quickwit_actors::{Actor, Universe};
     use quickwit_cluster::create_cluster_for_test;
+    use serde_json::{json, Value as JsonValue};
+    use tokio::sync::watch;
+
+    use super::{DependencyStatus, ReadinessReport};
+
+    #[derive(Default)]
+    struct ObservableActor;
+
+    impl Actor for ObservableActor {
+        type ObservableState = usize;
+
+        fn observable_state(&self) -> Self::ObservableState {
+            42
+        }
+    }
 
     #[tokio::test]
     async fn test_rest_search_api_health_checks() {
@@ -129,7 +226,16 @@ mod tests {
         let cluster = create_cluster_for_test(Vec::new(), &[], &transport, false)
             .await
             .unwrap();
-        let health_check_handler = super::health_check_handlers(cluster.clone(), None, None);
+        let universe = Universe::with_accelerated_time();
+        let (_readiness_report_tx, readiness_report_rx) =
+            watch::channel(ReadinessReport::default());
+        let health_check_handler = super::health_check_handlers(
+            cluster.clone(),
+            None,
+            None,
+            readiness_report_rx,
+            universe.spawn_ctx().clone(),
+        );
         let resp = warp::test::request()
             .path("/health/livez")
             .reply(&health_check_handler)
@@ -147,4 +253,111 @@ mod tests {
             .await;
         assert_eq!(resp.status(), 200);
     }
+
+    #[tokio::test]
+    async fn test_rest_readiness_check_with_unavailable_source() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        let universe = Universe::with_accelerated_time();
+        let available = DependencyStatus {
+            is_available: true,
+            error: None,
+            checked_at: 1,
+        };
+        let (readiness_report_tx, readiness_report_rx) = watch::channel(ReadinessReport {
+            metastore: Some(available.clone()),
+            storage: Some(available.clone()),
+            sources: BTreeMap::from_iter([("my-index:0/my-source".to_string(), available)]),
+        });
+        let health_check_handler = super::health_check_handlers(
+            cluster,
+            None,
+            None,
+            readiness_report_rx,
+            universe.spawn_ctx().clone(),
+        );
+        let resp = warp::test::request()
+            .path("/health/readyz")
+            .reply(&health_check_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        readiness_report_tx.send_modify(|readiness_report| {
+            readiness_report.sources.insert(
+                "my-index:0/my-source".to_string(),
+                DependencyStatus {
+                    is_available: false,
+                    error: Some("Broker transport failure.".to_string()),
+                    checked_at: 2,
+                },
+            );
+        });
+        let resp = warp::test::request()
+            .path("/health/readyz")
+            .reply(&health_check_handler)
+            .await;
+        assert_eq!(resp.status(), 503);
+        let is_ready: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(is_ready, json!(false));
+
+        let resp = warp::test::request()
+            .path("/health/readyz?verbose=true")
+            .reply(&health_check_handler)
+            .await;
+        assert_eq!(resp.status(), 503);
+        let readiness_response: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_readiness_response = json!({
+            "is_ready": false,
+            "dependencies": {
+                "metastore": {"is_available": true, "checked_at": 1},
+                "storage": {"is_available": true, "checked_at": 1},
+                "sources": {
+                    "my-index:0/my-source": {
+                        "is_available": false,
+                        "error": "Broker transport failure.",
+                        "checked_at": 2,
+                    },
+                },
+            },
+        });
+        assert_eq!(readiness_response, expected_readiness_response);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_rest_actors_health_check() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &[], &transport, false)
+            .await
+            .unwrap();
+        let universe = Universe::with_accelerated_time();
+        let (_mailbox, _handle) = universe
+            .spawn_builder::<ObservableActor>()
+            .supervise_default();
+        let (_readiness_report_tx, readiness_report_rx) =
+            watch::channel(ReadinessReport::default());
+        let health_check_handler = super::health_check_handlers(
+            cluster,
+            None,
+            None,
+            readiness_report_rx,
+            universe.spawn_ctx().clone(),
+        );
+        let resp = warp::test::request()
+            .path("/health/actors")
+            .reply(&health_check_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let actor_observations: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let actor_observations = actor_observations.as_array().unwrap();
+        assert_eq!(actor_observations.len(), 1);
+        assert_eq!(actor_observations[0]["obs"]["num_errors"], 0);
+
+        let children = actor_observations[0]["children"].as_array().unwrap();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0]["obs"], 42);
+        universe.assert_quit().await;
+    }
 }
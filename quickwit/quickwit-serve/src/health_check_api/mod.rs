@@ -18,5 +18,7 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 mod handler;
+mod readiness;
 
 pub(crate) use handler::{health_check_handlers, HealthCheckApi};
+pub(crate) use readiness::{ReadinessChecker, ReadinessReport};
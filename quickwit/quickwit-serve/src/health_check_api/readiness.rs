@@ -0,0 +1,295 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Context;
+use futures::future::join_all;
+use futures::Future;
+use itertools::Itertools;
+use quickwit_cluster::Cluster;
+use quickwit_common::uri::Uri;
+use quickwit_config::service::QuickwitService;
+use quickwit_indexing::check_source_connectivity;
+use quickwit_metastore::Metastore;
+use quickwit_proto::IndexUid;
+use quickwit_storage::StorageUriResolver;
+use serde::Serialize;
+use time::OffsetDateTime;
+use tracing::warn;
+
+/// Timeout applied to the check of each dependency.
+const DEPENDENCY_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Status of a dependency of the node as of its last check.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, utoipa::ToSchema)]
+pub(crate) struct DependencyStatus {
+    pub is_available: bool,
+    /// Error returned by the last check, if it failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Unix timestamp (seconds) of the last check.
+    pub checked_at: i64,
+}
+
+impl DependencyStatus {
+    fn from_check_result(check_res: anyhow::Result<()>) -> Self {
+        Self {
+            is_available: check_res.is_ok(),
+            error: check_res.err().map(|error| format!("{error:#}")),
+            checked_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+}
+
+/// Cached results of the last check of the dependencies of the node. The dependencies not
+/// required by the services of the node are not checked.
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, utoipa::ToSchema)]
+pub(crate) struct ReadinessReport {
+    pub metastore: Option<DependencyStatus>,
+    /// Storage of the default index root URI, checked on the indexer, searcher, and janitor
+    /// nodes.
+    pub storage: Option<DependencyStatus>,
+    /// Sources of the indexing pipelines running on the node, keyed by `{index_uid}/{source_id}`.
+    pub sources: BTreeMap<String, DependencyStatus>,
+}
+
+impl ReadinessReport {
+    /// Returns whether the node can serve requests: its metastore and storage are available. The
+    /// node leaves the set of ready members of the cluster otherwise.
+    pub fn can_serve_requests(&self) -> bool {
+        self.metastore
+            .iter()
+            .chain(self.storage.iter())
+            .all(|status| status.is_available)
+    }
+
+    /// Returns whether the sources of the indexing pipelines running on the node are available.
+    pub fn are_sources_available(&self) -> bool {
+        self.sources.values().all(|status| status.is_available)
+    }
+}
+
+/// Checks the availability of the dependencies of the node: the metastore, the storage, and the
+/// sources of the indexing pipelines running on the node.
+#[derive(Clone)]
+pub(crate) struct ReadinessChecker {
+    cluster: Cluster,
+    metastore: Arc<dyn Metastore>,
+    storage_resolver: StorageUriResolver,
+    default_index_root_uri: Uri,
+    services: HashSet<QuickwitService>,
+}
+
+impl fmt::Debug for ReadinessChecker {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("ReadinessChecker")
+            .field("default_index_root_uri", &self.default_index_root_uri)
+            .field("services", &self.services)
+            .finish()
+    }
+}
+
+impl ReadinessChecker {
+    pub fn new(
+        cluster: Cluster,
+        metastore: Arc<dyn Metastore>,
+        storage_resolver: StorageUriResolver,
+        default_index_root_uri: Uri,
+        services: HashSet<QuickwitService>,
+    ) -> Self {
+        Self {
+            cluster,
+            metastore,
+            storage_resolver,
+            default_index_root_uri,
+            services,
+        }
+    }
+
+    /// Checks the dependencies required by the services of the node.
+    pub async fn check(&self) -> ReadinessReport {
+        let metastore = check_dependency("metastore", self.metastore.check_connectivity()).await;
+
+        let storage = if [
+            QuickwitService::Indexer,
+            QuickwitService::Searcher,
+            QuickwitService::Janitor,
+        ]
+        .iter()
+        .any(|service| self.services.contains(service))
+        {
+            Some(check_dependency("storage", self.check_storage()).await)
+        } else {
+            None
+        };
+        let sources = if self.services.contains(&QuickwitService::Indexer) {
+            self.check_sources().await
+        } else {
+            BTreeMap::new()
+        };
+        ReadinessReport {
+            metastore: Some(metastore),
+            storage,
+            sources,
+        }
+    }
+
+    async fn check_storage(&self) -> anyhow::Result<()> {
+        let storage = self
+            .storage_resolver
+            .resolve(&self.default_index_root_uri)?;
+        storage.check_connectivity().await
+    }
+
+    async fn check_sources(&self) -> BTreeMap<String, DependencyStatus> {
+        let live_nodes = self.cluster.live_nodes().await;
+        let self_node = match live_nodes.iter().find(|node| node.is_self_node()) {
+            Some(self_node) => self_node,
+            None => return BTreeMap::new(),
+        };
+        let index_source_ids: Vec<(String, String)> = self_node
+            .indexing_tasks()
+            .iter()
+            .map(|indexing_task| {
+                (
+                    indexing_task.index_uid.clone(),
+                    indexing_task.source_id.clone(),
+                )
+            })
+            .unique()
+            .collect();
+        let source_check_futures =
+            index_source_ids
+                .into_iter()
+                .map(|(index_uid, source_id)| async move {
+                    let source_key = format!("{index_uid}/{source_id}");
+                    let source_check = self.check_source(IndexUid::from(index_uid), &source_id);
+                    let source_status = check_dependency(&source_key, source_check).await;
+                    (source_key, source_status)
+                });
+        join_all(source_check_futures).await.into_iter().collect()
+    }
+
+    async fn check_source(&self, index_uid: IndexUid, source_id: &str) -> anyhow::Result<()> {
+        let index_metadata = self.metastore.index_metadata_strict(&index_uid).await?;
+        let source_config = index_metadata
+            .sources
+            .get(source_id)
+            .with_context(|| format!("Source `{source_id}` does not exist."))?;
+        check_source_connectivity(source_config).await
+    }
+}
+
+async fn check_dependency(
+    dependency: &str,
+    check_future: impl Future<Output = anyhow::Result<()>>,
+) -> DependencyStatus {
+    let check_res = match tokio::time::timeout(DEPENDENCY_CHECK_TIMEOUT, check_future).await {
+        Ok(check_res) => check_res,
+        Err(_) => Err(anyhow::anyhow!(
+            "Check timed out after {} seconds.",
+            DEPENDENCY_CHECK_TIMEOUT.as_secs()
+        )),
+    };
+    if let Err(error) = &check_res {
+        warn!(dependency=%dependency, error=?error, "Dependency is unavailable.");
+    }
+    DependencyStatus::from_check_result(check_res)
+}
+
+#[cfg(test)]
+mod tests {
+    use chitchat::transport::ChannelTransport;
+    use quickwit_cluster::create_cluster_for_test;
+    use quickwit_metastore::MockMetastore;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_readiness_checker() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["searcher"], &transport, true)
+            .await
+            .unwrap();
+        let mut mock_metastore = MockMetastore::new();
+        mock_metastore
+            .expect_check_connectivity()
+            .returning(|| Ok(()));
+        let readiness_checker = ReadinessChecker::new(
+            cluster.clone(),
+            Arc::new(mock_metastore),
+            StorageUriResolver::for_test(),
+            Uri::for_test("ram:///indexes"),
+            HashSet::from_iter([QuickwitService::Searcher]),
+        );
+        let readiness_report = readiness_checker.check().await;
+        assert!(readiness_report.metastore.unwrap().is_available);
+        assert!(readiness_report.storage.unwrap().is_available);
+        assert!(readiness_report.sources.is_empty());
+
+        let mut mock_metastore = MockMetastore::new();
+        mock_metastore
+            .expect_check_connectivity()
+            .returning(|| Err(anyhow::anyhow!("connection refused")));
+        let readiness_checker = ReadinessChecker::new(
+            cluster,
+            Arc::new(mock_metastore),
+            StorageUriResolver::for_test(),
+            Uri::for_test("ram:///indexes"),
+            HashSet::from_iter([QuickwitService::Metastore]),
+        );
+        let readiness_report = readiness_checker.check().await;
+        assert!(!readiness_report.can_serve_requests());
+        assert!(readiness_report.storage.is_none());
+
+        let metastore_status = readiness_report.metastore.unwrap();
+        assert!(!metastore_status.is_available);
+        assert_eq!(metastore_status.error.unwrap(), "connection refused");
+    }
+
+    #[test]
+    fn test_readiness_report() {
+        let available = DependencyStatus::from_check_result(Ok(()));
+        let unavailable = DependencyStatus::from_check_result(Err(anyhow::anyhow!("error")));
+
+        let readiness_report = ReadinessReport::default();
+        assert!(readiness_report.can_serve_requests());
+        assert!(readiness_report.are_sources_available());
+
+        let readiness_report = ReadinessReport {
+            metastore: Some(available.clone()),
+            storage: Some(available.clone()),
+            sources: BTreeMap::from_iter([("index:0/kafka".to_string(), unavailable.clone())]),
+        };
+        assert!(readiness_report.can_serve_requests());
+        assert!(!readiness_report.are_sources_available());
+
+        let readiness_report = ReadinessReport {
+            metastore: Some(available),
+            storage: Some(unavailable),
+            sources: BTreeMap::new(),
+        };
+        assert!(!readiness_report.can_serve_requests());
+    }
+}
@@ -24,12 +24,16 @@ use hyper::header::CONTENT_TYPE;
 use quickwit_common::uri::Uri;
 use quickwit_common::FileEntry;
 use quickwit_config::{
-    load_source_config_from_user_config, ConfigFormat, QuickwitConfig, SourceConfig, SourceParams,
-    CLI_INGEST_SOURCE_ID, INGEST_API_SOURCE_ID,
+    load_source_config_from_user_config, AuthRole, ConfigFormat, QuickwitConfig, SourceConfig,
+    SourceParams, CLI_INGEST_SOURCE_ID, INGEST_API_SOURCE_ID,
 };
 use quickwit_core::{IndexService, IndexServiceError};
+use quickwit_indexing::check_source_connectivity;
+use quickwit_janitor::SplitRemovalInfo;
+use quickwit_metastore::checkpoint::SourceCheckpoint;
 use quickwit_metastore::{
-    IndexMetadata, ListSplitsQuery, Metastore, MetastoreError, Split, SplitState,
+    aggregate_field_stats, checkpoint_rewind_target_at, AuditEvent, IndexFieldStats, IndexMetadata,
+    ListSplitsQuery, Metastore, MetastoreError, Split, SplitState,
 };
 use quickwit_proto::IndexUid;
 use serde::de::DeserializeOwned;
@@ -38,6 +42,7 @@ use thiserror::Error;
 use tracing::info;
 use warp::{Filter, Rejection};
 
+use crate::auth::{with_principal, Principal};
 use crate::format::extract_format_from_qs;
 use crate::json_api_response::make_json_api_response;
 use crate::simple_list::{from_simple_list, to_simple_list};
@@ -47,18 +52,31 @@ use crate::with_arg;
 #[openapi(
     paths(
         create_index,
+        update_index,
         clear_index,
         delete_index,
+        restore_index,
+        get_index_metadata,
+        garbage_collect_index,
+        pause_merges,
+        resume_merges,
         get_indexes_metadatas,
         list_splits,
         describe_index,
+        get_field_stats,
+        list_audit_events,
+        list_tenants,
         mark_splits_for_deletion,
         create_source,
+        list_sources,
+        get_source,
+        check_source,
         reset_source_checkpoint,
+        rewind_source_checkpoint,
         toggle_source,
         delete_source,
     ),
-    components(schemas(ToggleSource, SplitsForDeletion, IndexStats))
+    components(schemas(ToggleSource, SplitsForDeletion, IndexStats, SourceCheck))
 )]
 pub struct IndexApi;
 
@@ -69,18 +87,32 @@ pub fn index_management_handlers(
     // Indexes handlers.
     get_index_metadata_handler(index_service.metastore())
         .or(get_indexes_metadatas_handler(index_service.metastore()))
-        .or(create_index_handler(index_service.clone(), quickwit_config))
+        .or(create_index_handler(
+            index_service.clone(),
+            quickwit_config.clone(),
+        ))
+        .or(update_index_handler(index_service.clone(), quickwit_config))
         .or(clear_index_handler(index_service.clone()))
         .or(delete_index_handler(index_service.clone()))
+        .or(restore_index_handler(index_service.clone()))
+        .or(garbage_collect_index_handler(index_service.clone()))
+        .or(pause_merges_handler(index_service.metastore()))
+        .or(resume_merges_handler(index_service.metastore()))
+        .or(list_audit_events_handler(index_service.metastore()))
+        .or(list_tenants_handler(index_service.metastore()))
         // Splits handlers
         .or(list_splits_handler(index_service.metastore()))
         .or(describe_index_handler(index_service.metastore()))
+        .or(get_field_stats_handler(index_service.metastore()))
         .or(mark_splits_for_deletion_handler(index_service.metastore()))
         // Sources handlers.
         .or(reset_source_checkpoint_handler(index_service.metastore()))
+        .or(rewind_source_checkpoint_handler(index_service.metastore()))
         .or(toggle_source_handler(index_service.metastore()))
         .or(create_source_handler(index_service.clone()))
+        .or(list_sources_handler(index_service.metastore()))
         .or(get_source_handler(index_service.metastore()))
+        .or(check_source_handler(index_service.metastore()))
         .or(delete_source_handler(index_service.metastore()))
 }
 
@@ -127,6 +159,19 @@ fn get_index_metadata_handler(
         .map(make_json_api_response)
 }
 
+#[utoipa::path(
+    get,
+    tag = "Indexes",
+    path = "/indexes/{index_id}",
+    responses(
+        // We return `VersionedIndexMetadata` as it's the serialized model view.
+        (status = 200, description = "Successfully fetched index metadata.", body = VersionedIndexMetadata)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to get the metadata of."),
+    )
+)]
+/// Gets index metadata.
 async fn get_index_metadata(
     index_id: String,
     metastore: Arc<dyn Metastore>,
@@ -140,6 +185,7 @@ fn get_indexes_metadatas_handler(
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     warp::path!("indexes")
         .and(warp::get())
+        .and(with_principal())
         .and(with_arg(metastore))
         .then(get_indexes_metadatas)
         .and(extract_format_from_qs())
@@ -158,6 +204,7 @@ struct IndexStats {
     pub timestamp_field_name: Option<String>,
     pub min_timestamp: Option<i64>,
     pub max_timestamp: Option<i64>,
+    pub merges_paused: bool,
 }
 
 #[utoipa::path(
@@ -203,6 +250,7 @@ async fn describe_index(
         }
     }
 
+    let merges_paused = index_metadata.merges_paused;
     let index_config = index_metadata.into_index_config();
     let index_stats = IndexStats {
         index_id,
@@ -213,6 +261,7 @@ async fn describe_index(
         timestamp_field_name: index_config.doc_mapping.timestamp_field,
         min_timestamp,
         max_timestamp,
+        merges_paused,
     };
 
     Ok(index_stats)
@@ -229,6 +278,103 @@ fn describe_index_handler(
         .map(make_json_api_response)
 }
 
+#[utoipa::path(
+    get,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/field-stats",
+    responses(
+        (status = 200, description = "Successfully fetched the field statistics of the index.", body = [IndexFieldStats])
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to fetch the field statistics of."),
+    )
+)]
+
+/// Returns the per-field statistics of an index, aggregated over its published splits.
+async fn get_field_stats(
+    index_id: String,
+    metastore: Arc<dyn Metastore>,
+) -> Result<Vec<IndexFieldStats>, MetastoreError> {
+    info!(index_id = %index_id, "get-field-stats");
+    let index_uid = metastore.index_metadata(&index_id).await?.index_uid;
+    let query = ListSplitsQuery::for_index(index_uid).with_split_state(SplitState::Published);
+    let splits = metastore.list_splits(query).await?;
+    let field_stats = aggregate_field_stats(splits.iter().map(|split| &split.split_metadata));
+    Ok(field_stats)
+}
+
+fn get_field_stats_handler(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "field-stats")
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(get_field_stats)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/audit-log",
+    responses(
+        (status = 200, description = "Successfully fetched the audit log of the index.", body = [AuditEvent])
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to fetch the audit log of."),
+    )
+)]
+
+/// Lists the audit events of an index, oldest first.
+///
+/// The audit log is kept after the index is deleted, so it can be fetched for indexes that no
+/// longer exist.
+async fn list_audit_events(
+    index_id: String,
+    metastore: Arc<dyn Metastore>,
+) -> Result<Vec<AuditEvent>, MetastoreError> {
+    info!(index_id = %index_id, "list-audit-events");
+    metastore.list_audit_events(&index_id).await
+}
+
+fn list_audit_events_handler(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "audit-log")
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(list_audit_events)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Indexes",
+    path = "/tenants",
+    responses(
+        (status = 200, description = "Successfully fetched the tenants.", body = [String])
+    )
+)]
+
+/// Lists the tenants owning at least one index, sorted by tenant ID.
+async fn list_tenants(metastore: Arc<dyn Metastore>) -> Result<Vec<String>, MetastoreError> {
+    info!("list-tenants");
+    metastore.list_tenants().await
+}
+
+fn list_tenants_handler(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("tenants")
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(list_tenants)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
 /// This struct represents the QueryString passed to
 /// the rest API to filter splits.
 #[derive(Debug, Clone, Deserialize, Serialize, utoipa::IntoParams, utoipa::ToSchema, Default)]
@@ -254,6 +400,38 @@ pub struct ListSplitsQueryParams {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub end_create_timestamp: Option<i64>,
+    /// If set, returns at most `page_size` splits sorted by split ID.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub page_size: Option<usize>,
+    /// If set, restrict splits to those whose split ID is strictly greater than this one. Used
+    /// along with `page_size` to fetch the next page of splits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub after_split_id: Option<String>,
+    /// If set, restrict splits to those carrying all the labels of this comma-separated list of
+    /// `key:value` labels.
+    #[serde(deserialize_with = "from_split_label_list")]
+    #[serde(serialize_with = "to_simple_list")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub labels: Option<Vec<String>>,
+}
+
+/// Deserializes a comma-separated list of `key:value` split labels.
+fn from_split_label_list<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
+where D: serde::Deserializer<'de> {
+    let labels_opt: Option<Vec<String>> = from_simple_list(deserializer)?;
+    if let Some(label) = labels_opt
+        .iter()
+        .flatten()
+        .find(|label| !label.contains(':'))
+    {
+        return Err(serde::de::Error::custom(format!(
+            "invalid split label `{label}`, expected `key:value`"
+        )));
+    }
+    Ok(labels_opt)
 }
 
 #[utoipa::path(
@@ -290,6 +468,18 @@ async fn list_splits(
     if let Some(end_created_timestamp) = list_split_query.end_create_timestamp {
         query = query.with_create_timestamp_lt(end_created_timestamp);
     }
+    for label in list_split_query.labels.iter().flatten() {
+        if let Some((key, value)) = label.split_once(':') {
+            query = query.with_label(key, value);
+        }
+    }
+    if let Some(after_split_id) = list_split_query.after_split_id {
+        query = query.with_after_split_id(after_split_id).sort_by_split_id();
+    }
+    if let Some(page_size) = list_split_query.page_size {
+        let splits_page = metastore.list_splits_page(query, page_size).await?;
+        return Ok(splits_page.splits);
+    }
     metastore.list_splits(query).await
 }
 
@@ -362,12 +552,20 @@ fn mark_splits_for_deletion_handler(
         (status = 200, description = "Successfully fetched all indexes.", body = [VersionedIndexMetadata])
     ),
 )]
-/// Gets indexes metadata.
+/// Gets indexes metadata. Only the indexes the caller manages are listed.
 async fn get_indexes_metadatas(
+    principal_opt: Option<Principal>,
     metastore: Arc<dyn Metastore>,
 ) -> Result<Vec<IndexMetadata>, MetastoreError> {
     info!("get-indexes-metadatas");
-    metastore.list_indexes_metadatas().await
+    let mut indexes_metadatas = metastore.list_indexes_metadatas().await?;
+
+    if let Some(principal) = principal_opt {
+        indexes_metadatas.retain(|index_metadata| {
+            principal.is_granted(AuthRole::Admin, index_metadata.index_id())
+        });
+    }
+    Ok(indexes_metadatas)
 }
 
 #[derive(Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
@@ -427,6 +625,71 @@ async fn create_index(
         .await
 }
 
+#[derive(Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
+#[into_params(parameter_in = Query)]
+struct UpdateIndexQueryParams {
+    /// Config version the index is expected to be at. If set, the update is rejected with a
+    /// `409 Conflict` when the index config was updated in the meantime.
+    expected_config_version: Option<u64>,
+}
+
+fn update_index_handler(
+    index_service: Arc<IndexService>,
+    quickwit_config: Arc<QuickwitConfig>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String)
+        .and(warp::put())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(config_format_filter())
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::filters::body::bytes())
+        .and(with_arg(index_service))
+        .and(with_arg(quickwit_config))
+        .then(update_index)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Indexes",
+    path = "/indexes/{index_id}",
+    request_body = VersionedIndexConfig,
+    responses(
+        // We return `VersionedIndexMetadata` as it's the serialized model view.
+        (status = 200, description = "Successfully updated index.", body = VersionedIndexMetadata),
+        (status = 409, description = "The index config was updated concurrently.")
+    ),
+    params(
+        UpdateIndexQueryParams,
+        ("index_id" = String, Path, description = "The index ID to update."),
+    )
+)]
+/// Updates index config.
+async fn update_index(
+    index_id: String,
+    update_index_query_params: UpdateIndexQueryParams,
+    config_format: ConfigFormat,
+    index_config_bytes: Bytes,
+    index_service: Arc<IndexService>,
+    quickwit_config: Arc<QuickwitConfig>,
+) -> Result<IndexMetadata, IndexServiceError> {
+    let index_config = quickwit_config::load_index_config_from_user_config(
+        config_format,
+        &index_config_bytes,
+        &quickwit_config.default_index_root_uri,
+    )
+    .map_err(IndexServiceError::InvalidConfig)?;
+    info!(index_id = %index_id, expected_config_version = ?update_index_query_params.expected_config_version, "update-index");
+    index_service
+        .update_index(
+            &index_id,
+            index_config,
+            update_index_query_params.expected_config_version,
+        )
+        .await
+}
+
 fn clear_index_handler(
     index_service: Arc<IndexService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
@@ -503,6 +766,150 @@ async fn delete_index(
         .await
 }
 
+fn restore_index_handler(
+    index_service: Arc<IndexService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "restore")
+        .and(warp::put())
+        .and(with_arg(index_service))
+        .then(restore_index)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/restore",
+    responses(
+        // We return `VersionedIndexMetadata` as it's the serialized model view.
+        (status = 200, description = "Successfully restored index.", body = VersionedIndexMetadata)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to restore from the trash."),
+    )
+)]
+/// Restores an index from the trash.
+async fn restore_index(
+    index_id: String,
+    index_service: Arc<IndexService>,
+) -> Result<IndexMetadata, IndexServiceError> {
+    info!(index_id = %index_id, "restore-index");
+    index_service.restore_index(&index_id).await
+}
+
+#[derive(Deserialize, utoipa::IntoParams, utoipa::ToSchema)]
+#[into_params(parameter_in = Query)]
+struct GarbageCollectIndexQueryParam {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+fn garbage_collect_index_handler(
+    index_service: Arc<IndexService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "garbage-collect")
+        .and(warp::put())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(with_arg(index_service))
+        .then(garbage_collect_index)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/garbage-collect",
+    responses(
+        (status = 200, description = "Successfully garbage collected index.", body = SplitRemovalInfo)
+    ),
+    params(
+        GarbageCollectIndexQueryParam,
+        ("index_id" = String, Path, description = "The index ID to garbage collect."),
+    )
+)]
+/// Garbage collects the stale staged splits and the splits marked for deletion of an index right
+/// away, using the staged split grace period of the index.
+async fn garbage_collect_index(
+    index_id: String,
+    garbage_collect_query_param: GarbageCollectIndexQueryParam,
+    index_service: Arc<IndexService>,
+) -> Result<SplitRemovalInfo, IndexServiceError> {
+    let dry_run = garbage_collect_query_param.dry_run;
+    info!(index_id = %index_id, dry_run = dry_run, "garbage-collect-index");
+    index_service
+        .garbage_collect_index(&index_id, None, dry_run)
+        .await
+}
+
+fn pause_merges_handler(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "merges" / "pause")
+        .and(warp::put())
+        .and(with_arg(metastore))
+        .then(pause_merges)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/merges/pause",
+    responses(
+        (status = 200, description = "Successfully paused merges.")
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to pause the merges of."),
+    )
+)]
+/// Pauses the merges of an index. No new merge is planned until the merges are resumed, ongoing
+/// merges run to completion.
+async fn pause_merges(
+    index_id: String,
+    metastore: Arc<dyn Metastore>,
+) -> Result<(), IndexServiceError> {
+    info!(index_id = %index_id, "pause-merges");
+    let index_uid = metastore.index_metadata(&index_id).await?.index_uid;
+    metastore.set_merges_paused(index_uid, true).await?;
+    Ok(())
+}
+
+fn resume_merges_handler(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "merges" / "resume")
+        .and(warp::put())
+        .and(with_arg(metastore))
+        .then(resume_merges)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    put,
+    tag = "Indexes",
+    path = "/indexes/{index_id}/merges/resume",
+    responses(
+        (status = 200, description = "Successfully resumed merges.")
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to resume the merges of."),
+    )
+)]
+/// Resumes the merges of an index.
+async fn resume_merges(
+    index_id: String,
+    metastore: Arc<dyn Metastore>,
+) -> Result<(), IndexServiceError> {
+    info!(index_id = %index_id, "resume-merges");
+    let index_uid = metastore.index_metadata(&index_id).await?.index_uid;
+    metastore.set_merges_paused(index_uid, false).await?;
+    Ok(())
+}
+
 fn create_source_handler(
     index_service: Arc<IndexService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
@@ -556,6 +963,45 @@ async fn create_source(
     index_service.create_source(index_uid, source_config).await
 }
 
+fn list_sources_handler(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "sources")
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(list_sources)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Sources",
+    path = "/indexes/{index_id}/sources",
+    responses(
+        // We return `VersionedSourceConfig` as it's the serialized model view.
+        (status = 200, description = "Successfully fetched sources.", body = [VersionedSourceConfig])
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to list the sources of."),
+    )
+)]
+/// Lists the sources of an index.
+async fn list_sources(
+    index_id: String,
+    metastore: Arc<dyn Metastore>,
+) -> Result<Vec<SourceConfig>, MetastoreError> {
+    info!(index_id = %index_id, "list-sources");
+    let mut source_configs: Vec<SourceConfig> = metastore
+        .index_metadata(&index_id)
+        .await?
+        .sources
+        .into_values()
+        .collect();
+    source_configs.sort_by(|left, right| left.source_id.cmp(&right.source_id));
+    Ok(source_configs)
+}
+
 fn get_source_handler(
     metastore: Arc<dyn Metastore>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
@@ -567,6 +1013,20 @@ fn get_source_handler(
         .map(make_json_api_response)
 }
 
+#[utoipa::path(
+    get,
+    tag = "Sources",
+    path = "/indexes/{index_id}/sources/{source_id}",
+    responses(
+        // We return `VersionedSourceConfig` as it's the serialized model view.
+        (status = 200, description = "Successfully fetched source.", body = VersionedSourceConfig)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the source."),
+        ("source_id" = String, Path, description = "The source ID to get."),
+    )
+)]
+/// Gets source.
 async fn get_source(
     index_id: String,
     source_id: String,
@@ -585,6 +1045,64 @@ async fn get_source(
     Ok(source_config)
 }
 
+fn check_source_handler(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "sources" / String / "check")
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(check_source)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+/// Result of the connectivity check of a source.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct SourceCheck {
+    pub source_id: String,
+    /// Whether Quickwit could connect to the source and read its partitions.
+    pub connected: bool,
+    /// Reason why the connectivity check failed.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[utoipa::path(
+    get,
+    tag = "Sources",
+    path = "/indexes/{index_id}/sources/{source_id}/check",
+    responses(
+        (status = 200, description = "Successfully checked source connectivity.", body = SourceCheck)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the source."),
+        ("source_id" = String, Path, description = "The source ID to check."),
+    )
+)]
+/// Checks that Quickwit can connect to a source, as done when the source is created.
+async fn check_source(
+    index_id: String,
+    source_id: String,
+    metastore: Arc<dyn Metastore>,
+) -> Result<SourceCheck, MetastoreError> {
+    info!(index_id = %index_id, source_id = %source_id, "check-source");
+    let index_metadata = metastore.index_metadata(&index_id).await?;
+    let source_config = index_metadata.sources.get(&source_id).ok_or_else(|| {
+        MetastoreError::SourceDoesNotExist {
+            source_id: source_id.clone(),
+        }
+    })?;
+    let error = check_source_connectivity(source_config)
+        .await
+        .err()
+        .map(|error| format!("{error:#}"));
+    Ok(SourceCheck {
+        source_id,
+        connected: error.is_none(),
+        error,
+    })
+}
+
 fn reset_source_checkpoint_handler(
     metastore: Arc<dyn Metastore>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
@@ -621,6 +1139,72 @@ async fn reset_source_checkpoint(
         .await
 }
 
+fn rewind_source_checkpoint_handler(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("indexes" / String / "sources" / String / "rewind-checkpoint")
+        .and(warp::put())
+        .and(json_body())
+        .and(with_arg(metastore))
+        .then(rewind_source_checkpoint)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+/// Positions a source checkpoint is rewound to, passed as the body of the rewind checkpoint
+/// request: `{"positions": {"<partition ID>": "<position>"}}` or `{"timestamp": <timestamp>}`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckpointRewindTarget {
+    /// Rewinds the listed partitions to the given positions. An empty position rewinds the
+    /// partition to its beginning.
+    Positions(SourceCheckpoint),
+    /// Rewinds the checkpoint to the positions it was at, at the given UTC Unix timestamp (in
+    /// seconds). The positions are retrieved from the audit log of the index.
+    Timestamp(i64),
+}
+
+#[utoipa::path(
+    put,
+    tag = "Sources",
+    path = "/indexes/{index_id}/sources/{source_id}/rewind-checkpoint",
+    responses(
+        (status = 200, description = "Successfully rewound source checkpoint.")
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID of the source."),
+        ("source_id" = String, Path, description = "The source ID whose checkpoint is rewound."),
+    )
+)]
+/// Rewinds source checkpoint and returns the checkpoint after the rewind.
+async fn rewind_source_checkpoint(
+    index_id: String,
+    source_id: String,
+    rewind_target: CheckpointRewindTarget,
+    metastore: Arc<dyn Metastore>,
+) -> Result<SourceCheckpoint, MetastoreError> {
+    let index_uid: IndexUid = metastore.index_metadata(&index_id).await?.index_uid;
+    info!(index_id = %index_id, source_id = %source_id, rewind_target = ?rewind_target, "rewind-checkpoint");
+    let target_checkpoint = match rewind_target {
+        CheckpointRewindTarget::Positions(target_checkpoint) => target_checkpoint,
+        CheckpointRewindTarget::Timestamp(timestamp) => {
+            let audit_events = metastore.list_audit_events(&index_id).await?;
+            checkpoint_rewind_target_at(&audit_events, &index_uid, &source_id, timestamp)
+        }
+    };
+    metastore
+        .rewind_source_checkpoint(index_uid, &source_id, target_checkpoint)
+        .await?;
+    let source_checkpoint = metastore
+        .index_metadata(&index_id)
+        .await?
+        .checkpoint
+        .source_checkpoint(&source_id)
+        .cloned()
+        .unwrap_or_default();
+    Ok(source_checkpoint)
+}
+
 fn toggle_source_handler(
     metastore: Arc<dyn Metastore>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
@@ -652,7 +1236,7 @@ struct ToggleSource {
         ("source_id" = String, Path, description = "The source ID to toggle."),
     )
 )]
-/// Toggles source.
+/// Toggles source. A source is only enabled if Quickwit can connect to it.
 async fn toggle_source(
     index_id: String,
     source_id: String,
@@ -660,13 +1244,21 @@ async fn toggle_source(
     metastore: Arc<dyn Metastore>,
 ) -> Result<(), IndexServiceError> {
     info!(index_id = %index_id, source_id = %source_id, enable = toggle_source.enable, "toggle-source");
-    let index_uid: IndexUid = metastore.index_metadata(&index_id).await?.index_uid;
+    let index_metadata = metastore.index_metadata(&index_id).await?;
     if [CLI_INGEST_SOURCE_ID, INGEST_API_SOURCE_ID].contains(&source_id.as_str()) {
         return Err(IndexServiceError::OperationNotAllowed(format!(
             "Source `{source_id}` is managed by Quickwit, you cannot enable or disable a source \
              managed by Quickwit."
         )));
     }
+    if toggle_source.enable {
+        if let Some(source_config) = index_metadata.sources.get(&source_id) {
+            check_source_connectivity(source_config)
+                .await
+                .map_err(IndexServiceError::InvalidConfig)?;
+        }
+    }
+    let index_uid: IndexUid = index_metadata.index_uid;
     metastore
         .toggle_source(index_uid, &source_id, toggle_source.enable)
         .await?;
@@ -720,11 +1312,15 @@ mod tests {
 
     use assert_json_diff::assert_json_include;
     use quickwit_common::uri::{Protocol, Uri};
-    use quickwit_config::{SourceParams, VecSourceParams};
+    use quickwit_config::{IndexConfig, SourceParams, VecSourceParams};
     use quickwit_indexing::mock_split;
+    use quickwit_metastore::checkpoint::{
+        IndexCheckpointDelta, PartitionId, Position, SourceCheckpointDelta,
+    };
     use quickwit_metastore::file_backed_metastore::FileBackedMetastoreFactory;
     use quickwit_metastore::{
-        IndexMetadata, Metastore, MetastoreError, MetastoreUriResolver, MockMetastore,
+        AuditLogMetastore, IndexMetadata, Metastore, MetastoreError, MetastoreUriResolver,
+        MockMetastore, SplitFieldStats, SplitMetadata, SplitsPage, TenantMetastore,
     };
     use quickwit_storage::StorageUriResolver;
     use serde::__private::from_utf8_lossy;
@@ -797,6 +1393,92 @@ mod tests {
         assert_eq!(resp.status(), 404);
     }
 
+    #[tokio::test]
+    async fn test_get_splits_page() {
+        let mut metastore = MockMetastore::new();
+        metastore
+            .expect_index_metadata()
+            .returning(|_index_id: &str| {
+                Ok(IndexMetadata::for_test(
+                    "quickwit-demo-index",
+                    "ram:///indexes/quickwit-demo-index",
+                ))
+            })
+            .times(1);
+        metastore.expect_list_splits_page().times(1).returning(
+            |list_split_query: ListSplitsQuery, page_size: usize| {
+                assert_eq!(list_split_query.after_split_id.as_deref(), Some("split_1"));
+                assert!(list_split_query.sort_by_split_id);
+                assert_eq!(page_size, 1);
+                Ok(SplitsPage::new(vec![mock_split("split_2")], page_size))
+            },
+        );
+        let index_service = IndexService::new(Arc::new(metastore), StorageUriResolver::for_test());
+        let index_management_handler = super::index_management_handlers(
+            Arc::new(index_service),
+            Arc::new(QuickwitConfig::for_test()),
+        )
+        .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes/quickwit-demo-index/splits?page_size=1&after_split_id=split_1")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let actual_response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!([{
+            "split_id": "split_2",
+        }]);
+        assert_json_include!(
+            actual: actual_response_json,
+            expected: expected_response_json
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_splits_by_labels() {
+        let mut metastore = MockMetastore::new();
+        metastore
+            .expect_index_metadata()
+            .returning(|_index_id: &str| {
+                Ok(IndexMetadata::for_test(
+                    "quickwit-demo-index",
+                    "ram:///indexes/quickwit-demo-index",
+                ))
+            })
+            .times(1);
+        metastore
+            .expect_list_splits()
+            .returning(|list_split_query: ListSplitsQuery| {
+                assert_eq!(
+                    list_split_query.labels.get("backfill").map(String::as_str),
+                    Some("2023-01")
+                );
+                assert_eq!(
+                    list_split_query.labels.get("batch_id").map(String::as_str),
+                    Some("42")
+                );
+                Ok(vec![mock_split("split_1")])
+            })
+            .times(1);
+        let index_service = IndexService::new(Arc::new(metastore), StorageUriResolver::for_test());
+        let index_management_handler = super::index_management_handlers(
+            Arc::new(index_service),
+            Arc::new(QuickwitConfig::for_test()),
+        )
+        .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes/quickwit-demo-index/splits?labels=backfill:2023-01,batch_id:42")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let resp = warp::test::request()
+            .path("/indexes/quickwit-demo-index/splits?labels=backfill")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+
     #[tokio::test]
     async fn test_get_splits() {
         let mut metastore = MockMetastore::new();
@@ -916,12 +1598,68 @@ mod tests {
             "timestamp_field_name": "timestamp",
             "min_timestamp": split_1_time_range.start() - 10,
             "max_timestamp": split_1_time_range.end() + 10,
+            "merges_paused": false,
         });
 
         assert_eq!(actual_response_json, expected_response_json);
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_get_field_stats() {
+        let mut metastore = MockMetastore::new();
+        metastore
+            .expect_index_metadata()
+            .return_once(|_index_id: &str| {
+                Ok(IndexMetadata::for_test(
+                    "test-index",
+                    "ram:///indexes/test-index",
+                ))
+            });
+        let mut split_1 = mock_split("split_1");
+        split_1.split_metadata.field_stats.insert(
+            "body".to_string(),
+            SplitFieldStats {
+                num_docs_with_value: 8,
+                num_values: 8,
+                value_num_bytes: 40,
+                num_tokens: 16,
+                fast_field_num_bytes: 0,
+            },
+        );
+        let split_2 = mock_split("split_2");
+        metastore
+            .expect_list_splits()
+            .return_once(|list_split_query: ListSplitsQuery| {
+                assert_eq!(list_split_query.split_states, &[SplitState::Published]);
+                Ok(vec![split_1, split_2])
+            });
+        let index_service = IndexService::new(Arc::new(metastore), StorageUriResolver::for_test());
+        let index_management_handler = super::index_management_handlers(
+            Arc::new(index_service),
+            Arc::new(QuickwitConfig::for_test()),
+        )
+        .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes/test-index/field-stats")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let actual_response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!([{
+            "field_name": "body",
+            "num_splits": 1,
+            "num_docs_with_value": 8,
+            "num_null_docs": 2,
+            "num_values": 8,
+            "num_tokens": 16,
+            "avg_value_num_bytes": 5.0,
+            "fast_field_num_bytes": 0,
+        }]);
+        assert_eq!(actual_response_json, expected_response_json);
+    }
+
     #[tokio::test]
     async fn test_get_all_splits() {
         let mut metastore = MockMetastore::new();
@@ -1047,6 +1785,43 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_get_list_indexes_filters_indexes_not_granted() -> anyhow::Result<()> {
+        let mut metastore = MockMetastore::new();
+        metastore.expect_list_indexes_metadatas().return_once(|| {
+            Ok(vec![
+                IndexMetadata::for_test("team-a-logs", "ram:///indexes/team-a-logs"),
+                IndexMetadata::for_test("team-b-logs", "ram:///indexes/team-b-logs"),
+            ])
+        });
+        let index_service = IndexService::new(Arc::new(metastore), StorageUriResolver::for_test());
+        let index_management_handler = super::index_management_handlers(
+            Arc::new(index_service),
+            Arc::new(QuickwitConfig::for_test()),
+        )
+        .recover(recover_fn);
+        let principal = Principal {
+            name: "team-a".to_string(),
+            grants: vec!["admin:team-a-*".parse().unwrap()],
+            tenant_scope_opt: None,
+        };
+        let resp = warp::test::request()
+            .path("/indexes")
+            .extension(principal)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let actual_response_json: JsonValue = serde_json::from_slice(resp.body())?;
+        let index_ids: Vec<&str> = actual_response_json
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|index_metadata| index_metadata["index_config"]["index_id"].as_str().unwrap())
+            .collect();
+        assert_eq!(index_ids, ["team-a-logs"]);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_clear_index() -> anyhow::Result<()> {
         let mut metastore = MockMetastore::new();
@@ -1120,51 +1895,264 @@ mod tests {
         )
         .recover(recover_fn);
         {
-            // Dry run
+            // Dry run
+            let resp = warp::test::request()
+                .path("/indexes/quickwit-demo-index?dry_run=true")
+                .method("DELETE")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+            let expected_response_json = serde_json::json!([{
+                "file_name": "split_1.split",
+                "file_size_in_bytes": 800,
+            }]);
+            assert_json_include!(actual: resp_json, expected: expected_response_json);
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/quickwit-demo-index")
+                .method("DELETE")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+            let expected_response_json = serde_json::json!([{
+                "file_name": "split_1.split",
+                "file_size_in_bytes": 800,
+            }]);
+            assert_json_include!(actual: resp_json, expected: expected_response_json);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delete_on_non_existing_index() {
+        let metastore = build_metastore_for_test().await;
+        let index_service = IndexService::new(metastore, StorageUriResolver::for_test());
+        let index_management_handler = super::index_management_handlers(
+            Arc::new(index_service),
+            Arc::new(QuickwitConfig::for_test()),
+        )
+        .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes/quickwit-demo-index")
+            .method("DELETE")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_delete_and_restore_index_with_trash() {
+        let metastore = build_metastore_for_test().await;
+        let index_service = IndexService::new(metastore.clone(), StorageUriResolver::for_test())
+            .with_index_trash_enabled(true);
+        let index_management_handler = super::index_management_handlers(
+            Arc::new(index_service),
+            Arc::new(QuickwitConfig::for_test()),
+        )
+        .recover(recover_fn);
+        {
+            let resp = warp::test::request()
+                .path("/indexes")
+                .method("POST")
+                .json(&true)
+                .body(r#"{"version": "0.6", "index_id": "hdfs-logs", "doc_mapping": {"field_mappings":[{"name": "timestamp", "type": "i64", "fast": true, "indexed": true}]}}"#)
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/hdfs-logs")
+                .method("DELETE")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+            assert_eq!(resp_json, serde_json::json!([]));
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/hdfs-logs")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 404);
+            let trashed_indexes_metadatas =
+                metastore.list_trashed_indexes_metadatas().await.unwrap();
+            assert_eq!(trashed_indexes_metadatas.len(), 1);
+            assert_eq!(trashed_indexes_metadatas[0].index_id(), "hdfs-logs");
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/hdfs-logs/restore")
+                .method("PUT")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+            let expected_response_json = serde_json::json!({
+                "index_config": {
+                    "index_id": "hdfs-logs",
+                }
+            });
+            assert_json_include!(actual: resp_json, expected: expected_response_json);
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/hdfs-logs")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/hdfs-logs/restore")
+                .method("PUT")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 404);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_garbage_collect_index() {
+        let metastore = build_metastore_for_test().await;
+        let index_service = IndexService::new(metastore.clone(), StorageUriResolver::for_test());
+        let index_management_handler = super::index_management_handlers(
+            Arc::new(index_service),
+            Arc::new(QuickwitConfig::for_test()),
+        )
+        .recover(recover_fn);
+        {
             let resp = warp::test::request()
-                .path("/indexes/quickwit-demo-index?dry_run=true")
-                .method("DELETE")
+                .path("/indexes")
+                .method("POST")
+                .json(&true)
+                .body(r#"{"version": "0.6", "index_id": "hdfs-logs", "doc_mapping": {"field_mappings":[{"name": "timestamp", "type": "i64", "fast": true, "indexed": true}]}, "garbage_collection": {"interval_secs": 300, "staged_split_grace_period_secs": 600}}"#)
                 .reply(&index_management_handler)
                 .await;
             assert_eq!(resp.status(), 200);
             let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
-            let expected_response_json = serde_json::json!([{
-                "file_name": "split_1.split",
-                "file_size_in_bytes": 800,
-            }]);
+            let expected_response_json = serde_json::json!({
+                "index_config": {
+                    "garbage_collection": {
+                        "interval_secs": 300,
+                        "staged_split_grace_period_secs": 600,
+                    }
+                }
+            });
             assert_json_include!(actual: resp_json, expected: expected_response_json);
         }
         {
             let resp = warp::test::request()
-                .path("/indexes/quickwit-demo-index")
-                .method("DELETE")
+                .path("/indexes/hdfs-logs/garbage-collect?dry_run=true")
+                .method("PUT")
                 .reply(&index_management_handler)
                 .await;
             assert_eq!(resp.status(), 200);
             let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
-            let expected_response_json = serde_json::json!([{
-                "file_name": "split_1.split",
-                "file_size_in_bytes": 800,
-            }]);
-            assert_json_include!(actual: resp_json, expected: expected_response_json);
+            let expected_response_json = serde_json::json!({
+                "removed_split_entries": [],
+                "failed_split_ids": [],
+            });
+            assert_eq!(resp_json, expected_response_json);
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/unknown-index/garbage-collect")
+                .method("PUT")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 404);
         }
     }
 
     #[tokio::test]
-    async fn test_delete_on_non_existing_index() {
+    async fn test_list_tenants() {
         let metastore = build_metastore_for_test().await;
-        let index_service = IndexService::new(metastore, StorageUriResolver::for_test());
+        let index_service = IndexService::new(metastore.clone(), StorageUriResolver::for_test());
         let index_management_handler = super::index_management_handlers(
             Arc::new(index_service),
             Arc::new(QuickwitConfig::for_test()),
         )
         .recover(recover_fn);
+
         let resp = warp::test::request()
-            .path("/indexes/quickwit-demo-index")
-            .method("DELETE")
+            .path("/tenants")
             .reply(&index_management_handler)
             .await;
-        assert_eq!(resp.status(), 404);
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json, serde_json::json!([]));
+
+        for tenant_id in ["globex", "acme"] {
+            let tenant_metastore = TenantMetastore::new(metastore.clone(), tenant_id).unwrap();
+            let index_config = IndexConfig::for_test("logs", "ram:///indexes/logs");
+            tenant_metastore.create_index(index_config).await.unwrap();
+        }
+        let resp = warp::test::request()
+            .path("/tenants")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json, serde_json::json!(["acme", "globex"]));
+    }
+
+    #[tokio::test]
+    async fn test_list_audit_events() {
+        let metastore: Arc<dyn Metastore> = Arc::new(AuditLogMetastore::new(
+            build_metastore_for_test().await,
+            "test-node",
+        ));
+        let index_service = IndexService::new(metastore, StorageUriResolver::for_test());
+        let index_management_handler = super::index_management_handlers(
+            Arc::new(index_service),
+            Arc::new(QuickwitConfig::for_test()),
+        )
+        .recover(recover_fn);
+        {
+            let resp = warp::test::request()
+                .path("/indexes/hdfs-logs/audit-log")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+            assert_eq!(resp_json, serde_json::json!([]));
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes")
+                .method("POST")
+                .json(&true)
+                .body(r#"{"version": "0.6", "index_id": "hdfs-logs", "doc_mapping": {"field_mappings":[{"name": "timestamp", "type": "i64", "fast": true, "indexed": true}]}}"#)
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/hdfs-logs")
+                .method("DELETE")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+        }
+        {
+            // The audit log is still available after the index is deleted.
+            let resp = warp::test::request()
+                .path("/indexes/hdfs-logs/audit-log")
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+            let expected_response_json = serde_json::json!([
+                {"actor": "test-node", "operation": "create_index"},
+                {"actor": "test-node", "operation": "delete_index"},
+            ]);
+            assert_json_include!(actual: resp_json, expected: expected_response_json);
+        }
     }
 
     #[tokio::test]
@@ -1208,6 +2196,67 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_update_index() {
+        let metastore = build_metastore_for_test().await;
+        let index_service = IndexService::new(metastore.clone(), StorageUriResolver::for_test());
+        let mut quickwit_config = QuickwitConfig::for_test();
+        quickwit_config.default_index_root_uri =
+            Uri::from_well_formed("file:///default-index-root-uri");
+        let index_management_handler =
+            super::index_management_handlers(Arc::new(index_service), Arc::new(quickwit_config));
+        let resp = warp::test::request()
+            .path("/indexes")
+            .method("POST")
+            .json(&true)
+            .body(r#"{"version": "0.6", "index_id": "hdfs-logs", "doc_mapping": {"field_mappings":[{"name": "timestamp", "type": "i64", "fast": true, "indexed": true}]}}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        assert_json_include!(actual: resp_json, expected: serde_json::json!({"config_version": 0}));
+
+        let updated_index_config_body = r#"{"version": "0.6", "index_id": "hdfs-logs", "doc_mapping": {"field_mappings":[{"name": "timestamp", "type": "i64", "fast": true, "indexed": true}]}, "indexing_settings": {"commit_timeout_secs": 30}}"#;
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs?expected_config_version=0")
+            .method("PUT")
+            .json(&true)
+            .body(updated_index_config_body)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: serde_json::Value = serde_json::from_slice(resp.body()).unwrap();
+        let expected_response_json = serde_json::json!({
+            "config_version": 1,
+            "index_config": {
+                "indexing_settings": {
+                    "commit_timeout_secs": 30,
+                }
+            }
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+        // A concurrent update based on the stale version is rejected.
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs?expected_config_version=0")
+            .method("PUT")
+            .json(&true)
+            .body(updated_index_config_body)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 409);
+
+        // The doc mapping cannot be updated.
+        let resp = warp::test::request()
+            .path("/indexes/hdfs-logs")
+            .method("PUT")
+            .json(&true)
+            .body(r#"{"version": "0.6", "index_id": "hdfs-logs", "doc_mapping": {"field_mappings":[{"name": "timestamp", "type": "u64", "fast": true, "indexed": true}]}}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 405);
+    }
+
     #[tokio::test]
     async fn test_create_delete_index_and_source() {
         let metastore = build_metastore_for_test().await;
@@ -1581,6 +2630,149 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_source_rewind_checkpoint() {
+        let metastore: Arc<dyn Metastore> = Arc::new(AuditLogMetastore::new(
+            build_metastore_for_test().await,
+            "test-node",
+        ));
+        let index_uid = metastore
+            .create_index(IndexConfig::for_test(
+                "test-index",
+                "ram:///indexes/test-index",
+            ))
+            .await
+            .unwrap();
+        metastore
+            .add_source(
+                index_uid.clone(),
+                SourceConfig::for_test("test-source", SourceParams::void()),
+            )
+            .await
+            .unwrap();
+        metastore
+            .stage_splits(
+                index_uid.clone(),
+                vec![SplitMetadata {
+                    split_id: "test-split".to_string(),
+                    index_uid: index_uid.clone(),
+                    ..Default::default()
+                }],
+            )
+            .await
+            .unwrap();
+        let checkpoint_delta = IndexCheckpointDelta {
+            source_id: "test-source".to_string(),
+            source_delta: SourceCheckpointDelta::from_partition_delta(
+                PartitionId::from("partition-0"),
+                Position::Beginning,
+                Position::from(100u64),
+            )
+            .unwrap(),
+        };
+        metastore
+            .publish_splits(index_uid, &["test-split"], &[], Some(checkpoint_delta))
+            .await
+            .unwrap();
+
+        let index_service = IndexService::new(metastore, StorageUriResolver::for_test());
+        let index_management_handler = super::index_management_handlers(
+            Arc::new(index_service),
+            Arc::new(QuickwitConfig::for_test()),
+        )
+        .recover(recover_fn);
+        {
+            let resp = warp::test::request()
+                .path("/indexes/test-index/sources/test-source/rewind-checkpoint")
+                .method("PUT")
+                .json(&CheckpointRewindTarget::Positions(
+                    vec![(PartitionId::from("partition-0"), Position::from(42u64))]
+                        .into_iter()
+                        .collect(),
+                ))
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            assert_eq!(
+                resp_json,
+                serde_json::json!({"partition-0": "00000000000000000042"})
+            );
+        }
+        {
+            // A checkpoint cannot be rewound forward.
+            let resp = warp::test::request()
+                .path("/indexes/test-index/sources/test-source/rewind-checkpoint")
+                .method("PUT")
+                .body(r#"{"positions": {"partition-0": "00000000000000000043"}}"#)
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 400);
+        }
+        {
+            // Rewinding to a timestamp anterior to the first publication rewinds the checkpoint
+            // to the beginning of the partition.
+            let resp = warp::test::request()
+                .path("/indexes/test-index/sources/test-source/rewind-checkpoint")
+                .method("PUT")
+                .body(r#"{"timestamp": 0}"#)
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 200);
+            let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+            assert_eq!(resp_json, serde_json::json!({}));
+        }
+        {
+            let resp = warp::test::request()
+                .path("/indexes/test-index/sources/source-not-found/rewind-checkpoint")
+                .method("PUT")
+                .body(r#"{"timestamp": 0}"#)
+                .reply(&index_management_handler)
+                .await;
+            assert_eq!(resp.status(), 404);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pause_and_resume_merges() {
+        let mut metastore = MockMetastore::new();
+        metastore
+            .expect_index_metadata()
+            .returning(|_index_id: &str| {
+                Ok(IndexMetadata::for_test(
+                    "quickwit-demo-index",
+                    "file:///path/to/index/quickwit-demo-index",
+                ))
+            })
+            .times(2);
+        metastore
+            .expect_set_merges_paused()
+            .withf(|index_uid, paused| index_uid.index_id() == "quickwit-demo-index" && *paused)
+            .return_once(|_, _| Ok(()));
+        metastore
+            .expect_set_merges_paused()
+            .withf(|index_uid, paused| index_uid.index_id() == "quickwit-demo-index" && !*paused)
+            .return_once(|_, _| Ok(()));
+        let index_service = IndexService::new(Arc::new(metastore), StorageUriResolver::for_test());
+        let index_management_handler = super::index_management_handlers(
+            Arc::new(index_service),
+            Arc::new(QuickwitConfig::for_test()),
+        )
+        .recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/indexes/quickwit-demo-index/merges/pause")
+            .method("PUT")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp = warp::test::request()
+            .path("/indexes/quickwit-demo-index/merges/resume")
+            .method("PUT")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+    }
+
     #[tokio::test]
     async fn test_source_toggle() -> anyhow::Result<()> {
         let mut metastore = MockMetastore::new();
@@ -1654,4 +2846,87 @@ mod tests {
         assert_eq!(resp.status(), 405);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_list_and_check_sources() {
+        let metastore = build_metastore_for_test().await;
+        let index_uid = metastore
+            .create_index(IndexConfig::for_test(
+                "test-index",
+                "ram:///indexes/test-index",
+            ))
+            .await
+            .unwrap();
+        metastore
+            .add_source(
+                index_uid.clone(),
+                SourceConfig::for_test("void-source", SourceParams::void()),
+            )
+            .await
+            .unwrap();
+        let mut file_source_config = SourceConfig::for_test(
+            "file-source",
+            SourceParams::file("/path/to/missing-file.json"),
+        );
+        file_source_config.enabled = false;
+        metastore
+            .add_source(index_uid, file_source_config)
+            .await
+            .unwrap();
+        let index_service = IndexService::new(metastore, StorageUriResolver::for_test());
+        let index_management_handler = super::index_management_handlers(
+            Arc::new(index_service),
+            Arc::new(QuickwitConfig::for_test()),
+        )
+        .recover(recover_fn);
+
+        let resp = warp::test::request()
+            .path("/indexes/test-index/sources")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let source_configs: Vec<SourceConfig> = serde_json::from_slice(resp.body()).unwrap();
+        let source_ids: Vec<&str> = source_configs
+            .iter()
+            .map(|source_config| source_config.source_id.as_str())
+            .collect();
+        assert_eq!(source_ids, ["file-source", "void-source"]);
+
+        let resp = warp::test::request()
+            .path("/indexes/test-index/sources/void-source/check")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let source_check: SourceCheck = serde_json::from_slice(resp.body()).unwrap();
+        assert!(source_check.connected);
+        assert!(source_check.error.is_none());
+
+        let resp = warp::test::request()
+            .path("/indexes/test-index/sources/file-source/check")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let source_check: SourceCheck = serde_json::from_slice(resp.body()).unwrap();
+        assert!(!source_check.connected);
+        assert_eq!(
+            source_check.error.as_deref(),
+            Some("File `/path/to/missing-file.json` does not exist.")
+        );
+
+        // A source cannot be enabled if Quickwit cannot connect to it.
+        let resp = warp::test::request()
+            .path("/indexes/test-index/sources/file-source/toggle")
+            .method("PUT")
+            .body(r#"{"enable": true}"#)
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+        assert!(from_utf8_lossy(resp.body()).contains("does not exist"));
+
+        let resp = warp::test::request()
+            .path("/indexes/test-index/sources/missing-source/check")
+            .reply(&index_management_handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+    }
 }
@@ -21,7 +21,7 @@ use std::convert::Infallible;
 
 use quickwit_actors::{AskError, Mailbox};
 use quickwit_indexing::actors::{IndexingService, IndexingServiceCounters};
-use quickwit_indexing::models::Observe;
+use quickwit_indexing::models::{Drain, Observe};
 use warp::{Filter, Rejection};
 
 use crate::format::extract_format_from_qs;
@@ -29,7 +29,7 @@ use crate::json_api_response::make_json_api_response;
 use crate::require;
 
 #[derive(utoipa::OpenApi)]
-#[openapi(paths(indexing_endpoint))]
+#[openapi(paths(indexing_endpoint, drain_indexing_endpoint))]
 pub struct IndexingApi;
 
 #[utoipa::path(
@@ -61,3 +61,37 @@ pub fn indexing_get_handler(
         .and(extract_format_from_qs())
         .map(make_json_api_response)
 }
+
+#[utoipa::path(
+    post,
+    tag = "Indexing",
+    path = "/indexing/drain",
+    responses(
+        (status = 200, description = "Successfully started draining the indexing pipelines.", body = IndexingStatistics)
+    ),
+)]
+/// Drain Indexing Pipelines
+///
+/// Stops pulling from the sources, and flushes and publishes the in-flight splits of the
+/// indexing pipelines of the node, which then exit. The drain is complete once the node reports
+/// no running pipeline.
+async fn drain_indexing_endpoint(
+    indexing_service_mailbox: Mailbox<IndexingService>,
+) -> Result<IndexingServiceCounters, AskError<Infallible>> {
+    let counters = indexing_service_mailbox.ask(Drain).await?;
+    Ok(counters)
+}
+
+fn indexing_drain_filter() -> impl Filter<Extract = (), Error = Rejection> + Clone {
+    warp::path!("indexing" / "drain").and(warp::post())
+}
+
+pub fn indexing_drain_handler(
+    indexing_service_mailbox_opt: Option<Mailbox<IndexingService>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    indexing_drain_filter()
+        .and(require(indexing_service_mailbox_opt))
+        .then(drain_indexing_endpoint)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
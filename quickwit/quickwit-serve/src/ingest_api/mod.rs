@@ -18,8 +18,13 @@
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
 mod rest_handler;
+mod router;
+mod tail_stream;
 
 #[cfg(test)]
 pub(crate) use rest_handler::tests::setup_ingest_service;
 pub(crate) use rest_handler::{ingest_api_handlers, lines};
 pub use rest_handler::{IngestApi, IngestApiSchemas};
+pub(crate) use router::IngestRouter;
+pub(crate) use tail_stream::tail_stream_handler;
+pub use tail_stream::TailStreamApi;
@@ -17,17 +17,26 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
 use bytes::{Buf, Bytes};
+use hyper::StatusCode;
+use quickwit_config::build_doc_mapper;
+use quickwit_doc_mapper::DocMapper;
 use quickwit_ingest::{
-    CommitType, DocBatchBuilder, FetchResponse, IngestRequest, IngestResponse, IngestService,
-    IngestServiceClient, IngestServiceError, TailRequest,
+    CommitType, DocBatchBuilder, FetchResponse, IngestRequest, IngestService, IngestServiceClient,
+    IngestServiceError, TailRequest,
 };
-use serde::Deserialize;
+use quickwit_metastore::{Metastore, MetastoreError};
+use quickwit_proto::{IndexUid, ServiceError, ServiceErrorCode};
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use warp::{Filter, Rejection};
 
 use crate::format::extract_format_from_qs;
-use crate::json_api_response::make_json_api_response;
+use crate::json_api_response::{make_json_api_response, JsonApiResponse};
 use crate::{with_arg, BodyFormat};
 
 #[derive(utoipa::OpenApi)]
@@ -40,9 +49,54 @@ pub struct IngestApi;
     quickwit_ingest::FetchResponse,
     quickwit_ingest::IngestResponse,
     quickwit_ingest::CommitType,
+    IngestRestResponse,
+    IngestDocStatus,
 )))]
 pub struct IngestApiSchemas;
 
+/// Outcome of the ingestion of a document.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum IngestDocStatus {
+    /// The document was added to the ingest queue of the index.
+    Accepted,
+    /// The document does not conform to the doc mapping of the index. It is rejected and should
+    /// not be sent again as is.
+    MappingError { reason: String },
+    /// The ingest queue of the index was full. The document can be sent again later.
+    QueueFull,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize, utoipa::ToSchema)]
+pub struct IngestRestResponse {
+    /// Number of documents added to the ingest queue of the index.
+    pub num_docs_for_processing: u64,
+    /// Number of documents rejected because of a mapping error or a full queue.
+    pub num_rejected_docs: u64,
+    /// Outcome of the ingestion of each document of the request, in order.
+    pub docs: Vec<IngestDocStatus>,
+}
+
+#[derive(Debug, Error)]
+enum IngestRestError {
+    #[error(transparent)]
+    Metastore(#[from] MetastoreError),
+    #[error(transparent)]
+    IngestService(#[from] IngestServiceError),
+    #[error("Internal error: {0}.")]
+    Internal(String),
+}
+
+impl ServiceError for IngestRestError {
+    fn status_code(&self) -> ServiceErrorCode {
+        match self {
+            IngestRestError::Metastore(error) => error.status_code(),
+            IngestRestError::IngestService(error) => error.status_code(),
+            IngestRestError::Internal(_) => ServiceErrorCode::Internal,
+        }
+    }
+}
+
 #[derive(Debug, Error)]
 #[error("Body is not utf-8.")]
 struct InvalidUtf8;
@@ -51,6 +105,64 @@ impl warp::reject::Reject for InvalidUtf8 {}
 
 const CONTENT_LENGTH_LIMIT: u64 = 10 * 1024 * 1024; // 10MiB
 
+/// Time during which the doc mapper of an index is used by the ingest handler without checking
+/// that the index config is unchanged.
+const DOC_MAPPER_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Caches the doc mappers validating the ingested documents, so that the ingest requests do not
+/// fetch the index metadata and build the doc mapper of their index every time. Once its entry is
+/// older than [`DOC_MAPPER_CACHE_TTL`], a doc mapper is reused only if the UID and the config
+/// version of the index are unchanged.
+#[derive(Clone, Default)]
+struct DocMapperCache {
+    inner: Arc<Mutex<HashMap<String, CachedDocMapper>>>,
+}
+
+struct CachedDocMapper {
+    cached_at: Instant,
+    index_uid: IndexUid,
+    config_version: u64,
+    doc_mapper: Arc<dyn DocMapper>,
+}
+
+impl DocMapperCache {
+    async fn doc_mapper(
+        &self,
+        index_id: &str,
+        metastore: &dyn Metastore,
+    ) -> Result<Arc<dyn DocMapper>, IngestRestError> {
+        if let Some(cached_doc_mapper) = self.inner.lock().unwrap().get(index_id) {
+            if cached_doc_mapper.cached_at.elapsed() < DOC_MAPPER_CACHE_TTL {
+                return Ok(cached_doc_mapper.doc_mapper.clone());
+            }
+        }
+        let index_metadata = metastore.index_metadata(index_id).await?;
+
+        if let Some(cached_doc_mapper) = self.inner.lock().unwrap().get_mut(index_id) {
+            if cached_doc_mapper.index_uid == index_metadata.index_uid
+                && cached_doc_mapper.config_version == index_metadata.config_version
+            {
+                cached_doc_mapper.cached_at = Instant::now();
+                return Ok(cached_doc_mapper.doc_mapper.clone());
+            }
+        }
+        let index_config = index_metadata.index_config();
+        let doc_mapper = build_doc_mapper(&index_config.doc_mapping, &index_config.search_settings)
+            .map_err(|error| IngestRestError::Internal(error.to_string()))?;
+        let cached_doc_mapper = CachedDocMapper {
+            cached_at: Instant::now(),
+            index_uid: index_metadata.index_uid.clone(),
+            config_version: index_metadata.config_version,
+            doc_mapper: doc_mapper.clone(),
+        };
+        self.inner
+            .lock()
+            .unwrap()
+            .insert(index_id.to_string(), cached_doc_mapper);
+        Ok(doc_mapper)
+    }
+}
+
 #[derive(Clone, Debug, Default, Deserialize, PartialEq)]
 struct IngestOptions {
     #[serde(alias = "commit")]
@@ -60,8 +172,9 @@ struct IngestOptions {
 
 pub(crate) fn ingest_api_handlers(
     ingest_service: IngestServiceClient,
+    metastore: Arc<dyn Metastore>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
-    ingest_handler(ingest_service.clone()).or(tail_handler(ingest_service))
+    ingest_handler(ingest_service.clone(), metastore).or(tail_handler(ingest_service))
 }
 
 fn ingest_filter(
@@ -77,11 +190,34 @@ fn ingest_filter(
 
 fn ingest_handler(
     ingest_service: IngestServiceClient,
+    metastore: Arc<dyn Metastore>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     ingest_filter()
+        .and(with_arg(metastore))
+        .and(with_arg(DocMapperCache::default()))
         .and(with_arg(ingest_service))
         .then(ingest)
-        .map(|result| make_json_api_response(result, BodyFormat::default()))
+        .map(make_ingest_response)
+}
+
+/// Reports the documents rejected because of a full ingest queue with a `429` status code, so
+/// that clients back off before sending them again.
+fn make_ingest_response(result: Result<IngestRestResponse, IngestRestError>) -> JsonApiResponse {
+    match result {
+        Ok(ingest_response)
+            if ingest_response
+                .docs
+                .iter()
+                .any(|doc_status| *doc_status == IngestDocStatus::QueueFull) =>
+        {
+            JsonApiResponse::new::<_, ()>(
+                &Ok(ingest_response),
+                StatusCode::TOO_MANY_REQUESTS,
+                &BodyFormat::default(),
+            )
+        }
+        result => make_json_api_response(result, BodyFormat::default()),
+    }
 }
 
 #[utoipa::path(
@@ -90,7 +226,8 @@ fn ingest_handler(
     path = "/{index_id}/ingest",
     request_body(content = String, description = "Documents to ingest in NDJSON format and limited to 10MB", content_type = "application/json"),
     responses(
-        (status = 200, description = "Successfully ingested documents.", body = IngestResponse)
+        (status = 200, description = "Successfully ingested the documents that conform to the doc mapping of the index.", body = IngestRestResponse),
+        (status = 429, description = "The ingest queue of the index is full.", body = IngestRestResponse)
     ),
     params(
         ("index_id" = String, Path, description = "The index ID to add docs to."),
@@ -98,25 +235,72 @@ fn ingest_handler(
     )
 )]
 /// Ingest documents
+///
+/// Validates the documents against the doc mapping of the index and adds the valid ones to its
+/// ingest queue. The response reports the outcome of each document, so that clients can retry
+/// only the documents rejected because of a full queue.
 async fn ingest(
     index_id: String,
     body: Bytes,
     ingest_options: IngestOptions,
+    metastore: Arc<dyn Metastore>,
+    doc_mapper_cache: DocMapperCache,
     mut ingest_service: IngestServiceClient,
-) -> Result<IngestResponse, IngestServiceError> {
-    // The size of the body should be an upper bound of the size of the batch. The removal of the
-    // end of line character for each doc compensates the addition of the `DocCommand` header.
-    let mut doc_batch_builder = DocBatchBuilder::with_capacity(index_id, body.remaining());
+) -> Result<IngestRestResponse, IngestRestError> {
+    let doc_mapper = doc_mapper_cache
+        .doc_mapper(&index_id, metastore.as_ref())
+        .await?;
+
+    // Parsing the documents is CPU intensive, so it is kept off the runtime threads.
+    let (doc_batch, mut doc_statuses) = tokio::task::spawn_blocking(move || {
+        // The size of the body should be an upper bound of the size of the batch. The removal of
+        // the end of line character for each doc compensates the addition of the `DocCommand`
+        // header.
+        let mut doc_batch_builder = DocBatchBuilder::with_capacity(index_id, body.remaining());
+        let mut doc_statuses = Vec::new();
+
+        for line in lines(&body) {
+            let doc_status = match doc_mapper.doc_from_json_bytes(line) {
+                Ok(_) => {
+                    doc_batch_builder.ingest_doc(line);
+                    IngestDocStatus::Accepted
+                }
+                Err(error) => IngestDocStatus::MappingError {
+                    reason: error.to_string(),
+                },
+            };
+            doc_statuses.push(doc_status);
+        }
+        (doc_batch_builder.build(), doc_statuses)
+    })
+    .await
+    .map_err(|error| IngestRestError::Internal(error.to_string()))?;
 
-    for line in lines(&body) {
-        doc_batch_builder.ingest_doc(line);
-    }
     let ingest_req = IngestRequest {
-        doc_batches: vec![doc_batch_builder.build()],
+        doc_batches: vec![doc_batch],
         commit: ingest_options.commit_type as u32,
     };
-    let ingest_response = ingest_service.ingest(ingest_req).await?;
-    Ok(ingest_response)
+    let num_docs_for_processing = match ingest_service.ingest(ingest_req).await {
+        Ok(ingest_response) => ingest_response.num_docs_for_processing,
+        Err(IngestServiceError::RateLimited) => {
+            for doc_status in &mut doc_statuses {
+                if *doc_status == IngestDocStatus::Accepted {
+                    *doc_status = IngestDocStatus::QueueFull;
+                }
+            }
+            0
+        }
+        Err(error) => return Err(error.into()),
+    };
+    let num_rejected_docs = doc_statuses
+        .iter()
+        .filter(|doc_status| **doc_status != IngestDocStatus::Accepted)
+        .count() as u64;
+    Ok(IngestRestResponse {
+        num_docs_for_processing,
+        num_rejected_docs,
+        docs: doc_statuses,
+    })
 }
 
 pub fn tail_handler(
@@ -160,18 +344,35 @@ pub(crate) fn lines(body: &Bytes) -> impl Iterator<Item = &[u8]> {
 
 #[cfg(test)]
 pub(crate) mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
     use std::time::Duration;
 
     use byte_unit::Byte;
     use quickwit_actors::{Mailbox, Universe};
     use quickwit_config::IngestApiConfig;
+    use quickwit_doc_mapper::DocMapper;
     use quickwit_ingest::{
         init_ingest_api, CreateQueueIfNotExistsRequest, FetchRequest, FetchResponse,
         IngestApiService, IngestResponse, IngestServiceClient, SuggestTruncateRequest,
         QUEUES_DIR_NAME,
     };
+    use quickwit_metastore::{IndexMetadata, Metastore, MockMetastore};
+
+    use super::{
+        ingest_api_handlers, DocMapperCache, IngestDocStatus, IngestRestResponse,
+        DOC_MAPPER_CACHE_TTL,
+    };
 
-    use super::ingest_api_handlers;
+    fn mock_metastore() -> Arc<dyn Metastore> {
+        let mut metastore = MockMetastore::new();
+        metastore
+            .expect_index_metadata()
+            .returning(|index_id: &str| {
+                Ok(IndexMetadata::for_test(index_id, "ram:///indexes/my-index"))
+            });
+        Arc::new(metastore)
+    }
 
     pub(crate) async fn setup_ingest_service(
         queues: &[&str],
@@ -205,7 +406,7 @@ pub(crate) mod tests {
     async fn test_ingest_api_returns_200_when_ingest_json_and_fetch() {
         let (universe, _temp_dir, ingest_service, _) =
             setup_ingest_service(&["my-index"], &IngestApiConfig::default()).await;
-        let ingest_api_handlers = ingest_api_handlers(ingest_service);
+        let ingest_api_handlers = ingest_api_handlers(ingest_service, mock_metastore());
         let resp = warp::test::request()
             .path("/my-index/ingest")
             .method("POST")
@@ -235,11 +436,93 @@ pub(crate) mod tests {
         universe.assert_quit().await;
     }
 
+    #[tokio::test]
+    async fn test_ingest_api_caches_doc_mapper() {
+        let (universe, _temp_dir, ingest_service, _) =
+            setup_ingest_service(&["my-index"], &IngestApiConfig::default()).await;
+        let mut metastore = MockMetastore::new();
+        metastore
+            .expect_index_metadata()
+            .times(1)
+            .returning(|index_id: &str| {
+                Ok(IndexMetadata::for_test(index_id, "ram:///indexes/my-index"))
+            });
+        let ingest_api_handlers = ingest_api_handlers(ingest_service, Arc::new(metastore));
+
+        for _ in 0..2 {
+            let resp = warp::test::request()
+                .path("/my-index/ingest")
+                .method("POST")
+                .body(r#"{"id": 1, "message": "push"}"#)
+                .reply(&ingest_api_handlers)
+                .await;
+            assert_eq!(resp.status(), 200);
+        }
+        universe.assert_quit().await;
+    }
+
+    /// Compares the addresses of the doc mappers, regardless of their vtables.
+    fn same_doc_mapper(left: &Arc<dyn DocMapper>, right: &Arc<dyn DocMapper>) -> bool {
+        Arc::as_ptr(left).cast::<()>() == Arc::as_ptr(right).cast::<()>()
+    }
+
+    #[tokio::test]
+    async fn test_doc_mapper_cache_checks_index_config_version() {
+        let index_metadata = IndexMetadata::for_test("my-index", "ram:///indexes/my-index");
+        let num_calls = Arc::new(AtomicUsize::new(0));
+        let num_calls_clone = num_calls.clone();
+        let mut metastore = MockMetastore::new();
+        metastore
+            .expect_index_metadata()
+            .times(3)
+            .returning(move |_index_id: &str| {
+                let mut index_metadata = index_metadata.clone();
+                // The index config is updated after the second call.
+                if num_calls_clone.fetch_add(1, Ordering::Relaxed) >= 2 {
+                    index_metadata.config_version += 1;
+                }
+                Ok(index_metadata)
+            });
+        let doc_mapper_cache = DocMapperCache::default();
+        let expire_entry = || {
+            let mut inner = doc_mapper_cache.inner.lock().unwrap();
+            let cached_doc_mapper = inner.get_mut("my-index").unwrap();
+            cached_doc_mapper.cached_at -= DOC_MAPPER_CACHE_TTL;
+        };
+        let doc_mapper = doc_mapper_cache
+            .doc_mapper("my-index", &metastore)
+            .await
+            .unwrap();
+
+        // The doc mapper is used without checking the metastore until the entry expires.
+        let cached_doc_mapper = doc_mapper_cache
+            .doc_mapper("my-index", &metastore)
+            .await
+            .unwrap();
+        assert!(same_doc_mapper(&doc_mapper, &cached_doc_mapper));
+        assert_eq!(num_calls.load(Ordering::Relaxed), 1);
+
+        expire_entry();
+        let unchanged_doc_mapper = doc_mapper_cache
+            .doc_mapper("my-index", &metastore)
+            .await
+            .unwrap();
+        assert!(same_doc_mapper(&doc_mapper, &unchanged_doc_mapper));
+
+        expire_entry();
+        let updated_doc_mapper = doc_mapper_cache
+            .doc_mapper("my-index", &metastore)
+            .await
+            .unwrap();
+        assert!(!same_doc_mapper(&doc_mapper, &updated_doc_mapper));
+        assert_eq!(num_calls.load(Ordering::Relaxed), 3);
+    }
+
     #[tokio::test]
     async fn test_ingest_api_returns_200_when_ingest_ndjson_and_fetch() {
         let (universe, _temp_dir, ingest_service, _) =
             setup_ingest_service(&["my-index"], &IngestApiConfig::default()).await;
-        let ingest_api_handlers = ingest_api_handlers(ingest_service);
+        let ingest_api_handlers = ingest_api_handlers(ingest_service, mock_metastore());
         let payload = r#"
             {"id": 1, "message": "push"}
             {"id": 2, "message": "push"}
@@ -265,7 +548,7 @@ pub(crate) mod tests {
         };
         let (universe, _temp_dir, ingest_service, _) =
             setup_ingest_service(&["my-index"], &config).await;
-        let ingest_api_handlers = ingest_api_handlers(ingest_service);
+        let ingest_api_handlers = ingest_api_handlers(ingest_service, mock_metastore());
         let resp = warp::test::request()
             .path("/my-index/ingest")
             .method("POST")
@@ -274,6 +557,55 @@ pub(crate) mod tests {
             .reply(&ingest_api_handlers)
             .await;
         assert_eq!(resp.status(), 429);
+        let ingest_response: IngestRestResponse = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(ingest_response.num_docs_for_processing, 0);
+        assert_eq!(ingest_response.num_rejected_docs, 1);
+        assert_eq!(ingest_response.docs, [IngestDocStatus::QueueFull]);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_ingest_api_reports_mapping_errors_per_doc() {
+        let (universe, _temp_dir, ingest_service, ingest_service_mailbox) =
+            setup_ingest_service(&["my-index"], &IngestApiConfig::default()).await;
+        let ingest_api_handlers = ingest_api_handlers(ingest_service, mock_metastore());
+        let payload = r#"
+            {"body": "accepted", "response_time": 0.5}
+            not a json object
+            {"body": "rejected", "response_time": "slow"}
+            {"body": "accepted too"}"#;
+        let resp = warp::test::request()
+            .path("/my-index/ingest")
+            .method("POST")
+            .body(payload)
+            .reply(&ingest_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let ingest_response: IngestRestResponse = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(ingest_response.num_docs_for_processing, 2);
+        assert_eq!(ingest_response.num_rejected_docs, 2);
+        assert_eq!(ingest_response.docs.len(), 4);
+        assert_eq!(ingest_response.docs[0], IngestDocStatus::Accepted);
+        assert!(matches!(
+            ingest_response.docs[1],
+            IngestDocStatus::MappingError { .. }
+        ));
+        assert!(matches!(
+            &ingest_response.docs[2],
+            IngestDocStatus::MappingError { reason } if reason.contains("response_time")
+        ));
+        assert_eq!(ingest_response.docs[3], IngestDocStatus::Accepted);
+
+        // Only the accepted documents are added to the ingest queue.
+        let fetch_response = ingest_service_mailbox
+            .ask_for_res(FetchRequest {
+                index_id: "my-index".to_string(),
+                start_after: None,
+                num_bytes_limit: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(fetch_response.doc_batch.unwrap().num_docs(), 2);
         universe.assert_quit().await;
     }
 
@@ -281,7 +613,7 @@ pub(crate) mod tests {
     async fn test_ingest_api_blocks_when_wait_is_specified() {
         let (universe, _temp_dir, ingest_service_client, ingest_service_mailbox) =
             setup_ingest_service(&["my-index"], &IngestApiConfig::default()).await;
-        let ingest_api_handlers = ingest_api_handlers(ingest_service_client);
+        let ingest_api_handlers = ingest_api_handlers(ingest_service_client, mock_metastore());
         let handle = tokio::spawn(async move {
             let resp = warp::test::request()
                 .path("/my-index/ingest?commit=wait_for")
@@ -325,7 +657,7 @@ pub(crate) mod tests {
     async fn test_ingest_api_blocks_when_force_is_specified() {
         let (universe, _temp_dir, ingest_service_client, ingest_service_mailbox) =
             setup_ingest_service(&["my-index"], &IngestApiConfig::default()).await;
-        let ingest_api_handlers = ingest_api_handlers(ingest_service_client);
+        let ingest_api_handlers = ingest_api_handlers(ingest_service_client, mock_metastore());
         let handle = tokio::spawn(async move {
             let resp = warp::test::request()
                 .path("/my-index/ingest?commit=force")
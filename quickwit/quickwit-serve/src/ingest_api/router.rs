@@ -0,0 +1,382 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use bytes::Bytes;
+use futures::future::try_join_all;
+use quickwit_cluster::{Cluster, ClusterNode};
+use quickwit_common::tower::BalanceChannel;
+use quickwit_config::INGEST_API_SOURCE_ID;
+use quickwit_doc_mapper::{JsonObject, RoutingExpr};
+use quickwit_ingest::{
+    DocBatch, DocBatchBuilder, DocCommand, FetchRequest, FetchResponse, IngestRequest,
+    IngestResponse, IngestService, IngestServiceClient, TailRequest,
+};
+use quickwit_metastore::{Metastore, MetastoreError};
+use quickwit_proto::IndexUid;
+use tracing::warn;
+
+/// Time during which the `partition_by` setting of an index is cached by the router.
+const PARTITION_BY_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Routes the documents of the indexes declaring a `partition_by` routing expression to the
+/// indexers running the ingest API pipelines of the index, so that the documents sharing the
+/// same partition value are always ingested by the same indexer. The requests targeting the other
+/// indexes are forwarded as is to the default ingest service.
+#[derive(Clone)]
+pub(crate) struct IngestRouter {
+    cluster: Cluster,
+    metastore: Arc<dyn Metastore>,
+    ingest_service: IngestServiceClient,
+    inner: Arc<Mutex<InnerIngestRouter>>,
+}
+
+#[derive(Default)]
+struct InnerIngestRouter {
+    partition_by_cache: HashMap<String, (Instant, Option<RoutingExpr>)>,
+    node_clients: HashMap<SocketAddr, IngestServiceClient>,
+}
+
+impl fmt::Debug for IngestRouter {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter
+            .debug_struct("IngestRouter")
+            .field("self_node_id", &self.cluster.self_node_id())
+            .finish()
+    }
+}
+
+impl IngestRouter {
+    pub fn new(
+        cluster: Cluster,
+        metastore: Arc<dyn Metastore>,
+        ingest_service: IngestServiceClient,
+    ) -> Self {
+        Self {
+            cluster,
+            metastore,
+            ingest_service,
+            inner: Arc::new(Mutex::new(InnerIngestRouter::default())),
+        }
+    }
+
+    /// Returns the `partition_by` routing expression of the index, if any.
+    async fn partition_by(&self, index_id: &str) -> Option<RoutingExpr> {
+        if let Some((cached_at, partition_by_opt)) =
+            self.inner.lock().unwrap().partition_by_cache.get(index_id)
+        {
+            if cached_at.elapsed() < PARTITION_BY_CACHE_TTL {
+                return partition_by_opt.clone();
+            }
+        }
+        let index_metadata = match self.metastore.index_metadata(index_id).await {
+            Ok(index_metadata) => index_metadata,
+            Err(MetastoreError::IndexDoesNotExist { .. }) => return None,
+            Err(error) => {
+                warn!(index_id=%index_id, error=?error, "Failed to fetch index metadata.");
+                return None;
+            }
+        };
+        let partition_by_opt = index_metadata
+            .index_config()
+            .indexing_settings
+            .partition_by
+            .as_ref()
+            .and_then(|partition_by| RoutingExpr::new(partition_by).ok());
+        self.inner.lock().unwrap().partition_by_cache.insert(
+            index_id.to_string(),
+            (Instant::now(), partition_by_opt.clone()),
+        );
+        partition_by_opt
+    }
+
+    /// Returns the clients of the ready indexers running an ingest API pipeline for the index,
+    /// ordered by node ID so that all the nodes of the cluster agree on the shard of a document.
+    async fn shard_clients(&self, index_id: &str) -> Vec<(SocketAddr, IngestServiceClient)> {
+        let live_nodes = self.cluster.live_nodes().await;
+        let mut shard_nodes: Vec<&ClusterNode> = live_nodes
+            .iter()
+            .filter(|node| {
+                node.is_ready()
+                    && node.indexing_tasks().iter().any(|indexing_task| {
+                        indexing_task.source_id == INGEST_API_SOURCE_ID
+                            && IndexUid::from(indexing_task.index_uid.clone()).index_id()
+                                == index_id
+                    })
+            })
+            .collect();
+        shard_nodes.sort_by(|left, right| left.node_id().cmp(right.node_id()));
+
+        let mut inner = self.inner.lock().unwrap();
+        let live_addrs: HashSet<SocketAddr> = live_nodes
+            .iter()
+            .map(|node| node.grpc_advertise_addr())
+            .collect();
+        inner
+            .node_clients
+            .retain(|grpc_addr, _| live_addrs.contains(grpc_addr));
+
+        shard_nodes
+            .into_iter()
+            .map(|node| {
+                let grpc_addr = node.grpc_advertise_addr();
+                if node.is_self_node() {
+                    return (grpc_addr, self.ingest_service.clone());
+                }
+                let client = inner
+                    .node_clients
+                    .entry(grpc_addr)
+                    .or_insert_with(|| {
+                        let balance_channel =
+                            BalanceChannel::from_channel(grpc_addr, node.channel());
+                        IngestServiceClient::from_channel(balance_channel)
+                    })
+                    .clone();
+                (grpc_addr, client)
+            })
+            .collect()
+    }
+}
+
+/// Splits the documents of the batch into `num_shards` batches according to the hash of the
+/// routing expression. Documents that are not JSON objects are sent to the first shard. Commit
+/// commands are copied to every shard. Shards that receive no document are omitted.
+fn split_doc_batch(
+    doc_batch: &DocBatch,
+    partition_by: &RoutingExpr,
+    num_shards: usize,
+) -> Vec<(usize, DocBatch)> {
+    let mut shard_builders: Vec<(DocBatchBuilder, usize)> = (0..num_shards)
+        .map(|_| (DocBatchBuilder::new(doc_batch.index_id.clone()), 0))
+        .collect();
+
+    for raw_command in doc_batch.iter_raw() {
+        match DocCommand::read(raw_command.clone()) {
+            DocCommand::Ingest { payload } => {
+                let shard_ord = shard_ord(&payload, partition_by, num_shards);
+                let (shard_builder, num_docs) = &mut shard_builders[shard_ord];
+                shard_builder.command_from_buf(raw_command);
+                *num_docs += 1;
+            }
+            DocCommand::Commit => {
+                for (shard_builder, _) in &mut shard_builders {
+                    shard_builder.command_from_buf(raw_command.clone());
+                }
+            }
+        }
+    }
+    shard_builders
+        .into_iter()
+        .enumerate()
+        .filter(|(_, (_, num_docs))| *num_docs > 0)
+        .map(|(shard_ord, (shard_builder, _))| (shard_ord, shard_builder.build()))
+        .collect()
+}
+
+fn shard_ord(payload: &Bytes, partition_by: &RoutingExpr, num_shards: usize) -> usize {
+    let Ok(json_obj) = serde_json::from_slice::<JsonObject>(payload) else {
+        return 0;
+    };
+    (partition_by.eval_hash(&json_obj) % num_shards as u64) as usize
+}
+
+#[async_trait]
+impl IngestService for IngestRouter {
+    async fn ingest(&mut self, request: IngestRequest) -> quickwit_ingest::Result<IngestResponse> {
+        let commit = request.commit;
+        let mut unrouted_doc_batches = Vec::new();
+        // The routed batches are grouped by indexer so that each indexer receives at most one
+        // request.
+        let mut shard_requests: HashMap<SocketAddr, (IngestServiceClient, Vec<DocBatch>)> =
+            HashMap::new();
+
+        for doc_batch in request.doc_batches {
+            let Some(partition_by) = self.partition_by(&doc_batch.index_id).await else {
+                unrouted_doc_batches.push(doc_batch);
+                continue;
+            };
+            let shard_clients = self.shard_clients(&doc_batch.index_id).await;
+            if shard_clients.len() < 2 {
+                unrouted_doc_batches.push(doc_batch);
+                continue;
+            }
+            for (shard_ord, shard_doc_batch) in
+                split_doc_batch(&doc_batch, &partition_by, shard_clients.len())
+            {
+                let (grpc_addr, shard_client) = &shard_clients[shard_ord];
+                shard_requests
+                    .entry(*grpc_addr)
+                    .or_insert_with(|| (shard_client.clone(), Vec::new()))
+                    .1
+                    .push(shard_doc_batch);
+            }
+        }
+        if shard_requests.is_empty() {
+            let request = IngestRequest {
+                doc_batches: unrouted_doc_batches,
+                commit,
+            };
+            return self.ingest_service.ingest(request).await;
+        }
+        let mut client_doc_batches: Vec<(IngestServiceClient, Vec<DocBatch>)> =
+            shard_requests.into_values().collect();
+
+        if !unrouted_doc_batches.is_empty() {
+            client_doc_batches.push((self.ingest_service.clone(), unrouted_doc_batches));
+        }
+        let ingest_futures =
+            client_doc_batches
+                .into_iter()
+                .map(|(mut client, doc_batches)| async move {
+                    let request = IngestRequest {
+                        doc_batches,
+                        commit,
+                    };
+                    client.ingest(request).await
+                });
+        let num_docs_for_processing = try_join_all(ingest_futures)
+            .await?
+            .into_iter()
+            .map(|ingest_response| ingest_response.num_docs_for_processing)
+            .sum();
+        Ok(IngestResponse {
+            num_docs_for_processing,
+        })
+    }
+
+    async fn fetch(&mut self, request: FetchRequest) -> quickwit_ingest::Result<FetchResponse> {
+        self.ingest_service.fetch(request).await
+    }
+
+    async fn tail(&mut self, request: TailRequest) -> quickwit_ingest::Result<FetchResponse> {
+        self.ingest_service.tail(request).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chitchat::transport::ChannelTransport;
+    use quickwit_cluster::create_cluster_for_test;
+    use quickwit_metastore::{IndexMetadata, MockMetastore};
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_split_doc_batch() {
+        let mut doc_batch_builder = DocBatchBuilder::new("test-index".to_string()).json_writer();
+        for doc_id in 0..30 {
+            let doc = json!({"tenant_id": format!("tenant-{}", doc_id % 5), "doc_id": doc_id});
+            doc_batch_builder.ingest_doc(doc).unwrap();
+        }
+        let mut doc_batch_builder = doc_batch_builder.into_inner();
+        doc_batch_builder.commit();
+        doc_batch_builder.ingest_doc(&b"not a json object"[..]);
+        let doc_batch = doc_batch_builder.build();
+
+        let partition_by = RoutingExpr::new("tenant_id").unwrap();
+        let shard_doc_batches = split_doc_batch(&doc_batch, &partition_by, 3);
+        assert!(!shard_doc_batches.is_empty());
+
+        let mut tenant_shards: HashMap<String, usize> = HashMap::new();
+        let mut num_ingested_docs = 0;
+
+        for (shard_ord, shard_doc_batch) in &shard_doc_batches {
+            assert_eq!(shard_doc_batch.index_id, "test-index");
+            let mut num_commits = 0;
+
+            for doc_command in shard_doc_batch.iter() {
+                let DocCommand::Ingest { payload } = doc_command else {
+                    num_commits += 1;
+                    continue;
+                };
+                num_ingested_docs += 1;
+                let Ok(doc) = serde_json::from_slice::<JsonObject>(&payload) else {
+                    assert_eq!(*shard_ord, 0);
+                    continue;
+                };
+                let tenant_id = doc["tenant_id"].as_str().unwrap().to_string();
+                assert_eq!(
+                    *tenant_shards.entry(tenant_id).or_insert(*shard_ord),
+                    *shard_ord
+                );
+            }
+            assert_eq!(num_commits, 1);
+        }
+        assert_eq!(num_ingested_docs, 31);
+        assert_eq!(tenant_shards.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_ingest_router_forwards_to_default_service_without_shards() {
+        let transport = ChannelTransport::default();
+        let cluster = create_cluster_for_test(Vec::new(), &["indexer"], &transport, true)
+            .await
+            .unwrap();
+        let mut mock_metastore = MockMetastore::new();
+        mock_metastore
+            .expect_index_metadata()
+            .times(1)
+            .returning(|index_id: &str| {
+                let mut index_metadata =
+                    IndexMetadata::for_test(index_id, "ram:///indexes/test-index");
+                index_metadata.index_config.indexing_settings.partition_by =
+                    Some("tenant_id".to_string());
+                Ok(index_metadata)
+            });
+        let mut mock_ingest_service = IngestServiceClient::mock();
+        mock_ingest_service
+            .expect_ingest()
+            .times(2)
+            .returning(|ingest_request| {
+                assert_eq!(ingest_request.doc_batches.len(), 1);
+                assert_eq!(ingest_request.doc_batches[0].num_docs(), 2);
+                Ok(IngestResponse {
+                    num_docs_for_processing: 2,
+                })
+            });
+        let mut ingest_router = IngestRouter::new(
+            cluster,
+            Arc::new(mock_metastore),
+            IngestServiceClient::new(mock_ingest_service),
+        );
+        for _ in 0..2 {
+            let mut doc_batch_builder =
+                DocBatchBuilder::new("test-index".to_string()).json_writer();
+            doc_batch_builder
+                .ingest_doc(json!({"tenant_id": "tenant-1"}))
+                .unwrap();
+            doc_batch_builder
+                .ingest_doc(json!({"tenant_id": "tenant-2"}))
+                .unwrap();
+            let ingest_request = IngestRequest {
+                doc_batches: vec![doc_batch_builder.build()],
+                commit: 0,
+            };
+            let ingest_response = ingest_router.ingest(ingest_request).await.unwrap();
+            assert_eq!(ingest_response.num_docs_for_processing, 2);
+        }
+    }
+}
@@ -0,0 +1,366 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::{stream, Stream, StreamExt};
+use quickwit_actors::Mailbox;
+use quickwit_ingest::{
+    DocCommand, FetchRequest, GetLastPosition, IngestApiService, IngestServiceError,
+};
+use quickwit_metastore::{Metastore, MetastoreError};
+use quickwit_proto::{query_ast_from_user_text, ServiceError, ServiceErrorCode};
+use quickwit_query::query_ast::QueryAst;
+use serde::Deserialize;
+use serde_json::{Map as JsonMap, Value as JsonValue};
+use tracing::warn;
+use warp::reply::Response;
+use warp::sse::Event;
+use warp::{Filter, Rejection, Reply};
+
+use crate::json_api_response::make_json_api_response;
+use crate::simple_list::from_simple_list;
+use crate::{require, with_arg, BodyFormat};
+
+#[derive(utoipa::OpenApi)]
+#[openapi(paths(tail_stream_endpoint))]
+pub struct TailStreamApi;
+
+/// Interval at which the ingest queue is polled for new documents.
+const TAIL_POLL_INTERVAL: Duration = if cfg!(any(test, feature = "testsuite")) {
+    Duration::from_millis(10)
+} else {
+    Duration::from_millis(500)
+};
+
+#[derive(Debug, thiserror::Error)]
+pub enum TailStreamError {
+    #[error("Invalid query: {0}.")]
+    InvalidQuery(String),
+    #[error(transparent)]
+    Metastore(#[from] MetastoreError),
+    #[error(transparent)]
+    IngestService(#[from] IngestServiceError),
+}
+
+impl ServiceError for TailStreamError {
+    fn status_code(&self) -> ServiceErrorCode {
+        match self {
+            TailStreamError::InvalidQuery(_) => ServiceErrorCode::BadRequest,
+            TailStreamError::Metastore(error) => error.status_code(),
+            TailStreamError::IngestService(error) => error.status_code(),
+        }
+    }
+}
+
+/// This struct represents the tail stream query passed to the REST API.
+#[derive(Debug, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(deny_unknown_fields)]
+struct TailStreamQueryString {
+    /// Query text filtering the streamed documents. The query language is that of tantivy. If
+    /// unset, all the documents are streamed.
+    #[serde(default)]
+    pub query: Option<String>,
+    /// Fields searched by the query terms that do not name a field.
+    #[param(rename = "search_field")]
+    #[serde(default)]
+    #[serde(rename(deserialize = "search_field"))]
+    #[serde(deserialize_with = "from_simple_list")]
+    pub search_fields: Option<Vec<String>>,
+}
+
+/// Follows the ingest queue of an index from its end, returning the documents appended to it that
+/// match a query.
+struct TailCursor {
+    index_id: String,
+    query_ast: QueryAst,
+    ingest_api_service: Mailbox<IngestApiService>,
+    start_after_opt: Option<u64>,
+}
+
+impl TailCursor {
+    async fn start(
+        index_id: String,
+        query_ast: QueryAst,
+        ingest_api_service: Mailbox<IngestApiService>,
+    ) -> Result<Self, IngestServiceError> {
+        // Skip the documents already in the queue.
+        let get_last_position = GetLastPosition {
+            queue_id: index_id.clone(),
+        };
+        let start_after_opt = ingest_api_service.ask_for_res(get_last_position).await?;
+        Ok(Self {
+            index_id,
+            query_ast,
+            ingest_api_service,
+            start_after_opt,
+        })
+    }
+
+    /// Returns the matching documents appended to the queue since the last call, waiting for new
+    /// ones if there are none.
+    async fn next_docs(&mut self) -> Result<Vec<JsonMap<String, JsonValue>>, IngestServiceError> {
+        loop {
+            let fetch_request = FetchRequest {
+                index_id: self.index_id.clone(),
+                start_after: self.start_after_opt,
+                num_bytes_limit: None,
+            };
+            let fetch_response = self.ingest_api_service.ask_for_res(fetch_request).await?;
+            let (first_position, doc_batch) =
+                match (fetch_response.first_position, fetch_response.doc_batch) {
+                    (Some(first_position), Some(doc_batch)) => (first_position, doc_batch),
+                    _ => {
+                        tokio::time::sleep(TAIL_POLL_INTERVAL).await;
+                        continue;
+                    }
+                };
+            let mut docs = Vec::new();
+
+            for (position, doc_command) in (first_position..).zip(doc_batch.iter()) {
+                self.start_after_opt = Some(position);
+
+                let payload = match doc_command {
+                    DocCommand::Ingest { payload } => payload,
+                    DocCommand::Commit => continue,
+                };
+                // Documents that are not JSON objects are rejected by the indexing pipeline.
+                let doc: JsonMap<String, JsonValue> = match serde_json::from_slice(&payload) {
+                    Ok(doc) => doc,
+                    Err(_) => continue,
+                };
+                match self.query_ast.matches_json_doc(&doc) {
+                    Ok(true) => docs.push(doc),
+                    Ok(false) => {}
+                    Err(error) => {
+                        warn!(
+                            index_id=%self.index_id,
+                            error=%error,
+                            "Failed to evaluate tail query."
+                        );
+                    }
+                }
+            }
+            if !docs.is_empty() {
+                return Ok(docs);
+            }
+        }
+    }
+
+    fn into_event_stream(self) -> impl Stream<Item = Result<Event, Infallible>> {
+        stream::unfold(Some(self), |tail_cursor_opt| async move {
+            let mut tail_cursor = tail_cursor_opt?;
+            match tail_cursor.next_docs().await {
+                Ok(docs) => {
+                    let events: Vec<Result<Event, Infallible>> = docs
+                        .into_iter()
+                        .map(|doc| {
+                            let doc_json = JsonValue::Object(doc).to_string();
+                            Ok(Event::default().event("doc").data(doc_json))
+                        })
+                        .collect();
+                    Some((stream::iter(events), Some(tail_cursor)))
+                }
+                Err(error) => {
+                    // The stream ends after reporting the error.
+                    warn!(
+                        index_id=%tail_cursor.index_id,
+                        error=%error,
+                        "Failed to tail ingest queue."
+                    );
+                    let event = Event::default().event("error").data(error.to_string());
+                    Some((stream::iter(vec![Ok(event)]), None))
+                }
+            }
+        })
+        .flatten()
+    }
+}
+
+async fn start_tail_cursor(
+    index_id: String,
+    tail_query: TailStreamQueryString,
+    metastore: Arc<dyn Metastore>,
+    ingest_api_service: Mailbox<IngestApiService>,
+) -> Result<TailCursor, TailStreamError> {
+    let query_ast = match tail_query.query {
+        Some(query) => {
+            let index_metadata = metastore.index_metadata(&index_id).await?;
+            let default_search_fields = &index_metadata
+                .index_config
+                .search_settings
+                .default_search_fields;
+            query_ast_from_user_text(&query, tail_query.search_fields)
+                .parse_user_query(default_search_fields)
+                .map_err(|error| TailStreamError::InvalidQuery(error.to_string()))?
+        }
+        None => QueryAst::MatchAll,
+    };
+    let tail_cursor = TailCursor::start(index_id, query_ast, ingest_api_service).await?;
+    Ok(tail_cursor)
+}
+
+#[utoipa::path(
+    get,
+    tag = "Ingest",
+    path = "/{index_id}/tail/stream",
+    responses(
+        (status = 200, description = "Successfully started streaming the ingested documents as server-sent events.")
+    ),
+    params(
+        TailStreamQueryString,
+        ("index_id" = String, Path, description = "The index ID to tail."),
+    )
+)]
+/// Stream ingested documents
+///
+/// Streams the documents ingested into the index from now on, and matching the query, as
+/// server-sent events. Each `doc` event carries a document. Only the ingest queue of the node
+/// handling the request, which must run the indexer service, is tailed.
+async fn tail_stream_endpoint(
+    index_id: String,
+    tail_query: TailStreamQueryString,
+    metastore: Arc<dyn Metastore>,
+    ingest_api_service: Mailbox<IngestApiService>,
+) -> Response {
+    match start_tail_cursor(index_id, tail_query, metastore, ingest_api_service).await {
+        Ok(tail_cursor) => {
+            let event_stream = warp::sse::keep_alive().stream(tail_cursor.into_event_stream());
+            warp::sse::reply(event_stream).into_response()
+        }
+        Err(error) => {
+            make_json_api_response::<(), _>(Err(error), BodyFormat::default()).into_response()
+        }
+    }
+}
+
+fn tail_stream_filter(
+) -> impl Filter<Extract = (String, TailStreamQueryString), Error = Rejection> + Clone {
+    warp::path!(String / "tail" / "stream")
+        .and(warp::get())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+}
+
+/// Tail stream handler. The ingest queues are only available on the nodes running the indexer
+/// service.
+pub(crate) fn tail_stream_handler(
+    metastore: Arc<dyn Metastore>,
+    ingest_api_service_opt: Option<Mailbox<IngestApiService>>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    tail_stream_filter()
+        .and(with_arg(metastore))
+        .and(require(ingest_api_service_opt))
+        .then(tail_stream_endpoint)
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_config::IngestApiConfig;
+    use quickwit_ingest::{DocBatchBuilder, IngestRequest, IngestService, IngestServiceClient};
+    use quickwit_metastore::{IndexMetadata, MockMetastore};
+    use serde_json::json;
+
+    use super::*;
+    use crate::ingest_api::setup_ingest_service;
+
+    async fn ingest_docs(ingest_service: &mut IngestServiceClient, docs: &[JsonValue]) {
+        let mut doc_batch_builder = DocBatchBuilder::new("my-index".to_string()).json_writer();
+        for doc in docs {
+            doc_batch_builder.ingest_doc(doc).unwrap();
+        }
+        let ingest_request = IngestRequest {
+            doc_batches: vec![doc_batch_builder.build()],
+            commit: 0,
+        };
+        ingest_service.ingest(ingest_request).await.unwrap();
+    }
+
+    fn mock_metastore() -> Arc<dyn Metastore> {
+        let mut metastore = MockMetastore::new();
+        metastore
+            .expect_index_metadata()
+            .returning(|index_id: &str| {
+                Ok(IndexMetadata::for_test(index_id, "ram:///indexes/my-index"))
+            });
+        Arc::new(metastore)
+    }
+
+    #[tokio::test]
+    async fn test_tail_cursor_streams_new_matching_docs() {
+        let (universe, _temp_dir, mut ingest_service, ingest_api_service) =
+            setup_ingest_service(&["my-index"], &IngestApiConfig::default()).await;
+        ingest_docs(&mut ingest_service, &[json!({"body": "error before"})]).await;
+
+        let tail_query = TailStreamQueryString {
+            query: Some("error".to_string()),
+            search_fields: None,
+        };
+        let mut tail_cursor = start_tail_cursor(
+            "my-index".to_string(),
+            tail_query,
+            mock_metastore(),
+            ingest_api_service,
+        )
+        .await
+        .unwrap();
+
+        ingest_docs(
+            &mut ingest_service,
+            &[
+                json!({"body": "error after"}),
+                json!({"body": "info after"}),
+            ],
+        )
+        .await;
+        let docs = tail_cursor.next_docs().await.unwrap();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0]["body"], "error after");
+
+        ingest_docs(&mut ingest_service, &[json!({"body": "another error"})]).await;
+        let docs = tail_cursor.next_docs().await.unwrap();
+        assert_eq!(docs.len(), 1);
+        assert_eq!(docs[0]["body"], "another error");
+
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_tail_stream_handler_errors() {
+        let (universe, _temp_dir, _, ingest_api_service) =
+            setup_ingest_service(&["my-index"], &IngestApiConfig::default()).await;
+        let tail_stream_handler = tail_stream_handler(mock_metastore(), Some(ingest_api_service));
+
+        let resp = warp::test::request()
+            .path("/my-index/tail/stream?query=body:[")
+            .reply(&tail_stream_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+
+        let resp = warp::test::request()
+            .path("/unknown-index/tail/stream")
+            .reply(&tail_stream_handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        universe.assert_quit().await;
+    }
+}
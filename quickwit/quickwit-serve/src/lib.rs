@@ -26,6 +26,9 @@ mod grpc;
 mod rest;
 pub(crate) mod simple_list;
 
+mod admin_api;
+mod audit_log;
+mod auth;
 mod build_info;
 mod cluster_api;
 mod delete_task_api;
@@ -37,10 +40,15 @@ mod ingest_api;
 mod json_api_response;
 mod node_info_handler;
 mod openapi;
+mod otlp_api;
+mod prometheus_api;
+mod rate_limit;
 mod search_api;
 #[cfg(test)]
 mod tests;
+mod ui_api;
 mod ui_handler;
+mod zipkin_api;
 
 use std::collections::{HashMap, HashSet};
 use std::convert::Infallible;
@@ -54,42 +62,64 @@ use byte_unit::n_mib_bytes;
 use format::BodyFormat;
 use futures::StreamExt;
 use itertools::Itertools;
-use quickwit_actors::{ActorExitStatus, Mailbox, Universe};
-use quickwit_cluster::{Cluster, ClusterChange, ClusterMember};
+use quickwit_actors::{ActorExitStatus, Mailbox, SpawnContext, Universe};
+use quickwit_cluster::{Cluster, ClusterChange, ClusterMember, DecommissionStatus, GrpcTransport};
 use quickwit_common::pubsub::{EventBroker, EventSubscriptionHandle};
 use quickwit_common::tower::{
     BalanceChannel, BoxFutureInfaillible, BufferLayer, Change, ConstantRate, EstimateRateLayer,
     Rate, RateLimitLayer, SmaRateEstimator,
 };
 use quickwit_config::service::QuickwitService;
-use quickwit_config::{load_index_config_from_user_config, ConfigFormat, QuickwitConfig};
+use quickwit_config::{
+    load_index_config_from_user_config, ConfigFormat, GossipTransport, IndexerConfig,
+    QuickwitConfig,
+};
+use quickwit_control_plane::leader_election::leader_channel;
 use quickwit_control_plane::{start_control_plane_service, ControlPlaneServiceClient};
 use quickwit_core::{IndexService, IndexServiceError};
 use quickwit_grpc_clients::service_client_pool::ServiceClientPool;
 use quickwit_indexing::actors::IndexingService;
+use quickwit_indexing::models::{Drain, Observe};
 use quickwit_indexing::start_indexing_service;
 use quickwit_ingest::{
-    start_ingest_api_service, GetMemoryCapacity, IngestRequest, IngestServiceClient, MemoryCapacity,
+    start_ingest_api_service, GetMemoryCapacity, IngestApiService, IngestRequest,
+    IngestServiceClient, MemoryCapacity,
 };
+pub use quickwit_janitor::SplitRemovalInfo;
 use quickwit_janitor::{start_janitor_service, JanitorService};
 use quickwit_metastore::{
-    quickwit_metastore_uri_resolver, Metastore, MetastoreError, MetastoreEvent,
-    MetastoreEventPublisher, MetastoreGrpcClient, RetryingMetastore,
+    quickwit_metastore_uri_resolver, set_default_audit_actor, Metastore, MetastoreError,
+    MetastoreEvent, MetastoreEventPublisher, MetastoreGrpcClient, RetryingMetastore,
+};
+use quickwit_opentelemetry::otlp::{
+    OtlpAttributePromoter, OtlpGrpcLogsService, OtlpGrpcTraceService, OtlpIndexRouter, TailSampler,
+    OTEL_LOGS_INDEX_CONFIG, OTEL_METRICS_INDEX_CONFIG, OTEL_TRACE_INDEX_CONFIG,
+};
+use quickwit_search::{
+    start_searcher_load_reporting, start_searcher_service, SearchJobPlacer, SearchService,
+    SearcherContext,
 };
-use quickwit_opentelemetry::otlp::{OTEL_LOGS_INDEX_CONFIG, OTEL_TRACE_INDEX_CONFIG};
-use quickwit_search::{start_searcher_service, SearchJobPlacer, SearchService};
 use quickwit_storage::quickwit_storage_uri_resolver;
-use tokio::sync::oneshot;
+use tokio::sync::{oneshot, watch};
 use tower::ServiceBuilder;
 use tracing::{debug, error, info, warn};
 use warp::{Filter, Rejection};
 
+use crate::admin_api::RuntimeSettingsService;
+pub use crate::admin_api::{LogLevel, RuntimeHooks, RuntimeSettings};
+use crate::audit_log::AuditLogger;
 pub use crate::build_info::{BuildInfo, RuntimeInfo};
-pub use crate::index_api::ListSplitsQueryParams;
+pub use crate::cluster_api::NodeDecommissionStatus;
+use crate::cluster_api::{cluster_settings_refresh_task, ClusterSettingsService};
+pub use crate::delete_task_api::{DeleteQueryRequest, DeleteTaskState, DeleteTaskStatus};
+use crate::health_check_api::{ReadinessChecker, ReadinessReport};
+pub use crate::index_api::{CheckpointRewindTarget, ListSplitsQueryParams, SourceCheck};
+use crate::ingest_api::IngestRouter;
 pub use crate::metrics::SERVE_METRICS;
+use crate::rate_limit::RateLimiter;
 #[cfg(test)]
 use crate::rest::recover_fn;
-pub use crate::search_api::{SearchRequestQueryString, SortByField};
+pub use crate::search_api::{SearchRequestQueryString, SortByField, SqlRequestQueryString};
 
 const READINESS_REPORTING_INTERVAL: Duration = if cfg!(any(test, feature = "testsuite")) {
     Duration::from_millis(25)
@@ -97,11 +127,27 @@ const READINESS_REPORTING_INTERVAL: Duration = if cfg!(any(test, feature = "test
     Duration::from_secs(10)
 };
 
+const DECOMMISSION_CHECK_INTERVAL: Duration = if cfg!(any(test, feature = "testsuite")) {
+    Duration::from_millis(25)
+} else {
+    Duration::from_secs(1)
+};
+
 struct QuickwitServices {
     pub config: Arc<QuickwitConfig>,
     pub cluster: Cluster,
+    /// Transport of the gossip messages when the nodes gossip over gRPC instead of UDP.
+    pub grpc_gossip_transport_opt: Option<GrpcTransport>,
     pub metastore: Arc<dyn Metastore>,
-    pub control_plane_service: Option<ControlPlaneServiceClient>,
+    /// Metastore exposed to the other nodes by the gRPC metastore service. Unlike `metastore`,
+    /// it does not record mutations in the audit log: the calling nodes record them on their own
+    /// behalf.
+    pub metastore_server_opt: Option<Arc<dyn Metastore>>,
+    /// Client of the control plane leader, which may run on this node or on another one.
+    pub control_plane_service: ControlPlaneServiceClient,
+    /// Control plane of the node exposed to the other nodes by the gRPC control plane service. It
+    /// only schedules indexing plans while it is the leader of the cluster.
+    pub control_plane_server_opt: Option<ControlPlaneServiceClient>,
     /// The control plane listens to metastore events.
     /// We need to keep the subscription handle to keep listening. If not, subscription is dropped.
     #[allow(dead_code)]
@@ -113,8 +159,30 @@ struct QuickwitServices {
     pub indexing_service: Option<Mailbox<IndexingService>>,
     pub janitor_service: Option<Mailbox<JanitorService>>,
     pub ingest_service: IngestServiceClient,
+    /// Ingest API service owning the ingest queues of the node, only running on indexers.
+    pub ingest_api_service_opt: Option<Mailbox<IngestApiService>>,
+    /// Ingest service used by the REST ingest endpoints. It routes the documents of the indexes
+    /// declaring a `partition_by` routing expression to the indexers.
+    pub ingest_router: IngestServiceClient,
+    /// Tail sampler shared by the OTLP trace services when tail sampling is enabled.
+    pub otlp_tail_sampler_opt: Option<Mailbox<TailSampler>>,
     pub index_service: Arc<IndexService>,
+    /// Records the search, ingest, and admin requests received by the node when the request audit
+    /// log is enabled.
+    pub audit_logger_opt: Option<AuditLogger>,
+    /// Rate limits shared by the REST and gRPC servers, set in the node config or in the cluster
+    /// settings.
+    pub rate_limiter: Arc<RateLimiter>,
+    /// Applies and persists the settings changed at runtime through the admin API.
+    pub runtime_settings_service: Arc<RuntimeSettingsService>,
+    /// Applies the cluster settings stored in the metastore.
+    pub cluster_settings_service: Arc<ClusterSettingsService>,
     pub services: HashSet<QuickwitService>,
+    /// Latest report of the availability of the node dependencies, refreshed by the readiness
+    /// reporting task.
+    pub readiness_report_rx: watch::Receiver<ReadinessReport>,
+    /// Spawn context of the universe hosting the actors of the node, used to observe them.
+    pub spawn_ctx: SpawnContext,
 }
 
 fn has_node_with_metastore_service(members: &[ClusterMember]) -> bool {
@@ -146,27 +214,58 @@ async fn balance_channel_for_service(
 
 pub async fn serve_quickwit(
     config: QuickwitConfig,
+    runtime_hooks: RuntimeHooks,
     shutdown_signal: BoxFutureInfaillible<()>,
 ) -> anyhow::Result<HashMap<String, ActorExitStatus>> {
     let universe = Universe::new();
     let event_broker = EventBroker::default();
     let storage_resolver = quickwit_storage_uri_resolver().clone();
-    let cluster =
-        quickwit_cluster::start_cluster_service(&config, &config.enabled_services).await?;
+    // The metastore records the mutations in its audit log on behalf of the principal of the
+    // request applying them, or of the node when authentication is disabled or the node applies
+    // them on its own, for instance from an indexing pipeline.
+    set_default_audit_actor(config.node_id.clone());
+    // The clients of the internal gRPC services present the cluster key to the other nodes.
+    if let Some(cluster_key) = &config.auth_config.cluster_key {
+        quickwit_common::tower::set_cluster_key(cluster_key)?;
+    }
+    // The clients of the gRPC services of the other nodes, including the gossip transport, connect
+    // with TLS when the gRPC server of the nodes serves TLS.
+    if let Some(grpc_tls_config) = &config.grpc_tls_config {
+        let client_tls_config = grpc::client_tls_config(grpc_tls_config).await?;
+        quickwit_common::tower::set_client_tls_config(client_tls_config)?;
+    }
+    // The gossip service is mounted on the gRPC server, which starts after the node joins the
+    // cluster. In the meantime, the gossip messages sent to the node are lost and retried by the
+    // protocol.
+    let grpc_gossip_transport_opt = match config.gossip_transport {
+        GossipTransport::Udp => None,
+        GossipTransport::Grpc => Some(GrpcTransport::new(config.gossip_advertise_addr)),
+    };
+    let cluster = quickwit_cluster::start_cluster_service(
+        &config,
+        &config.enabled_services,
+        grpc_gossip_transport_opt.as_ref(),
+    )
+    .await?;
 
     // Instantiate either a file-backed or postgresql [`Metastore`] if the node runs a `Metastore`
     // service, else instantiate a [`MetastoreGrpcClient`].
-    let metastore: Arc<dyn Metastore> = if config
+    let metastore_server_opt: Option<Arc<dyn Metastore>> = if config
         .enabled_services
         .contains(&QuickwitService::Metastore)
     {
         let metastore = quickwit_metastore_uri_resolver()
-            .resolve(&config.metastore_uri)
+            .resolve_with_config(&config.metastore_config, &config.metastore_uri)
             .await?;
-        Arc::new(MetastoreEventPublisher::new(
+        Some(Arc::new(MetastoreEventPublisher::new(
             metastore,
             event_broker.clone(),
-        ))
+        )))
+    } else {
+        None
+    };
+    let metastore: Arc<dyn Metastore> = if let Some(metastore_server) = &metastore_server_opt {
+        metastore_server.clone()
     } else {
         // Wait 10 seconds for nodes running a `Metastore` service.
         cluster
@@ -196,15 +295,20 @@ pub async fn serve_quickwit(
     .await?;
 
     // Always instantiate index management service.
-    let index_service = Arc::new(IndexService::new(
-        metastore.clone(),
-        storage_resolver.clone(),
-    ));
+    let index_service = Arc::new(
+        IndexService::new(metastore.clone(), storage_resolver.clone())
+            .with_index_trash_enabled(
+                config
+                    .janitor_config
+                    .index_trash_retention_period_opt()
+                    .is_some(),
+            )
+            .with_default_staged_grace_period(config.janitor_config.gc_staged_split_grace_period()),
+    );
 
-    // Instantiate the control plane service if enabled.
-    // If not and metastore service is enabled, we need to instantiate the control plane client
-    // so the metastore can notify the control plane.
-    let control_plane_service: Option<ControlPlaneServiceClient> = if config
+    // Instantiate the control plane service if enabled. Several nodes may run a control plane:
+    // they elect a leader among themselves and the others stand by.
+    let control_plane_server_opt: Option<ControlPlaneServiceClient> = if config
         .enabled_services
         .contains(&QuickwitService::ControlPlane)
     {
@@ -213,22 +317,26 @@ pub async fn serve_quickwit(
         Some(ControlPlaneServiceClient::from_mailbox(
             control_plane_mailbox,
         ))
-    } else if config
+    } else {
+        None
+    };
+    // We instantiate a client of the control plane leader so that the metastore can notify the
+    // control plane and the REST API can fetch its indexing plan.
+    let control_plane_service =
+        ControlPlaneServiceClient::from_channel(leader_channel(&cluster).await);
+    let control_plane_subscription_handle = if config
         .enabled_services
-        .contains(&QuickwitService::Metastore)
+        .contains(&QuickwitService::ControlPlane)
+        || config
+            .enabled_services
+            .contains(&QuickwitService::Metastore)
     {
-        let balance_channel =
-            balance_channel_for_service(&cluster, QuickwitService::ControlPlane).await;
-        Some(ControlPlaneServiceClient::from_channel(balance_channel))
+        Some(event_broker.subscribe::<MetastoreEvent>(control_plane_service.clone()))
     } else {
         None
     };
-    let control_plane_subscription_handle =
-        control_plane_service.as_ref().map(|scheduler_service| {
-            event_broker.subscribe::<MetastoreEvent>(scheduler_service.clone())
-        });
 
-    let (ingest_service, indexing_service) = if config
+    let (ingest_service, ingest_api_service_opt, indexing_service) = if config
         .enabled_services
         .contains(&QuickwitService::Indexer)
     {
@@ -236,19 +344,50 @@ pub async fn serve_quickwit(
             start_ingest_api_service(&universe, &config.data_dir_path, &config.ingest_api_config)
                 .await?;
         if config.indexer_config.enable_otlp_endpoint {
-            for index_config_content in [OTEL_LOGS_INDEX_CONFIG, OTEL_TRACE_INDEX_CONFIG] {
-                let index_config = load_index_config_from_user_config(
+            let attribute_promoter =
+                OtlpAttributePromoter::new(&config.indexer_config.otlp_promoted_attributes);
+            for (index_config_content, index_id_opt) in [
+                (
+                    OTEL_LOGS_INDEX_CONFIG,
+                    Some(&config.indexer_config.otlp_logs_index_id),
+                ),
+                (
+                    OTEL_TRACE_INDEX_CONFIG,
+                    Some(&config.indexer_config.otlp_trace_index_id),
+                ),
+                (OTEL_METRICS_INDEX_CONFIG, None),
+            ] {
+                let mut index_config = load_index_config_from_user_config(
                     ConfigFormat::Yaml,
                     index_config_content.as_bytes(),
                     &config.default_index_root_uri,
                 )?;
+                // The OTLP logs and trace indexes can be renamed in the indexer config and
+                // receive the promoted attributes.
+                if let Some(index_id) = index_id_opt {
+                    if &index_config.index_id != index_id {
+                        index_config.index_uri = config.default_index_root_uri.join(index_id)?;
+                        index_config.index_id = index_id.clone();
+                    }
+                    attribute_promoter.add_field_mappings(&mut index_config)?;
+                }
+                let index_id = index_config.index_id.clone();
                 match index_service.create_index(index_config, false).await {
-                    Ok(_)
-                    | Err(IndexServiceError::MetastoreError(
+                    Ok(_) => {}
+                    Err(IndexServiceError::MetastoreError(
                         MetastoreError::IndexAlreadyExists { .. },
-                    )) => Ok(()),
-                    Err(error) => Err(error),
-                }?;
+                    )) => {
+                        if index_id_opt.is_some() {
+                            warn_on_missing_promoted_fields(
+                                metastore.as_ref(),
+                                &index_id,
+                                &attribute_promoter,
+                            )
+                            .await?;
+                        }
+                    }
+                    Err(error) => return Err(error.into()),
+                }
             }
         }
         let indexing_service = start_indexing_service(
@@ -279,26 +418,70 @@ pub async fn serve_quickwit(
                     .layer(RateLimitLayer::new(rate_modulator))
                     .into_inner(),
             )
-            .build_from_mailbox(ingest_api_service);
-        (ingest_service, Some(indexing_service))
+            .build_from_mailbox(ingest_api_service.clone());
+        (
+            ingest_service,
+            Some(ingest_api_service),
+            Some(indexing_service),
+        )
     } else {
         let balance_channel = balance_channel_for_service(&cluster, QuickwitService::Indexer).await;
         let ingest_service = IngestServiceClient::from_channel(balance_channel);
-        (ingest_service, None)
+        (ingest_service, None, None)
     };
+    let otlp_tail_sampler_opt = match &config.indexer_config.otlp_tail_sampling {
+        Some(tail_sampling_config)
+            if config.indexer_config.enable_otlp_endpoint
+                && config.enabled_services.contains(&QuickwitService::Indexer) =>
+        {
+            let tail_sampler = TailSampler::new(tail_sampling_config, ingest_service.clone())
+                .with_id_encoding(config.indexer_config.otlp_id_encoding);
+            let (tail_sampler_mailbox, _tail_sampler_handle) =
+                universe.spawn_builder().spawn(tail_sampler);
+            Some(tail_sampler_mailbox)
+        }
+        _ => None,
+    };
+    let ingest_router = IngestServiceClient::new(IngestRouter::new(
+        cluster.clone(),
+        metastore.clone(),
+        ingest_service.clone(),
+    ));
 
     let ready_members_watcher = cluster.ready_members_watcher().await;
     let search_job_placer = SearchJobPlacer::new(
         ServiceClientPool::create_and_update_members(ready_members_watcher).await?,
-    );
+    )
+    .with_searcher_members(cluster.ready_members_watcher().await)
+    .with_availability_zone(config.availability_zone_opt.clone());
+
+    let searcher_context = Arc::new(SearcherContext::new(config.searcher_config.clone()));
+    if config.enabled_services.contains(&QuickwitService::Searcher) {
+        start_searcher_load_reporting(cluster.clone(), searcher_context.clone());
+    }
+    let search_service: Arc<dyn SearchService> = start_searcher_service(
+        metastore.clone(),
+        storage_resolver.clone(),
+        search_job_placer.clone(),
+        searcher_context.clone(),
+    )
+    .await?;
+    tokio::spawn(node_decommission_task(
+        cluster.clone(),
+        indexing_service.clone(),
+        searcher_context.clone(),
+    ));
 
     let janitor_service = if config.enabled_services.contains(&QuickwitService::Janitor) {
         let janitor_service = start_janitor_service(
             &universe,
             &config,
+            &cluster,
             metastore.clone(),
-            search_job_placer.clone(),
+            search_job_placer,
             storage_resolver.clone(),
+            search_service.clone(),
+            ingest_service.clone(),
         )
         .await?;
         Some(janitor_service)
@@ -306,13 +489,41 @@ pub async fn serve_quickwit(
         None
     };
 
-    let search_service: Arc<dyn SearchService> = start_searcher_service(
-        &config,
-        metastore.clone(),
-        storage_resolver,
-        search_job_placer,
+    let audit_logger_opt = AuditLogger::start(
+        &config.audit_log_config,
+        &index_service,
+        &config.default_index_root_uri,
+        ingest_service.clone(),
     )
     .await?;
+    let rate_limiter = Arc::new(RateLimiter::new(config.rate_limit_config.clone()));
+    let runtime_settings_service = RuntimeSettingsService::load(
+        &config.data_dir_path,
+        config.searcher_config.max_num_concurrent_split_searches,
+        searcher_context,
+        indexing_service.clone(),
+        runtime_hooks,
+    )
+    .await
+    .map(Arc::new)?;
+    let cluster_settings_service = Arc::new(ClusterSettingsService::new(
+        metastore.clone(),
+        config.rate_limit_config.clone(),
+        rate_limiter.clone(),
+        runtime_settings_service.clone(),
+    ));
+    tokio::spawn(cluster_settings_refresh_task(
+        cluster_settings_service.clone(),
+    ));
+
+    let readiness_checker = ReadinessChecker::new(
+        cluster.clone(),
+        metastore.clone(),
+        storage_resolver,
+        config.default_index_root_uri.clone(),
+        config.enabled_services.clone(),
+    );
+    let (readiness_report_tx, readiness_report_rx) = watch::channel(ReadinessReport::default());
 
     let grpc_listen_addr = config.grpc_listen_addr;
     let rest_listen_addr = config.rest_listen_addr;
@@ -320,15 +531,27 @@ pub async fn serve_quickwit(
     let quickwit_services: Arc<QuickwitServices> = Arc::new(QuickwitServices {
         config: Arc::new(config),
         cluster: cluster.clone(),
+        grpc_gossip_transport_opt,
         metastore: metastore.clone(),
+        metastore_server_opt,
         control_plane_service,
+        control_plane_server_opt,
         control_plane_subscription_handle,
         search_service,
         indexing_service,
         janitor_service,
         ingest_service,
+        ingest_api_service_opt,
+        ingest_router,
+        otlp_tail_sampler_opt,
         index_service,
+        audit_logger_opt,
+        rate_limiter,
+        runtime_settings_service,
+        cluster_settings_service,
         services,
+        readiness_report_rx,
+        spawn_ctx: universe.spawn_ctx().clone(),
     });
     // Setup and start gRPC server.
     let (grpc_readiness_trigger_tx, grpc_readiness_signal_rx) = oneshot::channel::<()>();
@@ -373,7 +596,8 @@ pub async fn serve_quickwit(
     // Thus readiness task is started once gRPC and REST servers are started.
     tokio::spawn(node_readiness_reporting_task(
         cluster,
-        metastore,
+        readiness_checker,
+        readiness_report_tx,
         grpc_readiness_signal_rx,
         rest_readiness_signal_rx,
     ));
@@ -467,6 +691,71 @@ where R: Rate
     }
 }
 
+/// Promoted attributes are only added to the doc mapping of the OTLP indexes when they are
+/// created. Since the indexes are strict, log records and spans would be rejected if one of the
+/// promoted fields were missing from an existing index.
+async fn warn_on_missing_promoted_fields(
+    metastore: &dyn Metastore,
+    index_id: &str,
+    attribute_promoter: &OtlpAttributePromoter,
+) -> anyhow::Result<()> {
+    let index_metadata = metastore.index_metadata(index_id).await?;
+    let missing_fields = attribute_promoter.missing_fields(&index_metadata.index_config);
+
+    if !missing_fields.is_empty() {
+        warn!(
+            index_id=%index_id,
+            missing_fields=?missing_fields,
+            "The OTLP index was created before some of the promoted attributes were configured. \
+             Recreate the index or configure another index ID to promote these attributes."
+        );
+    }
+    Ok(())
+}
+
+/// Builds the OTLP logs service, routing the log records as configured in the indexer config.
+fn otlp_logs_service(
+    ingest_service: IngestServiceClient,
+    indexer_config: &IndexerConfig,
+) -> OtlpGrpcLogsService {
+    let index_router = OtlpIndexRouter::new(
+        indexer_config.otlp_logs_index_id.clone(),
+        indexer_config.otlp_tenant_attribute.clone(),
+    );
+    let attribute_promoter = OtlpAttributePromoter::new(&indexer_config.otlp_promoted_attributes);
+    OtlpGrpcLogsService::new(ingest_service)
+        .with_index_router(index_router)
+        .with_attribute_promoter(attribute_promoter)
+        .with_id_encoding(indexer_config.otlp_id_encoding)
+        .with_limits(indexer_config.otlp_limits.clone())
+        .with_ingest_config(&indexer_config.otlp_logs_ingest)
+}
+
+/// Builds the OTLP trace service, routing the spans as configured in the indexer config and
+/// handing them over to the tail sampler, if any.
+fn otlp_trace_service(
+    ingest_service: IngestServiceClient,
+    indexer_config: &IndexerConfig,
+    tail_sampler_opt: Option<Mailbox<TailSampler>>,
+) -> OtlpGrpcTraceService {
+    let index_router = OtlpIndexRouter::new(
+        indexer_config.otlp_trace_index_id.clone(),
+        indexer_config.otlp_tenant_attribute.clone(),
+    );
+    let attribute_promoter = OtlpAttributePromoter::new(&indexer_config.otlp_promoted_attributes);
+    let otlp_trace_service = OtlpGrpcTraceService::new(ingest_service)
+        .with_index_router(index_router)
+        .with_attribute_promoter(attribute_promoter)
+        .with_id_encoding(indexer_config.otlp_id_encoding)
+        .with_span_metrics(indexer_config.enable_otlp_span_metrics)
+        .with_limits(indexer_config.otlp_limits.clone())
+        .with_ingest_config(&indexer_config.otlp_trace_ingest);
+    match tail_sampler_opt {
+        Some(tail_sampler) => otlp_trace_service.with_tail_sampler(tail_sampler),
+        None => otlp_trace_service,
+    }
+}
+
 fn require<T: Clone + Send>(
     val_opt: Option<T>,
 ) -> impl Filter<Extract = (T,), Error = Rejection> + Clone {
@@ -486,10 +775,13 @@ fn with_arg<T: Clone + Send>(arg: T) -> impl Filter<Extract = (T,), Error = Infa
     warp::any().map(move || arg.clone())
 }
 
-/// Reports node readiness to chitchat cluster every 10 seconds (25 ms for tests).
+/// Checks the node dependencies and reports node readiness to chitchat cluster every 10 seconds
+/// (25 ms for tests). The node is ready for the cluster as soon as the metastore and the storage
+/// are available; the full report is published for the readiness probe.
 async fn node_readiness_reporting_task(
     cluster: Cluster,
-    metastore: Arc<dyn Metastore>,
+    readiness_checker: ReadinessChecker,
+    readiness_report_tx: watch::Sender<ReadinessReport>,
     grpc_readiness_signal_rx: oneshot::Receiver<()>,
     rest_readiness_signal_rx: oneshot::Receiver<()>,
 ) {
@@ -510,18 +802,76 @@ async fn node_readiness_reporting_task(
     loop {
         interval.tick().await;
 
-        let node_ready = match metastore.check_connectivity().await {
-            Ok(()) => {
-                debug!(metastore_uri=%metastore.uri(), "Metastore service is available.");
-                true
-            }
-            Err(error) => {
-                warn!(metastore_uri=%metastore.uri(), error=?error, "Metastore service is unavailable.");
-                false
-            }
+        let readiness_report = readiness_checker.check().await;
+        let node_ready = readiness_report.can_serve_requests();
+        if node_ready {
+            debug!("Node dependencies are available.");
+        }
+        // A node being decommissioned stays out of the set of ready nodes so that it does not
+        // receive new work.
+        let is_active = cluster.self_node_decommission_status().await == DecommissionStatus::Active;
+        cluster
+            .set_self_node_readiness(node_ready && is_active)
+            .await;
+        readiness_report_tx.send_replace(readiness_report);
+    }
+}
+
+/// Decommissions the node once a node of the cluster requests it. The node leaves the set of ready
+/// nodes so that the control plane moves its indexing pipelines to the other indexers and the root
+/// searchers stop sending it leaf search jobs. It then drains its indexing pipelines and reports
+/// that it can be terminated safely once its ongoing work is complete.
+async fn node_decommission_task(
+    cluster: Cluster,
+    indexing_service_opt: Option<Mailbox<IndexingService>>,
+    searcher_context: Arc<SearcherContext>,
+) {
+    let mut interval = tokio::time::interval(DECOMMISSION_CHECK_INTERVAL);
+
+    while !cluster.is_self_node_decommission_requested().await {
+        interval.tick().await;
+    }
+    info!("Decommissioning node.");
+    cluster
+        .set_self_node_decommission_status(DecommissionStatus::Decommissioning)
+        .await;
+    cluster.set_self_node_readiness(false).await;
+
+    if let Some(indexing_service) = &indexing_service_opt {
+        if let Err(error) = indexing_service.ask(Drain).await {
+            error!(error=?error, "Failed to drain indexing pipelines.");
+        }
+    }
+    loop {
+        interval.tick().await;
+
+        let num_running_pipelines = match &indexing_service_opt {
+            Some(indexing_service) => match indexing_service.ask(Observe).await {
+                Ok(counters) => counters.num_running_pipelines,
+                Err(error) => {
+                    warn!(error=?error, "Failed to observe indexing service.");
+                    continue;
+                }
+            },
+            None => 0,
         };
-        cluster.set_self_node_readiness(node_ready).await;
+        let num_inflight_leaf_searches = searcher_context
+            .searcher_load_tracker
+            .num_inflight_leaf_searches();
+
+        if num_running_pipelines == 0 && num_inflight_leaf_searches == 0 {
+            break;
+        }
+        debug!(
+            num_running_pipelines=%num_running_pipelines,
+            num_inflight_leaf_searches=%num_inflight_leaf_searches,
+            "Waiting for ongoing work to complete before decommissioning node."
+        );
     }
+    cluster
+        .set_self_node_decommission_status(DecommissionStatus::Decommissioned)
+        .await;
+    info!("Node decommissioned, it can be terminated safely.");
 }
 
 /// Displays some warnings if the cluster runs a file-backed metastore or serves file-backed
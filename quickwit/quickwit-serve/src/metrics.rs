@@ -22,6 +22,9 @@ use quickwit_common::metrics::{new_counter, IntCounter};
 
 pub struct RestMetrics {
     pub http_requests_total: IntCounter,
+    pub http_auth_failures_total: IntCounter,
+    pub audit_records_dropped_total: IntCounter,
+    pub rate_limited_requests_total: IntCounter,
 }
 
 impl Default for RestMetrics {
@@ -32,6 +35,22 @@ impl Default for RestMetrics {
                 "Total number of HTTP requests received",
                 "quickwit",
             ),
+            http_auth_failures_total: new_counter(
+                "http_auth_failures_total",
+                "Total number of HTTP requests rejected for missing, invalid, or insufficient \
+                 credentials",
+                "quickwit",
+            ),
+            audit_records_dropped_total: new_counter(
+                "audit_records_dropped_total",
+                "Total number of audit records dropped because the audit log could not keep up",
+                "quickwit",
+            ),
+            rate_limited_requests_total: new_counter(
+                "rate_limited_requests_total",
+                "Total number of requests rejected for exceeding a rate limit",
+                "quickwit",
+            ),
         }
     }
 }
@@ -26,6 +26,12 @@ use warp::{Filter, Rejection};
 
 use crate::{with_arg, BuildInfo, RuntimeInfo};
 
+const REDACTED: &str = "***redacted***";
+
+#[derive(utoipa::OpenApi)]
+#[openapi(paths(get_version, get_config))]
+pub struct NodeInfoApi;
+
 pub fn node_info_handler(
     build_info: &'static BuildInfo,
     runtime_info: &'static RuntimeInfo,
@@ -45,6 +51,15 @@ fn node_version_handler(
         .then(get_version)
 }
 
+#[utoipa::path(
+    get,
+    tag = "Node Info",
+    path = "/version",
+    responses(
+        (status = 200, description = "Successfully fetched the build and runtime information of the node.")
+    )
+)]
+/// Gets the build and runtime information of the node.
 async fn get_version(
     build_info: &'static BuildInfo,
     runtime_info: &'static RuntimeInfo,
@@ -64,17 +79,40 @@ fn node_config_handler(
         .then(get_config)
 }
 
+#[utoipa::path(
+    get,
+    tag = "Node Info",
+    path = "/config",
+    responses(
+        (status = 200, description = "Successfully fetched the configuration of the node.")
+    )
+)]
+/// Gets the configuration of the node, with the metastore credentials and the authentication
+/// secrets redacted.
 async fn get_config(config: Arc<QuickwitConfig>) -> impl warp::Reply {
     // We need to hide sensitive information from metastore URI.
     let mut config_to_serialize = (*config).clone();
     let redacted_uri = Uri::from_well_formed(config_to_serialize.metastore_uri.as_redacted_str());
     config_to_serialize.metastore_uri = redacted_uri;
+
+    for api_key in &mut config_to_serialize.auth_config.api_keys {
+        api_key.key = REDACTED.to_string();
+    }
+    if config_to_serialize.auth_config.cluster_key.is_some() {
+        config_to_serialize.auth_config.cluster_key = Some(REDACTED.to_string());
+    }
+    if let Some(jwt_config) = &mut config_to_serialize.auth_config.jwt {
+        if jwt_config.hmac_secret.is_some() {
+            jwt_config.hmac_secret = Some(REDACTED.to_string());
+        }
+    }
     warp::reply::json(&config_to_serialize)
 }
 
 #[cfg(test)]
 mod tests {
     use assert_json_diff::assert_json_include;
+    use quickwit_config::ApiKeyConfig;
     use serde_json::Value as JsonValue;
 
     use super::*;
@@ -86,6 +124,13 @@ mod tests {
         let runtime_info = RuntimeInfo::get();
         let mut config = QuickwitConfig::for_test();
         config.metastore_uri = Uri::for_test("postgresql://username:password@db");
+        config.auth_config.api_keys.push(ApiKeyConfig {
+            name: "grafana".to_string(),
+            key: "grafana-0123456789abcdef".to_string(),
+            roles: vec!["search".parse().unwrap()],
+            tenant_id: None,
+        });
+        config.auth_config.cluster_key = Some("cluster-0123456789abcdef".to_string());
         let handler = node_info_handler(build_info, runtime_info, Arc::new(config.clone()))
             .recover(recover_fn);
         let resp = warp::test::request().path("/version").reply(&handler).await;
@@ -110,6 +155,10 @@ mod tests {
         let expected_response_json = serde_json::json!({
             "node_id": config.node_id,
             "metastore_uri": "postgresql://username:***redacted***@db",
+            "auth_config": {
+                "api_keys": [{"name": "grafana", "key": "***redacted***", "roles": ["search"]}],
+                "cluster_key": "***redacted***",
+            },
         });
         assert_json_include!(actual: resp_json, expected: expected_response_json);
     }
@@ -28,13 +28,18 @@ use quickwit_metastore::MetastoreApiSchemas;
 use utoipa::openapi::Tag;
 use utoipa::OpenApi;
 
+use crate::admin_api::AdminApi;
 use crate::cluster_api::ClusterApi;
 use crate::delete_task_api::DeleteTaskApi;
+use crate::elastic_search_api::ElasticCompatibleApi;
 use crate::health_check_api::HealthCheckApi;
 use crate::index_api::IndexApi;
 use crate::indexing_api::IndexingApi;
-use crate::ingest_api::{IngestApi, IngestApiSchemas};
+use crate::ingest_api::{IngestApi, IngestApiSchemas, TailStreamApi};
+use crate::node_info_handler::NodeInfoApi;
+use crate::prometheus_api::PrometheusApi;
 use crate::search_api::SearchApi;
+use crate::ui_api::UiApi;
 
 /// Builds the OpenApi docs structure using the registered/merged docs.
 pub fn build_docs() -> utoipa::openapi::OpenApi {
@@ -69,20 +74,31 @@ pub fn build_docs() -> utoipa::openapi::OpenApi {
         Tag::new("Sources"),
         Tag::new("Get Metrics"),
         Tag::new("Cluster Info"),
+        Tag::new("Node Info"),
         Tag::new("Indexing"),
         Tag::new("Splits"),
+        Tag::new("Admin"),
+        Tag::new("UI"),
     ];
     docs_base.tags = Some(tags);
 
     // Routing
     docs_base.merge_components_and_paths(HealthCheckApi::openapi().with_path_prefix("/health"));
     docs_base.merge_components_and_paths(MetricsApi::openapi().with_path_prefix("/metrics"));
+    docs_base.merge_components_and_paths(AdminApi::openapi().with_path_prefix("/api/v1"));
     docs_base.merge_components_and_paths(ClusterApi::openapi().with_path_prefix("/api/v1"));
     docs_base.merge_components_and_paths(DeleteTaskApi::openapi().with_path_prefix("/api/v1"));
     docs_base.merge_components_and_paths(IndexApi::openapi().with_path_prefix("/api/v1"));
     docs_base.merge_components_and_paths(IndexingApi::openapi().with_path_prefix("/api/v1"));
     docs_base.merge_components_and_paths(IngestApi::openapi().with_path_prefix("/api/v1"));
+    docs_base.merge_components_and_paths(TailStreamApi::openapi().with_path_prefix("/api/v1"));
     docs_base.merge_components_and_paths(SearchApi::openapi().with_path_prefix("/api/v1"));
+    docs_base.merge_components_and_paths(NodeInfoApi::openapi().with_path_prefix("/api/v1"));
+    docs_base.merge_components_and_paths(PrometheusApi::openapi().with_path_prefix("/api/v1"));
+    docs_base.merge_components_and_paths(UiApi::openapi().with_path_prefix("/api/v1"));
+    docs_base.merge_components_and_paths(
+        ElasticCompatibleApi::openapi().with_path_prefix("/api/v1/_elastic"),
+    );
 
     // Schemas
     docs_base.merge_components_and_paths(MetastoreApiSchemas::openapi());
@@ -158,6 +174,44 @@ mod openapi_schema_tests {
 
     use super::*;
 
+    #[test]
+    fn test_docs_cover_rest_api() {
+        let docs = build_docs();
+        let expected_paths = [
+            "/api/v1/{index_id}/search",
+            "/api/v1/{index_id}/search/stream/sse",
+            "/api/v1/{index_id}/ingest",
+            "/api/v1/{index_id}/tail/stream",
+            "/api/v1/_elastic/{index}/_search",
+            "/api/v1/_elastic/_bulk",
+            "/api/v1/indexes",
+            "/api/v1/indexes/{index_id}",
+            "/api/v1/indexes/{index_id}/sources",
+            "/api/v1/indexes/{index_id}/sources/{source_id}",
+            "/api/v1/indexes/{index_id}/sources/{source_id}/toggle",
+            "/api/v1/cluster",
+            "/api/v1/cluster/nodes",
+            "/api/v1/cluster/nodes/{node_id}/decommission",
+            "/api/v1/cluster/indexing-tasks",
+            "/api/v1/cluster/indexer-autoscaling",
+            "/api/v1/admin/settings",
+            "/api/v1/admin/log-level",
+            "/api/v1/version",
+            "/api/v1/{index_id}/prometheus/api/v1/query_range",
+            "/api/v1/ui/indexes",
+            "/api/v1/ui/indexes/{index_id}/split-timeline",
+            "/health/livez",
+        ];
+        for expected_path in expected_paths {
+            assert!(
+                docs.paths.paths.contains_key(expected_path),
+                "Path `{expected_path}` is missing from the OpenAPI docs."
+            );
+        }
+        let sources_path = &docs.paths.paths["/api/v1/indexes/{index_id}/sources"];
+        assert_eq!(sources_path.operations.len(), 2);
+    }
+
     #[test]
     fn ensure_schemas_resolve() {
         let docs = build_docs();
@@ -0,0 +1,644 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::io::Read;
+
+use bytes::Bytes;
+use flate2::read::GzDecoder;
+use hyper::header::{CONTENT_ENCODING, CONTENT_TYPE, RETRY_AFTER};
+use hyper::http::{HeaderValue, StatusCode};
+use prost::Message;
+use quickwit_opentelemetry::otlp::{
+    extract_retry_after, parse_otlp_json_logs_request, parse_otlp_json_trace_request,
+    OtlpGrpcLogsService, OtlpGrpcTraceService, OtlpProtocol, OTLP_INDEX_HEADER,
+};
+use quickwit_proto::opentelemetry::proto::collector::logs::v1::{
+    ExportLogsServiceRequest, ExportLogsServiceResponse,
+};
+use quickwit_proto::opentelemetry::proto::collector::trace::v1::{
+    ExportTraceServiceRequest, ExportTraceServiceResponse,
+};
+use quickwit_proto::tonic;
+use serde_json::{json, Value as JsonValue};
+use thiserror::Error;
+use warp::reply::Response;
+use warp::{Filter, Rejection};
+
+use crate::require;
+
+const CONTENT_LENGTH_LIMIT: u64 = 10 * 1024 * 1024; // 10MiB
+
+const PROTOBUF_CONTENT_TYPE: &str = "application/x-protobuf";
+const JSON_CONTENT_TYPE: &str = "application/json";
+
+/// Encoding of the payload of an OTLP HTTP request, given by its `Content-Type` header. The
+/// response is encoded the same way.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum OtlpHttpEncoding {
+    Protobuf,
+    Json,
+}
+
+impl OtlpHttpEncoding {
+    fn from_content_type(content_type_opt: Option<&str>) -> Option<Self> {
+        // The OTLP HTTP specification mandates the `Content-Type` header, but Protobuf is the
+        // default encoding of the protocol.
+        let content_type = match content_type_opt {
+            Some(content_type) => content_type,
+            None => return Some(OtlpHttpEncoding::Protobuf),
+        };
+        let mime_type = content_type.split(';').next().unwrap_or_default().trim();
+
+        match mime_type {
+            "application/x-protobuf" | "application/protobuf" => Some(OtlpHttpEncoding::Protobuf),
+            "application/json" => Some(OtlpHttpEncoding::Json),
+            _ => None,
+        }
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            OtlpHttpEncoding::Protobuf => PROTOBUF_CONTENT_TYPE,
+            OtlpHttpEncoding::Json => JSON_CONTENT_TYPE,
+        }
+    }
+
+    fn protocol(&self) -> OtlpProtocol {
+        match self {
+            OtlpHttpEncoding::Protobuf => OtlpProtocol::HttpProtobuf,
+            OtlpHttpEncoding::Json => OtlpProtocol::HttpJson,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+enum OtlpHttpError {
+    #[error(
+        "Unsupported content-type header. Choices are application/x-protobuf and application/json."
+    )]
+    UnsupportedContentType,
+    #[error("Unsupported content-encoding header. Choices are gzip and identity.")]
+    UnsupportedContentEncoding,
+    #[error("Failed to decompress payload: {0}.")]
+    Decompression(String),
+    #[error("Payload exceeds the limit of {0} bytes.")]
+    PayloadTooLarge(u64),
+    #[error("Failed to decode payload: {0}")]
+    InvalidPayload(String),
+    #[error("{}", .0.message())]
+    Export(tonic::Status),
+}
+
+impl OtlpHttpError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            OtlpHttpError::UnsupportedContentType | OtlpHttpError::UnsupportedContentEncoding => {
+                StatusCode::UNSUPPORTED_MEDIA_TYPE
+            }
+            OtlpHttpError::Decompression(_) | OtlpHttpError::InvalidPayload(_) => {
+                StatusCode::BAD_REQUEST
+            }
+            OtlpHttpError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            OtlpHttpError::Export(status) => match status.code() {
+                tonic::Code::InvalidArgument => StatusCode::BAD_REQUEST,
+                tonic::Code::NotFound => StatusCode::NOT_FOUND,
+                // Only throttled requests carry a retry delay, the others exceed a limit of the
+                // OTLP services and must not be retried.
+                tonic::Code::ResourceExhausted if extract_retry_after(status).is_some() => {
+                    StatusCode::TOO_MANY_REQUESTS
+                }
+                tonic::Code::ResourceExhausted => StatusCode::PAYLOAD_TOO_LARGE,
+                tonic::Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            },
+        }
+    }
+
+    fn grpc_code(&self) -> tonic::Code {
+        match self {
+            OtlpHttpError::Export(status) => status.code(),
+            OtlpHttpError::UnsupportedContentType | OtlpHttpError::UnsupportedContentEncoding => {
+                tonic::Code::Unimplemented
+            }
+            OtlpHttpError::Decompression(_) | OtlpHttpError::InvalidPayload(_) => {
+                tonic::Code::InvalidArgument
+            }
+            OtlpHttpError::PayloadTooLarge(_) => tonic::Code::ResourceExhausted,
+        }
+    }
+
+    fn retry_after_secs(&self) -> Option<u64> {
+        match self {
+            OtlpHttpError::Export(status) => {
+                extract_retry_after(status).map(|retry_after| retry_after.as_secs())
+            }
+            _ => None,
+        }
+    }
+}
+
+/// `google.rpc.Status` message returned on failure, as required by the OTLP HTTP specification.
+#[derive(Clone, PartialEq, Message)]
+struct RpcStatus {
+    #[prost(int32, tag = "1")]
+    code: i32,
+    #[prost(string, tag = "2")]
+    message: String,
+}
+
+/// Exposes the OTLP HTTP endpoints for logs (`/v1/logs`) and traces (`/v1/traces`), accepting
+/// both Protobuf and JSON payloads, optionally gzip-compressed.
+pub(crate) fn otlp_api_handlers(
+    otlp_logs_service_opt: Option<OtlpGrpcLogsService>,
+    otlp_trace_service_opt: Option<OtlpGrpcTraceService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    otlp_logs_handler(otlp_logs_service_opt).or(otlp_trace_handler(otlp_trace_service_opt))
+}
+
+fn otlp_request_filter(
+) -> impl Filter<Extract = (Option<String>, Option<String>, Option<String>, Bytes), Error = Rejection>
+       + Clone {
+    warp::post()
+        .and(warp::header::optional::<String>(CONTENT_TYPE.as_str()))
+        .and(warp::header::optional::<String>(CONTENT_ENCODING.as_str()))
+        .and(warp::header::optional::<String>(OTLP_INDEX_HEADER))
+        .and(warp::body::content_length_limit(CONTENT_LENGTH_LIMIT))
+        .and(warp::body::bytes())
+}
+
+fn otlp_logs_handler(
+    otlp_logs_service_opt: Option<OtlpGrpcLogsService>,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    warp::path!("v1" / "logs")
+        .and(require(otlp_logs_service_opt))
+        .and(otlp_request_filter())
+        .then(otlp_ingest_logs)
+}
+
+fn otlp_trace_handler(
+    otlp_trace_service_opt: Option<OtlpGrpcTraceService>,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    warp::path!("v1" / "traces")
+        .and(require(otlp_trace_service_opt))
+        .and(otlp_request_filter())
+        .then(otlp_ingest_traces)
+}
+
+async fn otlp_ingest_logs(
+    otlp_logs_service: OtlpGrpcLogsService,
+    content_type_opt: Option<String>,
+    content_encoding_opt: Option<String>,
+    header_index_id_opt: Option<String>,
+    body: Bytes,
+) -> Response {
+    let encoding = match OtlpHttpEncoding::from_content_type(content_type_opt.as_deref()) {
+        Some(encoding) => encoding,
+        None => {
+            return make_error_response(
+                OtlpHttpEncoding::Json,
+                OtlpHttpError::UnsupportedContentType,
+            )
+        }
+    };
+    let export_result: Result<ExportLogsServiceResponse, OtlpHttpError> = async {
+        let payload = decode_content_encoding(
+            content_encoding_opt.as_deref(),
+            body,
+            otlp_logs_service.max_payload_size(),
+        )?;
+        let request = match encoding {
+            OtlpHttpEncoding::Protobuf => ExportLogsServiceRequest::decode(payload)
+                .map_err(|error| OtlpHttpError::InvalidPayload(error.to_string()))?,
+            OtlpHttpEncoding::Json => parse_otlp_json_logs_request(&payload)
+                .map_err(|error| OtlpHttpError::InvalidPayload(error.to_string()))?,
+        };
+        otlp_logs_service
+            .export_with_protocol(
+                request,
+                encoding.protocol(),
+                header_index_id_opt.filter(|index_id| !index_id.is_empty()),
+            )
+            .await
+            .map_err(OtlpHttpError::Export)
+    }
+    .await;
+
+    match export_result {
+        Ok(response) => make_response(encoding, &response, logs_response_to_json),
+        Err(error) => make_error_response(encoding, error),
+    }
+}
+
+async fn otlp_ingest_traces(
+    otlp_trace_service: OtlpGrpcTraceService,
+    content_type_opt: Option<String>,
+    content_encoding_opt: Option<String>,
+    header_index_id_opt: Option<String>,
+    body: Bytes,
+) -> Response {
+    let encoding = match OtlpHttpEncoding::from_content_type(content_type_opt.as_deref()) {
+        Some(encoding) => encoding,
+        None => {
+            return make_error_response(
+                OtlpHttpEncoding::Json,
+                OtlpHttpError::UnsupportedContentType,
+            )
+        }
+    };
+    let export_result: Result<ExportTraceServiceResponse, OtlpHttpError> = async {
+        let payload = decode_content_encoding(
+            content_encoding_opt.as_deref(),
+            body,
+            otlp_trace_service.max_payload_size(),
+        )?;
+        let request = match encoding {
+            OtlpHttpEncoding::Protobuf => ExportTraceServiceRequest::decode(payload)
+                .map_err(|error| OtlpHttpError::InvalidPayload(error.to_string()))?,
+            OtlpHttpEncoding::Json => parse_otlp_json_trace_request(&payload)
+                .map_err(|error| OtlpHttpError::InvalidPayload(error.to_string()))?,
+        };
+        otlp_trace_service
+            .export_with_protocol(
+                request,
+                encoding.protocol(),
+                header_index_id_opt.filter(|index_id| !index_id.is_empty()),
+            )
+            .await
+            .map_err(OtlpHttpError::Export)
+    }
+    .await;
+
+    match export_result {
+        Ok(response) => make_response(encoding, &response, trace_response_to_json),
+        Err(error) => make_error_response(encoding, error),
+    }
+}
+
+/// Decompresses the payload, which must not exceed `max_payload_size` bytes once decompressed.
+fn decode_content_encoding(
+    content_encoding_opt: Option<&str>,
+    body: Bytes,
+    max_payload_size: u64,
+) -> Result<Bytes, OtlpHttpError> {
+    let payload = match content_encoding_opt.map(str::trim) {
+        None | Some("identity") => body,
+        Some("gzip") => {
+            let mut decompressed = Vec::new();
+            GzDecoder::new(&body[..])
+                .take(max_payload_size + 1)
+                .read_to_end(&mut decompressed)
+                .map_err(|error| OtlpHttpError::Decompression(error.to_string()))?;
+            Bytes::from(decompressed)
+        }
+        Some(_) => return Err(OtlpHttpError::UnsupportedContentEncoding),
+    };
+    if payload.len() as u64 > max_payload_size {
+        return Err(OtlpHttpError::PayloadTooLarge(max_payload_size));
+    }
+    Ok(payload)
+}
+
+// The OTLP/JSON encoding uses lowerCamelCase field names, which the serde implementations derived
+// on the Protobuf types do not follow.
+fn logs_response_to_json(response: &ExportLogsServiceResponse) -> JsonValue {
+    match &response.partial_success {
+        Some(partial_success) => json!({
+            "partialSuccess": {
+                "rejectedLogRecords": partial_success.rejected_log_records,
+                "errorMessage": partial_success.error_message,
+            }
+        }),
+        None => json!({}),
+    }
+}
+
+fn trace_response_to_json(response: &ExportTraceServiceResponse) -> JsonValue {
+    match &response.partial_success {
+        Some(partial_success) => json!({
+            "partialSuccess": {
+                "rejectedSpans": partial_success.rejected_spans,
+                "errorMessage": partial_success.error_message,
+            }
+        }),
+        None => json!({}),
+    }
+}
+
+fn make_response<M: Message>(
+    encoding: OtlpHttpEncoding,
+    message: &M,
+    to_json: fn(&M) -> JsonValue,
+) -> Response {
+    let body = match encoding {
+        OtlpHttpEncoding::Protobuf => message.encode_to_vec(),
+        OtlpHttpEncoding::Json => to_json(message).to_string().into_bytes(),
+    };
+    build_response(encoding, StatusCode::OK, body)
+}
+
+fn make_error_response(encoding: OtlpHttpEncoding, error: OtlpHttpError) -> Response {
+    let retry_after_secs_opt = error.retry_after_secs();
+    let rpc_status = RpcStatus {
+        code: error.grpc_code() as i32,
+        message: error.to_string(),
+    };
+    let body = match encoding {
+        OtlpHttpEncoding::Protobuf => rpc_status.encode_to_vec(),
+        OtlpHttpEncoding::Json => json!({
+            "code": rpc_status.code,
+            "message": rpc_status.message,
+        })
+        .to_string()
+        .into_bytes(),
+    };
+    let mut response = build_response(encoding, error.status_code(), body);
+
+    if let Some(retry_after_secs) = retry_after_secs_opt {
+        response
+            .headers_mut()
+            .insert(RETRY_AFTER, HeaderValue::from(retry_after_secs));
+    }
+    response
+}
+
+fn build_response(encoding: OtlpHttpEncoding, status_code: StatusCode, body: Vec<u8>) -> Response {
+    let mut response = Response::new(body.into());
+    response.headers_mut().insert(
+        CONTENT_TYPE,
+        HeaderValue::from_static(encoding.content_type()),
+    );
+    *response.status_mut() = status_code;
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::num::NonZeroUsize;
+
+    use byte_unit::Byte;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use quickwit_config::{IngestApiConfig, OtlpLimitsConfig};
+    use quickwit_opentelemetry::otlp::{OTEL_LOGS_INDEX_ID, OTEL_TRACE_INDEX_ID};
+    use quickwit_proto::opentelemetry::proto::trace::v1::{ResourceSpans, ScopeSpans, Span};
+
+    use super::*;
+    use crate::ingest_api::setup_ingest_service;
+
+    #[test]
+    fn test_otlp_http_encoding_from_content_type() {
+        assert_eq!(
+            OtlpHttpEncoding::from_content_type(None),
+            Some(OtlpHttpEncoding::Protobuf)
+        );
+        assert_eq!(
+            OtlpHttpEncoding::from_content_type(Some("application/x-protobuf")),
+            Some(OtlpHttpEncoding::Protobuf)
+        );
+        assert_eq!(
+            OtlpHttpEncoding::from_content_type(Some("application/json; charset=utf-8")),
+            Some(OtlpHttpEncoding::Json)
+        );
+        assert_eq!(
+            OtlpHttpEncoding::from_content_type(Some("text/plain")),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_otlp_http_logs_json() {
+        let (universe, _temp_dir, ingest_service, _) =
+            setup_ingest_service(&[OTEL_LOGS_INDEX_ID], &IngestApiConfig::default()).await;
+        let otlp_api_handlers = otlp_api_handlers(
+            Some(OtlpGrpcLogsService::new(ingest_service.clone())),
+            Some(OtlpGrpcTraceService::new(ingest_service)),
+        );
+        let payload = r#"{
+            "resourceLogs": [{
+                "scopeLogs": [{
+                    "logRecords": [{
+                        "timeUnixNano": "1544712660300000000",
+                        "severityText": "INFO",
+                        "body": {"stringValue": "Example log record"}
+                    }]
+                }]
+            }]
+        }"#;
+        let resp = warp::test::request()
+            .path("/v1/logs")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(payload)
+            .reply(&otlp_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.headers()["content-type"], "application/json");
+        let response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            response_json["partialSuccess"]["rejectedLogRecords"],
+            json!(0)
+        );
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_otlp_http_traces_gzip_protobuf() {
+        let (universe, _temp_dir, ingest_service, _) =
+            setup_ingest_service(&[OTEL_TRACE_INDEX_ID], &IngestApiConfig::default()).await;
+        let otlp_api_handlers = otlp_api_handlers(
+            Some(OtlpGrpcLogsService::new(ingest_service.clone())),
+            Some(OtlpGrpcTraceService::new(ingest_service)),
+        );
+        let request = ExportTraceServiceRequest {
+            resource_spans: vec![ResourceSpans {
+                resource: None,
+                scope_spans: vec![ScopeSpans {
+                    scope: None,
+                    spans: vec![Span {
+                        trace_id: vec![1; 16],
+                        span_id: vec![2; 8],
+                        name: "my-span".to_string(),
+                        start_time_unix_nano: 1_000_000_000,
+                        end_time_unix_nano: 2_000_000_000,
+                        ..Default::default()
+                    }],
+                    schema_url: "".to_string(),
+                }],
+                schema_url: "".to_string(),
+            }],
+        };
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&request.encode_to_vec()).unwrap();
+        let payload = encoder.finish().unwrap();
+
+        let resp = warp::test::request()
+            .path("/v1/traces")
+            .method("POST")
+            .header("content-type", "application/x-protobuf")
+            .header("content-encoding", "gzip")
+            .body(payload)
+            .reply(&otlp_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        assert_eq!(resp.headers()["content-type"], "application/x-protobuf");
+        let response = ExportTraceServiceResponse::decode(resp.body().clone()).unwrap();
+        assert_eq!(response.partial_success.unwrap().rejected_spans, 0);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_otlp_http_logs_index_header() {
+        let (universe, _temp_dir, ingest_service, _) =
+            setup_ingest_service(&["my-logs"], &IngestApiConfig::default()).await;
+        let otlp_api_handlers = otlp_api_handlers(
+            Some(OtlpGrpcLogsService::new(ingest_service.clone())),
+            Some(OtlpGrpcTraceService::new(ingest_service)),
+        );
+        let payload =
+            r#"{"resourceLogs": [{"scopeLogs": [{"logRecords": [{"severityText": "INFO"}]}]}]}"#;
+
+        // The `otel-logs-v0` index does not exist.
+        let resp = warp::test::request()
+            .path("/v1/logs")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(payload)
+            .reply(&otlp_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        let resp = warp::test::request()
+            .path("/v1/logs")
+            .method("POST")
+            .header("content-type", "application/json")
+            .header("x-qw-index", "my-logs")
+            .body(payload)
+            .reply(&otlp_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_otlp_http_limits() {
+        let (universe, _temp_dir, ingest_service, _) =
+            setup_ingest_service(&[OTEL_TRACE_INDEX_ID], &IngestApiConfig::default()).await;
+        let limits = OtlpLimitsConfig {
+            max_payload_size: Byte::from_bytes(256),
+            max_spans_per_request: NonZeroUsize::new(1).unwrap(),
+            ..Default::default()
+        };
+        let otlp_api_handlers = otlp_api_handlers(
+            None,
+            Some(OtlpGrpcTraceService::new(ingest_service).with_limits(limits)),
+        );
+        let span = json!({
+            "traceId": "0102030405060708090a0b0c0d0e0f10",
+            "spanId": "0102030405060708",
+            "name": "my-span",
+        });
+        let payload = json!({
+            "resourceSpans": [{
+                "scopeSpans": [{
+                    "spans": [span, span]
+                }]
+            }]
+        });
+        let resp = warp::test::request()
+            .path("/v1/traces")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(payload.to_string())
+            .reply(&otlp_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 413);
+        assert!(resp.headers().get("retry-after").is_none());
+        let response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            response_json["code"],
+            json!(tonic::Code::ResourceExhausted as i32)
+        );
+
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&[b' '; 1_000]).unwrap();
+        let payload = encoder.finish().unwrap();
+
+        let resp = warp::test::request()
+            .path("/v1/traces")
+            .method("POST")
+            .header("content-type", "application/json")
+            .header("content-encoding", "gzip")
+            .body(payload)
+            .reply(&otlp_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 413);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_otlp_http_errors() {
+        let (universe, _temp_dir, ingest_service, _) =
+            setup_ingest_service(&[OTEL_TRACE_INDEX_ID], &IngestApiConfig::default()).await;
+        let otlp_api_handlers =
+            otlp_api_handlers(None, Some(OtlpGrpcTraceService::new(ingest_service)));
+        let resp = warp::test::request()
+            .path("/v1/logs")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body("{}")
+            .reply(&otlp_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        let resp = warp::test::request()
+            .path("/v1/traces")
+            .method("POST")
+            .header("content-type", "text/plain")
+            .body("{}")
+            .reply(&otlp_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 415);
+
+        let resp = warp::test::request()
+            .path("/v1/traces")
+            .method("POST")
+            .header("content-type", "application/json")
+            .header("content-encoding", "br")
+            .body("{}")
+            .reply(&otlp_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 415);
+
+        let resp = warp::test::request()
+            .path("/v1/traces")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(r#"{"resourceSpans": 42}"#)
+            .reply(&otlp_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 400);
+        let response_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(
+            response_json["code"],
+            json!(tonic::Code::InvalidArgument as i32)
+        );
+        universe.assert_quit().await;
+    }
+}
@@ -0,0 +1,292 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use quickwit_metastore::Metastore;
+use quickwit_search::{
+    promql_query, promql_query_range, PromqlData, PromqlResponse, PromqlResultType, PromqlSeries,
+    SearchError, SearchService,
+};
+use serde::Deserialize;
+use tracing::info;
+use warp::{Filter, Rejection};
+
+use crate::json_api_response::make_json_api_response;
+use crate::{with_arg, BodyFormat};
+
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    paths(query_range, query),
+    components(schemas(PromqlResponse, PromqlData, PromqlResultType, PromqlSeries))
+)]
+pub struct PrometheusApi;
+
+/// This struct represents the range query passed to the Prometheus-compatible API.
+#[derive(Debug, Deserialize)]
+struct PromqlQueryRangeQueryString {
+    /// Metrics query.
+    query: String,
+    /// Start of the range, in seconds since epoch or as a RFC 3339 date.
+    start: String,
+    /// End of the range, in seconds since epoch or as a RFC 3339 date.
+    end: String,
+    /// Resolution, in seconds or as a duration such as `1m`.
+    step: String,
+}
+
+/// This struct represents the instant query passed to the Prometheus-compatible API.
+#[derive(Debug, Deserialize)]
+struct PromqlQueryQueryString {
+    /// Metrics query.
+    query: String,
+    /// Evaluation time, in seconds since epoch or as a RFC 3339 date. Defaults to now.
+    time: Option<String>,
+}
+
+/// Exposes the query endpoints of the Prometheus HTTP API for an index under
+/// `/{index_id}/prometheus/api/v1`, so that the index can be configured as a Prometheus
+/// datasource in Grafana. Both endpoints accept `GET` requests and form-encoded `POST` requests.
+pub(crate) fn prometheus_api_handlers(
+    metastore: Arc<dyn Metastore>,
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    prometheus_query_range_filter()
+        .and(with_arg(metastore.clone()))
+        .and(with_arg(search_service.clone()))
+        .then(query_range)
+        .or(prometheus_query_filter()
+            .and(with_arg(metastore))
+            .and(with_arg(search_service))
+            .then(query))
+}
+
+fn prometheus_query_range_filter(
+) -> impl Filter<Extract = (String, PromqlQueryRangeQueryString), Error = Rejection> + Clone {
+    let query_string = warp::get().and(serde_qs::warp::query(serde_qs::Config::default()));
+    let form = warp::post()
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::body::form());
+    warp::path!(String / "prometheus" / "api" / "v1" / "query_range")
+        .and(query_string.or(form).unify())
+}
+
+fn prometheus_query_filter(
+) -> impl Filter<Extract = (String, PromqlQueryQueryString), Error = Rejection> + Clone {
+    let query_string = warp::get().and(serde_qs::warp::query(serde_qs::Config::default()));
+    let form = warp::post()
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::body::form());
+    warp::path!(String / "prometheus" / "api" / "v1" / "query").and(query_string.or(form).unify())
+}
+
+/// Returns the timestamp field of the index, which the metrics queries bucket documents by.
+async fn timestamp_field(index_id: &str, metastore: &dyn Metastore) -> Result<String, SearchError> {
+    let index_metadata = metastore.index_metadata(index_id).await?;
+    index_metadata
+        .into_index_config()
+        .doc_mapping
+        .timestamp_field
+        .ok_or_else(|| {
+            SearchError::InvalidArgument(format!(
+                "Index `{index_id}` has no timestamp field and cannot be queried as metrics."
+            ))
+        })
+}
+
+#[utoipa::path(
+    get,
+    tag = "Search",
+    path = "/{index_id}/prometheus/api/v1/query_range",
+    responses(
+        (status = 200, description = "Successfully evaluated the metrics query.", body = PromqlResponse)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to query."),
+        ("query" = String, Query, description = "The metrics query."),
+        ("start" = String, Query, description = "Start of the range, in seconds since epoch or as a RFC 3339 date."),
+        ("end" = String, Query, description = "End of the range, in seconds since epoch or as a RFC 3339 date."),
+        ("step" = String, Query, description = "Resolution, in seconds or as a duration such as `1m`."),
+    )
+)]
+/// Metrics Range Query
+///
+/// Evaluates a metrics query over a time range, following the Prometheus `query_range` API.
+async fn query_range(
+    index_id: String,
+    query_string: PromqlQueryRangeQueryString,
+    metastore: Arc<dyn Metastore>,
+    search_service: Arc<dyn SearchService>,
+) -> impl warp::Reply {
+    info!(index_id = %index_id, request =? query_string, "promql-query-range");
+    let result = match timestamp_field(&index_id, &*metastore).await {
+        Ok(timestamp_field) => {
+            promql_query_range(
+                &index_id,
+                &timestamp_field,
+                &query_string.query,
+                &query_string.start,
+                &query_string.end,
+                &query_string.step,
+                &*search_service,
+            )
+            .await
+        }
+        Err(error) => Err(error),
+    };
+    make_json_api_response(result, BodyFormat::default())
+}
+
+#[utoipa::path(
+    get,
+    tag = "Search",
+    path = "/{index_id}/prometheus/api/v1/query",
+    responses(
+        (status = 200, description = "Successfully evaluated the metrics query.", body = PromqlResponse)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to query."),
+        ("query" = String, Query, description = "The metrics query."),
+        ("time" = Option<String>, Query, description = "Evaluation time, in seconds since epoch or as a RFC 3339 date. Defaults to now."),
+    )
+)]
+/// Metrics Instant Query
+///
+/// Evaluates a metrics query at a single instant, following the Prometheus `query` API.
+async fn query(
+    index_id: String,
+    query_string: PromqlQueryQueryString,
+    metastore: Arc<dyn Metastore>,
+    search_service: Arc<dyn SearchService>,
+) -> impl warp::Reply {
+    info!(index_id = %index_id, request =? query_string, "promql-query");
+    let result = match timestamp_field(&index_id, &*metastore).await {
+        Ok(timestamp_field) => {
+            promql_query(
+                &index_id,
+                &timestamp_field,
+                &query_string.query,
+                query_string.time.as_deref(),
+                &*search_service,
+            )
+            .await
+        }
+        Err(error) => Err(error),
+    };
+    make_json_api_response(result, BodyFormat::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use quickwit_metastore::{IndexMetadata, MockMetastore};
+    use quickwit_search::MockSearchService;
+    use serde_json::{json, Value as JsonValue};
+
+    use super::*;
+    use crate::recover_fn;
+
+    fn prometheus_handler(
+        mock_metastore: MockMetastore,
+        mock_search_service: MockSearchService,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+        prometheus_api_handlers(Arc::new(mock_metastore), Arc::new(mock_search_service))
+            .recover(recover_fn)
+    }
+
+    fn mock_metastore() -> MockMetastore {
+        let mut mock_metastore = MockMetastore::new();
+        mock_metastore
+            .expect_index_metadata()
+            .returning(|index_id: &str| {
+                Ok(IndexMetadata::for_test(
+                    index_id,
+                    "ram:///indexes/my-metrics",
+                ))
+            });
+        mock_metastore
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_query_range() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .withf(|search_request| {
+                search_request.index_id == "my-metrics"
+                    && search_request.start_timestamp == Some(0)
+                    && search_request.end_timestamp == Some(120)
+            })
+            .returning(|_| {
+                let aggregation = json!({
+                    "histogram": {
+                        "buckets": [
+                            {"key": 0.0, "doc_count": 2, "stats": {"count": 2, "sum": 30.0}},
+                            {"key": 60_000.0, "doc_count": 1, "stats": {"count": 1, "sum": 90.0}}
+                        ]
+                    }
+                });
+                Ok(quickwit_proto::SearchResponse {
+                    num_hits: 3,
+                    aggregation: Some(aggregation.to_string()),
+                    ..Default::default()
+                })
+            });
+        let prometheus_handler = prometheus_handler(mock_metastore(), mock_search_service);
+        let expected_response_json = json!({
+            "status": "success",
+            "data": {
+                "resultType": "matrix",
+                "result": [{"metric": {}, "values": [[60, "0.5"], [120, "1.5"]]}]
+            }
+        });
+        let resp = warp::test::request()
+            .path(
+                "/my-metrics/prometheus/api/v1/query_range?query=rate(response_time%5B1m%5D)&\
+                 start=60&end=120&step=60",
+            )
+            .reply(&prometheus_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json, expected_response_json);
+
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/my-metrics/prometheus/api/v1/query_range")
+            .header("content-type", "application/x-www-form-urlencoded")
+            .body("query=rate%28response_time%5B1m%5D%29&start=60&end=120&step=60")
+            .reply(&prometheus_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(resp_json, expected_response_json);
+    }
+
+    #[tokio::test]
+    async fn test_prometheus_query_invalid_query() {
+        let prometheus_handler = prometheus_handler(mock_metastore(), MockSearchService::new());
+        let resp = warp::test::request()
+            .path("/my-metrics/prometheus/api/v1/query?query=sum(rate(response_time))")
+            .reply(&prometheus_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+        let body = String::from_utf8_lossy(resp.body());
+        assert!(body.contains("Invalid PromQL query: Expected `[`, got `)`."));
+    }
+}
@@ -0,0 +1,583 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use quickwit_config::{AuthRole, RateLimitConfig, RateLimits};
+use quickwit_proto::{ServiceError, ServiceErrorCode};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::auth::{matches_pattern, Access};
+
+/// Number of token buckets or semaphores above which the idle ones are evicted.
+const MAX_IDLE_ENTRIES: usize = 10_000;
+
+/// Delay after which clients are told to retry the searches rejected because too many searches
+/// are already running.
+const CONCURRENT_SEARCHES_RETRY_AFTER: Duration = Duration::from_secs(1);
+
+#[derive(Debug, thiserror::Error)]
+#[error("Rate limit `{limit}` of {target} exceeded, retry in {retry_after:?}.")]
+pub struct RateLimitError {
+    target: LimitTarget,
+    limit: &'static str,
+    retry_after: Duration,
+}
+
+impl RateLimitError {
+    /// Delay after which the request is expected to be accepted.
+    pub fn retry_after(&self) -> Duration {
+        self.retry_after
+    }
+
+    /// Retry delay in whole seconds, as expected by the `Retry-After` header.
+    pub fn retry_after_secs(&self) -> u64 {
+        let retry_after_secs = self.retry_after.as_secs();
+        if self.retry_after.subsec_nanos() > 0 {
+            retry_after_secs + 1
+        } else {
+            retry_after_secs
+        }
+    }
+}
+
+impl ServiceError for RateLimitError {
+    fn status_code(&self) -> ServiceErrorCode {
+        ServiceErrorCode::RateLimited
+    }
+}
+
+/// Principal or index subject to rate limits.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum LimitTarget {
+    Principal(String),
+    Index(String),
+}
+
+impl fmt::Display for LimitTarget {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LimitTarget::Principal(name) => write!(formatter, "principal `{name}`"),
+            LimitTarget::Index(index_id) => write!(formatter, "index `{index_id}`"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Quota {
+    Requests,
+    IngestBytes,
+}
+
+impl Quota {
+    fn limit_name(&self) -> &'static str {
+        match self {
+            Quota::Requests => "requests_per_sec",
+            Quota::IngestBytes => "ingest_bytes_per_sec",
+        }
+    }
+}
+
+/// Bucket refilled continuously at `rate` tokens per second, holding at most one second worth
+/// of tokens. A request costing more than the capacity of the bucket is accepted once the bucket
+/// is full and leaves it in debt, so that large ingest requests are slowed down instead of being
+/// rejected forever.
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: u64, now: Instant) -> Self {
+        Self {
+            rate: rate as f64,
+            tokens: rate as f64,
+            refilled_at: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed_secs = now
+            .saturating_duration_since(self.refilled_at)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed_secs * self.rate).min(self.rate);
+        self.refilled_at = now;
+    }
+
+    /// Returns how long to wait before `cost` tokens can be withdrawn, or `None` if they can be
+    /// withdrawn right away.
+    fn wait_time(&self, cost: u64) -> Option<Duration> {
+        let required_tokens = (cost as f64).min(self.rate);
+
+        if self.tokens >= required_tokens {
+            return None;
+        }
+        Some(Duration::from_secs_f64(
+            (required_tokens - self.tokens) / self.rate,
+        ))
+    }
+
+    fn withdraw(&mut self, cost: u64) {
+        self.tokens -= cost as f64;
+    }
+
+    fn is_full(&self) -> bool {
+        self.tokens >= self.rate
+    }
+}
+
+/// Held for the duration of a request, releases the concurrent search slots taken by the request
+/// when dropped.
+#[derive(Debug, Default)]
+pub(crate) struct RateLimitPermit {
+    _search_permits: Vec<OwnedSemaphorePermit>,
+}
+
+/// Enforces the rate limits of the principals and of the indexes with token buckets, and limits
+/// their number of concurrent searches with semaphores. The limits can be replaced at runtime, when
+/// the cluster settings change.
+pub(crate) struct RateLimiter {
+    config: RwLock<RateLimitConfig>,
+    buckets: Mutex<HashMap<(LimitTarget, Quota), TokenBucket>>,
+    search_semaphores: Mutex<HashMap<LimitTarget, Arc<Semaphore>>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config: RwLock::new(config),
+            buckets: Mutex::default(),
+            search_semaphores: Mutex::default(),
+        }
+    }
+
+    /// Replaces the rate limits. The token buckets and the concurrent search slots start over,
+    /// although the searches running are not interrupted.
+    pub fn set_config(&self, config: RateLimitConfig) {
+        let mut current_config = self.config.write().unwrap();
+
+        if *current_config == config {
+            return;
+        }
+        *current_config = config;
+        self.buckets.lock().unwrap().clear();
+        self.search_semaphores.lock().unwrap().clear();
+    }
+
+    /// Whether a request of the principal `principal_name_opt` requiring `access` is charged the
+    /// bytes it ingests, in which case its size must be known before it is handled.
+    pub fn limits_ingest_bytes(&self, principal_name_opt: Option<&str>, access: &Access) -> bool {
+        let Some((AuthRole::Ingest, index_ids)) = limited_access(access) else {
+            return false;
+        };
+        let config = self.config.read().unwrap();
+
+        config.is_enabled()
+            && limited_targets(&config, principal_name_opt, index_ids)
+                .iter()
+                .any(|(_, limits)| limits.ingest_bytes_per_sec.is_some())
+    }
+
+    /// Charges a request to the principal sending it and to the indexes it targets. The request
+    /// costs `ingest_num_bytes` ingest bytes when it requires the `ingest` role. Rejected requests
+    /// are not charged.
+    pub fn acquire(
+        &self,
+        principal_name_opt: Option<&str>,
+        access: &Access,
+        ingest_num_bytes: u64,
+    ) -> Result<RateLimitPermit, RateLimitError> {
+        let Some((role, index_ids)) = limited_access(access) else {
+            return Ok(RateLimitPermit::default());
+        };
+        let targets = {
+            let config = self.config.read().unwrap();
+
+            if !config.is_enabled() {
+                return Ok(RateLimitPermit::default());
+            }
+            limited_targets(&config, principal_name_opt, index_ids)
+        };
+
+        let search_permits = if role == AuthRole::Search {
+            self.acquire_search_permits(&targets)?
+        } else {
+            Vec::new()
+        };
+        let mut withdrawals: Vec<(LimitTarget, Quota, u64, u64)> = Vec::new();
+
+        for (target, limits) in &targets {
+            if let Some(requests_per_sec) = limits.requests_per_sec {
+                let rate = u64::from(requests_per_sec.get());
+                withdrawals.push((target.clone(), Quota::Requests, rate, 1));
+            }
+            if role == AuthRole::Ingest && ingest_num_bytes > 0 {
+                if let Some(ingest_bytes_per_sec) = limits.ingest_bytes_per_sec {
+                    let rate = ingest_bytes_per_sec.get_bytes();
+                    withdrawals.push((target.clone(), Quota::IngestBytes, rate, ingest_num_bytes));
+                }
+            }
+        }
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+
+        for (target, quota, rate, cost) in &withdrawals {
+            let bucket = buckets
+                .entry((target.clone(), *quota))
+                .or_insert_with(|| TokenBucket::new(*rate, now));
+            bucket.refill(now);
+
+            if let Some(retry_after) = bucket.wait_time(*cost) {
+                return Err(RateLimitError {
+                    target: target.clone(),
+                    limit: quota.limit_name(),
+                    retry_after,
+                });
+            }
+        }
+        for (target, quota, _rate, cost) in withdrawals {
+            if let Some(bucket) = buckets.get_mut(&(target, quota)) {
+                bucket.withdraw(cost);
+            }
+        }
+        if buckets.len() > MAX_IDLE_ENTRIES {
+            buckets.retain(|_, bucket| {
+                bucket.refill(now);
+                !bucket.is_full()
+            });
+        }
+        Ok(RateLimitPermit {
+            _search_permits: search_permits,
+        })
+    }
+
+    fn acquire_search_permits(
+        &self,
+        targets: &[(LimitTarget, RateLimits)],
+    ) -> Result<Vec<OwnedSemaphorePermit>, RateLimitError> {
+        let mut search_semaphores = self.search_semaphores.lock().unwrap();
+        let mut search_permits = Vec::new();
+
+        for (target, limits) in targets {
+            let Some(max_concurrent_searches) = limits.max_concurrent_searches else {
+                continue;
+            };
+            let semaphore = search_semaphores
+                .entry(target.clone())
+                .or_insert_with(|| Arc::new(Semaphore::new(max_concurrent_searches.get())))
+                .clone();
+            // The permits acquired so far are released when returning early.
+            let search_permit = semaphore.try_acquire_owned().map_err(|_| RateLimitError {
+                target: target.clone(),
+                limit: "max_concurrent_searches",
+                retry_after: CONCURRENT_SEARCHES_RETRY_AFTER,
+            })?;
+            search_permits.push(search_permit);
+        }
+        if search_semaphores.len() > MAX_IDLE_ENTRIES {
+            search_semaphores.retain(|_, semaphore| Arc::strong_count(semaphore) > 1);
+        }
+        Ok(search_permits)
+    }
+}
+
+/// Returns the role and the indexes a request requiring `access` is charged for, or `None` if the
+/// request is not rate limited. Internal requests are sent by the other nodes of the cluster on
+/// behalf of requests that were already charged by the node that received them.
+fn limited_access(access: &Access) -> Option<(AuthRole, &[String])> {
+    match access {
+        Access::Public | Access::Internal | Access::Denied => None,
+        Access::Indexes(role, index_ids) => Some((*role, index_ids.as_slice())),
+        Access::Cluster(role) | Access::Handler(role) => Some((*role, &[])),
+    }
+}
+
+/// Returns the principal and the indexes subject to rate limits, along with their limits.
+fn limited_targets(
+    config: &RateLimitConfig,
+    principal_name_opt: Option<&str>,
+    index_ids: &[String],
+) -> Vec<(LimitTarget, RateLimits)> {
+    let mut targets = Vec::new();
+
+    if let Some(principal_name) = principal_name_opt {
+        let limits_opt = config
+            .principals
+            .get(principal_name)
+            .or_else(|| config.principals.get("*"));
+        if let Some(limits) = limits_opt {
+            targets.push((
+                LimitTarget::Principal(principal_name.to_string()),
+                limits.clone(),
+            ));
+        }
+    }
+    for index_id in index_ids {
+        let limits_opt = config
+            .indexes
+            .iter()
+            .filter(|(index_id_pattern, _)| matches_pattern(index_id_pattern, index_id))
+            .max_by_key(|(index_id_pattern, _)| index_id_pattern.len())
+            .map(|(_, limits)| limits);
+        let target = LimitTarget::Index(index_id.clone());
+
+        if let Some(limits) = limits_opt {
+            if !targets
+                .iter()
+                .any(|(other_target, _)| *other_target == target)
+            {
+                targets.push((target, limits.clone()));
+            }
+        }
+    }
+    targets
+}
+
+#[cfg(test)]
+mod tests {
+    use std::num::{NonZeroU32, NonZeroUsize};
+
+    use byte_unit::Byte;
+
+    use super::*;
+
+    fn rate_limiter() -> RateLimiter {
+        let mut config = RateLimitConfig::default();
+        config.principals.insert(
+            "*".to_string(),
+            RateLimits {
+                requests_per_sec: Some(NonZeroU32::new(3).unwrap()),
+                ..Default::default()
+            },
+        );
+        config.principals.insert(
+            "grafana".to_string(),
+            RateLimits {
+                max_concurrent_searches: Some(NonZeroUsize::new(1).unwrap()),
+                ..Default::default()
+            },
+        );
+        config.indexes.insert(
+            "team-*".to_string(),
+            RateLimits {
+                requests_per_sec: Some(NonZeroU32::new(1000).unwrap()),
+                ..Default::default()
+            },
+        );
+        config.indexes.insert(
+            "team-a-*".to_string(),
+            RateLimits {
+                ingest_bytes_per_sec: Some(Byte::from_bytes(1_000)),
+                ..Default::default()
+            },
+        );
+        RateLimiter::new(config)
+    }
+
+    fn index_access(role: AuthRole, index_id: &str) -> Access {
+        Access::Indexes(role, vec![index_id.to_string()])
+    }
+
+    #[test]
+    fn test_token_bucket() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(10, now);
+        assert!(bucket.is_full());
+        assert!(bucket.wait_time(10).is_none());
+
+        bucket.withdraw(25);
+        assert_eq!(bucket.wait_time(1).unwrap(), Duration::from_millis(1_600));
+        // Costs exceeding the capacity wait for a full bucket.
+        assert_eq!(bucket.wait_time(100).unwrap(), Duration::from_millis(2_500));
+
+        bucket.refill(now + Duration::from_millis(1_600));
+        assert!(bucket.wait_time(1).is_none());
+
+        bucket.refill(now + Duration::from_secs(10));
+        assert!(bucket.is_full());
+        assert!(bucket.wait_time(100).is_none());
+    }
+
+    #[test]
+    fn test_rate_limiter_disabled() {
+        let rate_limiter = RateLimiter::new(RateLimitConfig::default());
+        let access = Access::Cluster(AuthRole::Admin);
+
+        for _ in 0..10 {
+            rate_limiter.acquire(Some("vector"), &access, 0).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter_set_config() {
+        let rate_limiter = rate_limiter();
+        let access = Access::Cluster(AuthRole::Admin);
+
+        for _ in 0..3 {
+            rate_limiter.acquire(Some("vector"), &access, 0).unwrap();
+        }
+        rate_limiter
+            .acquire(Some("vector"), &access, 0)
+            .unwrap_err();
+
+        rate_limiter.set_config(RateLimitConfig::default());
+        for _ in 0..10 {
+            rate_limiter.acquire(Some("vector"), &access, 0).unwrap();
+        }
+        let mut config = RateLimitConfig::default();
+        config.principals.insert(
+            "vector".to_string(),
+            RateLimits {
+                requests_per_sec: Some(NonZeroU32::new(1).unwrap()),
+                ..Default::default()
+            },
+        );
+        rate_limiter.set_config(config);
+        rate_limiter.acquire(Some("vector"), &access, 0).unwrap();
+        rate_limiter
+            .acquire(Some("vector"), &access, 0)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn test_rate_limiter_requests_per_sec() {
+        let rate_limiter = rate_limiter();
+        let access = Access::Cluster(AuthRole::Admin);
+
+        for _ in 0..3 {
+            rate_limiter.acquire(Some("vector"), &access, 0).unwrap();
+        }
+        let error = rate_limiter
+            .acquire(Some("vector"), &access, 0)
+            .unwrap_err();
+        assert_eq!(error.status_code(), ServiceErrorCode::RateLimited);
+        assert_eq!(error.limit, "requests_per_sec");
+        assert_eq!(error.target, LimitTarget::Principal("vector".to_string()));
+        assert_eq!(error.retry_after_secs(), 1);
+
+        // Each principal gets its own bucket.
+        rate_limiter.acquire(Some("fluentd"), &access, 0).unwrap();
+        // The principals with a dedicated entry are not subject to the default limits.
+        for _ in 0..10 {
+            rate_limiter.acquire(Some("grafana"), &access, 0).unwrap();
+        }
+        // Anonymous and public requests are only subject to the index limits.
+        for _ in 0..10 {
+            rate_limiter.acquire(None, &access, 0).unwrap();
+            rate_limiter
+                .acquire(Some("vector"), &Access::Public, 0)
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_rate_limiter_ingest_bytes_per_sec() {
+        let rate_limiter = rate_limiter();
+        let access = index_access(AuthRole::Ingest, "team-a-logs");
+
+        rate_limiter.acquire(None, &access, 600).unwrap();
+        let error = rate_limiter.acquire(None, &access, 600).unwrap_err();
+        assert_eq!(error.limit, "ingest_bytes_per_sec");
+        assert_eq!(error.target, LimitTarget::Index("team-a-logs".to_string()));
+        assert!(error.retry_after() <= Duration::from_millis(200));
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "Rate limit `ingest_bytes_per_sec` of index `team-a-logs` exceeded, retry in {:?}.",
+                error.retry_after()
+            )
+        );
+        // The ingest bytes of the other indexes are not limited.
+        rate_limiter
+            .acquire(None, &index_access(AuthRole::Ingest, "team-b-logs"), 10_000)
+            .unwrap();
+        // Search requests do not consume ingest bytes.
+        rate_limiter
+            .acquire(None, &index_access(AuthRole::Search, "team-a-logs"), 10_000)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_rate_limiter_limits_ingest_bytes() {
+        let rate_limiter = rate_limiter();
+        let ingest_access = index_access(AuthRole::Ingest, "team-a-logs");
+        assert!(rate_limiter.limits_ingest_bytes(None, &ingest_access));
+        assert!(
+            !rate_limiter.limits_ingest_bytes(None, &index_access(AuthRole::Ingest, "team-b-logs"))
+        );
+        assert!(
+            !rate_limiter.limits_ingest_bytes(None, &index_access(AuthRole::Search, "team-a-logs"))
+        );
+        assert!(!rate_limiter.limits_ingest_bytes(None, &Access::Internal));
+
+        rate_limiter.set_config(RateLimitConfig::default());
+        assert!(!rate_limiter.limits_ingest_bytes(None, &ingest_access));
+    }
+
+    #[test]
+    fn test_rate_limiter_rejected_requests_are_not_charged() {
+        let rate_limiter = rate_limiter();
+        let access = index_access(AuthRole::Ingest, "team-a-logs");
+
+        rate_limiter
+            .acquire(Some("vector"), &access, 1_000)
+            .unwrap();
+        rate_limiter
+            .acquire(Some("vector"), &access, 1_000)
+            .unwrap_err();
+        // The rejected request did not consume the requests of the principal.
+        let other_access = index_access(AuthRole::Ingest, "team-b-logs");
+        rate_limiter
+            .acquire(Some("vector"), &other_access, 0)
+            .unwrap();
+        rate_limiter
+            .acquire(Some("vector"), &other_access, 0)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_rate_limiter_max_concurrent_searches() {
+        let rate_limiter = rate_limiter();
+        let search_access = index_access(AuthRole::Search, "team-a-logs");
+
+        let permit = rate_limiter
+            .acquire(Some("grafana"), &search_access, 0)
+            .unwrap();
+        let error = rate_limiter
+            .acquire(Some("grafana"), &search_access, 0)
+            .unwrap_err();
+        assert_eq!(error.limit, "max_concurrent_searches");
+        assert_eq!(error.retry_after_secs(), 1);
+
+        // Other requests are not subject to the search concurrency limit.
+        rate_limiter
+            .acquire(Some("grafana"), &Access::Cluster(AuthRole::Admin), 0)
+            .unwrap();
+
+        drop(permit);
+        rate_limiter
+            .acquire(Some("grafana"), &search_access, 0)
+            .unwrap();
+    }
+}
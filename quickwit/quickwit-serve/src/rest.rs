@@ -20,10 +20,14 @@
 use std::net::SocketAddr;
 use std::sync::Arc;
 
-use hyper::http::HeaderValue;
+use anyhow::Context;
+use hyper::http::{HeaderName, HeaderValue};
 use hyper::{http, Method};
 use quickwit_common::metrics;
 use quickwit_common::tower::BoxFutureInfaillible;
+use quickwit_config::service::QuickwitService;
+use quickwit_config::RestCompressionConfig;
+use quickwit_jaeger::ZipkinService;
 use quickwit_proto::ServiceErrorCode;
 use tower::make::Shared;
 use tower::ServiceBuilder;
@@ -33,22 +37,32 @@ use tower_http::cors::CorsLayer;
 use tracing::{error, info};
 use warp::{redirect, Filter, Rejection, Reply};
 
-use crate::cluster_api::cluster_handler;
+use crate::admin_api::admin_settings_handler;
+use crate::auth::{AccessPolicy, Authenticator, RestAuthLayer};
+use crate::cluster_api::{
+    cluster_handler, cluster_nodes_handler, cluster_settings_handler, decommission_node_handler,
+    indexer_autoscaling_handler, indexing_tasks_handler, node_decommission_status_handler,
+};
 use crate::delete_task_api::delete_task_api_handlers;
 use crate::elastic_search_api::elastic_api_handlers;
 use crate::health_check_api::health_check_handlers;
 use crate::index_api::index_management_handlers;
-use crate::indexing_api::indexing_get_handler;
-use crate::ingest_api::ingest_api_handlers;
+use crate::indexing_api::{indexing_drain_handler, indexing_get_handler};
+use crate::ingest_api::{ingest_api_handlers, tail_stream_handler};
 use crate::json_api_response::{ApiError, JsonApiResponse};
 use crate::node_info_handler::node_info_handler;
-use crate::search_api::{search_get_handler, search_post_handler, search_stream_handler};
+use crate::otlp_api::otlp_api_handlers;
+use crate::prometheus_api::prometheus_api_handlers;
+use crate::search_api::{
+    search_get_handler, search_post_handler, search_stream_handler, search_stream_sse_handler,
+    sql_get_handler, sql_post_handler, trace_search_handler,
+};
+use crate::ui_api::ui_api_handlers;
 use crate::ui_handler::ui_handler;
-use crate::{BodyFormat, BuildInfo, QuickwitServices, RuntimeInfo};
-
-/// The minimum size a response body must be in order to
-/// be automatically compressed with gzip.
-const MINIMUM_RESPONSE_COMPRESSION_SIZE: u16 = 10 << 10;
+use crate::zipkin_api::zipkin_api_handlers;
+use crate::{
+    otlp_logs_service, otlp_trace_service, BodyFormat, BuildInfo, QuickwitServices, RuntimeInfo,
+};
 
 #[derive(Debug)]
 pub(crate) struct InvalidJsonRequest(pub serde_json::Error);
@@ -80,6 +94,8 @@ pub(crate) async fn start_rest_server(
         quickwit_services.cluster.clone(),
         quickwit_services.indexing_service.clone(),
         quickwit_services.janitor_service.clone(),
+        quickwit_services.readiness_report_rx.clone(),
+        quickwit_services.spawn_ctx.clone(),
     );
 
     // `/metrics` route.
@@ -89,9 +105,61 @@ pub(crate) async fn start_rest_server(
 
     let ingest_service = quickwit_services.ingest_service.clone();
 
+    // `/v1/logs` and `/v1/traces` OTLP HTTP routes.
+    let enable_otlp_http_endpoints = quickwit_services.config.indexer_config.enable_otlp_endpoint
+        && quickwit_services
+            .services
+            .contains(&QuickwitService::Indexer);
+    let (otlp_logs_service_opt, otlp_trace_service_opt) = if enable_otlp_http_endpoints {
+        (
+            Some(otlp_logs_service(
+                ingest_service.clone(),
+                &quickwit_services.config.indexer_config,
+            )),
+            Some(otlp_trace_service(
+                ingest_service.clone(),
+                &quickwit_services.config.indexer_config,
+                quickwit_services.otlp_tail_sampler_opt.clone(),
+            )),
+        )
+    } else {
+        (None, None)
+    };
+    let otlp_routes = otlp_api_handlers(otlp_logs_service_opt, otlp_trace_service_opt.clone());
+
+    // `/zipkin/api/v2/*` routes.
+    let enable_zipkin_query_endpoints = quickwit_services.config.jaeger_config.enable_endpoint
+        && quickwit_services
+            .services
+            .contains(&QuickwitService::Searcher);
+    let zipkin_service_opt = if enable_zipkin_query_endpoints {
+        Some(ZipkinService::new(
+            quickwit_services.config.jaeger_config.clone(),
+            quickwit_services.search_service.clone(),
+        ))
+    } else {
+        None
+    };
+    let zipkin_routes = zipkin_api_handlers(otlp_trace_service_opt, zipkin_service_opt);
+
     // `/api/v1/*` routes.
     let api_v1_root_url = warp::path!("api" / "v1" / ..);
     let api_v1_routes = cluster_handler(quickwit_services.cluster.clone())
+        .or(cluster_nodes_handler(quickwit_services.cluster.clone()))
+        .or(decommission_node_handler(quickwit_services.cluster.clone()))
+        .or(cluster_settings_handler(
+            quickwit_services.cluster_settings_service.clone(),
+        ))
+        .or(node_decommission_status_handler(
+            quickwit_services.cluster.clone(),
+        ))
+        .or(indexing_tasks_handler(
+            quickwit_services.cluster.clone(),
+            quickwit_services.control_plane_service.clone(),
+        ))
+        .or(indexer_autoscaling_handler(
+            quickwit_services.control_plane_service.clone(),
+        ))
         .or(node_info_handler(
             BuildInfo::get(),
             RuntimeInfo::get(),
@@ -100,6 +168,9 @@ pub(crate) async fn start_rest_server(
         .or(indexing_get_handler(
             quickwit_services.indexing_service.clone(),
         ))
+        .or(indexing_drain_handler(
+            quickwit_services.indexing_service.clone(),
+        ))
         .or(search_get_handler(quickwit_services.search_service.clone()))
         .or(search_post_handler(
             quickwit_services.search_service.clone(),
@@ -107,7 +178,36 @@ pub(crate) async fn start_rest_server(
         .or(search_stream_handler(
             quickwit_services.search_service.clone(),
         ))
-        .or(ingest_api_handlers(ingest_service.clone()))
+        .or(search_stream_sse_handler(
+            quickwit_services.search_service.clone(),
+        ))
+        .or(trace_search_handler(
+            quickwit_services.search_service.clone(),
+            quickwit_services
+                .config
+                .indexer_config
+                .otlp_trace_index_id
+                .clone(),
+            quickwit_services
+                .config
+                .indexer_config
+                .otlp_logs_index_id
+                .clone(),
+        ))
+        .or(sql_get_handler(quickwit_services.search_service.clone()))
+        .or(sql_post_handler(quickwit_services.search_service.clone()))
+        .or(prometheus_api_handlers(
+            quickwit_services.metastore.clone(),
+            quickwit_services.search_service.clone(),
+        ))
+        .or(ingest_api_handlers(
+            quickwit_services.ingest_router.clone(),
+            quickwit_services.metastore.clone(),
+        ))
+        .or(tail_stream_handler(
+            quickwit_services.metastore.clone(),
+            quickwit_services.ingest_api_service_opt.clone(),
+        ))
         .or(index_management_handlers(
             quickwit_services.index_service.clone(),
             quickwit_services.config.clone(),
@@ -115,9 +215,13 @@ pub(crate) async fn start_rest_server(
         .or(delete_task_api_handlers(
             quickwit_services.metastore.clone(),
         ))
+        .or(ui_api_handlers(quickwit_services.metastore.clone()))
+        .or(admin_settings_handler(
+            quickwit_services.runtime_settings_service.clone(),
+        ))
         .or(elastic_api_handlers(
             quickwit_services.search_service.clone(),
-            ingest_service.clone(),
+            quickwit_services.ingest_router.clone(),
         ));
 
     let api_v1_root_route = api_v1_root_url.and(api_v1_routes);
@@ -132,22 +236,36 @@ pub(crate) async fn start_rest_server(
         .or(ui_handler())
         .or(health_check_routes)
         .or(metrics_routes)
+        .or(otlp_routes)
+        .or(zipkin_routes)
         .with(request_counter)
         .recover(recover_fn)
         .boxed();
 
     let warp_service = warp::service(rest_routes);
-    let compression_predicate =
-        DefaultPredicate::new().and(SizeAbove::new(MINIMUM_RESPONSE_COMPRESSION_SIZE));
-    let cors = build_cors(&quickwit_services.config.rest_cors_allow_origins);
+    let compression = build_compression(&quickwit_services.config.rest_compression_config);
+    let cors = build_cors(
+        &quickwit_services.config.rest_cors_allow_origins,
+        &quickwit_services.config.rest_cors_allow_headers,
+    )?;
+    let authenticator_opt = Authenticator::from_config(&quickwit_services.config.auth_config);
+    if authenticator_opt.is_some() {
+        info!("Authentication and authorization of the REST API are enabled.");
+    }
+    // The layer is installed even when authentication and audit are disabled: rate limits can be
+    // set at any time through the cluster settings.
+    let access_policy = AccessPolicy::new(&quickwit_services.config.indexer_config);
+    let auth_layer = RestAuthLayer::new(
+        authenticator_opt.map(Arc::new),
+        access_policy,
+        quickwit_services.audit_logger_opt.clone(),
+    )
+    .with_rate_limiter(quickwit_services.rate_limiter.clone());
 
     let service = ServiceBuilder::new()
-        .layer(
-            CompressionLayer::new()
-                .gzip(true)
-                .compress_when(compression_predicate),
-        )
+        .layer(compression)
         .layer(cors)
+        .layer(auth_layer)
         .service(warp_service);
 
     info!(
@@ -256,7 +374,20 @@ fn get_status_with_error(rejection: Rejection) -> ApiError {
     }
 }
 
-fn build_cors(cors_origins: &[String]) -> CorsLayer {
+fn build_compression(
+    compression_config: &RestCompressionConfig,
+) -> CompressionLayer<impl Predicate> {
+    // The node config validation guarantees that the minimum size fits in a `u16`.
+    let min_response_size =
+        u16::try_from(compression_config.min_response_size.get_bytes()).unwrap_or(u16::MAX);
+    let compression_predicate = DefaultPredicate::new().and(SizeAbove::new(min_response_size));
+    CompressionLayer::new()
+        .gzip(compression_config.gzip)
+        .deflate(compression_config.deflate)
+        .compress_when(compression_predicate)
+}
+
+fn build_cors(cors_origins: &[String], cors_headers: &[String]) -> anyhow::Result<CorsLayer> {
     let mut cors = CorsLayer::new().allow_methods([
         Method::GET,
         Method::POST,
@@ -274,13 +405,33 @@ fn build_cors(cors_origins: &[String]) -> CorsLayer {
             info!(origins = ?cors_origins, "CORS is enabled, the following origins will be allowed");
             let origins = cors_origins
                 .iter()
-                .map(|origin| origin.parse::<HeaderValue>().unwrap())
-                .collect::<Vec<_>>();
+                .map(|origin| {
+                    origin
+                        .parse::<HeaderValue>()
+                        .with_context(|| format!("Invalid CORS origin `{origin}`."))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
             cors = cors.allow_origin(origins);
         };
     }
+    if !cors_headers.is_empty() {
+        let allow_any = cors_headers.iter().any(|header| header.as_str() == "*");
 
-    cors
+        if allow_any {
+            cors = cors.allow_headers(tower_http::cors::Any);
+        } else {
+            let headers = cors_headers
+                .iter()
+                .map(|header| {
+                    header
+                        .parse::<HeaderName>()
+                        .with_context(|| format!("Invalid CORS header `{header}`."))
+                })
+                .collect::<anyhow::Result<Vec<_>>>()?;
+            cors = cors.allow_headers(headers);
+        }
+    }
+    Ok(cors)
 }
 
 #[cfg(test)]
@@ -289,6 +440,7 @@ mod tests {
     use std::pin::Pin;
     use std::task::{Context, Poll};
 
+    use byte_unit::Byte;
     use hyper::{Request, Response, StatusCode};
     use tower::Service;
 
@@ -298,7 +450,7 @@ mod tests {
     async fn test_cors() {
         // No cors enabled
         {
-            let cors = build_cors(&[]);
+            let cors = build_cors(&[], &[]).unwrap();
 
             let mut layer = ServiceBuilder::new().layer(cors).service(HelloWorld);
 
@@ -329,7 +481,7 @@ mod tests {
 
         // Wildcard cors enabled
         {
-            let cors = build_cors(&["*".to_string()]);
+            let cors = build_cors(&["*".to_string()], &[]).unwrap();
 
             let mut layer = ServiceBuilder::new().layer(cors).service(HelloWorld);
 
@@ -366,7 +518,7 @@ mod tests {
 
         // Specific origin cors enabled
         {
-            let cors = build_cors(&["https://quickwit.io".to_string()]);
+            let cors = build_cors(&["https://quickwit.io".to_string()], &[]).unwrap();
 
             let mut layer = ServiceBuilder::new().layer(cors).service(HelloWorld);
 
@@ -417,10 +569,14 @@ mod tests {
 
         // Specific multiple-origin cors enabled
         {
-            let cors = build_cors(&[
-                "https://quickwit.io".to_string(),
-                "http://localhost:3000".to_string(),
-            ]);
+            let cors = build_cors(
+                &[
+                    "https://quickwit.io".to_string(),
+                    "http://localhost:3000".to_string(),
+                ],
+                &[],
+            )
+            .unwrap();
 
             let mut layer = ServiceBuilder::new().layer(cors).service(HelloWorld);
 
@@ -473,6 +629,80 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_cors_allow_headers() {
+        let cors = build_cors(
+            &["*".to_string()],
+            &["authorization".to_string(), "content-type".to_string()],
+        )
+        .unwrap();
+        let mut layer = ServiceBuilder::new().layer(cors).service(HelloWorld);
+
+        let resp = layer
+            .call(cors_request("http://localhost:3000"))
+            .await
+            .unwrap();
+        let headers = resp.headers();
+        assert_eq!(
+            headers.get("Access-Control-Allow-Headers"),
+            Some(&"authorization,content-type".parse::<HeaderValue>().unwrap())
+        );
+
+        let error = build_cors(&["*".to_string()], &["bad header".to_string()]).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid CORS header `bad header`.");
+
+        let error = build_cors(&["https://bad\norigin".to_string()], &[]).unwrap_err();
+        assert!(error.to_string().starts_with("Invalid CORS origin"));
+    }
+
+    #[tokio::test]
+    async fn test_compression() {
+        let compression_config = RestCompressionConfig {
+            gzip: false,
+            deflate: true,
+            min_response_size: Byte::from_bytes(1024),
+        };
+        let mut layer = ServiceBuilder::new()
+            .layer(build_compression(&compression_config))
+            .service_fn(|request: Request<()>| async move {
+                let body_len = if request.uri().path() == "/large" {
+                    2048
+                } else {
+                    512
+                };
+                Response::builder()
+                    .status(StatusCode::OK)
+                    .body("a".repeat(body_len))
+            });
+        let compression_request = |path: &str, accept_encoding: &'static str| {
+            Request::builder()
+                .uri(path)
+                .header("Accept-Encoding", accept_encoding)
+                .body(())
+                .unwrap()
+        };
+        let resp = layer
+            .call(compression_request("/large", "gzip, deflate"))
+            .await
+            .unwrap();
+        assert_eq!(
+            resp.headers().get("Content-Encoding"),
+            Some(&HeaderValue::from_static("deflate"))
+        );
+
+        let resp = layer
+            .call(compression_request("/small", "gzip, deflate"))
+            .await
+            .unwrap();
+        assert_eq!(resp.headers().get("Content-Encoding"), None);
+
+        let resp = layer
+            .call(compression_request("/large", "gzip"))
+            .await
+            .unwrap();
+        assert_eq!(resp.headers().get("Content-Encoding"), None);
+    }
+
     fn cors_request(origin: &'static str) -> Request<()> {
         let mut request = Request::new(());
         (*request.method_mut()) = Method::OPTIONS;
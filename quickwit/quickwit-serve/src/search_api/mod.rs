@@ -22,8 +22,9 @@ mod rest_handler;
 
 pub use self::grpc_adapter::GrpcSearchAdapter;
 pub use self::rest_handler::{
-    search_get_handler, search_post_handler, search_stream_handler, SearchApi,
-    SearchRequestQueryString, SortByField,
+    search_get_handler, search_post_handler, search_stream_handler, search_stream_sse_handler,
+    sql_get_handler, sql_post_handler, trace_search_handler, SearchApi, SearchRequestQueryString,
+    SortByField, SqlRequestQueryString, TraceSearchQueryString, TraceSearchResponseRest,
 };
 
 #[cfg(test)]
@@ -73,6 +74,8 @@ mod tests {
             fast_field: "timestamp".to_string(),
             output_format: OutputFormat::Csv as i32,
             partition_by_field: None,
+            sort_by_split_id: false,
+            start_after_split_id: None,
         };
         let mut metastore = MockMetastore::new();
         metastore
@@ -17,31 +17,52 @@
 // You should have received a copy of the GNU Affero General Public License
 // along with this program. If not, see <http://www.gnu.org/licenses/>.
 
-use std::convert::TryFrom;
+use std::convert::{Infallible, TryFrom};
 use std::sync::Arc;
 
-use futures::stream::StreamExt;
+use base64::prelude::{Engine, BASE64_STANDARD};
+use futures::stream::{self, Stream, StreamExt};
 use hyper::header::HeaderValue;
 use hyper::HeaderMap;
-use quickwit_proto::{query_ast_from_user_text, OutputFormat, ServiceError, SortOrder};
-use quickwit_search::{SearchError, SearchResponseRest, SearchService};
+use quickwit_config::AuthRole;
+use quickwit_opentelemetry::otlp::TraceId;
+use quickwit_proto::{
+    query_ast_from_user_text, LeafSearchStreamResponse, OutputFormat, ServiceError, SortOrder,
+};
+use quickwit_search::{
+    sql_index_id, sql_search, SearchError, SearchResponseRest, SearchService, SqlResponse,
+};
 use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value as JsonValue;
 use tracing::info;
 use warp::hyper::header::CONTENT_TYPE;
 use warp::hyper::StatusCode;
+use warp::reply::Response;
+use warp::sse::Event;
 use warp::{reply, Filter, Rejection, Reply};
 
+use crate::auth::{authorize_index, restrict_to_tenant, with_principal, Principal};
 use crate::json_api_response::make_json_api_response;
 use crate::simple_list::{from_simple_list, to_simple_list};
 use crate::{with_arg, BodyFormat};
 
 #[derive(utoipa::OpenApi)]
 #[openapi(
-    paths(search_get_handler, search_post_handler, search_stream_handler,),
+    paths(
+        search_get_handler,
+        search_post_handler,
+        search_stream_handler,
+        search_stream_sse_handler,
+        trace_search_handler,
+        sql_get_handler,
+        sql_post_handler,
+    ),
     components(schemas(
         SearchRequestQueryString,
         SearchResponseRest,
+        TraceSearchResponseRest,
+        SqlRequestQueryString,
+        SqlResponse,
         SortByField,
         SortOrder,
         OutputFormat,
@@ -184,6 +205,7 @@ fn get_proto_search_by(search_request: &SearchRequestQueryString) -> (Option<i32
 async fn search_endpoint(
     index_id: String,
     search_request: SearchRequestQueryString,
+    principal_opt: Option<&Principal>,
     search_service: &dyn SearchService,
 ) -> Result<SearchResponseRest, SearchError> {
     let (sort_order, sort_by_field) = get_proto_search_by(&search_request);
@@ -191,6 +213,7 @@ async fn search_endpoint(
     // parsing of the user query will happen in the root service, and might require
     // the user of the docmapper default fields (which we do not have at this point).
     let query_ast = query_ast_from_user_text(&search_request.query, search_request.search_fields);
+    let query_ast = restrict_to_tenant(principal_opt, query_ast);
     let query_ast_json = serde_json::to_string(&query_ast)?;
     let search_request = quickwit_proto::SearchRequest {
         index_id,
@@ -229,11 +252,18 @@ fn search_post_filter(
 async fn search(
     index_id: String,
     search_request: SearchRequestQueryString,
+    principal_opt: Option<Principal>,
     search_service: Arc<dyn SearchService>,
 ) -> impl warp::Reply {
     info!(index_id = %index_id, request =? search_request, "search");
     let body_format = search_request.format;
-    let result = search_endpoint(index_id, search_request, &*search_service).await;
+    let result = search_endpoint(
+        index_id,
+        search_request,
+        principal_opt.as_ref(),
+        &*search_service,
+    )
+    .await;
     make_json_api_response(result, body_format)
 }
 
@@ -256,6 +286,7 @@ pub fn search_get_handler(
     search_service: Arc<dyn SearchService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     search_get_filter()
+        .and(with_principal())
         .and(with_arg(search_service))
         .then(search)
 }
@@ -281,6 +312,7 @@ pub fn search_post_handler(
     search_service: Arc<dyn SearchService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     search_post_filter()
+        .and(with_principal())
         .and(with_arg(search_service))
         .then(search)
 }
@@ -302,10 +334,297 @@ pub fn search_stream_handler(
     search_service: Arc<dyn SearchService>,
 ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
     search_stream_filter()
+        .and(with_principal())
         .and(with_arg(search_service))
         .then(search_stream)
 }
 
+#[utoipa::path(
+    get,
+    tag = "Search",
+    path = "/{index_id}/search/stream/sse",
+    responses(
+        (status = 200, description = "Successfully started streaming the search results as server-sent events.")
+    ),
+    params(
+        SearchStreamRequestQueryString,
+        ("index_id" = String, Path, description = "The index ID to search."),
+        ("Last-Event-ID" = Option<String>, Header, description = "The ID of the last event received, sent by clients resuming an interrupted stream."),
+    )
+)]
+/// Stream Search Index as Server-Sent Events
+///
+/// Streams the values of the fast field as server-sent events. The splits are searched one after
+/// the other in increasing split ID order, and each `data` event carries the values extracted from
+/// one split, identified by the event ID. An interrupted stream can be resumed after its last
+/// received event with the `Last-Event-ID` header or the `start_after_split_id` parameter. The
+/// stream ends with an `end` event, or an `error` event.
+pub fn search_stream_sse_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    search_stream_sse_filter()
+        .and(with_principal())
+        .and(with_arg(search_service))
+        .then(search_stream_sse)
+}
+
+/// This struct represents the trace search query passed to
+/// the REST API.
+#[derive(Debug, Default, Eq, PartialEq, Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+#[serde(deny_unknown_fields)]
+pub struct TraceSearchQueryString {
+    /// Index containing the spans. Defaults to the index receiving the spans ingested via OTLP.
+    pub trace_index_id: Option<String>,
+    /// Index containing the logs. Defaults to the index receiving the logs ingested via OTLP.
+    pub logs_index_id: Option<String>,
+    /// If set, restricts search to documents with a `timestamp >= start_timestamp`.
+    pub start_timestamp: Option<i64>,
+    /// If set, restricts search to documents with a `timestamp < end_timestamp``.
+    pub end_timestamp: Option<i64>,
+    /// Maximum number of spans and of logs to return.
+    #[serde(default = "default_trace_search_max_hits")]
+    pub max_hits: u64,
+    /// The output format requested.
+    #[serde(default)]
+    pub format: BodyFormat,
+}
+
+fn default_trace_search_max_hits() -> u64 {
+    1_000
+}
+
+/// Spans and logs of a trace.
+#[derive(Debug, Serialize, utoipa::ToSchema)]
+pub struct TraceSearchResponseRest {
+    /// Trace ID, hex-encoded.
+    pub trace_id: String,
+    /// Spans of the trace sorted by start time.
+    pub spans: SearchResponseRest,
+    /// Logs carrying the trace ID sorted by timestamp.
+    pub logs: SearchResponseRest,
+}
+
+async fn trace_search_endpoint(
+    trace_id: String,
+    trace_search_request: TraceSearchQueryString,
+    default_index_ids: (String, String),
+    search_service: &dyn SearchService,
+) -> Result<TraceSearchResponseRest, SearchError> {
+    let trace_id: TraceId = trace_id.parse().map_err(|_| {
+        SearchError::InvalidArgument(format!("`{trace_id}` is not a valid hex-encoded trace ID."))
+    })?;
+    let (default_trace_index_id, default_logs_index_id) = default_index_ids;
+    let trace_index_id = trace_search_request
+        .trace_index_id
+        .unwrap_or(default_trace_index_id);
+    let logs_index_id = trace_search_request
+        .logs_index_id
+        .unwrap_or(default_logs_index_id);
+
+    // Trace IDs are stored in Base64 or in hex depending on the OTLP ID encoding of the indexer
+    // that ingested the spans and logs.
+    let query = format!(
+        "trace_id:{} OR trace_id:{}",
+        trace_id.base64_display(),
+        trace_id.to_hex()
+    );
+    let query_ast_json = serde_json::to_string(&query_ast_from_user_text(&query, None))?;
+    let build_search_request = |index_id: String| quickwit_proto::SearchRequest {
+        index_id,
+        query_ast: query_ast_json.clone(),
+        start_timestamp: trace_search_request.start_timestamp,
+        end_timestamp: trace_search_request.end_timestamp,
+        max_hits: trace_search_request.max_hits,
+        ..Default::default()
+    };
+    let (spans, logs) = futures::try_join!(
+        search_trace_docs(
+            build_search_request(trace_index_id),
+            "span_start_timestamp_nanos",
+            search_service
+        ),
+        search_trace_docs(
+            build_search_request(logs_index_id),
+            "timestamp_nanos",
+            search_service
+        ),
+    )?;
+    Ok(TraceSearchResponseRest {
+        trace_id: trace_id.to_hex(),
+        spans,
+        logs,
+    })
+}
+
+/// Searches the spans or logs of a trace and sorts them by `timestamp_field`, which is stored
+/// but not fast in the OTEL indexes. A missing index is reported as a search error of the response
+/// instead of failing the whole request, since the logs or the spans of a trace may not be
+/// ingested in Quickwit.
+async fn search_trace_docs(
+    search_request: quickwit_proto::SearchRequest,
+    timestamp_field: &str,
+    search_service: &dyn SearchService,
+) -> Result<SearchResponseRest, SearchError> {
+    let mut search_response_rest = match search_service.root_search(search_request).await {
+        Ok(search_response) => SearchResponseRest::try_from(search_response)?,
+        Err(error @ SearchError::IndexDoesNotExist { .. }) => SearchResponseRest {
+            num_hits: 0,
+            hits: Vec::new(),
+            snippets: None,
+            elapsed_time_micros: 0,
+            errors: vec![error.to_string()],
+            aggregations: None,
+        },
+        Err(error) => return Err(error),
+    };
+    search_response_rest
+        .hits
+        .sort_by_key(|hit| hit.get(timestamp_field).and_then(JsonValue::as_u64));
+    Ok(search_response_rest)
+}
+
+async fn trace_search(
+    trace_id: String,
+    trace_search_request: TraceSearchQueryString,
+    default_index_ids: (String, String),
+    search_service: Arc<dyn SearchService>,
+) -> impl warp::Reply {
+    info!(trace_id = %trace_id, request =? trace_search_request, "trace-search");
+    let body_format = trace_search_request.format;
+    let result = trace_search_endpoint(
+        trace_id,
+        trace_search_request,
+        default_index_ids,
+        &*search_service,
+    )
+    .await;
+    make_json_api_response(result, body_format)
+}
+
+fn trace_search_filter(
+) -> impl Filter<Extract = (String, TraceSearchQueryString), Error = Rejection> + Clone {
+    warp::path!("traces" / String)
+        .and(warp::get())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+}
+
+#[utoipa::path(
+    get,
+    tag = "Search",
+    path = "/traces/{trace_id}",
+    responses(
+        (status = 200, description = "Successfully fetched the spans and logs of the trace.", body = TraceSearchResponseRest)
+    ),
+    params(
+        TraceSearchQueryString,
+        ("trace_id" = String, Path, description = "The hex-encoded trace ID."),
+    )
+)]
+/// Search Trace
+///
+/// Returns the spans of a trace and the logs carrying its trace ID in a single response.
+pub fn trace_search_handler(
+    search_service: Arc<dyn SearchService>,
+    trace_index_id: String,
+    logs_index_id: String,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    trace_search_filter()
+        .and(with_arg((trace_index_id, logs_index_id)))
+        .and(with_arg(search_service))
+        .then(trace_search)
+}
+
+/// This struct represents the SQL query passed to the REST API.
+#[derive(
+    Debug, Default, Eq, PartialEq, Serialize, Deserialize, utoipa::IntoParams, utoipa::ToSchema,
+)]
+#[into_params(parameter_in = Query)]
+#[serde(deny_unknown_fields)]
+pub struct SqlRequestQueryString {
+    /// SQL `SELECT` statement. The `FROM` clause names the index to query.
+    #[serde(deserialize_with = "deserialize_not_empty_string")]
+    pub query: String,
+    /// The output format.
+    #[serde(default)]
+    pub format: BodyFormat,
+}
+
+async fn sql(
+    sql_request: SqlRequestQueryString,
+    principal_opt: Option<Principal>,
+    search_service: Arc<dyn SearchService>,
+) -> impl warp::Reply {
+    info!(request =? sql_request, "sql");
+    let body_format = sql_request.format;
+
+    // Invalid queries are rejected by `sql_search` without running any search.
+    if let Ok(index_id) = sql_index_id(&sql_request.query) {
+        let auth_result = authorize_index(principal_opt.as_ref(), AuthRole::Search, &index_id);
+        if let Err(auth_error) = auth_result {
+            return make_json_api_response::<(), _>(Err(auth_error), body_format);
+        }
+    }
+    let result = sql_search(&sql_request.query, &*search_service).await;
+    make_json_api_response(result, body_format)
+}
+
+fn sql_get_filter() -> impl Filter<Extract = (SqlRequestQueryString,), Error = Rejection> + Clone {
+    warp::path!("sql")
+        .and(warp::get())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+}
+
+fn sql_post_filter() -> impl Filter<Extract = (SqlRequestQueryString,), Error = Rejection> + Clone {
+    warp::path!("sql")
+        .and(warp::post())
+        .and(warp::body::content_length_limit(1024 * 1024))
+        .and(warp::body::json())
+}
+
+#[utoipa::path(
+    get,
+    tag = "Search",
+    path = "/sql",
+    responses(
+        (status = 200, description = "Successfully executed the SQL query.", body = SqlResponse)
+    ),
+    params(SqlRequestQueryString)
+)]
+/// SQL Query (GET Variant)
+///
+/// Runs a SQL `SELECT` statement over an index. Aggregates and `GROUP BY` are translated into
+/// aggregations over fast fields.
+pub fn sql_get_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    sql_get_filter()
+        .and(with_principal())
+        .and(with_arg(search_service))
+        .then(sql)
+}
+
+#[utoipa::path(
+    post,
+    tag = "Search",
+    path = "/sql",
+    request_body = SqlRequestQueryString,
+    responses(
+        (status = 200, description = "Successfully executed the SQL query.", body = SqlResponse)
+    )
+)]
+/// SQL Query (POST Variant)
+///
+/// Parses the SQL query from the request body.
+pub fn sql_post_handler(
+    search_service: Arc<dyn SearchService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    sql_post_filter()
+        .and(with_principal())
+        .and(with_arg(search_service))
+        .then(sql)
+}
+
 /// This struct represents the search stream query passed to
 /// the REST API.
 #[derive(Deserialize, Debug, Eq, PartialEq, utoipa::IntoParams)]
@@ -337,16 +656,24 @@ struct SearchStreamRequestQueryString {
     pub output_format: OutputFormat,
     #[serde(default)]
     pub partition_by_field: Option<String>,
+    /// If set, only the splits with an ID greater than this one are searched, in increasing split
+    /// ID order. Used to resume an interrupted export.
+    #[serde(default)]
+    pub start_after_split_id: Option<String>,
 }
 
-async fn search_stream_endpoint(
+fn build_search_stream_request(
     index_id: String,
     search_request: SearchStreamRequestQueryString,
-    search_service: &dyn SearchService,
-) -> Result<hyper::Body, SearchError> {
+    principal_opt: Option<&Principal>,
+    sort_by_split_id: bool,
+) -> Result<quickwit_proto::SearchStreamRequest, SearchError> {
     let query_ast = query_ast_from_user_text(&search_request.query, search_request.search_fields);
+    let query_ast = restrict_to_tenant(principal_opt, query_ast);
     let query_ast_json = serde_json::to_string(&query_ast)?;
-    let request = quickwit_proto::SearchStreamRequest {
+    // Resuming an export is only possible if the splits are searched in a deterministic order.
+    let sort_by_split_id = sort_by_split_id || search_request.start_after_split_id.is_some();
+    Ok(quickwit_proto::SearchStreamRequest {
         index_id,
         query_ast: query_ast_json,
         snippet_fields: search_request.snippet_fields.unwrap_or_default(),
@@ -355,14 +682,25 @@ async fn search_stream_endpoint(
         fast_field: search_request.fast_field,
         output_format: search_request.output_format as i32,
         partition_by_field: search_request.partition_by_field,
-    };
+        sort_by_split_id,
+        start_after_split_id: search_request.start_after_split_id,
+    })
+}
+
+async fn search_stream_endpoint(
+    index_id: String,
+    search_request: SearchStreamRequestQueryString,
+    principal_opt: Option<&Principal>,
+    search_service: &dyn SearchService,
+) -> Result<hyper::Body, SearchError> {
+    let request = build_search_stream_request(index_id, search_request, principal_opt, false)?;
     let mut data = search_service.root_search_stream(request).await?;
     let (mut sender, body) = hyper::Body::channel();
     tokio::spawn(async move {
         while let Some(result) = data.next().await {
             match result {
-                Ok(bytes) => {
-                    if sender.send_data(bytes).await.is_err() {
+                Ok(leaf_response) => {
+                    if sender.send_data(leaf_response.data.into()).await.is_err() {
                         sender.abort();
                         break;
                     }
@@ -409,6 +747,7 @@ fn make_streaming_reply(result: Result<hyper::Body, SearchError>) -> impl Reply
 async fn search_stream(
     index_id: String,
     request: SearchStreamRequestQueryString,
+    principal_opt: Option<Principal>,
     search_service: Arc<dyn SearchService>,
 ) -> impl warp::Reply {
     info!(index_id=%index_id,request=?request, "search_stream");
@@ -416,8 +755,9 @@ async fn search_stream(
         OutputFormat::ClickHouseRowBinary => "application/octet-stream",
         OutputFormat::Csv => "text/csv",
     };
-    let reply =
-        make_streaming_reply(search_stream_endpoint(index_id, request, &*search_service).await);
+    let reply = make_streaming_reply(
+        search_stream_endpoint(index_id, request, principal_opt.as_ref(), &*search_service).await,
+    );
     reply::with_header(reply, CONTENT_TYPE, content_type)
 }
 
@@ -428,15 +768,95 @@ fn search_stream_filter(
         .and(serde_qs::warp::query(serde_qs::Config::default()))
 }
 
+fn search_stream_events(
+    leaf_responses: impl Stream<Item = Result<LeafSearchStreamResponse, SearchError>> + Unpin,
+    output_format: OutputFormat,
+) -> impl Stream<Item = Result<Event, Infallible>> {
+    stream::unfold(Some(leaf_responses), move |leaf_responses_opt| async move {
+        let mut leaf_responses = leaf_responses_opt?;
+        let event = match leaf_responses.next().await {
+            Some(Ok(leaf_response)) => {
+                // Server-sent events are text only: binary payloads are base64 encoded.
+                let data = match output_format {
+                    OutputFormat::Csv => String::from_utf8_lossy(&leaf_response.data).into_owned(),
+                    OutputFormat::ClickHouseRowBinary => {
+                        BASE64_STANDARD.encode(&leaf_response.data)
+                    }
+                };
+                let event = Event::default()
+                    .event("data")
+                    .id(leaf_response.split_id)
+                    .data(data);
+                return Some((Ok(event), Some(leaf_responses)));
+            }
+            Some(Err(error)) => {
+                tracing::error!(error=?error, "Error when streaming search results.");
+                Event::default().event("error").data(error.to_string())
+            }
+            None => Event::default().event("end").data(""),
+        };
+        // The stream ends after an `error` or an `end` event.
+        Some((Ok(event), None))
+    })
+}
+
+async fn search_stream_sse(
+    index_id: String,
+    mut request: SearchStreamRequestQueryString,
+    last_event_id_opt: Option<String>,
+    principal_opt: Option<Principal>,
+    search_service: Arc<dyn SearchService>,
+) -> Response {
+    info!(
+        index_id=%index_id,
+        request=?request,
+        last_event_id=?last_event_id_opt,
+        "search_stream_sse"
+    );
+    // Browsers reconnecting to an event stream send the ID of the last event they received.
+    if last_event_id_opt.is_some() {
+        request.start_after_split_id = last_event_id_opt;
+    }
+    let output_format = request.output_format;
+    let leaf_responses_res =
+        match build_search_stream_request(index_id, request, principal_opt.as_ref(), true) {
+            Ok(search_stream_request) => {
+                search_service
+                    .root_search_stream(search_stream_request)
+                    .await
+            }
+            Err(error) => Err(error),
+        };
+    match leaf_responses_res {
+        Ok(leaf_responses) => {
+            let event_stream = search_stream_events(leaf_responses, output_format);
+            warp::sse::reply(warp::sse::keep_alive().stream(event_stream)).into_response()
+        }
+        Err(error) => {
+            make_json_api_response::<(), _>(Err(error), BodyFormat::default()).into_response()
+        }
+    }
+}
+
+type SearchStreamSseRequest = (String, SearchStreamRequestQueryString, Option<String>);
+
+fn search_stream_sse_filter(
+) -> impl Filter<Extract = SearchStreamSseRequest, Error = Rejection> + Clone {
+    warp::path!(String / "search" / "stream" / "sse")
+        .and(warp::get())
+        .and(serde_qs::warp::query(serde_qs::Config::default()))
+        .and(warp::header::optional::<String>("last-event-id"))
+}
+
 #[cfg(test)]
 mod tests {
     use assert_json_diff::{assert_json_eq, assert_json_include};
-    use bytes::Bytes;
     use mockall::predicate;
     use quickwit_search::{MockSearchService, SearchError};
     use serde_json::{json, Value as JsonValue};
 
     use super::*;
+    use crate::auth::TenantScope;
     use crate::recover_fn;
 
     fn search_handler(
@@ -445,7 +865,8 @@ mod tests {
         let mock_search_service_in_arc = Arc::new(mock_search_service);
         search_get_handler(mock_search_service_in_arc.clone())
             .or(search_post_handler(mock_search_service_in_arc.clone()))
-            .or(search_stream_handler(mock_search_service_in_arc))
+            .or(search_stream_handler(mock_search_service_in_arc.clone()))
+            .or(search_stream_sse_handler(mock_search_service_in_arc))
             .recover(recover_fn)
     }
 
@@ -736,6 +1157,34 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_rest_search_api_restricts_tenant() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .withf(|search_request| {
+                search_request
+                    .query_ast
+                    .contains(r#""field":"tenant_id","value":"acme""#)
+            })
+            .returning(|_| Ok(Default::default()));
+        let rest_search_api_handler = search_handler(mock_search_service);
+        let principal = Principal {
+            name: "acme".to_string(),
+            grants: vec!["search:*".parse().unwrap()],
+            tenant_scope_opt: Some(TenantScope {
+                field_name: "tenant_id".to_string(),
+                tenant_id: "acme".to_string(),
+            }),
+        };
+        let resp = warp::test::request()
+            .path("/quickwit-demo-index/search?query=*")
+            .extension(principal)
+            .reply(&rest_search_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+    }
+
     #[tokio::test]
     async fn test_rest_search_api_with_index_does_not_exist() -> anyhow::Result<()> {
         let mut mock_search_service = MockSearchService::new();
@@ -798,8 +1247,14 @@ mod tests {
             .expect_root_search_stream()
             .return_once(|_| {
                 Ok(Box::pin(futures::stream::iter(vec![
-                    Ok(Bytes::from("first row\n")),
-                    Ok(Bytes::from("second row")),
+                    Ok(LeafSearchStreamResponse {
+                        data: b"first row\n".to_vec(),
+                        split_id: "split1".to_string(),
+                    }),
+                    Ok(LeafSearchStreamResponse {
+                        data: b"second row".to_vec(),
+                        split_id: "split2".to_string(),
+                    }),
                 ])))
             });
         let rest_search_stream_api_handler = search_handler(mock_search_service);
@@ -815,6 +1270,72 @@ mod tests {
         assert_eq!(body, "first row\nsecond row");
     }
 
+    #[tokio::test]
+    async fn test_rest_search_stream_sse_api() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search_stream()
+            .withf(|request| {
+                request.sort_by_split_id
+                    && request.start_after_split_id == Some("split1".to_string())
+            })
+            .return_once(|_| {
+                Ok(Box::pin(futures::stream::iter(vec![
+                    Ok(LeafSearchStreamResponse {
+                        data: b"1\n2\n".to_vec(),
+                        split_id: "split2".to_string(),
+                    }),
+                    Err(SearchError::InternalError("split3 failed".to_string())),
+                ])))
+            });
+        let rest_search_stream_api_handler = search_handler(mock_search_service);
+        let response = warp::test::request()
+            .path(
+                "/my-index/search/stream/sse?query=obama&fast_field=external_id&output_format=csv&\
+                 start_after_split_id=split0",
+            )
+            .header("Last-Event-ID", "split1")
+            .reply(&rest_search_stream_api_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        assert_eq!(
+            response.headers().get(CONTENT_TYPE).unwrap(),
+            "text/event-stream"
+        );
+        let body = String::from_utf8_lossy(response.body());
+        assert!(body.contains("event:data\ndata:1\ndata:2\ndata:\nid:split2\n\n"));
+        assert!(body.contains("event:error\ndata:Internal error: `split3 failed`."));
+        assert!(!body.contains("event:end"));
+    }
+
+    #[tokio::test]
+    async fn test_rest_search_stream_sse_api_click_house_row_binary() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search_stream()
+            .withf(|request| request.sort_by_split_id && request.start_after_split_id.is_none())
+            .return_once(|_| {
+                Ok(Box::pin(futures::stream::iter(vec![Ok(
+                    LeafSearchStreamResponse {
+                        data: vec![0, 1, 2],
+                        split_id: "split1".to_string(),
+                    },
+                )])))
+            });
+        let rest_search_stream_api_handler = search_handler(mock_search_service);
+        let response = warp::test::request()
+            .path(
+                "/my-index/search/stream/sse?query=obama&fast_field=external_id&\
+                 output_format=click_house_row_binary",
+            )
+            .reply(&rest_search_stream_api_handler)
+            .await;
+        assert_eq!(response.status(), 200);
+        let body = String::from_utf8_lossy(response.body());
+        assert!(body.contains("event:data\ndata:AAEC\nid:split1\n\n"));
+        assert!(body.ends_with("event:end\ndata:\n\n"));
+    }
+
     #[tokio::test]
     async fn test_rest_search_stream_api_csv() {
         let (index, req) = warp::test::request()
@@ -834,6 +1355,7 @@ mod tests {
                 fast_field: "external_id".to_string(),
                 output_format: OutputFormat::Csv,
                 partition_by_field: None,
+                start_after_split_id: None,
             }
         );
     }
@@ -860,6 +1382,7 @@ mod tests {
                 fast_field: "external_id".to_string(),
                 output_format: OutputFormat::ClickHouseRowBinary,
                 partition_by_field: None,
+                start_after_split_id: None,
             }
         );
     }
@@ -935,4 +1458,177 @@ mod tests {
         assert_json_eq!(resp_json, expected_response_json);
         Ok(())
     }
+
+    fn trace_search_handler_for_test(
+        mock_search_service: MockSearchService,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+        trace_search_handler(
+            Arc::new(mock_search_service),
+            "otel-trace-v0".to_string(),
+            "otel-logs-v0".to_string(),
+        )
+        .recover(recover_fn)
+    }
+
+    #[tokio::test]
+    async fn test_rest_trace_search_api() -> anyhow::Result<()> {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .withf(|search_request| {
+                search_request.index_id == "otel-trace-v0"
+                    && search_request
+                        .query_ast
+                        .contains("AQIDBAUGBwgJCgsMDQ4PEA==")
+                    && search_request
+                        .query_ast
+                        .contains("0102030405060708090a0b0c0d0e0f10")
+                    && search_request.max_hits == 1_000
+            })
+            .returning(|_| {
+                let hits = [2, 1]
+                    .iter()
+                    .map(|span_start_timestamp_nanos| quickwit_proto::Hit {
+                        json: json!({ "span_start_timestamp_nanos": span_start_timestamp_nanos })
+                            .to_string(),
+                        partial_hit: None,
+                        snippet: None,
+                    })
+                    .collect();
+                Ok(quickwit_proto::SearchResponse {
+                    hits,
+                    num_hits: 2,
+                    ..Default::default()
+                })
+            });
+        mock_search_service
+            .expect_root_search()
+            .withf(|search_request| search_request.index_id == "my-logs")
+            .returning(|_| {
+                Err(SearchError::IndexDoesNotExist {
+                    index_id: "my-logs".to_string(),
+                })
+            });
+        let trace_search_handler = trace_search_handler_for_test(mock_search_service);
+        let resp = warp::test::request()
+            .path("/traces/0102030405060708090a0b0c0d0e0f10?logs_index_id=my-logs")
+            .reply(&trace_search_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        let expected_response_json = json!({
+            "trace_id": "0102030405060708090a0b0c0d0e0f10",
+            "spans": {
+                "num_hits": 2,
+                "hits": [
+                    {"span_start_timestamp_nanos": 1},
+                    {"span_start_timestamp_nanos": 2},
+                ],
+            },
+            "logs": {
+                "num_hits": 0,
+                "hits": [],
+                "errors": ["Index `my-logs` does not exist."],
+            },
+        });
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rest_trace_search_api_invalid_trace_id() {
+        let trace_search_handler = trace_search_handler_for_test(MockSearchService::new());
+        let resp = warp::test::request()
+            .path("/traces/not-a-trace-id")
+            .reply(&trace_search_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+
+    fn sql_handler(
+        mock_search_service: MockSearchService,
+    ) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+        let mock_search_service_in_arc = Arc::new(mock_search_service);
+        sql_get_handler(mock_search_service_in_arc.clone())
+            .or(sql_post_handler(mock_search_service_in_arc))
+            .recover(recover_fn)
+    }
+
+    #[tokio::test]
+    async fn test_rest_sql_api() -> anyhow::Result<()> {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .withf(|search_request| {
+                search_request.index_id == "my-logs"
+                    && search_request.max_hits == 0
+                    && search_request.aggregation_request.is_some()
+            })
+            .returning(|_| {
+                let aggregation = json!({
+                    "group_by_0": {
+                        "buckets": [{"key": "api", "doc_count": 3}]
+                    }
+                });
+                Ok(quickwit_proto::SearchResponse {
+                    num_hits: 3,
+                    aggregation: Some(aggregation.to_string()),
+                    ..Default::default()
+                })
+            });
+        let sql_handler = sql_handler(mock_search_service);
+        let expected_response_json = json!({
+            "columns": ["service", "count(*)"],
+            "rows": [["api", 3]],
+            "num_hits": 3,
+        });
+        let resp = warp::test::request()
+            .path("/sql?query=SELECT%20service,count(*)%20FROM%20my-logs%20GROUP%20BY%20service")
+            .reply(&sql_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+
+        let resp = warp::test::request()
+            .method("POST")
+            .path("/sql")
+            .json(&json!({"query": "SELECT service, count(*) FROM my-logs GROUP BY service"}))
+            .reply(&sql_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let resp_json: JsonValue = serde_json::from_slice(resp.body())?;
+        assert_json_include!(actual: resp_json, expected: expected_response_json);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rest_sql_api_invalid_query() {
+        let sql_handler = sql_handler(MockSearchService::new());
+        let resp = warp::test::request()
+            .path("/sql?query=SELECT%20*")
+            .reply(&sql_handler)
+            .await;
+        assert_eq!(resp.status(), 400);
+        let body = String::from_utf8_lossy(resp.body());
+        assert!(body.contains("Invalid SQL query: Expected `FROM`, got end of statement."));
+    }
+
+    #[tokio::test]
+    async fn test_rest_sql_api_index_not_granted() {
+        let sql_handler = sql_handler(MockSearchService::new());
+        let principal = Principal {
+            name: "team-a".to_string(),
+            grants: vec!["search:team-a-*".parse().unwrap()],
+            tenant_scope_opt: None,
+        };
+        let resp = warp::test::request()
+            .path("/sql?query=SELECT%20*%20FROM%20team-b-logs")
+            .extension(principal)
+            .reply(&sql_handler)
+            .await;
+        assert_eq!(resp.status(), 403);
+        let body = String::from_utf8_lossy(resp.body());
+        assert!(body.contains("is not granted the `search` role on index `team-b-logs`"));
+    }
 }
@@ -26,8 +26,10 @@ use quickwit_cluster::create_cluster_for_test;
 use quickwit_common::uri::Uri;
 use quickwit_config::service::QuickwitService;
 use quickwit_metastore::{IndexMetadata, MockMetastore};
+use quickwit_storage::StorageUriResolver;
 use tokio::sync::{oneshot, watch};
 
+use crate::health_check_api::{ReadinessChecker, ReadinessReport};
 use crate::{check_cluster_configuration, node_readiness_reporting_task};
 
 #[tokio::test]
@@ -67,11 +69,20 @@ async fn test_readiness_updates() {
             Err(anyhow::anyhow!("Metastore not ready"))
         }
     });
+    let readiness_checker = ReadinessChecker::new(
+        cluster.clone(),
+        Arc::new(metastore),
+        StorageUriResolver::for_test(),
+        Uri::for_test("ram:///indexes"),
+        HashSet::from_iter([QuickwitService::Metastore]),
+    );
+    let (readiness_report_tx, readiness_report_rx) = watch::channel(ReadinessReport::default());
     let (grpc_readiness_trigger_tx, grpc_readiness_signal_rx) = oneshot::channel();
     let (rest_readiness_trigger_tx, rest_readiness_signal_rx) = oneshot::channel();
     tokio::spawn(node_readiness_reporting_task(
         cluster.clone(),
-        Arc::new(metastore),
+        readiness_checker,
+        readiness_report_tx,
         grpc_readiness_signal_rx,
         rest_readiness_signal_rx,
     ));
@@ -84,8 +95,10 @@ async fn test_readiness_updates() {
     metastore_readiness_tx.send(true).unwrap();
     tokio::time::sleep(Duration::from_millis(25)).await;
     assert!(cluster.is_self_node_ready().await);
+    assert!(readiness_report_rx.borrow().can_serve_requests());
 
     metastore_readiness_tx.send(false).unwrap();
     tokio::time::sleep(Duration::from_millis(25)).await;
     assert!(!cluster.is_self_node_ready().await);
+    assert!(!readiness_report_rx.borrow().can_serve_requests());
 }
@@ -0,0 +1,550 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use std::sync::Arc;
+
+use quickwit_common::uri::Uri;
+use quickwit_config::{AuthRole, DocMapping};
+use quickwit_metastore::{
+    IndexMetadata, ListSplitsQuery, Metastore, MetastoreError, Split, SplitState,
+};
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use warp::{Filter, Rejection};
+
+use crate::auth::{with_principal, Principal};
+use crate::format::extract_format_from_qs;
+use crate::json_api_response::make_json_api_response;
+use crate::with_arg;
+
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    paths(
+        list_index_summaries,
+        get_doc_mapping,
+        get_split_timeline,
+        list_source_checkpoints
+    ),
+    components(schemas(
+        IndexSummary,
+        SplitTimelineEntry,
+        SourceCheckpointSummary,
+        PartitionPosition
+    ))
+)]
+pub struct UiApi;
+
+/// Read-only routes giving the web UI an overview of the indexes, their splits, and their sources.
+pub fn ui_api_handlers(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    list_index_summaries_handler(metastore.clone())
+        .or(get_doc_mapping_handler(metastore.clone()))
+        .or(get_split_timeline_handler(metastore.clone()))
+        .or(list_source_checkpoints_handler(metastore))
+}
+
+/// Summary of an index displayed in the index browser.
+#[derive(Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+pub struct IndexSummary {
+    pub index_id: String,
+    #[schema(value_type = String)]
+    pub index_uri: Uri,
+    pub create_timestamp: i64,
+    pub num_sources: usize,
+    pub num_published_splits: usize,
+    pub num_published_docs: u64,
+    pub size_published_splits_in_bytes: u64,
+    pub timestamp_field_name: Option<String>,
+    pub min_timestamp: Option<i64>,
+    pub max_timestamp: Option<i64>,
+}
+
+impl IndexSummary {
+    fn new(index_metadata: &IndexMetadata, published_splits: &[Split]) -> Self {
+        let mut num_published_docs = 0;
+        let mut size_published_splits_in_bytes = 0;
+        let mut min_timestamp: Option<i64> = None;
+        let mut max_timestamp: Option<i64> = None;
+
+        for split in published_splits {
+            num_published_docs += split.split_metadata.num_docs as u64;
+            size_published_splits_in_bytes += split.split_metadata.footer_offsets.end;
+
+            if let Some(time_range) = &split.split_metadata.time_range {
+                min_timestamp = Some(
+                    min_timestamp.map_or(*time_range.start(), |min| min.min(*time_range.start())),
+                );
+                max_timestamp =
+                    Some(max_timestamp.map_or(*time_range.end(), |max| max.max(*time_range.end())));
+            }
+        }
+        let index_config = index_metadata.index_config();
+        Self {
+            index_id: index_config.index_id.clone(),
+            index_uri: index_config.index_uri.clone(),
+            create_timestamp: index_metadata.create_timestamp,
+            num_sources: index_metadata.sources.len(),
+            num_published_splits: published_splits.len(),
+            num_published_docs,
+            size_published_splits_in_bytes,
+            timestamp_field_name: index_config.doc_mapping.timestamp_field.clone(),
+            min_timestamp,
+            max_timestamp,
+        }
+    }
+}
+
+/// A split positioned on the timeline of its index: its time range against its size.
+#[derive(Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+pub struct SplitTimelineEntry {
+    pub split_id: String,
+    pub split_state: SplitState,
+    pub source_id: String,
+    pub node_id: String,
+    pub num_docs: usize,
+    pub size_in_bytes: u64,
+    pub time_range_start: Option<i64>,
+    pub time_range_end: Option<i64>,
+    pub create_timestamp: i64,
+    pub num_merge_ops: usize,
+}
+
+impl From<Split> for SplitTimelineEntry {
+    fn from(split: Split) -> Self {
+        let split_metadata = split.split_metadata;
+        Self {
+            split_state: split.split_state,
+            time_range_start: split_metadata
+                .time_range
+                .as_ref()
+                .map(|time_range| *time_range.start()),
+            time_range_end: split_metadata
+                .time_range
+                .as_ref()
+                .map(|time_range| *time_range.end()),
+            size_in_bytes: split_metadata.footer_offsets.end,
+            split_id: split_metadata.split_id,
+            source_id: split_metadata.source_id,
+            node_id: split_metadata.node_id,
+            num_docs: split_metadata.num_docs,
+            create_timestamp: split_metadata.create_timestamp,
+            num_merge_ops: split_metadata.num_merge_ops,
+        }
+    }
+}
+
+/// Checkpoint of a source, i.e. the last position indexed for each of its partitions.
+#[derive(Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+pub struct SourceCheckpointSummary {
+    pub source_id: String,
+    pub source_type: String,
+    pub enabled: bool,
+    pub partitions: Vec<PartitionPosition>,
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, utoipa::ToSchema)]
+pub struct PartitionPosition {
+    pub partition_id: String,
+    /// Empty when the partition is checkpointed at its beginning.
+    pub position: String,
+}
+
+fn list_index_summaries_handler(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("ui" / "indexes")
+        .and(warp::get())
+        .and(with_principal())
+        .and(with_arg(metastore))
+        .then(list_index_summaries)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "UI",
+    path = "/ui/indexes",
+    responses(
+        (status = 200, description = "Successfully fetched the index summaries.", body = [IndexSummary])
+    ),
+)]
+/// Lists the summaries of the indexes, sorted by index ID. Only the indexes the caller manages
+/// are listed.
+async fn list_index_summaries(
+    principal_opt: Option<Principal>,
+    metastore: Arc<dyn Metastore>,
+) -> Result<Vec<IndexSummary>, MetastoreError> {
+    info!("list-index-summaries");
+    let mut indexes_metadatas = metastore.list_indexes_metadatas().await?;
+
+    if let Some(principal) = principal_opt {
+        indexes_metadatas.retain(|index_metadata| {
+            principal.is_granted(AuthRole::Admin, index_metadata.index_id())
+        });
+    }
+    indexes_metadatas.sort_by(|left, right| left.index_id().cmp(right.index_id()));
+
+    let mut index_summaries = Vec::with_capacity(indexes_metadatas.len());
+    for index_metadata in indexes_metadatas {
+        let query = ListSplitsQuery::for_index(index_metadata.index_uid.clone())
+            .with_split_state(SplitState::Published);
+        let published_splits = metastore.list_splits(query).await?;
+        index_summaries.push(IndexSummary::new(&index_metadata, &published_splits));
+    }
+    Ok(index_summaries)
+}
+
+fn get_doc_mapping_handler(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("ui" / "indexes" / String / "doc-mapping")
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(get_doc_mapping)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "UI",
+    path = "/ui/indexes/{index_id}/doc-mapping",
+    responses(
+        (status = 200, description = "Successfully fetched the doc mapping.", body = DocMapping)
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to get the doc mapping of."),
+    )
+)]
+/// Gets the doc mapping of an index.
+async fn get_doc_mapping(
+    index_id: String,
+    metastore: Arc<dyn Metastore>,
+) -> Result<DocMapping, MetastoreError> {
+    info!(index_id = %index_id, "get-doc-mapping");
+    let index_metadata = metastore.index_metadata(&index_id).await?;
+    Ok(index_metadata.into_index_config().doc_mapping)
+}
+
+fn get_split_timeline_handler(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("ui" / "indexes" / String / "split-timeline")
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(get_split_timeline)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "UI",
+    path = "/ui/indexes/{index_id}/split-timeline",
+    responses(
+        (status = 200, description = "Successfully fetched the split timeline.", body = [SplitTimelineEntry])
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to get the split timeline of."),
+    )
+)]
+/// Gets the splits of an index sorted by the start of their time range. Splits without time
+/// range come first.
+async fn get_split_timeline(
+    index_id: String,
+    metastore: Arc<dyn Metastore>,
+) -> Result<Vec<SplitTimelineEntry>, MetastoreError> {
+    info!(index_id = %index_id, "get-split-timeline");
+    let index_uid = metastore.index_metadata(&index_id).await?.index_uid;
+    let query = ListSplitsQuery::for_index(index_uid);
+    let mut split_timeline: Vec<SplitTimelineEntry> = metastore
+        .list_splits(query)
+        .await?
+        .into_iter()
+        .map(SplitTimelineEntry::from)
+        .collect();
+    split_timeline.sort_by(|left, right| {
+        (left.time_range_start, &left.split_id).cmp(&(right.time_range_start, &right.split_id))
+    });
+    Ok(split_timeline)
+}
+
+fn list_source_checkpoints_handler(
+    metastore: Arc<dyn Metastore>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    warp::path!("ui" / "indexes" / String / "checkpoints")
+        .and(warp::get())
+        .and(with_arg(metastore))
+        .then(list_source_checkpoints)
+        .and(extract_format_from_qs())
+        .map(make_json_api_response)
+}
+
+#[utoipa::path(
+    get,
+    tag = "UI",
+    path = "/ui/indexes/{index_id}/checkpoints",
+    responses(
+        (status = 200, description = "Successfully fetched the source checkpoints.", body = [SourceCheckpointSummary])
+    ),
+    params(
+        ("index_id" = String, Path, description = "The index ID to get the source checkpoints of."),
+    )
+)]
+/// Lists the checkpoints of the sources of an index, sorted by source ID.
+async fn list_source_checkpoints(
+    index_id: String,
+    metastore: Arc<dyn Metastore>,
+) -> Result<Vec<SourceCheckpointSummary>, MetastoreError> {
+    info!(index_id = %index_id, "list-source-checkpoints");
+    let index_metadata = metastore.index_metadata(&index_id).await?;
+    let mut source_checkpoints: Vec<SourceCheckpointSummary> = index_metadata
+        .sources
+        .values()
+        .map(|source_config| {
+            let partitions = index_metadata
+                .checkpoint
+                .source_checkpoint(&source_config.source_id)
+                .map(|source_checkpoint| {
+                    source_checkpoint
+                        .iter()
+                        .map(|(partition_id, position)| PartitionPosition {
+                            partition_id: partition_id.0.to_string(),
+                            position: position.as_str().to_string(),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            SourceCheckpointSummary {
+                source_id: source_config.source_id.clone(),
+                source_type: source_config.source_type().to_string(),
+                enabled: source_config.enabled,
+                partitions,
+            }
+        })
+        .collect();
+    source_checkpoints.sort_by(|left, right| left.source_id.cmp(&right.source_id));
+    Ok(source_checkpoints)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use quickwit_config::{SourceConfig, SourceParams};
+    use quickwit_indexing::mock_split;
+    use quickwit_metastore::checkpoint::{IndexCheckpoint, SourceCheckpoint};
+    use quickwit_metastore::MockMetastore;
+    use serde_json::Value as JsonValue;
+
+    use super::*;
+    use crate::recover_fn;
+
+    #[tokio::test]
+    async fn test_list_index_summaries() {
+        let mut metastore = MockMetastore::new();
+        metastore.expect_list_indexes_metadatas().return_once(|| {
+            Ok(vec![
+                IndexMetadata::for_test("test-index-2", "ram:///indexes/test-index-2"),
+                IndexMetadata::for_test("test-index-1", "ram:///indexes/test-index-1"),
+            ])
+        });
+        metastore
+            .expect_list_splits()
+            .times(2)
+            .returning(|list_splits_query: ListSplitsQuery| {
+                if list_splits_query.index_uid.index_id() == "test-index-1" {
+                    let mut split_2 = mock_split("split_2");
+                    split_2.split_metadata.time_range = Some(100_000..=200_000);
+                    return Ok(vec![mock_split("split_1"), split_2]);
+                }
+                Ok(Vec::new())
+            });
+        let ui_api_handler = super::ui_api_handlers(Arc::new(metastore)).recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/ui/indexes")
+            .reply(&ui_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let index_summaries: Vec<IndexSummary> = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(index_summaries.len(), 2);
+        assert_eq!(index_summaries[0].index_id, "test-index-1");
+        assert_eq!(index_summaries[0].num_published_splits, 2);
+        assert_eq!(index_summaries[0].num_published_docs, 20);
+        assert_eq!(index_summaries[0].size_published_splits_in_bytes, 1600);
+        assert_eq!(index_summaries[0].min_timestamp, Some(100_000));
+        assert_eq!(index_summaries[0].max_timestamp, Some(200_000));
+        assert_eq!(index_summaries[1].index_id, "test-index-2");
+        assert_eq!(index_summaries[1].num_published_splits, 0);
+        assert_eq!(index_summaries[1].min_timestamp, None);
+    }
+
+    #[tokio::test]
+    async fn test_get_doc_mapping() {
+        let mut metastore = MockMetastore::new();
+        metastore
+            .expect_index_metadata()
+            .return_once(|index_id: &str| {
+                if index_id == "test-index" {
+                    return Ok(IndexMetadata::for_test(
+                        "test-index",
+                        "ram:///indexes/test-index",
+                    ));
+                }
+                Err(MetastoreError::IndexDoesNotExist {
+                    index_id: index_id.to_string(),
+                })
+            });
+        let ui_api_handler = super::ui_api_handlers(Arc::new(metastore)).recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/ui/indexes/test-index/doc-mapping")
+            .reply(&ui_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let doc_mapping_json: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(doc_mapping_json["timestamp_field"], "timestamp");
+        assert!(doc_mapping_json["field_mappings"].as_array().unwrap().len() > 1);
+    }
+
+    #[tokio::test]
+    async fn test_get_doc_mapping_of_missing_index() {
+        let mut metastore = MockMetastore::new();
+        metastore
+            .expect_index_metadata()
+            .return_once(|index_id: &str| {
+                Err(MetastoreError::IndexDoesNotExist {
+                    index_id: index_id.to_string(),
+                })
+            });
+        let ui_api_handler = super::ui_api_handlers(Arc::new(metastore)).recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/ui/indexes/missing-index/doc-mapping")
+            .reply(&ui_api_handler)
+            .await;
+        assert_eq!(resp.status(), 404);
+    }
+
+    #[tokio::test]
+    async fn test_get_split_timeline() {
+        let mut metastore = MockMetastore::new();
+        metastore
+            .expect_index_metadata()
+            .return_once(|_index_id: &str| {
+                Ok(IndexMetadata::for_test(
+                    "test-index",
+                    "ram:///indexes/test-index",
+                ))
+            });
+        metastore
+            .expect_list_splits()
+            .return_once(|_list_splits_query: ListSplitsQuery| {
+                let mut split_1 = mock_split("split_1");
+                split_1.split_metadata.time_range = Some(200_000..=300_000);
+                let mut split_2 = mock_split("split_2");
+                split_2.split_state = SplitState::MarkedForDeletion;
+                split_2.split_metadata.num_merge_ops = 1;
+                let mut split_3 = mock_split("split_3");
+                split_3.split_metadata.time_range = None;
+                Ok(vec![split_1, split_2, split_3])
+            });
+        let ui_api_handler = super::ui_api_handlers(Arc::new(metastore)).recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/ui/indexes/test-index/split-timeline")
+            .reply(&ui_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let split_timeline: Vec<SplitTimelineEntry> = serde_json::from_slice(resp.body()).unwrap();
+        let split_ids: Vec<&str> = split_timeline
+            .iter()
+            .map(|entry| entry.split_id.as_str())
+            .collect();
+        assert_eq!(split_ids, ["split_3", "split_2", "split_1"]);
+        assert_eq!(split_timeline[0].time_range_start, None);
+        assert_eq!(split_timeline[1].split_state, SplitState::MarkedForDeletion);
+        assert_eq!(split_timeline[1].time_range_start, Some(121_000));
+        assert_eq!(split_timeline[1].time_range_end, Some(130_198));
+        assert_eq!(split_timeline[1].size_in_bytes, 800);
+        assert_eq!(split_timeline[1].num_merge_ops, 1);
+    }
+
+    #[tokio::test]
+    async fn test_list_source_checkpoints() {
+        let mut metastore = MockMetastore::new();
+        metastore
+            .expect_index_metadata()
+            .return_once(|_index_id: &str| {
+                let mut index_metadata =
+                    IndexMetadata::for_test("test-index", "ram:///indexes/test-index");
+                for source_id in ["source-2", "source-1"] {
+                    let mut source_config = SourceConfig::for_test(source_id, SourceParams::void());
+                    source_config.enabled = source_id == "source-1";
+                    index_metadata
+                        .sources
+                        .insert(source_id.to_string(), source_config);
+                }
+                let source_checkpoint: SourceCheckpoint =
+                    [(0u64.into(), 42u64.into()), (1u64.into(), 1337u64.into())]
+                        .into_iter()
+                        .collect();
+                index_metadata.checkpoint = IndexCheckpoint::from(BTreeMap::from([(
+                    "source-1".to_string(),
+                    source_checkpoint,
+                )]));
+                Ok(index_metadata)
+            });
+        let ui_api_handler = super::ui_api_handlers(Arc::new(metastore)).recover(recover_fn);
+        let resp = warp::test::request()
+            .path("/ui/indexes/test-index/checkpoints")
+            .reply(&ui_api_handler)
+            .await;
+        assert_eq!(resp.status(), 200);
+
+        let source_checkpoints: Vec<SourceCheckpointSummary> =
+            serde_json::from_slice(resp.body()).unwrap();
+        let expected_source_checkpoints = vec![
+            SourceCheckpointSummary {
+                source_id: "source-1".to_string(),
+                source_type: "void".to_string(),
+                enabled: true,
+                partitions: vec![
+                    PartitionPosition {
+                        partition_id: "00000000000000000000".to_string(),
+                        position: "00000000000000000042".to_string(),
+                    },
+                    PartitionPosition {
+                        partition_id: "00000000000000000001".to_string(),
+                        position: "00000000000000001337".to_string(),
+                    },
+                ],
+            },
+            SourceCheckpointSummary {
+                source_id: "source-2".to_string(),
+                source_type: "void".to_string(),
+                enabled: false,
+                partitions: Vec::new(),
+            },
+        ];
+        assert_eq!(source_checkpoints, expected_source_checkpoints);
+    }
+}
@@ -0,0 +1,340 @@
+// Copyright (C) 2023 Quickwit, Inc.
+//
+// Quickwit is offered under the AGPL v3.0 and as commercial software.
+// For commercial licensing, contact us at hello@quickwit.io.
+//
+// AGPL:
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE. See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program. If not, see <http://www.gnu.org/licenses/>.
+
+use bytes::Bytes;
+use hyper::header::{CONTENT_TYPE, RETRY_AFTER};
+use hyper::http::{HeaderValue, StatusCode};
+use quickwit_jaeger::{ZipkinService, ZipkinTraceQuery};
+use quickwit_opentelemetry::otlp::{
+    extract_retry_after, parse_zipkin_json_spans, OtlpGrpcTraceService, OtlpProtocol, TraceId,
+};
+use quickwit_proto::tonic;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use warp::reply::Response;
+use warp::{Filter, Rejection, Reply};
+
+use crate::require;
+
+const CONTENT_LENGTH_LIMIT: u64 = 10 * 1024 * 1024; // 10MiB
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpanNamesQuery {
+    service_name: String,
+}
+
+/// Exposes the span ingestion endpoint of the Zipkin v2 API, which accepts JSON-encoded spans,
+/// and the subset of its query endpoints used by Zipkin UI, under `/zipkin/api/v2`.
+pub(crate) fn zipkin_api_handlers(
+    otlp_trace_service_opt: Option<OtlpGrpcTraceService>,
+    zipkin_service_opt: Option<ZipkinService>,
+) -> impl Filter<Extract = (impl warp::Reply,), Error = Rejection> + Clone {
+    zipkin_ingest_spans_handler(otlp_trace_service_opt)
+        .or(zipkin_services_handler(zipkin_service_opt.clone()))
+        .or(zipkin_span_names_handler(zipkin_service_opt.clone()))
+        .or(zipkin_remote_services_handler(zipkin_service_opt.clone()))
+        .or(zipkin_traces_handler(zipkin_service_opt.clone()))
+        .or(zipkin_trace_handler(zipkin_service_opt))
+}
+
+fn zipkin_ingest_spans_handler(
+    otlp_trace_service_opt: Option<OtlpGrpcTraceService>,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    warp::path!("zipkin" / "api" / "v2" / "spans")
+        .and(warp::post())
+        .and(require(otlp_trace_service_opt))
+        .and(warp::header::optional::<String>(CONTENT_TYPE.as_str()))
+        .and(warp::body::content_length_limit(CONTENT_LENGTH_LIMIT))
+        .and(warp::body::bytes())
+        .then(zipkin_ingest_spans)
+}
+
+fn zipkin_services_handler(
+    zipkin_service_opt: Option<ZipkinService>,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    warp::path!("zipkin" / "api" / "v2" / "services")
+        .and(warp::get())
+        .and(require(zipkin_service_opt))
+        .then(|zipkin_service: ZipkinService| async move {
+            make_json_response(zipkin_service.get_services().await)
+        })
+}
+
+fn zipkin_span_names_handler(
+    zipkin_service_opt: Option<ZipkinService>,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    warp::path!("zipkin" / "api" / "v2" / "spans")
+        .and(warp::get())
+        .and(require(zipkin_service_opt))
+        .and(warp::query::<SpanNamesQuery>())
+        .then(
+            |zipkin_service: ZipkinService, query: SpanNamesQuery| async move {
+                make_json_response(zipkin_service.get_span_names(&query.service_name).await)
+            },
+        )
+}
+
+// Remote service names are not indexed, so Zipkin UI is told that there are none.
+fn zipkin_remote_services_handler(
+    zipkin_service_opt: Option<ZipkinService>,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    warp::path!("zipkin" / "api" / "v2" / "remoteServices")
+        .and(warp::get())
+        .and(require(zipkin_service_opt))
+        .map(|_zipkin_service: ZipkinService| make_json_response::<Vec<String>>(Ok(Vec::new())))
+}
+
+fn zipkin_traces_handler(
+    zipkin_service_opt: Option<ZipkinService>,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    warp::path!("zipkin" / "api" / "v2" / "traces")
+        .and(warp::get())
+        .and(require(zipkin_service_opt))
+        .and(warp::query::<ZipkinTraceQuery>())
+        .then(
+            |zipkin_service: ZipkinService, trace_query: ZipkinTraceQuery| async move {
+                make_json_response(zipkin_service.find_traces(trace_query).await)
+            },
+        )
+}
+
+fn zipkin_trace_handler(
+    zipkin_service_opt: Option<ZipkinService>,
+) -> impl Filter<Extract = (Response,), Error = Rejection> + Clone {
+    warp::path!("zipkin" / "api" / "v2" / "trace" / String)
+        .and(warp::get())
+        .and(require(zipkin_service_opt))
+        .then(zipkin_get_trace)
+}
+
+async fn zipkin_ingest_spans(
+    otlp_trace_service: OtlpGrpcTraceService,
+    content_type_opt: Option<String>,
+    body: Bytes,
+) -> Response {
+    // Zipkin reporters also support a Protobuf encoding, but JSON is the default one.
+    let is_json = match content_type_opt.as_deref() {
+        Some(content_type) => {
+            content_type.split(';').next().unwrap_or_default().trim() == "application/json"
+        }
+        None => true,
+    };
+    if !is_json {
+        return make_error_response(
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            "Unsupported content-type header. Only application/json is supported.".to_string(),
+        );
+    }
+    let max_payload_size = otlp_trace_service.max_payload_size();
+
+    if body.len() as u64 > max_payload_size {
+        return make_error_response(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("Payload exceeds the limit of {max_payload_size} bytes."),
+        );
+    }
+    let request = match parse_zipkin_json_spans(&body) {
+        Ok(request) => request,
+        Err(error) => {
+            return make_error_response(
+                StatusCode::BAD_REQUEST,
+                format!("Failed to decode payload: {error}"),
+            )
+        }
+    };
+    let export_result = otlp_trace_service
+        .export_with_protocol(request, OtlpProtocol::HttpZipkinJson, None)
+        .await;
+    match export_result {
+        Ok(_) => StatusCode::ACCEPTED.into_response(),
+        Err(status) => {
+            let mut response =
+                make_error_response(status_code(&status), status.message().to_string());
+
+            if let Some(retry_after) = extract_retry_after(&status) {
+                response
+                    .headers_mut()
+                    .insert(RETRY_AFTER, HeaderValue::from(retry_after.as_secs()));
+            }
+            response
+        }
+    }
+}
+
+async fn zipkin_get_trace(trace_id: String, zipkin_service: ZipkinService) -> Response {
+    let trace_id = match parse_zipkin_trace_id(&trace_id) {
+        Some(trace_id) => trace_id,
+        None => {
+            return make_error_response(
+                StatusCode::BAD_REQUEST,
+                format!("Invalid trace ID `{trace_id}`."),
+            )
+        }
+    };
+    match zipkin_service.get_trace(trace_id).await {
+        Ok(spans) if spans.is_empty() => make_error_response(
+            StatusCode::NOT_FOUND,
+            format!("Trace `{}` not found.", trace_id.to_hex()),
+        ),
+        spans_result => make_json_response(spans_result),
+    }
+}
+
+/// Parses a Zipkin trace ID, which is 64 or 128 bits long and hex-encoded.
+fn parse_zipkin_trace_id(trace_id: &str) -> Option<TraceId> {
+    if trace_id.len() > TraceId::HEX_LENGTH
+        || !trace_id.bytes().all(|byte| byte.is_ascii_hexdigit())
+    {
+        return None;
+    }
+    format!("{trace_id:0>32}").parse().ok()
+}
+
+fn status_code(status: &tonic::Status) -> StatusCode {
+    match status.code() {
+        tonic::Code::InvalidArgument => StatusCode::BAD_REQUEST,
+        tonic::Code::NotFound => StatusCode::NOT_FOUND,
+        tonic::Code::ResourceExhausted if extract_retry_after(status).is_some() => {
+            StatusCode::TOO_MANY_REQUESTS
+        }
+        tonic::Code::ResourceExhausted => StatusCode::PAYLOAD_TOO_LARGE,
+        tonic::Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        _ => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+fn make_json_response<T: Serialize>(result: Result<T, tonic::Status>) -> Response {
+    match result {
+        Ok(value) => warp::reply::json(&value).into_response(),
+        Err(status) => make_error_response(status_code(&status), status.message().to_string()),
+    }
+}
+
+fn make_error_response(status_code: StatusCode, message: String) -> Response {
+    let body = json!({ "message": message });
+    warp::reply::with_status(warp::reply::json(&body), status_code).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use quickwit_config::{IngestApiConfig, JaegerConfig};
+    use quickwit_opentelemetry::otlp::OTEL_TRACE_INDEX_ID;
+    use quickwit_search::MockSearchService;
+    use serde_json::Value as JsonValue;
+
+    use super::*;
+    use crate::ingest_api::setup_ingest_service;
+
+    #[test]
+    fn test_parse_zipkin_trace_id() {
+        assert_eq!(
+            parse_zipkin_trace_id("0102030405060708"),
+            Some(TraceId::new([
+                0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4, 5, 6, 7, 8
+            ]))
+        );
+        assert_eq!(
+            parse_zipkin_trace_id("0102030405060708090a0b0c0d0e0f10"),
+            Some(TraceId::new([
+                1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16
+            ]))
+        );
+        assert!(parse_zipkin_trace_id("AQIDBAUGBwgJCgsMDQ4PEA==").is_none());
+        assert!(parse_zipkin_trace_id("0102030405060708090a0b0c0d0e0f1011").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_zipkin_ingest_spans() {
+        let (universe, _temp_dir, ingest_service, _) =
+            setup_ingest_service(&[OTEL_TRACE_INDEX_ID], &IngestApiConfig::default()).await;
+        let zipkin_api_handlers =
+            zipkin_api_handlers(Some(OtlpGrpcTraceService::new(ingest_service)), None);
+        let payload = r#"[{
+            "traceId": "0102030405060708",
+            "id": "0102030405060708",
+            "name": "get /api",
+            "kind": "SERVER",
+            "timestamp": 1000000,
+            "duration": 1000,
+            "localEndpoint": {"serviceName": "frontend"}
+        }]"#;
+        let resp = warp::test::request()
+            .path("/zipkin/api/v2/spans")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(payload)
+            .reply(&zipkin_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 202);
+
+        let resp = warp::test::request()
+            .path("/zipkin/api/v2/spans")
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(r#"[{"traceId": "not-hex", "id": "0102030405060708"}]"#)
+            .reply(&zipkin_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 400);
+
+        let resp = warp::test::request()
+            .path("/zipkin/api/v2/spans")
+            .method("POST")
+            .header("content-type", "application/x-protobuf")
+            .body(payload)
+            .reply(&zipkin_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 415);
+        universe.assert_quit().await;
+    }
+
+    #[tokio::test]
+    async fn test_zipkin_query_api() {
+        let mut mock_search_service = MockSearchService::new();
+        mock_search_service
+            .expect_root_search()
+            .withf(|search_request| search_request.index_id == OTEL_TRACE_INDEX_ID)
+            .returning(|_| Ok(Default::default()));
+        let zipkin_service =
+            ZipkinService::new(JaegerConfig::default(), Arc::new(mock_search_service));
+        let zipkin_api_handlers = zipkin_api_handlers(None, Some(zipkin_service));
+
+        let resp = warp::test::request()
+            .path("/zipkin/api/v2/trace/0102030405060708")
+            .reply(&zipkin_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 404);
+
+        let resp = warp::test::request()
+            .path("/zipkin/api/v2/remoteServices?serviceName=frontend")
+            .reply(&zipkin_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 200);
+        let remote_services: JsonValue = serde_json::from_slice(resp.body()).unwrap();
+        assert_eq!(remote_services, json!([]));
+
+        let resp = warp::test::request()
+            .path("/zipkin/api/v2/trace/not-hex")
+            .reply(&zipkin_api_handlers)
+            .await;
+        assert_eq!(resp.status(), 400);
+    }
+}
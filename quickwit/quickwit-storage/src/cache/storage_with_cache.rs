@@ -26,7 +26,7 @@ use quickwit_common::uri::Uri;
 
 use crate::cache::Cache;
 use crate::storage::{BulkDeleteError, SendableAsync};
-use crate::{OwnedBytes, Storage, StorageResult};
+use crate::{FileMetadata, OwnedBytes, Storage, StorageResult};
 
 /// Use with care, StorageWithCache is read-only.
 pub struct StorageWithCache {
@@ -90,6 +90,10 @@ impl Storage for StorageWithCache {
         self.storage.file_num_bytes(path).await
     }
 
+    async fn list_files_metadata(&self) -> StorageResult<Vec<FileMetadata>> {
+        self.storage.list_files_metadata().await
+    }
+
     fn uri(&self) -> &Uri {
         self.storage.uri()
     }
@@ -30,7 +30,7 @@ use quickwit_common::uri::Uri;
 use tantivy::directory::OwnedBytes;
 
 use crate::storage::{BulkDeleteError, SendableAsync};
-use crate::{Storage, StorageResult};
+use crate::{FileMetadata, Storage, StorageResult};
 
 /// The AsyncDebouncer debounces inflight Futures, so that concurrent async request to the same data
 /// source can be deduplicated.
@@ -182,6 +182,10 @@ impl<T: Storage> Storage for DebouncedStorage<T> {
     async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64> {
         self.underlying.file_num_bytes(path).await
     }
+
+    async fn list_files_metadata(&self) -> StorageResult<Vec<FileMetadata>> {
+        self.underlying.list_files_metadata().await
+    }
 }
 
 #[cfg(test)]
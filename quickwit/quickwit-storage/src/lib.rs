@@ -39,7 +39,7 @@ pub(crate) use debouncer::DebouncedStorage;
 
 pub use self::metrics::STORAGE_METRICS;
 pub use self::payload::PutPayload;
-pub use self::storage::Storage;
+pub use self::storage::{FileMetadata, Storage};
 
 mod bundle_storage;
 mod error;
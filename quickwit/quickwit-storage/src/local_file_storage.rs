@@ -23,6 +23,7 @@ use std::io::{ErrorKind, SeekFrom};
 use std::ops::Range;
 use std::path::{Component, Path, PathBuf};
 use std::sync::Arc;
+use std::time::UNIX_EPOCH;
 
 use async_trait::async_trait;
 use futures::future::{BoxFuture, FutureExt};
@@ -35,8 +36,8 @@ use tracing::warn;
 
 use crate::storage::{BulkDeleteError, DeleteFailure, SendableAsync};
 use crate::{
-    DebouncedStorage, OwnedBytes, Storage, StorageError, StorageErrorKind, StorageFactory,
-    StorageResolverError, StorageResult,
+    DebouncedStorage, FileMetadata, OwnedBytes, Storage, StorageError, StorageErrorKind,
+    StorageFactory, StorageResolverError, StorageResult,
 };
 
 /// File system compatible storage implementation.
@@ -164,6 +165,43 @@ fn delete_all_dirs_if_empty<'a>(
     .boxed()
 }
 
+/// Lists the files of the `{root}/{path}` directory and of its subdirectories.
+fn list_files_metadata_in_dir<'a>(
+    root: &'a Path,
+    path: &'a Path,
+    files_metadata: &'a mut Vec<FileMetadata>,
+) -> BoxFuture<'a, std::io::Result<()>> {
+    async move {
+        let mut dir_entries = match fs::read_dir(root.join(path)).await {
+            Ok(dir_entries) => dir_entries,
+            // The directory may have been deleted by a concurrent task.
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        while let Some(dir_entry) = dir_entries.next_entry().await? {
+            let relative_path = path.join(dir_entry.file_name());
+            let metadata = dir_entry.metadata().await?;
+
+            if metadata.is_dir() {
+                list_files_metadata_in_dir(root, &relative_path, files_metadata).await?;
+            } else if metadata.is_file() {
+                let last_modified_timestamp_opt = metadata
+                    .modified()
+                    .ok()
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map(|duration| duration.as_secs() as i64);
+                files_metadata.push(FileMetadata {
+                    path: relative_path,
+                    num_bytes: metadata.len(),
+                    last_modified_timestamp_opt,
+                });
+            }
+        }
+        Ok(())
+    }
+    .boxed()
+}
+
 #[async_trait]
 impl Storage for LocalFileStorage {
     async fn check_connectivity(&self) -> anyhow::Result<()> {
@@ -309,6 +347,12 @@ impl Storage for LocalFileStorage {
         &self.uri
     }
 
+    async fn list_files_metadata(&self) -> StorageResult<Vec<FileMetadata>> {
+        let mut files_metadata = Vec::new();
+        list_files_metadata_in_dir(&self.root, Path::new(""), &mut files_metadata).await?;
+        Ok(files_metadata)
+    }
+
     async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64> {
         let full_path = self.full_path(path)?;
         match fs::metadata(full_path).await {
@@ -49,8 +49,8 @@ use tracing::{instrument, warn};
 use crate::debouncer::DebouncedStorage;
 use crate::storage::{BulkDeleteError, DeleteFailure, SendableAsync};
 use crate::{
-    MultiPartPolicy, PutPayload, Storage, StorageError, StorageErrorKind, StorageFactory,
-    StorageResolverError, StorageResult, STORAGE_METRICS,
+    FileMetadata, MultiPartPolicy, PutPayload, Storage, StorageError, StorageErrorKind,
+    StorageFactory, StorageResolverError, StorageResult, STORAGE_METRICS,
 };
 
 /// Azure object storage URI resolver.
@@ -431,6 +431,40 @@ impl Storage for AzureBlobStorage {
         }
     }
 
+    async fn list_files_metadata(&self) -> StorageResult<Vec<FileMetadata>> {
+        // The trailing slash prevents listing the blobs of sibling prefixes, for instance
+        // `indexes/my-index-1` when listing `indexes/my-index`.
+        let mut blob_prefix = self.blob_name(Path::new(""));
+        if !blob_prefix.is_empty() && !blob_prefix.ends_with('/') {
+            blob_prefix.push('/');
+        }
+        let mut files_metadata = Vec::new();
+        let mut list_blobs_stream = self
+            .container_client
+            .list_blobs()
+            .prefix(blob_prefix)
+            .into_stream();
+
+        while let Some(list_blobs_result) = list_blobs_stream.next().await {
+            let list_blobs_response = list_blobs_result.map_err(AzureErrorWrapper::from)?;
+
+            for blob in list_blobs_response.blobs.blobs {
+                let path = Path::new(&blob.name)
+                    .strip_prefix(&self.prefix)
+                    .expect("The blobs should be listed under the storage prefix.")
+                    .to_path_buf();
+                files_metadata.push(FileMetadata {
+                    path,
+                    num_bytes: blob.properties.content_length,
+                    last_modified_timestamp_opt: Some(
+                        blob.properties.last_modified.unix_timestamp(),
+                    ),
+                });
+            }
+        }
+        Ok(files_metadata)
+    }
+
     fn uri(&self) -> &Uri {
         &self.uri
     }
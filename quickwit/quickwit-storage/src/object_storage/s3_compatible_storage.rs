@@ -25,6 +25,7 @@ use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
 use base64::prelude::{Engine, BASE64_STANDARD};
+use chrono::DateTime;
 use futures::{stream, StreamExt};
 use once_cell::sync::OnceCell;
 use quickwit_aws::error::RusotoErrorWrapper;
@@ -48,8 +49,8 @@ use tracing::{instrument, warn};
 use crate::object_storage::MultiPartPolicy;
 use crate::storage::{BulkDeleteError, DeleteFailure, SendableAsync};
 use crate::{
-    OwnedBytes, Storage, StorageError, StorageErrorKind, StorageResolverError, StorageResult,
-    STORAGE_METRICS,
+    FileMetadata, OwnedBytes, Storage, StorageError, StorageErrorKind, StorageResolverError,
+    StorageResult, STORAGE_METRICS,
 };
 
 /// S3 Compatible object storage implementation.
@@ -736,6 +737,58 @@ impl Storage for S3CompatibleObjectStorage {
         }
     }
 
+    async fn list_files_metadata(&self) -> StorageResult<Vec<FileMetadata>> {
+        // The trailing slash prevents listing the objects of sibling prefixes, for instance
+        // `indexes/my-index-1` when listing `indexes/my-index`.
+        let mut key_prefix = self.key(Path::new(""));
+        if !key_prefix.is_empty() && !key_prefix.ends_with('/') {
+            key_prefix.push('/');
+        }
+        let mut files_metadata = Vec::new();
+        let mut continuation_token_opt: Option<String> = None;
+
+        loop {
+            let list_objects_req = ListObjectsV2Request {
+                bucket: self.bucket.clone(),
+                prefix: Some(key_prefix.clone()),
+                continuation_token: continuation_token_opt.take(),
+                ..Default::default()
+            };
+            let list_objects_output = retry(&self.retry_params, || async {
+                self.s3_client
+                    .list_objects_v2(list_objects_req.clone())
+                    .await
+                    .map_err(RusotoErrorWrapper::from)
+            })
+            .await?;
+
+            for object in list_objects_output.contents.unwrap_or_default() {
+                let key = match object.key {
+                    Some(key) => key,
+                    None => continue,
+                };
+                let last_modified_timestamp_opt = object
+                    .last_modified
+                    .and_then(|last_modified| DateTime::parse_from_rfc3339(&last_modified).ok())
+                    .map(|last_modified| last_modified.timestamp());
+                files_metadata.push(FileMetadata {
+                    path: self.relative_path(&key),
+                    num_bytes: object.size.unwrap_or(0).max(0) as u64,
+                    last_modified_timestamp_opt,
+                });
+            }
+            if list_objects_output.is_truncated != Some(true) {
+                break;
+            }
+            continuation_token_opt = list_objects_output.next_continuation_token;
+
+            if continuation_token_opt.is_none() {
+                break;
+            }
+        }
+        Ok(files_metadata)
+    }
+
     fn uri(&self) -> &Uri {
         &self.uri
     }
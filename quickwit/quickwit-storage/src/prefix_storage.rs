@@ -25,7 +25,7 @@ use async_trait::async_trait;
 use quickwit_common::uri::Uri;
 
 use crate::storage::{BulkDeleteError, SendableAsync};
-use crate::{OwnedBytes, Storage};
+use crate::{FileMetadata, OwnedBytes, Storage};
 
 /// This storage acts as a proxy to another storage that simply modifies each API call
 /// by preceding each path with a given a prefix.
@@ -98,6 +98,23 @@ impl Storage for PrefixStorage {
     async fn file_num_bytes(&self, path: &Path) -> crate::StorageResult<u64> {
         self.storage.file_num_bytes(&self.prefix.join(path)).await
     }
+
+    async fn list_files_metadata(&self) -> crate::StorageResult<Vec<FileMetadata>> {
+        let files_metadata = self
+            .storage
+            .list_files_metadata()
+            .await?
+            .into_iter()
+            .filter_map(|file_metadata| {
+                let path = file_metadata.path.strip_prefix(&self.prefix).ok()?;
+                Some(FileMetadata {
+                    path: path.to_path_buf(),
+                    ..file_metadata
+                })
+            })
+            .collect();
+        Ok(files_metadata)
+    }
 }
 
 /// Creates a [`PrefixStorage`] using an underlying storage and a prefix.
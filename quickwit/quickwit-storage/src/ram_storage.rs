@@ -31,7 +31,8 @@ use tokio::sync::RwLock;
 use crate::prefix_storage::add_prefix_to_storage;
 use crate::storage::{BulkDeleteError, SendableAsync};
 use crate::{
-    OwnedBytes, Storage, StorageErrorKind, StorageFactory, StorageResolverError, StorageResult,
+    FileMetadata, OwnedBytes, Storage, StorageErrorKind, StorageFactory, StorageResolverError,
+    StorageResult,
 };
 
 /// In Ram implementation of quickwit's storage.
@@ -136,6 +137,22 @@ impl Storage for RamStorage {
         Ok(payload_bytes)
     }
 
+    async fn list_files_metadata(&self) -> StorageResult<Vec<FileMetadata>> {
+        // The RAM storage does not keep track of the modification time of its files.
+        let files_metadata = self
+            .files
+            .read()
+            .await
+            .iter()
+            .map(|(path, payload_bytes)| FileMetadata {
+                path: path.clone(),
+                num_bytes: payload_bytes.len() as u64,
+                last_modified_timestamp_opt: None,
+            })
+            .collect();
+        Ok(files_metadata)
+    }
+
     fn uri(&self) -> &Uri {
         &self.uri
     }
@@ -123,10 +123,31 @@ pub trait Storage: Send + Sync + 'static {
     /// Returns a file size.
     async fn file_num_bytes(&self, path: &Path) -> StorageResult<u64>;
 
+    /// Lists all the files of the storage, including the ones nested in "subdirectories".
+    ///
+    /// Storages that cannot be listed return an error.
+    async fn list_files_metadata(&self) -> StorageResult<Vec<FileMetadata>> {
+        Err(StorageErrorKind::InternalError.with_error(anyhow::anyhow!(
+            "Storage `{}` does not support listing files.",
+            self.uri()
+        )))
+    }
+
     /// Returns an URI identifying the storage
     fn uri(&self) -> &Uri;
 }
 
+/// A file listed by [`Storage::list_files_metadata`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileMetadata {
+    /// Path of the file, relative to the storage root.
+    pub path: PathBuf,
+    /// Size of the file in bytes.
+    pub num_bytes: u64,
+    /// Last modification time of the file as a Unix timestamp in seconds, if known.
+    pub last_modified_timestamp_opt: Option<i64>,
+}
+
 /// Error returned by `bulk_delete`. Under the hood, `bulk_delete` groups the files to
 /// delete into multiple batches of fixed size and issues one delete objects request per batch. The
 /// whole operation can fail in multiples ways, which is reflected by the quirckiness of the API of